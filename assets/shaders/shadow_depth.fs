@@ -0,0 +1,4 @@
+#version 330
+// Depth is written by the fixed pipeline; the shadow maps have no colour buffer.
+void main(){
+}
@@ -0,0 +1,7 @@
+#version 330
+// Sun shadow depth pass: position only; the light's view-projection arrives as mvp.
+in vec3 vertexPosition;
+uniform mat4 mvp;
+void main(){
+  gl_Position = mvp * vec4(vertexPosition, 1.0);
+}
@@ -0,0 +1,53 @@
+#version 330
+in vec2 fragNdc;
+out vec4 finalColor;
+// Camera basis and projection, to turn each pixel back into a view direction.
+uniform vec3 camForward;
+uniform vec3 camRight;
+uniform vec3 camUp;
+uniform float tanHalfFov;
+uniform float aspect;
+uniform vec3 zenithColor;
+// Also the surface fog color, so terrain fades into the sky right at the horizon.
+uniform vec3 horizonColor;
+uniform vec3 sunDir;
+uniform vec3 sunColor;
+uniform float starAlpha;
+// Stars turn about the sun's axis of travel with the day.
+uniform float starRotation;
+
+float hash13(vec3 p){
+  p = fract(p * 0.1031);
+  p += dot(p, p.zyx + 31.32);
+  return fract((p.x + p.y) * p.z);
+}
+
+// One candidate star per cell of a grid laid over the sky sphere; most cells stay empty.
+float stars(vec3 dir){
+  float c = cos(starRotation);
+  float s = sin(starRotation);
+  vec3 d = vec3(c * dir.x - s * dir.y, s * dir.x + c * dir.y, dir.z);
+  vec3 p = d * 160.0;
+  vec3 cell = floor(p);
+  float h = hash13(cell);
+  if (h < 0.985) return 0.0;
+  vec3 jitter = vec3(hash13(cell + 7.1), hash13(cell + 3.7), hash13(cell + 1.3)) - 0.5;
+  float dist = length(p - (cell + 0.5 + jitter * 0.6));
+  return smoothstep(0.35, 0.0, dist) * (h - 0.985) / 0.015;
+}
+
+void main(){
+  vec3 dir = normalize(camForward
+    + fragNdc.x * aspect * tanHalfFov * camRight
+    + fragNdc.y * tanHalfFov * camUp);
+  float up = clamp(dir.y, 0.0, 1.0);
+  vec3 col = mix(horizonColor, zenithColor, pow(up, 0.45));
+  // Halo around the sun, widest while it is low, fading out once it has set.
+  float toward = max(dot(dir, sunDir), 0.0);
+  float low = 1.0 - clamp(abs(sunDir.y) * 3.0, 0.0, 1.0);
+  float halo = pow(toward, 8.0) * (0.15 + 0.35 * low) + pow(toward, 256.0) * 0.6;
+  col += sunColor * halo * smoothstep(-0.15, 0.05, sunDir.y);
+  // Stars thin out into the horizon haze.
+  col += vec3(stars(dir)) * starAlpha * smoothstep(0.0, 0.25, up);
+  finalColor = vec4(clamp(col, 0.0, 1.0), 1.0);
+}
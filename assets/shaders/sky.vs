@@ -0,0 +1,9 @@
+#version 330
+// Full-screen sky pass: the quad arrives in normalized device coordinates laid out like
+// screen space (y down), so it keeps raylib's 2D winding and skips the mvp entirely.
+in vec3 vertexPosition;
+out vec2 fragNdc;
+void main(){
+  fragNdc = vec2(vertexPosition.x, -vertexPosition.y);
+  gl_Position = vec4(fragNdc, 1.0, 1.0);
+}
@@ -0,0 +1,25 @@
+#version 330
+in vec2 fragTexCoord;
+in vec4 fragColor;
+out vec4 finalColor;
+uniform sampler2D texture0;
+uniform vec2 texelSize;
+uniform float sharpness;
+
+// Contrast-adaptive sharpen over the bilinear upscale: a negative-lobe cross filter
+// whose weight backs off where the neighbourhood is already high-contrast, so edges
+// crisp up without ringing.
+void main(){
+  vec3 c = texture(texture0, fragTexCoord).rgb;
+  vec3 n = texture(texture0, fragTexCoord + vec2(0.0, -texelSize.y)).rgb;
+  vec3 s = texture(texture0, fragTexCoord + vec2(0.0,  texelSize.y)).rgb;
+  vec3 w = texture(texture0, fragTexCoord + vec2(-texelSize.x, 0.0)).rgb;
+  vec3 e = texture(texture0, fragTexCoord + vec2( texelSize.x, 0.0)).rgb;
+  vec3 mn = min(c, min(min(n, s), min(w, e)));
+  vec3 mx = max(c, max(max(n, s), max(w, e)));
+  vec3 amp = sqrt(clamp(min(mn, 1.0 - mx) / max(mx, vec3(1e-4)), 0.0, 1.0));
+  float peak = -1.0 / mix(8.0, 5.0, clamp(sharpness, 0.0, 1.0));
+  vec3 wgt = amp * peak;
+  vec3 col = (c + (n + s + w + e) * wgt) / (1.0 + 4.0 * wgt);
+  finalColor = vec4(clamp(col, 0.0, 1.0), 1.0) * fragColor;
+}
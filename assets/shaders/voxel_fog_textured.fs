@@ -3,23 +3,66 @@ in vec2 fragTexCoord;
 in vec4 fragColor;
 in vec3 fragWorldPos;
 in vec3 fragNormal;
+in vec3 fragLightPos;
+in vec3 fragLightNormal;
+in float fragLayer;
 out vec4 finalColor;
 uniform sampler2D texture0;
+// Optional block texture array: when useBlockArray is set, sample layer blockLayer instead of texture0.
+// A negative blockLayer reads each vertex's own layer.
+uniform sampler2DArray blockTextures;
+uniform int blockLayer;
+uniform int useBlockArray;
+
+vec4 sampleBlock(vec2 uv) {
+  if (useBlockArray > 0) {
+    float layer = blockLayer < 0 ? fragLayer : float(blockLayer);
+    return texture(blockTextures, vec3(uv, layer));
+  }
+  return texture(texture0, uv);
+}
 // Phase 2 lighting
 uniform sampler2D lightTex;         // packed 2D atlas of (sx x sz) tiles across Y slices
 uniform ivec3 lightDims;            // (sx+2, sy+2, sz+2) including seam rings
 uniform ivec2 lightGrid;            // (grid_cols, grid_rows)
-uniform vec3  chunkOrigin;          // world-space min corner of this chunk
+uniform vec3  chunkOrigin;          // mesh-space min corner of this light grid
 uniform float visualLightMin;       // 0..1 brightness floor
 uniform float skyLightScale;        // 0..1 scale applied to skylight channel
 
+// Dynamic point lights: world-space volume of block light, packed in Y slices like lightTex
+// but without seam rings. Zero dims when no dynamic light is lit.
+uniform sampler2D dynLightTex;
+uniform ivec3 dynLightDims;
+uniform ivec2 dynLightGrid;
+uniform vec3  dynLightOrigin;
+
+// Dynamic light in the open voxel in front of a world-space face.
+float sampleDynamicLight(vec3 worldPos, vec3 nrm) {
+  if (dynLightDims.x == 0 || dynLightDims.y == 0 || dynLightDims.z == 0) {
+    return 0.0;
+  }
+  ivec3 v = ivec3(floor(worldPos + nrm * 0.5 - dynLightOrigin));
+  if (any(lessThan(v, ivec3(0))) || any(greaterThanEqual(v, dynLightDims))) {
+    return 0.0;
+  }
+  int cols = max(dynLightGrid.x, 1);
+  int px = (v.y % cols) * dynLightDims.x + v.x;
+  int py = (v.y / cols) * dynLightDims.z + v.z;
+  vec2 size = vec2(float(dynLightDims.x * cols), float(dynLightDims.z * max(dynLightGrid.y, 1)));
+  return texture(dynLightTex, (vec2(px, py) + 0.5) / size).r;
+}
+
 uniform vec3 fogColor;
 uniform float fogStart;
 uniform float fogEnd;
 uniform vec3 cameraPos;
 // Underwater enhancements
 uniform float time;
+// 0..1 scale on emitter flicker; 0 holds emitters steady (reduced flashing)
+uniform float flickerAmount;
 uniform int underwater;
+// Fraction of R, G, B kept per block of water (WaterMedium::transmittance)
+uniform vec3 waterTransmittance;
 
 // Map voxel coords (vx, vy, vz) to atlas UV
 vec2 lightAtlasUV(ivec3 v) {
@@ -35,8 +78,92 @@ vec2 lightAtlasUV(ivec3 v) {
   return vec2(u, vuv);
 }
 
+// Animated emitter intensity; class ids match FlickerClass (atlas alpha, 0..255)
+float flickerWave(float cls, vec3 worldPos) {
+  int c = int(cls * 255.0 + 0.5);
+  if (c == 0) {
+    return 1.0;
+  }
+  // Spatially smooth phase so neighbouring voxels lit by one source move together
+  float ph = dot(floor(worldPos * 0.25), vec3(1.7, 3.1, 2.3));
+  if (c == 1) {
+    // Torch: fast irregular flicker
+    return 0.84 + 0.09 * sin(time * 9.0 + ph) + 0.07 * sin(time * 23.0 + ph * 1.7);
+  } else if (c == 2) {
+    // Candle: gentle waver
+    return 0.92 + 0.05 * sin(time * 5.0 + ph) + 0.03 * sin(time * 13.0 + ph * 2.3);
+  } else if (c == 3) {
+    // Pulse: slow breathing glow
+    return 0.75 + 0.25 * sin(time * 1.5 + ph);
+  }
+  return 1.0;
+}
+
+float flickerFactor(float cls, vec3 worldPos) {
+  return mix(1.0, flickerWave(cls, worldPos), clamp(flickerAmount, 0.0, 1.0));
+}
+
+// Sun shadow cascades: depth maps rendered looking down sunDir, each covering a sphere of
+// radius shadowSplits[i] around the camera, nearest first. shadowCount 0 (shadows off or
+// the sun down) leaves skylight unshadowed.
+uniform sampler2D shadowMap0;
+uniform sampler2D shadowMap1;
+uniform sampler2D shadowMap2;
+uniform mat4 shadowMatrix[3];
+uniform vec3 shadowSplits;
+uniform vec3 shadowTexel;           // render-space size of one map texel per cascade
+uniform vec3 shadowBias;            // depth bias per cascade, in the map's 0..1 range
+uniform float shadowMapTexel;       // 1 / map resolution
+uniform int shadowCount;
+uniform vec3 sunDir;                // towards the sun
+uniform float shadowStrength;       // share of skylight a full shadow takes away
+
+float shadowDepth(int c, vec2 uv) {
+  if (c == 0) return texture(shadowMap0, uv).r;
+  if (c == 1) return texture(shadowMap1, uv).r;
+  return texture(shadowMap2, uv).r;
+}
+
+// Fraction of skylight reaching a render-space surface: 1 in full sun, 1 - shadowStrength
+// in shadow. Faces turned from the sun count as shadowed.
+float sampleSunShadow(vec3 worldPos, vec3 nrm) {
+  if (shadowCount <= 0 || shadowStrength <= 0.0) {
+    return 1.0;
+  }
+  float facing = clamp(dot(nrm, sunDir) * 4.0, 0.0, 1.0);
+  float dist = length(worldPos - cameraPos);
+  int c = -1;
+  for (int i = 0; i < 3; i++) {
+    if (i < shadowCount && dist < shadowSplits[i]) {
+      c = i;
+      break;
+    }
+  }
+  float lit = facing;
+  if (c >= 0 && facing > 0.0) {
+    // Offset along the normal by a texel or so, so a face never shadows itself.
+    vec4 ls = shadowMatrix[c] * vec4(worldPos + nrm * shadowTexel[c] * 1.5, 1.0);
+    vec3 p = ls.xyz / ls.w * 0.5 + 0.5;
+    if (all(greaterThanEqual(p, vec3(0.0))) && all(lessThanEqual(p, vec3(1.0)))) {
+      float sum = 0.0;
+      for (int y = -1; y <= 1; y++) {
+        for (int x = -1; x <= 1; x++) {
+          float d = shadowDepth(c, p.xy + vec2(x, y) * shadowMapTexel);
+          sum += (p.z - shadowBias[c] <= d) ? 1.0 : 0.0;
+        }
+      }
+      lit *= sum / 9.0;
+    }
+    // Fade out towards the edge of the last cascade instead of ending in a hard line.
+    float last = shadowSplits[shadowCount - 1];
+    lit = mix(lit, facing, smoothstep(last * 0.85, last, dist));
+  }
+  return 1.0 - shadowStrength * (1.0 - lit);
+}
+
 // Sample brightness from local voxel and its neighbor along face normal
-float sampleBrightness(vec3 worldPos, vec3 nrm) {
+// sunVis scales the skylight channel (see sampleSunShadow).
+float sampleBrightness(vec3 worldPos, vec3 nrm, float sunVis) {
   // If lighting uniforms are unset for this draw, avoid sampling a stale texture
   if (lightDims.x == 0 || lightDims.y == 0 || lightDims.z == 0) {
     return visualLightMin;
@@ -64,17 +191,28 @@ float sampleBrightness(vec3 worldPos, vec3 nrm) {
   ivec3 vnAtlas = vnInner + ivec3(1, 1, 1);
   // Fetch R,G,B = block, sky, beacon; take max of local and neighbor
   vec2 uv0 = lightAtlasUV(vAtlas);
-  vec3 l0 = texture(lightTex, uv0).rgb;
+  vec4 l0 = texture(lightTex, uv0);
   vec2 uv1 = lightAtlasUV(vnAtlas);
-  vec3 l1 = texture(lightTex, uv1).rgb;
-  float blk = max(l0.r, l1.r);
-  float sky = max(l0.g, l1.g) * clamp(skyLightScale, 0.0, 1.0);
+  vec4 l1 = texture(lightTex, uv1);
+  // Block light keeps the flicker class (alpha) of whichever sample supplies it
+  float blkClass = (l0.r >= l1.r) ? l0.a : l1.a;
+  float blk = max(l0.r, l1.r) * flickerFactor(blkClass, worldPos);
+  float sky = max(l0.g, l1.g) * clamp(skyLightScale, 0.0, 1.0) * sunVis;
   float bcn = max(l0.b, l1.b);
   float lv = max(blk, max(sky, bcn));
   // Normalize from 0..1 (assuming input is 0..1 already from texture fetch)
   return max(lv, visualLightMin);
 }
 
+// Per-channel light left after travelling `dist` blocks through water; reds go first.
+// An unset uniform (all zero) leaves colours untouched.
+vec3 waterExtinction(float dist) {
+  if (waterTransmittance == vec3(0.0)) {
+    return vec3(1.0);
+  }
+  return pow(clamp(waterTransmittance, vec3(0.0), vec3(1.0)), vec3(min(dist, 48.0)));
+}
+
 void main(){
   // Subtle UV warp when underwater to simulate refractive wobble
   vec2 uv = fragTexCoord;
@@ -82,9 +220,11 @@ void main(){
     float w = sin(fragWorldPos.x * 0.13 + time * 0.8) * 0.008 + cos(fragWorldPos.z * 0.17 - time * 0.6) * 0.008;
     uv += vec2(w, w);
   }
-  vec4 base = texture(texture0, uv) * fragColor;
+  vec4 base = sampleBlock(uv) * fragColor;
   // Apply shader-sampled lighting
-  float bright = sampleBrightness(fragWorldPos, fragNormal);
+  float sunVis = sampleSunShadow(fragWorldPos, fragNormal);
+  float bright = sampleBrightness(fragLightPos, fragLightNormal, sunVis);
+  bright = max(bright, sampleDynamicLight(fragWorldPos, fragNormal));
   base.rgb *= bright;
   // Simple linear fog based on world-space distance from camera
   float dist = length(fragWorldPos - cameraPos);
@@ -92,6 +232,7 @@ void main(){
   vec3 rgb = mix(fogColor, base.rgb, f);
   // Extra tint when underwater
   if (underwater > 0) {
+    rgb *= waterExtinction(dist);
     rgb = mix(fogColor, rgb, 0.85);
   }
   finalColor = vec4(rgb, base.a);
@@ -3,18 +3,45 @@ in vec3 vertexPosition;
 in vec2 vertexTexCoord;
 in vec4 vertexColor;
 in vec3 vertexNormal;
+// Array layer per vertex, set only on parts that merge several materials.
+in vec2 vertexTexCoord2;
 out vec2 fragTexCoord;
 out vec4 fragColor;
 out vec3 fragWorldPos;
 out vec3 fragNormal;
+out vec3 fragLightPos;
+out vec3 fragLightNormal;
+out float fragLayer;
 uniform mat4 mvp;
 uniform mat4 matModel; // provided by raylib per draw (model transform)
+uniform float time;
+// Per-material wind sway; amplitude 0 (the default) leaves the mesh static.
+uniform float swayAmplitude;
+uniform float swayFrequency;
+uniform float swayStiffness;
 void main(){
   fragTexCoord = vertexTexCoord;
   fragColor = vertexColor;
-  // Compute true world-space position using the current model transform.
-  fragWorldPos = (matModel * vec4(vertexPosition, 1.0)).xyz;
+  fragLayer = vertexTexCoord2.x;
+  // Offset is a function of position only, so vertices shared between faces move
+  // together and greedy quads never crack. Phase follows the transformed position because
+  // chunk meshes are uploaded relative to their own corner and must agree across seams.
+  // Flexible materials phase-shift with height.
+  vec3 pos = vertexPosition;
+  if (swayAmplitude != 0.0) {
+    vec3 at = (matModel * vec4(pos, 1.0)).xyz;
+    float phase = time * swayFrequency * 6.2831853
+                + dot(at.xz, vec2(0.37, 0.23))
+                + at.y * (1.0 - clamp(swayStiffness, 0.0, 1.0));
+    pos.xz += vec2(sin(phase), 0.6 * sin(phase * 0.73 + 1.7)) * swayAmplitude;
+  }
+  // Render-space position: world space shifted by the floating origin.
+  fragWorldPos = (matModel * vec4(pos, 1.0)).xyz;
   // Normal in world space (model assumed rotationless or uniform scale for chunks)
   fragNormal = normalize((mat3(matModel) * vertexNormal));
-  gl_Position = mvp * vec4(vertexPosition, 1.0);
+  // Light grids are sampled in mesh space (chunkOrigin is relative to the uploaded
+  // vertices), so rotated or scaled structures still sample their own grid.
+  fragLightPos = vertexPosition;
+  fragLightNormal = vertexNormal;
+  gl_Position = mvp * vec4(pos, 1.0);
 }
@@ -0,0 +1,23 @@
+#version 330
+in vec2 fragTexCoord;
+in vec3 fragWorldPos;
+in float fragSky;
+in float fragBlock;
+out vec4 finalColor;
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+uniform vec3 cameraPos;
+uniform vec3 fogColor;
+uniform float fogStart;
+uniform float fogEnd;
+uniform float visualLightMin;       // 0..1 brightness floor
+uniform float skyLightScale;        // 0..1 scale applied to skylight
+void main(){
+  vec4 base = texture(texture0, fragTexCoord) * colDiffuse;
+  if (base.a < 0.5) discard;
+  float sky = fragSky * clamp(skyLightScale, 0.0, 1.0);
+  base.rgb *= max(max(sky, fragBlock), visualLightMin);
+  float dist = length(fragWorldPos - cameraPos);
+  float f = clamp((fogEnd - dist) / max(fogEnd - fogStart, 0.0001), 0.0, 1.0);
+  finalColor = vec4(mix(fogColor, base.rgb, f), 1.0);
+}
@@ -0,0 +1,30 @@
+#version 330
+in vec3 vertexPosition;
+in vec2 vertexTexCoord;
+in vec3 vertexNormal;
+// Per-instance model matrix. Its bottom row, always (0, 0, 0, 1) for a placement, carries
+// the instance's light instead: x = skylight, y = block light, both 0..1.
+in mat4 instanceTransform;
+out vec2 fragTexCoord;
+out vec3 fragWorldPos;
+out float fragSky;
+out float fragBlock;
+uniform mat4 mvp;
+uniform float time;
+void main(){
+  mat4 model = instanceTransform;
+  fragSky = model[0][3];
+  fragBlock = model[1][3];
+  model[0][3] = 0.0;
+  model[1][3] = 0.0;
+  model[2][3] = 0.0;
+  model[3][3] = 1.0;
+  vec3 pos = vertexPosition;
+  vec3 at = (model * vec4(pos, 1.0)).xyz;
+  // Tips sway in the wind; the base stays planted.
+  float phase = time * 1.7 + dot(at.xz, vec2(0.37, 0.23));
+  vec3 world = at + vec3(sin(phase), 0.0, 0.6 * sin(phase * 0.73 + 1.7)) * 0.06 * pos.y;
+  fragTexCoord = vertexTexCoord;
+  fragWorldPos = world;
+  gl_Position = mvp * vec4(world, 1.0);
+}
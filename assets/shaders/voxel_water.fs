@@ -3,6 +3,8 @@ in vec2 fragTexCoord;
 in vec4 fragColor;
 in vec3 fragWorldPos;
 in vec3 fragNormal;
+in vec3 fragLightPos;
+in vec3 fragLightNormal;
 out vec4 finalColor;
 uniform sampler2D texture0;
 // Phase 2 lighting
@@ -12,12 +14,40 @@ uniform ivec2 lightGrid;
 uniform vec3  chunkOrigin;
 uniform float visualLightMin;
 uniform float skyLightScale;
+
+// Dynamic point lights: world-space volume of block light, packed in Y slices like lightTex
+// but without seam rings. Zero dims when no dynamic light is lit.
+uniform sampler2D dynLightTex;
+uniform ivec3 dynLightDims;
+uniform ivec2 dynLightGrid;
+uniform vec3  dynLightOrigin;
+
+// Dynamic light in the open voxel in front of a world-space face.
+float sampleDynamicLight(vec3 worldPos, vec3 nrm) {
+  if (dynLightDims.x == 0 || dynLightDims.y == 0 || dynLightDims.z == 0) {
+    return 0.0;
+  }
+  ivec3 v = ivec3(floor(worldPos + nrm * 0.5 - dynLightOrigin));
+  if (any(lessThan(v, ivec3(0))) || any(greaterThanEqual(v, dynLightDims))) {
+    return 0.0;
+  }
+  int cols = max(dynLightGrid.x, 1);
+  int px = (v.y % cols) * dynLightDims.x + v.x;
+  int py = (v.y / cols) * dynLightDims.z + v.z;
+  vec2 size = vec2(float(dynLightDims.x * cols), float(dynLightDims.z * max(dynLightGrid.y, 1)));
+  return texture(dynLightTex, (vec2(px, py) + 0.5) / size).r;
+}
+
 uniform vec3 fogColor;
 uniform float fogStart;
 uniform float fogEnd;
 uniform vec3 cameraPos;
 uniform float time;
+// 0..1 scale on emitter flicker; 0 holds emitters steady (reduced flashing)
+uniform float flickerAmount;
 uniform int underwater;
+// Fraction of R, G, B kept per block of water (WaterMedium::transmittance)
+uniform vec3 waterTransmittance;
 
 vec2 lightAtlasUV(ivec3 v) {
   int tile_w = lightDims.x;
@@ -32,7 +62,91 @@ vec2 lightAtlasUV(ivec3 v) {
   return vec2(u, vuv);
 }
 
-float sampleBrightness(vec3 worldPos, vec3 nrm) {
+// Animated emitter intensity; class ids match FlickerClass (atlas alpha, 0..255)
+float flickerWave(float cls, vec3 worldPos) {
+  int c = int(cls * 255.0 + 0.5);
+  if (c == 0) {
+    return 1.0;
+  }
+  // Spatially smooth phase so neighbouring voxels lit by one source move together
+  float ph = dot(floor(worldPos * 0.25), vec3(1.7, 3.1, 2.3));
+  if (c == 1) {
+    // Torch: fast irregular flicker
+    return 0.84 + 0.09 * sin(time * 9.0 + ph) + 0.07 * sin(time * 23.0 + ph * 1.7);
+  } else if (c == 2) {
+    // Candle: gentle waver
+    return 0.92 + 0.05 * sin(time * 5.0 + ph) + 0.03 * sin(time * 13.0 + ph * 2.3);
+  } else if (c == 3) {
+    // Pulse: slow breathing glow
+    return 0.75 + 0.25 * sin(time * 1.5 + ph);
+  }
+  return 1.0;
+}
+
+float flickerFactor(float cls, vec3 worldPos) {
+  return mix(1.0, flickerWave(cls, worldPos), clamp(flickerAmount, 0.0, 1.0));
+}
+
+// Sun shadow cascades: depth maps rendered looking down sunDir, each covering a sphere of
+// radius shadowSplits[i] around the camera, nearest first. shadowCount 0 (shadows off or
+// the sun down) leaves skylight unshadowed.
+uniform sampler2D shadowMap0;
+uniform sampler2D shadowMap1;
+uniform sampler2D shadowMap2;
+uniform mat4 shadowMatrix[3];
+uniform vec3 shadowSplits;
+uniform vec3 shadowTexel;           // render-space size of one map texel per cascade
+uniform vec3 shadowBias;            // depth bias per cascade, in the map's 0..1 range
+uniform float shadowMapTexel;       // 1 / map resolution
+uniform int shadowCount;
+uniform vec3 sunDir;                // towards the sun
+uniform float shadowStrength;       // share of skylight a full shadow takes away
+
+float shadowDepth(int c, vec2 uv) {
+  if (c == 0) return texture(shadowMap0, uv).r;
+  if (c == 1) return texture(shadowMap1, uv).r;
+  return texture(shadowMap2, uv).r;
+}
+
+// Fraction of skylight reaching a render-space surface: 1 in full sun, 1 - shadowStrength
+// in shadow. Faces turned from the sun count as shadowed.
+float sampleSunShadow(vec3 worldPos, vec3 nrm) {
+  if (shadowCount <= 0 || shadowStrength <= 0.0) {
+    return 1.0;
+  }
+  float facing = clamp(dot(nrm, sunDir) * 4.0, 0.0, 1.0);
+  float dist = length(worldPos - cameraPos);
+  int c = -1;
+  for (int i = 0; i < 3; i++) {
+    if (i < shadowCount && dist < shadowSplits[i]) {
+      c = i;
+      break;
+    }
+  }
+  float lit = facing;
+  if (c >= 0 && facing > 0.0) {
+    // Offset along the normal by a texel or so, so a face never shadows itself.
+    vec4 ls = shadowMatrix[c] * vec4(worldPos + nrm * shadowTexel[c] * 1.5, 1.0);
+    vec3 p = ls.xyz / ls.w * 0.5 + 0.5;
+    if (all(greaterThanEqual(p, vec3(0.0))) && all(lessThanEqual(p, vec3(1.0)))) {
+      float sum = 0.0;
+      for (int y = -1; y <= 1; y++) {
+        for (int x = -1; x <= 1; x++) {
+          float d = shadowDepth(c, p.xy + vec2(x, y) * shadowMapTexel);
+          sum += (p.z - shadowBias[c] <= d) ? 1.0 : 0.0;
+        }
+      }
+      lit *= sum / 9.0;
+    }
+    // Fade out towards the edge of the last cascade instead of ending in a hard line.
+    float last = shadowSplits[shadowCount - 1];
+    lit = mix(lit, facing, smoothstep(last * 0.85, last, dist));
+  }
+  return 1.0 - shadowStrength * (1.0 - lit);
+}
+
+// sunVis scales the skylight channel (see sampleSunShadow).
+float sampleBrightness(vec3 worldPos, vec3 nrm, float sunVis) {
   // If lighting uniforms are unset for this draw, avoid sampling a stale texture
   if (lightDims.x == 0 || lightDims.y == 0 || lightDims.z == 0) {
     return visualLightMin;
@@ -56,33 +170,69 @@ float sampleBrightness(vec3 worldPos, vec3 nrm) {
   ivec3 vAtlas = vInner + ivec3(1, 1, 1);
   ivec3 vnAtlas = vnInner + ivec3(1, 1, 1);
   vec2 uv0 = lightAtlasUV(vAtlas);
-  vec3 l0 = texture(lightTex, uv0).rgb;
+  vec4 l0 = texture(lightTex, uv0);
   vec2 uv1 = lightAtlasUV(vnAtlas);
-  vec3 l1 = texture(lightTex, uv1).rgb;
-  float blk = max(l0.r, l1.r);
-  float sky = max(l0.g, l1.g) * clamp(skyLightScale, 0.0, 1.0);
+  vec4 l1 = texture(lightTex, uv1);
+  // Block light keeps the flicker class (alpha) of whichever sample supplies it
+  float blkClass = (l0.r >= l1.r) ? l0.a : l1.a;
+  float blk = max(l0.r, l1.r) * flickerFactor(blkClass, worldPos);
+  float sky = max(l0.g, l1.g) * clamp(skyLightScale, 0.0, 1.0) * sunVis;
   float bcn = max(l0.b, l1.b);
   float lv = max(blk, max(sky, bcn));
   return max(lv, visualLightMin);
 }
 
+// Per-channel light left after travelling `dist` blocks through water; reds go first.
+// An unset uniform (all zero) leaves colours untouched.
+vec3 waterExtinction(float dist) {
+  if (waterTransmittance == vec3(0.0)) {
+    return vec3(1.0);
+  }
+  return pow(clamp(waterTransmittance, vec3(0.0), vec3(1.0)), vec3(min(dist, 48.0)));
+}
+
+// Openness the mesher writes to vertex alpha on water tops: 0 on the waterline, 1 over
+// water at least four blocks deep with no bank alongside. Sides and bottoms read 1.
+float waterOpenness() {
+  return clamp(fragColor.a, 0.0, 1.0);
+}
+
+// Crossing swells, roughly -2..2; their slope shades the surface.
+float swell(vec2 p) {
+  return sin(dot(p, vec2(0.9, 0.4)) + time * 1.3) + sin(dot(p, vec2(-0.3, 1.1)) + time * 1.7);
+}
+
 void main(){
+  float open = waterOpenness();
+  float top = step(0.5, fragNormal.y);
+  // Waves calm down in shallows and along banks.
+  float amp = mix(0.35, 1.0, open);
   // Subtle UV distortion based on world position and time
-  float wave = sin(fragWorldPos.x * 0.15 + time * 0.8) * 0.01 + cos(fragWorldPos.z * 0.12 - time * 0.6) * 0.01;
+  float wave = (sin(fragWorldPos.x * 0.15 + time * 0.8) * 0.01 + cos(fragWorldPos.z * 0.12 - time * 0.6) * 0.01) * amp;
   vec2 uv = fragTexCoord + vec2(wave, wave);
-  vec4 base = texture(texture0, uv) * fragColor;
+  vec4 base = texture(texture0, uv) * vec4(fragColor.rgb, 1.0);
+  vec2 p = fragWorldPos.xz;
+  float slope = swell(p + vec2(0.25, 0.0)) - swell(p - vec2(0.25, 0.0));
+  base.rgb *= 1.0 + 0.08 * amp * slope * top;
+  // Foam: a broken white band along the waterline that breathes with the waves.
+  float breakup = 0.5 + 0.5 * sin(p.x * 2.3 + time * 1.1) * sin(p.y * 2.9 - time * 0.9);
+  float foam = (1.0 - smoothstep(0.05, 0.45, open + 0.2 * breakup)) * top;
+  base.rgb = mix(base.rgb, vec3(0.92, 0.96, 1.0), foam * 0.75);
   // Apply light
-  float bright = sampleBrightness(fragWorldPos, fragNormal);
+  float sunVis = sampleSunShadow(fragWorldPos, fragNormal);
+  float bright = sampleBrightness(fragLightPos, fragLightNormal, sunVis);
+  bright = max(bright, sampleDynamicLight(fragWorldPos, fragNormal));
   base.rgb *= bright;
   // Alpha depends on whether the camera is underwater
   // When underwater, make the surface opaque so nothing above is visible
-  float alpha = (underwater > 0) ? 1.0 : 0.7;
+  float alpha = (underwater > 0) ? 1.0 : mix(0.7, 0.9, foam);
   // Fog
   float dist = length(fragWorldPos - cameraPos);
   float f = clamp((fogEnd - dist) / max(fogEnd - fogStart, 0.0001), 0.0, 1.0);
   vec3 rgb = mix(fogColor, base.rgb, f);
   // Stronger tint when underwater, still draw surface from below (requires backface disabled)
   if (underwater > 0) {
+    rgb *= waterExtinction(dist);
     rgb = mix(fogColor, rgb, 0.70);
   }
   finalColor = vec4(rgb, alpha);
@@ -0,0 +1,322 @@
+//! Actors: free-moving boxes (props, NPCs, projectiles) with a per-chunk residency index.
+//!
+//! [`Actors`] owns every actor and knows which chunk each one lives in, so the owner of the
+//! world can step only the actors in loaded chunks and ask what is in a chunk without a
+//! scan. Behaviour beyond moving and colliding is left to the game built on top.
+#![forbid(unsafe_code)]
+
+use geist_geom::{Aabb, ChunkPos, IVec3, Vec3, WorldPos};
+use hashbrown::HashMap;
+
+/// Downward acceleration of actors with gravity, in voxels/sec².
+pub const ACTOR_GRAVITY: f32 = 28.0;
+/// Fastest an actor falls, in voxels/sec.
+pub const ACTOR_TERMINAL_SPEED: f32 = 40.0;
+// Longest move per substep, so a fast actor cannot pass through a one-voxel wall.
+const MAX_STEP: f32 = 0.45;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ActorId(pub u32);
+
+/// Where an actor is and which way it faces.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Transform {
+    /// Center of the actor's box in world space.
+    pub pos: Vec3,
+    pub yaw_deg: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Actor {
+    id: ActorId,
+    chunk: ChunkPos,
+    /// Game-defined kind, e.g. an index into a table of props.
+    pub kind: u32,
+    pub transform: Transform,
+    pub vel: Vec3,
+    /// Half size of the axis-aligned box around `transform.pos`.
+    pub half_extents: Vec3,
+    pub gravity: bool,
+    /// Whether the actor stops at solid boxes; projectiles that test hits themselves and
+    /// decorative actors can turn it off.
+    pub collides: bool,
+    /// Set by [`Actors::step`] while something solid is right under the actor.
+    pub on_ground: bool,
+}
+
+impl Actor {
+    /// An actor of `kind` centered at `pos`, falling and colliding by default.
+    pub fn new(kind: u32, pos: Vec3, half_extents: Vec3) -> Self {
+        Self {
+            id: ActorId(0),
+            chunk: ChunkPos::default(),
+            kind,
+            transform: Transform { pos, yaw_deg: 0.0 },
+            vel: Vec3::ZERO,
+            half_extents,
+            gravity: true,
+            collides: true,
+            on_ground: false,
+        }
+    }
+
+    pub fn with_velocity(mut self, vel: Vec3) -> Self {
+        self.vel = vel;
+        self
+    }
+
+    pub fn with_yaw(mut self, yaw_deg: f32) -> Self {
+        self.transform.yaw_deg = yaw_deg;
+        self
+    }
+
+    pub fn without_gravity(mut self) -> Self {
+        self.gravity = false;
+        self
+    }
+
+    pub fn without_collision(mut self) -> Self {
+        self.collides = false;
+        self
+    }
+
+    pub fn id(&self) -> ActorId {
+        self.id
+    }
+
+    /// The chunk the actor's center is in.
+    pub fn chunk(&self) -> ChunkPos {
+        self.chunk
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        aabb_at(self.transform.pos, self.half_extents)
+    }
+}
+
+fn aabb_at(pos: Vec3, half: Vec3) -> Aabb {
+    Aabb::new(pos - half, pos + half)
+}
+
+/// An actor whose center crossed into another chunk during a step.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ActorMoved {
+    pub id: ActorId,
+    pub from: ChunkPos,
+    pub to: ChunkPos,
+}
+
+/// Every actor, indexed by id and by the chunk it lives in.
+pub struct Actors {
+    chunk_size: IVec3,
+    next_id: u32,
+    actors: HashMap<ActorId, Actor>,
+    residents: HashMap<ChunkPos, Vec<ActorId>>,
+}
+
+impl Actors {
+    /// An empty set for a world whose chunks are `chunk_size` voxels.
+    pub fn new(chunk_size: IVec3) -> Self {
+        Self {
+            chunk_size,
+            next_id: 1,
+            actors: HashMap::new(),
+            residents: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.actors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actors.is_empty()
+    }
+
+    pub fn get(&self, id: ActorId) -> Option<&Actor> {
+        self.actors.get(&id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Actor> {
+        self.actors.values()
+    }
+
+    /// Chunk holding the world point `pos`.
+    pub fn chunk_of(&self, pos: Vec3) -> ChunkPos {
+        WorldPos::new(
+            pos.x.floor() as i32,
+            pos.y.floor() as i32,
+            pos.z.floor() as i32,
+        )
+        .chunk(self.chunk_size)
+    }
+
+    pub fn spawn(&mut self, mut actor: Actor) -> ActorId {
+        let id = ActorId(self.next_id);
+        self.next_id += 1;
+        actor.id = id;
+        actor.chunk = self.chunk_of(actor.transform.pos);
+        self.residents.entry(actor.chunk).or_default().push(id);
+        self.actors.insert(id, actor);
+        id
+    }
+
+    pub fn despawn(&mut self, id: ActorId) -> Option<Actor> {
+        let actor = self.actors.remove(&id)?;
+        leave(&mut self.residents, actor.chunk, id);
+        Some(actor)
+    }
+
+    /// Move an actor to `pos` without colliding; `None` if there is no such actor.
+    pub fn teleport(&mut self, id: ActorId, pos: Vec3) -> Option<ActorMoved> {
+        let to = self.chunk_of(pos);
+        let actor = self.actors.get_mut(&id)?;
+        actor.transform.pos = pos;
+        actor.on_ground = false;
+        let from = actor.chunk;
+        if from != to {
+            actor.chunk = to;
+            leave(&mut self.residents, from, id);
+            self.residents.entry(to).or_default().push(id);
+        }
+        Some(ActorMoved { id, from, to })
+    }
+
+    pub fn set_velocity(&mut self, id: ActorId, vel: Vec3) -> bool {
+        match self.actors.get_mut(&id) {
+            Some(actor) => {
+                actor.vel = vel;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Actors living in `chunk`.
+    pub fn in_chunk(&self, chunk: ChunkPos) -> impl Iterator<Item = &Actor> {
+        self.residents
+            .get(&chunk)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.actors.get(id))
+    }
+
+    pub fn count_in_chunk(&self, chunk: ChunkPos) -> usize {
+        self.residents.get(&chunk).map_or(0, Vec::len)
+    }
+
+    /// Chunks with at least one actor.
+    pub fn occupied_chunks(&self) -> impl Iterator<Item = ChunkPos> + '_ {
+        self.residents.keys().copied()
+    }
+
+    /// Remove and return every actor living in `chunk`, e.g. to save them when it unloads.
+    pub fn evict_chunk(&mut self, chunk: ChunkPos) -> Vec<Actor> {
+        let ids = self.residents.remove(&chunk).unwrap_or_default();
+        ids.into_iter()
+            .filter_map(|id| self.actors.remove(&id))
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.actors.clear();
+        self.residents.clear();
+    }
+
+    /// Advance the actors whose chunk is `active` by `dt`. Colliding actors move one axis
+    /// at a time and stop along an axis where `blocked` reports their box overlapping
+    /// something solid; an actor that starts a step inside something moves freely until it
+    /// is out. Returns the actors that changed chunk.
+    pub fn step(
+        &mut self,
+        dt: f32,
+        active: impl Fn(ChunkPos) -> bool,
+        blocked: impl Fn(&Aabb) -> bool,
+    ) -> Vec<ActorMoved> {
+        let mut moved = Vec::new();
+        if dt <= 0.0 {
+            return moved;
+        }
+        for actor in self.actors.values_mut() {
+            if !active(actor.chunk) {
+                continue;
+            }
+            if actor.gravity {
+                actor.vel.y = (actor.vel.y - ACTOR_GRAVITY * dt).max(-ACTOR_TERMINAL_SPEED);
+            }
+            let travel = actor.vel * dt;
+            if actor.collides && !blocked(&actor.aabb()) {
+                move_colliding(actor, travel, &blocked);
+            } else {
+                actor.transform.pos += travel;
+                actor.on_ground = false;
+            }
+            let to = WorldPos::new(
+                actor.transform.pos.x.floor() as i32,
+                actor.transform.pos.y.floor() as i32,
+                actor.transform.pos.z.floor() as i32,
+            )
+            .chunk(self.chunk_size);
+            if to != actor.chunk {
+                moved.push(ActorMoved {
+                    id: actor.id,
+                    from: actor.chunk,
+                    to,
+                });
+                actor.chunk = to;
+            }
+        }
+        for m in &moved {
+            leave(&mut self.residents, m.from, m.id);
+            self.residents.entry(m.to).or_default().push(m.id);
+        }
+        moved
+    }
+}
+
+fn move_colliding(actor: &mut Actor, travel: Vec3, blocked: &impl Fn(&Aabb) -> bool) {
+    let steps = (travel.length() / MAX_STEP).ceil().max(1.0) as u32;
+    let step = travel * (1.0 / steps as f32);
+    let mut hit = [false; 3];
+    for _ in 0..steps {
+        for axis in 0..3 {
+            if hit[axis] {
+                continue;
+            }
+            let d = [step.x, step.y, step.z][axis];
+            if d == 0.0 {
+                continue;
+            }
+            let mut pos = actor.transform.pos;
+            match axis {
+                0 => pos.x += d,
+                1 => pos.y += d,
+                _ => pos.z += d,
+            }
+            if blocked(&aabb_at(pos, actor.half_extents)) {
+                hit[axis] = true;
+            } else {
+                actor.transform.pos = pos;
+            }
+        }
+    }
+    if hit[0] {
+        actor.vel.x = 0.0;
+    }
+    if hit[2] {
+        actor.vel.z = 0.0;
+    }
+    actor.on_ground = hit[1] && travel.y < 0.0;
+    if hit[1] {
+        actor.vel.y = 0.0;
+    }
+}
+
+fn leave(residents: &mut HashMap<ChunkPos, Vec<ActorId>>, chunk: ChunkPos, id: ActorId) {
+    if let Some(ids) = residents.get_mut(&chunk) {
+        ids.retain(|&other| other != id);
+        if ids.is_empty() {
+            residents.remove(&chunk);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use geist_actors::{Actor, ActorMoved, Actors};
+use geist_geom::{Aabb, ChunkPos, IVec3, Vec3};
+
+const HALF: Vec3 = Vec3::new(0.4, 0.4, 0.4);
+
+fn actors() -> Actors {
+    Actors::new(IVec3::new(16, 16, 16))
+}
+
+// Solid ground filling everything below y = 0.
+fn ground(aabb: &Aabb) -> bool {
+    aabb.min.y < 0.0
+}
+
+#[test]
+fn spawned_actors_are_indexed_by_chunk() {
+    let mut actors = actors();
+    let a = actors.spawn(Actor::new(1, Vec3::new(3.0, 3.0, 3.0), HALF));
+    let b = actors.spawn(Actor::new(2, Vec3::new(-0.5, 3.0, 3.0), HALF));
+    assert_ne!(a, b);
+    assert_eq!(actors.get(a).unwrap().chunk(), ChunkPos::new(0, 0, 0));
+    assert_eq!(actors.get(b).unwrap().chunk(), ChunkPos::new(-1, 0, 0));
+    let here: Vec<_> = actors
+        .in_chunk(ChunkPos::new(0, 0, 0))
+        .map(|a| a.id())
+        .collect();
+    assert_eq!(here, vec![a]);
+
+    assert_eq!(actors.despawn(a).map(|a| a.kind), Some(1));
+    assert_eq!(actors.count_in_chunk(ChunkPos::new(0, 0, 0)), 0);
+    assert_eq!(
+        actors.occupied_chunks().collect::<Vec<_>>(),
+        vec![ChunkPos::new(-1, 0, 0)]
+    );
+}
+
+#[test]
+fn stepping_across_a_border_moves_residency() {
+    let mut actors = actors();
+    let id = actors.spawn(
+        Actor::new(0, Vec3::new(15.5, 8.0, 8.0), HALF)
+            .without_gravity()
+            .with_velocity(Vec3::new(2.0, 0.0, 0.0)),
+    );
+    let moved = actors.step(0.5, |_| true, |_| false);
+    assert_eq!(
+        moved,
+        vec![ActorMoved {
+            id,
+            from: ChunkPos::new(0, 0, 0),
+            to: ChunkPos::new(1, 0, 0),
+        }]
+    );
+    assert_eq!(actors.count_in_chunk(ChunkPos::new(0, 0, 0)), 0);
+    assert_eq!(actors.count_in_chunk(ChunkPos::new(1, 0, 0)), 1);
+}
+
+#[test]
+fn falling_actor_lands_on_the_ground() {
+    let mut actors = actors();
+    let id = actors.spawn(Actor::new(0, Vec3::new(4.0, 6.0, 4.0), HALF));
+    for _ in 0..120 {
+        actors.step(1.0 / 60.0, |_| true, ground);
+    }
+    let a = actors.get(id).unwrap();
+    assert!(a.on_ground);
+    assert_eq!(a.vel.y, 0.0);
+    assert!(
+        a.aabb().min.y >= 0.0 && a.aabb().min.y < 0.2,
+        "{:?}",
+        a.aabb()
+    );
+}
+
+#[test]
+fn fast_actor_does_not_tunnel_through_a_thin_wall() {
+    let mut actors = actors();
+    let id = actors.spawn(
+        Actor::new(0, Vec3::new(2.0, 8.0, 8.0), HALF)
+            .without_gravity()
+            .with_velocity(Vec3::new(60.0, 0.0, 0.0)),
+    );
+    let wall = |aabb: &Aabb| aabb.max.x > 5.0 && aabb.min.x < 6.0;
+    actors.step(0.5, |_| true, wall);
+    let a = actors.get(id).unwrap();
+    assert!(a.aabb().max.x <= 5.0, "{:?}", a.aabb());
+    assert_eq!(a.vel.x, 0.0);
+}
+
+#[test]
+fn actors_in_inactive_chunks_stay_put() {
+    let mut actors = actors();
+    let id = actors.spawn(Actor::new(0, Vec3::new(4.0, 6.0, 4.0), HALF));
+    actors.step(0.5, |c| c != ChunkPos::new(0, 0, 0), ground);
+    let a = actors.get(id).unwrap();
+    assert_eq!(a.transform.pos, Vec3::new(4.0, 6.0, 4.0));
+    assert_eq!(a.vel, Vec3::ZERO);
+}
+
+#[test]
+fn evicting_a_chunk_returns_its_actors() {
+    let mut actors = actors();
+    actors.spawn(Actor::new(7, Vec3::new(1.0, 1.0, 1.0), HALF));
+    actors.spawn(Actor::new(8, Vec3::new(2.0, 1.0, 1.0), HALF));
+    let other = actors.spawn(Actor::new(9, Vec3::new(20.0, 1.0, 1.0), HALF));
+    let mut kinds: Vec<_> = actors
+        .evict_chunk(ChunkPos::new(0, 0, 0))
+        .into_iter()
+        .map(|a| a.kind)
+        .collect();
+    kinds.sort();
+    assert_eq!(kinds, vec![7, 8]);
+    assert_eq!(actors.len(), 1);
+    assert!(actors.get(other).is_some());
+}
@@ -0,0 +1,154 @@
+//! Shapes made of config-listed boxes: which boxes a state shows, and the S=2 occupancy and
+//! face occlusion derived from them for lighting and culling.
+#![forbid(unsafe_code)]
+
+use crate::registry::BlockType;
+use crate::types::{BlockState, Shape, ShapeBox};
+
+/// Box coordinates run from 0 to this many units across a block.
+pub const BOX_UNITS: u8 = 16;
+
+const FULL_ROW: u16 = u16::MAX;
+
+impl ShapeBox {
+    /// Whether the box's `when` properties all match `state`.
+    pub fn applies_to(&self, ty: &BlockType, state: BlockState) -> bool {
+        self.when
+            .iter()
+            .all(|(prop, value)| ty.state_prop_is_value(state, prop, value))
+    }
+
+    /// Corners in block units, 0 to 1.
+    pub fn bounds(&self) -> ([f32; 3], [f32; 3]) {
+        let unit = |v: [u8; 3]| v.map(|c| c as f32 / BOX_UNITS as f32);
+        (unit(self.min), unit(self.max))
+    }
+}
+
+impl BlockType {
+    /// Boxes of a [`Shape::Boxes`](crate::types::Shape::Boxes) shown in `state`, including
+    /// ones that still depend on a neighbor connecting.
+    pub fn shape_boxes(&self, state: BlockState) -> impl Iterator<Item = &ShapeBox> {
+        let boxes = match &self.shape {
+            Shape::Boxes { boxes } => boxes.as_slice(),
+            _ => &[],
+        };
+        boxes.iter().filter(move |b| b.applies_to(self, state))
+    }
+}
+
+/// The union of some boxes rasterized at box resolution: `rows[y][z]` holds x bits.
+struct BoxGrid {
+    rows: [[u16; 16]; 16],
+}
+
+impl BoxGrid {
+    fn new<'a>(boxes: impl IntoIterator<Item = &'a ShapeBox>) -> Self {
+        let mut rows = [[0u16; 16]; 16];
+        for b in boxes {
+            let span = |lo: u8, hi: u8| (lo as usize)..(hi as usize);
+            let bits = span(b.min[0], b.max[0]).fold(0u16, |acc, x| acc | 1 << x);
+            for row in &mut rows[span(b.min[1], b.max[1])] {
+                for cell in &mut row[span(b.min[2], b.max[2])] {
+                    *cell |= bits;
+                }
+            }
+        }
+        Self { rows }
+    }
+
+    fn filled(&self, x: std::ops::Range<usize>, y: std::ops::Range<usize>, z: usize) -> bool {
+        let bits = x.fold(0u16, |acc, x| acc | 1 << x);
+        y.into_iter().all(|y| self.rows[y][z] & bits == bits)
+    }
+
+    fn layer_full(&self, y: usize) -> bool {
+        self.rows[y].iter().all(|&r| r == FULL_ROW)
+    }
+
+    fn wall_x_full(&self, x: usize) -> bool {
+        self.rows.iter().flatten().all(|&r| r & (1 << x) != 0)
+    }
+
+    fn wall_z_full(&self, z: usize) -> bool {
+        self.rows.iter().all(|layer| layer[z] == FULL_ROW)
+    }
+}
+
+/// S=2 occupancy of the union of `boxes`, in the `idx = (y<<2)|(z<<1)|x` layout used by
+/// slabs and stairs. A micro cell counts only when the boxes fill it, so light and
+/// neighbors see through posts thinner than half a block.
+pub fn boxes_occupancy_s2<'a>(boxes: impl IntoIterator<Item = &'a ShapeBox>) -> u8 {
+    let grid = BoxGrid::new(boxes);
+    let mut occ = 0u8;
+    for my in 0..2usize {
+        for mz in 0..2usize {
+            for mx in 0..2usize {
+                let full = (mz * 8..mz * 8 + 8)
+                    .all(|z| grid.filled(mx * 8..mx * 8 + 8, my * 8..my * 8 + 8, z));
+                if full {
+                    occ |= 1u8 << ((my << 2) | (mz << 1) | mx);
+                }
+            }
+        }
+    }
+    occ
+}
+
+/// Neighbor occlusion bits in `Face` order, as for `SlopeShape::occlusion_mask`: bit `f` is
+/// set when the boxes fill the whole wall facing away from `f`.
+pub fn boxes_occlusion_mask<'a>(boxes: impl IntoIterator<Item = &'a ShapeBox>) -> u8 {
+    let grid = BoxGrid::new(boxes);
+    (grid.layer_full(0) as u8)
+        | ((grid.layer_full(15) as u8) << 1)
+        | ((grid.wall_x_full(0) as u8) << 2)
+        | ((grid.wall_x_full(15) as u8) << 3)
+        | ((grid.wall_z_full(0) as u8) << 4)
+        | ((grid.wall_z_full(15) as u8) << 5)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cuboid(min: [u8; 3], max: [u8; 3]) -> ShapeBox {
+        ShapeBox {
+            min,
+            max,
+            when: Vec::new(),
+            connect: None,
+        }
+    }
+
+    #[test]
+    fn slab_boxes_fill_one_layer() {
+        let bottom = [cuboid([0, 0, 0], [16, 8, 16])];
+        assert_eq!(boxes_occupancy_s2(&bottom), 0b0000_1111);
+        // Half-height walls don't hide a neighbor's whole face.
+        assert_eq!(boxes_occlusion_mask(&bottom), 0b00_0001);
+        let top = [cuboid([0, 8, 0], [16, 16, 16])];
+        assert_eq!(boxes_occupancy_s2(&top), 0b1111_0000);
+        assert_eq!(boxes_occlusion_mask(&top), 0b00_0010);
+    }
+
+    #[test]
+    fn thin_posts_leave_cells_and_faces_open() {
+        let post = [cuboid([6, 0, 6], [10, 16, 10])];
+        assert_eq!(boxes_occupancy_s2(&post), 0);
+        assert_eq!(boxes_occlusion_mask(&post), 0);
+    }
+
+    #[test]
+    fn touching_boxes_fill_together() {
+        // Two half-width walls side by side make a full west half.
+        let halves = [
+            cuboid([0, 0, 0], [8, 16, 8]),
+            cuboid([0, 0, 8], [8, 16, 16]),
+        ];
+        let west = (0..8)
+            .filter(|i| i & 1 == 0)
+            .fold(0u8, |acc, i| acc | 1 << i);
+        assert_eq!(boxes_occupancy_s2(&halves), west);
+        assert_eq!(boxes_occlusion_mask(&halves), 1 << 2);
+    }
+}
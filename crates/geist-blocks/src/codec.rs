@@ -0,0 +1,79 @@
+//! Little-endian read helpers and atomic file writes shared by the binary formats that
+//! store blocks: edit regions, structure files and the chunk cache.
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use crate::types::Block;
+
+/// `InvalidData` error for a malformed file.
+pub fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+pub fn read_array<const N: usize>(r: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut b = [0u8; N];
+    r.read_exact(&mut b)?;
+    Ok(b)
+}
+
+pub fn read_u16(r: &mut impl Read) -> io::Result<u16> {
+    read_array::<2>(r).map(u16::from_le_bytes)
+}
+
+pub fn read_u32(r: &mut impl Read) -> io::Result<u32> {
+    read_array::<4>(r).map(u32::from_le_bytes)
+}
+
+pub fn read_u64(r: &mut impl Read) -> io::Result<u64> {
+    read_array::<8>(r).map(u64::from_le_bytes)
+}
+
+pub fn read_i32(r: &mut impl Read) -> io::Result<i32> {
+    read_array::<4>(r).map(i32::from_le_bytes)
+}
+
+pub fn read_f32(r: &mut impl Read) -> io::Result<f32> {
+    read_array::<4>(r).map(f32::from_le_bytes)
+}
+
+/// Read a `u32` item count and check that `r` still holds `item_bytes` for each item, so
+/// a corrupt count fails here instead of reserving memory the file cannot fill.
+pub fn read_count(r: &mut &[u8], item_bytes: usize) -> io::Result<usize> {
+    let n = read_u32(r)? as usize;
+    if n.saturating_mul(item_bytes) > r.len() {
+        return Err(invalid(format!(
+            "count {} needs more than the {} bytes left",
+            n,
+            r.len()
+        )));
+    }
+    Ok(n)
+}
+
+/// A block stored as `u16` id then `u16` state.
+pub fn read_block(r: &mut impl Read) -> io::Result<Block> {
+    let id = read_u16(r)?;
+    let state = read_u16(r)?;
+    Ok(Block { id, state })
+}
+
+pub fn write_block(out: &mut Vec<u8>, b: Block) {
+    out.extend_from_slice(&b.id.to_le_bytes());
+    out.extend_from_slice(&b.state.to_le_bytes());
+}
+
+/// Write `bytes` to `path` through a `.tmp` sibling renamed into place, so a crash never
+/// leaves a truncated file behind.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    let tmp = path.with_file_name(name);
+    {
+        let mut f = fs::File::create(&tmp)?;
+        f.write_all(bytes)?;
+        f.sync_all()?;
+    }
+    fs::rename(&tmp, path)
+}
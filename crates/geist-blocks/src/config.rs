@@ -26,7 +26,10 @@ pub struct BlockDef {
     #[serde(default)]
     pub propagates_light: Option<bool>,
     #[serde(default)]
-    pub emission: Option<u8>,
+    pub emission: Option<EmissionDef>,
+    /// Animated intensity for emitted light; applied by the shaders, not by relighting.
+    #[serde(default)]
+    pub flicker: Option<FlickerClass>,
 
     // Optional lighting behavior configuration
     #[serde(default)]
@@ -46,6 +49,10 @@ pub struct BlockDef {
     // Optional seam policy for meshing across neighbors
     #[serde(default)]
     pub seam: Option<SeamPolicyCfg>,
+
+    /// Free-form behavior tags, e.g. `"gravity"` for blocks that fall when unsupported.
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 // Shape config supports either a simple string ("cube") or a detailed table
@@ -67,6 +74,25 @@ pub struct ShapeDetailed {
     pub facing: Option<PropertyFrom>,
     #[serde(default)]
     pub open: Option<PropertyFrom>,
+    #[serde(default)]
+    pub corner: Option<PropertyFrom>,
+    /// Boxes of a `kind = "boxes"` shape.
+    #[serde(default)]
+    pub boxes: Vec<BoxConfig>,
+}
+
+/// One box of a `kind = "boxes"` shape, corners in sixteenths of a block (0..=16).
+#[derive(Deserialize, Debug, Clone)]
+pub struct BoxConfig {
+    pub from: [u8; 3],
+    pub to: [u8; 3],
+    /// Only present in states where each listed property has the given value.
+    #[serde(default)]
+    pub when: HashMap<String, String>,
+    /// Only present when the neighbor on this side ("north", "east", ...) is the same
+    /// block or a full cube.
+    #[serde(default)]
+    pub connect: Option<String>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -98,6 +124,25 @@ pub enum MaterialSelector {
     },
 }
 
+/// Light level a block emits: fixed, or picked by one state property so a furnace can
+/// glow only while `lit`. Values missing from the map emit nothing.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum EmissionDef {
+    Level(u8),
+    By {
+        by: String,
+        #[serde(default)]
+        map: HashMap<String, u8>,
+    },
+}
+
+impl From<u8> for EmissionDef {
+    fn from(level: u8) -> Self {
+        EmissionDef::Level(level)
+    }
+}
+
 // Top-level lighting config with reusable profiles
 #[derive(Deserialize, Debug, Clone, Default)]
 pub struct LightingConfig {
@@ -128,7 +173,7 @@ pub enum LightProfile {
     },
 }
 
-#[derive(Deserialize, Debug, Clone, Copy)]
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum SourceDirs {
     Horizontal,
@@ -136,6 +181,26 @@ pub enum SourceDirs {
     Any,
 }
 
+/// How an emitter's light varies over time. The class travels with block light into the
+/// light atlas so shaders can animate it without CPU relighting.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum FlickerClass {
+    #[default]
+    Steady = 0,
+    Torch = 1,
+    Candle = 2,
+    Pulse = 3,
+}
+
+impl FlickerClass {
+    #[inline]
+    pub fn as_u8(self) -> u8 {
+        self as u8
+    }
+}
+
 // Configurable seam policies for neighbor occlusion/fixups
 // Seam policy can be a simple keyword or a flags table
 #[derive(Deserialize, Debug, Clone, Copy)]
@@ -0,0 +1,57 @@
+//! Per-position data attached to a block (chest contents, sign text, machine state).
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::registry::BlockRegistry;
+use crate::types::{Block, BlockId};
+
+/// Tag of blocks that own a block entity while placed.
+pub const TAG_BLOCK_ENTITY: &str = "block_entity";
+/// Tag of block-entity blocks whose data affects their mesh (e.g. sign text); their
+/// entities are snapshotted into chunk build jobs.
+pub const TAG_ENTITY_VISUAL: &str = "entity_visual";
+
+/// One field of a block entity.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BlockEntityValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+    List(Vec<BlockEntityValue>),
+}
+
+/// Data stored for one block position, owned by the block type it was created for.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BlockEntity {
+    /// Block id the entity belongs to; replacing the block with another id removes it.
+    pub block: BlockId,
+    /// Whether meshing reads this entity; see [`TAG_ENTITY_VISUAL`].
+    #[serde(default)]
+    pub mesh_visible: bool,
+    #[serde(default)]
+    pub fields: BTreeMap<String, BlockEntityValue>,
+}
+
+impl BlockEntity {
+    /// Empty entity for `block` if its type is tagged [`TAG_BLOCK_ENTITY`].
+    pub fn for_block(reg: &BlockRegistry, block: Block) -> Option<Self> {
+        let ty = reg.get(block.id)?;
+        ty.has_tag(TAG_BLOCK_ENTITY).then(|| BlockEntity {
+            block: block.id,
+            mesh_visible: ty.has_tag(TAG_ENTITY_VISUAL),
+            fields: BTreeMap::new(),
+        })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&BlockEntityValue> {
+        self.fields.get(key)
+    }
+
+    pub fn set(&mut self, key: impl Into<String>, value: BlockEntityValue) {
+        self.fields.insert(key.into(), value);
+    }
+}
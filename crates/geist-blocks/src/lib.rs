@@ -1,13 +1,26 @@
 //! Block, material, and registry crate.
 #![forbid(unsafe_code)]
 
+pub mod boxes;
+pub mod codec;
 pub mod config;
+pub mod entity;
 pub mod material;
 pub mod micro;
+pub mod migrate;
+pub mod orient;
 pub mod registry;
+pub mod reload;
+pub mod slope;
+pub mod stable_hash;
 pub mod types;
 
 // Re-exports for convenience (match original crate layout)
-pub use material::MaterialCatalog;
-pub use registry::BlockRegistry;
-pub use types::{Block, FaceRole, MaterialId, Shape};
+pub use entity::{BlockEntity, BlockEntityValue, TAG_BLOCK_ENTITY, TAG_ENTITY_VISUAL};
+pub use material::{MaterialCatalog, RenderPass};
+pub use migrate::{BlockIdTable, IdMigration, MigrationReport};
+pub use orient::{Facing, Mirror, Placement};
+pub use registry::{BlockRegistry, FLUID_MAX_LEVEL, TAG_DECORATION, TAG_FLUID, TAG_GRAVITY};
+pub use reload::{IncompatibleReload, RegistryDiff};
+pub use slope::SlopeShape;
+pub use types::{Block, FaceRole, MaterialId, Shape, ShapeBox};
@@ -15,6 +15,42 @@ pub struct Material {
     pub key: String,
     pub texture_candidates: Vec<PathBuf>,
     pub render_tag: Option<String>,
+    /// Vertex sway for foliage and cloth; `None` renders the material static.
+    pub animation: Option<MaterialAnimation>,
+    pub render_pass: RenderPass,
+    /// Terrain-like material whose normals the mesher may smooth across block edges.
+    pub smooth: bool,
+    /// The texture is a 4x4 sheet of tiles and each face picks the one whose borders open
+    /// toward neighbors of the same material.
+    pub connected: bool,
+}
+
+/// When a material is drawn. Opaque parts go first and write depth; translucent parts
+/// (water, glass) blend over them afterwards, farthest first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RenderPass {
+    #[default]
+    Opaque,
+    Translucent,
+}
+
+/// Wind sway applied in the vertex shader, tuned per material in `materials.toml`.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub struct MaterialAnimation {
+    /// Peak horizontal offset in blocks.
+    #[serde(default)]
+    pub sway_amplitude: f32,
+    /// Oscillations per second.
+    #[serde(default = "default_sway_frequency")]
+    pub sway_frequency: f32,
+    /// 0 lets upper vertices lag behind lower ones so tall parts bend; 1 moves every
+    /// height in phase like a rigid body.
+    #[serde(default)]
+    pub stiffness: f32,
+}
+
+fn default_sway_frequency() -> f32 {
+    1.0
 }
 
 #[derive(Default, Clone, Debug)]
@@ -31,6 +67,10 @@ impl MaterialCatalog {
             key: String::new(),
             texture_candidates: Vec::new(),
             render_tag: None,
+            animation: None,
+            render_pass: RenderPass::Opaque,
+            smooth: false,
+            connected: false,
         });
         Self {
             materials,
@@ -46,6 +86,21 @@ impl MaterialCatalog {
         self.materials.get(id.0 as usize)
     }
 
+    /// Pass `id` is drawn in; unknown ids are opaque.
+    pub fn render_pass(&self, id: MaterialId) -> RenderPass {
+        self.get(id).map_or(RenderPass::Opaque, |m| m.render_pass)
+    }
+
+    /// Whether `id` opted into normal smoothing; unknown ids stay crisp.
+    pub fn smooth(&self, id: MaterialId) -> bool {
+        self.get(id).is_some_and(|m| m.smooth)
+    }
+
+    /// Whether `id` draws from a connected-texture sheet; unknown ids don't.
+    pub fn connected(&self, id: MaterialId) -> bool {
+        self.get(id).is_some_and(|m| m.connected)
+    }
+
     pub fn from_toml_str(toml_str: &str) -> Result<Self, Box<dyn Error>> {
         let cfg: MaterialsConfig = toml::from_str(toml_str)?;
         let mut catalog = MaterialCatalog::new();
@@ -53,9 +108,22 @@ impl MaterialCatalog {
         // HashMap iteration order is nondeterministic; sort keys so MaterialId assignment is stable.
         entries.sort_by(|a, b| a.0.cmp(&b.0));
         for (key, entry) in entries {
-            let (paths, render_tag) = match entry {
-                MaterialEntry::Paths(v) => (v, None),
-                MaterialEntry::Detail { paths, render_tag } => (paths, render_tag),
+            let (paths, render_tag, animation, translucent, smooth, connected) = match entry {
+                MaterialEntry::Paths(v) => (v, None, None, false, false, false),
+                MaterialEntry::Detail {
+                    paths,
+                    render_tag,
+                    animation,
+                    translucent,
+                    smooth,
+                    connected,
+                } => (paths, render_tag, animation, translucent, smooth, connected),
+            };
+            // Water always blends, whether or not its entry says so.
+            let render_pass = if translucent || render_tag.as_deref() == Some("water") {
+                RenderPass::Translucent
+            } else {
+                RenderPass::Opaque
             };
             let id = MaterialId(catalog.materials.len() as u16);
             catalog.by_key.insert(key.clone(), id);
@@ -64,6 +132,10 @@ impl MaterialCatalog {
                 key,
                 texture_candidates: paths.into_iter().map(PathBuf::from).collect(),
                 render_tag,
+                animation: animation.filter(|a| a.sway_amplitude != 0.0),
+                render_pass,
+                smooth,
+                connected,
             });
         }
         Ok(catalog)
@@ -87,9 +159,19 @@ pub struct MaterialsConfig {
 pub enum MaterialEntry {
     // Simple: material = ["assets/blocks/foo.png", ...]
     Paths(Vec<String>),
-    // Detailed: material = { paths = ["..."], render_tag = "leaves" }
+    // Detailed: material = { paths = ["..."], render_tag = "leaves",
+    //                        animation = { sway_amplitude = 0.05, sway_frequency = 1.2 },
+    //                        translucent = true, smooth = true, connected = true }
     Detail {
         paths: Vec<String>,
         render_tag: Option<String>,
+        #[serde(default)]
+        animation: Option<MaterialAnimation>,
+        #[serde(default)]
+        translucent: bool,
+        #[serde(default)]
+        smooth: bool,
+        #[serde(default)]
+        connected: bool,
     },
 }
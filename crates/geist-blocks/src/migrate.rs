@@ -0,0 +1,244 @@
+//! Registry fingerprints and name-based remapping of block ids saved under another registry.
+//!
+//! Ids are assigned by position in `blocks.toml`, so adding or reordering blocks shifts them.
+//! Data that stores raw ids keeps a [`BlockIdTable`] beside it; on load, an [`IdMigration`]
+//! built from that table maps each saved id to the block of the same name, repacking the
+//! state by property name, and replaces blocks that no longer exist with a placeholder.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::registry::{BlockRegistry, BlockType};
+use crate::stable_hash::Fnv1a;
+use crate::types::{Block, BlockId, BlockState};
+
+pub const ID_TABLE_VERSION: u32 = 1;
+
+impl BlockRegistry {
+    /// Hash of every block's id, name and state layout. Two registries with the same
+    /// fingerprint agree on what every saved `Block` means.
+    pub fn fingerprint(&self) -> u64 {
+        let mut h = Fnv1a::new();
+        for ty in &self.blocks {
+            h.write(&ty.id.to_le_bytes());
+            h.write(ty.name.as_bytes());
+            h.write(&[0]);
+            for f in &ty.state_fields {
+                h.write(f.name.as_bytes());
+                h.write(&[1]);
+                for v in &f.values {
+                    h.write(v.as_bytes());
+                    h.write(&[2]);
+                }
+            }
+            h.write(&[3]);
+        }
+        h.finish()
+    }
+}
+
+/// One registry entry as it was when the data was saved.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedBlockId {
+    pub id: BlockId,
+    pub name: String,
+    /// State properties and their values, in the order the values were numbered.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub state: BTreeMap<String, Vec<String>>,
+}
+
+/// The id → name table of the registry that wrote a save.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockIdTable {
+    pub version: u32,
+    /// `BlockRegistry::fingerprint` as hex; TOML integers cannot hold every `u64`.
+    pub fingerprint: String,
+    pub blocks: Vec<SavedBlockId>,
+}
+
+impl BlockIdTable {
+    pub fn from_registry(reg: &BlockRegistry) -> Self {
+        let blocks = reg
+            .blocks
+            .iter()
+            .map(|ty| SavedBlockId {
+                id: ty.id,
+                name: ty.name.clone(),
+                state: ty
+                    .state_fields
+                    .iter()
+                    .map(|f| (f.name.clone(), f.values.clone()))
+                    .collect(),
+            })
+            .collect();
+        Self {
+            version: ID_TABLE_VERSION,
+            fingerprint: format!("{:016x}", reg.fingerprint()),
+            blocks,
+        }
+    }
+
+    /// True when `reg` assigns the same ids and state layouts as the saving registry.
+    pub fn matches(&self, reg: &BlockRegistry) -> bool {
+        u64::from_str_radix(&self.fingerprint, 16).ok() == Some(reg.fingerprint())
+    }
+
+    pub fn to_toml_string(&self) -> Result<String, String> {
+        toml::to_string(self).map_err(|e| e.to_string())
+    }
+
+    pub fn from_toml_str(src: &str) -> Result<Self, String> {
+        let table: BlockIdTable = toml::from_str(src).map_err(|e| e.to_string())?;
+        if table.version > ID_TABLE_VERSION {
+            return Err(format!(
+                "block id table version {} is newer than supported {}",
+                table.version, ID_TABLE_VERSION
+            ));
+        }
+        Ok(table)
+    }
+}
+
+/// What an `IdMigration` did to the blocks passed through it.
+#[derive(Clone, Debug, Default)]
+pub struct MigrationReport {
+    /// Blocks rewritten to a new id or state.
+    pub remapped: usize,
+    /// Blocks replaced by the placeholder, by saved name (`#<id>` when the table lacks it).
+    pub missing: BTreeMap<String, usize>,
+}
+
+impl MigrationReport {
+    pub fn placeholders(&self) -> usize {
+        self.missing.values().sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remapped == 0 && self.missing.is_empty()
+    }
+
+    pub fn merge(&mut self, other: MigrationReport) {
+        self.remapped += other.remapped;
+        for (name, n) in other.missing {
+            *self.missing.entry(name).or_default() += n;
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Target {
+    Same,
+    Block {
+        id: BlockId,
+        // Saved state layout, when it differs from the current one.
+        repack: Option<Vec<(String, Vec<String>)>>,
+    },
+    Missing(String),
+}
+
+/// Maps blocks saved under an older registry onto the current one.
+#[derive(Clone, Debug)]
+pub struct IdMigration<'a> {
+    reg: &'a BlockRegistry,
+    targets: HashMap<BlockId, Target>,
+    placeholder: Block,
+    report: MigrationReport,
+}
+
+impl<'a> IdMigration<'a> {
+    /// Plan the remap from `saved` to `reg`. Names `reg` no longer has become its unknown
+    /// block, or air (id 0) if it has none.
+    pub fn new(saved: &BlockIdTable, reg: &'a BlockRegistry) -> Self {
+        let placeholder = Block {
+            id: reg
+                .unknown_block_id
+                .or_else(|| reg.id_by_name("unknown"))
+                .unwrap_or(0),
+            state: 0,
+        };
+        let targets = saved
+            .blocks
+            .iter()
+            .map(|entry| {
+                let target = match reg.id_by_name(&entry.name).and_then(|id| reg.get(id)) {
+                    Some(ty) => {
+                        let same_state = ty.state_fields.len() == entry.state.len()
+                            && ty
+                                .state_fields
+                                .iter()
+                                .all(|f| entry.state.get(&f.name) == Some(&f.values));
+                        if ty.id == entry.id && same_state {
+                            Target::Same
+                        } else {
+                            Target::Block {
+                                id: ty.id,
+                                repack: (!same_state)
+                                    .then(|| entry.state.clone().into_iter().collect()),
+                            }
+                        }
+                    }
+                    None => Target::Missing(entry.name.clone()),
+                };
+                (entry.id, target)
+            })
+            .collect();
+        Self {
+            reg,
+            targets,
+            placeholder,
+            report: MigrationReport::default(),
+        }
+    }
+
+    pub fn apply(&mut self, block: Block) -> Block {
+        match self.targets.get(&block.id) {
+            Some(Target::Same) => block,
+            Some(Target::Block { id, repack }) => {
+                let state = match (repack, self.reg.get(*id)) {
+                    (Some(fields), Some(ty)) => repack_state(fields, block.state, ty),
+                    _ => block.state,
+                };
+                self.report.remapped += 1;
+                Block { id: *id, state }
+            }
+            Some(Target::Missing(name)) => {
+                *self.report.missing.entry(name.clone()).or_default() += 1;
+                self.placeholder
+            }
+            None => {
+                *self
+                    .report
+                    .missing
+                    .entry(format!("#{}", block.id))
+                    .or_default() += 1;
+                self.placeholder
+            }
+        }
+    }
+
+    pub fn into_report(self) -> MigrationReport {
+        self.report
+    }
+}
+
+// Decode `state` with the saved layout (sorted keys, as `compute_state_layout` numbers them)
+// and pack the same property values into the current one.
+fn repack_state(fields: &[(String, Vec<String>)], state: BlockState, ty: &BlockType) -> BlockState {
+    let mut offset = 0u32;
+    let mut props: HashMap<String, String> = HashMap::new();
+    for (name, values) in fields {
+        let n = values.len() as u32;
+        let bits = if n <= 1 {
+            0
+        } else {
+            32 - (n - 1).leading_zeros()
+        };
+        let idx = (state as u32).checked_shr(offset).unwrap_or(0) & ((1u32 << bits) - 1);
+        if let Some(v) = values.get(idx as usize) {
+            props.insert(name.clone(), v.clone());
+        }
+        offset += bits;
+    }
+    ty.pack_state(&props)
+}
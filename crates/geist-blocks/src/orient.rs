@@ -0,0 +1,228 @@
+//! Orienting directional blocks: the state a block takes when placed, and turning or
+//! mirroring a state along with the blocks around it.
+//!
+//! The shape names the properties that orient a block (`facing`, `half`, `axis`). Blocks of
+//! other shapes that still declare a `facing` property, such as a furnace front, turn it
+//! toward whoever placed them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::registry::BlockType;
+use crate::types::{BlockState, Shape};
+
+/// Horizontal direction. North is -Z and east is +X.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Facing {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Facing {
+    /// Clockwise seen from above, starting at north.
+    pub const ALL: [Facing; 4] = [Facing::North, Facing::East, Facing::South, Facing::West];
+
+    /// The value the `facing` property stores.
+    pub fn name(self) -> &'static str {
+        match self {
+            Facing::North => "north",
+            Facing::East => "east",
+            Facing::South => "south",
+            Facing::West => "west",
+        }
+    }
+
+    pub fn from_name(s: &str) -> Option<Facing> {
+        Facing::ALL.into_iter().find(|f| f.name() == s)
+    }
+
+    /// The direction `(dx, dz)` mostly points along; ties go to the Z axis.
+    pub fn from_vector(dx: f32, dz: f32) -> Facing {
+        if dx.abs() > dz.abs() {
+            if dx > 0.0 { Facing::East } else { Facing::West }
+        } else if dz > 0.0 {
+            Facing::South
+        } else {
+            Facing::North
+        }
+    }
+
+    /// Unit step toward this direction as `(dx, dz)`.
+    pub fn offset(self) -> (i32, i32) {
+        match self {
+            Facing::North => (0, -1),
+            Facing::East => (1, 0),
+            Facing::South => (0, 1),
+            Facing::West => (-1, 0),
+        }
+    }
+
+    /// Turn by `turns` quarter turns clockwise seen from above.
+    pub fn rotated_cw(self, turns: u8) -> Facing {
+        Facing::ALL[(self as usize + turns as usize) % 4]
+    }
+
+    pub fn opposite(self) -> Facing {
+        self.rotated_cw(2)
+    }
+
+    pub fn mirrored(self, mirror: Mirror) -> Facing {
+        match (mirror, self) {
+            (Mirror::X, Facing::East) => Facing::West,
+            (Mirror::X, Facing::West) => Facing::East,
+            (Mirror::Z, Facing::North) => Facing::South,
+            (Mirror::Z, Facing::South) => Facing::North,
+            (_, f) => f,
+        }
+    }
+}
+
+/// Flip across a vertical plane: `X` negates x (east and west swap), `Z` negates z.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Mirror {
+    X,
+    Z,
+}
+
+/// How a block is being placed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Placement {
+    /// Outward normal of the clicked face, pointing from the clicked block into the new
+    /// one; zero when unknown.
+    pub normal: (i32, i32, i32),
+    /// Height of the hit point within its cell, 0 at the bottom to 1 at the top. Only
+    /// side hits read it.
+    pub hit_y: f32,
+    /// Horizontal look direction of the placer as `(x, z)`.
+    pub look: (f32, f32),
+}
+
+impl Placement {
+    /// Whether a half block goes in the upper half: under a ceiling, or high on a wall.
+    fn upper_half(&self) -> bool {
+        match self.normal.1 {
+            -1 => true,
+            1 => false,
+            _ => self.hit_y > 0.5,
+        }
+    }
+
+    fn look_facing(&self) -> Facing {
+        Facing::from_vector(self.look.0, self.look.1)
+    }
+}
+
+impl BlockType {
+    fn facing_prop(&self) -> Option<&str> {
+        match &self.shape {
+            Shape::Stairs { facing_from, .. }
+            | Shape::Slope { facing_from, .. }
+            | Shape::SlopeCorner { facing_from, .. }
+            | Shape::Gate { facing_from, .. }
+            | Shape::Ladder { facing_from } => Some(facing_from),
+            _ => self.prop_index.contains_key("facing").then_some("facing"),
+        }
+    }
+
+    fn half_prop(&self) -> Option<&str> {
+        match &self.shape {
+            Shape::Slab { half_from }
+            | Shape::Stairs { half_from, .. }
+            | Shape::Slope { half_from, .. }
+            | Shape::SlopeCorner { half_from, .. } => Some(half_from),
+            _ => None,
+        }
+    }
+
+    fn facing_of(&self, state: BlockState) -> Option<(&str, Facing)> {
+        let prop = self.facing_prop()?;
+        let facing = Facing::from_name(self.state_prop_value(state, prop)?)?;
+        Some((prop, facing))
+    }
+
+    /// The state for this block placed as `placement` describes; properties that don't
+    /// orient the block keep their values from `state`.
+    ///
+    /// Stairs, slopes and gates face the way the placer looks, so walking forward climbs
+    /// them; ladders face out of the wall they were placed on; other blocks with a
+    /// `facing` property face the placer. Half blocks take the upper half when placed
+    /// under a ceiling or high on a wall, and axis blocks follow the clicked face.
+    pub fn placement_state(&self, state: BlockState, placement: &Placement) -> BlockState {
+        let mut state = state;
+        if let Some(prop) = self.half_prop() {
+            let half = if placement.upper_half() {
+                "top"
+            } else {
+                "bottom"
+            };
+            state = self.with_state_prop(state, prop, half);
+        }
+        if let Some(prop) = self.facing_prop() {
+            let (nx, _, nz) = placement.normal;
+            let facing = match self.shape {
+                Shape::Stairs { .. }
+                | Shape::Slope { .. }
+                | Shape::SlopeCorner { .. }
+                | Shape::Gate { .. } => placement.look_facing(),
+                Shape::Ladder { .. } if (nx, nz) != (0, 0) => {
+                    Facing::from_vector(nx as f32, nz as f32)
+                }
+                _ => placement.look_facing().opposite(),
+            };
+            state = self.with_state_prop(state, prop, facing.name());
+        }
+        if let Shape::AxisCube { axis_from } = &self.shape {
+            let axis = match placement.normal {
+                (x, _, _) if x != 0 => "x",
+                (_, _, z) if z != 0 => "z",
+                _ => "y",
+            };
+            state = self.with_state_prop(state, axis_from, axis);
+        }
+        state
+    }
+
+    /// `state` turned `turns` quarter turns clockwise seen from above.
+    pub fn rotate_state(&self, state: BlockState, turns: u8) -> BlockState {
+        let turns = turns % 4;
+        let mut state = state;
+        if let Some((prop, facing)) = self.facing_of(state) {
+            state = self.with_state_prop(state, prop, facing.rotated_cw(turns).name());
+        }
+        if let Shape::AxisCube { axis_from } = &self.shape
+            && turns % 2 == 1
+        {
+            match self.state_prop_value(state, axis_from) {
+                Some("x") => state = self.with_state_prop(state, axis_from, "z"),
+                Some("z") => state = self.with_state_prop(state, axis_from, "x"),
+                _ => {}
+            }
+        }
+        state
+    }
+
+    /// `state` mirrored across `mirror`.
+    pub fn mirror_state(&self, state: BlockState, mirror: Mirror) -> BlockState {
+        let Some((prop, facing)) = self.facing_of(state) else {
+            return state;
+        };
+        let mirrored = match self.shape {
+            // A corner's odd corner sits clockwise of its facing, so mirroring swaps it
+            // to the neighbour on the other side of the flipped axis.
+            Shape::SlopeCorner { .. } => match (mirror, facing) {
+                (Mirror::X, Facing::North) => Facing::East,
+                (Mirror::X, Facing::East) => Facing::North,
+                (Mirror::X, Facing::South) => Facing::West,
+                (Mirror::X, Facing::West) => Facing::South,
+                (Mirror::Z, Facing::North) => Facing::West,
+                (Mirror::Z, Facing::West) => Facing::North,
+                (Mirror::Z, Facing::East) => Facing::South,
+                (Mirror::Z, Facing::South) => Facing::East,
+            },
+            _ => facing.mirrored(mirror),
+        };
+        self.with_state_prop(state, prop, mirrored.name())
+    }
+}
@@ -3,12 +3,15 @@ use std::error::Error;
 use std::fs;
 use std::path::Path;
 
+use super::boxes::{BOX_UNITS, boxes_occlusion_mask, boxes_occupancy_s2};
 use super::config::{
-    BlocksConfig, LightProfile, MaterialSelector, MaterialsDef, SeamPolicyCfg, SeamPolicyFlagsCfg,
-    SeamPolicySimple, ShapeConfig, SourceDirs,
+    BlocksConfig, BoxConfig, EmissionDef, FlickerClass, LightProfile, MaterialSelector,
+    MaterialsDef, SeamPolicyCfg, SeamPolicyFlagsCfg, SeamPolicySimple, ShapeConfig, SourceDirs,
 };
 use super::material::MaterialCatalog;
-use super::types::{Block, BlockId, BlockState, FaceRole, MaterialId, Shape};
+use super::orient::Facing;
+use super::slope::SlopeShape;
+use super::types::{Block, BlockId, BlockState, FaceRole, MaterialId, Shape, ShapeBox};
 
 // Minimal duplication of mesher-facing enums to avoid a dependency from blocks → mesher.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
@@ -27,27 +30,6 @@ impl Face {
     }
 }
 
-/// Simple cardinal facing used by stairs and similar shapes.
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-enum Facing {
-    North,
-    South,
-    West,
-    East,
-}
-impl Facing {
-    #[inline]
-    fn from_str(s: &str) -> Facing {
-        match s {
-            "north" => Facing::North,
-            "south" => Facing::South,
-            "west" => Facing::West,
-            "east" => Facing::East,
-            _ => Facing::North,
-        }
-    }
-}
-
 #[derive(Default, Clone, Debug)]
 pub struct CompiledMaterials {
     pub all: Option<ResolvedSelector>,
@@ -149,7 +131,8 @@ impl BlockRegistry {
             let solid = def.solid.unwrap_or(true);
             let blocks_skylight = def.blocks_skylight.unwrap_or(solid);
             let propagates_light = def.propagates_light.unwrap_or(false);
-            let emission = def.emission.unwrap_or(0);
+            let emission_def = def.emission.unwrap_or(EmissionDef::Level(0));
+            let flicker = def.flicker.unwrap_or_default();
             let light: CompiledLight = match def.light.or_else(|| {
                 def.light_profile
                     .as_ref()
@@ -180,9 +163,10 @@ impl BlockRegistry {
                     max_range: None,
                 },
             };
-            let shape = compile_shape(def.shape);
-            let mats = compile_materials(&reg.materials, def.materials);
             let state_schema = def.state_schema.unwrap_or_default();
+            let shape = compile_shape(def.shape, &state_schema)
+                .map_err(|e| format!("block '{}': {}", def.name, e))?;
+            let mats = compile_materials(&reg.materials, def.materials);
             let (state_fields, prop_index) = compute_state_layout(&state_schema);
 
             let mut ty = BlockType {
@@ -191,7 +175,8 @@ impl BlockRegistry {
                 solid,
                 blocks_skylight,
                 propagates_light,
-                emission,
+                emission: 0,
+                flicker,
                 light,
                 shape,
                 materials: mats,
@@ -200,6 +185,7 @@ impl BlockRegistry {
                 pre_mat_side: Vec::new(),
                 pre_occ_mask: Vec::new(),
                 pre_shape_variants: Vec::new(),
+                pre_emission: Vec::new(),
                 seam: match def.seam {
                     Some(SeamPolicyCfg::Simple(SeamPolicySimple::DontOccludeSame)) => SeamPolicy {
                         dont_occlude_same: true,
@@ -226,6 +212,7 @@ impl BlockRegistry {
                 state_schema,
                 state_fields,
                 prop_index,
+                tags: def.tags,
             };
 
             let (pre_top, pre_bottom, pre_side, pre_occ, pre_vars) = {
@@ -266,9 +253,10 @@ impl BlockRegistry {
                             let occ8 = match &ty.shape {
                                 Shape::Slab { .. } => Some(occ_slab(is_top)),
                                 Shape::Stairs { facing_from, .. } => {
-                                    let facing = Facing::from_str(
-                                        ty.state_prop_value(state, facing_from).unwrap_or("north"),
-                                    );
+                                    let facing = ty
+                                        .state_prop_value(state, facing_from)
+                                        .and_then(Facing::from_name)
+                                        .unwrap_or(Facing::North);
                                     Some(occ_stairs(facing, is_top))
                                 }
                                 _ => None,
@@ -278,6 +266,54 @@ impl BlockRegistry {
                                 ShapeVariant {
                                     occupancy: occ8,
                                     dynamic: None,
+                                    slope: None,
+                                    boxes: false,
+                                },
+                            )
+                        }
+                        Shape::Slope {
+                            facing_from,
+                            half_from,
+                        }
+                        | Shape::SlopeCorner {
+                            facing_from,
+                            half_from,
+                            ..
+                        } => {
+                            let is_top = ty.state_prop_is_value(state, half_from, "top");
+                            let facing = ty
+                                .state_prop_value(state, facing_from)
+                                .and_then(Facing::from_name)
+                                .unwrap_or(Facing::North);
+                            let heights = match &ty.shape {
+                                Shape::SlopeCorner { corner_from, .. } => {
+                                    let inner = ty.state_prop_is_value(state, corner_from, "inner");
+                                    slope_corner_heights(facing, inner)
+                                }
+                                _ => slope_heights(facing),
+                            };
+                            let slope = SlopeShape::new(heights, is_top);
+                            (
+                                slope.occlusion_mask(),
+                                ShapeVariant {
+                                    occupancy: Some(slope.occupancy_s2()),
+                                    dynamic: None,
+                                    slope: Some(slope),
+                                    boxes: false,
+                                },
+                            )
+                        }
+                        Shape::Boxes { .. } => {
+                            // Connecting boxes come and go with neighbors, so only the
+                            // unconditional ones count toward cover.
+                            let fixed = || ty.shape_boxes(state).filter(|b| b.connect.is_none());
+                            (
+                                boxes_occlusion_mask(fixed()),
+                                ShapeVariant {
+                                    occupancy: Some(boxes_occupancy_s2(fixed())),
+                                    dynamic: None,
+                                    slope: None,
+                                    boxes: true,
                                 },
                             )
                         }
@@ -286,6 +322,8 @@ impl BlockRegistry {
                             ShapeVariant {
                                 occupancy: None,
                                 dynamic: Some(DynamicShape::Pane),
+                                slope: None,
+                                boxes: false,
                             },
                         ),
                         Shape::Fence => (
@@ -293,6 +331,8 @@ impl BlockRegistry {
                             ShapeVariant {
                                 occupancy: None,
                                 dynamic: Some(DynamicShape::Fence),
+                                slope: None,
+                                boxes: false,
                             },
                         ),
                         Shape::Gate { .. } => (
@@ -300,6 +340,8 @@ impl BlockRegistry {
                             ShapeVariant {
                                 occupancy: None,
                                 dynamic: Some(DynamicShape::Gate),
+                                slope: None,
+                                boxes: false,
                             },
                         ),
                         Shape::Carpet => (
@@ -307,6 +349,8 @@ impl BlockRegistry {
                             ShapeVariant {
                                 occupancy: None,
                                 dynamic: Some(DynamicShape::Carpet),
+                                slope: None,
+                                boxes: false,
                             },
                         ),
                         Shape::Ladder { .. } => (
@@ -314,6 +358,8 @@ impl BlockRegistry {
                             ShapeVariant {
                                 occupancy: None,
                                 dynamic: Some(DynamicShape::Ladder),
+                                slope: None,
+                                boxes: false,
                             },
                         ),
                         _ => {
@@ -323,6 +369,8 @@ impl BlockRegistry {
                                     ShapeVariant {
                                         occupancy: None,
                                         dynamic: None,
+                                        slope: None,
+                                        boxes: false,
                                     },
                                 )
                             } else {
@@ -331,6 +379,8 @@ impl BlockRegistry {
                                     ShapeVariant {
                                         occupancy: None,
                                         dynamic: None,
+                                        slope: None,
+                                        boxes: false,
                                     },
                                 )
                             }
@@ -352,6 +402,16 @@ impl BlockRegistry {
             ty.pre_mat_side = pre_side;
             ty.pre_occ_mask = pre_occ;
             ty.pre_shape_variants = pre_vars;
+            ty.pre_emission = (0..ty.pre_occ_mask.len())
+                .map(|s| match &emission_def {
+                    EmissionDef::Level(level) => *level,
+                    EmissionDef::By { by, map } => ty
+                        .state_prop_value(s as BlockState, by)
+                        .and_then(|v| map.get(v).copied())
+                        .unwrap_or(0),
+                })
+                .collect();
+            ty.emission = ty.pre_emission.iter().copied().max().unwrap_or(0);
             if reg.blocks.len() <= id as usize {
                 reg.blocks
                     .resize(id as usize + 1, BlockType::placeholder(id));
@@ -399,7 +459,9 @@ pub struct BlockType {
     pub solid: bool,
     pub blocks_skylight: bool,
     pub propagates_light: bool,
+    /// Brightest level any state emits; `light_emission` gives the level per state.
     pub emission: u8,
+    pub flicker: FlickerClass,
     pub light: CompiledLight,
     pub shape: Shape,
     pub materials: CompiledMaterials,
@@ -411,6 +473,8 @@ pub struct BlockType {
     pub pre_occ_mask: Vec<u8>,
     // Precomputed shape variant per state (for micro-grid based shapes)
     pub pre_shape_variants: Vec<ShapeVariant>,
+    // Precomputed emitted light level per state
+    pub pre_emission: Vec<u8>,
     // Seam policy to control occlusion and fixup projection between neighbors
     pub seam: SeamPolicy,
     #[allow(dead_code)]
@@ -418,8 +482,19 @@ pub struct BlockType {
     // Precomputed, sorted layout for fast state packing/unpacking
     pub state_fields: Vec<StateField>,
     pub prop_index: HashMap<String, usize>,
+    pub tags: Vec<String>,
 }
 
+/// Tag of blocks that fall when nothing solid is below them.
+pub const TAG_GRAVITY: &str = "gravity";
+/// Tag of fluid blocks; their `level` state property holds the fill level.
+pub const TAG_FLUID: &str = "fluid";
+/// Tag of small repeated props (grass tufts, ferns) drawn as instanced crossed quads
+/// instead of being meshed with the chunk.
+pub const TAG_DECORATION: &str = "decoration";
+/// Level of the thinnest flowing fluid; level 0 is a full source block.
+pub const FLUID_MAX_LEVEL: u8 = 7;
+
 impl BlockType {
     fn placeholder(id: BlockId) -> Self {
         BlockType {
@@ -429,6 +504,7 @@ impl BlockType {
             blocks_skylight: false,
             propagates_light: false,
             emission: 0,
+            flicker: FlickerClass::Steady,
             light: CompiledLight::Omni {
                 attenuation: 32,
                 max_range: None,
@@ -440,6 +516,7 @@ impl BlockType {
             pre_mat_side: vec![MaterialId(0)],
             pre_occ_mask: vec![0],
             pre_shape_variants: vec![ShapeVariant::default()],
+            pre_emission: vec![0],
             seam: SeamPolicy {
                 dont_occlude_same: false,
                 dont_project_fixups: false,
@@ -447,6 +524,7 @@ impl BlockType {
             state_schema: HashMap::new(),
             state_fields: Vec::new(),
             prop_index: HashMap::new(),
+            tags: Vec::new(),
         }
     }
 }
@@ -463,6 +541,23 @@ pub struct StateField {
 pub struct ShapeVariant {
     pub occupancy: Option<u8>,
     pub dynamic: Option<DynamicShape>,
+    /// Sloped surface; `occupancy` then holds its S=2 cover for lighting and collision.
+    pub slope: Option<SlopeShape>,
+    /// Config boxes, emitted one by one; `occupancy` holds the micro cells they fill.
+    pub boxes: bool,
+}
+
+impl ShapeVariant {
+    /// Occupancy the micro-grid mesher should build faces from. Slopes and config boxes
+    /// emit their own quads, so they stay out of the grid.
+    #[inline]
+    pub fn mesh_occupancy(&self) -> Option<u8> {
+        if self.slope.is_some() || self.boxes {
+            None
+        } else {
+            self.occupancy
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -474,13 +569,13 @@ pub enum DynamicShape {
     Carpet,
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct SeamPolicy {
     pub dont_occlude_same: bool,
     pub dont_project_fixups: bool,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum CompiledLight {
     Omni {
         attenuation: u8,
@@ -495,9 +590,12 @@ pub enum CompiledLight {
     },
 }
 
-fn compile_shape(shape: Option<ShapeConfig>) -> Shape {
+fn compile_shape(
+    shape: Option<ShapeConfig>,
+    schema: &HashMap<String, Vec<String>>,
+) -> Result<Shape, String> {
     use super::config::{PropertyFrom, ShapeConfig::*, ShapeDetailed};
-    match shape.unwrap_or(Simple("cube".into())) {
+    Ok(match shape.unwrap_or(Simple("cube".into())) {
         Simple(k) => match k.as_str() {
             "cube" => Shape::Cube,
             "slab" => Shape::Slab {
@@ -507,6 +605,15 @@ fn compile_shape(shape: Option<ShapeConfig>) -> Shape {
                 facing_from: "facing".into(),
                 half_from: "half".into(),
             },
+            "slope" => Shape::Slope {
+                facing_from: "facing".into(),
+                half_from: "half".into(),
+            },
+            "slope_corner" => Shape::SlopeCorner {
+                facing_from: "facing".into(),
+                half_from: "half".into(),
+                corner_from: "corner".into(),
+            },
             "pane" => Shape::Pane,
             "fence" => Shape::Fence,
             "gate" => Shape::Gate {
@@ -525,6 +632,8 @@ fn compile_shape(shape: Option<ShapeConfig>) -> Shape {
             half,
             facing,
             open,
+            corner,
+            boxes,
         }) => match kind.as_str() {
             "cube" => Shape::Cube,
             "axis_cube" => Shape::AxisCube {
@@ -541,6 +650,21 @@ fn compile_shape(shape: Option<ShapeConfig>) -> Shape {
                     .unwrap_or_else(|| "facing".to_string()),
                 half_from: half.map(|p| p.from).unwrap_or_else(|| "half".to_string()),
             },
+            "slope" => Shape::Slope {
+                facing_from: facing
+                    .map(|p| p.from)
+                    .unwrap_or_else(|| "facing".to_string()),
+                half_from: half.map(|p| p.from).unwrap_or_else(|| "half".to_string()),
+            },
+            "slope_corner" => Shape::SlopeCorner {
+                facing_from: facing
+                    .map(|p| p.from)
+                    .unwrap_or_else(|| "facing".to_string()),
+                half_from: half.map(|p| p.from).unwrap_or_else(|| "half".to_string()),
+                corner_from: corner
+                    .map(|p| p.from)
+                    .unwrap_or_else(|| "corner".to_string()),
+            },
             "pane" => Shape::Pane,
             "fence" => Shape::Fence,
             "gate" => Shape::Gate {
@@ -555,9 +679,57 @@ fn compile_shape(shape: Option<ShapeConfig>) -> Shape {
                     .unwrap_or_else(|| "facing".to_string()),
             },
             "carpet" => Shape::Carpet,
+            "boxes" => Shape::Boxes {
+                boxes: compile_boxes(boxes, schema)?,
+            },
             _ => Shape::None,
         },
+    })
+}
+
+/// Check the boxes of a `kind = "boxes"` shape against the cell and the state schema.
+fn compile_boxes(
+    boxes: Vec<BoxConfig>,
+    schema: &HashMap<String, Vec<String>>,
+) -> Result<Vec<ShapeBox>, String> {
+    if boxes.is_empty() {
+        return Err("boxes shape lists no boxes".into());
     }
+    boxes
+        .into_iter()
+        .map(|b| {
+            if (0..3).any(|i| b.from[i] >= b.to[i] || b.to[i] > BOX_UNITS) {
+                return Err(format!(
+                    "box {:?}..{:?} is empty or leaves the 0..={} cell",
+                    b.from, b.to, BOX_UNITS
+                ));
+            }
+            let connect = match b.connect.as_deref() {
+                Some(side) => Some(
+                    Facing::from_name(side)
+                        .ok_or_else(|| format!("unknown connect side '{}'", side))?,
+                ),
+                None => None,
+            };
+            let mut when: Vec<(String, String)> = b.when.into_iter().collect();
+            when.sort();
+            if let Some((prop, value)) = when
+                .iter()
+                .find(|(prop, value)| !schema.get(prop).is_some_and(|vs| vs.contains(value)))
+            {
+                return Err(format!(
+                    "box condition {}={} is not in the state schema",
+                    prop, value
+                ));
+            }
+            Ok(ShapeBox {
+                min: b.from,
+                max: b.to,
+                when,
+                connect,
+            })
+        })
+        .collect()
 }
 
 #[inline]
@@ -586,6 +758,36 @@ fn occ_stairs(facing: Facing, is_top: bool) -> u8 {
     full_layer | half_minor
 }
 
+/// Corner heights (see `SlopeShape`) of a ramp whose high edge faces `facing`.
+fn slope_heights(facing: Facing) -> [u8; 4] {
+    match facing {
+        Facing::North => [1, 1, 0, 0],
+        Facing::South => [0, 0, 1, 1],
+        Facing::West => [1, 0, 1, 0],
+        Facing::East => [0, 1, 0, 1],
+    }
+}
+
+/// Corner wedge heights. The odd corner sits clockwise of `facing`: north → north-west,
+/// east → north-east, south → south-east, west → south-west. Outer wedges raise only
+/// that corner; inner wedges raise all but the opposite one.
+fn slope_corner_heights(facing: Facing, inner: bool) -> [u8; 4] {
+    let odd = match facing {
+        Facing::North => 0,
+        Facing::East => 1,
+        Facing::South => 3,
+        Facing::West => 2,
+    };
+    let mut heights = [0u8; 4];
+    if inner {
+        heights = [1; 4];
+        heights[3 - odd] = 0;
+    } else {
+        heights[odd] = 1;
+    }
+    heights
+}
+
 fn compile_materials(matcat: &MaterialCatalog, mats: Option<MaterialsDef>) -> CompiledMaterials {
     fn resolve_selector(
         matcat: &MaterialCatalog,
@@ -666,8 +868,42 @@ impl BlockType {
     pub fn propagates_light(&self, _state: BlockState) -> bool {
         self.propagates_light
     }
-    pub fn light_emission(&self, _state: BlockState) -> u8 {
-        self.emission
+    #[inline]
+    pub fn light_emission(&self, state: BlockState) -> u8 {
+        let len = self.pre_emission.len();
+        self.pre_emission[state as usize & (len - 1)]
+    }
+    pub fn light_flicker(&self, _state: BlockState) -> FlickerClass {
+        self.flicker
+    }
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+    /// Falls when nothing solid is below it (sand, gravel).
+    pub fn falls(&self) -> bool {
+        self.has_tag(TAG_GRAVITY)
+    }
+    /// Drawn as an instanced decoration rather than chunk geometry.
+    pub fn is_decoration(&self) -> bool {
+        self.has_tag(TAG_DECORATION)
+    }
+    /// Fill level of a fluid block: 0 for a source, up to [`FLUID_MAX_LEVEL`] for the
+    /// thinnest flow; `None` for blocks not tagged [`TAG_FLUID`].
+    pub fn fluid_level(&self, state: BlockState) -> Option<u8> {
+        if !self.has_tag(TAG_FLUID) {
+            return None;
+        }
+        let level = self
+            .state_prop_value(state, "level")
+            .and_then(|v| v.parse::<u8>().ok())
+            .unwrap_or(0);
+        Some(level.min(FLUID_MAX_LEVEL))
+    }
+    /// State of this fluid at `level`.
+    pub fn fluid_state(&self, level: u8) -> BlockState {
+        let mut props = std::collections::HashMap::new();
+        props.insert("level".to_string(), level.min(FLUID_MAX_LEVEL).to_string());
+        self.pack_state(&props)
     }
     #[allow(dead_code)]
     pub fn debug_name(&self) -> &str {
@@ -733,6 +969,22 @@ impl BlockType {
         }
         acc as BlockState
     }
+    /// `state` with `prop` set to `value`; unchanged if the block has no such property or
+    /// value.
+    pub fn with_state_prop(&self, state: BlockState, prop: &str, value: &str) -> BlockState {
+        let Some(&i) = self.prop_index.get(prop) else {
+            return state;
+        };
+        let f = &self.state_fields[i];
+        let Some(idx) = f.values.iter().position(|v| v == value) else {
+            return state;
+        };
+        if f.bits == 0 {
+            return state;
+        }
+        let mask = ((1u32 << f.bits) - 1) << f.offset;
+        ((state as u32 & !mask) | ((idx as u32) << f.offset)) as BlockState
+    }
     #[inline]
     pub fn material_for_cached(&self, role: FaceRole, state: BlockState) -> MaterialId {
         match role {
@@ -767,6 +1019,32 @@ mod tests {
     use super::*;
     use crate::config::BlocksConfig;
 
+    #[test]
+    fn block_tags_are_kept_and_gravity_marks_falling_blocks() {
+        let materials =
+            MaterialCatalog::from_toml_str("[materials]\nsand = [\"assets/blocks/sand.png\"]\n")
+                .expect("materials");
+        let cfg: BlocksConfig = toml::from_str(
+            r#"[[blocks]]
+name = "sand"
+materials = { all = "sand" }
+tags = ["gravity", "diggable"]
+
+[[blocks]]
+name = "stone"
+materials = { all = "sand" }
+"#,
+        )
+        .expect("blocks");
+        let reg = BlockRegistry::from_configs(materials, cfg).expect("registry");
+        let sand = reg.get(reg.id_by_name("sand").unwrap()).unwrap();
+        let stone = reg.get(reg.id_by_name("stone").unwrap()).unwrap();
+        assert!(sand.falls());
+        assert!(sand.has_tag("diggable"));
+        assert!(!stone.falls());
+        assert!(stone.tags.is_empty());
+    }
+
     #[test]
     fn ladder_shape_is_dynamic_and_non_solid() {
         let materials = MaterialCatalog::from_toml_str(
@@ -798,4 +1076,54 @@ materials = { all = "ladder" }
         assert_eq!(ladder.state_prop_value(2, "facing"), Some("west"));
         assert_eq!(ladder.state_prop_value(3, "facing"), Some("east"));
     }
+
+    #[test]
+    fn slope_shapes_compile_to_wedges_with_cover() {
+        let materials =
+            MaterialCatalog::from_toml_str("[materials]\nstone = [\"assets/blocks/stone.png\"]\n")
+                .expect("materials");
+        let cfg: BlocksConfig = toml::from_str(
+            r#"[[blocks]]
+name = "ramp"
+shape = { kind = "slope", half = { from = "half" }, facing = { from = "facing" } }
+state_schema = { half = ["bottom","top"], facing = ["north","south","west","east"] }
+materials = { all = "stone" }
+
+[[blocks]]
+name = "ramp_corner"
+shape = "slope_corner"
+state_schema = { corner = ["outer","inner"], half = ["bottom","top"], facing = ["north","south","west","east"] }
+materials = { all = "stone" }
+"#,
+        )
+        .expect("blocks");
+        let reg = BlockRegistry::from_configs(materials, cfg).expect("registry");
+        let ramp = reg.get(reg.id_by_name("ramp").unwrap()).unwrap();
+        let state = |ty: &BlockType, props: &[(&str, &str)]| {
+            let map = props
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            ty.pack_state(&map)
+        };
+        let east = state(ramp, &[("facing", "east")]);
+        let var = ramp.variant(east);
+        let slope = var.slope.expect("ramp is sloped");
+        assert_eq!(slope, SlopeShape::new([0, 1, 0, 1], false));
+        assert_eq!(var.occupancy, Some(slope.occupancy_s2()));
+        assert_eq!(var.mesh_occupancy(), None);
+        // Bottom wall plus the raised east wall occlude their neighbors.
+        assert_eq!(
+            ramp.occlusion_mask_cached(east),
+            (1 << Face::PosY.index()) | (1 << Face::NegX.index())
+        );
+        let hung = ramp.variant(state(ramp, &[("half", "top")])).slope.unwrap();
+        assert!(hung.top);
+
+        let corner = reg.get(reg.id_by_name("ramp_corner").unwrap()).unwrap();
+        let outer = corner.variant(state(corner, &[("facing", "south")]));
+        assert_eq!(outer.slope.unwrap().heights, [0, 0, 0, 1]);
+        let inner = corner.variant(state(corner, &[("facing", "south"), ("corner", "inner")]));
+        assert_eq!(inner.slope.unwrap().heights, [0, 1, 1, 1]);
+    }
 }
@@ -0,0 +1,191 @@
+//! Checking a re-parsed registry against the live one before swapping it in.
+//!
+//! Loaded chunks, edits and structures hold raw `Block`s, so a reload may only keep going
+//! if every existing id still names the same block with the same state layout; appending
+//! new blocks is fine. [`BlockRegistry::diff_reload`] refuses anything else and otherwise
+//! lists the blocks whose meshes or light changed, so only chunks holding them rebuild.
+
+use std::fmt;
+
+use crate::material::{Material, MaterialCatalog};
+use crate::registry::{BlockRegistry, BlockType};
+use crate::types::{BlockId, MaterialId};
+
+/// Why a reloaded registry cannot replace the live one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IncompatibleReload {
+    /// The id no longer exists.
+    Removed { id: BlockId, name: String },
+    /// The id now names another block.
+    Renamed {
+        id: BlockId,
+        old: String,
+        new: String,
+    },
+    /// The block's state properties or their values changed, so stored states would
+    /// decode differently.
+    StateLayout { id: BlockId, name: String },
+}
+
+impl fmt::Display for IncompatibleReload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IncompatibleReload::Removed { id, name } => {
+                write!(f, "block '{}' (id {}) was removed", name, id)
+            }
+            IncompatibleReload::Renamed { id, old, new } => {
+                write!(f, "id {} moved from '{}' to '{}'", id, old, new)
+            }
+            IncompatibleReload::StateLayout { id, name } => {
+                write!(f, "block '{}' (id {}) changed its state schema", name, id)
+            }
+        }
+    }
+}
+
+/// What changed between the live registry and a compatible reload.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RegistryDiff {
+    /// Existing blocks whose meshes differ: shape, materials, solidity, seams or tags.
+    /// Includes every block in `relit`.
+    pub changed: Vec<BlockId>,
+    /// Existing blocks whose light differs: emission, opacity or light profile.
+    pub relit: Vec<BlockId>,
+    /// Ids past the end of the live registry.
+    pub added: Vec<BlockId>,
+    /// Any material was added, removed, renumbered or redefined. Chunk parts and the
+    /// texture array are keyed by material id, so everything must remesh.
+    pub materials_changed: bool,
+}
+
+impl RegistryDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.added.is_empty() && !self.materials_changed
+    }
+
+    /// Per-id flags for the blocks in `changed`, for scanning chunk buffers.
+    pub fn changed_mask(&self) -> Vec<bool> {
+        let len = self
+            .changed
+            .iter()
+            .map(|&id| id as usize + 1)
+            .max()
+            .unwrap_or(0);
+        let mut mask = vec![false; len];
+        for &id in &self.changed {
+            mask[id as usize] = true;
+        }
+        mask
+    }
+}
+
+fn material_key(mats: &MaterialCatalog, id: MaterialId) -> Option<&str> {
+    mats.get(id).map(|m| m.key.as_str())
+}
+
+fn same_material(a: &Material, b: &Material) -> bool {
+    a.key == b.key
+        && a.texture_candidates == b.texture_candidates
+        && a.render_tag == b.render_tag
+        && a.animation == b.animation
+        && a.render_pass == b.render_pass
+        && a.smooth == b.smooth
+        && a.connected == b.connected
+}
+
+fn same_state_layout(a: &BlockType, b: &BlockType) -> bool {
+    a.state_fields.len() == b.state_fields.len()
+        && a.state_fields
+            .iter()
+            .zip(&b.state_fields)
+            .all(|(x, y)| x.name == y.name && x.values == y.values)
+}
+
+fn same_light(a: &BlockType, b: &BlockType) -> bool {
+    a.solid == b.solid
+        && a.blocks_skylight == b.blocks_skylight
+        && a.propagates_light == b.propagates_light
+        && a.pre_emission == b.pre_emission
+        && a.flicker == b.flicker
+        && a.light == b.light
+}
+
+fn same_mesh(a: &BlockType, old: &MaterialCatalog, b: &BlockType, new: &MaterialCatalog) -> bool {
+    let same_mats = |x: &[MaterialId], y: &[MaterialId]| {
+        x.len() == y.len()
+            && x.iter()
+                .zip(y)
+                .all(|(&m, &n)| material_key(old, m) == material_key(new, n))
+    };
+    a.solid == b.solid
+        && a.shape == b.shape
+        && a.seam == b.seam
+        && a.tags == b.tags
+        && same_mats(&a.pre_mat_top, &b.pre_mat_top)
+        && same_mats(&a.pre_mat_bottom, &b.pre_mat_bottom)
+        && same_mats(&a.pre_mat_side, &b.pre_mat_side)
+}
+
+impl BlockRegistry {
+    /// Compare `new`, freshly parsed from the same files, against this live registry.
+    /// Fails with every id `new` would reinterpret.
+    pub fn diff_reload(
+        &self,
+        new: &BlockRegistry,
+    ) -> Result<RegistryDiff, Vec<IncompatibleReload>> {
+        let mut problems = Vec::new();
+        let mut diff = RegistryDiff::default();
+        for old_ty in &self.blocks {
+            // Gaps in explicit ids hold unnamed placeholders; nothing can reference them.
+            if old_ty.name.is_empty() {
+                continue;
+            }
+            let Some(new_ty) = new.get(old_ty.id).filter(|t| !t.name.is_empty()) else {
+                problems.push(IncompatibleReload::Removed {
+                    id: old_ty.id,
+                    name: old_ty.name.clone(),
+                });
+                continue;
+            };
+            if new_ty.name != old_ty.name {
+                problems.push(IncompatibleReload::Renamed {
+                    id: old_ty.id,
+                    old: old_ty.name.clone(),
+                    new: new_ty.name.clone(),
+                });
+                continue;
+            }
+            if !same_state_layout(old_ty, new_ty) {
+                problems.push(IncompatibleReload::StateLayout {
+                    id: old_ty.id,
+                    name: old_ty.name.clone(),
+                });
+                continue;
+            }
+            let relit = !same_light(old_ty, new_ty);
+            if relit {
+                diff.relit.push(old_ty.id);
+            }
+            if relit || !same_mesh(old_ty, &self.materials, new_ty, &new.materials) {
+                diff.changed.push(old_ty.id);
+            }
+        }
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+        diff.added = new
+            .blocks
+            .iter()
+            .skip(self.blocks.len())
+            .filter(|t| !t.name.is_empty())
+            .map(|t| t.id)
+            .collect();
+        let (old_mats, new_mats) = (&self.materials.materials, &new.materials.materials);
+        diff.materials_changed = old_mats.len() != new_mats.len()
+            || old_mats
+                .iter()
+                .zip(new_mats)
+                .any(|(a, b)| !same_material(a, b));
+        Ok(diff)
+    }
+}
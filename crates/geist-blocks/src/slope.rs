@@ -0,0 +1,209 @@
+//! Sloped shapes (45° ramps and corner wedges) described by the heights of their four
+//! top corners.
+#![forbid(unsafe_code)]
+
+/// Corner order used by `SlopeShape::heights`: (x0,z0), (x1,z0), (x0,z1), (x1,z1).
+pub const SLOPE_CORNERS: [(f32, f32); 4] = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)];
+
+const EPS: f32 = 1e-4;
+
+/// A wedge filling a unit cell from its base up to a surface through four corner heights.
+///
+/// Heights are 0 or 1. Two raised corners on one edge make a ramp; one or three make an
+/// outer or inner corner wedge, whose surface folds along the diagonal through the odd
+/// corner. `top` hangs the wedge from the ceiling instead, for roof undersides.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SlopeShape {
+    pub heights: [u8; 4],
+    pub top: bool,
+}
+
+impl SlopeShape {
+    pub const fn new(heights: [u8; 4], top: bool) -> Self {
+        Self { heights, top }
+    }
+
+    /// True when the surface folds along (x0,z0)-(x1,z1); false for (x1,z0)-(x0,z1).
+    #[inline]
+    pub fn folds_on_main_diagonal(&self) -> bool {
+        let raised = self.heights.iter().filter(|&&h| h != 0).count();
+        let odd = match raised {
+            1 => self.heights.iter().position(|&h| h != 0),
+            3 => self.heights.iter().position(|&h| h == 0),
+            _ => None,
+        };
+        !matches!(odd, Some(1) | Some(2))
+    }
+
+    #[inline]
+    fn h(&self, i: usize) -> f32 {
+        (self.heights[i] != 0) as u8 as f32
+    }
+
+    /// Depth of the wedge measured from its base at local (x, z) in [0,1].
+    pub fn height_at(&self, x: f32, z: f32) -> f32 {
+        let (h00, h10, h01, h11) = (self.h(0), self.h(1), self.h(2), self.h(3));
+        if self.folds_on_main_diagonal() {
+            if x >= z {
+                h00 + (h10 - h00) * x + (h11 - h10) * z
+            } else {
+                h00 + (h01 - h00) * z + (h11 - h01) * x
+            }
+        } else if x + z <= 1.0 {
+            h00 + (h10 - h00) * x + (h01 - h00) * z
+        } else {
+            h11 + (h01 - h11) * (1.0 - x) + (h10 - h11) * (1.0 - z)
+        }
+    }
+
+    /// Whether local point (x, y, z) in [0,1]^3 lies inside the wedge.
+    pub fn contains(&self, x: f32, y: f32, z: f32) -> bool {
+        let h = self.height_at(x.clamp(0.0, 1.0), z.clamp(0.0, 1.0));
+        if self.top {
+            y >= 1.0 - h - EPS
+        } else {
+            y <= h + EPS
+        }
+    }
+
+    /// Whether the segment `a`-`b` (local cell coordinates) touches the wedge. The
+    /// surface is linear on each side of the fold, so checking the endpoints and the
+    /// fold crossing is exact.
+    pub fn segment_intersects(&self, a: [f32; 3], b: [f32; 3]) -> bool {
+        let at = |t: f32| {
+            [
+                a[0] + (b[0] - a[0]) * t,
+                a[1] + (b[1] - a[1]) * t,
+                a[2] + (b[2] - a[2]) * t,
+            ]
+        };
+        let fold = |p: [f32; 3]| {
+            if self.folds_on_main_diagonal() {
+                p[0] - p[2]
+            } else {
+                p[0] + p[2] - 1.0
+            }
+        };
+        let (fa, fb) = (fold(a), fold(b));
+        let mut ts = [0.0, 1.0, -1.0];
+        if (fa < 0.0) != (fb < 0.0) && (fa - fb).abs() > f32::EPSILON {
+            ts[2] = fa / (fa - fb);
+        }
+        ts.iter().filter(|t| (0.0..=1.0).contains(*t)).any(|&t| {
+            let p = at(t);
+            self.contains(p[0], p[1], p[2])
+        })
+    }
+
+    /// S=2 occupancy covering every micro cell the wedge reaches into, in the
+    /// `idx = (y<<2)|(z<<1)|x` layout used by slabs and stairs.
+    pub fn occupancy_s2(&self) -> u8 {
+        let mut occ = 0u8;
+        for mz in 0..2usize {
+            for mx in 0..2usize {
+                let (x0, z0) = (mx as f32 * 0.5, mz as f32 * 0.5);
+                let peak = [(0.0, 0.0), (0.5, 0.0), (0.0, 0.5), (0.5, 0.5)]
+                    .iter()
+                    .map(|(dx, dz)| self.height_at(x0 + dx, z0 + dz))
+                    .fold(0.0f32, f32::max);
+                for layer in 0..2usize {
+                    if peak > layer as f32 * 0.5 + EPS {
+                        let my = if self.top { 1 - layer } else { layer };
+                        occ |= 1u8 << ((my << 2) | (mz << 1) | mx);
+                    }
+                }
+            }
+        }
+        occ
+    }
+
+    /// Heights of the two corners on a side wall, ordered along the wall's u axis
+    /// (x for Z walls, z for X walls). Face indices: 2=+X, 3=-X, 4=+Z, 5=-Z.
+    pub fn side_heights(&self, face: usize) -> Option<(u8, u8)> {
+        let h = self.heights;
+        match face {
+            2 => Some((h[1], h[3])),
+            3 => Some((h[0], h[2])),
+            4 => Some((h[2], h[3])),
+            5 => Some((h[0], h[1])),
+            _ => None,
+        }
+    }
+
+    /// Neighbor occlusion bits in `Face` order: bit `f` is set when the wall facing away
+    /// from `f` is completely filled, i.e. this wedge hides a neighbor's face `f`.
+    pub fn occlusion_mask(&self) -> u8 {
+        let full = |face: usize| {
+            self.side_heights(face)
+                .is_some_and(|(a, b)| a != 0 && b != 0)
+        };
+        let base = if self.top { 1 } else { 0 };
+        (1 << base)
+            | ((full(3) as u8) << 2)
+            | ((full(2) as u8) << 3)
+            | ((full(5) as u8) << 4)
+            | ((full(4) as u8) << 5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bit(x: usize, y: usize, z: usize) -> u8 {
+        1u8 << ((y << 2) | (z << 1) | x)
+    }
+
+    #[test]
+    fn ramp_matches_stairs_cover_and_surface() {
+        // High along z=0 (north), falling to z=1.
+        let ramp = SlopeShape::new([1, 1, 0, 0], false);
+        assert!((ramp.height_at(0.3, 0.25) - 0.75).abs() < 1e-6);
+        let stairs_north =
+            bit(0, 0, 0) | bit(1, 0, 0) | bit(0, 0, 1) | bit(1, 0, 1) | bit(0, 1, 0) | bit(1, 1, 0);
+        assert_eq!(ramp.occupancy_s2(), stairs_north);
+        // Bottom wall and the north wall are full.
+        assert_eq!(ramp.occlusion_mask(), 0b01_0001);
+        assert!(ramp.contains(0.5, 0.4, 0.5));
+        assert!(!ramp.contains(0.5, 0.6, 0.5));
+    }
+
+    #[test]
+    fn corner_wedges_fold_through_odd_corner() {
+        let outer = SlopeShape::new([1, 0, 0, 0], false);
+        let inner = SlopeShape::new([1, 1, 1, 0], false);
+        assert!(outer.folds_on_main_diagonal());
+        assert!(inner.folds_on_main_diagonal());
+        assert!(!SlopeShape::new([0, 1, 0, 0], false).folds_on_main_diagonal());
+        // Outer = min of the two ramps, inner = max.
+        assert!((outer.height_at(0.2, 0.6) - 0.4).abs() < 1e-6);
+        assert!((inner.height_at(0.2, 0.6) - 0.8).abs() < 1e-6);
+        assert_eq!(outer.occupancy_s2().count_ones(), 5);
+        assert_eq!(inner.occupancy_s2().count_ones(), 7);
+        assert_eq!(outer.occlusion_mask(), 0b00_0001);
+        // Inner corner fills its north (-Z) and west (-X) walls.
+        assert_eq!(inner.occlusion_mask(), 0b01_0101);
+    }
+
+    #[test]
+    fn upside_down_wedge_mirrors_cover() {
+        let ramp = SlopeShape::new([1, 1, 0, 0], true);
+        let occ = ramp.occupancy_s2();
+        assert_eq!(occ & 0xF0, 0xF0, "ceiling layer is full");
+        assert_eq!(occ & 0x0F, bit(0, 0, 0) | bit(1, 0, 0));
+        assert_eq!(ramp.occlusion_mask() & 0b11, 0b10);
+    }
+
+    #[test]
+    fn segments_hit_only_the_solid_part() {
+        let ramp = SlopeShape::new([1, 1, 0, 0], false);
+        // Skims above the low (south) end.
+        assert!(!ramp.segment_intersects([0.0, 0.9, 0.95], [1.0, 0.9, 0.95]));
+        // Dives into the high end.
+        assert!(ramp.segment_intersects([0.5, 1.0, 0.5], [0.5, 0.2, 0.0]));
+        // Crosses the fold of an outer corner only above its surface.
+        let outer = SlopeShape::new([1, 0, 0, 0], false);
+        assert!(!outer.segment_intersects([1.0, 0.55, 0.0], [0.0, 0.55, 1.0]));
+        assert!(outer.segment_intersects([1.0, 0.3, 0.0], [0.0, 0.3, 1.0]));
+    }
+}
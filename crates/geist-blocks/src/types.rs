@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::orient::Facing;
+
 // Compact voxel representation used at runtime
 #[derive(Copy, Clone, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
 pub struct Block {
@@ -42,6 +44,17 @@ pub enum Shape {
         facing_from: String,
         half_from: String,
     },
+    /// 45° ramp rising toward `facing`.
+    Slope {
+        facing_from: String,
+        half_from: String,
+    },
+    /// Corner wedge; `corner` is "outer" (one raised corner) or "inner" (three).
+    SlopeCorner {
+        facing_from: String,
+        half_from: String,
+        corner_from: String,
+    },
     Pane,
     Fence,
     Gate {
@@ -52,5 +65,20 @@ pub enum Shape {
         facing_from: String,
     },
     Carpet,
+    /// Union of axis-aligned boxes listed in config, like model elements.
+    Boxes {
+        boxes: Vec<ShapeBox>,
+    },
     None,
 }
+
+/// One box of a [`Shape::Boxes`], in sixteenths of a block.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ShapeBox {
+    pub min: [u8; 3],
+    pub max: [u8; 3],
+    /// State properties that must all hold their values for the box to appear.
+    pub when: Vec<(String, String)>,
+    /// Side whose neighbor must connect for the box to appear, as for fence rails.
+    pub connect: Option<Facing>,
+}
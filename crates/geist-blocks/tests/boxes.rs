@@ -0,0 +1,100 @@
+use geist_blocks::config::BlocksConfig;
+use geist_blocks::material::MaterialCatalog;
+use geist_blocks::registry::BlockRegistry;
+use geist_blocks::{Facing, Shape};
+
+const MATERIALS: &str = "[materials]\nstone = [\"stone.png\"]\n";
+
+fn registry(blocks: &str) -> Result<BlockRegistry, String> {
+    let materials = MaterialCatalog::from_toml_str(MATERIALS).expect("materials");
+    let cfg: BlocksConfig = toml::from_str(blocks).expect("blocks");
+    BlockRegistry::from_configs(materials, cfg).map_err(|e| e.to_string())
+}
+
+fn boxes_block(boxes: &str, schema: &str) -> String {
+    format!(
+        "[[blocks]]\nname = \"shelf\"\nmaterials = {{ all = \"stone\" }}\nstate_schema = {{ {} }}\n[blocks.shape]\nkind = \"boxes\"\nboxes = [\n{}\n]\n",
+        schema, boxes
+    )
+}
+
+#[test]
+fn boxes_derive_cover_per_state() {
+    let reg = registry(&boxes_block(
+        r#"{ from = [0, 0, 0], to = [16, 8, 16] },
+{ from = [0, 8, 0], to = [16, 16, 8], when = { back = "north" } },
+{ from = [0, 8, 8], to = [16, 16, 16], when = { back = "south" } },"#,
+        r#"back = ["north", "south"]"#,
+    ))
+    .unwrap();
+    let ty = reg.get(reg.id_by_name("shelf").unwrap()).unwrap();
+    assert!(matches!(ty.shape, Shape::Boxes { .. }));
+    let north = ty.with_state_prop(0, "back", "north");
+    let south = ty.with_state_prop(0, "back", "south");
+    assert_eq!(ty.shape_boxes(north).count(), 2);
+
+    // Bottom layer plus the upper z=0 row; the mesher emits the boxes itself.
+    let var = ty.variant(north);
+    assert_eq!(var.occupancy, Some(0b0011_1111));
+    assert_eq!(var.mesh_occupancy(), None);
+    assert_eq!(ty.variant(south).occupancy, Some(0b1100_1111));
+    // The bottom is full either way; only the back wall is full to the top.
+    assert_eq!(ty.occlusion_mask_cached(north), 0b01_0001);
+    assert_eq!(ty.occlusion_mask_cached(south), 0b10_0001);
+}
+
+#[test]
+fn connecting_boxes_stay_out_of_the_cover() {
+    let reg = registry(&boxes_block(
+        r#"{ from = [0, 0, 0], to = [16, 16, 8] },
+{ from = [0, 0, 8], to = [16, 16, 16], connect = "south" },"#,
+        "",
+    ))
+    .unwrap();
+    let ty = reg.get(reg.id_by_name("shelf").unwrap()).unwrap();
+    let boxes: Vec<_> = ty.shape_boxes(0).collect();
+    assert_eq!(boxes.len(), 2);
+    assert_eq!(boxes[1].connect, Some(Facing::South));
+    assert_eq!(ty.variant(0).occupancy, Some(0b0011_0011));
+    assert_eq!(ty.occlusion_mask_cached(0), 1 << 4);
+}
+
+#[test]
+fn malformed_boxes_are_rejected() {
+    let cases = [
+        (r#"{ from = [0, 0, 0], to = [17, 16, 16] }"#, "leaves"),
+        (r#"{ from = [4, 0, 4], to = [4, 16, 12] }"#, "empty"),
+        (
+            r#"{ from = [0, 0, 0], to = [16, 16, 16], connect = "up" }"#,
+            "connect side 'up'",
+        ),
+        (
+            r#"{ from = [0, 0, 0], to = [16, 16, 16], when = { lit = "true" } }"#,
+            "lit=true",
+        ),
+    ];
+    for (boxes, expect) in cases {
+        let err = registry(&boxes_block(boxes, "")).unwrap_err();
+        assert!(
+            err.contains("block 'shelf'") && err.contains(expect),
+            "{}",
+            err
+        );
+    }
+    let err = registry(&boxes_block("", "")).unwrap_err();
+    assert!(err.contains("no boxes"), "{}", err);
+}
+
+#[test]
+fn shipped_wall_is_a_thin_post() {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let vox = root.join("../../assets/voxels");
+    let reg = BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml"))
+        .unwrap();
+    let wall = reg
+        .get(reg.id_by_name("cobblestone_wall").unwrap())
+        .unwrap();
+    assert_eq!(wall.shape_boxes(0).count(), 5);
+    assert_eq!(wall.variant(0).occupancy, Some(0));
+    assert_eq!(wall.occlusion_mask_cached(0), 0);
+}
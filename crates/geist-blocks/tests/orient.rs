@@ -0,0 +1,248 @@
+use geist_blocks::config::BlocksConfig;
+use geist_blocks::material::MaterialCatalog;
+use geist_blocks::registry::BlockType;
+use geist_blocks::types::BlockState;
+use geist_blocks::{BlockRegistry, Facing, Mirror, Placement, Shape};
+
+fn load_registry() -> BlockRegistry {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let vox = root.join("../../assets/voxels");
+    BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml")).unwrap()
+}
+
+fn block<'a>(reg: &'a BlockRegistry, name: &str) -> &'a BlockType {
+    reg.get(reg.id_by_name(name).unwrap()).unwrap()
+}
+
+fn states(ty: &BlockType) -> impl Iterator<Item = BlockState> {
+    let bits: u32 = ty.state_fields.iter().map(|f| f.bits).sum();
+    (0..1u32 << bits).map(|s| s as BlockState)
+}
+
+fn against_wall(normal: (i32, i32, i32), hit_y: f32, look: (f32, f32)) -> Placement {
+    Placement {
+        normal,
+        hit_y,
+        look,
+    }
+}
+
+// Occupancy bits are indexed (y << 2) | (z << 1) | x over the 2x2x2 sub-cells.
+fn turn_occupancy_cw(occ: u8) -> u8 {
+    let mut out = 0;
+    for i in 0..8 {
+        if occ & (1 << i) != 0 {
+            let (x, z, y) = (i & 1, (i >> 1) & 1, i >> 2);
+            // Clockwise from above: north (-z) goes to east (+x).
+            let (nx, nz) = (1 - z, x);
+            out |= 1 << ((y << 2) | (nz << 1) | nx);
+        }
+    }
+    out
+}
+
+fn mirror_occupancy(occ: u8, mirror: Mirror) -> u8 {
+    let flip = match mirror {
+        Mirror::X => 0b001,
+        Mirror::Z => 0b010,
+    };
+    (0..8)
+        .filter(|i| occ & (1 << i) != 0)
+        .fold(0, |acc, i| acc | 1 << (i ^ flip))
+}
+
+#[test]
+fn facing_turns_and_reads_look_vectors() {
+    for f in Facing::ALL {
+        assert_eq!(Facing::from_name(f.name()), Some(f));
+        assert_eq!(f.rotated_cw(4), f);
+        assert_eq!(f.opposite().opposite(), f);
+        let (dx, dz) = f.offset();
+        assert_eq!(Facing::from_vector(dx as f32, dz as f32), f);
+    }
+    assert_eq!(Facing::North.rotated_cw(1), Facing::East);
+    assert_eq!(Facing::from_vector(0.3, -0.9), Facing::North);
+    assert_eq!(Facing::from_vector(-0.8, 0.5), Facing::West);
+    assert_eq!(Facing::from_name("up"), None);
+}
+
+#[test]
+fn every_block_rotates_and_mirrors_back_to_itself() {
+    let reg = load_registry();
+    for ty in reg.blocks.iter().filter(|t| !t.name.is_empty()) {
+        for s in states(ty) {
+            assert_eq!(ty.rotate_state(s, 4), s, "{} {}", ty.name, s);
+            assert_eq!(
+                ty.rotate_state(ty.rotate_state(s, 1), 3),
+                s,
+                "{} {}",
+                ty.name,
+                s
+            );
+            for m in [Mirror::X, Mirror::Z] {
+                assert_eq!(ty.mirror_state(ty.mirror_state(s, m), m), s, "{}", ty.name);
+            }
+            // Only orientation moves; materials and other properties stay put.
+            for f in &ty.state_fields {
+                if f.name != "facing" {
+                    assert_eq!(
+                        ty.state_prop_value(ty.rotate_state(s, 1), &f.name),
+                        ty.state_prop_value(s, &f.name),
+                        "{}.{}",
+                        ty.name,
+                        f.name
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn turned_and_mirrored_states_match_turned_shapes() {
+    let reg = load_registry();
+    let mut checked = 0;
+    for ty in reg.blocks.iter().filter(|t| {
+        matches!(
+            t.shape,
+            Shape::Stairs { .. } | Shape::Slope { .. } | Shape::SlopeCorner { .. }
+        )
+    }) {
+        for s in states(ty) {
+            let occ = |s| ty.variant(s).occupancy.unwrap();
+            assert_eq!(
+                occ(ty.rotate_state(s, 1)),
+                turn_occupancy_cw(occ(s)),
+                "{} {}",
+                ty.name,
+                s
+            );
+            for m in [Mirror::X, Mirror::Z] {
+                assert_eq!(
+                    occ(ty.mirror_state(s, m)),
+                    mirror_occupancy(occ(s), m),
+                    "{} {:?}",
+                    ty.name,
+                    m
+                );
+            }
+            checked += 1;
+        }
+    }
+    assert!(checked > 0);
+}
+
+#[test]
+fn stairs_and_slopes_climb_the_way_the_placer_looks() {
+    let reg = load_registry();
+    for name in ["stairs", "slope", "slope_corner"] {
+        let ty = block(&reg, name);
+        let on_floor = against_wall((0, 1, 0), 0.0, (0.2, -1.0));
+        let s = ty.placement_state(0, &on_floor);
+        assert_eq!(ty.state_prop_value(s, "facing"), Some("north"), "{}", name);
+        assert_eq!(ty.state_prop_value(s, "half"), Some("bottom"), "{}", name);
+
+        let under_ceiling = against_wall((0, -1, 0), 0.0, (1.0, 0.1));
+        let s = ty.placement_state(0, &under_ceiling);
+        assert_eq!(ty.state_prop_value(s, "facing"), Some("east"), "{}", name);
+        assert_eq!(ty.state_prop_value(s, "half"), Some("top"), "{}", name);
+    }
+}
+
+#[test]
+fn slabs_pick_the_half_from_the_hit() {
+    let reg = load_registry();
+    let slab = block(&reg, "slab");
+    let half = |p: Placement| slab.state_prop_value(slab.placement_state(0, &p), "half");
+    assert_eq!(
+        half(against_wall((0, 1, 0), 0.9, (0.0, 1.0))),
+        Some("bottom")
+    );
+    assert_eq!(half(against_wall((0, -1, 0), 0.1, (0.0, 1.0))), Some("top"));
+    assert_eq!(half(against_wall((1, 0, 0), 0.75, (0.0, 1.0))), Some("top"));
+    assert_eq!(
+        half(against_wall((0, 0, -1), 0.25, (0.0, 1.0))),
+        Some("bottom")
+    );
+}
+
+#[test]
+fn placement_keeps_material_properties() {
+    let reg = load_registry();
+    let stairs = block(&reg, "stairs");
+    let base = stairs
+        .state_fields
+        .iter()
+        .find(|f| f.name == "material")
+        .map(|f| stairs.with_state_prop(0, "material", &f.values[f.values.len() - 1]))
+        .unwrap();
+    let placed = stairs.placement_state(base, &against_wall((0, 1, 0), 0.0, (-1.0, 0.0)));
+    assert_eq!(
+        stairs.state_prop_value(placed, "material"),
+        stairs.state_prop_value(base, "material")
+    );
+    assert_eq!(stairs.state_prop_value(placed, "facing"), Some("west"));
+}
+
+#[test]
+fn ladders_face_out_of_the_wall_and_gates_across_the_path() {
+    let reg = load_registry();
+    let ladder = block(&reg, "ladder");
+    for f in Facing::ALL {
+        let (dx, dz) = f.offset();
+        // Looking into the wall, so the look is opposite to the clicked face's normal.
+        let p = against_wall((dx, 0, dz), 0.5, (-dx as f32, -dz as f32));
+        let s = ladder.placement_state(0, &p);
+        assert_eq!(ladder.state_prop_value(s, "facing"), Some(f.name()));
+    }
+    // On a floor there is no wall; the ladder faces the placer.
+    let s = ladder.placement_state(0, &against_wall((0, 1, 0), 0.0, (0.0, 1.0)));
+    assert_eq!(ladder.state_prop_value(s, "facing"), Some("north"));
+
+    let gate = reg
+        .blocks
+        .iter()
+        .find(|t| matches!(t.shape, Shape::Gate { .. }))
+        .unwrap();
+    let s = gate.placement_state(0, &against_wall((0, 1, 0), 0.0, (0.0, 1.0)));
+    assert_eq!(gate.state_prop_value(s, "facing"), Some("south"));
+    assert_eq!(gate.state_prop_value(s, "open"), Some("false"));
+}
+
+#[test]
+fn axis_blocks_follow_the_clicked_face_and_turn_sideways() {
+    let materials = MaterialCatalog::from_toml_str("[materials]\nbark = [\"bark.png\"]\n").unwrap();
+    let cfg: BlocksConfig = toml::from_str(
+        r#"[[blocks]]
+name = "log"
+shape = { kind = "axis_cube", axis = { from = "axis" } }
+state_schema = { axis = ["y", "x", "z"] }
+materials = { all = "bark" }
+"#,
+    )
+    .unwrap();
+    let reg = BlockRegistry::from_configs(materials, cfg).unwrap();
+    let log = block(&reg, "log");
+    let axis = |s| log.state_prop_value(s, "axis");
+    let place = |n| log.placement_state(0, &against_wall(n, 0.5, (0.0, -1.0)));
+    assert_eq!(axis(place((1, 0, 0))), Some("x"));
+    assert_eq!(axis(place((0, 0, -1))), Some("z"));
+    assert_eq!(axis(place((0, -1, 0))), Some("y"));
+
+    let x = place((-1, 0, 0));
+    assert_eq!(axis(log.rotate_state(x, 1)), Some("z"));
+    assert_eq!(axis(log.rotate_state(x, 2)), Some("x"));
+    let y = place((0, 1, 0));
+    assert_eq!(axis(log.rotate_state(y, 3)), Some("y"));
+    assert_eq!(log.mirror_state(x, Mirror::X), x);
+}
+
+#[test]
+fn unoriented_blocks_place_as_given() {
+    let reg = load_registry();
+    let stone = block(&reg, "stone");
+    let p = against_wall((0, 0, 1), 0.9, (0.5, 0.5));
+    assert_eq!(stone.placement_state(0, &p), 0);
+    assert_eq!(stone.rotate_state(0, 1), 0);
+    assert_eq!(stone.mirror_state(0, Mirror::Z), 0);
+}
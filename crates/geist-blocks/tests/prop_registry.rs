@@ -18,13 +18,15 @@ fn pack_state_roundtrip_fixed() {
         solid: Some(true),
         blocks_skylight: Some(true),
         propagates_light: Some(false),
-        emission: Some(0),
+        emission: Some(0.into()),
+        flicker: None,
         light_profile: None,
         light: None,
         shape: None,
         materials: None,
         state_schema: Some(schema.clone()),
         seam: None,
+        tags: Vec::new(),
     };
     let cfg = BlocksConfig {
         blocks: vec![def],
@@ -94,13 +96,15 @@ fn material_cache_matches_dynamic_fixed() {
         solid: Some(true),
         blocks_skylight: Some(true),
         propagates_light: Some(false),
-        emission: Some(0),
+        emission: Some(0.into()),
+        flicker: None,
         light_profile: None,
         light: None,
         shape: Some(ShapeConfig::Simple("cube".into())),
         materials: Some(materials_def),
         state_schema: Some(schema.clone()),
         seam: None,
+        tags: Vec::new(),
     };
     let cfg = BlocksConfig {
         blocks: vec![def],
@@ -144,13 +148,15 @@ fn slab_occlusion_and_occupancy_half_fixed() {
         solid: Some(true),
         blocks_skylight: Some(false),
         propagates_light: Some(true),
-        emission: Some(0),
+        emission: Some(0.into()),
+        flicker: None,
         light_profile: None,
         light: None,
         shape: Some(ShapeConfig::Simple("slab".into())),
         materials: None,
         state_schema: Some(schema.clone()),
         seam: None,
+        tags: Vec::new(),
     };
     let cfg = BlocksConfig {
         blocks: vec![def],
@@ -175,3 +181,61 @@ fn slab_occlusion_and_occupancy_half_fixed() {
     assert_eq!(occ_bottom, 0x0F);
     assert_eq!(occ_top, 0xF0);
 }
+
+#[test]
+fn emission_follows_state_property() {
+    use geist_blocks::config::EmissionDef;
+    let def = BlockDef {
+        name: "furnace".into(),
+        id: Some(0),
+        solid: Some(true),
+        blocks_skylight: Some(true),
+        propagates_light: Some(false),
+        emission: Some(EmissionDef::By {
+            by: "lit".into(),
+            map: HashMap::from([("true".into(), 180)]),
+        }),
+        flicker: None,
+        light_profile: None,
+        light: None,
+        shape: None,
+        materials: None,
+        state_schema: Some(HashMap::from([
+            ("lit".into(), vec!["false".into(), "true".into()]),
+            ("facing".into(), vec!["north".into(), "south".into()]),
+        ])),
+        seam: None,
+        tags: Vec::new(),
+    };
+    let cfg = BlocksConfig {
+        blocks: vec![def],
+        lighting: None,
+        unknown_block: None,
+    };
+    let reg = BlockRegistry::from_configs(MaterialCatalog::new(), cfg).expect("registry");
+    let ty = reg.get(0).unwrap();
+    assert_eq!(ty.emission, 180);
+    for facing in ["north", "south"] {
+        for (lit, level) in [("false", 0), ("true", 180)] {
+            let state = ty.pack_state(&HashMap::from([
+                ("lit".to_string(), lit.to_string()),
+                ("facing".to_string(), facing.to_string()),
+            ]));
+            assert_eq!(ty.light_emission(state), level, "lit={lit} facing={facing}");
+        }
+    }
+}
+
+#[test]
+fn bundled_furnace_glows_only_while_lit() {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let vox = root.join("../../assets/voxels");
+    let reg = BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml"))
+        .unwrap();
+    let lit = HashMap::from([("lit".to_string(), "true".to_string())]);
+    let on = reg.make_block_by_name("furnace", Some(&lit)).unwrap();
+    let off = reg.make_block_by_name("furnace", None).unwrap();
+    let ty = reg.get(on.id).unwrap();
+    assert!(ty.light_emission(on.state) > 0);
+    assert_eq!(ty.light_emission(off.state), 0);
+}
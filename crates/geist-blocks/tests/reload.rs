@@ -0,0 +1,108 @@
+use geist_blocks::config::BlocksConfig;
+use geist_blocks::material::MaterialCatalog;
+use geist_blocks::registry::BlockRegistry;
+use geist_blocks::reload::IncompatibleReload;
+
+const MATERIALS: &str = "[materials]\nstone = [\"stone.png\"]\nglow = [\"glow.png\"]\n";
+
+const BASE: &str = r#"[[blocks]]
+name = "air"
+solid = false
+blocks_skylight = false
+
+[[blocks]]
+name = "stone"
+materials = { all = "stone" }
+
+[[blocks]]
+name = "lamp"
+emission = 10
+materials = { all = "glow" }
+
+[[blocks]]
+name = "lever"
+state_schema = { powered = ["false", "true"] }
+materials = { all = "stone" }
+"#;
+
+fn registry(materials: &str, blocks: &str) -> BlockRegistry {
+    let materials = MaterialCatalog::from_toml_str(materials).expect("materials");
+    let cfg: BlocksConfig = toml::from_str(blocks).expect("blocks");
+    BlockRegistry::from_configs(materials, cfg).expect("registry")
+}
+
+#[test]
+fn unchanged_files_reload_to_an_empty_diff() {
+    let live = registry(MATERIALS, BASE);
+    let diff = live.diff_reload(&registry(MATERIALS, BASE)).unwrap();
+    assert!(diff.is_empty(), "{:?}", diff);
+}
+
+#[test]
+fn emission_and_shape_edits_mark_only_those_blocks() {
+    let live = registry(MATERIALS, BASE);
+    let edited = BASE.replace("emission = 10", "emission = 14").replace(
+        "name = \"stone\"\n",
+        "name = \"stone\"\nshape = \"slab\"\nstate_schema = {}\n",
+    );
+    let diff = live.diff_reload(&registry(MATERIALS, &edited)).unwrap();
+    let (stone, lamp) = (
+        live.id_by_name("stone").unwrap(),
+        live.id_by_name("lamp").unwrap(),
+    );
+    assert_eq!(diff.relit, vec![lamp]);
+    assert_eq!(diff.changed, vec![stone, lamp]);
+    assert!(diff.added.is_empty());
+    assert!(!diff.materials_changed);
+    let mask = diff.changed_mask();
+    assert!(mask[stone as usize] && mask[lamp as usize]);
+    assert!(!mask[live.id_by_name("air").unwrap() as usize]);
+}
+
+#[test]
+fn appended_blocks_and_new_materials_are_accepted() {
+    let live = registry(MATERIALS, BASE);
+    let materials = format!("{}moss = [\"moss.png\"]\n", MATERIALS);
+    let blocks = format!(
+        "{}\n[[blocks]]\nname = \"moss\"\nmaterials = {{ all = \"moss\" }}\n",
+        BASE
+    );
+    let next = registry(&materials, &blocks);
+    let diff = live.diff_reload(&next).unwrap();
+    assert_eq!(diff.added, vec![next.id_by_name("moss").unwrap()]);
+    assert!(diff.materials_changed);
+    assert!(diff.changed.is_empty());
+}
+
+#[test]
+fn id_remaps_and_state_changes_are_refused() {
+    let live = registry(MATERIALS, BASE);
+    // Dropping "stone" shifts every later id down by one.
+    let start = BASE.find("[[blocks]]\nname = \"stone\"").unwrap();
+    let end = BASE.find("[[blocks]]\nname = \"lamp\"").unwrap();
+    let removed = format!("{}{}", &BASE[..start], &BASE[end..]);
+    let problems = live
+        .diff_reload(&registry(MATERIALS, &removed))
+        .unwrap_err();
+    assert!(problems.contains(&IncompatibleReload::Renamed {
+        id: 1,
+        old: "stone".into(),
+        new: "lamp".into(),
+    }));
+    assert!(problems.contains(&IncompatibleReload::Removed {
+        id: 3,
+        name: "lever".into(),
+    }));
+
+    let restated = BASE.replace("[\"false\", \"true\"]", "[\"off\", \"on\"]");
+    let problems = live
+        .diff_reload(&registry(MATERIALS, &restated))
+        .unwrap_err();
+    assert_eq!(
+        problems,
+        vec![IncompatibleReload::StateLayout {
+            id: 3,
+            name: "lever".into(),
+        }]
+    );
+}
@@ -0,0 +1,165 @@
+//! Content hashes of generated chunks, for catching worldgen determinism regressions.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use geist_blocks::BlockRegistry;
+use geist_blocks::stable_hash::Fnv1a;
+use geist_world::{ChunkCoord, World};
+
+use crate::{ChunkBuf, generate_chunk_buffer_with_ctx};
+
+impl ChunkBuf {
+    /// Stable 64-bit FNV-1a hash of the chunk coordinate, dimensions and every block's id
+    /// and state. Identical across runs, threads and platforms for identical contents.
+    pub fn content_hash(&self) -> u64 {
+        let mut hash = Fnv1a::new();
+        for v in [self.coord.cx, self.coord.cy, self.coord.cz] {
+            hash.write(&v.to_le_bytes());
+        }
+        for v in [self.sx, self.sy, self.sz] {
+            hash.write(&(v as u32).to_le_bytes());
+        }
+        for b in &self.blocks {
+            hash.write(&b.id.to_le_bytes());
+            hash.write(&b.state.to_le_bytes());
+        }
+        hash.finish()
+    }
+}
+
+/// A chunk whose hash differs between two manifests; `None` where one side lacks it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HashMismatch {
+    pub coord: ChunkCoord,
+    pub expected: Option<u64>,
+    pub actual: Option<u64>,
+}
+
+/// Content hashes for a set of chunks, ordered by `(cx, cy, cz)`.
+///
+/// The text form has one `cx cy cz hash` line per chunk with the hash in 16 hex digits;
+/// blank lines and lines starting with `#` are ignored.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChunkHashManifest {
+    hashes: BTreeMap<(i32, i32, i32), u64>,
+}
+
+impl ChunkHashManifest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Generate every chunk in `coords` and record its hash. Work is split across
+    /// `threads` workers (at least one), each with its own generation context.
+    pub fn generate(
+        world: &World,
+        reg: &BlockRegistry,
+        coords: &[ChunkCoord],
+        threads: usize,
+    ) -> Self {
+        let threads = threads.clamp(1, coords.len().max(1));
+        let per_thread = coords.len().div_ceil(threads).max(1);
+        let mut manifest = Self::new();
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = coords
+                .chunks(per_thread)
+                .map(|batch| {
+                    scope.spawn(move || {
+                        let mut ctx = world.make_gen_ctx();
+                        batch
+                            .iter()
+                            .map(|&coord| {
+                                let result =
+                                    generate_chunk_buffer_with_ctx(world, coord, reg, &mut ctx);
+                                (coord, result.buf.content_hash())
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            for worker in workers {
+                for (coord, hash) in worker.join().expect("chunk hash worker panicked") {
+                    manifest.insert(coord, hash);
+                }
+            }
+        });
+        manifest
+    }
+
+    pub fn insert(&mut self, coord: ChunkCoord, hash: u64) {
+        self.hashes.insert((coord.cx, coord.cy, coord.cz), hash);
+    }
+
+    pub fn get(&self, coord: ChunkCoord) -> Option<u64> {
+        self.hashes.get(&(coord.cx, coord.cy, coord.cz)).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ChunkCoord, u64)> + '_ {
+        self.hashes
+            .iter()
+            .map(|(&(cx, cy, cz), &hash)| (ChunkCoord::new(cx, cy, cz), hash))
+    }
+
+    /// Chunks whose hash differs from `expected`, or that only one side contains.
+    pub fn mismatches(&self, expected: &ChunkHashManifest) -> Vec<HashMismatch> {
+        let mut keys: Vec<_> = self.hashes.keys().chain(expected.hashes.keys()).collect();
+        keys.sort_unstable();
+        keys.dedup();
+        keys.into_iter()
+            .filter_map(|key| {
+                let (expected, actual) = (
+                    expected.hashes.get(key).copied(),
+                    self.hashes.get(key).copied(),
+                );
+                (expected != actual).then(|| HashMismatch {
+                    coord: ChunkCoord::new(key.0, key.1, key.2),
+                    expected,
+                    actual,
+                })
+            })
+            .collect()
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::from("# cx cy cz hash\n");
+        for ((cx, cy, cz), hash) in &self.hashes {
+            let _ = writeln!(out, "{cx} {cy} {cz} {hash:016x}");
+        }
+        out
+    }
+
+    pub fn from_text(src: &str) -> Result<Self, String> {
+        let mut manifest = Self::new();
+        for (idx, line) in src.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [cx, cy, cz, hash] = fields[..] else {
+                return Err(format!(
+                    "line {}: expected 'cx cy cz hash', got '{}'",
+                    idx + 1,
+                    line
+                ));
+            };
+            let coord = |v: &str| {
+                v.parse::<i32>()
+                    .map_err(|e| format!("line {}: invalid coordinate '{}': {}", idx + 1, v, e))
+            };
+            let hash = u64::from_str_radix(hash, 16)
+                .map_err(|e| format!("line {}: invalid hash '{}': {}", idx + 1, hash, e))?;
+            manifest.insert(ChunkCoord::new(coord(cx)?, coord(cy)?, coord(cz)?), hash);
+        }
+        Ok(manifest)
+    }
+}
@@ -1,19 +1,23 @@
 //! Chunk buffer and world generation helpers.
 #![forbid(unsafe_code)]
 
+mod hash;
+
+pub use hash::{ChunkHashManifest, HashMismatch};
+
 use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use geist_blocks::BlockRegistry;
-use geist_blocks::types::Block;
+use geist_blocks::types::{Block, BlockId};
 use geist_world::{
-    ChunkCoord, ChunkTiming, GenCtx, HeightTileStats, TerrainMetrics, TerrainStage,
+    ChunkCoord, ChunkTiming, GenCtx, HeightTileStats, StageColumn, TerrainMetrics, TerrainStage,
     TerrainTileCacheStats, World,
     voxel::generation::{
         BlockLookup, ChunkColumnPlan, ChunkColumnProfile, ColumnMaterials, ColumnSampler,
         TOWER_OUTER_RADIUS, TowerMaterial, TreePlan, apply_caves_and_features_blocks,
-        build_chunk_column_plan, tower_material,
+        build_chunk_column_plan, plan_features, plan_prefabs, sample_carve_column, tower_material,
     },
 };
 
@@ -112,18 +116,76 @@ impl ChunkOccupancy {
     }
 }
 
+/// Per-block-id voxel counts for a chunk, sorted by id. Block state is ignored.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChunkBlockStats {
+    counts: Vec<(BlockId, u32)>,
+}
+
+impl ChunkBlockStats {
+    pub fn from_blocks(blocks: &[Block]) -> Self {
+        let mut dense: Vec<u32> = Vec::new();
+        for block in blocks {
+            let id = block.id as usize;
+            if id >= dense.len() {
+                dense.resize(id + 1, 0);
+            }
+            dense[id] += 1;
+        }
+        let counts = dense
+            .into_iter()
+            .enumerate()
+            .filter(|(_, n)| *n > 0)
+            .map(|(id, n)| (id as BlockId, n))
+            .collect();
+        Self { counts }
+    }
+
+    #[inline]
+    pub fn count(&self, id: BlockId) -> u32 {
+        self.counts
+            .binary_search_by_key(&id, |(bid, _)| *bid)
+            .map(|i| self.counts[i].1)
+            .unwrap_or(0)
+    }
+
+    /// `(id, count)` pairs in ascending id order; ids with zero voxels are omitted.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (BlockId, u32)> + '_ {
+        self.counts.iter().copied()
+    }
+
+    #[inline]
+    pub fn distinct(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().map(|(_, n)| u64::from(*n)).sum()
+    }
+
+    #[inline]
+    pub fn non_air(&self) -> u64 {
+        self.total() - u64::from(self.count(Block::AIR.id))
+    }
+}
+
 #[derive(Debug)]
 pub struct ChunkGenerateResult {
     pub buf: ChunkBuf,
     pub occupancy: ChunkOccupancy,
     pub terrain_metrics: TerrainMetrics,
     pub column_profile: Option<ChunkColumnProfile>,
+    pub block_stats: ChunkBlockStats,
+    /// Worldgen revision the blocks were generated under; see `WorldgenRevTracker`.
+    pub worldgen_rev: u32,
 }
 
 struct MaterializeOutput {
     blocks: Vec<Block>,
     occupancy: ChunkOccupancy,
     metrics: TerrainMetrics,
+    block_stats: ChunkBlockStats,
 }
 
 fn collect_tree_plans(
@@ -200,21 +262,21 @@ fn materialize_chunk(
     let fill_start = Instant::now();
 
     let materials: &ColumnMaterials = &plan.materials;
-    let topsoil = materials.topsoil_thickness.max(0) as i32;
     for lz in 0..sz {
         let column = |lx: usize| plan.column(lx, lz);
         for lx in 0..sx {
             let column = column(lx);
+            let soil = plan.column_materials(column);
             let height = column.height;
             let surface_y = height - 1;
-            let soil_start = height - topsoil;
+            let soil_start = height - soil.topsoil_thickness.max(0);
 
             let deep_end = soil_start.min(surface_y + 1).min(chunk_max_y);
             if deep_end > chunk_min_y {
                 for wy in chunk_min_y..deep_end {
                     let ly = (wy - chunk_min_y) as usize;
                     let idx = (ly * sz + lz) * sx + lx;
-                    blocks[idx] = materials.sub_deep_block;
+                    blocks[idx] = soil.sub_deep_block;
                 }
             }
 
@@ -224,7 +286,7 @@ fn materialize_chunk(
                 for wy in near_start..near_end {
                     let ly = (wy - chunk_min_y) as usize;
                     let idx = (ly * sz + lz) * sx + lx;
-                    blocks[idx] = materials.sub_near_block;
+                    blocks[idx] = soil.sub_near_block;
                 }
             }
 
@@ -264,6 +326,15 @@ fn materialize_chunk(
                 if carve_top <= chunk_min_y {
                     continue;
                 }
+                let carve = sample_carve_column(
+                    &mut sampler,
+                    world,
+                    wx,
+                    wz,
+                    chunk_min_y,
+                    carve_top,
+                    height,
+                );
                 for wy in chunk_min_y..carve_top {
                     let ly = (wy - chunk_min_y) as usize;
                     let idx = (ly * sz + lz) * sx + lx;
@@ -276,6 +347,7 @@ fn materialize_chunk(
                         &mut sampler,
                         reg,
                         &mut block_lookup,
+                        Some(&carve),
                         wx,
                         wy,
                         wz,
@@ -288,6 +360,32 @@ fn materialize_chunk(
         }
     }
 
+    let features = if world.is_flat() {
+        Default::default()
+    } else {
+        plan_features(
+            world.seed,
+            &ctx.params.features,
+            (base_x, chunk_min_y, base_z),
+            (base_x + sx as i32, chunk_max_y, base_z + sz as i32),
+        )
+    };
+    if !features.veins.is_empty() {
+        ctx.terrain_profiler.begin_stage(TerrainStage::Caves);
+        let vein_start = Instant::now();
+        for v in &features.veins {
+            let rule = &ctx.params.features[v.rule];
+            let (lx, lz) = ((v.wx - base_x) as usize, (v.wz - base_z) as usize);
+            let idx = (((v.wy - chunk_min_y) as usize * sz) + lz) * sx + lx;
+            let name = reg.get(blocks[idx].id).map_or("air", |ty| ty.name.as_str());
+            if rule.vein_accepts(name, v.wy, plan.column(lx, lz).height) {
+                blocks[idx] = block_lookup.resolve(world, reg, &rule.place.block);
+            }
+        }
+        ctx.terrain_profiler
+            .record_stage_duration(TerrainStage::Caves, vein_start.elapsed());
+    }
+
     for tree in tree_plans {
         let trunk_x = tree.base_x - base_x;
         let trunk_z = tree.base_z - base_z;
@@ -356,9 +454,72 @@ fn materialize_chunk(
         }
     }
 
+    if !features.scatter.is_empty() {
+        ctx.terrain_profiler.begin_stage(TerrainStage::Trees);
+        let scatter_start = Instant::now();
+        for v in &features.scatter {
+            let (lx, lz) = ((v.wx - base_x) as usize, (v.wz - base_z) as usize);
+            let column = plan.column(lx, lz);
+            let wy = column.height;
+            if wy < chunk_min_y || wy >= chunk_max_y {
+                continue;
+            }
+            let idx = (((wy - chunk_min_y) as usize * sz) + lz) * sx + lx;
+            let rule = &ctx.params.features[v.rule];
+            let surface = reg
+                .get(column.surface_block.id)
+                .map_or("air", |ty| ty.name.as_str());
+            if blocks[idx] == materials.air_block
+                && rule.scatter_accepts(surface, column.height, column.water_level)
+            {
+                blocks[idx] = block_lookup.resolve(world, reg, &rule.place.block);
+            }
+        }
+        ctx.terrain_profiler
+            .record_stage_duration(TerrainStage::Trees, scatter_start.elapsed());
+    }
+
+    let prefabs = if world.is_flat() {
+        Vec::new()
+    } else {
+        let params_guard = Arc::clone(&ctx.params);
+        let mut sampler = ColumnSampler::new(world, ctx, &params_guard);
+        plan_prefabs(
+            world,
+            &mut sampler,
+            (base_x, chunk_min_y, base_z),
+            (base_x + sx as i32, chunk_max_y, base_z + sz as i32),
+        )
+    };
+    if !prefabs.is_empty() {
+        ctx.terrain_profiler.begin_stage(TerrainStage::Trees);
+        let prefab_start = Instant::now();
+        for p in &prefabs {
+            let palette = p.resolve_palette(world, reg);
+            let (ox, oy, oz) = p.origin;
+            let (w, h, d) = p.size();
+            let (x0, x1) = (ox.max(base_x), (ox + w).min(base_x + sx as i32));
+            let (y0, y1) = (oy.max(chunk_min_y), (oy + h).min(chunk_max_y));
+            let (z0, z1) = (oz.max(base_z), (oz + d).min(base_z + sz as i32));
+            for wy in y0..y1 {
+                let ly = (wy - chunk_min_y) as usize;
+                for wz in z0..z1 {
+                    let lz = (wz - base_z) as usize;
+                    for wx in x0..x1 {
+                        if let Some(i) = p.palette_index(wx, wy, wz) {
+                            let lx = (wx - base_x) as usize;
+                            blocks[(ly * sz + lz) * sx + lx] = palette[i];
+                        }
+                    }
+                }
+            }
+        }
+        ctx.terrain_profiler
+            .record_stage_duration(TerrainStage::Trees, prefab_start.elapsed());
+    }
+
     {
-        let tower_center_x = (world.world_size_x() as i32) / 2;
-        let tower_center_z = (world.world_size_z() as i32) / 2;
+        let (tower_center_x, tower_center_z) = world.center();
         let chunk_min_x = base_x;
         let chunk_max_x = base_x + sx as i32;
         let chunk_min_z = base_z;
@@ -423,8 +584,36 @@ fn materialize_chunk(
         }
     }
 
+    if !world.stage_plugins().is_empty() && !world.is_flat() {
+        for lz in 0..sz {
+            let wz = base_z + lz as i32;
+            for lx in 0..sx {
+                let wx = base_x + lx as i32;
+                let column = plan.column(lx, lz);
+                let stage_column = StageColumn {
+                    height: column.height,
+                    water_level: column.water_level,
+                };
+                for wy in chunk_min_y.max(0)..chunk_max_y {
+                    let ly = (wy - chunk_min_y) as usize;
+                    let idx = (ly * sz + lz) * sx + lx;
+                    blocks[idx] = world.apply_stage_plugins(
+                        reg,
+                        &mut ctx.terrain_profiler,
+                        wx,
+                        wy,
+                        wz,
+                        stage_column,
+                        blocks[idx],
+                    );
+                }
+            }
+        }
+    }
+
     let voxel_fill_us = duration_to_us(fill_start.elapsed());
-    let has_blocks = blocks.iter().any(|b| *b != Block::AIR);
+    let block_stats = ChunkBlockStats::from_blocks(&blocks);
+    let has_blocks = block_stats.non_air() > 0;
 
     let mut chunk_timing = ChunkTiming {
         total_us: duration_to_us(total_start.elapsed()),
@@ -452,6 +641,7 @@ fn materialize_chunk(
             ChunkOccupancy::Empty
         },
         metrics,
+        block_stats,
     }
 }
 
@@ -489,6 +679,10 @@ pub fn generate_chunk_buffer_with_ctx(
     world.prepare_height_tile(ctx, base_x, base_z, sx, sz);
     let height_tile_us = duration_to_us(tile_start.elapsed());
 
+    if world.generator().is_some() {
+        return generate_custom_chunk(world, coord, reg, ctx, height_tile_us, total_start);
+    }
+
     let column_plan = build_chunk_column_plan(world, ctx, reg, base_x, base_z, sx, sz);
     let tree_plans = collect_tree_plans(world, ctx, reg, &column_plan);
     let materialized = materialize_chunk(
@@ -504,14 +698,76 @@ pub fn generate_chunk_buffer_with_ctx(
     );
 
     let buf = ChunkBuf::from_blocks_local(coord, sx, sy, sz, materialized.blocks);
-    let profile =
-        ChunkColumnProfile::new(coord, world.current_worldgen_rev(), column_plan, tree_plans);
+    let profile = ChunkColumnProfile::new(coord, ctx.worldgen_rev, column_plan, tree_plans);
 
     ChunkGenerateResult {
         buf,
         occupancy: materialized.occupancy,
         terrain_metrics: materialized.metrics,
         column_profile: Some(profile),
+        block_stats: materialized.block_stats,
+        worldgen_rev: ctx.worldgen_rev,
+    }
+}
+
+// Chunks of a world with a custom `TerrainGenerator`: no column plan or tree pass, every
+// column comes from the generator.
+fn generate_custom_chunk(
+    world: &World,
+    coord: ChunkCoord,
+    reg: &BlockRegistry,
+    ctx: &mut GenCtx,
+    height_tile_us: u32,
+    total_start: Instant,
+) -> ChunkGenerateResult {
+    let sx = world.chunk_size_x;
+    let sy = world.chunk_size_y;
+    let sz = world.chunk_size_z;
+    let base_x = coord.cx * sx as i32;
+    let base_y = coord.cy * sy as i32;
+    let base_z = coord.cz * sz as i32;
+    let fill_start = Instant::now();
+    let mut blocks = vec![Block { id: 0, state: 0 }; sx * sy * sz];
+    let mut column = vec![Block { id: 0, state: 0 }; sy];
+    for lz in 0..sz {
+        for lx in 0..sx {
+            let wx = base_x + lx as i32;
+            let wz = base_z + lz as i32;
+            world.custom_column(reg, ctx, wx, wz, base_y, &mut column);
+            for (ly, block) in column.iter().enumerate() {
+                blocks[(ly * sz + lz) * sx + lx] = *block;
+            }
+        }
+    }
+    let voxel_fill_us = duration_to_us(fill_start.elapsed());
+    let block_stats = ChunkBlockStats::from_blocks(&blocks);
+    let occupancy = if block_stats.non_air() > 0 {
+        ChunkOccupancy::Populated
+    } else {
+        ChunkOccupancy::Empty
+    };
+
+    let mut metrics = ctx
+        .terrain_profiler
+        .snapshot(ctx.height_tile_stats, ctx.tile_cache_stats);
+    ctx.height_tile_stats = HeightTileStats::default();
+    ctx.tile_cache_stats = TerrainTileCacheStats::default();
+    metrics.chunk_timing = ChunkTiming {
+        total_us: duration_to_us(total_start.elapsed()),
+        height_tile_us,
+        voxel_fill_us,
+        feature_us: metrics.stages[TerrainStage::Caves as usize]
+            .time_us
+            .saturating_add(metrics.stages[TerrainStage::Trees as usize].time_us),
+    };
+
+    ChunkGenerateResult {
+        buf: ChunkBuf::from_blocks_local(coord, sx, sy, sz, blocks),
+        occupancy,
+        terrain_metrics: metrics,
+        column_profile: None,
+        block_stats,
+        worldgen_rev: ctx.worldgen_rev,
     }
 }
 
@@ -557,5 +813,8 @@ pub fn generate_chunk_buffer_from_profile(
         occupancy: materialized.occupancy,
         terrain_metrics: materialized.metrics,
         column_profile: None,
+        block_stats: materialized.block_stats,
+        // Only as fresh as the older of the reused columns and the params filling them.
+        worldgen_rev: profile.worldgen_rev.min(ctx.worldgen_rev),
     }
 }
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use geist_blocks::BlockRegistry;
+use geist_chunk::generate_chunk_buffer;
+use geist_world::voxel::generation::ChunkColumnProfile;
+use geist_world::worldgen::{BiomeDefParam, BiomesParams, WorldGenParams};
+use geist_world::{ChunkCoord, World, WorldGenMode};
+
+fn load_registry() -> BlockRegistry {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let vox = root.join("../../assets/voxels");
+    BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml")).unwrap()
+}
+
+// A world where every column is one biome with a gravel topsoil, and nothing carves or
+// decorates the terrain.
+fn gravel_biome_world() -> World {
+    let world = World::new(1, 4, 1, 21, WorldGenMode::Normal);
+    let mut params = WorldGenParams::default();
+    params.carvers_enable = false;
+    params.features = Arc::from(Vec::new());
+    params.water_enable = false;
+    params.biomes = Some(Arc::new(BiomesParams {
+        temp_freq: 0.01,
+        moisture_freq: 0.01,
+        defs: vec![BiomeDefParam {
+            name: "gravel_flats".into(),
+            temp_min: 0.0,
+            temp_max: 1.0,
+            moisture_min: 0.0,
+            moisture_max: 1.0,
+            top_block: None,
+            sub_near: Some("gravel".into()),
+            sub_deep: None,
+            topsoil_thickness: Some(5),
+            species_weights: HashMap::new(),
+            tree_density: Some(0.0),
+            leaf_tint: None,
+        }],
+        scale_x: 1.0,
+        scale_z: 1.0,
+        debug_pack_all: true,
+        debug_cell_size: 16,
+    }));
+    world.update_worldgen_params(params);
+    world
+}
+
+#[test]
+fn biome_subsoil_is_used_by_chunks_and_point_queries() {
+    let reg = load_registry();
+    let world = gravel_biome_world();
+    assert_eq!(
+        world.biome_at(5, -40).map(|b| b.name),
+        Some("gravel_flats".to_string())
+    );
+
+    let name = |b: geist_blocks::Block| reg.get(b.id).map_or("air", |ty| ty.name.as_str());
+    let (sx, sy, sz) = (world.chunk_size_x, world.chunk_size_y, world.chunk_size_z);
+    let mut columns = 0;
+    let mut profile = None;
+    for cy in 0..4 {
+        let result = generate_chunk_buffer(&world, ChunkCoord::new(0, cy, 0), &reg);
+        profile = profile.or(result.column_profile);
+        let buf = result.buf;
+        for z in 0..sz {
+            for x in 0..sx {
+                for y in 0..sy {
+                    let wy = cy * sy as i32 + y as i32;
+                    let b = buf.get_local(x, y, z);
+                    assert_eq!(
+                        b,
+                        world.block_at_runtime(&reg, x as i32, wy, z as i32),
+                        "chunk and point query differ at ({x}, {wy}, {z})"
+                    );
+                    // The four blocks under the surface are the biome's topsoil.
+                    let above = |d: usize| (y + d < sy).then(|| name(buf.get_local(x, y + d, z)));
+                    if name(b) == "gravel" && above(4).is_some_and(|n| n != "air" && n != "gravel")
+                    {
+                        columns += 1;
+                    }
+                    assert_ne!(name(b), "dirt", "global subsoil at ({x}, {wy}, {z})");
+                }
+            }
+        }
+    }
+    assert!(columns > 0, "no gravel topsoil found");
+
+    let profile = profile.expect("normal worlds build a column profile");
+    assert_eq!(profile.plan.biome_materials.len(), 1);
+    assert!(profile.plan.columns.iter().all(|c| c.materials == Some(0)));
+    let soil = profile.plan.column_materials(&profile.plan.columns[0]);
+    assert_eq!(soil.topsoil_thickness, 5);
+    assert_eq!(name(soil.sub_near_block), "gravel");
+    assert_eq!(name(soil.sub_deep_block), "stone");
+
+    let decoded = ChunkColumnProfile::from_bytes(&profile.to_bytes()).unwrap();
+    assert_eq!(decoded.plan.biome_materials.len(), 1);
+    assert_eq!(
+        decoded
+            .plan
+            .columns
+            .iter()
+            .map(|c| c.materials)
+            .collect::<Vec<_>>(),
+        profile
+            .plan
+            .columns
+            .iter()
+            .map(|c| c.materials)
+            .collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,143 @@
+use std::path::PathBuf;
+
+use geist_blocks::BlockRegistry;
+use geist_blocks::types::Block;
+use geist_chunk::{
+    ChunkHashManifest, HashMismatch, generate_chunk_buffer, generate_chunk_buffer_from_profile,
+};
+use geist_world::worldgen::{WorldGenParams, load_params_from_path};
+use geist_world::{ChunkCoord, World, WorldGenMode};
+
+fn load_registry() -> BlockRegistry {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let vox = root.join("../../assets/voxels");
+    BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml")).unwrap()
+}
+
+fn region(cols: i32, cy: std::ops::Range<i32>) -> Vec<ChunkCoord> {
+    let mut coords = Vec::new();
+    for cx in -1..cols - 1 {
+        for cz in 0..cols {
+            for cy in cy.clone() {
+                coords.push(ChunkCoord::new(cx, cy, cz));
+            }
+        }
+    }
+    coords
+}
+
+#[test]
+fn hash_tracks_blocks_state_and_coord() {
+    let reg = load_registry();
+    let world = World::new(1, 1, 1, 7, WorldGenMode::Normal);
+    let buf = generate_chunk_buffer(&world, ChunkCoord::new(0, 0, 0), &reg).buf;
+    let hash = buf.content_hash();
+    assert_eq!(buf.clone().content_hash(), hash);
+
+    let mut edited = buf.clone();
+    let i = edited.idx(3, 5, 7);
+    edited.blocks[i] = Block {
+        id: edited.blocks[i].id,
+        state: edited.blocks[i].state ^ 1,
+    };
+    assert_ne!(edited.content_hash(), hash, "state is part of the hash");
+
+    let mut moved = buf.clone();
+    moved.coord = ChunkCoord::new(1, 0, 0);
+    assert_ne!(moved.content_hash(), hash, "coord is part of the hash");
+}
+
+#[test]
+fn manifest_is_identical_across_threads_profiles_and_param_reloads() {
+    let reg = load_registry();
+    let world = World::new(2, 2, 2, 2024, WorldGenMode::Normal);
+    // Two chunks keep this cheap in debug builds; the golden test covers a wider region.
+    let coords = [ChunkCoord::new(0, 0, 0), ChunkCoord::new(-1, 0, 1)];
+    let sequential = ChunkHashManifest::generate(&world, &reg, &coords, 1);
+    assert_eq!(sequential.len(), coords.len());
+
+    // A fresh world generated from several threads must agree with the first pass.
+    let fresh = World::new(2, 2, 2, 2024, WorldGenMode::Normal);
+    let threaded = ChunkHashManifest::generate(&fresh, &reg, &coords, 4);
+    assert_eq!(threaded.mismatches(&sequential), Vec::new());
+
+    // Rebuilding an upper chunk from a column profile matches full generation.
+    let mut ctx = world.make_gen_ctx();
+    let base = generate_chunk_buffer(&world, ChunkCoord::new(0, 0, 1), &reg);
+    let profile = base.column_profile.expect("column profile");
+    let coord = ChunkCoord::new(0, 1, 1);
+    let reused = generate_chunk_buffer_from_profile(&world, coord, &reg, &mut ctx, &profile);
+    let full = generate_chunk_buffer(&world, coord, &reg);
+    assert_eq!(reused.buf.content_hash(), full.buf.content_hash());
+
+    // Reloading the same parameters keeps every hash; changing them does not.
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let config = root.join("../../assets/worldgen/worldgen.toml");
+    let loaded = load_params_from_path(&config).unwrap();
+    world.update_worldgen_params(loaded.clone());
+    let before = ChunkHashManifest::generate(&world, &reg, &coords, 2);
+    world.update_worldgen_params(loaded.clone());
+    let after = ChunkHashManifest::generate(&world, &reg, &coords, 3);
+    assert_eq!(after, before);
+
+    let mut raised = loaded;
+    raised.min_y_ratio += 0.05;
+    world.update_worldgen_params(raised);
+    let changed = ChunkHashManifest::generate(&world, &reg, &coords, 2);
+    assert!(!changed.mismatches(&before).is_empty());
+}
+
+#[test]
+fn manifest_text_round_trips_and_reports_differences() {
+    let mut a = ChunkHashManifest::new();
+    a.insert(ChunkCoord::new(-1, 0, 2), 0xdead_beef);
+    a.insert(ChunkCoord::new(0, 1, 0), u64::MAX);
+    let text = a.to_text();
+    assert_eq!(ChunkHashManifest::from_text(&text).unwrap(), a);
+    assert!(ChunkHashManifest::from_text("1 2 3").is_err());
+    assert!(ChunkHashManifest::from_text("1 2 x 00").is_err());
+
+    let mut b = a.clone();
+    b.insert(ChunkCoord::new(0, 1, 0), 1);
+    b.insert(ChunkCoord::new(5, 0, 5), 2);
+    assert_eq!(
+        b.mismatches(&a),
+        vec![
+            HashMismatch {
+                coord: ChunkCoord::new(0, 1, 0),
+                expected: Some(u64::MAX),
+                actual: Some(1),
+            },
+            HashMismatch {
+                coord: ChunkCoord::new(5, 0, 5),
+                expected: None,
+                actual: Some(2),
+            },
+        ]
+    );
+}
+
+/// Golden hashes for default-parameter worldgen. After an intentional worldgen change,
+/// regenerate with `GEIST_BLESS=1 cargo test -p geist-chunk --test content_hash`.
+#[test]
+fn default_worldgen_matches_golden_manifest() {
+    let reg = load_registry();
+    let world = World::new(2, 2, 2, 1337, WorldGenMode::Normal);
+    world.update_worldgen_params(WorldGenParams::default());
+    let manifest = ChunkHashManifest::generate(&world, &reg, &region(2, 0..2), 2);
+
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden/chunk_hashes.txt");
+    if std::env::var_os("GEIST_BLESS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, manifest.to_text()).unwrap();
+        return;
+    }
+    let golden = ChunkHashManifest::from_text(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let mismatches = manifest.mismatches(&golden);
+    assert!(
+        mismatches.is_empty(),
+        "worldgen output changed for {} chunks (rerun with GEIST_BLESS=1 if intended): {:?}",
+        mismatches.len(),
+        mismatches
+    );
+}
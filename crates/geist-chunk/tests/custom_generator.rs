@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use geist_blocks::BlockRegistry;
+use geist_chunk::generate_chunk_buffer;
+use geist_world::{ChunkCoord, TerrainGenerator, TerrainStage, World};
+
+fn load_registry() -> BlockRegistry {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let vox = root.join("../../assets/voxels");
+    BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml")).unwrap()
+}
+
+// Terraces rising along x, a shaft carved at x == z, glowstone on every 16th column top.
+struct Terraces;
+
+impl TerrainGenerator for Terraces {
+    fn height(&self, wx: i32, _wz: i32) -> i32 {
+        8 + wx.rem_euclid(32) / 4
+    }
+
+    fn surface_material(&self, _wx: i32, wy: i32, _wz: i32, height: i32) -> &str {
+        if wy >= height {
+            "air"
+        } else if wy == height - 1 {
+            "grass"
+        } else {
+            "stone"
+        }
+    }
+
+    fn carve(&self, wx: i32, wy: i32, wz: i32, _height: i32) -> bool {
+        wx == wz && wy > 2
+    }
+
+    fn feature(&self, wx: i32, wy: i32, _wz: i32, height: i32, base: &str) -> Option<&str> {
+        (wx % 16 == 0 && wy == height && base == "air").then_some("glowstone")
+    }
+}
+
+#[test]
+fn custom_generator_fills_chunks_and_matches_block_sampling() {
+    let reg = load_registry();
+    let world = World::with_generator(2, 2, 2, 7, Arc::new(Terraces));
+    let coord = ChunkCoord::new(0, 0, 0);
+    let result = generate_chunk_buffer(&world, coord, &reg);
+    let buf = &result.buf;
+    let name = |x: usize, y: usize, z: usize| {
+        let b = buf.get_local(x, y, z);
+        reg.get(b.id).map_or("air", |ty| ty.name.as_str())
+    };
+
+    assert_eq!(name(1, 0, 5), "stone");
+    assert_eq!(name(1, 7, 5), "grass");
+    assert_eq!(name(1, 8, 5), "air");
+    assert_eq!(name(5, 4, 5), "air", "carved shaft");
+    assert_eq!(name(5, 2, 5), "stone", "shaft stops above y=2");
+    assert_eq!(name(16, 12, 3), "glowstone");
+
+    for (x, y, z) in [(3, 6, 9), (16, 12, 3), (5, 4, 5), (40, 20, 1)] {
+        let sampled = world.block_at_runtime(&reg, x as i32, y as i32, z as i32);
+        assert_eq!(buf.get_local(x, y, z), sampled, "at ({x}, {y}, {z})");
+    }
+
+    let metrics = &result.terrain_metrics;
+    assert!(metrics.stages[TerrainStage::Surface as usize].calls > 0);
+    assert!(result.column_profile.is_none());
+}
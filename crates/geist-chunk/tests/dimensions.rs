@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use geist_blocks::BlockRegistry;
+use geist_chunk::generate_chunk_buffer;
+use geist_world::{ChunkCoord, DimensionId, DimensionKind, DimensionSet, World, WorldGenMode};
+
+fn load_registry() -> BlockRegistry {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let vox = root.join("../../assets/voxels");
+    BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml")).unwrap()
+}
+
+fn builtin(seed: i32) -> DimensionSet {
+    DimensionSet::with_builtin(Arc::new(World::new(
+        2,
+        2,
+        2,
+        seed,
+        WorldGenMode::Flat { thickness: 2 },
+    )))
+}
+
+#[test]
+fn builtin_dimensions_generate_their_own_terrain() {
+    let reg = load_registry();
+    let dims = builtin(11);
+    assert_eq!(dims.len(), 3);
+    assert_eq!(dims.next(DimensionId(2)), DimensionId::OVERWORLD);
+    let caves = dims.by_name("caves").expect("caves");
+    let sky = dims.by_name("sky").expect("sky");
+    assert_eq!(caves.kind, DimensionKind::Caves);
+    assert!(!caves.lighting.skylight && sky.lighting.void_below);
+    assert_ne!(caves.world.seed, sky.world.seed);
+
+    let name = |b: geist_blocks::Block| reg.get(b.id).map_or("air", |ty| ty.name.as_str());
+    let count = |dim: &geist_world::Dimension, coord: ChunkCoord, block: &str| {
+        let buf = generate_chunk_buffer(&dim.world, coord, &reg).buf;
+        buf.blocks.iter().filter(|b| name(**b) == block).count()
+    };
+    // The overworld here is a flat slab; the caves are rock with open caverns, and the
+    // sky is mostly void.
+    let volume = 64 * 64 * 64;
+    let rock = count(caves, ChunkCoord::new(0, 0, 0), "stone");
+    assert!(rock > volume / 4 && rock < volume, "cave rock {rock}");
+    assert_eq!(count(caves, ChunkCoord::new(0, 0, 0), "grass"), 0);
+    let sky_air = count(sky, ChunkCoord::new(0, 0, 0), "air");
+    assert!(sky_air > volume / 2, "sky air {sky_air}");
+
+    // Same session seed, same dimensions.
+    let again = builtin(11);
+    let a = generate_chunk_buffer(&caves.world, ChunkCoord::new(1, 0, 1), &reg).buf;
+    let b = generate_chunk_buffer(
+        &again.by_name("caves").unwrap().world,
+        ChunkCoord::new(1, 0, 1),
+        &reg,
+    )
+    .buf;
+    assert_eq!(a.blocks, b.blocks);
+}
+
+#[test]
+fn spawn_search_finds_standing_room() {
+    let reg = load_registry();
+    let dims = builtin(5);
+    for dim in dims.iter() {
+        let (x, y, z) = dim.find_spawn(&reg, 64, 64, 64).expect("a spawn spot");
+        let at = |y: i32| dim.world.block_at_runtime(&reg, x, y, z);
+        assert_eq!(at(y).id, 0, "{}: feet in air", dim.name);
+        assert_eq!(at(y + 1).id, 0, "{}: head in air", dim.name);
+        assert_ne!(at(y - 1).id, 0, "{}: standing on ground", dim.name);
+    }
+}
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use geist_blocks::BlockRegistry;
+use geist_chunk::generate_chunk_buffer;
+use geist_world::worldgen::{
+    FeatureKind, FeaturePlace, FeatureRule, FeatureWhen, PrefabSpec, ScatterSpec, VeinBand,
+    VeinSpec, WorldGenParams,
+};
+use geist_world::{ChunkCoord, World, WorldGenMode};
+
+fn load_registry() -> BlockRegistry {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let vox = root.join("../../assets/voxels");
+    BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml")).unwrap()
+}
+
+fn rule(name: &str, kind: FeatureKind, base_in: &[&str], block: &str) -> FeatureRule {
+    FeatureRule {
+        name: Some(name.into()),
+        kind,
+        when: FeatureWhen {
+            base_in: base_in.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        },
+        place: FeaturePlace {
+            block: block.into(),
+        },
+        vein: VeinSpec::default(),
+        scatter: ScatterSpec::default(),
+        prefab: PrefabSpec::default(),
+    }
+}
+
+// Dense coal veins through the whole stone band and cobblestone scattered on every
+// surface, with carving, trees and water off so nothing else touches the terrain.
+fn feature_world() -> World {
+    let world = World::new(2, 4, 2, 77, WorldGenMode::Normal);
+    let mut params = WorldGenParams::default();
+    params.carvers_enable = false;
+    params.water_enable = false;
+    params.tree_probability = 0.0;
+    params.biomes = None;
+    let mut veins = rule("coal", FeatureKind::Vein, &["stone"], "coal_block");
+    veins.vein.bands.push(VeinBand {
+        y_min: 0,
+        y_max: 128,
+        count: [20, 30],
+        size: [8, 16],
+    });
+    let mut rocks = rule("rocks", FeatureKind::Scatter, &[], "cobblestone");
+    rocks.scatter = ScatterSpec {
+        patches: [4, 6],
+        patch_size: [4, 8],
+        radius: 4,
+    };
+    params.features = Arc::from(vec![veins, rocks]);
+    world.update_worldgen_params(params);
+    world
+}
+
+#[test]
+fn veins_and_scatter_match_point_queries_and_rebuilds() {
+    let reg = load_registry();
+    let world = feature_world();
+    let coal = reg.id_by_name("coal_block").unwrap();
+    let cobble = reg.id_by_name("cobblestone").unwrap();
+    let mut ctx = world.make_gen_ctx();
+    let (sx, sy, sz) = (world.chunk_size_x, world.chunk_size_y, world.chunk_size_z);
+    let (mut ore, mut scattered) = (0, 0);
+    // Two neighbouring chunks in the band that holds both stone and the surface; checking
+    // every voxel against point queries is slow in debug builds.
+    for coord in [ChunkCoord::new(0, 1, 0), ChunkCoord::new(1, 1, 0)] {
+        let buf = generate_chunk_buffer(&world, coord, &reg).buf;
+        assert_eq!(
+            buf.blocks,
+            generate_chunk_buffer(&world, coord, &reg).buf.blocks,
+            "chunk {coord:?} differs between builds"
+        );
+        for y in 0..sy {
+            for z in 0..sz {
+                for x in 0..sx {
+                    let b = buf.get_local(x, y, z);
+                    let (wx, wy, wz) = (
+                        coord.cx * sx as i32 + x as i32,
+                        coord.cy * sy as i32 + y as i32,
+                        coord.cz * sz as i32 + z as i32,
+                    );
+                    assert_eq!(
+                        b,
+                        world.block_at_runtime_with(&reg, &mut ctx, wx, wy, wz),
+                        "chunk and point query differ at ({wx}, {wy}, {wz})"
+                    );
+                    ore += (b.id == coal) as usize;
+                    scattered += (b.id == cobble) as usize;
+                }
+            }
+        }
+    }
+    assert!(ore > 0, "no vein blocks placed");
+    assert!(scattered > 0, "no scatter blocks placed");
+}
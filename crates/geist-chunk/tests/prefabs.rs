@@ -0,0 +1,132 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use geist_blocks::BlockRegistry;
+use geist_chunk::generate_chunk_buffer;
+use geist_world::worldgen::{
+    FeatureKind, FeatureRule, FeatureWhen, PrefabSpec, ScatterSpec, VeinSpec, WorldGenParams,
+    load_params_from_path,
+};
+use geist_world::{ChunkCoord, Prefab, PrefabRegistry, World, WorldGenMode};
+
+fn load_registry() -> BlockRegistry {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let vox = root.join("../../assets/voxels");
+    BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml")).unwrap()
+}
+
+// An 80-block lapis wall with a row of north-facing stairs in front, longer than a
+// chunk so every placement straddles a chunk edge.
+fn long_wall() -> Prefab {
+    let len = 80;
+    let wall = "#".repeat(len);
+    let steps = "s".repeat(len);
+    let open = " ".repeat(len);
+    let layer = |rows: [&str; 3]| format!("[{}]", rows.map(|r| format!("\"{r}\"")).join(", "));
+    let src = format!(
+        "sink = 1\nlayers = [\n{},\n{},\n{},\n]\n[legend]\n\"#\" = \"lapis_block\"\n\"s\" = \"stairs[material=cobblestone,facing=north]\"\n",
+        layer([&wall, &wall, &wall]),
+        layer([&wall, &steps, &open]),
+        layer([&wall, &open, &open]),
+    );
+    Prefab::from_toml_str("long_wall", &src).unwrap()
+}
+
+fn prefab_world() -> World {
+    let world = World::new(2, 4, 2, 913, WorldGenMode::Normal);
+    let mut params = WorldGenParams::default();
+    params.carvers_enable = false;
+    params.water_enable = false;
+    params.tree_probability = 0.0;
+    params.biomes = None;
+    let mut prefabs = PrefabRegistry::default();
+    prefabs.insert(long_wall());
+    params.prefabs = Arc::new(prefabs);
+    params.features = Arc::from(vec![FeatureRule {
+        name: Some("walls".into()),
+        kind: FeatureKind::Prefab,
+        when: FeatureWhen::default(),
+        place: Default::default(),
+        vein: VeinSpec::default(),
+        scatter: ScatterSpec::default(),
+        prefab: PrefabSpec {
+            names: vec!["long_wall".into()],
+            spacing: 96,
+            ..Default::default()
+        },
+    }]);
+    world.update_worldgen_params(params);
+    world
+}
+
+#[test]
+fn prefabs_span_chunks_and_match_point_queries() {
+    let reg = load_registry();
+    let world = prefab_world();
+    let lapis = reg.id_by_name("lapis_block").unwrap();
+    let stairs = reg.id_by_name("stairs").unwrap();
+    let mut ctx = world.make_gen_ctx();
+    let (sx, sy, sz) = (world.chunk_size_x, world.chunk_size_y, world.chunk_size_z);
+    let mut columns_with_wall = HashSet::new();
+    let mut steps = 0;
+    for (cx, cz) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+        for cy in 0..4 {
+            let coord = ChunkCoord::new(cx, cy, cz);
+            let buf = generate_chunk_buffer(&world, coord, &reg).buf;
+            for y in 0..sy {
+                for z in 0..sz {
+                    for x in 0..sx {
+                        let b = buf.get_local(x, y, z);
+                        let (wx, wy, wz) = (
+                            cx * sx as i32 + x as i32,
+                            cy * sy as i32 + y as i32,
+                            cz * sz as i32 + z as i32,
+                        );
+                        assert_eq!(
+                            b,
+                            world.block_at_runtime_with(&reg, &mut ctx, wx, wy, wz),
+                            "chunk and point query differ at ({wx}, {wy}, {wz})"
+                        );
+                        if b.id == lapis {
+                            columns_with_wall.insert((cx, cz));
+                        }
+                        steps += (b.id == stairs) as usize;
+                    }
+                }
+            }
+        }
+    }
+    assert!(
+        columns_with_wall.len() >= 2,
+        "prefab did not cross a chunk edge: {columns_with_wall:?}"
+    );
+    assert!(steps > 0, "no prefab stairs placed");
+}
+
+#[test]
+fn bundled_prefab_rules_resolve() {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let params = load_params_from_path(&root.join("../../assets/worldgen/worldgen.toml")).unwrap();
+    let reg = load_registry();
+    let rules: Vec<_> = params
+        .features
+        .iter()
+        .filter(|r| r.kind == FeatureKind::Prefab)
+        .collect();
+    assert!(!rules.is_empty());
+    for rule in rules {
+        for name in &rule.prefab.names {
+            let prefab = params
+                .prefabs
+                .get(name)
+                .unwrap_or_else(|| panic!("missing prefab {name}"));
+            for block in &prefab.palette {
+                assert!(
+                    reg.id_by_name(&block.name).is_some(),
+                    "prefab {name} uses unknown block {}",
+                    block.name
+                );
+            }
+        }
+    }
+}
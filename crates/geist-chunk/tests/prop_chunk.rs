@@ -1,5 +1,5 @@
 use geist_blocks::types::Block;
-use geist_chunk::ChunkBuf;
+use geist_chunk::{ChunkBlockStats, ChunkBuf};
 use geist_world::ChunkCoord;
 use proptest::prelude::*;
 
@@ -131,4 +131,19 @@ proptest! {
             ChunkBuf::from_blocks_local(coord, sx, sy, sz, vec![Block::AIR; wrong_len]);
         prop_assert_eq!(buf_resized.blocks.len(), expect);
     }
+
+    // block stats agree with a direct scan of the buffer
+    #[test]
+    fn block_stats_match_buffer(ids in proptest::collection::vec(0u16..6, 0..256)) {
+        let blocks: Vec<Block> = ids.iter().map(|&id| Block { id, state: (id % 3) }).collect();
+        let stats = ChunkBlockStats::from_blocks(&blocks);
+        prop_assert_eq!(stats.total(), blocks.len() as u64);
+        for id in 0u16..8 {
+            let expect = blocks.iter().filter(|b| b.id == id).count() as u32;
+            prop_assert_eq!(stats.count(id), expect);
+        }
+        let non_air = blocks.iter().filter(|b| b.id != Block::AIR.id).count() as u64;
+        prop_assert_eq!(stats.non_air(), non_air);
+        prop_assert!(stats.iter().all(|(_, n)| n > 0));
+    }
 }
@@ -0,0 +1,211 @@
+use std::sync::Arc;
+
+use geist_blocks::BlockRegistry;
+use geist_blocks::types::Block;
+use geist_chunk::generate_chunk_buffer;
+use geist_world::worldgen::WorldGenParams;
+use geist_world::{
+    ChunkCoord, StageColumn, TerrainGenerator, TerrainStage, TerrainStagePlugin, World,
+    WorldGenMode,
+};
+
+fn load_registry() -> BlockRegistry {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let vox = root.join("../../assets/voxels");
+    BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml")).unwrap()
+}
+
+// Raises every other 16-wide band along x by six blocks and drains all water.
+struct Plateaus;
+
+impl TerrainStagePlugin for Plateaus {
+    fn name(&self) -> &str {
+        "plateaus"
+    }
+
+    fn height(&self, wx: i32, _wz: i32, height: i32) -> i32 {
+        if wx.div_euclid(16).rem_euclid(2) == 1 {
+            height + 6
+        } else {
+            height
+        }
+    }
+
+    fn water_level(&self, _wx: i32, _wz: i32, _level: i32) -> i32 {
+        -1
+    }
+}
+
+// Glowstone on the ground of one column in every 16x16 cell.
+struct Beacons(Block);
+
+impl TerrainStagePlugin for Beacons {
+    fn name(&self) -> &str {
+        "beacons"
+    }
+
+    fn modify_block(
+        &self,
+        _reg: &BlockRegistry,
+        wx: i32,
+        wy: i32,
+        wz: i32,
+        column: StageColumn,
+        block: Block,
+    ) -> Block {
+        if wx.rem_euclid(16) == 8 && wz.rem_euclid(16) == 8 && wy == column.height {
+            self.0
+        } else {
+            block
+        }
+    }
+}
+
+// Caps every other beacon row with stone; registered after `Beacons`, so it sees them.
+struct Capper {
+    glowstone: Block,
+    stone: Block,
+}
+
+impl TerrainStagePlugin for Capper {
+    fn name(&self) -> &str {
+        "capper"
+    }
+
+    fn modify_block(
+        &self,
+        _reg: &BlockRegistry,
+        _wx: i32,
+        _wy: i32,
+        wz: i32,
+        _column: StageColumn,
+        block: Block,
+    ) -> Block {
+        if block == self.glowstone && wz.rem_euclid(32) == 8 {
+            self.stone
+        } else {
+            block
+        }
+    }
+}
+
+fn block_of(reg: &BlockRegistry, name: &str) -> Block {
+    Block {
+        id: reg.id_by_name(name).unwrap(),
+        state: 0,
+    }
+}
+
+fn assert_chunk_matches_points(world: &World, reg: &BlockRegistry, coord: ChunkCoord) {
+    let buf = generate_chunk_buffer(world, coord, reg).buf;
+    let mut ctx = world.make_gen_ctx();
+    let (sx, sy, sz) = (world.chunk_size_x, world.chunk_size_y, world.chunk_size_z);
+    for y in (0..sy).step_by(3) {
+        for z in (0..sz).step_by(2) {
+            for x in (0..sx).step_by(2) {
+                let (wx, wy, wz) = (
+                    coord.cx * sx as i32 + x as i32,
+                    coord.cy * sy as i32 + y as i32,
+                    coord.cz * sz as i32 + z as i32,
+                );
+                assert_eq!(
+                    buf.get_local(x, y, z),
+                    world.block_at_runtime_with(reg, &mut ctx, wx, wy, wz),
+                    "chunk and point query differ at ({wx}, {wy}, {wz})"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn plugins_reshape_columns_and_blocks_in_order() {
+    let reg = load_registry();
+    let (glowstone, stone) = (block_of(&reg, "glowstone"), block_of(&reg, "stone"));
+    let make = || {
+        let world = World::new(2, 2, 2, 99, WorldGenMode::Normal);
+        let mut params = WorldGenParams::default();
+        params.tree_probability = 0.0;
+        params.biomes = None;
+        world.update_worldgen_params(params);
+        world
+    };
+    let plain = make();
+    let world = make()
+        .with_stage_plugin(Arc::new(Plateaus))
+        .with_stage_plugin(Arc::new(Beacons(glowstone)))
+        .with_stage_plugin(Arc::new(Capper { glowstone, stone }));
+    assert_eq!(world.stage_plugins().len(), 3);
+    assert_eq!(world.stage_plugins()[1].name(), "beacons");
+
+    let coord = ChunkCoord::new(1, 0, 1);
+    let base = generate_chunk_buffer(&plain, coord, &reg);
+    let result = generate_chunk_buffer(&world, coord, &reg);
+    let plan = &result.column_profile.as_ref().unwrap().plan;
+    let base_plan = &base.column_profile.as_ref().unwrap().plan;
+    for (lx, lz) in [(0, 0), (15, 40), (16, 3), (31, 63), (48, 20)] {
+        let raised = if (64 + lx as i32).div_euclid(16) % 2 == 1 {
+            6
+        } else {
+            0
+        };
+        assert_eq!(
+            plan.column(lx, lz).height,
+            base_plan.column(lx, lz).height + raised,
+            "column ({lx}, {lz})"
+        );
+    }
+    let water = reg.id_by_name("water").unwrap();
+    assert_eq!(
+        result.block_stats.count(water),
+        0,
+        "plugins drained the water"
+    );
+
+    // Beacons sit on the ground at local 8/24/40/56; rows with wz % 32 == 8 were capped.
+    for (lx, lz, want) in [
+        (8, 8, stone),
+        (8, 24, glowstone),
+        (40, 40, stone),
+        (24, 56, glowstone),
+    ] {
+        let (wx, wz) = (64 + lx as i32, 64 + lz as i32);
+        let h = plan.column(lx, lz).height;
+        assert_eq!(
+            world.block_at_runtime(&reg, wx, h, wz),
+            want,
+            "beacon at ({wx}, {wz})"
+        );
+    }
+    assert!(result.terrain_metrics.stages[TerrainStage::Plugins as usize].calls > 0);
+
+    for coord in [coord, ChunkCoord::new(-1, 0, 0), ChunkCoord::new(0, 1, -1)] {
+        assert_chunk_matches_points(&world, &reg, coord);
+    }
+}
+
+struct Flatland;
+
+impl TerrainGenerator for Flatland {
+    fn height(&self, _wx: i32, _wz: i32) -> i32 {
+        10
+    }
+
+    fn surface_material(&self, _wx: i32, wy: i32, _wz: i32, height: i32) -> &str {
+        if wy < height { "stone" } else { "air" }
+    }
+}
+
+#[test]
+fn plugins_run_on_top_of_custom_generators() {
+    let reg = load_registry();
+    let glowstone = block_of(&reg, "glowstone");
+    let world = World::with_generator(2, 2, 2, 5, Arc::new(Flatland))
+        .with_stage_plugin(Arc::new(Plateaus))
+        .with_stage_plugin(Arc::new(Beacons(glowstone)));
+    let buf = generate_chunk_buffer(&world, ChunkCoord::new(0, 0, 0), &reg).buf;
+    assert_eq!(buf.get_local(8, 10, 8), glowstone);
+    assert_eq!(buf.get_local(24, 16, 8), glowstone, "raised band");
+    assert_eq!(buf.get_local(24, 15, 9), block_of(&reg, "stone"));
+    assert_chunk_matches_points(&world, &reg, ChunkCoord::new(0, 0, 0));
+}
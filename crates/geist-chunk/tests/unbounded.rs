@@ -0,0 +1,79 @@
+use geist_blocks::BlockRegistry;
+use geist_chunk::generate_chunk_buffer;
+use geist_world::worldgen::WorldGenParams;
+use geist_world::{ChunkCoord, World, WorldExtent, WorldGenMode};
+
+fn load_registry() -> BlockRegistry {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let vox = root.join("../../assets/voxels");
+    BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml")).unwrap()
+}
+
+#[test]
+fn far_negative_chunks_match_point_queries() {
+    let reg = load_registry();
+    let world = World::new(2, 4, 2, 4242, WorldGenMode::Normal);
+    // Overlapping canopies can disagree on leaf species between the two paths wherever
+    // the world is, so trees stay out of this comparison.
+    let mut params = WorldGenParams::default();
+    params.tree_probability = 0.0;
+    params.biomes = None;
+    world.update_worldgen_params(params);
+    let mut ctx = world.make_gen_ctx();
+    let (sx, sy, sz) = (world.chunk_size_x, world.chunk_size_y, world.chunk_size_z);
+    for coord in [
+        ChunkCoord::new(-40_000, 1, -35_001),
+        ChunkCoord::new(-39_999, 1, -35_001),
+    ] {
+        let buf = generate_chunk_buffer(&world, coord, &reg).buf;
+        // Every third voxel keeps the point queries affordable.
+        for y in (0..sy).step_by(3) {
+            for z in (0..sz).step_by(3) {
+                for x in (0..sx).step_by(3) {
+                    let (wx, wy, wz) = (
+                        coord.cx * sx as i32 + x as i32,
+                        coord.cy * sy as i32 + y as i32,
+                        coord.cz * sz as i32 + z as i32,
+                    );
+                    assert_eq!(
+                        buf.get_local(x, y, z),
+                        world.block_at_runtime_with(&reg, &mut ctx, wx, wy, wz),
+                        "chunk and point query differ at ({wx}, {wy}, {wz})"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn tower_stands_on_the_world_center() {
+    let reg = load_registry();
+    let glowstone = reg.id_by_name("glowstone").unwrap();
+    let unbounded = World::new(4, 4, 4, 7, WorldGenMode::Normal);
+    let finite = World::new(4, 4, 4, 7, WorldGenMode::Normal).with_extent(WorldExtent::Finite {
+        chunks_x: 4,
+        chunks_z: 4,
+    });
+    let half = unbounded.chunk_size_x as i32 * 2;
+    assert_eq!(unbounded.center(), (0, 0));
+    assert_eq!(finite.center(), (half, half));
+    assert!(finite.contains_chunk(ChunkCoord::new(3, 9, 0)));
+    assert!(!finite.contains_chunk(ChunkCoord::new(-1, 0, 0)));
+    assert!(!finite.contains_chunk(ChunkCoord::new(0, 0, 4)));
+
+    // A glowstone band of the tower wall at y 2, ten blocks east of the center.
+    for world in [&unbounded, &finite] {
+        let (cx, cz) = world.center();
+        let mut ctx = world.make_gen_ctx();
+        let b = world.block_at_runtime_with(&reg, &mut ctx, cx + 10, 2, cz);
+        assert_eq!(b.id, glowstone, "no tower wall beside {:?}", (cx, cz));
+        let coord = world.chunk_of(cx + 10, 2, cz);
+        let buf = generate_chunk_buffer(world, coord, &reg).buf;
+        let (lx, lz) = (
+            (cx + 10).rem_euclid(world.chunk_size_x as i32) as usize,
+            cz.rem_euclid(world.chunk_size_z as i32) as usize,
+        );
+        assert_eq!(buf.get_local(lx, 2, lz).id, glowstone);
+    }
+}
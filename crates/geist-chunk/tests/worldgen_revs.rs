@@ -0,0 +1,97 @@
+use geist_blocks::BlockRegistry;
+use geist_chunk::{generate_chunk_buffer_from_profile, generate_chunk_buffer_with_ctx};
+use geist_world::worldgen::WorldGenParams;
+use geist_world::{ChunkCoord, ColumnRebuild, World, WorldGenMode, WorldgenRevTracker};
+
+fn load_registry() -> BlockRegistry {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let vox = root.join("../../assets/voxels");
+    BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml")).unwrap()
+}
+
+#[test]
+fn chunks_carry_the_rev_of_the_params_they_used() {
+    let reg = load_registry();
+    let world = World::new(2, 2, 2, 11, WorldGenMode::Normal);
+    let mut old_ctx = world.make_gen_ctx();
+    let first = world.current_worldgen_rev();
+    assert_eq!(old_ctx.worldgen_rev, first);
+
+    let coord = ChunkCoord::new(0, 0, 0);
+    let before = generate_chunk_buffer_with_ctx(&world, coord, &reg, &mut old_ctx);
+    assert_eq!(before.worldgen_rev, first);
+
+    let mut params = WorldGenParams::default();
+    params.min_y_ratio += 0.1;
+    world.update_worldgen_params(params);
+    let second = world.current_worldgen_rev();
+    assert!(second > first);
+
+    // A context made before the reload still generates, and reports, the old params.
+    let stale = generate_chunk_buffer_with_ctx(&world, coord, &reg, &mut old_ctx);
+    assert_eq!(stale.worldgen_rev, first);
+    assert_eq!(stale.buf.content_hash(), before.buf.content_hash());
+
+    let mut ctx = world.make_gen_ctx();
+    let fresh = generate_chunk_buffer_with_ctx(&world, coord, &reg, &mut ctx);
+    assert_eq!(fresh.worldgen_rev, second);
+    assert_ne!(fresh.buf.content_hash(), before.buf.content_hash());
+
+    // A column profile from the old params keeps a chunk stale even with a fresh context.
+    let old_profile = before.column_profile.unwrap();
+    let reused = generate_chunk_buffer_from_profile(
+        &world,
+        ChunkCoord::new(0, 1, 0),
+        &reg,
+        &mut ctx,
+        &old_profile,
+    );
+    assert_eq!(reused.worldgen_rev, first);
+}
+
+#[test]
+fn rebuild_plan_groups_stale_chunks_by_column_nearest_first() {
+    let mut revs = WorldgenRevTracker::new();
+    for cy in 0..3 {
+        revs.record(ChunkCoord::new(4, cy, 0), 1);
+        revs.record(ChunkCoord::new(0, cy, 1), 2);
+    }
+    revs.record(ChunkCoord::new(1, 0, 0), 3);
+    revs.record(ChunkCoord::new(9, 0, 9), 2);
+    assert!(revs.forget(ChunkCoord::new(9, 0, 9)).is_some());
+
+    assert!(revs.is_stale(ChunkCoord::new(4, 2, 0), 3));
+    assert!(!revs.is_stale(ChunkCoord::new(1, 0, 0), 3));
+    assert!(!revs.is_stale(ChunkCoord::new(7, 0, 7), 3), "untracked");
+    assert_eq!(revs.stale_count(3), 6);
+    assert_eq!(revs.stale_count(2), 3);
+
+    let plan = revs.rebuild_plan(3, ChunkCoord::new(0, 5, 0));
+    assert_eq!(plan.target_rev, 3);
+    assert_eq!(plan.up_to_date, 1);
+    assert_eq!(plan.stale_by_rev, vec![(1, 3), (2, 3)]);
+    assert_eq!(
+        plan.columns,
+        vec![
+            ColumnRebuild {
+                cx: 0,
+                cz: 1,
+                cys: vec![2, 1, 0],
+            },
+            ColumnRebuild {
+                cx: 4,
+                cz: 0,
+                cys: vec![2, 1, 0],
+            },
+        ]
+    );
+    assert_eq!(plan.chunk_count(), 6);
+    assert_eq!(plan.coords().next(), Some(ChunkCoord::new(0, 2, 1)));
+
+    for coord in plan.coords().collect::<Vec<_>>() {
+        revs.record(coord, 3);
+    }
+    let done = revs.rebuild_plan(3, ChunkCoord::new(0, 0, 0));
+    assert!(done.is_empty());
+    assert_eq!(done.up_to_date, 7);
+}
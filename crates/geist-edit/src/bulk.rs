@@ -0,0 +1,189 @@
+//! Bulk edits that bump every touched chunk once with a single stamp.
+
+use std::collections::HashSet;
+
+use geist_blocks::types::Block;
+use geist_world::ChunkCoord;
+
+use crate::EditStore;
+
+/// Inclusive box of world voxel coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BlockRegion {
+    pub min: (i32, i32, i32),
+    pub max: (i32, i32, i32),
+}
+
+impl BlockRegion {
+    /// Box spanning two corners given in any order.
+    pub fn new(a: (i32, i32, i32), b: (i32, i32, i32)) -> Self {
+        Self {
+            min: (a.0.min(b.0), a.1.min(b.1), a.2.min(b.2)),
+            max: (a.0.max(b.0), a.1.max(b.1), a.2.max(b.2)),
+        }
+    }
+
+    /// Number of voxels, 0 if any `max` axis lies below `min`.
+    pub fn volume(&self) -> u64 {
+        if self.max.0 < self.min.0 || self.max.1 < self.min.1 || self.max.2 < self.min.2 {
+            return 0;
+        }
+        let dx = (self.max.0 - self.min.0) as u64 + 1;
+        let dy = (self.max.1 - self.min.1) as u64 + 1;
+        let dz = (self.max.2 - self.min.2) as u64 + 1;
+        dx * dy * dz
+    }
+
+    pub fn contains(&self, wx: i32, wy: i32, wz: i32) -> bool {
+        (self.min.0..=self.max.0).contains(&wx)
+            && (self.min.1..=self.max.1).contains(&wy)
+            && (self.min.2..=self.max.2).contains(&wz)
+    }
+}
+
+/// Result of a bulk edit.
+#[derive(Clone, Debug, Default)]
+pub struct BatchEdit {
+    /// Revision stamp shared by every affected chunk; 0 if nothing was written.
+    pub stamp: u64,
+    pub edits: usize,
+    /// Chunks bumped to `stamp`, including seam neighbours, sorted by (cy, cz, cx).
+    pub affected_chunks: Vec<ChunkCoord>,
+}
+
+/// Chunk range along one axis touched by edits spanning `lo..=hi`, counting the
+/// neighbour across a seam when an edit sits on the chunk's first or last layer.
+fn axis_chunks(lo: i32, hi: i32, size: i32) -> (i32, i32) {
+    let mut c0 = lo.div_euclid(size);
+    let mut c1 = hi.div_euclid(size);
+    if lo.rem_euclid(size) == 0 {
+        c0 -= 1;
+    }
+    if hi.rem_euclid(size) == size - 1 {
+        c1 += 1;
+    }
+    (c0, c1)
+}
+
+impl EditStore {
+    /// Fill `region` with `block` as one undo step and one revision stamp.
+    pub fn set_region(&mut self, region: BlockRegion, block: Block) -> BatchEdit {
+        let edits = region.volume() as usize;
+        if edits == 0 {
+            return BatchEdit::default();
+        }
+        let (min, max) = (region.min, region.max);
+        self.begin_group();
+        for wy in min.1..=max.1 {
+            for wz in min.2..=max.2 {
+                for wx in min.0..=max.0 {
+                    self.set(wx, wy, wz, block);
+                }
+            }
+        }
+        self.end_group();
+        let (x0, x1) = axis_chunks(min.0, max.0, self.sx);
+        let (y0, y1) = axis_chunks(min.1, max.1, self.sy);
+        let (z0, z1) = axis_chunks(min.2, max.2, self.sz);
+        let mut chunks = Vec::new();
+        for cy in y0..=y1 {
+            for cz in z0..=z1 {
+                for cx in x0..=x1 {
+                    chunks.push(ChunkCoord::new(cx, cy, cz));
+                }
+            }
+        }
+        self.finish_batch(edits, chunks)
+    }
+
+    /// Write many edits as one undo step and one revision stamp.
+    pub fn apply_batch(
+        &mut self,
+        edits: impl IntoIterator<Item = ((i32, i32, i32), Block)>,
+    ) -> BatchEdit {
+        let mut affected: HashSet<ChunkCoord> = HashSet::new();
+        let mut count = 0usize;
+        self.begin_group();
+        for ((wx, wy, wz), block) in edits {
+            self.set(wx, wy, wz, block);
+            affected.extend(self.get_affected_chunks(wx, wy, wz));
+            count += 1;
+        }
+        self.end_group();
+        let mut chunks: Vec<ChunkCoord> = affected.into_iter().collect();
+        chunks.sort_by_key(|c| (c.cy, c.cz, c.cx));
+        self.finish_batch(count, chunks)
+    }
+
+    fn finish_batch(&mut self, edits: usize, affected_chunks: Vec<ChunkCoord>) -> BatchEdit {
+        if edits == 0 {
+            return BatchEdit::default();
+        }
+        self.counter = self.counter.wrapping_add(1).max(1);
+        let stamp = self.counter;
+        for coord in &affected_chunks {
+            self.rev.insert(*coord, stamp);
+        }
+        BatchEdit {
+            stamp,
+            edits,
+            affected_chunks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_store() -> EditStore {
+        EditStore::new(32, 32, 32)
+    }
+
+    #[test]
+    fn set_region_bumps_minimal_chunks_with_one_stamp() {
+        let mut store = make_store();
+        let stone = Block { id: 3, state: 0 };
+        // Touches the -X seam of chunk 0 and spans into chunk 1 along Z without reaching its far edge.
+        let region = BlockRegion::new((0, 4, 30), (5, 6, 33));
+        let batch = store.set_region(region, stone);
+        assert_eq!(batch.edits, 6 * 3 * 4);
+        assert_eq!(store.get(5, 6, 33), Some(stone));
+
+        let mut expected: HashSet<ChunkCoord> = HashSet::new();
+        for wy in 4..=6 {
+            for wz in 30..=33 {
+                for wx in 0..=5 {
+                    expected.extend(store.get_affected_chunks(wx, wy, wz));
+                }
+            }
+        }
+        let got: HashSet<ChunkCoord> = batch.affected_chunks.iter().copied().collect();
+        assert_eq!(got, expected);
+        for c in &batch.affected_chunks {
+            assert_eq!(store.get_rev(c.cx, c.cy, c.cz), batch.stamp);
+        }
+        assert_eq!(store.get_rev(1, 0, 0), 0);
+
+        // The fill undoes as one step.
+        store.undo();
+        assert_eq!(store.stats().block_edits, 0);
+    }
+
+    #[test]
+    fn apply_batch_shares_one_stamp() {
+        let mut store = make_store();
+        let b = Block { id: 1, state: 0 };
+        let batch = store.apply_batch([((0, 0, 0), b), ((100, 40, 7), b), ((1, 0, 0), b)]);
+        assert_eq!(batch.edits, 3);
+        assert!(batch.affected_chunks.contains(&ChunkCoord::new(3, 1, 0)));
+        assert!(batch.affected_chunks.contains(&ChunkCoord::new(-1, -1, -1)));
+        let stamps: HashSet<u64> = batch
+            .affected_chunks
+            .iter()
+            .map(|c| store.get_rev(c.cx, c.cy, c.cz))
+            .collect();
+        assert_eq!(stamps, HashSet::from([batch.stamp]));
+        assert_eq!(store.apply_batch([]).stamp, 0);
+    }
+}
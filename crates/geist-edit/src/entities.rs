@@ -0,0 +1,258 @@
+use geist_blocks::types::Block;
+use geist_blocks::{BlockEntity, BlockEntityValue, BlockRegistry};
+use geist_geom::{IVec3, WorldPos};
+use geist_world::ChunkCoord;
+use std::collections::{HashMap, HashSet};
+
+/// What a block change did to the entity at its position.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BlockEntityChange {
+    /// No entity before or after.
+    None,
+    /// Same block id (e.g. a state change); the entity was kept.
+    Kept,
+    /// The new block owns an entity; a fresh one was created.
+    Created,
+    /// The block was removed or replaced; its entity is handed back to the caller.
+    Removed(BlockEntity),
+    /// The block was replaced by another entity-owning block.
+    Replaced(BlockEntity),
+}
+
+/// Per-position block entity data, bucketed by chunk like [`crate::EditStore`].
+///
+/// Entities follow their block: call [`BlockEntityStore::on_block_changed`] whenever a
+/// voxel changes so placing an entity block creates its entity and removing or replacing
+/// it drops the entity.
+pub struct BlockEntityStore {
+    sx: i32,
+    sy: i32,
+    sz: i32,
+    inner: HashMap<ChunkCoord, HashMap<(i32, i32, i32), BlockEntity>>,
+    // Chunks whose mesh-visible entities changed since the last `take_visual_dirty`
+    visual_dirty: HashSet<ChunkCoord>,
+}
+
+impl BlockEntityStore {
+    pub fn new(sx: i32, sy: i32, sz: i32) -> Self {
+        Self {
+            sx,
+            sy,
+            sz,
+            inner: HashMap::new(),
+            visual_dirty: HashSet::new(),
+        }
+    }
+
+    #[inline]
+    fn chunk_key(&self, wx: i32, wy: i32, wz: i32) -> ChunkCoord {
+        WorldPos::new(wx, wy, wz)
+            .chunk(IVec3::new(self.sx, self.sy, self.sz))
+            .into()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.values().map(|m| m.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn get(&self, wx: i32, wy: i32, wz: i32) -> Option<&BlockEntity> {
+        let k = self.chunk_key(wx, wy, wz);
+        self.inner.get(&k).and_then(|m| m.get(&(wx, wy, wz)))
+    }
+
+    /// Set one field of the entity at a position; false when there is none.
+    pub fn set_field(
+        &mut self,
+        wx: i32,
+        wy: i32,
+        wz: i32,
+        key: &str,
+        value: BlockEntityValue,
+    ) -> bool {
+        let k = self.chunk_key(wx, wy, wz);
+        let Some(entity) = self
+            .inner
+            .get_mut(&k)
+            .and_then(|m| m.get_mut(&(wx, wy, wz)))
+        else {
+            return false;
+        };
+        entity.set(key, value);
+        if entity.mesh_visible {
+            self.visual_dirty.insert(k);
+        }
+        true
+    }
+
+    /// Store `entity` at a position, returning the one it replaced.
+    pub fn insert(
+        &mut self,
+        wx: i32,
+        wy: i32,
+        wz: i32,
+        entity: BlockEntity,
+    ) -> Option<BlockEntity> {
+        let k = self.chunk_key(wx, wy, wz);
+        if entity.mesh_visible {
+            self.visual_dirty.insert(k);
+        }
+        let old = self
+            .inner
+            .entry(k)
+            .or_default()
+            .insert((wx, wy, wz), entity);
+        if old.as_ref().is_some_and(|e| e.mesh_visible) {
+            self.visual_dirty.insert(k);
+        }
+        old
+    }
+
+    pub fn remove(&mut self, wx: i32, wy: i32, wz: i32) -> Option<BlockEntity> {
+        let k = self.chunk_key(wx, wy, wz);
+        let m = self.inner.get_mut(&k)?;
+        let old = m.remove(&(wx, wy, wz));
+        if m.is_empty() {
+            self.inner.remove(&k);
+        }
+        if old.as_ref().is_some_and(|e| e.mesh_visible) {
+            self.visual_dirty.insert(k);
+        }
+        old
+    }
+
+    /// Lifecycle hook for a voxel whose block became `after`.
+    ///
+    /// Entities are tied to the block id, so state changes keep them while removing or
+    /// replacing the block drops them; the removed entity is returned for the caller to
+    /// act on (spill contents, say).
+    pub fn on_block_changed(
+        &mut self,
+        reg: &BlockRegistry,
+        (wx, wy, wz): (i32, i32, i32),
+        after: Block,
+    ) -> BlockEntityChange {
+        if self.get(wx, wy, wz).is_some_and(|e| e.block == after.id) {
+            return BlockEntityChange::Kept;
+        }
+        let removed = self.remove(wx, wy, wz);
+        match (removed, BlockEntity::for_block(reg, after)) {
+            (old, Some(entity)) => {
+                self.insert(wx, wy, wz, entity);
+                old.map_or(BlockEntityChange::Created, BlockEntityChange::Replaced)
+            }
+            (Some(old), None) => BlockEntityChange::Removed(old),
+            (None, None) => BlockEntityChange::None,
+        }
+    }
+
+    /// Every entity in a chunk.
+    pub fn entities_in_chunk(
+        &self,
+        cx: i32,
+        cy: i32,
+        cz: i32,
+    ) -> impl Iterator<Item = ((i32, i32, i32), &BlockEntity)> + '_ {
+        self.inner
+            .get(&ChunkCoord::new(cx, cy, cz))
+            .into_iter()
+            .flat_map(|m| m.iter().map(|(k, v)| (*k, v)))
+    }
+
+    /// Mesh-visible entities of a chunk, for build jobs.
+    pub fn snapshot_for_chunk(
+        &self,
+        cx: i32,
+        cy: i32,
+        cz: i32,
+    ) -> Vec<((i32, i32, i32), BlockEntity)> {
+        self.entities_in_chunk(cx, cy, cz)
+            .filter(|(_, e)| e.mesh_visible)
+            .map(|(k, e)| (k, e.clone()))
+            .collect()
+    }
+
+    /// Chunks whose mesh-visible entities changed since the last call; they need a remesh.
+    pub fn take_visual_dirty(&mut self) -> Vec<ChunkCoord> {
+        self.visual_dirty.drain().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::registry;
+
+    #[test]
+    fn block_entities_follow_their_block() {
+        let reg = registry(
+            r#"[[blocks]]
+name = "air"
+solid = false
+[[blocks]]
+name = "chest"
+materials = { all = "stone" }
+state_schema = { facing = ["north", "south"] }
+tags = ["block_entity"]
+[[blocks]]
+name = "sign"
+materials = { all = "stone" }
+tags = ["block_entity", "entity_visual"]
+"#,
+        );
+        let by_name = |n: &str| reg.make_block_by_name(n, None).unwrap();
+        let (chest, sign) = (by_name("chest"), by_name("sign"));
+        let mut entities = BlockEntityStore::new(32, 32, 32);
+
+        assert_eq!(
+            entities.on_block_changed(&reg, (3, 4, 5), chest),
+            BlockEntityChange::Created
+        );
+        assert!(entities.set_field(3, 4, 5, "slots", BlockEntityValue::Int(27)));
+        assert!(!entities.set_field(9, 9, 9, "slots", BlockEntityValue::Int(1)));
+        assert!(
+            entities.take_visual_dirty().is_empty(),
+            "chests are not meshed"
+        );
+
+        // A state change keeps the data; replacing the block hands it back.
+        let turned = Block {
+            id: chest.id,
+            state: chest.state ^ 1,
+        };
+        assert_eq!(
+            entities.on_block_changed(&reg, (3, 4, 5), turned),
+            BlockEntityChange::Kept
+        );
+        let BlockEntityChange::Replaced(old) = entities.on_block_changed(&reg, (3, 4, 5), sign)
+        else {
+            panic!("chest entity should be replaced by the sign's");
+        };
+        assert_eq!(old.get("slots"), Some(&BlockEntityValue::Int(27)));
+        assert!(entities.get(3, 4, 5).unwrap().fields.is_empty());
+
+        // Only mesh-visible entities reach build jobs, and changing them dirties the chunk.
+        entities.on_block_changed(&reg, (40, 4, 5), chest);
+        let text = BlockEntityValue::Text("hello".into());
+        assert!(entities.set_field(3, 4, 5, "text", text.clone()));
+        assert_eq!(entities.take_visual_dirty(), vec![ChunkCoord::new(0, 0, 0)]);
+        let snap = entities.snapshot_for_chunk(0, 0, 0);
+        assert_eq!(snap.len(), 1);
+        assert_eq!(snap[0].1.get("text"), Some(&text));
+        assert!(entities.snapshot_for_chunk(1, 0, 0).is_empty());
+        assert_eq!(entities.entities_in_chunk(1, 0, 0).count(), 1);
+
+        assert!(matches!(
+            entities.on_block_changed(&reg, (3, 4, 5), Block::AIR),
+            BlockEntityChange::Removed(_)
+        ));
+        assert_eq!(
+            entities.on_block_changed(&reg, (3, 4, 5), Block::AIR),
+            BlockEntityChange::None
+        );
+        assert_eq!(entities.len(), 1);
+    }
+}
@@ -0,0 +1,242 @@
+//! Undo/redo log for world edits.
+//!
+//! Every `EditStore::set` records the overlay value it replaced. Edits made between
+//! `begin_group` and `end_group` (a brush stroke, a pasted patch) undo as one step.
+
+use std::collections::{HashSet, VecDeque};
+
+use geist_blocks::types::Block;
+use geist_world::ChunkCoord;
+
+use crate::EditStore;
+
+/// Undo steps kept by default before the oldest are dropped.
+pub const DEFAULT_HISTORY_LIMIT: usize = 256;
+
+/// One block write. `None` means "no edit here", i.e. the generated block shows through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EditChange {
+    pub pos: (i32, i32, i32),
+    pub before: Option<Block>,
+    pub after: Option<Block>,
+}
+
+/// Changes undone or redone together, in the order they were made.
+#[derive(Clone, Debug, Default)]
+pub struct EditGroup {
+    pub changes: Vec<EditChange>,
+}
+
+pub(crate) struct EditHistory {
+    undo: VecDeque<EditGroup>,
+    redo: Vec<EditGroup>,
+    open: Option<EditGroup>,
+    depth: u32,
+    limit: usize,
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            open: None,
+            depth: 0,
+            limit: DEFAULT_HISTORY_LIMIT,
+        }
+    }
+}
+
+impl EditHistory {
+    pub(crate) fn record(&mut self, change: EditChange) {
+        if change.before == change.after {
+            return;
+        }
+        self.redo.clear();
+        match self.open.as_mut() {
+            Some(group) => group.changes.push(change),
+            None => self.push(EditGroup {
+                changes: vec![change],
+            }),
+        }
+    }
+
+    fn push(&mut self, group: EditGroup) {
+        if self.limit == 0 || group.changes.is_empty() {
+            return;
+        }
+        self.undo.push_back(group);
+        while self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+}
+
+impl EditStore {
+    /// Start grouping edits into one undo step. Groups nest; only the outermost
+    /// `end_group` closes the step.
+    pub fn begin_group(&mut self) {
+        let h = &mut self.history;
+        if h.depth == 0 {
+            h.open = Some(EditGroup::default());
+        }
+        h.depth += 1;
+    }
+
+    pub fn end_group(&mut self) {
+        let h = &mut self.history;
+        if h.depth == 0 {
+            return;
+        }
+        h.depth -= 1;
+        if h.depth == 0
+            && let Some(group) = h.open.take()
+        {
+            h.push(group);
+        }
+    }
+
+    /// Maximum undo steps retained; older steps are discarded first.
+    pub fn set_history_limit(&mut self, limit: usize) {
+        let h = &mut self.history;
+        h.limit = limit;
+        while h.undo.len() > limit {
+            h.undo.pop_front();
+        }
+        h.redo.truncate(limit);
+    }
+
+    #[inline]
+    pub fn history_limit(&self) -> usize {
+        self.history.limit
+    }
+
+    /// Drop all undo/redo steps, e.g. after loading edits that should not be undoable.
+    pub fn clear_history(&mut self) {
+        let h = &mut self.history;
+        h.undo.clear();
+        h.redo.clear();
+        if h.open.is_some() {
+            h.open = Some(EditGroup::default());
+        }
+    }
+
+    #[inline]
+    pub fn can_undo(&self) -> bool {
+        !self.history.undo.is_empty()
+    }
+
+    #[inline]
+    pub fn can_redo(&self) -> bool {
+        !self.history.redo.is_empty()
+    }
+
+    /// The step `undo` would revert next.
+    pub fn peek_undo(&self) -> Option<&EditGroup> {
+        self.history.undo.back()
+    }
+
+    /// The step `redo` would reapply next.
+    pub fn peek_redo(&self) -> Option<&EditGroup> {
+        self.history.redo.last()
+    }
+
+    /// Revert the latest step and bump the chunks it touched (including seam neighbours).
+    /// Returns those chunks so the caller can schedule rebuilds; empty if nothing to undo.
+    pub fn undo(&mut self) -> Vec<ChunkCoord> {
+        let Some(group) = self.history.undo.pop_back() else {
+            return Vec::new();
+        };
+        let affected = self.replay(group.changes.iter().rev().map(|c| (c.pos, c.before)));
+        self.history.redo.push(group);
+        affected
+    }
+
+    /// Reapply the latest undone step. Any new edit clears the redo stack.
+    pub fn redo(&mut self) -> Vec<ChunkCoord> {
+        let Some(group) = self.history.redo.pop() else {
+            return Vec::new();
+        };
+        let affected = self.replay(group.changes.iter().map(|c| (c.pos, c.after)));
+        self.history.undo.push_back(group);
+        affected
+    }
+
+    fn replay(
+        &mut self,
+        writes: impl Iterator<Item = ((i32, i32, i32), Option<Block>)>,
+    ) -> Vec<ChunkCoord> {
+        let mut affected: HashSet<ChunkCoord> = HashSet::new();
+        for ((wx, wy, wz), value) in writes {
+            self.write_raw(wx, wy, wz, value);
+            self.bump_region_around(wx, wy, wz);
+            affected.extend(self.get_affected_chunks(wx, wy, wz));
+        }
+        let mut chunks: Vec<ChunkCoord> = affected.into_iter().collect();
+        chunks.sort_by_key(|c| (c.cy, c.cz, c.cx));
+        chunks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_store() -> EditStore {
+        EditStore::new(32, 32, 32)
+    }
+
+    #[test]
+    fn undo_redo_restores_prior_values_per_group() {
+        let mut store = make_store();
+        let a = Block { id: 1, state: 0 };
+        let b = Block { id: 2, state: 0 };
+        store.set(3, 3, 3, a);
+
+        // A stroke spanning a chunk seam undoes in one step.
+        store.begin_group();
+        store.set(3, 3, 3, b);
+        store.set(31, 3, 3, b);
+        store.set(32, 3, 3, b);
+        store.end_group();
+        assert!(store.can_undo());
+        let rev_before = store.get_rev(1, 0, 0);
+
+        let affected = store.undo();
+        assert_eq!(store.get(3, 3, 3), Some(a));
+        assert_eq!(store.get(31, 3, 3), None);
+        assert_eq!(store.get(32, 3, 3), None);
+        assert!(affected.contains(&ChunkCoord::new(0, 0, 0)));
+        assert!(affected.contains(&ChunkCoord::new(1, 0, 0)));
+        assert!(store.get_rev(1, 0, 0) > rev_before);
+
+        let affected = store.redo();
+        assert!(affected.contains(&ChunkCoord::new(1, 0, 0)));
+        assert_eq!(store.get(3, 3, 3), Some(b));
+        assert_eq!(store.get(32, 3, 3), Some(b));
+
+        store.undo();
+        store.undo();
+        assert_eq!(store.get(3, 3, 3), None);
+        assert_eq!(store.stats().chunk_entries, 0);
+        assert!(store.undo().is_empty());
+
+        // A fresh edit invalidates what was undone.
+        store.set(0, 0, 0, a);
+        assert!(!store.can_redo());
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut store = make_store();
+        store.set_history_limit(2);
+        for i in 0..5 {
+            store.set(i, 0, 0, Block { id: 1, state: 0 });
+        }
+        assert!(!store.undo().is_empty());
+        assert!(!store.undo().is_empty());
+        assert!(!store.can_undo());
+        assert_eq!(store.get(2, 0, 0), Some(Block { id: 1, state: 0 }));
+        assert_eq!(store.get(3, 0, 0), None);
+    }
+}
@@ -2,8 +2,20 @@
 #![forbid(unsafe_code)]
 
 use geist_blocks::types::Block;
+use geist_geom::{IVec3, WorldPos};
 use geist_world::ChunkCoord;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+mod bulk;
+mod entities;
+mod history;
+mod patch;
+mod savefile;
+pub use bulk::{BatchEdit, BlockRegion};
+pub use entities::{BlockEntityChange, BlockEntityStore};
+pub use history::{DEFAULT_HISTORY_LIMIT, EditChange, EditGroup};
+pub use patch::{PATCH_VERSION, PatchBlock, PatchFile, PatchReport};
+pub use savefile::{ID_TABLE_PREFIX, REGION_CHUNKS, SAVE_VERSION};
 
 #[derive(Default, Debug, Clone, Copy)]
 pub struct EditStoreStats {
@@ -24,6 +36,11 @@ pub struct EditStore {
     rev: HashMap<ChunkCoord, u64>, // latest requested change affecting chunk
     built: HashMap<ChunkCoord, u64>, // last built rev for chunk
     counter: u64,
+    // Chunks with edits not yet written to disk
+    dirty: HashSet<ChunkCoord>,
+    // `BlockRegistry::fingerprint` written into region headers; 0 when unknown
+    registry_fingerprint: u64,
+    history: history::EditHistory,
 }
 
 impl EditStore {
@@ -36,6 +53,9 @@ impl EditStore {
             rev: HashMap::new(),
             built: HashMap::new(),
             counter: 0,
+            dirty: HashSet::new(),
+            registry_fingerprint: 0,
+            history: history::EditHistory::default(),
         }
     }
 
@@ -52,13 +72,14 @@ impl EditStore {
         }
     }
 
+    #[inline]
+    fn dims(&self) -> IVec3 {
+        IVec3::new(self.sx, self.sy, self.sz)
+    }
+
     #[inline]
     fn chunk_key(&self, wx: i32, wy: i32, wz: i32) -> ChunkCoord {
-        ChunkCoord::new(
-            wx.div_euclid(self.sx),
-            wy.div_euclid(self.sy),
-            wz.div_euclid(self.sz),
-        )
+        WorldPos::new(wx, wy, wz).chunk(self.dims()).into()
     }
 
     pub fn get(&self, wx: i32, wy: i32, wz: i32) -> Option<Block> {
@@ -69,9 +90,37 @@ impl EditStore {
     }
 
     pub fn set(&mut self, wx: i32, wy: i32, wz: i32, b: Block) {
+        let before = self.get(wx, wy, wz);
+        self.history.record(EditChange {
+            pos: (wx, wy, wz),
+            before,
+            after: Some(b),
+        });
+        self.write_raw(wx, wy, wz, Some(b));
+    }
+
+    /// Write an edit made by a simulation (flowing fluid, say); it is not undoable.
+    pub fn set_untracked(&mut self, wx: i32, wy: i32, wz: i32, b: Block) {
+        self.write_raw(wx, wy, wz, Some(b));
+    }
+
+    /// Write or clear one edit without touching the undo history.
+    fn write_raw(&mut self, wx: i32, wy: i32, wz: i32, b: Option<Block>) {
         let k = self.chunk_key(wx, wy, wz);
-        let entry = self.inner.entry(k).or_default();
-        entry.insert((wx, wy, wz), b);
+        match b {
+            Some(b) => {
+                self.inner.entry(k).or_default().insert((wx, wy, wz), b);
+            }
+            None => {
+                if let Some(m) = self.inner.get_mut(&k) {
+                    m.remove(&(wx, wy, wz));
+                    if m.is_empty() {
+                        self.inner.remove(&k);
+                    }
+                }
+            }
+        }
+        self.dirty.insert(k);
     }
 
     /// Snapshot of all edits for a specific chunk
@@ -112,19 +161,16 @@ impl EditStore {
     pub fn bump_region_around(&mut self, wx: i32, wy: i32, wz: i32) -> u64 {
         self.counter = self.counter.wrapping_add(1).max(1);
         let stamp = self.counter;
-        let coord = self.chunk_key(wx, wy, wz);
-        let cx = coord.cx;
-        let cy = coord.cy;
-        let cz = coord.cz;
+        let (chunk, local) = WorldPos::new(wx, wy, wz).split(self.dims());
+        let coord = ChunkCoord::from(chunk);
+        let (cx, cy, cz) = (coord.cx, coord.cy, coord.cz);
+        let IVec3 {
+            x: lx,
+            y: ly,
+            z: lz,
+        } = local.0;
         // Only bump the chunk that was directly edited and its immediate neighbors
         // if the edit is near a chunk boundary (within 1 block of edge)
-        let x0 = cx * self.sx;
-        let y0 = cy * self.sy;
-        let z0 = cz * self.sz;
-        let lx = wx - x0;
-        let ly = wy - y0;
-        let lz = wz - z0;
-
         // Always bump the current chunk
         self.rev.insert(coord, stamp);
 
@@ -167,16 +213,14 @@ impl EditStore {
     /// Get list of chunks affected by an edit at world position
     pub fn get_affected_chunks(&self, wx: i32, wy: i32, wz: i32) -> Vec<ChunkCoord> {
         let mut affected: Vec<ChunkCoord> = Vec::new();
-        let coord = self.chunk_key(wx, wy, wz);
-        let cx = coord.cx;
-        let cy = coord.cy;
-        let cz = coord.cz;
-        let x0 = cx * self.sx;
-        let y0 = cy * self.sy;
-        let z0 = cz * self.sz;
-        let lx = wx - x0;
-        let ly = wy - y0;
-        let lz = wz - z0;
+        let (chunk, local) = WorldPos::new(wx, wy, wz).split(self.dims());
+        let coord = ChunkCoord::from(chunk);
+        let (cx, cy, cz) = (coord.cx, coord.cy, coord.cz);
+        let IVec3 {
+            x: lx,
+            y: ly,
+            z: lz,
+        } = local.0;
 
         // Always include current chunk
         affected.push(coord);
@@ -220,6 +264,12 @@ impl EditStore {
         affected
     }
 
+    /// Most recent revision stamp handed out; unchanged means no chunk was bumped since.
+    #[inline]
+    pub fn latest_stamp(&self) -> u64 {
+        self.counter
+    }
+
     pub fn get_rev(&self, cx: i32, cy: i32, cz: i32) -> u64 {
         self.rev
             .get(&ChunkCoord::new(cx, cy, cz))
@@ -304,4 +354,13 @@ mod tests {
             vec![ChunkCoord::new(cx, cy - 1, cz), ChunkCoord::new(cx, cy, cz)]
         );
     }
+
+    pub(crate) fn registry(blocks: &str) -> geist_blocks::BlockRegistry {
+        let materials = geist_blocks::MaterialCatalog::from_toml_str(
+            "[materials]\nstone = [\"assets/blocks/stone.png\"]\n",
+        )
+        .expect("materials");
+        let cfg: geist_blocks::config::BlocksConfig = toml::from_str(blocks).expect("blocks");
+        geist_blocks::BlockRegistry::from_configs(materials, cfg).expect("registry")
+    }
 }
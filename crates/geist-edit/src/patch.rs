@@ -0,0 +1,264 @@
+//! Shareable edit patches keyed by block name rather than registry id.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use geist_blocks::BlockRegistry;
+use geist_blocks::types::Block;
+use geist_world::ChunkCoord;
+use serde::{Deserialize, Serialize};
+
+use crate::EditStore;
+
+pub const PATCH_VERSION: u32 = 1;
+
+/// A palette entry: block name plus its state properties by name.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PatchBlock {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub state: BTreeMap<String, String>,
+}
+
+/// Edits relative to `origin`, each `[dx, dy, dz, palette_index]`.
+///
+/// Move `origin` before applying to place the build somewhere else.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PatchFile {
+    pub version: u32,
+    pub origin: [i32; 3],
+    pub palette: Vec<PatchBlock>,
+    pub edits: Vec<[i32; 4]>,
+}
+
+impl PatchFile {
+    pub fn to_toml_string(&self) -> Result<String, String> {
+        toml::to_string(self).map_err(|e| e.to_string())
+    }
+
+    pub fn from_toml_str(src: &str) -> Result<Self, String> {
+        let patch: PatchFile = toml::from_str(src).map_err(|e| e.to_string())?;
+        if patch.version > PATCH_VERSION {
+            return Err(format!(
+                "patch version {} is newer than supported {}",
+                patch.version, PATCH_VERSION
+            ));
+        }
+        if let Some(e) = patch
+            .edits
+            .iter()
+            .find(|e| e[3] < 0 || e[3] as usize >= patch.palette.len())
+        {
+            return Err(format!("edit palette index {} out of range", e[3]));
+        }
+        Ok(patch)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = self.to_toml_string()?;
+        std::fs::write(path, text).map_err(|e| format!("write {:?}: {}", path, e))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("read {:?}: {}", path, e))?;
+        Self::from_toml_str(&text)
+    }
+}
+
+/// What `apply_patch` did, including names this registry could not resolve.
+#[derive(Clone, Debug, Default)]
+pub struct PatchReport {
+    pub applied: usize,
+    /// Edits written as the placeholder block because their name was unknown.
+    pub placeholders: usize,
+    pub warnings: Vec<String>,
+    /// Chunks whose revision was bumped, including seam neighbours.
+    pub affected_chunks: Vec<ChunkCoord>,
+}
+
+fn patch_block(reg: &BlockRegistry, block: Block) -> PatchBlock {
+    let Some(ty) = reg.get(block.id) else {
+        return PatchBlock {
+            name: "unknown".to_string(),
+            state: BTreeMap::new(),
+        };
+    };
+    let state = ty
+        .state_fields
+        .iter()
+        .filter_map(|f| {
+            ty.state_prop_value(block.state, &f.name)
+                .map(|v| (f.name.clone(), v.to_string()))
+        })
+        .collect();
+    PatchBlock {
+        name: ty.name.clone(),
+        state,
+    }
+}
+
+impl EditStore {
+    /// Export every edit inside `chunks` with a name-based palette.
+    pub fn export_patch(&self, chunks: &[ChunkCoord], reg: &BlockRegistry) -> PatchFile {
+        let mut edits: Vec<((i32, i32, i32), Block)> = Vec::new();
+        let mut seen: HashSet<ChunkCoord> = HashSet::new();
+        for coord in chunks {
+            if !seen.insert(*coord) {
+                continue;
+            }
+            if let Some(m) = self.inner.get(coord) {
+                edits.extend(m.iter().map(|(k, v)| (*k, *v)));
+            }
+        }
+        edits.sort_by_key(|((x, y, z), _)| (*y, *z, *x));
+        let origin = edits
+            .iter()
+            .fold(None, |acc: Option<[i32; 3]>, ((x, y, z), _)| {
+                Some(match acc {
+                    Some(o) => [o[0].min(*x), o[1].min(*y), o[2].min(*z)],
+                    None => [*x, *y, *z],
+                })
+            });
+        let origin = origin.unwrap_or([0, 0, 0]);
+        let mut palette: Vec<PatchBlock> = Vec::new();
+        let mut index_of: HashMap<(u16, u16), i32> = HashMap::new();
+        let mut out = Vec::with_capacity(edits.len());
+        for ((x, y, z), b) in edits {
+            let idx = *index_of.entry((b.id, b.state)).or_insert_with(|| {
+                palette.push(patch_block(reg, b));
+                palette.len() as i32 - 1
+            });
+            out.push([x - origin[0], y - origin[1], z - origin[2], idx]);
+        }
+        PatchFile {
+            version: PATCH_VERSION,
+            origin,
+            palette,
+            edits: out,
+        }
+    }
+
+    /// Write a patch into the store at `patch.origin`, bumping revisions of touched chunks.
+    ///
+    /// Palette names missing from `reg` become `placeholder` and are listed in the report
+    /// warnings; unknown state properties fall back to the block's default value.
+    pub fn apply_patch(
+        &mut self,
+        patch: &PatchFile,
+        reg: &BlockRegistry,
+        placeholder: Block,
+    ) -> PatchReport {
+        let mut report = PatchReport::default();
+        let resolved: Vec<Option<Block>> = patch
+            .palette
+            .iter()
+            .map(|entry| {
+                let props: HashMap<String, String> = entry
+                    .state
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect();
+                let block = reg.make_block_by_name(&entry.name, Some(&props));
+                if block.is_none() {
+                    report.warnings.push(format!(
+                        "unknown block '{}' replaced by placeholder",
+                        entry.name
+                    ));
+                }
+                block
+            })
+            .collect();
+        let mut edits = Vec::with_capacity(patch.edits.len());
+        for e in &patch.edits {
+            let Some(slot) = usize::try_from(e[3]).ok().and_then(|i| resolved.get(i)) else {
+                report
+                    .warnings
+                    .push(format!("edit palette index {} out of range", e[3]));
+                continue;
+            };
+            let block = slot.unwrap_or_else(|| {
+                report.placeholders += 1;
+                placeholder
+            });
+            let pos = (
+                patch.origin[0] + e[0],
+                patch.origin[1] + e[1],
+                patch.origin[2] + e[2],
+            );
+            edits.push((pos, block));
+        }
+        // One undo step and one revision stamp for the whole patch.
+        let batch = self.apply_batch(edits);
+        report.applied = batch.edits;
+        report.affected_chunks = batch.affected_chunks;
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::registry;
+
+    #[test]
+    fn patch_round_trips_by_block_name() {
+        let src_reg = registry(
+            r#"[[blocks]]
+name = "air"
+solid = false
+[[blocks]]
+name = "stone"
+materials = { all = "stone" }
+[[blocks]]
+name = "slab"
+materials = { all = "stone" }
+state_schema = { half = ["bottom", "top"] }
+"#,
+        );
+        // Destination registry orders blocks differently and has no "slab".
+        let dst_reg = registry(
+            r#"[[blocks]]
+name = "air"
+solid = false
+[[blocks]]
+name = "unknown"
+materials = { all = "stone" }
+[[blocks]]
+name = "stone"
+materials = { all = "stone" }
+"#,
+        );
+        let stone = src_reg.make_block_by_name("stone", None).unwrap();
+        let mut props = HashMap::new();
+        props.insert("half".to_string(), "top".to_string());
+        let slab = src_reg.make_block_by_name("slab", Some(&props)).unwrap();
+
+        let mut store = EditStore::new(32, 32, 32);
+        store.set(40, 10, 5, stone);
+        store.set(41, 11, 5, slab);
+        store.set(200, 10, 5, stone); // outside the exported chunk
+        let patch = store.export_patch(&[ChunkCoord::new(1, 0, 0)], &src_reg);
+        assert_eq!(patch.origin, [40, 10, 5]);
+        assert_eq!(patch.edits.len(), 2);
+        assert_eq!(
+            patch.palette[1].state.get("half").map(String::as_str),
+            Some("top")
+        );
+
+        let text = patch.to_toml_string().unwrap();
+        let mut moved = PatchFile::from_toml_str(&text).unwrap();
+        assert_eq!(moved, patch);
+        moved.origin = [0, 64, 0];
+
+        let placeholder = dst_reg.make_block_by_name("unknown", None).unwrap();
+        let mut dst = EditStore::new(32, 32, 32);
+        let report = dst.apply_patch(&moved, &dst_reg, placeholder);
+        assert_eq!(report.applied, 2);
+        assert_eq!(report.placeholders, 1);
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(dst.get(0, 64, 0), dst_reg.make_block_by_name("stone", None));
+        assert_eq!(dst.get(1, 65, 0), Some(placeholder));
+        assert!(report.affected_chunks.contains(&ChunkCoord::new(0, 2, 0)));
+        assert!(dst.get_rev(0, 2, 0) > 0);
+    }
+}
@@ -0,0 +1,328 @@
+//! On-disk savefile for world edits: a directory of binary region files.
+//!
+//! Each region file holds every edited chunk in an `REGION_CHUNKS` x `REGION_CHUNKS` column
+//! footprint (all chunk Y levels), so flushing a dirty chunk rewrites one small file.
+//!
+//! Layout (little endian):
+//! - magic `GEDR`, `u16` version, chunk size `sx, sy, sz` as `u16`, `u64` registry
+//!   fingerprint (version 2+; 0 when unknown), `u32` chunk count
+//! - per chunk: `cx, cy, cz` as `i32`, `u32` edit count
+//! - per edit: `u32` chunk-local index `(ly * sz + lz) * sx + lx`, `u16` block id, `u16` state
+//!
+//! Block ids are stored as-is. Beside the regions, `ids.<fingerprint>.toml` holds the id
+//! table of each registry that wrote them, so `load_from_path_with_registry` can map
+//! regions written under an older registry onto the current one by block name.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use geist_blocks::codec::{
+    invalid, read_array, read_block, read_count, read_i32, read_u16, read_u32, read_u64,
+    write_atomic, write_block,
+};
+use geist_blocks::types::Block;
+use geist_blocks::{BlockIdTable, BlockRegistry, IdMigration, MigrationReport};
+use geist_world::ChunkCoord;
+
+use crate::EditStore;
+
+pub const SAVE_VERSION: u16 = 2;
+/// Chunks per region file along X and Z.
+pub const REGION_CHUNKS: i32 = 8;
+
+const MAGIC: &[u8; 4] = b"GEDR";
+const EXT: &str = "gedit";
+/// Bytes per chunk header (`cx, cy, cz`, edit count) and per edit in a region.
+const CHUNK_HEADER_BYTES: usize = 4 * 4;
+const EDIT_BYTES: usize = 4 + 2 + 2;
+/// File name prefix of the per-registry id tables.
+pub const ID_TABLE_PREFIX: &str = "ids.";
+
+type ChunkEdits = HashMap<(i32, i32, i32), Block>;
+type DecodedChunk = (ChunkCoord, Vec<((i32, i32, i32), Block)>);
+
+fn region_of(coord: ChunkCoord) -> (i32, i32) {
+    (
+        coord.cx.div_euclid(REGION_CHUNKS),
+        coord.cz.div_euclid(REGION_CHUNKS),
+    )
+}
+
+fn region_file(dir: &Path, (rx, rz): (i32, i32)) -> PathBuf {
+    dir.join(format!("r.{}.{}.{}", rx, rz, EXT))
+}
+
+fn parse_region_name(path: &Path) -> Option<(i32, i32)> {
+    let name = path.file_name()?.to_str()?;
+    let rest = name
+        .strip_prefix("r.")?
+        .strip_suffix(&format!(".{}", EXT))?;
+    let (rx, rz) = rest.split_once('.')?;
+    Some((rx.parse().ok()?, rz.parse().ok()?))
+}
+
+fn id_table_file(dir: &Path, fingerprint: u64) -> PathBuf {
+    dir.join(format!("{}{:016x}.toml", ID_TABLE_PREFIX, fingerprint))
+}
+
+fn read_id_table(dir: &Path, fingerprint: u64) -> io::Result<BlockIdTable> {
+    let path = id_table_file(dir, fingerprint);
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(invalid(format!(
+                "no block id table for registry {:016x}",
+                fingerprint
+            )));
+        }
+        Err(e) => return Err(e),
+    };
+    BlockIdTable::from_toml_str(&text).map_err(|e| invalid(format!("{}: {}", path.display(), e)))
+}
+
+fn write_id_table(dir: &Path, reg: &BlockRegistry) -> io::Result<()> {
+    let path = id_table_file(dir, reg.fingerprint());
+    if path.exists() {
+        return Ok(());
+    }
+    let text = BlockIdTable::from_registry(reg)
+        .to_toml_string()
+        .map_err(invalid)?;
+    fs::create_dir_all(dir)?;
+    fs::write(path, text)
+}
+
+impl EditStore {
+    /// Chunks edited since the last save or flush.
+    pub fn dirty_chunks(&self) -> impl Iterator<Item = &ChunkCoord> {
+        self.dirty.iter()
+    }
+
+    #[inline]
+    pub fn has_unsaved_edits(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+
+    /// Forget pending changes, e.g. edits regenerated on every start that need no saving.
+    pub fn clear_dirty(&mut self) {
+        self.dirty.clear();
+    }
+
+    /// Write every region to `dir`, removing region files that no longer hold edits.
+    pub fn save_to_path(&mut self, dir: &Path) -> io::Result<usize> {
+        fs::create_dir_all(dir)?;
+        let regions: HashSet<(i32, i32)> = self.inner.keys().copied().map(region_of).collect();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if let Some(region) = parse_region_name(&path)
+                && !regions.contains(&region)
+            {
+                fs::remove_file(&path)?;
+            }
+        }
+        for region in &regions {
+            self.write_region(dir, *region)?;
+        }
+        self.dirty.clear();
+        Ok(regions.len())
+    }
+
+    /// Rewrite only the regions containing chunks edited since the last save.
+    /// Returns the number of region files written or removed.
+    pub fn flush_dirty(&mut self, dir: &Path) -> io::Result<usize> {
+        if self.dirty.is_empty() {
+            return Ok(0);
+        }
+        fs::create_dir_all(dir)?;
+        let regions: HashSet<(i32, i32)> = self.dirty.iter().copied().map(region_of).collect();
+        for region in &regions {
+            self.write_region(dir, *region)?;
+            self.dirty.retain(|c| region_of(*c) != *region);
+        }
+        Ok(regions.len())
+    }
+
+    /// Merge every region file under `dir` into the store and bump the loaded chunks so
+    /// any that are already built get rebuilt. A missing directory loads nothing. Ids are
+    /// taken as-is; see `load_from_path_with_registry` to remap them.
+    pub fn load_from_path(&mut self, dir: &Path) -> io::Result<Vec<ChunkCoord>> {
+        let regions = self.read_regions(dir)?;
+        Ok(self.merge_loaded(regions.into_iter().flat_map(|(_, chunks)| chunks)))
+    }
+
+    /// Like `load_from_path`, but regions written under a registry other than `reg` are
+    /// remapped by block name through their id table, and names `reg` lacks become its
+    /// placeholder. Migrated regions are rewritten so each migrates once, and later saves
+    /// are stamped with `reg`'s fingerprint.
+    pub fn load_from_path_with_registry(
+        &mut self,
+        dir: &Path,
+        reg: &BlockRegistry,
+    ) -> io::Result<(Vec<ChunkCoord>, MigrationReport)> {
+        let current = reg.fingerprint();
+        self.registry_fingerprint = current;
+        let regions = self.read_regions(dir)?;
+        let mut migrations: HashMap<u64, IdMigration> = HashMap::new();
+        for (fp, _) in &regions {
+            // Regions from before fingerprints were saved are taken as current.
+            if *fp != 0 && *fp != current && !migrations.contains_key(fp) {
+                let table = read_id_table(dir, *fp)?;
+                migrations.insert(*fp, IdMigration::new(&table, reg));
+            }
+        }
+        let mut migrated: Vec<ChunkCoord> = Vec::new();
+        let mut chunks: Vec<DecodedChunk> = Vec::new();
+        for (fp, region) in regions {
+            let Some(m) = migrations.get_mut(&fp) else {
+                chunks.extend(region);
+                continue;
+            };
+            for (coord, edits) in region {
+                migrated.push(coord);
+                let edits = edits.into_iter().map(|(p, b)| (p, m.apply(b))).collect();
+                chunks.push((coord, edits));
+            }
+        }
+        let mut report = MigrationReport::default();
+        for m in migrations.into_values() {
+            report.merge(m.into_report());
+        }
+        let loaded = self.merge_loaded(chunks);
+        // Before any region is stamped with `current`, so every stamp has a table.
+        write_id_table(dir, reg)?;
+        if !migrated.is_empty() {
+            self.dirty.extend(migrated);
+            self.flush_dirty(dir)?;
+        }
+        Ok((loaded, report))
+    }
+
+    fn read_regions(&self, dir: &Path) -> io::Result<Vec<(u64, Vec<DecodedChunk>)>> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut regions = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if parse_region_name(&path).is_none() {
+                continue;
+            }
+            let bytes = fs::read(&path)?;
+            let region = self
+                .decode_region(&bytes)
+                .map_err(|e| invalid(format!("{}: {}", path.display(), e)))?;
+            regions.push(region);
+        }
+        Ok(regions)
+    }
+
+    fn merge_loaded(&mut self, chunks: impl IntoIterator<Item = DecodedChunk>) -> Vec<ChunkCoord> {
+        let mut loaded: Vec<ChunkCoord> = Vec::new();
+        for (coord, edits) in chunks {
+            self.inner.entry(coord).or_default().extend(edits);
+            loaded.push(coord);
+        }
+        if !loaded.is_empty() {
+            self.counter = self.counter.wrapping_add(1).max(1);
+            for coord in &loaded {
+                self.rev.insert(*coord, self.counter);
+            }
+        }
+        loaded.sort_by_key(|c| (c.cy, c.cz, c.cx));
+        loaded
+    }
+
+    fn write_region(&self, dir: &Path, region: (i32, i32)) -> io::Result<()> {
+        let path = region_file(dir, region);
+        let chunks: BTreeMap<(i32, i32, i32), &ChunkEdits> = self
+            .inner
+            .iter()
+            .filter(|(c, m)| region_of(**c) == region && !m.is_empty())
+            .map(|(c, m)| ((c.cy, c.cz, c.cx), m))
+            .collect();
+        if chunks.is_empty() {
+            return match fs::remove_file(&path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            };
+        }
+        let mut out: Vec<u8> = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&SAVE_VERSION.to_le_bytes());
+        for dim in [self.sx, self.sy, self.sz] {
+            out.extend_from_slice(&(dim as u16).to_le_bytes());
+        }
+        out.extend_from_slice(&self.registry_fingerprint.to_le_bytes());
+        out.extend_from_slice(&(chunks.len() as u32).to_le_bytes());
+        for ((cy, cz, cx), edits) in chunks {
+            for v in [cx, cy, cz] {
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            out.extend_from_slice(&(edits.len() as u32).to_le_bytes());
+            let (x0, y0, z0) = (cx * self.sx, cy * self.sy, cz * self.sz);
+            let mut packed: Vec<(u32, Block)> = edits
+                .iter()
+                .map(|((wx, wy, wz), b)| {
+                    let (lx, ly, lz) = (wx - x0, wy - y0, wz - z0);
+                    (((ly * self.sz + lz) * self.sx + lx) as u32, *b)
+                })
+                .collect();
+            packed.sort_by_key(|(i, _)| *i);
+            for (i, b) in packed {
+                out.extend_from_slice(&i.to_le_bytes());
+                write_block(&mut out, b);
+            }
+        }
+        write_atomic(&path, &out)
+    }
+
+    fn decode_region(&self, bytes: &[u8]) -> io::Result<(u64, Vec<DecodedChunk>)> {
+        let mut r = bytes;
+        if &read_array::<4>(&mut r)? != MAGIC {
+            return Err(invalid("not an edit region file"));
+        }
+        let version = read_u16(&mut r)?;
+        if version > SAVE_VERSION {
+            return Err(invalid(format!(
+                "region version {} is newer than supported {}",
+                version, SAVE_VERSION
+            )));
+        }
+        let dims = [read_u16(&mut r)?, read_u16(&mut r)?, read_u16(&mut r)?];
+        if dims.map(i32::from) != [self.sx, self.sy, self.sz] {
+            return Err(invalid(format!(
+                "chunk size {:?} does not match the world's {:?}",
+                dims,
+                [self.sx, self.sy, self.sz]
+            )));
+        }
+        let fingerprint = if version >= 2 { read_u64(&mut r)? } else { 0 };
+        let volume = (self.sx * self.sy * self.sz) as u32;
+        let count = read_count(&mut r, CHUNK_HEADER_BYTES)?;
+        let mut chunks = Vec::with_capacity(count);
+        for _ in 0..count {
+            let coord = ChunkCoord::new(read_i32(&mut r)?, read_i32(&mut r)?, read_i32(&mut r)?);
+            let n = read_count(&mut r, EDIT_BYTES)?;
+            let (x0, y0, z0) = (coord.cx * self.sx, coord.cy * self.sy, coord.cz * self.sz);
+            let mut edits = Vec::with_capacity(n);
+            for _ in 0..n {
+                let i = read_u32(&mut r)?;
+                if i >= volume {
+                    return Err(invalid(format!("edit index {} outside chunk", i)));
+                }
+                let b = read_block(&mut r)?;
+                let i = i as i32;
+                let lx = i % self.sx;
+                let lz = (i / self.sx) % self.sz;
+                let ly = i / (self.sx * self.sz);
+                edits.push(((x0 + lx, y0 + ly, z0 + lz), b));
+            }
+            chunks.push((coord, edits));
+        }
+        Ok((fingerprint, chunks))
+    }
+}
@@ -0,0 +1,172 @@
+//! Edit savefile round trips, corruption checks and id migration over a scratch directory.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use geist_blocks::types::Block;
+use geist_blocks::{BlockRegistry, MaterialCatalog};
+use geist_edit::EditStore;
+
+fn make_store() -> EditStore {
+    EditStore::new(32, 32, 32)
+}
+
+fn registry(blocks: &str) -> BlockRegistry {
+    let materials =
+        MaterialCatalog::from_toml_str("[materials]\nstone = [\"assets/blocks/stone.png\"]\n")
+            .expect("materials");
+    let cfg: geist_blocks::config::BlocksConfig = toml::from_str(blocks).expect("blocks");
+    BlockRegistry::from_configs(materials, cfg).expect("registry")
+}
+
+fn temp_save_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("geist-edit-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn savefile_round_trips_edits_across_regions() {
+    let dir = temp_save_dir("roundtrip");
+    let mut store = make_store();
+    let stone = Block { id: 3, state: 0 };
+    let slab = Block { id: 7, state: 5 };
+    store.set(1, 2, 3, stone);
+    store.set(-1, -40, -1, slab);
+    store.set(32 * 9 + 4, 70, 31, stone);
+    assert!(store.has_unsaved_edits());
+    assert_eq!(store.save_to_path(&dir).unwrap(), 3);
+    assert!(!store.has_unsaved_edits());
+
+    let mut loaded = make_store();
+    let chunks = loaded.load_from_path(&dir).unwrap();
+    assert_eq!(chunks.len(), 3);
+    assert_eq!(loaded.get(1, 2, 3), Some(stone));
+    assert_eq!(loaded.get(-1, -40, -1), Some(slab));
+    assert_eq!(loaded.get(32 * 9 + 4, 70, 31), Some(stone));
+    assert!(loaded.needs_rebuild(0, 0, 0));
+    assert!(!loaded.has_unsaved_edits());
+
+    let mut mismatched = EditStore::new(16, 16, 16);
+    assert!(mismatched.load_from_path(&dir).is_err());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn corrupt_region_counts_fail_before_reserving() {
+    let dir = temp_save_dir("counts");
+    let mut store = make_store();
+    store.set(1, 2, 3, Block { id: 3, state: 0 });
+    store.save_to_path(&dir).unwrap();
+    let path = dir.join("r.0.0.gedit");
+    let good = fs::read(&path).unwrap();
+    // Header: magic, version, chunk size, fingerprint; then the chunk count, and after
+    // the first chunk's coordinates its edit count.
+    let chunk_count = 4 + 2 + 3 * 2 + 8;
+    let edit_count = chunk_count + 4 + 3 * 4;
+    for at in [chunk_count, edit_count] {
+        let mut bytes = good.clone();
+        bytes[at..at + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        fs::write(&path, &bytes).unwrap();
+        let err = make_store().load_from_path(&dir).unwrap_err();
+        assert!(err.to_string().contains("bytes left"), "{}", err);
+    }
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn flush_dirty_rewrites_only_touched_regions() {
+    let dir = temp_save_dir("flush");
+    let mut store = make_store();
+    let a = Block { id: 1, state: 0 };
+    let b = Block { id: 2, state: 0 };
+    store.set(0, 0, 0, a);
+    store.set(32 * 8, 0, 0, a);
+    assert_eq!(store.flush_dirty(&dir).unwrap(), 2);
+    assert_eq!(store.flush_dirty(&dir).unwrap(), 0);
+
+    store.set(5, 5, 5, b);
+    assert_eq!(store.dirty_chunks().count(), 1);
+    assert_eq!(store.flush_dirty(&dir).unwrap(), 1);
+
+    let mut loaded = make_store();
+    loaded.load_from_path(&dir).unwrap();
+    assert_eq!(loaded.get(0, 0, 0), Some(a));
+    assert_eq!(loaded.get(5, 5, 5), Some(b));
+    assert_eq!(loaded.get(32 * 8, 0, 0), Some(a));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn savefile_migrates_ids_from_an_older_registry() {
+    let dir = temp_save_dir("migrate");
+    let old_reg = registry(
+        r#"[[blocks]]
+name = "air"
+solid = false
+[[blocks]]
+name = "stone"
+materials = { all = "stone" }
+[[blocks]]
+name = "slab"
+materials = { all = "stone" }
+state_schema = { half = ["bottom", "top"] }
+[[blocks]]
+name = "marble"
+materials = { all = "stone" }
+"#,
+    );
+    // Blocks reordered, "marble" removed and the slab gained a property.
+    let new_reg = registry(
+        r#"[[blocks]]
+name = "air"
+solid = false
+[[blocks]]
+name = "unknown"
+materials = { all = "stone" }
+[[blocks]]
+name = "slab"
+materials = { all = "stone" }
+state_schema = { half = ["bottom", "top"], axis = ["x", "z"] }
+[[blocks]]
+name = "stone"
+materials = { all = "stone" }
+"#,
+    );
+    assert_ne!(old_reg.fingerprint(), new_reg.fingerprint());
+    let top = HashMap::from([("half".to_string(), "top".to_string())]);
+    let old_slab = old_reg.make_block_by_name("slab", Some(&top)).unwrap();
+    let mut store = make_store();
+    store.load_from_path_with_registry(&dir, &old_reg).unwrap();
+    store.set(1, 1, 1, old_reg.make_block_by_name("stone", None).unwrap());
+    store.set(2, 1, 1, old_slab);
+    store.set(3, 1, 1, old_reg.make_block_by_name("marble", None).unwrap());
+    store.save_to_path(&dir).unwrap();
+
+    let mut loaded = make_store();
+    let (chunks, report) = loaded.load_from_path_with_registry(&dir, &new_reg).unwrap();
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(report.remapped, 2);
+    assert_eq!(report.missing.get("marble"), Some(&1));
+    assert_eq!(
+        loaded.get(1, 1, 1),
+        new_reg.make_block_by_name("stone", None)
+    );
+    let slab = loaded.get(2, 1, 1).unwrap();
+    assert_eq!(
+        slab,
+        new_reg.make_block_by_name("slab", Some(&top)).unwrap()
+    );
+    assert_eq!(
+        loaded.get(3, 1, 1),
+        new_reg.make_block_by_name("unknown", None)
+    );
+
+    // The region was rewritten under the new registry, so it loads without remapping.
+    let mut again = make_store();
+    let (_, report) = again.load_from_path_with_registry(&dir, &new_reg).unwrap();
+    assert!(report.is_empty());
+    assert_eq!(again.get(2, 1, 1), Some(slab));
+    let _ = fs::remove_dir_all(&dir);
+}
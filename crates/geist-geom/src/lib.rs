@@ -3,6 +3,9 @@
 
 use core::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
 
+mod voxel;
+pub use voxel::{ChunkPos, IVec3, LocalPos, WorldPos};
+
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 #[cfg_attr(test, derive(proptest_derive::Arbitrary))]
 pub struct Vec3 {
@@ -117,3 +120,100 @@ impl Aabb {
         Self { min, max }
     }
 }
+
+/// Unit quaternion rotation (`w` is the scalar part).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Default for Quat {
+    #[inline]
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Quat {
+    pub const IDENTITY: Quat = Quat {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+        w: 1.0,
+    };
+
+    /// Right-handed rotation of `radians` about `axis` (normalized here).
+    #[inline]
+    pub fn from_axis_angle(axis: Vec3, radians: f32) -> Self {
+        let a = axis.normalized();
+        let (s, c) = (radians * 0.5).sin_cos();
+        Self {
+            x: a.x * s,
+            y: a.y * s,
+            z: a.z * s,
+            w: c,
+        }
+    }
+
+    /// Inverse rotation, valid for unit quaternions.
+    #[inline]
+    pub fn conjugate(self) -> Self {
+        Self {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+            w: self.w,
+        }
+    }
+
+    #[inline]
+    pub fn normalized(self) -> Self {
+        let len = (self.x * self.x + self.y * self.y + self.z * self.z + self.w * self.w).sqrt();
+        if len > 0.0 {
+            Self {
+                x: self.x / len,
+                y: self.y / len,
+                z: self.z / len,
+                w: self.w / len,
+            }
+        } else {
+            Self::IDENTITY
+        }
+    }
+
+    #[inline]
+    pub fn rotate(self, v: Vec3) -> Vec3 {
+        // v' = v + 2w(q × v) + 2q × (q × v)
+        let q = Vec3::new(self.x, self.y, self.z);
+        let t = q.cross(v) * 2.0;
+        v + t * self.w + q.cross(t)
+    }
+
+    /// Axis and angle in radians; the axis is +Y for the identity.
+    pub fn to_axis_angle(self) -> (Vec3, f32) {
+        let q = self.normalized();
+        let w = q.w.clamp(-1.0, 1.0);
+        let s = (1.0 - w * w).sqrt();
+        if s < 1e-6 {
+            return (Vec3::UP, 0.0);
+        }
+        (Vec3::new(q.x / s, q.y / s, q.z / s), 2.0 * w.acos())
+    }
+}
+
+impl Mul for Quat {
+    type Output = Quat;
+    /// Composition: `(a * b).rotate(v) == a.rotate(b.rotate(v))`.
+    #[inline]
+    fn mul(self, r: Quat) -> Quat {
+        Quat {
+            x: self.w * r.x + self.x * r.w + self.y * r.z - self.z * r.y,
+            y: self.w * r.y - self.x * r.z + self.y * r.w + self.z * r.x,
+            z: self.w * r.z + self.x * r.y - self.y * r.x + self.z * r.w,
+            w: self.w * r.w - self.x * r.x - self.y * r.y - self.z * r.z,
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Integer voxel coordinates and the world/chunk/local conversions between them.
+//!
+//! Negative world coordinates round towards negative infinity (`div_euclid`/`rem_euclid`),
+//! so voxel -1 lives in chunk -1 at local `size - 1`, not in chunk 0.
+
+use core::ops::{Add, Sub};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct IVec3 {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl IVec3 {
+    pub const ZERO: IVec3 = IVec3 { x: 0, y: 0, z: 0 };
+
+    #[inline]
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Chunk dimensions as stored by worlds and buffers.
+    #[inline]
+    pub const fn from_dims(sx: usize, sy: usize, sz: usize) -> Self {
+        Self::new(sx as i32, sy as i32, sz as i32)
+    }
+
+    #[inline]
+    pub fn div_euclid(self, size: IVec3) -> IVec3 {
+        IVec3::new(
+            self.x.div_euclid(size.x),
+            self.y.div_euclid(size.y),
+            self.z.div_euclid(size.z),
+        )
+    }
+
+    #[inline]
+    pub fn rem_euclid(self, size: IVec3) -> IVec3 {
+        IVec3::new(
+            self.x.rem_euclid(size.x),
+            self.y.rem_euclid(size.y),
+            self.z.rem_euclid(size.z),
+        )
+    }
+
+    #[inline]
+    pub fn mul_elem(self, rhs: IVec3) -> IVec3 {
+        IVec3::new(self.x * rhs.x, self.y * rhs.y, self.z * rhs.z)
+    }
+}
+
+impl Add for IVec3 {
+    type Output = IVec3;
+    #[inline]
+    fn add(self, rhs: IVec3) -> IVec3 {
+        IVec3::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl Sub for IVec3 {
+    type Output = IVec3;
+    #[inline]
+    fn sub(self, rhs: IVec3) -> IVec3 {
+        IVec3::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl From<(i32, i32, i32)> for IVec3 {
+    #[inline]
+    fn from((x, y, z): (i32, i32, i32)) -> Self {
+        IVec3::new(x, y, z)
+    }
+}
+
+impl From<IVec3> for (i32, i32, i32) {
+    #[inline]
+    fn from(v: IVec3) -> Self {
+        (v.x, v.y, v.z)
+    }
+}
+
+/// A voxel in world space.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct WorldPos(pub IVec3);
+
+/// A chunk index: chunk `c` covers world voxels `c * size .. (c + 1) * size`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ChunkPos(pub IVec3);
+
+/// A voxel relative to its chunk's origin, each component in `0..size`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LocalPos(pub IVec3);
+
+impl WorldPos {
+    #[inline]
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        Self(IVec3::new(x, y, z))
+    }
+
+    #[inline]
+    pub fn chunk(self, size: IVec3) -> ChunkPos {
+        ChunkPos(self.0.div_euclid(size))
+    }
+
+    #[inline]
+    pub fn local(self, size: IVec3) -> LocalPos {
+        LocalPos(self.0.rem_euclid(size))
+    }
+
+    #[inline]
+    pub fn split(self, size: IVec3) -> (ChunkPos, LocalPos) {
+        (self.chunk(size), self.local(size))
+    }
+}
+
+impl ChunkPos {
+    #[inline]
+    pub const fn new(cx: i32, cy: i32, cz: i32) -> Self {
+        Self(IVec3::new(cx, cy, cz))
+    }
+
+    /// World position of local (0, 0, 0).
+    #[inline]
+    pub fn origin(self, size: IVec3) -> WorldPos {
+        WorldPos(self.0.mul_elem(size))
+    }
+
+    #[inline]
+    pub fn world(self, local: LocalPos, size: IVec3) -> WorldPos {
+        WorldPos(self.0.mul_elem(size) + local.0)
+    }
+}
+
+impl LocalPos {
+    #[inline]
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        Self(IVec3::new(x, y, z))
+    }
+
+    #[inline]
+    pub fn in_bounds(self, size: IVec3) -> bool {
+        let v = self.0;
+        (0..size.x).contains(&v.x) && (0..size.y).contains(&v.y) && (0..size.z).contains(&v.z)
+    }
+
+    /// Index into a chunk's `(y * sz + z) * sx + x` block array. Callers must check
+    /// `in_bounds` first.
+    #[inline]
+    pub fn index(self, size: IVec3) -> usize {
+        let v = self.0;
+        ((v.y * size.z + v.z) * size.x + v.x) as usize
+    }
+
+    #[inline]
+    pub fn to_usize(self) -> (usize, usize, usize) {
+        (self.0.x as usize, self.0.y as usize, self.0.z as usize)
+    }
+}
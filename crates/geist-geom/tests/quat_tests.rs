@@ -0,0 +1,45 @@
+use geist_geom::{Quat, Vec3};
+
+fn vec3_approx_eq(a: Vec3, b: Vec3, eps: f32) -> bool {
+    (a.x - b.x).abs() <= eps && (a.y - b.y).abs() <= eps && (a.z - b.z).abs() <= eps
+}
+
+#[test]
+fn quat_axis_rotations_follow_right_hand_rule() {
+    let half_pi = std::f32::consts::FRAC_PI_2;
+    let about_y = Quat::from_axis_angle(Vec3::UP, half_pi);
+    assert!(vec3_approx_eq(
+        about_y.rotate(Vec3::new(1.0, 0.0, 0.0)),
+        Vec3::new(0.0, 0.0, -1.0),
+        1e-6
+    ));
+    let about_x = Quat::from_axis_angle(Vec3::new(1.0, 0.0, 0.0), half_pi);
+    assert!(vec3_approx_eq(
+        about_x.rotate(Vec3::new(0.0, 1.0, 0.0)),
+        Vec3::new(0.0, 0.0, 1.0),
+        1e-6
+    ));
+    assert!(vec3_approx_eq(
+        Quat::IDENTITY.rotate(Vec3::new(3.0, -2.0, 1.0)),
+        Vec3::new(3.0, -2.0, 1.0),
+        1e-6
+    ));
+}
+
+#[test]
+fn quat_composition_and_inverse() {
+    let a = Quat::from_axis_angle(Vec3::new(1.0, 2.0, 0.5), 0.7);
+    let b = Quat::from_axis_angle(Vec3::new(-0.3, 0.0, 1.0), -1.9);
+    let v = Vec3::new(0.25, -4.0, 7.5);
+    assert!(vec3_approx_eq(
+        (a * b).rotate(v),
+        a.rotate(b.rotate(v)),
+        1e-4
+    ));
+    assert!(vec3_approx_eq(a.conjugate().rotate(a.rotate(v)), v, 1e-4));
+
+    let (axis, angle) = a.to_axis_angle();
+    let back = Quat::from_axis_angle(axis, angle);
+    assert!(vec3_approx_eq(back.rotate(v), a.rotate(v), 1e-4));
+    assert_eq!(Quat::IDENTITY.to_axis_angle(), (Vec3::UP, 0.0));
+}
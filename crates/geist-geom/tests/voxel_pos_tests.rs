@@ -0,0 +1,38 @@
+use geist_geom::{ChunkPos, IVec3, LocalPos, WorldPos};
+
+const SIZE: IVec3 = IVec3::new(32, 32, 32);
+
+#[test]
+fn negative_world_coords_floor_into_previous_chunk() {
+    let (c, l) = WorldPos::new(-1, -32, -33).split(SIZE);
+    assert_eq!(c, ChunkPos::new(-1, -1, -2));
+    assert_eq!(l, LocalPos::new(31, 0, 31));
+}
+
+#[test]
+fn chunk_origin_and_world_round_trip() {
+    let dims = IVec3::new(16, 64, 8);
+    for &(x, y, z) in &[
+        (0, 0, 0),
+        (15, 63, 7),
+        (16, 64, 8),
+        (-1, -1, -1),
+        (-17, 130, -9),
+    ] {
+        let w = WorldPos::new(x, y, z);
+        let (c, l) = w.split(dims);
+        assert!(l.in_bounds(dims));
+        assert_eq!(c.world(l, dims), w);
+        assert_eq!(WorldPos(c.origin(dims).0 + l.0), w);
+    }
+}
+
+#[test]
+fn local_index_matches_chunk_layout() {
+    let dims = IVec3::from_dims(4, 3, 5);
+    let l = LocalPos::new(1, 2, 3);
+    assert_eq!(l.index(dims), (2 * 5 + 3) * 4 + 1);
+    assert_eq!(l.to_usize(), (1, 2, 3));
+    assert!(!LocalPos::new(4, 0, 0).in_bounds(dims));
+    assert!(!LocalPos::new(0, -1, 0).in_bounds(dims));
+}
@@ -0,0 +1,446 @@
+//! On-disk cache of built chunk buffers, so streamed chunks load instead of regenerating.
+//!
+//! Chunks are grouped into region files covering `CACHE_REGION_CHUNKS` x
+//! `CACHE_REGION_CHUNKS` columns (all chunk Y levels). `store` encodes a buffer right away
+//! and queues it; a writer thread folds queued chunks into their regions in batches, so
+//! callers never wait on disk. Queued chunks are served to `load` before they land.
+//!
+//! Layout (little endian):
+//! - magic `GCHK`, `u16` version, chunk size `sx, sy, sz` as `u16`, `u64` cache key,
+//!   `u32` chunk count
+//! - index, per chunk: `cx, cy, cz` as `i32`, `u32` byte offset from the file start,
+//!   `u32` byte length
+//! - per chunk: `u32` run count, then runs of `u32` length, `u16` block id, `u16` state
+//!   over the chunk's blocks in `ChunkBuf` order
+//!
+//! The key stands for everything the cached blocks depend on (seed, worldgen settings,
+//! block registry); regions written under another key or chunk size read as empty and are
+//! replaced on their next write.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use geist_blocks::codec::{
+    invalid, read_array, read_block, read_i32, read_u16, read_u32, read_u64, write_atomic,
+    write_block,
+};
+use geist_blocks::stable_hash::Fnv1a;
+use geist_blocks::types::Block;
+use geist_chunk::ChunkBuf;
+use geist_world::ChunkCoord;
+
+pub const CHUNK_CACHE_VERSION: u16 = 1;
+/// Chunks per cache region file along X and Z.
+pub const CACHE_REGION_CHUNKS: i32 = 8;
+
+const MAGIC: &[u8; 4] = b"GCHK";
+const EXT: &str = "gchunk";
+const HEADER_BYTES: usize = 4 + 2 + 3 * 2 + 8 + 4;
+const INDEX_ENTRY_BYTES: usize = 3 * 4 + 4 + 4;
+/// Longest a stored chunk waits before the writer flushes it.
+const WRITE_DELAY: Duration = Duration::from_secs(2);
+/// Queued chunks that trigger a flush without waiting for `WRITE_DELAY`.
+const WRITE_BATCH: usize = 256;
+
+fn region_of(coord: ChunkCoord) -> (i32, i32) {
+    (
+        coord.cx.div_euclid(CACHE_REGION_CHUNKS),
+        coord.cz.div_euclid(CACHE_REGION_CHUNKS),
+    )
+}
+
+fn region_file(dir: &Path, (rx, rz): (i32, i32)) -> PathBuf {
+    dir.join(format!("c.{}.{}.{}", rx, rz, EXT))
+}
+
+fn is_region_file(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some(EXT)
+        && path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with("c."))
+}
+
+/// Stable key from the inputs cached chunks depend on, for [`ChunkCache::open`].
+pub fn chunk_cache_key(parts: &[&[u8]]) -> u64 {
+    let mut h = Fnv1a::new();
+    for part in parts {
+        h.write(part);
+        h.write(&[0xff]);
+    }
+    h.finish()
+}
+
+/// Run-length encode a chunk's blocks.
+fn encode_blocks(blocks: &[Block]) -> Vec<u8> {
+    let mut runs: Vec<(u32, Block)> = Vec::new();
+    for b in blocks {
+        match runs.last_mut() {
+            Some((n, last)) if last == b => *n += 1,
+            _ => runs.push((1, *b)),
+        }
+    }
+    let mut out = Vec::with_capacity(4 + runs.len() * 8);
+    out.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+    for (n, b) in runs {
+        out.extend_from_slice(&n.to_le_bytes());
+        write_block(&mut out, b);
+    }
+    out
+}
+
+fn decode_blocks(mut r: &[u8], volume: usize) -> io::Result<Vec<Block>> {
+    let runs = read_u32(&mut r)?;
+    let mut blocks = Vec::with_capacity(volume);
+    for _ in 0..runs {
+        let n = read_u32(&mut r)? as usize;
+        let b = read_block(&mut r)?;
+        if blocks.len() + n > volume {
+            return Err(invalid("block runs overflow the chunk"));
+        }
+        blocks.resize(blocks.len() + n, b);
+    }
+    if blocks.len() != volume {
+        return Err(invalid(format!(
+            "block runs cover {} of {} voxels",
+            blocks.len(),
+            volume
+        )));
+    }
+    Ok(blocks)
+}
+
+/// Counters since the cache was opened.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChunkCacheStats {
+    /// `load` calls answered from the cache.
+    pub hits: u64,
+    /// `load` calls that found nothing, leaving the chunk to be generated.
+    pub misses: u64,
+    /// Chunks written to region files.
+    pub written: u64,
+    /// Chunks stored but not yet written.
+    pub pending: usize,
+}
+
+struct Shared {
+    dir: PathBuf,
+    dims: (usize, usize, usize),
+    key: u64,
+    // Encoded chunks waiting for the writer.
+    pending: Mutex<HashMap<ChunkCoord, Arc<Vec<u8>>>>,
+    // Readers share region files; flushes and `clear` rewrite them.
+    files: RwLock<()>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    written: AtomicU64,
+}
+
+type QueuedChunk = (ChunkCoord, Arc<Vec<u8>>);
+
+enum Msg {
+    Stored,
+    Flush(mpsc::Sender<io::Result<usize>>),
+}
+
+/// Region-file cache of `ChunkBuf`s, shared by every build worker.
+///
+/// Dropping the cache flushes whatever is still queued.
+pub struct ChunkCache {
+    shared: Arc<Shared>,
+    tx: Option<mpsc::Sender<Msg>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl ChunkCache {
+    /// Open (creating if needed) the cache in `dir` for chunks of `sx`x`sy`x`sz` blocks.
+    /// Regions written under a key other than `key` are ignored.
+    pub fn open(
+        dir: impl Into<PathBuf>,
+        (sx, sy, sz): (usize, usize, usize),
+        key: u64,
+    ) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let shared = Arc::new(Shared {
+            dir,
+            dims: (sx, sy, sz),
+            key,
+            pending: Mutex::new(HashMap::new()),
+            files: RwLock::new(()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            written: AtomicU64::new(0),
+        });
+        let (tx, rx) = mpsc::channel::<Msg>();
+        let writer = {
+            let shared = Arc::clone(&shared);
+            thread::Builder::new()
+                .name("geist-chunk-cache".to_string())
+                .spawn(move || shared.run_writer(rx))?
+        };
+        Ok(Self {
+            shared,
+            tx: Some(tx),
+            writer: Some(writer),
+        })
+    }
+
+    #[inline]
+    pub fn dir(&self) -> &Path {
+        &self.shared.dir
+    }
+
+    /// The cached buffer for `coord`, or `None` when it has to be generated. Unreadable
+    /// regions are logged and treated as missing.
+    pub fn load(&self, coord: ChunkCoord) -> Option<ChunkBuf> {
+        let shared = &self.shared;
+        let queued = shared.pending.lock().unwrap().get(&coord).cloned();
+        let blob = match queued {
+            Some(blob) => Some(blob.to_vec()),
+            None => shared.read_chunk(coord).unwrap_or_else(|e| {
+                log::warn!("chunk cache: reading {:?} failed: {}", coord, e);
+                None
+            }),
+        };
+        let (sx, sy, sz) = shared.dims;
+        let buf = blob.and_then(|blob| match decode_blocks(&blob, sx * sy * sz) {
+            Ok(blocks) => Some(ChunkBuf::from_blocks_local(coord, sx, sy, sz, blocks)),
+            Err(e) => {
+                log::warn!("chunk cache: {:?} is corrupt: {}", coord, e);
+                None
+            }
+        });
+        let counter = if buf.is_some() {
+            &shared.hits
+        } else {
+            &shared.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        buf
+    }
+
+    /// Queue `buf` for writing; the writer thread puts it on disk shortly.
+    pub fn store(&self, buf: &ChunkBuf) {
+        let blob = Arc::new(encode_blocks(&buf.blocks));
+        self.shared.pending.lock().unwrap().insert(buf.coord, blob);
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(Msg::Stored);
+        }
+    }
+
+    /// Write every queued chunk now. Returns the number of region files written.
+    pub fn flush(&self) -> io::Result<usize> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        if let Some(tx) = &self.tx
+            && tx.send(Msg::Flush(reply_tx)).is_ok()
+            && let Ok(result) = reply_rx.recv()
+        {
+            return result;
+        }
+        self.shared.flush()
+    }
+
+    /// Drop queued chunks and delete every region file, e.g. after worldgen settings
+    /// changed underneath the cached blocks. Returns the number of files removed.
+    pub fn clear(&self) -> io::Result<usize> {
+        let _files = self.shared.files.write().unwrap();
+        self.shared.pending.lock().unwrap().clear();
+        let mut removed = 0;
+        for entry in fs::read_dir(&self.shared.dir)? {
+            let path = entry?.path();
+            if is_region_file(&path) {
+                fs::remove_file(&path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    pub fn stats(&self) -> ChunkCacheStats {
+        let shared = &self.shared;
+        ChunkCacheStats {
+            hits: shared.hits.load(Ordering::Relaxed),
+            misses: shared.misses.load(Ordering::Relaxed),
+            written: shared.written.load(Ordering::Relaxed),
+            pending: shared.pending.lock().unwrap().len(),
+        }
+    }
+}
+
+impl Drop for ChunkCache {
+    fn drop(&mut self) {
+        // Disconnecting the channel makes the writer flush and exit.
+        self.tx.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+impl Shared {
+    fn run_writer(&self, rx: mpsc::Receiver<Msg>) {
+        let mut last_flush = Instant::now();
+        loop {
+            match rx.recv_timeout(WRITE_DELAY) {
+                Ok(Msg::Stored) => {
+                    let queued = self.pending.lock().unwrap().len();
+                    if queued < WRITE_BATCH && last_flush.elapsed() < WRITE_DELAY {
+                        continue;
+                    }
+                }
+                Ok(Msg::Flush(reply)) => {
+                    let _ = reply.send(self.flush());
+                    last_flush = Instant::now();
+                    continue;
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.flush_logged();
+                    return;
+                }
+            }
+            self.flush_logged();
+            last_flush = Instant::now();
+        }
+    }
+
+    fn flush_logged(&self) {
+        if let Err(e) = self.flush() {
+            log::error!("chunk cache: write to {:?} failed: {}", self.dir, e);
+        }
+    }
+
+    fn flush(&self) -> io::Result<usize> {
+        let _files = self.files.write().unwrap();
+        let queued: Vec<QueuedChunk> = self
+            .pending
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(c, blob)| (*c, Arc::clone(blob)))
+            .collect();
+        let mut regions: BTreeMap<(i32, i32), Vec<QueuedChunk>> = BTreeMap::new();
+        for (coord, blob) in queued {
+            regions
+                .entry(region_of(coord))
+                .or_default()
+                .push((coord, blob));
+        }
+        for (region, chunks) in &regions {
+            self.write_region(*region, chunks)?;
+            let mut pending = self.pending.lock().unwrap();
+            for (coord, blob) in chunks {
+                // Stored again while writing: keep the newer buffer queued.
+                if pending.get(coord).is_some_and(|p| Arc::ptr_eq(p, blob)) {
+                    pending.remove(coord);
+                }
+            }
+            self.written
+                .fetch_add(chunks.len() as u64, Ordering::Relaxed);
+        }
+        Ok(regions.len())
+    }
+
+    /// Header check shared by reads and rewrites; `Ok(None)` for regions written under
+    /// another key or chunk size.
+    fn read_index(&self, r: &mut impl Read) -> io::Result<Option<Vec<(ChunkCoord, u32, u32)>>> {
+        if &read_array::<4>(r)? != MAGIC {
+            return Err(invalid("not a chunk cache region"));
+        }
+        let version = read_u16(r)?;
+        let dims = [read_u16(r)?, read_u16(r)?, read_u16(r)?].map(usize::from);
+        let key = read_u64(r)?;
+        if version != CHUNK_CACHE_VERSION
+            || dims != [self.dims.0, self.dims.1, self.dims.2]
+            || key != self.key
+        {
+            return Ok(None);
+        }
+        let count = read_u32(r)?;
+        let mut index = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let coord = ChunkCoord::new(read_i32(r)?, read_i32(r)?, read_i32(r)?);
+            index.push((coord, read_u32(r)?, read_u32(r)?));
+        }
+        Ok(Some(index))
+    }
+
+    fn read_chunk(&self, coord: ChunkCoord) -> io::Result<Option<Vec<u8>>> {
+        let _files = self.files.read().unwrap();
+        let path = region_file(&self.dir, region_of(coord));
+        let mut f = match fs::File::open(&path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let Some(index) = self.read_index(&mut f)? else {
+            return Ok(None);
+        };
+        let Some(&(_, offset, len)) = index.iter().find(|(c, _, _)| *c == coord) else {
+            return Ok(None);
+        };
+        f.seek(SeekFrom::Start(u64::from(offset)))?;
+        let mut blob = vec![0u8; len as usize];
+        f.read_exact(&mut blob)?;
+        Ok(Some(blob))
+    }
+
+    /// Rewrite `region` with `chunks` on top of the chunks it already holds.
+    fn write_region(&self, region: (i32, i32), chunks: &[QueuedChunk]) -> io::Result<()> {
+        let path = region_file(&self.dir, region);
+        let mut merged: BTreeMap<(i32, i32, i32), Vec<u8>> = BTreeMap::new();
+        match fs::read(&path) {
+            Ok(bytes) => match self.read_index(&mut bytes.as_slice()) {
+                Ok(Some(index)) => {
+                    for (c, offset, len) in index {
+                        let blob = bytes
+                            .get(offset as usize..offset as usize + len as usize)
+                            .ok_or_else(|| invalid(format!("{}: truncated", path.display())))?;
+                        merged.insert((c.cy, c.cz, c.cx), blob.to_vec());
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => log::warn!(
+                    "chunk cache: replacing unreadable {}: {}",
+                    path.display(),
+                    e
+                ),
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        for (c, blob) in chunks {
+            merged.insert((c.cy, c.cz, c.cx), blob.to_vec());
+        }
+
+        let mut out: Vec<u8> = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&CHUNK_CACHE_VERSION.to_le_bytes());
+        for dim in [self.dims.0, self.dims.1, self.dims.2] {
+            out.extend_from_slice(&(dim as u16).to_le_bytes());
+        }
+        out.extend_from_slice(&self.key.to_le_bytes());
+        out.extend_from_slice(&(merged.len() as u32).to_le_bytes());
+        let mut offset = HEADER_BYTES + merged.len() * INDEX_ENTRY_BYTES;
+        for ((cy, cz, cx), blob) in &merged {
+            for v in [cx, cy, cz] {
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            out.extend_from_slice(&(offset as u32).to_le_bytes());
+            out.extend_from_slice(&(blob.len() as u32).to_le_bytes());
+            offset += blob.len();
+        }
+        for blob in merged.values() {
+            out.extend_from_slice(blob);
+        }
+        write_atomic(&path, &out)
+    }
+}
@@ -0,0 +1,428 @@
+//! OpenEXR decoding for heightmaps: single-part scanline images with NONE, RLE, ZIPS or
+//! ZIP compression. One channel is read (`Y`, else `R`, `G`, or the first) and its values
+//! are stretched from the image's own range to 0..1.
+
+use std::io::{self, Read};
+
+use flate2::read::ZlibDecoder;
+
+use super::{HeightmapImage, check_size, invalid};
+
+pub(super) const MAGIC: [u8; 4] = [0x76, 0x2f, 0x31, 0x01];
+
+const FLAG_TILED: u32 = 0x200;
+const FLAG_DEEP: u32 = 0x800;
+const FLAG_MULTIPART: u32 = 0x1000;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    None,
+    Rle,
+    Zips,
+    Zip,
+}
+
+impl Compression {
+    fn from_u8(v: u8) -> io::Result<Self> {
+        match v {
+            0 => Ok(Compression::None),
+            1 => Ok(Compression::Rle),
+            2 => Ok(Compression::Zips),
+            3 => Ok(Compression::Zip),
+            _ => Err(invalid(format!(
+                "unsupported EXR compression {} (use NONE, RLE, ZIPS or ZIP)",
+                v
+            ))),
+        }
+    }
+
+    fn lines_per_block(self) -> usize {
+        match self {
+            Compression::Zip => 16,
+            _ => 1,
+        }
+    }
+}
+
+struct Channel {
+    name: String,
+    // 0 = UINT, 1 = HALF, 2 = FLOAT
+    pixel_type: i32,
+    sampling: (i32, i32),
+}
+
+impl Channel {
+    fn bytes(&self) -> usize {
+        if self.pixel_type == 1 { 2 } else { 4 }
+    }
+}
+
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        let out = self
+            .pos
+            .checked_add(n)
+            .and_then(|end| self.bytes.get(self.pos..end))
+            .ok_or_else(|| invalid("truncated EXR file"))?;
+        self.pos += n;
+        Ok(out)
+    }
+
+    fn cstr(&mut self) -> io::Result<&'a str> {
+        let len = self.bytes[self.pos.min(self.bytes.len())..]
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(|| invalid("unterminated EXR string"))?;
+        let s =
+            std::str::from_utf8(self.take(len)?).map_err(|_| invalid("EXR name is not UTF-8"))?;
+        self.pos += 1;
+        Ok(s)
+    }
+
+    fn i32(&mut self) -> io::Result<i32> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+pub(super) fn decode(bytes: &[u8]) -> io::Result<HeightmapImage> {
+    let mut cur = Cursor { bytes, pos: 0 };
+    if cur.take(4)? != MAGIC {
+        return Err(invalid("not an OpenEXR file"));
+    }
+    let flags = u32::from_le_bytes(cur.take(4)?.try_into().unwrap());
+    if flags & (FLAG_TILED | FLAG_DEEP | FLAG_MULTIPART) != 0 {
+        return Err(invalid(
+            "only single-part scanline EXR images are supported",
+        ));
+    }
+
+    let mut channels = Vec::new();
+    let mut compression = None;
+    let mut window = None;
+    loop {
+        let name = cur.cstr()?;
+        if name.is_empty() {
+            break;
+        }
+        let _ty = cur.cstr()?;
+        let size = cur.i32()?;
+        let value = cur.take(size.max(0) as usize)?;
+        match name {
+            "channels" => channels = parse_channels(value)?,
+            "compression" => {
+                compression = Some(Compression::from_u8(
+                    *value
+                        .first()
+                        .ok_or_else(|| invalid("empty EXR compression"))?,
+                )?)
+            }
+            "dataWindow" => {
+                let mut w = Cursor {
+                    bytes: value,
+                    pos: 0,
+                };
+                window = Some([w.i32()?, w.i32()?, w.i32()?, w.i32()?]);
+            }
+            _ => {}
+        }
+    }
+    let compression = compression.ok_or_else(|| invalid("EXR header has no compression"))?;
+    let [x0, y0, x1, y1] = window.ok_or_else(|| invalid("EXR header has no dataWindow"))?;
+    if x1 < x0 || y1 < y0 {
+        return Err(invalid("EXR data window is empty"));
+    }
+    let (width, height) = (
+        (x1 as i64 - x0 as i64 + 1) as usize,
+        (y1 as i64 - y0 as i64 + 1) as usize,
+    );
+    check_size(width, height)?;
+    if channels.iter().any(|c| c.sampling != (1, 1)) {
+        return Err(invalid("subsampled EXR channels are not supported"));
+    }
+    let pick = ["Y", "R", "G"]
+        .iter()
+        .find_map(|want| channels.iter().position(|c| c.name == *want))
+        .unwrap_or(0);
+    let channel = channels
+        .get(pick)
+        .ok_or_else(|| invalid("EXR image has no channels"))?;
+    // Byte offset of the chosen channel within one line, and the full line length.
+    let offset: usize = channels[..pick].iter().map(|c| c.bytes() * width).sum();
+    let line_bytes: usize = channels.iter().map(|c| c.bytes() * width).sum();
+
+    let lines = compression.lines_per_block();
+    let blocks = height.div_ceil(lines);
+    let offsets = (0..blocks)
+        .map(|_| cur.u64())
+        .collect::<io::Result<Vec<u64>>>()?;
+    let mut values = vec![0.0f32; width * height];
+    for block_start in offsets {
+        let mut block = Cursor {
+            bytes,
+            pos: block_start as usize,
+        };
+        let y = block.i32()?;
+        let size = block.i32()?.max(0) as usize;
+        let data = block.take(size)?;
+        let first =
+            y.checked_sub(y0)
+                .filter(|&r| r >= 0 && (r as usize) < height)
+                .ok_or_else(|| invalid("EXR block outside the data window"))? as usize;
+        let rows = lines.min(height - first);
+        let expected = rows * line_bytes;
+        let raw = if size == expected {
+            // Blocks that would not shrink are stored uncompressed.
+            data.to_vec()
+        } else {
+            decompress(compression, data, expected)?
+        };
+        for r in 0..rows {
+            let line = &raw[r * line_bytes + offset..][..channel.bytes() * width];
+            let out = &mut values[(first + r) * width..][..width];
+            for (v, px) in out.iter_mut().zip(line.chunks_exact(channel.bytes())) {
+                *v = match channel.pixel_type {
+                    0 => u32::from_le_bytes(px.try_into().unwrap()) as f32,
+                    1 => half_to_f32(u16::from_le_bytes(px.try_into().unwrap())),
+                    _ => f32::from_le_bytes(px.try_into().unwrap()),
+                };
+            }
+        }
+    }
+
+    let (lo, hi) = values
+        .iter()
+        .filter(|v| v.is_finite())
+        .fold((f32::MAX, f32::MIN), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+    let range = hi - lo;
+    for v in &mut values {
+        *v = if v.is_finite() && range > 0.0 {
+            (*v - lo) / range
+        } else {
+            0.0
+        };
+    }
+    Ok(HeightmapImage::new(width, height, values))
+}
+
+fn parse_channels(value: &[u8]) -> io::Result<Vec<Channel>> {
+    let mut cur = Cursor {
+        bytes: value,
+        pos: 0,
+    };
+    let mut out = Vec::new();
+    loop {
+        let name = cur.cstr()?;
+        if name.is_empty() {
+            return Ok(out);
+        }
+        let pixel_type = cur.i32()?;
+        if !(0..=2).contains(&pixel_type) {
+            return Err(invalid(format!("unknown EXR pixel type {}", pixel_type)));
+        }
+        // pLinear and three reserved bytes.
+        cur.take(4)?;
+        let sampling = (cur.i32()?, cur.i32()?);
+        out.push(Channel {
+            name: name.to_string(),
+            pixel_type,
+            sampling,
+        });
+    }
+}
+
+fn decompress(compression: Compression, data: &[u8], expected: usize) -> io::Result<Vec<u8>> {
+    let mut packed = Vec::with_capacity(expected);
+    match compression {
+        Compression::None => return Err(invalid("EXR block has the wrong size")),
+        Compression::Rle => {
+            let mut i = 0;
+            while i < data.len() {
+                let count = data[i] as i8;
+                i += 1;
+                if count < 0 {
+                    let n = -(count as isize) as usize;
+                    packed.extend_from_slice(
+                        data.get(i..i + n)
+                            .ok_or_else(|| invalid("truncated EXR RLE run"))?,
+                    );
+                    i += n;
+                } else {
+                    let b = *data
+                        .get(i)
+                        .ok_or_else(|| invalid("truncated EXR RLE run"))?;
+                    packed.extend(std::iter::repeat_n(b, count as usize + 1));
+                    i += 1;
+                }
+                if packed.len() > expected {
+                    break;
+                }
+            }
+        }
+        Compression::Zips | Compression::Zip => {
+            ZlibDecoder::new(data)
+                .take(expected as u64 + 1)
+                .read_to_end(&mut packed)?;
+        }
+    }
+    if packed.len() != expected {
+        return Err(invalid("EXR block decompressed to the wrong size"));
+    }
+    // Undo the byte delta predictor, then re-interleave the two halves.
+    for i in 1..packed.len() {
+        packed[i] = packed[i - 1].wrapping_add(packed[i]).wrapping_sub(128);
+    }
+    let (even, odd) = packed.split_at(expected.div_ceil(2));
+    let mut out = Vec::with_capacity(expected);
+    for (i, &b) in even.iter().enumerate() {
+        out.push(b);
+        if let Some(&b) = odd.get(i) {
+            out.push(b);
+        }
+    }
+    Ok(out)
+}
+
+fn half_to_f32(h: u16) -> f32 {
+    let sign = if h & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exp = ((h >> 10) & 0x1f) as i32;
+    let mant = (h & 0x3ff) as f32;
+    sign * match exp {
+        0 => mant * (-24f32).exp2(),
+        31 if mant == 0.0 => f32::INFINITY,
+        31 => f32::NAN,
+        _ => (1.0 + mant / 1024.0) * ((exp - 15) as f32).exp2(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use std::io::Write;
+
+    const W: usize = 3;
+    const H: usize = 2;
+    /// Elevations stored in the `Y` channel; they span 0..10.
+    const Y: [f32; W * H] = [0.0, 5.0, 10.0, 2.5, 7.5, 10.0];
+
+    fn attr(out: &mut Vec<u8>, name: &str, ty: &str, value: &[u8]) {
+        for s in [name, ty] {
+            out.extend_from_slice(s.as_bytes());
+            out.push(0);
+        }
+        out.extend_from_slice(&(value.len() as i32).to_le_bytes());
+        out.extend_from_slice(value);
+    }
+
+    /// Inverse of `decompress`'s predictor: split even and odd bytes, then delta-encode.
+    fn predict(raw: &[u8]) -> Vec<u8> {
+        let mut split: Vec<u8> = raw.iter().step_by(2).copied().collect();
+        split.extend(raw.iter().skip(1).step_by(2));
+        let mut out = split.clone();
+        for i in 1..split.len() {
+            out[i] = split[i].wrapping_sub(split[i - 1]).wrapping_add(128);
+        }
+        out
+    }
+
+    /// Scanline lines hold a half `A` channel (sorted first) and the float `Y` channel.
+    fn encode(compression: u8, x0: i32, y0: i32) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.extend_from_slice(&2u32.to_le_bytes());
+        let mut chlist = Vec::new();
+        for (name, pixel_type) in [("A", 1i32), ("Y", 2)] {
+            chlist.extend_from_slice(name.as_bytes());
+            chlist.push(0);
+            chlist.extend_from_slice(&pixel_type.to_le_bytes());
+            chlist.extend_from_slice(&[0; 4]);
+            chlist.extend_from_slice(&1i32.to_le_bytes());
+            chlist.extend_from_slice(&1i32.to_le_bytes());
+        }
+        chlist.push(0);
+        attr(&mut out, "channels", "chlist", &chlist);
+        attr(&mut out, "compression", "compression", &[compression]);
+        let window: Vec<u8> = [x0, y0, x0 + W as i32 - 1, y0 + H as i32 - 1]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        attr(&mut out, "dataWindow", "box2i", &window);
+        attr(&mut out, "lineOrder", "lineOrder", &[0]);
+        out.push(0);
+
+        let lines = Compression::from_u8(compression).unwrap().lines_per_block();
+        let table_at = out.len();
+        let blocks = H.div_ceil(lines);
+        out.resize(table_at + blocks * 8, 0);
+        for b in 0..blocks {
+            let mut raw = Vec::new();
+            for y in b * lines..((b + 1) * lines).min(H) {
+                // Half 1.0 in the alpha channel.
+                raw.extend(std::iter::repeat_n(0x3C00u16.to_le_bytes(), W).flatten());
+                raw.extend(Y[y * W..][..W].iter().flat_map(|v| v.to_le_bytes()));
+            }
+            let data = match compression {
+                0 => raw,
+                1 => predict(&raw)
+                    .chunks(127)
+                    .flat_map(|lit| [&[-(lit.len() as i8) as u8][..], lit].concat())
+                    .collect(),
+                _ => {
+                    let mut z = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+                    z.write_all(&predict(&raw)).unwrap();
+                    z.finish().unwrap()
+                }
+            };
+            let at = out.len() as u64;
+            out[table_at + b * 8..][..8].copy_from_slice(&at.to_le_bytes());
+            out.extend_from_slice(&(y0 + (b * lines) as i32).to_le_bytes());
+            out.extend_from_slice(&(data.len() as i32).to_le_bytes());
+            out.extend_from_slice(&data);
+        }
+        out
+    }
+
+    #[test]
+    fn scanline_images_decode_and_stretch_to_unit_range() {
+        for compression in [0u8, 1, 2, 3] {
+            let img = decode(&encode(compression, -4, 7)).unwrap();
+            assert_eq!((img.width(), img.height()), (W, H));
+            for y in 0..H {
+                for x in 0..W {
+                    let got = img.sample(x as i32, y as i32);
+                    assert_eq!(got, Y[y * W + x] / 10.0, "compression {compression}");
+                }
+            }
+        }
+        assert_eq!(half_to_f32(0x3C00), 1.0);
+        assert_eq!(half_to_f32(0xC000), -2.0);
+    }
+
+    #[test]
+    fn oversized_and_corrupt_images_are_rejected() {
+        let mut exr = encode(0, 0, 0);
+        // Widen the data window to the whole i32 range.
+        let window = exr.windows(10).position(|w| w == b"dataWindow").unwrap()
+            + "dataWindow\0box2i\0".len()
+            + 4;
+        exr[window..window + 4].copy_from_slice(&i32::MIN.to_le_bytes());
+        exr[window + 8..window + 12].copy_from_slice(&i32::MAX.to_le_bytes());
+        let err = decode(&exr).unwrap_err();
+        assert!(err.to_string().contains("larger than"), "{}", err);
+
+        // A block offset far past the end of the file.
+        let mut exr = encode(0, 0, 0);
+        let table = exr.len() - H * (8 + W * 6) - H * 8;
+        exr[table..table + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        let err = decode(&exr).unwrap_err();
+        assert!(err.to_string().contains("truncated"), "{}", err);
+    }
+}
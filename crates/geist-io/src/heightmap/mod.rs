@@ -0,0 +1,229 @@
+//! Heightmap import: a grayscale PNG or EXR becomes world terrain, one pixel per column,
+//! with brightness scaled between a minimum and maximum ground height.
+
+mod exr;
+mod png;
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use geist_world::{TerrainGenerator, World, WorldGenMode};
+
+// Neighbouring columns this much higher or lower turn the top block to stone (cliffs).
+const CLIFF_STEP: i32 = 3;
+// Sand runs this many blocks above the water line.
+const BEACH_HEIGHT: i32 = 2;
+const TOPSOIL_DEPTH: i32 = 4;
+// Largest image accepted; headers claiming more are refused before anything is allocated.
+const MAX_PIXELS: usize = 8192 * 8192;
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+fn check_size(width: usize, height: usize) -> io::Result<()> {
+    match width.checked_mul(height) {
+        Some(n) if n <= MAX_PIXELS => Ok(()),
+        _ => Err(invalid(format!(
+            "{}x{} heightmap is larger than the supported {} pixels",
+            width, height, MAX_PIXELS
+        ))),
+    }
+}
+
+/// Decoded heightmap samples in 0..1, row-major with row 0 at the image top.
+#[derive(Clone, Debug)]
+pub struct HeightmapImage {
+    width: usize,
+    height: usize,
+    samples: Vec<f32>,
+}
+
+impl HeightmapImage {
+    pub fn new(width: usize, height: usize, samples: Vec<f32>) -> Self {
+        assert_eq!(samples.len(), width * height);
+        Self {
+            width,
+            height,
+            samples,
+        }
+    }
+
+    /// Decode a PNG or EXR file, detected from its signature.
+    ///
+    /// PNG samples are scaled by the bit depth, so black and white are the ends of the
+    /// height range whatever the image contains. EXR holds real-valued elevations, so its
+    /// lowest and highest samples become the ends instead.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        if bytes.starts_with(&png::SIGNATURE) {
+            png::decode(bytes)
+        } else if bytes.starts_with(&exr::MAGIC) {
+            exr::decode(bytes)
+        } else {
+            Err(invalid("heightmap must be a PNG or OpenEXR image"))
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Sample at pixel `(x, y)`, clamped to the image edges.
+    pub fn sample(&self, x: i32, y: i32) -> f32 {
+        let x = x.clamp(0, self.width as i32 - 1) as usize;
+        let y = y.clamp(0, self.height as i32 - 1) as usize;
+        self.samples[y * self.width + x]
+    }
+}
+
+/// How image samples map to terrain.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HeightmapOptions {
+    /// Ground height of a black (or lowest) pixel.
+    pub min_y: i32,
+    /// Ground height of a white (or highest) pixel.
+    pub max_y: i32,
+    /// Columns below this fill with water up to it.
+    pub water_level: Option<i32>,
+}
+
+impl HeightmapOptions {
+    /// Defaults for a world `world_height` blocks tall: ground from y=1 to three quarters
+    /// of the height, no water.
+    pub fn for_world_height(world_height: i32) -> Self {
+        Self {
+            min_y: 1,
+            max_y: (world_height * 3 / 4).max(2),
+            water_level: None,
+        }
+    }
+}
+
+/// Terrain generator reading ground heights from a heightmap. Pixel `(x, y)` is column
+/// `(wx, wz) = (x, y)`; columns past the image repeat its edge.
+pub struct HeightmapTerrain {
+    width: usize,
+    depth: usize,
+    heights: Vec<i32>,
+    cliffs: Vec<bool>,
+    water_level: Option<i32>,
+}
+
+impl HeightmapTerrain {
+    pub fn new(image: &HeightmapImage, opts: &HeightmapOptions) -> Self {
+        let (width, depth) = (image.width(), image.height());
+        let span = (opts.max_y - opts.min_y) as f32;
+        let mut heights = Vec::with_capacity(width * depth);
+        for z in 0..depth as i32 {
+            for x in 0..width as i32 {
+                let v = image.sample(x, z).clamp(0.0, 1.0);
+                // Ground occupies y < height, so min_y itself is the first air block.
+                heights.push(opts.min_y + (v * span).round() as i32);
+            }
+        }
+        let at = |x: i32, z: i32| {
+            heights[z.clamp(0, depth as i32 - 1) as usize * width
+                + x.clamp(0, width as i32 - 1) as usize]
+        };
+        let mut cliffs = Vec::with_capacity(width * depth);
+        for z in 0..depth as i32 {
+            for x in 0..width as i32 {
+                let h = at(x, z);
+                let step = [(1, 0), (-1, 0), (0, 1), (0, -1)]
+                    .iter()
+                    .map(|&(dx, dz)| (at(x + dx, z + dz) - h).abs())
+                    .max()
+                    .unwrap_or(0);
+                cliffs.push(step >= CLIFF_STEP);
+            }
+        }
+        Self {
+            width,
+            depth,
+            heights,
+            cliffs,
+            water_level: opts.water_level,
+        }
+    }
+
+    pub fn load(path: &Path, opts: &HeightmapOptions) -> io::Result<Self> {
+        Ok(Self::new(&HeightmapImage::load(path)?, opts))
+    }
+
+    /// Image size in columns along X and Z.
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.depth)
+    }
+
+    fn index(&self, wx: i32, wz: i32) -> usize {
+        wz.clamp(0, self.depth as i32 - 1) as usize * self.width
+            + wx.clamp(0, self.width as i32 - 1) as usize
+    }
+}
+
+impl TerrainGenerator for HeightmapTerrain {
+    fn height(&self, wx: i32, wz: i32) -> i32 {
+        self.heights[self.index(wx, wz)]
+    }
+
+    fn surface_material(&self, wx: i32, wy: i32, wz: i32, height: i32) -> &str {
+        if wy >= height {
+            return match self.water_level {
+                Some(w) if wy < w => "water",
+                _ => "air",
+            };
+        }
+        let depth = height - 1 - wy;
+        if depth >= TOPSOIL_DEPTH {
+            return "stone";
+        }
+        let shore = self.water_level.is_some_and(|w| height <= w + BEACH_HEIGHT);
+        if shore {
+            "sand"
+        } else if self.cliffs[self.index(wx, wz)] {
+            "stone"
+        } else if depth == 0 {
+            "grass"
+        } else {
+            "dirt"
+        }
+    }
+}
+
+/// A world whose terrain is the heightmap at `path`, with `WorldGenMode::Heightmap`.
+pub fn heightmap_world(
+    chunks_x: usize,
+    chunks_y_hint: usize,
+    chunks_z: usize,
+    seed: i32,
+    path: &Path,
+    opts: &HeightmapOptions,
+) -> io::Result<World> {
+    let terrain = HeightmapTerrain::load(path, opts)?;
+    let (w, d) = terrain.size();
+    log::info!(
+        "heightmap {} ({}x{}) mapped to y {}..{}",
+        path.display(),
+        w,
+        d,
+        opts.min_y,
+        opts.max_y
+    );
+    let mut world =
+        World::with_generator(chunks_x, chunks_y_hint, chunks_z, seed, Arc::new(terrain));
+    world.mode = WorldGenMode::Heightmap {
+        path: path.to_path_buf(),
+    };
+    Ok(world)
+}
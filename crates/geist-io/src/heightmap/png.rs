@@ -0,0 +1,393 @@
+//! PNG decoding for heightmaps: every non-interlaced color type and bit depth, reduced
+//! to one luminance sample per pixel.
+
+use std::io::{self, Read};
+
+use flate2::read::ZlibDecoder;
+
+use super::{HeightmapImage, check_size, invalid};
+
+pub(super) const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+struct Header {
+    width: usize,
+    height: usize,
+    bit_depth: u8,
+    color_type: u8,
+}
+
+impl Header {
+    fn channels(&self) -> usize {
+        match self.color_type {
+            0 | 3 => 1,
+            4 => 2,
+            2 => 3,
+            _ => 4,
+        }
+    }
+
+    fn bits_per_pixel(&self) -> usize {
+        self.channels() * self.bit_depth as usize
+    }
+
+    fn row_bytes(&self) -> usize {
+        (self.width * self.bits_per_pixel()).div_ceil(8)
+    }
+}
+
+pub(super) fn decode(bytes: &[u8]) -> io::Result<HeightmapImage> {
+    let mut rest = bytes
+        .strip_prefix(&SIGNATURE)
+        .ok_or_else(|| invalid("not a PNG file"))?;
+    let mut header = None;
+    let mut palette: Vec<[u8; 3]> = Vec::new();
+    let mut idat = Vec::new();
+    while rest.len() >= 12 {
+        let len = u32::from_be_bytes(rest[0..4].try_into().unwrap()) as usize;
+        let kind = &rest[4..8];
+        let data = rest
+            .get(8..8 + len)
+            .ok_or_else(|| invalid("truncated PNG chunk"))?;
+        match kind {
+            b"IHDR" => header = Some(parse_header(data)?),
+            b"PLTE" => palette = data.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect(),
+            b"IDAT" => idat.extend_from_slice(data),
+            b"IEND" => break,
+            _ => {}
+        }
+        rest = &rest[(12 + len).min(rest.len())..];
+    }
+    let header = header.ok_or_else(|| invalid("PNG has no IHDR chunk"))?;
+    if header.color_type == 3 && palette.is_empty() {
+        return Err(invalid("palette PNG has no PLTE chunk"));
+    }
+
+    let row_bytes = header.row_bytes();
+    let expected = (row_bytes + 1) * header.height;
+    let mut raw = Vec::with_capacity(expected);
+    ZlibDecoder::new(&idat[..])
+        .take(expected as u64)
+        .read_to_end(&mut raw)?;
+    if raw.len() < expected {
+        return Err(invalid("PNG image data is truncated"));
+    }
+
+    let bpp = header.bits_per_pixel().div_ceil(8);
+    let mut prev = vec![0u8; row_bytes];
+    let mut row = vec![0u8; row_bytes];
+    let mut samples = Vec::with_capacity(header.width * header.height);
+    for line in raw.chunks_exact(row_bytes + 1).take(header.height) {
+        row.copy_from_slice(&line[1..]);
+        unfilter(line[0], &mut row, &prev, bpp)?;
+        push_luminance(&header, &palette, &row, &mut samples);
+        std::mem::swap(&mut row, &mut prev);
+    }
+    Ok(HeightmapImage::new(header.width, header.height, samples))
+}
+
+fn parse_header(data: &[u8]) -> io::Result<Header> {
+    if data.len() < 13 {
+        return Err(invalid("PNG IHDR is too short"));
+    }
+    let width = u32::from_be_bytes(data[0..4].try_into().unwrap()) as usize;
+    let height = u32::from_be_bytes(data[4..8].try_into().unwrap()) as usize;
+    let header = Header {
+        width,
+        height,
+        bit_depth: data[8],
+        color_type: data[9],
+    };
+    let depth_ok = match header.color_type {
+        0 => matches!(header.bit_depth, 1 | 2 | 4 | 8 | 16),
+        3 => matches!(header.bit_depth, 1 | 2 | 4 | 8),
+        2 | 4 | 6 => matches!(header.bit_depth, 8 | 16),
+        _ => false,
+    };
+    if !depth_ok {
+        return Err(invalid(format!(
+            "unsupported PNG color type {} at bit depth {}",
+            header.color_type, header.bit_depth
+        )));
+    }
+    if data[12] != 0 {
+        return Err(invalid("interlaced PNGs are not supported"));
+    }
+    if width == 0 || height == 0 {
+        return Err(invalid("PNG has no pixels"));
+    }
+    check_size(width, height)?;
+    Ok(header)
+}
+
+fn unfilter(filter: u8, row: &mut [u8], prev: &[u8], bpp: usize) -> io::Result<()> {
+    match filter {
+        0 => {}
+        1 => {
+            for i in bpp..row.len() {
+                row[i] = row[i].wrapping_add(row[i - bpp]);
+            }
+        }
+        2 => {
+            for (r, &p) in row.iter_mut().zip(prev) {
+                *r = r.wrapping_add(p);
+            }
+        }
+        3 => {
+            for i in 0..row.len() {
+                let left = if i >= bpp { row[i - bpp] } else { 0 };
+                row[i] = row[i].wrapping_add(((left as u16 + prev[i] as u16) / 2) as u8);
+            }
+        }
+        4 => {
+            for i in 0..row.len() {
+                let (a, c) = if i >= bpp {
+                    (row[i - bpp], prev[i - bpp])
+                } else {
+                    (0, 0)
+                };
+                row[i] = row[i].wrapping_add(paeth(a, prev[i], c));
+            }
+        }
+        _ => return Err(invalid(format!("unknown PNG filter type {}", filter))),
+    }
+    Ok(())
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+// Rec. 709 luma, so color heightmaps still rise with brightness.
+fn luma(r: f32, g: f32, b: f32) -> f32 {
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+fn push_luminance(header: &Header, palette: &[[u8; 3]], row: &[u8], out: &mut Vec<f32>) {
+    let depth = header.bit_depth as usize;
+    let max = ((1u32 << depth) - 1) as f32;
+    // Channel `c` of pixel `x`, as read from the row at the image's bit depth.
+    let channel = |x: usize, c: usize| -> u32 {
+        let index = x * header.channels() + c;
+        match depth {
+            16 => u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]) as u32,
+            8 => row[index] as u32,
+            _ => {
+                let bit = index * depth;
+                let shift = 8 - depth - bit % 8;
+                (row[bit / 8] >> shift) as u32 & ((1 << depth) - 1)
+            }
+        }
+    };
+    for x in 0..header.width {
+        let v = match header.color_type {
+            0 | 4 => channel(x, 0) as f32 / max,
+            3 => {
+                let [r, g, b] = palette
+                    .get(channel(x, 0) as usize)
+                    .copied()
+                    .unwrap_or_default();
+                luma(r as f32, g as f32, b as f32) / 255.0
+            }
+            _ => {
+                luma(
+                    channel(x, 0) as f32,
+                    channel(x, 1) as f32,
+                    channel(x, 2) as f32,
+                ) / max
+            }
+        };
+        out.push(v);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::ZlibEncoder;
+    use std::io::Write;
+
+    fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        out.extend_from_slice(kind);
+        out.extend_from_slice(data);
+        let mut crc = flate2::Crc::new();
+        crc.update(kind);
+        crc.update(data);
+        out.extend_from_slice(&crc.sum().to_be_bytes());
+    }
+
+    /// Encode packed `rows` as a PNG, filtering row `y` with filter type `y % 5` so every
+    /// filter is exercised.
+    fn encode(
+        width: u32,
+        color_type: u8,
+        bit_depth: u8,
+        palette: &[u8],
+        rows: &[Vec<u8>],
+    ) -> Vec<u8> {
+        let header = Header {
+            width: width as usize,
+            height: rows.len(),
+            bit_depth,
+            color_type,
+        };
+        let bpp = header.bits_per_pixel().div_ceil(8);
+        let mut filtered = Vec::new();
+        let mut prev = vec![0u8; header.row_bytes()];
+        for (y, row) in rows.iter().enumerate() {
+            let filter = (y % 5) as u8;
+            filtered.push(filter);
+            for i in 0..row.len() {
+                let a = if i >= bpp { row[i - bpp] } else { 0 };
+                let c = if i >= bpp { prev[i - bpp] } else { 0 };
+                let b = prev[i];
+                let predict = match filter {
+                    0 => 0,
+                    1 => a,
+                    2 => b,
+                    3 => ((a as u16 + b as u16) / 2) as u8,
+                    _ => paeth(a, b, c),
+                };
+                filtered.push(row[i].wrapping_sub(predict));
+            }
+            prev.clone_from(row);
+        }
+        let mut z = ZlibEncoder::new(Vec::new(), Compression::default());
+        z.write_all(&filtered).unwrap();
+
+        let mut out = SIGNATURE.to_vec();
+        let mut ihdr = Vec::new();
+        ihdr.extend_from_slice(&width.to_be_bytes());
+        ihdr.extend_from_slice(&(rows.len() as u32).to_be_bytes());
+        ihdr.extend_from_slice(&[bit_depth, color_type, 0, 0, 0]);
+        chunk(&mut out, b"IHDR", &ihdr);
+        if !palette.is_empty() {
+            chunk(&mut out, b"PLTE", palette);
+        }
+        chunk(&mut out, b"IDAT", &z.finish().unwrap());
+        chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    /// Pack a row of channel values at `depth` bits each, most significant bits first.
+    fn pack(values: &[u32], depth: u8) -> Vec<u8> {
+        let depth = depth as usize;
+        let mut row = vec![0u8; (values.len() * depth).div_ceil(8)];
+        for (i, &v) in values.iter().enumerate() {
+            match depth {
+                16 => row[i * 2..i * 2 + 2].copy_from_slice(&(v as u16).to_be_bytes()),
+                8 => row[i] = v as u8,
+                _ => {
+                    let bit = i * depth;
+                    row[bit / 8] |= (v as u8) << (8 - depth - bit % 8);
+                }
+            }
+        }
+        row
+    }
+
+    const W: usize = 7;
+    const H: usize = 6;
+
+    fn value(x: usize, y: usize, max: u32) -> u32 {
+        ((x * 37 + y * 101) as u32 * 0x9E37) % (max + 1)
+    }
+
+    fn assert_samples(img: &HeightmapImage, expect: impl Fn(usize, usize) -> f32) {
+        assert_eq!((img.width(), img.height()), (W, H));
+        for y in 0..H {
+            for x in 0..W {
+                let (got, want) = (img.sample(x as i32, y as i32), expect(x, y));
+                assert!((got - want).abs() < 1e-4, "({x},{y}): {got} != {want}");
+            }
+        }
+    }
+
+    #[test]
+    fn grayscale_decodes_at_every_bit_depth_and_filter() {
+        for depth in [1u8, 2, 4, 8, 16] {
+            let max = (1u32 << depth) - 1;
+            let rows: Vec<Vec<u8>> = (0..H)
+                .map(|y| {
+                    let vals: Vec<u32> = (0..W).map(|x| value(x, y, max)).collect();
+                    pack(&vals, depth)
+                })
+                .collect();
+            let img = decode(&encode(W as u32, 0, depth, &[], &rows)).unwrap();
+            assert_samples(&img, |x, y| value(x, y, max) as f32 / max as f32);
+        }
+    }
+
+    #[test]
+    fn color_types_reduce_to_luminance() {
+        // Gray palette entries, so luminance is the entry's level.
+        for depth in [1u8, 2, 4, 8] {
+            let max = (1u32 << depth) - 1;
+            let palette: Vec<u8> = (0..=max).flat_map(|i| [(i * 255 / max) as u8; 3]).collect();
+            let rows: Vec<Vec<u8>> = (0..H)
+                .map(|y| {
+                    let vals: Vec<u32> = (0..W).map(|x| value(x, y, max)).collect();
+                    pack(&vals, depth)
+                })
+                .collect();
+            let img = decode(&encode(W as u32, 3, depth, &palette, &rows)).unwrap();
+            assert_samples(&img, |x, y| (value(x, y, max) * 255 / max) as f32 / 255.0);
+        }
+
+        // Gray with alpha, RGB and RGBA with equal color channels.
+        for (color_type, channels) in [(4u8, 2usize), (2, 3), (6, 4)] {
+            for depth in [8u8, 16] {
+                let max = (1u32 << depth) - 1;
+                let rows: Vec<Vec<u8>> = (0..H)
+                    .map(|y| {
+                        let vals: Vec<u32> = (0..W)
+                            .flat_map(|x| {
+                                let v = value(x, y, max);
+                                let gray = [v; 3];
+                                let color = if channels < 3 { &gray[..1] } else { &gray[..] };
+                                let alpha = (channels % 2 == 0).then_some(max / 3);
+                                color.iter().copied().chain(alpha).collect::<Vec<_>>()
+                            })
+                            .collect();
+                        pack(&vals, depth)
+                    })
+                    .collect();
+                let img = decode(&encode(W as u32, color_type, depth, &[], &rows)).unwrap();
+                assert_samples(&img, |x, y| value(x, y, max) as f32 / max as f32);
+            }
+        }
+    }
+
+    #[test]
+    fn oversized_and_truncated_images_are_rejected() {
+        let mut out = SIGNATURE.to_vec();
+        let mut ihdr = Vec::new();
+        ihdr.extend_from_slice(&u32::MAX.to_be_bytes());
+        ihdr.extend_from_slice(&u32::MAX.to_be_bytes());
+        ihdr.extend_from_slice(&[16, 6, 0, 0, 0]);
+        chunk(&mut out, b"IHDR", &ihdr);
+        chunk(&mut out, b"IEND", &[]);
+        let err = decode(&out).unwrap_err();
+        assert!(err.to_string().contains("larger than"), "{}", err);
+
+        let rows = vec![pack(&[1, 2, 3], 8); 4];
+        let mut png = encode(3, 0, 8, &[], &rows);
+        // Claim twice the rows the image data holds.
+        png[SIGNATURE.len() + 8 + 4..][..4].copy_from_slice(&8u32.to_be_bytes());
+        let err = decode(&png).unwrap_err();
+        assert!(err.to_string().contains("truncated"), "{}", err);
+    }
+}
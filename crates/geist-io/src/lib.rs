@@ -1,14 +1,29 @@
-//! External I/O (schematics and optional Bedrock).
+//! External I/O (schematics, heightmap import, the on-disk chunk cache and optional Bedrock).
 #![forbid(unsafe_code)]
 
 use serde::Deserialize;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use geist_blocks::BlockRegistry;
+use geist_blocks::stable_hash::Fnv1a;
 use geist_blocks::types::Block as RtBlock;
 use geist_edit::EditStore;
-use geist_structures::Structure;
+use geist_geom::Vec3;
+use geist_structures::{Pose, Structure, StructureEditStore};
+
+mod chunk_cache;
+mod heightmap;
+mod library;
+pub use chunk_cache::{
+    CACHE_REGION_CHUNKS, CHUNK_CACHE_VERSION, ChunkCache, ChunkCacheStats, chunk_cache_key,
+};
+pub use heightmap::{HeightmapImage, HeightmapOptions, HeightmapTerrain, heightmap_world};
+pub use library::{
+    LibraryScan, SCHEMATIC_INDEX_FILE, SchematicLibrary, SchematicMeta, SchematicQuery,
+    SchematicSort,
+};
 
 // Map a Sponge palette key like "minecraft:oak_log[axis=y]" to our Block
 fn base_from_key(key: &str) -> &str {
@@ -91,6 +106,67 @@ fn load_palette_map() -> Option<PaletteMapConfig> {
     toml::from_str::<PaletteMapConfig>(&s).ok()
 }
 
+/// Palette translation rules (`assets/voxels/palette_map.toml`) from schematic block ids
+/// like "minecraft:oak_log[axis=y]" to runtime blocks.
+#[derive(Clone, Debug, Default)]
+pub struct PaletteMap {
+    lut: std::collections::HashMap<String, ToDef>,
+}
+
+impl PaletteMap {
+    /// Rules from the palette map found next to the assets; empty when none is found.
+    pub fn load_default() -> Self {
+        load_palette_map()
+            .map(Self::from_config)
+            .unwrap_or_default()
+    }
+
+    pub fn from_toml_str(s: &str) -> Result<Self, String> {
+        toml::from_str::<PaletteMapConfig>(s)
+            .map(Self::from_config)
+            .map_err(|e| format!("parse palette map: {e}"))
+    }
+
+    fn from_config(cfg: PaletteMapConfig) -> Self {
+        Self {
+            lut: cfg.rules.into_iter().map(|r| (r.from, r.to)).collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lut.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lut.is_empty()
+    }
+
+    /// A rule exists for the full key or its base id.
+    pub fn covers(&self, key: &str) -> bool {
+        self.lut.contains_key(key) || self.lut.contains_key(base_from_key(key))
+    }
+
+    /// Stable hash of the rule keys, to tell when data derived from the map is stale.
+    pub fn fingerprint(&self) -> u64 {
+        let mut keys: Vec<&String> = self.lut.keys().collect();
+        keys.sort();
+        let mut h = Fnv1a::new();
+        for k in keys {
+            h.write(k.as_bytes());
+            h.write(&[0]);
+        }
+        h.finish()
+    }
+
+    /// Runtime block for a full palette key, or `None` when no rule covers it.
+    pub fn map_key(&self, reg: &BlockRegistry, key: &str) -> Option<RtBlock> {
+        if self.lut.is_empty() {
+            return None;
+        }
+        runtime_from_palette_key_with_lut(reg, key, &self.lut)
+    }
+}
+
 fn runtime_from_palette_key_with_lut(
     reg: &BlockRegistry,
     key: &str,
@@ -126,6 +202,12 @@ fn runtime_from_palette_key_with_lut(
                 .entry("facing".to_string())
                 .or_insert_with(|| f.to_string());
         }
+    } else if to.name == "furnace" {
+        if let Some(lit) = state_value(key, "lit") {
+            state
+                .entry("lit".to_string())
+                .or_insert_with(|| lit.to_string());
+        }
     }
     reg.make_block_by_name(&to.name, Some(&state))
 }
@@ -165,11 +247,7 @@ pub fn load_sponge_schem_apply_edits(
     let (ox, oy, oz) = origin;
 
     // Build a fast lookup table for palette mapping to avoid re-parsing TOML per block
-    let lut: std::collections::HashMap<String, ToDef> = if let Some(cfg) = load_palette_map() {
-        cfg.rules.into_iter().map(|r| (r.from, r.to)).collect()
-    } else {
-        std::collections::HashMap::new()
-    };
+    let palette = PaletteMap::load_default();
 
     for x in 0..shape[0] {
         for y in 0..shape[1] {
@@ -180,11 +258,7 @@ pub fn load_sponge_schem_apply_edits(
                     }
                     let key = b.full_id(); // like "minecraft:oak_log[axis=y]"
                     // Config-driven translator only; skip if no rule is present
-                    let maybe_rt = if lut.is_empty() {
-                        None
-                    } else {
-                        runtime_from_palette_key_with_lut(reg, &key, &lut)
-                    };
+                    let maybe_rt = palette.map_key(reg, &key);
                     let wx = ox + x;
                     let wy = oy + y;
                     let wz = oz + z;
@@ -220,11 +294,7 @@ pub fn load_any_schematic_apply_into_structure(
     let (sx, sy, sz) = (shape[0] as usize, shape[1] as usize, shape[2] as usize);
     let (ox, oy, oz) = origin_local;
 
-    let lut: std::collections::HashMap<String, ToDef> = if let Some(cfg) = load_palette_map() {
-        cfg.rules.into_iter().map(|r| (r.from, r.to)).collect()
-    } else {
-        std::collections::HashMap::new()
-    };
+    let palette = PaletteMap::load_default();
 
     for x in 0..shape[0] {
         for y in 0..shape[1] {
@@ -234,11 +304,7 @@ pub fn load_any_schematic_apply_into_structure(
                         continue;
                     }
                     let key = b.full_id();
-                    let maybe_rt = if lut.is_empty() {
-                        None
-                    } else {
-                        runtime_from_palette_key_with_lut(reg, &key, &lut)
-                    };
+                    let maybe_rt = palette.map_key(reg, &key);
                     let lx = ox + x;
                     let ly = oy + y;
                     let lz = oz + z;
@@ -257,6 +323,91 @@ pub fn load_any_schematic_apply_into_structure(
     Ok((sx, sy, sz))
 }
 
+/// A schematic loaded as a structure, with the palette ids no rule covered.
+pub struct SchematicStructure {
+    pub structure: Structure,
+    /// Base ids (without block state) that fell back to the unknown block, with counts.
+    pub unmapped: Vec<(String, u64)>,
+}
+
+/// Build a `Structure` straight from a schematic file.
+pub trait StructureFromSchematic {
+    /// The structure is sized to the schematic, has id 0 and sits at the world origin;
+    /// set `id` and `pose` before inserting it. Air and structure voids stay empty and
+    /// unmapped blocks become the registry's unknown block.
+    fn from_schematic(
+        path: &Path,
+        reg: &BlockRegistry,
+        palette_map: &PaletteMap,
+    ) -> Result<SchematicStructure, String>;
+}
+
+impl StructureFromSchematic for Structure {
+    fn from_schematic(
+        path: &Path,
+        reg: &BlockRegistry,
+        palette_map: &PaletteMap,
+    ) -> Result<SchematicStructure, String> {
+        let (schem, _meta) = mc_schem::Schematic::from_file(
+            path.to_str().ok_or_else(|| "invalid path".to_string())?,
+        )
+        .map_err(|e| format!("parse schem: {e}"))?;
+
+        let shape = schem.shape();
+        if shape.iter().any(|&n| n <= 0) {
+            return Err(format!("empty schematic {:?}", path));
+        }
+        let (sx, sy, sz) = (shape[0] as usize, shape[1] as usize, shape[2] as usize);
+        let air = RtBlock {
+            id: reg.id_by_name("air").unwrap_or(0),
+            state: 0,
+        };
+        let mut blocks = vec![air; sx * sy * sz];
+        let mut unmapped: std::collections::BTreeMap<String, u64> =
+            std::collections::BTreeMap::new();
+        for x in 0..shape[0] {
+            for y in 0..shape[1] {
+                for z in 0..shape[2] {
+                    let Some(b) = schem.first_block_at([x, y, z]) else {
+                        continue;
+                    };
+                    if b.is_air() || b.is_structure_void() {
+                        continue;
+                    }
+                    let key = b.full_id();
+                    let rt = palette_map.map_key(reg, &key).unwrap_or_else(|| {
+                        *unmapped.entry(base_from_key(&key).to_string()).or_insert(0) += 1;
+                        RtBlock {
+                            id: reg.unknown_block_id_or_panic(),
+                            state: 0,
+                        }
+                    });
+                    let (x, y, z) = (x as usize, y as usize, z as usize);
+                    blocks[(y * sz + z) * sx + x] = rt;
+                }
+            }
+        }
+
+        let structure = Structure {
+            id: 0,
+            sx,
+            sy,
+            sz,
+            blocks: Arc::from(blocks.into_boxed_slice()),
+            edits: StructureEditStore::new(),
+            pose: Pose::from_yaw(Vec3::ZERO, 0.0),
+            last_delta: Vec3::ZERO,
+            last_velocity: Vec3::ZERO,
+            dirty_rev: 1,
+            built_rev: 0,
+        };
+        Ok(SchematicStructure {
+            structure,
+            unmapped: unmapped.into_iter().collect(),
+        })
+    }
+}
+
 pub fn find_unsupported_blocks_in_file(path: &Path) -> Result<Vec<String>, String> {
     let (schem, _meta) =
         mc_schem::Schematic::from_file(path.to_str().ok_or_else(|| "invalid path".to_string())?)
@@ -0,0 +1,285 @@
+//! Schematic library: metadata for every schematic in a directory, cached in an index file
+//! next to them so start-up only parses files that changed.
+//!
+//! Entries are invalidated by file length and modification time, and all of them when the
+//! palette map changes (the unsupported counts depend on it).
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use geist_blocks::codec::write_atomic;
+
+use crate::{PaletteMap, base_from_key};
+
+/// Index file written into the schematics directory.
+pub const SCHEMATIC_INDEX_FILE: &str = ".schematic_index.toml";
+const INDEX_VERSION: u32 = 1;
+
+/// What a schematic contains, without loading it into the world.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SchematicMeta {
+    /// File name inside the library directory.
+    pub file: String,
+    pub size: (i32, i32, i32),
+    /// Blocks other than air and structure voids.
+    pub block_count: u64,
+    /// Distinct base ids (block state stripped).
+    pub distinct_blocks: usize,
+    /// Blocks no palette rule covers; they paste as the unknown block.
+    pub unsupported_blocks: u64,
+    /// Base ids of the unsupported blocks, sorted.
+    pub unsupported_ids: Vec<String>,
+    file_len: u64,
+    modified_secs: u64,
+}
+
+impl SchematicMeta {
+    /// File name without the extension.
+    pub fn name(&self) -> &str {
+        Path::new(&self.file)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or(&self.file)
+    }
+
+    pub fn volume(&self) -> i64 {
+        self.size.0 as i64 * self.size.1 as i64 * self.size.2 as i64
+    }
+
+    /// Every block has a palette rule.
+    pub fn is_fully_supported(&self) -> bool {
+        self.unsupported_blocks == 0
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexFile {
+    version: u32,
+    /// `PaletteMap::fingerprint` the unsupported counts were computed with, in hex.
+    palette: String,
+    #[serde(default)]
+    entries: Vec<SchematicMeta>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SchematicSort {
+    #[default]
+    Name,
+    /// Largest bounding box first.
+    Volume,
+    /// Most blocks first.
+    BlockCount,
+}
+
+/// Filter for `SchematicLibrary::query`; the default matches everything, sorted by name.
+#[derive(Clone, Debug, Default)]
+pub struct SchematicQuery {
+    /// Case-insensitive substring of the file name.
+    pub name_contains: Option<String>,
+    /// Largest accepted (x, z) footprint.
+    pub max_footprint: Option<(i32, i32)>,
+    pub supported_only: bool,
+    pub sort: SchematicSort,
+}
+
+/// How a scan used the cached index.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LibraryScan {
+    pub reused: usize,
+    pub parsed: usize,
+    /// Files that failed to parse and were left out.
+    pub failed: usize,
+}
+
+pub struct SchematicLibrary {
+    dir: PathBuf,
+    entries: Vec<SchematicMeta>,
+}
+
+impl SchematicLibrary {
+    /// Scan `dir`, reusing cached metadata for unchanged files and rewriting the index if
+    /// anything was parsed or removed. A missing directory gives an empty library.
+    pub fn open(dir: &Path, palette: &PaletteMap) -> Result<(Self, LibraryScan), String> {
+        let mut lib = Self {
+            dir: dir.to_path_buf(),
+            entries: Vec::new(),
+        };
+        let scan = lib.rescan(palette)?;
+        Ok((lib, scan))
+    }
+
+    pub fn rescan(&mut self, palette: &PaletteMap) -> Result<LibraryScan, String> {
+        let mut scan = LibraryScan::default();
+        if !self.dir.is_dir() {
+            self.entries.clear();
+            return Ok(scan);
+        }
+        let fingerprint = format!("{:016x}", palette.fingerprint());
+        let index_path = self.dir.join(SCHEMATIC_INDEX_FILE);
+        let mut cached: HashMap<String, SchematicMeta> = fs::read_to_string(&index_path)
+            .ok()
+            .and_then(|s| toml::from_str::<IndexFile>(&s).ok())
+            .filter(|idx| idx.version == INDEX_VERSION && idx.palette == fingerprint)
+            .map(|idx| {
+                idx.entries
+                    .into_iter()
+                    .map(|e| (e.file.clone(), e))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let cached_len = cached.len();
+
+        let rd = fs::read_dir(&self.dir).map_err(|e| format!("read_dir {:?}: {}", self.dir, e))?;
+        let mut entries = Vec::new();
+        for ent in rd {
+            let ent = ent.map_err(|e| format!("read_dir entry: {}", e))?;
+            let path = ent.path();
+            if !path.is_file() || !is_schematic(&path) {
+                continue;
+            }
+            let Some(file) = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map(str::to_string)
+            else {
+                continue;
+            };
+            let (file_len, modified_secs) = file_stamp(&path);
+            if let Some(meta) = cached.remove(&file)
+                && meta.file_len == file_len
+                && meta.modified_secs == modified_secs
+            {
+                scan.reused += 1;
+                entries.push(meta);
+                continue;
+            }
+            match extract_meta(&path, palette) {
+                Ok(mut meta) => {
+                    meta.file = file;
+                    meta.file_len = file_len;
+                    meta.modified_secs = modified_secs;
+                    scan.parsed += 1;
+                    entries.push(meta);
+                }
+                Err(e) => {
+                    log::warn!("schematic library: skipping {:?}: {}", path, e);
+                    scan.failed += 1;
+                }
+            }
+        }
+        entries.sort_by(|a, b| a.file.cmp(&b.file));
+        self.entries = entries;
+
+        if scan.parsed > 0 || scan.reused != cached_len {
+            let index = IndexFile {
+                version: INDEX_VERSION,
+                palette: fingerprint,
+                entries: self.entries.clone(),
+            };
+            let text = toml::to_string(&index).map_err(|e| format!("encode index: {e}"))?;
+            if let Err(e) = write_atomic(&index_path, text.as_bytes()) {
+                // A read-only library still works, it just parses every start.
+                log::warn!("schematic library: cannot write {:?}: {}", index_path, e);
+            }
+        }
+        Ok(scan)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// All entries, sorted by file name.
+    pub fn entries(&self) -> &[SchematicMeta] {
+        &self.entries
+    }
+
+    /// Full path to load or paste `meta` from.
+    pub fn path_of(&self, meta: &SchematicMeta) -> PathBuf {
+        self.dir.join(&meta.file)
+    }
+
+    pub fn query(&self, q: &SchematicQuery) -> Vec<&SchematicMeta> {
+        let needle = q.name_contains.as_ref().map(|s| s.to_lowercase());
+        let mut out: Vec<&SchematicMeta> = self
+            .entries
+            .iter()
+            .filter(|m| {
+                needle
+                    .as_ref()
+                    .is_none_or(|n| m.file.to_lowercase().contains(n))
+            })
+            .filter(|m| {
+                q.max_footprint
+                    .is_none_or(|(fx, fz)| m.size.0 <= fx && m.size.2 <= fz)
+            })
+            .filter(|m| !q.supported_only || m.is_fully_supported())
+            .collect();
+        match q.sort {
+            SchematicSort::Name => {}
+            SchematicSort::Volume => out.sort_by_key(|m| std::cmp::Reverse(m.volume())),
+            SchematicSort::BlockCount => out.sort_by_key(|m| std::cmp::Reverse(m.block_count)),
+        }
+        out
+    }
+}
+
+fn is_schematic(path: &Path) -> bool {
+    path.extension()
+        .map(|e| e.eq_ignore_ascii_case("schem") || e.eq_ignore_ascii_case("schematic"))
+        .unwrap_or(false)
+}
+
+fn file_stamp(path: &Path) -> (u64, u64) {
+    let Ok(md) = fs::metadata(path) else {
+        return (0, 0);
+    };
+    let modified = md
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    (md.len(), modified)
+}
+
+/// Parse a schematic once and summarise it; `file` and the stamp are filled by the caller.
+fn extract_meta(path: &Path, palette: &PaletteMap) -> Result<SchematicMeta, String> {
+    let (schem, _meta) =
+        mc_schem::Schematic::from_file(path.to_str().ok_or_else(|| "invalid path".to_string())?)
+            .map_err(|e| format!("parse schem: {e}"))?;
+    let shape = schem.shape();
+    let mut counts: BTreeMap<String, u64> = BTreeMap::new();
+    let mut unsupported: BTreeMap<String, u64> = BTreeMap::new();
+    for x in 0..shape[0] {
+        for y in 0..shape[1] {
+            for z in 0..shape[2] {
+                let Some(b) = schem.first_block_at([x, y, z]) else {
+                    continue;
+                };
+                if b.is_air() || b.is_structure_void() {
+                    continue;
+                }
+                let key = b.full_id();
+                let base = base_from_key(&key).to_string();
+                if !palette.covers(&key) {
+                    *unsupported.entry(base.clone()).or_insert(0) += 1;
+                }
+                *counts.entry(base).or_insert(0) += 1;
+            }
+        }
+    }
+    Ok(SchematicMeta {
+        file: String::new(),
+        size: (shape[0], shape[1], shape[2]),
+        block_count: counts.values().sum(),
+        distinct_blocks: counts.len(),
+        unsupported_blocks: unsupported.values().sum(),
+        unsupported_ids: unsupported.into_keys().collect(),
+        file_len: 0,
+        modified_secs: 0,
+    })
+}
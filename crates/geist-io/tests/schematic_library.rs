@@ -0,0 +1,168 @@
+//! Schematic library index reuse, invalidation and queries over a scratch directory.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use geist_io::{
+    LibraryScan, PaletteMap, SCHEMATIC_INDEX_FILE, SchematicLibrary, SchematicQuery, SchematicSort,
+};
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../../schematics")
+        .join(name)
+}
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("geist-io-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn stone_palette() -> PaletteMap {
+    PaletteMap::from_toml_str(
+        r#"
+[[rules]]
+from = "minecraft:stone"
+to = { name = "stone" }
+"#,
+    )
+    .unwrap()
+}
+
+fn scan(reused: usize, parsed: usize, failed: usize) -> LibraryScan {
+    LibraryScan {
+        reused,
+        parsed,
+        failed,
+    }
+}
+
+fn set_modified(path: &Path, t: SystemTime) {
+    fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(t)
+        .unwrap();
+}
+
+#[test]
+fn rescan_reuses_the_index_and_reparses_only_changed_files() {
+    let dir = scratch_dir("library");
+    fs::copy(fixture("0213-wizard.schem"), dir.join("wizard.schem")).unwrap();
+    fs::copy(fixture("anvilstead.schem"), dir.join("anvilstead.schem")).unwrap();
+    fs::copy(fixture("lakeside-manor.schem"), dir.join("lakeside.schem")).unwrap();
+    fs::write(dir.join("notes.txt"), "not a schematic").unwrap();
+    fs::write(dir.join("broken.schem"), "not gzip").unwrap();
+    let palette = stone_palette();
+
+    let (mut lib, first) = SchematicLibrary::open(&dir, &palette).unwrap();
+    assert_eq!(first, scan(0, 3, 1));
+    assert!(dir.join(SCHEMATIC_INDEX_FILE).is_file());
+    let names: Vec<&str> = lib.entries().iter().map(|m| m.name()).collect();
+    assert_eq!(names, ["anvilstead", "lakeside", "wizard"]);
+    let wizard = lib.entries()[2].clone();
+    assert_eq!(wizard.size, (36, 81, 37));
+    assert!(!wizard.is_fully_supported());
+    let original = lib.entries().to_vec();
+
+    // A second library over the same directory parses nothing that is still valid.
+    let (again, second) = SchematicLibrary::open(&dir, &palette).unwrap();
+    assert_eq!(second, scan(3, 0, 1));
+    assert_eq!(again.entries(), &original[..]);
+
+    // New contents under the same name change the length.
+    fs::copy(fixture("roseridge-manor.schem"), dir.join("wizard.schem")).unwrap();
+    assert_eq!(lib.rescan(&palette).unwrap(), scan(2, 1, 1));
+    assert_ne!(lib.entries()[2].size, wizard.size);
+
+    // Same length, new modification time.
+    let lakeside = dir.join("lakeside.schem");
+    set_modified(
+        &lakeside,
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000),
+    );
+    assert_eq!(lib.rescan(&palette).unwrap(), scan(2, 1, 1));
+    let reparsed = &lib.entries()[1];
+    assert_eq!(reparsed.size, original[1].size);
+    assert_eq!(reparsed.block_count, original[1].block_count);
+    assert_eq!(reparsed.unsupported_ids, original[1].unsupported_ids);
+    assert_eq!(lib.rescan(&palette).unwrap(), scan(3, 0, 1));
+
+    // Another palette changes the unsupported counts, so every entry is stale.
+    let empty = PaletteMap::default();
+    assert_eq!(lib.rescan(&empty).unwrap(), scan(0, 3, 1));
+    assert!(
+        lib.entries()
+            .iter()
+            .all(|m| m.unsupported_blocks == m.block_count)
+    );
+
+    // Removed files drop out, and the rewritten index no longer lists them.
+    fs::remove_file(&lakeside).unwrap();
+    fs::remove_file(dir.join("broken.schem")).unwrap();
+    assert_eq!(lib.rescan(&empty).unwrap(), scan(2, 0, 0));
+    assert_eq!(lib.rescan(&empty).unwrap(), scan(2, 0, 0));
+    assert_eq!(lib.entries().len(), 2);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn query_filters_by_name_footprint_and_support_then_sorts() {
+    let dir = scratch_dir("library-query");
+    fs::copy(fixture("0213-wizard.schem"), dir.join("wizard.schem")).unwrap();
+    fs::copy(fixture("anvilstead.schem"), dir.join("anvilstead.schem")).unwrap();
+    fs::copy(fixture("lakeside-manor.schem"), dir.join("lakeside.schem")).unwrap();
+    let (lib, _) = SchematicLibrary::open(&dir, &stone_palette()).unwrap();
+
+    let all = lib.query(&SchematicQuery::default());
+    assert_eq!(all.len(), 3);
+    assert_eq!(lib.path_of(all[0]), dir.join("anvilstead.schem"));
+
+    let named = lib.query(&SchematicQuery {
+        name_contains: Some("WIZ".into()),
+        ..Default::default()
+    });
+    assert_eq!(named.len(), 1);
+    assert_eq!(named[0].name(), "wizard");
+
+    let (fx, _, fz) = named[0].size;
+    let fits = lib.query(&SchematicQuery {
+        max_footprint: Some((fx, fz)),
+        ..Default::default()
+    });
+    assert!(fits.iter().all(|m| m.size.0 <= fx && m.size.2 <= fz));
+    assert!(fits.iter().any(|m| m.name() == "wizard"));
+    let too_small = lib.query(&SchematicQuery {
+        max_footprint: Some((fx - 1, fz)),
+        ..Default::default()
+    });
+    assert!(too_small.iter().all(|m| m.name() != "wizard"));
+
+    let supported = lib.query(&SchematicQuery {
+        supported_only: true,
+        ..Default::default()
+    });
+    assert!(supported.iter().all(|m| m.is_fully_supported()));
+
+    let by_volume = lib.query(&SchematicQuery {
+        sort: SchematicSort::Volume,
+        ..Default::default()
+    });
+    assert!(by_volume.windows(2).all(|w| w[0].volume() >= w[1].volume()));
+    let by_blocks = lib.query(&SchematicQuery {
+        sort: SchematicSort::BlockCount,
+        ..Default::default()
+    });
+    assert!(
+        by_blocks
+            .windows(2)
+            .all(|w| w[0].block_count >= w[1].block_count)
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,66 @@
+//! Schematic fixtures imported straight into structures.
+
+use std::path::{Path, PathBuf};
+
+use geist_blocks::BlockRegistry;
+use geist_io::{PaletteMap, StructureFromSchematic, count_blocks_in_file};
+use geist_structures::Structure;
+
+fn root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../..")
+}
+
+fn load_registry() -> BlockRegistry {
+    let vox = root().join("assets/voxels");
+    BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml")).unwrap()
+}
+
+#[test]
+fn schematic_loads_as_a_structure_with_its_palette_and_unmapped_report() {
+    let reg = load_registry();
+    let path: &Path = &root().join("schematics/0213-wizard.schem");
+    // Two rules, so every other block id is reported as unmapped.
+    let palette = PaletteMap::from_toml_str(
+        r#"
+[[rules]]
+from = "minecraft:stone"
+to = { name = "stone" }
+
+[[rules]]
+from = "minecraft:podzol"
+to = { name = "dirt" }
+"#,
+    )
+    .unwrap();
+    let loaded = Structure::from_schematic(path, &reg, &palette).unwrap();
+    let st = &loaded.structure;
+    assert_eq!((st.sx, st.sy, st.sz), (36, 81, 37));
+    assert_eq!(st.blocks.len(), 36 * 81 * 37);
+    assert_eq!(st.id, 0);
+
+    let counts = count_blocks_in_file(path).unwrap();
+    let count_of = |id: &str| {
+        counts
+            .iter()
+            .find(|(name, _)| name == id)
+            .map_or(0, |(_, n)| *n)
+    };
+    let cells_of = |name: &str| {
+        let id = reg.id_by_name(name).unwrap();
+        st.blocks.iter().filter(|b| b.id == id).count() as u64
+    };
+    assert_eq!(cells_of("stone"), count_of("minecraft:stone"));
+    assert_eq!(cells_of("dirt"), count_of("minecraft:podzol"));
+
+    let expected: Vec<(String, u64)> = counts
+        .iter()
+        .filter(|(name, _)| name != "minecraft:stone" && name != "minecraft:podzol")
+        .cloned()
+        .collect();
+    assert_eq!(loaded.unmapped, expected);
+    let unknown: u64 = expected.iter().map(|(_, n)| n).sum();
+    assert_eq!(cells_of("unknown"), unknown);
+    // Everything else is air.
+    let solid = cells_of("stone") + cells_of("dirt") + unknown;
+    assert_eq!(cells_of("air"), st.blocks.len() as u64 - solid);
+}
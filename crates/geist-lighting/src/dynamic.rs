@@ -0,0 +1,270 @@
+//! Moving point lights (hand-held torches, projectiles) that never touch chunk lighting.
+//!
+//! Lights are flooded into a small world-space volume of block light that the renderer
+//! uploads as its own texture and combines with the chunk atlases in the shader, so moving
+//! a light costs one bounded flood fill and one upload instead of chunk recomputes. The
+//! volume is only rebuilt when a light changes voxel or level, or after `invalidate`.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use geist_geom::{IVec3, Vec3, WorldPos};
+
+/// Per-block falloff of a dynamic light; matches one coarse step of static emitters.
+pub const DEFAULT_DYNAMIC_ATTENUATION: u8 = 32;
+/// Largest edge of the lit volume; lights outside this box around the focus are skipped.
+pub const DYNAMIC_LIGHT_EXTENT: i32 = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DynamicLightId(u32);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DynamicLight {
+    pub pos: Vec3,
+    /// Block light at the light's own voxel (0..255).
+    pub level: u8,
+    /// Light lost per voxel step; reach is `level / attenuation` voxels.
+    pub attenuation: u8,
+}
+
+impl DynamicLight {
+    pub fn new(pos: Vec3, level: u8) -> Self {
+        Self {
+            pos,
+            level,
+            attenuation: DEFAULT_DYNAMIC_ATTENUATION,
+        }
+    }
+
+    #[inline]
+    fn cell(&self) -> IVec3 {
+        IVec3::new(
+            self.pos.x.floor() as i32,
+            self.pos.y.floor() as i32,
+            self.pos.z.floor() as i32,
+        )
+    }
+
+    #[inline]
+    fn reach(&self) -> i32 {
+        (self.level / self.attenuation.max(1)) as i32
+    }
+}
+
+/// Dynamic block light over a world-space box, packed as Y slices in a 2D grid the same
+/// way chunk atlases are (without seam rings): slice `y` sits at tile
+/// `(y % grid_cols, y / grid_cols)`, texel `(x, z)` within the tile.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DynamicLightVolume {
+    /// World position of texel `(0, 0)` of slice 0.
+    pub origin: WorldPos,
+    pub dims: IVec3,
+    pub grid_cols: usize,
+    pub grid_rows: usize,
+    pub width: usize,
+    pub height: usize,
+    /// One byte of block light per texel, `width * height` long.
+    pub data: Vec<u8>,
+}
+
+impl DynamicLightVolume {
+    fn new(origin: WorldPos, dims: IVec3) -> Self {
+        let grid_cols = ((dims.y as f32).sqrt().ceil() as usize).max(1);
+        let grid_rows = (dims.y as usize).div_ceil(grid_cols).max(1);
+        let width = dims.x as usize * grid_cols;
+        let height = dims.z as usize * grid_rows;
+        Self {
+            origin,
+            dims,
+            grid_cols,
+            grid_rows,
+            width,
+            height,
+            data: vec![0; width * height],
+        }
+    }
+
+    #[inline]
+    fn texel(&self, v: IVec3) -> Option<usize> {
+        let d = self.dims;
+        if v.x < 0 || v.y < 0 || v.z < 0 || v.x >= d.x || v.y >= d.y || v.z >= d.z {
+            return None;
+        }
+        let (tx, ty) = (v.y as usize % self.grid_cols, v.y as usize / self.grid_cols);
+        let px = tx * d.x as usize + v.x as usize;
+        let py = ty * d.z as usize + v.z as usize;
+        Some(py * self.width + px)
+    }
+
+    /// Dynamic light at a world voxel; 0 outside the volume.
+    pub fn get(&self, w: WorldPos) -> u8 {
+        self.texel(w.0 - self.origin.0)
+            .map(|i| self.data[i])
+            .unwrap_or(0)
+    }
+}
+
+/// What a volume was flooded from: `(cell, level, attenuation)` per light plus its bounds.
+#[derive(PartialEq, Eq)]
+struct BuiltFrom {
+    lights: Vec<(IVec3, u8, u8)>,
+    lo: IVec3,
+    hi: IVec3,
+}
+
+#[derive(Default)]
+pub struct DynamicLights {
+    next_id: u32,
+    lights: BTreeMap<DynamicLightId, DynamicLight>,
+    built: Option<BuiltFrom>,
+    volume: Option<DynamicLightVolume>,
+}
+
+impl DynamicLights {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, light: DynamicLight) -> DynamicLightId {
+        let id = DynamicLightId(self.next_id);
+        self.next_id = self.next_id.wrapping_add(1);
+        self.lights.insert(id, light);
+        id
+    }
+
+    /// Returns false when `id` is not a live light.
+    pub fn move_to(&mut self, id: DynamicLightId, pos: Vec3) -> bool {
+        match self.lights.get_mut(&id) {
+            Some(l) => {
+                l.pos = pos;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn set_level(&mut self, id: DynamicLightId, level: u8) -> bool {
+        match self.lights.get_mut(&id) {
+            Some(l) => {
+                l.level = level;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove(&mut self, id: DynamicLightId) -> bool {
+        self.lights.remove(&id).is_some()
+    }
+
+    pub fn get(&self, id: DynamicLightId) -> Option<&DynamicLight> {
+        self.lights.get(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.lights.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lights.is_empty()
+    }
+
+    /// Force the next `update` to re-flood, e.g. after blocks near a light were edited.
+    pub fn invalidate(&mut self) {
+        self.built = None;
+    }
+
+    /// Current volume; `None` while no light reaches anything near the focus.
+    pub fn volume(&self) -> Option<&DynamicLightVolume> {
+        self.volume.as_ref()
+    }
+
+    /// Rebuild the volume if any light changed voxel or level since the last call. Lights
+    /// flood through voxels for which `is_opaque` is false; opaque voxels stay dark, as in
+    /// chunk lighting, and faces sample the open voxel in front of them. Returns true when
+    /// the volume changed and needs uploading.
+    pub fn update(&mut self, focus: WorldPos, is_opaque: impl Fn(i32, i32, i32) -> bool) -> bool {
+        let half = DYNAMIC_LIGHT_EXTENT / 2;
+        let (fmin, fmax) = (
+            focus.0 - IVec3::new(half, half, half),
+            focus.0 + IVec3::new(half - 1, half - 1, half - 1),
+        );
+        let mut lo = IVec3::new(i32::MAX, i32::MAX, i32::MAX);
+        let mut hi = IVec3::new(i32::MIN, i32::MIN, i32::MIN);
+        let mut sig = Vec::with_capacity(self.lights.len());
+        for l in self.lights.values() {
+            if l.level == 0 {
+                continue;
+            }
+            let (c, r) = (l.cell(), l.reach());
+            let (a, b) = (c - IVec3::new(r, r, r), c + IVec3::new(r, r, r));
+            if b.x < fmin.x || b.y < fmin.y || b.z < fmin.z {
+                continue;
+            }
+            if a.x > fmax.x || a.y > fmax.y || a.z > fmax.z {
+                continue;
+            }
+            lo = IVec3::new(lo.x.min(a.x), lo.y.min(a.y), lo.z.min(a.z));
+            hi = IVec3::new(hi.x.max(b.x), hi.y.max(b.y), hi.z.max(b.z));
+            sig.push((c, l.level, l.attenuation.max(1)));
+        }
+        if sig.is_empty() {
+            self.built = Some(BuiltFrom {
+                lights: sig,
+                lo: IVec3::ZERO,
+                hi: IVec3::ZERO,
+            });
+            return self.volume.take().is_some();
+        }
+        let lo = IVec3::new(lo.x.max(fmin.x), lo.y.max(fmin.y), lo.z.max(fmin.z));
+        let hi = IVec3::new(hi.x.min(fmax.x), hi.y.min(fmax.y), hi.z.min(fmax.z));
+        let key = BuiltFrom {
+            lights: sig,
+            lo,
+            hi,
+        };
+        if self.built.as_ref() == Some(&key) {
+            return false;
+        }
+        let dims = hi - lo + IVec3::new(1, 1, 1);
+        let mut vol = DynamicLightVolume::new(WorldPos(lo), dims);
+        let mut queue: VecDeque<(IVec3, u8)> = VecDeque::new();
+        for &(cell, level, att) in &key.lights {
+            queue.push_back((cell, level));
+            while let Some((w, v)) = queue.pop_front() {
+                let Some(i) = vol.texel(w - lo) else {
+                    continue;
+                };
+                if vol.data[i] >= v {
+                    continue;
+                }
+                vol.data[i] = v;
+                // The light's own voxel may be solid (a torch held against a wall); it
+                // still lights its faces but does not flood through the block.
+                if is_opaque(w.x, w.y, w.z) {
+                    continue;
+                }
+                let next = v.saturating_sub(att);
+                if next == 0 {
+                    continue;
+                }
+                for d in [
+                    IVec3::new(1, 0, 0),
+                    IVec3::new(-1, 0, 0),
+                    IVec3::new(0, 1, 0),
+                    IVec3::new(0, -1, 0),
+                    IVec3::new(0, 0, 1),
+                    IVec3::new(0, 0, -1),
+                ] {
+                    let n = w + d;
+                    if is_opaque(n.x, n.y, n.z) {
+                        continue;
+                    }
+                    queue.push_back((n, next));
+                }
+            }
+        }
+        self.built = Some(key);
+        self.volume = Some(vol);
+        true
+    }
+}
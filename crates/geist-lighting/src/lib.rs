@@ -5,12 +5,21 @@ use geist_blocks::BlockRegistry;
 use geist_blocks::micro::micro_face_cell_open_s2;
 use geist_blocks::types::Block;
 use geist_chunk::ChunkBuf;
+use geist_geom::{IVec3, WorldPos};
 use geist_world::{ChunkCoord, World};
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
+mod dynamic;
 mod micro;
+mod seam;
+pub use dynamic::{
+    DEFAULT_DYNAMIC_ATTENUATION, DYNAMIC_LIGHT_EXTENT, DynamicLight, DynamicLightId,
+    DynamicLightVolume, DynamicLights,
+};
+pub use seam::{BorderChecksums, SeamFace, SeamInputs, SeamMismatch};
 // Removed alternative iterative mode implementation.
 
 // Runtime toggle: allow disabling S=2 micro lighting entirely.
@@ -92,6 +101,12 @@ fn occ_bit(occ: u8, x: usize, y: usize, z: usize) -> bool {
     (occ & (1u8 << idx)) != 0
 }
 
+/// Flicker class stored in a neighbor plane, or steady when the neighbor has none.
+#[inline]
+pub(crate) fn plane_class(plane: &Option<Arc<[u8]>>, i: usize) -> u8 {
+    plane.as_ref().and_then(|p| p.get(i).copied()).unwrap_or(0)
+}
+
 #[inline]
 fn occ8_for(reg: &BlockRegistry, b: Block) -> Option<u8> {
     reg.get(b.id).and_then(|ty| ty.variant(b.state).occupancy)
@@ -178,12 +193,57 @@ fn can_cross_face_s2(
     false
 }
 
+/// Day/night ceiling on skylight, shared by a `LightingStore` and every grid computed from
+/// it so sampling follows `set_skylight_max` without recomputing chunks. Grids store sky at
+/// full strength; only sampling scales it. The ceiling never drops below the world's night
+/// ambient (starlight), so open sky keeps a floor at midnight while caves stay dark.
+#[derive(Clone, Debug)]
+pub(crate) struct SkylightMax {
+    day: Arc<AtomicU8>,
+    night: Arc<AtomicU8>,
+}
+
+impl SkylightMax {
+    fn full() -> Self {
+        Self {
+            day: Arc::new(AtomicU8::new(MAX_SKYLIGHT)),
+            night: Arc::new(AtomicU8::new(0)),
+        }
+    }
+
+    #[inline]
+    fn get(&self) -> u8 {
+        self.day
+            .load(Ordering::Relaxed)
+            .max(self.night.load(Ordering::Relaxed))
+    }
+
+    #[inline]
+    fn night(&self) -> u8 {
+        self.night.load(Ordering::Relaxed)
+    }
+}
+
+const MAX_SKYLIGHT: u8 = 255;
+
+/// Suggested starlight for surface worlds; a fresh `LightingStore` has none until
+/// `set_night_ambient` is called.
+pub const DEFAULT_NIGHT_AMBIENT: u8 = 24;
+
+/// Skylight `sky` under a day/night ceiling of `max` (255 = noon).
+#[inline]
+pub fn scale_skylight(sky: u8, max: u8) -> u8 {
+    ((sky as u16 * max as u16 + 127) / 255) as u8
+}
+
 pub struct LightGrid {
     pub(crate) sx: usize,
     pub(crate) sy: usize,
     pub(crate) sz: usize,
     pub(crate) skylight: Vec<u8>,
     pub(crate) block_light: Vec<u8>,
+    // Flicker class (`FlickerClass as u8`) of the emitter supplying `block_light`
+    pub(crate) block_flicker: Vec<u8>,
     pub(crate) beacon_light: Vec<u8>,
     pub(crate) beacon_dir: Vec<u8>,
     // Optional micro-light fields (present in MicroS2 mode)
@@ -203,7 +263,7 @@ pub struct LightGrid {
     pub(crate) mnb_zp_sky: Option<Arc<[u8]>>,
     pub(crate) mnb_zn_blk: Option<Arc<[u8]>>,
     pub(crate) mnb_zp_blk: Option<Arc<[u8]>>,
-    // Y faces (usually not chunked vertically): size = mzs * mxs (index = mz * mxs + mx)
+    // Y faces, exchanged with the chunks above and below: size = mzs * mxs (index = mz * mxs + mx)
     pub(crate) mnb_yn_sky: Option<Arc<[u8]>>,
     pub(crate) mnb_yp_sky: Option<Arc<[u8]>>,
     pub(crate) mnb_yn_blk: Option<Arc<[u8]>>,
@@ -225,20 +285,45 @@ pub struct LightGrid {
     pub(crate) nb_zn_bcn_dir: Option<Arc<[u8]>>,
     pub(crate) nb_zp_bcn_dir: Option<Arc<[u8]>>,
     pub micro_change: BorderChangeMask,
+    pub(crate) sky_max: SkylightMax,
+    // Checksums of the neighbour planes this grid was seeded from
+    pub(crate) seam_inputs: SeamInputs,
 }
 
 impl LightGrid {
+    /// Stored skylight scaled by the current day/night ceiling.
+    #[inline]
+    fn sky(&self, v: u8) -> u8 {
+        match self.sky_max.get() {
+            MAX_SKYLIGHT => v,
+            max => scale_skylight(v, max),
+        }
+    }
+
     #[inline]
     pub fn skylight_at(&self, x: usize, y: usize, z: usize) -> u8 {
         let idx = (y * self.sz + z) * self.sx + x;
         self.skylight[idx]
     }
 
+    /// Light from emitters and beacons at a local cell, without skylight.
+    #[inline]
+    pub fn block_light_at(&self, x: usize, y: usize, z: usize) -> u8 {
+        let idx = (y * self.sz + z) * self.sx + x;
+        self.block_light[idx].max(self.beacon_light[idx])
+    }
+
     #[inline]
     fn idx(&self, x: usize, y: usize, z: usize) -> usize {
         (y * self.sz + z) * self.sx + x
     }
 
+    /// Whether the grid carries the S=2 micro field, i.e. came from a `Full` pass.
+    #[inline]
+    pub fn has_micro(&self) -> bool {
+        self.m_sky.is_some()
+    }
+
     pub fn new(sx: usize, sy: usize, sz: usize) -> Self {
         Self {
             sx,
@@ -246,6 +331,7 @@ impl LightGrid {
             sz,
             skylight: vec![0; sx * sy * sz],
             block_light: vec![0; sx * sy * sz],
+            block_flicker: vec![0; sx * sy * sz],
             beacon_light: vec![0; sx * sy * sz],
             beacon_dir: vec![0; sx * sy * sz],
             m_sky: None,
@@ -282,6 +368,8 @@ impl LightGrid {
             nb_zn_bcn_dir: None,
             nb_zp_bcn_dir: None,
             micro_change: BorderChangeMask::default(),
+            sky_max: SkylightMax::full(),
+            seam_inputs: [None; 6],
         }
     }
 
@@ -294,9 +382,11 @@ impl LightGrid {
         let sy = buf.sy;
         let sz = buf.sz;
         let mut lg = Self::new(sx, sy, sz);
+        lg.sky_max = store.skylight_max.clone();
         use std::collections::VecDeque;
         let mut q_sky = VecDeque::new();
-        let sun_level = store.skylight_max();
+        // Seed at full sun; the day/night ceiling is applied when sampling.
+        let sun_level = MAX_SKYLIGHT;
         for z in 0..sz {
             for x in 0..sx {
                 let mut open_above = true;
@@ -337,6 +427,7 @@ impl LightGrid {
                                 q_beacon.push_back((x, y, z, em, 0, sc, tc, vc));
                             } else {
                                 lg.block_light[idx] = em;
+                                lg.block_flicker[idx] = ty.light_flicker(b.state).as_u8();
                                 let att = ty.omni_attenuation();
                                 q.push_back((x, y, z, em, att));
                             }
@@ -345,8 +436,26 @@ impl LightGrid {
                 }
             }
         }
+        // Runtime emitters from the store (interactive placements) seed as omni lights,
+        // matching the micro pass.
+        for (x, y, z, level, _is_beacon) in store.emitters_for_chunk(buf.coord) {
+            if level == 0 || x >= sx || y >= sy || z >= sz {
+                continue;
+            }
+            let idx = lg.idx(x, y, z);
+            if lg.block_light[idx] < level {
+                lg.block_light[idx] = level;
+                let b = buf.get_local(x, y, z);
+                lg.block_flicker[idx] = reg
+                    .get(b.id)
+                    .map(|ty| ty.light_flicker(b.state).as_u8())
+                    .unwrap_or(0);
+                q.push_back((x, y, z, level, 32));
+            }
+        }
         // Seed from neighbors
         let nb = store.get_neighbor_borders(buf.coord);
+        lg.seam_inputs = nb.inputs;
         lg.nb_xn_blk = nb.xn.clone();
         lg.nb_xp_blk = nb.xp.clone();
         lg.nb_zn_blk = nb.zn.clone();
@@ -373,6 +482,7 @@ impl LightGrid {
                         let idx = lg.idx(0, y, z);
                         if lg.block_light[idx] < v8 {
                             lg.block_light[idx] = v8;
+                            lg.block_flicker[idx] = plane_class(&nb.flk_xn, y * sz + z);
                             q.push_back((0, y, z, v8, 32));
                         }
                     }
@@ -389,6 +499,7 @@ impl LightGrid {
                         let idx = lg.idx(xx, y, z);
                         if lg.block_light[idx] < v8 {
                             lg.block_light[idx] = v8;
+                            lg.block_flicker[idx] = plane_class(&nb.flk_xp, y * sz + z);
                             q.push_back((xx, y, z, v8, 32));
                         }
                     }
@@ -404,6 +515,7 @@ impl LightGrid {
                         let idx = lg.idx(x, y, 0);
                         if lg.block_light[idx] < v8 {
                             lg.block_light[idx] = v8;
+                            lg.block_flicker[idx] = plane_class(&nb.flk_zn, y * sx + x);
                             q.push_back((x, y, 0, v8, 32));
                         }
                     }
@@ -420,6 +532,7 @@ impl LightGrid {
                         let idx = lg.idx(x, y, zz);
                         if lg.block_light[idx] < v8 {
                             lg.block_light[idx] = v8;
+                            lg.block_flicker[idx] = plane_class(&nb.flk_zp, y * sx + x);
                             q.push_back((x, y, zz, v8, 32));
                         }
                     }
@@ -435,6 +548,7 @@ impl LightGrid {
                         let idx = lg.idx(x, 0, z);
                         if lg.block_light[idx] < v8 {
                             lg.block_light[idx] = v8;
+                            lg.block_flicker[idx] = plane_class(&nb.flk_yn, z * sx + x);
                             q.push_back((x, 0, z, v8, 32));
                         }
                     }
@@ -451,6 +565,7 @@ impl LightGrid {
                         let idx = lg.idx(x, yy, z);
                         if lg.block_light[idx] < v8 {
                             lg.block_light[idx] = v8;
+                            lg.block_flicker[idx] = plane_class(&nb.flk_yp, z * sx + x);
                             q.push_back((x, yy, z, v8, 32));
                         }
                     }
@@ -685,6 +800,7 @@ impl LightGrid {
             if level_i <= 1 {
                 continue;
             }
+            let class = lg.block_flicker[lg.idx(x, y, z)];
             let mut try_push = |nx: i32, ny: i32, nz: i32, face: usize| {
                 if nx < 0
                     || ny < 0
@@ -709,6 +825,7 @@ impl LightGrid {
                     let v8 = v as u8;
                     if lg.block_light[idx] < v8 {
                         lg.block_light[idx] = v8;
+                        lg.block_flicker[idx] = class;
                         q.push_back((nx as usize, ny as usize, nz as usize, v8, atten));
                     }
                 }
@@ -872,12 +989,13 @@ impl LightGrid {
                         .as_ref()
                         .and_then(|p| p.get(idxp).cloned())
                         .unwrap_or(0);
-                    let maxn = sky.max(blk).max(bcn);
+                    let maxn = self.sky(sky).max(blk).max(bcn);
                     if maxn > 0 {
                         return maxn;
                     }
                     let i = self.idx(self.sx - 1, y, z);
-                    return self.skylight[i]
+                    return self
+                        .sky(self.skylight[i])
                         .max(self.block_light[i])
                         .max(self.beacon_light[i]);
                 }
@@ -898,12 +1016,13 @@ impl LightGrid {
                         .as_ref()
                         .and_then(|p| p.get(idxp).cloned())
                         .unwrap_or(0);
-                    let maxn = sky.max(blk).max(bcn);
+                    let maxn = self.sky(sky).max(blk).max(bcn);
                     if maxn > 0 {
                         return maxn;
                     }
                     let i = self.idx(0, y, z);
-                    return self.skylight[i]
+                    return self
+                        .sky(self.skylight[i])
                         .max(self.block_light[i])
                         .max(self.beacon_light[i]);
                 }
@@ -924,12 +1043,13 @@ impl LightGrid {
                         .as_ref()
                         .and_then(|p| p.get(idxp).cloned())
                         .unwrap_or(0);
-                    let maxn = sky.max(blk).max(bcn);
+                    let maxn = self.sky(sky).max(blk).max(bcn);
                     if maxn > 0 {
                         return maxn;
                     }
                     let i = self.idx(x, y, self.sz - 1);
-                    return self.skylight[i]
+                    return self
+                        .sky(self.skylight[i])
                         .max(self.block_light[i])
                         .max(self.beacon_light[i]);
                 }
@@ -950,12 +1070,13 @@ impl LightGrid {
                         .as_ref()
                         .and_then(|p| p.get(idxp).cloned())
                         .unwrap_or(0);
-                    let maxn = sky.max(blk).max(bcn);
+                    let maxn = self.sky(sky).max(blk).max(bcn);
                     if maxn > 0 {
                         return maxn;
                     }
                     let i = self.idx(x, y, 0);
-                    return self.skylight[i]
+                    return self
+                        .sky(self.skylight[i])
                         .max(self.block_light[i])
                         .max(self.beacon_light[i]);
                 }
@@ -964,7 +1085,7 @@ impl LightGrid {
             return 0;
         }
         let i = self.idx(nx as usize, ny as usize, nz as usize);
-        self.skylight[i]
+        self.sky(self.skylight[i])
             .max(self.block_light[i])
             .max(self.beacon_light[i])
     }
@@ -972,7 +1093,8 @@ impl LightGrid {
     #[inline]
     pub fn sample_face_local(&self, x: usize, y: usize, z: usize, face: usize) -> u8 {
         let i = self.idx(x, y, z);
-        let local = self.skylight[i]
+        let local = self
+            .sky(self.skylight[i])
             .max(self.block_light[i])
             .max(self.beacon_light[i]);
         let nb = self.neighbor_light_max(x, y, z, face);
@@ -999,7 +1121,7 @@ impl LightGrid {
             let lval = |mx: usize, my: usize, mz: usize| -> u8 {
                 if mx < mxs && my < mys && mz < mzs {
                     let i = (my * mzs + mz) * mxs + mx;
-                    ms[i].max(mb[i])
+                    self.sky(ms[i]).max(mb[i])
                 } else {
                     0
                 }
@@ -1027,7 +1149,7 @@ impl LightGrid {
                             } else {
                                 if let Some(ref nbp) = self.mnb_xp_sky {
                                     let idx = my * mzs + mz;
-                                    let sv = *nbp.get(idx).unwrap_or(&0);
+                                    let sv = self.sky(*nbp.get(idx).unwrap_or(&0));
                                     sv.max(
                                         *self
                                             .mnb_xp_blk
@@ -1057,7 +1179,7 @@ impl LightGrid {
                             } else {
                                 if let Some(ref nbp) = self.mnb_xn_sky {
                                     let idx = my * mzs + mz;
-                                    let sv = *nbp.get(idx).unwrap_or(&0);
+                                    let sv = self.sky(*nbp.get(idx).unwrap_or(&0));
                                     sv.max(
                                         *self
                                             .mnb_xn_blk
@@ -1087,7 +1209,7 @@ impl LightGrid {
                             } else {
                                 if let Some(ref nbp) = self.mnb_zp_sky {
                                     let idx = my * mxs + mx;
-                                    let sv = *nbp.get(idx).unwrap_or(&0);
+                                    let sv = self.sky(*nbp.get(idx).unwrap_or(&0));
                                     sv.max(
                                         *self
                                             .mnb_zp_blk
@@ -1117,7 +1239,7 @@ impl LightGrid {
                             } else {
                                 if let Some(ref nbp) = self.mnb_zn_sky {
                                     let idx = my * mxs + mx;
-                                    let sv = *nbp.get(idx).unwrap_or(&0);
+                                    let sv = self.sky(*nbp.get(idx).unwrap_or(&0));
                                     sv.max(
                                         *self
                                             .mnb_zn_blk
@@ -1147,7 +1269,7 @@ impl LightGrid {
                             } else {
                                 if let Some(ref nbp) = self.mnb_yp_sky {
                                     let idx = mz * mxs + mx;
-                                    let sv = *nbp.get(idx).unwrap_or(&0);
+                                    let sv = self.sky(*nbp.get(idx).unwrap_or(&0));
                                     sv.max(
                                         *self
                                             .mnb_yp_blk
@@ -1177,7 +1299,7 @@ impl LightGrid {
                             } else {
                                 if let Some(ref nbp) = self.mnb_yn_sky {
                                     let idx = mz * mxs + mx;
-                                    let sv = *nbp.get(idx).unwrap_or(&0);
+                                    let sv = self.sky(*nbp.get(idx).unwrap_or(&0));
                                     sv.max(
                                         *self
                                             .mnb_yn_blk
@@ -1203,7 +1325,8 @@ impl LightGrid {
                 .max(self.beacon_light[macro_i]);
         }
         let i = self.idx(x, y, z);
-        let local = self.skylight[i]
+        let local = self
+            .sky(self.skylight[i])
             .max(self.block_light[i])
             .max(self.beacon_light[i]);
         // Compute neighbor coords
@@ -1257,7 +1380,8 @@ impl LightGrid {
                 && sz_i < buf.sz as i32
             {
                 let idx = self.idx(sx_i as usize, sy_i as usize, sz_i as usize);
-                let v = self.skylight[idx]
+                let v = self
+                    .sky(self.skylight[idx])
                     .max(self.block_light[idx])
                     .max(self.beacon_light[idx]);
                 if v > nb_max {
@@ -1363,6 +1487,14 @@ pub struct LightBorders {
     pub bcn_dir_xp: Arc<[u8]>,
     pub bcn_dir_zn: Arc<[u8]>,
     pub bcn_dir_zp: Arc<[u8]>,
+    pub flk_xn: Arc<[u8]>,
+    pub flk_xp: Arc<[u8]>,
+    pub flk_zn: Arc<[u8]>,
+    pub flk_zp: Arc<[u8]>,
+    pub flk_yn: Arc<[u8]>,
+    pub flk_yp: Arc<[u8]>,
+    /// Checksums of the neighbour planes the grid was lit with (see `SeamInputs`).
+    pub inputs: SeamInputs,
 }
 
 impl LightBorders {
@@ -1390,6 +1522,13 @@ impl LightBorders {
             bcn_dir_xp: vec![5; sy * sz].into(),
             bcn_dir_zn: vec![5; sy * sx].into(),
             bcn_dir_zp: vec![5; sy * sx].into(),
+            flk_xn: vec![0; sy * sz].into(),
+            flk_xp: vec![0; sy * sz].into(),
+            flk_zn: vec![0; sy * sx].into(),
+            flk_zp: vec![0; sy * sx].into(),
+            flk_yn: vec![0; sx * sz].into(),
+            flk_yp: vec![0; sx * sz].into(),
+            inputs: [None; 6],
         }
     }
     pub fn from_grid(grid: &LightGrid) -> Self {
@@ -1416,11 +1555,18 @@ impl LightBorders {
         let mut bcn_dir_xp = vec![5u8; sy * sz];
         let mut bcn_dir_zn = vec![5u8; sy * sx];
         let mut bcn_dir_zp = vec![5u8; sy * sx];
+        let mut flk_xn = vec![0u8; sy * sz];
+        let mut flk_xp = vec![0u8; sy * sz];
+        let mut flk_zn = vec![0u8; sy * sx];
+        let mut flk_zp = vec![0u8; sy * sx];
+        let mut flk_yn = vec![0u8; sx * sz];
+        let mut flk_yp = vec![0u8; sx * sz];
         let idx3 = |x: usize, y: usize, z: usize| -> usize { (y * sz + z) * sx + x };
         for z in 0..sz {
             for y in 0..sy {
                 let ii = y * sz + z;
                 xn[ii] = grid.block_light[idx3(0, y, z)];
+                flk_xn[ii] = grid.block_flicker[idx3(0, y, z)];
                 sk_xn[ii] = grid.skylight[idx3(0, y, z)];
                 bcn_xn[ii] = grid.beacon_light[idx3(0, y, z)];
                 let d = grid.beacon_dir[idx3(0, y, z)];
@@ -1431,6 +1577,7 @@ impl LightBorders {
             for y in 0..sy {
                 let ii = y * sz + z;
                 xp[ii] = grid.block_light[idx3(sx - 1, y, z)];
+                flk_xp[ii] = grid.block_flicker[idx3(sx - 1, y, z)];
                 sk_xp[ii] = grid.skylight[idx3(sx - 1, y, z)];
                 bcn_xp[ii] = grid.beacon_light[idx3(sx - 1, y, z)];
                 let d = grid.beacon_dir[idx3(sx - 1, y, z)];
@@ -1441,6 +1588,7 @@ impl LightBorders {
             for y in 0..sy {
                 let ii = y * sx + x;
                 zn[ii] = grid.block_light[idx3(x, y, 0)];
+                flk_zn[ii] = grid.block_flicker[idx3(x, y, 0)];
                 sk_zn[ii] = grid.skylight[idx3(x, y, 0)];
                 bcn_zn[ii] = grid.beacon_light[idx3(x, y, 0)];
                 let d = grid.beacon_dir[idx3(x, y, 0)];
@@ -1451,6 +1599,7 @@ impl LightBorders {
             for y in 0..sy {
                 let ii = y * sx + x;
                 zp[ii] = grid.block_light[idx3(x, y, sz - 1)];
+                flk_zp[ii] = grid.block_flicker[idx3(x, y, sz - 1)];
                 sk_zp[ii] = grid.skylight[idx3(x, y, sz - 1)];
                 bcn_zp[ii] = grid.beacon_light[idx3(x, y, sz - 1)];
                 let d = grid.beacon_dir[idx3(x, y, sz - 1)];
@@ -1461,6 +1610,7 @@ impl LightBorders {
             for x in 0..sx {
                 let ii = z * sx + x;
                 yn[ii] = grid.block_light[idx3(x, 0, z)];
+                flk_yn[ii] = grid.block_flicker[idx3(x, 0, z)];
                 sk_yn[ii] = grid.skylight[idx3(x, 0, z)];
                 bcn_yn[ii] = grid.beacon_light[idx3(x, 0, z)];
             }
@@ -1469,6 +1619,7 @@ impl LightBorders {
             for x in 0..sx {
                 let ii = z * sx + x;
                 yp[ii] = grid.block_light[idx3(x, sy - 1, z)];
+                flk_yp[ii] = grid.block_flicker[idx3(x, sy - 1, z)];
                 sk_yp[ii] = grid.skylight[idx3(x, sy - 1, z)];
                 bcn_yp[ii] = grid.beacon_light[idx3(x, sy - 1, z)];
             }
@@ -1496,6 +1647,13 @@ impl LightBorders {
             bcn_dir_xp: bcn_dir_xp.into(),
             bcn_dir_zn: bcn_dir_zn.into(),
             bcn_dir_zp: bcn_dir_zp.into(),
+            flk_xn: flk_xn.into(),
+            flk_xp: flk_xp.into(),
+            flk_zn: flk_zn.into(),
+            flk_zp: flk_zp.into(),
+            flk_yn: flk_yn.into(),
+            flk_yp: flk_yp.into(),
+            inputs: grid.seam_inputs,
         }
     }
 }
@@ -1505,27 +1663,145 @@ pub enum LightingMode {
     FullMicro = 0,
 }
 
+/// How much work one lighting pass spends on a chunk.
+///
+/// Large changes light with `Coarse` first so they show up at once, then refine to `Full`
+/// in the background and swap the atlas when that lands.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LightQuality {
+    /// Macro voxels only: one attenuation step per voxel and no S=2 micro field.
+    Coarse,
+    /// The regular micro pass.
+    #[default]
+    Full,
+}
+
 #[derive(Default, Debug, Clone, Copy)]
 pub struct LightingStoreStats {
     pub border_chunks: usize,
     pub emitter_chunks: usize,
     pub micro_chunks: usize,
+    pub seam_mismatches: u64,
+    pub last_seam_mismatch: Option<(SeamMismatch, Instant)>,
 }
 
 #[derive(Default)]
 struct LightingChunkEntry {
     borders: Option<LightBorders>,
+    // Checksums of `borders`, kept alongside them
+    checksums: Option<BorderChecksums>,
     emitters: Vec<(usize, usize, usize, u8, bool)>,
     micro_borders: Option<MicroBorders>,
+    levels: Option<LightLevels>,
 }
 
 impl LightingChunkEntry {
     #[inline]
     fn is_empty(&self) -> bool {
-        self.borders.is_none() && self.micro_borders.is_none() && self.emitters.is_empty()
+        self.borders.is_none()
+            && self.micro_borders.is_none()
+            && self.emitters.is_empty()
+            && self.levels.is_none()
+    }
+}
+
+/// Per-voxel light kept for gameplay queries after a chunk's grid is packed and dropped:
+/// skylight in the high nibble and block light in the low one, so a 64³ chunk costs
+/// 256 KiB, plus a byte per voxel of beacon light for chunks that have any. Levels are
+/// quantized to 16 steps.
+struct LightLevels {
+    data: Box<[u8]>,
+    beacon: Option<Box<[u8]>>,
+}
+
+impl LightLevels {
+    fn from_grid(lg: &LightGrid) -> Self {
+        let data = (0..lg.skylight.len())
+            .map(|i| (lg.skylight[i] & 0xF0) | (lg.block_light[i] >> 4))
+            .collect();
+        let beacon = lg
+            .beacon_light
+            .iter()
+            .any(|&v| v >= 0x10)
+            .then(|| lg.beacon_light.iter().map(|&v| v >> 4).collect());
+        Self { data, beacon }
+    }
+
+    /// `(sky, block, beacon)` at full scale (0..255).
+    #[inline]
+    fn get(&self, i: usize) -> (u8, u8, u8) {
+        let v = self.data[i];
+        let bcn = self.beacon.as_ref().map_or(0, |b| b[i]);
+        ((v >> 4) * 17, (v & 0x0F) * 17, bcn * 17)
     }
 }
 
+/// Light at one world voxel, split by channel. Skylight is already scaled by the
+/// day/night ceiling.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LightSample {
+    pub sky: u8,
+    pub block: u8,
+    pub beacon: u8,
+}
+
+impl LightSample {
+    /// The brightest channel, which is what faces at this voxel are lit with.
+    #[inline]
+    pub fn max(&self) -> u8 {
+        self.sky.max(self.block).max(self.beacon)
+    }
+}
+
+/// How skylight fades through water. The lighting BFS charges `sky_extinction`, and the
+/// water shaders use `transmittance` for the colour shift, so both describe one medium.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WaterMedium {
+    /// Skylight lost per micro step (half a block) inside water. Straight down this is the
+    /// whole cost; sideways and upward it adds to the usual skylight step.
+    pub sky_extinction: u8,
+    /// Fraction of red, green and blue light kept per block of water travelled.
+    pub transmittance: [f32; 3],
+}
+
+impl WaterMedium {
+    pub const DEFAULT: Self = Self {
+        sky_extinction: 5,
+        transmittance: [0.86, 0.95, 0.97],
+    };
+
+    /// Per-channel light remaining after `depth` blocks of water.
+    pub fn tint_at_depth(&self, depth: f32) -> [f32; 3] {
+        let d = depth.max(0.0);
+        self.transmittance.map(|t| t.clamp(0.0, 1.0).powf(d))
+    }
+}
+
+impl Default for WaterMedium {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// What lighting assumes lies below the world's lowest chunk row.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BelowWorld {
+    /// Open, unlit space (sky islands). Block light from anything built below the floor
+    /// still crosses the seam, but skylight never climbs back up out of the void.
+    #[default]
+    Void,
+    /// Bedrock: the floor seam is opaque and nothing below it seeds the lowest row.
+    Solid,
+}
+
+/// Bottom boundary of the world for seam seeding: chunks at `min_cy` take their -Y
+/// neighbour planes according to `below` instead of from whatever was lit underneath.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WorldFloor {
+    pub min_cy: i32,
+    pub below: BelowWorld,
+}
+
 pub struct LightingStore {
     sx: usize,
     sy: usize,
@@ -1533,7 +1809,11 @@ pub struct LightingStore {
     chunks: Mutex<HashMap<ChunkCoord, LightingChunkEntry>>,
     // Runtime mode selection
     mode: AtomicU8,
-    skylight_max: AtomicU8,
+    skylight_max: SkylightMax,
+    water: Mutex<WaterMedium>,
+    floor: Mutex<Option<WorldFloor>>,
+    seam_mismatch_count: AtomicU64,
+    last_seam_mismatch: Mutex<Option<(SeamMismatch, Instant)>>,
 }
 
 impl LightingStore {
@@ -1545,9 +1825,18 @@ impl LightingStore {
             chunks: Mutex::new(HashMap::new()),
             // FullMicro is the only supported mode
             mode: AtomicU8::new(LightingMode::FullMicro as u8),
-            skylight_max: AtomicU8::new(255),
+            skylight_max: SkylightMax::full(),
+            water: Mutex::new(WaterMedium::DEFAULT),
+            floor: Mutex::new(None),
+            seam_mismatch_count: AtomicU64::new(0),
+            last_seam_mismatch: Mutex::new(None),
         }
     }
+
+    #[inline]
+    fn dims(&self) -> IVec3 {
+        IVec3::from_dims(self.sx, self.sy, self.sz)
+    }
     /// Set the global lighting mode.
     pub fn set_mode(&self, m: LightingMode) {
         self.mode.store(m as u8, Ordering::Relaxed);
@@ -1557,11 +1846,78 @@ impl LightingStore {
         let _ = self.mode.load(Ordering::Relaxed);
         LightingMode::FullMicro
     }
+    /// Day/night ceiling on skylight (255 = noon). Grids computed from this store and
+    /// `light_at_world` apply it when sampling, so changing it never relights chunks.
     pub fn set_skylight_max(&self, level: u8) {
-        self.skylight_max.store(level, Ordering::Relaxed);
+        self.skylight_max.day.store(level, Ordering::Relaxed);
     }
+    /// The ceiling sampling applies: the day/night level, floored by `night_ambient`.
     pub fn skylight_max(&self) -> u8 {
-        self.skylight_max.load(Ordering::Relaxed)
+        self.skylight_max.get()
+    }
+    /// This world's starlight: the lowest skylight ceiling the night can reach. Sampling
+    /// picks it up at once; atlases packed afterwards carry it to the shaders, so chunks
+    /// already on the GPU keep the old floor until they are repacked.
+    pub fn set_night_ambient(&self, level: u8) {
+        self.skylight_max.night.store(level, Ordering::Relaxed);
+    }
+    pub fn night_ambient(&self) -> u8 {
+        self.skylight_max.night()
+    }
+    /// Keep `lg`'s light levels for `light_at_world`; call when a chunk's grid is accepted.
+    pub fn update_levels(&self, coord: ChunkCoord, lg: &LightGrid) {
+        let mut map = self.chunks.lock().unwrap();
+        map.entry(coord).or_default().levels = Some(LightLevels::from_grid(lg));
+    }
+    /// Light a player or mob would see at a world voxel: the brightest channel of
+    /// `sample_world`.
+    pub fn light_at_world(&self, wx: i32, wy: i32, wz: i32) -> Option<u8> {
+        self.sample_world(wx, wy, wz).map(|s| s.max())
+    }
+    /// Sky, block and beacon light at a world voxel, for light meters and spawn rules.
+    /// Reads the levels kept from the chunk's last lighting pass (quantized to 16 steps);
+    /// before those exist, voxels on the chunk's faces fall back to its stored border
+    /// planes. `None` when neither covers the voxel.
+    pub fn sample_world(&self, wx: i32, wy: i32, wz: i32) -> Option<LightSample> {
+        let dims = self.dims();
+        let (cpos, local) = WorldPos::new(wx, wy, wz).split(dims);
+        let map = self.chunks.lock().unwrap();
+        let entry = map.get(&ChunkCoord::from(cpos))?;
+        let (sky, block, beacon) = match entry.levels.as_ref() {
+            Some(levels) => levels.get(local.index(dims)),
+            None => border_sample(
+                entry.borders.as_ref()?,
+                local.to_usize(),
+                (self.sx, self.sy, self.sz),
+            )?,
+        };
+        Some(LightSample {
+            sky: scale_skylight(sky, self.skylight_max()),
+            block,
+            beacon,
+        })
+    }
+    /// Change the water model; chunks relit afterwards pick it up.
+    pub fn set_water_medium(&self, medium: WaterMedium) {
+        *self.water.lock().unwrap() = medium;
+    }
+    pub fn water_medium(&self) -> WaterMedium {
+        *self.water.lock().unwrap()
+    }
+    /// Set the world's bottom boundary; `None` (the default) treats the bottom like any
+    /// other seam. Chunks on the floor row must be relit to pick up a change.
+    pub fn set_world_floor(&self, floor: Option<WorldFloor>) {
+        *self.floor.lock().unwrap() = floor;
+    }
+    pub fn world_floor(&self) -> Option<WorldFloor> {
+        *self.floor.lock().unwrap()
+    }
+    /// What lies under `coord`'s -Y seam when it sits on the floor row.
+    #[inline]
+    fn below_floor(&self, coord: ChunkCoord) -> Option<BelowWorld> {
+        self.world_floor()
+            .filter(|f| f.min_cy == coord.cy)
+            .map(|f| f.below)
     }
     pub fn clear_chunk(&self, coord: ChunkCoord) {
         let mut map = self.chunks.lock().unwrap();
@@ -1571,6 +1927,7 @@ impl LightingStore {
         let mut map = self.chunks.lock().unwrap();
         map.retain(|_, entry| {
             entry.borders = None;
+            entry.checksums = None;
             !entry.is_empty()
         });
     }
@@ -1590,15 +1947,20 @@ impl LightingStore {
                 micro += 1;
             }
         }
+        drop(map);
         LightingStoreStats {
             border_chunks: borders,
             emitter_chunks: emitters,
             micro_chunks: micro,
+            seam_mismatches: self.seam_mismatch_count(),
+            last_seam_mismatch: self.last_seam_mismatch(),
         }
     }
     pub fn get_neighbor_borders(&self, coord: ChunkCoord) -> NeighborBorders {
+        let below = self.below_floor(coord);
         let map = self.chunks.lock().unwrap();
         let mut nb = NeighborBorders::empty(self.sx, self.sy, self.sz);
+        nb.inputs = SeamFace::ALL.map(|f| seam::neighbor_input(&map, coord, f, below));
         if let Some(b) = map
             .get(&coord.offset(-1, 0, 0))
             .and_then(|entry| entry.borders.as_ref())
@@ -1607,6 +1969,7 @@ impl LightingStore {
             nb.sk_xn = Some(b.sk_xp.clone());
             nb.bcn_xn = Some(b.bcn_xp.clone());
             nb.bcn_dir_xn = Some(b.bcn_dir_xp.clone());
+            nb.flk_xn = Some(b.flk_xp.clone());
         }
         if let Some(b) = map
             .get(&coord.offset(1, 0, 0))
@@ -1616,6 +1979,7 @@ impl LightingStore {
             nb.sk_xp = Some(b.sk_xn.clone());
             nb.bcn_xp = Some(b.bcn_xn.clone());
             nb.bcn_dir_xp = Some(b.bcn_dir_xn.clone());
+            nb.flk_xp = Some(b.flk_xn.clone());
         }
         if let Some(b) = map
             .get(&coord.offset(0, 0, -1))
@@ -1625,6 +1989,7 @@ impl LightingStore {
             nb.sk_zn = Some(b.sk_zp.clone());
             nb.bcn_zn = Some(b.bcn_zp.clone());
             nb.bcn_dir_zn = Some(b.bcn_dir_zp.clone());
+            nb.flk_zn = Some(b.flk_zp.clone());
         }
         if let Some(b) = map
             .get(&coord.offset(0, 0, 1))
@@ -1634,14 +1999,19 @@ impl LightingStore {
             nb.sk_zp = Some(b.sk_zn.clone());
             nb.bcn_zp = Some(b.bcn_zn.clone());
             nb.bcn_dir_zp = Some(b.bcn_dir_zn.clone());
+            nb.flk_zp = Some(b.flk_zn.clone());
         }
         if let Some(b) = map
             .get(&coord.offset(0, -1, 0))
             .and_then(|entry| entry.borders.as_ref())
+            .filter(|_| below != Some(BelowWorld::Solid))
         {
             nb.yn = Some(b.yp.clone());
-            nb.sk_yn = Some(b.sk_yp.clone());
+            if below.is_none() {
+                nb.sk_yn = Some(b.sk_yp.clone());
+            }
             nb.bcn_yn = Some(b.bcn_yp.clone());
+            nb.flk_yn = Some(b.flk_yp.clone());
         }
         if let Some(b) = map
             .get(&coord.offset(0, 1, 0))
@@ -1650,6 +2020,7 @@ impl LightingStore {
             nb.yp = Some(b.yn.clone());
             nb.sk_yp = Some(b.sk_yn.clone());
             nb.bcn_yp = Some(b.bcn_yn.clone());
+            nb.flk_yp = Some(b.flk_yn.clone());
         }
         nb
     }
@@ -1667,29 +2038,39 @@ impl LightingStore {
                 mask.xn = existing.xn.as_ref() != lb.xn.as_ref()
                     || existing.sk_xn.as_ref() != lb.sk_xn.as_ref()
                     || existing.bcn_xn.as_ref() != lb.bcn_xn.as_ref()
-                    || existing.bcn_dir_xn.as_ref() != lb.bcn_dir_xn.as_ref();
+                    || existing.bcn_dir_xn.as_ref() != lb.bcn_dir_xn.as_ref()
+                    || existing.flk_xn.as_ref() != lb.flk_xn.as_ref();
                 mask.xp = existing.xp.as_ref() != lb.xp.as_ref()
                     || existing.sk_xp.as_ref() != lb.sk_xp.as_ref()
                     || existing.bcn_xp.as_ref() != lb.bcn_xp.as_ref()
-                    || existing.bcn_dir_xp.as_ref() != lb.bcn_dir_xp.as_ref();
+                    || existing.bcn_dir_xp.as_ref() != lb.bcn_dir_xp.as_ref()
+                    || existing.flk_xp.as_ref() != lb.flk_xp.as_ref();
                 mask.zn = existing.zn.as_ref() != lb.zn.as_ref()
                     || existing.sk_zn.as_ref() != lb.sk_zn.as_ref()
                     || existing.bcn_zn.as_ref() != lb.bcn_zn.as_ref()
-                    || existing.bcn_dir_zn.as_ref() != lb.bcn_dir_zn.as_ref();
+                    || existing.bcn_dir_zn.as_ref() != lb.bcn_dir_zn.as_ref()
+                    || existing.flk_zn.as_ref() != lb.flk_zn.as_ref();
                 mask.zp = existing.zp.as_ref() != lb.zp.as_ref()
                     || existing.sk_zp.as_ref() != lb.sk_zp.as_ref()
                     || existing.bcn_zp.as_ref() != lb.bcn_zp.as_ref()
-                    || existing.bcn_dir_zp.as_ref() != lb.bcn_dir_zp.as_ref();
+                    || existing.bcn_dir_zp.as_ref() != lb.bcn_dir_zp.as_ref()
+                    || existing.flk_zp.as_ref() != lb.flk_zp.as_ref();
                 mask.yn = existing.yn.as_ref() != lb.yn.as_ref()
                     || existing.sk_yn.as_ref() != lb.sk_yn.as_ref()
-                    || existing.bcn_yn.as_ref() != lb.bcn_yn.as_ref();
+                    || existing.bcn_yn.as_ref() != lb.bcn_yn.as_ref()
+                    || existing.flk_yn.as_ref() != lb.flk_yn.as_ref();
                 mask.yp = existing.yp.as_ref() != lb.yp.as_ref()
                     || existing.sk_yp.as_ref() != lb.sk_yp.as_ref()
-                    || existing.bcn_yp.as_ref() != lb.bcn_yp.as_ref();
+                    || existing.bcn_yp.as_ref() != lb.bcn_yp.as_ref()
+                    || existing.flk_yp.as_ref() != lb.flk_yp.as_ref();
                 let any = mask.xn || mask.xp || mask.zn || mask.zp || mask.yn || mask.yp;
                 if any {
+                    entry.checksums = Some(BorderChecksums::from_borders(&lb));
                     *existing = lb;
+                } else {
+                    existing.inputs = lb.inputs;
                 }
+                self.note_seam_mismatches(&map, coord);
                 (any, mask)
             }
             None => {
@@ -1698,7 +2079,9 @@ impl LightingStore {
                 mask.zp = true;
                 mask.yn = true;
                 mask.yp = true;
+                entry.checksums = Some(BorderChecksums::from_borders(&lb));
                 entry.borders = Some(lb);
+                self.note_seam_mismatches(&map, coord);
                 (true, mask)
             }
         }
@@ -1715,13 +2098,9 @@ impl LightingStore {
         self.add_emitter_world_typed(wx, wy, wz, level, true);
     }
     fn add_emitter_world_typed(&self, wx: i32, wy: i32, wz: i32, level: u8, is_beacon: bool) {
-        let sx = self.sx as i32;
-        let sy = self.sy as i32;
-        let sz = self.sz as i32;
-        let coord = ChunkCoord::new(wx.div_euclid(sx), wy.div_euclid(sy), wz.div_euclid(sz));
-        let lx = wx.rem_euclid(sx) as usize;
-        let lz = wz.rem_euclid(sz) as usize;
-        let ly = wy.rem_euclid(sy) as usize;
+        let (chunk, local) = WorldPos::new(wx, wy, wz).split(self.dims());
+        let coord = ChunkCoord::from(chunk);
+        let (lx, ly, lz) = local.to_usize();
         let mut map = self.chunks.lock().unwrap();
         let entry = map.entry(coord).or_insert_with(LightingChunkEntry::default);
         if !entry
@@ -1732,24 +2111,95 @@ impl LightingStore {
             entry.emitters.push((lx, ly, lz, level, is_beacon));
         }
     }
-    pub fn remove_emitter_world(&self, wx: i32, wy: i32, wz: i32) {
-        let sx = self.sx as i32;
-        let sy = self.sy as i32;
-        let sz = self.sz as i32;
-        let coord = ChunkCoord::new(wx.div_euclid(sx), wy.div_euclid(sy), wz.div_euclid(sz));
-        let lx = wx.rem_euclid(sx) as usize;
-        let lz = wz.rem_euclid(sz) as usize;
-        let ly = wy.rem_euclid(sy) as usize;
+    /// Remove an emitter and send a darkening wave across the stored block-light border
+    /// planes it could have lit, so neighbours relit afterwards do not seed from stale
+    /// seams. `level` is the removed block's emission level: emitters placed by worldgen
+    /// or loaded from a save were never registered here, so the larger of it and any
+    /// registered level is darkened. The wave is a breadth-first walk over chunks that
+    /// starts at the emitter's chunk and only crosses faces whose planes it darkened. A
+    /// plane cell is cleared when it is no brighter than the emitter alone could have
+    /// made it there; brighter cells have another source and are kept. Returns the chunks
+    /// whose planes were darkened and on which faces; each needs a relight and a border
+    /// notification.
+    pub fn remove_emitter_world(
+        &self,
+        wx: i32,
+        wy: i32,
+        wz: i32,
+        level: u8,
+    ) -> Vec<(ChunkCoord, BorderChangeMask)> {
+        let dims = self.dims();
+        let (chunk, local) = WorldPos::new(wx, wy, wz).split(dims);
+        let coord = ChunkCoord::from(chunk);
+        let (lx, ly, lz) = local.to_usize();
         let mut map = self.chunks.lock().unwrap();
+        let mut level = level;
         if let std::collections::hash_map::Entry::Occupied(mut occ) = map.entry(coord) {
             let entry = occ.get_mut();
-            entry
-                .emitters
-                .retain(|&(x, y, z, _, _)| !(x == lx && y == ly && z == lz));
+            entry.emitters.retain(|&(x, y, z, lv, _)| {
+                let hit = x == lx && y == ly && z == lz;
+                if hit {
+                    level = level.max(lv);
+                }
+                !hit
+            });
             if entry.is_empty() {
                 occ.remove_entry();
             }
         }
+        if level == 0 {
+            return Vec::new();
+        }
+
+        let src = IVec3::new(wx, wy, wz);
+        // Furthest block the light reaches: seeded at full level one micro step into the
+        // next block, then two steps per block after that.
+        let reach = (level / micro::MICRO_BLOCK_ATTENUATION) as i32 / 2 + 2;
+        let lo = WorldPos(src - IVec3::new(reach, reach, reach))
+            .chunk(dims)
+            .0;
+        let hi = WorldPos(src + IVec3::new(reach, reach, reach))
+            .chunk(dims)
+            .0;
+        let in_reach = |c: ChunkCoord| {
+            (lo.x..=hi.x).contains(&c.cx)
+                && (lo.y..=hi.y).contains(&c.cy)
+                && (lo.z..=hi.z).contains(&c.cz)
+        };
+        let mut out = Vec::new();
+        let mut seen = std::collections::HashSet::from([coord]);
+        let mut queue = std::collections::VecDeque::from([coord]);
+        while let Some(c) = queue.pop_front() {
+            let mut mask = BorderChangeMask::default();
+            if let Some(entry) = map.get_mut(&c) {
+                if let Some(b) = entry.borders.as_mut() {
+                    mask.or_with(&darken_block_borders(b, c, dims, src, level));
+                }
+                if let Some(mb) = entry.micro_borders.as_mut() {
+                    mask.or_with(&darken_micro_block_borders(mb, c, dims, src, level));
+                }
+            }
+            // Light leaves the emitter's chunk through any face; elsewhere it only
+            // travelled on through the faces that just went dark.
+            let from_src = c == coord;
+            for (crossed, (dx, dy, dz)) in [
+                (mask.xn, (-1, 0, 0)),
+                (mask.xp, (1, 0, 0)),
+                (mask.yn, (0, -1, 0)),
+                (mask.yp, (0, 1, 0)),
+                (mask.zn, (0, 0, -1)),
+                (mask.zp, (0, 0, 1)),
+            ] {
+                let n = c.offset(dx, dy, dz);
+                if (crossed || from_src) && in_reach(n) && seen.insert(n) {
+                    queue.push_back(n);
+                }
+            }
+            if mask.any() {
+                out.push((c, mask));
+            }
+        }
+        out
     }
     pub fn emitters_for_chunk(&self, coord: ChunkCoord) -> Vec<(usize, usize, usize, u8, bool)> {
         let map = self.chunks.lock().unwrap();
@@ -1806,6 +2256,7 @@ impl LightingStore {
         let xm = self.sx * 2;
         let ym = self.sy * 2;
         let zm = self.sz * 2;
+        let below = self.below_floor(coord);
         let map = self.chunks.lock().unwrap();
         let mut nb = NeighborMicroBorders {
             xm_sk_neg: None,
@@ -1855,8 +2306,11 @@ impl LightingStore {
         if let Some(m) = map
             .get(&coord.offset(0, -1, 0))
             .and_then(|entry| entry.micro_borders.as_ref())
+            .filter(|_| below != Some(BelowWorld::Solid))
         {
-            nb.ym_sk_neg = Some(m.ym_sk_pos.clone());
+            if below.is_none() {
+                nb.ym_sk_neg = Some(m.ym_sk_pos.clone());
+            }
             nb.ym_bl_neg = Some(m.ym_bl_pos.clone());
         }
         if let Some(m) = map
@@ -1894,6 +2348,144 @@ fn equal_planes(a: &LightBorders, b: &LightBorders) -> bool {
         && a.bcn_dir_xp == b.bcn_dir_xp
         && a.bcn_dir_zn == b.bcn_dir_zn
         && a.bcn_dir_zp == b.bcn_dir_zp
+        && a.flk_xn == b.flk_xn
+        && a.flk_xp == b.flk_xp
+        && a.flk_zn == b.flk_zn
+        && a.flk_zp == b.flk_zp
+        && a.flk_yn == b.flk_yn
+        && a.flk_yp == b.flk_yp
+}
+
+/// `(sky, block, beacon)` at a voxel on a chunk face, read from that face's border
+/// planes; `None` for interior voxels.
+fn border_sample(
+    b: &LightBorders,
+    (x, y, z): (usize, usize, usize),
+    (sx, sy, sz): (usize, usize, usize),
+) -> Option<(u8, u8, u8)> {
+    let pick = |sk: &Arc<[u8]>, bl: &Arc<[u8]>, bcn: &Arc<[u8]>, i: usize| (sk[i], bl[i], bcn[i]);
+    if x == 0 {
+        Some(pick(&b.sk_xn, &b.xn, &b.bcn_xn, y * sz + z))
+    } else if x + 1 == sx {
+        Some(pick(&b.sk_xp, &b.xp, &b.bcn_xp, y * sz + z))
+    } else if z == 0 {
+        Some(pick(&b.sk_zn, &b.zn, &b.bcn_zn, y * sx + x))
+    } else if z + 1 == sz {
+        Some(pick(&b.sk_zp, &b.zp, &b.bcn_zp, y * sx + x))
+    } else if y == 0 {
+        Some(pick(&b.sk_yn, &b.yn, &b.bcn_yn, z * sx + x))
+    } else if y + 1 == sy {
+        Some(pick(&b.sk_yp, &b.yp, &b.bcn_yp, z * sx + x))
+    } else {
+        None
+    }
+}
+
+/// Most light a removed emitter of `level` at world voxel `src` could have left in a cell
+/// spanning `span` micro cells per axis from micro coordinate `m` (two per block); below
+/// zero when out of reach. Emissive blocks seed the micro cells just outside their faces
+/// at full level, so distance counts from one cell beyond the voxel.
+#[inline]
+fn removed_light_bound(level: u8, src: IVec3, m: IVec3, span: i32) -> i32 {
+    let axis = |c: i32, s: i32| (2 * s - 1 - (c + span - 1)).max(c - (2 * s + 2)).max(0);
+    let steps = axis(m.x, src.x) + axis(m.y, src.y) + axis(m.z, src.z);
+    level as i32 - steps * micro::MICRO_BLOCK_ATTENUATION as i32
+}
+
+/// Zero the cells of a face plane, rows of `width` cells, whose value does not exceed
+/// `bound(row, col)`; `paired` planes (flicker classes) are cleared alongside.
+fn darken_plane(
+    plane: &mut Arc<[u8]>,
+    paired: Option<&mut Arc<[u8]>>,
+    width: usize,
+    bound: impl Fn(i32, i32) -> i32,
+) -> bool {
+    let cleared: Vec<usize> = plane
+        .iter()
+        .enumerate()
+        .filter(|&(i, &v)| v != 0 && v as i32 <= bound((i / width) as i32, (i % width) as i32))
+        .map(|(i, _)| i)
+        .collect();
+    if cleared.is_empty() {
+        return false;
+    }
+    for p in std::iter::once(plane).chain(paired) {
+        let mut v = p.to_vec();
+        for &i in &cleared {
+            v[i] = 0;
+        }
+        *p = v.into();
+    }
+    true
+}
+
+fn darken_block_borders(
+    b: &mut LightBorders,
+    coord: ChunkCoord,
+    dims: IVec3,
+    src: IVec3,
+    level: u8,
+) -> BorderChangeMask {
+    let o = IVec3::new(coord.cx, coord.cy, coord.cz).mul_elem(dims);
+    let e = o + dims - IVec3::new(1, 1, 1);
+    let at = |x: i32, y: i32, z: i32| {
+        removed_light_bound(level, src, IVec3::new(2 * x, 2 * y, 2 * z), 2)
+    };
+    let (sx, sz) = (dims.x as usize, dims.z as usize);
+    BorderChangeMask {
+        xn: darken_plane(&mut b.xn, Some(&mut b.flk_xn), sz, |y, z| {
+            at(o.x, o.y + y, o.z + z)
+        }),
+        xp: darken_plane(&mut b.xp, Some(&mut b.flk_xp), sz, |y, z| {
+            at(e.x, o.y + y, o.z + z)
+        }),
+        zn: darken_plane(&mut b.zn, Some(&mut b.flk_zn), sx, |y, x| {
+            at(o.x + x, o.y + y, o.z)
+        }),
+        zp: darken_plane(&mut b.zp, Some(&mut b.flk_zp), sx, |y, x| {
+            at(o.x + x, o.y + y, e.z)
+        }),
+        yn: darken_plane(&mut b.yn, Some(&mut b.flk_yn), sx, |z, x| {
+            at(o.x + x, o.y, o.z + z)
+        }),
+        yp: darken_plane(&mut b.yp, Some(&mut b.flk_yp), sx, |z, x| {
+            at(o.x + x, e.y, o.z + z)
+        }),
+    }
+}
+
+fn darken_micro_block_borders(
+    mb: &mut MicroBorders,
+    coord: ChunkCoord,
+    dims: IVec3,
+    src: IVec3,
+    level: u8,
+) -> BorderChangeMask {
+    let o = IVec3::new(coord.cx, coord.cy, coord.cz).mul_elem(dims);
+    let o = IVec3::new(2 * o.x, 2 * o.y, 2 * o.z);
+    let e = o + IVec3::new(mb.xm as i32 - 1, mb.ym as i32 - 1, mb.zm as i32 - 1);
+    let at = |x: i32, y: i32, z: i32| removed_light_bound(level, src, IVec3::new(x, y, z), 1);
+    let (xm, zm) = (mb.xm, mb.zm);
+    BorderChangeMask {
+        xn: darken_plane(&mut mb.xm_bl_neg, None, zm, |y, z| {
+            at(o.x, o.y + y, o.z + z)
+        }),
+        xp: darken_plane(&mut mb.xm_bl_pos, None, zm, |y, z| {
+            at(e.x, o.y + y, o.z + z)
+        }),
+        zn: darken_plane(&mut mb.zm_bl_neg, None, xm, |y, x| {
+            at(o.x + x, o.y + y, o.z)
+        }),
+        zp: darken_plane(&mut mb.zm_bl_pos, None, xm, |y, x| {
+            at(o.x + x, o.y + y, e.z)
+        }),
+        yn: darken_plane(&mut mb.ym_bl_neg, None, xm, |z, x| {
+            at(o.x + x, o.y, o.z + z)
+        }),
+        yp: darken_plane(&mut mb.ym_bl_pos, None, xm, |z, x| {
+            at(o.x + x, e.y, o.z + z)
+        }),
+    }
 }
 
 pub struct NeighborBorders {
@@ -1919,6 +2511,14 @@ pub struct NeighborBorders {
     pub bcn_dir_xp: Option<Arc<[u8]>>,
     pub bcn_dir_zn: Option<Arc<[u8]>>,
     pub bcn_dir_zp: Option<Arc<[u8]>>,
+    pub flk_xn: Option<Arc<[u8]>>,
+    pub flk_xp: Option<Arc<[u8]>>,
+    pub flk_zn: Option<Arc<[u8]>>,
+    pub flk_zp: Option<Arc<[u8]>>,
+    pub flk_yn: Option<Arc<[u8]>>,
+    pub flk_yp: Option<Arc<[u8]>>,
+    /// Checksums of the planes above, for seam mismatch tracking.
+    pub inputs: SeamInputs,
 }
 
 impl NeighborBorders {
@@ -1946,6 +2546,13 @@ impl NeighborBorders {
             bcn_dir_xp: None,
             bcn_dir_zn: None,
             bcn_dir_zp: None,
+            flk_xn: None,
+            flk_xp: None,
+            flk_zn: None,
+            flk_zp: None,
+            flk_yn: None,
+            flk_yp: None,
+            inputs: [None; 6],
         }
     }
 }
@@ -1964,6 +2571,94 @@ pub fn compute_light_with_borders_buf(
     micro::compute_light_with_borders_buf_micro(buf, store, reg, world)
 }
 
+/// `compute_light_with_borders_buf` at a chosen quality; `Coarse` skips the micro pass.
+pub fn compute_light_with_quality(
+    buf: &ChunkBuf,
+    store: &LightingStore,
+    reg: &BlockRegistry,
+    world: &World,
+    quality: LightQuality,
+) -> LightGrid {
+    match quality {
+        LightQuality::Coarse => LightGrid::compute_with_borders_buf(buf, store, reg),
+        LightQuality::Full => compute_light_with_borders_buf(buf, store, reg, world),
+    }
+}
+
+/// A visible face whose sampled light differs between two grids of the same chunk.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FaceLightDiff {
+    pub x: usize,
+    pub y: usize,
+    pub z: usize,
+    /// Face index as used by `sample_face_local_s2` (0=+Y, 1=-Y, 2=+X, 3=-X, 4=+Z, 5=-Z).
+    pub face: usize,
+    pub a: u8,
+    pub b: u8,
+}
+
+/// Faces of solid blocks open to a non-solid neighbour inside the chunk whose sampled light
+/// differs by more than `threshold` between `a` and `b`. Seam faces are skipped since
+/// their light depends on neighbour borders rather than the grids being compared.
+pub fn face_light_diffs(
+    buf: &ChunkBuf,
+    reg: &BlockRegistry,
+    a: &LightGrid,
+    b: &LightGrid,
+    threshold: u8,
+) -> Vec<FaceLightDiff> {
+    const STEPS: [(i32, i32, i32); 6] = [
+        (0, 1, 0),
+        (0, -1, 0),
+        (1, 0, 0),
+        (-1, 0, 0),
+        (0, 0, 1),
+        (0, 0, -1),
+    ];
+    let solid = |x: usize, y: usize, z: usize| {
+        let blk = buf.get_local(x, y, z);
+        reg.get(blk.id)
+            .map(|ty| ty.is_solid(blk.state))
+            .unwrap_or(false)
+    };
+    let mut out = Vec::new();
+    for y in 0..buf.sy {
+        for z in 0..buf.sz {
+            for x in 0..buf.sx {
+                if !solid(x, y, z) {
+                    continue;
+                }
+                for (face, (dx, dy, dz)) in STEPS.iter().copied().enumerate() {
+                    let (nx, ny, nz) = (x as i32 + dx, y as i32 + dy, z as i32 + dz);
+                    if nx < 0
+                        || ny < 0
+                        || nz < 0
+                        || nx >= buf.sx as i32
+                        || ny >= buf.sy as i32
+                        || nz >= buf.sz as i32
+                        || solid(nx as usize, ny as usize, nz as usize)
+                    {
+                        continue;
+                    }
+                    let la = a.sample_face_local_s2(buf, reg, x, y, z, face);
+                    let lb = b.sample_face_local_s2(buf, reg, x, y, z, face);
+                    if la.abs_diff(lb) > threshold {
+                        out.push(FaceLightDiff {
+                            x,
+                            y,
+                            z,
+                            face,
+                            a: la,
+                            b: lb,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
 // --- GPU lightfield (Phase 2) helpers ---
 
 /// Packed 2D atlas representation of a chunk lightfield for shader sampling.
@@ -1971,10 +2666,11 @@ pub fn compute_light_with_borders_buf(
 /// of (grid_cols x grid_rows). The +2 accounts for border rings on both -X/+X and -Z/+Z
 /// sides to enable seamless sampling across chunk boundaries in the shader.
 /// Pixel format is RGBA8 where:
-/// - R = block light (0..255)
+/// - R = block light (0..255), or the world's night ambient share of skylight where that
+///   is brighter, so the shaders' `max` over channels floors open sky at starlight
 /// - G = skylight (0..255)
 /// - B = beacon light (0..255)
-/// - A = beacon primary direction (0..5) scaled to 0..255 for debug/optional use
+/// - A = flicker class of the block light (`FlickerClass as u8`), animated by the shaders
 #[derive(Clone)]
 pub struct LightAtlas {
     pub data: Vec<u8>,
@@ -1990,6 +2686,23 @@ pub struct LightAtlas {
 // Removed: worker-side atlas packing (`pack_light_grid_atlas`). Use
 // `pack_light_grid_atlas_with_neighbors` to assemble rings from authoritative borders.
 
+/// Write one atlas texel. Starlight is `sky` under the night ambient ceiling; it does not
+/// follow the day cycle, so it rides in the block channel (as steady light) and the shaders'
+/// `max(block, sky * scale)` yields `sky * max(scale, night)`, matching `sample_world`.
+#[inline]
+fn pack_texel(px: &mut [u8], blk: u8, sky: u8, bcn: u8, class: u8, night: u8) {
+    let star = scale_skylight(sky, night);
+    let (blk, class) = if star > blk {
+        (star, geist_blocks::config::FlickerClass::Steady.as_u8())
+    } else {
+        (blk, class)
+    };
+    px[0] = blk;
+    px[1] = sky;
+    px[2] = bcn;
+    px[3] = class;
+}
+
 /// Packs a `LightGrid` into a 2D RGBA8 atlas using the provided neighbor borders
 /// (fetched live from the `LightingStore` or cached externally). This avoids races
 /// where the worker-computed grid's embedded neighbor planes may be stale by the
@@ -1998,6 +2711,7 @@ pub fn pack_light_grid_atlas_with_neighbors(light: &LightGrid, nb: &NeighborBord
     let sx = light.sx;
     let sy = light.sy;
     let sz = light.sz;
+    let night = light.sky_max.night();
     let total_slices = sy + 2; // interior slices plus ±Y neighbor planes
     // Choose grid columns ~ sqrt(total_slices)
     let mut grid_cols = (total_slices as f32).sqrt().ceil() as usize;
@@ -2027,12 +2741,14 @@ pub fn pack_light_grid_atlas_with_neighbors(light: &LightGrid, nb: &NeighborBord
                 let dst_x = ox + 1 + x;
                 let dst_y = oy + 1 + z;
                 let di = (dst_y * width + dst_x) * 4;
-                data[di + 0] = light.block_light[src];
-                data[di + 1] = light.skylight[src];
-                data[di + 2] = light.beacon_light[src];
-                data[di + 3] = match light.beacon_dir[src] {
-                    v => (v as f32 * (255.0 / 5.0)).round() as u8,
-                };
+                pack_texel(
+                    &mut data[di..di + 4],
+                    light.block_light[src],
+                    light.skylight[src],
+                    light.beacon_light[src],
+                    light.block_flicker[src],
+                    night,
+                );
             }
         }
         // +X ring (from nb.xp)
@@ -2042,10 +2758,14 @@ pub fn pack_light_grid_atlas_with_neighbors(light: &LightGrid, nb: &NeighborBord
                 let dst_y = oy + 1 + z;
                 let di = (dst_y * width + dst_x) * 4;
                 let ii = y * sz + z;
-                data[di + 0] = nb_blk.get(ii).cloned().unwrap_or(0);
-                data[di + 1] = nb_sky.get(ii).cloned().unwrap_or(0);
-                data[di + 2] = nb_bcn.get(ii).cloned().unwrap_or(0);
-                data[di + 3] = 0;
+                pack_texel(
+                    &mut data[di..di + 4],
+                    nb_blk.get(ii).cloned().unwrap_or(0),
+                    nb_sky.get(ii).cloned().unwrap_or(0),
+                    nb_bcn.get(ii).cloned().unwrap_or(0),
+                    plane_class(&nb.flk_xp, ii),
+                    night,
+                );
             }
         }
         // -X ring (from nb.xn)
@@ -2055,10 +2775,14 @@ pub fn pack_light_grid_atlas_with_neighbors(light: &LightGrid, nb: &NeighborBord
                 let dst_y = oy + 1 + z;
                 let di = (dst_y * width + dst_x) * 4;
                 let ii = y * sz + z;
-                data[di + 0] = nb_blk.get(ii).cloned().unwrap_or(0);
-                data[di + 1] = nb_sky.get(ii).cloned().unwrap_or(0);
-                data[di + 2] = nb_bcn.get(ii).cloned().unwrap_or(0);
-                data[di + 3] = 0;
+                pack_texel(
+                    &mut data[di..di + 4],
+                    nb_blk.get(ii).cloned().unwrap_or(0),
+                    nb_sky.get(ii).cloned().unwrap_or(0),
+                    nb_bcn.get(ii).cloned().unwrap_or(0),
+                    plane_class(&nb.flk_xn, ii),
+                    night,
+                );
             }
         }
         // +Z ring (from nb.zp)
@@ -2068,10 +2792,14 @@ pub fn pack_light_grid_atlas_with_neighbors(light: &LightGrid, nb: &NeighborBord
                 let dst_y = oy + (sz + 1);
                 let di = (dst_y * width + dst_x) * 4;
                 let ii = y * sx + x;
-                data[di + 0] = nb_blk.get(ii).cloned().unwrap_or(0);
-                data[di + 1] = nb_sky.get(ii).cloned().unwrap_or(0);
-                data[di + 2] = nb_bcn.get(ii).cloned().unwrap_or(0);
-                data[di + 3] = 0;
+                pack_texel(
+                    &mut data[di..di + 4],
+                    nb_blk.get(ii).cloned().unwrap_or(0),
+                    nb_sky.get(ii).cloned().unwrap_or(0),
+                    nb_bcn.get(ii).cloned().unwrap_or(0),
+                    plane_class(&nb.flk_zp, ii),
+                    night,
+                );
             }
         }
         // -Z ring (from nb.zn)
@@ -2081,10 +2809,14 @@ pub fn pack_light_grid_atlas_with_neighbors(light: &LightGrid, nb: &NeighborBord
                 let dst_y = oy + 0;
                 let di = (dst_y * width + dst_x) * 4;
                 let ii = y * sx + x;
-                data[di + 0] = nb_blk.get(ii).cloned().unwrap_or(0);
-                data[di + 1] = nb_sky.get(ii).cloned().unwrap_or(0);
-                data[di + 2] = nb_bcn.get(ii).cloned().unwrap_or(0);
-                data[di + 3] = 0;
+                pack_texel(
+                    &mut data[di..di + 4],
+                    nb_blk.get(ii).cloned().unwrap_or(0),
+                    nb_sky.get(ii).cloned().unwrap_or(0),
+                    nb_bcn.get(ii).cloned().unwrap_or(0),
+                    plane_class(&nb.flk_zn, ii),
+                    night,
+                );
             }
         }
     }
@@ -2097,10 +2829,14 @@ pub fn pack_light_grid_atlas_with_neighbors(light: &LightGrid, nb: &NeighborBord
                 let dst_y = oy + 1 + z;
                 let di = (dst_y * width + dst_x) * 4;
                 let ii = z * sx + x;
-                data[di + 0] = nb_blk.get(ii).cloned().unwrap_or(0);
-                data[di + 1] = nb_sky.get(ii).cloned().unwrap_or(0);
-                data[di + 2] = nb_bcn.get(ii).cloned().unwrap_or(0);
-                data[di + 3] = 0;
+                pack_texel(
+                    &mut data[di..di + 4],
+                    nb_blk.get(ii).cloned().unwrap_or(0),
+                    nb_sky.get(ii).cloned().unwrap_or(0),
+                    nb_bcn.get(ii).cloned().unwrap_or(0),
+                    plane_class(&nb.flk_yn, ii),
+                    night,
+                );
             }
         }
     }
@@ -2113,10 +2849,14 @@ pub fn pack_light_grid_atlas_with_neighbors(light: &LightGrid, nb: &NeighborBord
                 let dst_y = oy + 1 + z;
                 let di = (dst_y * width + dst_x) * 4;
                 let ii = z * sx + x;
-                data[di + 0] = nb_blk.get(ii).cloned().unwrap_or(0);
-                data[di + 1] = nb_sky.get(ii).cloned().unwrap_or(0);
-                data[di + 2] = nb_bcn.get(ii).cloned().unwrap_or(0);
-                data[di + 3] = 0;
+                pack_texel(
+                    &mut data[di..di + 4],
+                    nb_blk.get(ii).cloned().unwrap_or(0),
+                    nb_sky.get(ii).cloned().unwrap_or(0),
+                    nb_bcn.get(ii).cloned().unwrap_or(0),
+                    plane_class(&nb.flk_yp, ii),
+                    night,
+                );
             }
         }
     }
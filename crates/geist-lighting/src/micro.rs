@@ -1,4 +1,4 @@
-use crate::{LightGrid, LightingStore, MicroBorders};
+use crate::{LightGrid, LightingStore, MicroBorders, NeighborBorders, plane_class};
 use rayon::prelude::*;
 // (Arc used via .into() conversions when publishing planes)
 use geist_blocks::micro::micro_face_cell_open_s2;
@@ -10,7 +10,7 @@ use geist_world::World;
 const MICRO_SCALE: usize = 2;
 
 // Light attenuation values
-const MICRO_BLOCK_ATTENUATION: u8 = 16; // Per-micro-step block light attenuation
+pub(crate) const MICRO_BLOCK_ATTENUATION: u8 = 16; // Per-micro-step block light attenuation
 pub const MICRO_SKY_ATTENUATION: u8 = 16; // Per-micro-step skylight attenuation
 const COARSE_SEAM_ATTENUATION: u8 = 32; // Attenuation when falling back to coarse neighbors
 
@@ -170,6 +170,38 @@ pub fn compute_light_with_borders_buf_micro(
         }
     }
 
+    // Water is solid for block light and the open-above scan, but skylight wades into it
+    // at the medium's cost. `sky_solid_bits` is the occupancy the skylight BFS respects.
+    let water = store.water_medium();
+    let water_id = reg.id_by_name("water");
+    let mut micro_water_bits = vec![0u64; micro_bit_count.div_ceil(64)];
+    let mut any_water = false;
+    if let Some(water_id) = water_id {
+        for z in 0..buf.sz {
+            for y in 0..buf.sy {
+                for x in 0..buf.sx {
+                    if buf.get_local(x, y, z).id != water_id {
+                        continue;
+                    }
+                    any_water = true;
+                    let base = ((y * 2) * mzs + z * 2) * mxs + x * 2;
+                    for off in [0, stride_z_m, stride_y_m, stride_y_m + stride_z_m] {
+                        bs_set(&mut micro_water_bits, base + off);
+                        bs_set(&mut micro_water_bits, base + off + 1);
+                    }
+                }
+            }
+        }
+    }
+    let sky_solid_bits: Vec<u64> = micro_solid_bits
+        .iter()
+        .zip(&micro_water_bits)
+        .map(|(solid, wet)| solid & !wet)
+        .collect();
+    // Skylight cost to enter a water cell; air passes it straight down for free.
+    let water_down = water.sky_extinction;
+    let water_side = MICRO_SKY_ATTENUATION.saturating_add(water.sky_extinction);
+
     // BFS queues (stable order). We seed as we write, so no full-volume scan needed.
     struct Bucket {
         data: Vec<(usize, u8)>,
@@ -382,6 +414,14 @@ pub fn compute_light_with_borders_buf_micro(
                 // Already set to 255 in Phase 2
                 q_sky.push_idx(i, MAX_LIGHT);
             }
+            // Resting on water: the bottom open cell lights the water column below
+            if any_water
+                && end_y <= start
+                && start > 0
+                && bs_get(&micro_water_bits, midx(mx, start - 1, mz, mxs, mzs))
+            {
+                q_sky.push_idx(midx(mx, start, mz, mxs, mzs), MAX_LIGHT);
+            }
         }
     }
 
@@ -1173,6 +1213,60 @@ pub fn compute_light_with_borders_buf_micro(
         }
     }
 
+    // Water cells on the faces take skylight from neighbour micro planes at water cost;
+    // the gated seam pass above only seeds cells that are open to block light.
+    if any_water {
+        let mut water_seeds: Vec<(usize, u8)> = Vec::new();
+        let mut seed_face =
+            |plane: &Option<std::sync::Arc<[u8]>>,
+             cost: u8,
+             cells: &mut dyn Iterator<Item = (usize, usize)>| {
+                let Some(p) = plane.as_ref() else {
+                    return;
+                };
+                for (pi, i) in cells {
+                    let v = clamp_sub_u8(p[pi], cost);
+                    if v > 0 && bs_get(&micro_water_bits, i) {
+                        water_seeds.push((i, v));
+                    }
+                }
+            };
+        let x_face = |mx: usize| {
+            (0..mys).flat_map(move |my| {
+                (0..mzs).map(move |mz| (my * mzs + mz, midx(mx, my, mz, mxs, mzs)))
+            })
+        };
+        let z_face = |mz: usize| {
+            (0..mys).flat_map(move |my| {
+                (0..mxs).map(move |mx| (my * mxs + mx, midx(mx, my, mz, mxs, mzs)))
+            })
+        };
+        let y_face = |my: usize| {
+            (0..mzs).flat_map(move |mz| {
+                (0..mxs).map(move |mx| (mz * mxs + mx, midx(mx, my, mz, mxs, mzs)))
+            })
+        };
+        seed_face(&nbm.xm_sk_neg, water_side, &mut x_face(0));
+        seed_face(&nbm.xm_sk_pos, water_side, &mut x_face(mxs - 1));
+        seed_face(&nbm.zm_sk_neg, water_side, &mut z_face(0));
+        seed_face(&nbm.zm_sk_pos, water_side, &mut z_face(mzs - 1));
+        seed_face(&nbm.ym_sk_neg, water_side, &mut y_face(0));
+        // Light arriving from above only pays the extinction, as it would inside the chunk
+        seed_face(&nbm.ym_sk_pos, water_down, &mut y_face(mys - 1));
+        for (i, v) in water_seeds {
+            if micro_sky[i] < v {
+                micro_sky[i] = v;
+                q_sky.push_idx(i, v);
+                let my = i / (mzs * mxs);
+                let rem = i - my * (mzs * mxs);
+                let mz = rem / mxs;
+                let mx = rem - mz * mxs;
+                let mii = ((my >> 1) * buf.sz + (mz >> 1)) * buf.sx + (mx >> 1);
+                bs_set(&mut macro_touched, mii);
+            }
+        }
+    }
+
     // Seed emissive blocks at micro resolution (fill interior air micro voxels of the macro cell)
     // A) Scan the chunk buffer for emissive blocks (covers worldgen + edits folded into buf)
     //    Seed at face boundaries so full-cube emitters (e.g., glowstone) light adjacent air.
@@ -1428,6 +1522,14 @@ pub fn compute_light_with_borders_buf_micro(
     }
 
     // BFS over skylight queue (parallel per-bucket)
+    // Entering water charges the medium's cost from the source level instead of the air step
+    let sky_step = |ii: usize, lvl: u8, air: u8, water_cost: u8| -> u8 {
+        if any_water && bs_get(&micro_water_bits, ii) {
+            clamp_sub_u8(lvl, water_cost)
+        } else {
+            air
+        }
+    };
     while q_sky.pending > 0 {
         let bi = (q_sky.cur_d & 15) as usize;
         let bucket = &mut q_sky.buckets[bi];
@@ -1458,7 +1560,7 @@ pub fn compute_light_with_borders_buf_micro(
                     }
                     let lvl = level;
                     let v = clamp_sub_u8(lvl, att_sky);
-                    if v == 0 {
+                    if v == 0 && (!any_water || lvl <= water_down) {
                         return out;
                     }
                     let my = idx0 / (mzs * mxs);
@@ -1468,14 +1570,16 @@ pub fn compute_light_with_borders_buf_micro(
                     // +X
                     if mx + 1 < mxs {
                         let ii = idx0 + 1;
-                        if micro_sky[ii] < v && !bs_get(&micro_solid_bits, ii) {
+                        let v = sky_step(ii, lvl, v, water_side);
+                        if micro_sky[ii] < v && !bs_get(&sky_solid_bits, ii) {
                             out.push((ii, v));
                         }
                     }
                     // -X
                     if mx > 0 {
                         let ii = idx0 - 1;
-                        if micro_sky[ii] < v && !bs_get(&micro_solid_bits, ii) {
+                        let v = sky_step(ii, lvl, v, water_side);
+                        if micro_sky[ii] < v && !bs_get(&sky_solid_bits, ii) {
                             out.push((ii, v));
                         }
                     }
@@ -1486,7 +1590,8 @@ pub fn compute_light_with_borders_buf_micro(
                             v = MAX_LIGHT;
                         }
                         let ii = idx0 + stride_y_m;
-                        if micro_sky[ii] < v && !bs_get(&micro_solid_bits, ii) {
+                        let v = sky_step(ii, lvl, v, water_side);
+                        if micro_sky[ii] < v && !bs_get(&sky_solid_bits, ii) {
                             out.push((ii, v));
                         }
                     }
@@ -1497,21 +1602,24 @@ pub fn compute_light_with_borders_buf_micro(
                             v = MAX_LIGHT;
                         }
                         let ii = idx0 - stride_y_m;
-                        if micro_sky[ii] < v && !bs_get(&micro_solid_bits, ii) {
+                        let v = sky_step(ii, lvl, v, water_down);
+                        if micro_sky[ii] < v && !bs_get(&sky_solid_bits, ii) {
                             out.push((ii, v));
                         }
                     }
                     // +Z
                     if mz + 1 < mzs {
                         let ii = idx0 + stride_z_m;
-                        if micro_sky[ii] < v && !bs_get(&micro_solid_bits, ii) {
+                        let v = sky_step(ii, lvl, v, water_side);
+                        if micro_sky[ii] < v && !bs_get(&sky_solid_bits, ii) {
                             out.push((ii, v));
                         }
                     }
                     // -Z
                     if mz > 0 {
                         let ii = idx0 - stride_z_m;
-                        if micro_sky[ii] < v && !bs_get(&micro_solid_bits, ii) {
+                        let v = sky_step(ii, lvl, v, water_side);
+                        if micro_sky[ii] < v && !bs_get(&sky_solid_bits, ii) {
                             out.push((ii, v));
                         }
                     }
@@ -1531,7 +1639,7 @@ pub fn compute_light_with_borders_buf_micro(
             if v == 0 {
                 continue;
             }
-            if micro_sky[ii] < v && !bs_get(&micro_solid_bits, ii) {
+            if micro_sky[ii] < v && !bs_get(&sky_solid_bits, ii) {
                 micro_sky[ii] = v;
                 q_sky.push_idx(ii, v);
                 // Mark macro cell as touched
@@ -1545,8 +1653,12 @@ pub fn compute_light_with_borders_buf_micro(
         }
     }
 
+    let micro_flk = trace_block_flicker(buf, store, reg, &nb, &micro_blk, (mxs, mys, mzs));
+
     // Downsample micro -> macro (max over the 2x2x2 block) and retain micro arrays + neighbor planes
     let mut lg = LightGrid::new(buf.sx, buf.sy, buf.sz);
+    lg.seam_inputs = nb.inputs;
+    lg.sky_max = store.skylight_max.clone();
     let stride_z = mxs; // +1 micro Z
     let stride_y = mxs * mzs; // +1 micro Y
     for z in 0..buf.sz {
@@ -1583,21 +1695,13 @@ pub fn compute_light_with_borders_buf_micro(
                 .iter()
                 .max()
                 .unwrap();
-                let bmax = *[
-                    micro_blk[i000],
-                    micro_blk[i001],
-                    micro_blk[i010],
-                    micro_blk[i011],
-                    micro_blk[i100],
-                    micro_blk[i101],
-                    micro_blk[i110],
-                    micro_blk[i111],
-                ]
-                .iter()
-                .max()
-                .unwrap();
+                let brightest = [i000, i001, i010, i011, i100, i101, i110, i111]
+                    .into_iter()
+                    .max_by_key(|&i| micro_blk[i])
+                    .unwrap();
                 lg.skylight[ii] = smax;
-                lg.block_light[ii] = bmax;
+                lg.block_light[ii] = micro_blk[brightest];
+                lg.block_flicker[ii] = micro_flk[brightest];
             }
         }
     }
@@ -1686,6 +1790,212 @@ pub fn compute_light_with_borders_buf_micro(
     lg
 }
 
+/// Resolve the flicker class of every lit micro cell after block light has settled.
+///
+/// Emitter cells take their block's class. Every other cell copies a neighbor exactly one
+/// attenuation step brighter (the cell BFS propagated from); visiting cells brightest first
+/// guarantees that neighbor is already resolved. Cells with no such neighbor were seeded
+/// across a seam and take the class from the neighbor chunk's coarse plane.
+fn trace_block_flicker(
+    buf: &ChunkBuf,
+    store: &LightingStore,
+    reg: &BlockRegistry,
+    nb: &NeighborBorders,
+    micro_blk: &[u8],
+    (mxs, mys, mzs): (usize, usize, usize),
+) -> Vec<u8> {
+    const UNSET: u8 = u8::MAX;
+    let mut flk = vec![UNSET; micro_blk.len()];
+    let stride_z = mxs;
+    let stride_y = mxs * mzs;
+    let claim = |flk: &mut [u8], ii: usize, level: u8, class: u8| {
+        if micro_blk[ii] == level {
+            flk[ii] = class;
+        }
+    };
+    // Emitters: the 2x2x2 cells of the block plus the air cells touching each face
+    for z in 0..buf.sz {
+        for y in 0..buf.sy {
+            for x in 0..buf.sx {
+                let b = buf.get_local(x, y, z);
+                let Some(ty) = reg.get(b.id) else {
+                    continue;
+                };
+                let level = ty.light_emission(b.state);
+                if level == 0 {
+                    continue;
+                }
+                let class = ty.light_flicker(b.state).as_u8();
+                let (bx, by, bz) = (x * MICRO_SCALE, y * MICRO_SCALE, z * MICRO_SCALE);
+                for oy in 0..MICRO_SCALE {
+                    for oz in 0..MICRO_SCALE {
+                        for ox in 0..MICRO_SCALE {
+                            claim(
+                                &mut flk,
+                                midx(bx + ox, by + oy, bz + oz, mxs, mzs),
+                                level,
+                                class,
+                            );
+                        }
+                    }
+                }
+                for a in 0..MICRO_SCALE {
+                    for c in 0..MICRO_SCALE {
+                        if bx > 0 {
+                            claim(
+                                &mut flk,
+                                midx(bx - 1, by + a, bz + c, mxs, mzs),
+                                level,
+                                class,
+                            );
+                        }
+                        if bx + MICRO_SCALE < mxs {
+                            claim(
+                                &mut flk,
+                                midx(bx + MICRO_SCALE, by + a, bz + c, mxs, mzs),
+                                level,
+                                class,
+                            );
+                        }
+                        if by > 0 {
+                            claim(
+                                &mut flk,
+                                midx(bx + a, by - 1, bz + c, mxs, mzs),
+                                level,
+                                class,
+                            );
+                        }
+                        if by + MICRO_SCALE < mys {
+                            claim(
+                                &mut flk,
+                                midx(bx + a, by + MICRO_SCALE, bz + c, mxs, mzs),
+                                level,
+                                class,
+                            );
+                        }
+                        if bz > 0 {
+                            claim(
+                                &mut flk,
+                                midx(bx + a, by + c, bz - 1, mxs, mzs),
+                                level,
+                                class,
+                            );
+                        }
+                        if bz + MICRO_SCALE < mzs {
+                            claim(
+                                &mut flk,
+                                midx(bx + a, by + c, bz + MICRO_SCALE, mxs, mzs),
+                                level,
+                                class,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+    for (lx, ly, lz, level, _is_beacon) in store.emitters_for_chunk(buf.coord) {
+        if level == 0 || lx >= buf.sx || ly >= buf.sy || lz >= buf.sz {
+            continue;
+        }
+        let b = buf.get_local(lx, ly, lz);
+        let class = reg
+            .get(b.id)
+            .map(|ty| ty.light_flicker(b.state).as_u8())
+            .unwrap_or(0);
+        for my in ly * MICRO_SCALE..(ly + 1) * MICRO_SCALE {
+            for mz in lz * MICRO_SCALE..(lz + 1) * MICRO_SCALE {
+                for mx in lx * MICRO_SCALE..(lx + 1) * MICRO_SCALE {
+                    claim(&mut flk, midx(mx, my, mz, mxs, mzs), level, class);
+                }
+            }
+        }
+    }
+
+    // Counting sort of lit cells, brightest first
+    let mut counts = [0usize; 256];
+    for &v in micro_blk {
+        counts[v as usize] += 1;
+    }
+    let mut start = [0usize; 256];
+    let mut acc = 0usize;
+    for level in (1..256).rev() {
+        start[level] = acc;
+        acc += counts[level];
+    }
+    let mut order = vec![0usize; acc];
+    for (ii, &v) in micro_blk.iter().enumerate() {
+        if v > 0 {
+            order[start[v as usize]] = ii;
+            start[v as usize] += 1;
+        }
+    }
+
+    let (sx, sz) = (buf.sx, buf.sz);
+    for ii in order {
+        if flk[ii] != UNSET {
+            continue;
+        }
+        let level = micro_blk[ii];
+        let my = ii / stride_y;
+        let rem = ii - my * stride_y;
+        let mz = rem / stride_z;
+        let mx = rem - mz * stride_z;
+        let mut class = None;
+        if let Some(parent) = level.checked_add(MICRO_BLOCK_ATTENUATION) {
+            let mut neighbors = [None; 6];
+            if mx > 0 {
+                neighbors[0] = Some(ii - 1);
+            }
+            if mx + 1 < mxs {
+                neighbors[1] = Some(ii + 1);
+            }
+            if my > 0 {
+                neighbors[2] = Some(ii - stride_y);
+            }
+            if my + 1 < mys {
+                neighbors[3] = Some(ii + stride_y);
+            }
+            if mz > 0 {
+                neighbors[4] = Some(ii - stride_z);
+            }
+            if mz + 1 < mzs {
+                neighbors[5] = Some(ii + stride_z);
+            }
+            class = neighbors
+                .into_iter()
+                .flatten()
+                .find(|&n| micro_blk[n] == parent && flk[n] != UNSET)
+                .map(|n| flk[n]);
+        }
+        let class = class.unwrap_or_else(|| {
+            let (x, y, z) = (mx / MICRO_SCALE, my / MICRO_SCALE, mz / MICRO_SCALE);
+            if mx == 0 {
+                plane_class(&nb.flk_xn, y * sz + z)
+            } else if mx + 1 == mxs {
+                plane_class(&nb.flk_xp, y * sz + z)
+            } else if mz == 0 {
+                plane_class(&nb.flk_zn, y * sx + x)
+            } else if mz + 1 == mzs {
+                plane_class(&nb.flk_zp, y * sx + x)
+            } else if my == 0 {
+                plane_class(&nb.flk_yn, z * sx + x)
+            } else if my + 1 == mys {
+                plane_class(&nb.flk_yp, z * sx + x)
+            } else {
+                0
+            }
+        });
+        flk[ii] = class;
+    }
+    for v in flk.iter_mut() {
+        if *v == UNSET {
+            *v = 0;
+        }
+    }
+    flk
+}
+
 // Scaffold for S=2 micro-voxel lighting engine.
 // For now, this delegates to the legacy voxel light grid to keep behavior unchanged
 // while wiring up mode toggling and rebuild plumbing. The full implementation will
@@ -0,0 +1,240 @@
+//! Seam checksums: each chunk's published border planes are hashed per face, and every
+//! lighting pass records the hashes of the neighbour planes it was seeded from. A chunk
+//! whose recorded input no longer matches what its neighbour publishes was lit against
+//! stale data, which shows up as a visible light seam.
+
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use geist_blocks::stable_hash::Fnv1a;
+use geist_world::ChunkCoord;
+
+use crate::{BelowWorld, LightBorders, LightingChunkEntry, LightingStore};
+
+/// Checksums of the neighbour planes a grid was lit with, indexed by `SeamFace`; `None`
+/// where the neighbour had published nothing.
+pub type SeamInputs = [Option<u64>; 6];
+
+/// A face of a chunk, in `BorderChecksums` order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SeamFace {
+    Xn = 0,
+    Xp = 1,
+    Zn = 2,
+    Zp = 3,
+    Yn = 4,
+    Yp = 5,
+}
+
+impl SeamFace {
+    pub const ALL: [SeamFace; 6] = [
+        SeamFace::Xn,
+        SeamFace::Xp,
+        SeamFace::Zn,
+        SeamFace::Zp,
+        SeamFace::Yn,
+        SeamFace::Yp,
+    ];
+
+    #[inline]
+    pub fn opposite(self) -> SeamFace {
+        match self {
+            SeamFace::Xn => SeamFace::Xp,
+            SeamFace::Xp => SeamFace::Xn,
+            SeamFace::Zn => SeamFace::Zp,
+            SeamFace::Zp => SeamFace::Zn,
+            SeamFace::Yn => SeamFace::Yp,
+            SeamFace::Yp => SeamFace::Yn,
+        }
+    }
+
+    /// Chunk offset of the neighbour across this face.
+    #[inline]
+    pub fn offset(self) -> (i32, i32, i32) {
+        match self {
+            SeamFace::Xn => (-1, 0, 0),
+            SeamFace::Xp => (1, 0, 0),
+            SeamFace::Zn => (0, 0, -1),
+            SeamFace::Zp => (0, 0, 1),
+            SeamFace::Yn => (0, -1, 0),
+            SeamFace::Yp => (0, 1, 0),
+        }
+    }
+
+    /// The face of `a` that touches `b`, if the two chunks are adjacent.
+    pub fn between(a: ChunkCoord, b: ChunkCoord) -> Option<SeamFace> {
+        let d = (b.cx - a.cx, b.cy - a.cy, b.cz - a.cz);
+        SeamFace::ALL.into_iter().find(|f| f.offset() == d)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SeamFace::Xn => "-X",
+            SeamFace::Xp => "+X",
+            SeamFace::Zn => "-Z",
+            SeamFace::Zp => "+Z",
+            SeamFace::Yn => "-Y",
+            SeamFace::Yp => "+Y",
+        }
+    }
+}
+
+/// Per-face checksums of every channel a chunk publishes across its seams.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BorderChecksums(pub [u64; 6]);
+
+impl BorderChecksums {
+    pub fn from_borders(lb: &LightBorders) -> Self {
+        let empty: &[u8] = &[];
+        // Y faces carry no beacon direction planes.
+        let faces: [[&[u8]; 5]; 6] = [
+            [&lb.xn, &lb.sk_xn, &lb.bcn_xn, &lb.bcn_dir_xn, &lb.flk_xn],
+            [&lb.xp, &lb.sk_xp, &lb.bcn_xp, &lb.bcn_dir_xp, &lb.flk_xp],
+            [&lb.zn, &lb.sk_zn, &lb.bcn_zn, &lb.bcn_dir_zn, &lb.flk_zn],
+            [&lb.zp, &lb.sk_zp, &lb.bcn_zp, &lb.bcn_dir_zp, &lb.flk_zp],
+            [&lb.yn, &lb.sk_yn, &lb.bcn_yn, empty, &lb.flk_yn],
+            [&lb.yp, &lb.sk_yp, &lb.bcn_yp, empty, &lb.flk_yp],
+        ];
+        Self(faces.map(|planes| {
+            let mut h = Fnv1a::new();
+            for p in planes {
+                hash_plane(&mut h, p);
+            }
+            h.finish()
+        }))
+    }
+
+    #[inline]
+    pub fn get(&self, face: SeamFace) -> u64 {
+        self.0[face as usize]
+    }
+}
+
+// Hashed a word at a time, with the length mixed in so planes cannot shift into each other.
+fn hash_plane(h: &mut Fnv1a, plane: &[u8]) {
+    h.write_word(plane.len() as u64);
+    let mut words = plane.chunks_exact(8);
+    for w in &mut words {
+        h.write_word(u64::from_le_bytes([
+            w[0], w[1], w[2], w[3], w[4], w[5], w[6], w[7],
+        ]));
+    }
+    h.write(words.remainder());
+}
+
+/// A chunk lit from a neighbour plane that the neighbour no longer publishes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SeamMismatch {
+    pub coord: ChunkCoord,
+    pub face: SeamFace,
+    /// Checksum of the plane the chunk was lit with; `None` if the neighbour had none yet.
+    pub used: Option<u64>,
+    /// Checksum of the plane the neighbour publishes now.
+    pub current: u64,
+}
+
+/// Checksum of the plane `coord` is handed across `face`, following the same rules as
+/// `get_neighbor_borders` (nothing crosses a solid world floor).
+pub(crate) fn neighbor_input(
+    map: &HashMap<ChunkCoord, LightingChunkEntry>,
+    coord: ChunkCoord,
+    face: SeamFace,
+    below: Option<BelowWorld>,
+) -> Option<u64> {
+    if face == SeamFace::Yn && below == Some(BelowWorld::Solid) {
+        return None;
+    }
+    let (dx, dy, dz) = face.offset();
+    map.get(&coord.offset(dx, dy, dz))?
+        .checksums
+        .map(|c| c.get(face.opposite()))
+}
+
+impl LightingStore {
+    fn seam_mismatch_in(
+        &self,
+        map: &HashMap<ChunkCoord, LightingChunkEntry>,
+        coord: ChunkCoord,
+        face: SeamFace,
+    ) -> Option<SeamMismatch> {
+        let used = map.get(&coord)?.borders.as_ref()?.inputs[face as usize];
+        let current = neighbor_input(map, coord, face, self.below_floor(coord))?;
+        (used != Some(current)).then_some(SeamMismatch {
+            coord,
+            face,
+            used,
+            current,
+        })
+    }
+
+    /// Whether `coord`'s stored light was computed from an older plane than its
+    /// neighbour across `face` publishes now. Chunks without stored borders, or without
+    /// a neighbour, never mismatch.
+    pub fn seam_mismatch(&self, coord: ChunkCoord, face: SeamFace) -> Option<SeamMismatch> {
+        let map = self.chunks.lock().unwrap();
+        self.seam_mismatch_in(&map, coord, face)
+    }
+
+    /// Every stale seam in the store. Empty once lighting has settled.
+    pub fn seam_mismatches(&self) -> Vec<SeamMismatch> {
+        let map = self.chunks.lock().unwrap();
+        let mut out: Vec<SeamMismatch> = map
+            .keys()
+            .flat_map(|&c| SeamFace::ALL.map(|f| self.seam_mismatch_in(&map, c, f)))
+            .flatten()
+            .collect();
+        out.sort_by_key(|m| (m.coord.cx, m.coord.cy, m.coord.cz, m.face as u8));
+        out
+    }
+
+    /// Panic unless both sides of the seam between adjacent chunks `a` and `b` were lit
+    /// from what the other side publishes.
+    #[track_caller]
+    pub fn assert_seam_consistent(&self, a: ChunkCoord, b: ChunkCoord) {
+        let face = SeamFace::between(a, b)
+            .unwrap_or_else(|| panic!("chunks {:?} and {:?} are not adjacent", a, b));
+        for (coord, face) in [(a, face), (b, face.opposite())] {
+            if let Some(m) = self.seam_mismatch(coord, face) {
+                panic!(
+                    "seam mismatch: chunk ({}, {}, {}) {} was lit with {:?}, neighbour publishes {:#018x}",
+                    m.coord.cx,
+                    m.coord.cy,
+                    m.coord.cz,
+                    m.face.as_str(),
+                    m.used,
+                    m.current
+                );
+            }
+        }
+    }
+
+    /// Stale seams of `coord` right after its borders were stored. Counted and kept as the
+    /// last mismatch; one that outlives the relight its neighbour triggered is a bug.
+    pub(crate) fn note_seam_mismatches(
+        &self,
+        map: &HashMap<ChunkCoord, LightingChunkEntry>,
+        coord: ChunkCoord,
+    ) {
+        let mut last = None;
+        for face in SeamFace::ALL {
+            if let Some(m) = self.seam_mismatch_in(map, coord, face) {
+                self.seam_mismatch_count.fetch_add(1, Ordering::Relaxed);
+                last = Some(m);
+            }
+        }
+        if let Some(m) = last {
+            *self.last_seam_mismatch.lock().unwrap() = Some((m, Instant::now()));
+        }
+    }
+
+    /// Seam mismatches seen as chunks stored their borders, since the store was created.
+    pub fn seam_mismatch_count(&self) -> u64 {
+        self.seam_mismatch_count.load(Ordering::Relaxed)
+    }
+
+    /// The most recent seam mismatch and when it was seen.
+    pub fn last_seam_mismatch(&self) -> Option<(SeamMismatch, Instant)> {
+        *self.last_seam_mismatch.lock().unwrap()
+    }
+}
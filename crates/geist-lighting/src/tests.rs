@@ -13,13 +13,15 @@ fn make_test_registry() -> BlockRegistry {
             solid: Some(false),
             blocks_skylight: Some(false),
             propagates_light: Some(true),
-            emission: Some(0),
+            emission: Some(0.into()),
+            flicker: None,
             light_profile: None,
             light: None,
             shape: Some(ShapeConfig::Simple("cube".into())),
             materials: None,
             state_schema: None,
             seam: None,
+            tags: Vec::new(),
         },
         BlockDef {
             name: "stone".into(),
@@ -27,13 +29,15 @@ fn make_test_registry() -> BlockRegistry {
             solid: Some(true),
             blocks_skylight: Some(true),
             propagates_light: Some(false),
-            emission: Some(0),
+            emission: Some(0.into()),
+            flicker: None,
             light_profile: None,
             light: None,
             shape: Some(ShapeConfig::Simple("cube".into())),
             materials: None,
             state_schema: None,
             seam: None,
+            tags: Vec::new(),
         },
         BlockDef {
             name: "slab".into(),
@@ -41,13 +45,31 @@ fn make_test_registry() -> BlockRegistry {
             solid: Some(true),
             blocks_skylight: Some(false),
             propagates_light: Some(true),
-            emission: Some(0),
+            emission: Some(0.into()),
+            flicker: None,
             light_profile: None,
             light: None,
             shape: Some(ShapeConfig::Simple("slab".into())),
             materials: None,
             state_schema: None,
             seam: None,
+            tags: Vec::new(),
+        },
+        BlockDef {
+            name: "water".into(),
+            id: Some(4),
+            solid: Some(true),
+            blocks_skylight: Some(true),
+            propagates_light: Some(false),
+            emission: Some(0.into()),
+            flicker: None,
+            light_profile: None,
+            light: None,
+            shape: Some(ShapeConfig::Simple("cube".into())),
+            materials: None,
+            state_schema: None,
+            seam: None,
+            tags: Vec::new(),
         },
         BlockDef {
             name: "fence".into(),
@@ -55,13 +77,15 @@ fn make_test_registry() -> BlockRegistry {
             solid: Some(false),
             blocks_skylight: Some(false),
             propagates_light: Some(true),
-            emission: Some(0),
+            emission: Some(0.into()),
+            flicker: None,
             light_profile: None,
             light: None,
             shape: Some(ShapeConfig::Simple("fence".into())),
             materials: None,
             state_schema: None,
             seam: None,
+            tags: Vec::new(),
         },
     ];
     BlockRegistry::from_configs(
@@ -723,6 +747,70 @@ fn micro_skylight_open_above_and_blocked() {
     assert_eq!(lg_blk.skylight[lg_blk.idx(0, 0, 0)], 0); // below stays dark
 }
 
+#[test]
+fn skylight_fades_with_water_depth() {
+    let reg = make_test_registry();
+    let (sx, sy, sz) = (1, 8, 1);
+    let world = geist_world::World::new(1, 1, 1, 1, WorldGenMode::Flat { thickness: 0 });
+    let air = Block {
+        id: reg.id_by_name("air").unwrap(),
+        state: 0,
+    };
+    let water = Block {
+        id: reg.id_by_name("water").unwrap(),
+        state: 0,
+    };
+    let stone = Block {
+        id: reg.id_by_name("stone").unwrap(),
+        state: 0,
+    };
+    let buf = make_chunk_buf_with(&reg, 0, 0, sx, sy, sz, &|_, y, _| match y {
+        0 => stone,
+        1..=5 => water,
+        _ => air,
+    });
+    let store = LightingStore::new(sx, sy, sz);
+    let medium = store.water_medium();
+    let lg = super::compute_light_with_borders_buf(&buf, &store, &reg, &world);
+    assert_eq!(lg.skylight[lg.idx(0, 6, 0)], 255);
+    // Each block of water costs two micro steps of extinction.
+    let per_block = medium.sky_extinction * 2;
+    for y in 1..=5 {
+        let expected = 255 - medium.sky_extinction - per_block * (5 - y as u8);
+        assert_eq!(lg.skylight[lg.idx(0, y, 0)], expected, "depth at y={}", y);
+    }
+    assert_eq!(lg.skylight[lg.idx(0, 0, 0)], 0);
+
+    let tint = medium.tint_at_depth(4.0);
+    assert!(tint[0] < tint[1] && tint[1] <= tint[2], "red fades first");
+}
+
+#[test]
+fn water_skylight_crosses_vertical_seam() {
+    let reg = make_test_registry();
+    let (sx, sy, sz) = (1, 2, 1);
+    let world = geist_world::World::new(1, 2, 1, 1, WorldGenMode::Flat { thickness: 0 });
+    let air = Block {
+        id: reg.id_by_name("air").unwrap(),
+        state: 0,
+    };
+    let water = Block {
+        id: reg.id_by_name("water").unwrap(),
+        state: 0,
+    };
+    let store = LightingStore::new(sx, sy, sz);
+    let upper = ChunkBuf::from_blocks_local(ChunkCoord::new(0, 1, 0), sx, sy, sz, vec![water, air]);
+    let lg_up = super::compute_light_with_borders_buf(&upper, &store, &reg, &world);
+    let lower =
+        ChunkBuf::from_blocks_local(ChunkCoord::new(0, 0, 0), sx, sy, sz, vec![water, water]);
+    let lg_lo = super::compute_light_with_borders_buf(&lower, &store, &reg, &world);
+    let step = store.water_medium().sky_extinction * 2;
+    let surface = lg_up.skylight[lg_up.idx(0, 0, 0)];
+    assert_eq!(surface, 255 - step / 2);
+    assert_eq!(lg_lo.skylight[lg_lo.idx(0, 1, 0)], surface - step);
+    assert_eq!(lg_lo.skylight[lg_lo.idx(0, 0, 0)], surface - 2 * step);
+}
+
 #[test]
 fn skylight_neighbors_coarse_and_micro_precedence() {
     let reg = make_test_registry();
@@ -806,12 +894,228 @@ fn emitters_seed_micro_and_remove() {
     assert_eq!(lg.block_light[lg.idx(1, 0, 0)], 184);
 
     // Removing the emitter clears the light next recompute
-    store.remove_emitter_world(0, 0, 0);
+    store.remove_emitter_world(0, 0, 0, 0);
     let lg_off = super::compute_light_with_borders_buf(&buf, &store, &reg, &world);
     assert_eq!(lg_off.block_light[lg_off.idx(0, 0, 0)], 0);
     assert_eq!(lg_off.block_light[lg_off.idx(1, 0, 0)], 0);
 }
 
+#[test]
+fn coarse_quality_skips_micro_and_keeps_light_reach() {
+    let reg = make_test_registry();
+    let (sx, sy, sz) = (4, 2, 1);
+    let world = geist_world::World::new(1, 1, 1, 5, WorldGenMode::Flat { thickness: 0 });
+    let air_id = reg.id_by_name("air").unwrap();
+    let buf = make_chunk_buf_with(&reg, 0, 0, sx, sy, sz, &|_, _, _| Block {
+        id: air_id,
+        state: 0,
+    });
+    let store = LightingStore::new(sx, sy, sz);
+    store.add_emitter_world(0, 0, 0, 200);
+
+    let coarse =
+        super::compute_light_with_quality(&buf, &store, &reg, &world, LightQuality::Coarse);
+    let full = super::compute_light_with_quality(&buf, &store, &reg, &world, LightQuality::Full);
+    assert!(!coarse.has_micro());
+    assert!(full.has_micro());
+    assert_eq!(coarse.block_light[coarse.idx(0, 0, 0)], 200);
+    for x in 1..sx {
+        let (c, f) = (
+            coarse.block_light[coarse.idx(x, 0, 0)],
+            full.block_light[full.idx(x, 0, 0)],
+        );
+        assert!(c > 0 && c < 200, "coarse light reaches x={} ({})", x, c);
+        assert!(c.abs_diff(f) <= 32, "coarse {} vs full {} at x={}", c, f, x);
+    }
+    assert_eq!(coarse.skylight_at(2, 1, 0), full.skylight_at(2, 1, 0));
+}
+
+#[test]
+fn face_light_diffs_report_only_open_faces_past_threshold() {
+    let reg = make_test_registry();
+    let (sx, sy, sz) = (4, 3, 1);
+    let world = geist_world::World::new(1, 1, 1, 5, WorldGenMode::Flat { thickness: 0 });
+    let air_id = reg.id_by_name("air").unwrap();
+    let stone_id = reg.id_by_name("stone").unwrap();
+    // Sealed corridor: the floor's +Y and the roof's -Y faces are the only open ones.
+    let buf = make_chunk_buf_with(&reg, 0, 0, sx, sy, sz, &|_, y, _| Block {
+        id: if y == 1 { air_id } else { stone_id },
+        state: 0,
+    });
+    let store = LightingStore::new(sx, sy, sz);
+    store.add_emitter_world(0, 1, 0, 200);
+    let lit = super::compute_light_with_quality(&buf, &store, &reg, &world, LightQuality::Full);
+    let dark = LightGrid::new(sx, sy, sz);
+
+    assert!(super::face_light_diffs(&buf, &reg, &lit, &lit, 0).is_empty());
+    let diffs = super::face_light_diffs(&buf, &reg, &lit, &dark, 0);
+    assert_eq!(diffs.len(), 2 * sx);
+    assert!(
+        diffs
+            .iter()
+            .all(|d| (d.face, d.y) == (0, 0) || (d.face, d.y) == (1, 2))
+    );
+    assert!(diffs.iter().all(|d| d.b == 0 && d.a > 0));
+    // Faces beside the emitter are the brightest; a high threshold keeps only those.
+    let peak = diffs.iter().map(|d| d.a).max().unwrap();
+    let near = super::face_light_diffs(&buf, &reg, &lit, &dark, peak - 1);
+    assert!(!near.is_empty());
+    assert!(near.iter().all(|d| d.x == 0));
+}
+
+#[test]
+fn flicker_class_follows_dominant_emitter() {
+    use geist_blocks::config::FlickerClass;
+    let emitter = |name: &str, id: u16, emission: u8, flicker: Option<FlickerClass>| BlockDef {
+        name: name.into(),
+        id: Some(id),
+        solid: Some(true),
+        blocks_skylight: Some(true),
+        propagates_light: Some(false),
+        emission: Some(emission.into()),
+        flicker,
+        light_profile: None,
+        light: None,
+        shape: Some(ShapeConfig::Simple("cube".into())),
+        materials: None,
+        state_schema: None,
+        seam: None,
+        tags: Vec::new(),
+    };
+    let air = BlockDef {
+        emission: Some(0.into()),
+        solid: Some(false),
+        blocks_skylight: Some(false),
+        propagates_light: Some(true),
+        ..emitter("air", 0, 0, None)
+    };
+    let reg = BlockRegistry::from_configs(
+        MaterialCatalog::new(),
+        BlocksConfig {
+            blocks: vec![
+                air,
+                emitter("torch", 1, 200, Some(FlickerClass::Torch)),
+                emitter("glow", 2, 255, None),
+            ],
+            lighting: None,
+            unknown_block: None,
+        },
+    )
+    .unwrap();
+    let world = geist_world::World::new(1, 1, 1, 4, WorldGenMode::Flat { thickness: 0 });
+    let (sx, sy, sz) = (6, 1, 1);
+    // Torch at x=0, brighter steady emitter at x=5
+    let buf = make_chunk_buf_with(&reg, 0, 0, sx, sy, sz, &|x, _, _| Block {
+        id: match x {
+            0 => 1,
+            5 => 2,
+            _ => 0,
+        },
+        state: 0,
+    });
+    let store = LightingStore::new(sx, sy, sz);
+    let lg = super::compute_light_with_borders_buf(&buf, &store, &reg, &world);
+    let torch = FlickerClass::Torch.as_u8();
+    assert_eq!(lg.block_flicker[lg.idx(0, 0, 0)], torch);
+    assert_eq!(lg.block_flicker[lg.idx(1, 0, 0)], torch);
+    assert_eq!(lg.block_flicker[lg.idx(2, 0, 0)], 0);
+    assert_eq!(lg.block_flicker[lg.idx(4, 0, 0)], 0);
+
+    // The class reaches the seam plane and the atlas alpha channel
+    let borders = LightBorders::from_grid(&lg);
+    assert_eq!(borders.flk_xn[0], torch);
+    let atlas =
+        super::pack_light_grid_atlas_with_neighbors(&lg, &NeighborBorders::empty(sx, sy, sz));
+    // Interior slice y=0 is atlas tile 1; voxel (1,0,0) sits one texel in from the ring
+    let ox = (1 % atlas.grid_cols) * atlas.sx;
+    let oy = (1 / atlas.grid_cols) * atlas.sz;
+    let di = ((oy + 1) * atlas.width + ox + 1 + 1) * 4;
+    assert_eq!(atlas.data[di + 3], torch);
+}
+
+#[test]
+fn toggling_a_state_driven_emitter_relights_both_chunks() {
+    use geist_blocks::config::EmissionDef;
+    use std::collections::HashMap;
+    let def = |name: &str, id: u16, solid: bool| BlockDef {
+        name: name.into(),
+        id: Some(id),
+        solid: Some(solid),
+        blocks_skylight: Some(solid),
+        propagates_light: Some(!solid),
+        emission: Some(0.into()),
+        flicker: None,
+        light_profile: None,
+        light: None,
+        shape: Some(ShapeConfig::Simple("cube".into())),
+        materials: None,
+        state_schema: None,
+        seam: None,
+        tags: Vec::new(),
+    };
+    let furnace = BlockDef {
+        emission: Some(EmissionDef::By {
+            by: "lit".into(),
+            map: HashMap::from([("true".into(), 180)]),
+        }),
+        state_schema: Some(HashMap::from([(
+            "lit".into(),
+            vec!["false".into(), "true".into()],
+        )])),
+        ..def("furnace", 1, true)
+    };
+    let reg = BlockRegistry::from_configs(
+        MaterialCatalog::new(),
+        BlocksConfig {
+            blocks: vec![def("air", 0, false), furnace],
+            lighting: None,
+            unknown_block: None,
+        },
+    )
+    .unwrap();
+    let ty = reg.get(1).unwrap();
+    let lit = ty.pack_state(&HashMap::from([("lit".into(), "true".into())]));
+    let unlit = ty.pack_state(&HashMap::from([("lit".into(), "false".into())]));
+    assert_eq!((ty.light_emission(lit), ty.light_emission(unlit)), (180, 0));
+    assert_eq!(ty.emission, 180);
+
+    let world = geist_world::World::new(2, 1, 1, 8, WorldGenMode::Flat { thickness: 0 });
+    let (sx, sy, sz) = (6, 1, 1);
+    let (a, b) = (ChunkCoord::new(0, 0, 0), ChunkCoord::new(1, 0, 0));
+    let store = LightingStore::new(sx, sy, sz);
+    let with_furnace = |state| {
+        make_chunk_buf_with(&reg, 0, 0, sx, sy, sz, &move |x, _, _| match x {
+            2 => Block { id: 1, state },
+            _ => Block::AIR,
+        })
+    };
+    let empty_b = make_chunk_buf_with(&reg, 1, 0, sx, sy, sz, &|_, _, _| Block::AIR);
+    // Light A, publish its seams, then light B from them, as streaming would.
+    let relight = |buf_a: &ChunkBuf| {
+        let lg_a = compute_light_with_borders_buf(buf_a, &store, &reg, &world);
+        store.update_borders(a, LightBorders::from_grid(&lg_a));
+        let lg_b = compute_light_with_borders_buf(&empty_b, &store, &reg, &world);
+        store.update_borders(b, LightBorders::from_grid(&lg_b));
+        (lg_a, lg_b)
+    };
+    let (dark_a, dark_b) = relight(&with_furnace(unlit));
+    assert!(dark_a.block_light.iter().all(|&v| v == 0));
+
+    // Turning it on registers the emitter the edit path adds and lights across the seam.
+    store.add_emitter_world(2, 0, 0, ty.light_emission(lit));
+    let (on_a, on_b) = relight(&with_furnace(lit));
+    assert_eq!(on_a.block_light[on_a.idx(2, 0, 0)], 180);
+    assert!(on_b.block_light[on_b.idx(0, 0, 0)] > 0);
+
+    // Turning it off drops the emitter; both chunks go back to never having been lit.
+    let darkened = store.remove_emitter_world(2, 0, 0, 0);
+    assert!(darkened.iter().any(|(c, _)| *c == a));
+    assert!(store.emitters_for_chunk(a).is_empty());
+    let (off_a, off_b) = relight(&with_furnace(unlit));
+    assert_eq!(off_a.block_light, dark_a.block_light);
+    assert_eq!(off_b.block_light, dark_b.block_light);
+}
+
 #[test]
 fn lightingstore_clear_chunk_and_all_borders() {
     let store = LightingStore::new(2, 2, 2);
@@ -1671,13 +1975,15 @@ fn can_cross_face_s2_basic_blocking_and_open() {
                 solid: Some(false),
                 blocks_skylight: Some(false),
                 propagates_light: Some(true),
-                emission: Some(0),
+                emission: Some(0.into()),
+                flicker: None,
                 light_profile: None,
                 light: None,
                 shape: Some(ShapeConfig::Simple("cube".into())),
                 materials: None,
                 state_schema: None,
                 seam: None,
+                tags: Vec::new(),
             },
             BlockDef {
                 name: "stone".into(),
@@ -1685,13 +1991,15 @@ fn can_cross_face_s2_basic_blocking_and_open() {
                 solid: Some(true),
                 blocks_skylight: Some(true),
                 propagates_light: Some(false),
-                emission: Some(0),
+                emission: Some(0.into()),
+                flicker: None,
                 light_profile: None,
                 light: None,
                 shape: Some(ShapeConfig::Simple("cube".into())),
                 materials: None,
                 state_schema: None,
                 seam: None,
+                tags: Vec::new(),
             },
             BlockDef {
                 name: "slab".into(),
@@ -1699,13 +2007,15 @@ fn can_cross_face_s2_basic_blocking_and_open() {
                 solid: Some(true),
                 blocks_skylight: Some(false),
                 propagates_light: Some(true),
-                emission: Some(0),
+                emission: Some(0.into()),
+                flicker: None,
                 light_profile: None,
                 light: None,
                 shape: Some(ShapeConfig::Simple("slab".into())),
                 materials: None,
                 state_schema: None,
                 seam: None,
+                tags: Vec::new(),
             },
             // Slab with dont_occlude_same: should permit face openness when both sides are the same
             BlockDef {
@@ -1714,13 +2024,15 @@ fn can_cross_face_s2_basic_blocking_and_open() {
                 solid: Some(true),
                 blocks_skylight: Some(false),
                 propagates_light: Some(true),
-                emission: Some(0),
+                emission: Some(0.into()),
+                flicker: None,
                 light_profile: None,
                 light: None,
                 shape: Some(ShapeConfig::Simple("slab".into())),
                 materials: None,
                 state_schema: None,
                 seam: Some(SeamPolicyCfg::Simple(SeamPolicySimple::DontOccludeSame)),
+                tags: Vec::new(),
             },
         ];
         BlockRegistry::from_configs(
@@ -1756,3 +2068,389 @@ fn can_cross_face_s2_basic_blocking_and_open() {
     });
     assert!(!super::can_cross_face_s2(&buf_stone, &reg, 0, 0, 0, 2));
 }
+
+#[test]
+fn dynamic_light_floods_around_walls_and_follows_moves() {
+    use geist_geom::{Vec3, WorldPos};
+    // A wall at x == 2 spanning y/z; the light sits at x == 0.
+    let wall = |x: i32, _y: i32, _z: i32| x == 2;
+    let mut lights = DynamicLights::new();
+    let id = lights.add(DynamicLight {
+        pos: Vec3::new(0.5, 0.5, 0.5),
+        level: 200,
+        attenuation: 20,
+    });
+    let focus = WorldPos::new(0, 0, 0);
+    assert!(lights.update(focus, wall));
+    let vol = lights.volume().unwrap();
+    assert_eq!(vol.get(WorldPos::new(0, 0, 0)), 200);
+    assert_eq!(vol.get(WorldPos::new(1, 0, 0)), 180);
+    assert_eq!(vol.get(WorldPos::new(0, 3, 0)), 140);
+    // Opaque voxels stay dark and block the flood behind them.
+    assert_eq!(vol.get(WorldPos::new(2, 0, 0)), 0);
+    assert_eq!(vol.get(WorldPos::new(3, 0, 0)), 0);
+
+    // Moving within the same voxel does not rebuild; crossing into the next one does.
+    assert!(lights.move_to(id, Vec3::new(0.9, 0.5, 0.5)));
+    assert!(!lights.update(focus, wall));
+    assert!(lights.move_to(id, Vec3::new(-2.5, 0.5, 0.5)));
+    assert!(lights.update(focus, wall));
+    let vol = lights.volume().unwrap();
+    assert_eq!(vol.get(WorldPos::new(-3, 0, 0)), 200);
+    assert_eq!(vol.get(WorldPos::new(0, 0, 0)), 140);
+
+    assert!(lights.remove(id));
+    assert!(lights.update(focus, wall));
+    assert!(lights.volume().is_none());
+    assert!(!lights.move_to(id, Vec3::ZERO));
+}
+
+#[test]
+fn skylight_max_scales_sampling_and_world_queries_without_relighting() {
+    let reg = make_test_registry();
+    let (sx, sy, sz) = (2, 2, 2);
+    let store = LightingStore::new(sx, sy, sz);
+    store.set_skylight_max(51);
+    let air_id = reg.id_by_name("air").unwrap();
+    let buf = make_chunk_buf_with(&reg, 0, 0, sx, sy, sz, &|_, _, _| Block {
+        id: air_id,
+        state: 0,
+    });
+    // Computed at night, but the grid keeps full-strength sky.
+    let lg = LightGrid::compute_with_borders_buf(&buf, &store, &reg);
+    assert_eq!(lg.skylight_at(0, 0, 0), 255);
+    assert_eq!(lg.sample_face_local(0, 0, 0, 0), 51);
+
+    let coord = ChunkCoord::new(0, 0, 0);
+    assert_eq!(store.light_at_world(1, 1, 1), None);
+    store.update_levels(coord, &lg);
+    assert_eq!(store.light_at_world(1, 1, 1), Some(51));
+    assert_eq!(store.light_at_world(-1, 1, 1), None);
+
+    store.set_skylight_max(255);
+    assert_eq!(lg.sample_face_local(0, 0, 0, 0), 255);
+    assert_eq!(store.light_at_world(1, 1, 1), Some(255));
+
+    store.clear_chunk(coord);
+    assert_eq!(store.light_at_world(1, 1, 1), None);
+}
+
+#[test]
+fn night_ambient_floors_open_sky_in_sampling_and_packed_atlas() {
+    let reg = make_test_registry();
+    let (sx, sy, sz) = (2, 2, 2);
+    let store = LightingStore::new(sx, sy, sz);
+    store.set_skylight_max(0);
+    store.set_night_ambient(40);
+    assert_eq!(store.skylight_max(), 40);
+    let air_id = reg.id_by_name("air").unwrap();
+    let buf = make_chunk_buf_with(&reg, 0, 0, sx, sy, sz, &|_, _, _| Block {
+        id: air_id,
+        state: 0,
+    });
+    let lg = LightGrid::compute_with_borders_buf(&buf, &store, &reg);
+    assert_eq!(lg.sample_face_local(0, 0, 0, 0), 40);
+    store.update_levels(ChunkCoord::new(0, 0, 0), &lg);
+    assert_eq!(store.light_at_world(1, 1, 1), Some(40));
+
+    // Starlight rides in the block channel as steady light; sky stays full-strength.
+    let atlas =
+        super::pack_light_grid_atlas_with_neighbors(&lg, &NeighborBorders::empty(sx, sy, sz));
+    // Slice 1 (y = 0) is the second tile in the top row; voxel (0, 0) sits inside its ring.
+    let di = atlas.width + (sx + 2) + 1;
+    assert_eq!(&atlas.data[di * 4..di * 4 + 4], &[40, 255, 0, 0]);
+
+    // Daylight above the floor wins; the floor is only a minimum.
+    store.set_skylight_max(200);
+    assert_eq!(store.skylight_max(), 200);
+    assert_eq!(lg.sample_face_local(0, 0, 0, 0), 200);
+}
+
+#[test]
+fn world_floor_filters_seam_seeding_from_below() {
+    let (sx, sy, sz) = (2, 1, 2);
+    let store = LightingStore::new(sx, sy, sz);
+    let below = ChunkCoord::new(0, -1, 0);
+    let floor = ChunkCoord::new(0, 0, 0);
+    let mut b = LightBorders::new(sx, sy, sz);
+    b.yp = vec![150; sx * sz].into();
+    b.sk_yp = vec![200; sx * sz].into();
+    store.update_borders(below, b);
+    let plane = |v: u8| -> Arc<[u8]> { vec![v; 4 * 4].into() };
+    let mb = MicroBorders {
+        xm_sk_neg: plane(0),
+        xm_sk_pos: plane(0),
+        ym_sk_neg: plane(0),
+        ym_sk_pos: plane(200),
+        zm_sk_neg: plane(0),
+        zm_sk_pos: plane(0),
+        xm_bl_neg: plane(0),
+        xm_bl_pos: plane(0),
+        ym_bl_neg: plane(0),
+        ym_bl_pos: plane(150),
+        zm_bl_neg: plane(0),
+        zm_bl_pos: plane(0),
+        xm: 4,
+        ym: 2,
+        zm: 4,
+    };
+    let _ = store.update_micro_borders(below, mb);
+
+    // No floor: the bottom seam is an ordinary seam.
+    let nb = store.get_neighbor_borders(floor);
+    assert!(nb.yn.is_some() && nb.sk_yn.is_some());
+    let nbm = store.get_neighbor_micro_borders(floor);
+    assert!(nbm.ym_bl_neg.is_some() && nbm.ym_sk_neg.is_some());
+
+    // Void: block light from below still crosses, skylight does not climb out.
+    store.set_world_floor(Some(WorldFloor {
+        min_cy: 0,
+        below: BelowWorld::Void,
+    }));
+    let nb = store.get_neighbor_borders(floor);
+    assert_eq!(nb.yn.as_deref().map(|p| p[0]), Some(150));
+    assert!(nb.sk_yn.is_none());
+    let nbm = store.get_neighbor_micro_borders(floor);
+    assert!(nbm.ym_bl_neg.is_some());
+    assert!(nbm.ym_sk_neg.is_none());
+    // Rows above the floor are unaffected.
+    assert!(store.get_neighbor_borders(below).yn.is_none());
+
+    // Solid: nothing below the floor seeds it.
+    store.set_world_floor(Some(WorldFloor {
+        min_cy: 0,
+        below: BelowWorld::Solid,
+    }));
+    let nb = store.get_neighbor_borders(floor);
+    assert!(nb.yn.is_none() && nb.sk_yn.is_none() && nb.bcn_yn.is_none());
+    let nbm = store.get_neighbor_micro_borders(floor);
+    assert!(nbm.ym_bl_neg.is_none() && nbm.ym_sk_neg.is_none());
+}
+
+#[test]
+fn removing_an_unregistered_emitter_darkens_with_the_block_level() {
+    // Worldgen and saved emitters never register with the store; the removed block's
+    // own level drives the wave, which crosses the east chunk into the one beyond.
+    let (sx, sy, sz) = (4, 4, 4);
+    let store = LightingStore::new(sx, sy, sz);
+    let east = ChunkCoord::new(1, 0, 0);
+    let far = ChunkCoord::new(2, 0, 0);
+
+    let mut b = LightBorders::new(sx, sy, sz);
+    let mut xn = vec![0u8; sy * sz];
+    xn[sz + 1] = 184;
+    b.xn = xn.into();
+    let mut xp = vec![0u8; sy * sz];
+    xp[sz + 1] = 100;
+    b.xp = xp.into();
+    store.update_borders(east, b);
+    let mut b = LightBorders::new(sx, sy, sz);
+    let mut xn = vec![0u8; sy * sz];
+    xn[sz + 1] = 70;
+    b.xn = xn.into();
+    store.update_borders(far, b);
+
+    // Without a registration or a block level there is nothing to darken.
+    assert!(store.remove_emitter_world(3, 1, 1, 0).is_empty());
+
+    let darkened = store.remove_emitter_world(3, 1, 1, 200);
+    let coords: Vec<ChunkCoord> = darkened.iter().map(|(c, _)| *c).collect();
+    assert_eq!(coords, vec![east, far]);
+    assert!(darkened[0].1.xn && darkened[0].1.xp);
+    assert!(darkened[1].1.xn && !darkened[1].1.xp);
+    let nb = store.get_neighbor_borders(ChunkCoord::new(0, 0, 0));
+    assert_eq!(nb.xp.expect("east borders")[sz + 1], 0);
+    let nb = store.get_neighbor_borders(east);
+    assert_eq!(nb.xp.expect("far borders")[sz + 1], 0);
+}
+
+#[test]
+fn removing_an_emitter_darkens_the_seams_it_could_have_lit() {
+    let (sx, sy, sz) = (4, 4, 4);
+    let store = LightingStore::new(sx, sy, sz);
+    store.add_emitter_world(3, 1, 1, 200);
+    let east = ChunkCoord::new(1, 0, 0);
+    let west = ChunkCoord::new(-1, 0, 0);
+
+    let mut b = LightBorders::new(sx, sy, sz);
+    let mut xn = vec![0u8; sy * sz];
+    // One block from the emitter: exactly what it lit.
+    xn[sz + 1] = 184;
+    // Brighter than the emitter could reach here: another source, kept.
+    xn[sz + 3] = 170;
+    // Dimmer than its bound: cleared.
+    xn[3] = 100;
+    b.xn = xn.into();
+    let mut flk = vec![0u8; sy * sz];
+    flk[sz + 1] = 2;
+    flk[sz + 3] = 2;
+    b.flk_xn = flk.into();
+    b.sk_xn = vec![255; sy * sz].into();
+    store.update_borders(east, b);
+
+    let (mxs, mys, mzs) = (sx * 2, sy * 2, sz * 2);
+    let mut xm_bl_neg = vec![0u8; mys * mzs];
+    xm_bl_neg[2 * mzs + 3] = 184;
+    xm_bl_neg[2 * mzs + 7] = 170;
+    let mb = MicroBorders {
+        xm_sk_neg: vec![0; mys * mzs].into(),
+        xm_sk_pos: vec![0; mys * mzs].into(),
+        ym_sk_neg: vec![0; mzs * mxs].into(),
+        ym_sk_pos: vec![0; mzs * mxs].into(),
+        zm_sk_neg: vec![0; mys * mxs].into(),
+        zm_sk_pos: vec![0; mys * mxs].into(),
+        xm_bl_neg: xm_bl_neg.into(),
+        xm_bl_pos: vec![0; mys * mzs].into(),
+        ym_bl_neg: vec![0; mzs * mxs].into(),
+        ym_bl_pos: vec![0; mzs * mxs].into(),
+        zm_bl_neg: vec![0; mys * mxs].into(),
+        zm_bl_pos: vec![0; mys * mxs].into(),
+        xm: mxs,
+        ym: mys,
+        zm: mzs,
+    };
+    let _ = store.update_micro_borders(east, mb);
+
+    // Four blocks away on -X the emitter could leave at most 104.
+    let mut b = LightBorders::new(sx, sy, sz);
+    let mut xp = vec![0u8; sy * sz];
+    xp[sz + 1] = 106;
+    b.xp = xp.into();
+    store.update_borders(west, b);
+
+    let darkened = store.remove_emitter_world(3, 1, 1, 0);
+    assert_eq!(darkened.len(), 1);
+    let (coord, mask) = darkened[0];
+    assert_eq!(coord, east);
+    assert!(mask.xn && !mask.xp && !mask.yn && !mask.yp && !mask.zn && !mask.zp);
+    assert!(
+        store
+            .emitters_for_chunk(ChunkCoord::new(0, 0, 0))
+            .is_empty()
+    );
+
+    // Chunk (0,0,0) reads its +X neighbour's -X planes.
+    let nb = store.get_neighbor_borders(ChunkCoord::new(0, 0, 0));
+    let xp = nb.xp.expect("east borders");
+    assert_eq!((xp[sz + 1], xp[sz + 3], xp[3]), (0, 170, 0));
+    let flk = nb.flk_xp.expect("east flicker");
+    assert_eq!((flk[sz + 1], flk[sz + 3]), (0, 2));
+    assert!(nb.sk_xp.expect("east sky").iter().all(|&v| v == 255));
+    let nbm = store.get_neighbor_micro_borders(ChunkCoord::new(0, 0, 0));
+    let xm = nbm.xm_bl_pos.expect("east micro");
+    assert_eq!((xm[2 * mzs + 3], xm[2 * mzs + 7]), (0, 170));
+    let nb = store.get_neighbor_borders(ChunkCoord::new(0, 0, 0));
+    assert_eq!(nb.xn.expect("west borders")[sz + 1], 106);
+
+    // Nothing left to remove.
+    assert!(store.remove_emitter_world(3, 1, 1, 0).is_empty());
+}
+
+#[test]
+fn sample_world_splits_channels_and_falls_back_to_border_planes() {
+    let (sx, sy, sz) = (4, 4, 4);
+    let store = LightingStore::new(sx, sy, sz);
+    let coord = ChunkCoord::new(0, 0, 0);
+    let mut b = LightBorders::new(sx, sy, sz);
+    b.sk_xn = vec![255; sy * sz].into();
+    b.xn = vec![100; sy * sz].into();
+    b.bcn_xn = vec![50; sy * sz].into();
+    store.update_borders(coord, b);
+
+    // No levels yet: face voxels read the border planes, interior ones are unknown.
+    let face = LightSample {
+        sky: 255,
+        block: 100,
+        beacon: 50,
+    };
+    assert_eq!(store.sample_world(0, 1, 1), Some(face));
+    assert_eq!(store.sample_world(1, 1, 1), None);
+    assert_eq!(store.sample_world(-1, 1, 1), None);
+
+    let mut lg = LightGrid::new(sx, sy, sz);
+    let i = lg.idx(1, 1, 1);
+    lg.skylight[i] = 0xF0;
+    lg.block_light[i] = 0xA0;
+    lg.beacon_light[i] = 0x50;
+    store.update_levels(coord, &lg);
+    let s = store.sample_world(1, 1, 1).expect("levels kept");
+    assert_eq!((s.sky, s.block, s.beacon), (255, 170, 85));
+    // Levels win over the planes once they exist.
+    assert_eq!(store.sample_world(0, 1, 1), Some(LightSample::default()));
+
+    store.set_skylight_max(51);
+    let s = store.sample_world(1, 1, 1).unwrap();
+    assert_eq!(s.sky, 51);
+    assert_eq!(s.max(), 170);
+    assert_eq!(store.light_at_world(1, 1, 1), Some(170));
+}
+
+#[test]
+fn far_negative_chunks_sample_and_track_emitters() {
+    let (sx, sy, sz) = (4, 4, 4);
+    let store = LightingStore::new(sx, sy, sz);
+    let coord = ChunkCoord::new(-250_000, -3, -1);
+    let (wx, wy, wz) = (-1_000_000 + 2, -12 + 1, -4 + 3);
+
+    let mut lg = LightGrid::new(sx, sy, sz);
+    let i = lg.idx(2, 1, 3);
+    lg.block_light[i] = 0xC0;
+    store.update_levels(coord, &lg);
+    assert_eq!(store.light_at_world(wx, wy, wz), Some(0xCC));
+    assert_eq!(store.light_at_world(wx + 2, wy, wz), None);
+
+    store.add_emitter_world(wx, wy, wz, 200);
+    store.add_emitter_world(wx, wy, wz, 200);
+    assert_eq!(store.emitters_for_chunk(coord), vec![(2, 1, 3, 200, false)]);
+    store.remove_emitter_world(wx, wy, wz, 0);
+    assert!(store.emitters_for_chunk(coord).is_empty());
+}
+
+#[test]
+fn seam_checksums_flag_chunks_lit_from_stale_neighbors() {
+    let reg = make_test_registry();
+    let (sx, sy, sz) = (4, 4, 4);
+    let world = geist_world::World::new(1, 1, 1, 10, WorldGenMode::Flat { thickness: 0 });
+    let air_id = reg.id_by_name("air").unwrap();
+    let stone_id = reg.id_by_name("stone").unwrap();
+    let open = |_: usize, _: usize, _: usize| Block {
+        id: air_id,
+        state: 0,
+    };
+    let roofed = |_: usize, y: usize, _: usize| Block {
+        id: if y == sy - 1 { stone_id } else { air_id },
+        state: 0,
+    };
+    let (a, b) = (ChunkCoord::new(0, 0, 0), ChunkCoord::new(1, 0, 0));
+    let buf_a = make_chunk_buf_with(&reg, 0, 0, sx, sy, sz, &open);
+    let buf_b = make_chunk_buf_with(&reg, 1, 0, sx, sy, sz, &open);
+    let store = LightingStore::new(sx, sy, sz);
+    let light = |buf: &ChunkBuf| {
+        LightBorders::from_grid(&compute_light_with_borders_buf(buf, &store, &reg, &world))
+    };
+
+    // A is lit before B exists: stale until relit, but not a mismatch when stored.
+    store.update_borders(a, light(&buf_a));
+    store.update_borders(b, light(&buf_b));
+    assert_eq!(store.seam_mismatch_count(), 0);
+    let stale = store.seam_mismatch(a, SeamFace::Xp).expect("A predates B");
+    assert_eq!(stale.used, None);
+    store.update_borders(a, light(&buf_a));
+    store.assert_seam_consistent(a, b);
+    assert!(store.seam_mismatches().is_empty());
+
+    // A is lit against B, then B changes before A's result is stored.
+    let lb_a = light(&buf_a);
+    let buf_b2 = make_chunk_buf_with(&reg, 1, 0, sx, sy, sz, &roofed);
+    store.update_borders(b, light(&buf_b2));
+    store.update_borders(a, lb_a);
+    assert_eq!(store.seam_mismatch_count(), 1);
+    let (last, _) = store.last_seam_mismatch().unwrap();
+    assert_eq!((last.coord, last.face), (a, SeamFace::Xp));
+    assert_eq!(store.stats().seam_mismatches, 1);
+    let caught = std::panic::catch_unwind(|| store.assert_seam_consistent(b, a));
+    assert!(caught.is_err());
+
+    store.update_borders(a, light(&buf_a));
+    store.assert_seam_consistent(a, b);
+}
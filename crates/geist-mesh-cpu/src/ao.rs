@@ -0,0 +1,46 @@
+//! Baked ambient occlusion: each face corner is darkened by the solids around it in the
+//! open layer in front of the face (two sides and the diagonal), and the shade is
+//! multiplied into the vertex colour. Occupancy is read at S=2, so slabs and stairs
+//! occlude by the half blocks they actually fill.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Shade strength used until `set_ao_strength` is called.
+pub const DEFAULT_AO_STRENGTH: f32 = 0.5;
+
+static AO_STRENGTH: AtomicU8 = AtomicU8::new((DEFAULT_AO_STRENGTH * 255.0) as u8);
+
+/// How dark a fully enclosed corner gets: 0 disables AO, 1 turns it black. Applies to
+/// chunks meshed afterwards.
+pub fn set_ao_strength(strength: f32) {
+    let v = (strength.clamp(0.0, 1.0) * 255.0).round() as u8;
+    AO_STRENGTH.store(v, Ordering::Relaxed);
+}
+
+pub fn ao_strength() -> f32 {
+    AO_STRENGTH.load(Ordering::Relaxed) as f32 / 255.0
+}
+
+#[inline]
+pub(crate) fn ao_strength_u8() -> u8 {
+    AO_STRENGTH.load(Ordering::Relaxed)
+}
+
+/// Vertex shade (255 = unoccluded) of a corner with the given neighbours filled. Two
+/// filled sides hide the diagonal, as in the classic voxel AO.
+#[inline]
+pub(crate) fn corner_shade(side1: bool, side2: bool, corner: bool, strength: u8) -> u8 {
+    let occluders = if side1 && side2 {
+        3
+    } else {
+        side1 as u32 + side2 as u32 + corner as u32
+    };
+    (255 - strength as u32 * occluders / 3) as u8
+}
+
+/// Scale a colour by a shade, keeping alpha.
+#[inline]
+pub(crate) fn shade_rgba(rgba: [u8; 4], shade: u8) -> [u8; 4] {
+    let f = |c: u8| ((c as u32 * shade as u32 + 127) / 255) as u8;
+    [f(rgba[0]), f(rgba[1]), f(rgba[2]), rgba[3]]
+}
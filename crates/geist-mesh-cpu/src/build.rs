@@ -3,8 +3,10 @@ use std::cell::RefCell;
 use std::time::Instant;
 
 use geist_blocks::BlockRegistry;
+use geist_blocks::SlopeShape;
 use geist_blocks::registry::BlockType;
-use geist_blocks::types::{Block, MaterialId};
+use geist_blocks::slope::SLOPE_CORNERS;
+use geist_blocks::types::{Block, FaceRole, MaterialId};
 use geist_chunk::ChunkBuf;
 use geist_geom::{Aabb, Vec3};
 use geist_lighting::{LightBorders, LightGrid, LightingStore, compute_light_with_borders_buf};
@@ -12,11 +14,16 @@ use geist_world::{ChunkCoord, World};
 
 use crate::chunk::ChunkMeshCPU;
 use crate::constants::MICROGRID_STEPS;
-use crate::emit::emit_box_generic_clipped;
+use crate::constants::OPAQUE_ALPHA;
+use crate::decoration::{DecorationInstance, collect_decorations};
+use crate::emit::{BuildSink, emit_box_generic_clipped};
 use crate::face::Face;
+use crate::fluid::emit_flowing_fluid;
 use crate::mesh_build::MeshBuild;
 use crate::parity::ParityMesher;
-use crate::util::is_occluder;
+use crate::pool::{recycle_build, take_build};
+use crate::smooth::{normal_smoothing, smooth_builds};
+use crate::util::{is_full_cube, is_occluder, neighbor_block};
 
 thread_local! {
     static LAST_MESH_RESERVE: RefCell<Vec<usize>> = RefCell::new(Vec::new());
@@ -45,18 +52,15 @@ fn elapsed_ms(start: Instant) -> u32 {
 }
 
 fn prepare_builds(mat_count: usize) -> Vec<MeshBuild> {
-    let mut builds = vec![MeshBuild::default(); mat_count];
     LAST_MESH_RESERVE.with(|cell| {
         let mut caps = cell.borrow_mut();
         if caps.len() != mat_count {
             caps.resize(mat_count, 64);
         }
-        for i in 0..mat_count {
-            let reserve = caps[i].max(64);
-            builds[i].reserve_quads(reserve);
-        }
-    });
-    builds
+        caps.iter()
+            .map(|&reserve| take_build(reserve.max(64)))
+            .collect()
+    })
 }
 
 fn run_wcc_phase(
@@ -74,6 +78,9 @@ fn run_wcc_phase(
 
     let t_scan_start = Instant::now();
     pm.build_occupancy();
+    if crate::pockets::sealed_pocket_culling() {
+        pm.seal_hidden_pockets();
+    }
     let scan_ms = elapsed_ms(t_scan_start);
 
     let mut builds = prepare_builds(mat_count);
@@ -116,7 +123,7 @@ fn thin_dynamic_shapes(
             for x in 0..sx {
                 let here = buf.get_local(x, y, z);
                 if let Some(ty) = reg.get(here.id) {
-                    if ty.variant(here.state).occupancy.is_some() {
+                    if ty.variant(here.state).mesh_occupancy().is_some() {
                         continue;
                     }
                     let fx = (base_x + x as i32) as f32;
@@ -153,6 +160,12 @@ fn emit_thin_shape(
 ) {
     use geist_blocks::types::Shape;
 
+    if let Some(level) = ty.fluid_level(here.state) {
+        if level > 0 {
+            emit_flowing_fluid(builds, buf, reg, world, edits, here, ty, fx, fy, fz);
+        }
+        return;
+    }
     match &ty.shape {
         Shape::Pane => emit_pane(
             builds, buf, reg, world, edits, here, ty, fx, fy, fz, base_x, base_y, base_z, sx, sy,
@@ -185,10 +198,146 @@ fn emit_thin_shape(
             builds, buf, reg, world, edits, here, ty, fx, fy, fz, base_x, base_y, base_z, sx, sy,
             sz,
         ),
+        Shape::Boxes { .. } => emit_boxes(
+            builds, buf, reg, world, edits, here, ty, fx, fy, fz, base_x, base_y, base_z, sx, sy,
+            sz,
+        ),
+        Shape::Slope { .. } | Shape::SlopeCorner { .. } => {
+            if let Some(slope) = ty.variant(here.state).slope {
+                emit_slope(builds, buf, reg, world, edits, here, ty, slope, fx, fy, fz);
+            }
+        }
         _ => {}
     }
 }
 
+/// Emits a wedge: its flat base, the walls under its raised corners, and the sloped
+/// surface as two triangles folded along `SlopeShape`'s diagonal. Full walls are culled
+/// against occluding neighbors like cube faces; the sloped surface is always visible.
+#[allow(clippy::too_many_arguments)]
+fn emit_slope(
+    builds: &mut Vec<MeshBuild>,
+    buf: &ChunkBuf,
+    reg: &BlockRegistry,
+    world: Option<&World>,
+    edits: Option<&HashMap<(i32, i32, i32), Block>>,
+    here: Block,
+    ty: &BlockType,
+    slope: SlopeShape,
+    fx: f32,
+    fy: f32,
+    fz: f32,
+) {
+    let rgba = [LIGHT_FULL, LIGHT_FULL, LIGHT_FULL, OPAQUE_ALPHA];
+    let occluded = |face: Face| {
+        let (dx, dy, dz) = face.delta();
+        is_occluder(
+            buf,
+            world,
+            edits,
+            reg,
+            here,
+            face,
+            fx as i32 + dx,
+            fy as i32 + dy,
+            fz as i32 + dz,
+        )
+    };
+    // Base plane and the direction the wedge grows away from it.
+    let (base_y, grow) = if slope.top {
+        (fy + 1.0, -1.0)
+    } else {
+        (fy, 1.0)
+    };
+    let corner = |i: usize, raised: bool| {
+        let (cx, cz) = SLOPE_CORNERS[i];
+        let lift = if raised { slope.heights[i] as f32 } else { 0.0 };
+        Vec3::new(fx + cx, base_y + grow * lift, fz + cz)
+    };
+    let mut emit = |face_role: FaceRole, pts: &[Vec3], n: Vec3, uv: &dyn Fn(Vec3) -> (f32, f32)| {
+        let mid = ty.material_for_cached(face_role, here.state);
+        let mb = builds.get_build_mut(mid);
+        match *pts {
+            [a, b, c] => mb.add_triangle_uv(a, b, c, n, [uv(a), uv(b), uv(c)], rgba),
+            [a, b, c, d] => {
+                mb.add_quad_uv(a, b, c, d, n, [uv(a), uv(d), uv(c), uv(b)], false, rgba)
+            }
+            _ => {}
+        }
+    };
+
+    // Flat base: a full face on the floor (or ceiling for hanging wedges).
+    let base_face = if slope.top { Face::PosY } else { Face::NegY };
+    if !occluded(base_face) {
+        let pts = [
+            corner(0, false),
+            corner(1, false),
+            corner(3, false),
+            corner(2, false),
+        ];
+        emit(base_face.role(), &pts, base_face.normal(), &|p| (p.x, p.z));
+    }
+
+    // Walls: a quad under two raised corners, a triangle under one, nothing under none.
+    const WALLS: [(Face, usize, usize); 4] = [
+        (Face::PosX, 1, 3),
+        (Face::NegX, 0, 2),
+        (Face::PosZ, 2, 3),
+        (Face::NegZ, 0, 1),
+    ];
+    for (face, i, j) in WALLS {
+        let (hi, hj) = (slope.heights[i] != 0, slope.heights[j] != 0);
+        if !(hi || hj) || occluded(face) {
+            continue;
+        }
+        let uv: &dyn Fn(Vec3) -> (f32, f32) = match face {
+            Face::PosX | Face::NegX => &|p| (p.z, p.y),
+            _ => &|p| (p.x, p.y),
+        };
+        let (bi, bj) = (corner(i, false), corner(j, false));
+        match (hi, hj) {
+            (true, true) => emit(
+                FaceRole::Side,
+                &[bi, bj, corner(j, true), corner(i, true)],
+                face.normal(),
+                uv,
+            ),
+            (true, false) => emit(
+                FaceRole::Side,
+                &[bi, bj, corner(i, true)],
+                face.normal(),
+                uv,
+            ),
+            _ => emit(
+                FaceRole::Side,
+                &[bi, bj, corner(j, true)],
+                face.normal(),
+                uv,
+            ),
+        }
+    }
+
+    // Sloped surface, split along the fold so corner wedges stay exact.
+    let p: [Vec3; 4] = std::array::from_fn(|i| corner(i, true));
+    let tris = if slope.folds_on_main_diagonal() {
+        [[p[0], p[1], p[3]], [p[0], p[3], p[2]]]
+    } else {
+        [[p[0], p[1], p[2]], [p[1], p[3], p[2]]]
+    };
+    let surface_role = if slope.top {
+        FaceRole::Bottom
+    } else {
+        FaceRole::Top
+    };
+    for [a, b, c] in tris {
+        let mut n = (b - a).cross(c - a).normalized();
+        if n.y * grow < 0.0 {
+            n = n * -1.0;
+        }
+        emit(surface_role, &[a, b, c], n, &|q| (q.x, q.z));
+    }
+}
+
 fn emit_pane(
     builds: &mut Vec<MeshBuild>,
     buf: &ChunkBuf,
@@ -628,6 +777,85 @@ fn emit_ladder(
     );
 }
 
+/// Emits each config box the state shows. Connecting boxes need the neighbor on their side
+/// to be the same block or a full cube; box faces on the cell boundary cull like cube faces.
+#[allow(clippy::too_many_arguments)]
+fn emit_boxes(
+    builds: &mut Vec<MeshBuild>,
+    buf: &ChunkBuf,
+    reg: &BlockRegistry,
+    world: Option<&World>,
+    edits: Option<&HashMap<(i32, i32, i32), Block>>,
+    here: Block,
+    ty: &BlockType,
+    fx: f32,
+    fy: f32,
+    fz: f32,
+    base_x: i32,
+    base_y: i32,
+    base_z: i32,
+    sx: usize,
+    sy: usize,
+    sz: usize,
+) {
+    let (wx, wy, wz) = (fx as i32, fy as i32, fz as i32);
+    let face_material = |face: Face| ty.material_for_cached(face.role(), here.state);
+    for b in ty.shape_boxes(here.state) {
+        if let Some(side) = b.connect {
+            let (dx, dz) = side.offset();
+            let nb = neighbor_block(buf, world, edits, reg, wx + dx, wy, wz + dz);
+            if nb.id != here.id && !is_full_cube(reg, nb) {
+                continue;
+            }
+        }
+        let (lo, hi) = b.bounds();
+        let on_boundary = |face: Face| match face {
+            Face::PosY => hi[1] >= 1.0,
+            Face::NegY => lo[1] <= 0.0,
+            Face::PosX => hi[0] >= 1.0,
+            Face::NegX => lo[0] <= 0.0,
+            Face::PosZ => hi[2] >= 1.0,
+            Face::NegZ => lo[2] <= 0.0,
+        };
+        emit_box_generic_clipped(
+            builds,
+            Vec3 {
+                x: fx + lo[0],
+                y: fy + lo[1],
+                z: fz + lo[2],
+            },
+            Vec3 {
+                x: fx + hi[0],
+                y: fy + hi[1],
+                z: fz + hi[2],
+            },
+            &face_material,
+            |face| {
+                let (dx, dy, dz) = face.delta();
+                on_boundary(face)
+                    && is_occluder(
+                        buf,
+                        world,
+                        edits,
+                        reg,
+                        here,
+                        face,
+                        wx + dx,
+                        wy + dy,
+                        wz + dz,
+                    )
+            },
+            |_| LIGHT_FULL,
+            base_x,
+            sx,
+            sy,
+            base_y,
+            base_z,
+            sz,
+        );
+    }
+}
+
 fn emit_carpet(
     builds: &mut Vec<MeshBuild>,
     buf: &ChunkBuf,
@@ -718,6 +946,7 @@ fn finalize_chunk(
     sy: usize,
     sz: usize,
     coord: ChunkCoord,
+    decorations: Vec<DecorationInstance>,
 ) -> (ChunkMeshCPU, Option<LightBorders>) {
     let mat_count = builds.len();
     update_last_mesh_reserve(mat_count, &builds);
@@ -738,12 +967,22 @@ fn finalize_chunk(
     let non_empty = builds.iter().filter(|mb| !mb.pos.is_empty()).count();
     let mut parts: HashMap<MaterialId, MeshBuild> = HashMap::with_capacity(non_empty);
     for (i, mb) in builds.into_iter().enumerate() {
-        if !mb.pos.is_empty() {
+        if mb.pos.is_empty() {
+            recycle_build(mb);
+        } else {
             parts.insert(MaterialId(i as u16), mb);
         }
     }
 
-    (ChunkMeshCPU { coord, bbox, parts }, light_borders)
+    (
+        ChunkMeshCPU {
+            coord,
+            bbox,
+            parts,
+            decorations,
+        },
+        light_borders,
+    )
 }
 
 fn finalize_chunk_simple(
@@ -774,12 +1013,20 @@ fn finalize_chunk_simple(
     let non_empty = builds.iter().filter(|mb| !mb.pos.is_empty()).count();
     let mut parts: HashMap<MaterialId, MeshBuild> = HashMap::with_capacity(non_empty);
     for (i, mb) in builds.into_iter().enumerate() {
-        if !mb.pos.is_empty() {
+        if mb.pos.is_empty() {
+            recycle_build(mb);
+        } else {
             parts.insert(MaterialId(i as u16), mb);
         }
     }
 
-    ChunkMeshCPU { coord, bbox, parts }
+    // Structures draw no decorations yet; their tagged blocks simply stay unmeshed.
+    ChunkMeshCPU {
+        coord,
+        bbox,
+        parts,
+        decorations: Vec::new(),
+    }
 }
 
 pub fn build_structure_wcc_cpu_buf(
@@ -904,6 +1151,16 @@ pub fn build_chunk_wcc_cpu_buf_with_light(
         sz,
     );
 
+    if normal_smoothing() {
+        smooth_builds(&mut builds, reg, |wx, wy, wz| {
+            let b = buf
+                .get_world(wx, wy, wz)
+                .or_else(|| edits.and_then(|ed| ed.get(&(wx, wy, wz)).copied()))
+                .unwrap_or_else(|| world.block_at_runtime(reg, wx, wy, wz));
+            is_full_cube(reg, b)
+        });
+    }
+
     let total_ms = elapsed_ms(total_start);
     let perf = MesherPerf {
         scan_ms,
@@ -914,8 +1171,19 @@ pub fn build_chunk_wcc_cpu_buf_with_light(
     };
     log_mesher_perf(s, coord, &perf);
 
-    let (chunk, light_borders) =
-        finalize_chunk(builds, light, base_x, base_y, base_z, sx, sy, sz, coord);
+    let decorations = collect_decorations(buf, reg, Some(light));
+    let (chunk, light_borders) = finalize_chunk(
+        builds,
+        light,
+        base_x,
+        base_y,
+        base_z,
+        sx,
+        sy,
+        sz,
+        coord,
+        decorations,
+    );
 
     Some((chunk, light_borders))
 }
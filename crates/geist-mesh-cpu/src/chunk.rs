@@ -1,7 +1,10 @@
+use geist_blocks::MaterialCatalog;
+use geist_blocks::RenderPass;
 use geist_blocks::types::MaterialId;
 use geist_geom::Aabb;
 use hashbrown::HashMap;
 
+use crate::decoration::DecorationInstance;
 use crate::mesh_build::MeshBuild;
 use geist_world::ChunkCoord;
 
@@ -9,4 +12,20 @@ pub struct ChunkMeshCPU {
     pub coord: ChunkCoord,
     pub bbox: Aabb,
     pub parts: HashMap<MaterialId, MeshBuild>,
+    /// Blocks tagged `decoration`, drawn as instances rather than in `parts`.
+    pub decorations: Vec<DecorationInstance>,
+}
+
+impl ChunkMeshCPU {
+    /// The material parts drawn in `pass`.
+    pub fn parts_in<'a>(
+        &'a self,
+        pass: RenderPass,
+        mats: &'a MaterialCatalog,
+    ) -> impl Iterator<Item = (MaterialId, &'a MeshBuild)> + 'a {
+        self.parts
+            .iter()
+            .filter(move |(mid, _)| mats.render_pass(**mid) == pass)
+            .map(|(mid, mb)| (*mid, mb))
+    }
 }
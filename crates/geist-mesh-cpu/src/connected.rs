@@ -0,0 +1,115 @@
+//! Connected textures: faces of materials marked `connected` pick a tile from a 4x4 sheet
+//! by which of their four in-plane neighbors show the same material, so runs of glass or
+//! framed panels read as one pane instead of a grid of bordered blocks.
+use hashbrown::HashMap;
+use std::cell::RefCell;
+
+use geist_blocks::BlockRegistry;
+use geist_blocks::types::{Block, MaterialId};
+use geist_chunk::ChunkBuf;
+use geist_world::World;
+
+use crate::face::Face;
+use crate::util::neighbor_block;
+
+/// Tiles per row and column of a connected-texture sheet.
+pub const TILE_GRID: u8 = 4;
+
+/// A tile of a connected-texture sheet, indexed by neighbor mask: bit 0 set when the face
+/// continues toward -u (the tile's left edge), bit 1 toward +u (right), bit 2 toward +v
+/// (top) and bit 3 toward -v (bottom). Tile `mask` sits at column `mask % 4`, row `mask / 4`
+/// counted from the top of the sheet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Tile(pub u8);
+
+impl Tile {
+    /// The tile with no neighbors: bordered on all four sides.
+    pub const ISOLATED: Tile = Tile(0);
+
+    pub fn column(self) -> u8 {
+        self.0 % TILE_GRID
+    }
+
+    pub fn row(self) -> u8 {
+        self.0 / TILE_GRID
+    }
+}
+
+/// In-plane block steps toward -u, +u, +v and -v for each face, matching the (u, v) axes
+/// `MeshBuild::add_face_rect` derives UVs from: (x, z) on Y faces, (z, y) on X faces and
+/// (x, y) on Z faces.
+fn plane_steps(face: Face) -> [(i32, i32, i32); 4] {
+    match face {
+        Face::PosY | Face::NegY => [(-1, 0, 0), (1, 0, 0), (0, 0, 1), (0, 0, -1)],
+        Face::PosX | Face::NegX => [(0, 0, -1), (0, 0, 1), (0, 1, 0), (0, -1, 0)],
+        Face::PosZ | Face::NegZ => [(-1, 0, 0), (1, 0, 0), (0, 1, 0), (0, -1, 0)],
+    }
+}
+
+/// Picks tiles for one chunk's faces. Masks are cached per block face, since the greedy
+/// mesher may emit several quads for one face when its corners shade differently.
+pub(crate) struct ConnectedTiles<'a> {
+    reg: &'a BlockRegistry,
+    buf: &'a ChunkBuf,
+    world: Option<&'a World>,
+    edits: Option<&'a HashMap<(i32, i32, i32), Block>>,
+    cache: RefCell<HashMap<(i32, i32, i32, Face), Tile>>,
+}
+
+impl<'a> ConnectedTiles<'a> {
+    /// `None` when no material in the registry is connected, so chunks without any pay
+    /// nothing beyond one check per emitted quad.
+    pub(crate) fn new(
+        reg: &'a BlockRegistry,
+        buf: &'a ChunkBuf,
+        world: Option<&'a World>,
+        edits: Option<&'a HashMap<(i32, i32, i32), Block>>,
+    ) -> Option<Self> {
+        reg.materials
+            .materials
+            .iter()
+            .any(|m| m.connected)
+            .then(|| Self {
+                reg,
+                buf,
+                world,
+                edits,
+                cache: RefCell::new(HashMap::new()),
+            })
+    }
+
+    #[inline]
+    pub(crate) fn connected(&self, mid: MaterialId) -> bool {
+        self.reg.materials.connected(mid)
+    }
+
+    /// Tile for the `face` of the block at world `(x, y, z)`, drawn with `mid`.
+    pub(crate) fn tile(&self, mid: MaterialId, face: Face, x: i32, y: i32, z: i32) -> Tile {
+        *self
+            .cache
+            .borrow_mut()
+            .entry((x, y, z, face))
+            .or_insert_with(|| {
+                let mut mask = 0u8;
+                for (bit, (dx, dy, dz)) in plane_steps(face).into_iter().enumerate() {
+                    let nb = neighbor_block(
+                        self.buf,
+                        self.world,
+                        self.edits,
+                        self.reg,
+                        x + dx,
+                        y + dy,
+                        z + dz,
+                    );
+                    let same = self
+                        .reg
+                        .get(nb.id)
+                        .is_some_and(|ty| ty.material_for_cached(face.role(), nb.state) == mid);
+                    if same {
+                        mask |= 1 << bit;
+                    }
+                }
+                Tile(mask)
+            })
+    }
+}
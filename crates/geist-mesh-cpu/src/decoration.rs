@@ -0,0 +1,96 @@
+//! Decoration instances: blocks tagged `decoration` are not meshed with their chunk but
+//! listed here, one entry per cell, for the renderer to draw as instances of a shared mesh.
+
+use geist_blocks::BlockRegistry;
+use geist_blocks::types::Block;
+use geist_chunk::ChunkBuf;
+use geist_geom::Vec3;
+use geist_lighting::LightGrid;
+
+/// How far, in blocks, an instance may sit from its cell centre.
+pub const DECORATION_JITTER: f32 = 0.2;
+
+/// One decoration block placed in the world.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DecorationInstance {
+    pub block: Block,
+    /// World-space centre of the instance's footprint, on the floor of its cell.
+    pub pos: Vec3,
+    pub yaw_deg: f32,
+    pub scale: f32,
+    /// Stored skylight in the cell, before the day/night scale.
+    pub sky: u8,
+    /// Emitter and beacon light in the cell.
+    pub block_light: u8,
+}
+
+fn cell_hash(x: i32, y: i32, z: i32) -> u32 {
+    let mix = |mut v: u32| {
+        v ^= v >> 16;
+        v = v.wrapping_mul(0x7feb_352d);
+        v ^= v >> 15;
+        v = v.wrapping_mul(0x846c_a68b);
+        v ^= v >> 16;
+        v
+    };
+    let mut h = 0x9e37_79b9u32;
+    h ^= mix(x as u32);
+    h ^= mix((y as u32).wrapping_add(0x85eb_ca6b));
+    h ^= mix((z as u32).wrapping_add(0xc2b2_ae35));
+    mix(h)
+}
+
+impl DecorationInstance {
+    /// Instance of `block` at world cell `(x, y, z)`. Offset, yaw and scale vary per cell
+    /// so a field of the same plant does not line up, but stay put across rebuilds.
+    pub fn at_cell(block: Block, x: i32, y: i32, z: i32, sky: u8, block_light: u8) -> Self {
+        let h = cell_hash(x, y, z);
+        let unit = |bits: u32| (bits & 0xff) as f32 / 255.0;
+        let ox = (unit(h) * 2.0 - 1.0) * DECORATION_JITTER;
+        let oz = (unit(h >> 8) * 2.0 - 1.0) * DECORATION_JITTER;
+        DecorationInstance {
+            block,
+            pos: Vec3::new(x as f32 + 0.5 + ox, y as f32, z as f32 + 0.5 + oz),
+            yaw_deg: unit(h >> 16) * 360.0,
+            scale: 0.8 + unit(h >> 24) * 0.3,
+            sky,
+            block_light,
+        }
+    }
+}
+
+/// Decoration instances for every tagged block in `buf`, lit from `light` when given
+/// (fully skylit otherwise).
+pub(crate) fn collect_decorations(
+    buf: &ChunkBuf,
+    reg: &BlockRegistry,
+    light: Option<&LightGrid>,
+) -> Vec<DecorationInstance> {
+    let base_x = buf.coord.cx * buf.sx as i32;
+    let base_y = buf.coord.cy * buf.sy as i32;
+    let base_z = buf.coord.cz * buf.sz as i32;
+    let mut out = Vec::new();
+    for y in 0..buf.sy {
+        for z in 0..buf.sz {
+            for x in 0..buf.sx {
+                let b = buf.get_local(x, y, z);
+                if b.id == 0 || !reg.get(b.id).is_some_and(|ty| ty.is_decoration()) {
+                    continue;
+                }
+                let (sky, block_light) = match light {
+                    Some(lg) => (lg.skylight_at(x, y, z), lg.block_light_at(x, y, z)),
+                    None => (u8::MAX, 0),
+                };
+                out.push(DecorationInstance::at_cell(
+                    b,
+                    base_x + x as i32,
+                    base_y + y as i32,
+                    base_z + z as i32,
+                    sky,
+                    block_light,
+                ));
+            }
+        }
+    }
+    out
+}
@@ -3,6 +3,8 @@ use hashbrown::HashMap;
 use geist_blocks::types::MaterialId;
 use geist_geom::Vec3;
 
+use crate::ao::shade_rgba;
+use crate::connected::Tile;
 use crate::constants::OPAQUE_ALPHA;
 use crate::face::Face;
 use crate::mesh_build::MeshBuild;
@@ -35,6 +37,7 @@ impl BuildSink for Vec<MeshBuild> {
 
 #[inline]
 /// Emits a face-aligned rectangle into the material's mesh build.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn emit_face_rect_for(
     builds: &mut impl BuildSink,
     mid: MaterialId,
@@ -43,13 +46,21 @@ pub(crate) fn emit_face_rect_for(
     u1: f32,
     v1: f32,
     rgba: [u8; 4],
+    tile: Option<Tile>,
 ) {
     let mb = builds.get_build_mut(mid);
-    mb.add_face_rect(face, origin, u1, v1, false, rgba);
+    match tile {
+        Some(tile) => mb.add_face_rect_tiled(face, origin, u1, v1, tile, rgba),
+        None => mb.add_face_rect(face, origin, u1, v1, false, rgba),
+    }
 }
 
 /// Clips a face-aligned rectangle to the current chunk interior and emits any visible portion.
 /// Chunk interior bounds: X in [base_x, base_x+sx), Z in [base_z, base_z+sz), Y in [base_y, base_y+sy).
+/// `ao` shades the corners `(u0,v0), (u1,v0), (u0,v1), (u1,v1)` in the plane emitters'
+/// (u, v) order: (z, y) for X faces, (x, z) for Y faces and (x, y) for Z faces. `tile`
+/// selects a connected-texture tile for a rectangle within one block. Returns whether any
+/// of the rectangle was emitted.
 #[inline]
 pub(crate) fn emit_face_rect_for_clipped(
     builds: &mut impl BuildSink,
@@ -59,13 +70,15 @@ pub(crate) fn emit_face_rect_for_clipped(
     u1: f32,
     v1: f32,
     rgba: [u8; 4],
+    ao: [u8; 4],
+    tile: Option<Tile>,
     base_x: i32,
     sx: usize,
     sy: usize,
     base_y: i32,
     base_z: i32,
     sz: usize,
-) {
+) -> bool {
     #[inline]
     fn clip_span(start: f32, len: f32, lo: f32, hi: f32) -> Option<(f32, f32)> {
         let s0 = start.max(lo);
@@ -134,8 +147,26 @@ pub(crate) fn emit_face_rect_for_clipped(
         }
     }
     if let Some((o, cu, cv)) = out {
-        emit_face_rect_for(builds, mid, face, o, cu, cv, rgba);
+        emit_face_rect_for(builds, mid, face, o, cu, cv, rgba, tile);
+        if ao != [255; 4] {
+            let plane_uv = |p: &[f32]| match face {
+                Face::PosX | Face::NegX => (p[2], p[1]),
+                Face::PosY | Face::NegY => (p[0], p[2]),
+                Face::PosZ | Face::NegZ => (p[0], p[1]),
+            };
+            let (ou, ov) = plane_uv(&[o.x, o.y, o.z]);
+            let mb = builds.get_build_mut(mid);
+            let first = mb.pos.len() / 3 - 4;
+            for vi in first..first + 4 {
+                let (pu, pv) = plane_uv(&mb.pos[vi * 3..vi * 3 + 3]);
+                let corner = (pu > ou + cu * 0.5) as usize | ((pv > ov + cv * 0.5) as usize) << 1;
+                let col = &mut mb.col[vi * 4..vi * 4 + 4];
+                let shaded = shade_rgba([col[0], col[1], col[2], col[3]], ao[corner]);
+                col.copy_from_slice(&shaded);
+            }
+        }
     }
+    out.is_some()
 }
 
 #[inline]
@@ -0,0 +1,442 @@
+//! Writes meshed chunks to interchange formats (Wavefront OBJ, glTF 2.0) for use in
+//! external tools. Parts of the same material are merged across chunks.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use geist_blocks::MaterialCatalog;
+use geist_blocks::RenderPass;
+use geist_blocks::types::MaterialId;
+
+use crate::chunk::ChunkMeshCPU;
+use crate::mesh_build::MeshBuild;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// `.gltf` JSON with a `.bin` buffer beside it.
+    Gltf,
+    /// `.obj` with an `.mtl` beside it.
+    Obj,
+}
+
+impl ExportFormat {
+    /// The format implied by a file extension, if it is one of ours.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "gltf" => Some(ExportFormat::Gltf),
+            "obj" => Some(ExportFormat::Obj),
+            _ => None,
+        }
+    }
+}
+
+/// Geometry of one material merged over every added chunk. Indices are 32-bit since a
+/// merged part easily outgrows a chunk's `u16` range.
+#[derive(Default)]
+pub struct ExportPart {
+    pub pos: Vec<f32>,
+    pub norm: Vec<f32>,
+    pub uv: Vec<f32>,
+    pub col: Vec<u8>,
+    pub idx: Vec<u32>,
+}
+
+impl ExportPart {
+    pub fn vertex_count(&self) -> usize {
+        self.pos.len() / 3
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.idx.len() / 3
+    }
+
+    fn append(&mut self, mb: &MeshBuild) {
+        let base = self.vertex_count() as u32;
+        let verts = mb.pos.len() / 3;
+        self.pos.extend_from_slice(&mb.pos);
+        self.norm.extend_from_slice(&mb.norm);
+        self.uv.extend_from_slice(&mb.uv);
+        if mb.col.len() == verts * 4 {
+            self.col.extend_from_slice(&mb.col);
+        } else {
+            self.col.resize(self.col.len() + verts * 4, 255);
+        }
+        if mb.idx.is_empty() {
+            self.idx.extend(base..base + verts as u32);
+        } else {
+            self.idx.extend(mb.idx.iter().map(|&i| base + i as u32));
+        }
+    }
+
+    fn bounds(&self) -> ([f32; 3], [f32; 3]) {
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        for p in self.pos.chunks_exact(3) {
+            for a in 0..3 {
+                min[a] = min[a].min(p[a]);
+                max[a] = max[a].max(p[a]);
+            }
+        }
+        (min, max)
+    }
+}
+
+/// Chunk meshes collected for export, keyed by material.
+#[derive(Default)]
+pub struct MeshExport {
+    parts: BTreeMap<u16, ExportPart>,
+}
+
+impl MeshExport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_chunk(&mut self, cpu: &ChunkMeshCPU) {
+        for (mid, mb) in &cpu.parts {
+            if mb.pos.is_empty() {
+                continue;
+            }
+            self.parts.entry(mid.0).or_default().append(mb);
+        }
+    }
+
+    /// Merged parts in material id order.
+    pub fn parts(&self) -> impl Iterator<Item = (MaterialId, &ExportPart)> {
+        self.parts.iter().map(|(id, p)| (MaterialId(*id), p))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.parts.values().map(ExportPart::vertex_count).sum()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.parts.values().map(ExportPart::triangle_count).sum()
+    }
+
+    /// Write `path` in `format`, plus its companion file. Returns every file written.
+    pub fn write(
+        &self,
+        path: &Path,
+        format: ExportFormat,
+        mats: &MaterialCatalog,
+    ) -> io::Result<Vec<PathBuf>> {
+        match format {
+            ExportFormat::Gltf => self.write_gltf(path, mats),
+            ExportFormat::Obj => self.write_obj(path, mats),
+        }
+    }
+
+    /// Wavefront OBJ with an `.mtl` of the same stem. Vertex colors (light and AO) have no
+    /// standard OBJ form and are left out.
+    pub fn write_obj(&self, path: &Path, mats: &MaterialCatalog) -> io::Result<Vec<PathBuf>> {
+        let mtl_path = path.with_extension("mtl");
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let mut obj = String::new();
+        let mut mtl = String::new();
+        let _ = writeln!(obj, "# geist world export");
+        let _ = writeln!(obj, "mtllib {}", file_name(&mtl_path));
+        let mut base = 1usize;
+        for (mid, part) in self.parts() {
+            let name = material_name(mid, mats);
+            let _ = writeln!(mtl, "newmtl {}", name);
+            let _ = writeln!(mtl, "Kd 1.000 1.000 1.000");
+            if let Some(tex) = texture_of(mid, mats) {
+                let _ = writeln!(mtl, "map_Kd {}", relative_ref(&tex, dir));
+            }
+            if mats.render_pass(mid) == RenderPass::Translucent {
+                let _ = writeln!(mtl, "d 0.8");
+            }
+            mtl.push('\n');
+
+            let _ = writeln!(obj, "o {}", name);
+            for p in part.pos.chunks_exact(3) {
+                let _ = writeln!(obj, "v {} {} {}", p[0], p[1], p[2]);
+            }
+            // OBJ puts v = 0 at the bottom of the image; the mesher at the top.
+            for t in part.uv.chunks_exact(2) {
+                let _ = writeln!(obj, "vt {} {}", t[0], 1.0 - t[1]);
+            }
+            for n in part.norm.chunks_exact(3) {
+                let _ = writeln!(obj, "vn {} {} {}", n[0], n[1], n[2]);
+            }
+            let _ = writeln!(obj, "usemtl {}", name);
+            for tri in part.idx.chunks_exact(3) {
+                let (a, b, c) = (
+                    base + tri[0] as usize,
+                    base + tri[1] as usize,
+                    base + tri[2] as usize,
+                );
+                let _ = writeln!(obj, "f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c}");
+            }
+            base += part.vertex_count();
+        }
+        std::fs::write(path, obj)?;
+        std::fs::write(&mtl_path, mtl)?;
+        Ok(vec![path.to_path_buf(), mtl_path])
+    }
+
+    /// glTF 2.0 with one mesh holding a primitive per material and a `.bin` buffer of the
+    /// same stem. Vertex colors go out as `COLOR_0`.
+    pub fn write_gltf(&self, path: &Path, mats: &MaterialCatalog) -> io::Result<Vec<PathBuf>> {
+        let bin_path = path.with_extension("bin");
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let mut bin: Vec<u8> = Vec::new();
+        let mut views: Vec<String> = Vec::new();
+        let mut accessors: Vec<String> = Vec::new();
+        let mut primitives: Vec<String> = Vec::new();
+        let mut materials: Vec<String> = Vec::new();
+        let mut images: Vec<String> = Vec::new();
+        let mut textures: Vec<String> = Vec::new();
+
+        // Append a 4-byte aligned view and its accessor; returns the accessor index.
+        let mut push = |bin: &mut Vec<u8>, bytes: &[u8], target: u32, accessor: String| {
+            while !bin.len().is_multiple_of(4) {
+                bin.push(0);
+            }
+            views.push(format!(
+                "{{\"buffer\":0,\"byteOffset\":{},\"byteLength\":{},\"target\":{}}}",
+                bin.len(),
+                bytes.len(),
+                target
+            ));
+            bin.extend_from_slice(bytes);
+            accessors.push(accessor.replace("{view}", &(views.len() - 1).to_string()));
+            accessors.len() - 1
+        };
+
+        for (mid, part) in self.parts() {
+            let count = part.vertex_count();
+            let (min, max) = part.bounds();
+            let pos = push(
+                &mut bin,
+                &f32_bytes(&part.pos),
+                ARRAY_BUFFER,
+                format!(
+                    "{{\"bufferView\":{{view}},\"componentType\":5126,\"count\":{},\"type\":\"VEC3\",\"min\":[{},{},{}],\"max\":[{},{},{}]}}",
+                    count, min[0], min[1], min[2], max[0], max[1], max[2]
+                ),
+            );
+            let norm = push(
+                &mut bin,
+                &f32_bytes(&part.norm),
+                ARRAY_BUFFER,
+                vec_accessor(5126, count, "VEC3", false),
+            );
+            let uv = push(
+                &mut bin,
+                &f32_bytes(&part.uv),
+                ARRAY_BUFFER,
+                vec_accessor(5126, count, "VEC2", false),
+            );
+            let col = push(
+                &mut bin,
+                &part.col,
+                ARRAY_BUFFER,
+                vec_accessor(5121, count, "VEC4", true),
+            );
+            let idx_bytes: Vec<u8> = part.idx.iter().flat_map(|i| i.to_le_bytes()).collect();
+            let idx = push(
+                &mut bin,
+                &idx_bytes,
+                ELEMENT_ARRAY_BUFFER,
+                vec_accessor(5125, part.idx.len(), "SCALAR", false),
+            );
+
+            let name = json_str(&material_name(mid, mats));
+            let blend = if mats.render_pass(mid) == RenderPass::Translucent {
+                ",\"alphaMode\":\"BLEND\""
+            } else {
+                ""
+            };
+            let pbr = match texture_of(mid, mats) {
+                Some(tex) => {
+                    images.push(format!(
+                        "{{\"uri\":{}}}",
+                        json_str(&uri_encode(&relative_ref(&tex, dir)))
+                    ));
+                    textures.push(format!("{{\"sampler\":0,\"source\":{}}}", images.len() - 1));
+                    format!(
+                        "{{\"baseColorTexture\":{{\"index\":{}}},\"metallicFactor\":0,\"roughnessFactor\":1}}",
+                        textures.len() - 1
+                    )
+                }
+                None => "{\"metallicFactor\":0,\"roughnessFactor\":1}".to_string(),
+            };
+            materials.push(format!(
+                "{{\"name\":{},\"pbrMetallicRoughness\":{}{}}}",
+                name, pbr, blend
+            ));
+            primitives.push(format!(
+                "{{\"attributes\":{{\"POSITION\":{},\"NORMAL\":{},\"TEXCOORD_0\":{},\"COLOR_0\":{}}},\"indices\":{},\"material\":{},\"mode\":4}}",
+                pos,
+                norm,
+                uv,
+                col,
+                idx,
+                materials.len() - 1
+            ));
+        }
+
+        let mut json = String::new();
+        json.push_str("{\"asset\":{\"version\":\"2.0\",\"generator\":\"geist\"}");
+        json.push_str(",\"scene\":0,\"scenes\":[{\"nodes\":[0]}]");
+        if primitives.is_empty() {
+            json.push_str(",\"nodes\":[{\"name\":\"world\"}]");
+        } else {
+            json.push_str(",\"nodes\":[{\"name\":\"world\",\"mesh\":0}]");
+            let _ = write!(
+                json,
+                ",\"meshes\":[{{\"name\":\"world\",\"primitives\":[{}]}}]",
+                primitives.join(",")
+            );
+            let _ = write!(json, ",\"materials\":[{}]", materials.join(","));
+            let _ = write!(json, ",\"accessors\":[{}]", accessors.join(","));
+            let _ = write!(json, ",\"bufferViews\":[{}]", views.join(","));
+            let _ = write!(
+                json,
+                ",\"buffers\":[{{\"uri\":{},\"byteLength\":{}}}]",
+                json_str(&uri_encode(&file_name(&bin_path))),
+                bin.len()
+            );
+        }
+        if !images.is_empty() {
+            // Pixel-art textures: nearest filtering, repeated across merged faces.
+            json.push_str(
+                ",\"samplers\":[{\"magFilter\":9728,\"minFilter\":9728,\"wrapS\":10497,\"wrapT\":10497}]",
+            );
+            let _ = write!(json, ",\"images\":[{}]", images.join(","));
+            let _ = write!(json, ",\"textures\":[{}]", textures.join(","));
+        }
+        json.push_str("}\n");
+
+        std::fs::write(path, json)?;
+        if primitives.is_empty() {
+            return Ok(vec![path.to_path_buf()]);
+        }
+        std::fs::write(&bin_path, bin)?;
+        Ok(vec![path.to_path_buf(), bin_path])
+    }
+}
+
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+fn vec_accessor(component: u32, count: usize, ty: &str, normalized: bool) -> String {
+    let norm = if normalized {
+        ",\"normalized\":true"
+    } else {
+        ""
+    };
+    format!(
+        "{{\"bufferView\":{{view}},\"componentType\":{},\"count\":{},\"type\":\"{}\"{}}}",
+        component, count, ty, norm
+    )
+}
+
+fn f32_bytes(v: &[f32]) -> Vec<u8> {
+    v.iter().flat_map(|f| f.to_le_bytes()).collect()
+}
+
+fn material_name(mid: MaterialId, mats: &MaterialCatalog) -> String {
+    match mats.get(mid) {
+        Some(m) if !m.key.is_empty() => m.key.replace(char::is_whitespace, "_"),
+        _ => format!("material_{}", mid.0),
+    }
+}
+
+// The first texture candidate on disk, else the first listed.
+fn texture_of(mid: MaterialId, mats: &MaterialCatalog) -> Option<PathBuf> {
+    let m = mats.get(mid)?;
+    m.texture_candidates
+        .iter()
+        .find(|p| p.exists())
+        .or_else(|| m.texture_candidates.first())
+        .cloned()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// `target` as referenced from a file in `dir`: relative when both resolve on disk, else
+/// as given. Separators are always `/`.
+fn relative_ref(target: &Path, dir: &Path) -> String {
+    let abs = |p: &Path| {
+        let p = if p.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            p
+        };
+        std::fs::canonicalize(p).ok()
+    };
+    let rel = match (abs(target), abs(dir)) {
+        (Some(t), Some(d)) => relative_path(&t, &d),
+        _ => None,
+    };
+    rel.unwrap_or_else(|| target.to_path_buf())
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn relative_path(target: &Path, dir: &Path) -> Option<PathBuf> {
+    let t: Vec<_> = target.components().collect();
+    let d: Vec<_> = dir.components().collect();
+    let common = t.iter().zip(&d).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        return None;
+    }
+    let mut out = PathBuf::new();
+    for _ in common..d.len() {
+        out.push("..");
+    }
+    for c in &t[common..] {
+        out.push(c);
+    }
+    Some(out)
+}
+
+fn json_str(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// glTF URIs are RFC 3986 references; keep path characters and escape the rest.
+fn uri_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                out.push(b as char)
+            }
+            _ => {
+                let _ = write!(out, "%{:02X}", b);
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,181 @@
+//! Sloped surfaces for flowing fluids.
+//!
+//! Source blocks (level 0) are full cubes meshed by the parity pass. A flowing block is
+//! meshed here instead, as a box whose top corners sit at the average fill height of the
+//! fluid columns sharing each corner. Neighbouring flowing blocks compute the same height
+//! for a shared corner, so their surfaces join without gaps and slope down the flow.
+
+use hashbrown::HashMap;
+
+use geist_blocks::registry::BlockType;
+use geist_blocks::slope::SLOPE_CORNERS;
+use geist_blocks::types::{Block, FaceRole};
+use geist_blocks::{BlockRegistry, FLUID_MAX_LEVEL};
+use geist_chunk::ChunkBuf;
+use geist_geom::Vec3;
+use geist_world::World;
+
+use crate::constants::OPAQUE_ALPHA;
+use crate::emit::BuildSink;
+use crate::face::Face;
+use crate::mesh_build::MeshBuild;
+use crate::util::occludes_face;
+
+const LIGHT_FULL: u8 = 255;
+
+fn block_at(
+    buf: &ChunkBuf,
+    world: Option<&World>,
+    edits: Option<&HashMap<(i32, i32, i32), Block>>,
+    reg: &BlockRegistry,
+    wx: i32,
+    wy: i32,
+    wz: i32,
+) -> Block {
+    if let Some(b) = buf.get_world(wx, wy, wz) {
+        return b;
+    }
+    if let Some(b) = edits.and_then(|es| es.get(&(wx, wy, wz))) {
+        return *b;
+    }
+    match world {
+        Some(world) => world.block_at_runtime(reg, wx, wy, wz),
+        None => Block::AIR,
+    }
+}
+
+/// Surface height of a fluid block with nothing of the same fluid above it.
+#[inline]
+fn fluid_height(level: u8) -> f32 {
+    1.0 - f32::from(level.min(FLUID_MAX_LEVEL)) / f32::from(FLUID_MAX_LEVEL + 1)
+}
+
+/// Emits the flowing fluid block `here` at world voxel `(fx, fy, fz)`: the sloped top,
+/// walls down to the floor where no fluid or occluder covers them, and the bottom.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn emit_flowing_fluid(
+    builds: &mut Vec<MeshBuild>,
+    buf: &ChunkBuf,
+    reg: &BlockRegistry,
+    world: Option<&World>,
+    edits: Option<&HashMap<(i32, i32, i32), Block>>,
+    here: Block,
+    ty: &BlockType,
+    fx: f32,
+    fy: f32,
+    fz: f32,
+) {
+    let (x, y, z) = (fx as i32, fy as i32, fz as i32);
+    let at = |dx: i32, dy: i32, dz: i32| block_at(buf, world, edits, reg, x + dx, y + dy, z + dz);
+    let same_fluid = |b: Block| b.id == here.id;
+    // Fill of the column at a horizontal offset; `None` where there is no fluid.
+    let fill = |dx: i32, dz: i32| {
+        let b = at(dx, 0, dz);
+        if !same_fluid(b) {
+            return None;
+        }
+        if same_fluid(at(dx, 1, dz)) {
+            return Some(1.0);
+        }
+        Some(fluid_height(ty.fluid_level(b.state).unwrap_or(0)))
+    };
+    let heights: [f32; 4] = std::array::from_fn(|i| {
+        let (cx, cz) = SLOPE_CORNERS[i];
+        let (cx, cz) = (cx as i32, cz as i32);
+        let mut sum = 0.0;
+        let mut n = 0.0;
+        for dx in cx - 1..=cx {
+            for dz in cz - 1..=cz {
+                if let Some(h) = fill(dx, dz) {
+                    if h >= 1.0 {
+                        return 1.0;
+                    }
+                    sum += h;
+                    n += 1.0;
+                }
+            }
+        }
+        sum / n
+    });
+    let corner = |i: usize, top: bool| {
+        let (cx, cz) = SLOPE_CORNERS[i];
+        let lift = if top { heights[i] } else { 0.0 };
+        Vec3::new(fx + cx, fy + lift, fz + cz)
+    };
+    let rgba = [LIGHT_FULL, LIGHT_FULL, LIGHT_FULL, OPAQUE_ALPHA];
+    let covered = |face: Face| {
+        let (dx, dy, dz) = face.delta();
+        let nb = at(dx, dy, dz);
+        same_fluid(nb) || occludes_face(nb, face, reg)
+    };
+
+    if !same_fluid(at(0, 1, 0)) {
+        let p: [Vec3; 4] = std::array::from_fn(|i| corner(i, true));
+        let mid = ty.material_for_cached(FaceRole::Top, here.state);
+        let mb = builds.get_build_mut(mid);
+        for [a, b, c] in [[p[0], p[1], p[3]], [p[0], p[3], p[2]]] {
+            let mut n = (b - a).cross(c - a).normalized();
+            if n.y < 0.0 {
+                n = n * -1.0;
+            }
+            mb.add_triangle_uv(a, b, c, n, [(a.x, a.z), (b.x, b.z), (c.x, c.z)], rgba);
+        }
+    }
+    if !covered(Face::NegY) {
+        let pts = [
+            corner(0, false),
+            corner(1, false),
+            corner(3, false),
+            corner(2, false),
+        ];
+        let mid = ty.material_for_cached(FaceRole::Bottom, here.state);
+        let uvs = [
+            (pts[0].x, pts[0].z),
+            (pts[3].x, pts[3].z),
+            (pts[2].x, pts[2].z),
+            (pts[1].x, pts[1].z),
+        ];
+        builds.get_build_mut(mid).add_quad_uv(
+            pts[0],
+            pts[1],
+            pts[2],
+            pts[3],
+            Face::NegY.normal(),
+            uvs,
+            false,
+            rgba,
+        );
+    }
+    const WALLS: [(Face, usize, usize); 4] = [
+        (Face::PosX, 1, 3),
+        (Face::NegX, 0, 2),
+        (Face::PosZ, 2, 3),
+        (Face::NegZ, 0, 1),
+    ];
+    for (face, i, j) in WALLS {
+        if covered(face) {
+            continue;
+        }
+        let pts = [
+            corner(i, false),
+            corner(j, false),
+            corner(j, true),
+            corner(i, true),
+        ];
+        let uv = |p: Vec3| match face {
+            Face::PosX | Face::NegX => (p.z, p.y),
+            _ => (p.x, p.y),
+        };
+        let mid = ty.material_for_cached(FaceRole::Side, here.state);
+        builds.get_build_mut(mid).add_quad_uv(
+            pts[0],
+            pts[1],
+            pts[2],
+            pts[3],
+            face.normal(),
+            [uv(pts[0]), uv(pts[3]), uv(pts[2]), uv(pts[1])],
+            false,
+            rgba,
+        );
+    }
+}
@@ -3,22 +3,39 @@
 
 pub mod microgrid_tables;
 
+mod ao;
 mod build;
 mod chunk;
+mod connected;
 mod constants;
+mod decoration;
 mod emit;
+mod export;
 mod face;
+mod fluid;
 mod mesh_build;
 mod neighbors;
 mod parity;
+mod pockets;
+mod pool;
+mod shore;
+mod smooth;
 mod util;
 
+pub use ao::{DEFAULT_AO_STRENGTH, ao_strength, set_ao_strength};
 pub use build::{
     build_chunk_wcc_cpu_buf, build_chunk_wcc_cpu_buf_with_light, build_structure_wcc_cpu_buf,
 };
 pub use chunk::ChunkMeshCPU;
+pub use connected::{TILE_GRID, Tile};
+pub use decoration::{DECORATION_JITTER, DecorationInstance};
+pub use export::{ExportFormat, ExportPart, MeshExport};
 pub use face::{Face, SIDE_NEIGHBORS};
 pub use mesh_build::MeshBuild;
 pub use neighbors::NeighborsLoaded;
 pub use parity::ParityMesher;
+pub use pockets::{sealed_pocket_culling, set_sealed_pocket_culling};
+pub use pool::{MeshPoolStats, mesh_pool_stats, recycle_build, recycle_chunk, take_build};
+pub use shore::WATER_OPEN_CAP;
+pub use smooth::{normal_smoothing, set_normal_smoothing};
 pub use util::is_full_cube;
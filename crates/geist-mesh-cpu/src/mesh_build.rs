@@ -1,5 +1,6 @@
 use geist_geom::Vec3;
 
+use crate::connected::{TILE_GRID, Tile};
 use crate::face::Face;
 
 #[derive(Default, Clone)]
@@ -104,6 +105,32 @@ impl MeshBuild {
         ]);
     }
 
+    /// Appends a single triangle with explicit UVs, wound counter-clockwise around `n`.
+    pub fn add_triangle_uv(
+        &mut self,
+        a: Vec3,
+        b: Vec3,
+        c: Vec3,
+        n: Vec3,
+        mut uvs: [(f32, f32); 3],
+        rgba: [u8; 4],
+    ) {
+        let base = self.pos.len() as u32 / 3;
+        let mut vs = [a, b, c];
+        if (vs[1] - vs[0]).cross(vs[2] - vs[0]).dot(n) < 0.0 {
+            vs.swap(1, 2);
+            uvs.swap(1, 2);
+        }
+        for i in 0..3 {
+            self.pos.extend_from_slice(&[vs[i].x, vs[i].y, vs[i].z]);
+            self.norm.extend_from_slice(&[n.x, n.y, n.z]);
+            self.uv.extend_from_slice(&[uvs[i].0, -uvs[i].1]);
+            self.col.extend_from_slice(&rgba);
+        }
+        self.idx
+            .extend_from_slice(&[base as u16, (base + 1) as u16, (base + 2) as u16]);
+    }
+
     /// Emits a face-aligned rectangle for the given face at `origin` with size `(u1,v1)`.
     pub fn add_face_rect(
         &mut self,
@@ -235,6 +262,59 @@ impl MeshBuild {
         self.add_quad_uv(a, b, c, d, n, uvs, flip_v, rgba);
     }
 
+    /// Like `add_face_rect`, but maps the rectangle into `tile` of a connected-texture sheet
+    /// instead of repeating the texture in world space. The rectangle must lie within one
+    /// block's face, as the tile covers exactly one block.
+    pub fn add_face_rect_tiled(
+        &mut self,
+        face: Face,
+        origin: Vec3,
+        u1: f32,
+        v1: f32,
+        tile: Tile,
+        rgba: [u8; 4],
+    ) {
+        self.add_face_rect(face, origin, u1, v1, false, rgba);
+        let plane_uv = |p: &[f32]| match face {
+            Face::PosY | Face::NegY => (p[0], p[2]),
+            Face::PosX | Face::NegX => (p[2], p[1]),
+            Face::PosZ | Face::NegZ => (p[0], p[1]),
+        };
+        let (ou, ov) = plane_uv(&[origin.x, origin.y, origin.z]);
+        let (cell_u, cell_v) = (ou.floor(), ov.floor());
+        let grid = TILE_GRID as f32;
+        let (col, row) = (tile.column() as f32, tile.row() as f32);
+        let first = self.pos.len() / 3 - 4;
+        for vi in first..first + 4 {
+            let (pu, pv) = plane_uv(&self.pos[vi * 3..vi * 3 + 3]);
+            // Sheet rows count down from the top while v counts up the face.
+            self.uv[vi * 2] = (col + (pu - cell_u)) / grid;
+            self.uv[vi * 2 + 1] = (row + 1.0 - (pv - cell_v)) / grid;
+        }
+    }
+
+    /// Sets the vertex alpha of the last quad added for `face`, per corner in the plane
+    /// emitters' order `(u0,v0), (u1,v0), (u0,v1), (u1,v1)`.
+    pub fn set_last_quad_alpha(&mut self, face: Face, alpha: [u8; 4]) {
+        let plane_uv = |p: &[f32]| match face {
+            Face::PosY | Face::NegY => (p[0], p[2]),
+            Face::PosX | Face::NegX => (p[2], p[1]),
+            Face::PosZ | Face::NegZ => (p[0], p[1]),
+        };
+        let first = self.pos.len() / 3 - 4;
+        let (mut cu, mut cv) = (0.0, 0.0);
+        for vi in first..first + 4 {
+            let (pu, pv) = plane_uv(&self.pos[vi * 3..vi * 3 + 3]);
+            cu += pu * 0.25;
+            cv += pv * 0.25;
+        }
+        for vi in first..first + 4 {
+            let (pu, pv) = plane_uv(&self.pos[vi * 3..vi * 3 + 3]);
+            let corner = (pu > cu) as usize | ((pv > cv) as usize) << 1;
+            self.col[vi * 4 + 3] = alpha[corner];
+        }
+    }
+
     /// Returns a slice of interleaved vertex positions (x,y,z per vertex).
     pub fn positions(&self) -> &[f32] {
         &self.pos
@@ -8,9 +8,12 @@ use geist_chunk::ChunkBuf;
 use geist_geom::Vec3;
 use geist_world::World;
 
+use crate::ao::{ao_strength_u8, corner_shade};
+use crate::connected::ConnectedTiles;
 use crate::constants::{BITS_PER_WORD, OPAQUE_ALPHA, WORD_INDEX_MASK, WORD_INDEX_SHIFT};
 use crate::emit::emit_face_rect_for_clipped;
 use crate::face::Face;
+use crate::shore::WaterShore;
 
 // Local small bitset type
 #[derive(Default)]
@@ -157,6 +160,74 @@ impl OccGrids {
         let i = self.idx(ix, iy, iz);
         self.occ.set(i, v);
     }
+    /// Occupancy of a micro cell up to one step outside the chunk on a single axis, where
+    /// the seam layers answer; cells past an edge or corner of the chunk read as empty.
+    fn occ_at(&self, ix: i32, iy: i32, iz: i32) -> bool {
+        let (nx, ny, nz) = (self.nx as i32, self.ny as i32, self.nz as i32);
+        let out = |c: i32, n: i32| c < 0 || c >= n;
+        match (out(ix, nx), out(iy, ny), out(iz, nz)) {
+            (false, false, false) => self.occ_get(ix as usize, iy as usize, iz as usize),
+            (true, false, false) => {
+                let i = self.idx_sx(iy as usize, iz as usize);
+                match ix {
+                    -1 => self.seam_x.get(i),
+                    _ if ix == nx => self.seam_x_pos.get(i),
+                    _ => false,
+                }
+            }
+            (false, true, false) => {
+                let i = self.idx_sy(ix as usize, iz as usize);
+                match iy {
+                    -1 => self.seam_y_neg.get(i),
+                    _ if iy == ny => self.seam_y_pos.get(i),
+                    _ => false,
+                }
+            }
+            (false, false, true) => {
+                let i = self.idx_sz(ix as usize, iy as usize);
+                match iz {
+                    -1 => self.seam_z.get(i),
+                    _ if iz == nz => self.seam_z_pos.get(i),
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+/// Corner shades for micro face cell `(u, v)` on plane `layer` of `axis`, ordered
+/// `(u0,v0), (u1,v0), (u0,v1), (u1,v1)`; all 255 when AO is off. Occluders are read in the
+/// open layer in front of the face.
+fn corner_ao(
+    ao: Option<(&OccGrids, u8)>,
+    axis: Axis,
+    layer: usize,
+    pos: bool,
+    u: usize,
+    v: usize,
+) -> [u8; 4] {
+    let Some((occ, strength)) = ao else {
+        return [255; 4];
+    };
+    let a = layer as i32 - i32::from(!pos);
+    let at = |du: i32, dv: i32| {
+        let (u, v) = (u as i32 + du, v as i32 + dv);
+        match axis {
+            Axis::X => occ.occ_at(a, v, u),
+            Axis::Y => occ.occ_at(u, a, v),
+            Axis::Z => occ.occ_at(u, v, a),
+        }
+    };
+    [(-1, -1), (1, -1), (-1, 1), (1, 1)]
+        .map(|(du, dv)| corner_shade(at(du, 0), at(0, dv), at(du, dv), strength))
 }
 
 // Thread-local scratch for v3 mesher
@@ -337,8 +408,12 @@ impl<'a> ParityMesher<'a> {
                         continue;
                     }
                     if let Some(ty) = self.reg.get(b.id) {
-                        // water first: mark only in water grid (exclude from solids)
+                        // water first: mark only in water grid (exclude from solids);
+                        // flowing water gets its sloped surface in the thin-shape pass
                         if ty.name == "water" {
+                            if ty.fluid_level(b.state).is_some_and(|l| l > 0) {
+                                continue;
+                            }
                             let (x0, x1, y0, y1, z0, z1) =
                                 (x * s, (x + 1) * s, y * s, (y + 1) * s, z * s, (z + 1) * s);
                             for iz in z0..z1 {
@@ -351,7 +426,7 @@ impl<'a> ParityMesher<'a> {
                             continue;
                         }
                         // micro occupancy (solids)
-                        if let Some(occ) = ty.variant(b.state).occupancy {
+                        if let Some(occ) = ty.variant(b.state).mesh_occupancy() {
                             // S=2 patterns
                             for mz in 0..s {
                                 for my in 0..s {
@@ -392,6 +467,51 @@ impl<'a> ParityMesher<'a> {
         }
     }
 
+    /// Fill sealed air pockets into the solid occupancy so no faces are emitted around
+    /// them; see [`crate::pockets`]. Call after [`Self::build_occupancy`]. Occupied cells
+    /// of see-through blocks (glass and other non-opaque materials) let the fill pass, so
+    /// a room behind a window stays meshed, and water seeds it, so submerged hollows keep
+    /// their surfaces.
+    pub fn seal_hidden_pockets(&mut self) {
+        let s = self.s;
+        let (nx, ny, nz) = (self.occs.nx, self.occs.ny, self.occs.nz);
+        let (sx, sy, sz) = (self.sx, self.sy, self.sz);
+        let mats = &self.reg.materials;
+        let mut see_through = vec![false; sx * sy * sz];
+        for z in 0..sz {
+            for y in 0..sy {
+                for x in 0..sx {
+                    let b = self.buf.get_local(x, y, z);
+                    let Some(ty) = self.reg.get(b.id) else {
+                        continue;
+                    };
+                    see_through[(z * sy + y) * sx + x] = (0..6).any(|fi| {
+                        let mid = ty.material_for_cached(Face::from_index(fi).role(), b.state);
+                        mats.render_pass(mid) != geist_blocks::RenderPass::Opaque
+                    });
+                }
+            }
+        }
+        let voxel = |i: usize| {
+            let iz = i % nz;
+            let iy = (i / nz) % ny;
+            let ix = i / (ny * nz);
+            ((iz / s) * sy + iy / s) * sx + ix / s
+        };
+        let occ = &self.occs.occ;
+        let water = &self.occs_water.occ;
+        let sealed = crate::pockets::sealed_cells(
+            nx,
+            ny,
+            nz,
+            |i| occ.get(i) && !see_through[voxel(i)],
+            |i| water.get(i),
+        );
+        for i in sealed {
+            self.occs.occ.set(i, true);
+        }
+    }
+
     pub fn seed_seam_layers(&mut self) {
         // -X seam layer (ix = -1)
         let t_x = Instant::now();
@@ -414,6 +534,9 @@ impl<'a> ParityMesher<'a> {
                 }
                 if let Some(ty) = self.reg.get(nb.id) {
                     if ty.name == "water" {
+                        if ty.fluid_level(nb.state).is_some_and(|l| l > 0) {
+                            continue;
+                        }
                         let y0 = ly * s;
                         let z0 = lz * s;
                         for iz in z0..(z0 + s) {
@@ -437,7 +560,7 @@ impl<'a> ParityMesher<'a> {
                                 self.occs.seam_x.set(i, true);
                             }
                         }
-                    } else if let Some(occ) = ty.variant(nb.state).occupancy {
+                    } else if let Some(occ) = ty.variant(nb.state).mesh_occupancy() {
                         let y0 = ly * s;
                         let z0 = lz * s;
                         for mz in 0..s {
@@ -471,6 +594,9 @@ impl<'a> ParityMesher<'a> {
                 }
                 if let Some(ty) = self.reg.get(nb.id) {
                     if ty.name == "water" {
+                        if ty.fluid_level(nb.state).is_some_and(|l| l > 0) {
+                            continue;
+                        }
                         let x0 = lx * s;
                         let y0 = ly * s;
                         for ix in x0..(x0 + s) {
@@ -494,7 +620,7 @@ impl<'a> ParityMesher<'a> {
                                 self.occs.seam_z.set(i, true);
                             }
                         }
-                    } else if let Some(occ) = ty.variant(nb.state).occupancy {
+                    } else if let Some(occ) = ty.variant(nb.state).mesh_occupancy() {
                         let x0 = lx * s;
                         let y0 = ly * s;
                         for my in 0..s {
@@ -528,6 +654,9 @@ impl<'a> ParityMesher<'a> {
                 }
                 if let Some(ty) = self.reg.get(nb.id) {
                     if ty.name == "water" {
+                        if ty.fluid_level(nb.state).is_some_and(|l| l > 0) {
+                            continue;
+                        }
                         let y0 = ly * s;
                         let z0 = lz * s;
                         for iz in z0..(z0 + s) {
@@ -551,7 +680,7 @@ impl<'a> ParityMesher<'a> {
                                 self.occs.seam_x_pos.set(i, true);
                             }
                         }
-                    } else if let Some(occ) = ty.variant(nb.state).occupancy {
+                    } else if let Some(occ) = ty.variant(nb.state).mesh_occupancy() {
                         let y0 = ly * s;
                         let z0 = lz * s;
                         for mz in 0..s {
@@ -585,6 +714,9 @@ impl<'a> ParityMesher<'a> {
                 }
                 if let Some(ty) = self.reg.get(nb.id) {
                     if ty.name == "water" {
+                        if ty.fluid_level(nb.state).is_some_and(|l| l > 0) {
+                            continue;
+                        }
                         let x0 = lx * s;
                         let y0 = ly * s;
                         for ix in x0..(x0 + s) {
@@ -608,7 +740,7 @@ impl<'a> ParityMesher<'a> {
                                 self.occs.seam_z_pos.set(i, true);
                             }
                         }
-                    } else if let Some(occ) = ty.variant(nb.state).occupancy {
+                    } else if let Some(occ) = ty.variant(nb.state).mesh_occupancy() {
                         let x0 = lx * s;
                         let y0 = ly * s;
                         for my in 0..s {
@@ -642,6 +774,9 @@ impl<'a> ParityMesher<'a> {
                 }
                 if let Some(ty) = self.reg.get(nb.id) {
                     if ty.name == "water" {
+                        if ty.fluid_level(nb.state).is_some_and(|l| l > 0) {
+                            continue;
+                        }
                         let x0 = lx * s;
                         let z0 = lz * s;
                         for ix in x0..(x0 + s) {
@@ -665,7 +800,7 @@ impl<'a> ParityMesher<'a> {
                                 self.occs.seam_y_neg.set(i, true);
                             }
                         }
-                    } else if let Some(occ) = ty.variant(nb.state).occupancy {
+                    } else if let Some(occ) = ty.variant(nb.state).mesh_occupancy() {
                         let x0 = lx * s;
                         let z0 = lz * s;
                         let my = s.saturating_sub(1);
@@ -700,6 +835,9 @@ impl<'a> ParityMesher<'a> {
                 }
                 if let Some(ty) = self.reg.get(nb.id) {
                     if ty.name == "water" {
+                        if ty.fluid_level(nb.state).is_some_and(|l| l > 0) {
+                            continue;
+                        }
                         let x0 = lx * s;
                         let z0 = lz * s;
                         for ix in x0..(x0 + s) {
@@ -723,7 +861,7 @@ impl<'a> ParityMesher<'a> {
                                 self.occs.seam_y_pos.set(i, true);
                             }
                         }
-                    } else if let Some(occ) = ty.variant(nb.state).occupancy {
+                    } else if let Some(occ) = ty.variant(nb.state).mesh_occupancy() {
                         let x0 = lx * s;
                         let z0 = lz * s;
                         let my = 0usize;
@@ -1211,6 +1349,10 @@ impl<'a> ParityMesher<'a> {
         let need_y = (s * sx) * (s * sz);
         let need_z = (s * sx) * (s * sy);
         let need = need_x.max(need_y).max(need_z);
+        let strength = ao_strength_u8();
+        let ao = (strength > 0).then_some((&self.occs, strength));
+        let tiles = ConnectedTiles::new(self.reg, self.buf, self.world, self.edits);
+        let shore = WaterShore::new(self.reg, self.buf, self.world, self.edits);
         VISITED_SCRATCH_V3.with(|cell| {
             let mut buf = cell.borrow_mut();
             if buf.len() < need {
@@ -1226,6 +1368,8 @@ impl<'a> ParityMesher<'a> {
                 self.base_y,
                 self.base_z,
                 &self.grids,
+                ao,
+                tiles.as_ref(),
                 builds,
                 &mut buf[..],
             );
@@ -1238,6 +1382,9 @@ impl<'a> ParityMesher<'a> {
                 self.base_y,
                 self.base_z,
                 &self.grids,
+                ao,
+                tiles.as_ref(),
+                None,
                 builds,
                 &mut buf[..],
             );
@@ -1250,6 +1397,8 @@ impl<'a> ParityMesher<'a> {
                 self.base_y,
                 self.base_z,
                 &self.grids,
+                ao,
+                tiles.as_ref(),
                 builds,
                 &mut buf[..],
             );
@@ -1263,6 +1412,8 @@ impl<'a> ParityMesher<'a> {
                 self.base_y,
                 self.base_z,
                 &self.grids_water,
+                None,
+                None,
                 builds,
                 &mut buf[..],
             );
@@ -1275,6 +1426,9 @@ impl<'a> ParityMesher<'a> {
                 self.base_y,
                 self.base_z,
                 &self.grids_water,
+                None,
+                None,
+                Some(&shore),
                 builds,
                 &mut buf[..],
             );
@@ -1287,6 +1441,8 @@ impl<'a> ParityMesher<'a> {
                 self.base_y,
                 self.base_z,
                 &self.grids_water,
+                None,
+                None,
                 builds,
                 &mut buf[..],
             );
@@ -1304,6 +1460,8 @@ fn emit_plane_x<B: crate::emit::BuildSink>(
     base_y: i32,
     base_z: i32,
     grids: &FaceGrids,
+    ao_occ: Option<(&OccGrids, u8)>,
+    tiles: Option<&ConnectedTiles>,
     builds: &mut B,
     visited_buf: &mut [u8],
 ) {
@@ -1344,20 +1502,38 @@ fn emit_plane_x<B: crate::emit::BuildSink>(
                     continue;
                 }
                 let pos = grids.ox.get(idx);
+                // A connected tile covers one block, so its quads stop at block borders.
+                let tiled = tiles.filter(|t| t.connected(mid));
+                let max_w = if tiled.is_some() {
+                    s - u % s
+                } else {
+                    width - u
+                };
+                let max_h = if tiled.is_some() {
+                    s - v % s
+                } else {
+                    height - v
+                };
+                let ao = corner_ao(ao_occ, Axis::X, ix, pos, u, v);
+                // Only evenly shaded cells merge; the others keep their own corners.
+                let merge = ao == [ao[0]; 4];
                 let mut run_w = 1usize;
-                while u + run_w < width {
+                while merge && run_w < max_w {
                     if visited_buf[idx2d(u + run_w, v)] == epoch {
                         break;
                     }
                     let idx_n = grids.idx_x(ix, v, u + run_w);
-                    if !grids.px.get(idx_n) || grids.kx[idx_n] != mid || grids.ox.get(idx_n) != pos
+                    if !grids.px.get(idx_n)
+                        || grids.kx[idx_n] != mid
+                        || grids.ox.get(idx_n) != pos
+                        || corner_ao(ao_occ, Axis::X, ix, pos, u + run_w, v) != ao
                     {
                         break;
                     }
                     run_w += 1;
                 }
                 let mut run_h = 1usize;
-                'outer: while v + run_h < height {
+                'outer: while merge && run_h < max_h {
                     for uu in u..(u + run_w) {
                         if visited_buf[idx2d(uu, v + run_h)] == epoch {
                             break 'outer;
@@ -1366,6 +1542,7 @@ fn emit_plane_x<B: crate::emit::BuildSink>(
                         if !grids.px.get(idx_n)
                             || grids.kx[idx_n] != mid
                             || grids.ox.get(idx_n) != pos
+                            || corner_ao(ao_occ, Axis::X, ix, pos, uu, v + run_h) != ao
                         {
                             break 'outer;
                         }
@@ -1390,8 +1567,19 @@ fn emit_plane_x<B: crate::emit::BuildSink>(
                     u += run_w;
                     continue;
                 }
+                let tile = tiled.map(|t| {
+                    let own = (ix as i32 - pos as i32).div_euclid(s as i32);
+                    t.tile(
+                        mid,
+                        face,
+                        base_x + own,
+                        base_y + (v / s) as i32,
+                        base_z + (u / s) as i32,
+                    )
+                });
                 emit_face_rect_for_clipped(
-                    builds, mid, face, origin, u1, v1, rgba, base_x, sx, sy, base_y, base_z, sz,
+                    builds, mid, face, origin, u1, v1, rgba, ao, tile, base_x, sx, sy, base_y,
+                    base_z, sz,
                 );
                 for dv in 0..run_h {
                     for du in 0..run_w {
@@ -1416,6 +1604,9 @@ fn emit_plane_y<B: crate::emit::BuildSink>(
     base_y: i32,
     base_z: i32,
     grids: &FaceGrids,
+    ao_occ: Option<(&OccGrids, u8)>,
+    tiles: Option<&ConnectedTiles>,
+    shore: Option<&WaterShore>,
     builds: &mut B,
     visited_buf: &mut [u8],
 ) {
@@ -1455,20 +1646,48 @@ fn emit_plane_y<B: crate::emit::BuildSink>(
                     continue;
                 }
                 let pos = grids.oy.get(idx);
+                // A connected tile covers one block, so its quads stop at block borders.
+                let tiled = tiles.filter(|t| t.connected(mid));
+                let max_w = if tiled.is_some() {
+                    s - u % s
+                } else {
+                    width - u
+                };
+                let max_h = if tiled.is_some() {
+                    s - v % s
+                } else {
+                    height - v
+                };
+                let ao = corner_ao(ao_occ, Axis::Y, iy, pos, u, v);
+                // Water tops carry their openness per corner in the vertex alpha.
+                let open = |u: usize, v: usize| match shore {
+                    Some(w) if pos => {
+                        let y = base_y + (iy as i32 - 1).div_euclid(s as i32);
+                        w.corners(s, base_x, base_z, y, u, v)
+                    }
+                    _ => [OPAQUE_ALPHA; 4],
+                };
+                let alpha = open(u, v);
+                // Only evenly shaded cells merge; the others keep their own corners.
+                let merge = ao == [ao[0]; 4] && alpha == [alpha[0]; 4];
                 let mut run_w = 1usize;
-                while u + run_w < width {
+                while merge && run_w < max_w {
                     if visited_buf[idx2d(u + run_w, v)] == epoch {
                         break;
                     }
                     let idx_n = grids.idx_y(u + run_w, iy, v);
-                    if !grids.py.get(idx_n) || grids.ky[idx_n] != mid || grids.oy.get(idx_n) != pos
+                    if !grids.py.get(idx_n)
+                        || grids.ky[idx_n] != mid
+                        || grids.oy.get(idx_n) != pos
+                        || corner_ao(ao_occ, Axis::Y, iy, pos, u + run_w, v) != ao
+                        || open(u + run_w, v) != alpha
                     {
                         break;
                     }
                     run_w += 1;
                 }
                 let mut run_h = 1usize;
-                'outer: while v + run_h < height {
+                'outer: while merge && run_h < max_h {
                     for uu in u..(u + run_w) {
                         if visited_buf[idx2d(uu, v + run_h)] == epoch {
                             break 'outer;
@@ -1477,6 +1696,8 @@ fn emit_plane_y<B: crate::emit::BuildSink>(
                         if !grids.py.get(idx_n)
                             || grids.ky[idx_n] != mid
                             || grids.oy.get(idx_n) != pos
+                            || corner_ao(ao_occ, Axis::Y, iy, pos, uu, v + run_h) != ao
+                            || open(uu, v + run_h) != alpha
                         {
                             break 'outer;
                         }
@@ -1492,9 +1713,23 @@ fn emit_plane_y<B: crate::emit::BuildSink>(
                 let u1 = (run_w as f32) * scale;
                 let v1 = (run_h as f32) * scale;
                 let rgba = [255u8, 255u8, 255u8, OPAQUE_ALPHA];
-                emit_face_rect_for_clipped(
-                    builds, mid, face, origin, u1, v1, rgba, base_x, sx, sy, base_y, base_z, sz,
+                let tile = tiled.map(|t| {
+                    let own = (iy as i32 - pos as i32).div_euclid(s as i32);
+                    t.tile(
+                        mid,
+                        face,
+                        base_x + (u / s) as i32,
+                        base_y + own,
+                        base_z + (v / s) as i32,
+                    )
+                });
+                let emitted = emit_face_rect_for_clipped(
+                    builds, mid, face, origin, u1, v1, rgba, ao, tile, base_x, sx, sy, base_y,
+                    base_z, sz,
                 );
+                if emitted && alpha != [OPAQUE_ALPHA; 4] {
+                    builds.get_build_mut(mid).set_last_quad_alpha(face, alpha);
+                }
                 for dv in 0..run_h {
                     for du in 0..run_w {
                         visited_buf[idx2d(u + du, v + dv)] = epoch;
@@ -1518,6 +1753,8 @@ fn emit_plane_z<B: crate::emit::BuildSink>(
     base_y: i32,
     base_z: i32,
     grids: &FaceGrids,
+    ao_occ: Option<(&OccGrids, u8)>,
+    tiles: Option<&ConnectedTiles>,
     builds: &mut B,
     visited_buf: &mut [u8],
 ) {
@@ -1558,20 +1795,38 @@ fn emit_plane_z<B: crate::emit::BuildSink>(
                     continue;
                 }
                 let pos = grids.oz.get(idx);
+                // A connected tile covers one block, so its quads stop at block borders.
+                let tiled = tiles.filter(|t| t.connected(mid));
+                let max_w = if tiled.is_some() {
+                    s - u % s
+                } else {
+                    width - u
+                };
+                let max_h = if tiled.is_some() {
+                    s - v % s
+                } else {
+                    height - v
+                };
+                let ao = corner_ao(ao_occ, Axis::Z, iz, pos, u, v);
+                // Only evenly shaded cells merge; the others keep their own corners.
+                let merge = ao == [ao[0]; 4];
                 let mut run_w = 1usize;
-                while u + run_w < width {
+                while merge && run_w < max_w {
                     if visited_buf[idx2d(u + run_w, v)] == epoch {
                         break;
                     }
                     let idx_n = grids.idx_z(u + run_w, v, iz);
-                    if !grids.pz.get(idx_n) || grids.kz[idx_n] != mid || grids.oz.get(idx_n) != pos
+                    if !grids.pz.get(idx_n)
+                        || grids.kz[idx_n] != mid
+                        || grids.oz.get(idx_n) != pos
+                        || corner_ao(ao_occ, Axis::Z, iz, pos, u + run_w, v) != ao
                     {
                         break;
                     }
                     run_w += 1;
                 }
                 let mut run_h = 1usize;
-                'outer: while v + run_h < height {
+                'outer: while merge && run_h < max_h {
                     for uu in u..(u + run_w) {
                         if visited_buf[idx2d(uu, v + run_h)] == epoch {
                             break 'outer;
@@ -1580,6 +1835,7 @@ fn emit_plane_z<B: crate::emit::BuildSink>(
                         if !grids.pz.get(idx_n)
                             || grids.kz[idx_n] != mid
                             || grids.oz.get(idx_n) != pos
+                            || corner_ao(ao_occ, Axis::Z, iz, pos, uu, v + run_h) != ao
                         {
                             break 'outer;
                         }
@@ -1604,8 +1860,19 @@ fn emit_plane_z<B: crate::emit::BuildSink>(
                     u += run_w;
                     continue;
                 }
+                let tile = tiled.map(|t| {
+                    let own = (iz as i32 - pos as i32).div_euclid(s as i32);
+                    t.tile(
+                        mid,
+                        face,
+                        base_x + (u / s) as i32,
+                        base_y + (v / s) as i32,
+                        base_z + own,
+                    )
+                });
                 emit_face_rect_for_clipped(
-                    builds, mid, face, origin, u1, v1, rgba, base_x, sx, sy, base_y, base_z, sz,
+                    builds, mid, face, origin, u1, v1, rgba, ao, tile, base_x, sx, sy, base_y,
+                    base_z, sz,
                 );
                 for dv in 0..run_h {
                     for du in 0..run_w {
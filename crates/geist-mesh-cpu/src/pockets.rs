@@ -0,0 +1,87 @@
+//! Optional culling of sealed interior air pockets.
+//!
+//! A hollow enclosed in solid blocks (a sealed room, a cave bubble) has no line of sight to
+//! anything outside it, yet parity meshing still emits every face lining it. With culling
+//! on, empty micro cells are flood-filled from the chunk's six boundary planes and from
+//! every water cell; whatever empty space the fill never reaches is a sealed pocket and is
+//! meshed as if it were solid, so the faces around it vanish.
+//!
+//! The fill is conservative: empty space touching the chunk border counts as open because
+//! the neighbour chunk may continue it, so a sealed pocket never reaches the border. Any
+//! edit that opens a pocket therefore changes a block inside this chunk, and the rebuild it
+//! triggers runs the fill again and brings the interior faces back.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static SEALED_POCKET_CULLING: AtomicBool = AtomicBool::new(false);
+
+/// Skip faces around sealed air pockets in chunks meshed afterwards. Off by default.
+pub fn set_sealed_pocket_culling(on: bool) {
+    SEALED_POCKET_CULLING.store(on, Ordering::Relaxed);
+}
+
+pub fn sealed_pocket_culling() -> bool {
+    SEALED_POCKET_CULLING.load(Ordering::Relaxed)
+}
+
+/// Indices of the non-`solid` cells of an `nx*ny*nz` grid (indexed `(ix * ny + iy) * nz + iz`)
+/// that no flood fill from the grid boundary or from a `seed` cell reaches through
+/// 6-connected non-`solid` cells.
+pub(crate) fn sealed_cells(
+    nx: usize,
+    ny: usize,
+    nz: usize,
+    solid: impl Fn(usize) -> bool,
+    seed: impl Fn(usize) -> bool,
+) -> Vec<usize> {
+    let n = nx * ny * nz;
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut reached = vec![false; n];
+    let mut stack: Vec<usize> = Vec::new();
+    let idx = |ix: usize, iy: usize, iz: usize| (ix * ny + iy) * nz + iz;
+    for ix in 0..nx {
+        for iy in 0..ny {
+            for iz in 0..nz {
+                let i = idx(ix, iy, iz);
+                let border =
+                    ix == 0 || iy == 0 || iz == 0 || ix + 1 == nx || iy + 1 == ny || iz + 1 == nz;
+                if (border || seed(i)) && !solid(i) {
+                    reached[i] = true;
+                    stack.push(i);
+                }
+            }
+        }
+    }
+    while let Some(i) = stack.pop() {
+        let iz = i % nz;
+        let iy = (i / nz) % ny;
+        let ix = i / (ny * nz);
+        let mut visit = |j: usize| {
+            if !reached[j] && !solid(j) {
+                reached[j] = true;
+                stack.push(j);
+            }
+        };
+        if ix > 0 {
+            visit(idx(ix - 1, iy, iz));
+        }
+        if ix + 1 < nx {
+            visit(idx(ix + 1, iy, iz));
+        }
+        if iy > 0 {
+            visit(idx(ix, iy - 1, iz));
+        }
+        if iy + 1 < ny {
+            visit(idx(ix, iy + 1, iz));
+        }
+        if iz > 0 {
+            visit(idx(ix, iy, iz - 1));
+        }
+        if iz + 1 < nz {
+            visit(idx(ix, iy, iz + 1));
+        }
+    }
+    (0..n).filter(|&i| !reached[i] && !solid(i)).collect()
+}
@@ -0,0 +1,167 @@
+//! Recycled `MeshBuild` buffers.
+//!
+//! Every chunk rebuild used to allocate a fresh set of vectors per material and drop them
+//! once the mesh was uploaded. Builds handed back with `recycle_build` are kept here,
+//! cleared, in power-of-two size classes by quad capacity, and `take_build` reuses one
+//! big enough before allocating. Each class holds a bounded number of builds, so a burst
+//! of large meshes does not pin memory forever.
+
+use std::sync::Mutex;
+
+use crate::chunk::ChunkMeshCPU;
+use crate::mesh_build::MeshBuild;
+
+/// Smallest capacity worth pooling, in quads; class `c` holds builds of at least
+/// `MIN_POOLED_QUADS << c` quads.
+const MIN_POOLED_QUADS: usize = 64;
+const SIZE_CLASSES: usize = 12;
+const MAX_PER_CLASS: usize = 64;
+
+static POOL: Mutex<MeshPool> = Mutex::new(MeshPool::new());
+
+/// Counters since startup, shown in the render stats overlay.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MeshPoolStats {
+    /// `take_build` calls served from the pool.
+    pub hits: u64,
+    /// `take_build` calls that allocated.
+    pub misses: u64,
+    /// Builds currently waiting for reuse.
+    pub pooled: usize,
+}
+
+struct MeshPool {
+    classes: [Vec<MeshBuild>; SIZE_CLASSES],
+    hits: u64,
+    misses: u64,
+}
+
+impl MeshPool {
+    const fn new() -> Self {
+        Self {
+            classes: [const { Vec::new() }; SIZE_CLASSES],
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    fn take(&mut self, quads: usize) -> MeshBuild {
+        let want = class_for_request(quads);
+        // The next class up still fits; anything larger would waste the pool's big buffers.
+        for c in want..(want + 2).min(SIZE_CLASSES) {
+            if let Some(mb) = self.classes[c].pop() {
+                self.hits += 1;
+                return mb;
+            }
+        }
+        self.misses += 1;
+        let mut mb = MeshBuild::default();
+        mb.reserve_quads(quads);
+        mb
+    }
+
+    fn put(&mut self, mut mb: MeshBuild) {
+        let Some(c) = class_for_capacity(quad_capacity(&mb)) else {
+            return;
+        };
+        let class = &mut self.classes[c];
+        if class.len() < MAX_PER_CLASS {
+            mb.clear_keep_capacity();
+            class.push(mb);
+        }
+    }
+
+    fn stats(&self) -> MeshPoolStats {
+        MeshPoolStats {
+            hits: self.hits,
+            misses: self.misses,
+            pooled: self.classes.iter().map(Vec::len).sum(),
+        }
+    }
+}
+
+/// Quads `mb` can hold in every array without reallocating.
+fn quad_capacity(mb: &MeshBuild) -> usize {
+    (mb.pos.capacity() / 12)
+        .min(mb.norm.capacity() / 12)
+        .min(mb.uv.capacity() / 8)
+        .min(mb.col.capacity() / 16)
+        .min(mb.idx.capacity() / 6)
+}
+
+/// Largest class whose minimum fits in `quads`; `None` below the smallest class.
+fn class_for_capacity(quads: usize) -> Option<usize> {
+    if quads < MIN_POOLED_QUADS {
+        return None;
+    }
+    let c = (quads / MIN_POOLED_QUADS).ilog2() as usize;
+    Some(c.min(SIZE_CLASSES - 1))
+}
+
+/// Smallest class guaranteed to hold `quads`.
+fn class_for_request(quads: usize) -> usize {
+    let steps = quads.div_ceil(MIN_POOLED_QUADS).max(1);
+    (steps.next_power_of_two().ilog2() as usize).min(SIZE_CLASSES - 1)
+}
+
+/// An empty build with room for at least `quads` quads, reused from the pool when one
+/// fits.
+pub fn take_build(quads: usize) -> MeshBuild {
+    let mut mb = POOL.lock().unwrap().take(quads);
+    // The top class is open-ended, so a reused build may still be short.
+    mb.reserve_quads(quads);
+    mb
+}
+
+/// Return `mb`'s buffers for a later `take_build`. Builds too small to matter, or
+/// arriving when their class is full, are dropped.
+pub fn recycle_build(mb: MeshBuild) {
+    POOL.lock().unwrap().put(mb);
+}
+
+/// Recycle every part of a mesh that is no longer needed (uploaded or superseded).
+pub fn recycle_chunk(cpu: ChunkMeshCPU) {
+    let mut pool = POOL.lock().unwrap();
+    for (_, mb) in cpu.parts {
+        pool.put(mb);
+    }
+}
+
+pub fn mesh_pool_stats() -> MeshPoolStats {
+    POOL.lock().unwrap().stats()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_come_back_from_their_size_class() {
+        let mut pool = MeshPool::new();
+        let mut mb = MeshBuild::default();
+        mb.reserve_quads(300);
+        mb.pos.push(1.0);
+        pool.put(mb);
+        assert_eq!(pool.stats().pooled, 1);
+
+        // 300 quads sit in the 256 class: too small for 400, fine for 200.
+        let big = pool.take(400);
+        assert!(quad_capacity(&big) >= 400);
+        assert_eq!(pool.stats().misses, 1);
+        let reused = pool.take(200);
+        assert!(reused.pos.is_empty());
+        assert!(quad_capacity(&reused) >= 200);
+        assert_eq!(
+            pool.stats(),
+            MeshPoolStats {
+                hits: 1,
+                misses: 1,
+                pooled: 0
+            }
+        );
+
+        // Too small to pool.
+        pool.put(MeshBuild::default());
+        assert_eq!(pool.stats().pooled, 0);
+    }
+}
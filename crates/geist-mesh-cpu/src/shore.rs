@@ -0,0 +1,100 @@
+//! Water surface openness for the water shader: how deep the water is under each corner
+//! of a top face and whether a bank is beside it, written to the vertex alpha. 0 is the
+//! waterline itself, where the shader draws foam; 255 is water at least
+//! [`WATER_OPEN_CAP`] blocks deep with no bank alongside, where waves run at full height.
+use hashbrown::HashMap;
+use std::cell::RefCell;
+
+use geist_blocks::BlockRegistry;
+use geist_blocks::types::Block;
+use geist_chunk::ChunkBuf;
+use geist_world::World;
+
+use crate::util::neighbor_block;
+
+/// Openness in blocks that maps to alpha 255. Depth is counted no further down than this.
+pub const WATER_OPEN_CAP: u8 = 4;
+
+/// Reads and caches per-block openness while one chunk's water tops are emitted.
+pub(crate) struct WaterShore<'a> {
+    reg: &'a BlockRegistry,
+    buf: &'a ChunkBuf,
+    world: Option<&'a World>,
+    edits: Option<&'a HashMap<(i32, i32, i32), Block>>,
+    cache: RefCell<HashMap<(i32, i32, i32), u8>>,
+}
+
+impl<'a> WaterShore<'a> {
+    pub(crate) fn new(
+        reg: &'a BlockRegistry,
+        buf: &'a ChunkBuf,
+        world: Option<&'a World>,
+        edits: Option<&'a HashMap<(i32, i32, i32), Block>>,
+    ) -> Self {
+        Self {
+            reg,
+            buf,
+            world,
+            edits,
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn is_water(&self, x: i32, y: i32, z: i32) -> bool {
+        let b = neighbor_block(self.buf, self.world, self.edits, self.reg, x, y, z);
+        self.reg.get(b.id).is_some_and(|ty| ty.name == "water")
+    }
+
+    /// Openness of the block at world `(x, y, z)` in blocks: 0 for anything but water, 1 for
+    /// water with a bank or drop among its eight horizontal neighbors, else its depth.
+    fn openness(&self, x: i32, y: i32, z: i32) -> u8 {
+        if let Some(&open) = self.cache.borrow().get(&(x, y, z)) {
+            return open;
+        }
+        let open = if !self.is_water(x, y, z) {
+            0
+        } else if (-1..=1)
+            .flat_map(|dz| (-1..=1).map(move |dx| (dx, dz)))
+            .any(|(dx, dz)| (dx, dz) != (0, 0) && !self.is_water(x + dx, y, z + dz))
+        {
+            1
+        } else {
+            1 + (1..WATER_OPEN_CAP as i32)
+                .take_while(|&d| self.is_water(x, y - d, z))
+                .count() as u8
+        };
+        self.cache.borrow_mut().insert((x, y, z), open);
+        open
+    }
+
+    /// Vertex alpha for the corners of micro cell `(u, v)` on the top of the water block at
+    /// world height `y`, in the plane emitters' corner order `(u0,v0), (u1,v0), (u0,v1),
+    /// (u1,v1)`. A corner takes the least open of the blocks touching it, so corners on a
+    /// bank read 0 and values agree across block and chunk borders.
+    pub(crate) fn corners(
+        &self,
+        s: usize,
+        base_x: i32,
+        base_z: i32,
+        y: i32,
+        u: usize,
+        v: usize,
+    ) -> [u8; 4] {
+        let s = s as i32;
+        // Blocks along one axis that touch micro coordinate `c`.
+        let touching = |c: i32| {
+            let hi = c.div_euclid(s);
+            let lo = if c.rem_euclid(s) == 0 { hi - 1 } else { hi };
+            lo..=hi
+        };
+        [(0, 0), (1, 0), (0, 1), (1, 1)].map(|(du, dv)| {
+            let (cu, cv) = (u as i32 + du, v as i32 + dv);
+            let open = touching(cu)
+                .flat_map(|bx| touching(cv).map(move |bz| (bx, bz)))
+                .map(|(bx, bz)| self.openness(base_x + bx, y, base_z + bz))
+                .min()
+                .unwrap_or(0);
+            (open as u32 * 255 / WATER_OPEN_CAP as u32) as u8
+        })
+    }
+}
@@ -0,0 +1,157 @@
+//! Optional smoothed normals for terrain materials.
+//!
+//! With smoothing on, every vertex of a material marked `smooth` that sits on a block
+//! corner takes the normal of the terrain around that corner instead of its face normal:
+//! the direction pointing away from the full cubes among the eight voxels that share the
+//! corner, i.e. the average of the exposed faces meeting there. Vertices are welded by
+//! position, so every face touching a corner gets the same normal, and the voxels are read
+//! through the world beyond the chunk's border planes, so both chunks on a seam agree.
+//!
+//! The vertex layout stays four vertices per quad, as uploading and the CPU light bake
+//! expect. Normals only tilt so far that the face axis stays dominant: the light lookups
+//! pick their neighbour voxel by the normal's dominant axis.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use geist_blocks::BlockRegistry;
+use geist_blocks::types::MaterialId;
+use geist_geom::Vec3;
+use hashbrown::HashMap;
+
+use crate::mesh_build::MeshBuild;
+
+static NORMAL_SMOOTHING: AtomicBool = AtomicBool::new(false);
+
+/// Largest tangential component of a smoothed normal, relative to its face component.
+const MAX_TILT: f32 = 0.9;
+const CORNER_EPS: f32 = 1e-4;
+
+/// Smooth normals of `smooth` materials in chunks meshed afterwards. Off by default.
+pub fn set_normal_smoothing(on: bool) {
+    NORMAL_SMOOTHING.store(on, Ordering::Relaxed);
+}
+
+pub fn normal_smoothing() -> bool {
+    NORMAL_SMOOTHING.load(Ordering::Relaxed)
+}
+
+/// Replace the normals of corner vertices in smooth materials' builds, indexed by
+/// material id. `solid` reports full cubes at world voxel coordinates.
+pub(crate) fn smooth_builds(
+    builds: &mut [MeshBuild],
+    reg: &BlockRegistry,
+    solid: impl Fn(i32, i32, i32) -> bool,
+) {
+    let mut corners: HashMap<(i32, i32, i32), Option<Vec3>> = HashMap::new();
+    for (i, mb) in builds.iter_mut().enumerate() {
+        if mb.pos.is_empty() || !reg.materials.smooth(MaterialId(i as u16)) {
+            continue;
+        }
+        for (p, n) in mb.pos.chunks_exact(3).zip(mb.norm.chunks_exact_mut(3)) {
+            let Some(corner) = corner_of(p) else {
+                continue;
+            };
+            let Some(dir) = *corners
+                .entry(corner)
+                .or_insert_with(|| corner_direction(corner, &solid))
+            else {
+                continue;
+            };
+            let face = Vec3 {
+                x: n[0],
+                y: n[1],
+                z: n[2],
+            };
+            let smoothed = limit_tilt(dir, face);
+            n.copy_from_slice(&[smoothed.x, smoothed.y, smoothed.z]);
+        }
+    }
+}
+
+/// The block corner a vertex sits on, if it sits on one.
+fn corner_of(p: &[f32]) -> Option<(i32, i32, i32)> {
+    let snap = |v: f32| {
+        let r = v.round();
+        ((v - r).abs() < CORNER_EPS).then_some(r as i32)
+    };
+    Some((snap(p[0])?, snap(p[1])?, snap(p[2])?))
+}
+
+/// Unit direction away from the full cubes around `corner`; `None` when they balance out.
+fn corner_direction(
+    (x, y, z): (i32, i32, i32),
+    solid: &impl Fn(i32, i32, i32) -> bool,
+) -> Option<Vec3> {
+    let mut g = Vec3::ZERO;
+    for dy in [-1, 0] {
+        for dz in [-1, 0] {
+            for dx in [-1, 0] {
+                if solid(x + dx, y + dy, z + dz) {
+                    // Voxel centre relative to the corner is (dx + 0.5, ...), pointing inwards.
+                    g.x -= dx as f32 + 0.5;
+                    g.y -= dy as f32 + 0.5;
+                    g.z -= dz as f32 + 0.5;
+                }
+            }
+        }
+    }
+    let len = g.length();
+    (len > CORNER_EPS).then(|| g * (1.0 / len))
+}
+
+/// `dir` pulled back towards `face` until the face axis dominates; `face` itself when
+/// `dir` points behind the face.
+fn limit_tilt(dir: Vec3, face: Vec3) -> Vec3 {
+    let along = dir.dot(face);
+    if along <= CORNER_EPS {
+        return face;
+    }
+    let mut t = dir - face * along;
+    let widest = t.x.abs().max(t.y.abs()).max(t.z.abs());
+    let limit = MAX_TILT * along;
+    if widest > limit {
+        t = t * (limit / widest);
+    }
+    let n = face * along + t;
+    n * (1.0 / n.length())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corner_directions_follow_the_terrain_and_keep_the_face_axis() {
+        // Flat ground below y=0: straight up.
+        let ground = |_: i32, y: i32, _: i32| y < 0;
+        let up = corner_direction((3, 0, 7), &ground).unwrap();
+        assert!((up.y - 1.0).abs() < 1e-6);
+
+        // A step edge: ground at y<1 for x<0, y<0 elsewhere. The top corner of the step
+        // leans out over the drop.
+        let step = |x: i32, y: i32, _: i32| y < if x < 0 { 1 } else { 0 };
+        let edge = corner_direction((0, 1, 0), &step).unwrap();
+        assert!(edge.x > 0.0 && edge.y > 0.0);
+        let top = Vec3 {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        };
+        let n = limit_tilt(edge, top);
+        assert!(n.y > n.x.abs() && n.y > n.z.abs());
+        assert!((n.length() - 1.0).abs() < 1e-5);
+
+        // Fully buried or fully open corners have no direction.
+        assert!(corner_direction((0, 0, 0), &|_, _, _| true).is_none());
+        assert!(corner_direction((0, 0, 0), &|_, _, _| false).is_none());
+        // A direction behind the face leaves the face normal alone.
+        let down = Vec3 {
+            x: 0.0,
+            y: -1.0,
+            z: 0.0,
+        };
+        assert_eq!(limit_tilt(down, top), top);
+        assert_eq!(corner_of(&[1.0, 2.5, 3.0]), None);
+        assert_eq!(corner_of(&[1.0, -2.0, 3.00001]), Some((1, -2, 3)));
+    }
+}
@@ -65,6 +65,37 @@ pub(crate) fn occludes_face(nb: Block, face: Face, reg: &BlockRegistry) -> bool
     (mask >> face.index()) & 1 == 1
 }
 
+#[inline]
+/// Block at world `(nx,ny,nz)` outside the chunk buffer, from edits, then the world.
+fn outside_block(
+    world: Option<&World>,
+    edits: Option<&HashMap<(i32, i32, i32), Block>>,
+    reg: &BlockRegistry,
+    nx: i32,
+    ny: i32,
+    nz: i32,
+) -> Block {
+    if let Some(b) = edits.and_then(|es| es.get(&(nx, ny, nz))) {
+        return *b;
+    }
+    world.map_or(Block::AIR, |w| w.block_at_runtime(reg, nx, ny, nz))
+}
+
+#[inline]
+/// Block at world `(nx,ny,nz)`, read from the buffer when inside it, else edits and world.
+pub(crate) fn neighbor_block(
+    buf: &ChunkBuf,
+    world: Option<&World>,
+    edits: Option<&HashMap<(i32, i32, i32), Block>>,
+    reg: &BlockRegistry,
+    nx: i32,
+    ny: i32,
+    nz: i32,
+) -> Block {
+    buf.get_world(nx, ny, nz)
+        .unwrap_or_else(|| outside_block(world, edits, reg, nx, ny, nz))
+}
+
 #[inline]
 /// Determines if the neighbor at `(nx,ny,nz)` occludes the face of `here`, using edits/world as needed.
 pub(crate) fn is_occluder(
@@ -100,19 +131,7 @@ pub(crate) fn is_occluder(
         return occludes_face(nb, face, reg);
     }
     // Out of local bounds: unconditionally consult world+edits to decide occlusion (overscan default)
-    let nb = if let Some(es) = edits {
-        if let Some(b) = es.get(&(nx, ny, nz)) {
-            *b
-        } else if let Some(world) = world {
-            world.block_at_runtime(reg, nx, ny, nz)
-        } else {
-            Block::AIR
-        }
-    } else if let Some(world) = world {
-        world.block_at_runtime(reg, nx, ny, nz)
-    } else {
-        Block::AIR
-    };
+    let nb = outside_block(world, edits, reg, nx, ny, nz);
     if let (Some(h), Some(_n)) = (reg.get(here.id), reg.get(nb.id)) {
         if h.seam.dont_occlude_same && here.id == nb.id {
             return false;
@@ -0,0 +1,76 @@
+use geist_blocks::BlockRegistry;
+use geist_blocks::types::Block;
+use geist_chunk::ChunkBuf;
+use geist_lighting::{LightGrid, LightingStore};
+use geist_mesh_cpu::{ChunkMeshCPU, build_chunk_wcc_cpu_buf_with_light};
+use geist_world::{ChunkCoord, World, WorldGenMode};
+
+fn load_registry() -> BlockRegistry {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let vox = root.join("../../assets/voxels");
+    BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml")).unwrap()
+}
+
+fn named(reg: &BlockRegistry, name: &str) -> Block {
+    Block {
+        id: reg.id_by_name(name).unwrap(),
+        state: 0,
+    }
+}
+
+// A stone floor at y = 0 with the named blocks standing on it at (x, 1, z).
+fn yard(reg: &BlockRegistry, placed: &[(usize, usize, &str)]) -> ChunkMeshCPU {
+    let (sx, sy, sz) = (6, 4, 6);
+    let mut blocks = vec![Block::AIR; sx * sy * sz];
+    for z in 0..sz {
+        for x in 0..sx {
+            blocks[z * sx + x] = named(reg, "stone");
+        }
+    }
+    for &(x, z, name) in placed {
+        blocks[(sz + z) * sx + x] = named(reg, name);
+    }
+    let world = World::new(1, 1, 1, 0, WorldGenMode::Flat { thickness: 0 });
+    let buf = ChunkBuf::from_blocks_local(ChunkCoord::new(0, 0, 0), sx, sy, sz, blocks);
+    let store = LightingStore::new(sx, sy, sz);
+    let light = LightGrid::compute_with_borders_buf(&buf, &store, reg);
+    build_chunk_wcc_cpu_buf_with_light(&buf, &light, &world, None, buf.coord, reg)
+        .expect("chunk mesh")
+        .0
+}
+
+fn triangles(m: &ChunkMeshCPU) -> usize {
+    m.parts.values().map(|p| p.idx.len() / 3).sum()
+}
+
+#[test]
+fn lone_post_emits_its_box_without_the_floor_face() {
+    let reg = load_registry();
+    let bare = triangles(&yard(&reg, &[]));
+    let post = triangles(&yard(&reg, &[(2, 2, "cobblestone_wall")]));
+    // Four sides and a top; the bottom sits on stone.
+    assert_eq!(post - bare, 10);
+}
+
+#[test]
+fn arms_reach_toward_walls_and_full_blocks() {
+    let reg = load_registry();
+    let bare = triangles(&yard(&reg, &[]));
+    let apart = triangles(&yard(
+        &reg,
+        &[(1, 2, "cobblestone_wall"), (4, 2, "cobblestone_wall")],
+    )) - bare;
+    let joined = triangles(&yard(
+        &reg,
+        &[(2, 2, "cobblestone_wall"), (3, 2, "cobblestone_wall")],
+    )) - bare;
+    // Each post grows one arm toward the other.
+    assert_eq!(joined, apart + 2 * 10);
+
+    let post = triangles(&yard(&reg, &[(2, 2, "cobblestone_wall")])) - bare;
+    let with_cube = triangles(&yard(&reg, &[(2, 2, "cobblestone_wall"), (2, 1, "stone")]));
+    let cube_alone = triangles(&yard(&reg, &[(2, 1, "stone")]));
+    // The arm's ends against the cube and the floor are culled: top, two sides and the
+    // end inside the post remain.
+    assert_eq!(with_cube - cube_alone, post + 8);
+}
@@ -0,0 +1,117 @@
+use geist_blocks::BlockRegistry;
+use geist_blocks::types::Block;
+use geist_chunk::ChunkBuf;
+use geist_lighting::{LightGrid, LightingStore};
+use geist_mesh_cpu::{ChunkMeshCPU, MeshBuild, build_chunk_wcc_cpu_buf_with_light};
+use geist_world::{ChunkCoord, World, WorldGenMode};
+
+fn load_registry() -> BlockRegistry {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let vox = root.join("../../assets/voxels");
+    BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml")).unwrap()
+}
+
+fn named(reg: &BlockRegistry, name: &str) -> Block {
+    Block {
+        id: reg.id_by_name(name).unwrap(),
+        state: 0,
+    }
+}
+
+// Framed glass at (x, 1, z) for each listed cell, standing on a stone floor.
+fn panes(reg: &BlockRegistry, placed: &[(usize, usize)]) -> ChunkMeshCPU {
+    let (sx, sy, sz) = (6, 4, 6);
+    let mut blocks = vec![Block::AIR; sx * sy * sz];
+    for z in 0..sz {
+        for x in 0..sx {
+            blocks[z * sx + x] = named(reg, "stone");
+        }
+    }
+    for &(x, z) in placed {
+        blocks[(sz + z) * sx + x] = named(reg, "framed_glass");
+    }
+    let world = World::new(1, 1, 1, 0, WorldGenMode::Flat { thickness: 0 });
+    let buf = ChunkBuf::from_blocks_local(ChunkCoord::new(0, 0, 0), sx, sy, sz, blocks);
+    let store = LightingStore::new(sx, sy, sz);
+    let light = LightGrid::compute_with_borders_buf(&buf, &store, reg);
+    build_chunk_wcc_cpu_buf_with_light(&buf, &light, &world, None, buf.coord, reg)
+        .expect("chunk mesh")
+        .0
+}
+
+// Top-face quads as (min x, min z, tile column, tile row), read back from positions and UVs.
+fn top_tiles(mb: &MeshBuild) -> Vec<(f32, f32, u8, u8)> {
+    let mut out = Vec::new();
+    for quad in 0..mb.pos.len() / 12 {
+        let verts = quad * 4..quad * 4 + 4;
+        if verts.clone().any(|v| mb.pos[v * 3 + 1] != 2.0) {
+            continue;
+        }
+        let min = |axis: usize| {
+            verts
+                .clone()
+                .map(|v| mb.pos[v * 3 + axis])
+                .fold(f32::MAX, f32::min)
+        };
+        let uv_min = |axis: usize| {
+            verts
+                .clone()
+                .map(|v| mb.uv[v * 2 + axis])
+                .fold(f32::MAX, f32::min)
+        };
+        let (u, v) = (uv_min(0), uv_min(1));
+        assert!(u >= 0.0 && v >= 0.0, "tiled UVs stay inside the sheet");
+        out.push((
+            min(0),
+            min(2),
+            (u * 4.0).round() as u8,
+            (v * 4.0).round() as u8,
+        ));
+    }
+    out.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    out
+}
+
+#[test]
+fn lone_pane_uses_the_framed_tile() {
+    let reg = load_registry();
+    let mid = reg.materials.get_id("framed_glass").unwrap();
+    assert!(reg.materials.connected(mid));
+    let mesh = panes(&reg, &[(2, 2)]);
+    assert_eq!(top_tiles(&mesh.parts[&mid]), vec![(2.0, 2.0, 0, 0)]);
+}
+
+#[test]
+fn neighbors_open_the_shared_edges() {
+    let reg = load_registry();
+    let mid = reg.materials.get_id("framed_glass").unwrap();
+    // An L: a row along +x, and one pane south (+z) of its east end.
+    let mesh = panes(&reg, &[(1, 2), (2, 2), (3, 2), (3, 3)]);
+    // On top faces u runs along +x and v along +z; tile n sits at (n % 4, n / 4).
+    let tile = |n: u8| (n % 4, n / 4);
+    let expect: Vec<_> = [
+        (1.0, 2.0, tile(2)),
+        (2.0, 2.0, tile(1 | 2)),
+        (3.0, 2.0, tile(1 | 4)),
+        (3.0, 3.0, tile(8)),
+    ]
+    .into_iter()
+    .map(|(x, z, (c, r))| (x, z, c, r))
+    .collect();
+    // One quad per block: connected faces don't merge across block borders.
+    assert_eq!(top_tiles(&mesh.parts[&mid]), expect);
+}
+
+#[test]
+fn unconnected_materials_keep_world_uvs() {
+    let reg = load_registry();
+    let stone = reg.materials.get_id("stone").unwrap();
+    assert!(!reg.materials.connected(stone));
+    let mesh = panes(&reg, &[]);
+    let mb = &mesh.parts[&stone];
+    // The floor's top merges into one quad spanning the chunk, repeating the texture.
+    let max_u = (0..mb.uv.len() / 2)
+        .map(|v| mb.uv[v * 2])
+        .fold(f32::MIN, f32::max);
+    assert_eq!(max_u, 6.0);
+}
@@ -0,0 +1,81 @@
+use geist_blocks::BlockRegistry;
+use geist_blocks::types::Block;
+use geist_chunk::ChunkBuf;
+use geist_lighting::{LightGrid, LightingStore};
+use geist_mesh_cpu::{
+    ChunkMeshCPU, DECORATION_JITTER, DecorationInstance, build_chunk_wcc_cpu_buf_with_light,
+};
+use geist_world::{ChunkCoord, World, WorldGenMode};
+
+fn load_registry() -> BlockRegistry {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let vox = root.join("../../assets/voxels");
+    BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml")).unwrap()
+}
+
+fn named(reg: &BlockRegistry, name: &str) -> Block {
+    Block {
+        id: reg.id_by_name(name).unwrap(),
+        state: 0,
+    }
+}
+
+// A stone floor at y = 0 with `plant` standing on it at (1, 1, 2) in chunk `cx`.
+fn meadow(reg: &BlockRegistry, cx: i32, plant: Option<&str>) -> ChunkMeshCPU {
+    let (sx, sy, sz) = (4, 4, 4);
+    let mut blocks = vec![Block::AIR; sx * sy * sz];
+    for z in 0..sz {
+        for x in 0..sx {
+            blocks[z * sx + x] = named(reg, "stone");
+        }
+    }
+    if let Some(plant) = plant {
+        blocks[(sz + 2) * sx + 1] = named(reg, plant);
+    }
+    let world = World::new(2, 1, 1, 0, WorldGenMode::Flat { thickness: 0 });
+    let buf = ChunkBuf::from_blocks_local(ChunkCoord::new(cx, 0, 0), sx, sy, sz, blocks);
+    let store = LightingStore::new(sx, sy, sz);
+    let light = LightGrid::compute_with_borders_buf(&buf, &store, reg);
+    build_chunk_wcc_cpu_buf_with_light(&buf, &light, &world, None, buf.coord, reg)
+        .expect("chunk mesh")
+        .0
+}
+
+fn triangles(m: &ChunkMeshCPU) -> usize {
+    m.parts.values().map(|p| p.idx.len() / 3).sum()
+}
+
+#[test]
+fn decorations_are_listed_instead_of_meshed() {
+    let reg = load_registry();
+    let bare = meadow(&reg, 1, None);
+    let grassy = meadow(&reg, 1, Some("short_grass"));
+    assert!(bare.decorations.is_empty());
+    assert_eq!(triangles(&grassy), triangles(&bare));
+
+    assert_eq!(grassy.decorations.len(), 1);
+    let d = grassy.decorations[0];
+    assert_eq!(d.block, named(&reg, "short_grass"));
+    // World cell (5, 1, 2): chunk 1 starts at x = 4.
+    assert!((d.pos.x - 5.5).abs() <= DECORATION_JITTER);
+    assert!((d.pos.z - 2.5).abs() <= DECORATION_JITTER);
+    assert_eq!(d.pos.y, 1.0);
+    assert!(d.sky > 0, "open sky above the plant");
+}
+
+#[test]
+fn placement_varies_by_cell_but_not_between_builds() {
+    let reg = load_registry();
+    let grass = named(&reg, "short_grass");
+    let a = DecorationInstance::at_cell(grass, 10, 4, -3, 255, 0);
+    assert_eq!(a, DecorationInstance::at_cell(grass, 10, 4, -3, 255, 0));
+    let cells: Vec<DecorationInstance> = (0..8)
+        .map(|i| DecorationInstance::at_cell(grass, i, 4, -3, 255, 0))
+        .collect();
+    assert!(cells.iter().any(|d| d.yaw_deg != cells[0].yaw_deg));
+    for (i, d) in cells.iter().enumerate() {
+        assert!((d.pos.x - (i as f32 + 0.5)).abs() <= DECORATION_JITTER);
+        assert!((0.8..=1.1).contains(&d.scale));
+        assert!((0.0..=360.0).contains(&d.yaw_deg));
+    }
+}
@@ -0,0 +1,109 @@
+use geist_blocks::BlockRegistry;
+use geist_blocks::types::Block;
+use geist_chunk::ChunkBuf;
+use geist_lighting::{LightGrid, LightingStore};
+use geist_mesh_cpu::{ChunkMeshCPU, ExportFormat, MeshExport, build_chunk_wcc_cpu_buf_with_light};
+use geist_world::{ChunkCoord, World, WorldGenMode};
+
+fn load_registry() -> BlockRegistry {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let vox = root.join("../../assets/voxels");
+    BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml")).unwrap()
+}
+
+// A 2x2x2 stone cube in the corner of chunk `cx`.
+fn cube_mesh(reg: &BlockRegistry, world: &World, cx: i32) -> ChunkMeshCPU {
+    let (sx, sy, sz) = (4, 4, 4);
+    let stone = reg.id_by_name("stone").unwrap_or(1);
+    let air = reg.id_by_name("air").unwrap_or(0);
+    let mut blocks = vec![Block { id: air, state: 0 }; sx * sy * sz];
+    for y in 0..2 {
+        for z in 0..2 {
+            for x in 0..2 {
+                blocks[(y * sz + z) * sx + x] = Block {
+                    id: stone,
+                    state: 0,
+                };
+            }
+        }
+    }
+    let buf = ChunkBuf::from_blocks_local(ChunkCoord::new(cx, 0, 0), sx, sy, sz, blocks);
+    let store = LightingStore::new(sx, sy, sz);
+    let light = LightGrid::compute_with_borders_buf(&buf, &store, reg);
+    build_chunk_wcc_cpu_buf_with_light(&buf, &light, world, None, buf.coord, reg)
+        .expect("chunk mesh")
+        .0
+}
+
+fn out_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("geist_export_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn export_merges_chunks_per_material() {
+    let reg = load_registry();
+    let world = World::new(2, 1, 1, 0, WorldGenMode::Flat { thickness: 0 });
+    let a = cube_mesh(&reg, &world, 0);
+    let b = cube_mesh(&reg, &world, 1);
+
+    let mut export = MeshExport::new();
+    export.add_chunk(&a);
+    export.add_chunk(&b);
+
+    let tris = |m: &ChunkMeshCPU| m.parts.values().map(|p| p.idx.len() / 3).sum::<usize>();
+    assert_eq!(export.triangle_count(), tris(&a) + tris(&b));
+    assert_eq!(export.parts().count(), a.parts.len());
+    for (_, part) in export.parts() {
+        let verts = part.vertex_count() as u32;
+        assert!(part.idx.iter().all(|&i| i < verts));
+        assert_eq!(part.col.len(), part.vertex_count() * 4);
+    }
+    // The second cube sits one chunk over.
+    let max_x = export
+        .parts()
+        .flat_map(|(_, p)| p.pos.chunks_exact(3).map(|v| v[0]))
+        .fold(f32::MIN, f32::max);
+    assert_eq!(max_x, 6.0);
+}
+
+#[test]
+fn export_writes_obj_and_gltf_with_companions() {
+    let reg = load_registry();
+    let world = World::new(1, 1, 1, 0, WorldGenMode::Flat { thickness: 0 });
+    let mut export = MeshExport::new();
+    export.add_chunk(&cube_mesh(&reg, &world, 0));
+    let dir = out_dir("formats");
+
+    let obj = dir.join("world.obj");
+    assert_eq!(ExportFormat::from_path(&obj), Some(ExportFormat::Obj));
+    let written = export
+        .write(&obj, ExportFormat::Obj, &reg.materials)
+        .unwrap();
+    assert_eq!(written, vec![obj.clone(), dir.join("world.mtl")]);
+    let text = std::fs::read_to_string(&obj).unwrap();
+    assert!(text.contains("mtllib world.mtl"));
+    assert_eq!(
+        text.lines().filter(|l| l.starts_with("f ")).count(),
+        export.triangle_count()
+    );
+    let mtl = std::fs::read_to_string(dir.join("world.mtl")).unwrap();
+    assert!(mtl.contains("newmtl "));
+    assert!(mtl.contains("map_Kd "));
+
+    let gltf = dir.join("world.gltf");
+    assert_eq!(ExportFormat::from_path(&gltf), Some(ExportFormat::Gltf));
+    let written = export
+        .write(&gltf, ExportFormat::Gltf, &reg.materials)
+        .unwrap();
+    assert_eq!(written, vec![gltf.clone(), dir.join("world.bin")]);
+    let json = std::fs::read_to_string(&gltf).unwrap();
+    let bin_len = std::fs::metadata(dir.join("world.bin")).unwrap().len();
+    assert!(json.contains("\"version\":\"2.0\""));
+    assert!(json.contains(&format!("\"uri\":\"world.bin\",\"byteLength\":{}", bin_len)));
+    assert!(json.contains("\"baseColorTexture\""));
+
+    assert_eq!(ExportFormat::from_path(&dir.join("world.fbx")), None);
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,97 @@
+use geist_blocks::BlockRegistry;
+use geist_blocks::types::Block;
+use geist_chunk::ChunkBuf;
+use geist_lighting::{LightGrid, LightingStore};
+use geist_mesh_cpu::build_chunk_wcc_cpu_buf_with_light;
+use geist_world::{ChunkCoord, World, WorldGenMode};
+
+fn load_registry() -> BlockRegistry {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let vox = root.join("../../assets/voxels");
+    BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml")).unwrap()
+}
+
+const N: usize = 8;
+
+fn set(blocks: &mut [Block], reg: &BlockRegistry, (x, y, z): (usize, usize, usize), name: &str) {
+    let id = reg.id_by_name(name).expect(name);
+    blocks[(y * N + z) * N + x] = Block { id, state: 0 };
+}
+
+/// Stone shell spanning 1..=6 on every axis around a 4x4x4 air pocket.
+fn hollow_box(reg: &BlockRegistry) -> Vec<Block> {
+    let mut blocks = vec![Block::AIR; N * N * N];
+    for z in 1..=6 {
+        for y in 1..=6 {
+            for x in 1..=6 {
+                let shell = [x, y, z].iter().any(|&c| c == 1 || c == 6);
+                if shell {
+                    set(&mut blocks, reg, (x, y, z), "stone");
+                }
+            }
+        }
+    }
+    blocks
+}
+
+/// Total area of the mesh's quads.
+fn mesh_area(reg: &BlockRegistry, blocks: &[Block]) -> f32 {
+    let buf = ChunkBuf::from_blocks_local(ChunkCoord::new(0, 0, 0), N, N, N, blocks.to_vec());
+    let store = LightingStore::new(N, N, N);
+    let light = LightGrid::compute_with_borders_buf(&buf, &store, reg);
+    let world = World::new(1, 1, 1, 0, WorldGenMode::Flat { thickness: 0 });
+    let (cpu, _) = build_chunk_wcc_cpu_buf_with_light(&buf, &light, &world, None, buf.coord, reg)
+        .expect("mesh generation");
+    let mut area = 0.0;
+    for part in cpu.parts.values() {
+        for tri in part.idx.chunks_exact(3) {
+            let p = |i: u16| {
+                let i = i as usize * 3;
+                [part.pos[i], part.pos[i + 1], part.pos[i + 2]]
+            };
+            let (a, b, c) = (p(tri[0]), p(tri[1]), p(tri[2]));
+            let ab = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+            let ac = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+            let cross = [
+                ab[1] * ac[2] - ab[2] * ac[1],
+                ab[2] * ac[0] - ab[0] * ac[2],
+                ab[0] * ac[1] - ab[1] * ac[0],
+            ];
+            area += 0.5 * (cross[0] * cross[0] + cross[1] * cross[1] + cross[2] * cross[2]).sqrt();
+        }
+    }
+    area
+}
+
+// The culling switch is process-wide, so every case lives in one test.
+#[test]
+fn sealed_pockets_lose_their_faces_until_opened() {
+    let reg = load_registry();
+    let sealed = hollow_box(&reg);
+    let mut opened = sealed.clone();
+    set(&mut opened, &reg, (1, 3, 3), "air");
+    let mut windowed = sealed.clone();
+    set(&mut windowed, &reg, (1, 3, 3), "blue_stained_glass");
+    let mut flooded = sealed.clone();
+    set(&mut flooded, &reg, (3, 3, 3), "water");
+
+    let cases = [&sealed, &opened, &windowed, &flooded];
+    let plain: Vec<f32> = cases.iter().map(|b| mesh_area(&reg, b)).collect();
+    geist_mesh_cpu::set_sealed_pocket_culling(true);
+    let culled: Vec<f32> = cases.iter().map(|b| mesh_area(&reg, b)).collect();
+    geist_mesh_cpu::set_sealed_pocket_culling(false);
+
+    // Outer shell 6 * 36, pocket lining 6 * 16.
+    assert_eq!(plain[0], 312.0);
+    assert_eq!(
+        culled[0], 216.0,
+        "only the outside of a sealed box is meshed"
+    );
+    assert!(!geist_mesh_cpu::sealed_pocket_culling());
+    assert_eq!(mesh_area(&reg, &sealed), plain[0]);
+    // Knocking a hole in the wall brings the whole lining back.
+    assert_eq!(culled[1], plain[1]);
+    // So do a window and water inside the pocket.
+    assert_eq!(culled[2], plain[2]);
+    assert_eq!(culled[3], plain[3]);
+}
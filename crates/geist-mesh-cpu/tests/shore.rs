@@ -0,0 +1,112 @@
+use geist_blocks::BlockRegistry;
+use geist_blocks::types::Block;
+use geist_chunk::ChunkBuf;
+use geist_lighting::{LightGrid, LightingStore};
+use geist_mesh_cpu::{ChunkMeshCPU, WATER_OPEN_CAP, build_chunk_wcc_cpu_buf_with_light};
+use geist_world::{ChunkCoord, World, WorldGenMode};
+
+fn load_registry() -> BlockRegistry {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let vox = root.join("../../assets/voxels");
+    BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml")).unwrap()
+}
+
+fn named(reg: &BlockRegistry, name: &str) -> Block {
+    Block {
+        id: reg.id_by_name(name).unwrap(),
+        state: 0,
+    }
+}
+
+// An 8x8 stone basin one block thick, filled with `depth` layers of water from y = 1.
+fn pool(reg: &BlockRegistry, depth: usize) -> ChunkMeshCPU {
+    let (sx, sy, sz) = (8, depth + 2, 8);
+    let mut blocks = vec![Block::AIR; sx * sy * sz];
+    for y in 0..=depth {
+        for z in 0..sz {
+            for x in 0..sx {
+                let wall = y == 0 || x == 0 || z == 0 || x == sx - 1 || z == sz - 1;
+                let name = if wall { "stone" } else { "water" };
+                blocks[(y * sz + z) * sx + x] = named(reg, name);
+            }
+        }
+    }
+    let world = World::new(1, 1, 1, 0, WorldGenMode::Flat { thickness: 0 });
+    let buf = ChunkBuf::from_blocks_local(ChunkCoord::new(0, 0, 0), sx, sy, sz, blocks);
+    let store = LightingStore::new(sx, sy, sz);
+    let light = LightGrid::compute_with_borders_buf(&buf, &store, reg);
+    build_chunk_wcc_cpu_buf_with_light(&buf, &light, &world, None, buf.coord, reg)
+        .expect("chunk mesh")
+        .0
+}
+
+// Alpha of every water-top vertex at `(x, z)`; the surface sits at y = depth + 1.
+fn surface_alpha(
+    mesh: &ChunkMeshCPU,
+    reg: &BlockRegistry,
+    depth: usize,
+    x: f32,
+    z: f32,
+) -> Vec<u8> {
+    let mb = &mesh.parts[&reg.materials.get_id("water").unwrap()];
+    (0..mb.pos.len() / 3)
+        .filter(|&v| {
+            let p = &mb.pos[v * 3..v * 3 + 3];
+            p[1] == (depth + 1) as f32 && p[0] == x && p[2] == z && mb.norm[v * 3 + 1] > 0.5
+        })
+        .map(|v| mb.col[v * 4 + 3])
+        .collect()
+}
+
+fn alpha_of(open: u8) -> u8 {
+    (open as u32 * 255 / WATER_OPEN_CAP as u32) as u8
+}
+
+#[test]
+fn banks_read_zero_and_the_middle_reads_depth() {
+    let reg = load_registry();
+    let mesh = pool(&reg, 2);
+    let at = |x, z| surface_alpha(&mesh, &reg, 2, x, z);
+    // Along the walls.
+    assert!(!at(1.0, 4.0).is_empty());
+    assert!(at(1.0, 4.0).iter().all(|&a| a == 0));
+    assert!(at(4.0, 7.0).iter().all(|&a| a == 0));
+    // Between the first ring of water and the next, bank-side openness wins.
+    assert!(!at(2.0, 4.0).is_empty());
+    assert!(at(2.0, 4.0).iter().all(|&a| a == alpha_of(1)));
+    // The middle is two blocks deep with no bank alongside.
+    assert!(!at(2.5, 2.5).is_empty());
+    assert!(at(2.5, 2.5).iter().all(|&a| a == alpha_of(2)));
+}
+
+#[test]
+fn depth_caps_and_even_areas_still_merge() {
+    let reg = load_registry();
+    let depth = WATER_OPEN_CAP as usize + 2;
+    let deep = pool(&reg, depth);
+    let at = |x, z| surface_alpha(&deep, &reg, depth, x, z);
+    assert!(!at(2.5, 2.5).is_empty());
+    assert!(at(2.5, 2.5).iter().all(|&a| a == 255));
+    // Corners read the blocks they touch, so the even middle runs from the middle of the
+    // second ring to the middle of the fifth in one quad.
+    let mb = &deep.parts[&reg.materials.get_id("water").unwrap()];
+    let middle = (0..mb.pos.len() / 12).any(|q| {
+        let corner = |axis: usize, pick: fn(f32, f32) -> f32, init: f32| {
+            (0..4)
+                .map(|v| mb.pos[q * 12 + v * 3 + axis])
+                .fold(init, pick)
+        };
+        let lo = (corner(0, f32::min, f32::MAX), corner(2, f32::min, f32::MAX));
+        let hi = (corner(0, f32::max, f32::MIN), corner(2, f32::max, f32::MIN));
+        mb.pos[q * 12 + 1] == (depth + 1) as f32 && lo == (2.5, 2.5) && hi == (5.5, 5.5)
+    });
+    assert!(middle);
+}
+
+#[test]
+fn other_materials_stay_opaque() {
+    let reg = load_registry();
+    let mesh = pool(&reg, 2);
+    let stone = &mesh.parts[&reg.materials.get_id("stone").unwrap()];
+    assert!(stone.col.chunks_exact(4).all(|c| c[3] == 255));
+}
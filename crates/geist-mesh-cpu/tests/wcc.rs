@@ -1,7 +1,8 @@
 use hashbrown::HashMap;
 
 use geist_blocks::BlockRegistry;
-use geist_blocks::types::Block;
+use geist_blocks::RenderPass;
+use geist_blocks::types::{Block, MaterialId};
 use geist_chunk::ChunkBuf;
 use geist_lighting::{LightGrid, LightingStore};
 use geist_mesh_cpu::{ChunkMeshCPU, ParityMesher, build_chunk_wcc_cpu_buf_with_light};
@@ -186,6 +187,7 @@ fn parity_area_random_full_cubes_s1() {
             },
         },
         parts: builds,
+        decorations: Vec::new(),
     };
     let area_mesh = tri_area_sum(&cpu);
     let solid_fn = |x: usize, y: usize, z: usize| blocks[(y * sz + z) * sx + x].id == stone;
@@ -328,6 +330,7 @@ fn seam_stitch_no_faces_on_shared_plane_s1() {
             },
         },
         parts: pa,
+        decorations: Vec::new(),
     };
     let cpu_b = ChunkMeshCPU {
         coord: ChunkCoord::new(1, 0, 0),
@@ -344,6 +347,7 @@ fn seam_stitch_no_faces_on_shared_plane_s1() {
             },
         },
         parts: pb,
+        decorations: Vec::new(),
     };
     let seam_x = sx as f32;
     let eps = 1e-6f32;
@@ -485,6 +489,7 @@ fn seam_vertical_no_faces_on_shared_plane_s1() {
             },
         },
         parts: builds_lo,
+        decorations: Vec::new(),
     };
     let cpu_hi = ChunkMeshCPU {
         coord: buf_hi.coord,
@@ -501,6 +506,7 @@ fn seam_vertical_no_faces_on_shared_plane_s1() {
             },
         },
         parts: builds_hi,
+        decorations: Vec::new(),
     };
 
     let seam_y = sy as f32;
@@ -643,3 +649,275 @@ fn per_face_quads_triangle_count_on_slab() {
         diff
     );
 }
+
+#[test]
+fn slope_emits_sloped_surface_with_outward_winding() {
+    let (sx, sy, sz) = (3, 3, 3);
+    let reg = load_registry();
+    let air = reg.id_by_name("air").unwrap_or(0);
+    let props: std::collections::HashMap<String, String> =
+        [("facing".to_string(), "north".to_string())].into();
+    let slope = reg
+        .make_block_by_name("slope", Some(&props))
+        .expect("slope block");
+    let mut blocks = vec![Block { id: air, state: 0 }; sx * sy * sz];
+    blocks[(sz + 1) * sx + 1] = slope;
+    let buf = make_buf(0, 0, sx, sy, sz, blocks);
+    let store = LightingStore::new(sx, sy, sz);
+    let light = LightGrid::compute_with_borders_buf(&buf, &store, &reg);
+    let world = World::new(1, 1, 1, 0, WorldGenMode::Flat { thickness: 0 });
+    let (cpu, _) = build_chunk_wcc_cpu_buf_with_light(&buf, &light, &world, None, buf.coord, &reg)
+        .expect("mesh generation");
+
+    // Base + north wall (1 each), two side triangles (0.5 each), sloped face (sqrt 2).
+    let expected = 3.0 + std::f32::consts::SQRT_2;
+    let area = tri_area_sum(&cpu);
+    assert!(
+        (area - expected).abs() < 1e-3,
+        "area {} expected {}",
+        area,
+        expected
+    );
+
+    let mut sloped = 0;
+    for part in cpu.parts.values() {
+        for t in part.idx.chunks(3) {
+            let v = |i: u16| {
+                let o = i as usize * 3;
+                [part.pos[o], part.pos[o + 1], part.pos[o + 2]]
+            };
+            let (a, b, c) = (v(t[0]), v(t[1]), v(t[2]));
+            let ab = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+            let ac = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+            let cross = [
+                ab[1] * ac[2] - ab[2] * ac[1],
+                ab[2] * ac[0] - ab[0] * ac[2],
+                ab[0] * ac[1] - ab[1] * ac[0],
+            ];
+            let o = t[0] as usize * 3;
+            let n = [part.norm[o], part.norm[o + 1], part.norm[o + 2]];
+            let dot = cross[0] * n[0] + cross[1] * n[1] + cross[2] * n[2];
+            assert!(dot > 0.0, "triangle wound against its normal");
+            if n[1] > 0.1 && n[2] > 0.1 {
+                sloped += 1;
+            }
+        }
+    }
+    assert_eq!(sloped, 2, "sloped face faces up and south");
+}
+
+#[test]
+fn ambient_occlusion_darkens_corners_against_walls() {
+    let (sx, sy, sz) = (4, 4, 4);
+    let reg = load_registry();
+    let stone = reg.id_by_name("stone").unwrap_or(1);
+    let air = reg.id_by_name("air").unwrap_or(0);
+    let mut blocks = vec![Block { id: air, state: 0 }; sx * sy * sz];
+    // A floor block with a wall block standing on its +X neighbour.
+    for (x, y, z) in [(1, 0, 1), (2, 0, 1), (2, 1, 1)] {
+        blocks[(y * sz + z) * sx + x] = Block {
+            id: stone,
+            state: 0,
+        };
+    }
+    let buf = make_buf(0, 0, sx, sy, sz, blocks);
+    let store = LightingStore::new(sx, sy, sz);
+    let light = LightGrid::compute_with_borders_buf(&buf, &store, &reg);
+    let world = World::new(1, 1, 1, 0, WorldGenMode::Flat { thickness: 0 });
+    let floor_top_shades = || {
+        let (cpu, _) =
+            build_chunk_wcc_cpu_buf_with_light(&buf, &light, &world, None, buf.coord, &reg)
+                .expect("mesh generation");
+        let mut out = Vec::new();
+        for part in cpu.parts.values() {
+            for (vi, p) in part.pos.chunks_exact(3).enumerate() {
+                let up = part.norm[vi * 3 + 1] > 0.5;
+                if up && p[1] == 1.0 && (1.0..=2.0).contains(&p[0]) && (1.0..=2.0).contains(&p[2]) {
+                    out.push((p[0], part.col[vi * 4]));
+                }
+            }
+        }
+        out
+    };
+
+    let shades = floor_top_shades();
+    assert!(!shades.is_empty());
+    for &(x, shade) in &shades {
+        if x == 2.0 {
+            assert!(shade < 255, "corner against the wall should be shaded");
+        } else {
+            assert_eq!(shade, 255, "open corner at x={} should be unshaded", x);
+        }
+    }
+
+    geist_mesh_cpu::set_ao_strength(0.0);
+    let flat = floor_top_shades();
+    geist_mesh_cpu::set_ao_strength(geist_mesh_cpu::DEFAULT_AO_STRENGTH);
+    assert!(flat.iter().all(|&(_, shade)| shade == 255));
+}
+
+#[test]
+fn water_and_glass_parts_are_translucent() {
+    let (sx, sy, sz) = (4, 4, 4);
+    let reg = load_registry();
+    let air = reg.id_by_name("air").unwrap_or(0);
+    let mut blocks = vec![Block { id: air, state: 0 }; sx * sy * sz];
+    for (x, name) in [(0, "stone"), (1, "water"), (2, "blue_stained_glass")] {
+        let id = reg.id_by_name(name).expect(name);
+        blocks[(sz + 1) * sx + x] = Block { id, state: 0 };
+    }
+    let buf = make_buf(0, 0, sx, sy, sz, blocks);
+    let store = LightingStore::new(sx, sy, sz);
+    let light = LightGrid::compute_with_borders_buf(&buf, &store, &reg);
+    let world = World::new(1, 1, 1, 0, WorldGenMode::Flat { thickness: 0 });
+    let (cpu, _) = build_chunk_wcc_cpu_buf_with_light(&buf, &light, &world, None, buf.coord, &reg)
+        .expect("mesh generation");
+
+    let key = |mid: MaterialId| reg.materials.get(mid).map(|m| m.key.clone()).unwrap();
+    let mut translucent: Vec<String> = cpu
+        .parts_in(RenderPass::Translucent, &reg.materials)
+        .map(|(mid, _)| key(mid))
+        .collect();
+    translucent.sort();
+    assert_eq!(translucent, ["blue_stained_glass", "water"]);
+    let opaque = cpu.parts_in(RenderPass::Opaque, &reg.materials).count();
+    assert_eq!(opaque + translucent.len(), cpu.parts.len());
+    assert!(opaque > 0);
+}
+
+#[test]
+fn smoothed_normals_agree_across_chunk_seams() {
+    let (sx, sy, sz) = (4, 4, 4);
+    let reg = load_registry();
+    let stone = reg.id_by_name("stone").expect("stone");
+    let air = reg.id_by_name("air").unwrap_or(0);
+    assert!(reg.materials.smooth(reg.materials.get_id("stone").unwrap()));
+    // Ground one block high for z < 2 and two blocks high beyond, running across the seam
+    // between chunks x=0 and x=1.
+    let height = |z: usize| if z < 2 { 1 } else { 2 };
+    let mut blocks = vec![Block { id: air, state: 0 }; sx * sy * sz];
+    for z in 0..sz {
+        for y in 0..height(z) {
+            for x in 0..sx {
+                blocks[(y * sz + z) * sx + x] = Block {
+                    id: stone,
+                    state: 0,
+                };
+            }
+        }
+    }
+    let bufs = [
+        make_buf(0, 0, sx, sy, sz, blocks.clone()),
+        make_buf(1, 0, sx, sy, sz, blocks),
+    ];
+    // Each chunk sees the other's blocks through the edit overlay, as it would see
+    // streamed terrain through the world.
+    let mut edits: HashMap<(i32, i32, i32), Block> = HashMap::new();
+    for buf in &bufs {
+        let bx = buf.coord.cx * sx as i32;
+        for z in 0..sz {
+            for y in 0..height(z) {
+                for x in 0..sx {
+                    let b = Block {
+                        id: stone,
+                        state: 0,
+                    };
+                    edits.insert((bx + x as i32, y as i32, z as i32), b);
+                }
+            }
+        }
+    }
+    let store = LightingStore::new(sx, sy, sz);
+    let world = World::new(2, 1, 1, 0, WorldGenMode::Flat { thickness: 0 });
+    let up_normals = |buf: &ChunkBuf| {
+        let light = LightGrid::compute_with_borders_buf(buf, &store, &reg);
+        let (cpu, _) =
+            build_chunk_wcc_cpu_buf_with_light(buf, &light, &world, Some(&edits), buf.coord, &reg)
+                .expect("mesh generation");
+        let mut out: HashMap<(i32, i32, i32), [f32; 3]> = HashMap::new();
+        for part in cpu.parts.values() {
+            for (p, n) in part.pos.chunks_exact(3).zip(part.norm.chunks_exact(3)) {
+                // Only block corners are welded; skip vertices inside a quad's span.
+                let on_corner = p.iter().all(|v| v.fract() == 0.0);
+                if on_corner && n[1] > n[0].abs() && n[1] > n[2].abs() {
+                    let key = (p[0] as i32, p[1] as i32, p[2] as i32);
+                    out.insert(key, [n[0], n[1], n[2]]);
+                }
+            }
+        }
+        out
+    };
+
+    geist_mesh_cpu::set_normal_smoothing(true);
+    let a = up_normals(&bufs[0]);
+    let b = up_normals(&bufs[1]);
+    geist_mesh_cpu::set_normal_smoothing(false);
+    let crisp = up_normals(&bufs[0]);
+
+    // Top edge of the rise leans out over the lower ground (-Z), but only when smoothing.
+    let edge = a[&(4, 2, 2)];
+    assert!(edge[2] < -0.1 && edge[1] > 0.5, "edge normal {:?}", edge);
+    assert!(
+        edge[0].abs() < 1e-6,
+        "the seam neighbour keeps x level: {:?}",
+        edge
+    );
+    assert_eq!(crisp[&(4, 2, 2)], [0.0, 1.0, 0.0]);
+    // Corners on the shared plane x=4 match on both sides of the seam.
+    let mut shared = 0;
+    for (pos, n) in a.iter().filter(|(p, _)| p.0 == 4) {
+        let other = b.get(pos).expect("seam vertex in both chunks");
+        for (u, v) in n.iter().zip(other) {
+            assert!((u - v).abs() < 1e-6, "{:?}: {:?} vs {:?}", pos, n, other);
+        }
+        shared += 1;
+    }
+    assert!(shared >= 4);
+}
+
+#[test]
+fn flowing_water_slopes_down_from_its_source() {
+    let (sx, sy, sz) = (4, 3, 3);
+    let reg = load_registry();
+    let air = reg.id_by_name("air").unwrap_or(0);
+    let stone = reg.id_by_name("stone").unwrap_or(1);
+    let water = reg.get(reg.id_by_name("water").unwrap()).unwrap();
+    let mut blocks = vec![Block { id: air, state: 0 }; sx * sy * sz];
+    for x in 0..sx {
+        blocks[sz * sx + x] = Block {
+            id: stone,
+            state: 0,
+        };
+    }
+    // Source, then flow at levels 1 and 2, on a floor at y = 0.
+    for level in 0..3u8 {
+        let x = level as usize;
+        blocks[(sz + 1) * sx + x] = Block {
+            id: water.id,
+            state: water.fluid_state(level),
+        };
+    }
+    let buf = make_buf(0, 0, sx, sy, sz, blocks);
+    let store = LightingStore::new(sx, sy, sz);
+    let light = LightGrid::compute_with_borders_buf(&buf, &store, &reg);
+    let world = World::new(1, 1, 1, 0, WorldGenMode::Flat { thickness: 0 });
+    let (cpu, _) = build_chunk_wcc_cpu_buf_with_light(&buf, &light, &world, None, buf.coord, &reg)
+        .expect("mesh generation");
+
+    let mut top_at_x = std::collections::BTreeMap::new();
+    for (mid, part) in &cpu.parts {
+        if reg.materials.get(*mid).map(|m| m.key.as_str()) != Some("water") {
+            continue;
+        }
+        for (vi, p) in part.pos.chunks_exact(3).enumerate() {
+            if part.norm[vi * 3 + 1] > 0.1 && p[1] > 1.0 {
+                let y = top_at_x.entry(p[0] as i32).or_insert(p[1]);
+                *y = f32::min(*y, p[1]);
+            }
+        }
+    }
+    // The flow's near edge sits at its source, its far edge at level 2's fill height.
+    assert_eq!(top_at_x.get(&3), Some(&1.75));
+    assert_eq!(top_at_x.get(&2), Some(&1.8125));
+    assert_eq!(top_at_x.get(&0), Some(&2.0));
+}
@@ -0,0 +1,185 @@
+//! Grid traversal: the voxels a ray passes through, in order (Amanatides & Woo DDA).
+
+use geist_geom::{IVec3, Vec3};
+
+/// A ray from `origin` along `dir`, ending `max_dist` voxels out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub dir: Vec3,
+    pub max_dist: f32,
+}
+
+/// The voxel a ray stopped in and how it got there, in the space the ray was cast in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    /// The hit voxel.
+    pub bx: i32,
+    pub by: i32,
+    pub bz: i32,
+    /// The voxel the ray came from; where a block placed against the hit face goes. Equal
+    /// to the hit voxel when the ray starts inside it.
+    pub px: i32,
+    pub py: i32,
+    pub pz: i32,
+    /// Outward normal of the face the ray entered through; zero when it starts inside.
+    pub nx: i32,
+    pub ny: i32,
+    pub nz: i32,
+    /// Distance along the ray to where it enters the hit voxel.
+    pub dist: f32,
+    /// Where the ray enters the hit voxel. Partial shapes such as slopes are hit further
+    /// in, but still report their cell boundary here.
+    pub enter: Vec3,
+}
+
+impl RayHit {
+    pub fn cell(&self) -> IVec3 {
+        IVec3::new(self.bx, self.by, self.bz)
+    }
+
+    pub fn before(&self) -> IVec3 {
+        IVec3::new(self.px, self.py, self.pz)
+    }
+
+    pub fn normal(&self) -> IVec3 {
+        IVec3::new(self.nx, self.ny, self.nz)
+    }
+}
+
+#[inline]
+fn inv_or_max(v: f32) -> f32 {
+    if v.abs() < 1e-8 {
+        f32::MAX
+    } else {
+        1.0 / v.abs()
+    }
+}
+
+#[inline]
+fn step_of(v: f32) -> i32 {
+    if v > 0.0 {
+        1
+    } else if v < 0.0 {
+        -1
+    } else {
+        0
+    }
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, dir: Vec3, max_dist: f32) -> Self {
+        Self {
+            origin,
+            dir,
+            max_dist,
+        }
+    }
+
+    /// Point `t` voxels along the normalized direction.
+    pub fn at(&self, t: f32) -> Vec3 {
+        self.origin + self.dir.normalized() * t
+    }
+
+    /// Walks the voxels along the ray until `hits` accepts one. `hits` receives where the
+    /// ray enters and leaves each voxel in cell-local coordinates, so partial shapes such
+    /// as slopes can let rays pass over their empty part.
+    pub fn walk<F>(&self, mut hits: F) -> Option<RayHit>
+    where
+        F: FnMut(i32, i32, i32, [f32; 3], [f32; 3]) -> bool,
+    {
+        let origin = self.origin;
+        let len = self.dir.length();
+        if len < 1e-6 || self.max_dist < 0.0 {
+            return None;
+        }
+        let d = self.dir * (1.0 / len);
+        let max_dist = self.max_dist;
+
+        let mut vx = origin.x.floor() as i32;
+        let mut vy = origin.y.floor() as i32;
+        let mut vz = origin.z.floor() as i32;
+        let (stepx, stepy, stepz) = (step_of(d.x), step_of(d.y), step_of(d.z));
+
+        let invx = inv_or_max(d.x);
+        let invy = inv_or_max(d.y);
+        let invz = inv_or_max(d.z);
+        let tdx = if stepx == 0 { f32::MAX } else { invx };
+        let tdy = if stepy == 0 { f32::MAX } else { invy };
+        let tdz = if stepz == 0 { f32::MAX } else { invz };
+
+        let fx = origin.x - origin.x.floor();
+        let fy = origin.y - origin.y.floor();
+        let fz = origin.z - origin.z.floor();
+        let first = |step: i32, f: f32, inv: f32| match step {
+            1 => (1.0 - f) * inv,
+            -1 => f * inv,
+            _ => f32::MAX,
+        };
+        let mut tmx = first(stepx, fx, invx);
+        let mut tmy = first(stepy, fy, invy);
+        let mut tmz = first(stepz, fz, invz);
+
+        let mut prevx = vx;
+        let mut prevy = vy;
+        let mut prevz = vz;
+        let mut t = 0.0f32;
+
+        // Each voxel boundary crossed advances one axis, and a ray of length L crosses at
+        // most L boundaries per axis.
+        let max_steps = (max_dist * 3.0).ceil() as usize + 3;
+        for _ in 0..max_steps {
+            if t > max_dist {
+                break;
+            }
+            let t_exit = tmx.min(tmy).min(tmz).min(max_dist);
+            let local = |t: f32| {
+                [
+                    origin.x + d.x * t - vx as f32,
+                    origin.y + d.y * t - vy as f32,
+                    origin.z + d.z * t - vz as f32,
+                ]
+            };
+            if hits(vx, vy, vz, local(t), local(t_exit)) {
+                // The face entered is the one opposite the step from the previous voxel.
+                return Some(RayHit {
+                    bx: vx,
+                    by: vy,
+                    bz: vz,
+                    px: prevx,
+                    py: prevy,
+                    pz: prevz,
+                    nx: prevx - vx,
+                    ny: prevy - vy,
+                    nz: prevz - vz,
+                    dist: t,
+                    enter: origin + d * t,
+                });
+            }
+            prevx = vx;
+            prevy = vy;
+            prevz = vz;
+            // Step through smallest tMax
+            if tmx < tmy {
+                if tmx < tmz {
+                    vx += stepx;
+                    t = tmx;
+                    tmx += tdx;
+                } else {
+                    vz += stepz;
+                    t = tmz;
+                    tmz += tdz;
+                }
+            } else if tmy < tmz {
+                vy += stepy;
+                t = tmy;
+                tmy += tdy;
+            } else {
+                vz += stepz;
+                t = tmz;
+                tmz += tdz;
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,123 @@
+//! Voxel raycasting against the world and posed structures.
+//!
+//! [`Ray::walk`] is the grid traversal; the functions here put block shapes on top of it:
+//! the world through any block sampler (loaded chunks, edits, the generator), and
+//! structures by casting the ray in their local space. Results carry the hit cell, the
+//! cell before it, the face normal and where the ray entered.
+#![forbid(unsafe_code)]
+
+use geist_blocks::BlockRegistry;
+use geist_blocks::types::Block;
+use geist_edit::EditStore;
+use geist_structures::{Structure, StructureId};
+use geist_world::World;
+
+mod dda;
+
+pub use dda::{Ray, RayHit};
+
+/// Whether a ray crossing voxel `b` from `enter` to `exit` (cell-local) hits it: solid
+/// blocks and decorations count as whole cells except slopes, which are only hit where the
+/// wedge is.
+pub fn ray_hits_block(reg: &BlockRegistry, b: Block, enter: [f32; 3], exit: [f32; 3]) -> bool {
+    let Some(ty) = reg.get(b.id) else {
+        return false;
+    };
+    if !ty.is_solid(b.state) && !ty.is_decoration() {
+        return false;
+    }
+    match ty.variant(b.state).slope {
+        Some(slope) => slope.segment_intersects(enter, exit),
+        None => true,
+    }
+}
+
+/// First solid block along `ray`, reading world blocks through `sample`.
+pub fn raycast_world(
+    reg: &BlockRegistry,
+    ray: &Ray,
+    mut sample: impl FnMut(i32, i32, i32) -> Block,
+) -> Option<RayHit> {
+    ray.walk(|x, y, z, enter, exit| ray_hits_block(reg, sample(x, y, z), enter, exit))
+}
+
+/// [`raycast_world`] over the generated terrain with `edits` on top, for callers without
+/// loaded chunks such as headless tools.
+pub fn raycast_terrain(
+    world: &World,
+    edits: &EditStore,
+    reg: &BlockRegistry,
+    ray: &Ray,
+) -> Option<RayHit> {
+    let mut ctx = world.make_gen_ctx();
+    raycast_world(reg, ray, |x, y, z| {
+        edits
+            .get(x, y, z)
+            .unwrap_or_else(|| world.block_at_runtime_with(reg, &mut ctx, x, y, z))
+    })
+}
+
+/// First solid cell of `st` along a world-space `ray`. The hit cells and entry point are
+/// structure-local; `dist` is still measured along the world ray.
+pub fn raycast_structure(st: &Structure, reg: &BlockRegistry, ray: &Ray) -> Option<RayHit> {
+    let scale = st.pose.safe_scale();
+    let local = Ray::new(
+        st.pose.world_to_local(ray.origin),
+        st.pose.rotate_inv(ray.dir),
+        ray.max_dist / scale,
+    );
+    let mut hit = local.walk(|lx, ly, lz, enter, exit| {
+        st.block_local(lx, ly, lz)
+            .is_some_and(|b| ray_hits_block(reg, b, enter, exit))
+    })?;
+    hit.dist *= scale;
+    Some(hit)
+}
+
+/// Nearest structure hit along `ray` among `structures`.
+pub fn raycast_structures<'a>(
+    structures: impl IntoIterator<Item = &'a Structure>,
+    reg: &BlockRegistry,
+    ray: &Ray,
+) -> Option<(StructureId, RayHit)> {
+    let mut best: Option<(StructureId, RayHit)> = None;
+    for st in structures {
+        if let Some(hit) = raycast_structure(st, reg, ray)
+            && best.is_none_or(|(_, b)| hit.dist < b.dist)
+        {
+            best = Some((st.id, hit));
+        }
+    }
+    best
+}
+
+/// What a ray hit first: a world block or a structure cell.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SceneHit {
+    World(RayHit),
+    Structure { id: StructureId, hit: RayHit },
+}
+
+impl SceneHit {
+    pub fn dist(&self) -> f32 {
+        match self {
+            SceneHit::World(hit) | SceneHit::Structure { hit, .. } => hit.dist,
+        }
+    }
+}
+
+/// Nearest of the world hit through `sample` and the structure hits along `ray`.
+pub fn raycast_scene<'a>(
+    reg: &BlockRegistry,
+    ray: &Ray,
+    sample: impl FnMut(i32, i32, i32) -> Block,
+    structures: impl IntoIterator<Item = &'a Structure>,
+) -> Option<SceneHit> {
+    let world = raycast_world(reg, ray, sample).map(SceneHit::World);
+    let structure =
+        raycast_structures(structures, reg, ray).map(|(id, hit)| SceneHit::Structure { id, hit });
+    match (world, structure) {
+        (Some(w), Some(s)) => Some(if s.dist() < w.dist() { s } else { w }),
+        (w, s) => w.or(s),
+    }
+}
@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use geist_blocks::BlockRegistry;
+use geist_blocks::types::Block;
+use geist_edit::EditStore;
+use geist_geom::{IVec3, Vec3};
+use geist_raycast::{
+    Ray, SceneHit, raycast_scene, raycast_structure, raycast_terrain, raycast_world,
+};
+use geist_structures::{Pose, Structure};
+use geist_world::{World, WorldGenMode};
+
+fn load_registry() -> BlockRegistry {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let vox = root.join("../../assets/voxels");
+    BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml")).unwrap()
+}
+
+fn named(reg: &BlockRegistry, name: &str) -> Block {
+    Block {
+        id: reg.id_by_name(name).unwrap(),
+        state: 0,
+    }
+}
+
+// Stone below y = 0, air above.
+fn floor(reg: &BlockRegistry) -> impl Fn(i32, i32, i32) -> Block + '_ {
+    move |_, y, _| {
+        if y < 0 {
+            named(reg, "stone")
+        } else {
+            Block::AIR
+        }
+    }
+}
+
+fn solid_deck(id: u32, size: usize, pose: Pose, reg: &BlockRegistry) -> Structure {
+    let mut st = Structure::new(id, size, 1, size, pose, reg);
+    st.blocks = Arc::from(vec![named(reg, "stone"); size * size].into_boxed_slice());
+    st
+}
+
+#[test]
+fn straight_down_hits_the_floor_top_face() {
+    let reg = load_registry();
+    let ray = Ray::new(Vec3::new(0.5, 5.5, 0.5), Vec3::new(0.0, -1.0, 0.0), 10.0);
+    let hit = raycast_world(&reg, &ray, floor(&reg)).unwrap();
+    assert_eq!(hit.cell(), IVec3::new(0, -1, 0));
+    assert_eq!(hit.before(), IVec3::new(0, 0, 0));
+    assert_eq!(hit.normal(), IVec3::new(0, 1, 0));
+    assert!((hit.dist - 5.5).abs() < 1e-4);
+    assert!(hit.enter.y.abs() < 1e-4);
+}
+
+#[test]
+fn max_distance_stops_the_ray() {
+    let reg = load_registry();
+    let ray = Ray::new(Vec3::new(0.5, 5.5, 0.5), Vec3::new(0.0, -1.0, 0.0), 5.0);
+    assert!(raycast_world(&reg, &ray, floor(&reg)).is_none());
+}
+
+#[test]
+fn long_diagonal_rays_reach_their_full_distance() {
+    let reg = load_registry();
+    let wall = |x: i32, _, _| {
+        if x >= 200 {
+            named(&reg, "stone")
+        } else {
+            Block::AIR
+        }
+    };
+    let ray = Ray::new(Vec3::new(0.5, 0.5, 0.5), Vec3::new(1.0, 1.0, 1.0), 400.0);
+    let hit = raycast_world(&reg, &ray, wall).unwrap();
+    assert_eq!(hit.bx, 200);
+    assert_eq!(hit.normal(), IVec3::new(-1, 0, 0));
+    assert!(
+        (hit.dist - 199.5 * 3f32.sqrt()).abs() < 1e-2,
+        "{}",
+        hit.dist
+    );
+}
+
+#[test]
+fn terrain_rays_see_edits_over_the_generator() {
+    let reg = load_registry();
+    let world = World::new(1, 4, 1, 7, WorldGenMode::Flat { thickness: 4 });
+    let mut edits = EditStore::new(32, 32, 32);
+    let down = Ray::new(Vec3::new(8.5, 20.5, 8.5), Vec3::new(0.0, -1.0, 0.0), 40.0);
+    let ground = raycast_terrain(&world, &edits, &reg, &down).unwrap();
+    assert_eq!(ground.by, 3);
+
+    edits.set(8, 10, 8, named(&reg, "stone"));
+    let hit = raycast_terrain(&world, &edits, &reg, &down).unwrap();
+    assert_eq!(hit.cell(), IVec3::new(8, 10, 8));
+}
+
+#[test]
+fn yawed_structures_are_hit_in_local_cells() {
+    let reg = load_registry();
+    // Yaw 90 maps local +X to world +Z and local +Z to world -X.
+    let st = solid_deck(1, 4, Pose::from_yaw(Vec3::new(10.0, 2.0, 0.0), 90.0), &reg);
+    let ray = Ray::new(Vec3::new(8.5, 6.0, 1.5), Vec3::new(0.0, -1.0, 0.0), 10.0);
+    let hit = raycast_structure(&st, &reg, &ray).unwrap();
+    assert_eq!(hit.cell(), IVec3::new(1, 0, 1));
+    assert_eq!(hit.normal(), IVec3::new(0, 1, 0));
+    assert!((hit.dist - 3.0).abs() < 1e-4);
+
+    // Beside the rotated deck, the ray misses it.
+    let beside = Ray::new(Vec3::new(11.5, 6.0, 1.5), Vec3::new(0.0, -1.0, 0.0), 10.0);
+    assert!(raycast_structure(&st, &reg, &beside).is_none());
+}
+
+#[test]
+fn scene_hits_the_nearer_of_world_and_structure() {
+    let reg = load_registry();
+    let st = solid_deck(3, 4, Pose::from_yaw(Vec3::new(0.0, 2.0, 0.0), 0.0), &reg);
+    let ray = Ray::new(Vec3::new(1.5, 6.0, 1.5), Vec3::new(0.0, -1.0, 0.0), 10.0);
+    match raycast_scene(&reg, &ray, floor(&reg), [&st]) {
+        Some(SceneHit::Structure { id, hit }) => {
+            assert_eq!(id, 3);
+            assert_eq!(hit.cell(), IVec3::new(1, 0, 1));
+        }
+        other => panic!("expected the deck, got {:?}", other),
+    }
+    let past = Ray::new(Vec3::new(6.5, 6.0, 1.5), Vec3::new(0.0, -1.0, 0.0), 10.0);
+    assert!(matches!(
+        raycast_scene(&reg, &past, floor(&reg), [&st]),
+        Some(SceneHit::World(hit)) if hit.by == -1
+    ));
+}
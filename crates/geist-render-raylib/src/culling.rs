@@ -0,0 +1,382 @@
+//! View culling: frustum planes from a camera, and coarse occlusion between chunks.
+//!
+//! Occlusion works on chunk faces, not pixels. Each chunk records which of its six faces
+//! are joined by see-through cells ([`ChunkVisibility`]). [`visible_chunks`] floods
+//! outward from the camera's chunk, only ever moving away from it, and only crosses a
+//! chunk from the face it entered by to a face joined to it. Chunks the flood never
+//! reaches — caves under solid ground, rooms behind walls — cannot be seen and are skipped.
+
+use std::collections::{HashSet, VecDeque};
+
+use geist_geom::Aabb;
+use geist_mesh_cpu::Face;
+use geist_world::ChunkCoord;
+use raylib::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Plane {
+    pub normal: Vector3,
+    pub distance: f32,
+}
+
+impl Plane {
+    pub fn new(normal: Vector3, point: Vector3) -> Self {
+        let normal = normal.normalized();
+        Self {
+            normal,
+            distance: -normal.dot(point),
+        }
+    }
+
+    pub fn distance_to_point(&self, point: Vector3) -> f32 {
+        self.normal.dot(point) + self.distance
+    }
+}
+
+/// The six planes bounding a perspective view, normals pointing inward.
+#[derive(Clone, Copy, Debug)]
+pub struct Frustum {
+    pub planes: [Plane; 6], // left, right, top, bottom, near, far
+}
+
+impl Frustum {
+    /// Frustum of a perspective `camera` (vertical `fovy` in degrees) for a viewport of
+    /// `aspect_ratio` (width / height), between the `near` and `far` distances.
+    pub fn from_camera(camera: &Camera3D, aspect_ratio: f32, near: f32, far: f32) -> Self {
+        let position = camera.position;
+        let forward = (camera.target - position).normalized();
+        let right = forward.cross(camera.up).normalized();
+        let up = right.cross(forward).normalized();
+
+        let tan_half_fov = (camera.fovy.to_radians() * 0.5).tan();
+        let half_h = near * tan_half_fov;
+        let half_w = half_h * aspect_ratio;
+        let nc = position + forward * near;
+        let fc = position + forward * far;
+
+        let nlt = nc + up * half_h - right * half_w;
+        let nrt = nc + up * half_h + right * half_w;
+        let nlb = nc - up * half_h - right * half_w;
+        let nrb = nc - up * half_h + right * half_w;
+
+        // A side plane through the eye and two near corners, facing into the frustum.
+        let side = |a: Vector3, b: Vector3| {
+            let normal = (b - position).cross(a - position).normalized();
+            Plane::new(normal, position)
+        };
+        Frustum {
+            planes: [
+                side(nlt, nlb),
+                side(nrb, nrt),
+                side(nrt, nlt),
+                side(nlb, nrb),
+                Plane::new(forward, nc),
+                Plane::new(-forward, fc),
+            ],
+        }
+    }
+
+    pub fn contains_point(&self, point: Vector3) -> bool {
+        self.planes
+            .iter()
+            .all(|p| p.distance_to_point(point) >= 0.0)
+    }
+
+    /// Whether any part of `bbox` may be inside. Boxes near the frustum's corners can pass
+    /// without being visible; none that are visible fail.
+    pub fn contains_bounding_box(&self, bbox: &BoundingBox) -> bool {
+        self.planes.iter().all(|plane| {
+            // The corner farthest along the plane normal.
+            let n = plane.normal;
+            let p = Vector3::new(
+                if n.x >= 0.0 { bbox.max.x } else { bbox.min.x },
+                if n.y >= 0.0 { bbox.max.y } else { bbox.min.y },
+                if n.z >= 0.0 { bbox.max.z } else { bbox.min.z },
+            );
+            plane.distance_to_point(p) >= 0.0
+        })
+    }
+
+    pub fn contains_aabb(&self, aabb: &Aabb) -> bool {
+        self.contains_bounding_box(&crate::conv::aabb_to_rl(*aabb))
+    }
+}
+
+/// Which faces of a chunk are joined through its see-through cells.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChunkVisibility {
+    // Bit `a * 6 + b` is set when faces `a` and `b` (by `Face::index`) are joined.
+    links: u64,
+}
+
+impl ChunkVisibility {
+    /// Every face joined to every other: an empty or unknown chunk.
+    pub const OPEN: ChunkVisibility = ChunkVisibility {
+        links: (1 << 36) - 1,
+    };
+    /// No face joined to any: nothing can be seen through the chunk.
+    pub const SEALED: ChunkVisibility = ChunkVisibility { links: 0 };
+
+    /// Visibility of an `sx*sy*sz` chunk whose cell `(x, y, z)` blocks sight when
+    /// `opaque` says so. Each 6-connected region of see-through cells joins every chunk
+    /// face it touches.
+    pub fn from_cells(
+        sx: usize,
+        sy: usize,
+        sz: usize,
+        opaque: impl Fn(usize, usize, usize) -> bool,
+    ) -> Self {
+        let n = sx * sy * sz;
+        let idx = |x: usize, y: usize, z: usize| (y * sz + z) * sx + x;
+        let mut seen = vec![false; n];
+        let mut stack = Vec::new();
+        let mut links = 0u64;
+        for y in 0..sy {
+            for z in 0..sz {
+                for x in 0..sx {
+                    let start = idx(x, y, z);
+                    if seen[start] || opaque(x, y, z) {
+                        continue;
+                    }
+                    seen[start] = true;
+                    stack.push((x, y, z));
+                    let mut faces = 0u8;
+                    while let Some((x, y, z)) = stack.pop() {
+                        faces |= border_faces(x, y, z, sx, sy, sz);
+                        let mut visit = |x: usize, y: usize, z: usize| {
+                            let i = idx(x, y, z);
+                            if !seen[i] && !opaque(x, y, z) {
+                                seen[i] = true;
+                                stack.push((x, y, z));
+                            }
+                        };
+                        if x > 0 {
+                            visit(x - 1, y, z);
+                        }
+                        if x + 1 < sx {
+                            visit(x + 1, y, z);
+                        }
+                        if y > 0 {
+                            visit(x, y - 1, z);
+                        }
+                        if y + 1 < sy {
+                            visit(x, y + 1, z);
+                        }
+                        if z > 0 {
+                            visit(x, y, z - 1);
+                        }
+                        if z + 1 < sz {
+                            visit(x, y, z + 1);
+                        }
+                    }
+                    for a in 0..6 {
+                        for b in 0..6 {
+                            if faces & (1 << a) != 0 && faces & (1 << b) != 0 {
+                                links |= 1 << (a * 6 + b);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        ChunkVisibility { links }
+    }
+
+    /// Whether something entering through face `a` can be seen leaving through face `b`.
+    pub fn connects(&self, a: Face, b: Face) -> bool {
+        self.links & (1 << (a.index() * 6 + b.index())) != 0
+    }
+
+    pub fn is_sealed(&self) -> bool {
+        self.links == 0
+    }
+}
+
+fn border_faces(x: usize, y: usize, z: usize, sx: usize, sy: usize, sz: usize) -> u8 {
+    let mut faces = 0u8;
+    let mut on = |hit: bool, face: Face| {
+        if hit {
+            faces |= 1 << face.index();
+        }
+    };
+    on(x == 0, Face::NegX);
+    on(x + 1 == sx, Face::PosX);
+    on(y == 0, Face::NegY);
+    on(y + 1 == sy, Face::PosY);
+    on(z == 0, Face::NegZ);
+    on(z + 1 == sz, Face::PosZ);
+    faces
+}
+
+fn opposite(face: Face) -> Face {
+    match face {
+        Face::PosY => Face::NegY,
+        Face::NegY => Face::PosY,
+        Face::PosX => Face::NegX,
+        Face::NegX => Face::PosX,
+        Face::PosZ => Face::NegZ,
+        Face::NegZ => Face::PosZ,
+    }
+}
+
+/// Chunks that may be seen from inside chunk `start`, within `radius` chunks of it on
+/// every axis. `visibility` describes a chunk (`None` counts as open, e.g. not loaded or
+/// empty) and `in_view` can prune chunks outside the frustum so the flood does not pass
+/// behind the camera.
+pub fn visible_chunks(
+    start: ChunkCoord,
+    radius: i32,
+    visibility: impl Fn(ChunkCoord) -> Option<ChunkVisibility>,
+    in_view: impl Fn(ChunkCoord) -> bool,
+) -> HashSet<ChunkCoord> {
+    const FACES: [Face; 6] = [
+        Face::PosY,
+        Face::NegY,
+        Face::PosX,
+        Face::NegX,
+        Face::PosZ,
+        Face::NegZ,
+    ];
+    let mut seen = HashSet::new();
+    seen.insert(start);
+    // (chunk, face it was entered through, faces stepped out of so far)
+    let mut queue: VecDeque<(ChunkCoord, Option<Face>, u8)> = VecDeque::new();
+    queue.push_back((start, None, 0));
+    while let Some((coord, entered, stepped)) = queue.pop_front() {
+        let vis = visibility(coord).unwrap_or(ChunkVisibility::OPEN);
+        for face in FACES {
+            // Never head back towards the camera.
+            if stepped & (1 << opposite(face).index()) != 0 {
+                continue;
+            }
+            if let Some(entered) = entered
+                && !vis.connects(entered, face)
+            {
+                continue;
+            }
+            let (dx, dy, dz) = face.delta();
+            let next = coord.offset(dx, dy, dz);
+            if (next.cx - start.cx).abs() > radius
+                || (next.cy - start.cy).abs() > radius
+                || (next.cz - start.cz).abs() > radius
+                || seen.contains(&next)
+                || !in_view(next)
+            {
+                continue;
+            }
+            seen.insert(next);
+            queue.push_back((next, Some(opposite(face)), stepped | (1 << face.index())));
+        }
+    }
+    seen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geist_geom::Vec3;
+
+    // Eye at the origin looking down -Z with a 60 degree square view out to 100 units.
+    fn frustum() -> Frustum {
+        let camera = Camera3D::perspective(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            60.0,
+        );
+        Frustum::from_camera(&camera, 1.0, 0.1, 100.0)
+    }
+
+    fn bbox(min: (f32, f32, f32), max: (f32, f32, f32)) -> BoundingBox {
+        BoundingBox::new(
+            Vector3::new(min.0, min.1, min.2),
+            Vector3::new(max.0, max.1, max.2),
+        )
+    }
+
+    #[test]
+    fn points_inside_and_outside_the_frustum() {
+        let f = frustum();
+        assert!(f.contains_point(Vector3::new(0.0, 0.0, -10.0)));
+        assert!(f.contains_point(Vector3::new(4.0, -4.0, -10.0)));
+        assert!(!f.contains_point(Vector3::new(0.0, 0.0, 10.0)), "behind");
+        assert!(
+            !f.contains_point(Vector3::new(0.0, 0.0, -150.0)),
+            "past far"
+        );
+        assert!(
+            !f.contains_point(Vector3::new(20.0, 0.0, -10.0)),
+            "right of view"
+        );
+        assert!(
+            !f.contains_point(Vector3::new(0.0, 20.0, -10.0)),
+            "above view"
+        );
+    }
+
+    #[test]
+    fn boxes_inside_outside_and_straddling() {
+        let f = frustum();
+        assert!(f.contains_bounding_box(&bbox((-1.0, -1.0, -11.0), (1.0, 1.0, -9.0))));
+        // Wholly behind, beside, above and past the far plane.
+        assert!(!f.contains_bounding_box(&bbox((-1.0, -1.0, 5.0), (1.0, 1.0, 7.0))));
+        assert!(!f.contains_bounding_box(&bbox((30.0, -1.0, -11.0), (32.0, 1.0, -9.0))));
+        assert!(!f.contains_bounding_box(&bbox((-1.0, 30.0, -11.0), (1.0, 32.0, -9.0))));
+        assert!(!f.contains_bounding_box(&bbox((-1.0, -1.0, -140.0), (1.0, 1.0, -120.0))));
+        // Crossing the right plane, the far plane, and around the eye.
+        assert!(f.contains_bounding_box(&bbox((4.0, -1.0, -11.0), (40.0, 1.0, -9.0))));
+        assert!(f.contains_bounding_box(&bbox((-1.0, -1.0, -120.0), (1.0, 1.0, -90.0))));
+        assert!(f.contains_bounding_box(&bbox((-1.0, -1.0, -1.0), (1.0, 1.0, 1.0))));
+
+        let aabb = |min: (f32, f32, f32), max: (f32, f32, f32)| {
+            Aabb::new(
+                Vec3::new(min.0, min.1, min.2),
+                Vec3::new(max.0, max.1, max.2),
+            )
+        };
+        assert!(f.contains_aabb(&aabb((-1.0, -1.0, -11.0), (1.0, 1.0, -9.0))));
+        assert!(!f.contains_aabb(&aabb((-1.0, -1.0, 5.0), (1.0, 1.0, 7.0))));
+    }
+
+    #[test]
+    fn cell_visibility_joins_faces_through_open_cells() {
+        assert_eq!(
+            ChunkVisibility::from_cells(4, 4, 4, |_, _, _| false),
+            ChunkVisibility::OPEN
+        );
+        assert!(ChunkVisibility::from_cells(4, 4, 4, |_, _, _| true).is_sealed());
+        // A solid wall at x = 2 splits the chunk into a west and an east half.
+        let wall = ChunkVisibility::from_cells(4, 4, 4, |x, _, _| x == 2);
+        assert!(!wall.connects(Face::NegX, Face::PosX));
+        assert!(wall.connects(Face::NegX, Face::PosY));
+        assert!(wall.connects(Face::PosX, Face::NegZ));
+        assert!(wall.connects(Face::PosY, Face::NegY));
+    }
+
+    #[test]
+    fn chunks_behind_a_solid_wall_are_occluded() {
+        let start = ChunkCoord::new(0, 0, 0);
+        // Every chunk at cx = 1 is solid.
+        let vis = |c: ChunkCoord| (c.cx == 1).then_some(ChunkVisibility::SEALED);
+        let seen = visible_chunks(start, 2, vis, |_| true);
+        assert!(
+            seen.contains(&ChunkCoord::new(1, 0, 0)),
+            "the wall itself shows"
+        );
+        assert!(seen.contains(&ChunkCoord::new(-2, 1, 2)));
+        assert!(seen.iter().all(|c| c.cx <= 1), "nothing past the wall");
+        assert!(!seen.contains(&ChunkCoord::new(2, 0, 0)));
+
+        // A single sealed chunk only hides what is straight behind it.
+        let vis =
+            |c: ChunkCoord| (c == ChunkCoord::new(1, 0, 0)).then_some(ChunkVisibility::SEALED);
+        let seen = visible_chunks(start, 2, vis, |_| true);
+        assert!(!seen.contains(&ChunkCoord::new(2, 0, 0)));
+        assert!(seen.contains(&ChunkCoord::new(2, 1, 0)));
+
+        // Chunks outside the view are pruned along with everything behind them.
+        let seen = visible_chunks(start, 2, |_| None, |c| c.cz >= 0);
+        assert!(seen.iter().all(|c| c.cz >= 0));
+        assert_eq!(seen.len(), 5 * 5 * 3);
+    }
+}
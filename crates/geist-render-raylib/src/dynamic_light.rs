@@ -0,0 +1,125 @@
+//! GPU side of `geist_lighting::DynamicLights`: one single-channel texture holding the
+//! dynamic light volume, bound for the whole frame and sampled in render space by every
+//! voxel shader next to the per-chunk light atlas.
+
+use geist_lighting::DynamicLightVolume;
+use raylib::prelude::*;
+
+use crate::FloatingOrigin;
+
+/// Texture unit the volume stays bound to; block arrays use 6 and chunk light 7.
+pub const DYNAMIC_LIGHT_SLOT: i32 = 8;
+
+pub struct DynamicLightTex {
+    pub tex: Texture2D,
+    pub width: i32,
+    pub height: i32,
+    pub origin: [f32; 3],
+    pub dims: [i32; 3],
+    pub grid: [i32; 2],
+}
+
+impl DynamicLightTex {
+    /// Bind to `DYNAMIC_LIGHT_SLOT`; like the block array this holds for the frame since
+    /// rlgl only unbinds the units its material maps use.
+    pub fn bind(&self) {
+        unsafe {
+            raylib::ffi::rlActiveTextureSlot(DYNAMIC_LIGHT_SLOT);
+            raylib::ffi::rlEnableTexture(self.tex.id);
+            raylib::ffi::rlActiveTextureSlot(0);
+        }
+    }
+}
+
+/// Upload `volume` into `slot`, reusing the texture when the size matches; `None` drops it.
+pub fn update_dynamic_light_texture(
+    rl: &mut RaylibHandle,
+    thread: &RaylibThread,
+    slot: &mut Option<DynamicLightTex>,
+    volume: Option<&DynamicLightVolume>,
+) {
+    let Some(vol) = volume else {
+        *slot = None;
+        return;
+    };
+    let (width, height) = (vol.width as i32, vol.height as i32);
+    let reuse = slot
+        .as_ref()
+        .is_some_and(|t| t.width == width && t.height == height);
+    if !reuse {
+        let mut img = Image::gen_image_color(width, height, Color::BLACK);
+        img.set_format(raylib::consts::PixelFormat::PIXELFORMAT_UNCOMPRESSED_GRAYSCALE);
+        let Ok(tex) = rl.load_texture_from_image(thread, &img) else {
+            *slot = None;
+            return;
+        };
+        tex.set_texture_filter(thread, raylib::consts::TextureFilter::TEXTURE_FILTER_POINT);
+        tex.set_texture_wrap(thread, raylib::consts::TextureWrap::TEXTURE_WRAP_CLAMP);
+        *slot = Some(DynamicLightTex {
+            tex,
+            width,
+            height,
+            origin: [0.0; 3],
+            dims: [0; 3],
+            grid: [0; 2],
+        });
+    }
+    let Some(t) = slot.as_mut() else {
+        return;
+    };
+    unsafe {
+        raylib::ffi::UpdateTexture(*t.tex.as_ref(), vol.data.as_ptr() as *const _);
+    }
+    let o = vol.origin.0;
+    t.origin = [o.x as f32, o.y as f32, o.z as f32];
+    t.dims = [vol.dims.x, vol.dims.y, vol.dims.z];
+    t.grid = [vol.grid_cols as i32, vol.grid_rows as i32];
+}
+
+/// Uniform locations for the dynamic light volume; any may be -1 if a shader omits it.
+#[derive(Clone, Copy)]
+pub struct DynamicLightLocs {
+    tex: i32,
+    dims: i32,
+    grid: i32,
+    origin: i32,
+}
+
+impl DynamicLightLocs {
+    pub(crate) fn locate(shader: &raylib::shaders::WeakShader) -> Self {
+        Self {
+            tex: shader.get_shader_location("dynLightTex"),
+            dims: shader.get_shader_location("dynLightDims"),
+            grid: shader.get_shader_location("dynLightGrid"),
+            origin: shader.get_shader_location("dynLightOrigin"),
+        }
+    }
+
+    /// Point the shader at `tex`, or zero its dims so the lookup is skipped. The volume
+    /// origin is given in render space, as the shaders see positions.
+    pub(crate) fn apply(
+        &self,
+        shader: &mut raylib::shaders::WeakShader,
+        tex: Option<&DynamicLightTex>,
+        render: &FloatingOrigin,
+    ) {
+        if self.tex >= 0 {
+            shader.set_shader_value(self.tex, DYNAMIC_LIGHT_SLOT);
+        }
+        let (dims, grid, origin) = tex
+            .map(|t| {
+                let o = render.to_render(Vector3::new(t.origin[0], t.origin[1], t.origin[2]));
+                (t.dims, t.grid, [o.x, o.y, o.z])
+            })
+            .unwrap_or(([0; 3], [0; 2], [0.0; 3]));
+        if self.dims >= 0 {
+            shader.set_shader_value(self.dims, dims);
+        }
+        if self.grid >= 0 {
+            shader.set_shader_value(self.grid, grid);
+        }
+        if self.origin >= 0 {
+            shader.set_shader_value(self.origin, origin);
+        }
+    }
+}
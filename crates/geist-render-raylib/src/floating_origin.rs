@@ -0,0 +1,83 @@
+//! Floating render origin for camera-relative drawing.
+//!
+//! f32 positions lose sub-block precision a few hundred thousand blocks out, which shows up
+//! as vertex jitter. Meshes are uploaded relative to their own origin (see
+//! `upload_chunk_mesh`), and everything is drawn relative to an integer origin kept near the
+//! camera, so the numbers reaching the GPU stay small wherever the world is.
+
+use raylib::prelude::{BoundingBox, Camera3D, Vector3};
+
+/// How far the camera may stray from the origin on any axis before it rebases.
+pub const DEFAULT_REBASE_DISTANCE: f32 = 1024.0;
+// Origins are multiples of this, so shader patterns keyed on world cells keep their period.
+const REBASE_SNAP: i32 = 256;
+
+#[derive(Clone, Copy, Debug)]
+pub struct FloatingOrigin {
+    origin: [i32; 3],
+    rebase_distance: f32,
+}
+
+impl Default for FloatingOrigin {
+    fn default() -> Self {
+        Self::new(DEFAULT_REBASE_DISTANCE)
+    }
+}
+
+impl FloatingOrigin {
+    pub fn new(rebase_distance: f32) -> Self {
+        Self {
+            origin: [0; 3],
+            rebase_distance: rebase_distance.max(REBASE_SNAP as f32),
+        }
+    }
+
+    pub fn origin(&self) -> [i32; 3] {
+        self.origin
+    }
+
+    /// Move the origin under the camera once it has strayed past the rebase distance.
+    /// Returns true when the origin moved.
+    pub fn update(&mut self, camera_world: Vector3) -> bool {
+        let off = self.to_render(camera_world);
+        if off.x.abs().max(off.y.abs()).max(off.z.abs()) <= self.rebase_distance {
+            return false;
+        }
+        let snap = |v: f32| (v / REBASE_SNAP as f32).round() as i32 * REBASE_SNAP;
+        let next = [
+            snap(camera_world.x),
+            snap(camera_world.y),
+            snap(camera_world.z),
+        ];
+        let moved = next != self.origin;
+        self.origin = next;
+        moved
+    }
+
+    /// World-space offset to add when drawing something positioned in world space.
+    pub fn offset(&self) -> Vector3 {
+        Vector3::new(
+            -self.origin[0] as f32,
+            -self.origin[1] as f32,
+            -self.origin[2] as f32,
+        )
+    }
+
+    pub fn to_render(&self, world: Vector3) -> Vector3 {
+        world + self.offset()
+    }
+
+    pub fn bbox_to_render(&self, bb: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            min: self.to_render(bb.min),
+            max: self.to_render(bb.max),
+        }
+    }
+
+    /// `camera` moved into render space; feed this to `begin_mode3D`.
+    pub fn camera(&self, mut camera: Camera3D) -> Camera3D {
+        camera.position = self.to_render(camera.position);
+        camera.target = self.to_render(camera.target);
+        camera
+    }
+}
@@ -0,0 +1,299 @@
+//! GPU instancing for small meshes repeated many times, such as grass tufts.
+//!
+//! [`InstancedMesh`] uploads its base mesh once; each draw hands raylib a buffer of
+//! per-instance transforms and the whole batch goes out in one call. The instance shader
+//! reads the transform from a vertex attribute, and since a placement never uses the
+//! matrix's bottom row, [`instance_transform`] stores the instance's light there.
+//! [`DecorationRenderer`] builds on this to draw the decoration blocks the mesher lists
+//! per chunk, one batch per material.
+
+use std::collections::HashMap;
+
+use geist_blocks::BlockRegistry;
+use geist_blocks::types::{FaceRole, MaterialId};
+use geist_mesh_cpu::DecorationInstance;
+use raylib::prelude::*;
+
+use crate::TextureCache;
+
+/// Model matrix placing an instance at `pos` (footprint centre), turned `yaw_deg` about
+/// +Y and scaled by `scale`, with its skylight and block light (0..255) packed into the
+/// bottom row for the instance shader.
+pub fn instance_transform(pos: Vector3, yaw_deg: f32, scale: f32, sky: u8, block: u8) -> Matrix {
+    let mut m = Matrix::scale(scale, scale, scale)
+        * Matrix::rotate_y(yaw_deg.to_radians())
+        * Matrix::translate(pos.x, pos.y, pos.z);
+    m.m3 = sky as f32 / 255.0;
+    m.m7 = block as f32 / 255.0;
+    m
+}
+
+/// The instancing shader: per-instance transform and light, fog, and wind sway.
+pub struct InstanceShader {
+    pub shader: raylib::shaders::WeakShader,
+    pub loc_fog_color: i32,
+    pub loc_fog_start: i32,
+    pub loc_fog_end: i32,
+    pub loc_camera_pos: i32,
+    pub loc_time: i32,
+    pub loc_vis_min: i32,
+    pub loc_sky_scale: i32,
+}
+
+impl InstanceShader {
+    pub fn load_with_base(
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        base: &std::path::Path,
+    ) -> Option<Self> {
+        let vs = base.join("assets/shaders/voxel_instanced.vs");
+        let fs = base.join("assets/shaders/voxel_instanced.fs");
+        let shader_strong = rl.load_shader(
+            thread,
+            Some(vs.to_string_lossy().as_ref()),
+            Some(fs.to_string_lossy().as_ref()),
+        );
+        let mut shader = unsafe { shader_strong.make_weak() };
+        if !crate::shader_compiled(&shader) {
+            return None;
+        }
+        // raylib feeds instance transforms to the attribute at the model-matrix slot.
+        let loc_instance = shader.get_shader_location_attribute("instanceTransform");
+        let loc_mvp = shader.get_shader_location("mvp");
+        if loc_instance < 0 {
+            return None;
+        }
+        let locs = shader.locs_mut();
+        locs[ShaderLocationIndex::SHADER_LOC_MATRIX_MODEL as usize] = loc_instance;
+        locs[ShaderLocationIndex::SHADER_LOC_MATRIX_MVP as usize] = loc_mvp;
+        Some(Self {
+            loc_fog_color: shader.get_shader_location("fogColor"),
+            loc_fog_start: shader.get_shader_location("fogStart"),
+            loc_fog_end: shader.get_shader_location("fogEnd"),
+            loc_camera_pos: shader.get_shader_location("cameraPos"),
+            loc_time: shader.get_shader_location("time"),
+            loc_vis_min: shader.get_shader_location("visualLightMin"),
+            loc_sky_scale: shader.get_shader_location("skyLightScale"),
+            shader,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_frame_uniforms(
+        &mut self,
+        camera_pos: Vector3,
+        fog_color: [f32; 3],
+        fog_start: f32,
+        fog_end: f32,
+        time: f32,
+        sky_scale: f32,
+        vis_min: f32,
+    ) {
+        if self.loc_fog_color >= 0 {
+            self.shader.set_shader_value(self.loc_fog_color, fog_color);
+        }
+        if self.loc_fog_start >= 0 {
+            self.shader.set_shader_value(self.loc_fog_start, fog_start);
+        }
+        if self.loc_fog_end >= 0 {
+            self.shader.set_shader_value(self.loc_fog_end, fog_end);
+        }
+        if self.loc_camera_pos >= 0 {
+            let cam = [camera_pos.x, camera_pos.y, camera_pos.z];
+            self.shader.set_shader_value(self.loc_camera_pos, cam);
+        }
+        if self.loc_time >= 0 {
+            self.shader.set_shader_value(self.loc_time, time);
+        }
+        if self.loc_sky_scale >= 0 {
+            self.shader.set_shader_value(self.loc_sky_scale, sky_scale);
+        }
+        if self.loc_vis_min >= 0 {
+            self.shader.set_shader_value(self.loc_vis_min, vis_min);
+        }
+    }
+}
+
+/// A mesh uploaded once and drawn many times from a buffer of instance transforms.
+pub struct InstancedMesh {
+    mesh: raylib::core::models::Mesh,
+    pub triangles: usize,
+}
+
+impl InstancedMesh {
+    /// Upload triangles given as flat `pos`/`norm` (xyz) and `uv` arrays, indexed by
+    /// `indices`.
+    pub fn new(pos: &[f32], norm: &[f32], uv: &[f32], indices: &[u16]) -> Self {
+        let verts = pos.len() / 3;
+        debug_assert_eq!(norm.len(), verts * 3);
+        debug_assert_eq!(uv.len(), verts * 2);
+        let mut raw: raylib::ffi::Mesh = unsafe { std::mem::zeroed() };
+        raw.vertexCount = verts as i32;
+        raw.triangleCount = (indices.len() / 3) as i32;
+        unsafe {
+            raw.vertices = raylib::ffi::MemAlloc(std::mem::size_of_val(pos) as u32) as *mut f32;
+            raw.normals = raylib::ffi::MemAlloc(std::mem::size_of_val(norm) as u32) as *mut f32;
+            raw.texcoords = raylib::ffi::MemAlloc(std::mem::size_of_val(uv) as u32) as *mut f32;
+            raw.indices = raylib::ffi::MemAlloc(std::mem::size_of_val(indices) as u32) as *mut u16;
+            std::ptr::copy_nonoverlapping(pos.as_ptr(), raw.vertices, pos.len());
+            std::ptr::copy_nonoverlapping(norm.as_ptr(), raw.normals, norm.len());
+            std::ptr::copy_nonoverlapping(uv.as_ptr(), raw.texcoords, uv.len());
+            std::ptr::copy_nonoverlapping(indices.as_ptr(), raw.indices, indices.len());
+        }
+        let mut mesh = unsafe { raylib::core::models::Mesh::from_raw(raw) };
+        unsafe { mesh.upload(false) };
+        InstancedMesh {
+            mesh,
+            triangles: indices.len() / 3,
+        }
+    }
+
+    /// Two unit quads crossed along the cell diagonals, one block tall and facing both
+    /// ways, standing on the origin: the usual plant billboard.
+    pub fn crossed_quads() -> Self {
+        let h = std::f32::consts::FRAC_1_SQRT_2 * 0.5;
+        let mut pos = Vec::with_capacity(8 * 3);
+        let mut norm = Vec::with_capacity(8 * 3);
+        let mut uv = Vec::with_capacity(8 * 2);
+        let mut indices = Vec::with_capacity(24);
+        for (dx, dz) in [(h, h), (h, -h)] {
+            let base = (pos.len() / 3) as u16;
+            for (x, y, z, u, v) in [
+                (-dx, 0.0, -dz, 0.0, 1.0),
+                (dx, 0.0, dz, 1.0, 1.0),
+                (dx, 1.0, dz, 1.0, 0.0),
+                (-dx, 1.0, -dz, 0.0, 0.0),
+            ] {
+                pos.extend_from_slice(&[x, y, z]);
+                norm.extend_from_slice(&[0.0, 1.0, 0.0]);
+                uv.extend_from_slice(&[u, v]);
+            }
+            // Front and back windings, so the quad shows from either side.
+            indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+            indices.extend_from_slice(&[base, base + 2, base + 1, base, base + 3, base + 2]);
+        }
+        Self::new(&pos, &norm, &uv, &indices)
+    }
+
+    /// Draw one copy per transform in a single call, inside 3D mode.
+    pub fn draw<D: RaylibDraw3D>(
+        &self,
+        _d3: &mut D,
+        material: &raylib::models::WeakMaterial,
+        transforms: &[Matrix],
+    ) {
+        if transforms.is_empty() {
+            return;
+        }
+        // Not `draw_mesh_instanced`: it drops its converted transform buffer before
+        // raylib reads it.
+        let raw: Vec<raylib::ffi::Matrix> = transforms.iter().map(|m| (*m).into()).collect();
+        unsafe {
+            raylib::ffi::DrawMeshInstanced(
+                *self.mesh.as_ref(),
+                *material.as_ref(),
+                raw.as_ptr(),
+                raw.len() as i32,
+            );
+        }
+    }
+}
+
+/// Draws decoration blocks as instanced crossed quads, one material per texture.
+pub struct DecorationRenderer {
+    pub shader: InstanceShader,
+    mesh: InstancedMesh,
+    // Never unloaded: raylib would free the textures they share with the texture cache.
+    materials: HashMap<MaterialId, raylib::models::WeakMaterial>,
+}
+
+/// What a [`DecorationRenderer::draw`] sent to the GPU.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DecorationDrawStats {
+    pub draw_calls: usize,
+    pub instances: usize,
+    pub triangles: usize,
+}
+
+impl DecorationRenderer {
+    pub fn load_with_base(
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        base: &std::path::Path,
+    ) -> Option<Self> {
+        let shader = InstanceShader::load_with_base(rl, thread, base)?;
+        Some(DecorationRenderer {
+            shader,
+            mesh: InstancedMesh::crossed_quads(),
+            materials: HashMap::new(),
+        })
+    }
+
+    fn material_of(reg: &BlockRegistry, d: &DecorationInstance) -> Option<MaterialId> {
+        reg.get(d.block.id)
+            .map(|ty| ty.material_for_cached(FaceRole::Side, d.block.state))
+    }
+
+    /// Set up materials for any decoration in `decorations` not seen before.
+    pub fn prepare(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        tex_cache: &mut TextureCache,
+        reg: &BlockRegistry,
+        decorations: &[DecorationInstance],
+    ) {
+        for d in decorations {
+            let Some(mid) = Self::material_of(reg, d) else {
+                continue;
+            };
+            if self.materials.contains_key(&mid) {
+                continue;
+            }
+            let mut mat = rl.load_material_default(thread);
+            let dest: *mut raylib::ffi::Shader = mat.shader_mut().as_mut();
+            let src: *const raylib::ffi::Shader = self.shader.shader.as_ref();
+            unsafe { std::ptr::copy_nonoverlapping(src, dest, 1) };
+            if let Some(tex) = crate::material_texture(rl, thread, tex_cache, &reg.materials, mid) {
+                mat.set_material_texture(MaterialMapIndex::MATERIAL_MAP_ALBEDO, tex);
+            }
+            self.materials.insert(mid, mat);
+        }
+    }
+
+    /// Draw `decorations`, shifted by `offset` into render space, batched by material.
+    /// Decorations whose material was never prepared are skipped.
+    pub fn draw<'a, D: RaylibDraw3D>(
+        &self,
+        d3: &mut D,
+        reg: &BlockRegistry,
+        decorations: impl IntoIterator<Item = &'a DecorationInstance>,
+        offset: Vector3,
+    ) -> DecorationDrawStats {
+        let mut batches: HashMap<MaterialId, Vec<Matrix>> = HashMap::new();
+        for d in decorations {
+            let Some(mid) = Self::material_of(reg, d) else {
+                continue;
+            };
+            let pos = Vector3::new(d.pos.x, d.pos.y, d.pos.z) + offset;
+            batches.entry(mid).or_default().push(instance_transform(
+                pos,
+                d.yaw_deg,
+                d.scale,
+                d.sky,
+                d.block_light,
+            ));
+        }
+        let mut stats = DecorationDrawStats::default();
+        for (mid, transforms) in &batches {
+            let Some(material) = self.materials.get(mid) else {
+                continue;
+            };
+            self.mesh.draw(d3, material, transforms);
+            stats.draw_calls += 1;
+            stats.instances += transforms.len();
+            stats.triangles += transforms.len() * self.mesh.triangles;
+        }
+        stats
+    }
+}
@@ -1,12 +1,44 @@
 //! Raylib-based GPU rendering utilities: conversions, upload, textures, shaders.
 // Unsafe is required for Raylib mesh/model upload operations in this crate.
 
-use geist_blocks::MaterialCatalog;
-use geist_mesh_cpu::ChunkMeshCPU;
+use dynamic_light::DynamicLightLocs;
+use geist_blocks::material::MaterialAnimation;
+use geist_blocks::{MaterialCatalog, RenderPass};
+use geist_mesh_cpu::{ChunkMeshCPU, DecorationInstance, MeshBuild, recycle_build};
 use geist_world::ChunkCoord;
 use raylib::prelude::*;
+use shadow::ShadowLocs;
 use std::collections::HashMap;
 
+pub mod culling;
+pub mod dynamic_light;
+pub mod floating_origin;
+pub mod instancing;
+pub mod scene_target;
+pub mod shadow;
+pub mod sky;
+pub mod texture_array;
+pub mod wide_index;
+pub use culling::{ChunkVisibility, Frustum, visible_chunks};
+pub use dynamic_light::{DYNAMIC_LIGHT_SLOT, DynamicLightTex, update_dynamic_light_texture};
+pub use floating_origin::{DEFAULT_REBASE_DISTANCE, FloatingOrigin};
+pub use instancing::{
+    DecorationDrawStats, DecorationRenderer, InstanceShader, InstancedMesh, instance_transform,
+};
+pub use scene_target::{
+    MAX_RENDER_SCALE, MIN_RENDER_SCALE, RENDER_SCALE_PRESETS, SceneTarget, SharpenShader,
+    UpscaleFilter,
+};
+pub use shadow::{
+    MAX_SHADOW_CASCADES, SHADOW_MAP_SLOT, SHADOW_RESOLUTION_PRESETS, ShadowMaps, ShadowSettings,
+};
+pub use sky::{SkyFrame, SkyPalette, SkyRenderer, sun_direction};
+pub use texture_array::{
+    BLOCK_ARRAY_SLOT, BlockTextureArray, LayeredBuild, VERTEX_LAYER, material_texture_path,
+    merge_layered_parts,
+};
+pub use wide_index::{U16_PART_VERTS, WideIndices};
+
 pub mod conv {
     use geist_geom::{Aabb, Vec3};
 
@@ -65,6 +97,175 @@ pub struct ChunkPart {
     pub model: raylib::core::models::Model,
     pub v_start: usize,
     pub v_count: usize,
+    /// Mesh-space point the vertices were uploaded relative to; the model transform adds it
+    /// back.
+    pub origin: [f32; 3],
+    /// Set when the part was uploaded with 32-bit indices; draw it with `ChunkPart::draw`,
+    /// as raylib's own model draws assume 16-bit ones.
+    pub wide: Option<WideIndices>,
+    pub pass: RenderPass,
+    /// Mesh-space centre of the part's vertices, for depth sorting.
+    pub center: Vector3,
+    /// Vertices the part's buffers were allocated for; a rebuild of the same chunk may
+    /// refill them in place with up to this many.
+    pub capacity: usize,
+    /// Set when the part holds several materials from [`merge_layered_parts`], each
+    /// vertex carrying its array layer in its second texture coordinate.
+    pub vertex_layers: bool,
+}
+
+impl ChunkPart {
+    /// `grid_origin`, a light grid corner in mesh space, as the shaders' `chunkOrigin`
+    /// for this part's rebased vertices.
+    /// The `blockLayer` to draw the part with, if its material is in `tex_array`.
+    pub fn block_layer(&self, tex_array: Option<&BlockTextureArray>) -> Option<i32> {
+        if self.vertex_layers {
+            return Some(VERTEX_LAYER);
+        }
+        tex_array.and_then(|a| a.layer(self.mid))
+    }
+
+    pub fn light_origin(&self, grid_origin: [f32; 3]) -> [f32; 3] {
+        [
+            grid_origin[0] - self.origin[0],
+            grid_origin[1] - self.origin[1],
+            grid_origin[2] - self.origin[2],
+        ]
+    }
+
+    pub fn draw<D: RaylibDraw3D>(&self, d3: &mut D, position: Vector3, tint: Color) {
+        self.draw_ex(
+            d3,
+            position,
+            Vector3::new(0.0, 1.0, 0.0),
+            0.0,
+            Vector3::one(),
+            tint,
+        );
+    }
+
+    pub fn draw_wires<D: RaylibDraw3D>(&self, d3: &mut D, position: Vector3, tint: Color) {
+        unsafe { raylib::ffi::rlEnableWireMode() };
+        self.draw(d3, position, tint);
+        unsafe { raylib::ffi::rlDisableWireMode() };
+    }
+
+    /// `draw_model_ex` for either index width.
+    pub fn draw_ex<D: RaylibDraw3D>(
+        &self,
+        d3: &mut D,
+        position: Vector3,
+        axis: Vector3,
+        angle_deg: f32,
+        scale: Vector3,
+        tint: Color,
+    ) {
+        match self.wide {
+            Some(wide) => wide.draw_model(
+                self.model.as_ref(),
+                None,
+                wide_index::model_ex_transform(position, axis, angle_deg, scale),
+                tint,
+            ),
+            None => d3.draw_model_ex(&self.model, position, axis, angle_deg, scale, tint),
+        }
+    }
+
+    /// Draw the part's geometry with `material` in place of its own, under `transform`, as
+    /// the shadow depth pass does.
+    pub fn draw_with_material(&self, material: &raylib::models::WeakMaterial, transform: Matrix) {
+        let model = self.model.as_ref();
+        match self.wide {
+            Some(wide) => wide.draw_model(model, Some(material.as_ref()), transform, Color::WHITE),
+            None => {
+                let transform = Matrix::from(model.transform) * transform;
+                for i in 0..model.meshCount as usize {
+                    // SAFETY: a loaded model holds `meshCount` meshes.
+                    unsafe {
+                        raylib::ffi::DrawMesh(
+                            *model.meshes.add(i),
+                            *material.as_ref(),
+                            transform.into(),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Overwrite the part's vertex buffers, CPU copies included, with `v_count` vertices of
+    /// `mb` starting at `v_start`, and their `layers` for a part with vertex layers. Every
+    /// part indexes quads with the same pattern, so a shorter mesh draws a prefix of the
+    /// indices already on the GPU.
+    ///
+    /// # Safety
+    /// `v_count` must not exceed `capacity`, and the part's GL context must be current.
+    unsafe fn refill(&mut self, mb: &MeshBuild, layers: &[f32], v_start: usize, v_count: usize) {
+        use raylib::ffi::{
+            RL_DEFAULT_SHADER_ATTRIB_LOCATION_COLOR as COLOR,
+            RL_DEFAULT_SHADER_ATTRIB_LOCATION_NORMAL as NORMAL,
+            RL_DEFAULT_SHADER_ATTRIB_LOCATION_POSITION as POSITION,
+            RL_DEFAULT_SHADER_ATTRIB_LOCATION_TEXCOORD as TEXCOORD,
+            RL_DEFAULT_SHADER_ATTRIB_LOCATION_TEXCOORD2 as TEXCOORD2,
+        };
+        unsafe fn refill_attrib<T: Copy>(vbo: u32, cpu: *mut T, src: &[T]) {
+            unsafe {
+                std::ptr::copy_nonoverlapping(src.as_ptr(), cpu, src.len());
+                raylib::ffi::rlUpdateVertexBuffer(
+                    vbo,
+                    src.as_ptr() as *const std::ffi::c_void,
+                    std::mem::size_of_val(src) as i32,
+                    0,
+                );
+            }
+        }
+        let range = |width: usize| v_start * width..(v_start + v_count) * width;
+        unsafe {
+            let mesh = &mut *self.model.as_mut().meshes;
+            let vbo = |slot: u32| *mesh.vboId.add(slot as usize);
+            refill_attrib(vbo(POSITION), mesh.vertices, &mb.pos[range(3)]);
+            refill_attrib(vbo(TEXCOORD), mesh.texcoords, &mb.uv[range(2)]);
+            refill_attrib(vbo(NORMAL), mesh.normals, &mb.norm[range(3)]);
+            refill_attrib(vbo(COLOR), mesh.colors, &mb.col[range(4)]);
+            if self.vertex_layers {
+                let uv2 = layer_coords(&layers[v_start..v_start + v_count]);
+                refill_attrib(vbo(TEXCOORD2), mesh.texcoords2, &uv2);
+            }
+            mesh.vertexCount = v_count as i32;
+            mesh.triangleCount = (v_count / 2) as i32;
+        }
+    }
+}
+
+// Reused buffers must be at least this full; emptier ones are freed so a chunk that lost
+// most of its faces gives the memory back.
+const REFILL_MIN_FILL: usize = 4;
+
+/// Per-vertex second texture coordinates carrying `layers`.
+fn layer_coords(layers: &[f32]) -> Vec<f32> {
+    layers.iter().flat_map(|&l| [l, 0.0]).collect()
+}
+
+/// Best-fitting part of `spare` to refill with `v_count` vertices of `mid`.
+fn take_spare(
+    spare: &mut Vec<ChunkPart>,
+    mid: geist_blocks::types::MaterialId,
+    wide: bool,
+    vertex_layers: bool,
+    v_count: usize,
+) -> Option<ChunkPart> {
+    let (i, _) = spare
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| {
+            p.mid == mid
+                && p.wide.is_some() == wide
+                && p.vertex_layers == vertex_layers
+                && p.capacity >= v_count
+                && v_count * REFILL_MIN_FILL >= p.capacity
+        })
+        .min_by_key(|(_, p)| p.capacity)?;
+    Some(spare.swap_remove(i))
 }
 
 pub struct ChunkLightTex {
@@ -80,35 +281,156 @@ pub struct ChunkLightTex {
 
 pub struct ChunkRender {
     pub coord: ChunkCoord,
+    /// Mesh-space min corner of the light grid sampled by `light_tex`.
     pub origin: [f32; 3],
     pub bbox: raylib::core::math::BoundingBox,
     pub parts: Vec<ChunkPart>,
     pub leaf_tint: Option<[f32; 3]>,
     pub light_tex: Option<ChunkLightTex>,
+    /// Decoration blocks in the chunk, drawn through a [`DecorationRenderer`].
+    pub decorations: Vec<DecorationInstance>,
+}
+
+impl ChunkRender {
+    pub fn parts_in(&self, pass: RenderPass) -> impl Iterator<Item = &ChunkPart> {
+        self.parts.iter().filter(move |p| p.pass == pass)
+    }
+}
+
+/// Draw order for the translucent pass: every translucent part of `renders` as
+/// `(key, index into parts)`, farthest from `camera` first. Draw opaque parts before
+/// these, and these with depth writes off, so each blends over everything behind it
+/// whatever order the chunks are stored in. `to_world` places a mesh-space point in the
+/// world (identity for chunks, the pose for structures).
+pub fn translucent_back_to_front<'a, K: Copy>(
+    renders: impl IntoIterator<Item = (K, &'a ChunkRender)>,
+    camera: Vector3,
+    to_world: impl Fn(K, Vector3) -> Vector3,
+) -> Vec<(K, usize)> {
+    let mut order: Vec<(K, usize, f32)> = Vec::new();
+    for (key, cr) in renders {
+        for (i, part) in cr.parts.iter().enumerate() {
+            if part.pass == RenderPass::Translucent {
+                let d = to_world(key, part.center) - camera;
+                let d2 = d.dot(d);
+                order.push((key, i, d2));
+            }
+        }
+    }
+    order.sort_by(|a, b| b.2.total_cmp(&a.2));
+    order.into_iter().map(|(key, i, _)| (key, i)).collect()
+}
+
+/// Texture for material `mid` (see [`material_texture_path`]), loaded into `tex_cache` on
+/// first use.
+pub fn material_texture<'a>(
+    rl: &mut RaylibHandle,
+    thread: &RaylibThread,
+    tex_cache: &'a mut TextureCache,
+    mats: &MaterialCatalog,
+    mid: geist_blocks::types::MaterialId,
+) -> Option<&'a raylib::core::texture::Texture2D> {
+    let key = material_texture_path(mats, mid)?;
+    use std::collections::hash_map::Entry;
+    match tex_cache.map.entry(key) {
+        Entry::Occupied(e) => Some(e.into_mut()),
+        Entry::Vacant(v) => {
+            let t = rl.load_texture(thread, v.key()).ok()?;
+            t.set_texture_filter(thread, raylib::consts::TextureFilter::TEXTURE_FILTER_POINT);
+            t.set_texture_wrap(thread, raylib::consts::TextureWrap::TEXTURE_WRAP_REPEAT);
+            Some(v.insert(t))
+        }
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn upload_chunk_mesh(
     rl: &mut RaylibHandle,
     thread: &RaylibThread,
     cpu: ChunkMeshCPU,
     tex_cache: &mut TextureCache,
     mats: &MaterialCatalog,
+    tex_array: Option<&BlockTextureArray>,
+    wide: Option<&WideIndices>,
+    previous: Option<ChunkRender>,
 ) -> Option<ChunkRender> {
-    let ChunkMeshCPU { coord, bbox, parts } = cpu;
+    let mut cpu = cpu;
+    let merged = tex_array.and_then(|a| merge_layered_parts(&mut cpu, mats, |mid| a.layer(mid)));
+    let ChunkMeshCPU {
+        coord,
+        bbox,
+        parts,
+        decorations,
+    } = cpu;
+    // A rebuild refills the previous render's buffers where the new part fits, and keeps
+    // its light texture for `update_chunk_light_texture` to update in place.
+    let (mut spare, light_tex) = match previous {
+        Some(prev) => (prev.parts, prev.light_tex),
+        None => (Vec::new(), None),
+    };
+    let origin = bbox.min;
+    // Integer chunk origins subtract exactly, leaving small coordinates that stay precise
+    // however far the chunk is from the world origin.
+    let to_origin = raylib::math::Matrix::translate(origin.x, origin.y, origin.z);
     let mut parts_gpu: Vec<ChunkPart> = Vec::new();
-    for (mid, mb) in parts.into_iter() {
+    let builds = parts
+        .into_iter()
+        .map(|(mid, mb)| (mid, mb, None))
+        .chain(merged.map(|m| (m.mid, m.build, Some(m.layers))));
+    for (mid, mut mb, layers) in builds {
+        let vertex_layers = layers.is_some();
+        let layers = layers.unwrap_or_default();
         let total_verts = mb.pos.len() / 3;
         if total_verts == 0 {
+            recycle_build(mb);
             continue;
         }
-        let max_verts: usize = 65000;
+        for p in mb.pos.chunks_exact_mut(3) {
+            p[0] -= origin.x;
+            p[1] -= origin.y;
+            p[2] -= origin.z;
+        }
         let total_quads = total_verts / 4;
-        let max_quads = max_verts / 4;
+        // Past the 16-bit limit, keep the material whole with 32-bit indices when allowed.
+        let part_wide = wide.filter(|_| total_verts > U16_PART_VERTS).copied();
+        let max_quads = if part_wide.is_some() {
+            total_quads
+        } else {
+            U16_PART_VERTS / 4
+        };
         let mut q = 0usize;
         while q < total_quads {
             let take_q = (total_quads - q).min(max_quads);
             let v_start = q * 4;
             let v_count = take_q * 4;
+            let (mut lo, mut hi) = ([f32::MAX; 3], [f32::MIN; 3]);
+            for p in mb.pos[v_start * 3..(v_start + v_count) * 3].chunks_exact(3) {
+                for a in 0..3 {
+                    lo[a] = lo[a].min(p[a]);
+                    hi[a] = hi[a].max(p[a]);
+                }
+            }
+            let center = Vector3::new(
+                (lo[0] + hi[0]) * 0.5 + origin.x,
+                (lo[1] + hi[1]) * 0.5 + origin.y,
+                (lo[2] + hi[2]) * 0.5 + origin.z,
+            );
+            if let Some(mut part) =
+                take_spare(&mut spare, mid, part_wide.is_some(), vertex_layers, v_count)
+            {
+                // SAFETY: `take_spare` only hands out parts with room for `v_count`.
+                unsafe { part.refill(&mb, &layers, v_start, v_count) };
+                part.model.set_transform(&to_origin);
+                part.v_start = v_start;
+                part.v_count = v_count;
+                part.origin = [origin.x, origin.y, origin.z];
+                part.wide = part_wide;
+                part.pass = mats.render_pass(mid);
+                part.center = center;
+                parts_gpu.push(part);
+                q += take_q;
+                continue;
+            }
             let mut raw: raylib::ffi::Mesh = unsafe { std::mem::zeroed() };
             raw.vertexCount = v_count as i32;
             raw.triangleCount = (take_q * 2) as i32;
@@ -125,12 +447,10 @@ pub fn upload_chunk_mesh(
                 let nbytes = (v_count * 3 * std::mem::size_of::<f32>()) as u32;
                 let tbytes = (v_count * 2 * std::mem::size_of::<f32>()) as u32;
                 let cbytes = (v_count * 4 * std::mem::size_of::<u8>()) as u32;
-                let ibytes = (take_q * 6 * std::mem::size_of::<u16>()) as u32;
                 raw.vertices = raylib::ffi::MemAlloc(vbytes) as *mut f32;
                 raw.normals = raylib::ffi::MemAlloc(nbytes) as *mut f32;
                 raw.texcoords = raylib::ffi::MemAlloc(tbytes) as *mut f32;
                 raw.colors = raylib::ffi::MemAlloc(cbytes) as *mut u8;
-                raw.indices = raylib::ffi::MemAlloc(ibytes) as *mut u16;
                 std::ptr::copy_nonoverlapping(
                     mb.pos[pos_start..pos_end].as_ptr(),
                     raw.vertices,
@@ -151,94 +471,159 @@ pub fn upload_chunk_mesh(
                     raw.colors,
                     v_count * 4,
                 );
-                let idx_ptr = raw.indices;
-                let mut write = 0usize;
-                for i in 0..take_q {
-                    let base = (i * 4) as u16;
-                    let tri = [base, base + 1, base + 2, base, base + 2, base + 3];
-                    let dst = idx_ptr.add(write);
-                    std::ptr::copy_nonoverlapping(tri.as_ptr(), dst, 6);
-                    write += 6;
+                if vertex_layers {
+                    let uv2 = layer_coords(&layers[v_start..v_start + v_count]);
+                    raw.texcoords2 = raylib::ffi::MemAlloc(tbytes) as *mut f32;
+                    std::ptr::copy_nonoverlapping(uv2.as_ptr(), raw.texcoords2, v_count * 2);
+                }
+                if part_wide.is_none() {
+                    let ibytes = (take_q * 6 * std::mem::size_of::<u16>()) as u32;
+                    raw.indices = raylib::ffi::MemAlloc(ibytes) as *mut u16;
+                    let idx_ptr = raw.indices;
+                    let mut write = 0usize;
+                    for i in 0..take_q {
+                        let base = (i * 4) as u16;
+                        let tri = [base, base + 1, base + 2, base, base + 2, base + 3];
+                        let dst = idx_ptr.add(write);
+                        std::ptr::copy_nonoverlapping(tri.as_ptr(), dst, 6);
+                        write += 6;
+                    }
                 }
             }
             let mut mesh = unsafe { raylib::core::models::Mesh::from_raw(raw) };
             unsafe {
                 mesh.upload(false);
             }
+            if let Some(w) = part_wide {
+                let idx: Vec<u32> = (0..take_q as u32)
+                    .flat_map(|i| {
+                        let base = i * 4;
+                        [base, base + 1, base + 2, base, base + 2, base + 3]
+                    })
+                    .collect();
+                unsafe { w.attach(mesh.as_mut(), &idx) };
+            }
             let model = rl
                 .load_model_from_mesh(thread, unsafe { mesh.make_weak() })
                 .ok()?;
             let mut model = model;
-            if let Some(mat) = model.materials_mut().get_mut(0) {
-                if let Some(mdef) = mats.get(mid) {
-                    let candidates: Vec<String> = mdef
-                        .texture_candidates
-                        .iter()
-                        .map(|p| p.to_string_lossy().to_string())
-                        .collect();
-                    let chosen: Option<String> = candidates
-                        .iter()
-                        .find(|p| std::path::Path::new(p.as_str()).exists())
-                        .cloned()
-                        .or_else(|| candidates.first().cloned());
-                    if let Some(path) = chosen {
-                        let key = std::fs::canonicalize(&path)
-                            .ok()
-                            .map(|p| p.to_string_lossy().to_string())
-                            .unwrap_or(path);
-                        use std::collections::hash_map::Entry;
-                        match tex_cache.map.entry(key.clone()) {
-                            Entry::Occupied(e) => {
-                                let tex = e.into_mut();
-                                mat.set_material_texture(
-                                    raylib::consts::MaterialMapIndex::MATERIAL_MAP_ALBEDO,
-                                    tex,
-                                );
-                            }
-                            Entry::Vacant(v) => {
-                                if let Ok(t) = rl.load_texture(thread, &key) {
-                                    t.set_texture_filter(
-                                        thread,
-                                        raylib::consts::TextureFilter::TEXTURE_FILTER_POINT,
-                                    );
-                                    t.set_texture_wrap(
-                                        thread,
-                                        raylib::consts::TextureWrap::TEXTURE_WRAP_REPEAT,
-                                    );
-                                    v.insert(t);
-                                    if let Some(tex) = tex_cache.get_ref(&key) {
-                                        mat.set_material_texture(
-                                            raylib::consts::MaterialMapIndex::MATERIAL_MAP_ALBEDO,
-                                            tex,
-                                        );
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+            model.set_transform(&to_origin);
+            // Layered materials sample the bound texture array; skip their per-material bind.
+            let layered = vertex_layers || tex_array.and_then(|a| a.layer(mid)).is_some();
+            if let Some(mat) = model.materials_mut().get_mut(0)
+                && !layered
+                && let Some(tex) = material_texture(rl, thread, tex_cache, mats, mid)
+            {
+                mat.set_material_texture(
+                    raylib::consts::MaterialMapIndex::MATERIAL_MAP_ALBEDO,
+                    tex,
+                );
             }
             parts_gpu.push(ChunkPart {
                 mid,
                 model,
                 v_start,
                 v_count,
+                origin: [origin.x, origin.y, origin.z],
+                wide: part_wide,
+                pass: mats.render_pass(mid),
+                center,
+                capacity: v_count,
+                vertex_layers,
             });
             q += take_q;
         }
+        recycle_build(mb);
     }
     Some(ChunkRender {
         coord,
-        origin: [bbox.min.x, bbox.min.y, bbox.min.z],
+        origin: [origin.x, origin.y, origin.z],
         bbox: conv::aabb_to_rl(bbox),
         parts: parts_gpu,
         leaf_tint: None,
-        light_tex: None,
+        light_tex,
+        decorations,
     })
 }
 
 // Phase 1 color buffer updates removed in Phase 2.
 
+/// raylib hands back its default shader when compile/link fails; treat that as a load failure.
+fn shader_compiled(shader: &raylib::shaders::WeakShader) -> bool {
+    let id = shader.as_ref().id;
+    id != 0 && id != unsafe { raylib::ffi::rlGetShaderIdDefault() }
+}
+
+/// CPU stand-in for the lighting shaders (GL compatibility mode).
+///
+/// Scales each quad's vertex color by the brightness the fragment shaders would
+/// sample from `atlas`: the voxel under the face and its neighbour along the normal.
+/// Skylight is baked at `sky_scale`, so the result does not follow the day cycle. Vertex
+/// alpha carries data only the water shader reads, so it is reset to opaque.
+pub fn bake_vertex_light(
+    cpu: &mut ChunkMeshCPU,
+    atlas: &geist_lighting::LightAtlas,
+    sky_scale: f32,
+    visual_min: f32,
+) {
+    let origin = cpu.bbox.min;
+    let (sx, sy, sz) = (atlas.sx as i32, atlas.sy as i32, atlas.sz as i32);
+    let tile_w = atlas.sx + 2;
+    let tile_h = atlas.sz + 2;
+    let cols = atlas.grid_cols.max(1);
+    let sky_scale = sky_scale.clamp(0.0, 1.0);
+    // Atlas coords carry a one-voxel seam ring on every axis.
+    let fetch = |x: i32, y: i32, z: i32| -> (u8, u8, u8) {
+        let (ax, ay, az) = ((x + 1) as usize, (y + 1) as usize, (z + 1) as usize);
+        let px = (ay % cols) * tile_w + ax;
+        let py = (ay / cols) * tile_h + az;
+        let i = (py * atlas.width + px) * 4;
+        match atlas.data.get(i..i + 3) {
+            Some(p) => (p[0], p[1], p[2]),
+            None => (0, 0, 0),
+        }
+    };
+    for mb in cpu.parts.values_mut() {
+        let quads = mb.pos.len() / 12;
+        for q in 0..quads {
+            let p = &mb.pos[q * 12..q * 12 + 12];
+            let cx = (p[0] + p[3] + p[6] + p[9]) * 0.25 - origin.x;
+            let cy = (p[1] + p[4] + p[7] + p[10]) * 0.25 - origin.y;
+            let cz = (p[2] + p[5] + p[8] + p[11]) * 0.25 - origin.z;
+            let n = &mb.norm[q * 12..q * 12 + 3];
+            let v = (
+                (cx.floor() as i32).clamp(0, sx - 1),
+                (cy.floor() as i32).clamp(0, sy - 1),
+                (cz.floor() as i32).clamp(0, sz - 1),
+            );
+            let step = if n[0].abs() > n[1].abs() && n[0].abs() > n[2].abs() {
+                (n[0].signum() as i32, 0, 0)
+            } else if n[2].abs() > n[1].abs() {
+                (0, 0, n[2].signum() as i32)
+            } else {
+                (0, n[1].signum() as i32, 0)
+            };
+            let vn = (
+                (v.0 + step.0).clamp(-1, sx),
+                (v.1 + step.1).clamp(-1, sy),
+                (v.2 + step.2).clamp(-1, sz),
+            );
+            let l0 = fetch(v.0, v.1, v.2);
+            let l1 = fetch(vn.0, vn.1, vn.2);
+            let blk = l0.0.max(l1.0) as f32 / 255.0;
+            let sky = l0.1.max(l1.1) as f32 / 255.0 * sky_scale;
+            let bcn = l0.2.max(l1.2) as f32 / 255.0;
+            let lv = blk.max(sky).max(bcn).max(visual_min);
+            for c in mb.col[q * 16..q * 16 + 16].chunks_exact_mut(4) {
+                c[0] = (c[0] as f32 * lv).round() as u8;
+                c[1] = (c[1] as f32 * lv).round() as u8;
+                c[2] = (c[2] as f32 * lv).round() as u8;
+                c[3] = 255;
+            }
+        }
+    }
+}
+
 pub struct LeavesShader {
     pub shader: raylib::shaders::WeakShader,
     pub loc_fog_color: i32,
@@ -259,6 +644,18 @@ pub struct LeavesShader {
     pub loc_chunk_origin: i32,
     pub loc_vis_min: i32,
     pub loc_sky_scale: i32,
+    pub loc_water_transmittance: i32,
+    pub loc_flicker_amount: i32,
+    pub loc_dyn_light: DynamicLightLocs,
+    pub loc_shadow: ShadowLocs,
+    // Block texture array
+    pub loc_block_textures: i32,
+    pub loc_block_layer: i32,
+    pub loc_use_block_array: i32,
+    // Per-material wind sway
+    pub loc_sway_amplitude: i32,
+    pub loc_sway_frequency: i32,
+    pub loc_sway_stiffness: i32,
 }
 
 impl LeavesShader {
@@ -266,7 +663,10 @@ impl LeavesShader {
         let vs = "assets/shaders/voxel_fog_textured.vs";
         let fs = "assets/shaders/voxel_fog_leaves.fs";
         let shader_strong = rl.load_shader(thread, Some(vs), Some(fs));
-        let shader = unsafe { shader_strong.make_weak() };
+        let mut shader = unsafe { shader_strong.make_weak() };
+        if !shader_compiled(&shader) {
+            return None;
+        }
         let loc_fog_color = shader.get_shader_location("fogColor");
         let loc_fog_start = shader.get_shader_location("fogStart");
         let loc_fog_end = shader.get_shader_location("fogEnd");
@@ -284,6 +684,22 @@ impl LeavesShader {
         let loc_chunk_origin = shader.get_shader_location("chunkOrigin");
         let loc_vis_min = shader.get_shader_location("visualLightMin");
         let loc_sky_scale = shader.get_shader_location("skyLightScale");
+        let loc_water_transmittance = shader.get_shader_location("waterTransmittance");
+        let loc_flicker_amount = shader.get_shader_location("flickerAmount");
+        if loc_flicker_amount >= 0 {
+            shader.set_shader_value(loc_flicker_amount, 1.0f32);
+        }
+        let loc_dyn_light = DynamicLightLocs::locate(&shader);
+        let loc_shadow = ShadowLocs::locate(&shader);
+        let loc_block_textures = shader.get_shader_location("blockTextures");
+        let loc_block_layer = shader.get_shader_location("blockLayer");
+        let loc_use_block_array = shader.get_shader_location("useBlockArray");
+        let loc_sway_amplitude = shader.get_shader_location("swayAmplitude");
+        let loc_sway_frequency = shader.get_shader_location("swayFrequency");
+        let loc_sway_stiffness = shader.get_shader_location("swayStiffness");
+        if loc_block_textures >= 0 {
+            shader.set_shader_value(loc_block_textures, BLOCK_ARRAY_SLOT);
+        }
         let mut s = Self {
             shader,
             loc_fog_color,
@@ -303,6 +719,16 @@ impl LeavesShader {
             loc_chunk_origin,
             loc_vis_min,
             loc_sky_scale,
+            loc_water_transmittance,
+            loc_flicker_amount,
+            loc_dyn_light,
+            loc_shadow,
+            loc_block_textures,
+            loc_block_layer,
+            loc_use_block_array,
+            loc_sway_amplitude,
+            loc_sway_frequency,
+            loc_sway_stiffness,
         };
         s.set_autumn_palette(
             [0.905, 0.678, 0.161],
@@ -325,7 +751,10 @@ impl LeavesShader {
             Some(vs.to_string_lossy().as_ref()),
             Some(fs.to_string_lossy().as_ref()),
         );
-        let shader = unsafe { shader_strong.make_weak() };
+        let mut shader = unsafe { shader_strong.make_weak() };
+        if !shader_compiled(&shader) {
+            return None;
+        }
         let loc_fog_color = shader.get_shader_location("fogColor");
         let loc_fog_start = shader.get_shader_location("fogStart");
         let loc_fog_end = shader.get_shader_location("fogEnd");
@@ -343,6 +772,22 @@ impl LeavesShader {
         let loc_chunk_origin = shader.get_shader_location("chunkOrigin");
         let loc_vis_min = shader.get_shader_location("visualLightMin");
         let loc_sky_scale = shader.get_shader_location("skyLightScale");
+        let loc_water_transmittance = shader.get_shader_location("waterTransmittance");
+        let loc_flicker_amount = shader.get_shader_location("flickerAmount");
+        if loc_flicker_amount >= 0 {
+            shader.set_shader_value(loc_flicker_amount, 1.0f32);
+        }
+        let loc_dyn_light = DynamicLightLocs::locate(&shader);
+        let loc_shadow = ShadowLocs::locate(&shader);
+        let loc_block_textures = shader.get_shader_location("blockTextures");
+        let loc_block_layer = shader.get_shader_location("blockLayer");
+        let loc_use_block_array = shader.get_shader_location("useBlockArray");
+        let loc_sway_amplitude = shader.get_shader_location("swayAmplitude");
+        let loc_sway_frequency = shader.get_shader_location("swayFrequency");
+        let loc_sway_stiffness = shader.get_shader_location("swayStiffness");
+        if loc_block_textures >= 0 {
+            shader.set_shader_value(loc_block_textures, BLOCK_ARRAY_SLOT);
+        }
         let mut s = Self {
             shader,
             loc_fog_color,
@@ -362,6 +807,16 @@ impl LeavesShader {
             loc_chunk_origin,
             loc_vis_min,
             loc_sky_scale,
+            loc_water_transmittance,
+            loc_flicker_amount,
+            loc_dyn_light,
+            loc_shadow,
+            loc_block_textures,
+            loc_block_layer,
+            loc_use_block_array,
+            loc_sway_amplitude,
+            loc_sway_frequency,
+            loc_sway_stiffness,
         };
         s.set_autumn_palette(
             [0.905, 0.678, 0.161],
@@ -430,6 +885,28 @@ impl LeavesShader {
             self.shader.set_shader_value(self.loc_sky_scale, sky_scale);
         }
     }
+    /// Per-block RGB transmittance of water, matching the lighting store's water medium.
+    pub fn set_water_transmittance(&mut self, rgb: [f32; 3]) {
+        if self.loc_water_transmittance >= 0 {
+            self.shader
+                .set_shader_value(self.loc_water_transmittance, rgb);
+        }
+    }
+    /// Strength of emitter flicker animation: 1 is full, 0 holds every emitter steady.
+    pub fn set_flicker_amount(&mut self, amount: f32) {
+        if self.loc_flicker_amount >= 0 {
+            self.shader
+                .set_shader_value(self.loc_flicker_amount, amount.clamp(0.0, 1.0));
+        }
+    }
+    /// Dynamic light volume for this frame; `None` disables the lookup.
+    pub fn set_dynamic_light(&mut self, tex: Option<&DynamicLightTex>, render: &FloatingOrigin) {
+        self.loc_dyn_light.apply(&mut self.shader, tex, render);
+    }
+    /// Sun shadow cascades for this frame; `None` leaves skylight unshadowed.
+    pub fn set_shadows(&mut self, shadows: Option<&ShadowMaps>) {
+        self.loc_shadow.apply(&mut self.shader, shadows);
+    }
     pub fn update_chunk_uniforms(
         &mut self,
         thread: &RaylibThread,
@@ -470,6 +947,36 @@ impl LeavesShader {
         }
         let _ = thread; // unused here but kept for parity
     }
+    /// Sample `layer` of the bound block texture array, or the material texture for `None`.
+    pub fn set_block_layer(&mut self, layer: Option<i32>) {
+        if self.loc_use_block_array >= 0 {
+            let v: i32 = if layer.is_some() { 1 } else { 0 };
+            self.shader.set_shader_value(self.loc_use_block_array, v);
+        }
+        if let Some(layer) = layer
+            && self.loc_block_layer >= 0
+        {
+            self.shader.set_shader_value(self.loc_block_layer, layer);
+        }
+    }
+    /// Sway parameters for the next draw; `None` keeps the mesh static.
+    pub fn set_material_animation(&mut self, anim: Option<&MaterialAnimation>) {
+        let (amplitude, frequency, stiffness) = anim
+            .map(|a| (a.sway_amplitude, a.sway_frequency, a.stiffness))
+            .unwrap_or((0.0, 0.0, 0.0));
+        if self.loc_sway_amplitude >= 0 {
+            self.shader
+                .set_shader_value(self.loc_sway_amplitude, amplitude);
+        }
+        if self.loc_sway_frequency >= 0 {
+            self.shader
+                .set_shader_value(self.loc_sway_frequency, frequency);
+        }
+        if self.loc_sway_stiffness >= 0 {
+            self.shader
+                .set_shader_value(self.loc_sway_stiffness, stiffness);
+        }
+    }
     pub fn update_chunk_uniforms_no_tex(
         &mut self,
         _thread: &RaylibThread,
@@ -511,6 +1018,18 @@ pub struct FogShader {
     pub loc_chunk_origin: i32,
     pub loc_vis_min: i32,
     pub loc_sky_scale: i32,
+    pub loc_water_transmittance: i32,
+    pub loc_flicker_amount: i32,
+    pub loc_dyn_light: DynamicLightLocs,
+    pub loc_shadow: ShadowLocs,
+    // Block texture array
+    pub loc_block_textures: i32,
+    pub loc_block_layer: i32,
+    pub loc_use_block_array: i32,
+    // Per-material wind sway
+    pub loc_sway_amplitude: i32,
+    pub loc_sway_frequency: i32,
+    pub loc_sway_stiffness: i32,
 }
 
 impl FogShader {
@@ -518,7 +1037,10 @@ impl FogShader {
         let vs = "assets/shaders/voxel_fog_textured.vs";
         let fs = "assets/shaders/voxel_fog_textured.fs";
         let shader_strong = rl.load_shader(thread, Some(vs), Some(fs));
-        let shader = unsafe { shader_strong.make_weak() };
+        let mut shader = unsafe { shader_strong.make_weak() };
+        if !shader_compiled(&shader) {
+            return None;
+        }
         let loc_fog_color = shader.get_shader_location("fogColor");
         let loc_fog_start = shader.get_shader_location("fogStart");
         let loc_fog_end = shader.get_shader_location("fogEnd");
@@ -531,6 +1053,22 @@ impl FogShader {
         let loc_chunk_origin = shader.get_shader_location("chunkOrigin");
         let loc_vis_min = shader.get_shader_location("visualLightMin");
         let loc_sky_scale = shader.get_shader_location("skyLightScale");
+        let loc_water_transmittance = shader.get_shader_location("waterTransmittance");
+        let loc_flicker_amount = shader.get_shader_location("flickerAmount");
+        if loc_flicker_amount >= 0 {
+            shader.set_shader_value(loc_flicker_amount, 1.0f32);
+        }
+        let loc_dyn_light = DynamicLightLocs::locate(&shader);
+        let loc_shadow = ShadowLocs::locate(&shader);
+        let loc_block_textures = shader.get_shader_location("blockTextures");
+        let loc_block_layer = shader.get_shader_location("blockLayer");
+        let loc_use_block_array = shader.get_shader_location("useBlockArray");
+        let loc_sway_amplitude = shader.get_shader_location("swayAmplitude");
+        let loc_sway_frequency = shader.get_shader_location("swayFrequency");
+        let loc_sway_stiffness = shader.get_shader_location("swayStiffness");
+        if loc_block_textures >= 0 {
+            shader.set_shader_value(loc_block_textures, BLOCK_ARRAY_SLOT);
+        }
         Some(Self {
             shader,
             loc_fog_color,
@@ -545,6 +1083,16 @@ impl FogShader {
             loc_chunk_origin,
             loc_vis_min,
             loc_sky_scale,
+            loc_water_transmittance,
+            loc_flicker_amount,
+            loc_dyn_light,
+            loc_shadow,
+            loc_block_textures,
+            loc_block_layer,
+            loc_use_block_array,
+            loc_sway_amplitude,
+            loc_sway_frequency,
+            loc_sway_stiffness,
         })
     }
     pub fn load_with_base(
@@ -559,7 +1107,10 @@ impl FogShader {
             Some(vs.to_string_lossy().as_ref()),
             Some(fs.to_string_lossy().as_ref()),
         );
-        let shader = unsafe { shader_strong.make_weak() };
+        let mut shader = unsafe { shader_strong.make_weak() };
+        if !shader_compiled(&shader) {
+            return None;
+        }
         let loc_fog_color = shader.get_shader_location("fogColor");
         let loc_fog_start = shader.get_shader_location("fogStart");
         let loc_fog_end = shader.get_shader_location("fogEnd");
@@ -572,6 +1123,22 @@ impl FogShader {
         let loc_chunk_origin = shader.get_shader_location("chunkOrigin");
         let loc_vis_min = shader.get_shader_location("visualLightMin");
         let loc_sky_scale = shader.get_shader_location("skyLightScale");
+        let loc_water_transmittance = shader.get_shader_location("waterTransmittance");
+        let loc_flicker_amount = shader.get_shader_location("flickerAmount");
+        if loc_flicker_amount >= 0 {
+            shader.set_shader_value(loc_flicker_amount, 1.0f32);
+        }
+        let loc_dyn_light = DynamicLightLocs::locate(&shader);
+        let loc_shadow = ShadowLocs::locate(&shader);
+        let loc_block_textures = shader.get_shader_location("blockTextures");
+        let loc_block_layer = shader.get_shader_location("blockLayer");
+        let loc_use_block_array = shader.get_shader_location("useBlockArray");
+        let loc_sway_amplitude = shader.get_shader_location("swayAmplitude");
+        let loc_sway_frequency = shader.get_shader_location("swayFrequency");
+        let loc_sway_stiffness = shader.get_shader_location("swayStiffness");
+        if loc_block_textures >= 0 {
+            shader.set_shader_value(loc_block_textures, BLOCK_ARRAY_SLOT);
+        }
         Some(Self {
             shader,
             loc_fog_color,
@@ -586,6 +1153,16 @@ impl FogShader {
             loc_chunk_origin,
             loc_vis_min,
             loc_sky_scale,
+            loc_water_transmittance,
+            loc_flicker_amount,
+            loc_dyn_light,
+            loc_shadow,
+            loc_block_textures,
+            loc_block_layer,
+            loc_use_block_array,
+            loc_sway_amplitude,
+            loc_sway_frequency,
+            loc_sway_stiffness,
         })
     }
     pub fn update_frame_uniforms(
@@ -622,6 +1199,28 @@ impl FogShader {
             self.shader.set_shader_value(self.loc_sky_scale, sky_scale);
         }
     }
+    /// Per-block RGB transmittance of water, matching the lighting store's water medium.
+    pub fn set_water_transmittance(&mut self, rgb: [f32; 3]) {
+        if self.loc_water_transmittance >= 0 {
+            self.shader
+                .set_shader_value(self.loc_water_transmittance, rgb);
+        }
+    }
+    /// Strength of emitter flicker animation: 1 is full, 0 holds every emitter steady.
+    pub fn set_flicker_amount(&mut self, amount: f32) {
+        if self.loc_flicker_amount >= 0 {
+            self.shader
+                .set_shader_value(self.loc_flicker_amount, amount.clamp(0.0, 1.0));
+        }
+    }
+    /// Dynamic light volume for this frame; `None` disables the lookup.
+    pub fn set_dynamic_light(&mut self, tex: Option<&DynamicLightTex>, render: &FloatingOrigin) {
+        self.loc_dyn_light.apply(&mut self.shader, tex, render);
+    }
+    /// Sun shadow cascades for this frame; `None` leaves skylight unshadowed.
+    pub fn set_shadows(&mut self, shadows: Option<&ShadowMaps>) {
+        self.loc_shadow.apply(&mut self.shader, shadows);
+    }
     pub fn update_chunk_uniforms(
         &mut self,
         thread: &RaylibThread,
@@ -662,6 +1261,36 @@ impl FogShader {
         }
         let _ = thread;
     }
+    /// Sample `layer` of the bound block texture array, or the material texture for `None`.
+    pub fn set_block_layer(&mut self, layer: Option<i32>) {
+        if self.loc_use_block_array >= 0 {
+            let v: i32 = if layer.is_some() { 1 } else { 0 };
+            self.shader.set_shader_value(self.loc_use_block_array, v);
+        }
+        if let Some(layer) = layer
+            && self.loc_block_layer >= 0
+        {
+            self.shader.set_shader_value(self.loc_block_layer, layer);
+        }
+    }
+    /// Sway parameters for the next draw; `None` keeps the mesh static.
+    pub fn set_material_animation(&mut self, anim: Option<&MaterialAnimation>) {
+        let (amplitude, frequency, stiffness) = anim
+            .map(|a| (a.sway_amplitude, a.sway_frequency, a.stiffness))
+            .unwrap_or((0.0, 0.0, 0.0));
+        if self.loc_sway_amplitude >= 0 {
+            self.shader
+                .set_shader_value(self.loc_sway_amplitude, amplitude);
+        }
+        if self.loc_sway_frequency >= 0 {
+            self.shader
+                .set_shader_value(self.loc_sway_frequency, frequency);
+        }
+        if self.loc_sway_stiffness >= 0 {
+            self.shader
+                .set_shader_value(self.loc_sway_stiffness, stiffness);
+        }
+    }
     pub fn update_chunk_uniforms_no_tex(
         &mut self,
         _thread: &RaylibThread,
@@ -688,6 +1317,9 @@ impl FogShader {
     }
 }
 
+/// Water surface shader. Reads the vertex alpha the mesher writes on water tops
+/// (`geist_mesh_cpu::WATER_OPEN_CAP`): foam where it nears 0 at the banks, and waves that
+/// grow toward 1 over deep open water.
 pub struct WaterShader {
     pub shader: raylib::shaders::WeakShader,
     pub loc_fog_color: i32,
@@ -703,6 +1335,10 @@ pub struct WaterShader {
     pub loc_chunk_origin: i32,
     pub loc_vis_min: i32,
     pub loc_sky_scale: i32,
+    pub loc_water_transmittance: i32,
+    pub loc_flicker_amount: i32,
+    pub loc_dyn_light: DynamicLightLocs,
+    pub loc_shadow: ShadowLocs,
 }
 
 impl WaterShader {
@@ -718,7 +1354,10 @@ impl WaterShader {
             Some(vs.to_string_lossy().as_ref()),
             Some(fs.to_string_lossy().as_ref()),
         );
-        let shader = unsafe { shader_strong.make_weak() };
+        let mut shader = unsafe { shader_strong.make_weak() };
+        if !shader_compiled(&shader) {
+            return None;
+        }
         let loc_fog_color = shader.get_shader_location("fogColor");
         let loc_fog_start = shader.get_shader_location("fogStart");
         let loc_fog_end = shader.get_shader_location("fogEnd");
@@ -731,6 +1370,13 @@ impl WaterShader {
         let loc_chunk_origin = shader.get_shader_location("chunkOrigin");
         let loc_vis_min = shader.get_shader_location("visualLightMin");
         let loc_sky_scale = shader.get_shader_location("skyLightScale");
+        let loc_water_transmittance = shader.get_shader_location("waterTransmittance");
+        let loc_flicker_amount = shader.get_shader_location("flickerAmount");
+        if loc_flicker_amount >= 0 {
+            shader.set_shader_value(loc_flicker_amount, 1.0f32);
+        }
+        let loc_dyn_light = DynamicLightLocs::locate(&shader);
+        let loc_shadow = ShadowLocs::locate(&shader);
         Some(Self {
             loc_fog_color,
             loc_fog_start,
@@ -745,6 +1391,10 @@ impl WaterShader {
             loc_vis_min,
             shader,
             loc_sky_scale,
+            loc_water_transmittance,
+            loc_flicker_amount,
+            loc_dyn_light,
+            loc_shadow,
         })
     }
     pub fn update_frame_uniforms(
@@ -781,6 +1431,28 @@ impl WaterShader {
             self.shader.set_shader_value(self.loc_sky_scale, sky_scale);
         }
     }
+    /// Per-block RGB transmittance of water, matching the lighting store's water medium.
+    pub fn set_water_transmittance(&mut self, rgb: [f32; 3]) {
+        if self.loc_water_transmittance >= 0 {
+            self.shader
+                .set_shader_value(self.loc_water_transmittance, rgb);
+        }
+    }
+    /// Strength of emitter flicker animation: 1 is full, 0 holds every emitter steady.
+    pub fn set_flicker_amount(&mut self, amount: f32) {
+        if self.loc_flicker_amount >= 0 {
+            self.shader
+                .set_shader_value(self.loc_flicker_amount, amount.clamp(0.0, 1.0));
+        }
+    }
+    /// Dynamic light volume for this frame; `None` disables the lookup.
+    pub fn set_dynamic_light(&mut self, tex: Option<&DynamicLightTex>, render: &FloatingOrigin) {
+        self.loc_dyn_light.apply(&mut self.shader, tex, render);
+    }
+    /// Sun shadow cascades for this frame; `None` leaves skylight unshadowed.
+    pub fn set_shadows(&mut self, shadows: Option<&ShadowMaps>) {
+        self.loc_shadow.apply(&mut self.shader, shadows);
+    }
     pub fn update_chunk_uniforms(
         &mut self,
         thread: &RaylibThread,
@@ -0,0 +1,204 @@
+//! Offscreen target the 3D scene renders into at a scale of the window size, then
+//! stretched onto the backbuffer. Below 1x it trades sharpness for fill rate; above 1x
+//! it supersamples. The window's MSAA only covers the backbuffer, so a scaled scene is
+//! not multisampled; at 1x with a plain filter the scene skips the target entirely.
+
+use raylib::prelude::*;
+
+pub const MIN_RENDER_SCALE: f32 = 0.25;
+pub const MAX_RENDER_SCALE: f32 = 2.0;
+/// Steps the settings window cycles through.
+pub const RENDER_SCALE_PRESETS: [f32; 7] = [0.5, 0.67, 0.75, 1.0, 1.25, 1.5, 2.0];
+
+/// How the scaled scene is stretched onto the window.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UpscaleFilter {
+    /// Blocky pixels, the cheapest.
+    Nearest,
+    #[default]
+    Bilinear,
+    /// Bilinear followed by a contrast-adaptive sharpen, to win back detail lost below 1x.
+    Sharpen,
+}
+
+impl UpscaleFilter {
+    pub const ALL: [Self; 3] = [Self::Nearest, Self::Bilinear, Self::Sharpen];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Nearest => "nearest",
+            Self::Bilinear => "bilinear",
+            Self::Sharpen => "sharpen",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|f| *f == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+/// Contrast-adaptive sharpen applied while blitting the scene target.
+pub struct SharpenShader {
+    pub shader: raylib::shaders::WeakShader,
+    pub loc_texel_size: i32,
+    pub loc_sharpness: i32,
+}
+
+impl SharpenShader {
+    pub fn load_with_base(
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        base: &std::path::Path,
+    ) -> Option<Self> {
+        let fs = base.join("assets/shaders/upscale_sharpen.fs");
+        let shader_strong = rl.load_shader(thread, None, Some(fs.to_string_lossy().as_ref()));
+        let shader = unsafe { shader_strong.make_weak() };
+        if !crate::shader_compiled(&shader) {
+            return None;
+        }
+        let loc_texel_size = shader.get_shader_location("texelSize");
+        let loc_sharpness = shader.get_shader_location("sharpness");
+        Some(Self {
+            shader,
+            loc_texel_size,
+            loc_sharpness,
+        })
+    }
+}
+
+pub struct SceneTarget {
+    rt: Option<RenderTexture2D>,
+    size: (i32, i32),
+    scale: f32,
+    filter: UpscaleFilter,
+    /// 0 keeps the bilinear image, 1 sharpens the most.
+    pub sharpness: f32,
+}
+
+impl SceneTarget {
+    pub fn new(scale: f32, filter: UpscaleFilter) -> Self {
+        Self {
+            rt: None,
+            size: (0, 0),
+            scale: scale.clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE),
+            filter,
+            sharpness: 0.6,
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE);
+    }
+
+    /// Move `steps` presets up or down from the current scale, stopping at the ends.
+    pub fn step_scale(&mut self, steps: i32) {
+        let current = RENDER_SCALE_PRESETS
+            .iter()
+            .position(|s| *s >= self.scale - 1e-3)
+            .unwrap_or(RENDER_SCALE_PRESETS.len() - 1) as i32;
+        let i = (current + steps).clamp(0, RENDER_SCALE_PRESETS.len() as i32 - 1);
+        self.set_scale(RENDER_SCALE_PRESETS[i as usize]);
+    }
+
+    pub fn filter(&self) -> UpscaleFilter {
+        self.filter
+    }
+
+    pub fn set_filter(&mut self, filter: UpscaleFilter) {
+        self.filter = filter;
+    }
+
+    /// Whether the scene draws straight to the backbuffer (1x without sharpening).
+    pub fn is_direct(&self) -> bool {
+        (self.scale - 1.0).abs() < 1e-3 && self.filter != UpscaleFilter::Sharpen
+    }
+
+    /// Internal resolution for a window of `screen` pixels.
+    pub fn scaled_size(&self, screen: (i32, i32)) -> (i32, i32) {
+        (
+            ((screen.0 as f32 * self.scale).round() as i32).max(1),
+            ((screen.1 as f32 * self.scale).round() as i32).max(1),
+        )
+    }
+
+    /// Size the target for `screen`, recreating it after a resize or scale change.
+    /// Returns false when the scene should draw to the backbuffer instead, either
+    /// because no scaling is needed or the target could not be created.
+    pub fn prepare(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        screen: (i32, i32),
+    ) -> bool {
+        if self.is_direct() {
+            self.rt = None;
+            return false;
+        }
+        let size = self.scaled_size(screen);
+        if self.rt.is_none() || self.size != size {
+            self.rt = match rl.load_render_texture(thread, size.0 as u32, size.1 as u32) {
+                Ok(rt) => Some(rt),
+                Err(e) => {
+                    log::warn!("scene target {}x{} unavailable: {}", size.0, size.1, e);
+                    None
+                }
+            };
+            self.size = size;
+        }
+        self.rt.is_some()
+    }
+
+    /// Redirect drawing into the target until `end`.
+    pub fn begin(&self) {
+        if let Some(rt) = &self.rt {
+            unsafe { raylib::ffi::BeginTextureMode(*rt.as_ref()) }
+        }
+    }
+
+    pub fn end(&self) {
+        if self.rt.is_some() {
+            unsafe { raylib::ffi::EndTextureMode() }
+        }
+    }
+
+    /// Stretch the target over the `screen`-sized backbuffer with the current filter.
+    pub fn blit<D: RaylibDraw>(
+        &self,
+        d: &mut D,
+        screen: (i32, i32),
+        sharpen: Option<&mut SharpenShader>,
+    ) {
+        let Some(rt) = &self.rt else {
+            return;
+        };
+        let tex = rt.texture();
+        let gl_filter = match self.filter {
+            UpscaleFilter::Nearest => TextureFilter::TEXTURE_FILTER_POINT,
+            _ => TextureFilter::TEXTURE_FILTER_BILINEAR,
+        };
+        unsafe { raylib::ffi::SetTextureFilter(*tex.as_ref(), gl_filter as i32) }
+        let shader = match (self.filter, sharpen) {
+            (UpscaleFilter::Sharpen, Some(s)) => {
+                let texel = Vector2::new(1.0 / self.size.0 as f32, 1.0 / self.size.1 as f32);
+                s.shader.set_shader_value(s.loc_texel_size, texel);
+                s.shader
+                    .set_shader_value(s.loc_sharpness, self.sharpness.clamp(0.0, 1.0));
+                unsafe { raylib::ffi::BeginShaderMode(*s.shader.as_ref()) }
+                true
+            }
+            _ => false,
+        };
+        // Render textures are stored bottom-up.
+        let src = Rectangle::new(0.0, 0.0, self.size.0 as f32, -(self.size.1 as f32));
+        let dest = Rectangle::new(0.0, 0.0, screen.0 as f32, screen.1 as f32);
+        d.draw_texture_pro(tex, src, dest, Vector2::zero(), 0.0, Color::WHITE);
+        if shader {
+            unsafe { raylib::ffi::EndShaderMode() }
+        }
+    }
+}
@@ -0,0 +1,502 @@
+//! Directional shadow maps for the sun.
+//!
+//! Each cascade is a depth texture rendered with an orthographic projection looking down
+//! the sun direction, covering a sphere around the camera; the nearest cascade is the
+//! smallest and sharpest. A sphere keeps the projection fixed while the camera turns, and
+//! snapping its centre to whole texels keeps shadow edges from crawling as it moves. The
+//! voxel shaders pick the first cascade whose sphere holds a fragment and scale its
+//! skylight by how much of a 3x3 neighbourhood of the map is lit, so shadows only darken
+//! what the sky lights and never touch torches or beacons.
+//!
+//! rlgl can build a depth-only framebuffer but not turn off its colour draw buffer, which
+//! strict GL 3.3 drivers require; those two calls are resolved through GLFW like the
+//! texture array's.
+
+use std::ffi::{CString, c_char, c_void};
+
+use raylib::prelude::*;
+
+use crate::ChunkPart;
+use crate::culling::{Frustum, Plane};
+
+/// First texture unit of the cascades; cascade `i` uses `SHADOW_MAP_SLOT + i`. Block
+/// arrays use 6, chunk light 7 and dynamic light 8.
+pub const SHADOW_MAP_SLOT: i32 = 9;
+/// Cascades the shaders can sample.
+pub const MAX_SHADOW_CASCADES: usize = 3;
+/// Map sizes the settings window cycles through.
+pub const SHADOW_RESOLUTION_PRESETS: [i32; 4] = [512, 1024, 2048, 4096];
+/// How far up-sun of a cascade's sphere casters are still drawn, so hills and trees
+/// just outside it keep their shadows.
+pub const SHADOW_CASTER_MARGIN: f32 = 96.0;
+
+const GL_NONE: u32 = 0;
+
+unsafe extern "C" {
+    fn glfwGetProcAddress(procname: *const c_char) -> *const c_void;
+}
+
+type DrawBuffer = unsafe extern "system" fn(u32);
+type ReadBuffer = unsafe extern "system" fn(u32);
+
+/// Shadow quality: map size, cascade count (0 turns shadows off) and reach.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShadowSettings {
+    pub resolution: i32,
+    pub cascades: usize,
+    /// Distance from the camera, in blocks, the last cascade reaches.
+    pub distance: f32,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self {
+            resolution: 2048,
+            cascades: 2,
+            distance: 96.0,
+        }
+    }
+}
+
+impl ShadowSettings {
+    pub fn enabled(&self) -> bool {
+        self.cascades > 0
+    }
+
+    /// Next resolution preset, wrapping from the largest back to the smallest.
+    pub fn cycle_resolution(&mut self) {
+        let next = SHADOW_RESOLUTION_PRESETS
+            .iter()
+            .position(|r| *r > self.resolution)
+            .unwrap_or(0);
+        self.resolution = SHADOW_RESOLUTION_PRESETS[next];
+    }
+
+    /// Next cascade count, wrapping from the maximum back to off.
+    pub fn cycle_cascades(&mut self) {
+        self.cascades = (self.cascades + 1) % (MAX_SHADOW_CASCADES + 1);
+    }
+
+    /// Radius around the camera each cascade covers, nearest first. Splits grow with the
+    /// square of the cascade index, giving the near cascades most of the detail.
+    pub fn splits(&self) -> Vec<f32> {
+        let n = self.cascades.min(MAX_SHADOW_CASCADES);
+        (1..=n)
+            .map(|i| {
+                let t = i as f32 / n as f32;
+                self.distance.max(1.0) * t * t
+            })
+            .collect()
+    }
+
+    pub fn label(&self) -> String {
+        if self.enabled() {
+            format!(
+                "{} x {} cascade{}",
+                self.resolution,
+                self.cascades,
+                if self.cascades == 1 { "" } else { "s" }
+            )
+        } else {
+            "off".to_string()
+        }
+    }
+}
+
+/// Light view and orthographic projection for a cascade covering the sphere of `radius`
+/// at `center` (render space), lit from `sun_dir` (towards the sun) on a map of
+/// `resolution` texels a side.
+pub fn cascade_view_proj(
+    sun_dir: Vector3,
+    center: Vector3,
+    radius: f32,
+    resolution: i32,
+) -> (Matrix, Matrix) {
+    let dir = sun_dir.normalized();
+    let up = if dir.y.abs() > 0.99 {
+        Vector3::new(0.0, 0.0, 1.0)
+    } else {
+        Vector3::new(0.0, 1.0, 0.0)
+    };
+    // Fixed at the origin so only the sun's direction, never the camera, turns it.
+    let view = Matrix::look_at(Vector3::zero(), -dir, up);
+    let c = center.transform_with(view);
+    let texel = 2.0 * radius / resolution.max(1) as f32;
+    let cx = (c.x / texel).floor() * texel;
+    let cy = (c.y / texel).floor() * texel;
+    // View space looks down -Z; `c.z` is minus the centre's depth.
+    let depth = -c.z;
+    let proj = Matrix::ortho(
+        cx - radius,
+        cx + radius,
+        cy - radius,
+        cy + radius,
+        depth - radius - SHADOW_CASTER_MARGIN,
+        depth + radius,
+    );
+    (view, proj)
+}
+
+struct ShadowCascade {
+    fbo: u32,
+    depth_tex: u32,
+    view: Matrix,
+    proj: Matrix,
+    radius: f32,
+}
+
+impl ShadowCascade {
+    fn view_proj(&self) -> Matrix {
+        self.view * self.proj
+    }
+
+    /// World size of one map texel.
+    fn texel(&self, resolution: i32) -> f32 {
+        2.0 * self.radius / resolution.max(1) as f32
+    }
+
+    /// Depth range the projection spans, in blocks.
+    fn depth_range(&self) -> f32 {
+        2.0 * self.radius + SHADOW_CASTER_MARGIN
+    }
+}
+
+pub struct ShadowMaps {
+    settings: ShadowSettings,
+    cascades: Vec<ShadowCascade>,
+    // Carries the depth-only shader; never unloaded, like the decoration materials.
+    depth_material: raylib::models::WeakMaterial,
+    draw_buffer: DrawBuffer,
+    read_buffer: ReadBuffer,
+    sun_dir: Vector3,
+    strength: f32,
+    // rlgl state the depth pass replaces, put back by `end_cascade`.
+    saved: Option<(Matrix, Matrix)>,
+}
+
+impl ShadowMaps {
+    pub fn load_with_base(
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        base: &std::path::Path,
+        settings: ShadowSettings,
+    ) -> Option<Self> {
+        let vs = base.join("assets/shaders/shadow_depth.vs");
+        let fs = base.join("assets/shaders/shadow_depth.fs");
+        let shader_strong = rl.load_shader(
+            thread,
+            Some(vs.to_string_lossy().as_ref()),
+            Some(fs.to_string_lossy().as_ref()),
+        );
+        let shader = unsafe { shader_strong.make_weak() };
+        if !crate::shader_compiled(&shader) {
+            return None;
+        }
+        let get = |name: &str| {
+            let cname = CString::new(name).expect("GL symbol name");
+            let ptr = unsafe { glfwGetProcAddress(cname.as_ptr()) };
+            (!ptr.is_null()).then_some(ptr)
+        };
+        // SAFETY: each pointer is the current context's entry point of that name, whose C
+        // signature matches the function type it is transmuted to.
+        let draw_buffer =
+            unsafe { std::mem::transmute::<*const c_void, DrawBuffer>(get("glDrawBuffer")?) };
+        let read_buffer =
+            unsafe { std::mem::transmute::<*const c_void, ReadBuffer>(get("glReadBuffer")?) };
+        let mut material = rl.load_material_default(thread);
+        let dest: *mut raylib::ffi::Shader = material.shader_mut().as_mut();
+        let src: *const raylib::ffi::Shader = shader.as_ref();
+        unsafe { std::ptr::copy_nonoverlapping(src, dest, 1) };
+        Some(Self {
+            settings,
+            cascades: Vec::new(),
+            depth_material: material,
+            draw_buffer,
+            read_buffer,
+            sun_dir: Vector3::new(0.0, 1.0, 0.0),
+            strength: 0.0,
+            saved: None,
+        })
+    }
+
+    pub fn settings(&self) -> ShadowSettings {
+        self.settings
+    }
+
+    /// Change quality; the maps are recreated on the next `update`.
+    pub fn set_settings(&mut self, settings: ShadowSettings) {
+        if settings.resolution != self.settings.resolution {
+            self.release();
+        }
+        self.settings = settings;
+    }
+
+    /// Cascades rendered this frame; 0 while shadows are off or the sun is down.
+    pub fn active_cascades(&self) -> usize {
+        if self.strength > 0.0 {
+            self.cascades.len()
+        } else {
+            0
+        }
+    }
+
+    /// Aim the cascades at `center` (render space) for the sun at `sun_dir`, creating or
+    /// dropping maps to match the settings. `strength` is the share of skylight a full
+    /// shadow removes; 0 skips the pass and leaves the scene unshadowed.
+    pub fn update(&mut self, center: Vector3, sun_dir: Vector3, strength: f32) {
+        self.sun_dir = sun_dir.normalized();
+        self.strength = strength.clamp(0.0, 1.0);
+        let splits = self.settings.splits();
+        while self.cascades.len() > splits.len() {
+            if let Some(c) = self.cascades.pop() {
+                Self::unload(&c);
+            }
+        }
+        while self.cascades.len() < splits.len() {
+            match self.create_cascade() {
+                Some(c) => self.cascades.push(c),
+                None => {
+                    log::warn!(
+                        "shadow map {}x{} unavailable; shadows off",
+                        self.settings.resolution,
+                        self.settings.resolution
+                    );
+                    self.settings.cascades = self.cascades.len();
+                    break;
+                }
+            }
+        }
+        let resolution = self.settings.resolution;
+        for (c, radius) in self.cascades.iter_mut().zip(splits) {
+            let (view, proj) = cascade_view_proj(self.sun_dir, center, radius, resolution);
+            c.view = view;
+            c.proj = proj;
+            c.radius = radius;
+        }
+    }
+
+    fn create_cascade(&self) -> Option<ShadowCascade> {
+        let res = self.settings.resolution;
+        unsafe {
+            let fbo = raylib::ffi::rlLoadFramebuffer();
+            if fbo == 0 {
+                return None;
+            }
+            let depth_tex = raylib::ffi::rlLoadTextureDepth(res, res, false);
+            raylib::ffi::rlFramebufferAttach(
+                fbo,
+                depth_tex,
+                raylib::ffi::rlFramebufferAttachType::RL_ATTACHMENT_DEPTH as i32,
+                raylib::ffi::rlFramebufferAttachTextureType::RL_ATTACHMENT_TEXTURE2D as i32,
+                0,
+            );
+            raylib::ffi::rlEnableFramebuffer(fbo);
+            (self.draw_buffer)(GL_NONE);
+            (self.read_buffer)(GL_NONE);
+            let complete = raylib::ffi::rlFramebufferComplete(fbo);
+            raylib::ffi::rlDisableFramebuffer();
+            let cascade = ShadowCascade {
+                fbo,
+                depth_tex,
+                view: Matrix::identity(),
+                proj: Matrix::identity(),
+                radius: 0.0,
+            };
+            if !complete || depth_tex == 0 {
+                Self::unload(&cascade);
+                return None;
+            }
+            Some(cascade)
+        }
+    }
+
+    fn unload(c: &ShadowCascade) {
+        unsafe {
+            raylib::ffi::rlUnloadTexture(c.depth_tex);
+            raylib::ffi::rlUnloadFramebuffer(c.fbo);
+        }
+    }
+
+    fn release(&mut self) {
+        for c in self.cascades.drain(..) {
+            Self::unload(&c);
+        }
+    }
+
+    /// Render-space volume cascade `i` draws casters from.
+    pub fn cascade_frustum(&self, i: usize) -> Option<Frustum> {
+        let c = self.cascades.get(i)?;
+        // Rows of the view matrix are the light's right, up and back axes.
+        let v = c.view;
+        let right = Vector3::new(v.m0, v.m4, v.m8);
+        let up = Vector3::new(v.m1, v.m5, v.m9);
+        let back = Vector3::new(v.m2, v.m6, v.m10);
+        let p = c.proj;
+        // Invert the orthographic projection's scale and offset per axis.
+        let span = |scale: f32, offset: f32| ((-1.0 - offset) / scale, (1.0 - offset) / scale);
+        let (l, r) = span(p.m0, p.m12);
+        let (b, t) = span(p.m5, p.m13);
+        let (z0, z1) = span(p.m10, p.m14);
+        Some(Frustum {
+            planes: [
+                Plane::new(right, right * l),
+                Plane::new(-right, right * r),
+                Plane::new(-up, up * t),
+                Plane::new(up, up * b),
+                Plane::new(back, back * z0.min(z1)),
+                Plane::new(-back, back * z0.max(z1)),
+            ],
+        })
+    }
+
+    /// Redirect drawing into cascade `i`'s depth map until `end_cascade`. Returns false,
+    /// drawing nothing, when the cascade does not exist.
+    pub fn begin_cascade(&mut self, i: usize) -> bool {
+        let Some(c) = self.cascades.get(i) else {
+            return false;
+        };
+        let res = self.settings.resolution;
+        unsafe {
+            raylib::ffi::rlDrawRenderBatchActive();
+            self.saved = Some((
+                raylib::ffi::rlGetMatrixProjection().into(),
+                raylib::ffi::rlGetMatrixModelview().into(),
+            ));
+            raylib::ffi::rlEnableFramebuffer(c.fbo);
+            raylib::ffi::rlViewport(0, 0, res, res);
+            raylib::ffi::rlClearScreenBuffers();
+            raylib::ffi::rlSetMatrixProjection(c.proj.into());
+            raylib::ffi::rlSetMatrixModelview(c.view.into());
+            raylib::ffi::rlEnableDepthTest();
+            // Surfaces are single-sided shells; draw both sides so none lets light through.
+            raylib::ffi::rlDisableBackfaceCulling();
+        }
+        true
+    }
+
+    /// Draw `part` into the current cascade under `transform` (render space).
+    pub fn draw_part(&self, part: &ChunkPart, transform: Matrix) {
+        part.draw_with_material(&self.depth_material, transform);
+    }
+
+    pub fn end_cascade(&mut self) {
+        unsafe {
+            raylib::ffi::rlDrawRenderBatchActive();
+            raylib::ffi::rlEnableBackfaceCulling();
+            raylib::ffi::rlDisableDepthTest();
+            raylib::ffi::rlDisableFramebuffer();
+            raylib::ffi::rlViewport(
+                0,
+                0,
+                raylib::ffi::rlGetFramebufferWidth(),
+                raylib::ffi::rlGetFramebufferHeight(),
+            );
+            if let Some((proj, view)) = self.saved.take() {
+                raylib::ffi::rlSetMatrixProjection(proj.into());
+                raylib::ffi::rlSetMatrixModelview(view.into());
+            }
+        }
+    }
+
+    /// Bind every cascade to its unit; like the block array this holds for the frame.
+    pub fn bind(&self) {
+        unsafe {
+            for (i, c) in self.cascades.iter().enumerate() {
+                raylib::ffi::rlActiveTextureSlot(SHADOW_MAP_SLOT + i as i32);
+                raylib::ffi::rlEnableTexture(c.depth_tex);
+            }
+            raylib::ffi::rlActiveTextureSlot(0);
+        }
+    }
+}
+
+impl Drop for ShadowMaps {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+/// Uniform locations for the sun shadow cascades; any may be -1 if a shader omits it.
+#[derive(Clone, Copy)]
+pub struct ShadowLocs {
+    maps: [i32; MAX_SHADOW_CASCADES],
+    matrices: [i32; MAX_SHADOW_CASCADES],
+    splits: i32,
+    texel: i32,
+    bias: i32,
+    map_texel: i32,
+    count: i32,
+    sun_dir: i32,
+    strength: i32,
+}
+
+impl ShadowLocs {
+    pub(crate) fn locate(shader: &raylib::shaders::WeakShader) -> Self {
+        Self {
+            maps: std::array::from_fn(|i| shader.get_shader_location(&format!("shadowMap{}", i))),
+            matrices: std::array::from_fn(|i| {
+                shader.get_shader_location(&format!("shadowMatrix[{}]", i))
+            }),
+            splits: shader.get_shader_location("shadowSplits"),
+            texel: shader.get_shader_location("shadowTexel"),
+            bias: shader.get_shader_location("shadowBias"),
+            map_texel: shader.get_shader_location("shadowMapTexel"),
+            count: shader.get_shader_location("shadowCount"),
+            sun_dir: shader.get_shader_location("sunDir"),
+            strength: shader.get_shader_location("shadowStrength"),
+        }
+    }
+
+    /// Point the shader at `shadows`' cascades, or set no cascades so skylight goes
+    /// unshadowed.
+    pub(crate) fn apply(
+        &self,
+        shader: &mut raylib::shaders::WeakShader,
+        shadows: Option<&ShadowMaps>,
+    ) {
+        for (i, &loc) in self.maps.iter().enumerate() {
+            if loc >= 0 {
+                shader.set_shader_value(loc, SHADOW_MAP_SLOT + i as i32);
+            }
+        }
+        let count = shadows.map_or(0, |s| s.active_cascades());
+        if self.count >= 0 {
+            shader.set_shader_value(self.count, count as i32);
+        }
+        let Some(s) = shadows.filter(|_| count > 0) else {
+            return;
+        };
+        let res = s.settings.resolution;
+        let mut splits = [0.0f32; MAX_SHADOW_CASCADES];
+        let mut texel = [0.0f32; MAX_SHADOW_CASCADES];
+        let mut bias = [0.0f32; MAX_SHADOW_CASCADES];
+        for (i, c) in s.cascades.iter().enumerate() {
+            if self.matrices[i] >= 0 {
+                shader.set_shader_value_matrix(self.matrices[i], c.view_proj());
+            }
+            splits[i] = c.radius;
+            texel[i] = c.texel(res);
+            // Two texels of depth, in the map's 0..1 range.
+            bias[i] = 2.0 * texel[i] / c.depth_range();
+        }
+        if self.splits >= 0 {
+            shader.set_shader_value(self.splits, splits);
+        }
+        if self.texel >= 0 {
+            shader.set_shader_value(self.texel, texel);
+        }
+        if self.bias >= 0 {
+            shader.set_shader_value(self.bias, bias);
+        }
+        if self.map_texel >= 0 {
+            shader.set_shader_value(self.map_texel, 1.0 / res.max(1) as f32);
+        }
+        if self.sun_dir >= 0 {
+            let d = [s.sun_dir.x, s.sun_dir.y, s.sun_dir.z];
+            shader.set_shader_value(self.sun_dir, d);
+        }
+        if self.strength >= 0 {
+            shader.set_shader_value(self.strength, s.strength);
+        }
+    }
+}
@@ -0,0 +1,308 @@
+//! The sky: a procedural gradient from horizon to zenith, sun and moon billboards driven
+//! by the day fraction, and stars at night.
+//!
+//! [`SkyFrame`] is the per-frame sample everything else reads: the horizon color doubles
+//! as the clear color and the surface fog color, and the sun direction and color feed the
+//! shadow cascades and the sun body's tint, so terrain, fog and sky agree on one palette.
+//! [`SkyRenderer`] draws it. Without its shader (GL compatibility rendering) the frame is
+//! cleared to the horizon color and only the billboards are drawn.
+
+use std::f32::consts::TAU;
+
+use geist_geom::Vec3;
+use raylib::prelude::*;
+
+use crate::conv::vec3_to_rl;
+
+/// How far from the camera the sun glow and moon are drawn. Beyond the voxel sun body,
+/// inside raylib's default far plane.
+const BODY_DISTANCE: f32 = 600.0;
+/// Billboard sizes at [`BODY_DISTANCE`]; the glow is about three sun discs across.
+const SUN_GLOW_SIZE: f32 = 330.0;
+const MOON_SIZE: f32 = 90.0;
+/// Brightness below which stars start to show; they are at full strength at 0.
+const STAR_BRIGHTNESS: f32 = 0.2;
+
+/// Direction toward the sun at `day_frac` (0 = sunrise, 0.25 = noon, 0.75 = midnight).
+/// The path is inclined slightly on Z so it arcs across the sky instead of a flat plane.
+pub fn sun_direction(day_frac: f32) -> Vec3 {
+    let phase = day_frac.rem_euclid(1.0) * TAU;
+    Vec3::new(phase.cos(), (phase.sin() * 1.05).clamp(-1.0, 1.0), 0.25).normalized()
+}
+
+/// Horizon colors at noon and midnight and the warmth mixed in around sunrise and sunset.
+/// Colors are linear RGB in 0..1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SkyPalette {
+    pub day: [f32; 3],
+    pub night: [f32; 3],
+    pub twilight_tint: [f32; 3],
+    pub twilight_strength: f32,
+}
+
+impl Default for SkyPalette {
+    fn default() -> Self {
+        Self {
+            day: [210.0 / 255.0, 221.0 / 255.0, 235.0 / 255.0],
+            night: [10.0 / 255.0, 12.0 / 255.0, 20.0 / 255.0],
+            twilight_tint: [1.0, 0.63, 0.32],
+            twilight_strength: 0.35,
+        }
+    }
+}
+
+fn lerp3(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    [
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        a[2] + (b[2] - a[2]) * t,
+    ]
+}
+
+fn color_of(rgb: [f32; 3], alpha: f32) -> Color {
+    let c = |v: f32| (v.clamp(0.0, 1.0) * 255.0) as u8;
+    Color::new(c(rgb[0]), c(rgb[1]), c(rgb[2]), c(alpha))
+}
+
+/// The sky at one moment of the day.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SkyFrame {
+    pub day_frac: f32,
+    pub sun_dir: Vec3,
+    /// Opposite the sun.
+    pub moon_dir: Vec3,
+    pub zenith: [f32; 3],
+    pub horizon: [f32; 3],
+    /// Sunlight color, already dimmed by how high the sun is.
+    pub sun_color: [f32; 3],
+    /// Star strength in 0..1.
+    pub stars: f32,
+}
+
+impl SkyFrame {
+    /// Sample `palette` at `day_frac`, with the day cycle's `sky_scale` and light
+    /// `brightness` for the same moment.
+    pub fn at(palette: &SkyPalette, day_frac: f32, sky_scale: f32, brightness: f32) -> Self {
+        let day_frac = day_frac.rem_euclid(1.0);
+        let phase = day_frac * TAU;
+        let base = lerp3(palette.night, palette.day, brightness);
+        let twilight = phase.cos().abs();
+        let warm =
+            (palette.twilight_strength.max(0.0) * twilight.powf(3.0) * sky_scale).clamp(0.0, 0.5);
+        let horizon = lerp3(base, palette.twilight_tint, warm);
+        // Overhead the sky is deeper and bluer and keeps none of the twilight warmth.
+        let zenith = [base[0] * 0.55, base[1] * 0.68, base[2] * 0.92];
+
+        let sun_dir = sun_direction(day_frac);
+        let hue = lerp3([1.0, 0.84, 0.42], [1.0, 0.58, 0.28], twilight.powf(1.5));
+        let visibility = if sun_dir.y > 0.0 { 1.0 } else { 0.15 };
+        let strength = ((0.2 + 0.8 * sky_scale) * visibility).clamp(0.0, 1.0);
+        Self {
+            day_frac,
+            sun_dir,
+            moon_dir: sun_dir * -1.0,
+            zenith,
+            horizon,
+            sun_color: hue.map(|c| c * strength),
+            stars: (1.0 - brightness / STAR_BRIGHTNESS).clamp(0.0, 1.0),
+        }
+    }
+
+    /// Fog above ground fades into the horizon.
+    #[inline]
+    pub fn fog_color(&self) -> [f32; 3] {
+        self.horizon
+    }
+
+    /// What the frame is cleared to: all of the sky without the shader.
+    pub fn clear_color(&self) -> Color {
+        color_of(self.horizon, 1.0)
+    }
+
+    /// Tint for the sun body.
+    pub fn sun_tint(&self) -> Color {
+        color_of(self.sun_color, 1.0)
+    }
+}
+
+struct SkyShader {
+    shader: raylib::shaders::WeakShader,
+    loc_cam_forward: i32,
+    loc_cam_right: i32,
+    loc_cam_up: i32,
+    loc_tan_half_fov: i32,
+    loc_aspect: i32,
+    loc_zenith: i32,
+    loc_horizon: i32,
+    loc_sun_dir: i32,
+    loc_sun_color: i32,
+    loc_star_alpha: i32,
+    loc_star_rotation: i32,
+}
+
+impl SkyShader {
+    fn load_with_base(
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        base: &std::path::Path,
+    ) -> Option<Self> {
+        let vs = base.join("assets/shaders/sky.vs");
+        let fs = base.join("assets/shaders/sky.fs");
+        let shader_strong = rl.load_shader(
+            thread,
+            Some(vs.to_string_lossy().as_ref()),
+            Some(fs.to_string_lossy().as_ref()),
+        );
+        let shader = unsafe { shader_strong.make_weak() };
+        if !crate::shader_compiled(&shader) {
+            return None;
+        }
+        Some(Self {
+            loc_cam_forward: shader.get_shader_location("camForward"),
+            loc_cam_right: shader.get_shader_location("camRight"),
+            loc_cam_up: shader.get_shader_location("camUp"),
+            loc_tan_half_fov: shader.get_shader_location("tanHalfFov"),
+            loc_aspect: shader.get_shader_location("aspect"),
+            loc_zenith: shader.get_shader_location("zenithColor"),
+            loc_horizon: shader.get_shader_location("horizonColor"),
+            loc_sun_dir: shader.get_shader_location("sunDir"),
+            loc_sun_color: shader.get_shader_location("sunColor"),
+            loc_star_alpha: shader.get_shader_location("starAlpha"),
+            loc_star_rotation: shader.get_shader_location("starRotation"),
+            shader,
+        })
+    }
+}
+
+/// Draws a [`SkyFrame`]: the gradient and stars behind everything, then the sun glow and
+/// moon as billboards that never write depth.
+pub struct SkyRenderer {
+    shader: Option<SkyShader>,
+    /// 1x1 white texture the full-screen quad is drawn with.
+    quad: Texture2D,
+    sun_glow: Texture2D,
+    moon: Texture2D,
+}
+
+impl SkyRenderer {
+    /// Loads the billboard textures and, with `with_shader`, the gradient shader. `None`
+    /// only when the textures cannot be created; a shader that fails to compile just
+    /// leaves the cleared horizon color as the sky.
+    pub fn load_with_base(
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        base: &std::path::Path,
+        with_shader: bool,
+    ) -> Option<Self> {
+        let clear = Color::new(255, 255, 255, 0);
+        let quad = Image::gen_image_color(1, 1, Color::WHITE);
+        let glow = Image::gen_image_gradient_radial(128, 128, 0.0, Color::WHITE, clear);
+        let disc = Image::gen_image_gradient_radial(64, 64, 0.85, Color::WHITE, clear);
+        let quad = rl.load_texture_from_image(thread, &quad).ok()?;
+        let sun_glow = rl.load_texture_from_image(thread, &glow).ok()?;
+        let moon = rl.load_texture_from_image(thread, &disc).ok()?;
+        for tex in [&sun_glow, &moon] {
+            tex.set_texture_filter(thread, TextureFilter::TEXTURE_FILTER_BILINEAR);
+        }
+        let shader = if with_shader {
+            let shader = SkyShader::load_with_base(rl, thread, base);
+            if shader.is_none() {
+                log::warn!("sky shader failed to compile/link; the sky is a flat color");
+            }
+            shader
+        } else {
+            None
+        };
+        Some(Self {
+            shader,
+            quad,
+            sun_glow,
+            moon,
+        })
+    }
+
+    pub fn has_shader(&self) -> bool {
+        self.shader.is_some()
+    }
+
+    /// Fill the frame with the gradient and stars as seen from `camera`. Call after
+    /// clearing and before 3D mode, so the world draws over it.
+    pub fn draw_background<D: RaylibDraw>(
+        &mut self,
+        d: &mut D,
+        camera: &Camera3D,
+        aspect: f32,
+        sky: &SkyFrame,
+    ) {
+        let Some(s) = &mut self.shader else {
+            return;
+        };
+        let forward = (camera.target - camera.position).normalized();
+        let right = forward.cross(camera.up).normalized();
+        let up = right.cross(forward);
+        let rgb = |c: [f32; 3]| Vector3::new(c[0], c[1], c[2]);
+        s.shader.set_shader_value(s.loc_cam_forward, forward);
+        s.shader.set_shader_value(s.loc_cam_right, right);
+        s.shader.set_shader_value(s.loc_cam_up, up);
+        s.shader
+            .set_shader_value(s.loc_tan_half_fov, (camera.fovy.to_radians() * 0.5).tan());
+        s.shader.set_shader_value(s.loc_aspect, aspect);
+        s.shader.set_shader_value(s.loc_zenith, rgb(sky.zenith));
+        s.shader.set_shader_value(s.loc_horizon, rgb(sky.horizon));
+        s.shader
+            .set_shader_value(s.loc_sun_dir, vec3_to_rl(sky.sun_dir));
+        s.shader
+            .set_shader_value(s.loc_sun_color, rgb(sky.sun_color));
+        s.shader.set_shader_value(s.loc_star_alpha, sky.stars);
+        // The stars keep their place relative to the sun as it crosses the sky.
+        s.shader
+            .set_shader_value(s.loc_star_rotation, -sky.day_frac * TAU);
+        unsafe { raylib::ffi::BeginShaderMode(*s.shader.as_ref()) }
+        d.draw_texture_pro(
+            &self.quad,
+            Rectangle::new(0.0, 0.0, 1.0, 1.0),
+            Rectangle::new(-1.0, -1.0, 2.0, 2.0),
+            Vector2::zero(),
+            0.0,
+            Color::WHITE,
+        );
+        unsafe { raylib::ffi::EndShaderMode() }
+    }
+
+    /// Draw the sun glow and the moon around `camera`, in render space. Call first thing
+    /// in 3D mode: they write no depth, so every other draw covers them, including the
+    /// voxel sun body that sits inside the glow.
+    pub fn draw_bodies<D: RaylibDraw3D>(&self, d3: &mut D, camera: Camera3D, sky: &SkyFrame) {
+        // Fade each body in over the last stretch before it clears the horizon.
+        let rise = |dir: Vec3| ((dir.y + 0.1) / 0.2).clamp(0.0, 1.0);
+        let glow_alpha = rise(sky.sun_dir) * 0.8;
+        // The moon washes out in daylight.
+        let moon_alpha = rise(sky.moon_dir) * (1.0 - 0.7 * (1.0 - sky.stars));
+        unsafe {
+            raylib::ffi::rlDrawRenderBatchActive();
+            raylib::ffi::rlDisableDepthMask();
+        }
+        if glow_alpha > 0.0 {
+            d3.draw_billboard(
+                camera,
+                &self.sun_glow,
+                camera.position + vec3_to_rl(sky.sun_dir * BODY_DISTANCE),
+                SUN_GLOW_SIZE,
+                color_of(sky.sun_color, glow_alpha),
+            );
+        }
+        if moon_alpha > 0.0 {
+            d3.draw_billboard(
+                camera,
+                &self.moon,
+                camera.position + vec3_to_rl(sky.moon_dir * BODY_DISTANCE),
+                MOON_SIZE,
+                color_of([0.86, 0.89, 0.96], moon_alpha),
+            );
+        }
+        unsafe {
+            raylib::ffi::rlDrawRenderBatchActive();
+            raylib::ffi::rlEnableDepthMask();
+        }
+    }
+}
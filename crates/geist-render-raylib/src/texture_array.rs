@@ -0,0 +1,360 @@
+//! Block textures stacked into a single GL_TEXTURE_2D_ARRAY.
+//!
+//! An alternative to per-material texture binds when the block textures cannot be
+//! packed into a 2D atlas: every same-size texture becomes one layer, chunk parts keep
+//! their own draw but only switch a layer uniform, and wrapping/mipmaps behave exactly
+//! as they do for standalone textures. rlgl has no array-texture entry points, so the
+//! few GL calls needed are resolved through GLFW, which raylib links in on desktop.
+//!
+//! With the array in place, [`merge_layered_parts`] goes further: the opaque materials of
+//! a chunk that need nothing but their layer are folded into one part whose vertices
+//! carry their own layer, so the chunk draws them in a single call.
+
+use std::collections::HashMap;
+use std::ffi::{CString, c_char, c_void};
+
+use geist_blocks::types::MaterialId;
+use geist_blocks::{MaterialCatalog, RenderPass};
+use geist_mesh_cpu::{ChunkMeshCPU, MeshBuild, recycle_build};
+
+/// Texture unit the array stays bound to; chunk material maps use slot 0 and light uses 7.
+pub const BLOCK_ARRAY_SLOT: i32 = 6;
+/// `blockLayer` value telling the voxel shaders to read each vertex's own layer.
+pub const VERTEX_LAYER: i32 = -1;
+
+const GL_TEXTURE_2D_ARRAY: u32 = 0x8C1A;
+const GL_TEXTURE_MIN_FILTER: u32 = 0x2801;
+const GL_TEXTURE_MAG_FILTER: u32 = 0x2800;
+const GL_TEXTURE_WRAP_S: u32 = 0x2802;
+const GL_TEXTURE_WRAP_T: u32 = 0x2803;
+const GL_NEAREST: i32 = 0x2600;
+const GL_NEAREST_MIPMAP_LINEAR: i32 = 0x2702;
+const GL_REPEAT: i32 = 0x2901;
+const GL_RGBA8: i32 = 0x8058;
+const GL_RGBA: u32 = 0x1908;
+const GL_UNSIGNED_BYTE: u32 = 0x1401;
+
+unsafe extern "C" {
+    fn glfwGetProcAddress(procname: *const c_char) -> *const c_void;
+}
+
+type GenTextures = unsafe extern "system" fn(i32, *mut u32);
+type DeleteTextures = unsafe extern "system" fn(i32, *const u32);
+type BindTexture = unsafe extern "system" fn(u32, u32);
+type TexParameteri = unsafe extern "system" fn(u32, u32, i32);
+type TexImage3D =
+    unsafe extern "system" fn(u32, i32, i32, i32, i32, i32, i32, u32, u32, *const c_void);
+type TexSubImage3D =
+    unsafe extern "system" fn(u32, i32, i32, i32, i32, i32, i32, i32, u32, u32, *const c_void);
+type GenerateMipmap = unsafe extern "system" fn(u32);
+
+struct GlFns {
+    gen_textures: GenTextures,
+    delete_textures: DeleteTextures,
+    bind_texture: BindTexture,
+    tex_parameteri: TexParameteri,
+    tex_image_3d: TexImage3D,
+    tex_sub_image_3d: TexSubImage3D,
+    generate_mipmap: GenerateMipmap,
+}
+
+impl GlFns {
+    fn load() -> Result<Self, String> {
+        fn get(name: &str) -> Result<*const c_void, String> {
+            let cname = CString::new(name).expect("GL symbol name");
+            let ptr = unsafe { glfwGetProcAddress(cname.as_ptr()) };
+            if ptr.is_null() {
+                Err(format!("{} unavailable", name))
+            } else {
+                Ok(ptr)
+            }
+        }
+        // SAFETY: each pointer comes from the current GL context for the named entry point,
+        // whose C signature matches the function type it is transmuted to.
+        unsafe {
+            Ok(Self {
+                gen_textures: std::mem::transmute::<*const c_void, GenTextures>(get(
+                    "glGenTextures",
+                )?),
+                delete_textures: std::mem::transmute::<*const c_void, DeleteTextures>(get(
+                    "glDeleteTextures",
+                )?),
+                bind_texture: std::mem::transmute::<*const c_void, BindTexture>(get(
+                    "glBindTexture",
+                )?),
+                tex_parameteri: std::mem::transmute::<*const c_void, TexParameteri>(get(
+                    "glTexParameteri",
+                )?),
+                tex_image_3d: std::mem::transmute::<*const c_void, TexImage3D>(get(
+                    "glTexImage3D",
+                )?),
+                tex_sub_image_3d: std::mem::transmute::<*const c_void, TexSubImage3D>(get(
+                    "glTexSubImage3D",
+                )?),
+                generate_mipmap: std::mem::transmute::<*const c_void, GenerateMipmap>(get(
+                    "glGenerateMipmap",
+                )?),
+            })
+        }
+    }
+}
+
+/// Resolve the texture a material renders with, preferring the first candidate on disk.
+/// Returns the canonical path so it matches `TextureCache` keys.
+pub fn material_texture_path(mats: &MaterialCatalog, mid: MaterialId) -> Option<String> {
+    let mdef = mats.get(mid)?;
+    let candidates: Vec<String> = mdef
+        .texture_candidates
+        .iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+    let chosen = candidates
+        .iter()
+        .find(|p| std::path::Path::new(p.as_str()).exists())
+        .cloned()
+        .or_else(|| candidates.first().cloned())?;
+    Some(
+        std::fs::canonicalize(&chosen)
+            .ok()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or(chosen),
+    )
+}
+
+pub struct BlockTextureArray {
+    gl: GlFns,
+    id: u32,
+    pub width: i32,
+    pub height: i32,
+    pub layers: i32,
+    layer_of: HashMap<MaterialId, i32>,
+    paths: Vec<String>,
+}
+
+impl BlockTextureArray {
+    /// Stack every material texture matching the size of the first one loaded.
+    ///
+    /// Water keeps its own texture (its shader animates the UVs), and materials whose
+    /// texture is missing or a different size have no layer, so they fall back to a
+    /// regular per-material bind.
+    pub fn build(mats: &MaterialCatalog) -> Result<Self, String> {
+        let gl = GlFns::load()?;
+        let mut layer_of: HashMap<MaterialId, i32> = HashMap::new();
+        let mut layer_by_path: HashMap<String, i32> = HashMap::new();
+        let mut paths: Vec<String> = Vec::new();
+        let mut pixels: Vec<u8> = Vec::new();
+        let mut size: Option<(i32, i32)> = None;
+        for mdef in &mats.materials {
+            if mdef.render_tag.as_deref() == Some("water") {
+                continue;
+            }
+            let Some(path) = material_texture_path(mats, mdef.id) else {
+                continue;
+            };
+            if let Some(&layer) = layer_by_path.get(&path) {
+                layer_of.insert(mdef.id, layer);
+                continue;
+            }
+            let Ok(mut image) = raylib::core::texture::Image::load_image(&path) else {
+                log::warn!("texture array: failed to load {}", path);
+                continue;
+            };
+            image.set_format(raylib::consts::PixelFormat::PIXELFORMAT_UNCOMPRESSED_R8G8B8A8);
+            let dims = (image.width(), image.height());
+            match size {
+                None => size = Some(dims),
+                Some(expect) if expect != dims => {
+                    log::info!(
+                        "texture array: {} is {}x{}, expected {}x{}; using a separate texture",
+                        path,
+                        dims.0,
+                        dims.1,
+                        expect.0,
+                        expect.1
+                    );
+                    continue;
+                }
+                Some(_) => {}
+            }
+            let bytes = (dims.0 * dims.1 * 4) as usize;
+            let data = image.as_ref().data as *const u8;
+            // SAFETY: the image was converted to RGBA8 above, so `data` holds width*height*4 bytes.
+            pixels.extend_from_slice(unsafe { std::slice::from_raw_parts(data, bytes) });
+            let layer = paths.len() as i32;
+            layer_by_path.insert(path.clone(), layer);
+            layer_of.insert(mdef.id, layer);
+            paths.push(path);
+        }
+        let Some((width, height)) = size else {
+            return Err("no block textures to stack".to_string());
+        };
+        let layers = paths.len() as i32;
+        let mut id = 0u32;
+        unsafe {
+            (gl.gen_textures)(1, &mut id);
+            (gl.bind_texture)(GL_TEXTURE_2D_ARRAY, id);
+            (gl.tex_image_3d)(
+                GL_TEXTURE_2D_ARRAY,
+                0,
+                GL_RGBA8,
+                width,
+                height,
+                layers,
+                0,
+                GL_RGBA,
+                GL_UNSIGNED_BYTE,
+                pixels.as_ptr() as *const c_void,
+            );
+            (gl.tex_parameteri)(GL_TEXTURE_2D_ARRAY, GL_TEXTURE_WRAP_S, GL_REPEAT);
+            (gl.tex_parameteri)(GL_TEXTURE_2D_ARRAY, GL_TEXTURE_WRAP_T, GL_REPEAT);
+            (gl.tex_parameteri)(GL_TEXTURE_2D_ARRAY, GL_TEXTURE_MAG_FILTER, GL_NEAREST);
+            (gl.tex_parameteri)(
+                GL_TEXTURE_2D_ARRAY,
+                GL_TEXTURE_MIN_FILTER,
+                GL_NEAREST_MIPMAP_LINEAR,
+            );
+            (gl.generate_mipmap)(GL_TEXTURE_2D_ARRAY);
+            (gl.bind_texture)(GL_TEXTURE_2D_ARRAY, 0);
+        }
+        if id == 0 {
+            return Err("glGenTextures returned no texture".to_string());
+        }
+        Ok(Self {
+            gl,
+            id,
+            width,
+            height,
+            layers,
+            layer_of,
+            paths,
+        })
+    }
+
+    #[inline]
+    pub fn layer(&self, mid: MaterialId) -> Option<i32> {
+        self.layer_of.get(&mid).copied()
+    }
+
+    /// Whether `path` (canonical) is one of the stacked layers.
+    pub fn contains_path(&self, path: &str) -> bool {
+        self.paths.iter().any(|p| p == path)
+    }
+
+    /// Re-upload the layer backed by `path` after the file changed on disk.
+    pub fn reload_layer(&mut self, path: &str) -> Result<(), String> {
+        let Some(layer) = self.paths.iter().position(|p| p == path) else {
+            return Ok(());
+        };
+        let mut image = raylib::core::texture::Image::load_image(path)
+            .map_err(|e| format!("failed to load {}: {}", path, e))?;
+        image.set_format(raylib::consts::PixelFormat::PIXELFORMAT_UNCOMPRESSED_R8G8B8A8);
+        if image.width() != self.width || image.height() != self.height {
+            return Err(format!(
+                "{} is now {}x{}; the array layers are {}x{}",
+                path,
+                image.width(),
+                image.height(),
+                self.width,
+                self.height
+            ));
+        }
+        unsafe {
+            (self.gl.bind_texture)(GL_TEXTURE_2D_ARRAY, self.id);
+            (self.gl.tex_sub_image_3d)(
+                GL_TEXTURE_2D_ARRAY,
+                0,
+                0,
+                0,
+                layer as i32,
+                self.width,
+                self.height,
+                1,
+                GL_RGBA,
+                GL_UNSIGNED_BYTE,
+                image.as_ref().data as *const c_void,
+            );
+            (self.gl.generate_mipmap)(GL_TEXTURE_2D_ARRAY);
+            (self.gl.bind_texture)(GL_TEXTURE_2D_ARRAY, 0);
+        }
+        Ok(())
+    }
+
+    /// Bind the array to `BLOCK_ARRAY_SLOT`. rlgl only rebinds the 2D target on that unit,
+    /// so this holds for the whole frame.
+    pub fn bind(&self) {
+        unsafe {
+            raylib::ffi::rlActiveTextureSlot(BLOCK_ARRAY_SLOT);
+            (self.gl.bind_texture)(GL_TEXTURE_2D_ARRAY, self.id);
+            raylib::ffi::rlActiveTextureSlot(0);
+        }
+    }
+}
+
+/// Several materials' geometry merged into one build, each vertex tagged with the array
+/// layer of the material it came from.
+pub struct LayeredBuild {
+    /// The first merged material; it stands in for the group when the part is drawn.
+    pub mid: MaterialId,
+    pub build: MeshBuild,
+    /// One layer per vertex of `build`.
+    pub layers: Vec<f32>,
+}
+
+/// Move the parts of `cpu` that can share a draw into one [`LayeredBuild`]: opaque
+/// materials with a layer under `layer_of`, drawn by the plain voxel shader (no render
+/// tag, no animation). Returns `None`, leaving `cpu` alone, when fewer than two qualify.
+/// The merged build's indices are left empty; uploads index every part as quads.
+pub fn merge_layered_parts(
+    cpu: &mut ChunkMeshCPU,
+    mats: &MaterialCatalog,
+    layer_of: impl Fn(MaterialId) -> Option<i32>,
+) -> Option<LayeredBuild> {
+    let mut group: Vec<(MaterialId, i32)> = cpu
+        .parts
+        .keys()
+        .filter_map(|&mid| {
+            let mdef = mats.get(mid)?;
+            let plain = mdef.render_tag.is_none() && mdef.animation.is_none();
+            (plain && mats.render_pass(mid) == RenderPass::Opaque)
+                .then(|| layer_of(mid).map(|layer| (mid, layer)))
+                .flatten()
+        })
+        .collect();
+    if group.len() < 2 {
+        return None;
+    }
+    // Stable vertex order across rebuilds, so refilled buffers line up with the old ones.
+    group.sort_by_key(|(mid, _)| mid.0);
+    let verts: usize = group
+        .iter()
+        .map(|(mid, _)| cpu.parts[mid].pos.len() / 3)
+        .sum();
+    let mut build = MeshBuild::default();
+    build.pos.reserve(verts * 3);
+    build.norm.reserve(verts * 3);
+    build.uv.reserve(verts * 2);
+    build.col.reserve(verts * 4);
+    let mut layers = Vec::with_capacity(verts);
+    for &(mid, layer) in &group {
+        let Some(mb) = cpu.parts.remove(&mid) else {
+            continue;
+        };
+        build.pos.extend_from_slice(&mb.pos);
+        build.norm.extend_from_slice(&mb.norm);
+        build.uv.extend_from_slice(&mb.uv);
+        build.col.extend_from_slice(&mb.col);
+        layers.resize(build.pos.len() / 3, layer as f32);
+        recycle_build(mb);
+    }
+    Some(LayeredBuild {
+        mid: group[0].0,
+        build,
+        layers,
+    })
+}
+
+impl Drop for BlockTextureArray {
+    fn drop(&mut self) {
+        unsafe { (self.gl.delete_textures)(1, &self.id) };
+    }
+}
@@ -0,0 +1,193 @@
+//! 32-bit index buffers for chunk parts too large for raylib's `u16` indices.
+//!
+//! raylib meshes index with `unsigned short`, so `upload_chunk_mesh` normally splits a
+//! material with more than 65k vertices into several models, one draw call each. When the
+//! GL backend can index with `GL_UNSIGNED_INT` (desktop GL 3.3+, GLES 3), such a material
+//! can stay one model instead: its vertices upload as usual and a `u32` element buffer is
+//! attached to the VAO. `DrawMesh` always draws `GL_UNSIGNED_SHORT`, so those parts are
+//! drawn here, following `DrawMesh` for the single-mesh, VAO-backed models chunks use.
+
+use std::ffi::{CString, c_char, c_void};
+
+use raylib::ffi;
+use raylib::math::Matrix;
+use raylib::prelude::{Color, Vector3};
+
+/// Vertices a `u16` part may hold; larger materials are split or need [`WideIndices`].
+pub const U16_PART_VERTS: usize = 65000;
+
+const GL_TRIANGLES: u32 = 0x0004;
+const GL_UNSIGNED_INT: u32 = 0x1405;
+
+unsafe extern "C" {
+    fn glfwGetProcAddress(procname: *const c_char) -> *const c_void;
+}
+
+type DrawElements = unsafe extern "system" fn(u32, i32, u32, *const c_void);
+
+/// Capability handle for the `u32` index path; exists only when the backend supports it.
+#[derive(Clone, Copy, Debug)]
+pub struct WideIndices {
+    draw_elements: DrawElements,
+}
+
+impl WideIndices {
+    /// Check the current GL context for 32-bit element indices.
+    pub fn detect() -> Result<Self, String> {
+        let version = unsafe { ffi::rlGetVersion() };
+        let supported = [
+            ffi::rlGlVersion::RL_OPENGL_33 as i32,
+            ffi::rlGlVersion::RL_OPENGL_43 as i32,
+            ffi::rlGlVersion::RL_OPENGL_ES_30 as i32,
+        ];
+        if !supported.contains(&version) {
+            return Err(format!(
+                "GL backend {} has no 32-bit element indices",
+                version
+            ));
+        }
+        let cname = CString::new("glDrawElements").expect("GL symbol name");
+        let ptr = unsafe { glfwGetProcAddress(cname.as_ptr()) };
+        if ptr.is_null() {
+            return Err("glDrawElements unavailable".to_string());
+        }
+        // SAFETY: the pointer is the current context's glDrawElements, whose C signature
+        // matches `DrawElements`.
+        let draw_elements = unsafe { std::mem::transmute::<*const c_void, DrawElements>(ptr) };
+        Ok(Self { draw_elements })
+    }
+
+    /// Attach `indices` to an uploaded mesh whose own `indices` are null. The buffer goes
+    /// in the mesh's index VBO slot, so `UnloadMesh` frees it with the rest.
+    ///
+    /// # Safety
+    /// `mesh` must have been uploaded (`vaoId` and `vboId` set) in the current context.
+    pub(crate) unsafe fn attach(&self, mesh: &mut ffi::Mesh, indices: &[u32]) {
+        unsafe {
+            ffi::rlEnableVertexArray(mesh.vaoId);
+            let ebo = ffi::rlLoadVertexBufferElement(
+                indices.as_ptr() as *const c_void,
+                std::mem::size_of_val(indices) as i32,
+                false,
+            );
+            // Unbind the VAO first so it keeps the element buffer.
+            ffi::rlDisableVertexArray();
+            *mesh
+                .vboId
+                .add(ffi::RL_DEFAULT_SHADER_ATTRIB_LOCATION_INDICES as usize) = ebo;
+        }
+    }
+
+    /// `DrawModelEx` for a model whose meshes carry attached `u32` indices. `material`,
+    /// when given, replaces the model's own for every mesh.
+    pub(crate) fn draw_model(
+        &self,
+        model: &ffi::Model,
+        material: Option<&ffi::Material>,
+        transform: Matrix,
+        tint: Color,
+    ) {
+        let transform = Matrix::from(model.transform) * transform;
+        for i in 0..model.meshCount as usize {
+            // SAFETY: a loaded model holds `meshCount` meshes, each with a valid material
+            // index and `MAX_MATERIAL_MAPS` maps per material.
+            unsafe {
+                let mesh = &*model.meshes.add(i);
+                let material = material
+                    .unwrap_or_else(|| &*model.materials.add(*model.meshMaterial.add(i) as usize));
+                self.draw_mesh(mesh, material, transform, tint);
+            }
+        }
+    }
+
+    // Chunk materials carry only an albedo map, and stereo rendering is not used.
+    unsafe fn draw_mesh(
+        &self,
+        mesh: &ffi::Mesh,
+        material: &ffi::Material,
+        transform: Matrix,
+        tint: Color,
+    ) {
+        use ffi::ShaderLocationIndex as Loc;
+        unsafe {
+            let shader = material.shader;
+            let loc = |l: Loc| *shader.locs.add(l as usize);
+            let albedo = &*material.maps;
+            ffi::rlEnableShader(shader.id);
+            if loc(Loc::SHADER_LOC_COLOR_DIFFUSE) != -1 {
+                let c = albedo.color;
+                let mul = |a: u8, b: u8| (a as u32 * b as u32) as f32 / (255.0 * 255.0);
+                let values = [
+                    mul(c.r, tint.r),
+                    mul(c.g, tint.g),
+                    mul(c.b, tint.b),
+                    mul(c.a, tint.a),
+                ];
+                ffi::rlSetUniform(
+                    loc(Loc::SHADER_LOC_COLOR_DIFFUSE),
+                    values.as_ptr() as *const c_void,
+                    ffi::ShaderUniformDataType::SHADER_UNIFORM_VEC4 as i32,
+                    1,
+                );
+            }
+            let view = Matrix::from(ffi::rlGetMatrixModelview());
+            let projection = Matrix::from(ffi::rlGetMatrixProjection());
+            let model = transform * Matrix::from(ffi::rlGetMatrixTransform());
+            if loc(Loc::SHADER_LOC_MATRIX_VIEW) != -1 {
+                ffi::rlSetUniformMatrix(loc(Loc::SHADER_LOC_MATRIX_VIEW), view.into());
+            }
+            if loc(Loc::SHADER_LOC_MATRIX_PROJECTION) != -1 {
+                ffi::rlSetUniformMatrix(loc(Loc::SHADER_LOC_MATRIX_PROJECTION), projection.into());
+            }
+            if loc(Loc::SHADER_LOC_MATRIX_MODEL) != -1 {
+                ffi::rlSetUniformMatrix(loc(Loc::SHADER_LOC_MATRIX_MODEL), model.into());
+            }
+            if loc(Loc::SHADER_LOC_MATRIX_NORMAL) != -1 {
+                ffi::rlSetUniformMatrix(
+                    loc(Loc::SHADER_LOC_MATRIX_NORMAL),
+                    model.inverted().transposed().into(),
+                );
+            }
+            ffi::rlSetUniformMatrix(
+                loc(Loc::SHADER_LOC_MATRIX_MVP),
+                (model * view * projection).into(),
+            );
+            if albedo.texture.id > 0 {
+                ffi::rlActiveTextureSlot(0);
+                ffi::rlEnableTexture(albedo.texture.id);
+                let slot = 0i32;
+                ffi::rlSetUniform(
+                    loc(Loc::SHADER_LOC_MAP_ALBEDO),
+                    &slot as *const i32 as *const c_void,
+                    ffi::ShaderUniformDataType::SHADER_UNIFORM_INT as i32,
+                    1,
+                );
+            }
+            ffi::rlEnableVertexArray(mesh.vaoId);
+            (self.draw_elements)(
+                GL_TRIANGLES,
+                mesh.triangleCount * 3,
+                GL_UNSIGNED_INT,
+                std::ptr::null(),
+            );
+            if albedo.texture.id > 0 {
+                ffi::rlActiveTextureSlot(0);
+                ffi::rlDisableTexture();
+            }
+            ffi::rlDisableVertexArray();
+            ffi::rlDisableShader();
+        }
+    }
+}
+
+/// The transform `DrawModelEx` builds from its position, rotation and scale arguments.
+pub(crate) fn model_ex_transform(
+    position: Vector3,
+    axis: Vector3,
+    angle_deg: f32,
+    scale: Vector3,
+) -> Matrix {
+    Matrix::scale(scale.x, scale.y, scale.z)
+        * Matrix::rotate(axis, angle_deg.to_radians())
+        * Matrix::translate(position.x, position.y, position.z)
+}
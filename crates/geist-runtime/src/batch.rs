@@ -0,0 +1,132 @@
+//! Batch jobs: one parent operation (paste, relight, export) spread over many chunk jobs.
+//!
+//! A batch is opened with the number of child jobs it expects. Build jobs carrying the
+//! batch id count towards its progress as workers finish them. Cancelling a batch drops
+//! its children that have not started yet; jobs already running still deliver results.
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use geist_world::ChunkCoord;
+use hashbrown::{HashMap, HashSet};
+
+pub type BatchId = u64;
+
+#[derive(Clone, Debug)]
+pub struct BatchProgress {
+    pub id: BatchId,
+    pub label: String,
+    pub total: usize,
+    pub done: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BatchEvent {
+    /// A child job finished.
+    Progress {
+        id: BatchId,
+        done: usize,
+        total: usize,
+    },
+    /// Every child job finished.
+    Completed { id: BatchId, total: usize },
+    /// The batch was cancelled after `done` of `total` jobs finished.
+    Cancelled {
+        id: BatchId,
+        done: usize,
+        total: usize,
+    },
+    /// A queued child of a cancelled batch was dropped without running, so whatever the
+    /// owner marked in flight for `coord` at `rev` will never complete.
+    JobSkipped {
+        id: BatchId,
+        coord: ChunkCoord,
+        rev: u64,
+    },
+}
+
+#[derive(Default)]
+struct BatchTable {
+    active: HashMap<BatchId, BatchProgress>,
+    cancelled: HashSet<BatchId>,
+    events: Vec<BatchEvent>,
+}
+
+#[derive(Default)]
+pub(crate) struct BatchTracker {
+    next_id: AtomicU64,
+    table: Mutex<BatchTable>,
+}
+
+impl BatchTracker {
+    pub(crate) fn begin(&self, label: String, total: usize) -> BatchId {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut t = self.table.lock().unwrap();
+        if total == 0 {
+            t.events.push(BatchEvent::Completed { id, total });
+        } else {
+            t.active.insert(
+                id,
+                BatchProgress {
+                    id,
+                    label,
+                    total,
+                    done: 0,
+                },
+            );
+        }
+        id
+    }
+
+    pub(crate) fn cancel(&self, id: BatchId) -> bool {
+        let mut t = self.table.lock().unwrap();
+        let Some(p) = t.active.remove(&id) else {
+            return false;
+        };
+        t.cancelled.insert(id);
+        t.events.push(BatchEvent::Cancelled {
+            id,
+            done: p.done,
+            total: p.total,
+        });
+        true
+    }
+
+    pub(crate) fn is_cancelled(&self, id: BatchId) -> bool {
+        self.table.lock().unwrap().cancelled.contains(&id)
+    }
+
+    pub(crate) fn job_done(&self, id: BatchId) {
+        let mut t = self.table.lock().unwrap();
+        let Some(p) = t.active.get_mut(&id) else {
+            return;
+        };
+        p.done += 1;
+        let (done, total) = (p.done, p.total);
+        t.events.push(BatchEvent::Progress { id, done, total });
+        if done >= total {
+            t.active.remove(&id);
+            t.events.push(BatchEvent::Completed { id, total });
+        }
+    }
+
+    pub(crate) fn job_skipped(&self, id: BatchId, coord: ChunkCoord, rev: u64) {
+        let mut t = self.table.lock().unwrap();
+        t.events.push(BatchEvent::JobSkipped { id, coord, rev });
+    }
+
+    pub(crate) fn progress(&self, id: BatchId) -> Option<BatchProgress> {
+        self.table.lock().unwrap().active.get(&id).cloned()
+    }
+
+    pub(crate) fn active(&self) -> Vec<BatchProgress> {
+        let t = self.table.lock().unwrap();
+        let mut out: Vec<BatchProgress> = t.active.values().cloned().collect();
+        out.sort_by_key(|p| p.id);
+        out
+    }
+
+    pub(crate) fn drain_events(&self) -> Vec<BatchEvent> {
+        std::mem::take(&mut self.table.lock().unwrap().events)
+    }
+}
@@ -0,0 +1,350 @@
+//! Scheduled block ticks.
+//!
+//! Blocks that change over time register a handler per block type with
+//! [`BlockTickHandlers`]. The owner of the world keeps a [`BlockTickScheduler`] of world
+//! positions due at some tick, pops the due ones into a [`BlockTickJob`] and runs it on the
+//! runtime's tick lane. Handlers only read blocks; they answer with the blocks to write and
+//! whether to tick again, and the owner applies the changes as edits.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use geist_blocks::BlockRegistry;
+use geist_blocks::types::{Block, BlockId};
+use hashbrown::{HashMap, HashSet};
+
+pub type BlockPos = (i32, i32, i32);
+
+/// What a handler sees of the world when its block ticks.
+pub struct BlockTickCtx<'a> {
+    pub pos: BlockPos,
+    pub block: Block,
+    pub tick: u64,
+    pub reg: &'a BlockRegistry,
+    blocks: &'a dyn Fn(i32, i32, i32) -> Block,
+}
+
+impl BlockTickCtx<'_> {
+    pub fn block_at(&self, wx: i32, wy: i32, wz: i32) -> Block {
+        (self.blocks)(wx, wy, wz)
+    }
+
+    /// The block at an offset from the ticking one.
+    pub fn neighbor(&self, dx: i32, dy: i32, dz: i32) -> Block {
+        self.block_at(self.pos.0 + dx, self.pos.1 + dy, self.pos.2 + dz)
+    }
+
+    /// Deterministic random number for this block and tick; `salt` picks separate draws.
+    pub fn roll(&self, salt: u32) -> u32 {
+        let (x, y, z) = self.pos;
+        let mut h = (x as u32 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (y as u32 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+            ^ (z as u32 as u64).wrapping_mul(0x1656_67B1_9E37_79F9)
+            ^ self.tick.wrapping_mul(0xFF51_AFD7_ED55_8CCD)
+            ^ u64::from(salt);
+        h ^= h >> 33;
+        h = h.wrapping_mul(0xC4CE_B9FE_1A85_EC53);
+        h ^= h >> 33;
+        h as u32
+    }
+
+    /// True once in `one_in` rolls on average.
+    pub fn chance(&self, salt: u32, one_in: u32) -> bool {
+        one_in <= 1 || self.roll(salt).is_multiple_of(one_in)
+    }
+}
+
+/// A handler's answer: blocks to write and, optionally, ticks until it runs again.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockTickOutcome {
+    pub changes: Vec<(BlockPos, Block)>,
+    pub again_in: Option<u32>,
+}
+
+impl BlockTickOutcome {
+    pub fn idle() -> Self {
+        Self::default()
+    }
+
+    pub fn set(mut self, pos: BlockPos, block: Block) -> Self {
+        self.changes.push((pos, block));
+        self
+    }
+
+    pub fn again_in(mut self, ticks: u32) -> Self {
+        self.again_in = Some(ticks.max(1));
+        self
+    }
+}
+
+pub type BlockTickFn = Box<dyn Fn(&BlockTickCtx) -> BlockTickOutcome + Send + Sync>;
+
+struct BlockTickHandler {
+    delay: u32,
+    run: BlockTickFn,
+}
+
+/// Tick handlers keyed by block type, resolved by name against a [`BlockRegistry`].
+pub struct BlockTickHandlers {
+    reg: Arc<BlockRegistry>,
+    by_id: HashMap<BlockId, BlockTickHandler>,
+}
+
+impl BlockTickHandlers {
+    pub fn new(reg: Arc<BlockRegistry>) -> Self {
+        Self {
+            reg,
+            by_id: HashMap::new(),
+        }
+    }
+
+    /// Tick blocks named `name` `delay` ticks after they or a neighbour change. Replaces
+    /// an earlier handler for the same block; fails for names the registry lacks.
+    pub fn register(
+        &mut self,
+        name: &str,
+        delay: u32,
+        run: impl Fn(&BlockTickCtx) -> BlockTickOutcome + Send + Sync + 'static,
+    ) -> Result<(), String> {
+        let id = self
+            .reg
+            .id_by_name(name)
+            .ok_or_else(|| format!("no block named '{}' to tick", name))?;
+        self.by_id.insert(
+            id,
+            BlockTickHandler {
+                delay: delay.max(1),
+                run: Box::new(run),
+            },
+        );
+        Ok(())
+    }
+
+    pub fn registry(&self) -> &Arc<BlockRegistry> {
+        &self.reg
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_id.is_empty()
+    }
+
+    /// Ticks a changed `block` waits before its handler runs; `None` if it has none.
+    pub fn delay_for(&self, block: Block) -> Option<u32> {
+        self.by_id.get(&block.id).map(|h| h.delay)
+    }
+
+    /// Run the handler of each cell at `tick`, reading blocks through `block_at`.
+    pub fn run(
+        &self,
+        tick: u64,
+        cells: &[(BlockPos, Block)],
+        block_at: &dyn Fn(i32, i32, i32) -> Block,
+    ) -> BlockTickOut {
+        let mut out = BlockTickOut {
+            tick,
+            ..Default::default()
+        };
+        for &(pos, block) in cells {
+            let Some(handler) = self.by_id.get(&block.id) else {
+                continue;
+            };
+            let ctx = BlockTickCtx {
+                pos,
+                block,
+                tick,
+                reg: &self.reg,
+                blocks: block_at,
+            };
+            let outcome = (handler.run)(&ctx);
+            for (at, to) in outcome.changes {
+                let from = block_at(at.0, at.1, at.2);
+                if from != to {
+                    out.changes.push(BlockTickChange { pos: at, from, to });
+                }
+            }
+            if let Some(n) = outcome.again_in {
+                out.again.push((pos, tick + u64::from(n)));
+            }
+        }
+        out
+    }
+}
+
+/// World positions waiting for a tick, each at most once, popped in due order.
+#[derive(Default)]
+pub struct BlockTickScheduler {
+    queue: BTreeSet<(u64, BlockPos)>,
+    due_at: HashMap<BlockPos, u64>,
+}
+
+impl BlockTickScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tick `pos` at `tick`; a position already scheduled keeps the earlier tick.
+    pub fn schedule(&mut self, pos: BlockPos, tick: u64) {
+        if let Some(&at) = self.due_at.get(&pos) {
+            if at <= tick {
+                return;
+            }
+            self.queue.remove(&(at, pos));
+        }
+        self.due_at.insert(pos, tick);
+        self.queue.insert((tick, pos));
+    }
+
+    pub fn cancel(&mut self, pos: BlockPos) {
+        if let Some(at) = self.due_at.remove(&pos) {
+            self.queue.remove(&(at, pos));
+        }
+    }
+
+    /// Up to `budget` positions due at or before `now`, earliest first. The rest wait for
+    /// a later call.
+    pub fn pop_due(&mut self, now: u64, budget: usize) -> Vec<BlockPos> {
+        let mut out = Vec::new();
+        while out.len() < budget {
+            match self.queue.first() {
+                Some(&(at, pos)) if at <= now => {
+                    self.queue.pop_first();
+                    self.due_at.remove(&pos);
+                    out.push(pos);
+                }
+                _ => break,
+            }
+        }
+        out
+    }
+
+    pub fn next_due(&self) -> Option<u64> {
+        self.queue.first().map(|&(at, _)| at)
+    }
+
+    pub fn len(&self) -> usize {
+        self.due_at.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.due_at.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.queue.clear();
+        self.due_at.clear();
+    }
+}
+
+/// Due cells of one tick, for the runtime's tick lane.
+pub struct BlockTickJob {
+    pub tick: u64,
+    /// Each cell with the block it held when scheduled out.
+    pub cells: Vec<(BlockPos, Block)>,
+    /// Edits around the cells; read before built chunks and the generator.
+    pub edits: HashMap<BlockPos, Block>,
+    pub handlers: Arc<BlockTickHandlers>,
+}
+
+/// One block a handler rewrites. `from` is what the handler saw, so the owner can skip a
+/// change whose cell was edited while the job ran.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlockTickChange {
+    pub pos: BlockPos,
+    pub from: Block,
+    pub to: Block,
+}
+
+#[derive(Debug, Default)]
+pub struct BlockTickOut {
+    pub tick: u64,
+    pub changes: Vec<BlockTickChange>,
+    /// Cells whose handler asked to tick again, with the tick they are due.
+    pub again: Vec<(BlockPos, u64)>,
+}
+
+/// The handlers shipped with the game: grass spreads onto lit dirt and dies under cover,
+/// and leaves with no log nearby decay. Blocks the registry lacks are skipped.
+pub fn builtin_block_ticks(reg: Arc<BlockRegistry>) -> BlockTickHandlers {
+    let mut handlers = BlockTickHandlers::new(reg.clone());
+    if let (Some(grass), Some(dirt)) = (reg.id_by_name("grass"), reg.id_by_name("dirt")) {
+        let _ = handlers.register("grass", GRASS_DELAY, move |ctx| {
+            grass_tick(ctx, grass, dirt)
+        });
+    }
+    let logs: HashSet<BlockId> = reg
+        .blocks
+        .iter()
+        .filter(|ty| ty.name.ends_with("_log"))
+        .map(|ty| ty.id)
+        .collect();
+    let logs = Arc::new(logs);
+    for ty in reg.blocks.iter().filter(|ty| ty.name.ends_with("_leaves")) {
+        let logs = logs.clone();
+        let _ = handlers.register(&ty.name, LEAF_DELAY, move |ctx| leaf_tick(ctx, &logs));
+    }
+    handlers
+}
+
+const GRASS_DELAY: u32 = 40;
+const LEAF_DELAY: u32 = 8;
+/// Leaves farther than this (Chebyshev distance) from every log decay.
+const LEAF_REACH: i32 = 4;
+
+// Grass needs open sky above it: anything that blocks skylight, or water, covers it.
+fn covers(ctx: &BlockTickCtx, b: Block) -> bool {
+    ctx.reg
+        .get(b.id)
+        .is_some_and(|ty| ty.blocks_skylight(b.state) || ty.fluid_level(b.state).is_some())
+}
+
+fn grass_tick(ctx: &BlockTickCtx, grass: BlockId, dirt: BlockId) -> BlockTickOutcome {
+    let dirt_block = Block { id: dirt, state: 0 };
+    if covers(ctx, ctx.neighbor(0, 1, 0)) {
+        return BlockTickOutcome::idle().set(ctx.pos, dirt_block);
+    }
+    let mut out = BlockTickOutcome::idle();
+    let mut waiting = false;
+    let mut salt = 0;
+    for dy in -1..=1 {
+        for (dx, dz) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            if ctx.neighbor(dx, dy, dz).id != dirt || covers(ctx, ctx.neighbor(dx, dy + 1, dz)) {
+                continue;
+            }
+            salt += 1;
+            let (x, y, z) = ctx.pos;
+            if ctx.chance(salt, 4) {
+                out = out.set(
+                    (x + dx, y + dy, z + dz),
+                    Block {
+                        id: grass,
+                        state: 0,
+                    },
+                );
+            } else {
+                waiting = true;
+            }
+        }
+    }
+    if waiting {
+        out = out.again_in(GRASS_DELAY);
+    }
+    out
+}
+
+fn leaf_tick(ctx: &BlockTickCtx, logs: &HashSet<BlockId>) -> BlockTickOutcome {
+    for dy in -LEAF_REACH..=LEAF_REACH {
+        for dz in -LEAF_REACH..=LEAF_REACH {
+            for dx in -LEAF_REACH..=LEAF_REACH {
+                if logs.contains(&ctx.neighbor(dx, dy, dz).id) {
+                    return BlockTickOutcome::idle();
+                }
+            }
+        }
+    }
+    // Stagger decay so a canopy thins out instead of vanishing in one tick.
+    if ctx.chance(0, 3) {
+        BlockTickOutcome::idle().set(ctx.pos, Block::AIR)
+    } else {
+        BlockTickOutcome::idle().again_in(LEAF_DELAY)
+    }
+}
@@ -0,0 +1,198 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+use geist_blocks::Block;
+use geist_chunk::{ChunkBuf, ChunkOccupancy};
+use geist_world::ChunkCoord;
+use hashbrown::HashMap;
+
+/// Blocks of one built chunk as published in a [`ChunkSnapshot`].
+#[derive(Clone, Debug)]
+pub enum ChunkBlocks {
+    /// Built and known to hold only air; no buffer is kept.
+    Empty,
+    Buf(Arc<ChunkBuf>),
+}
+
+/// Immutable view of every built chunk at one point in time. Lookups take no locks, and
+/// a snapshot never changes under its holder: later builds land in the next one.
+#[derive(Debug, Default)]
+pub struct ChunkSnapshot {
+    generation: u64,
+    dims: (i32, i32, i32),
+    chunks: HashMap<ChunkCoord, ChunkBlocks>,
+}
+
+impl ChunkSnapshot {
+    /// Bumped on every publish that changed anything.
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    #[inline]
+    pub fn get(&self, coord: ChunkCoord) -> Option<&ChunkBlocks> {
+        self.chunks.get(&coord)
+    }
+
+    /// The built buffer for `coord`; `None` for empty or unbuilt chunks.
+    pub fn buf(&self, coord: ChunkCoord) -> Option<&Arc<ChunkBuf>> {
+        match self.chunks.get(&coord)? {
+            ChunkBlocks::Buf(buf) => Some(buf),
+            ChunkBlocks::Empty => None,
+        }
+    }
+
+    /// Built block at a world voxel: air inside empty chunks, `None` where no chunk has
+    /// been built (callers fall back to the generator).
+    pub fn block_at(&self, wx: i32, wy: i32, wz: i32) -> Option<Block> {
+        let (sx, sy, sz) = self.dims;
+        let coord = ChunkCoord::new(wx.div_euclid(sx), wy.div_euclid(sy), wz.div_euclid(sz));
+        match self.chunks.get(&coord)? {
+            ChunkBlocks::Empty => Some(Block::AIR),
+            ChunkBlocks::Buf(buf) => Some(buf.get_world(wx, wy, wz).unwrap_or(Block::AIR)),
+        }
+    }
+}
+
+/// Runtime-owned map of the latest built `ChunkBuf`s, shared by everything that needs
+/// block data (raycasts, collision, dynamic lights, the minimap).
+///
+/// Writers stage changes with `insert`/`remove`; the next `snapshot` (or `publish`)
+/// folds them into a fresh [`ChunkSnapshot`] and swaps it in. Readers hold on to an
+/// `Arc<ChunkSnapshot>`, or a [`ChunkMapReader`] that refreshes only when the map moved
+/// on, so steady-state reads never touch a lock.
+pub struct ChunkMap {
+    dims: (i32, i32, i32),
+    published: RwLock<Arc<ChunkSnapshot>>,
+    generation: AtomicU64,
+    dirty: AtomicBool,
+    // `None` stages a removal.
+    pending: Mutex<HashMap<ChunkCoord, Option<ChunkBlocks>>>,
+}
+
+impl ChunkMap {
+    pub fn new(sx: usize, sy: usize, sz: usize) -> Self {
+        let dims = (sx as i32, sy as i32, sz as i32);
+        Self {
+            dims,
+            published: RwLock::new(Arc::new(ChunkSnapshot {
+                dims,
+                ..ChunkSnapshot::default()
+            })),
+            generation: AtomicU64::new(0),
+            dirty: AtomicBool::new(false),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Stage `coord`'s latest build; `buf` is ignored for empty chunks. A populated chunk
+    /// without a buffer is staged as removed.
+    pub fn insert(&self, coord: ChunkCoord, occupancy: ChunkOccupancy, buf: Option<Arc<ChunkBuf>>) {
+        let blocks = match (occupancy, buf) {
+            (ChunkOccupancy::Empty, _) => Some(ChunkBlocks::Empty),
+            (ChunkOccupancy::Populated, Some(buf)) => Some(ChunkBlocks::Buf(buf)),
+            (ChunkOccupancy::Populated, None) => None,
+        };
+        self.stage(coord, blocks);
+    }
+
+    pub fn remove(&self, coord: ChunkCoord) {
+        self.stage(coord, None);
+    }
+
+    /// Stage removal of every chunk, e.g. when worldgen changes invalidate all buffers.
+    pub fn clear(&self) {
+        let coords: Vec<ChunkCoord> = self
+            .published
+            .read()
+            .unwrap()
+            .chunks
+            .keys()
+            .copied()
+            .collect();
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|_, blocks| blocks.is_none());
+        for coord in coords {
+            pending.insert(coord, None);
+        }
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    fn stage(&self, coord: ChunkCoord, blocks: Option<ChunkBlocks>) {
+        self.pending.lock().unwrap().insert(coord, blocks);
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    /// Fold staged changes into a new snapshot. Returns false when nothing was staged.
+    pub fn publish(&self) -> bool {
+        if !self.dirty.load(Ordering::Acquire) {
+            return false;
+        }
+        let mut pending = self.pending.lock().unwrap();
+        self.dirty.store(false, Ordering::Release);
+        if pending.is_empty() {
+            return false;
+        }
+        let mut chunks = self.published.read().unwrap().chunks.clone();
+        for (coord, blocks) in pending.drain() {
+            match blocks {
+                Some(blocks) => {
+                    chunks.insert(coord, blocks);
+                }
+                None => {
+                    chunks.remove(&coord);
+                }
+            }
+        }
+        let generation = self.generation.load(Ordering::Relaxed) + 1;
+        *self.published.write().unwrap() = Arc::new(ChunkSnapshot {
+            generation,
+            dims: self.dims,
+            chunks,
+        });
+        self.generation.store(generation, Ordering::Release);
+        true
+    }
+
+    /// The latest snapshot, publishing staged changes first.
+    pub fn snapshot(&self) -> Arc<ChunkSnapshot> {
+        self.publish();
+        Arc::clone(&self.published.read().unwrap())
+    }
+
+    pub fn reader(self: &Arc<Self>) -> ChunkMapReader {
+        ChunkMapReader {
+            snapshot: self.snapshot(),
+            map: Arc::clone(self),
+        }
+    }
+}
+
+/// A cached snapshot that follows its [`ChunkMap`]: `current` checks two atomics and only
+/// re-reads the map when it changed.
+pub struct ChunkMapReader {
+    map: Arc<ChunkMap>,
+    snapshot: Arc<ChunkSnapshot>,
+}
+
+impl ChunkMapReader {
+    pub fn current(&mut self) -> &ChunkSnapshot {
+        if self.map.dirty.load(Ordering::Acquire)
+            || self.map.generation.load(Ordering::Acquire) != self.snapshot.generation
+        {
+            self.snapshot = self.map.snapshot();
+        }
+        &self.snapshot
+    }
+}
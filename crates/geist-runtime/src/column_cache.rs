@@ -5,12 +5,23 @@ use std::sync::{Arc, Mutex, RwLock};
 use geist_world::ChunkCoord;
 use geist_world::voxel::generation::ChunkColumnProfile;
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct ChunkColumnCacheStats {
     pub hits: u64,
     pub misses: u64,
     pub evictions: u64,
     pub entries: usize,
+    pub capacity: usize,
+    /// Approximate memory held by cached profiles.
+    pub bytes: usize,
+}
+
+impl ChunkColumnCacheStats {
+    /// Fraction of lookups served from the cache, or `None` before the first lookup.
+    pub fn hit_rate(&self) -> Option<f32> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f32 / lookups as f32)
+    }
 }
 
 pub struct ChunkColumnCache {
@@ -75,11 +86,18 @@ impl ChunkColumnCache {
     }
 
     pub fn stats(&self) -> ChunkColumnCacheStats {
+        let (entries, bytes) = self
+            .entries
+            .read()
+            .map(|m| (m.len(), m.values().map(|p| p.approx_bytes()).sum()))
+            .unwrap_or((0, 0));
         ChunkColumnCacheStats {
             hits: self.hits.load(Ordering::Relaxed),
             misses: self.misses.load(Ordering::Relaxed),
             evictions: self.evictions.load(Ordering::Relaxed),
-            entries: self.entries.read().map(|m| m.len()).unwrap_or(0),
+            entries,
+            capacity: self.capacity,
+            bytes,
         }
     }
 
@@ -138,3 +156,57 @@ impl ChunkColumnCache {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geist_blocks::types::Block;
+    use geist_world::voxel::generation::{ChunkColumnPlan, ColumnMaterials};
+
+    fn profile(cx: i32, rev: u32) -> Arc<ChunkColumnProfile> {
+        let plan = ChunkColumnPlan {
+            columns: Vec::new(),
+            materials: ColumnMaterials {
+                sub_near_block: Block::AIR,
+                sub_deep_block: Block::AIR,
+                water_block: None,
+                air_block: Block::AIR,
+                topsoil_thickness: 0,
+                leaf_radius: 0,
+            },
+            biome_materials: Vec::new(),
+            width: 0,
+            depth: 0,
+        };
+        Arc::new(ChunkColumnProfile::new(
+            ChunkCoord::new(cx, 0, 0),
+            rev,
+            plan,
+            Vec::new(),
+        ))
+    }
+
+    #[test]
+    fn stats_track_hits_misses_and_evictions() {
+        let cache = ChunkColumnCache::new(2);
+        assert_eq!(cache.stats().hit_rate(), None);
+
+        cache.insert(profile(0, 1));
+        cache.insert(profile(1, 1));
+        assert!(cache.get(ChunkCoord::new(0, 0, 0), 1).is_some());
+        assert!(cache.get(ChunkCoord::new(5, 0, 0), 1).is_none());
+        // Stale revision counts as a miss and drops the entry.
+        assert!(cache.get(ChunkCoord::new(1, 0, 0), 2).is_none());
+        cache.insert(profile(2, 1));
+        cache.insert(profile(3, 1));
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.evictions, 2);
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.capacity, 2);
+        assert!(stats.bytes >= 2 * std::mem::size_of::<ChunkColumnProfile>());
+        assert_eq!(stats.hit_rate(), Some(1.0 / 3.0));
+    }
+}
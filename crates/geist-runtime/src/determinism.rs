@@ -0,0 +1,105 @@
+//! Debug mode that generates every chunk a second time with a fresh `GenCtx` and compares
+//! the result against the pooled or profile-based buffer the worker actually used.
+//!
+//! Both paths are supposed to be bit-identical; a divergence points at state leaking
+//! through a reused context or a stale column profile, which otherwise only shows up as
+//! seams between chunks built by different workers.
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use geist_blocks::{Block, BlockRegistry};
+use geist_chunk as chunkbuf;
+use geist_world::{ChunkCoord, World};
+
+/// First voxel where the worker's buffer differs from a fresh generation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GenDivergence {
+    pub coord: ChunkCoord,
+    /// The worker built from a cached column profile rather than a pooled context.
+    pub from_profile: bool,
+    /// World position of the first differing voxel in `(y, z, x)` order.
+    pub first: (i32, i32, i32),
+    pub fresh: Block,
+    pub pooled: Block,
+    /// Number of differing voxels in the chunk.
+    pub differing: usize,
+}
+
+/// Compare two buffers of the same chunk; `None` when they match.
+pub fn first_divergence(
+    fresh: &chunkbuf::ChunkBuf,
+    pooled: &chunkbuf::ChunkBuf,
+    from_profile: bool,
+) -> Option<GenDivergence> {
+    let mut first: Option<usize> = None;
+    let mut differing = 0usize;
+    for (i, (a, b)) in fresh.blocks.iter().zip(pooled.blocks.iter()).enumerate() {
+        if a != b {
+            differing += 1;
+            first.get_or_insert(i);
+        }
+    }
+    differing += fresh.blocks.len().abs_diff(pooled.blocks.len());
+    if differing == 0 {
+        return None;
+    }
+    let i = first.unwrap_or(fresh.blocks.len().min(pooled.blocks.len()));
+    let (sx, sz) = (fresh.sx.max(1), fresh.sz.max(1));
+    let (x, z, y) = (i % sx, (i / sx) % sz, i / (sx * sz));
+    let coord = fresh.coord;
+    Some(GenDivergence {
+        coord,
+        from_profile,
+        first: (
+            coord.cx * fresh.sx as i32 + x as i32,
+            coord.cy * fresh.sy as i32 + y as i32,
+            coord.cz * fresh.sz as i32 + z as i32,
+        ),
+        fresh: fresh.blocks.get(i).copied().unwrap_or(Block::AIR),
+        pooled: pooled.blocks.get(i).copied().unwrap_or(Block::AIR),
+        differing,
+    })
+}
+
+#[derive(Default)]
+pub(crate) struct DeterminismCheck {
+    enabled: AtomicBool,
+    checked: AtomicU64,
+    found: Mutex<Vec<GenDivergence>>,
+}
+
+impl DeterminismCheck {
+    pub(crate) fn set_enabled(&self, on: bool) {
+        self.enabled.store(on, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Regenerate `coord` from scratch and record any difference from `pooled`.
+    pub(crate) fn check(
+        &self,
+        world: &World,
+        coord: ChunkCoord,
+        reg: &BlockRegistry,
+        pooled: &chunkbuf::ChunkBuf,
+        from_profile: bool,
+    ) {
+        let fresh = chunkbuf::generate_chunk_buffer(world, coord, reg);
+        self.checked.fetch_add(1, Ordering::Relaxed);
+        if let Some(d) = first_divergence(&fresh.buf, pooled, from_profile) {
+            self.found.lock().unwrap().push(d);
+        }
+    }
+
+    pub(crate) fn checked(&self) -> u64 {
+        self.checked.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn drain(&self) -> Vec<GenDivergence> {
+        std::mem::take(&mut *self.found.lock().unwrap())
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Length of a fairness window.
+pub(crate) const FAIR_INTERVAL: Duration = Duration::from_millis(100);
+/// Share of the jobs started in a window that goes to lighting while light jobs wait.
+pub(crate) const MIN_LIGHT_SHARE: f32 = 0.25;
+
+struct Window {
+    start: Instant,
+    light: u32,
+    bg: u32,
+    // Light jobs were seen queued during the window.
+    light_waiting: bool,
+}
+
+/// Keeps light jobs from starving behind background streaming. Background workers ask
+/// [`LightFairness::light_first`] before picking their next job; while light jobs wait and
+/// got less than `min_share` of the jobs started this window, they take a light job first.
+pub(crate) struct LightFairness {
+    interval: Duration,
+    min_share: f32,
+    window: Mutex<Window>,
+    starved: AtomicU64,
+    boosted: AtomicU64,
+}
+
+impl Default for LightFairness {
+    fn default() -> Self {
+        Self::new(FAIR_INTERVAL, MIN_LIGHT_SHARE)
+    }
+}
+
+impl LightFairness {
+    pub(crate) fn new(interval: Duration, min_share: f32) -> Self {
+        Self {
+            interval,
+            min_share,
+            window: Mutex::new(Window {
+                start: Instant::now(),
+                light: 0,
+                bg: 0,
+                light_waiting: false,
+            }),
+            starved: AtomicU64::new(0),
+            boosted: AtomicU64::new(0),
+        }
+    }
+
+    // Close the window once it has run its length. If light jobs waited through it without
+    // one starting, it counts as starved, as does every further window the wait spanned.
+    fn roll(&self, w: &mut Window) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(w.start);
+        if elapsed < self.interval {
+            return;
+        }
+        if w.light_waiting && w.light == 0 {
+            let windows = elapsed.as_nanos() / self.interval.as_nanos().max(1);
+            self.starved
+                .fetch_add(windows.min(u128::from(u64::MAX)) as u64, Ordering::Relaxed);
+        }
+        *w = Window {
+            start: now,
+            light: 0,
+            bg: 0,
+            light_waiting: false,
+        };
+    }
+
+    /// Whether a background worker should take a light job before its own; `light_queued`
+    /// is whether any are waiting.
+    pub(crate) fn light_first(&self, light_queued: bool) -> bool {
+        let mut w = self.window.lock().unwrap();
+        self.roll(&mut w);
+        if !light_queued {
+            return false;
+        }
+        w.light_waiting = true;
+        let total = w.light + w.bg + 1;
+        (w.light as f32) < self.min_share * total as f32
+    }
+
+    pub(crate) fn light_started(&self, boosted: bool) {
+        let mut w = self.window.lock().unwrap();
+        self.roll(&mut w);
+        w.light += 1;
+        if boosted {
+            self.boosted.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn bg_started(&self) {
+        let mut w = self.window.lock().unwrap();
+        self.roll(&mut w);
+        w.bg += 1;
+    }
+
+    /// Windows that ended with light jobs waiting and none started.
+    pub(crate) fn starved(&self) -> u64 {
+        self.starved.load(Ordering::Relaxed)
+    }
+
+    /// Light jobs background workers ran ahead of their own queue.
+    pub(crate) fn boosted(&self) -> u64 {
+        self.boosted.load(Ordering::Relaxed)
+    }
+}
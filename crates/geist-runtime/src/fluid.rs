@@ -0,0 +1,197 @@
+//! Cellular fluid flow.
+//!
+//! Fluids carry a fill level in their block state (see [`BlockType::fluid_level`]): level 0
+//! is a source, higher levels are thinner flow. Each tick re-evaluates the active cells
+//! against the blocks as they were when the tick started:
+//!
+//! - sources never change;
+//! - air or flowing fluid under a fluid becomes falling fluid at level 1;
+//! - otherwise it takes one level more than its fullest feeder, a horizontal fluid
+//!   neighbour resting on something other than air or flow (flow falls before it spreads);
+//! - flow left without a feeder drains to air.
+//!
+//! Active cells are bucketed by chunk and each tick's budget is shared between chunks, so
+//! a large flood cannot starve a small one. A cell whose block changed wakes itself and its
+//! six neighbours for the next tick; everything else settles out of the active set.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use geist_blocks::registry::BlockType;
+use geist_blocks::{Block, BlockRegistry, FLUID_MAX_LEVEL};
+
+type Pos = (i32, i32, i32);
+
+const NEIGHBORS: [(i32, i32, i32); 6] = [
+    (1, 0, 0),
+    (-1, 0, 0),
+    (0, 1, 0),
+    (0, -1, 0),
+    (0, 0, 1),
+    (0, 0, -1),
+];
+const HORIZONTAL: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// One voxel rewritten by a fluid tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FluidChange {
+    pub pos: (i32, i32, i32),
+    pub block: Block,
+}
+
+/// Active set and rules of the fluid simulation; the caller owns the blocks.
+pub struct FluidSim {
+    sx: i32,
+    sy: i32,
+    sz: i32,
+    // Keyed by chunk coordinate, ordered so ticks are deterministic.
+    active: BTreeMap<Pos, BTreeSet<Pos>>,
+}
+
+impl FluidSim {
+    pub fn new(sx: i32, sy: i32, sz: i32) -> Self {
+        Self {
+            sx,
+            sy,
+            sz,
+            active: BTreeMap::new(),
+        }
+    }
+
+    fn chunk_of(&self, (wx, wy, wz): (i32, i32, i32)) -> (i32, i32, i32) {
+        (
+            wx.div_euclid(self.sx),
+            wy.div_euclid(self.sy),
+            wz.div_euclid(self.sz),
+        )
+    }
+
+    fn wake(&mut self, pos: (i32, i32, i32)) {
+        let k = self.chunk_of(pos);
+        self.active.entry(k).or_default().insert(pos);
+    }
+
+    /// Queue `pos` and its six neighbours; call after any block change there.
+    pub fn activate(&mut self, (wx, wy, wz): (i32, i32, i32)) {
+        self.wake((wx, wy, wz));
+        for (dx, dy, dz) in NEIGHBORS {
+            self.wake((wx + dx, wy + dy, wz + dz));
+        }
+    }
+
+    pub fn active_cells(&self) -> usize {
+        self.active.values().map(|s| s.len()).sum()
+    }
+
+    pub fn active_chunks(&self) -> usize {
+        self.active.len()
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.active.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.active.clear();
+    }
+
+    /// Advance one tick over at most `budget` active cells, reading blocks through
+    /// `block_at`. Returns the changes for the caller to write back; the simulation
+    /// assumes they are applied before the next tick.
+    pub fn tick(
+        &mut self,
+        reg: &BlockRegistry,
+        budget: usize,
+        block_at: impl Fn(i32, i32, i32) -> Block,
+    ) -> Vec<FluidChange> {
+        let cells = self.take_cells(budget);
+        let mut changes = Vec::new();
+        for pos in cells {
+            let here = block_at(pos.0, pos.1, pos.2);
+            if let Some(next) = next_block(reg, pos, here, &block_at) {
+                changes.push(FluidChange { pos, block: next });
+            }
+        }
+        for change in &changes {
+            self.activate(change.pos);
+        }
+        changes
+    }
+
+    // Round-robin over chunks so every active chunk gets a share of the budget.
+    fn take_cells(&mut self, budget: usize) -> Vec<(i32, i32, i32)> {
+        let mut out = Vec::new();
+        while out.len() < budget && !self.active.is_empty() {
+            let share = (budget - out.len()).div_ceil(self.active.len()).max(1);
+            let mut emptied = Vec::new();
+            for (k, set) in self.active.iter_mut() {
+                for _ in 0..share {
+                    if out.len() >= budget {
+                        break;
+                    }
+                    match set.pop_first() {
+                        Some(pos) => out.push(pos),
+                        None => break,
+                    }
+                }
+                if set.is_empty() {
+                    emptied.push(*k);
+                }
+            }
+            for k in emptied {
+                self.active.remove(&k);
+            }
+        }
+        out
+    }
+}
+
+fn fluid_of(reg: &BlockRegistry, b: Block) -> Option<(&BlockType, u8)> {
+    let ty = reg.get(b.id)?;
+    ty.fluid_level(b.state).map(|level| (ty, level))
+}
+
+/// What `here` turns into this tick, or `None` when it stays.
+fn next_block(
+    reg: &BlockRegistry,
+    (x, y, z): (i32, i32, i32),
+    here: Block,
+    block_at: &impl Fn(i32, i32, i32) -> Block,
+) -> Option<Block> {
+    let current = fluid_of(reg, here);
+    match current {
+        Some((_, 0)) => return None,
+        Some(_) => {}
+        None if here.id != Block::AIR.id => return None,
+        None => {}
+    }
+    let is_flow = |b: Block| fluid_of(reg, b).is_some_and(|(_, level)| level > 0);
+
+    let mut best: Option<(&BlockType, u8)> = None;
+    if let Some((ty, _)) = fluid_of(reg, block_at(x, y + 1, z)) {
+        best = Some((ty, 1));
+    } else {
+        for (dx, dz) in HORIZONTAL {
+            let Some((ty, level)) = fluid_of(reg, block_at(x + dx, y, z + dz)) else {
+                continue;
+            };
+            if level >= FLUID_MAX_LEVEL {
+                continue;
+            }
+            let below = block_at(x + dx, y - 1, z + dz);
+            if below.id == Block::AIR.id || is_flow(below) {
+                continue;
+            }
+            if best.is_none_or(|(_, b)| level + 1 < b) {
+                best = Some((ty, level + 1));
+            }
+        }
+    }
+    let next = match best {
+        Some((ty, level)) => Block {
+            id: ty.id,
+            state: ty.fluid_state(level),
+        },
+        None => Block::AIR,
+    };
+    (next != here).then_some(next)
+}
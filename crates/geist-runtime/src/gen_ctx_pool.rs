@@ -28,7 +28,7 @@ impl GenCtxPool {
     /// Acquire a context from the pool, creating a new one if under capacity.
     pub fn acquire<'pool>(&'pool self, world: &World) -> PooledGenCtx<'pool> {
         if let Ok(mut ctx) = self.available_rx.try_recv() {
-            Self::prepare(&mut ctx);
+            Self::prepare(world, &mut ctx);
             return PooledGenCtx {
                 ctx: Some(ctx),
                 pool: self,
@@ -41,7 +41,7 @@ impl GenCtxPool {
                 let prev = self.allocated.fetch_add(1, Ordering::AcqRel);
                 if prev < self.max_contexts {
                     let mut ctx = world.make_gen_ctx();
-                    Self::prepare(&mut ctx);
+                    Self::prepare(world, &mut ctx);
                     return PooledGenCtx {
                         ctx: Some(ctx),
                         pool: self,
@@ -52,7 +52,7 @@ impl GenCtxPool {
 
             match self.available_rx.recv() {
                 Ok(mut ctx) => {
-                    Self::prepare(&mut ctx);
+                    Self::prepare(world, &mut ctx);
                     return PooledGenCtx {
                         ctx: Some(ctx),
                         pool: self,
@@ -63,7 +63,12 @@ impl GenCtxPool {
         }
     }
 
-    fn prepare(ctx: &mut GenCtx) {
+    fn prepare(world: &World, ctx: &mut GenCtx) {
+        // Pooled contexts snapshot the params they were made with; replace any that
+        // predate a worldgen reload so new jobs never generate from old params.
+        if ctx.worldgen_rev != world.current_worldgen_rev() {
+            *ctx = world.make_gen_ctx();
+        }
         ctx.terrain_profiler.reset();
         ctx.height_tile_stats = HeightTileStats::default();
         ctx.tile_cache_stats = TerrainTileCacheStats::default();
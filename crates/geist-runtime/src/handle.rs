@@ -0,0 +1,163 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::Sender;
+use hashbrown::HashMap;
+
+use crate::JobOut;
+
+/// Identifies a build submitted through [`Runtime::submit_build`](crate::Runtime::submit_build).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct JobHandleId(pub u64);
+
+/// Called with the result on the worker thread that finished the build.
+pub type JobCallback = Box<dyn FnOnce(&JobOut) + Send>;
+
+#[derive(Default)]
+struct SlotState {
+    out: Option<JobOut>,
+    finished: bool,
+    cancelled: bool,
+    callbacks: Vec<JobCallback>,
+}
+
+#[derive(Default)]
+struct Slot {
+    state: Mutex<SlotState>,
+    done: Condvar,
+}
+
+/// A build that resolves to its own result instead of going through
+/// [`Runtime::drain_worker_results`](crate::Runtime::drain_worker_results).
+///
+/// The handle resolves with the job's first result. For a `Coarse` job that is the coarse
+/// build; its light refinement is not tracked by the handle.
+pub struct JobHandle {
+    id: JobHandleId,
+    slot: Arc<Slot>,
+}
+
+impl JobHandle {
+    pub fn id(&self) -> JobHandleId {
+        self.id
+    }
+
+    /// The build produced a result, whether or not it was taken yet.
+    pub fn is_finished(&self) -> bool {
+        self.slot.state.lock().unwrap().finished
+    }
+
+    /// The job will never run: it was queued at shutdown, its batch was cancelled, or it
+    /// was submitted after shutdown.
+    pub fn is_cancelled(&self) -> bool {
+        self.slot.state.lock().unwrap().cancelled
+    }
+
+    /// Take the result if the build finished. Returns `None` before that and after the
+    /// result was taken once.
+    pub fn poll(&self) -> Option<JobOut> {
+        self.slot.state.lock().unwrap().out.take()
+    }
+
+    /// Block until the build finishes or `timeout` passes, then [`poll`](Self::poll).
+    /// Returns early with `None` when the job is cancelled.
+    pub fn wait(&self, timeout: Duration) -> Option<JobOut> {
+        let until = Instant::now() + timeout;
+        let mut state = self.slot.state.lock().unwrap();
+        while !state.finished && !state.cancelled {
+            let left = until.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return None;
+            }
+            state = self.slot.done.wait_timeout(state, left).unwrap().0;
+        }
+        state.out.take()
+    }
+
+    /// Run `f` with the result once the build finishes: on the worker thread, or right
+    /// away on this thread if the result is already here. Callbacks see the result before
+    /// `poll` can take it; one registered after it was taken never runs.
+    pub fn on_complete(&self, f: impl FnOnce(&JobOut) + Send + 'static) {
+        let mut state = self.slot.state.lock().unwrap();
+        if !state.finished {
+            state.callbacks.push(Box::new(f));
+            return;
+        }
+        if let Some(out) = state.out.take() {
+            drop(state);
+            f(&out);
+            let mut state = self.slot.state.lock().unwrap();
+            state.out = Some(out);
+        }
+    }
+}
+
+/// Handles of builds not finished yet.
+#[derive(Default)]
+pub(crate) struct JobHandles {
+    next: AtomicU64,
+    pending: Mutex<HashMap<JobHandleId, Arc<Slot>>>,
+}
+
+impl JobHandles {
+    pub(crate) fn register(&self) -> JobHandle {
+        let id = JobHandleId(self.next.fetch_add(1, Ordering::Relaxed));
+        let slot = Arc::new(Slot::default());
+        self.pending.lock().unwrap().insert(id, Arc::clone(&slot));
+        JobHandle { id, slot }
+    }
+
+    fn take(&self, id: JobHandleId) -> Option<Arc<Slot>> {
+        self.pending.lock().unwrap().remove(&id)
+    }
+
+    pub(crate) fn cancel(&self, id: JobHandleId) {
+        if let Some(slot) = self.take(id) {
+            slot.state.lock().unwrap().cancelled = true;
+            slot.done.notify_all();
+        }
+    }
+}
+
+/// Where workers deliver results: to the job's handle if it has one, else to the shared
+/// result channel.
+#[derive(Clone)]
+pub(crate) struct ResultSink {
+    tx: Sender<JobOut>,
+    handles: Arc<JobHandles>,
+}
+
+impl ResultSink {
+    pub(crate) fn new(tx: Sender<JobOut>, handles: Arc<JobHandles>) -> Self {
+        Self { tx, handles }
+    }
+
+    pub(crate) fn send(&self, handle: Option<JobHandleId>, out: JobOut) {
+        let Some(slot) = handle.and_then(|id| self.handles.take(id)) else {
+            let _ = self.tx.send(out);
+            return;
+        };
+        // Run callbacks outside the lock; more may be registered while they run.
+        loop {
+            let mut state = slot.state.lock().unwrap();
+            let callbacks = std::mem::take(&mut state.callbacks);
+            if callbacks.is_empty() {
+                state.out = Some(out);
+                state.finished = true;
+                slot.done.notify_all();
+                return;
+            }
+            drop(state);
+            for f in callbacks {
+                f(&out);
+            }
+        }
+    }
+
+    pub(crate) fn cancel(&self, handle: Option<JobHandleId>) {
+        if let Some(id) = handle {
+            self.handles.cancel(id);
+        }
+    }
+}
@@ -1,29 +1,61 @@
 //! Runtime job queues and worker orchestration (slim, engine-only).
 #![forbid(unsafe_code)]
 
+mod batch;
+mod block_tick;
+mod chunk_map;
 mod column_cache;
+mod determinism;
+mod fairness;
+mod fluid;
 mod gen_ctx_pool;
+mod handle;
+mod priority;
+mod stream_gate;
+mod structure_lane;
+mod validate;
 
-use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
 use std::thread;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use crossbeam_channel::{Receiver, Sender, TryRecvError, select, unbounded};
-use geist_blocks::{Block, BlockRegistry};
+use geist_blocks::{Block, BlockEntity, BlockRegistry};
 use geist_chunk as chunkbuf;
+use geist_io::ChunkCache;
 use geist_lighting::{
-    LightAtlas, LightBorders, LightGrid, LightingStore, compute_light_with_borders_buf,
+    LightAtlas, LightBorders, LightGrid, LightQuality, LightingStore, compute_light_with_quality,
 };
 use geist_mesh_cpu::{
     ChunkMeshCPU, NeighborsLoaded, build_chunk_wcc_cpu_buf_with_light, build_structure_wcc_cpu_buf,
+    recycle_chunk,
 };
+use geist_structures::{STRUCTURE_SECTION, SectionCoord};
 use geist_world::{ChunkCoord, TerrainMetrics, World, voxel::generation::ChunkColumnProfile};
 use hashbrown::HashMap;
 use rayon::{ThreadPool, ThreadPoolBuilder};
 
-use crate::column_cache::ChunkColumnCache;
+use crate::batch::BatchTracker;
+pub use crate::batch::{BatchEvent, BatchId, BatchProgress};
+pub use crate::block_tick::{
+    BlockPos, BlockTickChange, BlockTickCtx, BlockTickFn, BlockTickHandlers, BlockTickJob,
+    BlockTickOut, BlockTickOutcome, BlockTickScheduler, builtin_block_ticks,
+};
+pub use crate::chunk_map::{ChunkBlocks, ChunkMap, ChunkMapReader, ChunkSnapshot};
+pub use crate::column_cache::{ChunkColumnCache, ChunkColumnCacheStats};
+use crate::determinism::DeterminismCheck;
+pub use crate::determinism::{GenDivergence, first_divergence};
+use crate::fairness::LightFairness;
+pub use crate::fluid::{FluidChange, FluidSim};
 use crate::gen_ctx_pool::GenCtxPool;
+pub use crate::handle::{JobCallback, JobHandle, JobHandleId};
+use crate::handle::{JobHandles, ResultSink};
+use crate::priority::PriorityLane;
+pub use crate::priority::{JobFocus, job_priority};
+use crate::stream_gate::StreamGate;
+use crate::structure_lane::StructureLane;
+pub use crate::validate::{JobIssue, validate_job_out};
 
 #[derive(Clone, Debug)]
 pub struct BuildJob {
@@ -35,9 +67,19 @@ pub struct BuildJob {
     pub job_id: u64,
     pub chunk_edits: Vec<((i32, i32, i32), Block)>,
     pub region_edits: HashMap<(i32, i32, i32), Block>,
+    /// Mesh-visible block entities of this chunk (e.g. sign text), snapshotted with the
+    /// edits so meshing sees a consistent view.
+    pub block_entities: Vec<((i32, i32, i32), BlockEntity)>,
     pub prev_buf: Option<chunkbuf::ChunkBuf>,
     pub reg: Arc<BlockRegistry>,
     pub column_profile: Option<Arc<ChunkColumnProfile>>,
+    /// Parent operation this job counts towards, if any.
+    pub batch: Option<BatchId>,
+    /// `Coarse` delivers a quick result and queues a `Full` light-only refinement of the
+    /// same revision on the light lane.
+    pub light_quality: LightQuality,
+    /// Set by [`Runtime::submit_build`]; the result goes to that handle.
+    pub handle: Option<JobHandleId>,
 }
 
 pub struct JobOut {
@@ -60,6 +102,64 @@ pub struct JobOut {
     pub t_mesh_ms: u32,
     pub terrain_metrics: TerrainMetrics,
     pub column_profile: Option<Arc<ChunkColumnProfile>>,
+    /// Worldgen revision of the generated blocks; `None` when the job reused an earlier
+    /// buffer (the previous build or the chunk cache) instead of generating.
+    pub worldgen_rev: Option<u32>,
+    pub light_quality: LightQuality,
+    /// The chunk left the stream gate's radius (see [`Runtime::update_stream_gate`])
+    /// before the result was applied; it carries no buffer, light or mesh.
+    pub dropped: bool,
+    // Follow-up pass for a coarse result, submitted when results are drained.
+    refine: Option<BuildJob>,
+}
+
+impl JobOut {
+    // An out-of-range background build: no payload, nothing to refine.
+    fn out_of_range(
+        coord: ChunkCoord,
+        rev: u64,
+        job_id: u64,
+        light_quality: LightQuality,
+        column_profile: Option<Arc<ChunkColumnProfile>>,
+    ) -> Self {
+        Self {
+            cpu: None,
+            light_atlas: None,
+            light_grid: None,
+            buf: None,
+            light_borders: None,
+            cx: coord.cx,
+            cy: coord.cy,
+            cz: coord.cz,
+            rev,
+            job_id,
+            occupancy: chunkbuf::ChunkOccupancy::Empty,
+            kind: JobKind::Bg,
+            t_total_ms: 0,
+            t_gen_ms: 0,
+            t_apply_ms: 0,
+            t_light_ms: 0,
+            t_mesh_ms: 0,
+            terrain_metrics: TerrainMetrics::default(),
+            column_profile,
+            worldgen_rev: None,
+            light_quality,
+            dropped: true,
+            refine: None,
+        }
+    }
+
+    pub(crate) fn drop_payload(&mut self) {
+        if let Some(cpu) = self.cpu.take() {
+            recycle_chunk(cpu);
+        }
+        self.light_atlas = None;
+        self.light_grid = None;
+        self.buf = None;
+        self.light_borders = None;
+        self.refine = None;
+        self.dropped = true;
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -72,20 +172,82 @@ pub struct StructureBuildJob {
     pub base_blocks: Arc<[Block]>,
     pub edits: Vec<((i32, i32, i32), Block)>,
     pub reg: Arc<BlockRegistry>,
+    /// Sections to remesh; the light grid always covers the whole structure.
+    pub sections: Vec<SectionCoord>,
 }
 
 pub struct StructureJobOut {
     pub id: u32,
     pub rev: u64,
-    pub cpu: ChunkMeshCPU,
+    /// Meshes of the rebuilt sections, in structure-local coordinates.
+    pub sections: Vec<(SectionCoord, ChunkMeshCPU)>,
     pub light_grid: LightGrid,
     pub light_borders: LightBorders,
 }
 
+/// Mesh one section of a structure. The faces just outside the section are passed to the
+/// mesher as overscan so seams between sections stay closed without doubled faces.
+fn build_structure_section(
+    buf: &chunkbuf::ChunkBuf,
+    section: SectionCoord,
+    reg: &BlockRegistry,
+) -> ChunkMeshCPU {
+    let n = STRUCTURE_SECTION;
+    let (x0, y0, z0) = (
+        section.0 * n as i32,
+        section.1 * n as i32,
+        section.2 * n as i32,
+    );
+    let at = |x: i32, y: i32, z: i32| -> Option<Block> {
+        if x < 0 || y < 0 || z < 0 {
+            return None;
+        }
+        let (x, y, z) = (x as usize, y as usize, z as usize);
+        (x < buf.sx && y < buf.sy && z < buf.sz).then(|| buf.blocks[buf.idx(x, y, z)])
+    };
+    // Cells past the structure's far edges stay air so edge sections share the full size.
+    let mut blocks = vec![Block::AIR; n * n * n];
+    for ly in 0..n {
+        for lz in 0..n {
+            for lx in 0..n {
+                if let Some(b) = at(x0 + lx as i32, y0 + ly as i32, z0 + lz as i32) {
+                    blocks[(ly * n + lz) * n + lx] = b;
+                }
+            }
+        }
+    }
+    let mut overscan: HashMap<(i32, i32, i32), Block> = HashMap::new();
+    let (lo, hi) = (-1, n as i32);
+    for a in 0..n as i32 {
+        for b in 0..n as i32 {
+            for (x, y, z) in [
+                (x0 + lo, y0 + a, z0 + b),
+                (x0 + hi, y0 + a, z0 + b),
+                (x0 + a, y0 + lo, z0 + b),
+                (x0 + a, y0 + hi, z0 + b),
+                (x0 + a, y0 + b, z0 + lo),
+                (x0 + a, y0 + b, z0 + hi),
+            ] {
+                if let Some(block) = at(x, y, z) {
+                    overscan.insert((x, y, z), block);
+                }
+            }
+        }
+    }
+    let sec = chunkbuf::ChunkBuf::from_blocks_local(
+        ChunkCoord::new(section.0, section.1, section.2),
+        n,
+        n,
+        n,
+        blocks,
+    );
+    build_structure_wcc_cpu_buf(&sec, reg, Some(&overscan))
+}
+
 fn build_structure_outputs(
     job: &StructureBuildJob,
     _skylight_seed: u8,
-) -> (ChunkMeshCPU, LightGrid, LightBorders) {
+) -> (Vec<(SectionCoord, ChunkMeshCPU)>, LightGrid, LightBorders) {
     let mut buf = chunkbuf::ChunkBuf::from_blocks_local(
         ChunkCoord::new(0, 0, 0),
         job.sx,
@@ -110,10 +272,33 @@ fn build_structure_outputs(
     let local_store = LightingStore::new(buf.sx, buf.sy, buf.sz);
     let light_grid = LightGrid::compute_with_borders_buf(&buf, &local_store, &job.reg);
     let light_borders = LightBorders::from_grid(&light_grid);
-    let cpu = build_structure_wcc_cpu_buf(&buf, &job.reg, None);
-    (cpu, light_grid, light_borders)
+    let meshes = job
+        .sections
+        .iter()
+        .copied()
+        .map(|section| (section, build_structure_section(&buf, section, &job.reg)))
+        .collect();
+    (meshes, light_grid, light_borders)
 }
 
+/// Blocks are read from the job's edits, then the latest built chunks, then the generator,
+/// the same order the app resolves them in.
+fn run_block_tick_job(job: &BlockTickJob, world: &World, chunk_map: &ChunkMap) -> BlockTickOut {
+    let snapshot = chunk_map.snapshot();
+    let reg = job.handlers.registry();
+    let block_at = |wx: i32, wy: i32, wz: i32| {
+        job.edits
+            .get(&(wx, wy, wz))
+            .copied()
+            .or_else(|| snapshot.block_at(wx, wy, wz))
+            .unwrap_or_else(|| world.block_at_runtime(reg, wx, wy, wz))
+    };
+    job.handlers.run(job.tick, &job.cells, &block_at)
+}
+
+// Swapped by `Runtime::set_chunk_cache`; workers read it once per job.
+type ChunkCacheSlot = RwLock<Option<Arc<ChunkCache>>>;
+
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 enum Lane {
     Edit,
@@ -128,36 +313,120 @@ pub enum JobKind {
     Bg,
 }
 
+#[allow(clippy::too_many_arguments)]
 fn process_build_job(
     job: BuildJob,
     lane: Lane,
     world: &World,
     lighting: &LightingStore,
     ctx_pool: &GenCtxPool,
-    tx: &Sender<JobOut>,
+    batches: &BatchTracker,
+    determinism: &DeterminismCheck,
+    chunk_cache: &ChunkCacheSlot,
+    gate: &StreamGate,
+    tx: &ResultSink,
 ) {
+    let Some(batch) = job.batch else {
+        run_build_job(
+            job,
+            lane,
+            world,
+            lighting,
+            ctx_pool,
+            determinism,
+            chunk_cache,
+            gate,
+            tx,
+        );
+        return;
+    };
+    if batches.is_cancelled(batch) {
+        let coord = ChunkCoord::new(job.cx, job.cy, job.cz);
+        batches.job_skipped(batch, coord, job.rev);
+        tx.cancel(job.handle);
+        return;
+    }
+    run_build_job(
+        job,
+        lane,
+        world,
+        lighting,
+        ctx_pool,
+        determinism,
+        chunk_cache,
+        gate,
+        tx,
+    );
+    batches.job_done(batch);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_build_job(
+    job: BuildJob,
+    lane: Lane,
+    world: &World,
+    lighting: &LightingStore,
+    ctx_pool: &GenCtxPool,
+    determinism: &DeterminismCheck,
+    chunk_cache: &ChunkCacheSlot,
+    gate: &StreamGate,
+    tx: &ResultSink,
+) {
+    // Stream loads for chunks the view has left are not worth finishing. Handle
+    // submissions always finish; their caller is waiting on the result.
+    let gated = lane == Lane::Bg && job.handle.is_none();
+    let coord = ChunkCoord::new(job.cx, job.cy, job.cz);
+    if gated && !gate.admits(coord) {
+        gate.note_worker_drop();
+        let out = JobOut::out_of_range(
+            coord,
+            job.rev,
+            job.job_id,
+            job.light_quality,
+            job.column_profile,
+        );
+        tx.send(job.handle, out);
+        return;
+    }
     let BuildJob {
         cx,
         cy,
         cz,
+        neighbors,
         rev,
         job_id,
         chunk_edits,
         region_edits,
+        block_entities,
         prev_buf,
         reg,
         column_profile,
+        light_quality,
+        handle,
         ..
     } = job;
 
     let t_job_start = Instant::now();
     let mut t_gen_ms: u32 = 0;
     let mut t_mesh_ms: u32 = 0;
-    let coord = ChunkCoord::new(cx, cy, cz);
 
     let mut column_profile_out = column_profile.clone();
+    let mut worldgen_rev = None;
 
-    let (mut buf, mut occupancy, terrain_metrics) = if let Some(prev) = prev_buf {
+    // Buffers that did not come from generation are already cached unless edits change them.
+    let cache = chunk_cache.read().unwrap().clone();
+    let mut cached = true;
+    let cache_hit = match (&prev_buf, &cache) {
+        (None, Some(cache)) => {
+            let t0 = Instant::now();
+            let hit = cache.load(coord);
+            t_gen_ms = t0.elapsed().as_millis().min(u128::from(u32::MAX)) as u32;
+            hit
+        }
+        _ => None,
+    };
+
+    let (mut buf, mut occupancy, terrain_metrics) = if let Some(prev) = prev_buf.or(cache_hit) {
         let occ = if prev.has_non_air() {
             chunkbuf::ChunkOccupancy::Populated
         } else {
@@ -165,6 +434,7 @@ fn process_build_job(
         };
         (prev, occ, TerrainMetrics::default())
     } else if let Some(profile) = column_profile.clone() {
+        cached = false;
         let t0 = Instant::now();
         let mut pooled_ctx = ctx_pool.acquire(world);
         let generated = chunkbuf::generate_chunk_buffer_from_profile(
@@ -175,19 +445,28 @@ fn process_build_job(
             profile.as_ref(),
         );
         t_gen_ms = t0.elapsed().as_millis().min(u128::from(u32::MAX)) as u32;
+        if determinism.enabled() {
+            determinism.check(world, coord, &reg, &generated.buf, true);
+        }
         column_profile_out = Some(profile);
+        worldgen_rev = Some(generated.worldgen_rev);
         (
             generated.buf,
             generated.occupancy,
             generated.terrain_metrics,
         )
     } else {
+        cached = false;
         let t0 = Instant::now();
         let mut pooled_ctx = ctx_pool.acquire(world);
         let generated =
             chunkbuf::generate_chunk_buffer_with_ctx(world, coord, &reg, &mut pooled_ctx);
         t_gen_ms = t0.elapsed().as_millis().min(u128::from(u32::MAX)) as u32;
+        if determinism.enabled() {
+            determinism.check(world, coord, &reg, &generated.buf, false);
+        }
         column_profile_out = generated.column_profile.map(Arc::new);
+        worldgen_rev = Some(generated.worldgen_rev);
         (
             generated.buf,
             generated.occupancy,
@@ -211,6 +490,7 @@ fn process_build_job(
             let lz = (wz - base_z) as usize;
             if lx < buf.sx && lz < buf.sz {
                 let idx = buf.idx(lx, ly, lz);
+                cached &= buf.blocks[idx] == b;
                 buf.blocks[idx] = b;
                 applied_chunk_edit = true;
             }
@@ -218,6 +498,10 @@ fn process_build_job(
         t0.elapsed().as_millis().min(u128::from(u32::MAX)) as u32
     };
 
+    if !cached && let Some(cache) = &cache {
+        cache.store(&buf);
+    }
+
     if applied_chunk_edit {
         occupancy = if buf.has_non_air() {
             chunkbuf::ChunkOccupancy::Populated
@@ -240,43 +524,14 @@ fn process_build_job(
 
     if !occupancy.has_blocks() {
         let t_total_ms = t_job_start.elapsed().as_millis().min(u128::from(u32::MAX)) as u32;
-        let _ = tx.send(JobOut {
-            cpu: None,
-            light_atlas: None,
-            light_grid: None,
-            buf: None,
-            light_borders: None,
-            cx,
-            cy,
-            cz,
-            rev,
-            job_id,
-            occupancy,
-            kind: job_kind,
-            t_total_ms,
-            t_gen_ms,
-            t_apply_ms,
-            t_light_ms: 0,
-            t_mesh_ms,
-            terrain_metrics,
-            column_profile: column_profile_out.clone(),
-        });
-        return;
-    }
-
-    match lane {
-        Lane::Light => {
-            let t0 = Instant::now();
-            let lg = compute_light_with_borders_buf(&buf, lighting, &reg, world);
-            let t_light_ms = t0.elapsed().as_millis().min(u128::from(u32::MAX)) as u32;
-            let borders = LightBorders::from_grid(&lg);
-            let t_total_ms = t_job_start.elapsed().as_millis().min(u128::from(u32::MAX)) as u32;
-            let _ = tx.send(JobOut {
+        tx.send(
+            handle,
+            JobOut {
                 cpu: None,
                 light_atlas: None,
-                light_grid: Some(lg),
-                buf: Some(buf),
-                light_borders: Some(borders),
+                light_grid: None,
+                buf: None,
+                light_borders: None,
                 cx,
                 cy,
                 cz,
@@ -287,28 +542,62 @@ fn process_build_job(
                 t_total_ms,
                 t_gen_ms,
                 t_apply_ms,
-                t_light_ms,
+                t_light_ms: 0,
                 t_mesh_ms,
                 terrain_metrics,
                 column_profile: column_profile_out.clone(),
-            });
-        }
-        Lane::Edit | Lane::Bg => {
+                worldgen_rev,
+                light_quality,
+                dropped: false,
+                refine: None,
+            },
+        );
+        return;
+    }
+
+    // The view may have moved on while this chunk generated; lighting and meshing are
+    // the expensive part, so check again before starting them.
+    if gated && !gate.admits(coord) {
+        gate.note_worker_drop();
+        let out = JobOut::out_of_range(coord, rev, job_id, light_quality, column_profile_out);
+        tx.send(handle, out);
+        return;
+    }
+
+    // The refinement relights the buffer built here, so it skips generation and edits.
+    let refine = (light_quality == LightQuality::Coarse).then(|| BuildJob {
+        cx,
+        cy,
+        cz,
+        neighbors,
+        rev,
+        job_id,
+        chunk_edits: Vec::new(),
+        region_edits: HashMap::new(),
+        block_entities: block_entities.clone(),
+        prev_buf: Some(buf.clone()),
+        reg: reg.clone(),
+        column_profile: column_profile_out.clone(),
+        batch: None,
+        light_quality: LightQuality::Full,
+        handle: None,
+    });
+
+    match lane {
+        Lane::Light => {
             let t0 = Instant::now();
-            let lg = compute_light_with_borders_buf(&buf, lighting, &reg, world);
+            let lg = compute_light_with_quality(&buf, lighting, &reg, world, light_quality);
             let t_light_ms = t0.elapsed().as_millis().min(u128::from(u32::MAX)) as u32;
-            let t0 = Instant::now();
-            let built =
-                build_chunk_wcc_cpu_buf_with_light(&buf, &lg, world, region_edits_ref, coord, &reg);
-            t_mesh_ms = t0.elapsed().as_millis().min(u128::from(u32::MAX)) as u32;
-            if let Some((cpu, light_borders)) = built {
-                let t_total_ms = t_job_start.elapsed().as_millis().min(u128::from(u32::MAX)) as u32;
-                let _ = tx.send(JobOut {
-                    cpu: Some(cpu),
+            let borders = LightBorders::from_grid(&lg);
+            let t_total_ms = t_job_start.elapsed().as_millis().min(u128::from(u32::MAX)) as u32;
+            tx.send(
+                handle,
+                JobOut {
+                    cpu: None,
                     light_atlas: None,
                     light_grid: Some(lg),
                     buf: Some(buf),
-                    light_borders,
+                    light_borders: refine.is_none().then_some(borders),
                     cx,
                     cy,
                     cz,
@@ -322,44 +611,167 @@ fn process_build_job(
                     t_light_ms,
                     t_mesh_ms,
                     terrain_metrics,
-                    column_profile: column_profile_out,
-                });
+                    column_profile: column_profile_out.clone(),
+                    worldgen_rev,
+                    light_quality,
+                    dropped: false,
+                    refine,
+                },
+            );
+        }
+        Lane::Edit | Lane::Bg => {
+            let t0 = Instant::now();
+            let lg = compute_light_with_quality(&buf, lighting, &reg, world, light_quality);
+            let t_light_ms = t0.elapsed().as_millis().min(u128::from(u32::MAX)) as u32;
+            let t0 = Instant::now();
+            let built =
+                build_chunk_wcc_cpu_buf_with_light(&buf, &lg, world, region_edits_ref, coord, &reg);
+            t_mesh_ms = t0.elapsed().as_millis().min(u128::from(u32::MAX)) as u32;
+            if let Some((cpu, light_borders)) = built {
+                // Coarse seams would be replaced moments later; let the refinement publish them
+                // so neighbours relight once.
+                let light_borders = light_borders.filter(|_| refine.is_none());
+                let t_total_ms = t_job_start.elapsed().as_millis().min(u128::from(u32::MAX)) as u32;
+                tx.send(
+                    handle,
+                    JobOut {
+                        cpu: Some(cpu),
+                        light_atlas: None,
+                        light_grid: Some(lg),
+                        buf: Some(buf),
+                        light_borders,
+                        cx,
+                        cy,
+                        cz,
+                        rev,
+                        job_id,
+                        occupancy,
+                        kind: job_kind,
+                        t_total_ms,
+                        t_gen_ms,
+                        t_apply_ms,
+                        t_light_ms,
+                        t_mesh_ms,
+                        terrain_metrics,
+                        column_profile: column_profile_out,
+                        worldgen_rev,
+                        light_quality,
+                        dropped: false,
+                        refine,
+                    },
+                );
             }
         }
     }
 }
 
+/// Job counts per lane, as returned by [`Runtime::queue_debug_counts`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QueueCounts {
+    pub queued_edit: usize,
+    pub inflight_edit: usize,
+    pub queued_light: usize,
+    pub inflight_light: usize,
+    pub queued_bg: usize,
+    pub inflight_bg: usize,
+    pub queued_structure: usize,
+    pub inflight_structure: usize,
+    pub queued_tick: usize,
+    pub inflight_tick: usize,
+    /// Fairness windows in which light jobs waited and none started.
+    pub light_starved_windows: u64,
+    /// Light jobs that background workers ran ahead of background jobs to keep lighting's
+    /// minimum share.
+    pub light_boosted: u64,
+    /// Background builds workers dropped before lighting and meshing because their chunk
+    /// left the stream gate's radius.
+    pub dropped_on_worker: u64,
+    /// Finished background builds dropped while draining for the same reason.
+    pub dropped_on_drain: u64,
+}
+
+/// What `Runtime::shutdown` finished, cancelled and left behind.
+#[derive(Default)]
+pub struct ShutdownReport {
+    /// Results produced before the workers exited, including jobs that were in flight.
+    pub completed: Vec<JobOut>,
+    pub completed_structures: Vec<StructureJobOut>,
+    pub completed_ticks: Vec<BlockTickOut>,
+    /// Jobs still queued at shutdown; they never started.
+    pub cancelled: Vec<BuildJob>,
+    pub cancelled_structures: Vec<StructureBuildJob>,
+    pub cancelled_ticks: Vec<BlockTickJob>,
+    /// Workers still running when the deadline passed. Their results are lost.
+    pub abandoned_workers: usize,
+}
+
+impl ShutdownReport {
+    #[inline]
+    pub fn clean(&self) -> bool {
+        self.abandoned_workers == 0
+    }
+}
+
 pub struct Runtime {
     job_tx_edit: Sender<BuildJob>,
     job_tx_light: Sender<BuildJob>,
-    job_tx_bg: Sender<BuildJob>,
+    // One token per job queued in `bg_lane`; workers pop the best job on each token.
+    job_tx_bg: Sender<()>,
+    bg_lane: Arc<PriorityLane>,
     res_rx: Receiver<JobOut>,
     _edit_pool: Option<Arc<ThreadPool>>,
     light_pool: Option<Arc<ThreadPool>>,
     bg_pool: Option<Arc<ThreadPool>>,
+    _structure_pool: Arc<ThreadPool>,
     s_job_tx: Sender<StructureBuildJob>,
     s_res_rx: Receiver<StructureJobOut>,
+    // Receiver clones kept so shutdown can pull jobs that never started
+    job_rx_edit: Receiver<BuildJob>,
+    job_rx_light: Receiver<BuildJob>,
+    job_rx_bg: Receiver<()>,
+    s_job_rx: Receiver<StructureBuildJob>,
+    _tick_pool: Arc<ThreadPool>,
+    t_job_tx: Sender<BlockTickJob>,
+    t_job_rx: Receiver<BlockTickJob>,
+    t_res_rx: Receiver<BlockTickOut>,
+    live_workers: Arc<AtomicUsize>,
+    accepting: bool,
     q_edit: Arc<AtomicUsize>,
     q_light: Arc<AtomicUsize>,
     q_bg: Arc<AtomicUsize>,
     inflight_edit: Arc<AtomicUsize>,
     inflight_light: Arc<AtomicUsize>,
     inflight_bg: Arc<AtomicUsize>,
+    q_struct: Arc<AtomicUsize>,
+    inflight_struct: Arc<AtomicUsize>,
+    q_tick: Arc<AtomicUsize>,
+    inflight_tick: Arc<AtomicUsize>,
     pub w_edit: usize,
     pub w_light: usize,
     pub w_bg: usize,
+    pub w_struct: usize,
     _ctx_pool: Arc<GenCtxPool>,
     column_cache: Arc<ChunkColumnCache>,
+    chunk_map: Arc<ChunkMap>,
+    batches: Arc<BatchTracker>,
+    determinism: Arc<DeterminismCheck>,
+    chunk_cache: Arc<ChunkCacheSlot>,
+    handles: Arc<JobHandles>,
+    fairness: Arc<LightFairness>,
+    stream_gate: Arc<StreamGate>,
 }
 
 impl Runtime {
     pub fn new(world: Arc<World>, lighting: Arc<LightingStore>) -> Self {
         let (job_tx_edit, job_rx_edit) = unbounded::<BuildJob>();
         let (job_tx_light, job_rx_light) = unbounded::<BuildJob>();
-        let (job_tx_bg, job_rx_bg) = unbounded::<BuildJob>();
+        let (job_tx_bg, job_rx_bg) = unbounded::<()>();
+        let bg_lane = Arc::new(PriorityLane::default());
         let (res_tx, res_rx) = unbounded::<JobOut>();
         let (s_job_tx, s_job_rx) = unbounded::<StructureBuildJob>();
         let (s_res_tx, s_res_rx) = unbounded::<StructureJobOut>();
+        let (t_job_tx, t_job_rx) = unbounded::<BlockTickJob>();
+        let (t_res_tx, t_res_rx) = unbounded::<BlockTickOut>();
 
         let worker_count: usize = thread::available_parallelism()
             .map(|n| n.get())
@@ -368,10 +780,18 @@ impl Runtime {
         let remaining = worker_count.saturating_sub(w_edit);
         let w_light = if remaining >= 2 { 1 } else { 0 };
         let w_bg = remaining.saturating_sub(w_light);
+        // Structure builds mesh whole ships at once; a few threads keep several edited
+        // ships from queueing behind each other without starving chunk streaming.
+        let w_struct = (worker_count / 4).clamp(1, 4);
         let total_workers = w_edit + w_light + w_bg;
         let ctx_pool = GenCtxPool::with_capacity_from_workers(total_workers);
         let cache_capacity = (world.chunks_x.max(4) * world.chunks_z.max(4) * 4).max(64);
         let column_cache = Arc::new(ChunkColumnCache::new(cache_capacity));
+        let chunk_map = Arc::new(ChunkMap::new(
+            world.chunk_size_x,
+            world.chunk_size_y,
+            world.chunk_size_z,
+        ));
 
         let q_edit_ctr = Arc::new(AtomicUsize::new(0));
         let q_light_ctr = Arc::new(AtomicUsize::new(0));
@@ -379,6 +799,18 @@ impl Runtime {
         let inflight_edit_ctr = Arc::new(AtomicUsize::new(0));
         let inflight_light_ctr = Arc::new(AtomicUsize::new(0));
         let inflight_bg_ctr = Arc::new(AtomicUsize::new(0));
+        let q_struct_ctr = Arc::new(AtomicUsize::new(0));
+        let inflight_struct_ctr = Arc::new(AtomicUsize::new(0));
+        let q_tick_ctr = Arc::new(AtomicUsize::new(0));
+        let inflight_tick_ctr = Arc::new(AtomicUsize::new(0));
+        // Counted up before each worker starts and down when it returns
+        let live_workers = Arc::new(AtomicUsize::new(0));
+        let batches = Arc::new(BatchTracker::default());
+        let determinism = Arc::new(DeterminismCheck::default());
+        let chunk_cache: Arc<ChunkCacheSlot> = Arc::new(RwLock::new(None));
+        let handles = Arc::new(JobHandles::default());
+        let fairness = Arc::new(LightFairness::default());
+        let stream_gate = Arc::new(StreamGate::default());
 
         let edit_pool = if w_edit > 0 {
             let pool = Arc::new(
@@ -390,12 +822,18 @@ impl Runtime {
             );
             for _ in 0..w_edit {
                 let rx = job_rx_edit.clone();
-                let tx = res_tx.clone();
+                let tx = ResultSink::new(res_tx.clone(), handles.clone());
                 let world = world.clone();
                 let lighting = lighting.clone();
                 let q_edit = q_edit_ctr.clone();
                 let inflight_edit = inflight_edit_ctr.clone();
                 let ctx_pool = ctx_pool.clone();
+                let batches = batches.clone();
+                let determinism = determinism.clone();
+                let chunk_cache = chunk_cache.clone();
+                let gate = stream_gate.clone();
+                let live = live_workers.clone();
+                live.fetch_add(1, Ordering::SeqCst);
                 pool.spawn(move || {
                     while let Ok(job) = rx.recv() {
                         q_edit.fetch_sub(1, Ordering::Relaxed);
@@ -406,10 +844,15 @@ impl Runtime {
                             world.as_ref(),
                             lighting.as_ref(),
                             ctx_pool.as_ref(),
+                            batches.as_ref(),
+                            determinism.as_ref(),
+                            chunk_cache.as_ref(),
+                            gate.as_ref(),
                             &tx,
                         );
                         inflight_edit.fetch_sub(1, Ordering::Relaxed);
                     }
+                    live.fetch_sub(1, Ordering::SeqCst);
                 });
             }
             Some(pool)
@@ -427,15 +870,23 @@ impl Runtime {
             );
             for _ in 0..w_light {
                 let rx = job_rx_light.clone();
-                let tx = res_tx.clone();
+                let tx = ResultSink::new(res_tx.clone(), handles.clone());
                 let world = world.clone();
                 let lighting = lighting.clone();
                 let q_light = q_light_ctr.clone();
+                let fairness = fairness.clone();
                 let inflight_light = inflight_light_ctr.clone();
                 let ctx_pool = ctx_pool.clone();
+                let batches = batches.clone();
+                let determinism = determinism.clone();
+                let chunk_cache = chunk_cache.clone();
+                let gate = stream_gate.clone();
+                let live = live_workers.clone();
+                live.fetch_add(1, Ordering::SeqCst);
                 pool.spawn(move || {
                     while let Ok(job) = rx.recv() {
                         q_light.fetch_sub(1, Ordering::Relaxed);
+                        fairness.light_started(false);
                         inflight_light.fetch_add(1, Ordering::Relaxed);
                         process_build_job(
                             job,
@@ -443,10 +894,15 @@ impl Runtime {
                             world.as_ref(),
                             lighting.as_ref(),
                             ctx_pool.as_ref(),
+                            batches.as_ref(),
+                            determinism.as_ref(),
+                            chunk_cache.as_ref(),
+                            gate.as_ref(),
                             &tx,
                         );
                         inflight_light.fetch_sub(1, Ordering::Relaxed);
                     }
+                    live.fetch_sub(1, Ordering::SeqCst);
                 });
             }
             Some(pool)
@@ -464,20 +920,53 @@ impl Runtime {
             );
             for _ in 0..w_bg {
                 let bg_rx = job_rx_bg.clone();
+                let bg_lane = bg_lane.clone();
                 let light_rx = job_rx_light.clone();
-                let tx = res_tx.clone();
+                let tx = ResultSink::new(res_tx.clone(), handles.clone());
                 let world = world.clone();
                 let lighting = lighting.clone();
                 let q_bg = q_bg_ctr.clone();
                 let inflight_bg = inflight_bg_ctr.clone();
                 let q_light = q_light_ctr.clone();
+                let fairness = fairness.clone();
                 let inflight_light = inflight_light_ctr.clone();
                 let ctx_pool = ctx_pool.clone();
+                let batches = batches.clone();
+                let determinism = determinism.clone();
+                let chunk_cache = chunk_cache.clone();
+                let gate = stream_gate.clone();
+                let live = live_workers.clone();
+                live.fetch_add(1, Ordering::SeqCst);
                 pool.spawn(move || {
                     loop {
+                        if fairness.light_first(q_light.load(Ordering::Relaxed) > 0)
+                            && let Ok(job) = light_rx.try_recv()
+                        {
+                            q_light.fetch_sub(1, Ordering::Relaxed);
+                            fairness.light_started(true);
+                            inflight_light.fetch_add(1, Ordering::Relaxed);
+                            process_build_job(
+                                job,
+                                Lane::Light,
+                                world.as_ref(),
+                                lighting.as_ref(),
+                                ctx_pool.as_ref(),
+                                batches.as_ref(),
+                                determinism.as_ref(),
+                                chunk_cache.as_ref(),
+                                gate.as_ref(),
+                                &tx,
+                            );
+                            inflight_light.fetch_sub(1, Ordering::Relaxed);
+                            continue;
+                        }
                         match bg_rx.try_recv() {
-                            Ok(job) => {
+                            Ok(()) => {
+                                let Some(job) = bg_lane.pop() else {
+                                    continue;
+                                };
                                 q_bg.fetch_sub(1, Ordering::Relaxed);
+                                fairness.bg_started();
                                 inflight_bg.fetch_add(1, Ordering::Relaxed);
                                 process_build_job(
                                     job,
@@ -485,6 +974,10 @@ impl Runtime {
                                     world.as_ref(),
                                     lighting.as_ref(),
                                     ctx_pool.as_ref(),
+                                    batches.as_ref(),
+                                    determinism.as_ref(),
+                                    chunk_cache.as_ref(),
+                                    gate.as_ref(),
                                     &tx,
                                 );
                                 inflight_bg.fetch_sub(1, Ordering::Relaxed);
@@ -493,6 +986,7 @@ impl Runtime {
                             Err(TryRecvError::Disconnected) => {
                                 while let Ok(job) = light_rx.try_recv() {
                                     q_light.fetch_sub(1, Ordering::Relaxed);
+                                    fairness.light_started(false);
                                     inflight_light.fetch_add(1, Ordering::Relaxed);
                                     process_build_job(
                                         job,
@@ -500,6 +994,10 @@ impl Runtime {
                                         world.as_ref(),
                                         lighting.as_ref(),
                                         ctx_pool.as_ref(),
+                                        batches.as_ref(),
+                                        determinism.as_ref(),
+                                        chunk_cache.as_ref(),
+                                        gate.as_ref(),
                                         &tx,
                                     );
                                     inflight_light.fetch_sub(1, Ordering::Relaxed);
@@ -512,6 +1010,7 @@ impl Runtime {
                         match light_rx.try_recv() {
                             Ok(job) => {
                                 q_light.fetch_sub(1, Ordering::Relaxed);
+                                fairness.light_started(false);
                                 inflight_light.fetch_add(1, Ordering::Relaxed);
                                 process_build_job(
                                     job,
@@ -519,14 +1018,22 @@ impl Runtime {
                                     world.as_ref(),
                                     lighting.as_ref(),
                                     ctx_pool.as_ref(),
+                                    batches.as_ref(),
+                                    determinism.as_ref(),
+                                    chunk_cache.as_ref(),
+                                    gate.as_ref(),
                                     &tx,
                                 );
                                 inflight_light.fetch_sub(1, Ordering::Relaxed);
                                 continue;
                             }
                             Err(TryRecvError::Disconnected) => match bg_rx.recv() {
-                                Ok(job) => {
+                                Ok(()) => {
+                                    let Some(job) = bg_lane.pop() else {
+                                        continue;
+                                    };
                                     q_bg.fetch_sub(1, Ordering::Relaxed);
+                                    fairness.bg_started();
                                     inflight_bg.fetch_add(1, Ordering::Relaxed);
                                     process_build_job(
                                         job,
@@ -534,6 +1041,10 @@ impl Runtime {
                                         world.as_ref(),
                                         lighting.as_ref(),
                                         ctx_pool.as_ref(),
+                                        batches.as_ref(),
+                                        determinism.as_ref(),
+                                        chunk_cache.as_ref(),
+                                        gate.as_ref(),
                                         &tx,
                                     );
                                     inflight_bg.fetch_sub(1, Ordering::Relaxed);
@@ -546,8 +1057,12 @@ impl Runtime {
 
                         select! {
                             recv(bg_rx) -> res => match res {
-                                Ok(job) => {
+                                Ok(()) => {
+                                    let Some(job) = bg_lane.pop() else {
+                                        continue;
+                                    };
                                     q_bg.fetch_sub(1, Ordering::Relaxed);
+                                    fairness.bg_started();
                                     inflight_bg.fetch_add(1, Ordering::Relaxed);
                                     process_build_job(
                                         job,
@@ -555,6 +1070,10 @@ impl Runtime {
                                         world.as_ref(),
                                         lighting.as_ref(),
                                         ctx_pool.as_ref(),
+                                        batches.as_ref(),
+                                        determinism.as_ref(),
+                                        chunk_cache.as_ref(),
+                                        gate.as_ref(),
                                         &tx,
                                     );
                                     inflight_bg.fetch_sub(1, Ordering::Relaxed);
@@ -562,6 +1081,7 @@ impl Runtime {
                                 Err(_) => {
                                     while let Ok(job) = light_rx.recv() {
                                         q_light.fetch_sub(1, Ordering::Relaxed);
+                                        fairness.light_started(false);
                                         inflight_light.fetch_add(1, Ordering::Relaxed);
                                         process_build_job(
                                             job,
@@ -569,6 +1089,10 @@ impl Runtime {
                                             world.as_ref(),
                                             lighting.as_ref(),
                                             ctx_pool.as_ref(),
+                                            batches.as_ref(),
+                                            determinism.as_ref(),
+                                            chunk_cache.as_ref(),
+                                            gate.as_ref(),
                                             &tx,
                                         );
                                         inflight_light.fetch_sub(1, Ordering::Relaxed);
@@ -579,6 +1103,7 @@ impl Runtime {
                             recv(light_rx) -> res => match res {
                                 Ok(job) => {
                                     q_light.fetch_sub(1, Ordering::Relaxed);
+                                    fairness.light_started(false);
                                     inflight_light.fetch_add(1, Ordering::Relaxed);
                                     process_build_job(
                                         job,
@@ -586,6 +1111,10 @@ impl Runtime {
                                         world.as_ref(),
                                         lighting.as_ref(),
                                         ctx_pool.as_ref(),
+                                        batches.as_ref(),
+                                        determinism.as_ref(),
+                                        chunk_cache.as_ref(),
+                                        gate.as_ref(),
                                         &tx,
                                     );
                                     inflight_light.fetch_sub(1, Ordering::Relaxed);
@@ -594,6 +1123,7 @@ impl Runtime {
                             },
                         }
                     }
+                    live.fetch_sub(1, Ordering::SeqCst);
                 });
             }
             Some(pool)
@@ -601,21 +1131,72 @@ impl Runtime {
             None
         };
 
-        {
-            let s_res_tx = s_res_tx.clone();
+        let structure_pool = Arc::new(
+            ThreadPoolBuilder::new()
+                .num_threads(w_struct)
+                .thread_name(|i| format!("geist-struct-{i}"))
+                .build()
+                .expect("structure pool"),
+        );
+        let structure_lane = Arc::new(StructureLane::default());
+        for _ in 0..w_struct {
+            let rx = s_job_rx.clone();
+            let tx = s_res_tx.clone();
             let lighting = lighting.clone();
-            thread::spawn(move || {
-                while let Ok(job) = s_job_rx.recv() {
-                    let seed = lighting.skylight_max();
-                    let (cpu, light_grid, light_borders) = build_structure_outputs(&job, seed);
-                    let _ = s_res_tx.send(StructureJobOut {
-                        id: job.id,
-                        rev: job.rev,
-                        cpu,
-                        light_grid,
-                        light_borders,
-                    });
+            let lane = structure_lane.clone();
+            let q_struct = q_struct_ctr.clone();
+            let inflight_struct = inflight_struct_ctr.clone();
+            let live = live_workers.clone();
+            live.fetch_add(1, Ordering::SeqCst);
+            structure_pool.spawn(move || {
+                while let Ok(job) = rx.recv() {
+                    let mut next = lane.claim(job);
+                    while let Some(job) = next {
+                        q_struct.fetch_sub(1, Ordering::Relaxed);
+                        inflight_struct.fetch_add(1, Ordering::Relaxed);
+                        let seed = lighting.skylight_max();
+                        let (sections, light_grid, light_borders) =
+                            build_structure_outputs(&job, seed);
+                        let _ = tx.send(StructureJobOut {
+                            id: job.id,
+                            rev: job.rev,
+                            sections,
+                            light_grid,
+                            light_borders,
+                        });
+                        inflight_struct.fetch_sub(1, Ordering::Relaxed);
+                        next = lane.finish(job.id);
+                    }
+                }
+                live.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+
+        // Block ticks are small and ordered by tick; one thread runs them in submission order.
+        let tick_pool = Arc::new(
+            ThreadPoolBuilder::new()
+                .num_threads(1)
+                .thread_name(|i| format!("geist-tick-{i}"))
+                .build()
+                .expect("tick pool"),
+        );
+        {
+            let rx = t_job_rx.clone();
+            let tx = t_res_tx;
+            let world = world.clone();
+            let chunk_map = chunk_map.clone();
+            let q_tick = q_tick_ctr.clone();
+            let inflight_tick = inflight_tick_ctr.clone();
+            let live = live_workers.clone();
+            live.fetch_add(1, Ordering::SeqCst);
+            tick_pool.spawn(move || {
+                while let Ok(job) = rx.recv() {
+                    q_tick.fetch_sub(1, Ordering::Relaxed);
+                    inflight_tick.fetch_add(1, Ordering::Relaxed);
+                    let _ = tx.send(run_block_tick_job(&job, &world, &chunk_map));
+                    inflight_tick.fetch_sub(1, Ordering::Relaxed);
                 }
+                live.fetch_sub(1, Ordering::SeqCst);
             });
         }
 
@@ -623,27 +1204,54 @@ impl Runtime {
             job_tx_edit,
             job_tx_light,
             job_tx_bg,
+            bg_lane,
             res_rx,
             _edit_pool: edit_pool,
             light_pool,
             bg_pool,
+            _structure_pool: structure_pool,
             s_job_tx,
             s_res_rx,
+            job_rx_edit,
+            job_rx_light,
+            job_rx_bg,
+            s_job_rx,
+            _tick_pool: tick_pool,
+            t_job_tx,
+            t_job_rx,
+            t_res_rx,
+            live_workers,
+            accepting: true,
             q_edit: q_edit_ctr,
             q_light: q_light_ctr,
             q_bg: q_bg_ctr,
             inflight_edit: inflight_edit_ctr,
             inflight_light: inflight_light_ctr,
             inflight_bg: inflight_bg_ctr,
+            q_struct: q_struct_ctr,
+            inflight_struct: inflight_struct_ctr,
+            q_tick: q_tick_ctr,
+            inflight_tick: inflight_tick_ctr,
             w_edit,
             w_light,
             w_bg,
+            w_struct,
             _ctx_pool: ctx_pool,
             column_cache,
+            chunk_map,
+            batches,
+            determinism,
+            chunk_cache,
+            handles,
+            fairness,
+            stream_gate,
         }
     }
 
     pub fn submit_build_job_edit(&self, job: BuildJob) {
+        if !self.accepting {
+            return;
+        }
         self.q_edit.fetch_add(1, Ordering::Relaxed);
         if self.job_tx_edit.send(job).is_err() {
             self.q_edit.fetch_sub(1, Ordering::Relaxed);
@@ -651,6 +1259,9 @@ impl Runtime {
     }
 
     pub fn submit_build_job_light(&self, job: BuildJob) {
+        if !self.accepting {
+            return;
+        }
         if self.light_pool.is_some() {
             self.q_light.fetch_add(1, Ordering::Relaxed);
             if self.job_tx_light.send(job).is_err() {
@@ -664,9 +1275,14 @@ impl Runtime {
     }
 
     pub fn submit_build_job_bg(&self, job: BuildJob) {
+        if !self.accepting {
+            return;
+        }
         if self.bg_pool.is_some() {
             self.q_bg.fetch_add(1, Ordering::Relaxed);
-            if self.job_tx_bg.send(job).is_err() {
+            self.bg_lane.push(job);
+            if self.job_tx_bg.send(()).is_err() {
+                self.bg_lane.pop();
                 self.q_bg.fetch_sub(1, Ordering::Relaxed);
             }
         } else {
@@ -674,32 +1290,250 @@ impl Runtime {
         }
     }
 
+    /// Submit `job` on the lane of `kind` and get a handle that resolves with its result,
+    /// which then never shows up in [`drain_worker_results`](Self::drain_worker_results).
+    /// For embedding the runtime without polling every result; see [`JobHandle`].
+    pub fn submit_build(&self, mut job: BuildJob, kind: JobKind) -> JobHandle {
+        let handle = self.handles.register();
+        if !self.accepting {
+            self.handles.cancel(handle.id());
+            return handle;
+        }
+        job.handle = Some(handle.id());
+        match kind {
+            JobKind::Edit => self.submit_build_job_edit(job),
+            JobKind::Light => self.submit_build_job_light(job),
+            JobKind::Bg => self.submit_build_job_bg(job),
+        }
+        handle
+    }
+
+    /// Collect finished jobs, queueing the refinement of any coarse result on the light lane.
+    /// Finished jobs. Debug builds check each with `validate_job_out` and panic on the
+    /// first inconsistent result.
     pub fn drain_worker_results(&self) -> Vec<JobOut> {
-        self.res_rx.try_iter().collect()
+        let mut out: Vec<JobOut> = self.res_rx.try_iter().collect();
+        for r in &mut out {
+            if cfg!(debug_assertions) {
+                let issues = validate_job_out(r);
+                assert!(
+                    issues.is_empty(),
+                    "invalid {:?} result for chunk ({}, {}, {}) rev {}: {}",
+                    r.kind,
+                    r.cx,
+                    r.cy,
+                    r.cz,
+                    r.rev,
+                    issues
+                        .iter()
+                        .map(|i| i.to_string())
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                );
+            }
+            self.stream_gate.revalidate(r);
+            if let Some(job) = r.refine.take() {
+                self.submit_build_job_light(job);
+            }
+        }
+        out
     }
 
     pub fn column_cache(&self) -> Arc<ChunkColumnCache> {
         Arc::clone(&self.column_cache)
     }
 
-    pub fn queue_debug_counts(&self) -> (usize, usize, usize, usize, usize, usize) {
-        (
-            self.q_edit.load(Ordering::Relaxed),
-            self.inflight_edit.load(Ordering::Relaxed),
-            self.q_light.load(Ordering::Relaxed),
-            self.inflight_light.load(Ordering::Relaxed),
-            self.q_bg.load(Ordering::Relaxed),
-            self.inflight_bg.load(Ordering::Relaxed),
-        )
+    pub fn column_cache_stats(&self) -> ChunkColumnCacheStats {
+        self.column_cache.stats()
+    }
+
+    /// Read-only block data of the latest accepted builds; see [`ChunkMap`].
+    pub fn chunk_map(&self) -> Arc<ChunkMap> {
+        Arc::clone(&self.chunk_map)
+    }
+
+    /// Queued and in-flight jobs per lane, plus light fairness counters.
+    pub fn queue_debug_counts(&self) -> QueueCounts {
+        QueueCounts {
+            queued_edit: self.q_edit.load(Ordering::Relaxed),
+            inflight_edit: self.inflight_edit.load(Ordering::Relaxed),
+            queued_light: self.q_light.load(Ordering::Relaxed),
+            inflight_light: self.inflight_light.load(Ordering::Relaxed),
+            queued_bg: self.q_bg.load(Ordering::Relaxed),
+            inflight_bg: self.inflight_bg.load(Ordering::Relaxed),
+            queued_structure: self.q_struct.load(Ordering::Relaxed),
+            inflight_structure: self.inflight_struct.load(Ordering::Relaxed),
+            queued_tick: self.q_tick.load(Ordering::Relaxed),
+            inflight_tick: self.inflight_tick.load(Ordering::Relaxed),
+            light_starved_windows: self.fairness.starved(),
+            light_boosted: self.fairness.boosted(),
+            dropped_on_worker: self.stream_gate.dropped_on_worker(),
+            dropped_on_drain: self.stream_gate.dropped_on_drain(),
+        }
+    }
+
+    /// Order queued and future background builds around `center`, favouring chunks along
+    /// `view_dir` (see [`job_priority`]). Cheap when nothing moved; call every frame.
+    pub fn update_focus(&self, center: ChunkCoord, view_dir: [f32; 3]) {
+        self.bg_lane.set_focus(JobFocus { center, view_dir });
+    }
+
+    /// Drop background builds for chunks farther than `keep_radius` chunks from `center`:
+    /// workers check before generating and again before lighting and meshing, and
+    /// [`drain_worker_results`](Self::drain_worker_results) strips results that finished
+    /// after the center moved. Dropped builds still report, with [`JobOut::dropped`] set,
+    /// so callers can forget them. Cheap when nothing changed; call every frame.
+    pub fn update_stream_gate(&self, center: ChunkCoord, keep_radius: i32) {
+        self.stream_gate.set(center, keep_radius);
+    }
+
+    /// Let every background build finish again.
+    pub fn clear_stream_gate(&self) {
+        self.stream_gate.clear();
     }
 
     pub fn submit_structure_build_job(&self, job: StructureBuildJob) {
-        let _ = self.s_job_tx.send(job);
+        if !self.accepting {
+            return;
+        }
+        self.q_struct.fetch_add(1, Ordering::Relaxed);
+        if self.s_job_tx.send(job).is_err() {
+            self.q_struct.fetch_sub(1, Ordering::Relaxed);
+        }
     }
 
     pub fn drain_structure_results(&self) -> Vec<StructureJobOut> {
         self.s_res_rx.try_iter().collect()
     }
+
+    /// Run one tick's due block handlers on the tick lane; jobs finish in submission order.
+    pub fn submit_block_tick_job(&self, job: BlockTickJob) {
+        if !self.accepting {
+            return;
+        }
+        self.q_tick.fetch_add(1, Ordering::Relaxed);
+        if self.t_job_tx.send(job).is_err() {
+            self.q_tick.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn drain_block_tick_results(&self) -> Vec<BlockTickOut> {
+        self.t_res_rx.try_iter().collect()
+    }
+
+    /// Open a batch expecting `total` child jobs. Tag each with `BuildJob::batch`.
+    pub fn begin_batch(&self, label: impl Into<String>, total: usize) -> BatchId {
+        self.batches.begin(label.into(), total)
+    }
+
+    /// Drop the batch's queued jobs; jobs already running still deliver results.
+    /// Returns false if the batch already finished or was cancelled.
+    pub fn cancel_batch(&self, id: BatchId) -> bool {
+        self.batches.cancel(id)
+    }
+
+    /// Progress of a running batch; `None` once it completed or was cancelled.
+    pub fn batch_progress(&self, id: BatchId) -> Option<BatchProgress> {
+        self.batches.progress(id)
+    }
+
+    /// Running batches, oldest first.
+    pub fn active_batches(&self) -> Vec<BatchProgress> {
+        self.batches.active()
+    }
+
+    pub fn drain_batch_events(&self) -> Vec<BatchEvent> {
+        self.batches.drain_events()
+    }
+
+    /// Debug mode: generate every chunk a second time with a fresh `GenCtx` and record
+    /// where it differs from the pooled or profile-based result. Roughly doubles gen cost.
+    pub fn set_gen_determinism_check(&self, on: bool) {
+        self.determinism.set_enabled(on);
+    }
+
+    pub fn gen_determinism_check(&self) -> bool {
+        self.determinism.enabled()
+    }
+
+    /// Chunks compared since the check was first enabled.
+    pub fn gen_determinism_checked(&self) -> u64 {
+        self.determinism.checked()
+    }
+
+    pub fn drain_gen_divergences(&self) -> Vec<GenDivergence> {
+        self.determinism.drain()
+    }
+
+    /// Consult `cache` before generating chunks, and store every buffer that was generated
+    /// or changed by edits there. `None` turns the cache off; jobs already running finish
+    /// with the cache they started with.
+    pub fn set_chunk_cache(&self, cache: Option<Arc<ChunkCache>>) {
+        *self.chunk_cache.write().unwrap() = cache;
+    }
+
+    pub fn chunk_cache(&self) -> Option<Arc<ChunkCache>> {
+        self.chunk_cache.read().unwrap().clone()
+    }
+
+    pub fn is_accepting(&self) -> bool {
+        self.accepting
+    }
+
+    /// Stop taking jobs, cancel the ones still queued, and wait up to `deadline` for
+    /// in-flight work to finish so its results can be collected.
+    ///
+    /// Submissions after this call are ignored. Workers that outlive the deadline are
+    /// detached and counted in `abandoned_workers`; calling again only collects results.
+    pub fn shutdown(&mut self, deadline: Duration) -> ShutdownReport {
+        let until = Instant::now() + deadline;
+        let mut report = ShutdownReport::default();
+        if self.accepting {
+            self.accepting = false;
+            for (rx, q) in [
+                (&self.job_rx_edit, &self.q_edit),
+                (&self.job_rx_light, &self.q_light),
+            ] {
+                for job in rx.try_iter() {
+                    q.fetch_sub(1, Ordering::Relaxed);
+                    report.cancelled.push(job);
+                }
+            }
+            for () in self.job_rx_bg.try_iter() {
+                if let Some(job) = self.bg_lane.pop() {
+                    self.q_bg.fetch_sub(1, Ordering::Relaxed);
+                    report.cancelled.push(job);
+                }
+            }
+            for job in self.s_job_rx.try_iter() {
+                self.q_struct.fetch_sub(1, Ordering::Relaxed);
+                report.cancelled_structures.push(job);
+            }
+            for job in self.t_job_rx.try_iter() {
+                self.q_tick.fetch_sub(1, Ordering::Relaxed);
+                report.cancelled_ticks.push(job);
+            }
+            for job in &report.cancelled {
+                if let Some(id) = job.handle {
+                    self.handles.cancel(id);
+                }
+            }
+            // Replacing the only senders disconnects the queues, so idle workers return.
+            self.job_tx_edit = unbounded().0;
+            self.job_tx_light = unbounded().0;
+            self.job_tx_bg = unbounded().0;
+            self.s_job_tx = unbounded().0;
+            self.t_job_tx = unbounded().0;
+        }
+        while self.live_workers.load(Ordering::SeqCst) > 0 && Instant::now() < until {
+            thread::sleep(Duration::from_millis(1));
+        }
+        report.abandoned_workers = self.live_workers.load(Ordering::SeqCst);
+        report.completed = self.drain_worker_results();
+        report.completed_structures = self.drain_structure_results();
+        report.completed_ticks = self.drain_block_tick_results();
+        report
+    }
 }
 
 #[cfg(test)]
@@ -720,13 +1554,15 @@ mod tests {
                 solid: Some(false),
                 blocks_skylight: Some(false),
                 propagates_light: Some(true),
-                emission: Some(0),
+                emission: Some(0.into()),
+                flicker: None,
                 light_profile: None,
                 light: None,
                 shape: Some(ShapeConfig::Simple("cube".into())),
                 materials: None,
                 state_schema: None,
                 seam: None,
+                tags: Vec::new(),
             },
             BlockDef {
                 name: "stone".into(),
@@ -734,13 +1570,40 @@ mod tests {
                 solid: Some(true),
                 blocks_skylight: Some(true),
                 propagates_light: Some(false),
-                emission: Some(0),
+                emission: Some(0.into()),
+                flicker: None,
                 light_profile: None,
                 light: None,
                 shape: Some(ShapeConfig::Simple("cube".into())),
                 materials: None,
                 state_schema: None,
                 seam: None,
+                tags: Vec::new(),
+            },
+            BlockDef {
+                name: "water".into(),
+                id: Some(2),
+                solid: Some(false),
+                blocks_skylight: Some(false),
+                propagates_light: Some(true),
+                emission: Some(0.into()),
+                flicker: None,
+                light_profile: None,
+                light: None,
+                shape: Some(ShapeConfig::Simple("cube".into())),
+                materials: None,
+                state_schema: Some(
+                    [(
+                        "level".to_string(),
+                        (0..=geist_blocks::FLUID_MAX_LEVEL)
+                            .map(|l| l.to_string())
+                            .collect(),
+                    )]
+                    .into_iter()
+                    .collect(),
+                ),
+                seam: None,
+                tags: vec![geist_blocks::TAG_FLUID.into()],
             },
         ];
         BlockRegistry::from_configs(
@@ -796,6 +1659,7 @@ mod tests {
             base_blocks: Arc::from(base.into_boxed_slice()),
             edits: Vec::new(),
             reg: reg.clone(),
+            sections: vec![(0, 0, 0)],
         };
 
         // Simulate a midnight snapshot where skylight is zero.
@@ -807,4 +1671,925 @@ mod tests {
         // than the open column, even though horizontal bleed still occurs.
         assert!(light_grid.skylight_at(0, sy - 2, 0) < light_grid.skylight_at(1, sy - 2, 1));
     }
+
+    fn mesh_area(cpu: &ChunkMeshCPU) -> f32 {
+        let mut area = 0.0;
+        for mb in cpu.parts.values() {
+            let p = |i: u16| {
+                let i = i as usize * 3;
+                [mb.pos[i], mb.pos[i + 1], mb.pos[i + 2]]
+            };
+            for tri in mb.idx.chunks_exact(3) {
+                let (a, b, c) = (p(tri[0]), p(tri[1]), p(tri[2]));
+                let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+                let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+                let n = [
+                    u[1] * v[2] - u[2] * v[1],
+                    u[2] * v[0] - u[0] * v[2],
+                    u[0] * v[1] - u[1] * v[0],
+                ];
+                area += 0.5 * (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+            }
+        }
+        area
+    }
+
+    #[test]
+    fn structure_sections_mesh_without_seam_faces() {
+        // Meshing needs real materials, so use the shipped registry.
+        let vox = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../assets/voxels");
+        let reg = Arc::new(
+            BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml"))
+                .unwrap(),
+        );
+        let stone = Block {
+            id: reg.id_by_name("stone").unwrap(),
+            state: 0,
+        };
+        // A solid bar crossing one section seam along X and ending mid-section.
+        let (sx, sy, sz) = (STRUCTURE_SECTION + 8, 3usize, 2usize);
+        let job = StructureBuildJob {
+            id: 1,
+            rev: 1,
+            sx,
+            sy,
+            sz,
+            base_blocks: Arc::from(vec![stone; sx * sy * sz].into_boxed_slice()),
+            edits: Vec::new(),
+            reg: reg.clone(),
+            sections: vec![(0, 0, 0), (1, 0, 0)],
+        };
+        let (sections, _lg, _lb) = build_structure_outputs(&job, 255);
+        assert_eq!(sections.len(), 2);
+        let area: f32 = sections.iter().map(|(_, cpu)| mesh_area(cpu)).sum();
+        let surface = 2 * (sx * sy + sx * sz + sy * sz);
+        assert!((area - surface as f32).abs() < 1e-3, "area {}", area);
+
+        // Remeshing only the far section reproduces its share of the surface.
+        let far = StructureBuildJob {
+            sections: vec![(1, 0, 0)],
+            ..job
+        };
+        let (only, _lg, _lb) = build_structure_outputs(&far, 255);
+        assert_eq!(only.len(), 1);
+        assert!((mesh_area(&only[0].1) - mesh_area(&sections[1].1)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn shutdown_stops_accepting_and_accounts_for_every_job() {
+        use geist_world::WorldGenMode;
+        let reg = Arc::new(make_test_registry());
+        let air = Block {
+            id: reg.id_by_name("air").unwrap(),
+            state: 0,
+        };
+        let world = Arc::new(World::new(1, 1, 1, 3, WorldGenMode::Flat { thickness: 1 }));
+        let lighting = Arc::new(LightingStore::new(
+            world.chunk_size_x,
+            world.chunk_size_y,
+            world.chunk_size_z,
+        ));
+        let mut rt = Runtime::new(world, lighting);
+        let job = |id: u32| StructureBuildJob {
+            id,
+            rev: 1,
+            sx: 2,
+            sy: 2,
+            sz: 2,
+            base_blocks: Arc::from(vec![air; 8].into_boxed_slice()),
+            edits: Vec::new(),
+            reg: reg.clone(),
+            sections: vec![(0, 0, 0)],
+        };
+        for id in 0..4 {
+            rt.submit_structure_build_job(job(id));
+        }
+
+        let report = rt.shutdown(Duration::from_secs(10));
+        assert!(report.clean());
+        assert!(!rt.is_accepting());
+        assert_eq!(
+            report.completed_structures.len() + report.cancelled_structures.len(),
+            4
+        );
+
+        // Later submissions are dropped rather than queued.
+        rt.submit_structure_build_job(job(9));
+        let again = rt.shutdown(Duration::from_millis(10));
+        assert!(again.cancelled_structures.is_empty());
+        assert!(again.completed_structures.is_empty());
+        let counts = rt.queue_debug_counts();
+        assert_eq!(
+            counts,
+            QueueCounts {
+                light_starved_windows: counts.light_starved_windows,
+                light_boosted: counts.light_boosted,
+                ..QueueCounts::default()
+            }
+        );
+    }
+
+    #[test]
+    fn structure_builds_keep_order_per_structure_and_drain_the_counts() {
+        use geist_world::WorldGenMode;
+        let reg = Arc::new(make_test_registry());
+        let air = Block {
+            id: reg.id_by_name("air").unwrap(),
+            state: 0,
+        };
+        let world = Arc::new(World::new(1, 1, 1, 3, WorldGenMode::Flat { thickness: 1 }));
+        let lighting = Arc::new(LightingStore::new(
+            world.chunk_size_x,
+            world.chunk_size_y,
+            world.chunk_size_z,
+        ));
+        let rt = Runtime::new(world, lighting);
+        let job = |id: u32, rev: u64| StructureBuildJob {
+            id,
+            rev,
+            sx: 2,
+            sy: 2,
+            sz: 2,
+            base_blocks: Arc::from(vec![air; 8].into_boxed_slice()),
+            edits: Vec::new(),
+            reg: reg.clone(),
+            sections: vec![(0, 0, 0)],
+        };
+        for rev in 1..=6 {
+            for id in 0..3 {
+                rt.submit_structure_build_job(job(id, rev));
+            }
+        }
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut done = Vec::new();
+        while done.len() < 18 && Instant::now() < deadline {
+            done.extend(rt.drain_structure_results());
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(done.len(), 18);
+        for id in 0..3 {
+            let revs: Vec<u64> = done.iter().filter(|r| r.id == id).map(|r| r.rev).collect();
+            assert_eq!(revs, (1..=6).collect::<Vec<_>>(), "structure {id}");
+        }
+        let counts = rt.queue_debug_counts();
+        assert_eq!((counts.queued_structure, counts.inflight_structure), (0, 0));
+    }
+
+    fn wait_for_events(rt: &Runtime, until: impl Fn(&[BatchEvent]) -> bool) -> Vec<BatchEvent> {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut events = Vec::new();
+        while !until(&events) && Instant::now() < deadline {
+            events.extend(rt.drain_batch_events());
+            thread::sleep(Duration::from_millis(1));
+        }
+        events
+    }
+
+    #[test]
+    fn batch_reports_progress_and_cancellation_skips_queued_jobs() {
+        use geist_world::WorldGenMode;
+        let reg = Arc::new(make_test_registry());
+        let world = Arc::new(World::new(2, 1, 1, 3, WorldGenMode::Flat { thickness: 1 }));
+        let lighting = Arc::new(LightingStore::new(
+            world.chunk_size_x,
+            world.chunk_size_y,
+            world.chunk_size_z,
+        ));
+        let rt = Runtime::new(world, lighting);
+        let job = |cx: i32, batch: Option<BatchId>| BuildJob {
+            cx,
+            cy: 0,
+            cz: 0,
+            neighbors: NeighborsLoaded::default(),
+            rev: 1,
+            job_id: cx as u64,
+            chunk_edits: Vec::new(),
+            region_edits: HashMap::new(),
+            block_entities: Vec::new(),
+            prev_buf: None,
+            reg: reg.clone(),
+            column_profile: None,
+            batch,
+            light_quality: LightQuality::Full,
+            handle: None,
+        };
+
+        let id = rt.begin_batch("paste", 2);
+        assert_eq!(rt.active_batches().len(), 1);
+        rt.submit_build_job_edit(job(0, Some(id)));
+        rt.submit_build_job_edit(job(1, Some(id)));
+        let events = wait_for_events(&rt, |e| {
+            e.iter()
+                .any(|ev| matches!(ev, BatchEvent::Completed { .. }))
+        });
+        let progress = events
+            .iter()
+            .filter(|ev| matches!(ev, BatchEvent::Progress { .. }))
+            .count();
+        assert_eq!(progress, 2);
+        assert!(events.contains(&BatchEvent::Completed { id, total: 2 }));
+        assert!(rt.batch_progress(id).is_none());
+        assert!(!rt.cancel_batch(id));
+        assert_eq!(rt.drain_worker_results().len(), 2);
+
+        let id = rt.begin_batch("relight", 2);
+        assert!(rt.cancel_batch(id));
+        rt.submit_build_job_edit(job(0, Some(id)));
+        rt.submit_build_job_edit(job(1, Some(id)));
+        let events = wait_for_events(&rt, |e| {
+            e.iter()
+                .filter(|ev| matches!(ev, BatchEvent::JobSkipped { .. }))
+                .count()
+                == 2
+        });
+        assert_eq!(
+            events[0],
+            BatchEvent::Cancelled {
+                id,
+                done: 0,
+                total: 2
+            }
+        );
+        assert!(events.contains(&BatchEvent::JobSkipped {
+            id,
+            coord: ChunkCoord::new(1, 0, 0),
+            rev: 1
+        }));
+        assert!(rt.active_batches().is_empty());
+        // Skipped jobs produce no results
+        assert!(rt.drain_worker_results().is_empty());
+    }
+
+    #[test]
+    fn coarse_build_is_followed_by_full_light_refinement() {
+        use geist_world::WorldGenMode;
+        let reg = Arc::new(make_test_registry());
+        let world = Arc::new(World::new(1, 1, 1, 3, WorldGenMode::Flat { thickness: 1 }));
+        let lighting = Arc::new(LightingStore::new(
+            world.chunk_size_x,
+            world.chunk_size_y,
+            world.chunk_size_z,
+        ));
+        let rt = Runtime::new(world, lighting);
+        rt.submit_build_job_edit(BuildJob {
+            cx: 0,
+            cy: 0,
+            cz: 0,
+            neighbors: NeighborsLoaded::default(),
+            rev: 3,
+            job_id: 1,
+            chunk_edits: Vec::new(),
+            region_edits: HashMap::new(),
+            block_entities: Vec::new(),
+            prev_buf: None,
+            reg: reg.clone(),
+            column_profile: None,
+            batch: None,
+            light_quality: LightQuality::Coarse,
+            handle: None,
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut results: Vec<JobOut> = Vec::new();
+        while results.len() < 2 && Instant::now() < deadline {
+            results.extend(rt.drain_worker_results());
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(results.len(), 2);
+        let (coarse, full) = (&results[0], &results[1]);
+        assert_eq!(coarse.light_quality, LightQuality::Coarse);
+        assert!(coarse.cpu.is_some());
+        assert!(coarse.light_borders.is_none());
+        assert!(!coarse.light_grid.as_ref().unwrap().has_micro());
+        assert_eq!(full.light_quality, LightQuality::Full);
+        assert_eq!((full.rev, full.job_id), (3, 1));
+        assert!(full.light_borders.is_some());
+    }
+
+    #[test]
+    fn determinism_check_regenerates_and_reports_first_difference() {
+        use geist_world::WorldGenMode;
+        let reg = Arc::new(make_test_registry());
+        let world = Arc::new(World::new(1, 1, 1, 3, WorldGenMode::Flat { thickness: 1 }));
+        let lighting = Arc::new(LightingStore::new(
+            world.chunk_size_x,
+            world.chunk_size_y,
+            world.chunk_size_z,
+        ));
+        let rt = Runtime::new(world.clone(), lighting);
+        rt.set_gen_determinism_check(true);
+        rt.submit_build_job_edit(BuildJob {
+            cx: 0,
+            cy: 0,
+            cz: 0,
+            neighbors: NeighborsLoaded::default(),
+            rev: 1,
+            job_id: 1,
+            chunk_edits: Vec::new(),
+            region_edits: HashMap::new(),
+            block_entities: Vec::new(),
+            prev_buf: None,
+            reg: reg.clone(),
+            column_profile: None,
+            batch: None,
+            light_quality: LightQuality::Full,
+            handle: None,
+        });
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut results: Vec<JobOut> = Vec::new();
+        while results.is_empty() && Instant::now() < deadline {
+            results.extend(rt.drain_worker_results());
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(results.len(), 1);
+        assert_eq!(rt.gen_determinism_checked(), 1);
+        assert!(rt.drain_gen_divergences().is_empty());
+
+        let coord = ChunkCoord::new(0, 0, 0);
+        let fresh = chunkbuf::generate_chunk_buffer(&world, coord, &reg).buf;
+        let mut pooled = fresh.clone();
+        let idx = pooled.idx(2, 5, 1);
+        let swapped = Block {
+            id: if pooled.blocks[idx].id == 1 { 0 } else { 1 },
+            state: 0,
+        };
+        pooled.blocks[idx] = swapped;
+        let d = first_divergence(&fresh, &pooled, true).expect("divergence");
+        assert_eq!(d.first, (2, 5, 1));
+        assert_eq!(d.pooled, swapped);
+        assert_eq!(d.differing, 1);
+        assert!(first_divergence(&fresh, &fresh, false).is_none());
+    }
+    #[test]
+    fn validation_flags_inconsistent_job_results() {
+        use geist_world::WorldGenMode;
+        let reg = Arc::new(make_test_registry());
+        let world = Arc::new(World::new(1, 1, 1, 3, WorldGenMode::Flat { thickness: 1 }));
+        let lighting = Arc::new(LightingStore::new(
+            world.chunk_size_x,
+            world.chunk_size_y,
+            world.chunk_size_z,
+        ));
+        let rt = Runtime::new(world, lighting);
+        rt.submit_build_job_edit(BuildJob {
+            cx: 0,
+            cy: 0,
+            cz: 0,
+            neighbors: NeighborsLoaded::default(),
+            rev: 1,
+            job_id: 1,
+            chunk_edits: Vec::new(),
+            region_edits: HashMap::new(),
+            block_entities: Vec::new(),
+            prev_buf: None,
+            reg: reg.clone(),
+            column_profile: None,
+            batch: None,
+            light_quality: LightQuality::Full,
+            handle: None,
+        });
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut results: Vec<JobOut> = Vec::new();
+        while results.is_empty() && Instant::now() < deadline {
+            results.extend(rt.drain_worker_results());
+            thread::sleep(Duration::from_millis(1));
+        }
+        let mut out = results.pop().expect("result");
+        assert_eq!(validate_job_out(&out), Vec::new());
+
+        let cpu = out.cpu.as_mut().expect("mesh");
+        let part = cpu
+            .parts
+            .entry(geist_blocks::types::MaterialId(0))
+            .or_default();
+        part.pos.extend([-5.0, 0.0, 0.0]);
+        part.norm.extend([0.0, 1.0, 0.0]);
+        let mut lb = out.light_borders.take().expect("borders");
+        lb.sk_yp = vec![0; lb.sk_yp.len()].into();
+        out.light_borders = Some(lb);
+        out.occupancy = chunkbuf::ChunkOccupancy::Empty;
+        let issues = validate_job_out(&out);
+        assert!(issues.contains(&JobIssue::MeshForEmptyChunk));
+        assert!(issues.contains(&JobIssue::BorderMismatch { plane: "sk_yp" }));
+        assert!(
+            issues
+                .iter()
+                .any(|i| matches!(i, JobIssue::VertexOutside { pos } if pos[0] == -5.0))
+        );
+        assert_eq!(issues.len(), 3);
+    }
+
+    #[test]
+    fn chunk_map_publishes_snapshots_without_disturbing_held_ones() {
+        use chunkbuf::ChunkOccupancy;
+        let map = Arc::new(ChunkMap::new(2, 2, 2));
+        let mut reader = map.reader();
+        assert!(reader.current().is_empty());
+        let before = reader.current().generation();
+
+        let stone = Block { id: 1, state: 0 };
+        let coord = ChunkCoord::new(-1, 0, 0);
+        let buf = chunkbuf::ChunkBuf::from_blocks_local(coord, 2, 2, 2, vec![stone; 8]);
+        map.insert(coord, ChunkOccupancy::Populated, Some(Arc::new(buf)));
+        map.insert(ChunkCoord::new(0, 0, 0), ChunkOccupancy::Empty, None);
+        let held = map.snapshot();
+        assert_eq!(held.len(), 2);
+        assert!(held.generation() > before);
+        assert_eq!(held.block_at(-1, 1, 0), Some(stone));
+        assert_eq!(held.block_at(1, 1, 1), Some(Block::AIR));
+        assert_eq!(held.block_at(0, 2, 0), None);
+        assert_eq!(reader.current().generation(), held.generation());
+        assert!(!map.publish());
+
+        // Clearing also drops staged inserts.
+        map.insert(ChunkCoord::new(0, 1, 0), ChunkOccupancy::Empty, None);
+        map.clear();
+        assert!(map.publish());
+        assert_eq!(held.block_at(-2, 0, 1), Some(stone));
+        assert!(reader.current().is_empty());
+    }
+
+    #[test]
+    fn chunk_cache_serves_stored_chunks_with_their_edits() {
+        use geist_world::WorldGenMode;
+        let reg = Arc::new(make_test_registry());
+        let world = Arc::new(World::new(1, 1, 1, 3, WorldGenMode::Flat { thickness: 1 }));
+        let lighting = Arc::new(LightingStore::new(
+            world.chunk_size_x,
+            world.chunk_size_y,
+            world.chunk_size_z,
+        ));
+        let dims = (world.chunk_size_x, world.chunk_size_y, world.chunk_size_z);
+        let dir = std::env::temp_dir().join(format!("geist-chunk-cache-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let placed = Block { id: 1, state: 0 };
+        let job = |job_id: u64, chunk_edits: Vec<((i32, i32, i32), Block)>| BuildJob {
+            cx: 0,
+            cy: 0,
+            cz: 0,
+            neighbors: NeighborsLoaded::default(),
+            rev: job_id,
+            job_id,
+            chunk_edits,
+            region_edits: HashMap::new(),
+            block_entities: Vec::new(),
+            prev_buf: None,
+            reg: reg.clone(),
+            column_profile: None,
+            batch: None,
+            light_quality: LightQuality::Full,
+            handle: None,
+        };
+        let run = |rt: &Runtime, job: BuildJob| -> JobOut {
+            rt.submit_build_job_edit(job);
+            let deadline = Instant::now() + Duration::from_secs(10);
+            loop {
+                if let Some(out) = rt.drain_worker_results().pop() {
+                    return out;
+                }
+                assert!(Instant::now() < deadline, "build timed out");
+                thread::sleep(Duration::from_millis(1));
+            }
+        };
+
+        let rt = Runtime::new(world.clone(), lighting.clone());
+        let cache = Arc::new(ChunkCache::open(&dir, dims, 7).unwrap());
+        rt.set_chunk_cache(Some(cache.clone()));
+        run(&rt, job(1, vec![((3, 9, 4), placed)]));
+        assert_eq!(cache.stats().misses, 1);
+        // The writer thread may already have written it when the build ran slowly.
+        cache.flush().unwrap();
+        assert_eq!(cache.stats().pending, 0);
+        assert_eq!(cache.stats().written, 1);
+        rt.set_chunk_cache(None);
+        drop(cache);
+
+        // A fresh cache on the same directory loads the edited chunk instead of generating.
+        let cache = Arc::new(ChunkCache::open(&dir, dims, 7).unwrap());
+        rt.set_chunk_cache(Some(cache.clone()));
+        let out = run(&rt, job(2, Vec::new()));
+        assert_eq!(out.buf.unwrap().get_world(3, 9, 4), Some(placed));
+        assert_eq!(cache.stats().hits, 1);
+        assert_eq!(
+            cache.stats().pending,
+            0,
+            "unchanged loads are not written back"
+        );
+
+        // Another key sees nothing.
+        let other = ChunkCache::open(&dir, dims, 8).unwrap();
+        assert!(other.load(ChunkCoord::new(0, 0, 0)).is_none());
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert!(cache.load(ChunkCoord::new(0, 0, 0)).is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn handles_resolve_with_their_own_result() {
+        use geist_world::WorldGenMode;
+        let reg = Arc::new(make_test_registry());
+        let world = Arc::new(World::new(2, 1, 1, 3, WorldGenMode::Flat { thickness: 1 }));
+        let lighting = Arc::new(LightingStore::new(
+            world.chunk_size_x,
+            world.chunk_size_y,
+            world.chunk_size_z,
+        ));
+        let job = |cx: i32| BuildJob {
+            cx,
+            cy: 0,
+            cz: 0,
+            neighbors: NeighborsLoaded::default(),
+            rev: 1,
+            job_id: 40 + cx as u64,
+            chunk_edits: Vec::new(),
+            region_edits: HashMap::new(),
+            block_entities: Vec::new(),
+            prev_buf: None,
+            reg: reg.clone(),
+            column_profile: None,
+            batch: None,
+            light_quality: LightQuality::Full,
+            handle: None,
+        };
+        let mut rt = Runtime::new(world, lighting);
+
+        let (seen_tx, seen_rx) = crossbeam_channel::unbounded();
+        let a = rt.submit_build(job(0), JobKind::Bg);
+        a.on_complete(move |out| {
+            let _ = seen_tx.send((out.cx, out.job_id));
+        });
+        let b = rt.submit_build(job(1), JobKind::Edit);
+        let out = b.wait(Duration::from_secs(10)).expect("edit build");
+        assert_eq!((out.cx, out.job_id), (1, 41));
+        assert!(b.is_finished() && b.poll().is_none());
+
+        assert_eq!(seen_rx.recv_timeout(Duration::from_secs(10)), Ok((0, 40)));
+        assert_eq!(a.wait(Duration::from_secs(10)).map(|o| o.cx), Some(0));
+        // Handle results bypass the shared channel.
+        assert!(rt.drain_worker_results().is_empty());
+
+        rt.shutdown(Duration::from_secs(10));
+        let late = rt.submit_build(job(0), JobKind::Edit);
+        assert!(late.is_cancelled());
+        assert!(late.wait(Duration::from_secs(10)).is_none());
+    }
+
+    #[test]
+    fn light_fairness_keeps_a_share_and_counts_starved_windows() {
+        use crate::fairness::LightFairness;
+        let f = LightFairness::new(Duration::from_millis(20), 0.25);
+        assert!(!f.light_first(false));
+        // Nothing started yet: a waiting light job goes first.
+        assert!(f.light_first(true));
+        f.light_started(true);
+        f.bg_started();
+        f.bg_started();
+        // The next start would leave light with 1 of 4: its share holds.
+        assert!(!f.light_first(true));
+        f.bg_started();
+        f.bg_started();
+        // 1 of 6 would not.
+        assert!(f.light_first(true));
+        assert_eq!(f.boosted(), 1);
+        assert_eq!(f.starved(), 0);
+
+        // A window where light jobs wait and none start counts as starved.
+        thread::sleep(Duration::from_millis(25));
+        assert!(f.light_first(true));
+        f.bg_started();
+        thread::sleep(Duration::from_millis(25));
+        f.bg_started();
+        assert_eq!(f.starved(), 1);
+    }
+
+    #[test]
+    fn bg_lane_builds_chunks_in_view_first_and_refocuses() {
+        let reg = Arc::new(make_test_registry());
+        let job = |cx: i32, cz: i32| BuildJob {
+            cx,
+            cy: 0,
+            cz,
+            neighbors: NeighborsLoaded::default(),
+            rev: 1,
+            job_id: 0,
+            chunk_edits: Vec::new(),
+            region_edits: HashMap::new(),
+            block_entities: Vec::new(),
+            prev_buf: None,
+            reg: reg.clone(),
+            column_profile: None,
+            batch: None,
+            light_quality: LightQuality::Full,
+            handle: None,
+        };
+        let order = |lane: &PriorityLane| {
+            std::iter::from_fn(|| lane.pop().map(|j| (j.cx, j.cz))).collect::<Vec<_>>()
+        };
+
+        // Without a focus the lane is FIFO.
+        let lane = PriorityLane::default();
+        for (cx, cz) in [(-3, 0), (1, 0), (0, 2)] {
+            lane.push(job(cx, cz));
+        }
+        assert_eq!(order(&lane), vec![(-3, 0), (1, 0), (0, 2)]);
+
+        // Looking down +x: ahead before the side, the side before behind, nearer first.
+        let center = ChunkCoord::new(0, 0, 0);
+        lane.set_focus(JobFocus {
+            center,
+            view_dir: [1.0, 0.0, 0.0],
+        });
+        for (cx, cz) in [(-2, 0), (0, 2), (2, 0), (1, 0), (0, 0)] {
+            lane.push(job(cx, cz));
+        }
+        assert_eq!(order(&lane), vec![(0, 0), (1, 0), (2, 0), (0, 2), (-2, 0)]);
+
+        // Turning around reorders jobs already queued.
+        for (cx, cz) in [(-2, 0), (2, 0)] {
+            lane.push(job(cx, cz));
+        }
+        lane.set_focus(JobFocus {
+            center,
+            view_dir: [-1.0, 0.0, 0.0],
+        });
+        assert_eq!(order(&lane), vec![(-2, 0), (2, 0)]);
+        let focus = JobFocus {
+            center: ChunkCoord::new(5, 0, 0),
+            view_dir: [0.0; 3],
+        };
+        assert_eq!(job_priority(&focus, ChunkCoord::new(5, 0, 3)), 3.0 * 1.75);
+    }
+
+    #[test]
+    fn pooled_contexts_are_replaced_after_a_worldgen_reload() {
+        use geist_world::WorldGenMode;
+        use geist_world::worldgen::WorldGenParams;
+        let world = World::new(1, 1, 1, 3, WorldGenMode::Normal);
+        let pool = GenCtxPool::new(1);
+        let first = world.current_worldgen_rev();
+        assert_eq!(pool.acquire(&world).worldgen_rev, first);
+        let mut params = WorldGenParams::default();
+        params.height_frequency *= 2.0;
+        world.update_worldgen_params(params.clone());
+        let ctx = pool.acquire(&world);
+        assert!(ctx.worldgen_rev > first);
+        assert_eq!(ctx.params.height_frequency, params.height_frequency);
+    }
+
+    #[test]
+    fn stream_gate_drops_background_builds_that_left_the_radius() {
+        use crate::handle::JobHandles;
+        use geist_world::WorldGenMode;
+        let reg = Arc::new(make_test_registry());
+        let world = World::new(8, 1, 1, 3, WorldGenMode::Flat { thickness: 1 });
+        let lighting =
+            LightingStore::new(world.chunk_size_x, world.chunk_size_y, world.chunk_size_z);
+        let ctx_pool = GenCtxPool::new(1);
+        let (batches, determinism) = (BatchTracker::default(), DeterminismCheck::default());
+        let chunk_cache: ChunkCacheSlot = RwLock::new(None);
+        let gate = StreamGate::default();
+        let (res_tx, res_rx) = unbounded();
+        let tx = ResultSink::new(res_tx, Arc::new(JobHandles::default()));
+        let run = |cx: i32, lane: Lane| -> JobOut {
+            let job = BuildJob {
+                cx,
+                cy: 0,
+                cz: 0,
+                neighbors: NeighborsLoaded::default(),
+                rev: 1,
+                job_id: cx as u64,
+                chunk_edits: Vec::new(),
+                region_edits: HashMap::new(),
+                block_entities: Vec::new(),
+                prev_buf: None,
+                reg: reg.clone(),
+                column_profile: None,
+                batch: None,
+                light_quality: LightQuality::Full,
+                handle: None,
+            };
+            process_build_job(
+                job,
+                lane,
+                &world,
+                &lighting,
+                &ctx_pool,
+                &batches,
+                &determinism,
+                &chunk_cache,
+                &gate,
+                &tx,
+            );
+            res_rx.try_recv().expect("every build reports")
+        };
+
+        gate.set(ChunkCoord::new(0, 0, 0), 2);
+        let near = run(1, Lane::Bg);
+        assert!(!near.dropped && near.cpu.is_some());
+        let far = run(5, Lane::Bg);
+        assert!(far.dropped);
+        assert!(far.cpu.is_none() && far.buf.is_none() && far.light_grid.is_none());
+        assert_eq!((far.cx, far.rev, far.job_id), (5, 1, 5));
+        // Edits always finish.
+        assert!(!run(5, Lane::Edit).dropped);
+        assert_eq!(gate.dropped_on_worker(), 1);
+
+        // A result that finished in range is stripped once the center moves away.
+        let mut late = run(2, Lane::Bg);
+        assert!(late.cpu.is_some());
+        gate.revalidate(&mut late);
+        assert!(!late.dropped);
+        gate.set(ChunkCoord::new(6, 0, 0), 2);
+        gate.revalidate(&mut late);
+        assert!(late.dropped && late.cpu.is_none() && late.buf.is_none());
+        assert_eq!(gate.dropped_on_drain(), 1);
+
+        gate.clear();
+        assert!(!run(7, Lane::Bg).dropped);
+    }
+
+    #[test]
+    fn fluid_spreads_falls_and_drains() {
+        let reg = make_test_registry();
+        let stone = Block {
+            id: reg.id_by_name("stone").unwrap(),
+            state: 0,
+        };
+        let water = reg.get(reg.id_by_name("water").unwrap()).unwrap();
+        let level_at = |blocks: &HashMap<(i32, i32, i32), Block>, p: (i32, i32, i32)| {
+            blocks
+                .get(&p)
+                .and_then(|b| reg.get(b.id)?.fluid_level(b.state))
+        };
+        let run = |blocks: &mut HashMap<(i32, i32, i32), Block>, sim: &mut FluidSim| {
+            let mut ticks = 0;
+            while !sim.is_idle() {
+                ticks += 1;
+                assert!(ticks < 100, "fluid never settled");
+                let changes = sim.tick(&reg, 1024, |x, y, z| {
+                    if y < 0 {
+                        return stone;
+                    }
+                    blocks.get(&(x, y, z)).copied().unwrap_or(Block::AIR)
+                });
+                for c in changes {
+                    blocks.insert(c.pos, c.block);
+                }
+            }
+        };
+
+        // A source on a stone floor (y < 0) spreads one level per block.
+        let mut blocks = HashMap::new();
+        let mut sim = FluidSim::new(8, 8, 8);
+        let source = (0, 0, 0);
+        blocks.insert(
+            source,
+            Block {
+                id: water.id,
+                state: water.fluid_state(0),
+            },
+        );
+        sim.activate(source);
+        run(&mut blocks, &mut sim);
+        assert_eq!(level_at(&blocks, (0, 0, 0)), Some(0));
+        assert_eq!(level_at(&blocks, (3, 0, 0)), Some(3));
+        assert_eq!(level_at(&blocks, (-2, 0, -2)), Some(4));
+        assert_eq!(level_at(&blocks, (7, 0, 0)), Some(7));
+        assert_eq!(level_at(&blocks, (8, 0, 0)), None);
+
+        // Flow over a ledge falls before it spreads.
+        blocks.insert(
+            (0, 3, 0),
+            Block {
+                id: water.id,
+                state: water.fluid_state(0),
+            },
+        );
+        blocks.insert((0, 2, 0), stone);
+        blocks.insert((1, 2, 0), stone);
+        sim.activate((0, 3, 0));
+        run(&mut blocks, &mut sim);
+        assert_eq!(level_at(&blocks, (1, 3, 0)), Some(1));
+        assert_eq!(level_at(&blocks, (2, 3, 0)), Some(2));
+        assert_eq!(level_at(&blocks, (2, 2, 0)), Some(1));
+        assert_eq!(level_at(&blocks, (2, 1, 0)), Some(1));
+        assert_eq!(level_at(&blocks, (3, 3, 0)), None);
+
+        // Removing the sources drains every flowing block back to air.
+        for p in [source, (0, 3, 0)] {
+            blocks.insert(p, Block::AIR);
+            sim.activate(p);
+        }
+        run(&mut blocks, &mut sim);
+        assert!(
+            blocks.iter().all(|(_, b)| b.id != water.id),
+            "flow left without a source"
+        );
+    }
+
+    #[test]
+    fn block_ticks_run_on_their_lane_in_due_order() {
+        use geist_world::WorldGenMode;
+        use hashbrown::HashSet;
+        let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let vox = root.join("../../assets/voxels");
+        let reg = Arc::new(
+            BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml"))
+                .unwrap(),
+        );
+        let block = |name: &str| Block {
+            id: reg.id_by_name(name).unwrap(),
+            state: 0,
+        };
+        let (grass, dirt, leaves, log) = (
+            block("grass"),
+            block("dirt"),
+            block("oak_leaves"),
+            block("oak_log"),
+        );
+
+        let mut sched = BlockTickScheduler::new();
+        sched.schedule((0, 0, 0), 5);
+        sched.schedule((1, 0, 0), 3);
+        sched.schedule((0, 0, 0), 9);
+        sched.schedule((2, 0, 0), 4);
+        sched.cancel((2, 0, 0));
+        assert_eq!(sched.len(), 2);
+        assert_eq!(sched.next_due(), Some(3));
+        assert!(sched.pop_due(2, 8).is_empty());
+        assert_eq!(sched.pop_due(5, 8), vec![(1, 0, 0), (0, 0, 0)]);
+        assert!(sched.is_empty());
+
+        let mut handlers = BlockTickHandlers::new(reg.clone());
+        assert!(
+            handlers
+                .register("no_such_block", 1, |_| BlockTickOutcome::idle())
+                .is_err()
+        );
+        let handlers = Arc::new(builtin_block_ticks(reg.clone()));
+        assert!(handlers.delay_for(grass).is_some() && handlers.delay_for(leaves).is_some());
+        assert_eq!(handlers.delay_for(log), None);
+
+        // A grass block in a dirt field, a leaf next to a log, and a lone leaf in the air.
+        let mut edits = HashMap::new();
+        for x in -1..=1 {
+            for z in -1..=1 {
+                edits.insert((x, 40, z), if (x, z) == (0, 0) { grass } else { dirt });
+            }
+        }
+        edits.insert((10, 45, 0), log);
+        edits.insert((11, 45, 0), leaves);
+        edits.insert((30, 45, 0), leaves);
+        let world = Arc::new(World::new(1, 1, 1, 3, WorldGenMode::Flat { thickness: 1 }));
+        let lighting = Arc::new(LightingStore::new(
+            world.chunk_size_x,
+            world.chunk_size_y,
+            world.chunk_size_z,
+        ));
+        let rt = Runtime::new(world, lighting);
+        let mut spread = HashSet::new();
+        let mut decayed = false;
+        for tick in 1..=64u64 {
+            rt.submit_block_tick_job(BlockTickJob {
+                tick,
+                cells: vec![
+                    ((0, 40, 0), grass),
+                    ((11, 45, 0), leaves),
+                    ((30, 45, 0), leaves),
+                ],
+                edits: edits.clone(),
+                handlers: handlers.clone(),
+            });
+        }
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut outs = Vec::new();
+        while outs.len() < 64 && Instant::now() < deadline {
+            outs.extend(rt.drain_block_tick_results());
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(
+            outs.iter().map(|o| o.tick).collect::<Vec<_>>(),
+            (1..=64).collect::<Vec<_>>()
+        );
+        for out in &outs {
+            for c in &out.changes {
+                match c.pos {
+                    (30, 45, 0) => {
+                        assert_eq!((c.from, c.to), (leaves, Block::AIR));
+                        decayed = true;
+                    }
+                    pos => {
+                        assert_eq!((c.from, c.to), (dirt, grass), "at {:?}", pos);
+                        spread.insert(pos);
+                    }
+                }
+            }
+            assert!(out.again.iter().all(|&(_, at)| at > out.tick));
+        }
+        assert!(decayed, "a leaf with no log in reach decays");
+        assert_eq!(spread.len(), 4, "grass reaches every side in 64 tries");
+        let counts = rt.queue_debug_counts();
+        assert_eq!((counts.queued_tick, counts.inflight_tick), (0, 0));
+    }
 }
@@ -0,0 +1,122 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::Mutex;
+
+use geist_world::ChunkCoord;
+
+use crate::BuildJob;
+
+/// How much more a chunk straight behind the camera waits than one straight ahead at the
+/// same distance: its score is `1 + BEHIND_WEIGHT` times as large.
+const BEHIND_WEIGHT: f32 = 1.5;
+/// View directions closer than this (cosine) don't reorder the queue.
+const REFOCUS_COS: f32 = 0.995;
+
+/// Where the camera is and looks, for ordering background builds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JobFocus {
+    pub center: ChunkCoord,
+    /// Unit view direction; zero disables the frustum term.
+    pub view_dir: [f32; 3],
+}
+
+/// Ordering key of a chunk: distance from the focus chunk, stretched for chunks away from
+/// the view direction. Lower builds first.
+pub fn job_priority(focus: &JobFocus, coord: ChunkCoord) -> f32 {
+    let d = [
+        (coord.cx - focus.center.cx) as f32,
+        (coord.cy - focus.center.cy) as f32,
+        (coord.cz - focus.center.cz) as f32,
+    ];
+    let dist = (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt();
+    if dist == 0.0 {
+        return 0.0;
+    }
+    let v = focus.view_dir;
+    let cos = (d[0] * v[0] + d[1] * v[1] + d[2] * v[2]) / dist;
+    dist * (1.0 + BEHIND_WEIGHT * (1.0 - cos.clamp(-1.0, 1.0)) * 0.5)
+}
+
+struct Pending {
+    score: f32,
+    seq: u64,
+    job: BuildJob,
+}
+
+impl PartialEq for Pending {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Pending {}
+
+impl PartialOrd for Pending {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Pending {
+    // `BinaryHeap` pops the greatest: lowest score first, then oldest.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .score
+            .total_cmp(&self.score)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+#[derive(Default)]
+struct LaneInner {
+    focus: Option<JobFocus>,
+    heap: BinaryHeap<Pending>,
+    next_seq: u64,
+}
+
+impl LaneInner {
+    fn score(&self, job: &BuildJob) -> f32 {
+        self.focus.as_ref().map_or(0.0, |f| {
+            job_priority(f, ChunkCoord::new(job.cx, job.cy, job.cz))
+        })
+    }
+}
+
+/// Background jobs ordered by [`job_priority`]; FIFO until a focus is set. Workers are
+/// woken through the lane's channel, which carries one token per queued job.
+#[derive(Default)]
+pub(crate) struct PriorityLane {
+    inner: Mutex<LaneInner>,
+}
+
+impl PriorityLane {
+    pub(crate) fn push(&self, job: BuildJob) {
+        let mut inner = self.inner.lock().unwrap();
+        let score = inner.score(&job);
+        let seq = inner.next_seq;
+        inner.next_seq += 1;
+        inner.heap.push(Pending { score, seq, job });
+    }
+
+    pub(crate) fn pop(&self) -> Option<BuildJob> {
+        self.inner.lock().unwrap().heap.pop().map(|p| p.job)
+    }
+
+    /// Rescore every queued job when the focus chunk changed or the view turned.
+    pub(crate) fn set_focus(&self, focus: JobFocus) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(old) = inner.focus {
+            let [a, b] = [old.view_dir, focus.view_dir];
+            let cos = a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+            if old == focus || (old.center == focus.center && cos >= REFOCUS_COS) {
+                return;
+            }
+        }
+        inner.focus = Some(focus);
+        let mut pending = std::mem::take(&mut inner.heap).into_vec();
+        for p in &mut pending {
+            p.score = inner.score(&p.job);
+        }
+        inner.heap = BinaryHeap::from(pending);
+    }
+}
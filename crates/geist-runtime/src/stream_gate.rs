@@ -0,0 +1,65 @@
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use geist_world::ChunkCoord;
+
+use crate::{JobKind, JobOut};
+
+/// The streaming view center and keep radius shared with background workers, so builds
+/// for chunks the view has left stop before lighting and meshing instead of finishing
+/// only to be thrown away on the main thread.
+#[derive(Default)]
+pub(crate) struct StreamGate {
+    // Center and squared keep radius; `None` admits every chunk.
+    keep: RwLock<Option<(ChunkCoord, i64)>>,
+    dropped_on_worker: AtomicU64,
+    dropped_on_drain: AtomicU64,
+}
+
+impl StreamGate {
+    pub(crate) fn set(&self, center: ChunkCoord, keep_radius: i32) {
+        let r = i64::from(keep_radius.max(0));
+        let next = Some((center, r * r));
+        if *self.keep.read().unwrap() != next {
+            *self.keep.write().unwrap() = next;
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        *self.keep.write().unwrap() = None;
+    }
+
+    /// Whether `coord` is within the keep radius of the current center.
+    pub(crate) fn admits(&self, coord: ChunkCoord) -> bool {
+        match *self.keep.read().unwrap() {
+            Some((center, keep_sq)) => center.distance_sq(coord) <= keep_sq,
+            None => true,
+        }
+    }
+
+    /// Strip a finished background build whose chunk fell out of range after it left
+    /// the worker, so the main thread never uploads it.
+    pub(crate) fn revalidate(&self, out: &mut JobOut) {
+        if matches!(out.kind, JobKind::Bg)
+            && !out.dropped
+            && !self.admits(ChunkCoord::new(out.cx, out.cy, out.cz))
+        {
+            self.dropped_on_drain.fetch_add(1, Ordering::Relaxed);
+            out.drop_payload();
+        }
+    }
+
+    pub(crate) fn note_worker_drop(&self) {
+        self.dropped_on_worker.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Builds dropped by workers before lighting and meshing.
+    pub(crate) fn dropped_on_worker(&self) -> u64 {
+        self.dropped_on_worker.load(Ordering::Relaxed)
+    }
+
+    /// Finished builds dropped while draining, before the main thread applied them.
+    pub(crate) fn dropped_on_drain(&self) -> u64 {
+        self.dropped_on_drain.load(Ordering::Relaxed)
+    }
+}
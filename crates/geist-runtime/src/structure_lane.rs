@@ -0,0 +1,42 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use hashbrown::HashMap;
+
+use crate::StructureBuildJob;
+
+/// Keeps builds of one structure in submission order while different structures build in
+/// parallel. A worker that claims a structure also runs the jobs queued behind it, so each
+/// result carries every earlier rebuild and a stale light grid never lands last.
+#[derive(Default)]
+pub(crate) struct StructureLane {
+    // Structures being built, with the jobs waiting behind the running one.
+    busy: Mutex<HashMap<u32, VecDeque<StructureBuildJob>>>,
+}
+
+impl StructureLane {
+    /// Hand back `job` to run now, or park it behind the build of the same structure.
+    pub(crate) fn claim(&self, job: StructureBuildJob) -> Option<StructureBuildJob> {
+        let mut busy = self.busy.lock().unwrap();
+        match busy.get_mut(&job.id) {
+            Some(waiting) => {
+                waiting.push_back(job);
+                None
+            }
+            None => {
+                busy.insert(job.id, VecDeque::new());
+                Some(job)
+            }
+        }
+    }
+
+    /// The next parked job of structure `id`, releasing the structure when there is none.
+    pub(crate) fn finish(&self, id: u32) -> Option<StructureBuildJob> {
+        let mut busy = self.busy.lock().unwrap();
+        let next = busy.get_mut(&id).and_then(|waiting| waiting.pop_front());
+        if next.is_none() {
+            busy.remove(&id);
+        }
+        next
+    }
+}
@@ -0,0 +1,141 @@
+//! Consistency checks on finished build jobs, run on every drained result in debug builds.
+//!
+//! A mesh poking out of its chunk, seam planes that disagree with the grid they were cut
+//! from, or geometry for a chunk marked empty all end up as rendering artifacts far from
+//! the worker that produced them; checking here fails at the job instead.
+
+use std::fmt;
+
+use geist_lighting::LightBorders;
+use geist_world::ChunkCoord;
+
+use crate::JobOut;
+
+// Slack for float rounding in vertex positions.
+const BOUNDS_EPS: f32 = 1e-3;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum JobIssue {
+    /// The mesh was built for a different chunk than the job.
+    MeshCoord { mesh: ChunkCoord },
+    /// The mesh's bounding box is not the chunk's.
+    MeshBounds,
+    /// A vertex lies outside the chunk.
+    VertexOutside { pos: [f32; 3] },
+    /// A triangle index points past the vertex data, or the arrays disagree in length.
+    MalformedPart { material: u16 },
+    /// A published border plane differs from the edge of the light grid.
+    BorderMismatch { plane: &'static str },
+    /// The chunk is marked empty but carries geometry.
+    MeshForEmptyChunk,
+}
+
+impl fmt::Display for JobIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MeshCoord { mesh } => {
+                write!(f, "mesh built for ({}, {}, {})", mesh.cx, mesh.cy, mesh.cz)
+            }
+            Self::MeshBounds => write!(f, "mesh bbox is not the chunk bounds"),
+            Self::VertexOutside { pos } => {
+                write!(
+                    f,
+                    "vertex ({}, {}, {}) outside chunk",
+                    pos[0], pos[1], pos[2]
+                )
+            }
+            Self::MalformedPart { material } => {
+                write!(f, "material {} has mismatched vertex/index data", material)
+            }
+            Self::BorderMismatch { plane } => {
+                write!(f, "border plane {} differs from the light grid", plane)
+            }
+            Self::MeshForEmptyChunk => write!(f, "geometry for a chunk marked empty"),
+        }
+    }
+}
+
+/// Every inconsistency in `out`; empty when the result is sound.
+pub fn validate_job_out(out: &JobOut) -> Vec<JobIssue> {
+    let mut issues = Vec::new();
+    let coord = ChunkCoord::new(out.cx, out.cy, out.cz);
+    if let Some(cpu) = out.cpu.as_ref() {
+        if cpu.coord != coord {
+            issues.push(JobIssue::MeshCoord { mesh: cpu.coord });
+        }
+        if out.occupancy.is_empty() && !cpu.parts.is_empty() {
+            issues.push(JobIssue::MeshForEmptyChunk);
+        }
+        if let Some(buf) = out.buf.as_ref() {
+            let min = [
+                (out.cx * buf.sx as i32) as f32,
+                (out.cy * buf.sy as i32) as f32,
+                (out.cz * buf.sz as i32) as f32,
+            ];
+            let max = [
+                min[0] + buf.sx as f32,
+                min[1] + buf.sy as f32,
+                min[2] + buf.sz as f32,
+            ];
+            let b = &cpu.bbox;
+            if [b.min.x, b.min.y, b.min.z] != min || [b.max.x, b.max.y, b.max.z] != max {
+                issues.push(JobIssue::MeshBounds);
+            }
+            let mut mats: Vec<_> = cpu.parts.keys().copied().collect();
+            mats.sort_by_key(|m| m.0);
+            for mat in mats {
+                let mb = &cpu.parts[&mat];
+                let verts = mb.pos.len() / 3;
+                if mb.pos.len() % 3 != 0
+                    || mb.norm.len() != mb.pos.len()
+                    || mb.idx.iter().any(|&i| i as usize >= verts)
+                {
+                    issues.push(JobIssue::MalformedPart { material: mat.0 });
+                }
+                if let Some(p) = mb.pos.chunks_exact(3).find(|p| {
+                    (0..3).any(|a| p[a] < min[a] - BOUNDS_EPS || p[a] > max[a] + BOUNDS_EPS)
+                }) {
+                    issues.push(JobIssue::VertexOutside {
+                        pos: [p[0], p[1], p[2]],
+                    });
+                }
+            }
+        }
+    }
+    if let (Some(lb), Some(lg)) = (out.light_borders.as_ref(), out.light_grid.as_ref()) {
+        let expect = LightBorders::from_grid(lg);
+        for (plane, got, want) in border_planes(lb, &expect) {
+            if got != want {
+                issues.push(JobIssue::BorderMismatch { plane });
+            }
+        }
+    }
+    issues
+}
+
+fn border_planes<'a>(
+    a: &'a LightBorders,
+    b: &'a LightBorders,
+) -> [(&'static str, &'a [u8], &'a [u8]); 18] {
+    [
+        ("xn", &a.xn, &b.xn),
+        ("xp", &a.xp, &b.xp),
+        ("zn", &a.zn, &b.zn),
+        ("zp", &a.zp, &b.zp),
+        ("yn", &a.yn, &b.yn),
+        ("yp", &a.yp, &b.yp),
+        ("sk_xn", &a.sk_xn, &b.sk_xn),
+        ("sk_xp", &a.sk_xp, &b.sk_xp),
+        ("sk_zn", &a.sk_zn, &b.sk_zn),
+        ("sk_zp", &a.sk_zp, &b.sk_zp),
+        ("sk_yn", &a.sk_yn, &b.sk_yn),
+        ("sk_yp", &a.sk_yp, &b.sk_yp),
+        ("bcn_xn", &a.bcn_xn, &b.bcn_xn),
+        ("bcn_xp", &a.bcn_xp, &b.bcn_xp),
+        ("bcn_zn", &a.bcn_zn, &b.bcn_zn),
+        ("bcn_zp", &a.bcn_zp, &b.bcn_zp),
+        ("bcn_yn", &a.bcn_yn, &b.bcn_yn),
+        ("bcn_yp", &a.bcn_yp, &b.bcn_yp),
+    ]
+    .map(|(name, x, y)| (name, x.as_ref(), y.as_ref()))
+}
@@ -0,0 +1,297 @@
+//! Headless streaming + lighting convergence over a scripted flight path.
+//!
+//! Drives the runtime the way the app does, minus the renderer: stream in every chunk
+//! around the camera on the background lane, store each result's light borders, and
+//! rebuild loaded neighbours on the light lane whenever a border they read changed.
+//! Guards the scheduler and lighting against rebuild loops and borders that never settle.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use geist_blocks::BlockRegistry;
+use geist_chunk::ChunkBuf;
+use geist_lighting::{BorderChangeMask, LightQuality, LightingStore};
+use geist_mesh_cpu::NeighborsLoaded;
+use geist_runtime::{BuildJob, JobOut, Runtime};
+use geist_world::{ChunkCoord, World, WorldGenMode};
+use hashbrown::{HashMap, HashSet};
+
+const SEED: i32 = 1337;
+const RADIUS: i32 = 1;
+/// Builds of one chunk while the camera holds still: its stream load plus a light rebuild
+/// per neighbour border update. More than this is a rebuild loop.
+const MAX_BUILDS_PER_CHUNK: u32 = 8;
+const MAX_POLLS: u32 = 200_000;
+const TIMEOUT: Duration = Duration::from_secs(600);
+
+fn load_registry() -> BlockRegistry {
+    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let vox = root.join("../../assets/voxels");
+    BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml")).unwrap()
+}
+
+#[derive(Debug, Default)]
+struct ConvergenceStats {
+    chunks: usize,
+    stream_builds: u32,
+    light_rebuilds: u32,
+    border_updates: u32,
+    max_builds_per_chunk: u32,
+    polls: u32,
+    elapsed: Duration,
+}
+
+struct Flight {
+    rt: Runtime,
+    lighting: Arc<LightingStore>,
+    reg: Arc<BlockRegistry>,
+    chunks_y: i32,
+    center: ChunkCoord,
+    built: HashSet<ChunkCoord>,
+    bufs: HashMap<ChunkCoord, ChunkBuf>,
+    inflight: HashSet<ChunkCoord>,
+    // Border-dirty chunks that were in flight; rebuilt again once their job lands.
+    pending: HashSet<ChunkCoord>,
+    builds: HashMap<ChunkCoord, u32>,
+    next_job: u64,
+}
+
+impl Flight {
+    fn new(mode: WorldGenMode, chunks_y: i32) -> Self {
+        let world = Arc::new(World::new(4, chunks_y as usize, 4, SEED, mode));
+        let lighting = Arc::new(LightingStore::new(
+            world.chunk_size_x,
+            world.chunk_size_y,
+            world.chunk_size_z,
+        ));
+        Self {
+            rt: Runtime::new(world, lighting.clone()),
+            lighting,
+            reg: Arc::new(load_registry()),
+            chunks_y,
+            center: ChunkCoord::new(0, 0, 0),
+            built: HashSet::new(),
+            bufs: HashMap::new(),
+            inflight: HashSet::new(),
+            pending: HashSet::new(),
+            builds: HashMap::new(),
+            next_job: 1,
+        }
+    }
+
+    fn in_view(&self, c: ChunkCoord) -> bool {
+        (c.cx - self.center.cx).abs() <= RADIUS
+            && (c.cz - self.center.cz).abs() <= RADIUS
+            && (0..self.chunks_y).contains(&c.cy)
+    }
+
+    fn view(&self) -> Vec<ChunkCoord> {
+        let mut out = Vec::new();
+        for cx in self.center.cx - RADIUS..=self.center.cx + RADIUS {
+            for cz in self.center.cz - RADIUS..=self.center.cz + RADIUS {
+                for cy in (0..self.chunks_y).rev() {
+                    out.push(ChunkCoord::new(cx, cy, cz));
+                }
+            }
+        }
+        out
+    }
+
+    fn submit(&mut self, coord: ChunkCoord, light: bool) {
+        let loaded = |dx, dy, dz| self.built.contains(&coord.offset(dx, dy, dz));
+        let job = BuildJob {
+            cx: coord.cx,
+            cy: coord.cy,
+            cz: coord.cz,
+            neighbors: NeighborsLoaded {
+                neg_x: loaded(-1, 0, 0),
+                pos_x: loaded(1, 0, 0),
+                neg_y: loaded(0, -1, 0),
+                pos_y: loaded(0, 1, 0),
+                neg_z: loaded(0, 0, -1),
+                pos_z: loaded(0, 0, 1),
+            },
+            rev: 0,
+            job_id: self.next_job,
+            chunk_edits: Vec::new(),
+            region_edits: HashMap::new(),
+            block_entities: Vec::new(),
+            prev_buf: self.bufs.get(&coord).cloned(),
+            reg: self.reg.clone(),
+            column_profile: None,
+            batch: None,
+            light_quality: LightQuality::Full,
+            handle: None,
+        };
+        self.next_job += 1;
+        self.inflight.insert(coord);
+        *self.builds.entry(coord).or_insert(0) += 1;
+        if light {
+            self.rt.submit_build_job_light(job);
+        } else {
+            self.rt.submit_build_job_bg(job);
+        }
+    }
+
+    /// Apply one result; returns the faces whose borders changed.
+    fn apply(&mut self, out: JobOut) -> BorderChangeMask {
+        let coord = ChunkCoord::new(out.cx, out.cy, out.cz);
+        self.inflight.remove(&coord);
+        assert!(
+            !out.dropped,
+            "no stream gate is set, nothing may be dropped"
+        );
+        self.built.insert(coord);
+        if let Some(buf) = out.buf {
+            self.bufs.insert(coord, buf);
+        }
+        if let Some(lg) = out.light_grid.as_ref() {
+            self.lighting.update_levels(coord, lg);
+        }
+        let mut mask = BorderChangeMask::default();
+        if let Some(lb) = out.light_borders {
+            let (changed, m) = self.lighting.update_borders_mask(coord, lb);
+            if changed {
+                mask = m;
+            }
+        }
+        if let Some(lg) = out.light_grid.as_ref() {
+            mask.or_with(&lg.micro_change);
+        }
+        mask
+    }
+
+    /// Fly to `center` and pump results until every chunk in view is built and no border
+    /// update is left to propagate.
+    fn fly_to(&mut self, center: ChunkCoord) -> ConvergenceStats {
+        let start = Instant::now();
+        self.center = center;
+        self.builds.clear();
+        self.rt.update_focus(center, [1.0, 0.0, 0.0]);
+        let view = self.view();
+        let mut stats = ConvergenceStats {
+            chunks: view.len(),
+            ..Default::default()
+        };
+        for &coord in &view {
+            if !self.built.contains(&coord) && !self.inflight.contains(&coord) {
+                self.submit(coord, false);
+                stats.stream_builds += 1;
+            }
+        }
+        while !self.inflight.is_empty() {
+            stats.polls += 1;
+            assert!(
+                stats.polls <= MAX_POLLS && start.elapsed() < TIMEOUT,
+                "no convergence at {:?}: {} in flight, {:?}",
+                center,
+                self.inflight.len(),
+                stats
+            );
+            let results = self.rt.drain_worker_results();
+            if results.is_empty() {
+                std::thread::sleep(Duration::from_millis(2));
+                continue;
+            }
+            for out in results {
+                let coord = ChunkCoord::new(out.cx, out.cy, out.cz);
+                let mask = self.apply(out);
+                if mask.any() {
+                    stats.border_updates += 1;
+                }
+                let faces = [
+                    (mask.xn, (-1, 0, 0)),
+                    (mask.xp, (1, 0, 0)),
+                    (mask.yn, (0, -1, 0)),
+                    (mask.yp, (0, 1, 0)),
+                    (mask.zn, (0, 0, -1)),
+                    (mask.zp, (0, 0, 1)),
+                ];
+                for (changed, (dx, dy, dz)) in faces {
+                    let nb = coord.offset(dx, dy, dz);
+                    if changed && self.built.contains(&nb) && self.in_view(nb) {
+                        self.pending.insert(nb);
+                    }
+                }
+            }
+            let ready: Vec<ChunkCoord> = self
+                .pending
+                .iter()
+                .copied()
+                .filter(|c| !self.inflight.contains(c))
+                .collect();
+            for coord in ready {
+                self.pending.remove(&coord);
+                self.submit(coord, true);
+                stats.light_rebuilds += 1;
+            }
+        }
+        for coord in &view {
+            assert!(self.built.contains(coord), "{:?} never built", coord);
+        }
+        stats.max_builds_per_chunk = self.builds.values().copied().max().unwrap_or(0);
+        stats.elapsed = start.elapsed();
+        stats
+    }
+
+    /// Fly along `path`, checking each stop converges without a rebuild loop, then check
+    /// the settled borders are a fixed point.
+    fn fly_path(&mut self, path: &[ChunkCoord]) {
+        for &waypoint in path {
+            let stats = self.fly_to(waypoint);
+            assert_eq!(
+                stats.chunks,
+                ((2 * RADIUS + 1) * (2 * RADIUS + 1) * self.chunks_y) as usize
+            );
+            assert!(
+                stats.max_builds_per_chunk <= MAX_BUILDS_PER_CHUNK,
+                "oscillating rebuilds at {:?}: {:?}",
+                waypoint,
+                stats
+            );
+        }
+
+        // Settled borders are a fixed point: rebuilding everything in view changes none.
+        let view = self.view();
+        for &coord in &view {
+            self.submit(coord, true);
+        }
+        let start = Instant::now();
+        while !self.inflight.is_empty() {
+            assert!(start.elapsed() < TIMEOUT, "verification pass stalled");
+            let results = self.rt.drain_worker_results();
+            if results.is_empty() {
+                std::thread::sleep(Duration::from_millis(2));
+            }
+            for out in results {
+                let coord = ChunkCoord::new(out.cx, out.cy, out.cz);
+                let mask = self.apply(out);
+                assert!(
+                    !mask.any(),
+                    "borders of {:?} still moving: {:?}",
+                    coord,
+                    mask
+                );
+            }
+        }
+    }
+}
+
+/// Small enough for the default run: one stop on a flat world, one chunk layer deep.
+#[test]
+fn streaming_and_lighting_converge_after_one_hop() {
+    Flight::new(WorldGenMode::Flat { thickness: 40 }, 1).fly_path(&[ChunkCoord::new(0, 0, 0)]);
+}
+
+/// Generates and lights every chunk along a longer path, which takes minutes in a debug
+/// build. Run it with
+/// `cargo test --release -p geist-runtime --test streaming_convergence -- --ignored`.
+#[test]
+#[ignore = "slow: run with --release -- --ignored"]
+fn streaming_and_lighting_converge_along_a_flight_path() {
+    Flight::new(WorldGenMode::Normal, 2).fly_path(&[
+        ChunkCoord::new(0, 0, 0),
+        ChunkCoord::new(1, 0, 0),
+        ChunkCoord::new(2, 0, 1),
+    ]);
+}
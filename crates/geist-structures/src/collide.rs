@@ -0,0 +1,372 @@
+//! Box collision against posed structures.
+//!
+//! A world-space AABB seen from a structure is an oriented box in its local grid (rotated by
+//! the pose, scaled by `1 / scale`), while the structure's cells stay unit cubes. Queries
+//! map the box into local space once and test it against each solid cell with the separating
+//! axis theorem: the three cell axes, the box's three axes and their nine cross products.
+//! Sweeps solve, per axis, when the moving projections overlap; the latest entry across all
+//! axes is the time of impact, exact for any yaw, pitch and roll.
+
+use geist_blocks::{BlockRegistry, types::Block};
+use geist_geom::{Aabb, Vec3};
+
+use crate::{Structure, StructureId};
+
+// Axes shorter than this (parallel edge pairs) separate nothing and are skipped.
+const AXIS_EPS: f32 = 1e-6;
+// Overlap below this depth counts as touching, so resting contact is not a collision.
+const TOUCH_EPS: f32 = 1e-4;
+
+/// First contact of a swept box with a structure.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SweepHit {
+    /// Fraction of the sweep travelled before contact, in `0..=1`.
+    pub toi: f32,
+    /// World-space unit normal of the touched face, pointing back against the motion.
+    pub normal: Vec3,
+    /// Local cell that was hit.
+    pub cell: (i32, i32, i32),
+}
+
+/// A world AABB as an oriented box in a structure's local grid.
+struct LocalBox {
+    center: Vec3,
+    // Local directions of the world X, Y and Z axes, scaled by the half extents.
+    half_axes: [Vec3; 3],
+}
+
+impl LocalBox {
+    fn new(st: &Structure, aabb: &Aabb) -> Self {
+        let center = (aabb.min + aabb.max) * 0.5;
+        let half = (aabb.max - aabb.min) * 0.5;
+        Self {
+            center: st.pose.world_to_local(center),
+            half_axes: [
+                st.pose.vector_to_local(Vec3::new(half.x, 0.0, 0.0)),
+                st.pose.vector_to_local(Vec3::new(0.0, half.y, 0.0)),
+                st.pose.vector_to_local(Vec3::new(0.0, 0.0, half.z)),
+            ],
+        }
+    }
+
+    fn radius(&self, axis: Vec3) -> f32 {
+        self.half_axes.iter().map(|h| h.dot(axis).abs()).sum()
+    }
+
+    /// Local cells the box touches anywhere along `delta`, clamped to the structure.
+    fn cell_range(&self, st: &Structure, delta: Vec3) -> Option<[(i32, i32); 3]> {
+        let ext = [0, 1, 2].map(|i| {
+            self.half_axes
+                .iter()
+                .map(|h| [h.x, h.y, h.z][i].abs())
+                .sum::<f32>()
+        });
+        let (c, d) = (
+            [self.center.x, self.center.y, self.center.z],
+            [delta.x, delta.y, delta.z],
+        );
+        let dims = [st.sx as i32, st.sy as i32, st.sz as i32];
+        let mut out = [(0, 0); 3];
+        for i in 0..3 {
+            let lo = (c[i] + d[i].min(0.0) - ext[i]).floor() as i32;
+            let hi = (c[i] + d[i].max(0.0) + ext[i]).floor() as i32;
+            let (lo, hi) = (lo.max(0), hi.min(dims[i] - 1));
+            if lo > hi {
+                return None;
+            }
+            out[i] = (lo, hi);
+        }
+        Some(out)
+    }
+
+    fn axes(&self) -> Vec<Vec3> {
+        let cell = [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        ];
+        let mut axes = cell.to_vec();
+        for h in &self.half_axes {
+            axes.push(h.normalized());
+        }
+        for c in cell {
+            for h in &self.half_axes {
+                let a = c.cross(h.normalized());
+                if a.length() > AXIS_EPS {
+                    axes.push(a.normalized());
+                }
+            }
+        }
+        axes
+    }
+}
+
+fn blocks_motion(reg: &BlockRegistry, b: Block) -> bool {
+    reg.get(b.id)
+        .is_some_and(|ty| ty.is_solid(b.state) && ty.fluid_level(b.state).is_none())
+}
+
+impl Structure {
+    /// Whether the local cell holds a block boxes collide with (solid, not fluid).
+    pub fn cell_collides(&self, reg: &BlockRegistry, lx: i32, ly: i32, lz: i32) -> bool {
+        self.block_local(lx, ly, lz)
+            .is_some_and(|b| blocks_motion(reg, b))
+    }
+
+    /// Whether the world-space box overlaps a solid cell of the structure by more than a
+    /// touch.
+    pub fn overlaps_aabb(&self, reg: &BlockRegistry, aabb: &Aabb) -> bool {
+        let bx = LocalBox::new(self, aabb);
+        let Some([(x0, x1), (y0, y1), (z0, z1)]) = bx.cell_range(self, Vec3::ZERO) else {
+            return false;
+        };
+        let axes = bx.axes();
+        for ly in y0..=y1 {
+            for lz in z0..=z1 {
+                for lx in x0..=x1 {
+                    if !self.cell_collides(reg, lx, ly, lz) {
+                        continue;
+                    }
+                    let cell = Vec3::new(lx as f32 + 0.5, ly as f32 + 0.5, lz as f32 + 0.5);
+                    let separated = axes.iter().any(|&a| {
+                        let gap = (bx.center - cell).dot(a).abs();
+                        gap >= bx.radius(a) + cell_radius(a) - TOUCH_EPS
+                    });
+                    if !separated {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Sweep the world-space box by `delta` and return the first solid cell it runs into.
+    /// Cells the box already overlaps at the start are ignored, so a box can always move
+    /// out of them.
+    pub fn sweep_aabb(&self, reg: &BlockRegistry, aabb: &Aabb, delta: Vec3) -> Option<SweepHit> {
+        let bx = LocalBox::new(self, aabb);
+        let d = self.pose.vector_to_local(delta);
+        let [(x0, x1), (y0, y1), (z0, z1)] = bx.cell_range(self, d)?;
+        let axes = bx.axes();
+        let mut best: Option<SweepHit> = None;
+        for ly in y0..=y1 {
+            for lz in z0..=z1 {
+                for lx in x0..=x1 {
+                    if !self.cell_collides(reg, lx, ly, lz) {
+                        continue;
+                    }
+                    let Some((toi, normal)) = sweep_cell(&bx, &axes, d, (lx, ly, lz)) else {
+                        continue;
+                    };
+                    if best.is_none_or(|b| toi < b.toi) {
+                        best = Some(SweepHit {
+                            toi,
+                            normal: self.pose.rotate(normal).normalized(),
+                            cell: (lx, ly, lz),
+                        });
+                    }
+                }
+            }
+        }
+        best
+    }
+}
+
+#[inline]
+fn cell_radius(axis: Vec3) -> f32 {
+    0.5 * (axis.x.abs() + axis.y.abs() + axis.z.abs())
+}
+
+/// Time of impact in `0..=1` and local contact normal of `bx` moving by `d` into a cell.
+fn sweep_cell(
+    bx: &LocalBox,
+    axes: &[Vec3],
+    d: Vec3,
+    (lx, ly, lz): (i32, i32, i32),
+) -> Option<(f32, Vec3)> {
+    let cell = Vec3::new(lx as f32 + 0.5, ly as f32 + 0.5, lz as f32 + 0.5);
+    let (mut enter, mut exit) = (f32::NEG_INFINITY, f32::INFINITY);
+    let mut normal = Vec3::ZERO;
+    for &a in axes {
+        let gap = (bx.center - cell).dot(a);
+        let reach = bx.radius(a) + cell_radius(a) - TOUCH_EPS;
+        let speed = d.dot(a);
+        if speed.abs() < AXIS_EPS {
+            if gap.abs() >= reach {
+                return None;
+            }
+            continue;
+        }
+        // Projections overlap while |gap + speed * t| < reach.
+        let (t0, t1) = {
+            let (a0, a1) = ((-reach - gap) / speed, (reach - gap) / speed);
+            (a0.min(a1), a0.max(a1))
+        };
+        if t0 > enter {
+            enter = t0;
+            normal = if speed > 0.0 { a * -1.0 } else { a };
+        }
+        exit = exit.min(t1);
+        if enter >= exit {
+            return None;
+        }
+    }
+    // Already overlapping at the start, or contact beyond this sweep.
+    if !(0.0..=1.0).contains(&enter) {
+        return None;
+    }
+    Some((enter, normal))
+}
+
+/// First hit of a world-space box swept by `delta` across several structures.
+pub fn sweep_structures<'a>(
+    structures: impl IntoIterator<Item = &'a Structure>,
+    reg: &BlockRegistry,
+    aabb: &Aabb,
+    delta: Vec3,
+) -> Option<(StructureId, SweepHit)> {
+    structures
+        .into_iter()
+        .filter_map(|st| st.sweep_aabb(reg, aabb, delta).map(|hit| (st.id, hit)))
+        .min_by(|a, b| a.1.toi.total_cmp(&b.1.toi))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Pose;
+    use crate::tests::registry;
+    use std::sync::Arc;
+
+    /// An 8x1x2 plank deck: long along local X, two cells wide along local Z.
+    fn deck(reg: &BlockRegistry, pose: Pose) -> Structure {
+        let stone = Block {
+            id: reg.id_by_name("stone").unwrap(),
+            state: 0,
+        };
+        let mut st = Structure::new(1, 8, 1, 2, pose, reg);
+        st.blocks = Arc::from(vec![stone; 16].into_boxed_slice());
+        st
+    }
+
+    fn player_box(feet: Vec3) -> Aabb {
+        Aabb::new(
+            Vec3::new(feet.x - 0.3, feet.y, feet.z - 0.3),
+            Vec3::new(feet.x + 0.3, feet.y + 1.8, feet.z + 0.3),
+        )
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn falling_box_lands_on_a_yawed_deck() {
+        let reg = registry();
+        let st = deck(&reg, Pose::from_yaw(Vec3::new(10.0, 5.0, 10.0), 45.0));
+        // Halfway along the deck's length, over its middle.
+        let mid = st.pose.local_to_world(Vec3::new(4.0, 1.0, 1.0));
+        let feet = Vec3::new(mid.x, 8.0, mid.z);
+        let hit = st
+            .sweep_aabb(&reg, &player_box(feet), Vec3::new(0.0, -5.0, 0.0))
+            .expect("lands on the deck");
+        // Top of the deck is y = 6; the fall from 8 stops after 2 of 5 units.
+        assert!(close(hit.toi, 0.4), "toi {}", hit.toi);
+        assert!(close(hit.normal.y, 1.0), "normal {:?}", hit.normal);
+        let rest = player_box(Vec3::new(feet.x, 6.0, feet.z));
+        assert!(
+            !st.overlaps_aabb(&reg, &rest),
+            "standing on the deck is not inside it"
+        );
+        let sunk = player_box(Vec3::new(feet.x, 5.9, feet.z));
+        assert!(st.overlaps_aabb(&reg, &sunk));
+    }
+
+    #[test]
+    fn yawed_deck_only_blocks_inside_its_rotated_footprint() {
+        let reg = registry();
+        let st = deck(&reg, Pose::from_yaw(Vec3::new(0.0, 0.0, 0.0), 45.0));
+        // This spot lies inside the deck's world-space bounding box but off the deck
+        // itself: a world-AABB test would stop the fall, the rotated test must not.
+        let off = st.pose.local_to_world(Vec3::new(5.0, 1.0, -1.5));
+        let corners = [(0.0, 0.0), (8.0, 0.0), (0.0, 2.0), (8.0, 2.0)]
+            .map(|(x, z)| st.pose.local_to_world(Vec3::new(x, 0.0, z)));
+        let within = |v: f32, f: fn(&Vec3) -> f32| {
+            let (lo, hi) = corners
+                .iter()
+                .map(f)
+                .fold((f32::MAX, f32::MIN), |(lo, hi), c| (lo.min(c), hi.max(c)));
+            (lo..=hi).contains(&v)
+        };
+        assert!(within(off.x, |c| c.x) && within(off.z, |c| c.z));
+        let small = Aabb::new(
+            Vec3::new(off.x - 0.1, 3.0, off.z - 0.1),
+            Vec3::new(off.x + 0.1, 3.5, off.z + 0.1),
+        );
+        assert_eq!(st.sweep_aabb(&reg, &small, Vec3::new(0.0, -5.0, 0.0)), None);
+    }
+
+    #[test]
+    fn sliding_into_a_yawed_deck_edge_reports_the_rotated_side_normal() {
+        let reg = registry();
+        let yaw = 30.0;
+        let st = deck(&reg, Pose::from_yaw(Vec3::new(0.0, 0.0, 0.0), yaw));
+        // Start beside the deck's -Z side and walk straight into it along local +Z.
+        let start = st.pose.local_to_world(Vec3::new(4.0, 0.2, -1.5));
+        let dir = st.pose.rotate(Vec3::new(0.0, 0.0, 1.0));
+        let bx = Aabb::new(
+            Vec3::new(start.x - 0.2, start.y, start.z - 0.2),
+            Vec3::new(start.x + 0.2, start.y + 0.5, start.z + 0.2),
+        );
+        let hit = st.sweep_aabb(&reg, &bx, dir * 3.0).expect("hits the side");
+        let expect = st.pose.rotate(Vec3::new(0.0, 0.0, -1.0));
+        assert!(hit.normal.dot(expect) > 0.999, "normal {:?}", hit.normal);
+        assert_eq!(hit.cell.2, 0);
+        // The box's rotated corner reaches the side before its center is 1.5 away.
+        assert!(hit.toi > 0.0 && hit.toi < 1.5 / 3.0);
+    }
+
+    #[test]
+    fn sweeps_pick_the_nearest_structure_and_can_leave_overlaps() {
+        let reg = registry();
+        let low = deck(&reg, Pose::from_yaw(Vec3::new(0.0, 0.0, 0.0), 0.0));
+        let mut high = deck(&reg, Pose::from_yaw(Vec3::new(0.0, 3.0, 0.0), 90.0));
+        high.id = 2;
+        // Over the corner where the decks cross: the high one spans x -2..0, z 0..8.
+        let bx = player_box(Vec3::new(0.0, 6.0, 0.5));
+        let (id, hit) =
+            sweep_structures([&low, &high], &reg, &bx, Vec3::new(0.0, -10.0, 0.0)).unwrap();
+        assert_eq!(id, 2);
+        assert!(close(hit.toi, 0.2), "toi {}", hit.toi);
+
+        // Embedded in the low deck, moving up is free.
+        let inside = player_box(Vec3::new(2.0, 0.5, 1.0));
+        assert!(low.overlaps_aabb(&reg, &inside));
+        assert_eq!(
+            low.sweep_aabb(&reg, &inside, Vec3::new(0.0, 1.0, 0.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn pitched_deck_collides_along_its_slope() {
+        let reg = registry();
+        let mut pose = Pose::from_yaw(Vec3::new(0.0, 0.0, 0.0), 0.0);
+        pose.roll_deg = 30.0;
+        let st = deck(&reg, pose);
+        // Drop onto the deck's local (4, 1, 1): the top surface midpoint, tilted by roll.
+        let top = st.pose.local_to_world(Vec3::new(4.0, 1.0, 1.0));
+        let bx = Aabb::new(
+            Vec3::new(top.x - 0.05, top.y + 2.0, top.z - 0.05),
+            Vec3::new(top.x + 0.05, top.y + 2.1, top.z + 0.05),
+        );
+        let hit = st
+            .sweep_aabb(&reg, &bx, Vec3::new(0.0, -4.0, 0.0))
+            .expect("lands on the tilted deck");
+        // A thin box lands within its own half width of the surface point.
+        assert!((hit.toi * 4.0 - 2.0).abs() < 0.1, "toi {}", hit.toi);
+        let up = st.pose.rotate(Vec3::new(0.0, 1.0, 0.0));
+        assert!(hit.normal.dot(up) > 0.99, "normal {:?}", hit.normal);
+    }
+}
@@ -0,0 +1,120 @@
+//! Blocks in free fall between leaving their cell and landing in another.
+
+use geist_blocks::types::Block;
+use geist_geom::Vec3;
+
+use crate::StructureId;
+
+/// Downward acceleration of falling blocks, in voxels/sec².
+pub const FALL_GRAVITY: f32 = 28.0;
+/// Fastest a block falls, in voxels/sec.
+pub const FALL_TERMINAL_SPEED: f32 = 40.0;
+/// Seconds before a block that never lands is dropped.
+pub const FALL_MAX_AGE: f32 = 30.0;
+// Horizontal speed kept per second; a block thrown off a platform drifts to a stop.
+const HORIZONTAL_DAMPING: f32 = 0.35;
+// Longest move per substep, so a fast block cannot pass through a one-voxel floor.
+const MAX_STEP: f32 = 0.45;
+// How far under a block's bottom face support is looked for.
+const PROBE_DEPTH: f32 = 0.02;
+
+/// Cell a falling block comes to rest in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LandingSpot {
+    World {
+        wx: i32,
+        wy: i32,
+        wz: i32,
+    },
+    Structure {
+        id: StructureId,
+        lx: i32,
+        ly: i32,
+        lz: i32,
+    },
+}
+
+/// A block that left its cell and is falling as an entity.
+#[derive(Clone, Debug)]
+pub struct FallingBlock {
+    pub block: Block,
+    /// Center of the block in world space.
+    pub pos: Vec3,
+    pub vel: Vec3,
+    pub age: f32,
+}
+
+/// Every block currently falling.
+#[derive(Default)]
+pub struct FallingBlocks {
+    blocks: Vec<FallingBlock>,
+}
+
+impl FallingBlocks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &FallingBlock> {
+        self.blocks.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+    }
+
+    /// Start `block` falling from `center` with `vel`, e.g. the velocity of the platform it
+    /// slid off.
+    pub fn spawn(&mut self, block: Block, center: Vec3, vel: Vec3) {
+        self.blocks.push(FallingBlock {
+            block,
+            pos: center,
+            vel,
+            age: 0.0,
+        });
+    }
+
+    /// Advance every block by `dt`. `probe` is asked for the point just under a block's
+    /// bottom face and returns where the block rests if that point is solid. Landed blocks
+    /// are returned for the caller to place; blocks that fall below `floor_y` or outlive
+    /// `FALL_MAX_AGE` are dropped.
+    pub fn step(
+        &mut self,
+        dt: f32,
+        floor_y: f32,
+        mut probe: impl FnMut(Vec3) -> Option<LandingSpot>,
+    ) -> Vec<(Block, LandingSpot)> {
+        let mut landed = Vec::new();
+        if dt <= 0.0 {
+            return landed;
+        }
+        let damping = HORIZONTAL_DAMPING.powf(dt);
+        self.blocks.retain_mut(|fb| {
+            fb.age += dt;
+            fb.vel.y = (fb.vel.y - FALL_GRAVITY * dt).max(-FALL_TERMINAL_SPEED);
+            fb.vel.x *= damping;
+            fb.vel.z *= damping;
+            let travel = fb.vel * dt;
+            let steps = (travel.length() / MAX_STEP).ceil().max(1.0) as u32;
+            let step = travel * (1.0 / steps as f32);
+            for _ in 0..steps {
+                fb.pos += step;
+                let under = Vec3::new(fb.pos.x, fb.pos.y - 0.5 - PROBE_DEPTH, fb.pos.z);
+                if let Some(spot) = probe(under) {
+                    landed.push((fb.block, spot));
+                    return false;
+                }
+            }
+            fb.pos.y >= floor_y && fb.age < FALL_MAX_AGE
+        });
+        landed
+    }
+}
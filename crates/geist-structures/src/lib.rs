@@ -2,16 +2,121 @@
 #![forbid(unsafe_code)]
 
 use geist_blocks::{BlockRegistry, types::Block};
-use geist_geom::Vec3;
-use std::collections::HashMap;
+use geist_geom::{Quat, Vec3};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
+mod collide;
+mod falling;
+mod savefile;
+
+pub use collide::{SweepHit, sweep_structures};
+pub use falling::{
+    FALL_GRAVITY, FALL_MAX_AGE, FALL_TERMINAL_SPEED, FallingBlock, FallingBlocks, LandingSpot,
+};
+pub use savefile::STRUCTURE_VERSION;
+
 pub type StructureId = u32;
 
+/// Edge length, in cells, of the cubic sections a structure is meshed in.
+pub const STRUCTURE_SECTION: usize = 32;
+
+/// Section index along each local axis (`cell / STRUCTURE_SECTION`).
+pub type SectionCoord = (i32, i32, i32);
+
+// Speeds below this (voxels/sec) count as parked.
+const STATIONARY_EPS: f32 = 1e-4;
+
+/// Placement of a structure: `world = pos + R(yaw) * R(pitch) * R(roll) * (local * scale)`.
 #[derive(Clone)]
 pub struct Pose {
     pub pos: Vec3,
+    /// Heading about world +Y, same sense as `rotate_yaw`.
     pub yaw_deg: f32,
+    /// Nose up/down about the structure's local X axis.
+    pub pitch_deg: f32,
+    /// Bank about the structure's local Z axis.
+    pub roll_deg: f32,
+    /// Uniform size of one local cell in world units.
+    pub scale: f32,
+}
+
+impl Pose {
+    /// Upright, unscaled pose: the only kind structures had before pitch, roll and scale.
+    pub fn from_yaw(pos: Vec3, yaw_deg: f32) -> Self {
+        Self {
+            pos,
+            yaw_deg,
+            pitch_deg: 0.0,
+            roll_deg: 0.0,
+            scale: 1.0,
+        }
+    }
+
+    /// Orientation as a quaternion (yaw, then pitch, then roll from the outside in).
+    pub fn rotation(&self) -> Quat {
+        let yaw = Quat::from_axis_angle(Vec3::UP, -self.yaw_deg.to_radians());
+        let pitch = Quat::from_axis_angle(Vec3::new(1.0, 0.0, 0.0), self.pitch_deg.to_radians());
+        let roll = Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), self.roll_deg.to_radians());
+        yaw * pitch * roll
+    }
+
+    #[inline]
+    pub fn is_upright(&self) -> bool {
+        self.pitch_deg == 0.0 && self.roll_deg == 0.0
+    }
+
+    /// World units per local cell; non-positive scales count as 1.
+    #[inline]
+    pub fn safe_scale(&self) -> f32 {
+        if self.scale > 0.0 { self.scale } else { 1.0 }
+    }
+
+    /// Rotate a local direction into world space (no scale, no translation).
+    pub fn rotate(&self, v: Vec3) -> Vec3 {
+        if self.is_upright() {
+            return rotate_yaw(v, self.yaw_deg);
+        }
+        self.rotation().rotate(v)
+    }
+
+    /// Rotate a world direction into structure-local space.
+    pub fn rotate_inv(&self, v: Vec3) -> Vec3 {
+        if self.is_upright() {
+            return rotate_yaw_inv(v, self.yaw_deg);
+        }
+        self.rotation().conjugate().rotate(v)
+    }
+
+    /// Local offset or velocity to world space: rotated and scaled, not translated.
+    #[inline]
+    pub fn vector_to_world(&self, v: Vec3) -> Vec3 {
+        self.rotate(v * self.safe_scale())
+    }
+
+    /// World offset or velocity to local space; inverse of `vector_to_world`.
+    #[inline]
+    pub fn vector_to_local(&self, v: Vec3) -> Vec3 {
+        self.rotate_inv(v) / self.safe_scale()
+    }
+
+    #[inline]
+    pub fn local_to_world(&self, local: Vec3) -> Vec3 {
+        self.vector_to_world(local) + self.pos
+    }
+
+    #[inline]
+    pub fn world_to_local(&self, world: Vec3) -> Vec3 {
+        self.vector_to_local(world - self.pos)
+    }
+}
+
+/// Emissive cell of a structure, in structure-local coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LocalEmitter {
+    pub cell: (i32, i32, i32),
+    pub level: u8,
+    pub is_beacon: bool,
 }
 
 pub struct Structure {
@@ -86,6 +191,52 @@ impl Structure {
         (y * self.sz + z) * self.sx + x
     }
 
+    /// Number of sections along each axis; edge sections may be partially filled.
+    pub fn section_counts(&self) -> (usize, usize, usize) {
+        (
+            self.sx.div_ceil(STRUCTURE_SECTION),
+            self.sy.div_ceil(STRUCTURE_SECTION),
+            self.sz.div_ceil(STRUCTURE_SECTION),
+        )
+    }
+
+    /// Every section of the structure, sorted by (y, z, x).
+    pub fn all_sections(&self) -> Vec<SectionCoord> {
+        let (nx, ny, nz) = self.section_counts();
+        let mut out = Vec::with_capacity(nx * ny * nz);
+        for y in 0..ny as i32 {
+            for z in 0..nz as i32 {
+                for x in 0..nx as i32 {
+                    out.push((x, y, z));
+                }
+            }
+        }
+        out
+    }
+
+    /// Queue every section for remeshing, e.g. after the block registry changed.
+    pub fn mark_all_sections_dirty(&mut self) {
+        let all = self.all_sections();
+        self.edits.dirty_sections.extend(all);
+    }
+
+    /// Sections whose mesh is stale since the last call, sorted by (y, z, x).
+    pub fn take_dirty_sections(&mut self) -> Vec<SectionCoord> {
+        let (nx, ny, nz) = self.section_counts();
+        let mut out: Vec<SectionCoord> = self
+            .edits
+            .dirty_sections
+            .drain()
+            .filter(|&(x, y, z)| {
+                (0..nx as i32).contains(&x)
+                    && (0..ny as i32).contains(&y)
+                    && (0..nz as i32).contains(&z)
+            })
+            .collect();
+        out.sort_by_key(|&(x, y, z)| (y, z, x));
+        out
+    }
+
     pub fn set_local(&mut self, lx: i32, ly: i32, lz: i32, b: Block) {
         if lx < 0 || ly < 0 || lz < 0 {
             return;
@@ -110,6 +261,98 @@ impl Structure {
         self.bump_rev();
     }
 
+    /// Effective block at a local cell (edits over base blocks); `None` outside bounds.
+    pub fn block_local(&self, lx: i32, ly: i32, lz: i32) -> Option<Block> {
+        if lx < 0 || ly < 0 || lz < 0 {
+            return None;
+        }
+        let (lxu, lyu, lzu) = (lx as usize, ly as usize, lz as usize);
+        if lxu >= self.sx || lyu >= self.sy || lzu >= self.sz {
+            return None;
+        }
+        Some(
+            self.edits
+                .get(lx, ly, lz)
+                .unwrap_or(self.blocks[self.idx(lxu, lyu, lzu)]),
+        )
+    }
+
+    /// World voxel containing the center of a local cell under the current pose.
+    pub fn local_to_world_voxel(&self, lx: i32, ly: i32, lz: i32) -> (i32, i32, i32) {
+        let center = Vec3::new(lx as f32 + 0.5, ly as f32 + 0.5, lz as f32 + 0.5);
+        let w = self.pose.local_to_world(center);
+        (w.x.floor() as i32, w.y.floor() as i32, w.z.floor() as i32)
+    }
+
+    /// Emitter at a local cell, if the effective block there gives off light.
+    pub fn emitter_at(
+        &self,
+        reg: &BlockRegistry,
+        lx: i32,
+        ly: i32,
+        lz: i32,
+    ) -> Option<LocalEmitter> {
+        let b = self.block_local(lx, ly, lz)?;
+        let ty = reg.get(b.id)?;
+        let level = ty.light_emission(b.state);
+        (level > 0).then(|| LocalEmitter {
+            cell: (lx, ly, lz),
+            level,
+            is_beacon: ty.light_is_beam(),
+        })
+    }
+
+    /// Every emissive cell. Scans the whole volume, so callers keep the result and patch it
+    /// with `emitter_at` as cells change.
+    pub fn local_emitters(&self, reg: &BlockRegistry) -> Vec<LocalEmitter> {
+        let mut out = Vec::new();
+        for ly in 0..self.sy as i32 {
+            for lz in 0..self.sz as i32 {
+                for lx in 0..self.sx as i32 {
+                    out.extend(self.emitter_at(reg, lx, ly, lz));
+                }
+            }
+        }
+        out
+    }
+
+    /// Every non-air cell (edits over base blocks) at the world voxel it covers under the
+    /// current pose, ready to write into the world's edit store. Cells are placed by their
+    /// centers, so off-axis rotations or scales below 1 can map two cells to one voxel; the
+    /// later cell wins and each voxel appears once.
+    pub fn world_blocks(&self, reg: &BlockRegistry) -> Vec<((i32, i32, i32), Block)> {
+        let air = reg.id_by_name("air").unwrap_or(0);
+        let mut out: Vec<((i32, i32, i32), Block)> = Vec::new();
+        let mut slot_of: HashMap<(i32, i32, i32), usize> = HashMap::new();
+        for ly in 0..self.sy as i32 {
+            for lz in 0..self.sz as i32 {
+                for lx in 0..self.sx as i32 {
+                    let Some(b) = self.block_local(lx, ly, lz) else {
+                        continue;
+                    };
+                    if b.id == air {
+                        continue;
+                    }
+                    let pos = self.local_to_world_voxel(lx, ly, lz);
+                    match slot_of.get(&pos) {
+                        Some(&i) => out[i].1 = b,
+                        None => {
+                            slot_of.insert(pos, out.len());
+                            out.push((pos, b));
+                        }
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Parked structures get their world lights resynced at once; moving ones are throttled.
+    #[inline]
+    pub fn is_stationary(&self) -> bool {
+        self.last_velocity.length() <= STATIONARY_EPS
+    }
+
     fn bump_rev(&mut self) {
         self.dirty_rev = self.dirty_rev.wrapping_add(1).max(1);
     }
@@ -117,12 +360,26 @@ impl Structure {
 
 pub struct StructureEditStore {
     inner: HashMap<(i32, i32, i32), Block>,
+    dirty_sections: HashSet<SectionCoord>,
+}
+
+/// Sections along one axis whose mesh can change when cell `l` changes: its own, plus the
+/// neighbour across a seam when the cell sits on the section's first or last layer.
+fn axis_sections(l: i32) -> (i32, i32) {
+    let size = STRUCTURE_SECTION as i32;
+    let s = l.div_euclid(size);
+    match l.rem_euclid(size) {
+        0 => (s - 1, s),
+        r if r == size - 1 => (s, s + 1),
+        _ => (s, s),
+    }
 }
 
 impl StructureEditStore {
     pub fn new() -> Self {
         Self {
             inner: HashMap::new(),
+            dirty_sections: HashSet::new(),
         }
     }
 
@@ -132,6 +389,27 @@ impl StructureEditStore {
 
     pub fn set(&mut self, lx: i32, ly: i32, lz: i32, b: Block) {
         self.inner.insert((lx, ly, lz), b);
+        self.mark_dirty(lx, ly, lz);
+    }
+
+    /// Flag the section holding a cell, and any section sharing a face with it, for remeshing.
+    pub fn mark_dirty(&mut self, lx: i32, ly: i32, lz: i32) {
+        let own = (
+            lx.div_euclid(STRUCTURE_SECTION as i32),
+            ly.div_euclid(STRUCTURE_SECTION as i32),
+            lz.div_euclid(STRUCTURE_SECTION as i32),
+        );
+        let ((x0, x1), (y0, y1), (z0, z1)) =
+            (axis_sections(lx), axis_sections(ly), axis_sections(lz));
+        for x in [x0, x1] {
+            self.dirty_sections.insert((x, own.1, own.2));
+        }
+        for y in [y0, y1] {
+            self.dirty_sections.insert((own.0, y, own.2));
+        }
+        for z in [z0, z1] {
+            self.dirty_sections.insert((own.0, own.1, z));
+        }
     }
 
     pub fn snapshot_all(&self) -> Vec<((i32, i32, i32), Block)> {
@@ -155,3 +433,193 @@ pub fn rotate_yaw(v: Vec3, yaw_deg: f32) -> Vec3 {
 pub fn rotate_yaw_inv(v: Vec3, yaw_deg: f32) -> Vec3 {
     rotate_yaw(v, -yaw_deg)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geist_blocks::config::{BlockDef, BlocksConfig, ShapeConfig};
+    use geist_blocks::material::MaterialCatalog;
+
+    pub(crate) fn registry() -> BlockRegistry {
+        let def = |name: &str, id: u16, solid: bool, emission: u8| BlockDef {
+            name: name.into(),
+            id: Some(id),
+            solid: Some(solid),
+            blocks_skylight: Some(solid),
+            propagates_light: Some(!solid),
+            emission: Some(emission.into()),
+            flicker: None,
+            light_profile: None,
+            light: None,
+            shape: Some(ShapeConfig::Simple("cube".into())),
+            materials: None,
+            state_schema: None,
+            seam: None,
+            tags: Vec::new(),
+        };
+        BlockRegistry::from_configs(
+            MaterialCatalog::new(),
+            BlocksConfig {
+                blocks: vec![
+                    def("air", 0, false, 0),
+                    def("stone", 1, true, 0),
+                    def("lamp", 2, true, 12),
+                ],
+                lighting: None,
+                unknown_block: None,
+            },
+        )
+        .unwrap()
+    }
+
+    fn block(reg: &BlockRegistry, name: &str) -> Block {
+        Block {
+            id: reg.id_by_name(name).unwrap(),
+            state: 0,
+        }
+    }
+
+    /// A structure of `sx * sy * sz` cells, all `fill`.
+    fn filled(
+        reg: &BlockRegistry,
+        size: (usize, usize, usize),
+        fill: &str,
+        pose: Pose,
+    ) -> Structure {
+        let (sx, sy, sz) = size;
+        let mut st = Structure::new(1, sx, sy, sz, pose, reg);
+        st.blocks = Arc::from(vec![block(reg, fill); sx * sy * sz].into_boxed_slice());
+        st
+    }
+
+    fn sorted(mut v: Vec<((i32, i32, i32), Block)>) -> Vec<((i32, i32, i32), Block)> {
+        v.sort_by_key(|&(p, _)| p);
+        v
+    }
+
+    #[test]
+    fn world_blocks_translate_cells_at_yaw_0() {
+        let reg = registry();
+        let stone = block(&reg, "stone");
+        let st = filled(
+            &reg,
+            (3, 2, 2),
+            "stone",
+            Pose::from_yaw(Vec3::new(10.0, 5.0, -3.0), 0.0),
+        );
+        let mut expected = Vec::new();
+        for ly in 0..2 {
+            for lz in 0..2 {
+                for lx in 0..3 {
+                    expected.push(((10 + lx, 5 + ly, -3 + lz), stone));
+                }
+            }
+        }
+        assert_eq!(sorted(st.world_blocks(&reg)), sorted(expected));
+    }
+
+    #[test]
+    fn world_blocks_rotate_cells_at_yaw_90() {
+        let reg = registry();
+        let stone = block(&reg, "stone");
+        let st = filled(
+            &reg,
+            (3, 1, 2),
+            "stone",
+            Pose::from_yaw(Vec3::new(10.0, 5.0, -3.0), 90.0),
+        );
+        // Yaw 90 maps local (x, z) to world (-z, x).
+        let mut expected = Vec::new();
+        for lz in 0..2 {
+            for lx in 0..3 {
+                expected.push(((10 - lz - 1, 5, -3 + lx), stone));
+            }
+        }
+        assert_eq!(sorted(st.world_blocks(&reg)), sorted(expected));
+    }
+
+    #[test]
+    fn world_blocks_apply_edits_and_skip_air() {
+        let reg = registry();
+        let (stone, lamp) = (block(&reg, "stone"), block(&reg, "lamp"));
+        let mut st = filled(&reg, (2, 1, 2), "air", Pose::from_yaw(Vec3::ZERO, 0.0));
+        let mut base = st.blocks.to_vec();
+        base[st.idx(0, 0, 0)] = stone;
+        base[st.idx(1, 0, 0)] = stone;
+        st.blocks = Arc::from(base.into_boxed_slice());
+        st.set_local(1, 0, 0, lamp);
+        st.remove_local(0, 0, 0);
+        st.set_local(1, 0, 1, stone);
+        assert_eq!(
+            sorted(st.world_blocks(&reg)),
+            vec![((1, 0, 0), lamp), ((1, 0, 1), stone)]
+        );
+    }
+
+    #[test]
+    fn world_blocks_list_each_voxel_once() {
+        let reg = registry();
+        // At half scale eight cells share each world voxel.
+        let mut pose = Pose::from_yaw(Vec3::ZERO, 0.0);
+        pose.scale = 0.5;
+        let st = filled(&reg, (4, 2, 2), "stone", pose);
+        let out = st.world_blocks(&reg);
+        let voxels: HashSet<(i32, i32, i32)> = out.iter().map(|&(p, _)| p).collect();
+        assert_eq!(voxels.len(), out.len());
+        assert_eq!(
+            sorted(out).iter().map(|&(p, _)| p).collect::<Vec<_>>(),
+            [(0, 0, 0), (1, 0, 0)]
+        );
+
+        // Off-axis yaw: every voxel still appears once.
+        let st = filled(
+            &reg,
+            (5, 1, 5),
+            "stone",
+            Pose::from_yaw(Vec3::new(0.5, 0.0, 0.5), 30.0),
+        );
+        let out = st.world_blocks(&reg);
+        let voxels: HashSet<(i32, i32, i32)> = out.iter().map(|&(p, _)| p).collect();
+        assert_eq!(voxels.len(), out.len());
+    }
+
+    #[test]
+    fn placed_and_removed_emitters_resolve_to_the_posed_world_voxel() {
+        let reg = registry();
+        let (stone, lamp) = (block(&reg, "stone"), block(&reg, "lamp"));
+        let pose = Pose::from_yaw(Vec3::new(20.0, 4.0, 8.0), 90.0);
+        let mut st = filled(&reg, (4, 2, 3), "air", pose);
+        assert!(st.local_emitters(&reg).is_empty());
+
+        st.set_local(2, 1, 1, lamp);
+        let placed = LocalEmitter {
+            cell: (2, 1, 1),
+            level: 12,
+            is_beacon: false,
+        };
+        assert_eq!(st.emitter_at(&reg, 2, 1, 1), Some(placed));
+        assert_eq!(st.local_emitters(&reg), vec![placed]);
+        // Yaw 90 maps local (x, z) to world (-z, x).
+        assert_eq!(st.local_to_world_voxel(2, 1, 1), (18, 5, 10));
+
+        // Overwriting the lamp with a solid block removes its light too.
+        st.set_local(2, 1, 1, stone);
+        assert_eq!(st.emitter_at(&reg, 2, 1, 1), None);
+        st.set_local(2, 1, 1, lamp);
+        st.remove_local(2, 1, 1);
+        assert_eq!(st.emitter_at(&reg, 2, 1, 1), None);
+        assert!(st.local_emitters(&reg).is_empty());
+    }
+
+    #[test]
+    fn non_emitters_and_out_of_bounds_cells_report_nothing() {
+        let reg = registry();
+        let mut st = filled(&reg, (2, 2, 2), "air", Pose::from_yaw(Vec3::ZERO, 0.0));
+        st.set_local(0, 0, 0, block(&reg, "stone"));
+        st.set_local(5, 0, 0, block(&reg, "lamp"));
+        assert_eq!(st.emitter_at(&reg, 0, 0, 0), None);
+        assert_eq!(st.emitter_at(&reg, 1, 1, 1), None);
+        assert_eq!(st.emitter_at(&reg, 5, 0, 0), None);
+        assert!(st.local_emitters(&reg).is_empty());
+    }
+}
@@ -0,0 +1,267 @@
+//! On-disk format for a single structure: dimensions, pose, base blocks and edits overlay.
+//!
+//! Layout (little endian):
+//! - magic `GSTR`, `u16` version, `u32` structure id, `sx, sy, sz` as `u32`
+//! - pose: `x, y, z, yaw_deg, pitch_deg, roll_deg, scale` as `f32`
+//! - `u32` run count, per run: `u32` length, `u16` block id, `u16` state, covering the
+//!   base blocks in `(y * sz + z) * sx + x` order
+//! - `u32` edit count, per edit: `lx, ly, lz` as `i32`, `u16` block id, `u16` state
+//!
+//! Block ids are stored as-is, so a structure file is tied to the registry that wrote it.
+//! Motion state (velocity, last delta) is not saved; a loaded structure starts at rest.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use geist_blocks::codec::{
+    invalid, read_array, read_block, read_f32, read_i32, read_u16, read_u32, write_atomic,
+    write_block,
+};
+use geist_blocks::types::Block;
+use geist_geom::Vec3;
+
+use crate::{Pose, Structure, StructureEditStore};
+
+pub const STRUCTURE_VERSION: u16 = 1;
+
+const MAGIC: &[u8; 4] = b"GSTR";
+
+impl Structure {
+    /// Encode the structure in the current `STRUCTURE_VERSION` format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out: Vec<u8> = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&STRUCTURE_VERSION.to_le_bytes());
+        out.extend_from_slice(&self.id.to_le_bytes());
+        for dim in [self.sx, self.sy, self.sz] {
+            out.extend_from_slice(&(dim as u32).to_le_bytes());
+        }
+        let p = &self.pose;
+        for v in [
+            p.pos.x,
+            p.pos.y,
+            p.pos.z,
+            p.yaw_deg,
+            p.pitch_deg,
+            p.roll_deg,
+            p.scale,
+        ] {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+
+        let mut runs: Vec<(u32, Block)> = Vec::new();
+        for &b in self.blocks.iter() {
+            match runs.last_mut() {
+                Some((n, last)) if *last == b => *n += 1,
+                _ => runs.push((1, b)),
+            }
+        }
+        out.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+        for (n, b) in runs {
+            out.extend_from_slice(&n.to_le_bytes());
+            write_block(&mut out, b);
+        }
+
+        let mut edits = self.edits.snapshot_all();
+        edits.sort_by_key(|((x, y, z), _)| (*y, *z, *x));
+        out.extend_from_slice(&(edits.len() as u32).to_le_bytes());
+        for ((lx, ly, lz), b) in edits {
+            for v in [lx, ly, lz] {
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            write_block(&mut out, b);
+        }
+        out
+    }
+
+    /// Decode a structure written by `to_bytes` with this or an older version.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut r = bytes;
+        if &read_array::<4>(&mut r)? != MAGIC {
+            return Err(invalid("not a structure file"));
+        }
+        let version = read_u16(&mut r)?;
+        if version > STRUCTURE_VERSION {
+            return Err(invalid(format!(
+                "structure version {} is newer than supported {}",
+                version, STRUCTURE_VERSION
+            )));
+        }
+        let id = read_u32(&mut r)?;
+        let (sx, sy, sz) = (
+            read_u32(&mut r)? as usize,
+            read_u32(&mut r)? as usize,
+            read_u32(&mut r)? as usize,
+        );
+        let volume = sx
+            .checked_mul(sy)
+            .and_then(|v| v.checked_mul(sz))
+            .filter(|&v| v > 0)
+            .ok_or_else(|| invalid(format!("bad structure size {}x{}x{}", sx, sy, sz)))?;
+        let mut pose_vals = [0.0f32; 7];
+        for v in &mut pose_vals {
+            *v = read_f32(&mut r)?;
+        }
+        let [x, y, z, yaw_deg, pitch_deg, roll_deg, scale] = pose_vals;
+        let pose = Pose {
+            pos: Vec3::new(x, y, z),
+            yaw_deg,
+            pitch_deg,
+            roll_deg,
+            scale,
+        };
+
+        let runs = read_u32(&mut r)?;
+        let mut blocks: Vec<Block> = Vec::with_capacity(volume.min(1 << 24));
+        for _ in 0..runs {
+            let n = read_u32(&mut r)? as usize;
+            let b = read_block(&mut r)?;
+            if blocks.len() + n > volume {
+                return Err(invalid("block runs overflow the structure volume"));
+            }
+            blocks.extend(std::iter::repeat_n(b, n));
+        }
+        if blocks.len() != volume {
+            return Err(invalid(format!(
+                "block runs cover {} of {} cells",
+                blocks.len(),
+                volume
+            )));
+        }
+
+        let mut edits = StructureEditStore::new();
+        for _ in 0..read_u32(&mut r)? {
+            let (lx, ly, lz) = (read_i32(&mut r)?, read_i32(&mut r)?, read_i32(&mut r)?);
+            edits.set(lx, ly, lz, read_block(&mut r)?);
+        }
+
+        Ok(Self {
+            id,
+            sx,
+            sy,
+            sz,
+            blocks: Arc::from(blocks.into_boxed_slice()),
+            edits,
+            pose,
+            last_delta: Vec3::ZERO,
+            last_velocity: Vec3::ZERO,
+            dirty_rev: 1,
+            built_rev: 0,
+        })
+    }
+
+    /// Write the structure to `path`, replacing any previous file atomically.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        write_atomic(path, &self.to_bytes())
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        Self::from_bytes(&bytes).map_err(|e| invalid(format!("{}: {}", path.display(), e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(id: u16, state: u16) -> Block {
+        Block { id, state }
+    }
+
+    /// A 3x2x2 structure with a stone floor, a lamp and one edit, under a tilted pose.
+    fn sample() -> Structure {
+        let mut blocks = vec![block(0, 0); 12];
+        blocks[..6].fill(block(1, 0));
+        blocks[7] = block(5, 3);
+        let mut edits = StructureEditStore::new();
+        edits.set(2, 1, 1, block(4, 1));
+        edits.set(0, 0, 0, block(0, 0));
+        Structure {
+            id: 7,
+            sx: 3,
+            sy: 2,
+            sz: 2,
+            blocks: Arc::from(blocks.into_boxed_slice()),
+            edits,
+            pose: Pose {
+                pos: Vec3::new(10.5, 64.0, -3.25),
+                yaw_deg: 30.0,
+                pitch_deg: -5.0,
+                roll_deg: 12.5,
+                scale: 0.5,
+            },
+            last_delta: Vec3::new(1.0, 0.0, 0.0),
+            last_velocity: Vec3::new(2.0, 0.0, 0.0),
+            dirty_rev: 9,
+            built_rev: 9,
+        }
+    }
+
+    /// Byte offset of the run count: magic, version, id, size and pose come first.
+    const RUNS_AT: usize = 4 + 2 + 4 + 3 * 4 + 7 * 4;
+
+    #[test]
+    fn round_trip_keeps_blocks_edits_and_pose() {
+        let st = sample();
+        let back = Structure::from_bytes(&st.to_bytes()).unwrap();
+        assert_eq!((back.id, back.sx, back.sy, back.sz), (7, 3, 2, 2));
+        assert_eq!(&*back.blocks, &*st.blocks);
+        assert_eq!(back.edits.snapshot_all().len(), 2);
+        assert_eq!(back.edits.get(2, 1, 1), Some(block(4, 1)));
+        assert_eq!(back.edits.get(0, 0, 0), Some(block(0, 0)));
+        let (p, q) = (&back.pose, &st.pose);
+        assert_eq!((p.pos.x, p.pos.y, p.pos.z), (q.pos.x, q.pos.y, q.pos.z));
+        assert_eq!(
+            (p.yaw_deg, p.pitch_deg, p.roll_deg, p.scale),
+            (q.yaw_deg, q.pitch_deg, q.roll_deg, q.scale)
+        );
+        // Motion is not saved.
+        assert_eq!(back.last_velocity, Vec3::ZERO);
+
+        let path = std::env::temp_dir()
+            .join(format!("geist-structure-{}", std::process::id()))
+            .join("deck.gstr");
+        st.save(&path).unwrap();
+        let loaded = Structure::load(&path).unwrap();
+        assert_eq!(&*loaded.blocks, &*st.blocks);
+        assert_eq!(loaded.edits.get(2, 1, 1), Some(block(4, 1)));
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn newer_versions_are_refused() {
+        let mut bytes = sample().to_bytes();
+        bytes[4..6].copy_from_slice(&(STRUCTURE_VERSION + 1).to_le_bytes());
+        let err = Structure::from_bytes(&bytes).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("newer"), "{}", err);
+    }
+
+    #[test]
+    fn truncated_and_oversized_runs_are_rejected() {
+        let bytes = sample().to_bytes();
+        for cut in [3, RUNS_AT + 6, bytes.len() - 1] {
+            let err = Structure::from_bytes(&bytes[..cut]).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof, "cut at {}", cut);
+        }
+
+        // First run (six stone cells) claims more than the 12-cell volume.
+        let mut long = bytes.clone();
+        let first = RUNS_AT + 4;
+        long[first..first + 4].copy_from_slice(&100u32.to_le_bytes());
+        let err = Structure::from_bytes(&long).err().unwrap();
+        assert!(err.to_string().contains("overflow"), "{}", err);
+
+        // Runs that stop short of the volume.
+        let mut short = bytes;
+        short[first..first + 4].copy_from_slice(&1u32.to_le_bytes());
+        let err = Structure::from_bytes(&short).err().unwrap();
+        assert!(err.to_string().contains("of 12 cells"), "{}", err);
+    }
+}
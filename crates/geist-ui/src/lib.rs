@@ -2,9 +2,12 @@ pub mod text;
 pub mod windows;
 
 pub use windows::{
-    HitRegion, IRect, OverlayWindow, OverlayWindowManager, ResizeHandle, TabDefinition, TabSlot,
-    TabStrip, TabStripLayout, WindowButton, WindowChrome, WindowFrame, WindowId, WindowState,
-    WindowTheme,
+    HitRegion, IRect, ModalButton, ModalDialog, ModalFrame, ModalId, ModalKey, ModalLayer,
+    ModalResult, OverlayWindow, OverlayWindowManager, ResizeHandle, TabDefinition,
+    TabScrollButtons, TabSlot, TabStrip, TabStripHit, TabStripLayout, TabStripState, WindowButton,
+    WindowChrome, WindowFrame, WindowId, WindowState, WindowTheme,
 };
 
-pub use text::{UiTextMeasure, UiTextRenderer};
+pub use text::{
+    TextAlign, TextSpan, TextTable, UiTextMeasure, UiTextRenderer, wrap_spans, wrap_text,
+};
@@ -8,6 +8,36 @@ pub trait UiTextMeasure {
 /// Drawing interface that honors the same font overrides used for measurement.
 pub trait UiTextRenderer: UiTextMeasure {
     fn ui_draw_text(&mut self, text: &str, x: i32, y: i32, font_size: i32, color: Color);
+
+    /// Draw `spans` one after another on a line starting at `(x, y)`; returns the width used.
+    fn ui_draw_spans(&mut self, spans: &[TextSpan], x: i32, y: i32, font_size: i32) -> i32 {
+        let mut cx = x;
+        for span in spans {
+            if !span.text.is_empty() {
+                self.ui_draw_text(&span.text, cx, y, font_size, span.color);
+                cx += self.ui_measure_text(&span.text, font_size);
+            }
+        }
+        cx - x
+    }
+
+    /// Draw `spans` word-wrapped to `max_width`, one line every `line_height` pixels;
+    /// returns the height used.
+    fn ui_draw_wrapped(
+        &mut self,
+        spans: &[TextSpan],
+        x: i32,
+        y: i32,
+        max_width: i32,
+        font_size: i32,
+        line_height: i32,
+    ) -> i32 {
+        let lines = wrap_spans(&*self, spans, font_size, max_width);
+        for (i, line) in lines.iter().enumerate() {
+            self.ui_draw_spans(line, x, y + i as i32 * line_height, font_size);
+        }
+        lines.len() as i32 * line_height
+    }
 }
 
 impl UiTextMeasure for RaylibDrawHandle<'_> {
@@ -27,3 +57,379 @@ impl UiTextRenderer for RaylibDrawHandle<'_> {
         self.draw_text(text, x, y, font_size, color);
     }
 }
+
+/// A run of text in one color.
+#[derive(Clone, Debug)]
+pub struct TextSpan {
+    pub text: String,
+    pub color: Color,
+}
+
+impl TextSpan {
+    pub fn new(text: impl Into<String>, color: Color) -> Self {
+        Self {
+            text: text.into(),
+            color,
+        }
+    }
+}
+
+/// Break `text` into lines no wider than `max_width`, at spaces where possible and inside
+/// words that are wider than a whole line. Explicit newlines always break.
+pub fn wrap_text<M: UiTextMeasure + ?Sized>(
+    m: &M,
+    text: &str,
+    font_size: i32,
+    max_width: i32,
+) -> Vec<String> {
+    wrap_spans(
+        m,
+        &[TextSpan::new(text, Color::WHITE)],
+        font_size,
+        max_width,
+    )
+    .into_iter()
+    .map(|line| line.into_iter().map(|s| s.text).collect())
+    .collect()
+}
+
+/// [`wrap_text`] over colored spans; each span keeps its color across the breaks.
+pub fn wrap_spans<M: UiTextMeasure + ?Sized>(
+    m: &M,
+    spans: &[TextSpan],
+    font_size: i32,
+    max_width: i32,
+) -> Vec<Vec<TextSpan>> {
+    let mut wrap = Wrapper {
+        m,
+        font_size,
+        max_width: max_width.max(1),
+        lines: Vec::new(),
+        line: Vec::new(),
+        width: 0,
+    };
+    for span in spans {
+        for (i, para) in span.text.split('\n').enumerate() {
+            if i > 0 {
+                wrap.break_line();
+            }
+            // Words keep their leading space so spans join without extra gaps.
+            let mut start = 0;
+            for (pos, _) in para.match_indices(' ') {
+                if pos > start {
+                    wrap.push_word(&para[start..pos], span.color);
+                }
+                start = pos;
+            }
+            if start < para.len() {
+                wrap.push_word(&para[start..], span.color);
+            }
+        }
+    }
+    if !wrap.line.is_empty() || wrap.lines.is_empty() {
+        wrap.break_line();
+    }
+    wrap.lines
+}
+
+// Field-wise: raylib's `Color` equality calls into the C library.
+fn same_color(a: Color, b: Color) -> bool {
+    (a.r, a.g, a.b, a.a) == (b.r, b.g, b.b, b.a)
+}
+
+struct Wrapper<'m, M: ?Sized> {
+    m: &'m M,
+    font_size: i32,
+    max_width: i32,
+    lines: Vec<Vec<TextSpan>>,
+    line: Vec<TextSpan>,
+    width: i32,
+}
+
+impl<M: UiTextMeasure + ?Sized> Wrapper<'_, M> {
+    fn measure(&self, text: &str) -> i32 {
+        self.m.ui_measure_text(text, self.font_size)
+    }
+
+    fn break_line(&mut self) {
+        self.lines.push(std::mem::take(&mut self.line));
+        self.width = 0;
+    }
+
+    fn append(&mut self, text: &str, color: Color) {
+        self.width += self.measure(text);
+        match self.line.last_mut() {
+            Some(last) if same_color(last.color, color) => last.text.push_str(text),
+            _ => self.line.push(TextSpan::new(text, color)),
+        }
+    }
+
+    fn push_word(&mut self, word: &str, color: Color) {
+        let w = self.measure(word);
+        if self.width + w <= self.max_width {
+            self.append(word, color);
+            return;
+        }
+        // The space a line break replaces is dropped.
+        let word = if self.width > 0 {
+            self.break_line();
+            word.trim_start_matches(' ')
+        } else {
+            word
+        };
+        if self.measure(word) <= self.max_width {
+            self.append(word, color);
+            return;
+        }
+        // Wider than a whole line: hard-break by characters, at least one per line.
+        let mut rest = word;
+        while !rest.is_empty() {
+            let ends = rest.char_indices().skip(1).map(|(i, _)| i);
+            let mut end = 0;
+            for pos in ends.chain(std::iter::once(rest.len())) {
+                if self.width + self.measure(&rest[..pos]) > self.max_width {
+                    break;
+                }
+                end = pos;
+            }
+            if end == 0 {
+                end = rest.chars().next().map_or(rest.len(), char::len_utf8);
+            }
+            self.append(&rest[..end], color);
+            rest = &rest[end..];
+            if !rest.is_empty() {
+                self.break_line();
+            }
+        }
+    }
+}
+
+/// Horizontal alignment of a table column.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Right,
+}
+
+/// Rows of cells drawn in aligned columns, for diagnostics panes. Columns are sized from
+/// their widest cell, so alignment holds with proportional fonts too; `to_monospace_lines`
+/// gives the same layout as padded strings for logs and dumps.
+#[derive(Clone, Debug, Default)]
+pub struct TextTable {
+    headers: Vec<String>,
+    aligns: Vec<TextAlign>,
+    rows: Vec<Vec<TextSpan>>,
+    gap: i32,
+}
+
+impl TextTable {
+    /// Pixels between columns unless changed with [`TextTable::with_gap`].
+    pub const DEFAULT_GAP: i32 = 14;
+
+    /// A table with these column headers; an empty header row is not drawn.
+    pub fn new<S: Into<String>>(headers: impl IntoIterator<Item = S>) -> Self {
+        let headers: Vec<String> = headers.into_iter().map(Into::into).collect();
+        Self {
+            aligns: vec![TextAlign::Left; headers.len()],
+            headers,
+            rows: Vec::new(),
+            gap: Self::DEFAULT_GAP,
+        }
+    }
+
+    pub fn with_gap(mut self, gap: i32) -> Self {
+        self.gap = gap.max(0);
+        self
+    }
+
+    /// Align column `col`; out-of-range columns are ignored.
+    pub fn align(mut self, col: usize, align: TextAlign) -> Self {
+        if let Some(a) = self.aligns.get_mut(col) {
+            *a = align;
+        }
+        self
+    }
+
+    /// Append a row; missing cells are blank and extra cells are dropped.
+    pub fn push_row(&mut self, cells: impl IntoIterator<Item = TextSpan>) {
+        let mut row: Vec<TextSpan> = cells.into_iter().take(self.headers.len()).collect();
+        while row.len() < self.headers.len() {
+            row.push(TextSpan::new("", Color::WHITE));
+        }
+        self.rows.push(row);
+    }
+
+    pub fn columns(&self) -> usize {
+        self.headers.len()
+    }
+
+    pub fn rows(&self) -> &[Vec<TextSpan>] {
+        &self.rows
+    }
+
+    fn has_header(&self) -> bool {
+        self.headers.iter().any(|h| !h.is_empty())
+    }
+
+    /// Lines drawn, including the header row.
+    pub fn line_count(&self) -> usize {
+        self.rows.len() + usize::from(self.has_header())
+    }
+
+    /// Pixel width of each column at `font_size`.
+    pub fn column_widths<M: UiTextMeasure + ?Sized>(&self, m: &M, font_size: i32) -> Vec<i32> {
+        let mut widths: Vec<i32> = self
+            .headers
+            .iter()
+            .map(|h| m.ui_measure_text(h, font_size))
+            .collect();
+        for row in &self.rows {
+            for (w, cell) in widths.iter_mut().zip(row) {
+                *w = (*w).max(m.ui_measure_text(&cell.text, font_size));
+            }
+        }
+        widths
+    }
+
+    /// Total width at `font_size`, gaps included.
+    pub fn width<M: UiTextMeasure + ?Sized>(&self, m: &M, font_size: i32) -> i32 {
+        let widths = self.column_widths(m, font_size);
+        widths.iter().sum::<i32>() + self.gap * (widths.len() as i32 - 1).max(0)
+    }
+
+    /// Draw at `(x, y)` with headers in `header_color`; returns the height used.
+    pub fn draw<D: UiTextRenderer + ?Sized>(
+        &self,
+        d: &mut D,
+        x: i32,
+        y: i32,
+        font_size: i32,
+        line_height: i32,
+        header_color: Color,
+    ) -> i32 {
+        let widths = self.column_widths(&*d, font_size);
+        let mut cy = y;
+        let header = self.has_header().then(|| {
+            self.headers
+                .iter()
+                .map(|h| TextSpan::new(h.as_str(), header_color))
+                .collect::<Vec<_>>()
+        });
+        for row in header.iter().chain(&self.rows) {
+            let mut cx = x;
+            for ((cell, &w), align) in row.iter().zip(&widths).zip(&self.aligns) {
+                let tx = match align {
+                    TextAlign::Left => cx,
+                    TextAlign::Right => cx + w - d.ui_measure_text(&cell.text, font_size),
+                };
+                if !cell.text.is_empty() {
+                    d.ui_draw_text(&cell.text, tx, cy, font_size, cell.color);
+                }
+                cx += w + self.gap;
+            }
+            cy += line_height;
+        }
+        cy - y
+    }
+
+    /// The table as space-padded lines, columns two spaces apart; trailing spaces trimmed.
+    pub fn to_monospace_lines(&self) -> Vec<String> {
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| h.chars().count()).collect();
+        for row in &self.rows {
+            for (w, cell) in widths.iter_mut().zip(row) {
+                *w = (*w).max(cell.text.chars().count());
+            }
+        }
+        let header = self.has_header().then_some(&self.headers);
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|c| &c.text).collect::<Vec<_>>());
+        header
+            .map(|h| h.iter().collect::<Vec<_>>())
+            .into_iter()
+            .chain(rows)
+            .map(|cells| {
+                let mut line = String::new();
+                for (i, ((cell, &w), align)) in
+                    cells.iter().zip(&widths).zip(&self.aligns).enumerate()
+                {
+                    if i > 0 {
+                        line.push_str("  ");
+                    }
+                    match align {
+                        TextAlign::Left => line.push_str(&format!("{:<w$}", cell, w = w)),
+                        TextAlign::Right => line.push_str(&format!("{:>w$}", cell, w = w)),
+                    }
+                }
+                line.trim_end().to_string()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every character is `font_size / 2` pixels wide.
+    struct Fixed;
+
+    impl UiTextMeasure for Fixed {
+        fn ui_measure_text(&self, text: &str, font_size: i32) -> i32 {
+            text.chars().count() as i32 * font_size / 2
+        }
+    }
+
+    #[test]
+    fn wraps_at_spaces_and_breaks_long_words() {
+        // 10 px per char, 80 px per line: 8 characters.
+        let lines = wrap_text(&Fixed, "queue depth is rising fast", 20, 80);
+        assert_eq!(lines, vec!["queue", "depth is", "rising", "fast"]);
+
+        let lines = wrap_text(&Fixed, "aaaaaaaaaaaaaaaaaaaa b\nc", 20, 80);
+        assert_eq!(lines, vec!["aaaaaaaa", "aaaaaaaa", "aaaa b", "c"]);
+
+        assert_eq!(wrap_text(&Fixed, "", 20, 80), vec![String::new()]);
+    }
+
+    #[test]
+    fn wrapped_spans_keep_their_colors() {
+        let spans = [
+            TextSpan::new("light ", Color::YELLOW),
+            TextSpan::new("queue stalled", Color::RED),
+        ];
+        let lines: Vec<Vec<(String, u8)>> = wrap_spans(&Fixed, &spans, 20, 120)
+            .into_iter()
+            .map(|line| line.into_iter().map(|s| (s.text, s.color.g)).collect())
+            .collect();
+        let (yellow, red) = (Color::YELLOW.g, Color::RED.g);
+        assert_eq!(
+            lines,
+            vec![
+                vec![("light ".to_string(), yellow), ("queue".to_string(), red)],
+                vec![("stalled".to_string(), red)],
+            ]
+        );
+    }
+
+    #[test]
+    fn tables_size_columns_from_their_widest_cell() {
+        let mut table = TextTable::new(["Lane", "Queued"])
+            .with_gap(10)
+            .align(1, TextAlign::Right);
+        table.push_row([
+            TextSpan::new("edit", Color::WHITE),
+            TextSpan::new("3", Color::WHITE),
+        ]);
+        table.push_row([TextSpan::new("background", Color::WHITE)]);
+        assert_eq!(table.column_widths(&Fixed, 10), vec![50, 30]);
+        assert_eq!(table.width(&Fixed, 10), 90);
+        assert_eq!(table.line_count(), 3);
+        assert_eq!(
+            table.to_monospace_lines(),
+            vec!["Lane        Queued", "edit             3", "background"]
+        );
+    }
+}
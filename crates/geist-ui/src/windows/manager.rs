@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use raylib::prelude::Vector2;
 
-use super::{HitRegion, OverlayWindow, WindowId, WindowTheme};
+use super::{HitRegion, ModalLayer, OverlayWindow, WindowId, WindowTheme};
 
 #[derive(Default)]
 pub struct OverlayWindowManager {
@@ -10,6 +10,7 @@ pub struct OverlayWindowManager {
     order: Vec<WindowId>,
     theme: WindowTheme,
     focus_stack: Vec<WindowId>,
+    modals: ModalLayer,
 }
 
 impl OverlayWindowManager {
@@ -19,6 +20,7 @@ impl OverlayWindowManager {
             order: Vec::new(),
             theme,
             focus_stack: Vec::new(),
+            modals: ModalLayer::new(),
         }
     }
 
@@ -26,6 +28,11 @@ impl OverlayWindowManager {
         &self.theme
     }
 
+    /// Swap the theme; window sizes and positions are re-clamped on the next layout.
+    pub fn set_theme(&mut self, theme: WindowTheme) {
+        self.theme = theme;
+    }
+
     pub fn insert(&mut self, window: OverlayWindow) {
         let id = window.id();
         let pinned = window.is_pinned();
@@ -72,6 +79,9 @@ impl OverlayWindowManager {
     }
 
     pub fn bring_to_front(&mut self, id: WindowId) {
+        if self.modals.is_active() {
+            return;
+        }
         if let Some(pos) = self.order.iter().position(|existing| *existing == id) {
             let entry = self.order.remove(pos);
             let pinned = self
@@ -89,7 +99,27 @@ impl OverlayWindowManager {
         self.focus(id);
     }
 
+    pub fn modals(&self) -> &ModalLayer {
+        &self.modals
+    }
+
+    pub fn modals_mut(&mut self) -> &mut ModalLayer {
+        &mut self.modals
+    }
+
+    pub fn has_modal(&self) -> bool {
+        self.modals.is_active()
+    }
+
     pub fn handle_hover(&mut self, cursor: Vector2) -> Option<WindowId> {
+        if self.modals.is_active() {
+            // Modal dialogs own the pointer; nothing underneath reacts to hover.
+            self.modals.update_hover(cursor);
+            for window in self.windows.values_mut() {
+                window.reset_hover();
+            }
+            return None;
+        }
         let mut hovered = None;
         let descending = self.ordered_ids_rev();
         for id in descending {
@@ -1,5 +1,6 @@
 mod chrome;
 mod manager;
+mod modal;
 mod overlay;
 mod tab_strip;
 mod theme;
@@ -8,8 +9,11 @@ mod util;
 
 pub use chrome::WindowChrome;
 pub use manager::OverlayWindowManager;
+pub use modal::{ModalButton, ModalDialog, ModalFrame, ModalId, ModalKey, ModalLayer, ModalResult};
 pub use overlay::{OverlayWindow, ResizeSlot, ScrollInfo, TitleBarButtonSlot, WindowFrame};
-pub use tab_strip::{TabDefinition, TabSlot, TabStrip, TabStripLayout};
+pub use tab_strip::{
+    TabDefinition, TabScrollButtons, TabSlot, TabStrip, TabStripHit, TabStripLayout, TabStripState,
+};
 pub use theme::WindowTheme;
 pub use types::{HitRegion, IRect, ResizeHandle, WindowButton, WindowId, WindowState};
 
@@ -38,6 +42,20 @@ mod tests {
         assert_eq!(frame.outer.w, 360);
     }
 
+    #[test]
+    fn scaled_theme_grows_metrics_but_keeps_colors() {
+        let base = WindowTheme::default();
+        let big = base.scaled(1.5);
+        assert_eq!(big.titlebar_height, 51);
+        assert_eq!(big.title_font, 30);
+        assert_eq!(big.tab_height, 48);
+        let rgba = |c: raylib::prelude::Color| (c.r, c.g, c.b, c.a);
+        assert_eq!(rgba(big.title_text), rgba(base.title_text));
+        let same = base.scaled(1.0);
+        assert_eq!(same.padding_x, base.padding_x);
+        assert!(base.scaled(0.01).tab_font >= 1);
+    }
+
     #[test]
     fn pinned_windows_remain_on_top() {
         let theme = WindowTheme::default();
@@ -106,4 +124,144 @@ mod tests {
         assert!(back);
         assert!(window.content_offset().y <= 1.0);
     }
+    struct FixedMeasure;
+
+    impl crate::text::UiTextMeasure for FixedMeasure {
+        fn ui_measure_text(&self, text: &str, font_size: i32) -> i32 {
+            text.len() as i32 * font_size / 2
+        }
+    }
+
+    #[test]
+    fn tab_strip_overflow_scrolls_and_drag_reorders() {
+        let theme = WindowTheme::default();
+        let mut window = OverlayWindow::new(
+            WindowId::DebugTabs,
+            Vector2::new(40.0, 40.0),
+            (400, 300),
+            (200, 160),
+        );
+        let frame = window.layout((1280, 720), &theme);
+        let titles = ["A", "B", "C", "D", "E", "F"];
+        let tabs: Vec<TabDefinition> = titles.iter().map(|t| TabDefinition::new(t)).collect();
+        let mut state = TabStripState::default();
+
+        let layout = TabStrip::layout(&FixedMeasure, &theme, &frame, &tabs, &mut state);
+        let buttons = layout.scroll.expect("six min-width tabs overflow 400px");
+        assert!(!buttons.can_scroll_left && buttons.can_scroll_right);
+        let visible: Vec<usize> = layout.tabs.iter().map(|slot| slot.index).collect();
+        assert_eq!(visible.first(), Some(&0));
+        assert!(visible.len() < tabs.len());
+        let right_center = Vector2::new(
+            (buttons.right.x + buttons.right.w / 2) as f32,
+            (buttons.right.y + buttons.right.h / 2) as f32,
+        );
+        assert_eq!(layout.hit(right_center), Some(TabStripHit::ScrollRight));
+
+        state.scroll_by(100);
+        let layout = TabStrip::layout(&FixedMeasure, &theme, &frame, &tabs, &mut state);
+        let buttons = layout.scroll.unwrap();
+        assert!(buttons.can_scroll_left && !buttons.can_scroll_right);
+        assert_eq!(layout.tabs.last().map(|slot| slot.index), Some(5));
+        assert_eq!(state.first_visible(), tabs.len() - layout.tabs.len());
+
+        state.scroll_by(-100);
+        let layout = TabStrip::layout(&FixedMeasure, &theme, &frame, &tabs, &mut state);
+        let first = layout.tabs[0].bounds;
+        let second = layout.tabs[1].bounds;
+        let y = (first.y + first.h / 2) as f32;
+        state.begin_drag(0, Vector2::new((first.x + 4) as f32, y));
+        assert!(!state.update_drag(&layout, tabs.len(), Vector2::new((first.x + 6) as f32, y)));
+        let past_mid = (second.x + second.w / 2 + 1) as f32;
+        assert!(state.update_drag(&layout, tabs.len(), Vector2::new(past_mid, y)));
+        assert_eq!(state.dragged_tab(), Some(0));
+        state.end_drag();
+        assert_eq!(state.order(tabs.len()), vec![1, 0, 2, 3, 4, 5]);
+
+        let layout = TabStrip::layout(&FixedMeasure, &theme, &frame, &tabs, &mut state);
+        assert_eq!(layout.tabs[0].index, 1);
+        assert_eq!(layout.tabs[1].index, 0);
+    }
+
+    #[test]
+    fn prompt_collects_text_and_submits_on_enter() {
+        let mut modals = ModalLayer::new();
+        let id = modals.open(ModalDialog::prompt("Save", "Name the layer", "").with_max_len(4));
+        for ch in "walls".chars() {
+            assert!(modals.handle_key(ModalKey::Char(ch)).is_none());
+        }
+        assert!(modals.handle_key(ModalKey::Backspace).is_none());
+        assert_eq!(modals.active().and_then(|(_, d)| d.text()), Some("wal"));
+        let result = modals.handle_key(ModalKey::Enter);
+        assert_eq!(
+            result,
+            Some((id, ModalResult::Submitted("wal".to_string())))
+        );
+        assert!(!modals.is_active());
+    }
+
+    #[test]
+    fn confirm_navigation_and_escape() {
+        let mut modals = ModalLayer::new();
+        let outer = modals.open(ModalDialog::confirm("Discard", "Discard layer?"));
+        let inner =
+            modals.open(ModalDialog::confirm("Overwrite", "Overwrite file?").default_cancel());
+        assert_eq!(
+            modals.handle_key(ModalKey::Enter),
+            Some((inner, ModalResult::Cancelled))
+        );
+        assert_eq!(modals.handle_key(ModalKey::Tab), None);
+        assert_eq!(modals.handle_key(ModalKey::Right), None);
+        assert_eq!(
+            modals.handle_key(ModalKey::Enter),
+            Some((outer, ModalResult::Confirmed))
+        );
+
+        let id = modals.open(ModalDialog::confirm("Quit", "Really quit?"));
+        assert_eq!(
+            modals.handle_key(ModalKey::Escape),
+            Some((id, ModalResult::Cancelled))
+        );
+        assert!(modals.handle_key(ModalKey::Enter).is_none());
+    }
+
+    #[test]
+    fn modal_blocks_window_hover_and_accepts_clicks() {
+        let theme = WindowTheme::default();
+        let mut manager = OverlayWindowManager::new(theme);
+        manager.insert(OverlayWindow::new(
+            WindowId::DebugTabs,
+            Vector2::new(0.0, 0.0),
+            (1280, 720),
+            (120, 120),
+        ));
+        manager.clamp_all((1280, 720));
+        let inside = Vector2::new(200.0, 200.0);
+        assert_eq!(manager.handle_hover(inside), Some(WindowId::DebugTabs));
+
+        let id = manager
+            .modals_mut()
+            .open(ModalDialog::confirm("Load", "Load world?"));
+        assert_eq!(manager.handle_hover(inside), None);
+        assert_eq!(
+            manager.get(WindowId::DebugTabs).map(|w| w.hover_region()),
+            Some(HitRegion::None)
+        );
+
+        let frame = manager
+            .modals_mut()
+            .layout((1280, 720), &theme)
+            .expect("modal frame");
+        let click = Vector2::new(
+            (frame.confirm.x + frame.confirm.w / 2) as f32,
+            (frame.confirm.y + frame.confirm.h / 2) as f32,
+        );
+        assert!(manager.modals_mut().handle_click(inside).is_none());
+        assert_eq!(
+            manager.modals_mut().handle_click(click),
+            Some((id, ModalResult::Confirmed))
+        );
+        assert!(!manager.has_modal());
+        assert_eq!(manager.handle_hover(inside), Some(WindowId::DebugTabs));
+    }
 }
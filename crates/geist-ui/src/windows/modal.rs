@@ -0,0 +1,510 @@
+use raylib::prelude::{Color, RaylibDraw, Vector2};
+
+use crate::text::UiTextRenderer;
+
+use super::util::{blend_color, scale_alpha};
+use super::{IRect, WindowTheme};
+
+const MODAL_WIDTH: i32 = 440;
+const MODAL_BUTTON_WIDTH: i32 = 112;
+const MODAL_LINE_SPACING: i32 = 4;
+const PROMPT_MAX_LEN_DEFAULT: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ModalId(u64);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ModalResult {
+    Confirmed,
+    Cancelled,
+    Submitted(String),
+}
+
+/// Keyboard input understood by modal dialogs; the host maps its own key codes onto these.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModalKey {
+    Enter,
+    Escape,
+    Tab,
+    Left,
+    Right,
+    Backspace,
+    Char(char),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModalButton {
+    Confirm,
+    Cancel,
+}
+
+impl ModalButton {
+    fn other(self) -> Self {
+        match self {
+            Self::Confirm => Self::Cancel,
+            Self::Cancel => Self::Confirm,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ModalKind {
+    Confirm,
+    TextPrompt {
+        text: String,
+        placeholder: String,
+        max_len: usize,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct ModalDialog {
+    title: String,
+    message: String,
+    confirm_label: String,
+    cancel_label: String,
+    kind: ModalKind,
+    focused: ModalButton,
+}
+
+impl ModalDialog {
+    /// Yes/no confirmation; Enter confirms, Escape cancels.
+    pub fn confirm(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            message: message.into(),
+            confirm_label: "OK".to_string(),
+            cancel_label: "Cancel".to_string(),
+            kind: ModalKind::Confirm,
+            focused: ModalButton::Confirm,
+        }
+    }
+
+    /// Single-line text prompt; Enter submits the current text, Escape cancels.
+    pub fn prompt(
+        title: impl Into<String>,
+        message: impl Into<String>,
+        initial: impl Into<String>,
+    ) -> Self {
+        Self {
+            title: title.into(),
+            message: message.into(),
+            confirm_label: "OK".to_string(),
+            cancel_label: "Cancel".to_string(),
+            kind: ModalKind::TextPrompt {
+                text: initial.into(),
+                placeholder: String::new(),
+                max_len: PROMPT_MAX_LEN_DEFAULT,
+            },
+            focused: ModalButton::Confirm,
+        }
+    }
+
+    pub fn with_labels(mut self, confirm: impl Into<String>, cancel: impl Into<String>) -> Self {
+        self.confirm_label = confirm.into();
+        self.cancel_label = cancel.into();
+        self
+    }
+
+    pub fn with_placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        if let ModalKind::TextPrompt {
+            placeholder: ref mut slot,
+            ..
+        } = self.kind
+        {
+            *slot = placeholder.into();
+        }
+        self
+    }
+
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        if let ModalKind::TextPrompt {
+            ref mut text,
+            max_len: ref mut slot,
+            ..
+        } = self.kind
+        {
+            *slot = max_len.max(1);
+            truncate_chars(text, *slot);
+        }
+        self
+    }
+
+    /// Start with the cancel button focused, so a stray Enter does not trigger the action.
+    pub fn default_cancel(mut self) -> Self {
+        self.focused = ModalButton::Cancel;
+        self
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn focused(&self) -> ModalButton {
+        self.focused
+    }
+
+    pub fn is_prompt(&self) -> bool {
+        matches!(self.kind, ModalKind::TextPrompt { .. })
+    }
+
+    pub fn text(&self) -> Option<&str> {
+        match &self.kind {
+            ModalKind::TextPrompt { text, .. } => Some(text),
+            ModalKind::Confirm => None,
+        }
+    }
+
+    fn resolve(&self, button: ModalButton) -> ModalResult {
+        match (button, &self.kind) {
+            (ModalButton::Cancel, _) => ModalResult::Cancelled,
+            (ModalButton::Confirm, ModalKind::Confirm) => ModalResult::Confirmed,
+            (ModalButton::Confirm, ModalKind::TextPrompt { text, .. }) => {
+                ModalResult::Submitted(text.clone())
+            }
+        }
+    }
+
+    fn handle_key(&mut self, key: ModalKey) -> Option<ModalResult> {
+        match key {
+            ModalKey::Enter => Some(self.resolve(self.focused)),
+            ModalKey::Escape => Some(ModalResult::Cancelled),
+            ModalKey::Tab | ModalKey::Left | ModalKey::Right => {
+                self.focused = self.focused.other();
+                None
+            }
+            ModalKey::Backspace => {
+                if let ModalKind::TextPrompt { text, .. } = &mut self.kind {
+                    text.pop();
+                }
+                None
+            }
+            ModalKey::Char(ch) => {
+                if let ModalKind::TextPrompt { text, max_len, .. } = &mut self.kind
+                    && !ch.is_control()
+                    && text.chars().count() < *max_len
+                {
+                    text.push(ch);
+                }
+                None
+            }
+        }
+    }
+
+    fn message_lines(&self) -> impl Iterator<Item = &str> {
+        self.message.lines()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ModalFrame {
+    pub outer: IRect,
+    pub titlebar: IRect,
+    pub body: IRect,
+    pub input: Option<IRect>,
+    pub confirm: IRect,
+    pub cancel: IRect,
+}
+
+impl ModalFrame {
+    pub fn button_at(&self, cursor: Vector2) -> Option<ModalButton> {
+        if self.confirm.contains(cursor) {
+            Some(ModalButton::Confirm)
+        } else if self.cancel.contains(cursor) {
+            Some(ModalButton::Cancel)
+        } else {
+            None
+        }
+    }
+}
+
+/// Stack of modal dialogs drawn above every overlay window. While any dialog is open the
+/// window manager stops reporting hover, so other windows cannot be interacted with.
+#[derive(Debug, Default)]
+pub struct ModalLayer {
+    stack: Vec<(ModalId, ModalDialog)>,
+    next_id: u64,
+    frame: Option<ModalFrame>,
+    hovered: Option<ModalButton>,
+}
+
+impl ModalLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn open(&mut self, dialog: ModalDialog) -> ModalId {
+        self.next_id = self.next_id.wrapping_add(1);
+        let id = ModalId(self.next_id);
+        self.stack.push((id, dialog));
+        self.frame = None;
+        self.hovered = None;
+        id
+    }
+
+    pub fn close(&mut self, id: ModalId) -> bool {
+        let before = self.stack.len();
+        self.stack.retain(|(existing, _)| *existing != id);
+        let removed = self.stack.len() != before;
+        if removed {
+            self.frame = None;
+            self.hovered = None;
+        }
+        removed
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.stack.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    pub fn active(&self) -> Option<(ModalId, &ModalDialog)> {
+        self.stack.last().map(|(id, dialog)| (*id, dialog))
+    }
+
+    pub fn frame(&self) -> Option<&ModalFrame> {
+        self.frame.as_ref()
+    }
+
+    pub fn hovered(&self) -> Option<ModalButton> {
+        self.hovered
+    }
+
+    /// Feed a key to the top dialog. Returns the dialog's result once it closes.
+    pub fn handle_key(&mut self, key: ModalKey) -> Option<(ModalId, ModalResult)> {
+        let (id, dialog) = self.stack.last_mut()?;
+        let id = *id;
+        let result = dialog.handle_key(key)?;
+        self.pop_top();
+        Some((id, result))
+    }
+
+    pub fn update_hover(&mut self, cursor: Vector2) {
+        self.hovered = self.frame.and_then(|frame| frame.button_at(cursor));
+    }
+
+    /// Resolve a click against the last laid-out frame. Clicks outside the buttons are swallowed.
+    pub fn handle_click(&mut self, cursor: Vector2) -> Option<(ModalId, ModalResult)> {
+        let button = self.frame?.button_at(cursor)?;
+        let (id, dialog) = self.stack.last()?;
+        let id = *id;
+        let result = dialog.resolve(button);
+        self.pop_top();
+        Some((id, result))
+    }
+
+    pub fn layout(&mut self, screen_size: (i32, i32), theme: &WindowTheme) -> Option<ModalFrame> {
+        let (_, dialog) = self.stack.last()?;
+        let line_h = theme.subtitle_font + MODAL_LINE_SPACING;
+        let lines = dialog.message_lines().count().max(1) as i32;
+        let field_h = theme.tab_height;
+        let width = MODAL_WIDTH
+            .min(screen_size.0 - theme.screen_padding * 2)
+            .max(1);
+
+        let mut body_h = theme.padding_y + lines * line_h;
+        if dialog.is_prompt() {
+            body_h += theme.padding_y + field_h;
+        }
+        body_h += theme.padding_y + field_h + theme.padding_y;
+        let height = theme.titlebar_height + body_h;
+
+        let x = (screen_size.0 - width) / 2;
+        let y = ((screen_size.1 - height) / 2).max(theme.screen_padding);
+        let outer = IRect::new(x, y, width, height);
+        let titlebar = IRect::new(x, y, width, theme.titlebar_height);
+        let body = IRect::new(x, y + theme.titlebar_height, width, body_h);
+
+        let inner_x = x + theme.padding_x;
+        let inner_w = (width - theme.padding_x * 2).max(0);
+        let mut cursor_y = body.y + theme.padding_y + lines * line_h;
+        let input = if dialog.is_prompt() {
+            cursor_y += theme.padding_y;
+            let rect = IRect::new(inner_x, cursor_y, inner_w, field_h);
+            cursor_y += field_h;
+            Some(rect)
+        } else {
+            None
+        };
+        cursor_y += theme.padding_y;
+
+        let button_w = MODAL_BUTTON_WIDTH.min((inner_w - theme.tab_gap) / 2).max(0);
+        let cancel = IRect::new(inner_x + inner_w - button_w, cursor_y, button_w, field_h);
+        let confirm = IRect::new(
+            cancel.x - theme.tab_gap - button_w,
+            cursor_y,
+            button_w,
+            field_h,
+        );
+
+        let frame = ModalFrame {
+            outer,
+            titlebar,
+            body,
+            input,
+            confirm,
+            cancel,
+        };
+        self.frame = Some(frame);
+        Some(frame)
+    }
+
+    /// Dim the screen and draw the top dialog. Call after every other overlay window.
+    pub fn draw<D>(&mut self, d: &mut D, screen_size: (i32, i32), theme: &WindowTheme, time: f32)
+    where
+        D: RaylibDraw + UiTextRenderer,
+    {
+        let Some(frame) = self.layout(screen_size, theme) else {
+            return;
+        };
+        let Some((_, dialog)) = self.stack.last() else {
+            return;
+        };
+
+        d.draw_rectangle(
+            0,
+            0,
+            screen_size.0,
+            screen_size.1,
+            scale_alpha(theme.frame_shadow, 0.8),
+        );
+
+        let IRect { x, y, w, h } = frame.outer;
+        d.draw_rectangle(x + 6, y + 8, w, h, theme.frame_shadow);
+        d.draw_rectangle(x, y, w, h, theme.frame_color);
+        d.draw_rectangle(
+            frame.titlebar.x,
+            frame.titlebar.y,
+            frame.titlebar.w,
+            frame.titlebar.h,
+            theme.title_bottom,
+        );
+        d.draw_rectangle(
+            frame.body.x,
+            frame.body.y,
+            frame.body.w,
+            frame.body.h,
+            theme.body_color,
+        );
+        d.draw_rectangle(x, y, w, 1, theme.top_highlight);
+        d.draw_rectangle(x, y + theme.titlebar_height - 1, w, 1, theme.title_border);
+        d.draw_rectangle_lines(x - 1, y - 1, w + 2, h + 2, theme.focus_glow);
+        d.draw_rectangle_lines(x, y, w, h, theme.focus_outline);
+
+        let title_y = y + (theme.titlebar_height - theme.title_font) / 2;
+        d.ui_draw_text(
+            &dialog.title,
+            x + theme.padding_x,
+            title_y,
+            theme.title_font,
+            theme.title_text,
+        );
+
+        let line_h = theme.subtitle_font + MODAL_LINE_SPACING;
+        let mut line_y = frame.body.y + theme.padding_y;
+        for line in dialog.message_lines() {
+            d.ui_draw_text(
+                line,
+                x + theme.padding_x,
+                line_y,
+                theme.subtitle_font,
+                theme.subtitle_text,
+            );
+            line_y += line_h;
+        }
+
+        if let (
+            Some(input),
+            ModalKind::TextPrompt {
+                text, placeholder, ..
+            },
+        ) = (frame.input, &dialog.kind)
+        {
+            d.draw_rectangle(
+                input.x,
+                input.y,
+                input.w,
+                input.h,
+                theme.tab_inactive_background,
+            );
+            d.draw_rectangle_lines(input.x, input.y, input.w, input.h, theme.focus_outline);
+            let text_x = input.x + theme.tab_padding_x / 2;
+            let text_y = input.y + (input.h - theme.tab_font) / 2;
+            if text.is_empty() {
+                d.ui_draw_text(
+                    placeholder,
+                    text_x,
+                    text_y,
+                    theme.tab_font,
+                    scale_alpha(theme.tab_text_inactive, 0.6),
+                );
+            } else {
+                d.ui_draw_text(text, text_x, text_y, theme.tab_font, theme.tab_text_active);
+            }
+            // Blinking caret after the text.
+            if time.fract() < 0.5 {
+                let caret_x = text_x + d.ui_measure_text(text, theme.tab_font) + 2;
+                d.draw_rectangle(caret_x, text_y, 2, theme.tab_font, theme.tab_text_active);
+            }
+        }
+
+        for (button, rect, label) in [
+            (ModalButton::Confirm, frame.confirm, &dialog.confirm_label),
+            (ModalButton::Cancel, frame.cancel, &dialog.cancel_label),
+        ] {
+            let focused = dialog.focused == button;
+            let hovered = self.hovered == Some(button);
+            let mut fill: Color = if focused {
+                theme.button_active
+            } else {
+                theme.button_normal
+            };
+            if hovered {
+                fill = blend_color(fill, theme.button_hover, 0.6);
+            }
+            d.draw_rectangle(rect.x, rect.y, rect.w, rect.h, fill);
+            let border = if focused {
+                theme.focus_outline
+            } else {
+                theme.inner_outline
+            };
+            d.draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, border);
+            let label_w = d.ui_measure_text(label, theme.tab_font);
+            let label_color = if hovered || focused {
+                theme.button_icon_hover
+            } else {
+                theme.button_icon
+            };
+            d.ui_draw_text(
+                label,
+                rect.x + (rect.w - label_w) / 2,
+                rect.y + (rect.h - theme.tab_font) / 2,
+                theme.tab_font,
+                label_color,
+            );
+        }
+    }
+
+    fn pop_top(&mut self) {
+        self.stack.pop();
+        self.frame = None;
+        self.hovered = None;
+    }
+}
+
+fn truncate_chars(text: &mut String, max_chars: usize) {
+    if let Some((idx, _)) = text.char_indices().nth(max_chars) {
+        text.truncate(idx);
+    }
+}
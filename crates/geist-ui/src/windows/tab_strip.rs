@@ -2,8 +2,12 @@ use raylib::prelude::{RaylibDraw, Vector2};
 
 use crate::text::{UiTextMeasure, UiTextRenderer};
 
+use super::util::scale_alpha;
 use super::{IRect, WindowFrame, WindowTheme};
 
+/// Cursor travel before a pressed tab starts reordering.
+const TAB_DRAG_THRESHOLD: f32 = 4.0;
+
 #[derive(Debug, Clone)]
 pub struct TabDefinition<'a> {
     pub title: &'a str,
@@ -17,7 +21,10 @@ impl<'a> TabDefinition<'a> {
 
 #[derive(Debug, Clone)]
 pub struct TabSlot<'a> {
+    /// Index into the definitions passed to `TabStrip::layout`.
     pub index: usize,
+    /// Position in the display order.
+    pub position: usize,
     pub title: &'a str,
     pub bounds: IRect,
     pub text_pos: Vector2,
@@ -31,11 +38,29 @@ impl TabSlot<'_> {
     }
 }
 
+/// Scroll arrows shown when the tabs do not fit the strip.
+#[derive(Debug, Clone, Copy)]
+pub struct TabScrollButtons {
+    pub left: IRect,
+    pub right: IRect,
+    pub can_scroll_left: bool,
+    pub can_scroll_right: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabStripHit {
+    Tab(usize),
+    ScrollLeft,
+    ScrollRight,
+}
+
 #[derive(Debug, Clone)]
 pub struct TabStripLayout<'a> {
     pub strip: IRect,
     pub content: IRect,
+    /// Visible tabs, left to right.
     pub tabs: Vec<TabSlot<'a>>,
+    pub scroll: Option<TabScrollButtons>,
 }
 
 impl TabStripLayout<'_> {
@@ -47,20 +72,151 @@ impl TabStripLayout<'_> {
             .map(|slot| slot.index)
     }
 
+    pub fn hit(&self, cursor: Vector2) -> Option<TabStripHit> {
+        if let Some(buttons) = &self.scroll {
+            if buttons.left.contains(cursor) {
+                return Some(TabStripHit::ScrollLeft);
+            }
+            if buttons.right.contains(cursor) {
+                return Some(TabStripHit::ScrollRight);
+            }
+        }
+        self.hovered(cursor).map(TabStripHit::Tab)
+    }
+
     #[inline]
     pub fn content_rect(&self) -> IRect {
         self.content
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+struct TabDrag {
+    index: usize,
+    origin_x: f32,
+    moved: bool,
+}
+
+/// Tab order, scroll position and in-progress drag for one strip. Owners keep it across
+/// frames so a reordered strip stays that way until the owner resets it.
+#[derive(Debug, Clone, Default)]
+pub struct TabStripState {
+    order: Vec<usize>,
+    first_visible: usize,
+    drag: Option<TabDrag>,
+}
+
+impl TabStripState {
+    /// State with an explicit display order, e.g. one restored by the owner.
+    pub fn with_order(order: Vec<usize>) -> Self {
+        Self {
+            order,
+            ..Self::default()
+        }
+    }
+
+    /// Display order for `count` tabs. Unknown indices are dropped and tabs missing from
+    /// the stored order are appended, so adding tabs never hides them.
+    pub fn order(&self, count: usize) -> Vec<usize> {
+        let mut order: Vec<usize> = Vec::with_capacity(count);
+        for &index in &self.order {
+            if index < count && !order.contains(&index) {
+                order.push(index);
+            }
+        }
+        for index in 0..count {
+            if !order.contains(&index) {
+                order.push(index);
+            }
+        }
+        order
+    }
+
+    #[inline]
+    pub fn first_visible(&self) -> usize {
+        self.first_visible
+    }
+
+    /// Scroll by whole tabs; `layout` clamps the result to the tabs that exist.
+    pub fn scroll_by(&mut self, tabs: i32) {
+        self.first_visible = (self.first_visible as i64 + tabs as i64).max(0) as usize;
+    }
+
+    #[inline]
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// The tab being dragged once it has moved past the drag threshold.
+    pub fn dragged_tab(&self) -> Option<usize> {
+        self.drag.filter(|drag| drag.moved).map(|drag| drag.index)
+    }
+
+    pub fn begin_drag(&mut self, index: usize, cursor: Vector2) {
+        self.drag = Some(TabDrag {
+            index,
+            origin_x: cursor.x,
+            moved: false,
+        });
+    }
+
+    /// Move the dragged tab into the slot under the cursor once the cursor crosses that
+    /// slot's midpoint. Returns true when the order changed.
+    pub fn update_drag(
+        &mut self,
+        layout: &TabStripLayout<'_>,
+        count: usize,
+        cursor: Vector2,
+    ) -> bool {
+        let Some(drag) = self.drag.as_mut() else {
+            return false;
+        };
+        if !drag.moved {
+            if (cursor.x - drag.origin_x).abs() < TAB_DRAG_THRESHOLD {
+                return false;
+            }
+            drag.moved = true;
+        }
+        let dragged = drag.index;
+        let mut order = self.order(count);
+        let Some(from) = order.iter().position(|&i| i == dragged) else {
+            return false;
+        };
+        let target = layout.tabs.iter().find(|slot| {
+            let mid = slot.bounds.x as f32 + slot.bounds.w as f32 * 0.5;
+            (slot.position > from && cursor.x >= mid) || (slot.position < from && cursor.x <= mid)
+        });
+        let Some(to) = target.map(|slot| slot.position) else {
+            return false;
+        };
+        let index = order.remove(from);
+        order.insert(to, index);
+        self.order = order;
+        true
+    }
+
+    pub fn end_drag(&mut self) {
+        self.drag = None;
+    }
+
+    /// Forget the custom order and scroll position.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
 pub struct TabStrip;
 
 impl TabStrip {
+    /// Lay out `tabs` in `state`'s display order. Tabs shrink towards `tab_min_width`
+    /// first; if they still overflow, scroll arrows take the right end of the strip and
+    /// only the tabs from `state.first_visible()` that fit are placed.
     pub fn layout<'a, D>(
         d: &D,
         theme: &WindowTheme,
         frame: &WindowFrame,
         tabs: &'a [TabDefinition<'a>],
+        state: &mut TabStripState,
     ) -> TabStripLayout<'a>
     where
         D: UiTextMeasure,
@@ -82,6 +238,7 @@ impl TabStrip {
                 strip,
                 content: adjusted_content,
                 tabs: Vec::new(),
+                scroll: None,
             };
         }
 
@@ -133,10 +290,55 @@ impl TabStrip {
             }
         }
 
-        let mut tabs_layout = Vec::with_capacity(tabs.len());
+        let order = state.order(tabs.len());
+        let width_of = |index: usize| widths[index];
         let mut x = strip.x + theme.tab_strip_padding;
-        for (index, (tab, width)) in tabs.iter().zip(widths.iter()).enumerate() {
-            let clamped_width = (*width).max(theme.tab_min_width);
+        let mut tabs_end = strip.x + strip.w - theme.tab_strip_padding;
+        let mut first = 0;
+        let mut scroll = None;
+        let fitted_total = widths.iter().sum::<i32>() + gap_total;
+        if fitted_total > available_width && available_width > 0 {
+            let button_w = theme
+                .tab_scroll_button_width
+                .min(available_width / 4)
+                .max(1);
+            let right = IRect::new(tabs_end - button_w, strip.y, button_w, strip.h);
+            let left = IRect::new(right.x - button_w, strip.y, button_w, strip.h);
+            tabs_end = left.x - theme.tab_gap;
+            let viewport = (tabs_end - x).max(0);
+
+            // Furthest first tab that still leaves the strip full.
+            let mut max_first = order.len() - 1;
+            let mut used = 0;
+            for (position, &index) in order.iter().enumerate().rev() {
+                let need = if used == 0 {
+                    width_of(index)
+                } else {
+                    used + theme.tab_gap + width_of(index)
+                };
+                if used > 0 && need > viewport {
+                    break;
+                }
+                used = need;
+                max_first = position;
+            }
+            first = state.first_visible.min(max_first);
+            scroll = Some(TabScrollButtons {
+                left,
+                right,
+                can_scroll_left: first > 0,
+                can_scroll_right: false,
+            });
+        }
+        state.first_visible = first;
+
+        let mut tabs_layout = Vec::with_capacity(tabs.len());
+        for (position, &index) in order.iter().enumerate().skip(first) {
+            let tab = &tabs[index];
+            let clamped_width = width_of(index);
+            if !tabs_layout.is_empty() && x + clamped_width > tabs_end {
+                break;
+            }
             let bounds = IRect::new(x, strip.y, clamped_width, strip.h);
             let text_width = d.ui_measure_text(tab.title, base_font);
             let text_x = x + (clamped_width - text_width) / 2;
@@ -147,21 +349,27 @@ impl TabStrip {
             }
             tabs_layout.push(TabSlot {
                 index,
+                position,
                 title: tab.title,
                 bounds,
                 text_pos: Vector2::new(text_x as f32, text_y as f32),
                 text_width,
             });
             x += clamped_width + theme.tab_gap;
-            if x > strip.x + strip.w {
+            if x > tabs_end {
                 break;
             }
         }
+        if let Some(buttons) = scroll.as_mut() {
+            let last = tabs_layout.last().map_or(0, |slot| slot.position);
+            buttons.can_scroll_right = last + 1 < order.len();
+        }
 
         TabStripLayout {
             strip,
             content: adjusted_content,
             tabs: tabs_layout,
+            scroll,
         }
     }
 
@@ -170,7 +378,7 @@ impl TabStrip {
         theme: &WindowTheme,
         layout: &TabStripLayout<'_>,
         selected: usize,
-        hovered: Option<usize>,
+        hovered: Option<TabStripHit>,
     ) where
         D: RaylibDraw + UiTextRenderer,
     {
@@ -195,7 +403,7 @@ impl TabStrip {
 
         for slot in &layout.tabs {
             let is_selected = slot.index == selected;
-            let is_hovered = hovered == Some(slot.index);
+            let is_hovered = hovered == Some(TabStripHit::Tab(slot.index));
             let (bg, border, text) = if is_selected {
                 (
                     theme.tab_active_background,
@@ -238,5 +446,53 @@ impl TabStrip {
                 text,
             );
         }
+
+        if let Some(buttons) = &layout.scroll {
+            let arrows = [
+                (
+                    buttons.left,
+                    "<",
+                    buttons.can_scroll_left,
+                    TabStripHit::ScrollLeft,
+                ),
+                (
+                    buttons.right,
+                    ">",
+                    buttons.can_scroll_right,
+                    TabStripHit::ScrollRight,
+                ),
+            ];
+            for (rect, glyph, enabled, hit) in arrows {
+                let (bg, border, text) = if enabled && hovered == Some(hit) {
+                    (
+                        theme.tab_hover_background,
+                        theme.tab_hover_border,
+                        theme.tab_text_active,
+                    )
+                } else if enabled {
+                    (
+                        theme.tab_inactive_background,
+                        theme.tab_inactive_border,
+                        theme.tab_text_inactive,
+                    )
+                } else {
+                    (
+                        theme.tab_strip_background,
+                        theme.tab_inactive_border,
+                        scale_alpha(theme.tab_text_inactive, 0.35),
+                    )
+                };
+                d.draw_rectangle(rect.x, rect.y, rect.w, rect.h, bg);
+                d.draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, border);
+                let glyph_w = d.ui_measure_text(glyph, theme.tab_font);
+                d.ui_draw_text(
+                    glyph,
+                    rect.x + (rect.w - glyph_w) / 2,
+                    rect.y + (rect.h - theme.tab_font) / 2,
+                    theme.tab_font,
+                    text,
+                );
+            }
+        }
     }
 }
@@ -35,6 +35,7 @@ pub struct WindowTheme {
     pub tab_content_spacing: i32,
     pub tab_font: i32,
     pub tab_min_width: i32,
+    pub tab_scroll_button_width: i32,
     pub tab_strip_background: Color,
     pub tab_active_background: Color,
     pub tab_active_border: Color,
@@ -93,6 +94,7 @@ impl Default for WindowTheme {
             tab_content_spacing: 12,
             tab_font: 18,
             tab_min_width: 120,
+            tab_scroll_button_width: 24,
             tab_strip_background: Color::new(22, 28, 40, 235),
             tab_active_background: Color::new(68, 108, 176, 240),
             tab_active_border: Color::new(28, 48, 78, 255),
@@ -116,3 +118,32 @@ impl Default for WindowTheme {
         }
     }
 }
+
+impl WindowTheme {
+    /// A copy with every metric (paddings, bar heights, font sizes) multiplied by `scale`;
+    /// colors are unchanged.
+    pub fn scaled(&self, scale: f32) -> Self {
+        let px = |v: i32| ((v as f32 * scale).round() as i32).max(1);
+        Self {
+            padding_x: px(self.padding_x),
+            padding_y: px(self.padding_y),
+            titlebar_height: px(self.titlebar_height),
+            resize_handle: px(self.resize_handle),
+            screen_padding: px(self.screen_padding),
+            title_font: px(self.title_font),
+            subtitle_font: px(self.subtitle_font),
+            tab_height: px(self.tab_height),
+            tab_padding_x: px(self.tab_padding_x),
+            tab_padding_y: px(self.tab_padding_y),
+            tab_gap: px(self.tab_gap),
+            tab_strip_padding: px(self.tab_strip_padding),
+            tab_content_spacing: px(self.tab_content_spacing),
+            tab_font: px(self.tab_font),
+            tab_min_width: px(self.tab_min_width),
+            tab_scroll_button_width: px(self.tab_scroll_button_width),
+            title_button_spacing: px(self.title_button_spacing),
+            title_button_size: px(self.title_button_size),
+            ..*self
+        }
+    }
+}
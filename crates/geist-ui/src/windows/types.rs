@@ -6,6 +6,8 @@ pub enum WindowId {
     DiagnosticsTabs,
     Minimap,
     ChunkVoxels,
+    SchematicLibrary,
+    Settings,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -0,0 +1,109 @@
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+
+use geist_world::{CHUNK_SIZE, NoiseBackend, NoiseField, World, WorldGenMode};
+
+const SEED: i32 = 1337;
+
+fn tile_coords(base_x: i32, base_z: i32, size: usize) -> (Vec<f32>, Vec<f32>) {
+    let mut xs = Vec::with_capacity(size * size);
+    let mut zs = Vec::with_capacity(size * size);
+    for dz in 0..size {
+        for dx in 0..size {
+            xs.push((base_x + dx as i32) as f32);
+            zs.push((base_z + dz as i32) as f32);
+        }
+    }
+    (xs, zs)
+}
+
+fn bench_noise_2d_tile(c: &mut Criterion) {
+    let mut group = c.benchmark_group("noise_2d_tile");
+    let (xs, zs) = tile_coords(-40, 75, CHUNK_SIZE);
+    let mut out = vec![0.0; xs.len()];
+    let point = NoiseField::open_simplex2(SEED, 0.01, NoiseBackend::Scalar);
+    group.bench_function("point_64x64", |b| {
+        b.iter(|| {
+            for i in 0..xs.len() {
+                out[i] = point.get_noise_2d(xs[i], zs[i]);
+            }
+            black_box(&out);
+        })
+    });
+    for (name, backend) in [
+        ("batch_scalar_64x64", NoiseBackend::Scalar),
+        ("batch_simd_64x64", NoiseBackend::Simd),
+    ] {
+        let field = NoiseField::open_simplex2(SEED, 0.01, backend);
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                field.sample_2d_batch(&xs, &zs, &mut out);
+                black_box(&out);
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_noise_3d_column(c: &mut Criterion) {
+    let mut group = c.benchmark_group("noise_3d_column");
+    let n = CHUNK_SIZE;
+    let xs = vec![113.0_f32; n];
+    let zs = vec![-57.0_f32; n];
+    let ys: Vec<f32> = (0..n).map(|y| y as f32).collect();
+    let mut out = vec![0.0; n];
+    let point = NoiseField::open_simplex2(SEED, 0.017, NoiseBackend::Scalar);
+    group.bench_function("point_64", |b| {
+        b.iter(|| {
+            for i in 0..n {
+                out[i] = point.get_noise_3d(xs[i], ys[i], zs[i]);
+            }
+            black_box(&out);
+        })
+    });
+    for (name, backend) in [
+        ("batch_scalar_64", NoiseBackend::Scalar),
+        ("batch_simd_64", NoiseBackend::Simd),
+    ] {
+        let field = NoiseField::open_simplex2(SEED, 0.017, backend);
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                field.sample_3d_batch(&xs, &ys, &zs, &mut out);
+                black_box(&out);
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_prepare_height_tile(c: &mut Criterion) {
+    let mut group = c.benchmark_group("prepare_height_tile");
+    let world = World::new(4, 8, 4, SEED, WorldGenMode::Normal);
+    for (name, backend) in [
+        ("scalar_64x64", NoiseBackend::Scalar),
+        ("simd_64x64", NoiseBackend::Simd),
+    ] {
+        world.set_noise_backend(backend);
+        let mut ctx = world.make_gen_ctx();
+        let mut tile = 0i32;
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                // A fresh tile each time so the shared tile cache never short-circuits.
+                tile += 1;
+                let base_x = tile * CHUNK_SIZE as i32;
+                world.prepare_height_tile(&mut ctx, base_x, 0, CHUNK_SIZE, CHUNK_SIZE);
+                black_box(&ctx.height_tile);
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_noise_2d_tile,
+    bench_noise_3d_column,
+    bench_prepare_height_tile
+);
+criterion_main!(benches);
@@ -0,0 +1,365 @@
+//! Dimensions: several independent worlds in one session.
+//!
+//! A [`DimensionSet`] holds the overworld plus any number of extra `World`s, each with its
+//! own seed, terrain generator and lighting setup. The app keeps one dimension active and
+//! streams only that one; switching swaps the streaming target without touching the others.
+//! [`DimensionSet::with_builtin`] adds a cave dimension (solid rock hollowed into caverns,
+//! no sky) and a sky dimension (floating islands over the void).
+
+use std::sync::Arc;
+
+use geist_blocks::registry::BlockRegistry;
+
+use crate::voxel::generation::TerrainGenerator;
+use crate::voxel::{NoiseBackend, NoiseField, World};
+
+/// Index of a dimension in its [`DimensionSet`]; the overworld is always 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DimensionId(pub u8);
+
+impl DimensionId {
+    pub const OVERWORLD: Self = Self(0);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DimensionKind {
+    Overworld,
+    Caves,
+    Sky,
+}
+
+impl DimensionKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Overworld => "overworld",
+            Self::Caves => "caves",
+            Self::Sky => "sky",
+        }
+    }
+}
+
+/// How a dimension is lit. Applied to the dimension's own `LightingStore` by the app.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DimensionLighting {
+    /// Sunlight from above; without it only emitters and the night ambient floor light.
+    pub skylight: bool,
+    /// Overrides the session's night ambient floor.
+    pub night_ambient: Option<u8>,
+    /// Below the world is open void rather than solid ground.
+    pub void_below: bool,
+}
+
+impl Default for DimensionLighting {
+    fn default() -> Self {
+        Self {
+            skylight: true,
+            night_ambient: None,
+            void_below: false,
+        }
+    }
+}
+
+pub struct Dimension {
+    pub id: DimensionId,
+    pub name: String,
+    pub kind: DimensionKind,
+    pub world: Arc<World>,
+    pub lighting: DimensionLighting,
+}
+
+impl Dimension {
+    /// A place to stand near column `(wx, wz)`, as (x, feet y, z): the lowest air voxel
+    /// over ground with headroom, searching outwards ring by ring. `None` if no column
+    /// within `radius` has one.
+    pub fn find_spawn(
+        &self,
+        reg: &BlockRegistry,
+        wx: i32,
+        wz: i32,
+        radius: i32,
+    ) -> Option<(i32, i32, i32)> {
+        const STEP: i32 = 4;
+        let world = &self.world;
+        let mut ctx = world.make_gen_ctx();
+        let air = reg.id_by_name("air").unwrap_or(0);
+        let top = world.world_height_hint() as i32;
+        let mut column = |x: i32, z: i32| {
+            let mut below = false;
+            let mut open = 0;
+            for y in 0..top {
+                let is_air = world.block_at_runtime_with(reg, &mut ctx, x, y, z).id == air;
+                if !is_air {
+                    below = true;
+                    open = 0;
+                } else if below {
+                    open += 1;
+                    if open == 2 {
+                        return Some(y - 1);
+                    }
+                }
+            }
+            None
+        };
+        for r in (0..=radius.max(0)).step_by(STEP as usize) {
+            for dz in (-r..=r).step_by(STEP as usize) {
+                for dx in (-r..=r).step_by(STEP as usize) {
+                    if dx.abs() != r && dz.abs() != r {
+                        continue;
+                    }
+                    if let Some(y) = column(wx + dx, wz + dz) {
+                        return Some((wx + dx, y, wz + dz));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Seed of a dimension derived from the session seed, so every dimension differs but is
+/// reproducible.
+pub fn dimension_seed(seed: i32, kind: DimensionKind) -> i32 {
+    match kind {
+        DimensionKind::Overworld => seed,
+        DimensionKind::Caves => seed ^ 0x0CA7_E500,
+        DimensionKind::Sky => seed.wrapping_mul(31) ^ 0x05C1_E500,
+    }
+}
+
+pub struct DimensionSet {
+    dims: Vec<Dimension>,
+}
+
+impl DimensionSet {
+    pub fn new(overworld: Arc<World>) -> Self {
+        Self {
+            dims: vec![Dimension {
+                id: DimensionId::OVERWORLD,
+                name: DimensionKind::Overworld.label().to_string(),
+                kind: DimensionKind::Overworld,
+                world: overworld,
+                lighting: DimensionLighting::default(),
+            }],
+        }
+    }
+
+    /// The overworld plus the built-in cave and sky dimensions, sized like the overworld.
+    pub fn with_builtin(overworld: Arc<World>) -> Self {
+        let (cx, cy, cz, seed, extent) = (
+            overworld.chunks_x,
+            overworld.chunks_y_hint,
+            overworld.chunks_z,
+            overworld.seed,
+            overworld.extent(),
+        );
+        let height = overworld.world_height_hint() as i32;
+        let mut set = Self::new(overworld);
+        let caves_seed = dimension_seed(seed, DimensionKind::Caves);
+        set.add(
+            DimensionKind::Caves,
+            World::with_generator(
+                cx,
+                cy,
+                cz,
+                caves_seed,
+                Arc::new(CaveDimension::new(caves_seed, height)),
+            )
+            .with_extent(extent),
+            DimensionLighting {
+                skylight: false,
+                night_ambient: Some(40),
+                void_below: false,
+            },
+        );
+        let sky_seed = dimension_seed(seed, DimensionKind::Sky);
+        set.add(
+            DimensionKind::Sky,
+            World::with_generator(
+                cx,
+                cy,
+                cz,
+                sky_seed,
+                Arc::new(SkyDimension::new(sky_seed, height)),
+            )
+            .with_extent(extent),
+            DimensionLighting {
+                skylight: true,
+                night_ambient: None,
+                void_below: true,
+            },
+        );
+        set
+    }
+
+    /// Register `world` as a new dimension named after `kind`. Panics past 256 dimensions.
+    pub fn add(
+        &mut self,
+        kind: DimensionKind,
+        world: World,
+        lighting: DimensionLighting,
+    ) -> DimensionId {
+        let id = DimensionId(u8::try_from(self.dims.len()).expect("at most 256 dimensions"));
+        self.dims.push(Dimension {
+            id,
+            name: kind.label().to_string(),
+            kind,
+            world: Arc::new(world),
+            lighting,
+        });
+        id
+    }
+
+    #[inline]
+    pub fn get(&self, id: DimensionId) -> Option<&Dimension> {
+        self.dims.get(id.0 as usize)
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<&Dimension> {
+        self.dims.iter().find(|d| d.name == name)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.dims.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.dims.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Dimension> {
+        self.dims.iter()
+    }
+
+    /// The dimension after `id`, wrapping back to the overworld.
+    pub fn next(&self, id: DimensionId) -> DimensionId {
+        DimensionId(((id.0 as usize + 1) % self.dims.len().max(1)) as u8)
+    }
+}
+
+/// Deterministic per-voxel hash in [0, 1).
+fn hash01(seed: i32, x: i32, y: i32, z: i32) -> f32 {
+    let mut h = (seed as u32).wrapping_mul(0x9E37_79B9)
+        ^ (x as u32).wrapping_mul(0x85EB_CA6B)
+        ^ (y as u32).wrapping_mul(0xC2B2_AE35)
+        ^ (z as u32).wrapping_mul(0x27D4_EB2F);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2C1B_3C6D);
+    h ^= h >> 12;
+    (h >> 8) as f32 / (1u32 << 24) as f32
+}
+
+/// Solid rock up to a ceiling, hollowed into caverns lit by glowstone clusters.
+pub struct CaveDimension {
+    seed: i32,
+    ceiling: i32,
+    caverns: NoiseField,
+    floor: NoiseField,
+}
+
+impl CaveDimension {
+    const OPEN: f32 = 0.2;
+
+    pub fn new(seed: i32, height: i32) -> Self {
+        Self {
+            seed,
+            ceiling: height.clamp(32, 192),
+            caverns: NoiseField::open_simplex2(seed, 0.025, NoiseBackend::Scalar),
+            floor: NoiseField::open_simplex2(seed ^ 0x51, 0.05, NoiseBackend::Scalar),
+        }
+    }
+
+    fn open(&self, wx: i32, wy: i32, wz: i32) -> bool {
+        // Keep a few layers of floor and roof rock.
+        if wy < 4 || wy >= self.ceiling - 4 {
+            return false;
+        }
+        // Caverns flatten out towards the middle of the band so there is room to walk.
+        let band = (wy as f32 / self.ceiling as f32 - 0.5).abs() * 2.0;
+        let n = self
+            .caverns
+            .get_noise_3d(wx as f32, wy as f32 * 1.6, wz as f32);
+        n - band * 0.35 > Self::OPEN
+    }
+}
+
+impl TerrainGenerator for CaveDimension {
+    fn height(&self, _wx: i32, _wz: i32) -> i32 {
+        self.ceiling
+    }
+
+    fn surface_material(&self, wx: i32, wy: i32, wz: i32, height: i32) -> &str {
+        if wy >= height {
+            "air"
+        } else if self.floor.get_noise_2d(wx as f32, wz as f32) > 0.4 && self.open(wx, wy + 1, wz) {
+            "gravel"
+        } else {
+            "stone"
+        }
+    }
+
+    fn carve(&self, wx: i32, wy: i32, wz: i32, _height: i32) -> bool {
+        self.open(wx, wy, wz)
+    }
+
+    fn feature(&self, wx: i32, wy: i32, wz: i32, _height: i32, base: &str) -> Option<&str> {
+        // Glowstone hanging from cavern roofs.
+        let roof = base == "air" && !self.open(wx, wy + 1, wz);
+        (roof && hash01(self.seed, wx, wy, wz) < 0.015).then_some("glowstone")
+    }
+}
+
+/// Grassy floating islands scattered over open void.
+pub struct SkyDimension {
+    level: i32,
+    islands: NoiseField,
+    relief: NoiseField,
+}
+
+impl SkyDimension {
+    const THRESHOLD: f32 = 0.25;
+
+    pub fn new(seed: i32, height: i32) -> Self {
+        Self {
+            level: (height / 2).max(16),
+            islands: NoiseField::open_simplex2(seed, 0.012, NoiseBackend::Scalar),
+            relief: NoiseField::open_simplex2(seed ^ 0x77, 0.06, NoiseBackend::Scalar),
+        }
+    }
+
+    /// Island thickness in column `(wx, wz)`; zero over the void.
+    fn mass(&self, wx: i32, wz: i32) -> f32 {
+        let n = self.islands.get_noise_2d(wx as f32, wz as f32);
+        ((n - Self::THRESHOLD) / (1.0 - Self::THRESHOLD)).max(0.0)
+    }
+}
+
+impl TerrainGenerator for SkyDimension {
+    // The island top, or 0 over the void. The underside hangs twice as deep as the top
+    // rises above the island level.
+    fn height(&self, wx: i32, wz: i32) -> i32 {
+        let m = self.mass(wx, wz);
+        if m <= 0.0 {
+            return 0;
+        }
+        let bumps = self.relief.get_noise_2d(wx as f32, wz as f32) * 2.0;
+        self.level + 1 + (m * 10.0 + bumps).max(0.0) as i32
+    }
+
+    fn surface_material(&self, _wx: i32, wy: i32, _wz: i32, height: i32) -> &str {
+        if height <= 0 {
+            return "air";
+        }
+        let bottom = self.level - 2 * (height - self.level);
+        if wy >= height || wy < bottom {
+            "air"
+        } else if wy == height - 1 {
+            "grass"
+        } else if wy >= height - 3 {
+            "dirt"
+        } else {
+            "stone"
+        }
+    }
+}
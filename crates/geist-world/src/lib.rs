@@ -1,15 +1,25 @@
 //! World sizing, sampling, and worldgen parameters.
 #![forbid(unsafe_code)]
 
+pub mod dimension;
+pub mod prefab;
 pub mod voxel;
 pub mod worldgen;
 
+pub use dimension::{
+    CaveDimension, Dimension, DimensionId, DimensionKind, DimensionLighting, DimensionSet,
+    SkyDimension, dimension_seed,
+};
+pub use prefab::{Prefab, PrefabBlock, PrefabRegistry};
 pub use voxel::{
-    CHUNK_SIZE, ChunkCoord, ChunkTiming, GenCtx, HeightTileStats, TERRAIN_STAGE_COUNT,
-    TERRAIN_STAGE_LABELS, TerrainMetrics, TerrainStage, TerrainStageSample, TerrainTileCacheStats,
-    World, WorldGenMode,
+    CHUNK_SIZE, ChunkCoord, ChunkTiming, ColumnRebuild, GenCtx, HeightTileStats, NOISE_LANES,
+    NoiseBackend, NoiseField, TERRAIN_STAGE_COUNT, TERRAIN_STAGE_LABELS, TerrainMetrics,
+    TerrainStage, TerrainStageSample, TerrainTileCacheStats, World, WorldExtent, WorldGenMode,
+    WorldgenRebuildPlan, WorldgenRevTracker,
+    generation::{StageColumn, TerrainGenerator, TerrainStagePlugin},
+    nav::{ChunkNavSummary, NAV_MAX_STEP, NavEdge, NavGraph, SLOPE_CLASS_COUNT, SlopeClass},
     overview::{
-        OverviewError, OverviewMode, OverviewRegion, WorldOverview, WorldOverviewImage,
-        WorldOverviewJob,
+        CaveSlice, CaveSliceRange, OverviewError, OverviewMode, OverviewRegion, PngWriter,
+        RowDownsampler, WorldOverview, WorldOverviewImage, WorldOverviewJob, cave_contact_sheet,
     },
 };
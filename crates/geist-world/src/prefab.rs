@@ -0,0 +1,253 @@
+//! Prefabs: small hand-authored block templates (ruins, wells, dungeons) that prefab
+//! feature rules stamp into generated terrain.
+//!
+//! A prefab file is TOML with a legend of one-character keys and a stack of layers,
+//! bottom to top. Each layer is rows along +Z of characters along +X; a space leaves
+//! the generated terrain alone, so prefabs can be partial and blend into the ground.
+//!
+//! ```toml
+//! sink = 1
+//! layers = [
+//!   ["###", "#.#", "###"],
+//!   ["# #", "   ", "# #"],
+//! ]
+//! [legend]
+//! "#" = "mossy_cobblestone"
+//! "." = "air"
+//! ```
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+/// Horizontal directions in clockwise order, for rotating `facing` states.
+const FACINGS: [&str; 4] = ["north", "east", "south", "west"];
+
+/// One legend entry: a block name and its state properties, written `name[key=value,...]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrefabBlock {
+    pub name: String,
+    pub props: HashMap<String, String>,
+}
+
+impl PrefabBlock {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        let (name, props) = match spec.split_once('[') {
+            Some((name, rest)) => {
+                let inner = rest
+                    .strip_suffix(']')
+                    .ok_or_else(|| format!("unclosed state in block '{}'", spec))?;
+                let mut props = HashMap::new();
+                for pair in inner.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+                    let (k, v) = pair
+                        .split_once('=')
+                        .ok_or_else(|| format!("state '{}' in '{}' needs key=value", pair, spec))?;
+                    props.insert(k.trim().to_string(), v.trim().to_string());
+                }
+                (name, props)
+            }
+            None => (spec, HashMap::new()),
+        };
+        if name.is_empty() {
+            return Err("empty block name in prefab legend".to_string());
+        }
+        Ok(Self {
+            name: name.to_string(),
+            props,
+        })
+    }
+
+    /// State properties after turning the prefab `rotation` quarter turns clockwise
+    /// (seen from above): `facing` follows the turn and `axis` swaps x and z.
+    pub fn rotated_props(&self, rotation: u8) -> HashMap<String, String> {
+        let mut props = self.props.clone();
+        let turns = (rotation % 4) as usize;
+        if turns == 0 {
+            return props;
+        }
+        if let Some(facing) = props.get_mut("facing")
+            && let Some(i) = FACINGS.iter().position(|f| f == facing)
+        {
+            *facing = FACINGS[(i + turns) % 4].to_string();
+        }
+        if turns % 2 == 1
+            && let Some(axis) = props.get_mut("axis")
+        {
+            match axis.as_str() {
+                "x" => *axis = "z".to_string(),
+                "z" => *axis = "x".to_string(),
+                _ => {}
+            }
+        }
+        props
+    }
+}
+
+#[derive(Deserialize)]
+struct PrefabFile {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    sink: i32,
+    layers: Vec<Vec<String>>,
+    #[serde(default)]
+    legend: HashMap<String, String>,
+}
+
+/// A block template. Cells index `palette` or leave the terrain untouched.
+#[derive(Clone, Debug)]
+pub struct Prefab {
+    pub name: String,
+    /// Size along X, Y and Z before rotation.
+    pub size: (i32, i32, i32),
+    /// Layers buried below the surface, so foundations sit in the ground.
+    pub sink: i32,
+    pub palette: Vec<PrefabBlock>,
+    // Palette index + 1 per cell in (y, z, x) order; 0 keeps the terrain.
+    cells: Vec<u16>,
+}
+
+impl Prefab {
+    /// Parse a prefab file's contents; `name` is used unless the file sets its own.
+    pub fn from_toml_str(name: &str, src: &str) -> Result<Self, String> {
+        let file: PrefabFile =
+            toml::from_str(src).map_err(|e| format!("prefab '{}': {}", name, e))?;
+        let name = file.name.unwrap_or_else(|| name.to_string());
+        let fail = |msg: String| format!("prefab '{}': {}", name, msg);
+
+        let mut palette = Vec::new();
+        let mut keys: HashMap<char, u16> = HashMap::new();
+        for (key, spec) in &file.legend {
+            let mut chars = key.chars();
+            let (Some(c), None) = (chars.next(), chars.next()) else {
+                return Err(fail(format!("legend key '{}' must be one character", key)));
+            };
+            if c == ' ' {
+                return Err(fail("space is reserved for untouched terrain".to_string()));
+            }
+            palette.push(PrefabBlock::parse(spec).map_err(fail)?);
+            keys.insert(c, palette.len() as u16);
+        }
+
+        let sy = file.layers.len();
+        let sz = file.layers.iter().map(Vec::len).max().unwrap_or(0);
+        let sx = file
+            .layers
+            .iter()
+            .flatten()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        if sx == 0 || sy == 0 || sz == 0 {
+            return Err(fail("has no blocks".to_string()));
+        }
+        let mut cells = vec![0u16; sx * sy * sz];
+        for (y, layer) in file.layers.iter().enumerate() {
+            for (z, row) in layer.iter().enumerate() {
+                for (x, c) in row.chars().enumerate() {
+                    if c == ' ' {
+                        continue;
+                    }
+                    let idx = keys.get(&c).ok_or_else(|| {
+                        fail(format!(
+                            "'{}' at layer {} row {} is not in the legend",
+                            c, y, z
+                        ))
+                    })?;
+                    cells[(y * sz + z) * sx + x] = *idx;
+                }
+            }
+        }
+        Ok(Self {
+            name,
+            size: (sx as i32, sy as i32, sz as i32),
+            sink: file.sink.max(0),
+            palette,
+            cells,
+        })
+    }
+
+    /// Size along X and Z after `rotation` quarter turns.
+    pub fn footprint(&self, rotation: u8) -> (i32, i32) {
+        if rotation % 2 == 1 {
+            (self.size.2, self.size.0)
+        } else {
+            (self.size.0, self.size.2)
+        }
+    }
+
+    /// Palette index at `(x, y, z)` of the prefab turned `rotation` quarter turns
+    /// clockwise, in its rotated frame; `None` outside it or where it keeps the terrain.
+    pub fn cell(&self, rotation: u8, x: i32, y: i32, z: i32) -> Option<usize> {
+        let (sx, sy, sz) = self.size;
+        let (w, d) = self.footprint(rotation);
+        if x < 0 || z < 0 || y < 0 || x >= w || z >= d || y >= sy {
+            return None;
+        }
+        let (px, pz) = match rotation % 4 {
+            0 => (x, z),
+            1 => (z, sz - 1 - x),
+            2 => (sx - 1 - x, sz - 1 - z),
+            _ => (sx - 1 - z, x),
+        };
+        let v = self.cells[((y * sz + pz) * sx + px) as usize];
+        (v != 0).then(|| v as usize - 1)
+    }
+}
+
+/// Prefabs by name, shared by every chunk built with the same worldgen params.
+#[derive(Clone, Debug, Default)]
+pub struct PrefabRegistry {
+    prefabs: HashMap<String, Arc<Prefab>>,
+}
+
+impl PrefabRegistry {
+    /// Load every `*.toml` prefab in `dir`, named after its file stem unless it sets a
+    /// name. A missing directory gives an empty registry.
+    pub fn load_dir(dir: &Path) -> Result<Self, Box<dyn Error>> {
+        let mut reg = Self::default();
+        if !dir.is_dir() {
+            return Ok(reg);
+        }
+        let mut paths: Vec<_> = fs::read_dir(dir)?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|e| e == "toml"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            let stem = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let src = fs::read_to_string(&path)?;
+            reg.insert(Prefab::from_toml_str(&stem, &src)?);
+        }
+        Ok(reg)
+    }
+
+    /// Add or replace a prefab under its name.
+    pub fn insert(&mut self, prefab: Prefab) {
+        self.prefabs.insert(prefab.name.clone(), Arc::new(prefab));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<Prefab>> {
+        self.prefabs.get(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.prefabs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prefabs.is_empty()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.prefabs.keys().map(String::as_str)
+    }
+}
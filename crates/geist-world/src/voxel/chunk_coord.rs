@@ -1,3 +1,4 @@
+use geist_geom::ChunkPos;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -47,3 +48,15 @@ impl From<ChunkCoord> for (i32, i32, i32) {
         (value.cx, value.cy, value.cz)
     }
 }
+
+impl From<ChunkPos> for ChunkCoord {
+    fn from(value: ChunkPos) -> Self {
+        Self::new(value.0.x, value.0.y, value.0.z)
+    }
+}
+
+impl From<ChunkCoord> for ChunkPos {
+    fn from(value: ChunkCoord) -> Self {
+        ChunkPos::new(value.cx, value.cy, value.cz)
+    }
+}
@@ -1,22 +1,27 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use fastnoise_lite::FastNoiseLite;
-
 use crate::worldgen::WorldGenParams;
 
+use super::generation::LakeBasin;
+use super::noise::NoiseField;
 use super::tile_cache::{TerrainTile, TerrainTileCacheStats};
 
 pub struct GenCtx {
-    pub terrain: FastNoiseLite,
-    pub warp: FastNoiseLite,
-    pub tunnel: FastNoiseLite,
+    pub terrain: NoiseField,
+    pub warp: NoiseField,
+    pub tunnel: NoiseField,
     pub params: Arc<WorldGenParams>,
-    pub temp2d: Option<FastNoiseLite>,
-    pub moist2d: Option<FastNoiseLite>,
+    /// Worldgen revision `params` belong to; chunks generated with this context carry it.
+    pub worldgen_rev: u32,
+    pub temp2d: Option<NoiseField>,
+    pub moist2d: Option<NoiseField>,
     pub height_tile_stats: HeightTileStats,
     pub height_tile: Option<Arc<TerrainTile>>,
     pub tile_cache_stats: TerrainTileCacheStats,
     pub terrain_profiler: TerrainProfiler,
+    // Lake basins per lake cell, reused across chunks generated with this context.
+    pub(crate) lake_basins: HashMap<(i32, i32), Option<LakeBasin>>,
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -59,11 +64,13 @@ pub enum TerrainStage {
     Water,
     Caves,
     Trees,
+    /// Registered `TerrainStagePlugin` block passes.
+    Plugins,
 }
 
-pub const TERRAIN_STAGE_COUNT: usize = TerrainStage::Trees as usize + 1;
+pub const TERRAIN_STAGE_COUNT: usize = TerrainStage::Plugins as usize + 1;
 pub const TERRAIN_STAGE_LABELS: [&str; TERRAIN_STAGE_COUNT] = [
-    "Block", "Tower", "Height", "Surface", "Water", "Caves", "Trees",
+    "Block", "Tower", "Height", "Surface", "Water", "Caves", "Trees", "Plugins",
 ];
 
 #[derive(Clone, Debug, Default)]
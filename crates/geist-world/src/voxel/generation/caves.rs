@@ -1,14 +1,14 @@
 use std::collections::HashMap;
 use std::time::Instant;
 
-use fastnoise_lite::FastNoiseLite;
 use geist_blocks::registry::BlockRegistry;
 use geist_blocks::types::Block;
 
-use crate::worldgen::{Fractal, WorldGenParams};
+use crate::worldgen::{FeatureKind, Fractal, WorldGenParams};
 
 use super::super::World;
 use super::super::gen_ctx::TerrainStage;
+use super::super::noise::NoiseField;
 use super::column_sampler::ColumnSampler;
 
 #[derive(Default)]
@@ -32,6 +32,177 @@ impl BlockLookup {
     }
 }
 
+/// Which carver, if any, opens a voxel. Tunnels win where both overlap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CarveKind {
+    Solid,
+    Tunnel,
+    Room,
+}
+
+/// Classify a voxel below `height` the way the carvers would, ignoring the base block.
+pub(crate) fn classify_carve(
+    world: &World,
+    sampler: &ColumnSampler<'_, '_>,
+    x: i32,
+    y: i32,
+    z: i32,
+    height: i32,
+) -> CarveKind {
+    let params = sampler.params;
+    let soil = height as f32 - y as f32;
+    if !params.carvers_enable || soil <= params.soil_min || y as f32 <= params.min_y {
+        return CarveKind::Solid;
+    }
+    carve_kind(world, sampler, x, y, z, soil)
+}
+
+fn carve_kind(
+    world: &World,
+    sampler: &ColumnSampler<'_, '_>,
+    x: i32,
+    y: i32,
+    z: i32,
+    soil: f32,
+) -> CarveKind {
+    let params = sampler.params;
+    let wx = x as f32;
+    let wy = y as f32;
+    let wz = z as f32;
+    let wxw = fractal3(&sampler.ctx.warp, wx, wy, wz, &params.warp);
+    let wyw = fractal3(
+        &sampler.ctx.warp,
+        wx + 133.7,
+        wy + 71.3,
+        wz - 19.1,
+        &params.warp,
+    );
+    let wzw = fractal3(
+        &sampler.ctx.warp,
+        wx - 54.2,
+        wy + 29.7,
+        wz + 88.8,
+        &params.warp,
+    );
+    let xp = wx + wxw * params.warp_xy;
+    let yp = wy + wyw * params.warp_y;
+    let zp = wz + wzw * params.warp_xy;
+    let tn = fractal3(
+        &sampler.ctx.tunnel,
+        xp,
+        yp * params.y_scale,
+        zp,
+        &params.tunnel,
+    );
+    let depth01 = (soil / sampler.world_height_f()).clamp(0.0, 1.0);
+    let eps = params.eps_base + params.eps_add * depth01;
+    if tn.abs() < eps {
+        return CarveKind::Tunnel;
+    }
+    let wn = worley3_f1_norm(world.seed as u32, xp, yp, zp, params.room_cell);
+    let room_thr = params.room_thr_base + params.room_thr_add * depth01;
+    if wn < room_thr {
+        CarveKind::Room
+    } else {
+        CarveKind::Solid
+    }
+}
+
+/// Carver decisions for a run of voxels in one column, sampled with batch noise.
+pub struct CarveColumn {
+    y_start: i32,
+    kinds: Vec<CarveKind>,
+}
+
+impl CarveColumn {
+    #[inline]
+    fn kind_at(&self, y: i32) -> Option<CarveKind> {
+        usize::try_from(y - self.y_start)
+            .ok()
+            .and_then(|i| self.kinds.get(i).copied())
+    }
+}
+
+/// Classify `y_start..y_end` of column (`x`, `z`) for the carvers in one batched pass.
+///
+/// Matches `carve_kind` voxel for voxel; pass the result to
+/// `apply_caves_and_features_blocks` to skip its per-voxel noise calls. The time is
+/// charged to the caves stage.
+pub fn sample_carve_column(
+    sampler: &mut ColumnSampler<'_, '_>,
+    world: &World,
+    x: i32,
+    z: i32,
+    y_start: i32,
+    y_end: i32,
+    height: i32,
+) -> CarveColumn {
+    let stage_start = Instant::now();
+    let params = sampler.params;
+    let mut kinds = vec![CarveKind::Solid; (y_end - y_start).max(0) as usize];
+    let h = height as f32;
+    let open: Vec<i32> = if params.carvers_enable {
+        (y_start..y_end)
+            .filter(|&y| h - y as f32 > params.soil_min && y as f32 > params.min_y)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    if open.is_empty() {
+        return CarveColumn { y_start, kinds };
+    }
+    let n = open.len();
+    let wx = x as f32;
+    let wz = z as f32;
+    let ys: Vec<f32> = open.iter().map(|&y| y as f32).collect();
+    let splat = |v: f32| vec![v; n];
+    let shifted = |d: f32| ys.iter().map(|&y| y + d).collect::<Vec<f32>>();
+    let warp = &sampler.ctx.warp;
+    let wxw = fractal3_batch(warp, &splat(wx), &ys, &splat(wz), &params.warp);
+    let wyw = fractal3_batch(
+        warp,
+        &splat(wx + 133.7),
+        &shifted(71.3),
+        &splat(wz - 19.1),
+        &params.warp,
+    );
+    let wzw = fractal3_batch(
+        warp,
+        &splat(wx - 54.2),
+        &shifted(29.7),
+        &splat(wz + 88.8),
+        &params.warp,
+    );
+    let xp: Vec<f32> = wxw.iter().map(|w| wx + w * params.warp_xy).collect();
+    let yp: Vec<f32> = ys
+        .iter()
+        .zip(&wyw)
+        .map(|(y, w)| y + w * params.warp_y)
+        .collect();
+    let zp: Vec<f32> = wzw.iter().map(|w| wz + w * params.warp_xy).collect();
+    let yp_scaled: Vec<f32> = yp.iter().map(|y| y * params.y_scale).collect();
+    let tn = fractal3_batch(&sampler.ctx.tunnel, &xp, &yp_scaled, &zp, &params.tunnel);
+    for (i, &y) in open.iter().enumerate() {
+        let soil = h - ys[i];
+        let depth01 = (soil / sampler.world_height_f()).clamp(0.0, 1.0);
+        let eps = params.eps_base + params.eps_add * depth01;
+        kinds[(y - y_start) as usize] = if tn[i].abs() < eps {
+            CarveKind::Tunnel
+        } else {
+            let wn = worley3_f1_norm(world.seed as u32, xp[i], yp[i], zp[i], params.room_cell);
+            if wn < params.room_thr_base + params.room_thr_add * depth01 {
+                CarveKind::Room
+            } else {
+                CarveKind::Solid
+            }
+        };
+    }
+    sampler
+        .profiler_mut()
+        .record_stage_duration(TerrainStage::Caves, stage_start.elapsed());
+    CarveColumn { y_start, kinds }
+}
+
 pub(crate) fn apply_caves_and_features<'p>(
     world: &World,
     sampler: &mut ColumnSampler<'_, 'p>,
@@ -64,33 +235,7 @@ pub(crate) fn apply_caves_and_features<'p>(
         let wy = y as f32;
         let soil = h - wy;
         if params.carvers_enable && soil > soil_min && wy > min_y {
-            let wx = x as f32;
-            let wyf = y as f32;
-            let wz = z as f32;
-            let wxw = fractal3(&sampler.ctx.warp, wx, wyf, wz, &params.warp);
-            let wyw = fractal3(
-                &sampler.ctx.warp,
-                wx + 133.7,
-                wyf + 71.3,
-                wz - 19.1,
-                &params.warp,
-            );
-            let wzw = fractal3(
-                &sampler.ctx.warp,
-                wx - 54.2,
-                wyf + 29.7,
-                wz + 88.8,
-                &params.warp,
-            );
-            let xp = wx + wxw * warp_xy;
-            let yp = wyf + wyw * warp_y;
-            let zp = wz + wzw * warp_xy;
-            let tn = fractal3(&sampler.ctx.tunnel, xp, yp * y_scale, zp, &params.tunnel);
-            let depth01 = (soil / world_height_f).clamp(0.0, 1.0);
-            let eps = eps_base + eps_add * depth01;
-            let wn = worley3_f1_norm(world.seed as u32, xp, yp, zp, room_cell);
-            let room_thr = room_thr_base + room_thr_add * depth01;
-            let carved_air = (tn.abs() < eps) || (wn < room_thr);
+            let carved_air = carve_kind(world, sampler, x, y, z, soil) != CarveKind::Solid;
             if carved_air {
                 *base = "air";
                 carved_here = true;
@@ -100,6 +245,9 @@ pub(crate) fn apply_caves_and_features<'p>(
             let features = &params.features;
             if !features.is_empty() {
                 for (ri, rule) in features.iter().enumerate() {
+                    if rule.kind != FeatureKind::Replace {
+                        continue;
+                    }
                     let w = &rule.when;
                     if !w.base_in.is_empty() && !w.base_in.iter().any(|s| s.as_str() == *base) {
                         continue;
@@ -159,7 +307,7 @@ pub(crate) fn apply_caves_and_features<'p>(
                     if let Some(p) = w.chance {
                         if p < 1.0 {
                             let salt = ((world.seed as u32).wrapping_add(0xC0FF_EE15))
-                                .wrapping_add(ri as u32 * 0x9E37_79B9);
+                                .wrapping_add((ri as u32).wrapping_mul(0x9E37_79B9));
                             let h = hash3_feature(x, y, z, salt) & 0x00FF_FFFF;
                             let r = (h as f32) / 16_777_216.0;
                             if r >= p {
@@ -186,6 +334,7 @@ pub fn apply_caves_and_features_blocks<'p>(
     sampler: &mut ColumnSampler<'_, 'p>,
     reg: &BlockRegistry,
     lookup: &mut BlockLookup,
+    carve: Option<&CarveColumn>,
     x: i32,
     y: i32,
     z: i32,
@@ -216,33 +365,10 @@ pub fn apply_caves_and_features_blocks<'p>(
         let wy = y as f32;
         let soil = h - wy;
         if params.carvers_enable && soil > soil_min && wy > min_y {
-            let wx = x as f32;
-            let wyf = y as f32;
-            let wz = z as f32;
-            let wxw = fractal3(&sampler.ctx.warp, wx, wyf, wz, &params.warp);
-            let wyw = fractal3(
-                &sampler.ctx.warp,
-                wx + 133.7,
-                wyf + 71.3,
-                wz - 19.1,
-                &params.warp,
-            );
-            let wzw = fractal3(
-                &sampler.ctx.warp,
-                wx - 54.2,
-                wyf + 29.7,
-                wz + 88.8,
-                &params.warp,
-            );
-            let xp = wx + wxw * warp_xy;
-            let yp = wyf + wyw * warp_y;
-            let zp = wz + wzw * warp_xy;
-            let tn = fractal3(&sampler.ctx.tunnel, xp, yp * y_scale, zp, &params.tunnel);
-            let depth01 = (soil / world_height_f).clamp(0.0, 1.0);
-            let eps = eps_base + eps_add * depth01;
-            let wn = worley3_f1_norm(world.seed as u32, xp, yp, zp, room_cell);
-            let room_thr = room_thr_base + room_thr_add * depth01;
-            let carved_air = (tn.abs() < eps) || (wn < room_thr);
+            let kind = carve
+                .and_then(|c| c.kind_at(y))
+                .unwrap_or_else(|| carve_kind(world, sampler, x, y, z, soil));
+            let carved_air = kind != CarveKind::Solid;
             if carved_air {
                 base_block = lookup.resolve(world, reg, "air");
                 carved_here = true;
@@ -252,6 +378,9 @@ pub fn apply_caves_and_features_blocks<'p>(
             let features = &params.features;
             if !features.is_empty() {
                 for (ri, rule) in features.iter().enumerate() {
+                    if rule.kind != FeatureKind::Replace {
+                        continue;
+                    }
                     let w = &rule.when;
                     let current_name = block_name(reg, base_block);
                     if !w.base_in.is_empty()
@@ -314,7 +443,7 @@ pub fn apply_caves_and_features_blocks<'p>(
                     if let Some(p) = w.chance {
                         if p < 1.0 {
                             let salt = ((world.seed as u32).wrapping_add(0xC0FF_EE15))
-                                .wrapping_add(ri as u32 * 0x9E37_79B9);
+                                .wrapping_add((ri as u32).wrapping_mul(0x9E37_79B9));
                             let h = hash3_feature(x, y, z, salt) & 0x00FF_FFFF;
                             let r = (h as f32) / 16_777_216.0;
                             if r >= p {
@@ -434,7 +563,7 @@ fn is_carvable_block(reg: &BlockRegistry, block: Block) -> bool {
     )
 }
 
-fn fractal3(noise: &FastNoiseLite, x: f32, y: f32, z: f32, fractal: &Fractal) -> f32 {
+fn fractal3(noise: &NoiseField, x: f32, y: f32, z: f32, fractal: &Fractal) -> f32 {
     // PERF: Each call runs multiple octaves for the same coordinates; reuse when stepping coherently.
     let mut amp = 1.0_f32;
     let mut freq = 1.0_f32 / fractal.scale.max(0.0001);
@@ -449,6 +578,45 @@ fn fractal3(noise: &FastNoiseLite, x: f32, y: f32, z: f32, fractal: &Fractal) ->
     if max_amp > 0.0 { sum / max_amp } else { sum }
 }
 
+/// `fractal3` over parallel coordinate slices, with each octave sampled as one batch.
+fn fractal3_batch(
+    noise: &NoiseField,
+    xs: &[f32],
+    ys: &[f32],
+    zs: &[f32],
+    fractal: &Fractal,
+) -> Vec<f32> {
+    let n = xs.len();
+    let mut sum = vec![0.0_f32; n];
+    let mut sx = vec![0.0_f32; n];
+    let mut sy = vec![0.0_f32; n];
+    let mut sz = vec![0.0_f32; n];
+    let mut octave = vec![0.0_f32; n];
+    let mut amp = 1.0_f32;
+    let mut freq = 1.0_f32 / fractal.scale.max(0.0001);
+    let mut max_amp = 0.0_f32;
+    for _ in 0..fractal.octaves.max(1) {
+        for i in 0..n {
+            sx[i] = xs[i] * freq;
+            sy[i] = ys[i] * freq;
+            sz[i] = zs[i] * freq;
+        }
+        noise.sample_3d_batch(&sx, &sy, &sz, &mut octave);
+        for (s, v) in sum.iter_mut().zip(&octave) {
+            *s += v * amp;
+        }
+        max_amp += amp;
+        amp *= fractal.persistence;
+        freq *= fractal.lacunarity;
+    }
+    if max_amp > 0.0 {
+        for s in &mut sum {
+            *s /= max_amp;
+        }
+    }
+    sum
+}
+
 fn worley3_f1_norm(seed: u32, x: f32, y: f32, z: f32, cell: f32) -> f32 {
     // PERF: Worley lookup scans 27 pseudo-random cells; avoid in tight loops if possible.
     let cell = if cell <= 0.0001 { 1.0 } else { cell };
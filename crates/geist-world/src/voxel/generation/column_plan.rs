@@ -20,6 +20,8 @@ pub struct ColumnInfo {
     pub surface_block: Block,
     pub column_seed: u32,
     pub tree: Option<TreePlan>,
+    /// Index into `ChunkColumnPlan::biome_materials`; `None` uses the plan's defaults.
+    pub materials: Option<u16>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -36,6 +38,8 @@ pub struct ColumnMaterials {
 pub struct ChunkColumnPlan {
     pub columns: Vec<ColumnInfo>,
     pub materials: ColumnMaterials,
+    /// Materials of biomes that override the subsoil rules.
+    pub biome_materials: Vec<ColumnMaterials>,
     pub width: usize,
     pub depth: usize,
 }
@@ -51,6 +55,15 @@ impl ChunkColumnPlan {
         let idx = self.index(lx, lz);
         &self.columns[idx]
     }
+
+    /// Subsoil materials under a column's surface block.
+    #[inline]
+    pub fn column_materials(&self, column: &ColumnInfo) -> &ColumnMaterials {
+        column
+            .materials
+            .and_then(|i| self.biome_materials.get(i as usize))
+            .unwrap_or(&self.materials)
+    }
 }
 
 pub fn build_chunk_column_plan(
@@ -82,10 +95,20 @@ pub fn build_chunk_column_plan(
     };
     let air_block = world.air_block(reg);
     let topsoil_thickness = params.topsoil_thickness;
+    let materials = ColumnMaterials {
+        sub_near_block,
+        sub_deep_block,
+        water_block,
+        air_block,
+        topsoil_thickness,
+        leaf_radius: params.leaf_radius,
+    };
 
     let mut columns = Vec::with_capacity(size_x * size_z);
+    let mut biome_materials: Vec<ColumnMaterials> = Vec::new();
+    // Slot in `biome_materials` per biome index, filled the first time a biome shows up.
+    let mut biome_slots: Vec<Option<Option<u16>>> = Vec::new();
     let mut sampler = ColumnSampler::new(world, ctx, params);
-    let water_level = sampler.water_level();
 
     for lz in 0..size_z {
         let wz = base_z + lz as i32;
@@ -99,6 +122,32 @@ pub fn build_chunk_column_plan(
             };
             let column_seed = column_seed(world.seed as u32, wx, wz);
             let tree = plan_tree_for_column(world, &mut sampler, reg, wx, wz, height);
+            let water_level = sampler.water_level_for(wx, wz);
+            let column_materials = sampler.biome_index_for(wx, wz).and_then(|idx| {
+                if biome_slots.len() <= idx {
+                    biome_slots.resize(idx + 1, None);
+                }
+                *biome_slots[idx].get_or_insert_with(|| {
+                    let def = params.biomes.as_ref()?.defs.get(idx)?;
+                    if !def.overrides_subsoil() {
+                        return None;
+                    }
+                    let block = |name: Option<&str>, fallback: Block| match name {
+                        Some(name) => Block {
+                            id: world.resolve_block_id(reg, name),
+                            state: 0,
+                        },
+                        None => fallback,
+                    };
+                    biome_materials.push(ColumnMaterials {
+                        sub_near_block: block(def.sub_near.as_deref(), sub_near_block),
+                        sub_deep_block: block(def.sub_deep.as_deref(), sub_deep_block),
+                        topsoil_thickness: def.topsoil_thickness.unwrap_or(topsoil_thickness),
+                        ..materials.clone()
+                    });
+                    Some((biome_materials.len() - 1) as u16)
+                })
+            });
 
             columns.push(ColumnInfo {
                 wx,
@@ -108,20 +157,15 @@ pub fn build_chunk_column_plan(
                 surface_block,
                 column_seed,
                 tree,
+                materials: column_materials,
             });
         }
     }
 
     ChunkColumnPlan {
         columns,
-        materials: ColumnMaterials {
-            sub_near_block,
-            sub_deep_block,
-            water_block,
-            air_block,
-            topsoil_thickness,
-            leaf_radius: params.leaf_radius,
-        },
+        materials,
+        biome_materials,
         width: size_x,
         depth: size_z,
     }
@@ -175,9 +219,16 @@ impl ChunkColumnProfile {
         self.reuse_count.load(std::sync::atomic::Ordering::Relaxed)
     }
 
+    /// Approximate heap + inline footprint, for cache accounting.
+    pub fn approx_bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.plan.columns.capacity() * std::mem::size_of::<ColumnInfo>()
+            + self.trees.capacity() * std::mem::size_of::<TreePlan>()
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut buf = Vec::new();
-        buf.push(1); // version
+        buf.push(2); // version
         write_i32(&mut buf, self.coord.cx);
         write_i32(&mut buf, self.coord.cy);
         write_i32(&mut buf, self.coord.cz);
@@ -199,19 +250,13 @@ impl ChunkColumnProfile {
                 }
                 None => buf.push(0),
             }
+            write_u16(&mut buf, column.materials.unwrap_or(NO_MATERIALS));
         }
-        write_block(&mut buf, self.plan.materials.sub_near_block);
-        write_block(&mut buf, self.plan.materials.sub_deep_block);
-        match self.plan.materials.water_block {
-            Some(block) => {
-                buf.push(1);
-                write_block(&mut buf, block);
-            }
-            None => buf.push(0),
+        write_materials(&mut buf, &self.plan.materials);
+        write_u16(&mut buf, self.plan.biome_materials.len() as u16);
+        for materials in &self.plan.biome_materials {
+            write_materials(&mut buf, materials);
         }
-        write_block(&mut buf, self.plan.materials.air_block);
-        write_i32(&mut buf, self.plan.materials.topsoil_thickness);
-        write_i32(&mut buf, self.plan.materials.leaf_radius);
 
         write_u32(&mut buf, self.trees.len() as u32);
         for tree in &self.trees {
@@ -224,7 +269,8 @@ impl ChunkColumnProfile {
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
         let mut cursor = Cursor::new(bytes);
         let version = read_u8(&mut cursor)?;
-        if version != 1 {
+        // Version 1 predates per-biome materials.
+        if version != 1 && version != 2 {
             return Err(format!("unsupported column profile version {}", version));
         }
         let cx = read_i32(&mut cursor)?;
@@ -248,6 +294,11 @@ impl ChunkColumnProfile {
             } else {
                 None
             };
+            let materials = if version >= 2 {
+                Some(read_u16(&mut cursor)?).filter(|&i| i != NO_MATERIALS)
+            } else {
+                None
+            };
             columns.push(ColumnInfo {
                 wx,
                 wz,
@@ -256,20 +307,25 @@ impl ChunkColumnProfile {
                 surface_block,
                 column_seed,
                 tree,
+                materials,
             });
         }
 
-        let sub_near_block = read_block(&mut cursor)?;
-        let sub_deep_block = read_block(&mut cursor)?;
-        let water_flag = read_u8(&mut cursor)?;
-        let water_block = if water_flag != 0 {
-            Some(read_block(&mut cursor)?)
-        } else {
-            None
-        };
-        let air_block = read_block(&mut cursor)?;
-        let topsoil_thickness = read_i32(&mut cursor)?;
-        let leaf_radius = read_i32(&mut cursor)?;
+        let materials = read_materials(&mut cursor)?;
+        let mut biome_materials = Vec::new();
+        if version >= 2 {
+            let count = read_u16(&mut cursor)? as usize;
+            for _ in 0..count {
+                biome_materials.push(read_materials(&mut cursor)?);
+            }
+        }
+        if let Some(bad) = columns
+            .iter()
+            .filter_map(|c| c.materials)
+            .find(|&i| i as usize >= biome_materials.len())
+        {
+            return Err(format!("column materials index {} out of range", bad));
+        }
 
         let tree_count = read_u32(&mut cursor)? as usize;
         let mut trees = Vec::with_capacity(tree_count);
@@ -279,14 +335,8 @@ impl ChunkColumnProfile {
 
         let plan = ChunkColumnPlan {
             columns,
-            materials: ColumnMaterials {
-                sub_near_block,
-                sub_deep_block,
-                water_block,
-                air_block,
-                topsoil_thickness,
-                leaf_radius,
-            },
+            materials,
+            biome_materials,
             width,
             depth,
         };
@@ -300,6 +350,43 @@ impl ChunkColumnProfile {
     }
 }
 
+// Serialized `ColumnInfo::materials` of a column using the plan's defaults.
+const NO_MATERIALS: u16 = u16::MAX;
+
+fn write_materials(buf: &mut Vec<u8>, materials: &ColumnMaterials) {
+    write_block(buf, materials.sub_near_block);
+    write_block(buf, materials.sub_deep_block);
+    match materials.water_block {
+        Some(block) => {
+            buf.push(1);
+            write_block(buf, block);
+        }
+        None => buf.push(0),
+    }
+    write_block(buf, materials.air_block);
+    write_i32(buf, materials.topsoil_thickness);
+    write_i32(buf, materials.leaf_radius);
+}
+
+fn read_materials(cursor: &mut Cursor<&[u8]>) -> Result<ColumnMaterials, String> {
+    let sub_near_block = read_block(cursor)?;
+    let sub_deep_block = read_block(cursor)?;
+    let water_flag = read_u8(cursor)?;
+    let water_block = if water_flag != 0 {
+        Some(read_block(cursor)?)
+    } else {
+        None
+    };
+    Ok(ColumnMaterials {
+        sub_near_block,
+        sub_deep_block,
+        water_block,
+        air_block: read_block(cursor)?,
+        topsoil_thickness: read_i32(cursor)?,
+        leaf_radius: read_i32(cursor)?,
+    })
+}
+
 fn write_i32(buf: &mut Vec<u8>, value: i32) {
     buf.extend_from_slice(&value.to_le_bytes());
 }
@@ -1,9 +1,13 @@
+use std::sync::Arc;
 use std::time::Instant;
 
-use crate::worldgen::WorldGenParams;
+use crate::worldgen::{BiomeDefParam, WorldGenParams};
 
 use super::super::gen_ctx::{TerrainProfiler, TerrainStage};
 use super::super::{GenCtx, World};
+use super::custom::TerrainGenerator;
+use super::lakes::water_level_at;
+use super::plugins::{StagePlugins, plugin_height, plugin_water_level};
 
 pub(super) fn remap_noise_to_height(
     noise: f32,
@@ -23,6 +27,8 @@ pub struct ColumnSampler<'ctx, 'p> {
     pub(super) params: &'p WorldGenParams,
     world_height: i32,
     world_height_f: f32,
+    generator: Option<Arc<dyn TerrainGenerator>>,
+    plugins: StagePlugins,
 }
 
 impl<'ctx, 'p> ColumnSampler<'ctx, 'p> {
@@ -34,6 +40,8 @@ impl<'ctx, 'p> ColumnSampler<'ctx, 'p> {
             params,
             world_height,
             world_height_f,
+            generator: world.generator().cloned(),
+            plugins: Arc::clone(&world.stage_plugins),
         }
     }
 
@@ -62,49 +70,55 @@ impl<'ctx, 'p> ColumnSampler<'ctx, 'p> {
             }
         }
         self.profiler_mut().record_height_cache(false);
-        let noise = self.ctx.terrain.get_noise_2d(wx as f32, wz as f32);
-        let height =
-            remap_noise_to_height(noise, self.params, self.world_height, self.world_height_f);
+        let height = self.raw_height(wx, wz);
         self.profiler_mut()
             .record_stage_duration(TerrainStage::Height, stage_start.elapsed());
         height
     }
 
     pub(super) fn water_level(&self) -> i32 {
-        if self.params.water_enable {
-            (self.world_height_f * self.params.water_level_ratio).round() as i32
-        } else {
-            -1
+        self.params.sea_level(self.world_height)
+    }
+
+    /// Water level for a single column, accounting for elevated lakes.
+    pub fn water_level_for(&mut self, wx: i32, wz: i32) -> i32 {
+        let level = water_level_at(self, wx, wz);
+        plugin_water_level(&self.plugins, wx, wz, level)
+    }
+
+    /// Terrain height without touching the profiler; used for wide-area lookups.
+    pub(super) fn raw_height(&self, wx: i32, wz: i32) -> i32 {
+        if let Some(height) = self
+            .ctx
+            .height_tile
+            .as_ref()
+            .and_then(|tile| tile.height(wx, wz))
+        {
+            return height;
         }
+        let height = if let Some(generator) = self.generator.as_deref() {
+            generator.height(wx, wz)
+        } else {
+            let noise = self.ctx.terrain.get_noise_2d(wx as f32, wz as f32);
+            remap_noise_to_height(noise, self.params, self.world_height, self.world_height_f)
+        };
+        plugin_height(&self.plugins, wx, wz, height)
     }
 
-    pub(super) fn biome_for(
-        &mut self,
-        wx: i32,
-        wz: i32,
-    ) -> Option<&'p crate::worldgen::BiomeDefParam> {
-        let biomes = self.params.biomes.as_ref()?;
-        let pack = &**biomes;
-        if pack.debug_pack_all && !pack.defs.is_empty() {
-            let cell = pack.debug_cell_size.max(1);
-            let cx = (wx.div_euclid(cell)) as i64;
-            let cz = (wz.div_euclid(cell)) as i64;
-            let idx = ((cx * 31 + cz * 17).rem_euclid(pack.defs.len() as i64)) as usize;
-            if let Some(def) = pack.defs.get(idx) {
-                return Some(def);
-            }
+    /// Index into the biome pack's `defs` for a column, or `None` when biomes are off or
+    /// no biome's climate ranges match.
+    pub fn biome_index_for(&mut self, wx: i32, wz: i32) -> Option<usize> {
+        let pack = &**self.params.biomes.as_ref()?;
+        if let Some(idx) = pack.debug_cell_biome(wx, wz) {
+            return Some(idx);
         }
         let (t, m) = self.climate_for(wx, wz)?;
-        for def in &pack.defs {
-            if t >= def.temp_min
-                && t < def.temp_max
-                && m >= def.moisture_min
-                && m < def.moisture_max
-            {
-                return Some(def);
-            }
-        }
-        None
+        pack.classify(t, m)
+    }
+
+    pub fn biome_for(&mut self, wx: i32, wz: i32) -> Option<&'p BiomeDefParam> {
+        let idx = self.biome_index_for(wx, wz)?;
+        self.params.biomes.as_ref()?.defs.get(idx)
     }
 
     pub(super) fn top_block_for_column(&mut self, wx: i32, wz: i32, hh: i32) -> &'p str {
@@ -122,6 +136,20 @@ impl<'ctx, 'p> ColumnSampler<'ctx, 'p> {
         self.params.top_mid.as_str()
     }
 
+    /// Near-surface block, deep block and topsoil depth under a column's surface block.
+    pub(super) fn subsoil_for_column(&mut self, wx: i32, wz: i32) -> (&'p str, &'p str, i32) {
+        let params = self.params;
+        let def = self.biome_for(wx, wz);
+        (
+            def.and_then(|d| d.sub_near.as_deref())
+                .unwrap_or(params.sub_near.as_str()),
+            def.and_then(|d| d.sub_deep.as_deref())
+                .unwrap_or(params.sub_deep.as_str()),
+            def.and_then(|d| d.topsoil_thickness)
+                .unwrap_or(params.topsoil_thickness),
+        )
+    }
+
     pub(super) fn tree_probability(&mut self, wx: i32, wz: i32) -> f32 {
         if let Some(def) = self.biome_for(wx, wz) {
             if let Some(density) = def.tree_density {
@@ -136,19 +164,7 @@ impl<'ctx, 'p> ColumnSampler<'ctx, 'p> {
         let biomes = self.params.biomes.as_ref()?;
         let temp = self.ctx.temp2d.as_ref()?;
         let moist = self.ctx.moist2d.as_ref()?;
-        let pack = &**biomes;
-        let sx = if pack.scale_x == 0.0 {
-            1.0
-        } else {
-            pack.scale_x
-        };
-        let sz = if pack.scale_z == 0.0 {
-            1.0
-        } else {
-            pack.scale_z
-        };
-        let x = wx as f32 * sx;
-        let z = wz as f32 * sz;
+        let (x, z) = biomes.climate_coords(wx, wz);
         let tt = ((temp.get_noise_2d(x, z) + 1.0) * 0.5).clamp(0.0, 1.0);
         let mm = ((moist.get_noise_2d(x, z) + 1.0) * 0.5).clamp(0.0, 1.0);
         Some((tt, mm))
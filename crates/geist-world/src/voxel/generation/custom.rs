@@ -0,0 +1,135 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use geist_blocks::registry::BlockRegistry;
+use geist_blocks::types::Block as RtBlock;
+
+use super::super::gen_ctx::TerrainStage;
+use super::super::{GenCtx, World};
+use super::column_sampler::ColumnSampler;
+use super::plugins::StageColumn;
+
+/// Terrain supplied from outside the built-in pipeline.
+///
+/// A `World` built with [`World::with_generator`] asks the generator for column heights
+/// (cached in terrain tiles like the built-in heightmap), then, per voxel below the
+/// chunk top, for the base material, whether it is carved out, and any feature placed
+/// over it. Blocks are named as in `blocks.toml`; unknown names become air. Timings land
+/// in the usual `TerrainStage`s, so metrics and the HUD stay meaningful. Registered
+/// [`TerrainStagePlugin`](super::TerrainStagePlugin)s still run on top.
+pub trait TerrainGenerator: Send + Sync {
+    /// First y above the ground in column `(wx, wz)`.
+    fn height(&self, wx: i32, wz: i32) -> i32;
+
+    /// Block at `(wx, wy, wz)` in a column whose ground ends at `height`, before carving.
+    fn surface_material(&self, wx: i32, wy: i32, wz: i32, height: i32) -> &str;
+
+    /// Whether the solid block at `(wx, wy, wz)` is carved to air.
+    fn carve(&self, _wx: i32, _wy: i32, _wz: i32, _height: i32) -> bool {
+        false
+    }
+
+    /// Block replacing `base` at `(wx, wy, wz)` (ores, trees, structures), if any.
+    fn feature(&self, _wx: i32, _wy: i32, _wz: i32, _height: i32, _base: &str) -> Option<&str> {
+        None
+    }
+}
+
+impl World {
+    /// A world whose terrain comes from `generator` instead of the built-in pipeline.
+    pub fn with_generator(
+        chunks_x: usize,
+        chunks_y_hint: usize,
+        chunks_z: usize,
+        seed: i32,
+        generator: Arc<dyn TerrainGenerator>,
+    ) -> Self {
+        let mut world = Self::new(
+            chunks_x,
+            chunks_y_hint,
+            chunks_z,
+            seed,
+            super::super::WorldGenMode::Normal,
+        );
+        world.generator = Some(generator);
+        world
+    }
+
+    #[inline]
+    pub fn generator(&self) -> Option<&Arc<dyn TerrainGenerator>> {
+        self.generator.as_ref()
+    }
+
+    /// Fill `out` with the custom generator's blocks for column `(wx, wz)` from `y0` up.
+    /// Returns false, leaving `out` untouched, when the world has no generator.
+    pub fn custom_column(
+        &self,
+        reg: &BlockRegistry,
+        ctx: &mut GenCtx,
+        wx: i32,
+        wz: i32,
+        y0: i32,
+        out: &mut [RtBlock],
+    ) -> bool {
+        let Some(generator) = self.generator.as_deref() else {
+            return false;
+        };
+        let air = self.air_block(reg);
+        let params = Arc::clone(&ctx.params);
+        let mut sampler = ColumnSampler::new(self, ctx, &params);
+        let height = sampler.height_for(wx, wz);
+        let profiler = sampler.profiler_mut();
+
+        profiler.begin_stage(TerrainStage::Surface);
+        let start = Instant::now();
+        for (wy, block) in (y0..).zip(out.iter_mut()) {
+            let name = generator.surface_material(wx, wy, wz, height);
+            *block = RtBlock {
+                id: self.resolve_block_id(reg, name),
+                state: 0,
+            };
+        }
+        profiler.record_stage_duration(TerrainStage::Surface, start.elapsed());
+
+        profiler.begin_stage(TerrainStage::Caves);
+        let start = Instant::now();
+        for (wy, block) in (y0..).zip(out.iter_mut()) {
+            if *block != air && generator.carve(wx, wy, wz, height) {
+                *block = air;
+            }
+        }
+        profiler.record_stage_duration(TerrainStage::Caves, start.elapsed());
+
+        profiler.begin_stage(TerrainStage::Trees);
+        let start = Instant::now();
+        for (wy, block) in (y0..).zip(out.iter_mut()) {
+            let base = reg.get(block.id).map_or("air", |ty| ty.name.as_str());
+            if let Some(name) = generator.feature(wx, wy, wz, height, base) {
+                *block = RtBlock {
+                    id: self.resolve_block_id(reg, name),
+                    state: 0,
+                };
+            }
+        }
+        profiler.record_stage_duration(TerrainStage::Trees, start.elapsed());
+
+        if !self.stage_plugins.is_empty() {
+            let column = StageColumn {
+                height,
+                water_level: sampler.water_level_for(wx, wz),
+            };
+            for (wy, block) in (y0..).zip(out.iter_mut()) {
+                *block = self.apply_stage_plugins(
+                    reg,
+                    sampler.profiler_mut(),
+                    wx,
+                    wy,
+                    wz,
+                    column,
+                    *block,
+                );
+            }
+        }
+        true
+    }
+}
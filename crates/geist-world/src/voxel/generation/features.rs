@@ -0,0 +1,275 @@
+//! Vein and scatter features. Placements are seeded per `FEATURE_CELL` square from the
+//! world seed, the rule and the cell, so chunks agree on veins and patches that cross
+//! their edges and rebuilds reproduce them exactly. A rule's `when.chance` keeps each
+//! vein or patch with that probability.
+
+use std::time::Instant;
+
+use crate::worldgen::{CountRange, FeatureKind, FeatureRule, ScatterSpec, VeinBand};
+
+use super::super::World;
+use super::super::gen_ctx::TerrainStage;
+use super::column_sampler::ColumnSampler;
+
+/// Side of the square cells features are seeded in.
+pub const FEATURE_CELL: i32 = 32;
+// Farthest a vein walks from its origin along any axis.
+const VEIN_REACH: i32 = 6;
+const MAX_VEIN_SIZE: u32 = 96;
+const MAX_SCATTER_RADIUS: i32 = 12;
+
+/// One placement of rule `rule` (an index into the feature list).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeatureVoxel {
+    pub wx: i32,
+    pub wy: i32,
+    pub wz: i32,
+    pub rule: usize,
+}
+
+/// Vein voxels and scatter columns overlapping a region, in rule order. Scatter entries
+/// carry no height (`wy` is 0); they land on top of their column.
+#[derive(Clone, Debug, Default)]
+pub struct FeaturePlacements {
+    pub veins: Vec<FeatureVoxel>,
+    pub scatter: Vec<FeatureVoxel>,
+}
+
+impl FeaturePlacements {
+    pub fn is_empty(&self) -> bool {
+        self.veins.is_empty() && self.scatter.is_empty()
+    }
+}
+
+/// Placements of every vein and scatter rule inside `min..max` (exclusive; Y is ignored
+/// for scatter).
+pub fn plan_features(
+    seed: i32,
+    features: &[FeatureRule],
+    min: (i32, i32, i32),
+    max: (i32, i32, i32),
+) -> FeaturePlacements {
+    let mut out = FeaturePlacements::default();
+    let inside = |wx: i32, wz: i32| wx >= min.0 && wx < max.0 && wz >= min.2 && wz < max.2;
+    for (ri, rule) in features.iter().enumerate() {
+        let reach = match rule.kind {
+            FeatureKind::Replace | FeatureKind::Prefab => continue,
+            FeatureKind::Vein => VEIN_REACH,
+            FeatureKind::Scatter => rule.scatter.radius.clamp(0, MAX_SCATTER_RADIUS),
+        };
+        let salt = rule.salt(ri);
+        let cells_x =
+            (min.0 - reach).div_euclid(FEATURE_CELL)..=(max.0 - 1 + reach).div_euclid(FEATURE_CELL);
+        let cells_z =
+            (min.2 - reach).div_euclid(FEATURE_CELL)..=(max.2 - 1 + reach).div_euclid(FEATURE_CELL);
+        for cz in cells_z {
+            for cx in cells_x.clone() {
+                let mut rng = CellRng::new(seed as u32, salt, cx, cz);
+                let chance = rule.when.chance.unwrap_or(1.0);
+                match rule.kind {
+                    FeatureKind::Vein => {
+                        for band in &rule.vein.bands {
+                            seed_band(&mut rng, band, cx, cz, chance, |wx, wy, wz| {
+                                if inside(wx, wz) && wy >= min.1 && wy < max.1 {
+                                    out.veins.push(FeatureVoxel {
+                                        wx,
+                                        wy,
+                                        wz,
+                                        rule: ri,
+                                    });
+                                }
+                            });
+                        }
+                    }
+                    FeatureKind::Scatter => {
+                        seed_scatter(&mut rng, &rule.scatter, cx, cz, chance, |wx, wz| {
+                            if inside(wx, wz) {
+                                out.scatter.push(FeatureVoxel {
+                                    wx,
+                                    wy: 0,
+                                    wz,
+                                    rule: ri,
+                                });
+                            }
+                        });
+                    }
+                    FeatureKind::Replace | FeatureKind::Prefab => {}
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Vein blocks over `base` at `(x, y, z)`, for point queries; chunk generation applies
+/// the same placements in bulk.
+pub(super) fn apply_vein_features<'p>(
+    world: &World,
+    sampler: &mut ColumnSampler<'_, 'p>,
+    x: i32,
+    y: i32,
+    z: i32,
+    height: i32,
+    base: &mut &'p str,
+) {
+    let features = &sampler.params.features;
+    if *base == "air" || !features.iter().any(|r| r.kind == FeatureKind::Vein) {
+        return;
+    }
+    sampler.profiler_mut().begin_stage(TerrainStage::Caves);
+    let stage_start = Instant::now();
+    let placed = plan_features(world.seed, features, (x, y, z), (x + 1, y + 1, z + 1));
+    for v in &placed.veins {
+        let rule = &features[v.rule];
+        if rule.vein_accepts(base, y, height) {
+            *base = rule.place.block.as_str();
+        }
+    }
+    sampler
+        .profiler_mut()
+        .record_stage_duration(TerrainStage::Caves, stage_start.elapsed());
+}
+
+/// Scatter block resting on the surface at `(x, y, z)`, for point queries.
+pub(super) fn apply_scatter_features<'p>(
+    world: &World,
+    sampler: &mut ColumnSampler<'_, 'p>,
+    x: i32,
+    y: i32,
+    z: i32,
+    height: i32,
+    base: &mut &'p str,
+) {
+    let features = &sampler.params.features;
+    if y != height || *base != "air" || !features.iter().any(|r| r.kind == FeatureKind::Scatter) {
+        return;
+    }
+    sampler.profiler_mut().begin_stage(TerrainStage::Trees);
+    let stage_start = Instant::now();
+    let placed = plan_features(world.seed, features, (x, y, z), (x + 1, y + 1, z + 1));
+    if !placed.scatter.is_empty() {
+        let surface = sampler.top_block_for_column(x, z, height);
+        let water_level = sampler.water_level_for(x, z);
+        if let Some(v) = placed
+            .scatter
+            .iter()
+            .find(|v| features[v.rule].scatter_accepts(surface, height, water_level))
+        {
+            *base = features[v.rule].place.block.as_str();
+        }
+    }
+    sampler
+        .profiler_mut()
+        .record_stage_duration(TerrainStage::Trees, stage_start.elapsed());
+}
+
+// Veins of one band in cell `(cx, cz)`: random walks from an origin inside the cell,
+// staying within `VEIN_REACH` of it and inside the band.
+fn seed_band(
+    rng: &mut CellRng,
+    band: &VeinBand,
+    cx: i32,
+    cz: i32,
+    chance: f32,
+    mut emit: impl FnMut(i32, i32, i32),
+) {
+    let (y_lo, y_hi) = (band.y_min.min(band.y_max), band.y_min.max(band.y_max));
+    let count = rng.range(band.count);
+    for _ in 0..count {
+        let size = rng.range(band.size).min(MAX_VEIN_SIZE);
+        let origin = [
+            cx * FEATURE_CELL + rng.below(FEATURE_CELL as u32) as i32,
+            y_lo + rng.below((y_hi - y_lo) as u32 + 1) as i32,
+            cz * FEATURE_CELL + rng.below(FEATURE_CELL as u32) as i32,
+        ];
+        let keep = rng.unit() < chance;
+        let mut p = origin;
+        for _ in 0..size {
+            if keep {
+                emit(p[0], p[1], p[2]);
+            }
+            let r = rng.next();
+            let axis = (r % 3) as usize;
+            let step = if r & 8 == 0 { 1 } else { -1 };
+            let next = p[axis] + step;
+            let (lo, hi) = match axis {
+                1 => (
+                    (origin[1] - VEIN_REACH).max(y_lo),
+                    (origin[1] + VEIN_REACH).min(y_hi),
+                ),
+                _ => (origin[axis] - VEIN_REACH, origin[axis] + VEIN_REACH),
+            };
+            // Bounce off the reach limits instead of stalling against them.
+            p[axis] = if (lo..=hi).contains(&next) {
+                next
+            } else {
+                (p[axis] - step).clamp(lo, hi)
+            };
+        }
+    }
+}
+
+fn seed_scatter(
+    rng: &mut CellRng,
+    spec: &ScatterSpec,
+    cx: i32,
+    cz: i32,
+    chance: f32,
+    mut emit: impl FnMut(i32, i32),
+) {
+    let radius = spec.radius.clamp(0, MAX_SCATTER_RADIUS);
+    let span = (radius * 2 + 1) as u32;
+    let patches = rng.range(spec.patches);
+    for _ in 0..patches {
+        let center_x = cx * FEATURE_CELL + rng.below(FEATURE_CELL as u32) as i32;
+        let center_z = cz * FEATURE_CELL + rng.below(FEATURE_CELL as u32) as i32;
+        let keep = rng.unit() < chance;
+        let size = rng.range(spec.patch_size);
+        for _ in 0..size {
+            let dx = rng.below(span) as i32 - radius;
+            let dz = rng.below(span) as i32 - radius;
+            if keep {
+                emit(center_x + dx, center_z + dz);
+            }
+        }
+    }
+}
+
+// Counter-based stream keyed by seed, rule and cell.
+pub(super) struct CellRng(u32);
+
+impl CellRng {
+    pub(super) fn new(seed: u32, salt: u32, cx: i32, cz: i32) -> Self {
+        let mut h = seed ^ 0xF3A7_1C2D;
+        h ^= mix(salt.wrapping_add(0x27d4_eb2f));
+        h ^= mix((cx as u32).wrapping_add(0x85eb_ca6b));
+        h ^= mix((cz as u32).wrapping_add(0xc2b2_ae35));
+        Self(mix(h))
+    }
+
+    pub(super) fn next(&mut self) -> u32 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9);
+        mix(self.0)
+    }
+
+    pub(super) fn below(&mut self, n: u32) -> u32 {
+        if n == 0 { 0 } else { self.next() % n }
+    }
+
+    pub(super) fn range(&mut self, [a, b]: CountRange) -> u32 {
+        a.min(b) + self.below(a.abs_diff(b) + 1)
+    }
+
+    pub(super) fn unit(&mut self) -> f32 {
+        (self.next() & 0x00FF_FFFF) as f32 / 16_777_216.0
+    }
+}
+
+fn mix(mut v: u32) -> u32 {
+    v ^= v >> 16;
+    v = v.wrapping_mul(0x7feb_352d);
+    v ^= v >> 15;
+    v = v.wrapping_mul(0x846c_a68b);
+    v ^= v >> 16;
+    v
+}
@@ -0,0 +1,220 @@
+use std::f32::consts::TAU;
+
+use super::column_sampler::ColumnSampler;
+
+// Samples per axis when searching a lake cell for its lowest point.
+const FLOOR_SAMPLES: i32 = 8;
+// Bound on cached cells per `GenCtx`; the cache is cleared wholesale when full.
+const LAKE_CACHE_CAP: usize = 4096;
+
+/// A depression in the height field filled with water up to its spill height.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LakeBasin {
+    pub center_x: i32,
+    pub center_z: i32,
+    pub floor: i32,
+    pub level: i32,
+}
+
+impl LakeBasin {
+    #[inline]
+    fn covers(&self, wx: i32, wz: i32, radius: i32) -> bool {
+        let dx = i64::from(wx - self.center_x);
+        let dz = i64::from(wz - self.center_z);
+        dx * dx + dz * dz < i64::from(radius) * i64::from(radius)
+    }
+}
+
+/// Local water level for a column: the highest lake covering it, or sea level.
+pub(super) fn water_level_at(sampler: &mut ColumnSampler<'_, '_>, wx: i32, wz: i32) -> i32 {
+    let sea = sampler.water_level();
+    let params = sampler.params;
+    if !params.water_enable || !params.lakes_enable {
+        return sea;
+    }
+    let cell = params.lake_cell_size;
+    let radius = params.lake_rim_radius;
+    let cx = wx.div_euclid(cell);
+    let cz = wz.div_euclid(cell);
+    let mut level = sea;
+    // Rim radius never exceeds the cell size, so neighbours cover every basin in reach.
+    for dz in -1..=1 {
+        for dx in -1..=1 {
+            if let Some(basin) = lake_basin(sampler, cx + dx, cz + dz)
+                && basin.level > level
+                && basin.covers(wx, wz, radius)
+            {
+                level = basin.level;
+            }
+        }
+    }
+    level
+}
+
+fn lake_basin(sampler: &mut ColumnSampler<'_, '_>, cell_x: i32, cell_z: i32) -> Option<LakeBasin> {
+    if let Some(cached) = sampler.ctx.lake_basins.get(&(cell_x, cell_z)) {
+        return *cached;
+    }
+    let basin = find_basin(sampler, cell_x, cell_z);
+    let cache = &mut sampler.ctx.lake_basins;
+    if cache.len() >= LAKE_CACHE_CAP {
+        cache.clear();
+    }
+    cache.insert((cell_x, cell_z), basin);
+    basin
+}
+
+fn find_basin(sampler: &mut ColumnSampler<'_, '_>, cell_x: i32, cell_z: i32) -> Option<LakeBasin> {
+    let params = sampler.params;
+    let cell = params.lake_cell_size;
+    let step = (cell / FLOOR_SAMPLES).max(1);
+    let origin_x = cell_x * cell + step / 2;
+    let origin_z = cell_z * cell + step / 2;
+
+    let mut center_x = origin_x;
+    let mut center_z = origin_z;
+    let mut floor = i32::MAX;
+    for iz in 0..FLOOR_SAMPLES {
+        for ix in 0..FLOOR_SAMPLES {
+            let wx = origin_x + ix * step;
+            let wz = origin_z + iz * step;
+            let h = sampler.raw_height(wx, wz);
+            if h < floor {
+                floor = h;
+                center_x = wx;
+                center_z = wz;
+            }
+        }
+    }
+
+    // Water rises until it spills over the lowest point on the rim.
+    let radius = params.lake_rim_radius as f32;
+    let samples = params.lake_rim_samples;
+    let mut spill = i32::MAX;
+    for i in 0..samples {
+        let angle = i as f32 / samples as f32 * TAU;
+        let wx = center_x + (radius * angle.cos()).round() as i32;
+        let wz = center_z + (radius * angle.sin()).round() as i32;
+        spill = spill.min(sampler.raw_height(wx, wz));
+    }
+
+    let level = spill - 1;
+    let sea = sampler.water_level();
+    if level - floor < params.lake_min_depth || level <= sea || level >= sampler.world_height() - 1
+    {
+        return None;
+    }
+    Some(LakeBasin {
+        center_x,
+        center_z,
+        floor,
+        level,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::voxel::World;
+    use crate::voxel::generation::custom::TerrainGenerator;
+    use crate::worldgen::{WorldGenConfig, WorldGenParams};
+
+    const GROUND: i32 = 60;
+    // A sample point of lake cell (0, 0) with the cell size below.
+    const BOWL: (i32, i32) = (28, 28);
+
+    /// Flat ground with a small round pit, optionally lower from `low_x` eastward.
+    struct Pit {
+        depth: i32,
+        low_x: Option<(i32, i32)>,
+    }
+
+    impl TerrainGenerator for Pit {
+        fn height(&self, wx: i32, wz: i32) -> i32 {
+            let (dx, dz) = (wx - BOWL.0, wz - BOWL.1);
+            if dx * dx + dz * dz < 36 {
+                return GROUND - self.depth;
+            }
+            match self.low_x {
+                Some((x, h)) if wx >= x => h,
+                _ => GROUND,
+            }
+        }
+
+        fn surface_material(&self, _wx: i32, _wy: i32, _wz: i32, _height: i32) -> &str {
+            "stone"
+        }
+    }
+
+    fn params(extra: &str) -> WorldGenParams {
+        let cfg: WorldGenConfig = toml::from_str(&format!(
+            "[water]\n{extra}\n\
+             [water.lakes]\nenable = true\ncell_size = 64\nrim_radius = 24\nmin_depth = 4\n"
+        ))
+        .unwrap();
+        WorldGenParams::from_config(&cfg)
+    }
+
+    fn level_at(pit: Pit, params: &WorldGenParams, wx: i32, wz: i32) -> i32 {
+        let world = World::with_generator(1, 2, 1, 7, Arc::new(pit));
+        let mut ctx = world.make_gen_ctx();
+        let mut sampler = ColumnSampler::new(&world, &mut ctx, params);
+        water_level_at(&mut sampler, wx, wz)
+    }
+
+    #[test]
+    fn absolute_water_level_overrides_the_ratio() {
+        let flat = || Pit {
+            depth: 0,
+            low_x: None,
+        };
+        let p = params("level = 30");
+        assert_eq!(p.sea_level(128), 30);
+        assert_eq!(level_at(flat(), &p, 5, 5), 30);
+        // Clamped into the world.
+        assert_eq!(params("level = 500").sea_level(128), 127);
+        let ratio = params("level_ratio = 0.25");
+        assert_eq!(ratio.sea_level(128), 32);
+        assert_eq!(level_at(flat(), &ratio, 5, 5), 32);
+    }
+
+    #[test]
+    fn basins_shallower_than_min_depth_stay_dry() {
+        let p = params("level = 30");
+        // Spill height 60 fills to 59: 3 deep over a floor at 56, short of `min_depth`.
+        let shallow = Pit {
+            depth: 4,
+            low_x: None,
+        };
+        assert_eq!(level_at(shallow, &p, BOWL.0, BOWL.1), 30);
+        let deep = Pit {
+            depth: 5,
+            low_x: None,
+        };
+        assert_eq!(level_at(deep, &p, BOWL.0, BOWL.1), GROUND - 1);
+    }
+
+    #[test]
+    fn lakes_fill_above_sea_level_up_to_the_lowest_rim() {
+        let p = params("level = 30");
+        let pit = || Pit {
+            depth: 20,
+            low_x: Some((BOWL.0 + 20, 50)),
+        };
+        // The rim is lower to the east, so the water stops below it.
+        let level = level_at(pit(), &p, BOWL.0, BOWL.1);
+        assert_eq!(level, 49);
+        assert!(level > p.sea_level(128));
+        assert!(level < GROUND);
+        // Outside the rim radius the sea takes over again.
+        assert_eq!(level_at(pit(), &p, BOWL.0, BOWL.1 + 30), 30);
+
+        // A lake at or below the sea is left to the sea.
+        let high_sea = params("level = 49");
+        assert_eq!(level_at(pit(), &high_sea, BOWL.0, BOWL.1), 49);
+        let higher_sea = params("level = 55");
+        assert_eq!(level_at(pit(), &higher_sea, BOWL.0, BOWL.1), 55);
+    }
+}
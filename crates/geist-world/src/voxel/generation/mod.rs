@@ -1,6 +1,11 @@
 pub(crate) mod caves;
 mod column_plan;
 mod column_sampler;
+mod custom;
+mod features;
+mod lakes;
+mod plugins;
+mod prefabs;
 mod surface;
 mod tower;
 mod trees;
@@ -19,12 +24,23 @@ use super::tile_cache::{TerrainTile, TileKey};
 use super::{GenCtx, World, WorldGenMode};
 
 use self::caves::apply_caves_and_features;
-pub use self::caves::{BlockLookup, apply_caves_and_features_blocks};
+pub use self::caves::{
+    BlockLookup, CarveColumn, apply_caves_and_features_blocks, sample_carve_column,
+};
 pub use self::column_plan::{
     ChunkColumnPlan, ChunkColumnProfile, ColumnInfo, ColumnMaterials, build_chunk_column_plan,
 };
 pub use self::column_sampler::ColumnSampler;
 use self::column_sampler::remap_noise_to_height;
+pub use self::custom::TerrainGenerator;
+pub use self::features::{FEATURE_CELL, FeaturePlacements, FeatureVoxel, plan_features};
+use self::features::{apply_scatter_features, apply_vein_features};
+pub use self::lakes::LakeBasin;
+pub(crate) use self::plugins::StagePlugins;
+use self::plugins::plugin_height;
+pub use self::plugins::{StageColumn, TerrainStagePlugin};
+use self::prefabs::apply_prefab_blocks;
+pub use self::prefabs::{PrefabPlacement, plan_prefabs};
 use self::surface::select_surface_block;
 pub use self::tower::{
     TOWER_INNER_RADIUS, TOWER_OUTER_RADIUS, TOWER_TOP, TowerMaterial, evaluate_tower,
@@ -66,9 +82,42 @@ impl World {
             return RtBlock { id, state: 0 };
         }
 
-        if let Some(block) = evaluate_tower(self, reg, &mut ctx.terrain_profiler, x, y, z, air) {
+        if self.generator().is_some() {
+            let mut out = [air];
+            self.custom_column(reg, ctx, x, z, y, &mut out);
             ctx.terrain_profiler
                 .record_stage_duration(TerrainStage::Block, block_start.elapsed());
+            return out[0];
+        }
+
+        let block = self.builtin_block_at(reg, ctx, x, y, z, air);
+        let block = if self.stage_plugins.is_empty() {
+            block
+        } else {
+            let params_guard: Arc<WorldGenParams> = Arc::clone(&ctx.params);
+            let mut sampler = ColumnSampler::new(self, ctx, &params_guard);
+            let column = StageColumn {
+                height: sampler.height_for(x, z),
+                water_level: sampler.water_level_for(x, z),
+            };
+            self.apply_stage_plugins(reg, sampler.profiler_mut(), x, y, z, column, block)
+        };
+        ctx.terrain_profiler
+            .record_stage_duration(TerrainStage::Block, block_start.elapsed());
+        block
+    }
+
+    // The built-in stages for one voxel of a normal world, before any plugin.
+    fn builtin_block_at(
+        &self,
+        reg: &BlockRegistry,
+        ctx: &mut GenCtx,
+        x: i32,
+        y: i32,
+        z: i32,
+        air: RtBlock,
+    ) -> RtBlock {
+        if let Some(block) = evaluate_tower(self, reg, &mut ctx.terrain_profiler, x, y, z, air) {
             return block;
         }
 
@@ -76,16 +125,21 @@ impl World {
         let mut sampler = ColumnSampler::new(self, ctx, &params_guard);
 
         let height = sampler.height_for(x, z);
-        let water_level = sampler.water_level();
+        let water_level = sampler.water_level_for(x, z);
         let mut base = select_surface_block(&mut sampler, x, y, z, height);
         apply_water_fill(&mut sampler, y, water_level, &mut base);
         let _ = apply_caves_and_features(self, &mut sampler, x, y, z, height, &mut base);
+        apply_vein_features(self, &mut sampler, x, y, z, height, &mut base);
         apply_tree_blocks(self, &mut sampler, x, y, z, &mut base);
+        apply_scatter_features(self, &mut sampler, x, y, z, height, &mut base);
+        if let Some(block) = apply_prefab_blocks(self, reg, &mut sampler, x, y, z) {
+            return block;
+        }
 
-        let id = self.resolve_block_id(reg, base);
-        ctx.terrain_profiler
-            .record_stage_duration(TerrainStage::Block, block_start.elapsed());
-        RtBlock { id, state: 0 }
+        RtBlock {
+            id: self.resolve_block_id(reg, base),
+            state: 0,
+        }
     }
 
     pub fn prepare_height_tile(
@@ -109,9 +163,11 @@ impl World {
 
         let total_columns = (size_x * size_z) as u32;
         let key = TileKey::new(base_x, base_z, size_x, size_z);
-        if ctx.height_tile.as_ref().is_some_and(|tile| {
-            tile.matches(&key) && tile.worldgen_rev == self.current_worldgen_rev()
-        }) {
+        if ctx
+            .height_tile
+            .as_ref()
+            .is_some_and(|tile| tile.matches(&key) && tile.worldgen_rev == ctx.worldgen_rev)
+        {
             ctx.height_tile_stats = HeightTileStats {
                 duration_us: 0,
                 columns: total_columns,
@@ -121,7 +177,9 @@ impl World {
             return;
         }
 
-        let rev = self.current_worldgen_rev();
+        // Tiles are stamped with the revision of the params that built them, so a context
+        // created before a reload neither reuses nor publishes tiles for the new params.
+        let rev = ctx.worldgen_rev;
         if let Some(tile) = self.tile_cache().get(&key, rev) {
             ctx.height_tile = Some(tile);
             ctx.height_tile_stats = HeightTileStats {
@@ -137,15 +195,33 @@ impl World {
         let params = &*params_guard;
         let world_height = self.world_height_hint() as i32;
         let world_height_f = world_height as f32;
-        let mut heights = Vec::with_capacity(size_x * size_z);
+        let columns = size_x * size_z;
         let t0 = Instant::now();
+        let mut xs = Vec::with_capacity(columns);
+        let mut zs = Vec::with_capacity(columns);
         for dz in 0..size_z {
-            let wz = base_z + dz as i32;
+            let wz = (base_z + dz as i32) as f32;
             for dx in 0..size_x {
-                let wx = base_x + dx as i32;
-                let noise = ctx.terrain.get_noise_2d(wx as f32, wz as f32);
-                let height = remap_noise_to_height(noise, params, world_height, world_height_f);
-                heights.push(height);
+                xs.push((base_x + dx as i32) as f32);
+                zs.push(wz);
+            }
+        }
+        let mut heights: Vec<i32> = if let Some(generator) = self.generator() {
+            xs.iter()
+                .zip(&zs)
+                .map(|(&x, &z)| generator.height(x as i32, z as i32))
+                .collect()
+        } else {
+            let mut noise = vec![0.0f32; columns];
+            ctx.terrain.sample_2d_batch(&xs, &zs, &mut noise);
+            noise
+                .iter()
+                .map(|&n| remap_noise_to_height(n, params, world_height, world_height_f))
+                .collect()
+        };
+        if !self.stage_plugins.is_empty() {
+            for ((h, &x), &z) in heights.iter_mut().zip(&xs).zip(&zs) {
+                *h = plugin_height(&self.stage_plugins, x as i32, z as i32, *h);
             }
         }
         let elapsed_us = t0.elapsed().as_micros().min(u128::from(u32::MAX)) as u32;
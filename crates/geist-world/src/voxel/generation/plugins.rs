@@ -0,0 +1,105 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use geist_blocks::registry::BlockRegistry;
+use geist_blocks::types::Block as RtBlock;
+
+use super::super::World;
+use super::super::gen_ctx::{TerrainProfiler, TerrainStage};
+
+/// Column values a plugin's block pass sees: the final height and water level after
+/// every column hook has run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StageColumn {
+    pub height: i32,
+    pub water_level: i32,
+}
+
+/// A generation stage injected after the built-in ones, without replacing the pipeline.
+///
+/// Plugins registered with [`World::with_stage_plugin`] run in registration order, each
+/// seeing the previous one's output. The column hooks apply wherever the generator samples
+/// a column (height tiles, caves, trees, lakes), so everything built on the terrain follows
+/// the modified values. `modify_block` runs on every voxel at or above y 0 after the
+/// built-in stages, tower and prefabs included. Chunk generation and point queries call the
+/// same hooks, so they stay in agreement as long as the plugin is deterministic. Flat
+/// worlds skip plugins; time lands in `TerrainStage::Plugins`.
+pub trait TerrainStagePlugin: Send + Sync {
+    /// Label for diagnostics.
+    fn name(&self) -> &str;
+
+    /// First y above the ground in column `(wx, wz)`, given `height` from earlier stages.
+    fn height(&self, _wx: i32, _wz: i32, height: i32) -> i32 {
+        height
+    }
+
+    /// Water level of column `(wx, wz)`, given `level` from earlier stages.
+    fn water_level(&self, _wx: i32, _wz: i32, level: i32) -> i32 {
+        level
+    }
+
+    /// Block at `(wx, wy, wz)` replacing `block` from earlier stages.
+    fn modify_block(
+        &self,
+        _reg: &BlockRegistry,
+        _wx: i32,
+        _wy: i32,
+        _wz: i32,
+        _column: StageColumn,
+        block: RtBlock,
+    ) -> RtBlock {
+        block
+    }
+}
+
+/// Registered plugins in run order; cheap to clone into samplers.
+pub(crate) type StagePlugins = Arc<Vec<Arc<dyn TerrainStagePlugin>>>;
+
+impl World {
+    /// Append `plugin` to the stages run after the built-in pipeline.
+    ///
+    /// Register plugins before generating anything: heights are cached with the plugins'
+    /// changes applied.
+    pub fn with_stage_plugin(mut self, plugin: Arc<dyn TerrainStagePlugin>) -> Self {
+        Arc::make_mut(&mut self.stage_plugins).push(plugin);
+        self
+    }
+
+    #[inline]
+    pub fn stage_plugins(&self) -> &[Arc<dyn TerrainStagePlugin>] {
+        &self.stage_plugins
+    }
+
+    /// Run every plugin's block pass over `block`; a no-op without plugins or below y 0.
+    #[allow(clippy::too_many_arguments)]
+    pub fn apply_stage_plugins(
+        &self,
+        reg: &BlockRegistry,
+        profiler: &mut TerrainProfiler,
+        wx: i32,
+        wy: i32,
+        wz: i32,
+        column: StageColumn,
+        block: RtBlock,
+    ) -> RtBlock {
+        if self.stage_plugins.is_empty() || wy < 0 {
+            return block;
+        }
+        profiler.begin_stage(TerrainStage::Plugins);
+        let start = Instant::now();
+        let block = self
+            .stage_plugins
+            .iter()
+            .fold(block, |b, p| p.modify_block(reg, wx, wy, wz, column, b));
+        profiler.record_stage_duration(TerrainStage::Plugins, start.elapsed());
+        block
+    }
+}
+
+pub(super) fn plugin_height(plugins: &StagePlugins, wx: i32, wz: i32, height: i32) -> i32 {
+    plugins.iter().fold(height, |h, p| p.height(wx, wz, h))
+}
+
+pub(super) fn plugin_water_level(plugins: &StagePlugins, wx: i32, wz: i32, level: i32) -> i32 {
+    plugins.iter().fold(level, |l, p| p.water_level(wx, wz, l))
+}
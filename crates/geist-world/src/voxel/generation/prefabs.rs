@@ -0,0 +1,212 @@
+//! Prefab features. Each rule seeds at most one prefab per `prefab.spacing` cell from
+//! the world seed, the rule and the cell, keeping its rotated footprint inside the cell.
+//! The placement depends only on those and the terrain height under the footprint, so
+//! chunks stamp their own slice of a prefab that spans many of them and the pieces line
+//! up without any cross-chunk state.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use geist_blocks::BlockRegistry;
+use geist_blocks::types::Block as RtBlock;
+
+use crate::prefab::{Prefab, PrefabBlock};
+use crate::worldgen::{FeatureKind, FeatureRule};
+
+use super::super::World;
+use super::super::gen_ctx::TerrainStage;
+use super::column_sampler::ColumnSampler;
+use super::features::CellRng;
+
+/// One prefab stamped by rule `rule` (an index into the feature list).
+#[derive(Clone, Debug)]
+pub struct PrefabPlacement {
+    pub rule: usize,
+    pub prefab: Arc<Prefab>,
+    /// Quarter turns clockwise, seen from above.
+    pub rotation: u8,
+    /// World position of the rotated prefab's minimum corner.
+    pub origin: (i32, i32, i32),
+}
+
+impl PrefabPlacement {
+    /// Extent along X, Y and Z after rotation.
+    pub fn size(&self) -> (i32, i32, i32) {
+        let (w, d) = self.prefab.footprint(self.rotation);
+        (w, self.prefab.size.1, d)
+    }
+
+    /// Palette index the prefab puts at a world position, if it replaces the terrain there.
+    pub fn palette_index(&self, wx: i32, wy: i32, wz: i32) -> Option<usize> {
+        let (ox, oy, oz) = self.origin;
+        self.prefab.cell(self.rotation, wx - ox, wy - oy, wz - oz)
+    }
+
+    /// Runtime blocks for every palette entry, with states turned to match the rotation.
+    pub fn resolve_palette(&self, world: &World, reg: &BlockRegistry) -> Vec<RtBlock> {
+        self.prefab
+            .palette
+            .iter()
+            .map(|b| resolve_prefab_block(world, reg, b, self.rotation))
+            .collect()
+    }
+}
+
+/// Every prefab placement overlapping `min..max` (exclusive), in rule order.
+pub fn plan_prefabs(
+    world: &World,
+    sampler: &mut ColumnSampler<'_, '_>,
+    min: (i32, i32, i32),
+    max: (i32, i32, i32),
+) -> Vec<PrefabPlacement> {
+    let params = sampler.params;
+    let mut out = Vec::new();
+    for (ri, rule) in params.features.iter().enumerate() {
+        if rule.kind != FeatureKind::Prefab {
+            continue;
+        }
+        let choices: Vec<&Arc<Prefab>> = rule
+            .prefab
+            .names
+            .iter()
+            .filter_map(|name| params.prefabs.get(name))
+            .collect();
+        if choices.is_empty() {
+            continue;
+        }
+        let cell = choices
+            .iter()
+            .map(|p| p.size.0.max(p.size.2))
+            .fold(rule.prefab.spacing, i32::max)
+            .max(1);
+        let salt = rule.salt(ri);
+        for cz in min.2.div_euclid(cell)..=(max.2 - 1).div_euclid(cell) {
+            for cx in min.0.div_euclid(cell)..=(max.0 - 1).div_euclid(cell) {
+                if let Some(p) =
+                    place_in_cell(world, sampler, rule, ri, salt, &choices, cell, cx, cz)
+                {
+                    let (w, h, d) = p.size();
+                    let (ox, oy, oz) = p.origin;
+                    if ox < max.0
+                        && ox + w > min.0
+                        && oz < max.2
+                        && oz + d > min.2
+                        && oy < max.1
+                        && oy + h > min.1
+                    {
+                        out.push(p);
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+// The placement of `rule` in cell `(cx, cz)`, if the draw keeps it and the ground
+// under the footprint satisfies the rule.
+#[allow(clippy::too_many_arguments)]
+fn place_in_cell(
+    world: &World,
+    sampler: &mut ColumnSampler<'_, '_>,
+    rule: &FeatureRule,
+    ri: usize,
+    salt: u32,
+    choices: &[&Arc<Prefab>],
+    cell: i32,
+    cx: i32,
+    cz: i32,
+) -> Option<PrefabPlacement> {
+    let mut rng = CellRng::new(world.seed as u32, salt, cx, cz);
+    let keep = rng.unit() < rule.when.chance.unwrap_or(1.0);
+    let prefab = choices[rng.below(choices.len() as u32) as usize];
+    let rotation = rng.below(4) as u8;
+    let (w, d) = prefab.footprint(rotation);
+    let ox = cx * cell + rng.below((cell - w + 1).max(1) as u32) as i32;
+    let oz = cz * cell + rng.below((cell - d + 1).max(1) as u32) as i32;
+    let depth = rng.range(rule.prefab.depth) as i32;
+    if !keep {
+        return None;
+    }
+
+    let (center_x, center_z) = (ox + w / 2, oz + d / 2);
+    let ground = sampler.raw_height(center_x, center_z);
+    if let Some(max_slope) = rule.prefab.max_slope {
+        let corners = [
+            (ox, oz),
+            (ox + w - 1, oz),
+            (ox, oz + d - 1),
+            (ox + w - 1, oz + d - 1),
+        ]
+        .map(|(x, z)| sampler.raw_height(x, z));
+        let lo = corners.iter().copied().fold(ground, i32::min);
+        let hi = corners.iter().copied().fold(ground, i32::max);
+        if hi - lo > max_slope {
+            return None;
+        }
+    }
+    let surface = sampler.top_block_for_column(center_x, center_z, ground);
+    let water_level = sampler.water_level_for(center_x, center_z);
+    if !rule.prefab_accepts(surface, ground, water_level) {
+        return None;
+    }
+    Some(PrefabPlacement {
+        rule: ri,
+        prefab: Arc::clone(prefab),
+        rotation,
+        origin: (ox, ground - prefab.sink - depth, oz),
+    })
+}
+
+/// Block a prefab puts at `(x, y, z)`, for point queries; chunk generation stamps the
+/// same placements in bulk. Later rules win where prefabs overlap.
+pub(super) fn apply_prefab_blocks(
+    world: &World,
+    reg: &BlockRegistry,
+    sampler: &mut ColumnSampler<'_, '_>,
+    x: i32,
+    y: i32,
+    z: i32,
+) -> Option<RtBlock> {
+    if sampler.params.prefabs.is_empty()
+        || !sampler
+            .params
+            .features
+            .iter()
+            .any(|r| r.kind == FeatureKind::Prefab)
+    {
+        return None;
+    }
+    sampler.profiler_mut().begin_stage(TerrainStage::Trees);
+    let stage_start = Instant::now();
+    let placed = plan_prefabs(world, sampler, (x, y, z), (x + 1, y + 1, z + 1));
+    let block = placed.iter().rev().find_map(|p| {
+        let i = p.palette_index(x, y, z)?;
+        Some(resolve_prefab_block(
+            world,
+            reg,
+            &p.prefab.palette[i],
+            p.rotation,
+        ))
+    });
+    sampler
+        .profiler_mut()
+        .record_stage_duration(TerrainStage::Trees, stage_start.elapsed());
+    block
+}
+
+fn resolve_prefab_block(
+    world: &World,
+    reg: &BlockRegistry,
+    block: &PrefabBlock,
+    rotation: u8,
+) -> RtBlock {
+    let id = world.resolve_block_id(reg, &block.name);
+    let state = if block.props.is_empty() {
+        0
+    } else {
+        reg.get(id)
+            .map_or(0, |ty| ty.pack_state(&block.rotated_props(rotation)))
+    };
+    RtBlock { id, state }
+}
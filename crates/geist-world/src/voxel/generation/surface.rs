@@ -16,10 +16,9 @@ pub(super) fn select_surface_block<'p>(
         "air"
     } else if y == height - 1 {
         sampler.top_block_for_column(x, z, height)
-    } else if y + sampler.params.topsoil_thickness >= height {
-        sampler.params.sub_near.as_str()
     } else {
-        sampler.params.sub_deep.as_str()
+        let (near, deep, topsoil) = sampler.subsoil_for_column(x, z);
+        if y + topsoil >= height { near } else { deep }
     };
     sampler
         .profiler_mut()
@@ -58,8 +58,7 @@ pub fn evaluate_tower(
 ) -> Option<RtBlock> {
     profiler.begin_stage(TerrainStage::Tower);
     let stage_start = Instant::now();
-    let tower_center_x = (world.world_size_x() as i32) / 2;
-    let tower_center_z = (world.world_size_z() as i32) / 2;
+    let (tower_center_x, tower_center_z) = world.center();
     let dx = x - tower_center_x;
     let dz = z - tower_center_z;
     let dist2 = (dx as i64).pow(2) + (dz as i64).pow(2);
@@ -3,14 +3,20 @@ pub const CHUNK_SIZE: usize = 64;
 mod chunk_coord;
 mod gen_ctx;
 pub mod generation;
+pub mod nav;
+mod noise;
 pub mod overview;
+mod png;
 mod tile_cache;
 mod world;
+mod worldgen_revs;
 
 pub use chunk_coord::ChunkCoord;
 pub use gen_ctx::{
     ChunkTiming, GenCtx, HeightTileStats, TERRAIN_STAGE_COUNT, TERRAIN_STAGE_LABELS,
     TerrainMetrics, TerrainProfiler, TerrainStage, TerrainStageSample,
 };
+pub use noise::{NOISE_LANES, NoiseBackend, NoiseField};
 pub use tile_cache::{TerrainTile, TerrainTileCache, TerrainTileCacheStats};
-pub use world::{World, WorldGenMode};
+pub use world::{World, WorldExtent, WorldGenMode};
+pub use worldgen_revs::{ColumnRebuild, WorldgenRebuildPlan, WorldgenRevTracker};
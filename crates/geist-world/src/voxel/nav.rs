@@ -0,0 +1,302 @@
+//! Coarse navigation summary of the generated surface: one node per chunk column, linked to
+//! its four neighbours by how many boundary cells an agent can walk or swim across.
+//!
+//! The summary reads the same height tiles and water levels worldgen uses, so it describes
+//! the terrain before caves, trees, towers and edits. Water cells stand in for rivers and
+//! lakes; worldgen has no roads yet, so none are reported.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::voxel::generation::ColumnSampler;
+use crate::voxel::{World, WorldGenMode};
+use crate::worldgen::WorldGenParams;
+
+pub const SLOPE_CLASS_COUNT: usize = 4;
+
+/// Largest height step between neighbouring columns an agent can walk up or down.
+pub const NAV_MAX_STEP: i32 = 1;
+
+/// Steepest rise from a column to any of its four neighbours.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+pub enum SlopeClass {
+    Flat,
+    Gentle,
+    Steep,
+    Cliff,
+}
+
+impl SlopeClass {
+    pub fn from_rise(rise: i32) -> Self {
+        match rise.abs() {
+            0 => SlopeClass::Flat,
+            1 => SlopeClass::Gentle,
+            2..=3 => SlopeClass::Steep,
+            _ => SlopeClass::Cliff,
+        }
+    }
+
+    #[inline]
+    pub fn is_walkable(self) -> bool {
+        matches!(self, SlopeClass::Flat | SlopeClass::Gentle)
+    }
+}
+
+/// Surface statistics for one chunk column.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ChunkNavSummary {
+    pub cx: i32,
+    pub cz: i32,
+    /// Dry cells no steeper than `SlopeClass::Gentle`.
+    pub walkable: u32,
+    /// Cells whose surface lies under water.
+    pub water: u32,
+    /// Dry cells per slope class, indexed by `SlopeClass as usize`.
+    pub slopes: [u32; SLOPE_CLASS_COUNT],
+    pub min_height: i32,
+    pub max_height: i32,
+}
+
+/// Link between two chunk columns sharing a face, `from` being the -X or -Z side.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct NavEdge {
+    pub from: (i32, i32),
+    pub to: (i32, i32),
+    /// Boundary cell pairs that are both dry and within `NAV_MAX_STEP` of each other.
+    pub walk_crossings: u32,
+    /// Boundary cell pairs where either side is water.
+    pub water_crossings: u32,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct NavGraph {
+    /// Chunk columns sorted by (cz, cx).
+    pub nodes: Vec<ChunkNavSummary>,
+    /// Edges with at least one crossing, sorted like `nodes` by their `from` column.
+    pub edges: Vec<NavEdge>,
+}
+
+/// Surface height (first air y) and water flag for each cell of a chunk column and a one
+/// cell margin around it.
+struct SurfaceGrid {
+    size_x: usize,
+    size_z: usize,
+    heights: Vec<i32>,
+    water: Vec<bool>,
+}
+
+impl SurfaceGrid {
+    #[inline]
+    fn idx(&self, x: i32, z: i32) -> usize {
+        (z + 1) as usize * (self.size_x + 2) + (x + 1) as usize
+    }
+
+    #[inline]
+    fn height(&self, x: i32, z: i32) -> i32 {
+        self.heights[self.idx(x, z)]
+    }
+
+    #[inline]
+    fn is_water(&self, x: i32, z: i32) -> bool {
+        self.water[self.idx(x, z)]
+    }
+
+    fn summary(&self, cx: i32, cz: i32) -> ChunkNavSummary {
+        let mut out = ChunkNavSummary {
+            cx,
+            cz,
+            walkable: 0,
+            water: 0,
+            slopes: [0; SLOPE_CLASS_COUNT],
+            min_height: i32::MAX,
+            max_height: i32::MIN,
+        };
+        for z in 0..self.size_z as i32 {
+            for x in 0..self.size_x as i32 {
+                let h = self.height(x, z);
+                out.min_height = out.min_height.min(h);
+                out.max_height = out.max_height.max(h);
+                if self.is_water(x, z) {
+                    out.water += 1;
+                    continue;
+                }
+                let rise = [(1, 0), (-1, 0), (0, 1), (0, -1)]
+                    .iter()
+                    .map(|(dx, dz)| (self.height(x + dx, z + dz) - h).abs())
+                    .max()
+                    .unwrap_or(0);
+                let slope = SlopeClass::from_rise(rise);
+                out.slopes[slope as usize] += 1;
+                if slope.is_walkable() {
+                    out.walkable += 1;
+                }
+            }
+        }
+        out
+    }
+
+    /// Count crossings over the +X (`along_x`) or +Z face into the margin cells.
+    fn crossings(&self, along_x: bool) -> (u32, u32) {
+        let (mut walk, mut water) = (0, 0);
+        let n = if along_x { self.size_z } else { self.size_x } as i32;
+        for i in 0..n {
+            let (a, b) = if along_x {
+                ((self.size_x as i32 - 1, i), (self.size_x as i32, i))
+            } else {
+                ((i, self.size_z as i32 - 1), (i, self.size_z as i32))
+            };
+            if self.is_water(a.0, a.1) || self.is_water(b.0, b.1) {
+                water += 1;
+            } else if (self.height(a.0, a.1) - self.height(b.0, b.1)).abs() <= NAV_MAX_STEP {
+                walk += 1;
+            }
+        }
+        (walk, water)
+    }
+}
+
+impl NavGraph {
+    /// Summarize chunk columns `min..max` (exclusive, in chunk coordinates).
+    pub fn generate(world: &World, min: (i32, i32), max: (i32, i32)) -> NavGraph {
+        let mut graph = NavGraph::default();
+        let mut ctx = world.make_gen_ctx();
+        let params_guard: Arc<WorldGenParams> = Arc::clone(&ctx.params);
+        let params = &*params_guard;
+        let sx = world.chunk_size_x;
+        let sz = world.chunk_size_z;
+        for cz in min.1..max.1 {
+            for cx in min.0..max.0 {
+                let base_x = cx * sx as i32 - 1;
+                let base_z = cz * sz as i32 - 1;
+                let cells = (sx + 2) * (sz + 2);
+                let mut grid = SurfaceGrid {
+                    size_x: sx,
+                    size_z: sz,
+                    heights: Vec::with_capacity(cells),
+                    water: Vec::with_capacity(cells),
+                };
+                if let WorldGenMode::Flat { thickness } = world.mode {
+                    grid.heights.resize(cells, thickness.max(0));
+                    grid.water.resize(cells, false);
+                } else {
+                    world.prepare_height_tile(&mut ctx, base_x, base_z, sx + 2, sz + 2);
+                    let Some(tile) = ctx.height_tile.clone() else {
+                        continue;
+                    };
+                    let mut sampler = ColumnSampler::new(world, &mut ctx, params);
+                    for dz in 0..(sz + 2) as i32 {
+                        for dx in 0..(sx + 2) as i32 {
+                            let (wx, wz) = (base_x + dx, base_z + dz);
+                            let h = tile.height(wx, wz).unwrap_or(0);
+                            let level = sampler.water_level_for(wx, wz);
+                            grid.heights.push(h);
+                            grid.water.push(params.water_enable && h <= level);
+                        }
+                    }
+                }
+                graph.nodes.push(grid.summary(cx, cz));
+                for (along_x, to) in [(true, (cx + 1, cz)), (false, (cx, cz + 1))] {
+                    if to.0 >= max.0 || to.1 >= max.1 {
+                        continue;
+                    }
+                    let (walk_crossings, water_crossings) = grid.crossings(along_x);
+                    if walk_crossings + water_crossings > 0 {
+                        graph.edges.push(NavEdge {
+                            from: (cx, cz),
+                            to,
+                            walk_crossings,
+                            water_crossings,
+                        });
+                    }
+                }
+            }
+        }
+        graph
+    }
+
+    pub fn node(&self, cx: i32, cz: i32) -> Option<&ChunkNavSummary> {
+        self.nodes.iter().find(|n| n.cx == cx && n.cz == cz)
+    }
+
+    /// Edges touching a chunk column, in either direction.
+    pub fn edges_of(&self, cx: i32, cz: i32) -> impl Iterator<Item = &NavEdge> {
+        self.edges
+            .iter()
+            .filter(move |e| e.from == (cx, cz) || e.to == (cx, cz))
+    }
+
+    /// Serialize for tools and mods that consume world data outside the engine.
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 4x4 column whose heights step up along X: 10, 10, 13, 14, with the +X margin at
+    /// 20. Cell (0, 0) and the margin cell beside (3, 2) are water.
+    fn stepped_grid() -> SurfaceGrid {
+        let (size_x, size_z) = (4usize, 4usize);
+        let mut grid = SurfaceGrid {
+            size_x,
+            size_z,
+            heights: Vec::new(),
+            water: Vec::new(),
+        };
+        for z in -1..=size_z as i32 {
+            for x in -1..=size_x as i32 {
+                grid.heights.push(match x {
+                    ..=1 => 10,
+                    2 => 13,
+                    3 => 14,
+                    _ => 20,
+                });
+                grid.water.push((x, z) == (0, 0) || (x, z) == (4, 2));
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn slopes_are_classified_by_the_steepest_neighbour() {
+        let s = stepped_grid().summary(3, -2);
+        assert_eq!((s.cx, s.cz), (3, -2));
+        // x=0 is flat (one cell is water), x=1 and x=2 face a 3-block step, x=3 a 6-block one.
+        assert_eq!(s.slopes, [3, 0, 8, 4]);
+        assert_eq!(s.water, 1);
+        assert_eq!(s.walkable, 3);
+        assert_eq!((s.min_height, s.max_height), (10, 14));
+        assert!(SlopeClass::from_rise(-1).is_walkable());
+        assert!(!SlopeClass::from_rise(2).is_walkable());
+    }
+
+    #[test]
+    fn crossings_count_water_and_walkable_steps() {
+        let grid = stepped_grid();
+        // +X face: the 14 -> 20 cliff blocks walking; one neighbour cell is water.
+        assert_eq!(grid.crossings(true), (0, 1));
+        // +Z face: heights match across it and no boundary cell is wet.
+        assert_eq!(grid.crossings(false), (4, 0));
+    }
+
+    #[test]
+    fn flat_world_links_every_neighbouring_column() {
+        let world = World::new(2, 1, 2, 7, WorldGenMode::Flat { thickness: 5 });
+        let cells = (world.chunk_size_x * world.chunk_size_z) as u32;
+        let graph = NavGraph::generate(&world, (0, 0), (2, 2));
+        assert_eq!(graph.nodes.len(), 4);
+        for n in &graph.nodes {
+            assert_eq!(n.walkable, cells);
+            assert_eq!(n.slopes[SlopeClass::Flat as usize], cells);
+            assert_eq!((n.min_height, n.max_height), (5, 5));
+        }
+        assert_eq!(graph.edges.len(), 4);
+        assert!(graph.edges.iter().all(|e| e.water_crossings == 0));
+        assert_eq!(graph.edges_of(0, 0).count(), 2);
+        let edge = graph.edges_of(1, 1).next().unwrap();
+        assert_eq!(edge.walk_crossings, world.chunk_size_x as u32);
+    }
+}
@@ -1,9 +1,12 @@
 use std::sync::Arc;
 use std::thread::{self, JoinHandle};
 
-use crate::voxel::generation::{ColumnSampler, caves::apply_caves_and_features};
+use crate::voxel::generation::ColumnSampler;
+use crate::voxel::generation::caves::{CarveKind, apply_caves_and_features, classify_carve};
 use crate::voxel::{GenCtx, World};
-use crate::worldgen::WorldGenParams;
+use crate::worldgen::{BiomeDefParam, WorldGenParams};
+
+pub use super::png::{PngWriter, RowDownsampler};
 
 #[derive(Clone, Copy, Debug)]
 pub struct OverviewRegion {
@@ -37,6 +40,30 @@ impl OverviewRegion {
     pub fn height(&self) -> usize {
         (self.max_z - self.min_z) as usize
     }
+
+    /// Split into a grid of `cols` x `rows` tiles, returned row by row (north to south).
+    /// Tile sizes differ by at most one block; the counts are clamped to the region size.
+    pub fn split(&self, cols: usize, rows: usize) -> Vec<Vec<OverviewRegion>> {
+        let cols = cols.clamp(1, self.width());
+        let rows = rows.clamp(1, self.height());
+        let edges = |min: i32, len: usize, n: usize| -> Vec<i32> {
+            (0..=n).map(|i| min + (len * i / n) as i32).collect()
+        };
+        let xs = edges(self.min_x, self.width(), cols);
+        let zs = edges(self.min_z, self.height(), rows);
+        zs.windows(2)
+            .map(|z| {
+                xs.windows(2)
+                    .map(|x| OverviewRegion {
+                        min_x: x[0],
+                        min_z: z[0],
+                        max_x: x[1],
+                        max_z: z[1],
+                    })
+                    .collect()
+            })
+            .collect()
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -44,11 +71,47 @@ pub enum OverviewMode {
     HeightMap,
     BiomeMap,
     CavePreview,
+    /// Horizontal cave slices at each level of the range, tiled into one contact sheet.
+    CaveSlices(CaveSliceRange),
+}
+
+/// Y levels sampled by the cave slice export: `y_min..=y_max` every `step` blocks.
+#[derive(Clone, Copy, Debug)]
+pub struct CaveSliceRange {
+    pub y_min: i32,
+    pub y_max: i32,
+    pub step: i32,
+}
+
+impl CaveSliceRange {
+    pub fn new(y_min: i32, y_max: i32, step: i32) -> Result<Self, OverviewError> {
+        if step <= 0 {
+            return Err(OverviewError::InvalidSlices("slice step must be positive"));
+        }
+        if y_min > y_max {
+            return Err(OverviewError::InvalidSlices(
+                "slice y_max must not be below y_min",
+            ));
+        }
+        Ok(Self { y_min, y_max, step })
+    }
+
+    pub fn levels(&self) -> impl Iterator<Item = i32> {
+        (self.y_min..=self.y_max).step_by(self.step.max(1) as usize)
+    }
+}
+
+/// One horizontal layer of a cave slice export.
+#[derive(Clone, Debug)]
+pub struct CaveSlice {
+    pub y: i32,
+    pub image: WorldOverviewImage,
 }
 
 #[derive(Debug)]
 pub enum OverviewError {
     InvalidRegion(&'static str),
+    InvalidSlices(&'static str),
     ThreadPanicked,
 }
 
@@ -77,6 +140,26 @@ impl WorldOverviewImage {
         let idx = (y * self.width + x) * 3;
         self.data[idx..idx + 3].copy_from_slice(&rgb);
     }
+
+    pub fn encode_png(&self) -> std::io::Result<Vec<u8>> {
+        let mut png = PngWriter::new(Vec::new(), self.width, self.height)?;
+        png.write_rows(&self.data)?;
+        png.finish()
+    }
+
+    /// Box-filtered copy `factor` times smaller on each axis (rounded up).
+    pub fn downsample(&self, factor: usize) -> WorldOverviewImage {
+        let mut sampler = RowDownsampler::new(self.width, factor);
+        let (width, height) = (sampler.output_width(), sampler.output_height(self.height));
+        let mut data = Vec::with_capacity(width * height * 3);
+        sampler.push_rows(&self.data, &mut data);
+        sampler.finish(&mut data);
+        WorldOverviewImage {
+            width,
+            height,
+            data,
+        }
+    }
 }
 
 pub struct WorldOverviewJob {
@@ -123,29 +206,45 @@ impl WorldOverview {
         let params_guard: Arc<WorldGenParams> = Arc::clone(&ctx.params);
         let params = &*params_guard;
         let world_height = self.world.world_height_hint() as i32;
-        let water_level = if params.water_enable {
-            (world_height as f32 * params.water_level_ratio).round() as i32
-        } else {
-            -1
-        };
         match mode {
             OverviewMode::HeightMap => {
-                self.render_height_map(region, water_level, world_height, &mut ctx, &mut image)?;
+                self.render_height_map(region, params, world_height, &mut ctx, &mut image)?;
             }
             OverviewMode::BiomeMap => {
-                self.render_biome_map(region, &mut ctx, &mut image)?;
+                self.render_biome_map(region, params, &mut ctx, &mut image)?;
             }
             OverviewMode::CavePreview => {
                 self.render_cave_preview(region, params, &mut ctx, &mut image)?;
             }
+            OverviewMode::CaveSlices(range) => {
+                let slices = self.render_cave_slices(region, range, params, &mut ctx)?;
+                image = cave_contact_sheet(&slices);
+            }
         }
         Ok(image)
     }
 
+    /// Render one image per Y level showing which voxels the carvers open, so tunnel
+    /// `y_scale` and room thresholds can be tuned layer by layer.
+    pub fn generate_cave_slices(
+        &self,
+        region: OverviewRegion,
+        range: CaveSliceRange,
+    ) -> Result<Vec<CaveSlice>, OverviewError> {
+        if region.min_x >= region.max_x || region.min_z >= region.max_z {
+            return Err(OverviewError::InvalidRegion(
+                "region max must be greater than min",
+            ));
+        }
+        let mut ctx = self.world.make_gen_ctx();
+        let params_guard: Arc<WorldGenParams> = Arc::clone(&ctx.params);
+        self.render_cave_slices(region, range, &params_guard, &mut ctx)
+    }
+
     fn render_height_map(
         &self,
         region: OverviewRegion,
-        water_level: i32,
+        params: &WorldGenParams,
         world_height: i32,
         ctx: &mut GenCtx,
         image: &mut WorldOverviewImage,
@@ -167,7 +266,8 @@ impl WorldOverview {
                     chunk_sx as usize,
                     chunk_sz as usize,
                 );
-                if let Some(tile) = ctx.height_tile.as_ref() {
+                if let Some(tile) = ctx.height_tile.clone() {
+                    let mut sampler = ColumnSampler::new(&self.world, ctx, params);
                     for dz in 0..chunk_sz {
                         let world_z = tile_z + dz;
                         if world_z < region.min_z || world_z >= region.max_z {
@@ -179,6 +279,7 @@ impl WorldOverview {
                                 continue;
                             }
                             if let Some(height) = tile.height(world_x, world_z) {
+                                let water_level = sampler.water_level_for(world_x, world_z);
                                 let color = height_color(height, water_level, world_height);
                                 let px = (world_x - region.min_x) as usize;
                                 let py = (world_z - region.min_z) as usize;
@@ -197,6 +298,7 @@ impl WorldOverview {
     fn render_biome_map(
         &self,
         region: OverviewRegion,
+        params: &WorldGenParams,
         ctx: &mut GenCtx,
         image: &mut WorldOverviewImage,
     ) -> Result<(), OverviewError> {
@@ -217,6 +319,7 @@ impl WorldOverview {
                     chunk_sx as usize,
                     chunk_sz as usize,
                 );
+                let mut sampler = ColumnSampler::new(self.world.as_ref(), ctx, params);
                 for dz in 0..chunk_sz {
                     let world_z = tile_z + dz;
                     if world_z < region.min_z || world_z >= region.max_z {
@@ -227,7 +330,7 @@ impl WorldOverview {
                         if world_x < region.min_x || world_x >= region.max_x {
                             continue;
                         }
-                        let color = biome_color(self.world.as_ref(), world_x, world_z);
+                        let color = biome_color(sampler.biome_for(world_x, world_z));
                         let px = (world_x - region.min_x) as usize;
                         let py = (world_z - region.min_z) as usize;
                         image.put_pixel(px, py, color);
@@ -314,6 +417,126 @@ impl WorldOverview {
         }
         Ok(())
     }
+
+    fn render_cave_slices(
+        &self,
+        region: OverviewRegion,
+        range: CaveSliceRange,
+        params: &WorldGenParams,
+        ctx: &mut GenCtx,
+    ) -> Result<Vec<CaveSlice>, OverviewError> {
+        if range.step <= 0 || range.y_min > range.y_max {
+            return Err(OverviewError::InvalidSlices("empty slice range"));
+        }
+        let width = region.width();
+        let height = region.height();
+        let mut slices: Vec<CaveSlice> = range
+            .levels()
+            .map(|y| CaveSlice {
+                y,
+                image: WorldOverviewImage::new(width, height),
+            })
+            .collect();
+        let chunk_sx = self.world.chunk_size_x as i32;
+        let chunk_sz = self.world.chunk_size_z as i32;
+        let min_tile_x = region.min_x.div_euclid(chunk_sx) * chunk_sx;
+        let min_tile_z = region.min_z.div_euclid(chunk_sz) * chunk_sz;
+        let max_tile_x = (region.max_x - 1).div_euclid(chunk_sx) * chunk_sx;
+        let max_tile_z = (region.max_z - 1).div_euclid(chunk_sz) * chunk_sz;
+        let mut tile_z = min_tile_z;
+        while tile_z <= max_tile_z {
+            let mut tile_x = min_tile_x;
+            while tile_x <= max_tile_x {
+                self.world.prepare_height_tile(
+                    ctx,
+                    tile_x,
+                    tile_z,
+                    chunk_sx as usize,
+                    chunk_sz as usize,
+                );
+                if let Some(tile) = ctx.height_tile.clone() {
+                    let mut sampler = ColumnSampler::new(self.world.as_ref(), ctx, params);
+                    for dz in 0..chunk_sz {
+                        let world_z = tile_z + dz;
+                        if world_z < region.min_z || world_z >= region.max_z {
+                            continue;
+                        }
+                        for dx in 0..chunk_sx {
+                            let world_x = tile_x + dx;
+                            if world_x < region.min_x || world_x >= region.max_x {
+                                continue;
+                            }
+                            let Some(column_height) = tile.height(world_x, world_z) else {
+                                continue;
+                            };
+                            let water_level = sampler.water_level_for(world_x, world_z);
+                            let px = (world_x - region.min_x) as usize;
+                            let py = (world_z - region.min_z) as usize;
+                            for slice in &mut slices {
+                                let y = slice.y;
+                                let color = if y >= column_height {
+                                    if water_level >= 0 && y <= water_level {
+                                        SLICE_WATER
+                                    } else {
+                                        SLICE_OPEN_AIR
+                                    }
+                                } else {
+                                    match classify_carve(
+                                        self.world.as_ref(),
+                                        &sampler,
+                                        world_x,
+                                        y,
+                                        world_z,
+                                        column_height,
+                                    ) {
+                                        CarveKind::Solid => SLICE_SOLID,
+                                        CarveKind::Tunnel => SLICE_TUNNEL,
+                                        CarveKind::Room => SLICE_ROOM,
+                                    }
+                                };
+                                slice.image.put_pixel(px, py, color);
+                            }
+                        }
+                    }
+                }
+                tile_x += chunk_sx;
+            }
+            tile_z += chunk_sz;
+        }
+        Ok(slices)
+    }
+}
+
+const SLICE_SOLID: [u8; 3] = [60, 60, 65];
+const SLICE_TUNNEL: [u8; 3] = [0, 170, 200];
+const SLICE_ROOM: [u8; 3] = [200, 80, 220];
+const SLICE_WATER: [u8; 3] = [20, 50, 120];
+const SLICE_OPEN_AIR: [u8; 3] = [16, 18, 24];
+const SHEET_GAP: usize = 2;
+
+/// Tile slices into a near-square grid, lowest Y first, separated by black gutters.
+pub fn cave_contact_sheet(slices: &[CaveSlice]) -> WorldOverviewImage {
+    let Some(first) = slices.first() else {
+        return WorldOverviewImage::new(0, 0);
+    };
+    let (tile_w, tile_h) = (first.image.width, first.image.height);
+    let cols = (slices.len() as f32).sqrt().ceil().max(1.0) as usize;
+    let rows = slices.len().div_ceil(cols);
+    let mut sheet = WorldOverviewImage::new(
+        cols * tile_w + (cols - 1) * SHEET_GAP,
+        rows * tile_h + (rows - 1) * SHEET_GAP,
+    );
+    for (i, slice) in slices.iter().enumerate() {
+        let ox = (i % cols) * (tile_w + SHEET_GAP);
+        let oy = (i / cols) * (tile_h + SHEET_GAP);
+        for row in 0..tile_h.min(slice.image.height) {
+            let src = row * slice.image.width * 3;
+            let len = tile_w.min(slice.image.width) * 3;
+            let dst = ((oy + row) * sheet.width + ox) * 3;
+            sheet.data[dst..dst + len].copy_from_slice(&slice.image.data[src..src + len]);
+        }
+    }
+    sheet
 }
 
 fn height_color(height: i32, water_level: i32, world_height: i32) -> [u8; 3] {
@@ -341,8 +564,8 @@ fn height_color(height: i32, water_level: i32, world_height: i32) -> [u8; 3] {
     }
 }
 
-fn biome_color(world: &World, wx: i32, wz: i32) -> [u8; 3] {
-    if let Some(biome) = world.biome_at(wx, wz) {
+fn biome_color(biome: Option<&BiomeDefParam>) -> [u8; 3] {
+    if let Some(biome) = biome {
         if let Some(tint) = biome.leaf_tint {
             return [
                 (tint[0] * 255.0).clamp(0.0, 255.0) as u8,
@@ -395,9 +618,161 @@ impl std::fmt::Display for OverviewError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             OverviewError::InvalidRegion(msg) => write!(f, "invalid region: {}", msg),
+            OverviewError::InvalidSlices(msg) => write!(f, "invalid slice range: {}", msg),
             OverviewError::ThreadPanicked => write!(f, "overview job panicked"),
         }
     }
 }
 
 impl std::error::Error for OverviewError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WorldGenMode;
+
+    #[test]
+    fn split_tiles_cover_the_region_exactly_once() {
+        let region = OverviewRegion::new(-7, 3, 50, 40).unwrap();
+        for (cols, rows) in [(1, 1), (3, 2), (4, 5), (100, 1), (0, 0)] {
+            let tiles = region.split(cols, rows);
+            let expect_rows = rows.clamp(1, region.height());
+            let expect_cols = cols.clamp(1, region.width());
+            assert_eq!(tiles.len(), expect_rows);
+            let mut hits = vec![0u8; region.width() * region.height()];
+            for row in &tiles {
+                assert_eq!(row.len(), expect_cols);
+                // Every tile in a row spans the same Z band.
+                assert!(
+                    row.iter()
+                        .all(|t| (t.min_z, t.max_z) == (row[0].min_z, row[0].max_z))
+                );
+                for t in row {
+                    assert!(t.min_x < t.max_x && t.min_z < t.max_z);
+                    assert!(t.width().abs_diff(region.width() / expect_cols) <= 1);
+                    assert!(t.height().abs_diff(region.height() / expect_rows) <= 1);
+                    for z in t.min_z..t.max_z {
+                        for x in t.min_x..t.max_x {
+                            let i = (z - region.min_z) as usize * region.width()
+                                + (x - region.min_x) as usize;
+                            hits[i] += 1;
+                        }
+                    }
+                }
+            }
+            assert!(hits.iter().all(|&n| n == 1), "{cols}x{rows} split");
+        }
+    }
+
+    #[test]
+    fn downsample_rounds_the_size_up() {
+        let mut image = WorldOverviewImage::new(5, 3);
+        image.put_pixel(4, 2, [90, 60, 30]);
+        image.put_pixel(0, 0, [40, 40, 40]);
+        let small = image.downsample(2);
+        assert_eq!((small.width, small.height), (3, 2));
+        assert_eq!(small.data.len(), 3 * 2 * 3);
+        assert_eq!(small.data[..3], [10, 10, 10]);
+        // The bottom-right output pixel covers only the source corner.
+        assert_eq!(small.data[15..], [90, 60, 30]);
+        assert_eq!(image.downsample(1).data, image.data);
+    }
+
+    #[test]
+    fn cave_slice_ranges_reject_empty_ranges() {
+        assert!(matches!(
+            CaveSliceRange::new(10, 5, 1),
+            Err(OverviewError::InvalidSlices(_))
+        ));
+        assert!(CaveSliceRange::new(0, 10, 0).is_err());
+        assert!(CaveSliceRange::new(0, 10, -2).is_err());
+        let one = CaveSliceRange::new(5, 5, 1).unwrap();
+        assert_eq!(one.levels().collect::<Vec<_>>(), [5]);
+        let range = CaveSliceRange::new(0, 10, 4).unwrap();
+        assert_eq!(range.levels().collect::<Vec<_>>(), [0, 4, 8]);
+
+        // Ranges built by hand are checked again before rendering.
+        let overview = WorldOverview::new(Arc::new(World::new(1, 2, 1, 3, WorldGenMode::Normal)));
+        let region = OverviewRegion::new(0, 0, 4, 4).unwrap();
+        let backwards = CaveSliceRange {
+            y_min: 9,
+            y_max: 1,
+            step: 1,
+        };
+        assert!(matches!(
+            overview.generate_cave_slices(region, backwards),
+            Err(OverviewError::InvalidSlices(_))
+        ));
+    }
+
+    #[test]
+    fn cave_slices_classify_solid_ground_water_and_open_air() {
+        let world = World::new(1, 2, 1, 11, WorldGenMode::Normal);
+        let top = world.world_height_hint() as i32 - 1;
+        let mut params = WorldGenParams::default();
+        params.carvers_enable = false;
+        params.max_y_ratio = 0.8;
+        params.water_enable = true;
+        params.water_level = Some(top - 1);
+        world.update_worldgen_params(params);
+        let overview = WorldOverview::new(Arc::new(world));
+        let region = OverviewRegion::new(-8, -8, 8, 8).unwrap();
+        let range = CaveSliceRange::new(0, top, top - 1).unwrap();
+        let slices = overview.generate_cave_slices(region, range).unwrap();
+        let ys: Vec<i32> = slices.iter().map(|s| s.y).collect();
+        assert_eq!(ys, [0, top - 1]);
+        let all =
+            |slice: &CaveSlice, rgb: [u8; 3]| slice.image.data.chunks_exact(3).all(|p| p == rgb);
+        // Without carvers the bottom layer is solid; above the terrain the sea fills up
+        // to its level and open air starts above it.
+        assert!(all(&slices[0], SLICE_SOLID));
+        assert!(all(&slices[1], SLICE_WATER));
+        let air = overview
+            .generate_cave_slices(region, CaveSliceRange::new(top, top, 1).unwrap())
+            .unwrap();
+        assert!(all(&air[0], SLICE_OPEN_AIR));
+    }
+
+    #[test]
+    fn carved_cells_show_as_tunnels_or_rooms() {
+        let world = World::new(1, 2, 1, 11, WorldGenMode::Normal);
+        world.update_worldgen_params(WorldGenParams::default());
+        let overview = WorldOverview::new(Arc::new(world));
+        let region = OverviewRegion::new(0, 0, 64, 64).unwrap();
+        let range = CaveSliceRange::new(8, 56, 8).unwrap();
+        let slices = overview.generate_cave_slices(region, range).unwrap();
+        let palette = [
+            SLICE_SOLID,
+            SLICE_TUNNEL,
+            SLICE_ROOM,
+            SLICE_WATER,
+            SLICE_OPEN_AIR,
+        ];
+        let pixels = || slices.iter().flat_map(|s| s.image.data.chunks_exact(3));
+        assert!(pixels().all(|p| palette.iter().any(|c| p == c)));
+        assert!(pixels().any(|p| p == SLICE_SOLID));
+        assert!(pixels().any(|p| p == SLICE_TUNNEL || p == SLICE_ROOM));
+    }
+
+    #[test]
+    fn contact_sheet_tiles_slices_lowest_first() {
+        let slices: Vec<CaveSlice> = (0..5)
+            .map(|i| {
+                let mut image = WorldOverviewImage::new(4, 3);
+                image.put_pixel(0, 0, [i as u8 + 1, 0, 0]);
+                CaveSlice { y: i * 8, image }
+            })
+            .collect();
+        let sheet = cave_contact_sheet(&slices);
+        // Five slices make a 3 x 2 grid with gutters between tiles.
+        assert_eq!(
+            (sheet.width, sheet.height),
+            (3 * 4 + 2 * SHEET_GAP, 2 * 3 + SHEET_GAP)
+        );
+        for (i, _) in slices.iter().enumerate() {
+            let (ox, oy) = ((i % 3) * (4 + SHEET_GAP), (i / 3) * (3 + SHEET_GAP));
+            assert_eq!(sheet.data[(oy * sheet.width + ox) * 3], i as u8 + 1);
+        }
+        assert_eq!(cave_contact_sheet(&[]).data.len(), 0);
+    }
+}
@@ -0,0 +1,278 @@
+//! Streaming RGB8 PNG writer and a row-wise box downsampler, so overview maps larger
+//! than memory can be written one strip at a time.
+
+use std::io::{self, Write};
+
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+// Compressed bytes buffered before they are flushed as one IDAT chunk.
+const IDAT_CHUNK_BYTES: usize = 256 * 1024;
+
+/// Writes an 8-bit RGB PNG row by row; rows must be pushed top to bottom.
+pub struct PngWriter<W: Write> {
+    out: W,
+    zlib: ZlibEncoder<Vec<u8>>,
+    width: usize,
+    height: usize,
+    rows_written: usize,
+}
+
+impl<W: Write> PngWriter<W> {
+    /// Write the signature and header for a `width` x `height` image.
+    pub fn new(mut out: W, width: usize, height: usize) -> io::Result<Self> {
+        if width == 0 || height == 0 || width > u32::MAX as usize || height > u32::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "png dimensions must be between 1 and u32::MAX",
+            ));
+        }
+        out.write_all(&PNG_SIGNATURE)?;
+        let mut ihdr = Vec::with_capacity(13);
+        ihdr.extend_from_slice(&(width as u32).to_be_bytes());
+        ihdr.extend_from_slice(&(height as u32).to_be_bytes());
+        // Bit depth 8, color type 2 (RGB), deflate, adaptive filtering, no interlace.
+        ihdr.extend_from_slice(&[8, 2, 0, 0, 0]);
+        write_chunk(&mut out, b"IHDR", &ihdr)?;
+        Ok(Self {
+            out,
+            zlib: ZlibEncoder::new(Vec::new(), Compression::default()),
+            width,
+            height,
+            rows_written: 0,
+        })
+    }
+
+    pub fn rows_written(&self) -> usize {
+        self.rows_written
+    }
+
+    /// Append whole rows of packed RGB; `rgb.len()` must be a multiple of `width * 3`.
+    pub fn write_rows(&mut self, rgb: &[u8]) -> io::Result<()> {
+        let stride = self.width * 3;
+        if !rgb.len().is_multiple_of(stride) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "row data is not a whole number of rows",
+            ));
+        }
+        let rows = rgb.len() / stride;
+        if self.rows_written + rows > self.height {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "more rows than the image height",
+            ));
+        }
+        for row in rgb.chunks_exact(stride) {
+            // Filter type 0 (None) per scanline.
+            self.zlib.write_all(&[0])?;
+            self.zlib.write_all(row)?;
+        }
+        self.rows_written += rows;
+        if self.zlib.get_ref().len() >= IDAT_CHUNK_BYTES {
+            let data = std::mem::take(self.zlib.get_mut());
+            write_chunk(&mut self.out, b"IDAT", &data)?;
+        }
+        Ok(())
+    }
+
+    /// Flush the remaining image data and the trailer; fails if rows are missing.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.rows_written != self.height {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("png has {} of {} rows", self.rows_written, self.height),
+            ));
+        }
+        let data = self.zlib.finish()?;
+        write_chunk(&mut self.out, b"IDAT", &data)?;
+        write_chunk(&mut self.out, b"IEND", &[])?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+fn write_chunk<W: Write>(out: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(kind);
+    crc.update(data);
+    out.write_all(&crc.finalize().to_be_bytes())
+}
+
+/// Averages `factor` x `factor` pixel blocks of a row stream. Output is
+/// `ceil(width / factor)` wide; partial blocks at the right and bottom edges average
+/// the pixels they have.
+pub struct RowDownsampler {
+    width: usize,
+    factor: usize,
+    sums: Vec<u64>,
+    counts: Vec<u32>,
+    pending_rows: usize,
+}
+
+impl RowDownsampler {
+    pub fn new(width: usize, factor: usize) -> Self {
+        let factor = factor.max(1);
+        let out_width = width.div_ceil(factor);
+        Self {
+            width,
+            factor,
+            sums: vec![0; out_width * 3],
+            counts: vec![0; out_width],
+            pending_rows: 0,
+        }
+    }
+
+    pub fn output_width(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Output height for a source of `height` rows.
+    pub fn output_height(&self, height: usize) -> usize {
+        height.div_ceil(self.factor)
+    }
+
+    /// Feed whole source rows; completed output rows are appended to `out`.
+    pub fn push_rows(&mut self, rgb: &[u8], out: &mut Vec<u8>) {
+        for row in rgb.chunks_exact(self.width * 3) {
+            for (x, px) in row.chunks_exact(3).enumerate() {
+                let o = x / self.factor;
+                self.sums[o * 3] += px[0] as u64;
+                self.sums[o * 3 + 1] += px[1] as u64;
+                self.sums[o * 3 + 2] += px[2] as u64;
+                self.counts[o] += 1;
+            }
+            self.pending_rows += 1;
+            if self.pending_rows == self.factor {
+                self.emit(out);
+            }
+        }
+    }
+
+    /// Emit the last, partial output row if the source height was not a multiple of
+    /// the factor.
+    pub fn finish(&mut self, out: &mut Vec<u8>) {
+        if self.pending_rows > 0 {
+            self.emit(out);
+        }
+    }
+
+    fn emit(&mut self, out: &mut Vec<u8>) {
+        for (o, count) in self.counts.iter_mut().enumerate() {
+            let n = (*count).max(1) as u64;
+            for c in 0..3 {
+                out.push(((self.sums[o * 3 + c] + n / 2) / n) as u8);
+                self.sums[o * 3 + c] = 0;
+            }
+            *count = 0;
+        }
+        self.pending_rows = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::ZlibDecoder;
+
+    use super::*;
+
+    /// Check the signature and every chunk's CRC; returns (IHDR, inflated IDAT stream).
+    fn parse(png: &[u8]) -> (Vec<u8>, Vec<u8>, usize) {
+        assert_eq!(png[..8], PNG_SIGNATURE);
+        let (mut ihdr, mut idat, mut idat_chunks) = (Vec::new(), Vec::new(), 0);
+        let mut rest = &png[8..];
+        loop {
+            let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let (kind, data) = (&rest[4..8], &rest[8..8 + len]);
+            let crc = u32::from_be_bytes(rest[8 + len..12 + len].try_into().unwrap());
+            let mut hasher = crc32fast::Hasher::new();
+            hasher.update(kind);
+            hasher.update(data);
+            assert_eq!(crc, hasher.finalize(), "bad CRC on {:?}", kind);
+            rest = &rest[12 + len..];
+            match kind {
+                b"IHDR" => ihdr = data.to_vec(),
+                b"IDAT" => {
+                    idat.extend_from_slice(data);
+                    idat_chunks += 1;
+                }
+                b"IEND" => break,
+                other => panic!("unexpected chunk {:?}", other),
+            }
+        }
+        assert!(rest.is_empty(), "bytes after IEND");
+        let mut raw = Vec::new();
+        ZlibDecoder::new(&idat[..]).read_to_end(&mut raw).unwrap();
+        (ihdr, raw, idat_chunks)
+    }
+
+    #[test]
+    fn rows_round_trip_through_signature_header_and_crcs() {
+        let (w, h) = (400, 400);
+        // Noise that deflate cannot shrink, so the data spans several IDAT chunks.
+        let mut state = 0x2545_f491_u32;
+        let rgb: Vec<u8> = (0..w * h * 3)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect();
+        let mut png = PngWriter::new(Vec::new(), w, h).unwrap();
+        for strip in rgb.chunks(w * 3 * 50) {
+            png.write_rows(strip).unwrap();
+        }
+        assert_eq!(png.rows_written(), h);
+        let (ihdr, raw, idat_chunks) = parse(&png.finish().unwrap());
+
+        assert_eq!(ihdr[..4], (w as u32).to_be_bytes());
+        assert_eq!(ihdr[4..8], (h as u32).to_be_bytes());
+        assert_eq!(ihdr[8..], [8, 2, 0, 0, 0]);
+        assert!(idat_chunks > 1);
+        assert_eq!(raw.len(), h * (1 + w * 3));
+        for (y, line) in raw.chunks_exact(1 + w * 3).enumerate() {
+            assert_eq!(line[0], 0, "filter byte of row {y}");
+            assert_eq!(line[1..], rgb[y * w * 3..(y + 1) * w * 3]);
+        }
+    }
+
+    #[test]
+    fn bad_sizes_and_row_counts_are_rejected() {
+        assert!(PngWriter::new(Vec::new(), 0, 4).is_err());
+        assert!(PngWriter::new(Vec::new(), 4, 0).is_err());
+
+        let mut png = PngWriter::new(Vec::new(), 2, 2).unwrap();
+        assert!(png.write_rows(&[0; 5]).is_err());
+        png.write_rows(&[0; 6]).unwrap();
+        assert!(png.write_rows(&[0; 12]).is_err());
+        assert_eq!(
+            png.finish().unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn downsampler_averages_blocks_and_partial_edges() {
+        // 3x3 source, factor 2: a 2x2 block, two 1x2 edges and the corner pixel.
+        let px = |v: u8| [v, v, v];
+        let rows: Vec<u8> = [[10, 30, 100], [50, 70, 200], [1, 3, 255]]
+            .iter()
+            .flat_map(|row| row.iter().flat_map(|&v| px(v)))
+            .collect();
+        let mut ds = RowDownsampler::new(3, 2);
+        assert_eq!((ds.output_width(), ds.output_height(3)), (2, 2));
+        let mut out = Vec::new();
+        ds.push_rows(&rows, &mut out);
+        assert_eq!(out.len(), 2 * 3, "only the first output row is complete");
+        ds.finish(&mut out);
+        let grey: Vec<u8> = out.chunks_exact(3).map(|p| p[0]).collect();
+        assert_eq!(grey, [40, 150, 2, 255]);
+    }
+}
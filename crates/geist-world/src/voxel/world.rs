@@ -1,16 +1,19 @@
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU8, AtomicU32, Ordering};
 use std::sync::{Arc, RwLock};
 
-use fastnoise_lite::{FastNoiseLite, NoiseType};
 use geist_blocks::registry::BlockRegistry;
 use geist_blocks::types::Block as RtBlock;
+use geist_geom::{IVec3, WorldPos};
 
-use crate::worldgen::WorldGenParams;
+use crate::worldgen::{BiomeDefParam, BiomesParams, WorldGenParams};
 
 use super::{
-    CHUNK_SIZE, GenCtx,
+    CHUNK_SIZE, ChunkCoord, GenCtx,
     gen_ctx::{HeightTileStats, TerrainProfiler},
+    generation::{StagePlugins, TerrainGenerator},
+    noise::{NoiseBackend, NoiseField},
     tile_cache::{TerrainTileCache, TerrainTileCacheStats},
 };
 
@@ -18,21 +21,45 @@ pub struct World {
     pub chunk_size_x: usize,
     pub chunk_size_y: usize,
     pub chunk_size_z: usize,
+    /// Chunk columns along X and Z to size caches and layouts for. Only a finite extent
+    /// turns them into the world's actual size.
     pub chunks_x: usize,
     pub chunks_y_hint: usize,
     pub chunks_z: usize,
     pub seed: i32,
     pub mode: WorldGenMode,
+    extent: WorldExtent,
     pub gen_params: Arc<RwLock<Arc<WorldGenParams>>>,
     block_id_cache: RwLock<HashMap<String, u16>>,
     tile_cache: Arc<TerrainTileCache>,
     worldgen_rev: AtomicU32,
+    noise_backend: AtomicU8,
+    pub(super) generator: Option<Arc<dyn TerrainGenerator>>,
+    pub(super) stage_plugins: StagePlugins,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+/// Horizontal extent. An unbounded world exists wherever streaming asks for chunks; a
+/// finite one is clamped to `0..chunks` on X and Z.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum WorldExtent {
+    #[default]
+    Unbounded,
+    Finite {
+        chunks_x: usize,
+        chunks_z: usize,
+    },
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum WorldGenMode {
     Normal,
-    Flat { thickness: i32 },
+    Flat {
+        thickness: i32,
+    },
+    /// Terrain imported from a heightmap image; the world's generator supplies the columns.
+    Heightmap {
+        path: PathBuf,
+    },
 }
 
 impl World {
@@ -52,23 +79,61 @@ impl World {
             chunks_z,
             seed,
             mode,
+            extent: WorldExtent::Unbounded,
             gen_params: Arc::new(RwLock::new(Arc::new(WorldGenParams::default()))),
             block_id_cache: RwLock::new(HashMap::new()),
             tile_cache: Arc::new(TerrainTileCache::new(
                 (chunks_x.max(4) * chunks_z.max(4) * 4).max(64),
             )),
             worldgen_rev: AtomicU32::new(1),
+            noise_backend: AtomicU8::new(NoiseBackend::default().as_u8()),
+            generator: None,
+            stage_plugins: StagePlugins::default(),
         }
     }
 
+    /// Clamp the world to `extent`; unbounded by default.
+    pub fn with_extent(mut self, extent: WorldExtent) -> Self {
+        self.extent = extent;
+        self
+    }
+
     #[inline]
-    pub fn world_size_x(&self) -> usize {
-        self.chunk_size_x * self.chunks_x
+    pub fn extent(&self) -> WorldExtent {
+        self.extent
     }
 
+    /// Block bounds `(min_x, min_z, max_x, max_z)` of a finite world, `max` exclusive.
+    pub fn block_bounds(&self) -> Option<(i32, i32, i32, i32)> {
+        match self.extent {
+            WorldExtent::Unbounded => None,
+            WorldExtent::Finite { chunks_x, chunks_z } => Some((
+                0,
+                0,
+                (chunks_x * self.chunk_size_x) as i32,
+                (chunks_z * self.chunk_size_z) as i32,
+            )),
+        }
+    }
+
+    /// Whether the chunk column at `coord` exists; always true for unbounded worlds.
     #[inline]
-    pub fn world_size_z(&self) -> usize {
-        self.chunk_size_z * self.chunks_z
+    pub fn contains_chunk(&self, coord: ChunkCoord) -> bool {
+        match self.extent {
+            WorldExtent::Unbounded => true,
+            WorldExtent::Finite { chunks_x, chunks_z } => {
+                (0..chunks_x as i32).contains(&coord.cx) && (0..chunks_z as i32).contains(&coord.cz)
+            }
+        }
+    }
+
+    /// Horizontal center the tower and spawn sit on: the middle of a finite world, or
+    /// the origin of an unbounded one.
+    pub fn center(&self) -> (i32, i32) {
+        match self.block_bounds() {
+            Some((min_x, min_z, max_x, max_z)) => ((min_x + max_x) / 2, (min_z + max_z) / 2),
+            None => (0, 0),
+        }
     }
 
     #[inline]
@@ -76,6 +141,18 @@ impl World {
         self.chunks_y_hint
     }
 
+    /// Chunk dimensions for `WorldPos`/`ChunkPos`/`LocalPos` conversions.
+    #[inline]
+    pub fn chunk_dims(&self) -> IVec3 {
+        IVec3::from_dims(self.chunk_size_x, self.chunk_size_y, self.chunk_size_z)
+    }
+
+    /// Chunk containing world voxel `(wx, wy, wz)`.
+    #[inline]
+    pub fn chunk_of(&self, wx: i32, wy: i32, wz: i32) -> ChunkCoord {
+        WorldPos::new(wx, wy, wz).chunk(self.chunk_dims()).into()
+    }
+
     #[inline]
     pub fn world_height_hint(&self) -> usize {
         self.chunk_size_y * self.chunks_y_hint
@@ -108,54 +185,47 @@ impl World {
     }
 
     pub fn make_gen_ctx(&self) -> GenCtx {
-        // PERF: Initialises several FastNoiseLite instances; keep one `GenCtx` per worker instead of per voxel.
-        let params = {
+        // PERF: Initialises several noise fields; keep one `GenCtx` per worker instead of per voxel.
+        // Read the revision under the same lock as the params so the pair always matches.
+        let (params, worldgen_rev) = {
             let guard = self.gen_params.read().unwrap();
-            Arc::clone(&*guard)
+            (Arc::clone(&*guard), self.current_worldgen_rev())
         };
-        let mut terrain = FastNoiseLite::with_seed(self.seed);
-        terrain.set_noise_type(Some(NoiseType::OpenSimplex2));
-        terrain.set_frequency(Some(params.height_frequency));
-        let mut warp = FastNoiseLite::with_seed(self.seed ^ 99_173);
-        warp.set_noise_type(Some(NoiseType::OpenSimplex2));
-        warp.set_frequency(Some(0.012));
-        let mut tunnel = FastNoiseLite::with_seed(self.seed ^ 41_337);
-        tunnel.set_noise_type(Some(NoiseType::OpenSimplex2));
-        tunnel.set_frequency(Some(0.017));
-        let (temp2d, moist2d) = if let Some(b) = params.biomes.as_ref() {
-            let b = &**b;
-            let mut t = FastNoiseLite::with_seed(self.seed ^ 0x1203_5F31);
-            t.set_noise_type(Some(NoiseType::OpenSimplex2));
-            t.set_frequency(Some(b.temp_freq));
-            let mut m = FastNoiseLite::with_seed(((self.seed as u32) ^ 0x92E3_A1B2u32) as i32);
-            m.set_noise_type(Some(NoiseType::OpenSimplex2));
-            m.set_frequency(Some(b.moisture_freq));
-            (Some(t), Some(m))
-        } else {
-            (None, None)
+        let backend = self.noise_backend();
+        let terrain = NoiseField::open_simplex2(self.seed, params.height_frequency, backend);
+        let warp = NoiseField::open_simplex2(self.seed ^ 99_173, 0.012, backend);
+        let tunnel = NoiseField::open_simplex2(self.seed ^ 41_337, 0.017, backend);
+        let (temp2d, moist2d) = match params.biomes.as_ref() {
+            Some(b) => {
+                let (t, m) = self.climate_fields(b, backend);
+                (Some(t), Some(m))
+            }
+            None => (None, None),
         };
         GenCtx {
             terrain,
             warp,
             tunnel,
             params,
+            worldgen_rev,
             temp2d,
             moist2d,
             height_tile_stats: HeightTileStats::default(),
             height_tile: None,
             tile_cache_stats: TerrainTileCacheStats::default(),
             terrain_profiler: TerrainProfiler::default(),
+            lake_basins: HashMap::new(),
         }
     }
 
     pub fn update_worldgen_params(&self, params: WorldGenParams) {
         if let Ok(mut guard) = self.gen_params.write() {
             *guard = Arc::new(params);
+            self.worldgen_rev.fetch_add(1, Ordering::AcqRel);
         }
         if let Ok(mut ids) = self.block_id_cache.write() {
             ids.clear();
         }
-        self.worldgen_rev.fetch_add(1, Ordering::AcqRel);
         self.tile_cache.invalidate_all();
     }
 
@@ -169,6 +239,17 @@ impl World {
         self.tile_cache.snapshot()
     }
 
+    /// Backend used by batch noise sampling in gen contexts created from now on.
+    #[inline]
+    pub fn noise_backend(&self) -> NoiseBackend {
+        NoiseBackend::from_u8(self.noise_backend.load(Ordering::Relaxed))
+    }
+
+    /// Both backends produce identical terrain; this only trades speed for a reference path.
+    pub fn set_noise_backend(&self, backend: NoiseBackend) {
+        self.noise_backend.store(backend.as_u8(), Ordering::Relaxed);
+    }
+
     #[inline]
     pub fn current_worldgen_rev(&self) -> u32 {
         self.worldgen_rev.load(Ordering::Acquire)
@@ -179,34 +260,35 @@ impl World {
         &self.tile_cache
     }
 
-    pub fn biome_at(&self, wx: i32, wz: i32) -> Option<crate::worldgen::BiomeDefParam> {
+    /// Temperature and moisture noise sampled by biome lookups.
+    fn climate_fields(&self, b: &BiomesParams, backend: NoiseBackend) -> (NoiseField, NoiseField) {
+        let t = NoiseField::open_simplex2(self.seed ^ 0x1203_5F31, b.temp_freq, backend);
+        let m = NoiseField::open_simplex2(
+            ((self.seed as u32) ^ 0x92E3_A1B2u32) as i32,
+            b.moisture_freq,
+            backend,
+        );
+        (t, m)
+    }
+
+    /// Biome of a column, picked the same way chunk generation picks it. Builds its own
+    /// noise fields; bulk lookups should go through a `ColumnSampler` instead.
+    pub fn biome_at(&self, wx: i32, wz: i32) -> Option<BiomeDefParam> {
         let params = {
             let guard = self.gen_params.read().ok()?;
             Arc::clone(&*guard)
         };
-        let biomes = params.biomes.as_ref()?.clone();
-        let b = &*biomes;
-        let mut t = FastNoiseLite::with_seed(self.seed ^ 0x1203_5F31);
-        t.set_noise_type(Some(NoiseType::OpenSimplex2));
-        t.set_frequency(Some(b.temp_freq));
-        let mut m = FastNoiseLite::with_seed(((self.seed as u32) ^ 0x92E3_A1B2u32) as i32);
-        m.set_noise_type(Some(NoiseType::OpenSimplex2));
-        m.set_frequency(Some(b.moisture_freq));
-        let sx = if b.scale_x == 0.0 { 1.0 } else { b.scale_x };
-        let sz = if b.scale_z == 0.0 { 1.0 } else { b.scale_z };
-        let x = wx as f32 * sx;
-        let z = wz as f32 * sz;
-        let temp = (t.get_noise_2d(x, z) * 0.5 + 0.5).clamp(0.0, 1.0);
-        let moist = (m.get_noise_2d(x, z) * 0.5 + 0.5).clamp(0.0, 1.0);
-        for def in &b.defs {
-            if temp >= def.temp_min
-                && temp < def.temp_max
-                && moist >= def.moisture_min
-                && moist < def.moisture_max
-            {
-                return Some(def.clone());
+        let b = &**params.biomes.as_ref()?;
+        let idx = match b.debug_cell_biome(wx, wz) {
+            Some(idx) => idx,
+            None => {
+                let (t, m) = self.climate_fields(b, self.noise_backend());
+                let (x, z) = b.climate_coords(wx, wz);
+                let temp = (t.get_noise_2d(x, z) * 0.5 + 0.5).clamp(0.0, 1.0);
+                let moist = (m.get_noise_2d(x, z) * 0.5 + 0.5).clamp(0.0, 1.0);
+                b.classify(temp, moist)?
             }
-        }
-        None
+        };
+        b.defs.get(idx).cloned()
     }
 }
@@ -0,0 +1,151 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::ChunkCoord;
+
+/// Worldgen revision each built chunk was generated under.
+///
+/// `World::current_worldgen_rev` is global, so after a params reload it cannot say which
+/// loaded chunks still hold terrain from older params. Recording the revision per chunk
+/// makes that a query: a chunk is stale exactly when its revision is older than the world's.
+#[derive(Clone, Debug, Default)]
+pub struct WorldgenRevTracker {
+    revs: HashMap<ChunkCoord, u32>,
+}
+
+impl WorldgenRevTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Note that `coord` now holds blocks generated under `rev`.
+    pub fn record(&mut self, coord: ChunkCoord, rev: u32) {
+        self.revs.insert(coord, rev);
+    }
+
+    pub fn forget(&mut self, coord: ChunkCoord) -> Option<u32> {
+        self.revs.remove(&coord)
+    }
+
+    #[inline]
+    pub fn rev_of(&self, coord: ChunkCoord) -> Option<u32> {
+        self.revs.get(&coord).copied()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.revs.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.revs.is_empty()
+    }
+
+    #[inline]
+    pub fn is_stale(&self, coord: ChunkCoord, current_rev: u32) -> bool {
+        self.rev_of(coord).is_some_and(|rev| rev < current_rev)
+    }
+
+    /// Tracked chunks built under a revision older than `current_rev`, in no particular order.
+    pub fn stale(&self, current_rev: u32) -> impl Iterator<Item = (ChunkCoord, u32)> + '_ {
+        self.revs
+            .iter()
+            .filter(move |&(_, &rev)| rev < current_rev)
+            .map(|(&coord, &rev)| (coord, rev))
+    }
+
+    pub fn stale_count(&self, current_rev: u32) -> usize {
+        self.stale(current_rev).count()
+    }
+
+    /// Group the stale chunks into columns, nearest to `center` first.
+    pub fn rebuild_plan(&self, current_rev: u32, center: ChunkCoord) -> WorldgenRebuildPlan {
+        let mut columns: HashMap<(i32, i32), Vec<i32>> = HashMap::new();
+        let mut stale_by_rev: BTreeMap<u32, usize> = BTreeMap::new();
+        for (coord, rev) in self.stale(current_rev) {
+            columns
+                .entry((coord.cx, coord.cz))
+                .or_default()
+                .push(coord.cy);
+            *stale_by_rev.entry(rev).or_default() += 1;
+        }
+        let mut columns: Vec<ColumnRebuild> = columns
+            .into_iter()
+            .map(|((cx, cz), mut cys)| {
+                cys.sort_unstable_by(|a, b| b.cmp(a));
+                ColumnRebuild { cx, cz, cys }
+            })
+            .collect();
+        columns.sort_by_key(|c| {
+            let (dx, dz) = (i64::from(c.cx - center.cx), i64::from(c.cz - center.cz));
+            (dx * dx + dz * dz, c.cx, c.cz)
+        });
+        let stale: usize = stale_by_rev.values().sum();
+        WorldgenRebuildPlan {
+            target_rev: current_rev,
+            columns,
+            up_to_date: self.revs.len() - stale,
+            stale_by_rev: stale_by_rev.into_iter().collect(),
+        }
+    }
+}
+
+/// Stale chunks of one column, top down so skylight from above lands before the chunks below.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnRebuild {
+    pub cx: i32,
+    pub cz: i32,
+    pub cys: Vec<i32>,
+}
+
+/// Consolidated set of chunks to regenerate after a worldgen change.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WorldgenRebuildPlan {
+    /// Revision the rebuilt chunks will be generated under.
+    pub target_rev: u32,
+    /// Columns holding stale chunks, nearest to the plan's center first.
+    pub columns: Vec<ColumnRebuild>,
+    /// Tracked chunks already generated under `target_rev`.
+    pub up_to_date: usize,
+    /// Stale chunk counts per older revision, ascending.
+    pub stale_by_rev: Vec<(u32, usize)>,
+}
+
+impl WorldgenRebuildPlan {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.columns.iter().map(|c| c.cys.len()).sum()
+    }
+
+    /// Chunks in rebuild order.
+    pub fn coords(&self) -> impl Iterator<Item = ChunkCoord> + '_ {
+        self.columns
+            .iter()
+            .flat_map(|c| c.cys.iter().map(move |&cy| ChunkCoord::new(c.cx, cy, c.cz)))
+    }
+
+    /// One-line description for logs.
+    pub fn summary(&self) -> String {
+        let revs: Vec<String> = self
+            .stale_by_rev
+            .iter()
+            .map(|(rev, n)| format!("rev {}: {}", rev, n))
+            .collect();
+        format!(
+            "{} chunks in {} columns to rev {} ({}); {} up to date",
+            self.chunk_count(),
+            self.columns.len(),
+            self.target_rev,
+            if revs.is_empty() {
+                "none stale".to_string()
+            } else {
+                revs.join(", ")
+            },
+            self.up_to_date
+        )
+    }
+}
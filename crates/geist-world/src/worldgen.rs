@@ -4,6 +4,8 @@ use std::fs;
 use std::path::Path;
 use std::sync::Arc;
 
+use crate::prefab::PrefabRegistry;
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct WorldGenConfig {
     #[serde(default)]
@@ -24,6 +26,13 @@ pub struct WorldGenConfig {
     pub biomes: Biomes,
     #[serde(default)]
     pub water: Water,
+    /// Directory of prefab files for `kind = "prefab"` features, relative to this file.
+    #[serde(default = "d_prefab_dir")]
+    pub prefab_dir: String,
+}
+
+fn d_prefab_dir() -> String {
+    "prefabs".to_string()
 }
 
 impl Default for WorldGenConfig {
@@ -38,6 +47,7 @@ impl Default for WorldGenConfig {
             features: Vec::new(),
             biomes: Biomes::default(),
             water: Water::default(),
+            prefab_dir: d_prefab_dir(),
         }
     }
 }
@@ -170,6 +180,11 @@ pub struct Water {
     pub enable: bool,
     #[serde(default = "default_water_level_ratio")]
     pub level_ratio: f32,
+    // Absolute sea level in voxels; overrides `level_ratio` when set.
+    #[serde(default)]
+    pub level: Option<i32>,
+    #[serde(default)]
+    pub lakes: Lakes,
 }
 fn default_water_enable() -> bool {
     true
@@ -182,6 +197,46 @@ impl Default for Water {
         Self {
             enable: true,
             level_ratio: default_water_level_ratio(),
+            level: None,
+            lakes: Lakes::default(),
+        }
+    }
+}
+
+// Elevated lakes: basins in the height field filled up to their spill height.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Lakes {
+    #[serde(default)]
+    pub enable: bool,
+    #[serde(default = "default_lake_cell_size")]
+    pub cell_size: i32,
+    #[serde(default = "default_lake_rim_radius")]
+    pub rim_radius: i32,
+    #[serde(default = "default_lake_rim_samples")]
+    pub rim_samples: u32,
+    #[serde(default = "default_lake_min_depth")]
+    pub min_depth: i32,
+}
+fn default_lake_cell_size() -> i32 {
+    128
+}
+fn default_lake_rim_radius() -> i32 {
+    40
+}
+fn default_lake_rim_samples() -> u32 {
+    24
+}
+fn default_lake_min_depth() -> i32 {
+    2
+}
+impl Default for Lakes {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            cell_size: default_lake_cell_size(),
+            rim_radius: default_lake_rim_radius(),
+            rim_samples: default_lake_rim_samples(),
+            min_depth: default_lake_min_depth(),
         }
     }
 }
@@ -386,18 +441,37 @@ pub struct WorldGenParams {
     pub trunk_max: i32,
     pub leaf_radius: i32,
     pub features: Arc<[FeatureRule]>,
+    /// Prefabs that `kind = "prefab"` features stamp, by name.
+    pub prefabs: Arc<PrefabRegistry>,
     pub biomes: Option<Arc<BiomesParams>>,
     // Platform controls (for flying structures)
     pub platform_y_ratio: f32,
     pub platform_y_offset: f32,
     pub water_enable: bool,
     pub water_level_ratio: f32,
+    pub water_level: Option<i32>,
+    pub lakes_enable: bool,
+    pub lake_cell_size: i32,
+    pub lake_rim_radius: i32,
+    pub lake_rim_samples: u32,
+    pub lake_min_depth: i32,
 }
 
 impl WorldGenParams {
     pub fn default() -> Self {
         Self::from_config(&WorldGenConfig::default())
     }
+
+    /// Global sea level for a world of `world_height` voxels, or -1 when water is disabled.
+    pub fn sea_level(&self, world_height: i32) -> i32 {
+        if !self.water_enable {
+            return -1;
+        }
+        match self.water_level {
+            Some(level) => level.clamp(0, world_height - 1),
+            None => (world_height as f32 * self.water_level_ratio).round() as i32,
+        }
+    }
 }
 
 impl WorldGenParams {
@@ -434,6 +508,7 @@ impl WorldGenParams {
             trunk_max: cfg.trees.trunk_max,
             leaf_radius: cfg.trees.leaf_radius,
             features: Arc::from(cfg.features.clone()),
+            prefabs: Arc::new(PrefabRegistry::default()),
             biomes: if cfg.biomes.enable {
                 Some(Arc::new(BiomesParams::from(&cfg.biomes)))
             } else {
@@ -443,6 +518,16 @@ impl WorldGenParams {
             platform_y_offset: cfg.platform.y_offset,
             water_enable: cfg.water.enable,
             water_level_ratio: cfg.water.level_ratio,
+            water_level: cfg.water.level,
+            lakes_enable: cfg.water.lakes.enable,
+            lake_cell_size: cfg.water.lakes.cell_size.max(8),
+            lake_rim_radius: cfg
+                .water
+                .lakes
+                .rim_radius
+                .clamp(2, cfg.water.lakes.cell_size.max(8)),
+            lake_rim_samples: cfg.water.lakes.rim_samples.max(4),
+            lake_min_depth: cfg.water.lakes.min_depth.max(1),
         }
     }
 }
@@ -450,17 +535,183 @@ impl WorldGenParams {
 pub fn load_params_from_path(path: &Path) -> Result<WorldGenParams, Box<dyn Error>> {
     let s = fs::read_to_string(path)?;
     let cfg: WorldGenConfig = toml::from_str(&s)?;
-    Ok(WorldGenParams::from_config(&cfg))
+    let mut params = WorldGenParams::from_config(&cfg);
+    let prefab_dir = path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join(&cfg.prefab_dir);
+    params.prefabs = Arc::new(PrefabRegistry::load_dir(&prefab_dir)?);
+    Ok(params)
 }
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct FeatureRule {
     #[serde(default)]
-    #[allow(dead_code)]
     pub name: Option<String>,
     #[serde(default)]
+    pub kind: FeatureKind,
+    #[serde(default)]
     pub when: FeatureWhen,
+    /// Block to place; prefab rules take theirs from the prefab.
+    #[serde(default)]
     pub place: FeaturePlace,
+    /// Vein bands (`kind = "vein"`).
+    #[serde(default)]
+    pub vein: VeinSpec,
+    /// Patch layout (`kind = "scatter"`).
+    #[serde(default)]
+    pub scatter: ScatterSpec,
+    /// Prefab choice and spacing (`kind = "prefab"`).
+    #[serde(default)]
+    pub prefab: PrefabSpec,
+}
+
+impl FeatureRule {
+    /// Seed salt for this rule's placements: its name when it has one, so reordering
+    /// rules does not move named veins.
+    pub fn salt(&self, index: usize) -> u32 {
+        match &self.name {
+            Some(name) => name.bytes().fold(0x811c_9dc5u32, |h, b| {
+                (h ^ b as u32).wrapping_mul(0x0100_0193)
+            }),
+            None => (index as u32).wrapping_mul(0x9E37_79B9),
+        }
+    }
+
+    fn base_allowed(&self, base: &str) -> bool {
+        let w = &self.when;
+        (w.base_in.is_empty() || w.base_in.iter().any(|s| s == base))
+            && !w.base_not_in.iter().any(|s| s == base)
+    }
+
+    /// Whether a vein voxel at `wy` may replace `base` in a column whose ground ends at
+    /// `height`. Veins never fill air.
+    pub fn vein_accepts(&self, base: &str, wy: i32, height: i32) -> bool {
+        let w = &self.when;
+        base != "air"
+            && self.base_allowed(base)
+            && w.y_min.is_none_or(|y| wy >= y)
+            && w.y_max.is_none_or(|y| wy <= y)
+            && w.below_height_offset.is_none_or(|off| wy < height - off)
+    }
+
+    /// Whether a scatter block may sit on a column whose ground ends at `height` with
+    /// `surface` on top. Submerged columns are skipped.
+    pub fn scatter_accepts(&self, surface: &str, height: i32, water_level: i32) -> bool {
+        let w = &self.when;
+        height > water_level
+            && self.base_allowed(surface)
+            && w.y_min.is_none_or(|y| height >= y)
+            && w.y_max.is_none_or(|y| height <= y)
+    }
+
+    /// Whether a prefab may stand on ground ending at `height` with `surface` on top.
+    /// Prefabs sitting on the surface skip submerged ground; buried ones do not care.
+    pub fn prefab_accepts(&self, surface: &str, height: i32, water_level: i32) -> bool {
+        let w = &self.when;
+        (self.prefab.depth != [0, 0] || height > water_level)
+            && self.base_allowed(surface)
+            && w.y_min.is_none_or(|y| height >= y)
+            && w.y_max.is_none_or(|y| height <= y)
+    }
+}
+
+/// How a feature rule places blocks.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureKind {
+    /// Per-voxel replacement inside the carver band (the original rule behavior).
+    #[default]
+    Replace,
+    /// Clustered veins seeded per cell in Y bands, e.g. ores.
+    Vein,
+    /// Blocks dropped on the surface in patches, e.g. flowers and rocks.
+    Scatter,
+    /// Prefabs from the prefab registry, at most one per `prefab.spacing` cell.
+    Prefab,
+}
+
+/// Inclusive `[min, max]` drawn uniformly per cell or vein.
+pub type CountRange = [u32; 2];
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct VeinSpec {
+    #[serde(default)]
+    pub bands: Vec<VeinBand>,
+}
+
+/// Veins between `y_min` and `y_max`: `count` per 32x32 cell, each `size` blocks.
+#[derive(Clone, Debug, Deserialize)]
+pub struct VeinBand {
+    pub y_min: i32,
+    pub y_max: i32,
+    pub count: CountRange,
+    pub size: CountRange,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ScatterSpec {
+    /// Patches per 32x32 cell.
+    #[serde(default = "d_scatter_patches")]
+    pub patches: CountRange,
+    /// Blocks tried per patch.
+    #[serde(default = "d_scatter_patch_size")]
+    pub patch_size: CountRange,
+    /// Spread of a patch around its center, in blocks.
+    #[serde(default = "d_scatter_radius")]
+    pub radius: i32,
+}
+
+fn d_scatter_patches() -> CountRange {
+    [1, 3]
+}
+fn d_scatter_patch_size() -> CountRange {
+    [4, 8]
+}
+fn d_scatter_radius() -> i32 {
+    3
+}
+
+impl Default for ScatterSpec {
+    fn default() -> Self {
+        Self {
+            patches: d_scatter_patches(),
+            patch_size: d_scatter_patch_size(),
+            radius: d_scatter_radius(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PrefabSpec {
+    /// Prefabs picked uniformly per cell.
+    #[serde(default)]
+    pub names: Vec<String>,
+    /// Side of the square cells prefabs are seeded in; raised to fit the largest prefab.
+    #[serde(default = "d_prefab_spacing")]
+    pub spacing: i32,
+    /// Blocks buried below the surface, on top of the prefab's own `sink`. `[0, 0]`
+    /// keeps prefabs on the surface.
+    #[serde(default)]
+    pub depth: CountRange,
+    /// Largest height difference between footprint corners, if limited.
+    #[serde(default)]
+    pub max_slope: Option<i32>,
+}
+
+fn d_prefab_spacing() -> i32 {
+    96
+}
+
+impl Default for PrefabSpec {
+    fn default() -> Self {
+        Self {
+            names: Vec::new(),
+            spacing: d_prefab_spacing(),
+            depth: [0, 0],
+            max_slope: None,
+        }
+    }
 }
 
 // --- Biomes (Phase 3) ---
@@ -521,6 +772,10 @@ pub struct BiomeDef {
     #[serde(default)]
     pub top_block: Option<String>,
     #[serde(default)]
+    pub subsoil: BiomeSubsoil,
+    #[serde(default)]
+    pub topsoil_thickness: Option<i32>,
+    #[serde(default)]
     pub species_weights: std::collections::HashMap<String, f32>,
     #[serde(default)]
     pub tree_density: Option<f32>,
@@ -528,6 +783,15 @@ pub struct BiomeDef {
     pub leaf_tint: Option<[f32; 3]>,
 }
 
+/// Per-biome subsoil; unset layers keep the global `[surface.subsoil]` blocks.
+#[derive(Clone, Debug, Deserialize, Default)]
+pub struct BiomeSubsoil {
+    #[serde(default)]
+    pub near_surface: Option<String>,
+    #[serde(default)]
+    pub deep: Option<String>,
+}
+
 #[derive(Clone, Debug)]
 pub struct BiomesParams {
     pub temp_freq: f32,
@@ -547,11 +811,21 @@ pub struct BiomeDefParam {
     pub moisture_min: f32,
     pub moisture_max: f32,
     pub top_block: Option<String>,
+    pub sub_near: Option<String>,
+    pub sub_deep: Option<String>,
+    pub topsoil_thickness: Option<i32>,
     pub species_weights: std::collections::HashMap<String, f32>,
     pub tree_density: Option<f32>,
     pub leaf_tint: Option<[f32; 3]>,
 }
 
+impl BiomeDefParam {
+    /// Whether the biome replaces any of the global subsoil rules.
+    pub fn overrides_subsoil(&self) -> bool {
+        self.sub_near.is_some() || self.sub_deep.is_some() || self.topsoil_thickness.is_some()
+    }
+}
+
 impl BiomesParams {
     pub fn from(cfg: &Biomes) -> Self {
         let defs = cfg
@@ -564,6 +838,9 @@ impl BiomesParams {
                 moisture_min: b.moisture_min.unwrap_or(0.0),
                 moisture_max: b.moisture_max.unwrap_or(1.0),
                 top_block: b.top_block.clone(),
+                sub_near: b.subsoil.near_surface.clone(),
+                sub_deep: b.subsoil.deep.clone(),
+                topsoil_thickness: b.topsoil_thickness,
                 species_weights: b.species_weights.clone(),
                 tree_density: b.tree_density,
                 leaf_tint: b.leaf_tint,
@@ -579,6 +856,43 @@ impl BiomesParams {
             debug_cell_size: cfg.debug_cell_size,
         }
     }
+
+    /// Noise-space coordinates of a column; a zero scale counts as 1.
+    pub fn climate_coords(&self, wx: i32, wz: i32) -> (f32, f32) {
+        let sx = if self.scale_x == 0.0 {
+            1.0
+        } else {
+            self.scale_x
+        };
+        let sz = if self.scale_z == 0.0 {
+            1.0
+        } else {
+            self.scale_z
+        };
+        (wx as f32 * sx, wz as f32 * sz)
+    }
+
+    /// With `debug_pack_all`, every biome repeats in `debug_cell_size` cells so all of them
+    /// can be inspected near spawn.
+    pub fn debug_cell_biome(&self, wx: i32, wz: i32) -> Option<usize> {
+        if !self.debug_pack_all || self.defs.is_empty() {
+            return None;
+        }
+        let cell = self.debug_cell_size.max(1);
+        let cx = wx.div_euclid(cell) as i64;
+        let cz = wz.div_euclid(cell) as i64;
+        Some((cx * 31 + cz * 17).rem_euclid(self.defs.len() as i64) as usize)
+    }
+
+    /// First biome whose ranges hold `temp` and `moisture` (both in `0..=1`).
+    pub fn classify(&self, temp: f32, moisture: f32) -> Option<usize> {
+        self.defs.iter().position(|def| {
+            temp >= def.temp_min
+                && temp < def.temp_max
+                && moisture >= def.moisture_min
+                && moisture < def.moisture_max
+        })
+    }
 }
 
 // Feature condition and placement types
@@ -602,7 +916,7 @@ pub struct FeatureWhen {
     pub chance: Option<f32>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize)]
 pub struct FeaturePlace {
     pub block: String,
 }
@@ -0,0 +1,289 @@
+use std::path::{Path, PathBuf};
+
+use raylib::prelude::Color;
+use serde::{Deserialize, Serialize};
+
+use super::{App, Toast, WindowTheme};
+
+/// UI scale presets stepped through by F5 / Shift+F5.
+const UI_SCALE_STEPS: [f32; 6] = [0.75, 1.0, 1.25, 1.5, 1.75, 2.0];
+
+/// Colors for debug overlays that encode data (minimap heat, chunk layers, lighting diffs).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum OverlayPalette {
+    #[default]
+    Standard,
+    /// Blue/orange/yellow hues (Okabe-Ito) that stay distinct under red-green and
+    /// blue-yellow color vision deficiencies; never relies on red vs green.
+    ColorblindSafe,
+}
+
+impl OverlayPalette {
+    pub(crate) fn next(self) -> Self {
+        match self {
+            Self::Standard => Self::ColorblindSafe,
+            Self::ColorblindSafe => Self::Standard,
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::ColorblindSafe => "colorblind-safe",
+        }
+    }
+
+    /// Minimap cell fill (0-255 channels before distance fade) for mesh and light work as
+    /// 0..1 heats, tinted by the layer offset `dy` from the camera's chunk.
+    pub(crate) fn minimap_rgb(self, mesh_heat: f32, light_heat: f32, dy: i32) -> [f32; 3] {
+        match self {
+            Self::Standard => {
+                let mut rgb = [
+                    55.0 + 130.0 * light_heat,
+                    110.0 + 120.0 * mesh_heat,
+                    140.0 + 80.0 * (1.0 - mesh_heat),
+                ];
+                if dy > 0 {
+                    rgb[2] += 45.0;
+                    rgb[1] += 10.0;
+                } else if dy < 0 {
+                    rgb[0] += 50.0;
+                    rgb[1] -= 15.0;
+                }
+                rgb
+            }
+            Self::ColorblindSafe => {
+                // Mesh work runs dark blue to yellow; light work lifts brightness, so the
+                // two read as hue and lightness rather than as two competing hues.
+                let cool = [40.0, 78.0, 150.0];
+                let warm = [240.0, 210.0, 70.0];
+                let lift = 0.7 + 0.3 * light_heat;
+                let mut rgb = [0.0; 3];
+                for (c, out) in rgb.iter_mut().enumerate() {
+                    *out = (cool[c] + (warm[c] - cool[c]) * mesh_heat) * lift;
+                }
+                if dy > 0 {
+                    rgb[2] += 45.0;
+                } else if dy < 0 {
+                    rgb[0] += 30.0;
+                    rgb[1] += 15.0;
+                }
+                rgb
+            }
+        }
+    }
+
+    /// Wireframe color for a chunk `dy` layers above (+) or below (-) the camera's chunk.
+    pub(crate) fn chunk_layer(self, dy: i32, alpha: u8) -> Color {
+        match (self, dy.signum()) {
+            (Self::Standard, 1) => Color::new(72, 144, 255, alpha),
+            (Self::Standard, -1) => Color::new(255, 140, 88, alpha),
+            (Self::Standard, _) => Color::new(255, 64, 32, alpha),
+            (Self::ColorblindSafe, 1) => Color::new(86, 180, 233, alpha),
+            (Self::ColorblindSafe, -1) => Color::new(230, 159, 0, alpha),
+            (Self::ColorblindSafe, _) => Color::new(204, 121, 167, alpha),
+        }
+    }
+
+    /// Lighting-compare face marker: brighter or darker under the compared quality.
+    pub(crate) fn diff(self, brighter: bool, alpha: u8) -> Color {
+        match (self, brighter) {
+            (Self::Standard, true) => Color::new(255, 96, 48, alpha),
+            (Self::Standard, false) => Color::new(48, 140, 255, alpha),
+            (Self::ColorblindSafe, true) => Color::new(240, 228, 66, alpha),
+            (Self::ColorblindSafe, false) => Color::new(0, 114, 178, alpha),
+        }
+    }
+}
+
+/// Player accessibility preferences, stored under `[accessibility]` in the settings file.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct AccessibilitySettings {
+    /// Multiplier on window chrome, panel text and HUD text.
+    pub(crate) ui_scale: f32,
+    pub(crate) palette: OverlayPalette,
+    /// Hold emitters steady and freeze animated overlay stripes.
+    pub(crate) reduced_flashing: bool,
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self {
+            ui_scale: 1.0,
+            palette: OverlayPalette::Standard,
+            reduced_flashing: false,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SettingsFile {
+    accessibility: AccessibilitySettings,
+}
+
+impl AccessibilitySettings {
+    pub(crate) fn from_toml_str(src: &str) -> Result<Self, String> {
+        let file: SettingsFile = toml::from_str(src).map_err(|e| e.to_string())?;
+        let mut settings = file.accessibility;
+        settings.ui_scale = clamp_ui_scale(settings.ui_scale);
+        Ok(settings)
+    }
+
+    pub(crate) fn to_toml_string(self) -> Result<String, String> {
+        toml::to_string(&SettingsFile {
+            accessibility: self,
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    /// Move to the next (`delta > 0`) or previous UI scale preset.
+    pub(crate) fn step_ui_scale(&mut self, delta: i32) {
+        let current = UI_SCALE_STEPS
+            .iter()
+            .enumerate()
+            .min_by(|a, b| {
+                (a.1 - self.ui_scale)
+                    .abs()
+                    .total_cmp(&(b.1 - self.ui_scale).abs())
+            })
+            .map_or(1, |(i, _)| i);
+        let next = (current as i32 + delta.signum()).clamp(0, UI_SCALE_STEPS.len() as i32 - 1);
+        self.ui_scale = UI_SCALE_STEPS[next as usize];
+    }
+
+    /// Strength of emitter flicker handed to the voxel shaders.
+    pub(crate) fn flicker_amount(self) -> f32 {
+        if self.reduced_flashing { 0.0 } else { 1.0 }
+    }
+
+    /// Clock for decorative overlay animations; stopped under reduced flashing.
+    pub(crate) fn animation_time(self, time: f32) -> f32 {
+        if self.reduced_flashing { 0.0 } else { time }
+    }
+}
+
+fn clamp_ui_scale(scale: f32) -> f32 {
+    let (lo, hi) = (UI_SCALE_STEPS[0], UI_SCALE_STEPS[UI_SCALE_STEPS.len() - 1]);
+    if scale.is_finite() {
+        scale.clamp(lo, hi)
+    } else {
+        1.0
+    }
+}
+
+impl App {
+    /// Load accessibility settings from `path` (defaults when it does not exist yet) and
+    /// write them back there whenever they change in-game.
+    pub fn set_settings_path(&mut self, path: PathBuf) {
+        match read_settings(&path) {
+            Ok(Some(settings)) => {
+                log::info!("loaded settings from {:?}", path);
+                self.accessibility = settings;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                log::warn!("failed to load settings from {:?}: {}", path, e);
+                self.toast = Some(Toast::new(format!("Settings load failed: {}", e), 6.0));
+            }
+        }
+        self.settings_path = Some(path);
+        self.apply_accessibility();
+    }
+
+    /// Push the current accessibility settings into the window theme; shaders and overlays
+    /// read them each frame.
+    pub(crate) fn apply_accessibility(&mut self) {
+        self.overlay_windows
+            .set_theme(WindowTheme::default().scaled(self.accessibility.ui_scale));
+    }
+
+    /// Apply a changed setting and persist it.
+    pub(crate) fn accessibility_changed(&mut self) {
+        self.apply_accessibility();
+        let Some(path) = self.settings_path.as_ref() else {
+            return;
+        };
+        let written = self
+            .accessibility
+            .to_toml_string()
+            .and_then(|src| std::fs::write(path, src).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            log::warn!("failed to save settings to {:?}: {}", path, e);
+        }
+    }
+}
+
+fn read_settings(path: &Path) -> Result<Option<AccessibilitySettings>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(src) => AccessibilitySettings::from_toml_str(&src).map(Some),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_round_trip_through_toml() {
+        let settings = AccessibilitySettings {
+            ui_scale: 1.5,
+            palette: OverlayPalette::ColorblindSafe,
+            reduced_flashing: true,
+        };
+        let src = settings.to_toml_string().unwrap();
+        assert!(src.contains("[accessibility]"));
+        assert_eq!(
+            AccessibilitySettings::from_toml_str(&src).unwrap(),
+            settings
+        );
+    }
+
+    #[test]
+    fn missing_keys_default_and_scale_is_clamped() {
+        let partial =
+            AccessibilitySettings::from_toml_str("[accessibility]\nui_scale = 9.0\n").unwrap();
+        assert_eq!(partial.ui_scale, 2.0);
+        assert_eq!(partial.palette, OverlayPalette::Standard);
+        assert!(!partial.reduced_flashing);
+        assert_eq!(
+            AccessibilitySettings::from_toml_str("").unwrap(),
+            AccessibilitySettings::default()
+        );
+        assert!(
+            AccessibilitySettings::from_toml_str("[accessibility]\npalette = \"neon\"").is_err()
+        );
+    }
+
+    #[test]
+    fn ui_scale_steps_stop_at_the_ends() {
+        let mut s = AccessibilitySettings::default();
+        s.step_ui_scale(1);
+        assert_eq!(s.ui_scale, 1.25);
+        for _ in 0..10 {
+            s.step_ui_scale(1);
+        }
+        assert_eq!(s.ui_scale, 2.0);
+        for _ in 0..10 {
+            s.step_ui_scale(-1);
+        }
+        assert_eq!(s.ui_scale, 0.75);
+    }
+
+    #[test]
+    fn colorblind_palette_separates_layers_and_diffs_by_blue_content() {
+        let p = OverlayPalette::ColorblindSafe;
+        // Above/below and brighter/darker differ in blue, which survives red-green deficiency.
+        let (above, below) = (p.chunk_layer(1, 255), p.chunk_layer(-1, 255));
+        assert!(above.b as i32 - below.b as i32 > 150);
+        let (brighter, darker) = (p.diff(true, 255), p.diff(false, 255));
+        assert!(darker.b as i32 - brighter.b as i32 > 80);
+        // Mesh heat moves along one monotonic lightness ramp.
+        let lum = |c: [f32; 3]| 0.3 * c[0] + 0.59 * c[1] + 0.11 * c[2];
+        assert!(lum(p.minimap_rgb(1.0, 0.0, 0)) > lum(p.minimap_rgb(0.0, 0.0, 0)) + 80.0);
+    }
+}
@@ -0,0 +1,76 @@
+//! Actors in the world: stepped each frame while their chunk is loaded, colliding with
+//! solid voxels and structures, and drawn as boxes until something renders them properly.
+
+use geist_actors::Actor;
+use geist_geom::Aabb;
+use geist_world::ChunkCoord;
+use raylib::prelude::*;
+
+use super::App;
+
+impl App {
+    /// Advance the actors living in loaded chunks by `dt` seconds.
+    pub(crate) fn step_actors(&mut self, dt: f32) {
+        if self.actors.is_empty() {
+            return;
+        }
+        let sun_id = self.sun.as_ref().map(|s| s.id);
+        let (gs, reg) = (&self.gs, &self.reg);
+        let blocks = gs.chunks.blocks();
+        let solid = |x: i32, y: i32, z: i32| {
+            let b = gs
+                .edits
+                .get(x, y, z)
+                .or_else(|| blocks.block_at(x, y, z))
+                .unwrap_or_else(|| gs.world.block_at_runtime(reg, x, y, z));
+            reg.get(b.id).is_some_and(|t| t.is_solid(b.state))
+        };
+        // Solid world voxels and structure cells stop actors.
+        let blocked = |aabb: &Aabb| {
+            let (x0, y0, z0) = (
+                aabb.min.x.floor() as i32,
+                aabb.min.y.floor() as i32,
+                aabb.min.z.floor() as i32,
+            );
+            let (x1, y1, z1) = (
+                aabb.max.x.ceil() as i32,
+                aabb.max.y.ceil() as i32,
+                aabb.max.z.ceil() as i32,
+            );
+            for y in y0..y1 {
+                for z in z0..z1 {
+                    for x in x0..x1 {
+                        if solid(x, y, z) {
+                            return true;
+                        }
+                    }
+                }
+            }
+            gs.structures
+                .values()
+                .any(|st| Some(st.id) != sun_id && st.overlaps_aabb(reg, aabb))
+        };
+        self.actors
+            .step(dt, |chunk| gs.chunks.is_ready(chunk.into()), blocked);
+    }
+
+    /// Actors living in a chunk.
+    pub(crate) fn actors_in_chunk(&self, coord: ChunkCoord) -> impl Iterator<Item = &Actor> {
+        self.actors.in_chunk(coord.into())
+    }
+
+    /// Draw each actor's box.
+    pub(crate) fn draw_actors<D: RaylibDraw3D>(&self, d3: &mut D) {
+        for actor in self.actors.iter() {
+            let p = actor.transform.pos;
+            let s = actor.half_extents * 2.0;
+            d3.draw_cube_wires(
+                Vector3::new(p.x, p.y, p.z),
+                s.x,
+                s.y,
+                s.z,
+                Color::new(255, 200, 90, 220),
+            );
+        }
+    }
+}
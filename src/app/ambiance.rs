@@ -0,0 +1,206 @@
+use std::path::Path;
+
+use geist_render_raylib::SkyPalette;
+use serde::Deserialize;
+
+use super::{App, SkyCurve};
+
+/// Named look for the scene: sky curve, fog colors, light floor and leaf palette.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AmbiancePreset {
+    pub name: String,
+    pub day_sky: [f32; 3],
+    pub night_sky: [f32; 3],
+    pub twilight_tint: [f32; 3],
+    pub twilight_strength: f32,
+    pub sky_scale_min: f32,
+    pub sky_scale_max: f32,
+    pub sky_scale_gamma: f32,
+    pub brightness_gamma: f32,
+    pub cave_fog: [f32; 3],
+    pub water_fog: [f32; 3],
+    pub fog_start: f32,
+    /// Minimum visible light level (0-255) so unlit faces never go fully black. Open sky at
+    /// night is floored by the world's night ambient instead, which gameplay sees too.
+    pub visual_light_min: u8,
+    /// Leaf colors from brightest to darkest, used where a chunk has no biome tint.
+    pub leaf_palette: [[f32; 3]; 4],
+}
+
+impl Default for AmbiancePreset {
+    fn default() -> Self {
+        let sky = SkyCurve::default();
+        Self {
+            name: "default".to_string(),
+            day_sky: sky.palette.day,
+            night_sky: sky.palette.night,
+            twilight_tint: sky.palette.twilight_tint,
+            twilight_strength: sky.palette.twilight_strength,
+            sky_scale_min: sky.scale_min,
+            sky_scale_max: sky.scale_max,
+            sky_scale_gamma: sky.scale_gamma,
+            brightness_gamma: sky.brightness_gamma,
+            cave_fog: [0.0, 0.0, 0.0],
+            water_fog: [0.16, 0.32, 0.45],
+            fog_start: 64.0,
+            visual_light_min: 18,
+            leaf_palette: [
+                [0.32, 0.55, 0.25],
+                [0.28, 0.48, 0.22],
+                [0.20, 0.40, 0.18],
+                [0.12, 0.28, 0.10],
+            ],
+        }
+    }
+}
+
+impl AmbiancePreset {
+    pub fn sky_curve(&self) -> SkyCurve {
+        SkyCurve {
+            palette: SkyPalette {
+                day: self.day_sky,
+                night: self.night_sky,
+                twilight_tint: self.twilight_tint,
+                twilight_strength: self.twilight_strength.max(0.0),
+            },
+            scale_min: self.sky_scale_min.clamp(0.0, 1.0),
+            scale_max: self.sky_scale_max.clamp(0.0, 1.0),
+            scale_gamma: self.sky_scale_gamma,
+            brightness_gamma: self.brightness_gamma,
+        }
+    }
+
+    #[inline]
+    pub fn visual_min(&self) -> f32 {
+        self.visual_light_min as f32 / 255.0
+    }
+}
+
+#[derive(Deserialize)]
+struct AmbianceFile {
+    #[serde(default)]
+    active: Option<String>,
+    #[serde(default, rename = "preset")]
+    presets: Vec<AmbiancePreset>,
+}
+
+/// The loaded preset list and which one is active.
+pub struct Ambiance {
+    presets: Vec<AmbiancePreset>,
+    active: usize,
+}
+
+impl Default for Ambiance {
+    fn default() -> Self {
+        Self {
+            presets: vec![AmbiancePreset::default()],
+            active: 0,
+        }
+    }
+}
+
+impl Ambiance {
+    pub fn from_toml_str(src: &str) -> Result<Self, String> {
+        let file: AmbianceFile = toml::from_str(src).map_err(|e| e.to_string())?;
+        if file.presets.is_empty() {
+            return Err("no [[preset]] entries".to_string());
+        }
+        let mut ambiance = Self {
+            presets: file.presets,
+            active: 0,
+        };
+        if let Some(name) = file.active
+            && !ambiance.select(&name)
+        {
+            return Err(format!("active preset '{}' is not defined", name));
+        }
+        Ok(ambiance)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let src = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::from_toml_str(&src)
+    }
+
+    pub fn current(&self) -> &AmbiancePreset {
+        &self.presets[self.active]
+    }
+
+    pub fn select(&mut self, name: &str) -> bool {
+        match self.presets.iter().position(|p| p.name == name) {
+            Some(idx) => {
+                self.active = idx;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn cycle(&mut self) -> &AmbiancePreset {
+        self.active = (self.active + 1) % self.presets.len();
+        self.current()
+    }
+
+    /// Swap in freshly loaded presets, keeping the active preset by name when it still exists.
+    pub fn reload_from(&mut self, mut loaded: Ambiance) {
+        let current = self.current().name.clone();
+        if !loaded.select(&current) {
+            log::info!(
+                "ambiance preset '{}' no longer defined; using '{}'",
+                current,
+                loaded.current().name
+            );
+        }
+        *self = loaded;
+    }
+}
+
+impl App {
+    /// Push the active preset into the day cycle so the current frame's sample matches it.
+    pub(crate) fn apply_ambiance(&mut self) {
+        self.day_cycle
+            .set_curve(self.ambiance.current().sky_curve());
+        self.day_sample = self.day_cycle.sample();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_presets_parse() {
+        let src = include_str!("../../assets/voxels/ambiance.toml");
+        let mut ambiance = Ambiance::from_toml_str(src).expect("bundled ambiance.toml");
+        assert_eq!(ambiance.current(), &AmbiancePreset::default());
+        assert!(ambiance.select("warm_dusk"));
+        assert!(ambiance.select("cold_night"));
+    }
+
+    #[test]
+    fn reload_keeps_active_preset_by_name() {
+        let src = r#"
+            active = "b"
+            [[preset]]
+            name = "a"
+            [[preset]]
+            name = "b"
+            visual_light_min = 30
+        "#;
+        let mut ambiance = Ambiance::from_toml_str(src).unwrap();
+        assert_eq!(ambiance.current().visual_light_min, 30);
+        assert_eq!(ambiance.cycle().name, "a");
+        assert_eq!(ambiance.cycle().name, "b");
+
+        let reloaded = Ambiance::from_toml_str(
+            "[[preset]]\nname = \"a\"\n[[preset]]\nname = \"b\"\nvisual_light_min = 5\n",
+        )
+        .unwrap();
+        ambiance.reload_from(reloaded);
+        assert_eq!(ambiance.current().name, "b");
+        assert_eq!(ambiance.current().visual_light_min, 5);
+
+        assert!(Ambiance::from_toml_str("active = \"x\"\n[[preset]]\nname = \"a\"\n").is_err());
+    }
+}
@@ -1,22 +1,19 @@
 use crate::gamestate::StructureAnchor;
 use geist_blocks::Block;
 use geist_geom::Vec3;
-use geist_structures::{Structure, rotate_yaw, rotate_yaw_inv};
-
-type Degrees = f32;
+use geist_structures::{Pose, Structure};
 
 /// Convert a structure-local position into world space using the provided pose.
 #[cfg(test)]
 #[inline]
-pub fn structure_local_to_world(local: Vec3, pose_pos: Vec3, pose_yaw: Degrees) -> Vec3 {
-    rotate_yaw(local, pose_yaw) + pose_pos
+pub fn structure_local_to_world(local: Vec3, pose: &Pose) -> Vec3 {
+    pose.local_to_world(local)
 }
 
 /// Convert a world-space position into structure-local coordinates using the provided pose.
 #[inline]
-pub fn structure_world_to_local(world: Vec3, pose_pos: Vec3, pose_yaw: Degrees) -> Vec3 {
-    let diff = world - pose_pos;
-    rotate_yaw_inv(diff, pose_yaw)
+pub fn structure_world_to_local(world: Vec3, pose: &Pose) -> Vec3 {
+    pose.world_to_local(world)
 }
 
 /// Compute the world position of an anchor relative to a structure pose.
@@ -52,7 +49,7 @@ where
 
         // Translate the local cell center back into world space for fallback sampling.
         let local_center = Vec3::new(lx as f32 + 0.5, ly as f32 + 0.5, lz as f32 + 0.5);
-        let world_center = rotate_yaw(local_center, structure.pose.yaw_deg) + structure.pose.pos;
+        let world_center = structure.pose.local_to_world(local_center);
         let wx = world_center.x.floor() as i32;
         let wy = world_center.y.floor() as i32;
         let wz = world_center.z.floor() as i32;
@@ -66,33 +63,60 @@ mod tests {
 
     #[test]
     fn roundtrip_local_world_position() {
-        let pose_pos = Vec3::new(10.0, 5.0, -2.0);
-        let yaw = 90.0;
+        let pose = Pose::from_yaw(Vec3::new(10.0, 5.0, -2.0), 90.0);
         let local = Vec3::new(1.0, 2.0, 3.0);
-        let world = structure_local_to_world(local, pose_pos, yaw);
-        let back = structure_world_to_local(world, pose_pos, yaw);
+        let world = structure_local_to_world(local, &pose);
+        let back = structure_world_to_local(world, &pose);
         assert!((back.x - local.x).abs() < 1e-5);
         assert!((back.y - local.y).abs() < 1e-5);
         assert!((back.z - local.z).abs() < 1e-5);
     }
 
+    #[test]
+    fn roundtrip_banked_scaled_pose() {
+        let pose = Pose {
+            pos: Vec3::new(-4.0, 30.0, 12.0),
+            yaw_deg: 30.0,
+            pitch_deg: 15.0,
+            roll_deg: -25.0,
+            scale: 0.5,
+        };
+        let local = Vec3::new(3.0, -1.0, 7.0);
+        let world = structure_local_to_world(local, &pose);
+        // Scale shrinks the offset from the pose origin; rotation keeps its length.
+        let expected_len = (local * 0.5).length();
+        assert!(((world - pose.pos).length() - expected_len).abs() < 1e-4);
+        let back = structure_world_to_local(world, &pose);
+        assert!((back.x - local.x).abs() < 1e-4);
+        assert!((back.y - local.y).abs() < 1e-4);
+        assert!((back.z - local.z).abs() < 1e-4);
+    }
+
+    #[test]
+    fn upright_pose_matches_yaw_rotation() {
+        use geist_structures::rotate_yaw;
+
+        let mut pose = Pose::from_yaw(Vec3::ZERO, 70.0);
+        let v = Vec3::new(2.0, 1.0, -3.0);
+        let fast = pose.rotate(v);
+        // Nudge off-upright so the quaternion path runs, then compare against the yaw helper.
+        pose.pitch_deg = 1e-6;
+        let quat = pose.rotate(v);
+        let yaw = rotate_yaw(v, 70.0);
+        for (a, b) in [(fast, yaw), (quat, yaw)] {
+            assert!((a.x - b.x).abs() < 1e-4);
+            assert!((a.y - b.y).abs() < 1e-4);
+            assert!((a.z - b.z).abs() < 1e-4);
+        }
+    }
+
     #[test]
     fn anchor_velocity_combines_structure_motion() {
         use geist_blocks::BlockRegistry;
-        use geist_structures::{Pose, Structure};
+        use geist_structures::Structure;
 
         let reg = BlockRegistry::new();
-        let mut structure = Structure::new(
-            1,
-            2,
-            2,
-            2,
-            Pose {
-                pos: Vec3::ZERO,
-                yaw_deg: 45.0,
-            },
-            &reg,
-        );
+        let mut structure = Structure::new(1, 2, 2, 2, Pose::from_yaw(Vec3::ZERO, 45.0), &reg);
         structure.last_velocity = Vec3::new(0.0, 2.0, 0.0);
 
         let mut anchor = StructureAnchor::new(1, Vec3::ZERO, 0.0);
@@ -0,0 +1,89 @@
+use std::path::PathBuf;
+
+use geist_world::DimensionId;
+
+use super::{App, Toast};
+
+/// Seconds between background flushes of edited chunks.
+const AUTOSAVE_INTERVAL_SECS: f32 = 30.0;
+
+impl App {
+    /// Load saved edits from `dir`, migrating ids saved under another block registry, and
+    /// keep flushing new edits there.
+    ///
+    /// Call before the first `step`, so that streamed chunks are built with the saved edits.
+    /// Edits placed during start-up (schematics) are regenerated every session and are not
+    /// written back unless something edits their chunk again.
+    pub fn set_save_dir(&mut self, dir: PathBuf) {
+        if self.dimensions.active == DimensionId::OVERWORLD {
+            self.dimensions.save_root = Some(dir.clone());
+        }
+        self.gs.edits.clear_dirty();
+        let reg = self.reg.clone();
+        match self.gs.edits.load_from_path_with_registry(&dir, &reg) {
+            Ok((chunks, report)) => {
+                if !chunks.is_empty() {
+                    log::info!("loaded edits for {} chunks from {:?}", chunks.len(), dir);
+                }
+                if report.remapped > 0 {
+                    log::info!(
+                        "migrated {} saved blocks to the current block registry",
+                        report.remapped
+                    );
+                }
+                if !report.missing.is_empty() {
+                    let names: Vec<String> = report
+                        .missing
+                        .iter()
+                        .map(|(name, n)| format!("{} x{}", name, n))
+                        .collect();
+                    log::warn!(
+                        "saved blocks missing from the registry were replaced: {}",
+                        names.join(", ")
+                    );
+                    self.toast = Some(Toast::new(
+                        format!(
+                            "{} saved blocks had no current block type",
+                            report.placeholders()
+                        ),
+                        6.0,
+                    ));
+                }
+            }
+            Err(e) => {
+                log::error!("failed to load edits from {:?}: {}", dir, e);
+                self.toast = Some(Toast::new(format!("Save load failed: {}", e), 6.0));
+            }
+        }
+        self.save_dir = Some(dir);
+        self.autosave_timer = 0.0;
+    }
+
+    pub(super) fn autosave_tick(&mut self, dt: f32) {
+        if self.save_dir.is_none() {
+            return;
+        }
+        self.autosave_timer += dt.max(0.0);
+        if self.autosave_timer >= AUTOSAVE_INTERVAL_SECS {
+            self.autosave_timer = 0.0;
+            self.flush_edits();
+        }
+    }
+
+    /// Write chunks edited since the last flush. No-op without a save directory.
+    pub fn flush_edits(&mut self) {
+        let Some(dir) = self.save_dir.as_ref() else {
+            return;
+        };
+        if !self.gs.edits.has_unsaved_edits() {
+            return;
+        }
+        match self.gs.edits.flush_dirty(dir) {
+            Ok(regions) => log::debug!("autosave: wrote {} region files to {:?}", regions, dir),
+            Err(e) => {
+                log::error!("autosave to {:?} failed: {}", dir, e);
+                self.toast = Some(Toast::new(format!("Autosave failed: {}", e), 6.0));
+            }
+        }
+    }
+}
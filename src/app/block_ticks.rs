@@ -0,0 +1,107 @@
+//! Scheduled block ticks: a change wakes the tick handlers of the block and its
+//! neighbours, due positions run one tick at a time on the runtime's tick lane, and the
+//! handlers' changes come back as `BlockTicked` edits.
+
+use std::collections::HashSet;
+
+use geist_runtime::{BlockPos, BlockTickJob};
+use hashbrown::HashMap;
+
+use super::App;
+use crate::event::Event;
+
+// Seconds per block tick.
+const BLOCK_TICK_SECS: f32 = 0.1;
+// Due positions handed to one tick job; the rest run on the following ticks.
+const BLOCK_TICK_BUDGET: usize = 512;
+
+const WAKE: [(i32, i32, i32); 7] = [
+    (0, 0, 0),
+    (1, 0, 0),
+    (-1, 0, 0),
+    (0, 1, 0),
+    (0, -1, 0),
+    (0, 0, 1),
+    (0, 0, -1),
+];
+
+impl App {
+    /// Schedule the handlers of `pos` and its six neighbours after a change there.
+    pub(crate) fn schedule_block_ticks_around(&mut self, (wx, wy, wz): BlockPos) {
+        if self.block_tick_handlers.is_empty() {
+            return;
+        }
+        for (dx, dy, dz) in WAKE {
+            let pos = (wx + dx, wy + dy, wz + dz);
+            let block = self.world_block(pos.0, pos.1, pos.2);
+            if let Some(delay) = self.block_tick_handlers.delay_for(block) {
+                self.block_ticks
+                    .schedule(pos, self.block_tick + u64::from(delay));
+            }
+        }
+    }
+
+    /// Apply finished tick jobs, advance the tick clock by `dt` seconds, and submit the
+    /// positions now due once the previous job is back.
+    pub(crate) fn step_block_ticks(&mut self, dt: f32) {
+        for out in self.runtime.drain_block_tick_results() {
+            // Results from before a world switch belong to no job we are waiting for.
+            if self.block_tick_inflight != Some(out.tick) {
+                continue;
+            }
+            self.block_tick_inflight = None;
+            for (pos, at) in out.again {
+                self.block_ticks.schedule(pos, at);
+            }
+            for change in out.changes {
+                let (wx, wy, wz) = change.pos;
+                self.queue.emit_now(Event::BlockTicked {
+                    wx,
+                    wy,
+                    wz,
+                    from: change.from,
+                    to: change.to,
+                });
+            }
+        }
+
+        self.block_tick_accum += dt;
+        while self.block_tick_accum >= BLOCK_TICK_SECS {
+            self.block_tick_accum -= BLOCK_TICK_SECS;
+            self.block_tick += 1;
+        }
+        let due = self
+            .block_ticks
+            .next_due()
+            .is_some_and(|at| at <= self.block_tick);
+        if self.block_tick_inflight.is_some() || !due {
+            return;
+        }
+        let mut cells = Vec::new();
+        let mut chunks = HashSet::new();
+        for pos in self.block_ticks.pop_due(self.block_tick, BLOCK_TICK_BUDGET) {
+            let block = self.world_block(pos.0, pos.1, pos.2);
+            if self.block_tick_handlers.delay_for(block).is_some() {
+                let c = self.gs.world.chunk_of(pos.0, pos.1, pos.2);
+                chunks.insert((c.cx, c.cy, c.cz));
+                cells.push((pos, block));
+            }
+        }
+        if cells.is_empty() {
+            return;
+        }
+        // Handlers read a few blocks around their cell; edits not built into a chunk yet
+        // travel with the job.
+        let mut edits = HashMap::new();
+        for (cx, cy, cz) in chunks {
+            edits.extend(self.gs.edits.snapshot_for_region(cx, cy, cz, 1, 1));
+        }
+        self.runtime.submit_block_tick_job(BlockTickJob {
+            tick: self.block_tick,
+            cells,
+            edits,
+            handlers: self.block_tick_handlers.clone(),
+        });
+        self.block_tick_inflight = Some(self.block_tick);
+    }
+}
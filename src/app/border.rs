@@ -0,0 +1,366 @@
+//! World border: an X/Z rectangle the player, structures and chunk streaming stay inside,
+//! drawn as an animated translucent wall once the camera comes near it.
+
+use geist_geom::Vec3;
+use geist_structures::Structure;
+use geist_world::{ChunkCoord, World};
+use raylib::prelude::*;
+
+use super::App;
+
+// The wall fades in within this many blocks of the camera.
+const WALL_SHOW_DISTANCE: f32 = 24.0;
+// Half the length of wall drawn on each side of the camera.
+const WALL_HALF_SPAN: f32 = 32.0;
+// Wall extent below and above the camera.
+const WALL_BELOW: f32 = 12.0;
+const WALL_ABOVE: f32 = 24.0;
+// Width of one animated stripe and how fast the stripes scroll (blocks/sec).
+const STRIPE_WIDTH: f32 = 1.0;
+const STRIPE_SPEED: f32 = 1.5;
+
+/// Extent given on the command line, resolved against the world once it exists.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum WorldBorderSpec {
+    /// The world's finite extent, or the density-hint area around its center when the
+    /// world is unbounded.
+    World,
+    /// A square of this half-size around the world's center.
+    HalfSize(f32),
+    Rect {
+        min_x: f32,
+        min_z: f32,
+        max_x: f32,
+        max_z: f32,
+    },
+}
+
+impl WorldBorderSpec {
+    pub(crate) fn parse(arg: &str) -> Result<Self, String> {
+        let arg = arg.trim();
+        if arg.eq_ignore_ascii_case("world") {
+            return Ok(WorldBorderSpec::World);
+        }
+        let values = arg
+            .split(',')
+            .map(|p| {
+                p.trim()
+                    .parse::<f32>()
+                    .map_err(|e| format!("invalid border value '{}': {}", p.trim(), e))
+            })
+            .collect::<Result<Vec<f32>, String>>()?;
+        match values[..] {
+            [half] if half > 0.0 => Ok(WorldBorderSpec::HalfSize(half)),
+            [min_x, min_z, max_x, max_z] if max_x > min_x && max_z > min_z => {
+                Ok(WorldBorderSpec::Rect {
+                    min_x,
+                    min_z,
+                    max_x,
+                    max_z,
+                })
+            }
+            _ => Err(
+                "border must be 'world', a positive half-size, or min_x,min_z,max_x,max_z"
+                    .to_string(),
+            ),
+        }
+    }
+
+    pub(crate) fn resolve(self, world: &World) -> WorldBorder {
+        let (cx, cz) = world.center();
+        let (cx, cz) = (cx as f32, cz as f32);
+        match self {
+            WorldBorderSpec::World => match world.block_bounds() {
+                Some((min_x, min_z, max_x, max_z)) => {
+                    WorldBorder::new(min_x as f32, min_z as f32, max_x as f32, max_z as f32)
+                }
+                None => {
+                    let hx = (world.chunks_x * world.chunk_size_x) as f32 * 0.5;
+                    let hz = (world.chunks_z * world.chunk_size_z) as f32 * 0.5;
+                    WorldBorder::new(cx - hx, cz - hz, cx + hx, cz + hz)
+                }
+            },
+            WorldBorderSpec::HalfSize(half) => {
+                WorldBorder::new(cx - half, cz - half, cx + half, cz + half)
+            }
+            WorldBorderSpec::Rect {
+                min_x,
+                min_z,
+                max_x,
+                max_z,
+            } => WorldBorder::new(min_x, min_z, max_x, max_z),
+        }
+    }
+}
+
+/// Bounds in world blocks; `min` is inclusive, `max` exclusive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct WorldBorder {
+    pub min_x: f32,
+    pub min_z: f32,
+    pub max_x: f32,
+    pub max_z: f32,
+}
+
+impl WorldBorder {
+    pub(crate) fn new(min_x: f32, min_z: f32, max_x: f32, max_z: f32) -> Self {
+        Self {
+            min_x,
+            min_z,
+            max_x,
+            max_z,
+        }
+    }
+
+    /// Whether any part of the chunk column at `coord` lies inside.
+    pub(crate) fn contains_chunk(&self, coord: ChunkCoord, sx: usize, sz: usize) -> bool {
+        let x0 = (coord.cx * sx as i32) as f32;
+        let z0 = (coord.cz * sz as i32) as f32;
+        x0 < self.max_x
+            && x0 + sx as f32 > self.min_x
+            && z0 < self.max_z
+            && z0 + sz as f32 > self.min_z
+    }
+
+    /// `(x, z)` moved inside, keeping `inset` from every edge.
+    pub(crate) fn clamp_point(&self, x: f32, z: f32, inset: f32) -> (f32, f32) {
+        (
+            clamp_axis(x, self.min_x + inset, self.max_x - inset),
+            clamp_axis(z, self.min_z + inset, self.max_z - inset),
+        )
+    }
+
+    /// Shift that brings the X/Z box `[min, max]` inside; a box wider than the border is
+    /// aligned with its min edge.
+    pub(crate) fn push_inside(&self, min: (f32, f32), max: (f32, f32)) -> (f32, f32) {
+        (
+            push_axis(min.0, max.0, self.min_x, self.max_x),
+            push_axis(min.1, max.1, self.min_z, self.max_z),
+        )
+    }
+
+    /// Distance from `(x, z)` to the nearest edge; negative outside.
+    pub(crate) fn distance_to_edge(&self, x: f32, z: f32) -> f32 {
+        (x - self.min_x)
+            .min(self.max_x - x)
+            .min(z - self.min_z)
+            .min(self.max_z - z)
+    }
+}
+
+fn clamp_axis(v: f32, lo: f32, hi: f32) -> f32 {
+    if lo > hi {
+        (lo + hi) * 0.5
+    } else {
+        v.clamp(lo, hi)
+    }
+}
+
+fn push_axis(min: f32, max: f32, lo: f32, hi: f32) -> f32 {
+    if min < lo || max - min > hi - lo {
+        lo - min
+    } else if max > hi {
+        hi - max
+    } else {
+        0.0
+    }
+}
+
+impl App {
+    pub(crate) fn set_world_border(&mut self, border: Option<WorldBorder>) {
+        self.world_border = border;
+        if let Some(b) = border {
+            log::info!(
+                "world border x {}..{} z {}..{}",
+                b.min_x,
+                b.max_x,
+                b.min_z,
+                b.max_z
+            );
+        }
+    }
+
+    /// Whether streaming may request the chunk at `coord`: it must exist in the world
+    /// and lie inside the border, if one is set.
+    pub(crate) fn chunk_in_border(&self, coord: ChunkCoord) -> bool {
+        if !self.gs.world.contains_chunk(coord) {
+            return false;
+        }
+        self.world_border.is_none_or(|b| {
+            b.contains_chunk(
+                coord,
+                self.gs.world.chunk_size_x,
+                self.gs.world.chunk_size_z,
+            )
+        })
+    }
+
+    /// Keep the walker (or the free camera) inside the border, stopping outward motion.
+    pub(crate) fn clamp_to_world_border(&mut self) {
+        let Some(border) = self.world_border else {
+            return;
+        };
+        if self.gs.walk_mode && !self.gs.spectator {
+            let w = &mut self.gs.walker;
+            let (x, z) = border.clamp_point(w.pos.x, w.pos.z, w.radius);
+            if x != w.pos.x {
+                w.vel.x = 0.0;
+            }
+            if z != w.pos.z {
+                w.vel.z = 0.0;
+            }
+            w.pos.x = x;
+            w.pos.z = z;
+        } else {
+            let p = &mut self.cam.position;
+            (p.x, p.z) = border.clamp_point(p.x, p.z, 0.0);
+        }
+    }
+
+    /// Offset that keeps a structure's footprint at `pos` inside the border.
+    pub(crate) fn structure_border_shift(&self, st: &Structure, pos: Vec3) -> (f32, f32) {
+        let Some(border) = self.world_border else {
+            return (0.0, 0.0);
+        };
+        let mut pose = st.pose.clone();
+        pose.pos = pos;
+        let (sx, sz) = (st.sx as f32, st.sz as f32);
+        let (mut min, mut max) = ((f32::MAX, f32::MAX), (f32::MIN, f32::MIN));
+        for (lx, lz) in [(0.0, 0.0), (sx, 0.0), (0.0, sz), (sx, sz)] {
+            for ly in [0.0, st.sy as f32] {
+                let w = pose.local_to_world(Vec3::new(lx, ly, lz));
+                min = (min.0.min(w.x), min.1.min(w.z));
+                max = (max.0.max(w.x), max.1.max(w.z));
+            }
+        }
+        border.push_inside(min, max)
+    }
+
+    /// Animated wall along the edges near the camera.
+    pub(crate) fn draw_world_border<D: RaylibDraw3D>(&self, d3: &mut D, time: f32) {
+        let Some(b) = self.world_border else {
+            return;
+        };
+        let cam = self.cam.position;
+        if b.distance_to_edge(cam.x, cam.z) > WALL_SHOW_DISTANCE {
+            return;
+        }
+        let (y0, y1) = (cam.y - WALL_BELOW, cam.y + WALL_ABOVE);
+        // Each edge: its fixed coordinate, whether it runs along X, and the span it covers.
+        let edges = [
+            (b.min_x, false, b.min_z, b.max_z),
+            (b.max_x, false, b.min_z, b.max_z),
+            (b.min_z, true, b.min_x, b.max_x),
+            (b.max_z, true, b.min_x, b.max_x),
+        ];
+        for (fixed, along_x, lo, hi) in edges {
+            let (across, along) = if along_x {
+                (cam.z, cam.x)
+            } else {
+                (cam.x, cam.z)
+            };
+            let dist = (across - fixed).abs();
+            if dist > WALL_SHOW_DISTANCE {
+                continue;
+            }
+            let fade = 1.0 - dist / WALL_SHOW_DISTANCE;
+            let start = (along - WALL_HALF_SPAN).max(lo);
+            let end = (along + WALL_HALF_SPAN).min(hi);
+            let mut t = (start / STRIPE_WIDTH).floor() * STRIPE_WIDTH;
+            while t < end {
+                let (a, c) = (t.max(start), (t + STRIPE_WIDTH).min(end));
+                let lit =
+                    (t - time * STRIPE_SPEED).rem_euclid(STRIPE_WIDTH * 4.0) < STRIPE_WIDTH * 2.0;
+                let alpha = fade * if lit { 0.45 } else { 0.2 };
+                let color = Color::new(90, 170, 255, (alpha * 255.0) as u8);
+                let p = |s: f32, y: f32| {
+                    if along_x {
+                        Vector3::new(s, y, fixed)
+                    } else {
+                        Vector3::new(fixed, y, s)
+                    }
+                };
+                let (p00, p10, p11, p01) = (p(a, y0), p(c, y0), p(c, y1), p(a, y1));
+                // Both windings so the wall shows from either side.
+                d3.draw_triangle3D(p00, p10, p11, color);
+                d3.draw_triangle3D(p00, p11, p01, color);
+                d3.draw_triangle3D(p00, p11, p10, color);
+                d3.draw_triangle3D(p00, p01, p11, color);
+                t += STRIPE_WIDTH;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn border_specs_parse() {
+        assert_eq!(WorldBorderSpec::parse("world"), Ok(WorldBorderSpec::World));
+        assert_eq!(
+            WorldBorderSpec::parse("256"),
+            Ok(WorldBorderSpec::HalfSize(256.0))
+        );
+        assert_eq!(
+            WorldBorderSpec::parse("-10, 0, 10, 20"),
+            Ok(WorldBorderSpec::Rect {
+                min_x: -10.0,
+                min_z: 0.0,
+                max_x: 10.0,
+                max_z: 20.0
+            })
+        );
+        assert!(WorldBorderSpec::parse("0").is_err());
+        assert!(WorldBorderSpec::parse("10,0,0,20").is_err());
+        assert!(WorldBorderSpec::parse("1,2").is_err());
+    }
+
+    #[test]
+    fn chunks_points_and_boxes_are_kept_inside() {
+        let b = WorldBorder::new(0.0, 0.0, 64.0, 32.0);
+        assert!(b.contains_chunk(ChunkCoord::new(1, 5, 0), 32, 32));
+        assert!(!b.contains_chunk(ChunkCoord::new(2, 0, 0), 32, 32));
+        assert!(!b.contains_chunk(ChunkCoord::new(0, 0, -1), 32, 32));
+
+        assert_eq!(b.clamp_point(-5.0, 40.0, 0.5), (0.5, 31.5));
+        assert_eq!(b.clamp_point(10.0, 10.0, 0.5), (10.0, 10.0));
+        assert_eq!(b.distance_to_edge(10.0, 4.0), 4.0);
+        assert!(b.distance_to_edge(-1.0, 4.0) < 0.0);
+
+        assert_eq!(b.push_inside((60.0, 2.0), (70.0, 12.0)), (-6.0, 0.0));
+        assert_eq!(b.push_inside((-3.0, 30.0), (5.0, 34.0)), (3.0, -2.0));
+        // Wider than the border: aligned with the min edge.
+        assert_eq!(b.push_inside((10.0, 0.0), (90.0, 8.0)), (-10.0, 0.0));
+    }
+
+    #[test]
+    fn world_spec_follows_the_world_extent() {
+        use geist_world::{WorldExtent, WorldGenMode};
+
+        let unbounded = World::new(2, 1, 4, 0, WorldGenMode::Flat { thickness: 1 });
+        let (sx, sz) = (unbounded.chunk_size_x as f32, unbounded.chunk_size_z as f32);
+        assert_eq!(
+            WorldBorderSpec::World.resolve(&unbounded),
+            WorldBorder::new(-sx, -2.0 * sz, sx, 2.0 * sz)
+        );
+        assert_eq!(
+            WorldBorderSpec::HalfSize(10.0).resolve(&unbounded),
+            WorldBorder::new(-10.0, -10.0, 10.0, 10.0)
+        );
+
+        let finite = unbounded.with_extent(WorldExtent::Finite {
+            chunks_x: 2,
+            chunks_z: 1,
+        });
+        assert_eq!(
+            WorldBorderSpec::World.resolve(&finite),
+            WorldBorder::new(0.0, 0.0, 2.0 * sx, sz)
+        );
+        assert_eq!(
+            WorldBorderSpec::HalfSize(10.0).resolve(&finite),
+            WorldBorder::new(sx - 10.0, sz * 0.5 - 10.0, sx + 10.0, sz * 0.5 + 10.0)
+        );
+    }
+}
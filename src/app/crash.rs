@@ -0,0 +1,286 @@
+//! Crash bundles for bug reports.
+//!
+//! `install_crash_hook` replaces the panic hook with one that writes a directory holding the
+//! panic message and backtrace, a dump of what the app was doing (camera, streaming centre,
+//! queue depths) and the last events it processed. A panic on the main thread also saves
+//! the framebuffer, which still holds the last rendered frame. The app keeps the dumped
+//! state current through `note_event` and `App::update_crash_state`; the hook only reads it.
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::App;
+
+/// Processed events kept for the bundle, newest last.
+const CRASH_EVENTS: usize = 100;
+
+static CRASH_STATE: Mutex<CrashState> = Mutex::new(CrashState::new());
+static CRASH_HOOK: OnceLock<CrashHook> = OnceLock::new();
+
+struct CrashHook {
+    dir: PathBuf,
+    // Only the thread owning the GL context may read the framebuffer.
+    main_thread: std::thread::ThreadId,
+}
+
+/// What the app was doing, as of its last completed frame.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CrashState {
+    pub(crate) tick: u64,
+    pub(crate) camera: [f32; 3],
+    pub(crate) yaw: f32,
+    pub(crate) pitch: f32,
+    pub(crate) walk_mode: bool,
+    pub(crate) center_chunk: (i32, i32, i32),
+    pub(crate) view_radius: i32,
+    pub(crate) loaded_chunks: usize,
+    pub(crate) renders: usize,
+    pub(crate) queued_events: usize,
+    pub(crate) intents: usize,
+    /// Runtime queues: (edit, light, background, structure), each as (queued, in flight).
+    pub(crate) build_queues: [(usize, usize); 4],
+    pub(crate) edited_blocks: usize,
+    /// (tick, event name) of the most recently processed events.
+    pub(crate) events: VecDeque<(u64, &'static str)>,
+}
+
+impl CrashState {
+    const fn new() -> Self {
+        Self {
+            tick: 0,
+            camera: [0.0; 3],
+            yaw: 0.0,
+            pitch: 0.0,
+            walk_mode: false,
+            center_chunk: (0, 0, 0),
+            view_radius: 0,
+            loaded_chunks: 0,
+            renders: 0,
+            queued_events: 0,
+            intents: 0,
+            build_queues: [(0, 0); 4],
+            edited_blocks: 0,
+            events: VecDeque::new(),
+        }
+    }
+
+    fn push_event(&mut self, tick: u64, name: &'static str) {
+        if self.events.len() == CRASH_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back((tick, name));
+    }
+
+    /// `state.txt`: one `key = value` line per field.
+    pub(crate) fn state_text(&self) -> String {
+        let mut s = String::new();
+        let [x, y, z] = self.camera;
+        let (cx, cy, cz) = self.center_chunk;
+        let _ = writeln!(s, "tick = {}", self.tick);
+        let _ = writeln!(s, "camera = ({:.2}, {:.2}, {:.2})", x, y, z);
+        let _ = writeln!(s, "yaw_pitch = ({:.1}, {:.1})", self.yaw, self.pitch);
+        let _ = writeln!(s, "mode = {}", if self.walk_mode { "walk" } else { "fly" });
+        let _ = writeln!(s, "center_chunk = ({}, {}, {})", cx, cy, cz);
+        let _ = writeln!(s, "view_radius = {}", self.view_radius);
+        let _ = writeln!(s, "loaded_chunks = {}", self.loaded_chunks);
+        let _ = writeln!(s, "chunk_renders = {}", self.renders);
+        let _ = writeln!(s, "queued_events = {}", self.queued_events);
+        let _ = writeln!(s, "intents = {}", self.intents);
+        for (name, (queued, inflight)) in ["edit", "light", "bg", "struct"]
+            .iter()
+            .zip(self.build_queues)
+        {
+            let _ = writeln!(
+                s,
+                "{}_jobs = {} queued, {} in flight",
+                name, queued, inflight
+            );
+        }
+        let _ = writeln!(s, "edited_blocks = {}", self.edited_blocks);
+        s
+    }
+
+    /// `events.txt`: oldest first.
+    pub(crate) fn events_text(&self) -> String {
+        let mut s = String::new();
+        for (tick, name) in &self.events {
+            let _ = writeln!(s, "[tick {}] {}", tick, name);
+        }
+        s
+    }
+}
+
+/// Record a processed event for the next crash bundle.
+pub(crate) fn note_event(tick: u64, name: &'static str) {
+    if let Ok(mut state) = CRASH_STATE.lock() {
+        state.push_event(tick, name);
+    }
+}
+
+/// Write crash bundles under `dir` on any panic; call from the main (GL) thread. The
+/// previous hook still runs afterwards, so the usual message reaches stderr.
+pub(crate) fn install_crash_hook(dir: PathBuf) {
+    let hook = CrashHook {
+        dir,
+        main_thread: std::thread::current().id(),
+    };
+    if CRASH_HOOK.set(hook).is_err() {
+        return;
+    }
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if let Some(hook) = CRASH_HOOK.get() {
+            let on_main = std::thread::current().id() == hook.main_thread;
+            match write_crash_bundle(&hook.dir, &panic_text(info), on_main) {
+                Ok(path) => eprintln!("crash bundle written to {}", path.display()),
+                Err(e) => eprintln!("could not write crash bundle under {:?}: {}", hook.dir, e),
+            }
+        }
+        previous(info);
+    }));
+}
+
+fn panic_text(info: &std::panic::PanicHookInfo<'_>) -> String {
+    let msg = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "<non-string panic payload>".to_string());
+    let thread = std::thread::current();
+    let mut s = String::new();
+    let _ = writeln!(s, "panic: {}", msg);
+    if let Some(loc) = info.location() {
+        let _ = writeln!(s, "at: {}:{}:{}", loc.file(), loc.line(), loc.column());
+    }
+    let _ = writeln!(s, "thread: {}", thread.name().unwrap_or("<unnamed>"));
+    let _ = writeln!(
+        s,
+        "\nbacktrace:\n{}",
+        std::backtrace::Backtrace::force_capture()
+    );
+    s
+}
+
+/// Write `panic.txt`, `state.txt` and `events.txt` (plus `frame.png` when `capture_frame`)
+/// into a new directory under `dir`, returning its path.
+pub(crate) fn write_crash_bundle(
+    dir: &Path,
+    panic: &str,
+    capture_frame: bool,
+) -> io::Result<PathBuf> {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let bundle = dir.join(format!("crash-{}-{}", secs, std::process::id()));
+    std::fs::create_dir_all(&bundle)?;
+    std::fs::write(bundle.join("panic.txt"), panic)?;
+    // A panic while the state is being updated poisons the lock; the data is still usable.
+    let state = match CRASH_STATE.try_lock() {
+        Ok(state) => Some(state.clone()),
+        Err(std::sync::TryLockError::Poisoned(p)) => Some(p.into_inner().clone()),
+        Err(std::sync::TryLockError::WouldBlock) => None,
+    };
+    match state {
+        Some(state) => {
+            std::fs::write(bundle.join("state.txt"), state.state_text())?;
+            std::fs::write(bundle.join("events.txt"), state.events_text())?;
+        }
+        None => std::fs::write(bundle.join("state.txt"), "state unavailable (locked)\n")?,
+    }
+    if capture_frame {
+        capture_framebuffer(&bundle.join("frame.png"));
+    }
+    Ok(bundle)
+}
+
+/// Save what the window's framebuffer holds; a no-op before the window opens.
+fn capture_framebuffer(path: &Path) {
+    let Ok(cpath) = std::ffi::CString::new(path.to_string_lossy().as_bytes()) else {
+        return;
+    };
+    // Runs on the GL thread (checked by the caller); raylib reads the back buffer.
+    unsafe {
+        if !raylib::ffi::IsWindowReady() {
+            return;
+        }
+        let img = raylib::ffi::LoadImageFromScreen();
+        raylib::ffi::ExportImage(img, cpath.as_ptr());
+        raylib::ffi::UnloadImage(img);
+    }
+}
+
+impl App {
+    /// Refresh the state a crash bundle reports; once per frame.
+    pub(super) fn update_crash_state(&self) {
+        let Ok(mut state) = CRASH_STATE.lock() else {
+            return;
+        };
+        let q = self.runtime.queue_debug_counts();
+        let p = self.cam.position;
+        let c = self.gs.center_chunk;
+        state.tick = self.gs.tick;
+        state.camera = [p.x, p.y, p.z];
+        state.yaw = self.cam.yaw;
+        state.pitch = self.cam.pitch;
+        state.walk_mode = self.gs.walk_mode;
+        state.center_chunk = (c.cx, c.cy, c.cz);
+        state.view_radius = self.gs.view_radius_chunks;
+        state.loaded_chunks = self.debug_stats.loaded_chunks;
+        state.renders = self.renders.len();
+        state.queued_events = self.debug_stats.queued_events_total;
+        state.intents = self.intents.len();
+        state.build_queues = [
+            (q.queued_edit, q.inflight_edit),
+            (q.queued_light, q.inflight_light),
+            (q.queued_bg, q.inflight_bg),
+            (q.queued_structure, q.inflight_structure),
+        ];
+        state.edited_blocks = self.debug_stats.edit_block_edits;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_dump_keeps_the_latest_events() {
+        let mut state = CrashState::new();
+        for tick in 0..(CRASH_EVENTS as u64 + 5) {
+            state.push_event(tick, "Tick");
+        }
+        state.push_event(200, "BlockPlaced");
+        assert_eq!(state.events.len(), CRASH_EVENTS);
+        let events = state.events_text();
+        assert!(events.starts_with("[tick 6] Tick\n"));
+        assert!(events.ends_with("[tick 200] BlockPlaced\n"));
+
+        state.center_chunk = (1, -2, 3);
+        state.build_queues[1] = (4, 2);
+        let text = state.state_text();
+        assert!(text.contains("center_chunk = (1, -2, 3)\n"));
+        assert!(text.contains("light_jobs = 4 queued, 2 in flight\n"));
+    }
+
+    #[test]
+    fn bundles_hold_the_panic_and_the_state() {
+        let dir = std::env::temp_dir().join(format!("geist-crash-{}", std::process::id()));
+        note_event(7, "ViewCenterChanged");
+        let bundle = write_crash_bundle(&dir, "panic: boom\n", false).unwrap();
+        assert!(bundle.starts_with(&dir));
+        let panic = std::fs::read_to_string(bundle.join("panic.txt")).unwrap();
+        assert_eq!(panic, "panic: boom\n");
+        let state = std::fs::read_to_string(bundle.join("state.txt")).unwrap();
+        assert!(state.contains("tick = "));
+        let events = std::fs::read_to_string(bundle.join("events.txt")).unwrap();
+        assert!(events.contains("[tick 7] ViewCenterChanged"));
+        assert!(!bundle.join("frame.png").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,21 +1,49 @@
 use std::f32::consts::TAU;
 
 use geist_geom::Vec3;
+use geist_render_raylib::{SkyFrame, SkyPalette};
 
 #[derive(Clone, Copy, Debug)]
 pub struct DayLightSample {
-    pub phase: f32,
     pub sky_scale: f32,
     pub brightness: f32,
-    pub surface_sky: [f32; 3],
+    /// Sky colors and sun for this moment; also the surface fog color.
+    pub sky: SkyFrame,
     pub sun_dir: Vec3,
     pub sun_visible: bool,
+    /// The world's starlight (0..1): open sky never gets darker than this, in the
+    /// lighting store's sampling or in packed light atlases.
+    pub night_ambient: f32,
 }
 
 impl DayLightSample {
+    /// Skylight ceiling for gameplay sampling, floored by the world's night ambient.
     #[inline]
     pub fn skylight_max(&self) -> u8 {
-        (self.brightness.clamp(0.0, 1.0) * 255.0).round() as u8
+        (self.brightness.max(self.night_ambient).clamp(0.0, 1.0) * 255.0).round() as u8
+    }
+}
+
+/// Shape of the sky over a day: its palette, and how sky scale and light brightness
+/// follow the sun.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SkyCurve {
+    pub palette: SkyPalette,
+    pub scale_min: f32,
+    pub scale_max: f32,
+    pub scale_gamma: f32,
+    pub brightness_gamma: f32,
+}
+
+impl Default for SkyCurve {
+    fn default() -> Self {
+        Self {
+            palette: SkyPalette::default(),
+            scale_min: 0.0,
+            scale_max: 1.0,
+            scale_gamma: 1.0,
+            brightness_gamma: 1.5,
+        }
     }
 }
 
@@ -23,6 +51,8 @@ pub struct DayCycle {
     time: f32,
     day_length: f32,
     fixed_frac: Option<f32>,
+    curve: SkyCurve,
+    night_ambient: u8,
 }
 
 impl DayCycle {
@@ -31,6 +61,8 @@ impl DayCycle {
             time: 0.0,
             day_length: day_length.max(1.0),
             fixed_frac: None,
+            curve: SkyCurve::default(),
+            night_ambient: 0,
         }
     }
 
@@ -49,7 +81,7 @@ impl DayCycle {
                 0.0
             }
         });
-        Self::sample_from_frac(frac)
+        Self::sample_from_frac(&self.curve, frac, self.night_ambient)
     }
 
     pub fn set_fixed_frac(&mut self, frac: Option<f32>) {
@@ -65,35 +97,30 @@ impl DayCycle {
         }
     }
 
-    fn sample_from_frac(frac: f32) -> DayLightSample {
+    pub fn set_curve(&mut self, curve: SkyCurve) {
+        self.curve = curve;
+    }
+
+    /// Mirror the lighting store's `night_ambient` so samples report the same floor.
+    pub fn set_night_ambient(&mut self, level: u8) {
+        self.night_ambient = level;
+    }
+
+    fn sample_from_frac(curve: &SkyCurve, frac: f32, night_ambient: u8) -> DayLightSample {
         let phase = frac.rem_euclid(1.0) * TAU;
-        let sky_scale = 0.5 * (1.0 + phase.sin());
-        let brightness = sky_scale.powf(1.5);
-        let day_sky = [210.0 / 255.0, 221.0 / 255.0, 235.0 / 255.0];
-        let night_sky = [10.0 / 255.0, 12.0 / 255.0, 20.0 / 255.0];
-        let base_sky = [
-            night_sky[0] + (day_sky[0] - night_sky[0]) * brightness,
-            night_sky[1] + (day_sky[1] - night_sky[1]) * brightness,
-            night_sky[2] + (day_sky[2] - night_sky[2]) * brightness,
-        ];
-        let warm_tint = [1.0, 0.63, 0.32];
-        let twilight = phase.cos().abs().powf(3.0);
-        let warm_strength = (0.35 * twilight * sky_scale).clamp(0.0, 0.5);
-        let surface_sky = [
-            base_sky[0] * (1.0 - warm_strength) + warm_tint[0] * warm_strength,
-            base_sky[1] * (1.0 - warm_strength) + warm_tint[1] * warm_strength,
-            base_sky[2] * (1.0 - warm_strength) + warm_tint[2] * warm_strength,
-        ];
-        // Incline the sun slightly on Z so the path arcs across the sky instead of a flat plane.
-        let raw_dir = Vec3::new(phase.cos(), (phase.sin() * 1.05).clamp(-1.0, 1.0), 0.25);
-        let sun_dir = raw_dir.normalized();
+        let raw_scale = (0.5 * (1.0 + phase.sin())).powf(curve.scale_gamma.max(0.01));
+        let sky_scale = curve.scale_min + (curve.scale_max - curve.scale_min) * raw_scale;
+        let brightness = sky_scale
+            .clamp(0.0, 1.0)
+            .powf(curve.brightness_gamma.max(0.01));
+        let sky = SkyFrame::at(&curve.palette, frac, sky_scale, brightness);
         DayLightSample {
-            phase,
             sky_scale,
             brightness,
-            surface_sky,
-            sun_dir,
-            sun_visible: sun_dir.y > 0.0,
+            sky,
+            sun_dir: sky.sun_dir,
+            sun_visible: sky.sun_dir.y > 0.0,
+            night_ambient: night_ambient as f32 / 255.0,
         }
     }
 }
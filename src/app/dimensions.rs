@@ -0,0 +1,83 @@
+//! The session's dimensions and what the app keeps of the ones not being streamed.
+//!
+//! Only the active dimension has a runtime, chunk meshes and streaming state. Leaving one
+//! parks its edits, lighting store and chunk cache here so coming back restores them;
+//! its chunks are rebuilt from those on return.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use geist_edit::EditStore;
+use geist_io::ChunkCache;
+use geist_lighting::{BelowWorld, LightingStore, WorldFloor};
+use geist_world::{Dimension, DimensionId, DimensionSet};
+use raylib::prelude::Vector3;
+
+/// State of a dimension while another one is active.
+pub(crate) struct ParkedDimension {
+    pub(crate) edits: EditStore,
+    pub(crate) lighting: Arc<LightingStore>,
+    pub(crate) chunk_cache: Option<Arc<ChunkCache>>,
+    /// Walker feet when the player left; travel back lands here.
+    pub(crate) feet: Vector3,
+}
+
+pub(crate) struct DimensionState {
+    pub(crate) set: DimensionSet,
+    pub(crate) active: DimensionId,
+    pub(crate) parked: HashMap<DimensionId, ParkedDimension>,
+    // `--save-dir`: the overworld saves there, other dimensions under `dimensions/<name>`.
+    pub(crate) save_root: Option<PathBuf>,
+}
+
+impl DimensionState {
+    pub(crate) fn new(set: DimensionSet) -> Self {
+        Self {
+            set,
+            active: DimensionId::OVERWORLD,
+            parked: HashMap::new(),
+            save_root: None,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn active(&self) -> Option<&Dimension> {
+        self.set.get(self.active)
+    }
+
+    /// Whether the active dimension gets sunlight.
+    #[inline]
+    pub(crate) fn skylight(&self) -> bool {
+        self.active().is_none_or(|d| d.lighting.skylight)
+    }
+
+    pub(crate) fn save_dir(&self, id: DimensionId) -> Option<PathBuf> {
+        let root = self.save_root.as_ref()?;
+        let dim = self.set.get(id)?;
+        Some(dimension_save_dir(root, id, &dim.name))
+    }
+}
+
+pub(crate) fn dimension_save_dir(root: &Path, id: DimensionId, name: &str) -> PathBuf {
+    if id == DimensionId::OVERWORLD {
+        root.to_path_buf()
+    } else {
+        root.join("dimensions").join(name)
+    }
+}
+
+/// A fresh lighting store for the first visit to `dim`; `night_ambient` is the session
+/// default the dimension may override.
+pub(crate) fn dimension_lighting_store(dim: &Dimension, night_ambient: u8) -> Arc<LightingStore> {
+    let world = &dim.world;
+    let store = LightingStore::new(world.chunk_size_x, world.chunk_size_y, world.chunk_size_z);
+    let below = if dim.lighting.void_below {
+        BelowWorld::Void
+    } else {
+        BelowWorld::Solid
+    };
+    store.set_world_floor(Some(WorldFloor { min_cy: 0, below }));
+    store.set_night_ambient(dim.lighting.night_ambient.unwrap_or(night_ambient));
+    Arc::new(store)
+}
@@ -0,0 +1,61 @@
+//! Per-frame upkeep of dynamic point lights: the hand torch follows the camera, the
+//! light volume re-floods only when a light changes voxel, and the result is uploaded to
+//! the shared dynamic light texture. Chunk light atlases are never touched.
+
+use geist_geom::{Vec3, WorldPos};
+use geist_lighting::DynamicLight;
+use geist_render_raylib::update_dynamic_light_texture;
+use raylib::prelude::*;
+
+use super::App;
+
+pub(crate) const HAND_TORCH_LEVEL: u8 = 200;
+/// Lighter falloff than placed torches so the held light reaches about ten blocks.
+pub(crate) const HAND_TORCH_ATTENUATION: u8 = 20;
+
+impl App {
+    pub(crate) fn toggle_hand_torch(&mut self) -> bool {
+        if let Some(id) = self.hand_torch.take() {
+            self.dynamic_lights.remove(id);
+            return false;
+        }
+        let id = self.dynamic_lights.add(DynamicLight {
+            pos: self.hand_torch_pos(),
+            level: HAND_TORCH_LEVEL,
+            attenuation: HAND_TORCH_ATTENUATION,
+        });
+        self.hand_torch = Some(id);
+        true
+    }
+
+    fn hand_torch_pos(&self) -> Vec3 {
+        let p = self.cam.position;
+        Vec3::new(p.x, p.y, p.z)
+    }
+
+    pub(crate) fn update_dynamic_lights(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
+        if let Some(id) = self.hand_torch {
+            let pos = self.hand_torch_pos();
+            self.dynamic_lights.move_to(id, pos);
+        }
+        let p = self.cam.position;
+        let focus = WorldPos::new(p.x.floor() as i32, p.y.floor() as i32, p.z.floor() as i32);
+        let (edits, blocks, reg) = (&self.gs.edits, self.gs.chunks.blocks(), &self.reg);
+        let opaque = |wx: i32, wy: i32, wz: i32| -> bool {
+            let b = edits
+                .get(wx, wy, wz)
+                .or_else(|| blocks.block_at(wx, wy, wz));
+            // Unloaded space counts as open so lights never stall on streaming.
+            b.and_then(|b| reg.get(b.id).map(|t| t.is_solid(b.state)))
+                .unwrap_or(false)
+        };
+        if self.dynamic_lights.update(focus, opaque) {
+            update_dynamic_light_texture(
+                rl,
+                thread,
+                &mut self.dynamic_light_tex,
+                self.dynamic_lights.volume(),
+            );
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+use geist_world::ChunkCoord;
+
+// Rolling window for finished edit traces (matches the perf windows in runtime.rs).
+const LATENCY_WIN_CAP: usize = 200;
+// Drop traces that never reached the GPU (chunk evicted, superseded, etc.).
+const TRACE_TIMEOUT_MS: u128 = 10_000;
+
+/// Pipeline stages an edit passes through on its way from input to a visible mesh.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum EditStage {
+    /// Input → EditStore rev bump.
+    Bump,
+    /// Rev bump → build job handed to the runtime.
+    Submit,
+    /// Job submission → worker picked it up (queue wait).
+    Queue,
+    /// Worker compute (gen + apply + light + mesh).
+    Worker,
+    /// Worker result → GPU upload finished.
+    Upload,
+}
+
+impl EditStage {
+    pub(crate) const ALL: [Self; 5] = [
+        Self::Bump,
+        Self::Submit,
+        Self::Queue,
+        Self::Worker,
+        Self::Upload,
+    ];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Bump => "Input->Bump",
+            Self::Submit => "Bump->Submit",
+            Self::Queue => "Queue wait",
+            Self::Worker => "Worker",
+            Self::Upload => "Result->Upload",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct EditTrace {
+    rev: u64,
+    input: Instant,
+    bumped: Instant,
+    submitted: Option<Instant>,
+    worker_ms: Option<u32>,
+    result: Option<Instant>,
+}
+
+/// Percentile summary over a rolling window of samples (milliseconds).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct LatencySummary {
+    pub(crate) n: usize,
+    pub(crate) p50: u32,
+    pub(crate) p95: u32,
+    pub(crate) p99: u32,
+    pub(crate) max: u32,
+}
+
+impl LatencySummary {
+    pub(crate) fn from_samples(q: &VecDeque<u32>) -> Self {
+        let n = q.len();
+        if n == 0 {
+            return Self::default();
+        }
+        let mut values: Vec<u32> = q.iter().copied().collect();
+        values.sort_unstable();
+        let pick = |p: f32| -> u32 {
+            let idx = ((n as f32) * p).ceil().max(1.0) as usize - 1;
+            values[idx.min(n - 1)]
+        };
+        Self {
+            n,
+            p50: pick(0.50),
+            p95: pick(0.95),
+            p99: pick(0.99),
+            max: values[n - 1],
+        }
+    }
+}
+
+/// Tracks edits end-to-end (click → EditStore bump → job → worker → GPU upload).
+///
+/// Traces are keyed by the chunk that owns the edit; a newer edit to the same chunk
+/// keeps the oldest pending input so the reported latency is what the user waited.
+#[derive(Default)]
+pub(crate) struct EditLatencyTracker {
+    pending: HashMap<ChunkCoord, EditTrace>,
+    end_to_end_ms: VecDeque<u32>,
+    stage_ms: [VecDeque<u32>; EditStage::ALL.len()],
+    dropped: usize,
+}
+
+impl EditLatencyTracker {
+    /// Start (or extend) a trace after the EditStore bump for `coord`.
+    pub(crate) fn begin(&mut self, coord: ChunkCoord, rev: u64, input: Instant) {
+        let now = Instant::now();
+        self.pending
+            .entry(coord)
+            .and_modify(|t| t.rev = t.rev.max(rev))
+            .or_insert(EditTrace {
+                rev,
+                input,
+                bumped: now,
+                submitted: None,
+                worker_ms: None,
+                result: None,
+            });
+    }
+
+    pub(crate) fn mark_submitted(&mut self, coord: ChunkCoord, rev: u64) {
+        if let Some(t) = self.pending.get_mut(&coord)
+            && rev >= t.rev
+            && t.submitted.is_none()
+        {
+            t.submitted = Some(Instant::now());
+        }
+    }
+
+    pub(crate) fn mark_worker_done(&mut self, coord: ChunkCoord, rev: u64, worker_ms: u32) {
+        if let Some(t) = self.pending.get_mut(&coord)
+            && rev >= t.rev
+            && t.submitted.is_some()
+            && t.result.is_none()
+        {
+            t.worker_ms = Some(worker_ms);
+            t.result = Some(Instant::now());
+        }
+    }
+
+    /// Close the trace once the chunk mesh for `rev` (or newer) is on the GPU.
+    /// Returns the end-to-end latency in milliseconds when a trace finished.
+    pub(crate) fn finish(&mut self, coord: ChunkCoord, rev: u64) -> Option<u32> {
+        let t = self.pending.get(&coord).copied()?;
+        if rev < t.rev {
+            return None;
+        }
+        self.pending.remove(&coord);
+        let now = Instant::now();
+        let submitted = t.submitted.unwrap_or(t.bumped);
+        let result = t.result.unwrap_or(now);
+        let worker_ms = t.worker_ms.unwrap_or(0);
+        let queue_ms = ms_between(submitted, result).saturating_sub(worker_ms);
+        let total = ms_between(t.input, now);
+        let samples = [
+            ms_between(t.input, t.bumped),
+            ms_between(t.bumped, submitted),
+            queue_ms,
+            worker_ms,
+            ms_between(result, now),
+        ];
+        for (q, v) in self.stage_ms.iter_mut().zip(samples) {
+            push_window(q, v);
+        }
+        push_window(&mut self.end_to_end_ms, total);
+        Some(total)
+    }
+
+    /// Forget traces whose chunk never produced a mesh within the timeout.
+    pub(crate) fn expire_stale(&mut self) {
+        let before = self.pending.len();
+        self.pending
+            .retain(|_, t| t.input.elapsed().as_millis() < TRACE_TIMEOUT_MS);
+        self.dropped = self
+            .dropped
+            .saturating_add(before.saturating_sub(self.pending.len()));
+    }
+
+    pub(crate) fn end_to_end(&self) -> LatencySummary {
+        LatencySummary::from_samples(&self.end_to_end_ms)
+    }
+
+    pub(crate) fn stage(&self, stage: EditStage) -> LatencySummary {
+        let idx = EditStage::ALL.iter().position(|s| *s == stage).unwrap_or(0);
+        LatencySummary::from_samples(&self.stage_ms[idx])
+    }
+
+    pub(crate) fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub(crate) fn dropped(&self) -> usize {
+        self.dropped
+    }
+}
+
+#[inline]
+fn ms_between(a: Instant, b: Instant) -> u32 {
+    b.saturating_duration_since(a)
+        .as_millis()
+        .min(u128::from(u32::MAX)) as u32
+}
+
+#[inline]
+fn push_window(q: &mut VecDeque<u32>, v: u32) {
+    q.push_back(v);
+    if q.len() > LATENCY_WIN_CAP {
+        q.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_reports_percentiles() {
+        let q: VecDeque<u32> = (1..=100).collect();
+        let s = LatencySummary::from_samples(&q);
+        assert_eq!(s.n, 100);
+        assert_eq!(s.p50, 50);
+        assert_eq!(s.p95, 95);
+        assert_eq!(s.p99, 99);
+        assert_eq!(s.max, 100);
+    }
+
+    #[test]
+    fn trace_finishes_only_for_current_rev() {
+        let mut tracker = EditLatencyTracker::default();
+        let coord = ChunkCoord::new(1, 0, -2);
+        tracker.begin(coord, 5, Instant::now());
+        tracker.mark_submitted(coord, 5);
+        tracker.mark_worker_done(coord, 5, 3);
+        assert_eq!(tracker.finish(coord, 4), None);
+        assert!(tracker.finish(coord, 5).is_some());
+        assert_eq!(tracker.pending_len(), 0);
+        assert_eq!(tracker.end_to_end().n, 1);
+        assert_eq!(tracker.stage(EditStage::Worker).max, 3);
+    }
+}
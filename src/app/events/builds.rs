@@ -1,14 +1,17 @@
 use super::{App, lighting};
 use crate::event::{Event, RebuildCause};
+use geist_blocks::{Block, BlockRegistry, FaceRole, RenderPass};
 use geist_chunk::{ChunkBuf, ChunkOccupancy};
-use geist_lighting::{LightBorders, LightGrid, pack_light_grid_atlas_with_neighbors};
-use geist_mesh_cpu::{ChunkMeshCPU, NeighborsLoaded};
-use geist_render_raylib::{update_chunk_light_texture, upload_chunk_mesh};
+use geist_lighting::{LightBorders, LightGrid, LightQuality, pack_light_grid_atlas_with_neighbors};
+use geist_mesh_cpu::{ChunkMeshCPU, NeighborsLoaded, recycle_chunk};
+use geist_render_raylib::{
+    ChunkRender, ChunkVisibility, bake_vertex_light, update_chunk_light_texture, upload_chunk_mesh,
+};
 use geist_runtime::{BuildJob, StructureBuildJob};
-use geist_structures::StructureId;
+use geist_structures::{SectionCoord, StructureId};
 use geist_world::ChunkCoord;
 use geist_world::voxel::generation::ChunkColumnProfile;
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use raylib::prelude::*;
 use std::sync::Arc;
 
@@ -31,17 +34,39 @@ impl App {
             .snapshot_for_region(cx, cy, cz, 1, 1)
             .into_iter()
             .collect::<HashMap<_, _>>();
+        let block_entities = self.gs.block_entities.snapshot_for_chunk(cx, cy, cz);
         let expected_rev = self.gs.world.current_worldgen_rev();
         let mut column_profile = self.runtime.column_cache().get(coord, expected_rev);
         if column_profile.is_none() {
-            column_profile = self.gs.chunks.column_profile(&coord);
+            column_profile = self
+                .gs
+                .chunks
+                .column_profile(&coord)
+                .filter(|profile| profile.worldgen_rev == expected_rev);
         }
+        // Never carry blocks from older worldgen params into a new build.
+        let stale = self.gs.chunks.worldgen_revs().is_stale(coord, expected_rev);
         let prev_buf = self
             .gs
             .chunks
             .get(&coord)
-            .and_then(|c| if c.has_blocks() { c.buf.as_ref() } else { None })
+            .filter(|_| !stale)
+            .and_then(|c| {
+                if c.has_blocks() {
+                    c.buf.as_deref()
+                } else {
+                    None
+                }
+            })
             .cloned();
+        // Chunks rebuilt as part of a batch get a quick macro-only light pass first; the
+        // runtime follows up with the full relight once the mesh is on screen.
+        let batch = self.batch_chunks.remove(&coord);
+        let light_quality = if batch.is_some() {
+            LightQuality::Coarse
+        } else {
+            LightQuality::Full
+        };
         let job = BuildJob {
             cx,
             cy,
@@ -51,12 +76,18 @@ impl App {
             job_id,
             chunk_edits,
             region_edits,
+            block_entities,
             prev_buf,
             reg: self.reg.clone(),
             column_profile,
+            batch,
+            light_quality,
+            handle: None,
         };
+        self.rebuild_history.record(coord, cause, rev);
         match cause {
             RebuildCause::Edit => {
+                self.edit_latency.mark_submitted(coord, rev);
                 self.runtime.submit_build_job_edit(job);
             }
             RebuildCause::LightingBorder => {
@@ -69,7 +100,16 @@ impl App {
     }
 
     pub(super) fn handle_structure_build_requested(&mut self, id: StructureId, rev: u64) {
-        if let Some(st) = self.gs.structures.get(&id) {
+        let built = self.structure_renders.contains_key(&id);
+        if let Some(st) = self.gs.structures.get_mut(&id) {
+            let dirty = st.take_dirty_sections();
+            // Baked vertex light would go stale in sections left alone, so compat mode
+            // remeshes everything, as does the first build.
+            let sections = if !built || self.shader_compat {
+                st.all_sections()
+            } else {
+                dirty
+            };
             let job = StructureBuildJob {
                 id,
                 rev,
@@ -79,6 +119,7 @@ impl App {
                 base_blocks: st.blocks.clone(),
                 edits: st.edits.snapshot_all(),
                 reg: self.reg.clone(),
+                sections,
             };
             self.runtime.submit_structure_build_job(job);
         }
@@ -91,14 +132,65 @@ impl App {
         thread: &RaylibThread,
         id: StructureId,
         rev: u64,
-        cpu: ChunkMeshCPU,
+        sections: Vec<(SectionCoord, ChunkMeshCPU)>,
         light_grid: LightGrid,
         light_borders: LightBorders,
     ) {
-        if let Some(mut cr) =
-            upload_chunk_mesh(rl, thread, cpu, &mut self.tex_cache, &self.reg.materials)
-        {
-            for part in &mut cr.parts {
+        let Some((sx, sy, sz)) = self.gs.structures.get(&id).map(|st| (st.sx, st.sy, st.sz)) else {
+            return;
+        };
+        let atlas = {
+            let nb = lighting::structure_neighbor_borders(&light_borders);
+            pack_light_grid_atlas_with_neighbors(&light_grid, &nb)
+        };
+        // Sections are meshed in structure-local cells, so one render spanning the whole
+        // structure holds every section's parts and the structure-wide light texture.
+        let mut cr = self
+            .structure_renders
+            .remove(&id)
+            .unwrap_or_else(|| ChunkRender {
+                coord: ChunkCoord::new(0, 0, 0),
+                origin: [0.0; 3],
+                bbox: BoundingBox::new(
+                    Vector3::zero(),
+                    Vector3::new(sx as f32, sy as f32, sz as f32),
+                ),
+                parts: Vec::new(),
+                leaf_tint: None,
+                light_tex: None,
+                decorations: Vec::new(),
+            });
+        let mut part_sections = self.structure_part_sections.remove(&id).unwrap_or_default();
+        let rebuilt: HashSet<SectionCoord> = sections.iter().map(|(s, _)| *s).collect();
+        let mut kept = Vec::with_capacity(cr.parts.len());
+        let mut kept_sections = Vec::with_capacity(cr.parts.len());
+        for (part, section) in cr.parts.drain(..).zip(part_sections.drain(..)) {
+            if !rebuilt.contains(&section) {
+                kept.push(part);
+                kept_sections.push(section);
+            }
+        }
+        cr.parts = kept;
+        part_sections = kept_sections;
+
+        for (section, mut cpu) in sections {
+            if self.shader_compat {
+                let vis_min = self.ambiance.current().visual_min();
+                bake_vertex_light(&mut cpu, &atlas, self.day_sample.sky_scale, vis_min);
+            }
+            let Some(mut sec) = upload_chunk_mesh(
+                rl,
+                thread,
+                cpu,
+                &mut self.tex_cache,
+                &self.reg.materials,
+                self.block_textures.as_ref(),
+                self.wide_indices.as_ref(),
+                None,
+            ) else {
+                continue;
+            };
+            for part in &mut sec.parts {
                 if let Some(mat) = part.model.materials_mut().get_mut(0) {
                     let tag = self
                         .reg
@@ -133,13 +225,12 @@ impl App {
                     }
                 }
             }
-            let atlas = {
-                let nb = lighting::structure_neighbor_borders(&light_borders);
-                pack_light_grid_atlas_with_neighbors(&light_grid, &nb)
-            };
-            update_chunk_light_texture(rl, thread, &mut cr, &atlas);
-            self.structure_renders.insert(id, cr);
+            part_sections.extend(std::iter::repeat_n(section, sec.parts.len()));
+            cr.parts.append(&mut sec.parts);
         }
+        update_chunk_light_texture(rl, thread, &mut cr, &atlas);
+        self.structure_renders.insert(id, cr);
+        self.structure_part_sections.insert(id, part_sections);
         self.structure_lights.insert(id, light_grid);
         self.structure_light_borders.insert(id, light_borders);
         if let Some(st) = self.gs.structures.get_mut(&id) {
@@ -147,6 +238,25 @@ impl App {
         }
     }
 
+    pub(super) fn handle_build_chunk_job_dropped(&mut self, coord: ChunkCoord, rev: u64) {
+        if self.gs.inflight_rev.get(&coord) == Some(&rev) {
+            self.gs.inflight_rev.remove(&coord);
+        }
+        // The gate follows `center_chunk`, so this is rare: the view came back between
+        // the drop and now. Ask again rather than leave a hole.
+        let load_r = i64::from(self.stream_load_radius());
+        if self.gs.center_chunk.distance_sq(coord) <= load_r * load_r
+            && !self.gs.chunks.mesh_ready(coord)
+            && !self.gs.inflight_rev.contains_key(&coord)
+        {
+            self.queue.emit_now(Event::EnsureChunkLoaded {
+                cx: coord.cx,
+                cy: coord.cy,
+                cz: coord.cz,
+            });
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub(super) fn handle_build_chunk_job_completed(
         &mut self,
@@ -160,6 +270,7 @@ impl App {
         light_borders: Option<LightBorders>,
         light_grid: Option<LightGrid>,
         column_profile: Option<Arc<ChunkColumnProfile>>,
+        worldgen_rev: Option<u32>,
     ) {
         let cur_rev = self.gs.edits.get_rev(coord.cx, coord.cy, coord.cz);
         if rev < cur_rev {
@@ -178,6 +289,9 @@ impl App {
                 });
                 self.gs.inflight_rev.insert(coord, cur_rev);
             }
+            if let Some(cpu) = cpu {
+                recycle_chunk(cpu);
+            }
             return;
         }
         let center = self.gs.center_chunk;
@@ -186,6 +300,9 @@ impl App {
         let keep_sq = i64::from(keep_r) * i64::from(keep_r);
         if dist_sq > keep_sq {
             self.gs.inflight_rev.remove(&coord);
+            if let Some(cpu) = cpu {
+                recycle_chunk(cpu);
+            }
             return;
         }
 
@@ -195,13 +312,44 @@ impl App {
             self.gs.chunks.clear_column_profile(&coord);
         }
 
+        // Reused buffers keep the rev they were generated under. An untracked reuse is a
+        // chunk cache hit, and that cache is cleared whenever worldgen params change.
+        let current_worldgen = self.gs.world.current_worldgen_rev();
+        let worldgen_rev = worldgen_rev
+            .or_else(|| self.gs.chunks.worldgen_revs().rev_of(coord))
+            .unwrap_or(current_worldgen);
+        if worldgen_rev < current_worldgen {
+            log::debug!(
+                "chunk ({},{},{}) built under stale worldgen rev {} (current {})",
+                coord.cx,
+                coord.cy,
+                coord.cz,
+                worldgen_rev,
+                current_worldgen
+            );
+            // Finished after a reload it started before; the reload's plan could not see it.
+            if self.rebuild_on_worldgen {
+                self.queue.emit_now(Event::ChunkRebuildRequested {
+                    cx: coord.cx,
+                    cy: coord.cy,
+                    cz: coord.cz,
+                    cause: RebuildCause::HotReload,
+                });
+            }
+        }
+
         if occupancy.is_empty() {
             self.renders.remove(&coord);
+            self.chunk_visibility.remove(&coord);
             self.gs.lighting.clear_chunk(coord);
-            let entry =
-                self.gs
-                    .chunks
-                    .mark_ready(coord, occupancy, None, rev, column_profile.clone());
+            let entry = self.gs.chunks.mark_ready(
+                coord,
+                occupancy,
+                None,
+                rev,
+                worldgen_rev,
+                column_profile.clone(),
+            );
             entry.lighting_ready = true;
             entry.mesh_ready = false;
             self.gs.inflight_rev.remove(&coord);
@@ -212,7 +360,7 @@ impl App {
             return;
         }
 
-        let cpu = match cpu {
+        let mut cpu = match cpu {
             Some(cpu) => cpu,
             None => {
                 log::warn!(
@@ -240,9 +388,27 @@ impl App {
                 return;
             }
         };
-        if let Some(mut cr) =
-            upload_chunk_mesh(rl, thread, cpu, &mut self.tex_cache, &self.reg.materials)
+        if let Some(ref lg) = light_grid {
+            self.gs.lighting.update_levels(coord, lg);
+        }
+        if self.shader_compat
+            && let Some(ref lg) = light_grid
         {
+            let nb = self.gs.lighting.get_neighbor_borders(coord);
+            let atlas = pack_light_grid_atlas_with_neighbors(lg, &nb);
+            let vis_min = self.ambiance.current().visual_min();
+            bake_vertex_light(&mut cpu, &atlas, self.day_sample.sky_scale, vis_min);
+        }
+        if let Some(mut cr) = upload_chunk_mesh(
+            rl,
+            thread,
+            cpu,
+            &mut self.tex_cache,
+            &self.reg.materials,
+            self.block_textures.as_ref(),
+            self.wide_indices.as_ref(),
+            self.renders.remove(&coord),
+        ) {
             let sx = self.gs.world.chunk_size_x as i32;
             let sz = self.gs.world.chunk_size_z as i32;
             let wx = coord.cx * sx + sx / 2;
@@ -287,6 +453,9 @@ impl App {
                     }
                 }
             }
+            if let Some(ref mut dr) = self.decoration_renderer {
+                dr.prepare(rl, thread, &mut self.tex_cache, &self.reg, &cr.decorations);
+            }
             self.renders.insert(coord, cr);
             if let Some(ref lg) = light_grid {
                 let nb = self.gs.lighting.get_neighbor_borders(coord);
@@ -297,15 +466,32 @@ impl App {
                 }
             }
         }
-        let entry =
-            self.gs
-                .chunks
-                .mark_ready(coord, occupancy, Some(buf), rev, column_profile.clone());
+        self.chunk_visibility
+            .insert(coord, chunk_visibility(&self.reg, &buf));
+        let entry = self.gs.chunks.mark_ready(
+            coord,
+            occupancy,
+            Some(buf),
+            rev,
+            worldgen_rev,
+            column_profile.clone(),
+        );
         entry.mesh_ready = true;
         entry.lighting_ready = light_grid.is_some();
         self.gs.inflight_rev.remove(&coord);
         self.gs.edits.mark_built(coord.cx, coord.cy, coord.cz, rev);
         *self.gs.mesh_counts.entry(coord).or_insert(0) += 1;
+        if let Some(e2e_ms) = self.edit_latency.finish(coord, rev) {
+            log::info!(
+                target: "perf",
+                "edit_to_visible_ms={} cx={} cy={} cz={} rev={}",
+                e2e_ms,
+                coord.cx,
+                coord.cy,
+                coord.cz,
+                rev
+            );
+        }
         if let Some(q) = self.perf_remove_start.get_mut(&coord) {
             if let Some(t0) = q.pop_front() {
                 let dt_ms_u32 = t0.elapsed().as_millis().min(u128::from(u32::MAX)) as u32;
@@ -371,3 +557,30 @@ impl App {
         }
     }
 }
+
+// Full cubes drawn opaque on every side block sight; cutout materials such as leaves
+// carry a render tag and are seen through.
+fn blocks_sight(reg: &BlockRegistry, b: Block) -> bool {
+    if !geist_mesh_cpu::is_full_cube(reg, b) {
+        return false;
+    }
+    let Some(ty) = reg.get(b.id) else {
+        return false;
+    };
+    [FaceRole::Top, FaceRole::Bottom, FaceRole::Side]
+        .into_iter()
+        .all(|role| {
+            let mid = ty.material_for_cached(role, b.state);
+            reg.materials.render_pass(mid) == RenderPass::Opaque
+                && reg
+                    .materials
+                    .get(mid)
+                    .is_none_or(|m| m.render_tag.is_none())
+        })
+}
+
+fn chunk_visibility(reg: &BlockRegistry, buf: &ChunkBuf) -> ChunkVisibility {
+    ChunkVisibility::from_cells(buf.sx, buf.sy, buf.sz, |x, y, z| {
+        blocks_sight(reg, buf.get_local(x, y, z))
+    })
+}
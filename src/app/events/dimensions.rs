@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use geist_actors::Actors;
+use geist_edit::EditStore;
+use geist_geom::IVec3;
+use geist_world::{ChunkCoord, DimensionId};
+use raylib::prelude::Vector3;
+
+use super::App;
+use crate::app::Toast;
+use crate::app::dimensions::{ParkedDimension, dimension_lighting_store};
+use crate::gamestate::ChunkInventory;
+
+// Columns searched around the world centre for a place to stand on a first visit.
+const SPAWN_SEARCH_RADIUS: i32 = 96;
+
+impl App {
+    /// Make `target` the streamed dimension: park the current one's edits, lighting and
+    /// chunk cache, restore or create the target's, and restart streaming around the
+    /// player's spot there.
+    pub(super) fn handle_dimension_travel_requested(&mut self, target: DimensionId) {
+        if target == self.dimensions.active {
+            return;
+        }
+        let Some(dim) = self.dimensions.set.get(target) else {
+            log::warn!("no dimension {:?}", target);
+            return;
+        };
+        let world = Arc::clone(&dim.world);
+        let name = dim.name.clone();
+        let incoming = self.dimensions.parked.remove(&target);
+        let fresh = incoming.is_none();
+
+        let (edits, lighting, chunk_cache, feet) = match incoming {
+            Some(p) => (p.edits, p.lighting, p.chunk_cache, Some(p.feet)),
+            None => {
+                let night = match self.dimensions.parked.get(&DimensionId::OVERWORLD) {
+                    Some(p) => p.lighting.night_ambient(),
+                    None => self.gs.lighting.night_ambient(),
+                };
+                let edits = EditStore::new(
+                    world.chunk_size_x as i32,
+                    world.chunk_size_y as i32,
+                    world.chunk_size_z as i32,
+                );
+                (edits, dimension_lighting_store(dim, night), None, None)
+            }
+        };
+        let feet = feet.unwrap_or_else(|| {
+            let (cx, cz) = world.center();
+            match dim.find_spawn(&self.reg, cx, cz, SPAWN_SEARCH_RADIUS) {
+                Some((x, y, z)) => Vector3::new(x as f32 + 0.5, y as f32, z as f32 + 0.5),
+                None => Vector3::new(cx as f32, world.world_height_hint() as f32, cz as f32),
+            }
+        });
+
+        // Structure lights belong to the store being parked; they are re-registered below.
+        for entry in std::mem::take(&mut self.structure_emitters).into_values() {
+            for (wx, wy, wz) in entry.world {
+                self.gs.lighting.remove_emitter_world(wx, wy, wz, 0);
+            }
+        }
+        self.flush_edits();
+
+        let old_cache = self.replace_runtime(world.clone(), lighting.clone(), chunk_cache);
+        let left = self.dimensions.active;
+        self.dimensions.parked.insert(
+            left,
+            ParkedDimension {
+                edits: std::mem::replace(&mut self.gs.edits, edits),
+                lighting: std::mem::replace(&mut self.gs.lighting, lighting),
+                chunk_cache: old_cache,
+                feet: self.gs.walker.pos,
+            },
+        );
+        self.dimensions.active = target;
+
+        // Nothing built for the old world survives; streaming starts over.
+        self.gs.world = world;
+        self.gs.chunks = ChunkInventory::new(self.runtime.chunk_map());
+        self.gs.mesh_counts.clear();
+        self.gs.light_counts.clear();
+        self.gs.inflight_rev.clear();
+        self.gs.finalize.clear();
+        self.gs.center_chunk = ChunkCoord::new(i32::MIN, i32::MIN, i32::MIN);
+        self.renders.clear();
+        self.chunk_visibility.clear();
+        self.intents.clear();
+        self.batch_chunks.clear();
+        self.stamp_batch = None;
+        self.perf_remove_start.clear();
+        self.lighting_compare = None;
+        self.falling.clear();
+        self.actors = Actors::new(IVec3::from_dims(
+            self.gs.world.chunk_size_x,
+            self.gs.world.chunk_size_y,
+            self.gs.world.chunk_size_z,
+        ));
+        self.fluids.clear();
+        self.block_ticks.clear();
+        self.block_tick_inflight = None;
+        self.queue.retain(|ev| !ev.is_world_bound());
+
+        self.gs.walker.pos = feet;
+        self.gs.walker.vel = Vector3::zero();
+        self.cam.position = Vector3::new(feet.x, feet.y + self.gs.walker.eye_height, feet.z);
+
+        if let Some(dir) = self.dimensions.save_dir(target) {
+            if fresh {
+                self.set_save_dir(dir);
+            } else {
+                self.save_dir = Some(dir);
+            }
+        }
+        let ids: Vec<_> = self.gs.structures.keys().copied().collect();
+        for id in ids {
+            self.sync_structure_lights(id, true);
+        }
+        log::info!(
+            "entered dimension {} at ({:.1}, {:.1}, {:.1})",
+            name,
+            feet.x,
+            feet.y,
+            feet.z
+        );
+        self.toast = Some(Toast::new(format!("Entered the {}", name), 3.0));
+    }
+}
@@ -1,133 +1,103 @@
 use super::App;
+use crate::app::Toast;
+use crate::app::state::StructureEmitters;
 use crate::event::{Event, RebuildCause};
-use crate::raycast;
-use geist_blocks::Block;
-use geist_chunk::ChunkOccupancy;
+use geist_blocks::{Block, BlockRegistry, Placement};
+use geist_edit::{BlockEntityChange, EditChange};
 use geist_geom::Vec3;
-use geist_render_raylib::conv::{vec3_from_rl, vec3_to_rl};
-use geist_structures::{StructureId, rotate_yaw, rotate_yaw_inv};
+use geist_io::StructureFromSchematic;
+use geist_lighting::BorderChangeMask;
+use geist_raycast::{Ray, RayHit, SceneHit, raycast_scene, raycast_world};
+use geist_render_raylib::conv::vec3_from_rl;
+use geist_runtime::BatchEvent;
+use geist_structures::{Pose, Structure, StructureId};
 use geist_world::ChunkCoord;
-use raylib::prelude::*;
-use std::time::Instant;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+// Moving structures re-register their lights in the world at most this often.
+const MOVING_STRUCTURE_LIGHT_INTERVAL: Duration = Duration::from_millis(200);
+
+/// `block` oriented for placing against `hit`, with `look` in the hit's space.
+fn oriented_for_hit(reg: &BlockRegistry, block: Block, hit: &RayHit, look: Vec3) -> Block {
+    let Some(ty) = reg.get(block.id) else {
+        return block;
+    };
+    let placement = Placement {
+        normal: (hit.nx, hit.ny, hit.nz),
+        hit_y: hit.enter.y - hit.by as f32,
+        look: (look.x, look.z),
+    };
+    Block {
+        id: block.id,
+        state: ty.placement_state(block.state, &placement),
+    }
+}
 
 impl App {
-    pub(super) fn handle_raycast_edit_requested(&mut self, place: bool, block: Block) {
+    /// Select the world block under the crosshair for placing, state included.
+    pub(super) fn handle_block_pick_requested(&mut self) {
+        let org = self.cam.position;
+        let dir = self.cam.forward();
+        let blocks = self.gs.chunks.blocks();
+        let sampler = |wx: i32, wy: i32, wz: i32| -> Block {
+            if let Some(b) = self.gs.edits.get(wx, wy, wz) {
+                return b;
+            }
+            if let Some(b) = blocks.block_at(wx, wy, wz) {
+                return b;
+            }
+            self.gs.world.block_at_runtime(&self.reg, wx, wy, wz)
+        };
+        let ray = Ray::new(vec3_from_rl(org), vec3_from_rl(dir), 8.0 * 32.0);
+        if let Some(hit) = raycast_world(&self.reg, &ray, &sampler) {
+            let block = sampler(hit.bx, hit.by, hit.bz);
+            self.queue.emit_now(Event::PlaceTypeSelected { block });
+        }
+    }
+
+    pub(super) fn handle_raycast_edit_requested(
+        &mut self,
+        place: bool,
+        block: Block,
+        issued_at: Instant,
+    ) {
         let org = self.cam.position;
         let dir = self.cam.forward();
-        let sx = self.gs.world.chunk_size_x as i32;
-        let sy = self.gs.world.chunk_size_y as i32;
-        let sz = self.gs.world.chunk_size_z as i32;
         let reg = self.reg.clone();
+        let blocks = self.gs.chunks.blocks();
         let sampler = |wx: i32, wy: i32, wz: i32| -> Block {
             if let Some(b) = self.gs.edits.get(wx, wy, wz) {
                 return b;
             }
-            let cx = wx.div_euclid(sx);
-            let cy = wy.div_euclid(sy);
-            let cz = wz.div_euclid(sz);
-            if let Some(cent) = self.gs.chunks.get(&ChunkCoord::new(cx, cy, cz)) {
-                match (cent.occupancy_or_empty(), cent.buf.as_ref()) {
-                    (ChunkOccupancy::Empty, _) => {
-                        return Block {
-                            id: reg.id_by_name("air").unwrap_or(0),
-                            state: 0,
-                        };
-                    }
-                    (_, Some(buf)) => {
-                        return buf.get_world(wx, wy, wz).unwrap_or(Block {
-                            id: reg.id_by_name("air").unwrap_or(0),
-                            state: 0,
-                        });
-                    }
-                    (_, None) => {}
-                }
+            if let Some(b) = blocks.block_at(wx, wy, wz) {
+                return b;
             }
             Block {
                 id: reg.id_by_name("air").unwrap_or(0),
                 state: 0,
             }
         };
-        let world_hit = raycast::raycast_first_hit_with_face(org, dir, 8.0 * 32.0, |x, y, z| {
-            let b = sampler(x, y, z);
-            self.reg
-                .get(b.id)
-                .map(|ty| ty.is_solid(b.state))
-                .unwrap_or(false)
-        });
-        let mut struct_hit: Option<(StructureId, raycast::RayHit, f32)> = None;
+        let ray = Ray::new(vec3_from_rl(org), vec3_from_rl(dir), 8.0 * 32.0);
         let sun_id = self.sun.as_ref().map(|s| s.id);
-        for (id, st) in &self.gs.structures {
-            if Some(*id) == sun_id {
-                continue;
-            }
-            let o = vec3_from_rl(org);
-            let diff = Vec3 {
-                x: o.x - st.pose.pos.x,
-                y: o.y - st.pose.pos.y,
-                z: o.z - st.pose.pos.z,
-            };
-            let local_org = vec3_to_rl(rotate_yaw_inv(diff, st.pose.yaw_deg));
-            let local_dir = vec3_to_rl(rotate_yaw_inv(vec3_from_rl(dir), st.pose.yaw_deg));
-            let is_solid_local = |lx: i32, ly: i32, lz: i32| -> bool {
-                if lx < 0 || ly < 0 || lz < 0 {
-                    return false;
-                }
-                let (lxu, lyu, lzu) = (lx as usize, ly as usize, lz as usize);
-                if lxu >= st.sx || lyu >= st.sy || lzu >= st.sz {
-                    return false;
-                }
-                if let Some(b) = st.edits.get(lx, ly, lz) {
-                    return self
-                        .reg
-                        .get(b.id)
-                        .map(|ty| ty.is_solid(b.state))
-                        .unwrap_or(false);
-                }
-                let b = st.blocks[st.idx(lxu, lyu, lzu)];
-                self.reg
-                    .get(b.id)
-                    .map(|ty| ty.is_solid(b.state))
-                    .unwrap_or(false)
-            };
-            if let Some(hit) = raycast::raycast_first_hit_with_face(
-                local_org,
-                local_dir,
-                8.0 * 32.0,
-                is_solid_local,
-            ) {
-                let cc_local = Vector3::new(
-                    hit.bx as f32 + 0.5,
-                    hit.by as f32 + 0.5,
-                    hit.bz as f32 + 0.5,
-                );
-                let wl = rotate_yaw(vec3_from_rl(cc_local), st.pose.yaw_deg);
-                let cc_world = Vec3 {
-                    x: wl.x + st.pose.pos.x,
-                    y: wl.y + st.pose.pos.y,
-                    z: wl.z + st.pose.pos.z,
-                };
-                let cw = vec3_to_rl(cc_world);
-                let d = Vector3::new(cw.x - org.x, cw.y - org.y, cw.z - org.z);
-                let dist2 = d.x * d.x + d.y * d.y + d.z * d.z;
-                struct_hit = Some((*id, hit, dist2));
-                break;
-            }
-        }
-        let choose_struct = match (world_hit.as_ref(), struct_hit.as_ref()) {
-            (None, Some(_)) => true,
-            (Some(_), None) => false,
-            (Some(wh), Some((_id, _sh, sdist2))) => {
-                let wc = Vector3::new(wh.bx as f32 + 0.5, wh.by as f32 + 0.5, wh.bz as f32 + 0.5);
-                let dw = wc - org;
-                let wdist2 = dw.x * dw.x + dw.y * dw.y + dw.z * dw.z;
-                *sdist2 < wdist2
-            }
-            _ => false,
+        let structures = self
+            .gs
+            .structures
+            .values()
+            .filter(|st| Some(st.id) != sun_id);
+        let Some(scene_hit) = raycast_scene(&self.reg, &ray, &sampler, structures) else {
+            return;
         };
-        if choose_struct {
-            if let Some((id, hit, _)) = struct_hit {
+        match scene_hit {
+            SceneHit::Structure { id, hit } => {
                 if place {
                     let (lx, ly, lz) = (hit.px, hit.py, hit.pz);
+                    let look = self
+                        .gs
+                        .structures
+                        .get(&id)
+                        .map_or(ray.dir, |st| st.pose.rotate_inv(ray.dir));
+                    let block = oriented_for_hit(&self.reg, block, &hit, look);
                     self.queue.emit_now(Event::StructureBlockPlaced {
                         id,
                         lx,
@@ -144,25 +114,37 @@ impl App {
                     });
                 }
             }
-        } else if let Some(hit) = world_hit {
-            if place {
-                let wx = hit.px;
-                let wy = hit.py;
-                let wz = hit.pz;
-                self.queue
-                    .emit_now(Event::BlockPlaced { wx, wy, wz, block });
-            } else {
-                let wx = hit.bx;
-                let wy = hit.by;
-                let wz = hit.bz;
-                let prev = sampler(wx, wy, wz);
-                if self
-                    .reg
-                    .get(prev.id)
-                    .map(|t| t.is_solid(prev.state))
-                    .unwrap_or(false)
-                {
-                    self.queue.emit_now(Event::BlockRemoved { wx, wy, wz });
+            SceneHit::World(hit) => {
+                if place {
+                    let wx = hit.px;
+                    let wy = hit.py;
+                    let wz = hit.pz;
+                    let block = oriented_for_hit(&self.reg, block, &hit, ray.dir);
+                    self.queue.emit_now(Event::BlockPlaced {
+                        wx,
+                        wy,
+                        wz,
+                        block,
+                        issued_at: Some(issued_at),
+                    });
+                } else {
+                    let wx = hit.bx;
+                    let wy = hit.by;
+                    let wz = hit.bz;
+                    let prev = sampler(wx, wy, wz);
+                    if self
+                        .reg
+                        .get(prev.id)
+                        .map(|t| t.is_solid(prev.state))
+                        .unwrap_or(false)
+                    {
+                        self.queue.emit_now(Event::BlockRemoved {
+                            wx,
+                            wy,
+                            wz,
+                            issued_at: Some(issued_at),
+                        });
+                    }
                 }
             }
         }
@@ -181,6 +163,8 @@ impl App {
             let rev = st.dirty_rev;
             self.queue
                 .emit_now(Event::StructureBuildRequested { id, rev });
+            self.refresh_structure_emitter(id, (lx, ly, lz));
+            self.release_structure_if_unsupported(id, lx, ly, lz);
         }
     }
 
@@ -196,35 +180,136 @@ impl App {
             let rev = st.dirty_rev;
             self.queue
                 .emit_now(Event::StructureBuildRequested { id, rev });
+            self.refresh_structure_emitter(id, (lx, ly, lz));
+            self.release_structure_if_unsupported(id, lx, ly + 1, lz);
         }
     }
 
-    pub(super) fn handle_block_placed(&mut self, wx: i32, wy: i32, wz: i32, block: Block) {
-        self.gs.edits.set(wx, wy, wz, block);
-        let em = self
-            .reg
-            .get(block.id)
-            .map(|t| t.light_emission(block.state))
-            .unwrap_or(0);
-        if em > 0 {
-            let is_beacon = self
-                .reg
-                .get(block.id)
-                .map(|t| t.light_is_beam())
-                .unwrap_or(false);
-            self.queue.emit_now(Event::LightEmitterAdded {
-                wx,
-                wy,
-                wz,
-                level: em,
-                is_beacon,
+    /// Project a structure's emitters through its pose into the world lighting store,
+    /// re-registering any that now fall in a different voxel and relighting the chunks they
+    /// leave or enter. Moving structures are throttled to one resync per
+    /// `MOVING_STRUCTURE_LIGHT_INTERVAL` unless `force` is set.
+    pub(super) fn sync_structure_lights(&mut self, id: StructureId, force: bool) {
+        if self.sun.as_ref().is_some_and(|s| s.id == id) {
+            return;
+        }
+        let Some(st) = self.gs.structures.get(&id) else {
+            return;
+        };
+        let now = Instant::now();
+        let reg = &self.reg;
+        let entry = self
+            .structure_emitters
+            .entry(id)
+            .or_insert_with(|| StructureEmitters {
+                local: st.local_emitters(reg),
+                world: Vec::new(),
+                synced_at: None,
+            });
+        if !force
+            && !st.is_stationary()
+            && entry
+                .synced_at
+                .is_some_and(|t| now.duration_since(t) < MOVING_STRUCTURE_LIGHT_INTERVAL)
+        {
+            return;
+        }
+        entry.synced_at = Some(now);
+
+        let mut seen: HashSet<(i32, i32, i32)> = HashSet::new();
+        let mut target = Vec::with_capacity(entry.local.len());
+        for e in &entry.local {
+            let p = st.local_to_world_voxel(e.cell.0, e.cell.1, e.cell.2);
+            // Cells that share a voxel under the pose register one light.
+            if seen.insert(p) {
+                target.push((p, *e));
+            }
+        }
+        let lighting = &self.gs.lighting;
+        let mut touched: HashSet<(i32, i32, i32)> = HashSet::new();
+        let mut darkened = Vec::new();
+        for &p in &entry.world {
+            if !seen.contains(&p) {
+                darkened.extend(lighting.remove_emitter_world(p.0, p.1, p.2, 0));
+                touched.insert(p);
+            }
+        }
+        let registered: HashSet<(i32, i32, i32)> = entry.world.iter().copied().collect();
+        for (p, e) in &target {
+            if registered.contains(p) {
+                continue;
+            }
+            if e.is_beacon {
+                lighting.add_beacon_world(p.0, p.1, p.2, e.level);
+            } else {
+                lighting.add_emitter_world(p.0, p.1, p.2, e.level);
+            }
+            touched.insert(*p);
+        }
+        entry.world = target.into_iter().map(|(p, _)| p).collect();
+        self.request_structure_relight(touched);
+        self.relight_darkened_borders(darkened);
+    }
+
+    /// Update the cached emitter of one edited structure cell and resync at once.
+    fn refresh_structure_emitter(&mut self, id: StructureId, cell: (i32, i32, i32)) {
+        let mut darkened = Vec::new();
+        if let (Some(st), Some(entry)) = (
+            self.gs.structures.get(&id),
+            self.structure_emitters.get_mut(&id),
+        ) {
+            let (lx, ly, lz) = cell;
+            if let Some(i) = entry.local.iter().position(|e| e.cell == cell) {
+                entry.local.swap_remove(i);
+                // Drop the old registration so a changed level is re-added below.
+                let p = st.local_to_world_voxel(lx, ly, lz);
+                if let Some(j) = entry.world.iter().position(|q| *q == p) {
+                    entry.world.swap_remove(j);
+                    darkened = self.gs.lighting.remove_emitter_world(p.0, p.1, p.2, 0);
+                }
+            }
+            entry.local.extend(st.emitter_at(&self.reg, lx, ly, lz));
+        }
+        self.relight_darkened_borders(darkened);
+        self.sync_structure_lights(id, true);
+    }
+
+    /// Light-only rebuild of the chunks holding the given world voxels.
+    fn request_structure_relight(&mut self, voxels: HashSet<(i32, i32, i32)>) {
+        let chunks: HashSet<ChunkCoord> = voxels
+            .into_iter()
+            .map(|(wx, wy, wz)| self.gs.world.chunk_of(wx, wy, wz))
+            .collect();
+        for coord in chunks {
+            self.queue.emit_now(Event::ChunkRebuildRequested {
+                cx: coord.cx,
+                cy: coord.cy,
+                cz: coord.cz,
+                cause: RebuildCause::LightingBorder,
             });
         }
-        let _ = self.gs.edits.bump_region_around(wx, wy, wz);
-        let sx = self.gs.world.chunk_size_x as i32;
-        let sy = self.gs.world.chunk_size_y as i32;
-        let sz = self.gs.world.chunk_size_z as i32;
-        let origin = ChunkCoord::new(wx.div_euclid(sx), wy.div_euclid(sy), wz.div_euclid(sz));
+    }
+
+    pub(super) fn handle_block_placed(
+        &mut self,
+        wx: i32,
+        wy: i32,
+        wz: i32,
+        block: Block,
+        issued_at: Option<Instant>,
+    ) {
+        let prev = self.world_block(wx, wy, wz);
+        self.gs.edits.set(wx, wy, wz, block);
+        self.sync_block_entity((wx, wy, wz), block);
+        for ev in self.emitter_swap_events((wx, wy, wz), prev, block) {
+            self.queue.emit_now(ev);
+        }
+        let stamp = self.gs.edits.bump_region_around(wx, wy, wz);
+        self.dynamic_lights.invalidate();
+        let origin = self.gs.world.chunk_of(wx, wy, wz);
+        if let Some(t0) = issued_at {
+            self.edit_latency.begin(origin, stamp, t0);
+        }
         for coord in self.gs.edits.get_affected_chunks(wx, wy, wz) {
             let Some(cause) = Self::classify_edit_rebuild_cause(origin, coord) else {
                 continue;
@@ -246,44 +331,30 @@ impl App {
                 self.prepare_chunk_for_edit(coord);
             }
         }
+        self.release_if_unsupported(wx, wy, wz);
+        self.fluids.activate((wx, wy, wz));
+        self.schedule_block_ticks_around((wx, wy, wz));
     }
 
-    pub(super) fn handle_block_removed(&mut self, wx: i32, wy: i32, wz: i32) {
-        let sx = self.gs.world.chunk_size_x as i32;
-        let sy = self.gs.world.chunk_size_y as i32;
-        let sz = self.gs.world.chunk_size_z as i32;
-        let reg = &self.reg;
-        let sampler = |wx: i32, wy: i32, wz: i32| -> Block {
-            if let Some(b) = self.gs.edits.get(wx, wy, wz) {
-                return b;
-            }
-            let cx = wx.div_euclid(sx);
-            let cy = wy.div_euclid(sy);
-            let cz = wz.div_euclid(sz);
-            if let Some(cent) = self.gs.chunks.get(&ChunkCoord::new(cx, cy, cz)) {
-                match (cent.occupancy_or_empty(), cent.buf.as_ref()) {
-                    (ChunkOccupancy::Empty, _) => return Block::AIR,
-                    (_, Some(buf)) => {
-                        return buf.get_world(wx, wy, wz).unwrap_or(Block::AIR);
-                    }
-                    (_, None) => {}
-                }
-            }
-            self.gs.world.block_at_runtime(reg, wx, wy, wz)
-        };
-        let prev = sampler(wx, wy, wz);
-        let prev_em = self
-            .reg
-            .get(prev.id)
-            .map(|t| t.light_emission(prev.state))
-            .unwrap_or(0);
-        if prev_em > 0 {
-            self.queue
-                .emit_now(Event::LightEmitterRemoved { wx, wy, wz });
+    pub(super) fn handle_block_removed(
+        &mut self,
+        wx: i32,
+        wy: i32,
+        wz: i32,
+        issued_at: Option<Instant>,
+    ) {
+        let prev = self.world_block(wx, wy, wz);
+        for ev in self.emitter_swap_events((wx, wy, wz), prev, Block::AIR) {
+            self.queue.emit_now(ev);
         }
         self.gs.edits.set(wx, wy, wz, Block::AIR);
-        let _ = self.gs.edits.bump_region_around(wx, wy, wz);
-        let origin = ChunkCoord::new(wx.div_euclid(sx), wy.div_euclid(sy), wz.div_euclid(sz));
+        self.sync_block_entity((wx, wy, wz), Block::AIR);
+        let stamp = self.gs.edits.bump_region_around(wx, wy, wz);
+        self.dynamic_lights.invalidate();
+        let origin = self.gs.world.chunk_of(wx, wy, wz);
+        if let Some(t0) = issued_at {
+            self.edit_latency.begin(origin, stamp, t0);
+        }
         for coord in self.gs.edits.get_affected_chunks(wx, wy, wz) {
             let Some(cause) = Self::classify_edit_rebuild_cause(origin, coord) else {
                 continue;
@@ -299,6 +370,101 @@ impl App {
                 self.prepare_chunk_for_edit(coord);
             }
         }
+        self.release_if_unsupported(wx, wy + 1, wz);
+        self.fluids.activate((wx, wy, wz));
+        self.schedule_block_ticks_around((wx, wy, wz));
+    }
+
+    /// Write a block tick handler's change as an untracked edit and remesh around it.
+    pub(super) fn handle_block_ticked(
+        &mut self,
+        wx: i32,
+        wy: i32,
+        wz: i32,
+        from: Block,
+        to: Block,
+    ) {
+        let prev = self.world_block(wx, wy, wz);
+        if prev != from {
+            return;
+        }
+        for ev in self.emitter_swap_events((wx, wy, wz), prev, to) {
+            self.queue.emit_now(ev);
+        }
+        self.gs.edits.set_untracked(wx, wy, wz, to);
+        self.sync_block_entity((wx, wy, wz), to);
+        self.gs.edits.bump_region_around(wx, wy, wz);
+        self.dynamic_lights.invalidate();
+        let affected = self.gs.edits.get_affected_chunks(wx, wy, wz);
+        self.request_edit_rebuilds(affected);
+        self.release_if_unsupported(wx, wy + 1, wz);
+        self.fluids.activate((wx, wy, wz));
+        self.schedule_block_ticks_around((wx, wy, wz));
+    }
+
+    /// Create or drop the block entity at `pos` to match its new block.
+    fn sync_block_entity(&mut self, pos: (i32, i32, i32), block: Block) {
+        match self
+            .gs
+            .block_entities
+            .on_block_changed(&self.reg, pos, block)
+        {
+            BlockEntityChange::Removed(old) | BlockEntityChange::Replaced(old)
+                if !old.fields.is_empty() =>
+            {
+                log::debug!(
+                    "dropped block entity at {:?} with {} fields",
+                    pos,
+                    old.fields.len()
+                );
+            }
+            _ => {}
+        }
+    }
+
+    /// Remesh loaded chunks whose mesh-visible block entities changed.
+    pub(crate) fn flush_block_entity_changes(&mut self) {
+        for coord in self.gs.block_entities.take_visual_dirty() {
+            if self.gs.chunks.mesh_ready(coord) {
+                self.queue.emit_now(Event::ChunkRebuildRequested {
+                    cx: coord.cx,
+                    cy: coord.cy,
+                    cz: coord.cz,
+                    cause: RebuildCause::Edit,
+                });
+            }
+        }
+    }
+
+    /// Emitter events for a voxel whose block goes from `old` to `new`. Light is compared
+    /// per state, so flipping a furnace's `lit` swaps its emitter like replacing the
+    /// block would, while changes that keep the light leave the emitter alone.
+    fn emitter_swap_events(&self, pos: (i32, i32, i32), old: Block, new: Block) -> Vec<Event> {
+        let light = |b: Block| {
+            self.reg
+                .get(b.id)
+                .map(|t| (t.light_emission(b.state), t.light_is_beam()))
+                .filter(|(level, _)| *level > 0)
+        };
+        let (before, after) = (light(old), light(new));
+        if before == after {
+            return Vec::new();
+        }
+        let (wx, wy, wz) = pos;
+        let mut events = Vec::with_capacity(2);
+        if let Some((level, _)) = before {
+            events.push(Event::LightEmitterRemoved { wx, wy, wz, level });
+        }
+        if let Some((level, is_beacon)) = after {
+            events.push(Event::LightEmitterAdded {
+                wx,
+                wy,
+                wz,
+                level,
+                is_beacon,
+            });
+        }
+        events
     }
 
     pub(super) fn handle_light_emitter_added(
@@ -314,33 +480,281 @@ impl App {
         } else {
             self.gs.lighting.add_emitter_world(wx, wy, wz, level);
         }
-        let sx = self.gs.world.chunk_size_x as i32;
-        let sy = self.gs.world.chunk_size_y as i32;
-        let sz = self.gs.world.chunk_size_z as i32;
-        let cx = wx.div_euclid(sx);
-        let cy = wy.div_euclid(sy);
-        let cz = wz.div_euclid(sz);
+        let coord = self.gs.world.chunk_of(wx, wy, wz);
         self.queue.emit_now(Event::ChunkRebuildRequested {
-            cx,
-            cy,
-            cz,
+            cx: coord.cx,
+            cy: coord.cy,
+            cz: coord.cz,
             cause: RebuildCause::Edit,
         });
     }
 
-    pub(super) fn handle_light_emitter_removed(&mut self, wx: i32, wy: i32, wz: i32) {
-        self.gs.lighting.remove_emitter_world(wx, wy, wz);
-        let sx = self.gs.world.chunk_size_x as i32;
-        let sy = self.gs.world.chunk_size_y as i32;
-        let sz = self.gs.world.chunk_size_z as i32;
-        let cx = wx.div_euclid(sx);
-        let cy = wy.div_euclid(sy);
-        let cz = wz.div_euclid(sz);
+    pub(super) fn handle_light_emitter_removed(&mut self, wx: i32, wy: i32, wz: i32, level: u8) {
+        let darkened = self.gs.lighting.remove_emitter_world(wx, wy, wz, level);
+        let coord = self.gs.world.chunk_of(wx, wy, wz);
         self.queue.emit_now(Event::ChunkRebuildRequested {
-            cx,
-            cy,
-            cz,
+            cx: coord.cx,
+            cy: coord.cy,
+            cz: coord.cz,
             cause: RebuildCause::Edit,
         });
+        self.relight_darkened_borders(darkened);
+    }
+
+    /// Relight chunks whose seam planes lost a removed emitter's light and notify their
+    /// neighbours across the darkened faces, so the darkness spreads like a border update.
+    fn relight_darkened_borders(&mut self, darkened: Vec<(ChunkCoord, BorderChangeMask)>) {
+        for (coord, mask) in darkened {
+            self.queue.emit_now(Event::ChunkRebuildRequested {
+                cx: coord.cx,
+                cy: coord.cy,
+                cz: coord.cz,
+                cause: RebuildCause::LightingBorder,
+            });
+            self.queue.emit_now(Event::LightBordersUpdated {
+                cx: coord.cx,
+                cy: coord.cy,
+                cz: coord.cz,
+                xn_changed: mask.xn,
+                xp_changed: mask.xp,
+                yn_changed: mask.yn,
+                yp_changed: mask.yp,
+                zn_changed: mask.zn,
+                zp_changed: mask.zp,
+            });
+        }
+    }
+
+    pub(super) fn handle_edit_history_step(&mut self, redo: bool) {
+        let group = if redo {
+            self.gs.edits.peek_redo()
+        } else {
+            self.gs.edits.peek_undo()
+        };
+        let Some(group) = group else {
+            let what = if redo { "redo" } else { "undo" };
+            self.toast = Some(Toast::new(format!("Nothing to {}", what), 1.5));
+            return;
+        };
+        // Net change per position: first value replaced → last value written
+        let mut steps: Vec<EditChange> = group.changes.clone();
+        if !redo {
+            steps.reverse();
+            for c in &mut steps {
+                std::mem::swap(&mut c.before, &mut c.after);
+            }
+        }
+        let mut net: HashMap<(i32, i32, i32), EditChange> = HashMap::new();
+        for c in steps {
+            net.entry(c.pos).or_insert(c).after = c.after;
+        }
+        let count = group.changes.len();
+        // `None` means no edit, so the generated block is what shows there
+        let resolve = |app: &Self, (wx, wy, wz): (i32, i32, i32), b: Option<Block>| {
+            b.unwrap_or_else(|| app.gs.world.block_at_runtime(&app.reg, wx, wy, wz))
+        };
+        let mut light_events = Vec::new();
+        let mut restored = Vec::new();
+        for c in net.values().filter(|c| c.before != c.after) {
+            let (old, new) = (
+                resolve(self, c.pos, c.before),
+                resolve(self, c.pos, c.after),
+            );
+            light_events.extend(self.emitter_swap_events(c.pos, old, new));
+            restored.push((c.pos, new));
+        }
+
+        let affected = if redo {
+            self.gs.edits.redo()
+        } else {
+            self.gs.edits.undo()
+        };
+        for ev in light_events {
+            self.queue.emit_now(ev);
+        }
+        // Entity data is not part of the history; restored entity blocks start empty.
+        for (pos, block) in restored {
+            self.sync_block_entity(pos, block);
+            self.fluids.activate(pos);
+            self.schedule_block_ticks_around(pos);
+        }
+        self.request_edit_rebuilds(affected);
+        let what = if redo { "Redo" } else { "Undo" };
+        self.toast = Some(Toast::new(
+            format!(
+                "{}: {} block{}",
+                what,
+                count,
+                if count == 1 { "" } else { "s" }
+            ),
+            1.5,
+        ));
+    }
+
+    pub(super) fn handle_structure_stamp_requested(&mut self, id: StructureId) {
+        let Some(st) = self.gs.structures.get(&id) else {
+            return;
+        };
+        let blocks = st.world_blocks(&self.reg);
+        if blocks.is_empty() {
+            self.toast = Some(Toast::new(
+                format!("Structure {} has no blocks to stamp", id),
+                1.5,
+            ));
+            return;
+        }
+        self.stamp_world_blocks(format!("Stamping structure {}", id), blocks);
+    }
+
+    /// Write `blocks` into the world edits as one undoable stamp, swapping light emitters
+    /// and tracking the chunk rebuilds as a runtime batch.
+    fn stamp_world_blocks(&mut self, label: String, blocks: Vec<((i32, i32, i32), Block)>) {
+        // Swap emitters at every voxel the stamp overwrites
+        let mut ctx = self.gs.world.make_gen_ctx();
+        let mut light_events = Vec::new();
+        for &((wx, wy, wz), b) in &blocks {
+            let old = self.gs.edits.get(wx, wy, wz).unwrap_or_else(|| {
+                self.gs
+                    .world
+                    .block_at_runtime_with(&self.reg, &mut ctx, wx, wy, wz)
+            });
+            light_events.extend(self.emitter_swap_events((wx, wy, wz), old, b));
+        }
+
+        for &(pos, b) in &blocks {
+            self.sync_block_entity(pos, b);
+            self.fluids.activate(pos);
+            self.schedule_block_ticks_around(pos);
+        }
+        let batch = self.gs.edits.apply_batch(blocks);
+        for ev in light_events {
+            self.queue.emit_now(ev);
+        }
+        // Track the rebuilds as one runtime batch so the HUD can show progress
+        let batch_id = self.runtime.begin_batch(label, batch.affected_chunks.len());
+        for coord in &batch.affected_chunks {
+            self.batch_chunks.insert(*coord, batch_id);
+        }
+        self.stamp_batch = Some((batch_id, batch.stamp));
+        self.request_edit_rebuilds(batch.affected_chunks);
+    }
+
+    pub(super) fn handle_schematic_selection_moved(&mut self, delta: i32) {
+        let n = self
+            .schematic_library
+            .as_ref()
+            .map_or(0, |lib| lib.entries().len());
+        if n == 0 {
+            return;
+        }
+        let cur = self.schematic_selected.min(n - 1) as i64;
+        self.schematic_selected = (cur + delta as i64).rem_euclid(n as i64) as usize;
+    }
+
+    /// Paste a library schematic so its footprint is centered a few blocks ahead of the
+    /// camera, with its base at eye level minus one.
+    pub(super) fn handle_schematic_paste_requested(&mut self, file: &str) {
+        let Some(lib) = self.schematic_library.as_ref() else {
+            return;
+        };
+        let Some(meta) = lib.entries().iter().find(|m| m.file == file) else {
+            self.toast = Some(Toast::new(
+                format!("Schematic {} is not indexed", file),
+                1.5,
+            ));
+            return;
+        };
+        let path = lib.path_of(meta);
+        let name = meta.name().to_string();
+        let palette = geist_io::PaletteMap::load_default();
+        let mut st = match Structure::from_schematic(&path, &self.reg, &palette) {
+            Ok(s) => s.structure,
+            Err(e) => {
+                log::warn!("Schematic paste {:?} failed: {}", path, e);
+                self.toast = Some(Toast::new(format!("Cannot load {}: {}", name, e), 2.0));
+                return;
+            }
+        };
+        let fwd = self.cam.forward();
+        let reach = 4.0 + st.sx.max(st.sz) as f32 * 0.5;
+        let c = self.cam.position + fwd * reach;
+        let origin = geist_geom::Vec3::new(
+            (c.x - st.sx as f32 * 0.5).floor(),
+            self.cam.position.y.floor() - 1.0,
+            (c.z - st.sz as f32 * 0.5).floor(),
+        );
+        st.pose = Pose::from_yaw(origin, 0.0);
+        let blocks = st.world_blocks(&self.reg);
+        if blocks.is_empty() {
+            self.toast = Some(Toast::new(format!("Schematic {} is empty", name), 1.5));
+            return;
+        }
+        self.stamp_world_blocks(format!("Pasting schematic {}", name), blocks);
+    }
+
+    pub(super) fn handle_structure_stamp_cancel_requested(&mut self) {
+        let Some((batch_id, _)) = self.stamp_batch else {
+            return;
+        };
+        self.runtime.cancel_batch(batch_id);
+    }
+
+    pub(super) fn handle_batch_event(&mut self, event: BatchEvent) {
+        match event {
+            BatchEvent::Progress { .. } => {}
+            BatchEvent::Completed { id, total } => {
+                self.batch_chunks.retain(|_, b| *b != id);
+                if self.stamp_batch.is_some_and(|(b, _)| b == id) {
+                    self.stamp_batch = None;
+                    self.toast = Some(Toast::new(
+                        format!("Stamp finished: {} chunks rebuilt", total),
+                        2.0,
+                    ));
+                }
+            }
+            BatchEvent::Cancelled { id, done, total } => {
+                self.batch_chunks.retain(|_, b| *b != id);
+                let Some((_, stamp)) = self.stamp_batch.filter(|(b, _)| *b == id) else {
+                    return;
+                };
+                self.stamp_batch = None;
+                // Revert only if nothing was edited after the stamp
+                let reverted = self.gs.edits.latest_stamp() == stamp && self.gs.edits.can_undo();
+                if reverted {
+                    self.handle_edit_history_step(false);
+                }
+                self.toast = Some(Toast::new(
+                    format!(
+                        "Stamp cancelled at {}/{} chunks{}",
+                        done,
+                        total,
+                        if reverted { ", reverted" } else { "" }
+                    ),
+                    2.0,
+                ));
+            }
+            BatchEvent::JobSkipped { coord, rev, .. } => {
+                // The job never ran; release the chunk so later rebuilds are not blocked
+                if self.gs.inflight_rev.get(&coord) == Some(&rev) {
+                    self.gs.inflight_rev.remove(&coord);
+                }
+            }
+        }
+    }
+
+    /// Rebuild edited chunks that have a mesh; load the rest so they pick the edits up.
+    fn request_edit_rebuilds(&mut self, chunks: Vec<ChunkCoord>) {
+        for coord in chunks {
+            if self.gs.chunks.mesh_ready(coord) {
+                self.queue.emit_now(Event::ChunkRebuildRequested {
+                    cx: coord.cx,
+                    cy: coord.cy,
+                    cz: coord.cz,
+                    cause: RebuildCause::Edit,
+                });
+            } else {
+                self.prepare_chunk_for_edit(coord);
+            }
+        }
     }
 }
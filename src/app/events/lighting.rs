@@ -30,6 +30,7 @@ impl App {
             self.gs.inflight_rev.remove(&coord);
             return;
         }
+        self.gs.lighting.update_levels(coord, &light_grid);
         let nb = self.gs.lighting.get_neighbor_borders(coord);
         let atlas = pack_light_grid_atlas_with_neighbors(&light_grid, &nb);
         self.validate_chunk_light_atlas(coord, &atlas);
@@ -189,5 +190,12 @@ pub(crate) fn structure_neighbor_borders(lb: &LightBorders) -> NeighborBorders {
         bcn_dir_xp: Some(lb.bcn_dir_xp.clone()),
         bcn_dir_zn: Some(lb.bcn_zn.clone()),
         bcn_dir_zp: Some(lb.bcn_zp.clone()),
+        flk_xn: Some(lb.flk_xn.clone()),
+        flk_xp: Some(lb.flk_xp.clone()),
+        flk_zn: Some(lb.flk_zn.clone()),
+        flk_zp: Some(lb.flk_zp.clone()),
+        flk_yn: Some(lb.flk_yn.clone()),
+        flk_yp: Some(lb.flk_yp.clone()),
+        inputs: [None; 6],
     }
 }
@@ -11,6 +11,9 @@ impl App {
             E::WalkModeToggled => {
                 log::info!(target: "events", "[tick {}] WalkModeToggled", tick);
             }
+            E::SpectatorToggled => {
+                log::info!(target: "events", "[tick {}] SpectatorToggled", tick);
+            }
             E::GridToggled => {
                 log::info!(target: "events", "[tick {}] GridToggled", tick);
             }
@@ -29,9 +32,54 @@ impl App {
             E::DebugOverlayToggled => {
                 log::info!(target: "events", "[tick {}] DebugOverlayToggled", tick);
             }
+            E::AmbianceCycled => {
+                log::info!(target: "events", "[tick {}] AmbianceCycled", tick);
+            }
+            E::WorldMapExportRequested => {
+                log::info!(target: "events", "[tick {}] WorldMapExportRequested", tick);
+            }
+            E::LightingCompareToggled => {
+                log::info!(target: "events", "[tick {}] LightingCompareToggled", tick);
+            }
+            E::LightingCompareFlipped => {
+                log::info!(target: "events", "[tick {}] LightingCompareFlipped", tick);
+            }
+            E::HandTorchToggled => {
+                log::info!(target: "events", "[tick {}] HandTorchToggled", tick);
+            }
+            E::RenderScaleStepped { delta } => {
+                log::info!(target: "events", "[tick {}] RenderScaleStepped delta={}", tick, delta);
+            }
+            E::UpscaleFilterCycled => {
+                log::info!(target: "events", "[tick {}] UpscaleFilterCycled", tick);
+            }
+            E::ShadowCascadesCycled => {
+                log::info!(target: "events", "[tick {}] ShadowCascadesCycled", tick);
+            }
+            E::ShadowResolutionCycled => {
+                log::info!(target: "events", "[tick {}] ShadowResolutionCycled", tick);
+            }
+            E::UiScaleStepped { delta } => {
+                log::info!(target: "events", "[tick {}] UiScaleStepped delta={}", tick, delta);
+            }
+            E::OverlayPaletteCycled => {
+                log::info!(target: "events", "[tick {}] OverlayPaletteCycled", tick);
+            }
+            E::ReducedFlashingToggled => {
+                log::info!(target: "events", "[tick {}] ReducedFlashingToggled", tick);
+            }
             E::PlaceTypeSelected { block } => {
                 log::info!(target: "events", "[tick {}] PlaceTypeSelected block={:?}", tick, block);
             }
+            E::HotbarSlotSelected { slot } => {
+                log::info!(target: "events", "[tick {}] HotbarSlotSelected slot={}", tick, slot);
+            }
+            E::HotbarScrolled { delta } => {
+                log::info!(target: "events", "[tick {}] HotbarScrolled delta={}", tick, delta);
+            }
+            E::BlockPickRequested => {
+                log::info!(target: "events", "[tick {}] BlockPickRequested", tick);
+            }
             E::MovementRequested {
                 dt_ms,
                 yaw,
@@ -46,7 +94,7 @@ impl App {
                     if *walk_mode { "walk" } else { "fly" }
                 );
             }
-            E::RaycastEditRequested { place, block } => {
+            E::RaycastEditRequested { place, block, .. } => {
                 log::info!(
                     target: "events",
                     "[tick {}] RaycastEditRequested {} block={:?}",
@@ -55,7 +103,17 @@ impl App {
                     block
                 );
             }
-            E::BlockPlaced { wx, wy, wz, block } => {
+            E::EditHistoryStepRequested { redo } => {
+                log::info!(
+                    target: "events",
+                    "[tick {}] EditHistoryStepRequested {}",
+                    tick,
+                    if *redo { "redo" } else { "undo" }
+                );
+            }
+            E::BlockPlaced {
+                wx, wy, wz, block, ..
+            } => {
                 log::info!(
                     target: "events",
                     "[tick {}] BlockPlaced ({},{},{}) block={:?}",
@@ -66,7 +124,7 @@ impl App {
                     block
                 );
             }
-            E::BlockRemoved { wx, wy, wz } => {
+            E::BlockRemoved { wx, wy, wz, .. } => {
                 log::info!(
                     target: "events",
                     "[tick {}] BlockRemoved ({},{},{})",
@@ -76,6 +134,27 @@ impl App {
                     wz
                 );
             }
+            E::BlockTicked {
+                wx,
+                wy,
+                wz,
+                from,
+                to,
+            } => {
+                log::debug!(
+                    target: "events",
+                    "[tick {}] BlockTicked ({},{},{}) {:?} -> {:?}",
+                    tick,
+                    wx,
+                    wy,
+                    wz,
+                    from,
+                    to
+                );
+            }
+            E::DimensionTravelRequested { target } => {
+                log::info!(target: "events", "[tick {}] DimensionTravelRequested target={}", tick, target.0);
+            }
             E::ViewCenterChanged { ccx, ccy, ccz } => {
                 log::info!(
                     target: "events",
@@ -166,6 +245,17 @@ impl App {
                     job_id
                 );
             }
+            E::BuildChunkJobDropped { cx, cy, cz, rev } => {
+                log::debug!(
+                    target: "events",
+                    "[tick {}] BuildChunkJobDropped ({}, {}, {}) rev={}",
+                    tick,
+                    cx,
+                    cy,
+                    cz,
+                    rev
+                );
+            }
             E::ChunkLightingRecomputed {
                 cx,
                 cy,
@@ -195,18 +285,22 @@ impl App {
                 id,
                 pos,
                 yaw_deg,
+                pitch_deg,
+                roll_deg,
                 delta,
                 velocity,
             } => {
                 log::trace!(
                     target: "events",
-                    "[tick {}] StructurePoseUpdated id={} pos=({:.2},{:.2},{:.2}) yaw={:.1} delta=({:.2},{:.2},{:.2}) vel=({:.2},{:.2},{:.2})",
+                    "[tick {}] StructurePoseUpdated id={} pos=({:.2},{:.2},{:.2}) yaw={:.1} pitch={:.1} roll={:.1} delta=({:.2},{:.2},{:.2}) vel=({:.2},{:.2},{:.2})",
                     tick,
                     id,
                     pos.x,
                     pos.y,
                     pos.z,
                     yaw_deg,
+                    pitch_deg,
+                    roll_deg,
                     delta.x,
                     delta.y,
                     delta.z,
@@ -244,6 +338,36 @@ impl App {
                     lz
                 );
             }
+            E::StructureStampRequested { id } => {
+                log::info!(
+                    target: "events",
+                    "[tick {}] StructureStampRequested id={}",
+                    tick,
+                    id
+                );
+            }
+            E::StructureStampCancelRequested => {
+                log::info!(target: "events", "[tick {}] StructureStampCancelRequested", tick);
+            }
+            E::SchematicSelectionMoved { delta } => {
+                log::info!(
+                    target: "events",
+                    "[tick {}] SchematicSelectionMoved delta={}",
+                    tick,
+                    delta
+                );
+            }
+            E::SchematicPasteRequested { file } => {
+                log::info!(
+                    target: "events",
+                    "[tick {}] SchematicPasteRequested file={}",
+                    tick,
+                    file
+                );
+            }
+            E::BatchJobsUpdated { event } => {
+                log::debug!(target: "events", "[tick {}] BatchJobsUpdated {:?}", tick, event);
+            }
             E::PlayerAttachedToStructure { id, local_offset } => {
                 log::info!(
                     target: "events",
@@ -276,14 +400,15 @@ impl App {
                     is_beacon
                 );
             }
-            E::LightEmitterRemoved { wx, wy, wz } => {
+            E::LightEmitterRemoved { wx, wy, wz, level } => {
                 log::info!(
                     target: "events",
-                    "[tick {}] LightEmitterRemoved ({},{},{})",
+                    "[tick {}] LightEmitterRemoved ({},{},{}) level={}",
                     tick,
                     wx,
                     wy,
-                    wz
+                    wz,
+                    level
                 );
             }
             E::LightBordersUpdated {
@@ -312,6 +437,9 @@ impl App {
                     zp_changed
                 );
             }
+            E::ModalResolved { id, result } => {
+                log::info!(target: "events", "[tick {}] ModalResolved {:?} -> {:?}", tick, id, result);
+            }
         }
     }
 }
@@ -1,4 +1,5 @@
 mod builds;
+mod dimensions;
 mod editing;
 mod helpers;
 mod lighting;
@@ -33,10 +34,18 @@ impl App {
                 id,
                 pos,
                 yaw_deg,
+                pitch_deg,
+                roll_deg,
                 delta,
                 velocity,
             } => {
-                self.handle_structure_pose_updated(id, pos, yaw_deg, delta, velocity);
+                self.handle_structure_pose_updated(
+                    id,
+                    pos,
+                    [yaw_deg, pitch_deg, roll_deg],
+                    delta,
+                    velocity,
+                );
             }
             Event::MovementRequested {
                 dt_ms,
@@ -51,6 +60,9 @@ impl App {
             Event::PlayerDetachedFromStructure { id } => {
                 self.handle_player_detached_from_structure(id);
             }
+            Event::DimensionTravelRequested { target } => {
+                self.handle_dimension_travel_requested(target);
+            }
             Event::ViewCenterChanged { ccx, ccy, ccz } => {
                 self.handle_view_center_changed(ccx, ccy, ccz);
             }
@@ -80,7 +92,7 @@ impl App {
             Event::StructureBuildCompleted {
                 id,
                 rev,
-                cpu,
+                sections,
                 light_grid,
                 light_borders,
             } => {
@@ -89,11 +101,14 @@ impl App {
                     thread,
                     id,
                     rev,
-                    cpu,
+                    sections,
                     light_grid,
                     light_borders,
                 );
             }
+            Event::BuildChunkJobDropped { cx, cy, cz, rev } => {
+                self.handle_build_chunk_job_dropped(ChunkCoord::new(cx, cy, cz), rev);
+            }
             Event::BuildChunkJobCompleted {
                 cx,
                 cy,
@@ -106,6 +121,7 @@ impl App {
                 light_grid,
                 job_id: _,
                 column_profile,
+                worldgen_rev,
             } => {
                 let coord = ChunkCoord::new(cx, cy, cz);
                 self.handle_build_chunk_job_completed(
@@ -119,6 +135,7 @@ impl App {
                     light_borders,
                     light_grid,
                     column_profile,
+                    worldgen_rev,
                 );
             }
             Event::ChunkLightingRecomputed {
@@ -136,8 +153,15 @@ impl App {
                 let coord = ChunkCoord::new(cx, cy, cz);
                 self.handle_chunk_rebuild_requested(coord, cause);
             }
-            Event::RaycastEditRequested { place, block } => {
-                self.handle_raycast_edit_requested(place, block);
+            Event::RaycastEditRequested {
+                place,
+                block,
+                issued_at,
+            } => {
+                self.handle_raycast_edit_requested(place, block, issued_at);
+            }
+            Event::EditHistoryStepRequested { redo } => {
+                self.handle_edit_history_step(redo);
             }
             Event::StructureBlockPlaced {
                 id,
@@ -151,11 +175,46 @@ impl App {
             Event::StructureBlockRemoved { id, lx, ly, lz } => {
                 self.handle_structure_block_removed(id, lx, ly, lz);
             }
-            Event::BlockPlaced { wx, wy, wz, block } => {
-                self.handle_block_placed(wx, wy, wz, block);
+            Event::StructureStampRequested { id } => {
+                self.handle_structure_stamp_requested(id);
+            }
+            Event::StructureStampCancelRequested => {
+                self.handle_structure_stamp_cancel_requested();
+            }
+            Event::SchematicSelectionMoved { delta } => {
+                self.handle_schematic_selection_moved(delta);
+            }
+            Event::SchematicPasteRequested { file } => {
+                self.handle_schematic_paste_requested(&file);
             }
-            Event::BlockRemoved { wx, wy, wz } => {
-                self.handle_block_removed(wx, wy, wz);
+            Event::BatchJobsUpdated { event } => {
+                self.handle_batch_event(event);
+            }
+            Event::BlockPlaced {
+                wx,
+                wy,
+                wz,
+                block,
+                issued_at,
+            } => {
+                self.handle_block_placed(wx, wy, wz, block, issued_at);
+            }
+            Event::BlockRemoved {
+                wx,
+                wy,
+                wz,
+                issued_at,
+            } => {
+                self.handle_block_removed(wx, wy, wz, issued_at);
+            }
+            Event::BlockTicked {
+                wx,
+                wy,
+                wz,
+                from,
+                to,
+            } => {
+                self.handle_block_ticked(wx, wy, wz, from, to);
             }
             Event::LightEmitterAdded {
                 wx,
@@ -166,8 +225,8 @@ impl App {
             } => {
                 self.handle_light_emitter_added(wx, wy, wz, level, is_beacon);
             }
-            Event::LightEmitterRemoved { wx, wy, wz } => {
-                self.handle_light_emitter_removed(wx, wy, wz);
+            Event::LightEmitterRemoved { wx, wy, wz, level } => {
+                self.handle_light_emitter_removed(wx, wy, wz, level);
             }
             Event::LightBordersUpdated {
                 cx,
@@ -188,6 +247,9 @@ impl App {
             Event::WalkModeToggled => {
                 self.handle_walk_mode_toggled();
             }
+            Event::SpectatorToggled => {
+                self.handle_spectator_toggled();
+            }
             Event::GridToggled => {
                 self.handle_grid_toggle();
             }
@@ -206,9 +268,57 @@ impl App {
             Event::DebugOverlayToggled => {
                 self.handle_debug_overlay_toggle();
             }
+            Event::AmbianceCycled => {
+                self.handle_ambiance_cycled();
+            }
+            Event::WorldMapExportRequested => {
+                self.handle_world_map_export_requested();
+            }
+            Event::LightingCompareToggled => {
+                self.handle_lighting_compare_toggled(rl, thread);
+            }
+            Event::LightingCompareFlipped => {
+                self.handle_lighting_compare_flipped(rl, thread);
+            }
+            Event::HandTorchToggled => {
+                self.handle_hand_torch_toggled();
+            }
+            Event::RenderScaleStepped { delta } => {
+                self.handle_render_scale_stepped(delta);
+            }
+            Event::UpscaleFilterCycled => {
+                self.handle_upscale_filter_cycled();
+            }
+            Event::ShadowCascadesCycled => {
+                self.handle_shadow_cascades_cycled();
+            }
+            Event::ShadowResolutionCycled => {
+                self.handle_shadow_resolution_cycled();
+            }
+            Event::UiScaleStepped { delta } => {
+                self.handle_ui_scale_stepped(delta);
+            }
+            Event::OverlayPaletteCycled => {
+                self.handle_overlay_palette_cycled();
+            }
+            Event::ReducedFlashingToggled => {
+                self.handle_reduced_flashing_toggled();
+            }
             Event::PlaceTypeSelected { block } => {
                 self.handle_place_type_selected(block);
             }
+            Event::HotbarSlotSelected { slot } => {
+                self.handle_hotbar_slot_selected(slot);
+            }
+            Event::HotbarScrolled { delta } => {
+                self.handle_hotbar_scrolled(delta);
+            }
+            Event::BlockPickRequested => {
+                self.handle_block_pick_requested();
+            }
+            Event::ModalResolved { .. } => {
+                // Flows that open a dialog keep its ModalId and react to the result here.
+            }
         }
     }
 }
@@ -5,10 +5,9 @@ use super::{
 use crate::event::Event;
 use crate::gamestate::{StructureAnchor, WalkerAnchor};
 use geist_blocks::Block;
-use geist_chunk::ChunkOccupancy;
-use geist_geom::Vec3;
+use geist_geom::{Aabb, Vec3};
 use geist_render_raylib::conv::{vec3_from_rl, vec3_to_rl};
-use geist_structures::{Structure, StructureId, rotate_yaw_inv};
+use geist_structures::{Structure, StructureId};
 use raylib::prelude::*;
 
 impl App {
@@ -16,19 +15,39 @@ impl App {
         &mut self,
         id: StructureId,
         pos: Vector3,
-        yaw_deg: f32,
+        [yaw_deg, pitch_deg, roll_deg]: [f32; 3],
         delta: Vector3,
         velocity: Vector3,
     ) {
+        let (mut pos, mut delta, mut velocity) = (pos, delta, velocity);
+        let sun_id = self.sun.as_ref().map(|s| s.id);
+        if Some(id) != sun_id
+            && let Some(st) = self.gs.structures.get(&id)
+        {
+            let (dx, dz) = self.structure_border_shift(st, vec3_from_rl(pos));
+            if dx != 0.0 {
+                pos.x += dx;
+                delta.x += dx;
+                velocity.x = 0.0;
+            }
+            if dz != 0.0 {
+                pos.z += dz;
+                delta.z += dz;
+                velocity.z = 0.0;
+            }
+        }
         if let Some(st) = self.gs.structures.get_mut(&id) {
             st.last_delta = vec3_from_rl(delta);
             st.last_velocity = vec3_from_rl(velocity);
             st.pose.pos = vec3_from_rl(pos);
             st.pose.yaw_deg = yaw_deg;
+            st.pose.pitch_deg = pitch_deg;
+            st.pose.roll_deg = roll_deg;
             if matches!(self.gs.anchor, WalkerAnchor::Structure(anchor) if anchor.id == id) {
                 self.sync_anchor_world_pose();
             }
         }
+        self.sync_structure_lights(id, false);
     }
 
     pub(super) fn handle_movement_requested(
@@ -40,10 +59,7 @@ impl App {
         walk_mode: bool,
     ) {
         let _ = (thread, dt_ms, walk_mode);
-        if self.gs.walk_mode {
-            let sx = self.gs.world.chunk_size_x as i32;
-            let sz = self.gs.world.chunk_size_z as i32;
-
+        if self.gs.walk_mode && !self.gs.spectator {
             if matches!(self.gs.anchor, WalkerAnchor::World) {
                 let sun_id = self.sun.as_ref().map(|s| s.id);
                 for (id, st) in &self.gs.structures {
@@ -66,66 +82,28 @@ impl App {
             }
 
             let reg = &self.reg;
+            let blocks = self.gs.chunks.blocks();
+            let sun_id = self.sun.as_ref().map(|s| s.id);
+            let structures = &self.gs.structures;
             let world_sampler = |wx: i32, wy: i32, wz: i32| -> Block {
-                let sun_id = self.sun.as_ref().map(|s| s.id);
-                for st in self.gs.structures.values() {
-                    if Some(st.id) == sun_id {
-                        continue;
-                    }
-                    let p = vec3_from_rl(Vector3::new(
-                        wx as f32 + 0.5,
-                        wy as f32 + 0.5,
-                        wz as f32 + 0.5,
-                    ));
-                    let diff = Vec3 {
-                        x: p.x - st.pose.pos.x,
-                        y: p.y - st.pose.pos.y,
-                        z: p.z - st.pose.pos.z,
-                    };
-                    let local = rotate_yaw_inv(diff, st.pose.yaw_deg);
-                    let lx = local.x.floor() as i32;
-                    let ly = local.y.floor() as i32;
-                    let lz = local.z.floor() as i32;
-                    if lx >= 0
-                        && ly >= 0
-                        && lz >= 0
-                        && (lx as usize) < st.sx
-                        && (ly as usize) < st.sy
-                        && (lz as usize) < st.sz
-                    {
-                        if let Some(b) = st.edits.get(lx, ly, lz) {
-                            if reg.get(b.id).map(|t| t.is_solid(b.state)).unwrap_or(false) {
-                                return b;
-                            }
-                        }
-                        let idx = st.idx(lx as usize, ly as usize, lz as usize);
-                        let b = st.blocks[idx];
-                        if reg.get(b.id).map(|t| t.is_solid(b.state)).unwrap_or(false) {
-                            return b;
-                        }
-                    }
-                }
                 if let Some(b) = self.gs.edits.get(wx, wy, wz) {
                     return b;
                 }
-                let cx = wx.div_euclid(sx);
-                let cy = wy.div_euclid(self.gs.world.chunk_size_y as i32);
-                let cz = wz.div_euclid(sz);
-                if let Some(cent) = self
-                    .gs
-                    .chunks
-                    .get(&geist_world::ChunkCoord::new(cx, cy, cz))
-                {
-                    match (cent.occupancy_or_empty(), cent.buf.as_ref()) {
-                        (ChunkOccupancy::Empty, _) => return Block::AIR,
-                        (_, Some(buf)) => {
-                            return buf.get_world(wx, wy, wz).unwrap_or(Block::AIR);
-                        }
-                        (_, None) => {}
-                    }
+                if let Some(b) = blocks.block_at(wx, wy, wz) {
+                    return b;
                 }
                 self.gs.world.block_at_runtime(reg, wx, wy, wz)
             };
+            // Posed structures collide as boxes against their voxels; the one the walker
+            // stands on is sampled as voxels in its own space instead.
+            let obstacles_except = |skip: Option<StructureId>, aabb: &Aabb| {
+                structures.values().any(|st| {
+                    Some(st.id) != sun_id && Some(st.id) != skip && st.overlaps_aabb(reg, aabb)
+                })
+            };
+            let world_obstacles = |min: Vector3, max: Vector3| {
+                obstacles_except(None, &Aabb::new(vec3_from_rl(min), vec3_from_rl(max)))
+            };
             let dt_sec = self.last_frame_dt.max(0.0);
             let mut detach_request: Option<StructureId> = None;
             let mut predicted_anchor: Option<(StructureId, Vector3)> = None;
@@ -140,17 +118,35 @@ impl App {
                         );
                         let relative_vel_world =
                             vec3_from_rl(self.gs.walker.vel) - st.last_velocity;
-                        let local_vel_before = rotate_yaw_inv(relative_vel_world, st.pose.yaw_deg);
+                        let local_vel_before = st.pose.vector_to_local(relative_vel_world);
 
                         self.gs.walker.pos = vec3_to_rl(local_before);
                         self.gs.walker.vel = vec3_to_rl(local_vel_before);
 
                         let structure_sampler =
                             structure_local_sampler(st, |wx, wy, wz| world_sampler(wx, wy, wz));
+                        let structure_obstacles = |min: Vector3, max: Vector3| {
+                            let (min, max) = (vec3_from_rl(min), vec3_from_rl(max));
+                            // World box around the posed local box.
+                            let mut lo = Vec3::new(f32::MAX, f32::MAX, f32::MAX);
+                            let mut hi = Vec3::new(f32::MIN, f32::MIN, f32::MIN);
+                            for i in 0..8 {
+                                let corner = Vec3::new(
+                                    if i & 1 == 0 { min.x } else { max.x },
+                                    if i & 2 == 0 { min.y } else { max.y },
+                                    if i & 4 == 0 { min.z } else { max.z },
+                                );
+                                let w = st.pose.local_to_world(corner);
+                                lo = Vec3::new(lo.x.min(w.x), lo.y.min(w.y), lo.z.min(w.z));
+                                hi = Vec3::new(hi.x.max(w.x), hi.y.max(w.y), hi.z.max(w.z));
+                            }
+                            obstacles_except(Some(st.id), &Aabb::new(lo, hi))
+                        };
                         let prev_local = local_before;
                         self.gs.walker.update_structure_space(
                             rl,
                             &structure_sampler,
+                            &structure_obstacles,
                             &self.reg,
                             dt_sec,
                             yaw,
@@ -177,15 +173,21 @@ impl App {
                         self.gs.walker.update_world_space(
                             rl,
                             &world_sampler,
+                            &world_obstacles,
                             &self.reg,
                             dt_sec,
                             yaw,
                         );
                     }
                 } else {
-                    self.gs
-                        .walker
-                        .update_world_space(rl, &world_sampler, &self.reg, dt_sec, yaw);
+                    self.gs.walker.update_world_space(
+                        rl,
+                        &world_sampler,
+                        &world_obstacles,
+                        &self.reg,
+                        dt_sec,
+                        yaw,
+                    );
                 }
             }
             if let Some((anchor_id, predicted_rl)) = predicted_anchor {
@@ -220,9 +222,11 @@ impl App {
                 self.queue
                     .emit_now(Event::PlayerDetachedFromStructure { id });
             }
+            self.clamp_to_world_border();
             self.cam.position = self.gs.walker.eye_position();
             self.emit_view_center_if_changed();
         } else {
+            self.clamp_to_world_border();
             self.emit_view_center_if_changed();
         }
     }
@@ -263,7 +267,7 @@ impl App {
         for off in &offsets {
             let p = feet_world + *off;
             let pv = vec3_from_rl(p);
-            let local = structure_world_to_local(pv, st.pose.pos, st.pose.yaw_deg);
+            let local = structure_world_to_local(pv, &st.pose);
             let lx = local.x.floor() as i32;
             let ly = (local.y - 0.08).floor() as i32;
             let lz = local.z.floor() as i32;
@@ -12,13 +12,14 @@ impl App {
         let evict_radius = self.stream_evict_radius();
         let desired: HashSet<ChunkCoord> = spherical_chunk_coords(center, load_radius)
             .into_iter()
+            .filter(|&c| self.chunk_in_border(c))
             .collect();
         let evict_limit_sq = {
             let er = evict_radius;
             i64::from(er) * i64::from(er)
         };
         for key in self.gs.chunks.coords_any().collect::<Vec<_>>() {
-            if center.distance_sq(key) > evict_limit_sq {
+            if center.distance_sq(key) > evict_limit_sq || !self.chunk_in_border(key) {
                 self.queue.emit_now(Event::EnsureChunkUnloaded {
                     cx: key.cx,
                     cy: key.cy,
@@ -52,6 +53,7 @@ impl App {
 
     pub(super) fn handle_ensure_chunk_unloaded(&mut self, coord: ChunkCoord) {
         self.renders.remove(&coord);
+        self.chunk_visibility.remove(&coord);
         self.gs.chunks.mark_missing(coord);
         self.gs.inflight_rev.remove(&coord);
         self.gs.finalize.remove(&coord);
@@ -59,6 +61,9 @@ impl App {
     }
 
     pub(super) fn handle_ensure_chunk_loaded(&mut self, coord: ChunkCoord) {
+        if !self.chunk_in_border(coord) {
+            return;
+        }
         if let Some(entry) = self.gs.chunks.get(&coord) {
             if entry.occupancy_or_empty().is_empty() {
                 self.mark_empty_chunk_ready(coord);
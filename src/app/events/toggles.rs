@@ -1,6 +1,10 @@
+use std::path::PathBuf;
+
 use super::App;
+use crate::app::Toast;
 use geist_blocks::Block;
-use raylib::prelude::Vector3;
+use geist_render_raylib::ShadowSettings;
+use raylib::prelude::{RaylibHandle, RaylibThread, Vector3};
 
 impl App {
     pub(super) fn handle_walk_mode_toggled(&mut self) {
@@ -18,6 +22,33 @@ impl App {
         }
     }
 
+    /// Detach the camera from the player, or snap it back to where the player is looking from.
+    pub(super) fn handle_spectator_toggled(&mut self) {
+        if !self.gs.spectator {
+            self.gs.spectator = true;
+            self.gs.spectator_return = Some((self.cam.position, self.cam.yaw, self.cam.pitch));
+            self.toast = Some(Toast::new(
+                format!(
+                    "Spectator on ({:.0} m/s, wheel to adjust, N to return)",
+                    self.gs.spectator_speed
+                ),
+                2.5,
+            ));
+            return;
+        }
+        self.gs.spectator = false;
+        if let Some((pos, yaw, pitch)) = self.gs.spectator_return.take() {
+            self.cam.yaw = yaw;
+            self.cam.pitch = pitch;
+            // The walker may have ridden a structure meanwhile; follow it rather than the old pose
+            self.cam.position = if self.gs.walk_mode {
+                self.gs.walker.eye_position()
+            } else {
+                pos
+            };
+        }
+    }
+
     pub(super) fn handle_grid_toggle(&mut self) {
         self.gs.show_grid = !self.gs.show_grid;
     }
@@ -43,6 +74,149 @@ impl App {
     }
 
     pub(super) fn handle_place_type_selected(&mut self, block: Block) {
+        self.hotbar.pick(block);
         self.gs.place_type = block;
     }
+
+    pub(super) fn handle_hotbar_slot_selected(&mut self, slot: usize) {
+        if let Some(block) = self.hotbar.select(slot) {
+            self.gs.place_type = block;
+        }
+    }
+
+    pub(super) fn handle_hotbar_scrolled(&mut self, delta: i32) {
+        if let Some(block) = self.hotbar.scroll(delta) {
+            self.gs.place_type = block;
+        }
+    }
+
+    pub(super) fn handle_ambiance_cycled(&mut self) {
+        let name = self.ambiance.cycle().name.clone();
+        self.apply_ambiance();
+        self.toast = Some(Toast::new(format!("Ambiance: {}", name), 2.5));
+    }
+
+    pub(super) fn handle_world_map_export_requested(&mut self) {
+        let path = PathBuf::from(format!("geist_map_{}.png", self.gs.tick));
+        let msg = match self.export_world_map(&path) {
+            Ok((w, h)) => {
+                log::info!("exported {}x{} world map to {:?}", w, h, path);
+                format!("Map saved: {}", path.display())
+            }
+            Err(e) => {
+                log::error!("world map export failed: {}", e);
+                format!("Map export failed: {}", e)
+            }
+        };
+        self.toast = Some(Toast::new(msg, 4.0));
+    }
+
+    pub(super) fn handle_lighting_compare_toggled(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+    ) {
+        let msg = if self.lighting_compare.is_some() {
+            self.stop_lighting_compare(rl, thread);
+            "Lighting compare off".to_string()
+        } else {
+            self.start_lighting_compare(rl, thread);
+            match self.lighting_compare.as_ref() {
+                Some(cmp) => format!("Lighting compare: {} (F7 to flip)", cmp.summary()),
+                None => "Lighting compare: no built chunks near the camera".to_string(),
+            }
+        };
+        self.toast = Some(Toast::new(msg, 4.0));
+    }
+
+    pub(super) fn handle_lighting_compare_flipped(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+    ) {
+        if self.lighting_compare.is_some() {
+            self.flip_lighting_compare(rl, thread);
+        }
+    }
+
+    pub(super) fn handle_hand_torch_toggled(&mut self) {
+        let msg = if self.toggle_hand_torch() {
+            "Hand torch on"
+        } else {
+            "Hand torch off"
+        };
+        self.toast = Some(Toast::new(msg.to_string(), 2.0));
+    }
+
+    pub(super) fn handle_render_scale_stepped(&mut self, delta: i32) {
+        self.scene_target.step_scale(delta);
+        self.toast = Some(Toast::new(
+            format!("Render scale {:.2}x", self.scene_target.scale()),
+            2.0,
+        ));
+    }
+
+    pub(super) fn handle_upscale_filter_cycled(&mut self) {
+        let filter = self.scene_target.filter().next();
+        self.scene_target.set_filter(filter);
+        let note = if self.scene_target.is_direct() {
+            " (applies below or above 1x)"
+        } else {
+            ""
+        };
+        self.toast = Some(Toast::new(
+            format!("Upscale filter: {}{}", filter.label(), note),
+            2.0,
+        ));
+    }
+
+    pub(super) fn handle_shadow_cascades_cycled(&mut self) {
+        self.adjust_shadows(|s| s.cycle_cascades());
+    }
+
+    pub(super) fn handle_shadow_resolution_cycled(&mut self) {
+        self.adjust_shadows(|s| s.cycle_resolution());
+    }
+
+    fn adjust_shadows(&mut self, change: impl FnOnce(&mut ShadowSettings)) {
+        let msg = match self.shadow_maps.as_mut() {
+            Some(shadows) => {
+                let mut settings = shadows.settings();
+                change(&mut settings);
+                shadows.set_settings(settings);
+                format!("Shadows: {}", settings.label())
+            }
+            None => "Shadows unavailable".to_string(),
+        };
+        self.toast = Some(Toast::new(msg, 2.0));
+    }
+
+    pub(super) fn handle_ui_scale_stepped(&mut self, delta: i32) {
+        self.accessibility.step_ui_scale(delta);
+        self.accessibility_changed();
+        self.toast = Some(Toast::new(
+            format!("UI scale {:.2}x", self.accessibility.ui_scale),
+            2.0,
+        ));
+    }
+
+    pub(super) fn handle_overlay_palette_cycled(&mut self) {
+        self.accessibility.palette = self.accessibility.palette.next();
+        self.accessibility_changed();
+        self.toast = Some(Toast::new(
+            format!("Overlay palette: {}", self.accessibility.palette.label()),
+            2.0,
+        ));
+    }
+
+    pub(super) fn handle_reduced_flashing_toggled(&mut self) {
+        self.accessibility.reduced_flashing = !self.accessibility.reduced_flashing;
+        self.accessibility_changed();
+        let msg = if self.accessibility.reduced_flashing {
+            "Reduced flashing on"
+        } else {
+            "Reduced flashing off"
+        };
+        self.toast = Some(Toast::new(msg.to_string(), 2.0));
+    }
 }
@@ -0,0 +1,205 @@
+//! Gravity blocks (sand, gravel): an edit that leaves one without anything solid below
+//! turns it into a falling block entity, which is placed again where it lands, in the world
+//! or on a structure. Blocks on a moving platform keep its velocity when they slide off.
+
+use geist_blocks::{Block, FaceRole};
+use geist_geom::Vec3;
+use geist_structures::{LandingSpot, StructureId};
+use raylib::prelude::*;
+
+use super::App;
+use super::map_export::material_color;
+use crate::event::Event;
+
+// Falling blocks below the world's bottom by this much are dropped.
+const FALL_FLOOR_MARGIN: f32 = 64.0;
+// Cells searched upward for room when a block lands where something solid already is.
+const LANDING_HEADROOM: i32 = 4;
+
+impl App {
+    /// Effective world block: edits, then loaded chunks, then the generator.
+    pub(super) fn world_block(&self, wx: i32, wy: i32, wz: i32) -> Block {
+        if let Some(b) = self.gs.edits.get(wx, wy, wz) {
+            return b;
+        }
+        if let Some(b) = self.gs.chunks.blocks().block_at(wx, wy, wz) {
+            return b;
+        }
+        self.gs.world.block_at_runtime(&self.reg, wx, wy, wz)
+    }
+
+    fn is_solid_block(&self, b: Block) -> bool {
+        self.reg
+            .get(b.id)
+            .map(|t| t.is_solid(b.state))
+            .unwrap_or(false)
+    }
+
+    fn is_gravity_block(&self, b: Block) -> bool {
+        self.reg.get(b.id).map(|t| t.falls()).unwrap_or(false)
+    }
+
+    /// Start the world block at a cell falling if it is a gravity block with nothing solid
+    /// below. Its removal re-checks the cell above, so a whole column follows.
+    pub(super) fn release_if_unsupported(&mut self, wx: i32, wy: i32, wz: i32) {
+        let b = self.world_block(wx, wy, wz);
+        if !self.is_gravity_block(b) || self.is_solid_block(self.world_block(wx, wy - 1, wz)) {
+            return;
+        }
+        let center = Vec3::new(wx as f32 + 0.5, wy as f32 + 0.5, wz as f32 + 0.5);
+        self.falling.spawn(b, center, Vec3::ZERO);
+        self.queue.emit_now(Event::BlockRemoved {
+            wx,
+            wy,
+            wz,
+            issued_at: None,
+        });
+    }
+
+    /// Structure counterpart of [`release_if_unsupported`](Self::release_if_unsupported):
+    /// the cell below in the structure, or the world under its bottom layer, must be solid.
+    pub(super) fn release_structure_if_unsupported(
+        &mut self,
+        id: StructureId,
+        lx: i32,
+        ly: i32,
+        lz: i32,
+    ) {
+        let Some(st) = self.gs.structures.get(&id) else {
+            return;
+        };
+        let Some(b) = st.block_local(lx, ly, lz) else {
+            return;
+        };
+        if !self.is_gravity_block(b) {
+            return;
+        }
+        let supported = match st.block_local(lx, ly - 1, lz) {
+            Some(below) => self.is_solid_block(below),
+            None => {
+                let (wx, wy, wz) = st.local_to_world_voxel(lx, ly, lz);
+                self.is_solid_block(self.world_block(wx, wy - 1, wz))
+            }
+        };
+        if supported {
+            return;
+        }
+        let center =
+            st.pose
+                .local_to_world(Vec3::new(lx as f32 + 0.5, ly as f32 + 0.5, lz as f32 + 0.5));
+        let vel = st.last_velocity;
+        self.falling.spawn(b, center, vel);
+        self.queue
+            .emit_now(Event::StructureBlockRemoved { id, lx, ly, lz });
+    }
+
+    /// Where a falling block rests if the point `p` under it is solid: the cell above, on a
+    /// structure when a structure holds `p` and has room, else in the world.
+    fn landing_spot(&self, p: Vec3) -> Option<LandingSpot> {
+        let sun_id = self.sun.as_ref().map(|s| s.id);
+        for (id, st) in &self.gs.structures {
+            if Some(*id) == sun_id {
+                continue;
+            }
+            let local = st.pose.world_to_local(p);
+            let (lx, ly, lz) = (
+                local.x.floor() as i32,
+                local.y.floor() as i32,
+                local.z.floor() as i32,
+            );
+            let Some(below) = st.block_local(lx, ly, lz) else {
+                continue;
+            };
+            if !self.is_solid_block(below) {
+                continue;
+            }
+            if st.block_local(lx, ly + 1, lz).is_some() {
+                return Some(LandingSpot::Structure {
+                    id: *id,
+                    lx,
+                    ly: ly + 1,
+                    lz,
+                });
+            }
+            let (wx, wy, wz) = st.local_to_world_voxel(lx, ly + 1, lz);
+            return Some(LandingSpot::World { wx, wy, wz });
+        }
+        let (wx, wy, wz) = (p.x.floor() as i32, p.y.floor() as i32, p.z.floor() as i32);
+        self.is_solid_block(self.world_block(wx, wy, wz))
+            .then_some(LandingSpot::World { wx, wy: wy + 1, wz })
+    }
+
+    /// Advance falling blocks and place the ones that landed.
+    pub(crate) fn step_falling_blocks(&mut self, dt: f32) {
+        if self.falling.is_empty() {
+            return;
+        }
+        let mut falling = std::mem::take(&mut self.falling);
+        let floor_y = -FALL_FLOOR_MARGIN;
+        let landed = falling.step(dt, floor_y, |p| self.landing_spot(p));
+        self.falling = falling;
+        for (block, spot) in landed {
+            match spot {
+                LandingSpot::World { wx, wy, wz } => {
+                    let Some(wy) = (wy..wy + LANDING_HEADROOM)
+                        .find(|&y| !self.is_solid_block(self.world_block(wx, y, wz)))
+                    else {
+                        continue;
+                    };
+                    self.queue.emit_now(Event::BlockPlaced {
+                        wx,
+                        wy,
+                        wz,
+                        block,
+                        issued_at: None,
+                    });
+                }
+                LandingSpot::Structure { id, lx, ly, lz } => {
+                    let Some(st) = self.gs.structures.get(&id) else {
+                        continue;
+                    };
+                    let Some(ly) = (ly..ly + LANDING_HEADROOM).find(|&y| {
+                        st.block_local(lx, y, lz)
+                            .is_some_and(|b| !self.is_solid_block(b))
+                    }) else {
+                        continue;
+                    };
+                    self.queue.emit_now(Event::StructureBlockPlaced {
+                        id,
+                        lx,
+                        ly,
+                        lz,
+                        block,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Draw falling blocks as cubes in the average color of their side texture.
+    pub(crate) fn draw_falling_blocks<D: RaylibDraw3D>(&mut self, d3: &mut D) {
+        if self.falling.is_empty() {
+            return;
+        }
+        let mut cubes = Vec::with_capacity(self.falling.len());
+        for fb in self.falling.iter() {
+            let id = fb.block.id;
+            let color = match self.falling_colors.get(&id) {
+                Some(c) => *c,
+                None => {
+                    let c = match self.reg.get(id) {
+                        Some(ty) => material_color(self, ty.material_for_cached(FaceRole::Side, 0)),
+                        None => Color::GRAY,
+                    };
+                    self.falling_colors.insert(id, c);
+                    c
+                }
+            };
+            cubes.push((Vector3::new(fb.pos.x, fb.pos.y, fb.pos.z), color));
+        }
+        for (center, color) in cubes {
+            d3.draw_cube(center, 1.0, 1.0, 1.0, color);
+            d3.draw_cube_wires(center, 1.0, 1.0, 1.0, Color::new(0, 0, 0, 90));
+        }
+    }
+}
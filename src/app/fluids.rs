@@ -0,0 +1,65 @@
+//! Flowing fluids: block edits wake the runtime's [`FluidSim`](geist_runtime::FluidSim)
+//! around them, and fixed-rate ticks write its changes back as untracked edits and remesh
+//! the chunks they touch.
+
+use std::collections::HashSet;
+
+use geist_runtime::FluidChange;
+
+use super::App;
+use crate::event::{Event, RebuildCause};
+
+// Seconds per fluid tick; flow advances one block per tick.
+const FLUID_TICK_SECS: f32 = 0.25;
+// Active cells evaluated per tick, shared between the active chunks.
+const FLUID_TICK_BUDGET: usize = 4096;
+// Ticks run in one frame to catch up after a stall; the rest of the backlog is dropped.
+const MAX_FLUID_TICKS_PER_FRAME: u32 = 2;
+
+impl App {
+    /// Run the fluid ticks due after `dt` seconds.
+    pub(crate) fn step_fluids(&mut self, dt: f32) {
+        if self.fluids.is_idle() {
+            self.fluid_tick_accum = 0.0;
+            return;
+        }
+        self.fluid_tick_accum += dt;
+        let mut ticks = 0;
+        while self.fluid_tick_accum >= FLUID_TICK_SECS && ticks < MAX_FLUID_TICKS_PER_FRAME {
+            self.fluid_tick_accum -= FLUID_TICK_SECS;
+            ticks += 1;
+            let (gs, reg) = (&self.gs, &self.reg);
+            let changes = self.fluids.tick(reg, FLUID_TICK_BUDGET, |wx, wy, wz| {
+                gs.edits
+                    .get(wx, wy, wz)
+                    .or_else(|| gs.chunks.blocks().block_at(wx, wy, wz))
+                    .unwrap_or_else(|| gs.world.block_at_runtime(reg, wx, wy, wz))
+            });
+            self.apply_fluid_changes(changes);
+        }
+        self.fluid_tick_accum = self.fluid_tick_accum.min(FLUID_TICK_SECS);
+    }
+
+    fn apply_fluid_changes(&mut self, changes: Vec<FluidChange>) {
+        let mut chunks = HashSet::new();
+        for FluidChange {
+            pos: (wx, wy, wz),
+            block,
+        } in changes
+        {
+            self.gs.edits.set_untracked(wx, wy, wz, block);
+            self.gs.edits.bump_region_around(wx, wy, wz);
+            chunks.extend(self.gs.edits.get_affected_chunks(wx, wy, wz));
+        }
+        for coord in chunks {
+            if self.gs.chunks.mesh_ready(coord) {
+                self.queue.emit_now(Event::ChunkRebuildRequested {
+                    cx: coord.cx,
+                    cy: coord.cy,
+                    cz: coord.cz,
+                    cause: RebuildCause::Edit,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use geist_blocks::Block;
+
+/// Slots on the hotbar, bound to keys 1-9.
+pub(crate) const HOTBAR_SLOTS: usize = 9;
+
+/// Blocks ready to place, one of them selected. The selected slot's block is what
+/// right-click places (`GameState::place_type`).
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Hotbar {
+    slots: [Option<Block>; HOTBAR_SLOTS],
+    selected: usize,
+}
+
+impl Hotbar {
+    /// Fill slots in order; blocks past the last slot are dropped.
+    pub(crate) fn from_blocks(blocks: impl IntoIterator<Item = Block>) -> Self {
+        let mut bar = Self::default();
+        for (slot, block) in bar.slots.iter_mut().zip(blocks) {
+            *slot = Some(block);
+        }
+        bar
+    }
+
+    pub(crate) fn slots(&self) -> &[Option<Block>; HOTBAR_SLOTS] {
+        &self.slots
+    }
+
+    pub(crate) fn selected_slot(&self) -> usize {
+        self.selected
+    }
+
+    pub(crate) fn selected(&self) -> Option<Block> {
+        self.slots[self.selected]
+    }
+
+    /// Select `slot` and return its block. Out-of-range slots are ignored.
+    pub(crate) fn select(&mut self, slot: usize) -> Option<Block> {
+        if slot < HOTBAR_SLOTS {
+            self.selected = slot;
+        }
+        self.selected()
+    }
+
+    /// Move the selection by `delta` slots, wrapping around, skipping empty slots when any
+    /// slot is filled.
+    pub(crate) fn scroll(&mut self, delta: i32) -> Option<Block> {
+        if delta == 0 {
+            return self.selected();
+        }
+        let step = delta.signum();
+        let mut remaining = delta.unsigned_abs();
+        let mut at = self.selected as i32;
+        let filled = self.slots.iter().any(Option::is_some);
+        for _ in 0..HOTBAR_SLOTS * remaining as usize {
+            at = (at + step).rem_euclid(HOTBAR_SLOTS as i32);
+            if !filled || self.slots[at as usize].is_some() {
+                remaining -= 1;
+                if remaining == 0 {
+                    break;
+                }
+            }
+        }
+        self.select(at as usize)
+    }
+
+    /// Make `block` the selection: select the slot already holding it, else put it in the
+    /// selected slot.
+    pub(crate) fn pick(&mut self, block: Block) {
+        if let Some(i) = self.slots.iter().position(|s| *s == Some(block)) {
+            self.selected = i;
+        } else {
+            self.slots[self.selected] = Some(block);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(id: u16) -> Block {
+        Block { id, state: 0 }
+    }
+
+    #[test]
+    fn scrolling_wraps_over_filled_slots_and_pick_reuses_slots() {
+        let mut bar = Hotbar::from_blocks([block(1), block(2), block(3)]);
+        assert_eq!(bar.selected(), Some(block(1)));
+        assert_eq!(bar.scroll(1), Some(block(2)));
+        assert_eq!(bar.scroll(2), Some(block(1)));
+        assert_eq!(bar.scroll(-1), Some(block(3)));
+        assert_eq!(bar.select(HOTBAR_SLOTS), Some(block(3)));
+
+        bar.pick(block(2));
+        assert_eq!(bar.selected_slot(), 1);
+        bar.pick(Block { id: 2, state: 4 });
+        assert_eq!(bar.selected_slot(), 1);
+        assert_eq!(bar.slots()[1], Some(Block { id: 2, state: 4 }));
+        assert_eq!(bar.slots().iter().flatten().count(), 3);
+
+        let mut empty = Hotbar::default();
+        assert_eq!(empty.scroll(-1), None);
+        assert_eq!(empty.selected_slot(), HOTBAR_SLOTS - 1);
+    }
+}
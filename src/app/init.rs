@@ -7,28 +7,58 @@ use raylib::core::texture::RaylibTexture2D;
 use raylib::prelude::*;
 use serde::Deserialize;
 
+use super::dimensions::DimensionState;
 use super::{
-    App, DayCycle, DebugOverlayTab, DebugStats, DiagnosticsTab, OverlayWindow,
-    OverlayWindowManager, SUN_STRUCTURE_ID, SchematicOrbit, SunBody, WindowId, WindowTheme,
+    AccessibilitySettings, Ambiance, App, DayCycle, DebugOverlayTab, DebugStats, DiagnosticsTab,
+    EditLatencyTracker, Hotbar, OverlayWindow, OverlayWindowManager, RebuildHistory,
+    SUN_STRUCTURE_ID, SchematicOrbit, SunBody, TabStripState, Toast, WindowId, WindowTheme,
     render::MINIMAP_MIN_CONTENT_SIDE,
 };
 use crate::event::{Event, EventQueue};
 use crate::gamestate::GameState;
+use geist_actors::Actors;
 use geist_blocks::{Block, BlockRegistry};
 use geist_edit::EditStore;
-use geist_geom::Vec3;
-use geist_lighting::LightingStore;
-use geist_render_raylib::{FogShader, LeavesShader, TextureCache, conv::vec3_from_rl};
-use geist_runtime::Runtime;
-use geist_structures::{Pose, Structure, StructureEditStore, StructureId};
+use geist_geom::{IVec3, Vec3};
+use geist_lighting::{DynamicLights, LightingStore};
+use geist_render_raylib::{
+    DecorationRenderer, FloatingOrigin, FogShader, LeavesShader, SceneTarget, ShadowMaps,
+    ShadowSettings, SharpenShader, SkyRenderer, TextureCache, UpscaleFilter, conv::vec3_from_rl,
+};
+use geist_runtime::{BlockTickScheduler, FluidSim, Runtime, builtin_block_ticks};
+use geist_structures::{FallingBlocks, Pose, Structure, StructureEditStore, StructureId};
+use geist_world::DimensionSet;
 use geist_world::voxel::generation::TOWER_OUTER_RADIUS;
 use geist_world::voxel::{World, WorldGenMode};
 
 #[derive(Deserialize)]
 struct HotbarConfig {
-    items: Vec<String>,
+    items: Vec<HotbarItem>,
 }
 
+/// A hotbar entry: a block name, or a name with state properties.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HotbarItem {
+    Name(String),
+    Block {
+        name: String,
+        #[serde(default)]
+        state: HashMap<String, String>,
+    },
+}
+
+// Slots filled when assets/voxels/hotbar.toml is missing or unreadable.
+const DEFAULT_HOTBAR: &[&str] = &[
+    "dirt",
+    "stone",
+    "sand",
+    "grass",
+    "snow",
+    "glowstone",
+    "beacon",
+];
+
 const MONO_FONT_BASE_SIZE: i32 = 96;
 
 const MONO_FONT_CANDIDATES: &[&str] = &[
@@ -59,29 +89,68 @@ impl App {
         assets_root: std::path::PathBuf,
         fixed_day_frac: Option<f32>,
     ) -> Self {
-        // Spawn: if flat world, start a few blocks above the slab; else near world top
+        let dimensions = DimensionState::new(DimensionSet::with_builtin(world.clone()));
+        // Spawn over the world center: if flat world, a few blocks above the slab; else
+        // near world top
+        let (center_x, center_z) = world.center();
         let spawn = if world.is_flat() {
-            Vector3::new(
-                (world.world_size_x() as f32) * 0.5,
-                6.0,
-                (world.world_size_z() as f32) * 0.5,
-            )
+            Vector3::new(center_x as f32, 6.0, center_z as f32)
         } else {
             Vector3::new(
-                (world.world_size_x() as f32) * 0.5,
+                center_x as f32,
                 (world.world_height_hint() as f32) * 0.8,
-                (world.world_size_z() as f32) * 0.5,
+                center_z as f32,
             )
         };
         let cam = crate::camera::FlyCamera::new(spawn + Vector3::new(0.0, 5.0, 20.0));
 
         // Renderer-side resources and file watchers (moved from Runtime in Phase 5)
-        let leaves_shader = LeavesShader::load_with_base(rl, thread, &assets_root)
+        let mut leaves_shader = LeavesShader::load_with_base(rl, thread, &assets_root)
             .or_else(|| LeavesShader::load(rl, thread));
-        let fog_shader = FogShader::load_with_base(rl, thread, &assets_root)
+        let mut fog_shader = FogShader::load_with_base(rl, thread, &assets_root)
             .or_else(|| FogShader::load(rl, thread));
-        let water_shader =
+        let mut water_shader =
             geist_render_raylib::WaterShader::load_with_base(rl, thread, &assets_root);
+        // Any failed shader drops the whole set so chunks render consistently through
+        // the default material with CPU-baked light instead of mixing pipelines.
+        let shader_compat =
+            leaves_shader.is_none() || fog_shader.is_none() || water_shader.is_none();
+        let toast = if shader_compat {
+            log::warn!(
+                "custom shaders failed to compile/link (leaves={} fog={} water={}); using GL compatibility rendering",
+                leaves_shader.is_some(),
+                fog_shader.is_some(),
+                water_shader.is_some()
+            );
+            leaves_shader = None;
+            fog_shader = None;
+            water_shader = None;
+            Some(Toast::new(
+                "Shaders unavailable: compatibility rendering (no fog, static lighting)",
+                10.0,
+            ))
+        } else {
+            None
+        };
+        // Only used when upscaling; without it the sharpen filter falls back to bilinear.
+        let sharpen_shader = SharpenShader::load_with_base(rl, thread, &assets_root);
+        // Compatibility rendering keeps the billboards but clears to a flat sky.
+        let sky_renderer = SkyRenderer::load_with_base(rl, thread, &assets_root, !shader_compat);
+        let decoration_renderer = DecorationRenderer::load_with_base(rl, thread, &assets_root);
+        if decoration_renderer.is_none() {
+            log::warn!("instancing shader failed to compile/link; decorations will not be drawn");
+        }
+        // Shadows are sampled by the voxel shaders, so compatibility rendering goes without.
+        let shadow_maps = if fog_shader.is_some() {
+            let maps =
+                ShadowMaps::load_with_base(rl, thread, &assets_root, ShadowSettings::default());
+            if maps.is_none() {
+                log::warn!("shadow depth pass unavailable; the sun casts no shadows");
+            }
+            maps
+        } else {
+            None
+        };
         let tex_cache = TextureCache::new();
         // File watcher for textures under assets/blocks
         let (tex_tx, tex_rx) = std::sync::mpsc::channel::<String>();
@@ -153,7 +222,13 @@ impl App {
         let ui_font = Self::load_system_mono_font(rl, thread).map(std::sync::Arc::new);
 
         let runtime = Runtime::new(world.clone(), lighting.clone());
-        let mut gs = GameState::new(world.clone(), edits, lighting.clone(), cam.position);
+        let mut gs = GameState::new(
+            world.clone(),
+            edits,
+            lighting.clone(),
+            runtime.chunk_map(),
+            cam.position,
+        );
         let mut queue = EventQueue::new();
         let hotbar = Self::load_hotbar(&reg, &assets_root);
         let mut schem_orbits = Vec::new();
@@ -198,7 +273,7 @@ impl App {
                                 };
                                 let margin: i32 = 4;
                                 let row_width_limit: i32 =
-                                    (world.world_size_x() as i32).max(64) - margin;
+                                    ((world.chunks_x * world.chunk_size_x) as i32).max(64) - margin;
                                 let mut placements: Vec<(
                                     std::path::PathBuf,
                                     (i32, i32, i32),
@@ -239,8 +314,7 @@ impl App {
                                 }
                                 let layout_cx = (min_x + max_x) / 2;
                                 let layout_cz = (min_z + max_z) / 2;
-                                let world_cx = (world.world_size_x() as i32) / 2;
-                                let world_cz = (world.world_size_z() as i32) / 2;
+                                let (world_cx, world_cz) = world.center();
                                 let shift_x = world_cx - layout_cx;
                                 let shift_z = world_cz - layout_cz;
                                 for (p, (lx, ly, lz), (_sx, _sz)) in placements {
@@ -286,8 +360,8 @@ impl App {
                                     (TOWER_OUTER_RADIUS as f32 + 48.0).max(max_span * 0.75 + 32.0);
                                 let total = list.len() as f32;
                                 let mut next_structure_id: StructureId = SCHEM_STRUCTURE_ID_BASE;
-                                let center_x = (world.world_size_x() as f32) * 0.5;
-                                let center_z = (world.world_size_z() as f32) * 0.5;
+                                let (center_x, center_z) = world.center();
+                                let (center_x, center_z) = (center_x as f32, center_z as f32);
                                 let platform_block = reg
                                     .id_by_name("stone_bricks")
                                     .or_else(|| reg.id_by_name("stone"))
@@ -345,14 +419,14 @@ impl App {
                                     };
                                     let target_center_x = center_x + radius * angle.cos();
                                     let target_center_z = center_z + radius * angle.sin();
-                                    let pose = Pose {
-                                        pos: Vec3::new(
+                                    let pose = Pose::from_yaw(
+                                        Vec3::new(
                                             target_center_x - struct_sx as f32 * 0.5,
                                             orbit_height,
                                             target_center_z - struct_sz as f32 * 0.5,
                                         ),
-                                        yaw_deg: 0.0,
-                                    };
+                                        0.0,
+                                    );
 
                                     let id = next_structure_id;
                                     next_structure_id = next_structure_id.wrapping_add(1);
@@ -419,6 +493,27 @@ impl App {
             }
         }
 
+        let schematic_library = {
+            let dir = crate::assets::schematics_dir(&assets_root);
+            match geist_io::SchematicLibrary::open(&dir, &geist_io::PaletteMap::load_default()) {
+                Ok((lib, scan)) => {
+                    log::info!(
+                        "Schematic library {:?}: {} entries ({} cached, {} parsed, {} failed)",
+                        dir,
+                        lib.entries().len(),
+                        scan.reused,
+                        scan.parsed,
+                        scan.failed
+                    );
+                    Some(lib)
+                }
+                Err(e) => {
+                    log::warn!("Schematic library unavailable: {}", e);
+                    None
+                }
+            }
+        };
+
         let window_theme = WindowTheme::default();
         let mut overlay_windows = OverlayWindowManager::new(window_theme);
         overlay_windows.insert(OverlayWindow::new(
@@ -439,6 +534,18 @@ impl App {
             (520, 360),
             (360, 240),
         ));
+        overlay_windows.insert(OverlayWindow::new(
+            WindowId::SchematicLibrary,
+            Vector2::new(1380.0, 40.0),
+            (460, 420),
+            (340, 220),
+        ));
+        overlay_windows.insert(OverlayWindow::new(
+            WindowId::Settings,
+            Vector2::new(1380.0, 480.0),
+            (420, 200),
+            (340, 160),
+        ));
         let minimap_side =
             App::minimap_side_px(gs.view_radius_chunks).max(MINIMAP_MIN_CONTENT_SIDE);
         let minimap_size = (
@@ -457,19 +564,28 @@ impl App {
         ));
         overlay_windows.clamp_all((rl.get_screen_width(), rl.get_screen_height()));
 
+        // Schematic edits are part of the starting world, not something to undo
+        gs.edits.clear_history();
+
         // Bootstrap initial streaming based on camera (after edits are applied)
         let ccx = (cam.position.x / world.chunk_size_x as f32).floor() as i32;
         let ccy = (cam.position.y / world.chunk_size_y as f32).floor() as i32;
         let ccz = (cam.position.z / world.chunk_size_z as f32).floor() as i32;
         queue.emit_now(Event::ViewCenterChanged { ccx, ccy, ccz });
         // Do not spawn a default platform in non-flat: schematics drive platform creation now.
-        // Default place_type: stone
-        if let Some(id) = reg.id_by_name("stone") {
-            gs.place_type = Block { id, state: 0 };
+        // Place the first hotbar block, else stone.
+        if let Some(block) = hotbar
+            .selected()
+            .or_else(|| reg.make_block_by_name("stone", None))
+        {
+            gs.place_type = block;
         }
 
+        let ambiance = Self::load_ambiance(&assets_root);
         let mut day_cycle = DayCycle::new(60.0);
         day_cycle.set_fixed_frac(fixed_day_frac);
+        day_cycle.set_curve(ambiance.current().sky_curve());
+        day_cycle.set_night_ambient(gs.lighting.night_ambient());
         let day_sample = day_cycle.sample();
         let mut sun = None;
         if let Some((body, structure)) = SunBody::new(
@@ -484,6 +600,17 @@ impl App {
             sun = Some(body);
         }
 
+        let fluids = FluidSim::new(
+            gs.world.chunk_size_x as i32,
+            gs.world.chunk_size_y as i32,
+            gs.world.chunk_size_z as i32,
+        );
+        let actors = Actors::new(IVec3::from_dims(
+            gs.world.chunk_size_x,
+            gs.world.chunk_size_y,
+            gs.world.chunk_size_z,
+        ));
+        let block_tick_handlers = Arc::new(builtin_block_ticks(reg.clone()));
         Self {
             gs,
             queue,
@@ -498,11 +625,43 @@ impl App {
             leaves_shader,
             fog_shader,
             water_shader,
+            shader_compat,
+            toast,
             tex_cache,
+            block_textures: None,
+            wide_indices: None,
+            dynamic_lights: DynamicLights::new(),
+            dynamic_light_tex: None,
+            falling: FallingBlocks::new(),
+            falling_colors: HashMap::new(),
+            actors,
+            fluids,
+            fluid_tick_accum: 0.0,
+            block_tick_handlers,
+            block_ticks: BlockTickScheduler::new(),
+            block_tick: 0,
+            block_tick_accum: 0.0,
+            block_tick_inflight: None,
+            world_border: None,
+            hand_torch: None,
+            schematic_library,
+            schematic_selected: 0,
+            render_origin: FloatingOrigin::default(),
+            scene_target: SceneTarget::new(1.0, UpscaleFilter::default()),
+            sharpen_shader,
+            sky_renderer,
+            decoration_renderer,
+            shadow_maps,
+            msaa: true,
+            accessibility: AccessibilitySettings::default(),
+            settings_path: None,
             renders: HashMap::new(),
+            chunk_visibility: HashMap::new(),
             structure_renders: HashMap::new(),
+            structure_part_sections: HashMap::new(),
             structure_lights: HashMap::new(),
             structure_light_borders: HashMap::new(),
+            structure_emitters: HashMap::new(),
             ui_font,
             minimap_rt: None,
             minimap_zoom: 1.0,
@@ -517,16 +676,24 @@ impl App {
             overlay_hover: None,
             overlay_debug_tab: DebugOverlayTab::default(),
             overlay_diagnostics_tab: DiagnosticsTab::default(),
+            overlay_debug_tab_strip: TabStripState::default(),
+            overlay_diagnostics_tab_strip: TabStripState::default(),
             reg: reg.clone(),
             evt_processed_total: 0,
             evt_processed_by: HashMap::new(),
             intents: HashMap::new(),
+            batch_chunks: HashMap::new(),
+            gen_divergences: 0,
+            lighting_compare: None,
+            stamp_batch: None,
             perf_remove_start: HashMap::new(),
             perf_mesh_ms: std::collections::VecDeque::new(),
             perf_light_ms: std::collections::VecDeque::new(),
             perf_total_ms: std::collections::VecDeque::new(),
             perf_remove_ms: std::collections::VecDeque::new(),
             perf_gen_ms: std::collections::VecDeque::new(),
+            edit_latency: EditLatencyTracker::default(),
+            rebuild_history: RebuildHistory::default(),
             terrain_stage_us: std::array::from_fn(|_| std::collections::VecDeque::new()),
             terrain_stage_calls: std::array::from_fn(|_| std::collections::VecDeque::new()),
             terrain_height_tile_us: std::collections::VecDeque::new(),
@@ -604,7 +771,39 @@ impl App {
                 });
                 srx
             },
+            ambiance,
+            ambiance_event_rx: {
+                let (atx, arx) = std::sync::mpsc::channel::<()>();
+                let path = crate::assets::ambiance_path(&assets_root);
+                std::thread::spawn(move || {
+                    use notify::{EventKind, RecursiveMode, Watcher};
+                    if let Ok(mut watcher) = notify::recommended_watcher(
+                        move |res: Result<notify::Event, notify::Error>| {
+                            if let Ok(event) = res {
+                                match event.kind {
+                                    EventKind::Modify(_)
+                                    | EventKind::Create(_)
+                                    | EventKind::Remove(_)
+                                    | EventKind::Any => {
+                                        let _ = atx.send(());
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        },
+                    ) {
+                        let _ = watcher.watch(path.as_path(), RecursiveMode::NonRecursive);
+                        loop {
+                            std::thread::sleep(std::time::Duration::from_secs(3600));
+                        }
+                    }
+                });
+                arx
+            },
             last_frame_dt: 0.0,
+            save_dir: None,
+            dimensions,
+            autosave_timer: 0.0,
         }
     }
 
@@ -644,26 +843,56 @@ impl App {
         None
     }
 
-    fn load_hotbar(reg: &BlockRegistry, assets_root: &std::path::Path) -> Vec<Block> {
+    fn load_ambiance(assets_root: &std::path::Path) -> Ambiance {
+        let path = crate::assets::ambiance_path(assets_root);
+        if !path.exists() {
+            return Ambiance::default();
+        }
+        match Ambiance::load(&path) {
+            Ok(ambiance) => {
+                log::info!("Ambiance preset '{}' active", ambiance.current().name);
+                ambiance
+            }
+            Err(e) => {
+                log::warn!("ambiance.toml load error: {}", e);
+                Ambiance::default()
+            }
+        }
+    }
+
+    fn load_hotbar(reg: &BlockRegistry, assets_root: &std::path::Path) -> Hotbar {
         let path = crate::assets::hotbar_path(assets_root);
+        let defaults = || {
+            Hotbar::from_blocks(
+                DEFAULT_HOTBAR
+                    .iter()
+                    .filter_map(|name| reg.make_block_by_name(name, None)),
+            )
+        };
         if !path.exists() {
-            return Vec::new();
+            return defaults();
         }
         match std::fs::read_to_string(&path) {
             Ok(s) => match toml::from_str::<HotbarConfig>(&s) {
-                Ok(cfg) => cfg
-                    .items
-                    .into_iter()
-                    .filter_map(|name| reg.id_by_name(&name).map(|id| Block { id, state: 0 }))
-                    .collect(),
+                Ok(cfg) => Hotbar::from_blocks(cfg.items.into_iter().filter_map(|item| {
+                    let (name, state) = match item {
+                        HotbarItem::Name(name) => (name, HashMap::new()),
+                        HotbarItem::Block { name, state } => (name, state),
+                    };
+                    let block = reg.make_block_by_name(&name, Some(&state));
+                    if block.is_none() {
+                        log::warn!("hotbar.toml: unknown block '{}'", name);
+                    }
+                    block
+                })),
                 Err(e) => {
                     log::warn!("hotbar.toml parse error: {}", e);
-                    Vec::new()
+                    defaults()
                 }
             },
             Err(e) => {
                 log::warn!("hotbar.toml read error: {}", e);
-                Vec::new()
+                defaults()
             }
         }
     }
@@ -0,0 +1,213 @@
+//! Debug A/B comparison of lighting qualities on the chunks around the camera.
+//!
+//! F6 lights every built chunk within `LIGHTING_COMPARE_RADIUS` twice, once per
+//! `LightQuality`, on the main thread so both timings come from the same conditions. F7
+//! flips which result the chunks display; faces whose sampled light differs by more than
+//! `LIGHTING_COMPARE_THRESHOLD` are outlined in either view.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use geist_geom::ChunkPos;
+use geist_lighting::{
+    FaceLightDiff, LightAtlas, LightQuality, compute_light_with_quality, face_light_diffs,
+    pack_light_grid_atlas_with_neighbors,
+};
+use geist_render_raylib::update_chunk_light_texture;
+use geist_world::ChunkCoord;
+use raylib::prelude::*;
+
+use super::App;
+
+pub(crate) const LIGHTING_COMPARE_RADIUS: i32 = 1;
+/// Light difference (0..255) above which a face is outlined; about one and a half levels.
+pub(crate) const LIGHTING_COMPARE_THRESHOLD: u8 = 24;
+// Keeps a badly diverging view from drawing a cube per face of every chunk.
+const MAX_DRAWN_DIFFS: usize = 20_000;
+
+pub(crate) struct LightingCompareChunk {
+    /// Revision both grids were computed for; a rebuild replaces the chunk's texture.
+    rev: u64,
+    full: LightAtlas,
+    coarse: LightAtlas,
+    full_ms: f32,
+    coarse_ms: f32,
+    /// `a` is the full sample, `b` the coarse one.
+    diffs: Vec<FaceLightDiff>,
+}
+
+pub(crate) struct LightingCompare {
+    pub(crate) showing: LightQuality,
+    chunks: HashMap<ChunkCoord, LightingCompareChunk>,
+}
+
+impl LightingCompare {
+    pub(crate) fn summary(&self) -> String {
+        let n = self.chunks.len().max(1) as f32;
+        let full_ms: f32 = self.chunks.values().map(|c| c.full_ms).sum();
+        let coarse_ms: f32 = self.chunks.values().map(|c| c.coarse_ms).sum();
+        let diffs: usize = self.chunks.values().map(|c| c.diffs.len()).sum();
+        format!(
+            "showing {:?} | {} chunks | full {:.1} ms/chunk, coarse {:.1} ms/chunk | {} faces differ by >{}",
+            self.showing,
+            self.chunks.len(),
+            full_ms / n,
+            coarse_ms / n,
+            diffs,
+            LIGHTING_COMPARE_THRESHOLD
+        )
+    }
+}
+
+impl App {
+    pub(crate) fn start_lighting_compare(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
+        let world = self.gs.world.clone();
+        let p = self.cam.position;
+        let center = world.chunk_of(p.x.floor() as i32, p.y.floor() as i32, p.z.floor() as i32);
+        let r = LIGHTING_COMPARE_RADIUS;
+        let mut chunks = HashMap::new();
+        for dy in -r..=r {
+            for dz in -r..=r {
+                for dx in -r..=r {
+                    let coord = center.offset(dx, dy, dz);
+                    if !self.renders.contains_key(&coord) {
+                        continue;
+                    }
+                    let Some((buf, rev)) = self
+                        .gs
+                        .chunks
+                        .get(&coord)
+                        .and_then(|e| e.buf.as_ref().map(|b| (b, e.built_rev)))
+                    else {
+                        continue;
+                    };
+                    let t0 = Instant::now();
+                    let full = compute_light_with_quality(
+                        buf,
+                        &self.gs.lighting,
+                        &self.reg,
+                        &world,
+                        LightQuality::Full,
+                    );
+                    let full_ms = t0.elapsed().as_secs_f32() * 1000.0;
+                    let t0 = Instant::now();
+                    let coarse = compute_light_with_quality(
+                        buf,
+                        &self.gs.lighting,
+                        &self.reg,
+                        &world,
+                        LightQuality::Coarse,
+                    );
+                    let coarse_ms = t0.elapsed().as_secs_f32() * 1000.0;
+                    let diffs = face_light_diffs(
+                        buf,
+                        &self.reg,
+                        &full,
+                        &coarse,
+                        LIGHTING_COMPARE_THRESHOLD,
+                    );
+                    let nb = self.gs.lighting.get_neighbor_borders(coord);
+                    chunks.insert(
+                        coord,
+                        LightingCompareChunk {
+                            rev,
+                            full: pack_light_grid_atlas_with_neighbors(&full, &nb),
+                            coarse: pack_light_grid_atlas_with_neighbors(&coarse, &nb),
+                            full_ms,
+                            coarse_ms,
+                            diffs,
+                        },
+                    );
+                }
+            }
+        }
+        if chunks.is_empty() {
+            self.lighting_compare = None;
+            return;
+        }
+        let cmp = LightingCompare {
+            showing: LightQuality::Full,
+            chunks,
+        };
+        log::info!("lighting compare: {}", cmp.summary());
+        self.lighting_compare = Some(cmp);
+        self.show_lighting_compare(rl, thread);
+    }
+
+    /// Put the full-quality light back on chunks that have not been rebuilt since.
+    pub(crate) fn stop_lighting_compare(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
+        if let Some(cmp) = self.lighting_compare.as_mut()
+            && cmp.showing != LightQuality::Full
+        {
+            cmp.showing = LightQuality::Full;
+            self.show_lighting_compare(rl, thread);
+        }
+        self.lighting_compare = None;
+    }
+
+    pub(crate) fn flip_lighting_compare(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
+        let Some(cmp) = self.lighting_compare.as_mut() else {
+            return;
+        };
+        cmp.showing = match cmp.showing {
+            LightQuality::Full => LightQuality::Coarse,
+            LightQuality::Coarse => LightQuality::Full,
+        };
+        self.show_lighting_compare(rl, thread);
+    }
+
+    fn show_lighting_compare(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
+        let Some(cmp) = self.lighting_compare.as_ref() else {
+            return;
+        };
+        for (coord, c) in &cmp.chunks {
+            let current = self.gs.chunks.get(coord).map(|e| e.built_rev);
+            if current != Some(c.rev) {
+                continue;
+            }
+            let atlas = match cmp.showing {
+                LightQuality::Full => &c.full,
+                LightQuality::Coarse => &c.coarse,
+            };
+            if let Some(cr) = self.renders.get_mut(coord) {
+                update_chunk_light_texture(rl, thread, cr, atlas);
+            }
+        }
+    }
+
+    /// Outline faces whose light differs: warm where coarse is brighter, cool where darker.
+    pub(crate) fn draw_lighting_compare(&self, d3: &mut impl RaylibDraw3D) {
+        let Some(cmp) = self.lighting_compare.as_ref() else {
+            return;
+        };
+        let dims = self.gs.world.chunk_dims();
+        let palette = self.accessibility.palette;
+        const NORMALS: [(f32, f32, f32); 6] = [
+            (0.0, 1.0, 0.0),
+            (0.0, -1.0, 0.0),
+            (1.0, 0.0, 0.0),
+            (-1.0, 0.0, 0.0),
+            (0.0, 0.0, 1.0),
+            (0.0, 0.0, -1.0),
+        ];
+        let mut drawn = 0usize;
+        for (coord, c) in &cmp.chunks {
+            let base = ChunkPos::from(*coord).origin(dims).0;
+            for d in &c.diffs {
+                if drawn >= MAX_DRAWN_DIFFS {
+                    return;
+                }
+                let (nx, ny, nz) = NORMALS[d.face];
+                let center = Vector3::new(
+                    (base.x + d.x as i32) as f32 + 0.5 + nx * 0.51,
+                    (base.y + d.y as i32) as f32 + 0.5 + ny * 0.51,
+                    (base.z + d.z as i32) as f32 + 0.5 + nz * 0.51,
+                );
+                let size = |n: f32| if n != 0.0 { 0.02 } else { 0.9 };
+                let col = palette.diff(d.b > d.a, 170);
+                d3.draw_cube(center, size(nx), size(ny), size(nz), col);
+                drawn += 1;
+            }
+        }
+    }
+}
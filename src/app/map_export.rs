@@ -0,0 +1,383 @@
+//! Top-down PNG snapshot of the loaded world, for bug reports and documenting test scenes.
+//!
+//! Layers, bottom to top: surface colors shaded by height, edited columns, the chunk grid,
+//! structure footprints and light emitters.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use geist_blocks::types::{Block, FaceRole, MaterialId};
+use geist_geom::Vec3;
+use raylib::prelude::{Color, Image};
+
+use super::App;
+
+/// Pixels per voxel edge in exported maps.
+pub(crate) const MAP_PX_PER_VOXEL: i32 = 2;
+
+const UNLOADED: Color = Color::new(24, 24, 28, 255);
+const EDIT_TINT: Color = Color::new(255, 40, 200, 255);
+const GRID: Color = Color::new(0, 0, 0, 255);
+const FOOTPRINT: Color = Color::new(255, 140, 20, 255);
+const EMITTER: Color = Color::new(255, 230, 60, 255);
+const BEACON: Color = Color::new(60, 220, 255, 255);
+
+/// What a world map shows, in world voxel coordinates on the X/Z plane.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MapScene {
+    /// Voxel columns covered: `min` inclusive, `max` exclusive.
+    pub(crate) min: (i32, i32),
+    pub(crate) max: (i32, i32),
+    /// Chunk edge lengths along X and Z, for the grid.
+    pub(crate) chunk_size: (i32, i32),
+    /// Topmost non-air block of each loaded column: (wx, wz, wy, color).
+    pub(crate) surface: Vec<(i32, i32, i32, Color)>,
+    /// Columns holding at least one edit.
+    pub(crate) edits: Vec<(i32, i32)>,
+    /// Light emitters: (wx, wz, is_beacon).
+    pub(crate) lights: Vec<(i32, i32, bool)>,
+    /// Structure base outlines as four world (x, z) corners.
+    pub(crate) footprints: Vec<[(f32, f32); 4]>,
+}
+
+/// RGBA pixels of a rendered map, row-major from the -Z edge.
+pub(crate) struct MapImage {
+    pub(crate) width: i32,
+    pub(crate) height: i32,
+    pub(crate) pixels: Vec<Color>,
+}
+
+impl MapImage {
+    fn new(width: i32, height: i32, fill: Color) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![fill; (width.max(0) * height.max(0)) as usize],
+        }
+    }
+
+    #[cfg(test)]
+    fn get(&self, x: i32, y: i32) -> Option<Color> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.pixels[(y * self.width + x) as usize])
+    }
+
+    /// Mix `c` over the pixel at (x, y) with weight `alpha` in [0,1]; off-image is ignored.
+    fn blend(&mut self, x: i32, y: i32, c: Color, alpha: f32) {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return;
+        }
+        let p = &mut self.pixels[(y * self.width + x) as usize];
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * alpha).round() as u8;
+        *p = Color::new(mix(p.r, c.r), mix(p.g, c.g), mix(p.b, c.b), 255);
+    }
+
+    fn line(&mut self, (x0, y0): (i32, i32), (x1, y1): (i32, i32), c: Color) {
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (sx, sy) = (if x0 < x1 { 1 } else { -1 }, if y0 < y1 { 1 } else { -1 });
+        let (mut x, mut y, mut err) = (x0, y0, dx + dy);
+        loop {
+            self.blend(x, y, c, 1.0);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    /// Write the map as a PNG through raylib's CPU image export.
+    pub(crate) fn write_png(&self, path: &Path) -> Result<(), String> {
+        let mut img = Image::gen_image_color(self.width, self.height, UNLOADED);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                img.draw_pixel(x, y, self.pixels[(y * self.width + x) as usize]);
+            }
+        }
+        let _ = std::fs::remove_file(path);
+        img.export_image(&path.to_string_lossy());
+        if path.exists() {
+            Ok(())
+        } else {
+            Err(format!("could not write {}", path.display()))
+        }
+    }
+}
+
+/// Rasterize `scene` at `MAP_PX_PER_VOXEL` pixels per voxel.
+pub(crate) fn render_map(scene: &MapScene) -> MapImage {
+    let s = MAP_PX_PER_VOXEL;
+    let (w, h) = (scene.max.0 - scene.min.0, scene.max.1 - scene.min.1);
+    let mut img = MapImage::new(w.max(0) * s, h.max(0) * s, UNLOADED);
+    let to_px = |wx: f32, wz: f32| {
+        (
+            ((wx - scene.min.0 as f32) * s as f32).floor() as i32,
+            ((wz - scene.min.1 as f32) * s as f32).floor() as i32,
+        )
+    };
+    let fill_cell = |img: &mut MapImage, wx: i32, wz: i32, c: Color, alpha: f32| {
+        let (px, py) = ((wx - scene.min.0) * s, (wz - scene.min.1) * s);
+        for dy in 0..s {
+            for dx in 0..s {
+                img.blend(px + dx, py + dy, c, alpha);
+            }
+        }
+    };
+
+    // Surface, brighter with height across the loaded range.
+    let (lo, hi) = scene
+        .surface
+        .iter()
+        .fold((i32::MAX, i32::MIN), |(lo, hi), s| {
+            (lo.min(s.2), hi.max(s.2))
+        });
+    let span = (hi - lo).max(1) as f32;
+    for &(wx, wz, wy, c) in &scene.surface {
+        let k = 0.6 + 0.5 * (wy - lo) as f32 / span;
+        let shade = |v: u8| (v as f32 * k).clamp(0.0, 255.0) as u8;
+        fill_cell(
+            &mut img,
+            wx,
+            wz,
+            Color::new(shade(c.r), shade(c.g), shade(c.b), 255),
+            1.0,
+        );
+    }
+
+    for &(wx, wz) in &scene.edits {
+        fill_cell(&mut img, wx, wz, EDIT_TINT, 0.65);
+    }
+
+    // Chunk grid on every chunk boundary inside the map.
+    let (csx, csz) = (scene.chunk_size.0.max(1), scene.chunk_size.1.max(1));
+    for wx in scene.min.0..scene.max.0 {
+        if wx.rem_euclid(csx) == 0 {
+            let px = (wx - scene.min.0) * s;
+            for py in 0..img.height {
+                img.blend(px, py, GRID, 0.45);
+            }
+        }
+    }
+    for wz in scene.min.1..scene.max.1 {
+        if wz.rem_euclid(csz) == 0 {
+            let py = (wz - scene.min.1) * s;
+            for px in 0..img.width {
+                img.blend(px, py, GRID, 0.45);
+            }
+        }
+    }
+
+    for corners in &scene.footprints {
+        for i in 0..4 {
+            let (a, b) = (corners[i], corners[(i + 1) % 4]);
+            img.line(to_px(a.0, a.1), to_px(b.0, b.1), FOOTPRINT);
+        }
+    }
+
+    // Emitters as small discs centred on their voxel.
+    let r = s + 1;
+    for &(wx, wz, beacon) in &scene.lights {
+        let (cx, cy) = to_px(wx as f32 + 0.5, wz as f32 + 0.5);
+        let c = if beacon { BEACON } else { EMITTER };
+        for dy in -r..=r {
+            for dx in -r..=r {
+                if dx * dx + dy * dy <= r * r {
+                    img.blend(cx + dx, cy + dy, c, 1.0);
+                }
+            }
+        }
+    }
+    img
+}
+
+/// Average opaque color of a material's texture; grey when it cannot be loaded.
+pub(super) fn material_color(app: &App, mid: MaterialId) -> Color {
+    let fallback = Color::new(128, 128, 128, 255);
+    let Some(path) = geist_render_raylib::material_texture_path(&app.reg.materials, mid) else {
+        return fallback;
+    };
+    let Ok(img) = Image::load_image(&path) else {
+        return fallback;
+    };
+    let (mut sum, mut n) = ([0u64; 3], 0u64);
+    for c in img.get_image_data().iter().filter(|c| c.a > 0) {
+        sum[0] += c.r as u64;
+        sum[1] += c.g as u64;
+        sum[2] += c.b as u64;
+        n += 1;
+    }
+    if n == 0 {
+        return fallback;
+    }
+    Color::new(
+        (sum[0] / n) as u8,
+        (sum[1] / n) as u8,
+        (sum[2] / n) as u8,
+        255,
+    )
+}
+
+impl App {
+    /// Collect the map layers for every loaded chunk column.
+    pub(crate) fn world_map_scene(&self) -> Option<MapScene> {
+        let mut columns: HashMap<(i32, i32), Vec<(i32, &geist_chunk::ChunkBuf)>> = HashMap::new();
+        for (coord, entry) in self.gs.chunks.iter() {
+            if let (true, Some(buf)) = (entry.is_ready(), entry.buf.as_ref()) {
+                columns
+                    .entry((coord.cx, coord.cz))
+                    .or_default()
+                    .push((coord.cy, buf));
+            }
+        }
+        let (sx, sz) = {
+            let buf = columns.values().next()?.first()?.1;
+            (buf.sx as i32, buf.sz as i32)
+        };
+        let (mut min, mut max) = ((i32::MAX, i32::MAX), (i32::MIN, i32::MIN));
+        for &(cx, cz) in columns.keys() {
+            min = (min.0.min(cx * sx), min.1.min(cz * sz));
+            max = (max.0.max((cx + 1) * sx), max.1.max((cz + 1) * sz));
+        }
+        let mut scene = MapScene {
+            min,
+            max,
+            chunk_size: (sx, sz),
+            ..Default::default()
+        };
+
+        let mut colors: HashMap<(u16, u16), Color> = HashMap::new();
+        for (&(cx, cz), stack) in columns.iter_mut() {
+            stack.sort_by_key(|(cy, _)| -cy);
+            for lz in 0..sz as usize {
+                for lx in 0..sx as usize {
+                    let top = stack.iter().find_map(|(cy, buf)| {
+                        (0..buf.sy).rev().find_map(|ly| {
+                            let b = buf.get_local(lx, ly, lz);
+                            (b != Block::AIR).then_some((cy * buf.sy as i32 + ly as i32, b))
+                        })
+                    });
+                    let Some((wy, b)) = top else {
+                        continue;
+                    };
+                    let c = *colors.entry((b.id, b.state)).or_insert_with(|| {
+                        self.reg
+                            .get(b.id)
+                            .map(|ty| {
+                                material_color(self, ty.material_for_cached(FaceRole::Top, b.state))
+                            })
+                            .unwrap_or(UNLOADED)
+                    });
+                    scene
+                        .surface
+                        .push((cx * sx + lx as i32, cz * sz + lz as i32, wy, c));
+                }
+            }
+            for &(cy, buf) in stack.iter() {
+                let base = (cx * sx, cy * buf.sy as i32, cz * sz);
+                for ((wx, _, wz), _) in self.gs.edits.snapshot_for_chunk(cx, cy, cz) {
+                    scene.edits.push((wx, wz));
+                }
+                let coord = geist_world::ChunkCoord::new(cx, cy, cz);
+                for (lx, _, lz, _, beacon) in self.gs.lighting.emitters_for_chunk(coord) {
+                    scene
+                        .lights
+                        .push((base.0 + lx as i32, base.2 + lz as i32, beacon));
+                }
+            }
+        }
+        for emitters in self.structure_emitters.values() {
+            scene
+                .lights
+                .extend(emitters.world.iter().map(|&(wx, _, wz)| (wx, wz, false)));
+        }
+        for st in self.gs.structures.values() {
+            let (x1, z1) = (st.sx as f32, st.sz as f32);
+            let corner = |x: f32, z: f32| {
+                let p = st.pose.local_to_world(Vec3::new(x, 0.0, z));
+                (p.x, p.z)
+            };
+            scene.footprints.push([
+                corner(0.0, 0.0),
+                corner(x1, 0.0),
+                corner(x1, z1),
+                corner(0.0, z1),
+            ]);
+        }
+        scene.edits.sort_unstable();
+        scene.edits.dedup();
+        Some(scene)
+    }
+
+    /// Render the loaded world top-down into a PNG at `path`.
+    pub(crate) fn export_world_map(&self, path: &Path) -> Result<(i32, i32), String> {
+        let scene = self
+            .world_map_scene()
+            .ok_or_else(|| "no chunks loaded".to_string())?;
+        let img = render_map(&scene);
+        img.write_png(path)?;
+        Ok((img.width, img.height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scene() -> MapScene {
+        MapScene {
+            min: (0, 0),
+            max: (8, 8),
+            chunk_size: (4, 4),
+            surface: (0..8)
+                .flat_map(|z| (0..8).map(move |x| (x, z, x, Color::new(100, 100, 100, 255))))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn surface_is_shaded_by_height_and_gaps_stay_unloaded() {
+        let mut s = scene();
+        s.surface.retain(|&(x, z, _, _)| !(x == 7 && z == 7));
+        let img = render_map(&s);
+        assert_eq!((img.width, img.height), (16, 16));
+        let low = img.get(3, 3).unwrap();
+        let high = img.get(13, 3).unwrap();
+        assert!(high.r > low.r, "higher columns render brighter");
+        assert_eq!(img.get(15, 15), Some(UNLOADED));
+    }
+
+    #[test]
+    fn overlays_mark_edits_grid_lights_and_footprints() {
+        let mut s = scene();
+        s.edits.push((1, 1));
+        s.lights.push((6, 1, true));
+        s.footprints
+            .push([(1.0, 5.0), (3.0, 5.0), (3.0, 7.0), (1.0, 7.0)]);
+        let img = render_map(&s);
+        let base = render_map(&scene());
+        let px = |x: i32, z: i32| (x * MAP_PX_PER_VOXEL + 1, z * MAP_PX_PER_VOXEL + 1);
+
+        let (ex, ey) = px(1, 1);
+        assert_ne!(img.get(ex, ey), base.get(ex, ey));
+        assert!(img.get(ex, ey).unwrap().r > img.get(ex, ey).unwrap().g);
+        // Chunk boundary at x=4 is darker than the pixel beside it.
+        assert!(base.get(8, 3).unwrap().r < base.get(9, 3).unwrap().r);
+        assert_eq!(img.get(13, 3), Some(BEACON));
+        assert_eq!(img.get(2, 12), Some(FOOTPRINT));
+        assert_eq!(
+            img.get(4, 12),
+            base.get(4, 12),
+            "footprint interior is left alone"
+        );
+    }
+}
@@ -1,7 +1,23 @@
+mod accessibility;
+mod actors;
+mod ambiance;
 mod attachment;
+mod autosave;
+mod block_ticks;
+mod border;
+mod crash;
 mod day_cycle;
+mod dimensions;
+mod dynamic_lights;
+mod edit_latency;
 mod events;
+mod falling;
+mod fluids;
+mod hotbar;
 mod init;
+mod lighting_compare;
+mod map_export;
+mod rebuild_history;
 mod render;
 mod runtime;
 mod state;
@@ -9,13 +25,23 @@ mod step;
 mod sun;
 mod watchers;
 
+pub(crate) use accessibility::AccessibilitySettings;
+pub(crate) use ambiance::Ambiance;
 pub(crate) use attachment::{
     anchor_world_position, anchor_world_velocity, structure_local_sampler, structure_world_to_local,
 };
-pub use day_cycle::{DayCycle, DayLightSample};
+pub(crate) use border::WorldBorderSpec;
+pub(crate) use crash::install_crash_hook;
+pub use day_cycle::{DayCycle, DayLightSample, SkyCurve};
+pub(crate) use edit_latency::{EditLatencyTracker, EditStage};
 pub(crate) use geist_ui::{
-    HitRegion, IRect, OverlayWindow, OverlayWindowManager, TabDefinition, TabStrip, UiTextMeasure,
-    UiTextRenderer, WindowButton, WindowChrome, WindowFrame, WindowId, WindowTheme,
+    HitRegion, IRect, ModalKey, OverlayWindow, OverlayWindowManager, TabDefinition, TabStrip,
+    TabStripHit, TabStripLayout, TabStripState, UiTextMeasure, UiTextRenderer, WindowButton,
+    WindowChrome, WindowFrame, WindowId, WindowTheme,
 };
+pub(crate) use hotbar::{HOTBAR_SLOTS, Hotbar};
+pub(crate) use lighting_compare::LightingCompare;
+pub(crate) use rebuild_history::RebuildHistory;
+pub(crate) use state::Toast;
 pub use state::{App, DebugOverlayTab, DebugStats, DiagnosticsTab, SchematicOrbit};
 pub use sun::{SUN_STRUCTURE_ID, SunBody};
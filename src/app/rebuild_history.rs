@@ -0,0 +1,97 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use geist_world::ChunkCoord;
+
+use crate::event::RebuildCause;
+
+// Most recent rebuilds kept per chunk.
+const HISTORY_PER_CHUNK: usize = 8;
+// Past this many tracked chunks, forget the ones with no rebuild in `HISTORY_TTL`.
+const HISTORY_MAX_CHUNKS: usize = 4096;
+const HISTORY_TTL: Duration = Duration::from_secs(60);
+
+impl RebuildCause {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Edit => "Edit",
+            Self::LightingBorder => "LightingBorder",
+            Self::StreamLoad => "StreamLoad",
+            Self::HotReload => "WorldGenReload",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct RebuildRecord {
+    pub(crate) cause: RebuildCause,
+    pub(crate) rev: u64,
+    pub(crate) at: Instant,
+}
+
+/// Why each chunk was last rebuilt, for chasing rebuild storms. Entries survive unloads so
+/// load/unload thrash shows up as repeated `StreamLoad`s.
+#[derive(Default)]
+pub(crate) struct RebuildHistory {
+    chunks: HashMap<ChunkCoord, VecDeque<RebuildRecord>>,
+}
+
+impl RebuildHistory {
+    pub(crate) fn record(&mut self, coord: ChunkCoord, cause: RebuildCause, rev: u64) {
+        let now = Instant::now();
+        if self.chunks.len() >= HISTORY_MAX_CHUNKS && !self.chunks.contains_key(&coord) {
+            self.chunks.retain(|_, q| {
+                q.back()
+                    .is_some_and(|r| now.duration_since(r.at) < HISTORY_TTL)
+            });
+        }
+        let q = self.chunks.entry(coord).or_default();
+        if q.len() == HISTORY_PER_CHUNK {
+            q.pop_front();
+        }
+        q.push_back(RebuildRecord {
+            cause,
+            rev,
+            at: now,
+        });
+    }
+
+    /// Oldest first.
+    pub(crate) fn get(&self, coord: ChunkCoord) -> impl Iterator<Item = &RebuildRecord> {
+        self.chunks.get(&coord).into_iter().flatten()
+    }
+
+    /// Rebuilds of `coord` within the last `window`.
+    pub(crate) fn count_within(&self, coord: ChunkCoord, window: Duration) -> usize {
+        let now = Instant::now();
+        self.get(coord)
+            .filter(|r| now.duration_since(r.at) <= window)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_only_the_latest_rebuilds_per_chunk() {
+        let mut h = RebuildHistory::default();
+        let a = ChunkCoord::new(0, 0, 0);
+        let b = ChunkCoord::new(1, 0, 0);
+        for rev in 0..(HISTORY_PER_CHUNK as u64 + 3) {
+            h.record(a, RebuildCause::LightingBorder, rev);
+        }
+        h.record(b, RebuildCause::Edit, 1);
+        let revs: Vec<u64> = h.get(a).map(|r| r.rev).collect();
+        assert_eq!(revs.len(), HISTORY_PER_CHUNK);
+        assert_eq!(revs.first(), Some(&3));
+        assert_eq!(revs.last(), Some(&(HISTORY_PER_CHUNK as u64 + 2)));
+        assert_eq!(
+            h.count_within(a, Duration::from_secs(60)),
+            HISTORY_PER_CHUNK
+        );
+        assert_eq!(h.get(b).next().map(|r| r.cause), Some(RebuildCause::Edit));
+        assert_eq!(h.get(ChunkCoord::new(5, 5, 5)).count(), 0);
+    }
+}
@@ -4,6 +4,7 @@ use raylib::prelude::*;
 use std::sync::Arc;
 
 use super::{UiTextMeasure, UiTextRenderer, WindowFrame};
+use geist_ui::{TextSpan, TextTable, wrap_spans};
 
 pub(crate) fn format_count(count: usize) -> String {
     match count {
@@ -56,6 +57,12 @@ pub(crate) struct DisplayLine {
     pub(crate) font: i32,
     pub(crate) line_height: i32,
     pub(crate) indent: i32,
+    /// Drawn instead of `text` when not empty.
+    pub(crate) spans: Vec<TextSpan>,
+    /// Word-wrap to the content width; the line grows by `line_height` per wrapped row.
+    pub(crate) wrap: bool,
+    /// Drawn instead of text, one `line_height` per row; `color` colors the headers.
+    pub(crate) table: Option<TextTable>,
 }
 
 impl DisplayLine {
@@ -67,9 +74,26 @@ impl DisplayLine {
             font,
             line_height: font + 4,
             indent: 0,
+            spans: Vec::new(),
+            wrap: false,
+            table: None,
         }
     }
 
+    /// A line of colored runs.
+    pub(crate) fn spans(spans: Vec<TextSpan>, font: i32) -> Self {
+        let mut line = Self::new(String::new(), font, Color::WHITE);
+        line.spans = spans;
+        line
+    }
+
+    /// An aligned table with headers in `header_color`.
+    pub(crate) fn table(table: TextTable, font: i32, header_color: Color) -> Self {
+        let mut line = Self::new(String::new(), font, header_color);
+        line.table = Some(table);
+        line
+    }
+
     pub(crate) fn with_indent(mut self, indent: i32) -> Self {
         self.indent = indent.max(0);
         self
@@ -79,16 +103,51 @@ impl DisplayLine {
         self.line_height = line_height.max(self.font);
         self
     }
+
+    pub(crate) fn wrapped(mut self) -> Self {
+        self.wrap = true;
+        self
+    }
+
+    /// Height without wrapping, which depends on the content width.
+    pub(crate) fn min_height(&self) -> i32 {
+        let rows = self.table.as_ref().map_or(1, TextTable::line_count);
+        self.line_height * rows as i32
+    }
+
+    fn to_spans(&self) -> Vec<TextSpan> {
+        if self.spans.is_empty() {
+            vec![TextSpan::new(self.text.as_str(), self.color)]
+        } else {
+            self.spans.clone()
+        }
+    }
 }
 
 pub(crate) struct GeistDraw<'a> {
     pub(crate) inner: RaylibDrawHandle<'a>,
     pub(crate) font: Option<Arc<Font>>,
+    /// Multiplier on panel text metrics (font, line height, indent) in `draw_lines`.
+    pub(crate) ui_scale: f32,
 }
 
 impl<'a> GeistDraw<'a> {
     pub(crate) fn new(inner: RaylibDrawHandle<'a>, font: Option<Arc<Font>>) -> Self {
-        Self { inner, font }
+        Self {
+            inner,
+            font,
+            ui_scale: 1.0,
+        }
+    }
+
+    pub(crate) fn with_ui_scale(mut self, scale: f32) -> Self {
+        self.ui_scale = scale;
+        self
+    }
+
+    /// `v` pixels at the current UI scale.
+    pub(crate) fn ui_px(&self, v: i32) -> i32 {
+        ((v as f32 * self.ui_scale).round() as i32).max(1)
     }
 
     pub(crate) fn draw_text(&mut self, text: &str, x: i32, y: i32, font_size: i32, color: Color) {
@@ -160,20 +219,37 @@ pub(crate) fn draw_lines(
     }
     let offset_y = frame.scroll.offset.y.max(0.0).round() as i32;
     let mut y = content.y - offset_y;
+    let scale = d.ui_scale;
+    let px = |v: i32| ((v as f32 * scale).round() as i32).max(1);
     {
         let mut scoped = d.begin_scissor_mode(content.x, content.y, content.w, content.h);
         for (idx, line) in lines.iter().enumerate() {
-            let next_y = y + line.line_height;
-            layout.add_custom(line.line_height);
+            let font = px(line.font);
+            let line_height = px(line.line_height);
+            let indent = if line.indent > 0 { px(line.indent) } else { 0 };
+            let x = content.x + indent;
+            let wrapped = line
+                .wrap
+                .then(|| wrap_spans(&*scoped, &line.to_spans(), font, content.w - indent));
+            let rows = match (&line.table, &wrapped) {
+                (Some(table), _) => table.line_count(),
+                (None, Some(rows)) => rows.len(),
+                (None, None) => 1,
+            };
+            let height = line_height * rows as i32;
+            let next_y = y + height;
+            layout.add_custom(height);
             if next_y > content.y && y < content.y + content.h {
-                if !line.text.is_empty() {
-                    scoped.draw_text(
-                        &line.text,
-                        content.x + line.indent,
-                        y,
-                        line.font,
-                        line.color,
-                    );
+                if let Some(table) = &line.table {
+                    table.draw(&mut *scoped, x, y, font, line_height, line.color);
+                } else if let Some(rows) = &wrapped {
+                    for (i, row) in rows.iter().enumerate() {
+                        scoped.ui_draw_spans(row, x, y + i as i32 * line_height, font);
+                    }
+                } else if !line.spans.is_empty() {
+                    scoped.ui_draw_spans(&line.spans, x, y, font);
+                } else if !line.text.is_empty() {
+                    scoped.draw_text(&line.text, x, y, font, line.color);
                 }
             }
             if next_y >= content.y + content.h {
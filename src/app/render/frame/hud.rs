@@ -1,23 +1,144 @@
+use geist_blocks::FaceRole;
+use geist_render_raylib::material_texture_path;
 use raylib::prelude::*;
 
 use super::super::{App, GeistDraw};
+use crate::app::HOTBAR_SLOTS;
+
+const HOTBAR_SLOT: i32 = 44;
+const HOTBAR_GAP: i32 = 4;
+const HOTBAR_MARGIN: i32 = 14;
 
 impl App {
     pub(super) fn draw_hud(&self, d: &mut GeistDraw) {
-        let hud_mode = if self.gs.walk_mode { "Walk" } else { "Fly" };
+        let hud_mode = if self.gs.spectator {
+            format!(
+                "Spectator {:.0} m/s (wheel speed, N return)",
+                self.gs.spectator_speed
+            )
+        } else if self.gs.walk_mode {
+            "Walk".to_string()
+        } else {
+            "Fly".to_string()
+        };
+        let flying = self.gs.spectator || !self.gs.walk_mode;
         let hud = format!(
-            "{}: Tab capture, WASD{} move{}, V toggle mode, F wireframe, G grid, B bounds, C culling, H biome label, F3 debug overlay, L add light, K remove light | Place: {:?} (1-7) | Castle vX={:.1} (-/= adj, 0 stop) vY={:.1} ([/] adj, \\ stop)",
+            "{}: Tab capture, WASD{} move{}, V toggle mode, N spectator, F wireframe, G grid, B bounds, C culling, H biome label, F3 debug overlay, F4 ambiance, F6/F7 lighting compare, F9 export map, T hand torch, L add light, K remove light, P stamp structure, O next dimension, PgUp/PgDn+F8 paste schematic, Ctrl+Z/Y undo/redo | Place: {:?} (1-9/wheel, middle-click pick) | Castle vX={:.1} (-/= adj, 0 stop) vY={:.1} ([/] adj, \\ stop)",
             hud_mode,
-            if self.gs.walk_mode { "" } else { "+QE" },
-            if self.gs.walk_mode {
-                ", Space jump, Shift run"
-            } else {
+            if flying { "+QE" } else { "" },
+            if flying {
                 ""
+            } else {
+                ", Space jump, Shift run"
             },
             self.gs.place_type,
             self.gs.structure_speed,
             self.gs.structure_elev_speed,
         );
-        d.draw_text(&hud, 12, 12, 18, Color::DARKGRAY);
+        let (font, step) = (d.ui_px(18), d.ui_px(24));
+        d.draw_text(&hud, 12, 12, font, Color::DARKGRAY);
+        let mut line_y = 12 + step;
+        if let Some(toast) = self.toast.as_ref().filter(|t| !t.expired()) {
+            d.draw_text(&toast.text, 12, line_y, font, Color::ORANGE);
+            line_y += step;
+        }
+        if let Some(cmp) = self.lighting_compare.as_ref() {
+            let text = format!("Lighting compare (F6 off, F7 flip): {}", cmp.summary());
+            d.draw_text(&text, 12, line_y, font, Color::LIME);
+            line_y += step;
+        }
+        for p in self.runtime.active_batches() {
+            let text = format!("{}... {}/{} chunks", p.label, p.done, p.total);
+            let text = if self.stamp_batch.is_some_and(|(id, _)| id == p.id) {
+                format!("{} (Del to cancel)", text)
+            } else {
+                text
+            };
+            d.draw_text(&text, 12, line_y, font, Color::SKYBLUE);
+            line_y += step;
+        }
+        self.draw_hotbar(d);
+    }
+
+    /// Slots along the bottom edge, each showing its block's side texture when loaded.
+    fn draw_hotbar(&self, d: &mut GeistDraw) {
+        let n = HOTBAR_SLOTS as i32;
+        let total_w = n * HOTBAR_SLOT + (n - 1) * HOTBAR_GAP;
+        let x0 = (d.get_screen_width() - total_w) / 2;
+        let y = d.get_screen_height() - HOTBAR_SLOT - HOTBAR_MARGIN;
+        let selected = self.hotbar.selected_slot();
+        for (i, slot) in self.hotbar.slots().iter().enumerate() {
+            let x = x0 + i as i32 * (HOTBAR_SLOT + HOTBAR_GAP);
+            d.draw_rectangle(x, y, HOTBAR_SLOT, HOTBAR_SLOT, Color::new(12, 18, 28, 190));
+            let inset = 5;
+            let icon = HOTBAR_SLOT - inset * 2;
+            if let Some((block, ty)) = slot.and_then(|b| self.reg.get(b.id).map(|ty| (b, ty))) {
+                let tex = material_texture_path(
+                    &self.reg.materials,
+                    ty.material_for_cached(FaceRole::Side, block.state),
+                )
+                .and_then(|path| self.tex_cache.get_ref(&path));
+                if let Some(tex) = tex {
+                    d.draw_texture_pro(
+                        tex,
+                        Rectangle::new(0.0, 0.0, tex.width() as f32, tex.height() as f32),
+                        Rectangle::new(
+                            (x + inset) as f32,
+                            (y + inset) as f32,
+                            icon as f32,
+                            icon as f32,
+                        ),
+                        Vector2::new(0.0, 0.0),
+                        0.0,
+                        Color::WHITE,
+                    );
+                } else {
+                    // Texture not uploaded yet (or packed into the block array): show the name.
+                    let label: String = ty.name.chars().take(4).collect();
+                    d.draw_rectangle(
+                        x + inset,
+                        y + inset,
+                        icon,
+                        icon,
+                        Color::new(70, 82, 104, 255),
+                    );
+                    d.draw_text(
+                        &label,
+                        x + inset + 2,
+                        y + HOTBAR_SLOT / 2 - 6,
+                        12,
+                        Color::WHITE,
+                    );
+                }
+            }
+            let border = if i == selected {
+                Color::new(255, 226, 140, 255)
+            } else {
+                Color::new(48, 64, 92, 220)
+            };
+            d.draw_rectangle_lines_ex(
+                Rectangle::new(x as f32, y as f32, HOTBAR_SLOT as f32, HOTBAR_SLOT as f32),
+                if i == selected { 3.0 } else { 1.0 },
+                border,
+            );
+            d.draw_text(
+                &(i + 1).to_string(),
+                x + 3,
+                y + 2,
+                12,
+                Color::new(206, 220, 240, 255),
+            );
+        }
+        if let Some(ty) = self.hotbar.selected().and_then(|b| self.reg.get(b.id)) {
+            let fs = 18;
+            let w = d.measure_text(&ty.name, fs);
+            d.draw_text(
+                &ty.name,
+                x0 + (total_w - w) / 2,
+                y - fs - 6,
+                fs,
+                Color::new(236, 244, 255, 255),
+            );
+        }
     }
 }
@@ -1,3 +1,4 @@
+use geist_render_raylib::Frustum;
 use raylib::prelude::*;
 
 use super::App;
@@ -5,6 +6,7 @@ use super::GeistDraw;
 
 mod hud;
 mod overlay;
+mod shadows;
 mod stats;
 mod world;
 
@@ -18,45 +20,60 @@ impl App {
         let screen_width = rl.get_screen_width() as f32;
         let screen_height = rl.get_screen_height() as f32;
         let aspect_ratio = screen_width / screen_height;
-        let frustum = self.cam.calculate_frustum(aspect_ratio, 0.1, 10000.0);
+        let frustum = Frustum::from_camera(&self.cam.to_camera3d(), aspect_ratio, 0.1, 10000.0);
 
         let time_now = rl.get_time() as f32;
         let sample = self.day_sample;
         let sky_scale = sample.sky_scale;
-        let surface_sky = sample.surface_sky;
+        let sky = sample.sky;
         let sun_id = self.sun.as_ref().map(|s| s.id);
-        let sun_tint = world::sun_tint_color(sample);
 
-        let camera3d = self.cam.to_camera3d();
+        if self.render_origin.update(self.cam.position) {
+            log::debug!("render origin rebased to {:?}", self.render_origin.origin());
+        }
+        let camera3d = self.render_origin.camera(self.cam.to_camera3d());
         self.minimap_ui_rect = None;
 
         let screen_dims = (screen_width as i32, screen_height as i32);
         let overlay_theme = *self.overlay_windows.theme();
         let minimap_render_side = self.prepare_minimap_render_side(screen_dims, overlay_theme);
         self.render_minimap_to_texture(rl, thread, minimap_render_side);
+        let scaled_scene = self.scene_target.prepare(rl, thread, screen_dims);
 
         let cursor_position = rl.get_mouse_position();
         let mouse_left_pressed = rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT);
+        let mouse_left_down = rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT);
 
         let font_for_frame = self.ui_font.clone();
-        let mut d = GeistDraw::new(rl.begin_drawing(thread), font_for_frame);
-        d.clear_background(world::surface_color(surface_sky));
+        let mut d = GeistDraw::new(rl.begin_drawing(thread), font_for_frame)
+            .with_ui_scale(self.accessibility.ui_scale);
+        d.clear_background(sky.clear_color());
 
         unsafe {
             raylib::ffi::rlClearScreenBuffers();
         }
+        self.render_shadow_maps(&sample);
 
+        if scaled_scene {
+            self.scene_target.begin();
+            d.clear_background(sky.clear_color());
+        }
         self.draw_world_scene(
             &mut d,
             thread,
             camera3d,
             &frustum,
             time_now,
+            aspect_ratio,
             sky_scale,
-            surface_sky,
+            &sky,
             sun_id,
-            sun_tint,
         );
+        if scaled_scene {
+            self.scene_target.end();
+            self.scene_target
+                .blit(&mut d.inner, screen_dims, self.sharpen_shader.as_mut());
+        }
 
         self.draw_debug_overlay(
             &mut d,
@@ -64,10 +81,16 @@ impl App {
             overlay_theme,
             cursor_position,
             mouse_left_pressed,
+            mouse_left_down,
         );
 
         self.draw_hud(&mut d);
 
+        // Modal dialogs sit above every window and the HUD.
+        self.overlay_windows
+            .modals_mut()
+            .draw(&mut d, screen_dims, &overlay_theme, time_now);
+
         if !self.gs.show_debug_overlay {
             return;
         }
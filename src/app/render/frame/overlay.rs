@@ -4,8 +4,8 @@ use super::super::{
     App, AttachmentDebugView, ChunkVoxelView, ContentLayout, DebugOverlayTab, DiagnosticsTab,
     EventHistogramView, GeistDraw, HitRegion, IRect, IntentHistogramView, MINIMAP_BORDER_PX,
     MINIMAP_MAX_CONTENT_SIDE, MINIMAP_MIN_CONTENT_SIDE, RenderStatsView, RuntimeStatsView,
-    TabDefinition, TabStrip, TerrainHistogramView, WindowChrome, WindowFrame, WindowId,
-    WindowTheme,
+    SchematicLibraryView, SettingsView, TabDefinition, TabStrip, TabStripHit, TabStripLayout,
+    TabStripState, TerrainHistogramView, WindowChrome, WindowFrame, WindowId, WindowTheme,
 };
 
 impl App {
@@ -45,6 +45,7 @@ impl App {
         overlay_theme: WindowTheme,
         cursor_position: Vector2,
         mouse_left_pressed: bool,
+        mouse_left_down: bool,
     ) {
         if !self.gs.show_debug_overlay {
             self.minimap_ui_rect = None;
@@ -85,22 +86,32 @@ impl App {
                             TabDefinition::new(DiagnosticsTab::RuntimeStats.title()),
                             TabDefinition::new(DiagnosticsTab::AttachmentDebug.title()),
                         ];
-                        let tab_layout =
-                            TabStrip::layout(&*d, &overlay_theme, &frame, &tab_definitions);
-                        let hovered_tab = tab_layout.hovered(cursor_position);
-                        if mouse_left_pressed
-                            && hovered_tab.is_some()
-                            && matches!(hover, Some(HitRegion::Content))
+                        let tab_layout = TabStrip::layout(
+                            &*d,
+                            &overlay_theme,
+                            &frame,
+                            &tab_definitions,
+                            &mut self.overlay_diagnostics_tab_strip,
+                        );
+                        let interactive = matches!(hover, Some(HitRegion::Content))
                             && !window.is_dragging()
-                            && !window.is_resizing()
-                        {
-                            if let Some(index) = hovered_tab {
-                                let next_tab = DiagnosticsTab::from_index(index);
-                                if next_tab != self.overlay_diagnostics_tab {
-                                    self.overlay_diagnostics_tab = next_tab;
-                                }
-                            }
+                            && !window.is_resizing();
+                        if let Some(index) = update_tab_strip(
+                            &mut self.overlay_diagnostics_tab_strip,
+                            &tab_layout,
+                            tab_definitions.len(),
+                            cursor_position,
+                            mouse_left_pressed,
+                            mouse_left_down,
+                            interactive,
+                        ) {
+                            self.overlay_diagnostics_tab = DiagnosticsTab::from_index(index);
                         }
+                        let hovered_tab = self
+                            .overlay_diagnostics_tab_strip
+                            .dragged_tab()
+                            .map(TabStripHit::Tab)
+                            .or_else(|| tab_layout.hit(cursor_position));
 
                         let selected_tab = self.overlay_diagnostics_tab;
                         let selected_index = selected_tab.as_index();
@@ -189,22 +200,32 @@ impl App {
                             TabDefinition::new(DebugOverlayTab::IntentQueue.title()),
                             TabDefinition::new(DebugOverlayTab::TerrainPipeline.title()),
                         ];
-                        let tab_layout =
-                            TabStrip::layout(&*d, &overlay_theme, &frame, &tab_definitions);
-                        let hovered_tab = tab_layout.hovered(cursor_position);
-                        if mouse_left_pressed
-                            && hovered_tab.is_some()
-                            && matches!(hover, Some(HitRegion::Content))
+                        let tab_layout = TabStrip::layout(
+                            &*d,
+                            &overlay_theme,
+                            &frame,
+                            &tab_definitions,
+                            &mut self.overlay_debug_tab_strip,
+                        );
+                        let interactive = matches!(hover, Some(HitRegion::Content))
                             && !window.is_dragging()
-                            && !window.is_resizing()
-                        {
-                            if let Some(index) = hovered_tab {
-                                let next_tab = DebugOverlayTab::from_index(index);
-                                if next_tab != self.overlay_debug_tab {
-                                    self.overlay_debug_tab = next_tab;
-                                }
-                            }
+                            && !window.is_resizing();
+                        if let Some(index) = update_tab_strip(
+                            &mut self.overlay_debug_tab_strip,
+                            &tab_layout,
+                            tab_definitions.len(),
+                            cursor_position,
+                            mouse_left_pressed,
+                            mouse_left_down,
+                            interactive,
+                        ) {
+                            self.overlay_debug_tab = DebugOverlayTab::from_index(index);
                         }
+                        let hovered_tab = self
+                            .overlay_debug_tab_strip
+                            .dragged_tab()
+                            .map(TabStripHit::Tab)
+                            .or_else(|| tab_layout.hit(cursor_position));
 
                         let selected_tab = self.overlay_debug_tab;
                         let selected_index = selected_tab.as_index();
@@ -299,6 +320,66 @@ impl App {
                         self.draw_overflow_hint(d, &content_frame, layout);
                     }
                 }
+                WindowId::SchematicLibrary => {
+                    let is_focused = self.overlay_windows.is_focused(id);
+                    let view = SchematicLibraryView::new(self);
+                    if let Some(window) = self.overlay_windows.get_mut(id) {
+                        window.set_min_size(view.min_size(&overlay_theme));
+                        let frame = window.layout(screen_dims, &overlay_theme);
+                        let window_state = window.state();
+                        let is_pinned = window.is_pinned();
+
+                        WindowChrome::draw(
+                            d,
+                            &overlay_theme,
+                            &frame,
+                            "Schematics",
+                            view.subtitle(),
+                            hover,
+                            window_state,
+                            is_focused,
+                            is_pinned,
+                        );
+
+                        let content = frame.content;
+                        window.update_content_viewport(content);
+                        let mut content_frame = *window.frame();
+                        content_frame.content = content;
+                        let layout = view.draw(d, &content_frame);
+                        window.set_content_extent((content_frame.content.w, layout.used_height));
+                        self.draw_overflow_hint(d, &content_frame, layout);
+                    }
+                }
+                WindowId::Settings => {
+                    let is_focused = self.overlay_windows.is_focused(id);
+                    let view = SettingsView::new(self, screen_dims);
+                    if let Some(window) = self.overlay_windows.get_mut(id) {
+                        window.set_min_size(view.min_size(&overlay_theme));
+                        let frame = window.layout(screen_dims, &overlay_theme);
+                        let window_state = window.state();
+                        let is_pinned = window.is_pinned();
+
+                        WindowChrome::draw(
+                            d,
+                            &overlay_theme,
+                            &frame,
+                            "Settings",
+                            view.subtitle(),
+                            hover,
+                            window_state,
+                            is_focused,
+                            is_pinned,
+                        );
+
+                        let content = frame.content;
+                        window.update_content_viewport(content);
+                        let mut content_frame = *window.frame();
+                        content_frame.content = content;
+                        let layout = view.draw(d, &content_frame);
+                        window.set_content_extent((content_frame.content.w, layout.used_height));
+                        self.draw_overflow_hint(d, &content_frame, layout);
+                    }
+                }
                 WindowId::Minimap => {
                     minimap_drawn = true;
                     let is_focused = self.overlay_windows.is_focused(id);
@@ -524,3 +605,35 @@ impl App {
         );
     }
 }
+
+/// Select, scroll and drag-reorder one tab strip. Returns the tab clicked this frame.
+fn update_tab_strip(
+    state: &mut TabStripState,
+    layout: &TabStripLayout<'_>,
+    count: usize,
+    cursor: Vector2,
+    pressed: bool,
+    down: bool,
+    interactive: bool,
+) -> Option<usize> {
+    let mut clicked = None;
+    if pressed && interactive {
+        match layout.hit(cursor) {
+            Some(TabStripHit::Tab(index)) => {
+                state.begin_drag(index, cursor);
+                clicked = Some(index);
+            }
+            Some(TabStripHit::ScrollLeft) => state.scroll_by(-1),
+            Some(TabStripHit::ScrollRight) => state.scroll_by(1),
+            None => {}
+        }
+    }
+    if state.is_dragging() {
+        if down {
+            state.update_drag(layout, count, cursor);
+        } else {
+            state.end_drag();
+        }
+    }
+    clicked
+}
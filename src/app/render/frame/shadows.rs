@@ -0,0 +1,75 @@
+use raylib::prelude::*;
+
+use super::super::App;
+use super::world::structure_world_bbox;
+use crate::app::DayLightSample;
+use geist_blocks::RenderPass;
+use geist_render_raylib::conv::vec3_to_rl;
+
+/// Share of skylight a full shadow takes away at noon; the rest stands in for light
+/// scattered from the open sky.
+const SHADOW_STRENGTH: f32 = 0.6;
+
+impl App {
+    /// Render the sun's shadow cascades for this frame from the opaque chunk and structure
+    /// parts around the camera. Nothing is drawn while the sun is down or shadows are off,
+    /// and the voxel shaders then leave skylight unshadowed.
+    pub(super) fn render_shadow_maps(&mut self, sample: &DayLightSample) {
+        let Some(shadows) = self.shadow_maps.as_mut() else {
+            return;
+        };
+        let strength = if sample.sun_visible {
+            SHADOW_STRENGTH * sample.brightness
+        } else {
+            0.0
+        };
+        let offset = self.render_origin.offset();
+        let render_cam = self.render_origin.to_render(self.cam.position);
+        shadows.update(render_cam, vec3_to_rl(sample.sun_dir), strength);
+        let sun_id = self.sun.as_ref().map(|s| s.id);
+        for i in 0..shadows.active_cascades() {
+            let Some(frustum) = shadows.cascade_frustum(i) else {
+                continue;
+            };
+            if !shadows.begin_cascade(i) {
+                continue;
+            }
+            let to_render = Matrix::translate(offset.x, offset.y, offset.z);
+            for cr in self.renders.values() {
+                let bbox = BoundingBox::new(cr.bbox.min + offset, cr.bbox.max + offset);
+                if !frustum.contains_bounding_box(&bbox) {
+                    continue;
+                }
+                for part in cr.parts.iter().filter(|p| p.pass == RenderPass::Opaque) {
+                    shadows.draw_part(part, to_render);
+                    self.debug_stats.shadow_draw_calls += 1;
+                }
+            }
+            for (id, cr) in &self.structure_renders {
+                // The sun is a structure too, far out along the light.
+                if Some(*id) == sun_id {
+                    continue;
+                }
+                let Some(st) = self.gs.structures.get(id) else {
+                    continue;
+                };
+                let world = structure_world_bbox(&cr.bbox, &st.pose);
+                let bbox = BoundingBox::new(world.min + offset, world.max + offset);
+                if !frustum.contains_bounding_box(&bbox) {
+                    continue;
+                }
+                let (axis, angle) = st.pose.rotation().to_axis_angle();
+                let s = st.pose.scale;
+                let pos = vec3_to_rl(st.pose.pos) + offset;
+                let transform = Matrix::scale(s, s, s)
+                    * Matrix::rotate(vec3_to_rl(axis), angle)
+                    * Matrix::translate(pos.x, pos.y, pos.z);
+                for part in cr.parts.iter().filter(|p| p.pass == RenderPass::Opaque) {
+                    shadows.draw_part(part, transform);
+                    self.debug_stats.shadow_draw_calls += 1;
+                }
+            }
+            shadows.end_cascade();
+        }
+    }
+}
@@ -47,6 +47,8 @@ impl App {
         self.debug_stats.lighting_border_chunks = light_stats.border_chunks;
         self.debug_stats.lighting_emitter_chunks = light_stats.emitter_chunks;
         self.debug_stats.lighting_micro_chunks = light_stats.micro_chunks;
+        self.debug_stats.lighting_seam_mismatches = light_stats.seam_mismatches;
+        self.debug_stats.lighting_last_seam = light_stats.last_seam_mismatch;
     }
 
     pub(super) fn update_edit_debug_stats(&mut self) {
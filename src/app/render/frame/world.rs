@@ -1,41 +1,79 @@
 use raylib::prelude::*;
 
 use super::super::{App, GeistDraw};
-use crate::app::DayLightSample;
-use crate::camera::Frustum;
-use crate::raycast;
 use geist_blocks::Block;
-use geist_chunk::ChunkOccupancy;
-use geist_render_raylib::conv::vec3_to_rl;
-use geist_structures::StructureId;
+use geist_blocks::RenderPass;
+use geist_geom::Vec3;
+use geist_raycast::{Ray, raycast_world};
+use geist_render_raylib::conv::{vec3_from_rl, vec3_to_rl};
+use geist_render_raylib::{
+    ChunkPart, ChunkVisibility, Frustum, SkyFrame, translucent_back_to_front, visible_chunks,
+};
+use geist_structures::{Pose, StructureId};
 use geist_world::ChunkCoord;
 
-pub(super) fn surface_color(surface_sky: [f32; 3]) -> Color {
-    Color::new(
-        (surface_sky[0] * 255.0) as u8,
-        (surface_sky[1] * 255.0) as u8,
-        (surface_sky[2] * 255.0) as u8,
-        255,
-    )
+/// What a translucent part belongs to, for the combined back-to-front sort.
+#[derive(Clone, Copy)]
+enum TranslucentKey {
+    Chunk(ChunkCoord),
+    Structure(StructureId),
 }
 
-pub(super) fn sun_tint_color(sample: DayLightSample) -> Color {
-    let warm = [1.0, 0.84, 0.42];
-    let ember = [1.0, 0.58, 0.28];
-    let twilight = sample.phase.cos().abs().clamp(0.0, 1.0);
-    let ember_mix = twilight.powf(1.5);
-    let warm_mix = 1.0 - ember_mix;
-    let base = [
-        warm[0] * warm_mix + ember[0] * ember_mix,
-        warm[1] * warm_mix + ember[1] * ember_mix,
-        warm[2] * warm_mix + ember[2] * ember_mix,
-    ];
-    let visibility = if sample.sun_visible { 1.0 } else { 0.15 };
-    let brightness = ((0.2 + 0.8 * sample.sky_scale) * visibility).clamp(0.0, 1.0);
-    let r = (base[0] * brightness * 255.0).clamp(0.0, 255.0) as u8;
-    let g = (base[1] * brightness * 255.0).clamp(0.0, 255.0) as u8;
-    let b = (base[2] * brightness * 255.0).clamp(0.0, 255.0) as u8;
-    Color::new(r, g, b, 255)
+/// World-space box around a structure mesh's local `bbox` under `pose`.
+pub(super) fn structure_world_bbox(bbox: &BoundingBox, pose: &Pose) -> BoundingBox {
+    if pose.is_upright() && pose.yaw_deg == 0.0 && pose.scale == 1.0 {
+        return BoundingBox {
+            min: bbox.min + vec3_to_rl(pose.pos),
+            max: bbox.max + vec3_to_rl(pose.pos),
+        };
+    }
+    let mut min = Vector3::new(f32::MAX, f32::MAX, f32::MAX);
+    let mut max = Vector3::new(f32::MIN, f32::MIN, f32::MIN);
+    for i in 0..8 {
+        let corner = Vec3::new(
+            if i & 1 == 0 { bbox.min.x } else { bbox.max.x },
+            if i & 2 == 0 { bbox.min.y } else { bbox.max.y },
+            if i & 4 == 0 { bbox.min.z } else { bbox.max.z },
+        );
+        let w = vec3_to_rl(pose.local_to_world(corner));
+        min = Vector3::new(min.x.min(w.x), min.y.min(w.y), min.z.min(w.z));
+        max = Vector3::new(max.x.max(w.x), max.y.max(w.y), max.z.max(w.z));
+    }
+    BoundingBox { min, max }
+}
+
+fn draw_structure_part<D: RaylibDraw3D>(
+    d3: &mut D,
+    part: &ChunkPart,
+    pose: &Pose,
+    offset: Vector3,
+    tint: Color,
+) {
+    let (axis, angle) = pose.rotation().to_axis_angle();
+    let s = pose.scale;
+    part.draw_ex(
+        d3,
+        vec3_to_rl(pose.pos) + offset,
+        vec3_to_rl(axis),
+        angle.to_degrees(),
+        Vector3::new(s, s, s),
+        tint,
+    );
+}
+
+/// Shift immediate-mode draws (lines, cubes, grid) given in world coordinates into the
+/// render space of the floating origin, until `pop_world_space`.
+fn push_world_space(offset: Vector3) {
+    unsafe {
+        raylib::ffi::rlPushMatrix();
+        raylib::ffi::rlTranslatef(offset.x, offset.y, offset.z);
+    }
+}
+
+fn pop_world_space() {
+    unsafe {
+        raylib::ffi::rlPopMatrix();
+    }
 }
 
 impl App {
@@ -47,48 +85,49 @@ impl App {
         camera3d: Camera3D,
         frustum: &Frustum,
         time_now: f32,
+        aspect: f32,
         sky_scale: f32,
-        surface_sky: [f32; 3],
+        sky: &SkyFrame,
         sun_id: Option<StructureId>,
-        sun_tint: Color,
     ) {
+        if let Some(ref mut sr) = self.sky_renderer {
+            sr.draw_background(&mut d.inner, &camera3d, aspect, sky);
+        }
         let mut d3 = d.begin_mode3D(camera3d);
+        if let Some(ref sr) = self.sky_renderer {
+            sr.draw_bodies(&mut d3, camera3d, sky);
+        }
+        // `camera3d` is in render space; world-space geometry is shifted by `offset`.
+        let offset = self.render_origin.offset();
+        let render_cam = self.render_origin.to_render(self.cam.position);
         if self.gs.show_grid {
+            push_world_space(offset);
             d3.draw_grid(64, 1.0);
+            pop_world_space();
         }
 
         let p_cam = self.cam.position;
         let wx = p_cam.x.floor() as i32;
         let wy = p_cam.y.floor() as i32;
         let wz = p_cam.z.floor() as i32;
-        let b_cam = if let Some(edit) = self.gs.edits.get(wx, wy, wz) {
-            edit
-        } else {
-            let sx = self.gs.world.chunk_size_x as i32;
-            let sy = self.gs.world.chunk_size_y as i32;
-            let sz = self.gs.world.chunk_size_z as i32;
-            let cx = wx.div_euclid(sx);
-            let cy = wy.div_euclid(sy);
-            let cz = wz.div_euclid(sz);
-            let coord = ChunkCoord::new(cx, cy, cz);
-            if let Some(cent) = self.gs.chunks.get(&coord) {
-                match (cent.occupancy_or_empty(), cent.buf.as_ref()) {
-                    (ChunkOccupancy::Empty, _) => Block::AIR,
-                    (_, Some(buf)) => buf.get_world(wx, wy, wz).unwrap_or(Block::AIR),
-                    (_, None) => self.gs.world.block_at_runtime(&self.reg, wx, wy, wz),
-                }
-            } else {
-                self.gs.world.block_at_runtime(&self.reg, wx, wy, wz)
-            }
-        };
+        let blocks = self.gs.chunks.blocks();
+        let b_cam = self
+            .gs
+            .edits
+            .get(wx, wy, wz)
+            .or_else(|| blocks.block_at(wx, wy, wz))
+            .unwrap_or_else(|| self.gs.world.block_at_runtime(&self.reg, wx, wy, wz));
         let underwater = self
             .reg
             .get(b_cam.id)
-            .map(|ty| ty.name == "water")
-            .unwrap_or(false);
+            .is_some_and(|ty| ty.fluid_level(b_cam.state).is_some());
 
-        let cave_fog = [0.0, 0.0, 0.0];
-        let water_fog = [0.16, 0.32, 0.45];
+        let ambiance = self.ambiance.current();
+        let cave_fog = ambiance.cave_fog;
+        let water_fog = ambiance.water_fog;
+        let surface_fog_start = ambiance.fog_start;
+        let leaf_palette = ambiance.leaf_palette;
+        let ambiance_vis_min = ambiance.visual_min();
         let world_h = self.gs.world.world_height_hint() as f32;
         let underground_thr = 0.30_f32 * world_h;
         let underground = self.cam.position.y < underground_thr;
@@ -97,64 +136,79 @@ impl App {
         } else if underground {
             cave_fog
         } else {
-            surface_sky
+            sky.fog_color()
         };
-        let fog_start = if underwater { 4.0 } else { 64.0 as f32 };
+        let fog_start = if underwater { 4.0 } else { surface_fog_start };
         let fog_end = if underwater {
             48.0
         } else {
             64.0 * self.gs.view_radius_chunks as f32
         };
+        let water_rgb = self.gs.lighting.water_medium().transmittance;
+        let flicker = self.accessibility.flicker_amount();
         if let Some(ref mut ls) = self.leaves_shader {
+            ls.set_water_transmittance(water_rgb);
+            ls.set_flicker_amount(flicker);
             ls.update_frame_uniforms(
-                self.cam.position,
-                fog_color,
-                fog_start,
-                fog_end,
-                time_now,
-                underwater,
-                sky_scale,
+                render_cam, fog_color, fog_start, fog_end, time_now, underwater, sky_scale,
             );
         }
         if let Some(ref mut fs) = self.fog_shader {
+            fs.set_water_transmittance(water_rgb);
+            fs.set_flicker_amount(flicker);
             fs.update_frame_uniforms(
-                self.cam.position,
-                fog_color,
-                fog_start,
-                fog_end,
-                time_now,
-                underwater,
-                sky_scale,
+                render_cam, fog_color, fog_start, fog_end, time_now, underwater, sky_scale,
             );
         }
         if let Some(ref mut ws) = self.water_shader {
+            ws.set_water_transmittance(water_rgb);
+            ws.set_flicker_amount(flicker);
             ws.update_frame_uniforms(
-                self.cam.position,
-                fog_color,
-                fog_start,
-                fog_end,
-                time_now,
-                underwater,
-                sky_scale,
+                render_cam, fog_color, fog_start, fog_end, time_now, underwater, sky_scale,
             );
         }
 
-        let mut visible_chunks: Vec<(ChunkCoord, f32)> = Vec::new();
+        if let Some(ref arr) = self.block_textures {
+            arr.bind();
+        }
+        if let Some(ref dl) = self.dynamic_light_tex {
+            dl.bind();
+        }
+        if let Some(ref sm) = self.shadow_maps {
+            sm.bind();
+        }
+        let dyn_light = self.dynamic_light_tex.as_ref();
+        let shadows = self.shadow_maps.as_ref();
+        if let Some(ref mut ls) = self.leaves_shader {
+            ls.set_dynamic_light(dyn_light, &self.render_origin);
+            ls.set_shadows(shadows);
+        }
+        if let Some(ref mut fs) = self.fog_shader {
+            fs.set_dynamic_light(dyn_light, &self.render_origin);
+            fs.set_shadows(shadows);
+        }
+        if let Some(ref mut ws) = self.water_shader {
+            ws.set_dynamic_light(dyn_light, &self.render_origin);
+            ws.set_shadows(shadows);
+        }
+
+        let reachable = (self.gs.frustum_culling_enabled && self.gs.occlusion_culling_enabled)
+            .then(|| self.reachable_chunks(frustum));
+        let mut visible_chunks: Vec<ChunkCoord> = Vec::new();
         for (ckey, cr) in self.renders.iter() {
             if self.gs.frustum_culling_enabled && !frustum.contains_bounding_box(&cr.bbox) {
                 self.debug_stats.chunks_culled += 1;
                 continue;
             }
+            if reachable.as_ref().is_some_and(|r| !r.contains(ckey)) {
+                self.debug_stats.chunks_occluded += 1;
+                continue;
+            }
 
             self.debug_stats.chunks_rendered += 1;
-            let center = (cr.bbox.min + cr.bbox.max) * 0.5;
-            let dx = center.x - self.cam.position.x;
-            let dy = center.y - self.cam.position.y;
-            let dz = center.z - self.cam.position.z;
-            let dist2 = dx * dx + dy * dy + dz * dz;
-            visible_chunks.push((*ckey, dist2));
+            visible_chunks.push(*ckey);
             let origin = cr.origin;
-            let vis_min = 18.0f32 / 255.0f32;
+            let vis_min = ambiance_vis_min;
             let (dims_some, grid_some) = if let Some(ref lt) = cr.light_tex {
                 ((lt.sx, lt.sy, lt.sz), (lt.grid_cols, lt.grid_rows))
             } else {
@@ -168,13 +222,8 @@ impl App {
                     let p3 = [t[0] * 0.5, t[1] * 0.5, t[2] * 0.5];
                     ls.set_autumn_palette(p0, p1, p2, p3, 1.0);
                 } else {
-                    ls.set_autumn_palette(
-                        [0.32, 0.55, 0.25],
-                        [0.28, 0.48, 0.22],
-                        [0.20, 0.40, 0.18],
-                        [0.12, 0.28, 0.10],
-                        1.0,
-                    );
+                    let [p0, p1, p2, p3] = leaf_palette;
+                    ls.set_autumn_palette(p0, p1, p2, p3, 1.0);
                 }
             }
             for part in &cr.parts {
@@ -188,30 +237,58 @@ impl App {
                     .materials
                     .get(part.mid)
                     .and_then(|m| m.render_tag.as_deref());
-                if tag != Some("water") {
+                let anim = self
+                    .reg
+                    .materials
+                    .get(part.mid)
+                    .and_then(|m| m.animation.as_ref());
+                let layer = part.block_layer(self.block_textures.as_ref());
+                if part.pass == RenderPass::Opaque {
                     match tag {
                         Some("leaves") => {
                             if let Some(ref mut ls) = self.leaves_shader {
+                                ls.set_block_layer(layer);
+                                ls.set_material_animation(anim);
                                 if let Some(ref lt) = cr.light_tex {
                                     ls.update_chunk_uniforms(
-                                        thread, &lt.tex, dims_some, grid_some, origin, vis_min,
+                                        thread,
+                                        &lt.tex,
+                                        dims_some,
+                                        grid_some,
+                                        part.light_origin(origin),
+                                        vis_min,
                                     );
                                 } else {
                                     ls.update_chunk_uniforms_no_tex(
-                                        thread, dims_some, grid_some, origin, vis_min,
+                                        thread,
+                                        dims_some,
+                                        grid_some,
+                                        part.light_origin(origin),
+                                        vis_min,
                                     );
                                 }
                             }
                         }
                         _ => {
                             if let Some(ref mut fs) = self.fog_shader {
+                                fs.set_block_layer(layer);
+                                fs.set_material_animation(anim);
                                 if let Some(ref lt) = cr.light_tex {
                                     fs.update_chunk_uniforms(
-                                        thread, &lt.tex, dims_some, grid_some, origin, vis_min,
+                                        thread,
+                                        &lt.tex,
+                                        dims_some,
+                                        grid_some,
+                                        part.light_origin(origin),
+                                        vis_min,
                                     );
                                 } else {
                                     fs.update_chunk_uniforms_no_tex(
-                                        thread, dims_some, grid_some, origin, vis_min,
+                                        thread,
+                                        dims_some,
+                                        grid_some,
+                                        part.light_origin(origin),
+                                        vis_min,
                                     );
                                 }
                             }
@@ -219,21 +296,38 @@ impl App {
                     }
                     self.debug_stats.draw_calls += 1;
                     if self.gs.wireframe {
-                        d3.draw_model_wires(&part.model, Vector3::zero(), 1.0, Color::WHITE);
+                        part.draw_wires(&mut d3, offset, Color::WHITE);
                     } else {
-                        d3.draw_model(&part.model, Vector3::zero(), 1.0, Color::WHITE);
+                        part.draw(&mut d3, offset, Color::WHITE);
                     }
                 }
             }
         }
 
-        let mut visible_structs: Vec<(StructureId, f32)> = Vec::new();
+        if let Some(ref mut dr) = self.decoration_renderer {
+            dr.shader.update_frame_uniforms(
+                render_cam,
+                fog_color,
+                fog_start,
+                fog_end,
+                time_now,
+                sky_scale,
+                ambiance_vis_min,
+            );
+            let decorations = visible_chunks
+                .iter()
+                .filter_map(|c| self.renders.get(c))
+                .flat_map(|cr| cr.decorations.iter());
+            let stats = dr.draw(&mut d3, &self.reg, decorations, offset);
+            self.debug_stats.draw_calls += stats.draw_calls;
+            self.debug_stats.total_triangles += stats.triangles;
+            self.debug_stats.decorations_drawn += stats.instances;
+        }
+
+        let mut visible_structs: Vec<StructureId> = Vec::new();
         for (id, cr) in &self.structure_renders {
             if let Some(st) = self.gs.structures.get(id) {
-                let translated_bbox = raylib::core::math::BoundingBox {
-                    min: cr.bbox.min + vec3_to_rl(st.pose.pos),
-                    max: cr.bbox.max + vec3_to_rl(st.pose.pos),
-                };
+                let translated_bbox = structure_world_bbox(&cr.bbox, &st.pose);
 
                 if self.gs.frustum_culling_enabled
                     && !frustum.contains_bounding_box(&translated_bbox)
@@ -243,18 +337,11 @@ impl App {
                 }
 
                 self.debug_stats.structures_rendered += 1;
-                let center = (translated_bbox.min + translated_bbox.max) * 0.5;
-                let dx = center.x - self.cam.position.x;
-                let dy = center.y - self.cam.position.y;
-                let dz = center.z - self.cam.position.z;
-                let dist2 = dx * dx + dy * dy + dz * dz;
-                visible_structs.push((*id, dist2));
-                let origin_world = [
-                    cr.origin[0] + st.pose.pos.x,
-                    cr.origin[1] + st.pose.pos.y,
-                    cr.origin[2] + st.pose.pos.z,
-                ];
-                let vis_min = 18.0f32 / 255.0f32;
+                visible_structs.push(*id);
+                // Structure meshes are in local cells and the shaders sample light in mesh
+                // space, so the grid origin stays local whatever the pose.
+                let light_origin = cr.origin;
+                let vis_min = ambiance_vis_min;
                 let (dims_some, grid_some) = if let Some(ref lt) = cr.light_tex {
                     ((lt.sx, lt.sy, lt.sz), (lt.grid_cols, lt.grid_rows))
                 } else {
@@ -271,17 +358,25 @@ impl App {
                         .materials
                         .get(part.mid)
                         .and_then(|m| m.render_tag.as_deref());
-                    if tag != Some("water") {
+                    let anim = self
+                        .reg
+                        .materials
+                        .get(part.mid)
+                        .and_then(|m| m.animation.as_ref());
+                    let layer = part.block_layer(self.block_textures.as_ref());
+                    if part.pass == RenderPass::Opaque {
                         match tag {
                             Some("leaves") => {
                                 if let Some(ref mut ls) = self.leaves_shader {
+                                    ls.set_block_layer(layer);
+                                    ls.set_material_animation(anim);
                                     if let Some(ref lt) = cr.light_tex {
                                         ls.update_chunk_uniforms(
                                             thread,
                                             &lt.tex,
                                             dims_some,
                                             grid_some,
-                                            origin_world,
+                                            part.light_origin(light_origin),
                                             vis_min,
                                         );
                                     } else {
@@ -289,7 +384,7 @@ impl App {
                                             thread,
                                             dims_some,
                                             grid_some,
-                                            origin_world,
+                                            part.light_origin(light_origin),
                                             vis_min,
                                         );
                                     }
@@ -297,13 +392,15 @@ impl App {
                             }
                             _ => {
                                 if let Some(ref mut fs) = self.fog_shader {
+                                    fs.set_block_layer(layer);
+                                    fs.set_material_animation(anim);
                                     if let Some(ref lt) = cr.light_tex {
                                         fs.update_chunk_uniforms(
                                             thread,
                                             &lt.tex,
                                             dims_some,
                                             grid_some,
-                                            origin_world,
+                                            part.light_origin(light_origin),
                                             vis_min,
                                         );
                                     } else {
@@ -311,7 +408,7 @@ impl App {
                                             thread,
                                             dims_some,
                                             grid_some,
-                                            origin_world,
+                                            part.light_origin(light_origin),
                                             vis_min,
                                         );
                                     }
@@ -320,168 +417,144 @@ impl App {
                         }
                         self.debug_stats.draw_calls += 1;
                         let tint = if Some(*id) == sun_id {
-                            sun_tint
+                            sky.sun_tint()
                         } else {
                             Color::WHITE
                         };
-                        d3.draw_model(&part.model, vec3_to_rl(st.pose.pos), 1.0, tint);
+                        draw_structure_part(&mut d3, part, &st.pose, offset, tint);
                     }
                 }
             }
         }
 
+        // Translucent pass: chunk and structure parts together, farthest first, blended over
+        // the opaque scene without writing depth.
+        let chunks = visible_chunks.iter().filter_map(|c| {
+            self.renders
+                .get(c)
+                .map(|cr| (TranslucentKey::Chunk(*c), cr))
+        });
+        let structs = visible_structs.iter().filter_map(|id| {
+            self.structure_renders
+                .get(id)
+                .map(|cr| (TranslucentKey::Structure(*id), cr))
+        });
+        let order = translucent_back_to_front(
+            chunks.chain(structs),
+            self.cam.position,
+            |key, p| match key {
+                TranslucentKey::Chunk(_) => p,
+                TranslucentKey::Structure(id) => self
+                    .gs
+                    .structures
+                    .get(&id)
+                    .map_or(p, |st| vec3_to_rl(st.pose.local_to_world(vec3_from_rl(p)))),
+            },
+        );
         unsafe {
             raylib::ffi::rlDisableDepthMask();
+            raylib::ffi::rlDisableBackfaceCulling();
         }
-        visible_chunks.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-        for (ckey, _) in &visible_chunks {
-            if let Some(cr) = self.renders.get(ckey) {
-                if self.gs.frustum_culling_enabled && !frustum.contains_bounding_box(&cr.bbox) {
-                    continue;
+        for (key, i) in order {
+            let (cr, pose) = match key {
+                TranslucentKey::Chunk(c) => match self.renders.get(&c) {
+                    Some(cr) => (cr, None),
+                    None => continue,
+                },
+                TranslucentKey::Structure(id) => {
+                    match (self.structure_renders.get(&id), self.gs.structures.get(&id)) {
+                        (Some(cr), Some(st)) => (cr, Some(&st.pose)),
+                        _ => continue,
+                    }
                 }
-                let origin = cr.origin;
-                let vis_min = 18.0f32 / 255.0f32;
-                let (dims_some, grid_some) = if let Some(ref lt) = cr.light_tex {
-                    ((lt.sx, lt.sy, lt.sz), (lt.grid_cols, lt.grid_rows))
-                } else {
-                    ((0, 0, 0), (0, 0))
-                };
-                for part in &cr.parts {
-                    let tag = self
-                        .reg
-                        .materials
-                        .get(part.mid)
-                        .and_then(|m| m.render_tag.as_deref());
-                    if tag == Some("water") {
-                        if let Some(ref mut ws) = self.water_shader {
-                            if let Some(ref lt) = cr.light_tex {
-                                ws.update_chunk_uniforms(
-                                    thread, &lt.tex, dims_some, grid_some, origin, vis_min,
-                                );
-                            } else {
-                                ws.update_chunk_uniforms_no_tex(
-                                    thread, dims_some, grid_some, origin, vis_min,
-                                );
-                            }
-                        }
-                        self.debug_stats.draw_calls += 1;
-                        unsafe {
-                            raylib::ffi::rlDisableBackfaceCulling();
-                        }
-                        d3.draw_model(&part.model, Vector3::zero(), 1.0, Color::WHITE);
-                        unsafe {
-                            raylib::ffi::rlEnableBackfaceCulling();
-                        }
+            };
+            let part = &cr.parts[i];
+            let vis_min = ambiance_vis_min;
+            let (dims_some, grid_some) = if let Some(ref lt) = cr.light_tex {
+                ((lt.sx, lt.sy, lt.sz), (lt.grid_cols, lt.grid_rows))
+            } else {
+                ((0, 0, 0), (0, 0))
+            };
+            let light_origin = part.light_origin(cr.origin);
+            let mat = self.reg.materials.get(part.mid);
+            if mat.and_then(|m| m.render_tag.as_deref()) == Some("water") {
+                if let Some(ref mut ws) = self.water_shader {
+                    if let Some(ref lt) = cr.light_tex {
+                        ws.update_chunk_uniforms(
+                            thread,
+                            &lt.tex,
+                            dims_some,
+                            grid_some,
+                            light_origin,
+                            vis_min,
+                        );
+                    } else {
+                        ws.update_chunk_uniforms_no_tex(
+                            thread,
+                            dims_some,
+                            grid_some,
+                            light_origin,
+                            vis_min,
+                        );
                     }
                 }
+            } else if let Some(ref mut fs) = self.fog_shader {
+                fs.set_block_layer(part.block_layer(self.block_textures.as_ref()));
+                fs.set_material_animation(mat.and_then(|m| m.animation.as_ref()));
+                if let Some(ref lt) = cr.light_tex {
+                    fs.update_chunk_uniforms(
+                        thread,
+                        &lt.tex,
+                        dims_some,
+                        grid_some,
+                        light_origin,
+                        vis_min,
+                    );
+                } else {
+                    fs.update_chunk_uniforms_no_tex(
+                        thread,
+                        dims_some,
+                        grid_some,
+                        light_origin,
+                        vis_min,
+                    );
+                }
             }
-        }
-
-        visible_structs.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-        for (sid, _) in &visible_structs {
-            if let Some(cr) = self.structure_renders.get(sid) {
-                if let Some(st) = self.gs.structures.get(sid) {
-                    let translated_bbox = raylib::core::math::BoundingBox {
-                        min: cr.bbox.min + vec3_to_rl(st.pose.pos),
-                        max: cr.bbox.max + vec3_to_rl(st.pose.pos),
-                    };
-                    if self.gs.frustum_culling_enabled
-                        && !frustum.contains_bounding_box(&translated_bbox)
-                    {
-                        continue;
-                    }
-                    let origin_world = [
-                        cr.origin[0] + st.pose.pos.x,
-                        cr.origin[1] + st.pose.pos.y,
-                        cr.origin[2] + st.pose.pos.z,
-                    ];
-                    let vis_min = 18.0f32 / 255.0f32;
-                    let (dims_some, grid_some) = if let Some(ref lt) = cr.light_tex {
-                        ((lt.sx, lt.sy, lt.sz), (lt.grid_cols, lt.grid_rows))
+            self.debug_stats.draw_calls += 1;
+            match (key, pose) {
+                (TranslucentKey::Structure(id), Some(pose)) => {
+                    let tint = if Some(id) == sun_id {
+                        sky.sun_tint()
                     } else {
-                        ((0, 0, 0), (0, 0))
+                        Color::WHITE
                     };
-                    for part in &cr.parts {
-                        let tag = self
-                            .reg
-                            .materials
-                            .get(part.mid)
-                            .and_then(|m| m.render_tag.as_deref());
-                        if tag == Some("water") {
-                            if let Some(ref mut ws) = self.water_shader {
-                                if let Some(ref lt) = cr.light_tex {
-                                    ws.update_chunk_uniforms(
-                                        thread,
-                                        &lt.tex,
-                                        dims_some,
-                                        grid_some,
-                                        origin_world,
-                                        vis_min,
-                                    );
-                                } else {
-                                    ws.update_chunk_uniforms_no_tex(
-                                        thread,
-                                        dims_some,
-                                        grid_some,
-                                        origin_world,
-                                        vis_min,
-                                    );
-                                }
-                            }
-                            self.debug_stats.draw_calls += 1;
-                            unsafe {
-                                raylib::ffi::rlDisableBackfaceCulling();
-                            }
-                            let tint = if Some(*sid) == sun_id {
-                                sun_tint
-                            } else {
-                                Color::WHITE
-                            };
-                            d3.draw_model(&part.model, vec3_to_rl(st.pose.pos), 1.0, tint);
-                            unsafe {
-                                raylib::ffi::rlEnableBackfaceCulling();
-                            }
-                        }
-                    }
+                    draw_structure_part(&mut d3, part, pose, offset, tint);
                 }
+                _ => part.draw(&mut d3, offset, Color::WHITE),
             }
         }
+        unsafe {
+            raylib::ffi::rlEnableBackfaceCulling();
+        }
         unsafe {
             raylib::ffi::rlEnableDepthMask();
         }
 
+        // Outlines and debug boxes below are drawn at world coordinates.
+        push_world_space(offset);
         let org = self.cam.position;
         let dir = self.cam.forward();
-        let sx = self.gs.world.chunk_size_x as i32;
-        let sy = self.gs.world.chunk_size_y as i32;
-        let sz = self.gs.world.chunk_size_z as i32;
         let sampler = |wx: i32, wy: i32, wz: i32| -> Block {
             if let Some(b) = self.gs.edits.get(wx, wy, wz) {
                 return b;
             }
-            let cx = wx.div_euclid(sx);
-            let cy = wy.div_euclid(sy);
-            let cz = wz.div_euclid(sz);
-            let coord = ChunkCoord::new(cx, cy, cz);
-            if let Some(cent) = self.gs.chunks.get(&coord) {
-                match (cent.occupancy_or_empty(), cent.buf.as_ref()) {
-                    (ChunkOccupancy::Empty, _) => return Block::AIR,
-                    (_, Some(buf)) => {
-                        return buf.get_world(wx, wy, wz).unwrap_or(Block::AIR);
-                    }
-                    (_, None) => {}
-                }
+            if let Some(b) = blocks.block_at(wx, wy, wz) {
+                return b;
             }
             self.gs.world.block_at_runtime(&self.reg, wx, wy, wz)
         };
-        let is_solid = |wx: i32, wy: i32, wz: i32| -> bool {
-            let b = sampler(wx, wy, wz);
-            self.reg
-                .get(b.id)
-                .map(|ty| ty.is_solid(b.state))
-                .unwrap_or(false)
-        };
-        if let Some(hit) = raycast::raycast_first_hit_with_face(org, dir, 5.0, is_solid) {
+        let ray = Ray::new(vec3_from_rl(org), vec3_from_rl(dir), 5.0);
+        if let Some(hit) = raycast_world(&self.reg, &ray, sampler) {
             let (bx, by, bz) = (hit.bx, hit.by, hit.bz);
             let (x0, y0, z0) = (bx as f32, by as f32, bz as f32);
             let (x1, y1, z1) = (x0 + 1.0, y0 + 1.0, z0 + 1.0);
@@ -519,6 +592,14 @@ impl App {
             }
         }
 
+        // Mark where the player was left while the camera spectates
+        if self.gs.spectator && self.gs.walk_mode {
+            let w = &self.gs.walker;
+            let center = Vector3::new(w.pos.x, w.pos.y + w.height * 0.5, w.pos.z);
+            let size = Vector3::new(w.radius * 2.0, w.height, w.radius * 2.0);
+            d3.draw_cube_wires(center, size.x, size.y, size.z, Color::MAGENTA);
+        }
+
         if self.gs.show_chunk_bounds {
             let center_chunk = self.gs.center_chunk;
             for cr in self.renders.values() {
@@ -537,18 +618,48 @@ impl App {
                 let dy = cr.coord.cy - center_chunk.cy;
                 let abs_dy = dy.abs();
                 let alpha = (220 - (abs_dy.min(4) * 30)).clamp(90, 220) as u8;
-                let mut col = if dy > 0 {
-                    Color::new(72, 144, 255, alpha)
-                } else if dy < 0 {
-                    Color::new(255, 140, 88, alpha)
-                } else {
-                    Color::new(255, 64, 32, alpha)
-                };
+                let mut col = self.accessibility.palette.chunk_layer(dy, alpha);
                 if cr.coord == center_chunk {
                     col = Color::YELLOW;
                 }
                 d3.draw_cube_wires(center, size.x, size.y, size.z, col);
             }
         }
+
+        self.draw_falling_blocks(&mut d3);
+        self.draw_actors(&mut d3);
+        self.draw_world_border(&mut d3, self.accessibility.animation_time(time_now));
+        self.draw_lighting_compare(&mut d3);
+        pop_world_space();
+    }
+
+    /// Loaded chunks the camera may see past solid ground and walls.
+    fn reachable_chunks(&self, frustum: &Frustum) -> std::collections::HashSet<ChunkCoord> {
+        let sx = self.gs.world.chunk_size_x as f32;
+        let sy = self.gs.world.chunk_size_y as f32;
+        let sz = self.gs.world.chunk_size_z as f32;
+        let p = self.cam.position;
+        let start = ChunkCoord::new(
+            (p.x / sx).floor() as i32,
+            (p.y / sy).floor() as i32,
+            (p.z / sz).floor() as i32,
+        );
+        let chunk_box = |c: ChunkCoord| {
+            let min = Vector3::new(c.cx as f32 * sx, c.cy as f32 * sy, c.cz as f32 * sz);
+            BoundingBox::new(min, min + Vector3::new(sx, sy, sz))
+        };
+        visible_chunks(
+            start,
+            self.gs.view_radius_chunks + 1,
+            |c| {
+                self.gs.chunks.is_ready(c).then(|| {
+                    self.chunk_visibility
+                        .get(&c)
+                        .copied()
+                        .unwrap_or(ChunkVisibility::OPEN)
+                })
+            },
+            |c| frustum.contains_bounding_box(&chunk_box(c)),
+        )
     }
 }
@@ -81,16 +81,10 @@ impl App {
                     } else {
                         (dist_sq as f32).sqrt() / radius_f
                     };
-                    let mut r = 55.0 + 130.0 * light_heat;
-                    let mut g = 110.0 + 120.0 * mesh_heat;
-                    let mut b = 140.0 + 80.0 * (1.0 - mesh_heat);
-                    if dy > 0 {
-                        b += 45.0;
-                        g += 10.0;
-                    } else if dy < 0 {
-                        r += 50.0;
-                        g -= 15.0;
-                    }
+                    let [mut r, mut g, mut b] = self
+                        .accessibility
+                        .palette
+                        .minimap_rgb(mesh_heat, light_heat, dy);
                     let fade = 0.4 + 0.6 * (1.0 - dist_norm * 0.7);
                     r *= fade;
                     g *= fade;
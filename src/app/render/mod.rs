@@ -1,6 +1,7 @@
 pub(super) use super::{
-    App, DebugOverlayTab, DebugStats, DiagnosticsTab, HitRegion, IRect, TabDefinition, TabStrip,
-    UiTextMeasure, UiTextRenderer, WindowChrome, WindowFrame, WindowId, WindowTheme,
+    App, DebugOverlayTab, DebugStats, DiagnosticsTab, EditStage, HitRegion, IRect, TabDefinition,
+    TabStrip, TabStripHit, TabStripLayout, TabStripState, UiTextMeasure, UiTextRenderer,
+    WindowChrome, WindowFrame, WindowId, WindowTheme,
 };
 
 mod common;
@@ -12,5 +13,5 @@ pub(crate) use common::{ContentLayout, DisplayLine, GeistDraw, draw_lines, forma
 pub(crate) use minimap::{MINIMAP_BORDER_PX, MINIMAP_MAX_CONTENT_SIDE, MINIMAP_MIN_CONTENT_SIDE};
 pub(crate) use views::{
     AttachmentDebugView, ChunkVoxelView, EventHistogramView, IntentHistogramView, RenderStatsView,
-    RuntimeStatsView, TerrainHistogramView,
+    RuntimeStatsView, SchematicLibraryView, SettingsView, TerrainHistogramView,
 };
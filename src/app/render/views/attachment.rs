@@ -137,6 +137,16 @@ impl AttachmentDebugView {
             15,
             Color::new(210, 220, 238, 255),
         ));
+        if app.gs.walker.in_fluid() {
+            lines.push(DisplayLine::new(
+                format!(
+                    "Swimming: {:.0}% submerged",
+                    app.gs.walker.submersion * 100.0
+                ),
+                15,
+                Color::new(150, 200, 240, 255),
+            ));
+        }
 
         if app.gs.structures.is_empty() {
             lines.push(DisplayLine::new(
@@ -173,7 +183,7 @@ impl AttachmentDebugView {
             );
 
             let walker = vec3_from_rl(app.gs.walker.pos);
-            let local = structure_world_to_local(walker, st.pose.pos, st.pose.yaw_deg);
+            let local = structure_world_to_local(walker, &st.pose);
             let test_y = local.y - 0.08;
             let lx = local.x.floor() as i32;
             let ly = test_y.floor() as i32;
@@ -324,7 +334,7 @@ impl AttachmentDebugView {
     }
 
     pub(crate) fn min_size(&self, theme: &WindowTheme) -> (i32, i32) {
-        let height: i32 = self.lines.iter().map(|line| line.line_height).sum();
+        let height: i32 = self.lines.iter().map(DisplayLine::min_height).sum();
         let min_height = theme.titlebar_height + height + theme.padding_y * 2;
         let h = min_height.max(theme.titlebar_height + theme.padding_y * 2 + 240);
         let w = theme.padding_x * 2 + Self::MIN_WIDTH;
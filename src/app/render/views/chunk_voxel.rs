@@ -1,5 +1,6 @@
 use raylib::prelude::Color;
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
 use super::super::{
     App, ContentLayout, DisplayLine, GeistDraw, WindowFrame, WindowTheme, draw_lines, format_count,
@@ -266,6 +267,41 @@ impl ChunkVoxelView {
             }
         };
 
+        let history: Vec<_> = app.rebuild_history.get(center).collect();
+        if !history.is_empty() {
+            let recent = app
+                .rebuild_history
+                .count_within(center, Duration::from_secs(1));
+            lines.push(
+                DisplayLine::new(
+                    format!("Rebuild history ({} in the last second)", recent),
+                    16,
+                    if recent > 2 {
+                        Color::new(255, 170, 140, 255)
+                    } else {
+                        Color::new(220, 232, 250, 255)
+                    },
+                )
+                .with_line_height(24),
+            );
+            let now = Instant::now();
+            for r in history.iter().rev() {
+                lines.push(
+                    DisplayLine::new(
+                        format!(
+                            "  {:>7.2}s ago  {:<15} rev {}",
+                            now.duration_since(r.at).as_secs_f32(),
+                            r.cause.label(),
+                            r.rev
+                        ),
+                        14,
+                        Color::new(198, 208, 226, 255),
+                    )
+                    .with_line_height(18),
+                );
+            }
+        }
+
         Self { lines, subtitle }
     }
 
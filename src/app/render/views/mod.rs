@@ -3,9 +3,13 @@ mod chunk_voxel;
 mod histograms;
 mod render_stats;
 mod runtime_stats;
+mod schematic_library;
+mod settings;
 
 pub(crate) use attachment::AttachmentDebugView;
 pub(crate) use chunk_voxel::ChunkVoxelView;
 pub(crate) use histograms::{EventHistogramView, IntentHistogramView, TerrainHistogramView};
 pub(crate) use render_stats::RenderStatsView;
 pub(crate) use runtime_stats::RuntimeStatsView;
+pub(crate) use schematic_library::SchematicLibraryView;
+pub(crate) use settings::SettingsView;
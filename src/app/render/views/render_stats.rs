@@ -33,9 +33,10 @@ impl RenderStatsView {
         ));
         lines.push(DisplayLine::new(
             format!(
-                "Chunks rendered: {} (culled {})",
+                "Chunks rendered: {} (culled {}, occluded {})",
                 format_count(app.debug_stats.chunks_rendered),
-                format_count(app.debug_stats.chunks_culled)
+                format_count(app.debug_stats.chunks_culled),
+                format_count(app.debug_stats.chunks_occluded)
             ),
             16,
             Color::new(190, 204, 226, 255),
@@ -50,10 +51,39 @@ impl RenderStatsView {
             Color::new(190, 204, 226, 255),
         ));
         lines.push(DisplayLine::new(
-            format!("Draw calls: {}", format_count(app.debug_stats.draw_calls)),
+            format!(
+                "Draw calls: {} | decorations {} | shadow {}",
+                format_count(app.debug_stats.draw_calls),
+                format_count(app.debug_stats.decorations_drawn),
+                format_count(app.debug_stats.shadow_draw_calls)
+            ),
             16,
             Color::new(206, 220, 240, 255),
         ));
+        let pool = geist_mesh_cpu::mesh_pool_stats();
+        lines.push(DisplayLine::new(
+            format!(
+                "Mesh pool: {} reused, {} allocated ({} idle)",
+                format_count(pool.hits as usize),
+                format_count(pool.misses as usize),
+                format_count(pool.pooled)
+            ),
+            16,
+            Color::new(190, 204, 226, 255),
+        ));
+        if let Some(cache) = app.runtime.chunk_cache() {
+            let stats = cache.stats();
+            lines.push(DisplayLine::new(
+                format!(
+                    "Chunk cache: {} loaded, {} generated ({} unsaved)",
+                    format_count(stats.hits as usize),
+                    format_count(stats.misses as usize),
+                    format_count(stats.pending)
+                ),
+                16,
+                Color::new(190, 204, 226, 255),
+            ));
+        }
         let center = app.gs.center_chunk;
         lines.push(DisplayLine::new(
             format!(
@@ -63,16 +93,26 @@ impl RenderStatsView {
             16,
             Color::new(188, 198, 214, 255),
         ));
-        if app.gs.show_biome_label {
-            let wx = app.cam.position.x.floor() as i32;
-            let wz = app.cam.position.z.floor() as i32;
-            if let Some(biome) = app.gs.world.biome_at(wx, wz) {
-                lines.push(DisplayLine::new(
-                    format!("Biome: {}", biome.name),
-                    16,
-                    Color::new(188, 198, 214, 255),
-                ));
-            }
+        let p = app.cam.position;
+        let (wx, wy, wz) = (p.x.floor() as i32, p.y.floor() as i32, p.z.floor() as i32);
+        if let Some(light) = app.gs.lighting.sample_world(wx, wy, wz) {
+            lines.push(DisplayLine::new(
+                format!(
+                    "Light: sky {}  block {}  beacon {}",
+                    light.sky, light.block, light.beacon
+                ),
+                16,
+                Color::new(188, 198, 214, 255),
+            ));
+        }
+        if app.gs.show_biome_label
+            && let Some(biome) = app.gs.world.biome_at(wx, wz)
+        {
+            lines.push(DisplayLine::new(
+                format!("Biome: {}", biome.name),
+                16,
+                Color::new(188, 198, 214, 255),
+            ));
         }
 
         Self {
@@ -82,7 +122,7 @@ impl RenderStatsView {
     }
 
     pub(crate) fn min_size(&self, theme: &WindowTheme) -> (i32, i32) {
-        let height: i32 = self.lines.iter().map(|line| line.line_height).sum();
+        let height: i32 = self.lines.iter().map(DisplayLine::min_height).sum();
         let min_height = theme.titlebar_height + height + theme.padding_y * 2;
         let h = min_height.max(theme.titlebar_height + theme.padding_y * 2 + 160);
         let w = theme.padding_x * 2 + Self::MIN_WIDTH;
@@ -1,8 +1,10 @@
+use geist_ui::{TextAlign, TextSpan, TextTable};
 use raylib::prelude::Color;
 use std::collections::VecDeque;
 
 use super::super::{
-    App, ContentLayout, DisplayLine, GeistDraw, WindowFrame, WindowTheme, draw_lines, format_count,
+    App, ContentLayout, DisplayLine, EditStage, GeistDraw, WindowFrame, WindowTheme, draw_lines,
+    format_count,
 };
 
 pub(crate) struct RuntimeStatsView {
@@ -40,22 +42,106 @@ impl RuntimeStatsView {
             Color::new(176, 192, 214, 255),
         ));
 
-        let (q_e, if_e, q_l, if_l, q_b, if_b) = app.runtime.queue_debug_counts();
+        let counts = app.runtime.queue_debug_counts();
+        let (q_e, if_e) = (counts.queued_edit, counts.inflight_edit);
+        let (q_l, if_l) = (counts.queued_light, counts.inflight_light);
+        let (q_b, if_b) = (counts.queued_bg, counts.inflight_bg);
+        let (q_s, if_s) = (counts.queued_structure, counts.inflight_structure);
+        let (q_t, if_t) = (counts.queued_tick, counts.inflight_tick);
         lines.push(
             DisplayLine::new("Runtime queues", 17, Color::new(214, 226, 246, 255))
                 .with_line_height(22),
         );
+        let row_color = Color::new(186, 200, 222, 255);
+        let mut queues = TextTable::new(["Lane", "Queued", "In flight", "Workers"])
+            .align(1, TextAlign::Right)
+            .align(2, TextAlign::Right)
+            .align(3, TextAlign::Right);
+        for (lane, queued, inflight, workers) in [
+            ("Edit", q_e, if_e, app.runtime.w_edit),
+            ("Light", q_l, if_l, app.runtime.w_light),
+            ("Background", q_b, if_b, app.runtime.w_bg),
+            ("Structures", q_s, if_s, app.runtime.w_struct),
+            ("Block ticks", q_t, if_t, 1),
+        ] {
+            queues.push_row([
+                TextSpan::new(lane, row_color),
+                TextSpan::new(format_count(queued), row_color),
+                TextSpan::new(format_count(inflight), row_color),
+                TextSpan::new(workers.to_string(), row_color),
+            ]);
+        }
+        lines.push(DisplayLine::table(queues, 15, Color::new(150, 168, 196, 255)).with_indent(18));
+        let starved = if counts.light_starved_windows > 0 {
+            Color::new(255, 170, 110, 255)
+        } else {
+            row_color
+        };
+        lines.push(
+            DisplayLine::spans(
+                vec![
+                    TextSpan::new(
+                        format!(
+                            "Light fairness: boosted {} | ",
+                            format_count(counts.light_boosted as usize)
+                        ),
+                        row_color,
+                    ),
+                    TextSpan::new(
+                        format!(
+                            "starved windows {}",
+                            format_count(counts.light_starved_windows as usize)
+                        ),
+                        starved,
+                    ),
+                ],
+                15,
+            )
+            .with_indent(18),
+        );
         lines.push(
             DisplayLine::new(
-                format!("Edit: queued {} | inflight {}", q_e, if_e),
+                format!(
+                    "Out-of-range builds dropped: {} before meshing | {} after",
+                    format_count(counts.dropped_on_worker as usize),
+                    format_count(counts.dropped_on_drain as usize)
+                ),
                 15,
-                Color::new(186, 200, 222, 255),
+                row_color,
+            )
+            .with_indent(18),
+        );
+        lines.push(
+            DisplayLine::new(
+                format!(
+                    "Actors: {} in {} chunks | {} in view chunk",
+                    format_count(app.actors.len()),
+                    format_count(app.actors.occupied_chunks().count()),
+                    format_count(app.actors_in_chunk(app.gs.center_chunk).count())
+                ),
+                15,
+                row_color,
             )
             .with_indent(18),
         );
+
+        let cache = app.runtime.column_cache_stats();
+        lines.push(
+            DisplayLine::new("Column cache", 17, Color::new(214, 226, 246, 255))
+                .with_line_height(22),
+        );
+        let hit_rate = cache
+            .hit_rate()
+            .map(|r| format!("{:.1}%", r * 100.0))
+            .unwrap_or_else(|| "-".to_string());
         lines.push(
             DisplayLine::new(
-                format!("Light: queued {} | inflight {}", q_l, if_l),
+                format!(
+                    "Hit rate {} | hits {} | misses {}",
+                    hit_rate,
+                    format_count(cache.hits as usize),
+                    format_count(cache.misses as usize)
+                ),
                 15,
                 Color::new(186, 200, 222, 255),
             )
@@ -63,13 +149,68 @@ impl RuntimeStatsView {
         );
         lines.push(
             DisplayLine::new(
-                format!("Background: queued {} | inflight {}", q_b, if_b),
+                format!(
+                    "Entries {}/{} | evictions {} | {:.1} MiB",
+                    format_count(cache.entries),
+                    format_count(cache.capacity),
+                    format_count(cache.evictions as usize),
+                    cache.bytes as f64 / (1024.0 * 1024.0)
+                ),
                 15,
                 Color::new(186, 200, 222, 255),
             )
             .with_indent(18),
         );
 
+        let revs = app.gs.chunks.worldgen_revs();
+        let worldgen_rev = app.gs.world.current_worldgen_rev();
+        let stale = revs.stale_count(worldgen_rev);
+        lines.push(
+            DisplayLine::new(
+                format!(
+                    "Worldgen rev {} | stale chunks {}/{}",
+                    worldgen_rev,
+                    format_count(stale),
+                    format_count(revs.len())
+                ),
+                15,
+                if stale > 0 {
+                    Color::new(255, 196, 110, 255)
+                } else {
+                    Color::new(186, 200, 222, 255)
+                },
+            )
+            .with_indent(18),
+        );
+
+        if app.runtime.gen_determinism_check() {
+            let text = Color::new(186, 200, 222, 255);
+            let diverged = if app.gen_divergences > 0 {
+                Color::new(255, 120, 110, 255)
+            } else {
+                text
+            };
+            lines.push(
+                DisplayLine::spans(
+                    vec![
+                        TextSpan::new(
+                            format!(
+                                "Gen determinism: checked {} | ",
+                                format_count(app.runtime.gen_determinism_checked() as usize)
+                            ),
+                            text,
+                        ),
+                        TextSpan::new(
+                            format!("diverged {}", format_count(app.gen_divergences)),
+                            diverged,
+                        ),
+                    ],
+                    15,
+                )
+                .with_indent(18),
+            );
+        }
+
         lines.push(
             DisplayLine::new("Chunk residency", 17, Color::new(214, 226, 246, 255))
                 .with_line_height(22),
@@ -129,6 +270,29 @@ impl RuntimeStatsView {
             )
             .with_indent(18),
         );
+        let seam_line = match app.debug_stats.lighting_last_seam {
+            Some((m, at)) => format!(
+                "Seam mismatches {} | last ({}, {}, {}) {} {:.1}s ago",
+                format_count(app.debug_stats.lighting_seam_mismatches as usize),
+                m.coord.cx,
+                m.coord.cy,
+                m.coord.cz,
+                m.face.as_str(),
+                at.elapsed().as_secs_f32()
+            ),
+            None => "Seam mismatches 0".to_string(),
+        };
+        // A mismatch in the last few seconds is drawn as a warning.
+        let recent = app
+            .debug_stats
+            .lighting_last_seam
+            .is_some_and(|(_, at)| at.elapsed().as_secs_f32() < 5.0);
+        let seam_color = if recent {
+            Color::new(255, 170, 120, 255)
+        } else {
+            Color::new(180, 196, 222, 255)
+        };
+        lines.push(DisplayLine::new(seam_line, 15, seam_color).with_indent(18));
 
         lines.push(
             DisplayLine::new("Edit store", 17, Color::new(214, 226, 246, 255)).with_line_height(22),
@@ -191,17 +355,71 @@ impl RuntimeStatsView {
             ("Load", avg_gen, p95_gen, n_gen, Some(last_gen)),
         ];
 
+        let row_color = Color::new(172, 190, 218, 255);
+        let mut perf = TextTable::new(["Stage", "Last", "Avg", "p95", "n"]);
+        for col in 1..5 {
+            perf = perf.align(col, TextAlign::Right);
+        }
         for (label, avg, p95, n, last) in perf_lines {
-            let text = if let Some(last_ms) = last {
+            perf.push_row(
+                [
+                    label.to_string(),
+                    last.map_or_else(|| "-".to_string(), |ms| ms.to_string()),
+                    avg.to_string(),
+                    p95.to_string(),
+                    n.to_string(),
+                ]
+                .map(|cell| TextSpan::new(cell, row_color)),
+            );
+        }
+        lines.push(DisplayLine::table(perf, 15, Color::new(150, 168, 196, 255)).with_indent(18));
+
+        lines.push(
+            DisplayLine::new("Edit latency (ms)", 17, Color::new(214, 226, 246, 255))
+                .with_line_height(22),
+        );
+        let e2e = app.edit_latency.end_to_end();
+        lines.push(
+            DisplayLine::new(
                 format!(
-                    "{}: last {} | avg {} | p95 {} | n {}",
-                    label, last_ms, avg, p95, n
-                )
-            } else {
-                format!("{}: avg {} | p95 {} | n {}", label, avg, p95, n)
-            };
-            lines.push(DisplayLine::new(text, 15, Color::new(172, 190, 218, 255)).with_indent(18));
+                    "Click->Visible: p50 {} | p95 {} | p99 {} | max {} | n {}",
+                    e2e.p50, e2e.p95, e2e.p99, e2e.max, e2e.n
+                ),
+                15,
+                Color::new(196, 212, 236, 255),
+            )
+            .with_indent(18)
+            .wrapped(),
+        );
+        let mut stages = TextTable::new(["Stage", "p50", "p95", "p99"]);
+        for col in 1..4 {
+            stages = stages.align(col, TextAlign::Right);
+        }
+        for stage in EditStage::ALL {
+            let s = app.edit_latency.stage(stage);
+            stages.push_row(
+                [
+                    stage.label().to_string(),
+                    s.p50.to_string(),
+                    s.p95.to_string(),
+                    s.p99.to_string(),
+                ]
+                .map(|cell| TextSpan::new(cell, row_color)),
+            );
         }
+        lines.push(DisplayLine::table(stages, 15, Color::new(150, 168, 196, 255)).with_indent(30));
+        lines.push(
+            DisplayLine::new(
+                format!(
+                    "Pending traces {} | dropped {}",
+                    app.edit_latency.pending_len(),
+                    app.edit_latency.dropped()
+                ),
+                15,
+                Color::new(172, 190, 218, 255),
+            )
+            .with_indent(18),
+        );
 
         let total_queue = q_e + q_l + q_b;
         let subtitle = Some(format!(
@@ -214,7 +432,7 @@ impl RuntimeStatsView {
     }
 
     pub(crate) fn min_size(&self, theme: &WindowTheme) -> (i32, i32) {
-        let height: i32 = self.lines.iter().map(|line| line.line_height).sum();
+        let height: i32 = self.lines.iter().map(DisplayLine::min_height).sum();
         let min_height = theme.titlebar_height + height + theme.padding_y * 2;
         let h = min_height.max(theme.titlebar_height + theme.padding_y * 2 + 220);
         let w = theme.padding_x * 2 + Self::MIN_WIDTH;
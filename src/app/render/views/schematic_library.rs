@@ -0,0 +1,117 @@
+use raylib::prelude::Color;
+
+use super::super::{
+    App, ContentLayout, DisplayLine, GeistDraw, WindowFrame, WindowTheme, draw_lines, format_count,
+};
+
+pub(crate) struct SchematicLibraryView {
+    lines: Vec<DisplayLine>,
+    subtitle: Option<String>,
+}
+
+impl SchematicLibraryView {
+    const MIN_WIDTH: i32 = 320;
+
+    pub(crate) fn new(app: &App) -> Self {
+        let mut lines = Vec::new();
+        let Some(lib) = app.schematic_library.as_ref() else {
+            lines.push(
+                DisplayLine::new(
+                    "Schematics directory could not be indexed",
+                    16,
+                    Color::new(210, 220, 240, 255),
+                )
+                .with_line_height(22),
+            );
+            return Self {
+                lines,
+                subtitle: Some("unavailable".to_string()),
+            };
+        };
+        let entries = lib.entries();
+        if entries.is_empty() {
+            lines.push(
+                DisplayLine::new(
+                    format!("No schematics in {}", lib.dir().display()),
+                    16,
+                    Color::new(210, 220, 240, 255),
+                )
+                .with_line_height(22),
+            );
+            return Self {
+                lines,
+                subtitle: Some("empty".to_string()),
+            };
+        }
+
+        let selected = app.schematic_selected.min(entries.len() - 1);
+        lines.push(
+            DisplayLine::new(
+                "PgUp/PgDn select, F8 paste ahead",
+                14,
+                Color::new(170, 184, 210, 255),
+            )
+            .with_line_height(22),
+        );
+        for (i, meta) in entries.iter().enumerate() {
+            let is_sel = i == selected;
+            let color = if is_sel {
+                Color::new(255, 226, 140, 255)
+            } else if meta.is_fully_supported() {
+                Color::new(220, 230, 245, 255)
+            } else {
+                Color::new(232, 176, 160, 255)
+            };
+            let (sx, sy, sz) = meta.size;
+            lines.push(
+                DisplayLine::new(
+                    format!(
+                        "{} {}  {}x{}x{}  {} blocks",
+                        if is_sel { ">" } else { " " },
+                        meta.name(),
+                        sx,
+                        sy,
+                        sz,
+                        format_count(meta.block_count as usize)
+                    ),
+                    16,
+                    color,
+                )
+                .with_line_height(20),
+            );
+            if is_sel {
+                let detail = if meta.is_fully_supported() {
+                    format!("{} distinct blocks, all mapped", meta.distinct_blocks)
+                } else {
+                    format!(
+                        "{} distinct blocks, {} unsupported ({})",
+                        meta.distinct_blocks,
+                        format_count(meta.unsupported_blocks as usize),
+                        meta.unsupported_ids.join(", ")
+                    )
+                };
+                lines.push(
+                    DisplayLine::new(detail, 14, Color::new(190, 200, 222, 255))
+                        .with_line_height(20),
+                );
+            }
+        }
+
+        let subtitle = Some(format!("{} / {}", selected + 1, entries.len()));
+        Self { lines, subtitle }
+    }
+
+    pub(crate) fn min_size(&self, theme: &WindowTheme) -> (i32, i32) {
+        let h = theme.titlebar_height + theme.padding_y * 2 + 160;
+        let w = theme.padding_x * 2 + Self::MIN_WIDTH;
+        (w, h)
+    }
+
+    pub(crate) fn subtitle(&self) -> Option<&str> {
+        self.subtitle.as_deref()
+    }
+
+    pub(crate) fn draw(&self, d: &mut GeistDraw, frame: &WindowFrame) -> ContentLayout {
+        draw_lines(d, &self.lines, frame)
+    }
+}
@@ -0,0 +1,111 @@
+use raylib::prelude::Color;
+
+use super::super::{
+    App, ContentLayout, DisplayLine, GeistDraw, WindowFrame, WindowTheme, draw_lines,
+};
+use geist_render_raylib::UpscaleFilter;
+
+pub(crate) struct SettingsView {
+    lines: Vec<DisplayLine>,
+    subtitle: Option<String>,
+}
+
+impl SettingsView {
+    const MIN_WIDTH: i32 = 320;
+
+    pub(crate) fn new(app: &App, screen: (i32, i32)) -> Self {
+        let label = Color::new(210, 220, 240, 255);
+        let hint = Color::new(170, 184, 210, 255);
+        let muted = Color::new(150, 160, 182, 255);
+        let target = &app.scene_target;
+        let (w, h) = target.scaled_size(screen);
+        let mut lines = vec![
+            DisplayLine::new("F10 / Shift+F10 render scale, F11 upscale filter", 14, hint)
+                .with_line_height(22),
+            DisplayLine::new(
+                format!("Render scale  {:.2}x  ({}x{})", target.scale(), w, h),
+                16,
+                label,
+            )
+            .with_line_height(22),
+        ];
+        let filter = if target.is_direct() {
+            "Upscale       off (native resolution)".to_string()
+        } else if target.filter() == UpscaleFilter::Sharpen && app.sharpen_shader.is_none() {
+            "Upscale       sharpen unavailable, using bilinear".to_string()
+        } else {
+            format!("Upscale       {}", target.filter().label())
+        };
+        lines.push(DisplayLine::new(filter, 16, label).with_line_height(22));
+        let msaa = match (app.msaa, target.is_direct()) {
+            (false, _) => "MSAA          off (--no-msaa)",
+            (true, true) => "MSAA          4x",
+            (true, false) => "MSAA          4x, inactive while scaled",
+        };
+        lines.push(DisplayLine::new(msaa, 16, label).with_line_height(22));
+        lines.push(DisplayLine::new("MSAA is chosen at startup", 14, muted).with_line_height(20));
+        lines.push(
+            DisplayLine::new("J shadow cascades, Shift+J shadow resolution", 14, hint)
+                .with_line_height(28),
+        );
+        let shadows = match &app.shadow_maps {
+            Some(sm) => format!("Shadows       {}", sm.settings().label()),
+            None => "Shadows       unavailable".to_string(),
+        };
+        lines.push(DisplayLine::new(shadows, 16, label).with_line_height(22));
+
+        let access = &app.accessibility;
+        lines.push(
+            DisplayLine::new(
+                "F5 / Shift+F5 UI scale, F2 overlay palette, F1 reduced flashing",
+                14,
+                hint,
+            )
+            .with_line_height(28),
+        );
+        lines.push(
+            DisplayLine::new(format!("UI scale      {:.2}x", access.ui_scale), 16, label)
+                .with_line_height(22),
+        );
+        lines.push(
+            DisplayLine::new(
+                format!("Palette       {}", access.palette.label()),
+                16,
+                label,
+            )
+            .with_line_height(22),
+        );
+        let flashing = if access.reduced_flashing {
+            "Flashing      reduced (steady emitters, still overlays)"
+        } else {
+            "Flashing      full"
+        };
+        lines.push(DisplayLine::new(flashing, 16, label).with_line_height(22));
+        let saved = match &app.settings_path {
+            Some(path) => format!("Saved to {}", path.display()),
+            None => "Not saved (no settings file)".to_string(),
+        };
+        lines.push(DisplayLine::new(saved, 14, muted).with_line_height(20));
+
+        let subtitle = Some(format!(
+            "{:.2}x {}",
+            target.scale(),
+            target.filter().label()
+        ));
+        Self { lines, subtitle }
+    }
+
+    pub(crate) fn min_size(&self, theme: &WindowTheme) -> (i32, i32) {
+        let h = theme.titlebar_height + theme.padding_y * 2 + 290;
+        let w = theme.padding_x * 2 + Self::MIN_WIDTH;
+        (w, h)
+    }
+
+    pub(crate) fn subtitle(&self) -> Option<&str> {
+        self.subtitle.as_deref()
+    }
+
+    pub(crate) fn draw(&self, d: &mut GeistDraw, frame: &WindowFrame) -> ContentLayout {
+        draw_lines(d, &self.lines, frame)
+    }
+}
@@ -1,20 +1,82 @@
 use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
 
 use super::App;
 use super::state::{IntentCause, IntentEntry};
 use crate::event::{Event, RebuildCause};
 use crate::gamestate::FinalizeState;
-use geist_lighting::LightAtlas;
+use geist_io::ChunkCache;
+use geist_lighting::{LightAtlas, LightingStore};
 use geist_mesh_cpu::NeighborsLoaded;
-use geist_world::ChunkCoord;
+use geist_runtime::Runtime;
+use geist_world::{ChunkCoord, World};
 
 // Scheduling/queue tuning knobs
 // Increase per-frame submissions and per-lane queue headroom so workers stay busier.
 const JOB_FRAME_CAP_MULT: usize = 4; // was 2
 const LANE_QUEUE_EXTRA: usize = 3; // was 1 (target = workers + extra)
 const PERF_WIN_CAP: usize = 200; // rolling window size for perf stats
+const SHUTDOWN_DEADLINE: Duration = Duration::from_secs(2);
 
 impl App {
+    /// Flush unsaved edits and stop the worker pools on exit, letting in-flight jobs finish
+    /// within a short deadline.
+    pub fn shutdown(&mut self) {
+        self.flush_edits();
+        let report = self.runtime.shutdown(SHUTDOWN_DEADLINE);
+        log::info!(
+            "runtime shutdown: {} results collected, {} structure results, {} jobs cancelled, {} structure jobs cancelled",
+            report.completed.len(),
+            report.completed_structures.len(),
+            report.cancelled.len(),
+            report.cancelled_structures.len()
+        );
+        if !report.clean() {
+            log::warn!(
+                "runtime shutdown: {} workers still busy after {:?}; their results are lost",
+                report.abandoned_workers,
+                SHUTDOWN_DEADLINE
+            );
+        }
+        if let Some(cache) = self.runtime.chunk_cache() {
+            match cache.flush() {
+                Ok(regions) => log::info!("chunk cache: wrote {} region files", regions),
+                Err(e) => log::error!("chunk cache flush to {:?} failed: {}", cache.dir(), e),
+            }
+        }
+    }
+
+    /// Swap in a runtime building `world` lit by `lighting`, carrying over the debug
+    /// settings. The old runtime is shut down and its results dropped; its chunk cache, if
+    /// any, is flushed and returned.
+    pub(super) fn replace_runtime(
+        &mut self,
+        world: Arc<World>,
+        lighting: Arc<LightingStore>,
+        chunk_cache: Option<Arc<ChunkCache>>,
+    ) -> Option<Arc<ChunkCache>> {
+        let mut old = std::mem::replace(&mut self.runtime, Runtime::new(world, lighting));
+        self.runtime
+            .set_gen_determinism_check(old.gen_determinism_check());
+        self.runtime.set_chunk_cache(chunk_cache);
+        let report = old.shutdown(SHUTDOWN_DEADLINE);
+        if !report.clean() {
+            log::warn!(
+                "replaced runtime: {} workers still busy after {:?}",
+                report.abandoned_workers,
+                SHUTDOWN_DEADLINE
+            );
+        }
+        let cache = old.chunk_cache();
+        if let Some(cache) = &cache
+            && let Err(e) = cache.flush()
+        {
+            log::error!("chunk cache flush to {:?} failed: {}", cache.dir(), e);
+        }
+        cache
+    }
+
     #[inline]
     pub(super) fn perf_push(q: &mut VecDeque<u32>, v: u32) {
         q.push_back(v);
@@ -254,7 +316,10 @@ impl App {
         let mut submitted = 0usize;
         let mut submitted_keys: Vec<ChunkCoord> = Vec::new();
 
-        let (q_e, if_e, q_l, if_l, q_b, if_b) = self.runtime.queue_debug_counts();
+        let counts = self.runtime.queue_debug_counts();
+        let (q_e, if_e) = (counts.queued_edit, counts.inflight_edit);
+        let (q_l, if_l) = (counts.queued_light, counts.inflight_light);
+        let (q_b, if_b) = (counts.queued_bg, counts.inflight_bg);
         let target_edit = self.runtime.w_edit.max(1) + LANE_QUEUE_EXTRA;
         let target_light = self.runtime.w_light.max(1) + LANE_QUEUE_EXTRA;
         let target_bg = self.runtime.w_bg.max(1) + LANE_QUEUE_EXTRA;
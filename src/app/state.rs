@@ -4,23 +4,44 @@ use std::sync::Arc;
 use std::sync::mpsc::Receiver;
 use std::time::Instant;
 
-use geist_blocks::{Block, BlockRegistry};
-use geist_lighting::{LightBorders, LightGrid};
-use geist_render_raylib::{ChunkRender, FogShader, LeavesShader, TextureCache, WaterShader};
-use geist_runtime::Runtime;
-use geist_structures::StructureId;
+use geist_actors::Actors;
+use geist_blocks::BlockRegistry;
+use geist_io::SchematicLibrary;
+use geist_lighting::{DynamicLightId, DynamicLights, LightBorders, LightGrid, SeamMismatch};
+use geist_render_raylib::{
+    BlockTextureArray, ChunkRender, ChunkVisibility, DecorationRenderer, DynamicLightTex,
+    FloatingOrigin, FogShader, LeavesShader, SceneTarget, ShadowMaps, SharpenShader, SkyRenderer,
+    TextureCache, WaterShader, WideIndices,
+};
+use geist_runtime::{BatchId, BlockTickHandlers, BlockTickScheduler, FluidSim, Runtime};
+use geist_structures::{FallingBlocks, LocalEmitter, SectionCoord, StructureId};
 use geist_world::{ChunkCoord, TERRAIN_STAGE_COUNT};
-use raylib::prelude::{Font, MouseButton, RenderTexture2D, Vector2, Vector3};
+use raylib::prelude::{Color, Font, MouseButton, RenderTexture2D, Vector2, Vector3};
 
 use crate::camera::FlyCamera;
 use crate::event::EventQueue;
 use crate::gamestate::GameState;
 
-use super::{DayCycle, DayLightSample, HitRegion, OverlayWindowManager, SunBody, WindowId};
+use super::border::WorldBorder;
+use super::dimensions::DimensionState;
+use super::{
+    AccessibilitySettings, Ambiance, DayCycle, DayLightSample, EditLatencyTracker, HitRegion,
+    Hotbar, LightingCompare, OverlayWindowManager, RebuildHistory, SunBody, TabStripState,
+    WindowId,
+};
 
 pub(crate) const STREAM_LOAD_SHELLS: i32 = 1;
 pub(crate) const STREAM_EVICT_SHELLS: i32 = 2;
 
+/// A structure's light sources and where they are registered in the world.
+pub(crate) struct StructureEmitters {
+    /// Emissive cells in structure-local space, patched as the structure is edited.
+    pub local: Vec<LocalEmitter>,
+    /// World voxels currently registered with the lighting store.
+    pub world: Vec<(i32, i32, i32)>,
+    pub synced_at: Option<Instant>,
+}
+
 pub struct App {
     pub gs: GameState,
     pub queue: EventQueue,
@@ -31,15 +52,75 @@ pub struct App {
     pub day_sample: DayLightSample,
     pub sun: Option<SunBody>,
     pub schem_orbits: Vec<SchematicOrbit>,
-    pub(crate) hotbar: Vec<Block>,
+    // Blocks on keys 1-9 and the mouse wheel; the selected one is `gs.place_type`.
+    pub(crate) hotbar: Hotbar,
     pub leaves_shader: Option<LeavesShader>,
     pub fog_shader: Option<FogShader>,
     pub water_shader: Option<WaterShader>,
+    // GL compatibility mode: custom shaders failed, chunks use raylib's default
+    // material with light baked into vertex colors on the CPU.
+    pub(crate) shader_compat: bool,
+    pub(crate) toast: Option<Toast>,
     pub tex_cache: TextureCache,
+    // Same-size block textures stacked into one GL array (opt-in via --texture-array).
+    pub(crate) block_textures: Option<BlockTextureArray>,
+    // Materials past 65k vertices upload whole with 32-bit indices (opt-in via --wide-indices).
+    pub(crate) wide_indices: Option<WideIndices>,
+    // Moving point lights composited in the shaders over chunk light (hand torch: T).
+    pub(crate) dynamic_lights: DynamicLights,
+    pub(crate) dynamic_light_tex: Option<DynamicLightTex>,
+    // Gravity blocks between leaving their cell and landing, with their draw colors by id.
+    pub(crate) falling: FallingBlocks,
+    pub(crate) falling_colors: HashMap<u16, Color>,
+    // Props, NPCs and projectiles, indexed by the chunk they live in.
+    pub(crate) actors: Actors,
+    // Cells where fluid may flow next, and time banked towards the next fluid tick.
+    pub(crate) fluids: FluidSim,
+    pub(crate) fluid_tick_accum: f32,
+    // Block tick handlers by type, positions due, the tick clock and the job in flight.
+    pub(crate) block_tick_handlers: Arc<BlockTickHandlers>,
+    pub(crate) block_ticks: BlockTickScheduler,
+    pub(crate) block_tick: u64,
+    pub(crate) block_tick_accum: f32,
+    pub(crate) block_tick_inflight: Option<u64>,
+    // Bounds streaming, the player and structures stay inside; `None` for an open world.
+    pub(crate) world_border: Option<WorldBorder>,
+    pub(crate) hand_torch: Option<DynamicLightId>,
+    // Indexed schematics directory for the library browser (PageUp/PageDown select, F8 paste).
+    pub(crate) schematic_library: Option<SchematicLibrary>,
+    pub(crate) schematic_selected: usize,
+    // Integer origin near the camera that 3D drawing is relative to, so vertices stay
+    // precise far from the world origin.
+    pub(crate) render_origin: FloatingOrigin,
+    // Internal resolution the scene renders at and how it is upscaled (Settings window:
+    // F10/Shift+F10 scale, F11 filter).
+    pub(crate) scene_target: SceneTarget,
+    pub(crate) sharpen_shader: Option<SharpenShader>,
+    // Sky gradient, stars and the sun glow and moon; without it the frame is cleared to
+    // the horizon color.
+    pub(crate) sky_renderer: Option<SkyRenderer>,
+    // Instanced grass and other decoration blocks; without it they are not drawn.
+    pub(crate) decoration_renderer: Option<DecorationRenderer>,
+    // Sun shadow cascades (Settings window: J cascades, Shift+J resolution); `None` when
+    // the voxel or depth shaders are unavailable.
+    pub(crate) shadow_maps: Option<ShadowMaps>,
+    // Whether the window was created with 4x MSAA (--no-msaa); fixed for the session.
+    pub(crate) msaa: bool,
+    // UI scale, overlay palette and reduced flashing (Settings window: F5/Shift+F5, F2, F1),
+    // written back to `settings_path` whenever they change.
+    pub(crate) accessibility: AccessibilitySettings,
+    pub(crate) settings_path: Option<PathBuf>,
     pub renders: HashMap<ChunkCoord, ChunkRender>,
+    // Which faces of each loaded, non-empty chunk see through to which, for occlusion culling.
+    pub(crate) chunk_visibility: HashMap<ChunkCoord, ChunkVisibility>,
     pub structure_renders: HashMap<StructureId, ChunkRender>,
+    // Section each part of the matching `structure_renders` entry was meshed from, index
+    // for index, so a partial rebuild can swap out just the stale parts.
+    pub(crate) structure_part_sections: HashMap<StructureId, Vec<SectionCoord>>,
     pub structure_lights: HashMap<StructureId, LightGrid>,
     pub structure_light_borders: HashMap<StructureId, LightBorders>,
+    // Structure emitters mirrored into the world lighting store.
+    pub(crate) structure_emitters: HashMap<StructureId, StructureEmitters>,
     pub ui_font: Option<Arc<Font>>,
     pub minimap_rt: Option<RenderTexture2D>,
     pub minimap_zoom: f32,
@@ -54,16 +135,29 @@ pub struct App {
     pub overlay_hover: Option<(WindowId, HitRegion)>,
     pub overlay_debug_tab: DebugOverlayTab,
     pub overlay_diagnostics_tab: DiagnosticsTab,
+    /// Tab order and scroll of the Debug and Diagnostics strips; kept while the app runs.
+    pub overlay_debug_tab_strip: TabStripState,
+    pub overlay_diagnostics_tab_strip: TabStripState,
     pub reg: Arc<BlockRegistry>,
     pub(crate) evt_processed_total: usize,
     pub(crate) evt_processed_by: HashMap<String, usize>,
     pub(crate) intents: HashMap<ChunkCoord, IntentEntry>,
+    // Chunks whose next build job counts towards a runtime batch
+    pub(crate) batch_chunks: HashMap<ChunkCoord, BatchId>,
+    // Running structure stamp and the edit stamp it wrote, so cancelling can revert it
+    pub(crate) stamp_batch: Option<(BatchId, u64)>,
+    // Chunks whose pooled generation differed from a fresh one (--check-gen-determinism)
+    pub(crate) gen_divergences: usize,
+    // Debug A/B of lighting qualities around the camera (F6/F7)
+    pub(crate) lighting_compare: Option<LightingCompare>,
     pub(crate) perf_remove_start: HashMap<ChunkCoord, VecDeque<Instant>>,
     pub(crate) perf_mesh_ms: VecDeque<u32>,
     pub(crate) perf_light_ms: VecDeque<u32>,
     pub(crate) perf_total_ms: VecDeque<u32>,
     pub(crate) perf_remove_ms: VecDeque<u32>,
     pub(crate) perf_gen_ms: VecDeque<u32>,
+    pub(crate) edit_latency: EditLatencyTracker,
+    pub(crate) rebuild_history: RebuildHistory,
     pub(crate) terrain_stage_us: [VecDeque<u32>; TERRAIN_STAGE_COUNT],
     pub(crate) terrain_stage_calls: [VecDeque<u32>; TERRAIN_STAGE_COUNT],
     pub(crate) terrain_height_tile_us: VecDeque<u32>,
@@ -84,8 +178,15 @@ pub struct App {
     pub(crate) worldgen_dirty: bool,
     pub assets_root: PathBuf,
     pub(crate) reg_event_rx: Receiver<()>,
+    pub(crate) ambiance: Ambiance,
+    pub(crate) ambiance_event_rx: Receiver<()>,
     pub(crate) shader_event_rx: Receiver<()>,
     pub last_frame_dt: f32,
+    // Directory edits are autosaved to (--save-dir); `None` keeps edits in memory only.
+    pub(crate) save_dir: Option<PathBuf>,
+    // Overworld plus the cave and sky dimensions; O travels to the next one.
+    pub(crate) dimensions: DimensionState,
+    pub(crate) autosave_timer: f32,
 }
 
 #[derive(Clone, Debug)]
@@ -177,9 +278,12 @@ pub struct DebugStats {
     pub total_triangles: usize,
     pub chunks_rendered: usize,
     pub chunks_culled: usize,
+    pub chunks_occluded: usize,
     pub structures_rendered: usize,
     pub structures_culled: usize,
     pub draw_calls: usize,
+    pub decorations_drawn: usize,
+    pub shadow_draw_calls: usize,
     pub queued_events_total: usize,
     pub queued_events_by: Vec<(String, usize)>,
     pub intents_size: usize,
@@ -195,12 +299,35 @@ pub struct DebugStats {
     pub lighting_border_chunks: usize,
     pub lighting_emitter_chunks: usize,
     pub lighting_micro_chunks: usize,
+    pub lighting_seam_mismatches: u64,
+    pub lighting_last_seam: Option<(SeamMismatch, Instant)>,
     pub edit_chunk_entries: usize,
     pub edit_block_edits: usize,
     pub edit_rev_entries: usize,
     pub edit_built_entries: usize,
 }
 
+/// Short-lived notice drawn below the HUD line.
+#[derive(Clone, Debug)]
+pub(crate) struct Toast {
+    pub(crate) text: String,
+    pub(crate) until: Instant,
+}
+
+impl Toast {
+    pub(crate) fn new(text: impl Into<String>, secs: f32) -> Self {
+        Self {
+            text: text.into(),
+            until: Instant::now() + std::time::Duration::from_secs_f32(secs.max(0.0)),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn expired(&self) -> bool {
+        Instant::now() >= self.until
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub(crate) enum IntentCause {
     Edit = 0,
@@ -1,4 +1,3 @@
-use geist_blocks::Block;
 use geist_geom::Vec3;
 use geist_render_raylib::conv::{vec3_from_rl, vec3_to_rl};
 use geist_runtime::JobOut;
@@ -6,7 +5,10 @@ use geist_world::{ChunkCoord, TERRAIN_STAGE_COUNT, TerrainMetrics};
 use raylib::prelude::*;
 use std::collections::BTreeMap;
 
-use super::{App, HitRegion, WindowButton, WindowId, anchor_world_position, anchor_world_velocity};
+use super::{
+    Ambiance, App, HitRegion, ModalKey, SUN_STRUCTURE_ID, WindowButton, WindowId,
+    anchor_world_position, anchor_world_velocity,
+};
 use crate::event::{Event, RebuildCause};
 use crate::gamestate::WalkerAnchor;
 
@@ -18,7 +20,7 @@ impl App {
                 let world_vel = anchor_world_velocity(&anchor, st);
                 self.gs.walker.pos = vec3_to_rl(world_pos);
                 self.gs.walker.vel = vec3_to_rl(world_vel);
-                if self.gs.walk_mode {
+                if self.gs.walk_mode && !self.gs.spectator {
                     self.cam.position = self.gs.walker.eye_position();
                 }
             }
@@ -29,11 +31,20 @@ impl App {
         self.last_frame_dt = dt.max(0.0);
         self.day_sample = self.day_cycle.advance(dt.max(0.0));
         self.sync_anchor_world_pose();
+        self.autosave_tick(dt);
         self.gs
             .lighting
-            .set_skylight_max(self.day_sample.skylight_max());
+            .set_skylight_max(if self.dimensions.skylight() {
+                self.day_sample.skylight_max()
+            } else {
+                0
+            });
         // Shader hot-reload
-        if self.shader_event_rx.try_iter().next().is_some() {
+        let shaders_changed = self.shader_event_rx.try_iter().next().is_some();
+        if shaders_changed && self.shader_compat {
+            // Meshes already carry baked light; mixing in the shader path would double it.
+            log::info!("Shader change ignored in compatibility rendering; restart to retry");
+        } else if shaders_changed {
             // Attempt to reload both shaders; fall back to previous if load fails
             if let Some(ls) =
                 geist_render_raylib::LeavesShader::load_with_base(rl, thread, &self.assets_root)
@@ -91,56 +102,46 @@ impl App {
             log::info!("Reloaded shaders and rebound on existing models");
         }
         // Registry hot-reload (materials/blocks)
-        if self.reg_event_rx.try_iter().next().is_some() {
-            let mats = crate::assets::materials_path(&self.assets_root);
-            let blks = crate::assets::blocks_path(&self.assets_root);
-            match geist_blocks::BlockRegistry::load_from_paths(&mats, &blks) {
-                Ok(mut newreg) => {
-                    for m in &mut newreg.materials.materials {
-                        for p in &mut m.texture_candidates {
-                            if p.is_relative() {
-                                *p = self.assets_root.join(&p);
-                            }
-                        }
-                    }
-                    self.reg = std::sync::Arc::new(newreg);
-                    self.tex_cache.map.clear();
-                    let keys: Vec<ChunkCoord> = self.renders.keys().copied().collect();
-                    for coord in keys {
-                        self.queue.emit_now(Event::ChunkRebuildRequested {
-                            cx: coord.cx,
-                            cy: coord.cy,
-                            cz: coord.cz,
-                            cause: RebuildCause::HotReload,
-                        });
-                    }
-                    for (id, st) in self.gs.structures.iter() {
-                        let next_rev = st.built_rev.wrapping_add(1);
-                        self.queue.emit_now(Event::StructureBuildRequested {
-                            id: *id,
-                            rev: next_rev,
-                        });
-                    }
-                    log::info!("Reloaded voxel registry and scheduled rebuilds");
+        if self.reg_event_rx.try_iter().count() > 0 {
+            self.reload_block_registry();
+        }
+        // Ambiance presets hot-reload
+        if self.ambiance_event_rx.try_iter().next().is_some() {
+            let path = crate::assets::ambiance_path(&self.assets_root);
+            match Ambiance::load(&path) {
+                Ok(loaded) => {
+                    self.ambiance.reload_from(loaded);
+                    self.apply_ambiance();
+                    log::info!(
+                        "Reloaded ambiance presets; '{}' active",
+                        self.ambiance.current().name
+                    );
                 }
-                Err(e) => log::warn!("Registry reload failed: {}", e),
+                Err(e) => log::warn!("Ambiance reload failed: {}", e),
             }
         }
         // Handle worldgen hot-reload
         // Always invalidate previous CPU buffers on change; optionally schedule rebuilds
         if self.take_worldgen_dirty() {
-            let keys: Vec<ChunkCoord> = self.gs.chunks.ready_coords().collect();
-            let total_chunks = self.gs.chunks.ready_len();
-            for (_coord, ent) in self.gs.chunks.iter_mut() {
-                ent.buf = None; // prevent reuse across worldgen param changes
-            }
+            let plan = self
+                .gs
+                .chunks
+                .worldgen_revs()
+                .rebuild_plan(self.gs.world.current_worldgen_rev(), self.gs.center_chunk);
+            // Prevent reuse across worldgen param changes
+            self.gs.chunks.drop_bufs();
             let cached_coords: Vec<ChunkCoord> = self.gs.chunks.coords_any().collect();
             for coord in &cached_coords {
                 self.gs.chunks.clear_column_profile(coord);
             }
             self.runtime.column_cache().clear();
+            if let Some(cache) = self.runtime.chunk_cache()
+                && let Err(e) = cache.clear()
+            {
+                log::error!("clearing chunk cache at {:?} failed: {}", cache.dir(), e);
+            }
             if self.rebuild_on_worldgen {
-                for coord in &keys {
+                for coord in plan.coords() {
                     self.queue.emit_now(Event::ChunkRebuildRequested {
                         cx: coord.cx,
                         cy: coord.cy,
@@ -148,481 +149,158 @@ impl App {
                         cause: RebuildCause::HotReload,
                     });
                 }
-                log::info!(
-                    "Scheduled rebuild of {} loaded chunks due to worldgen change",
-                    keys.len()
-                );
+                log::info!("Worldgen changed; rebuilding {}", plan.summary());
             } else {
                 log::info!(
-                    "Worldgen changed; invalidated {} chunk buffers (rebuild on demand)",
-                    total_chunks
+                    "Worldgen changed; invalidated chunk buffers (rebuild on demand): {}",
+                    plan.summary()
                 );
             }
         }
-        // Input handling → emit events
-        if rl.is_key_pressed(KeyboardKey::KEY_V) {
-            self.queue.emit_now(Event::WalkModeToggled);
-        }
-        if self.gs.walk_mode {
-            self.cam.update_look_only(rl, dt);
-        } else {
-            self.cam.update(rl, dt);
-        }
-
+        self.flush_block_entity_changes();
         if let Some(ref mut sun) = self.sun {
             let cam_vec = vec3_from_rl(self.cam.position);
             let target = sun.target_position(cam_vec, &self.day_sample);
             sun.update_pose(&mut self.queue, target);
         }
 
-        if rl.is_key_pressed(KeyboardKey::KEY_G) {
-            self.queue.emit_now(Event::GridToggled);
-        }
-        if rl.is_key_pressed(KeyboardKey::KEY_F) {
-            self.queue.emit_now(Event::WireframeToggled);
-        }
-        if rl.is_key_pressed(KeyboardKey::KEY_B) {
-            self.queue.emit_now(Event::ChunkBoundsToggled);
-        }
-        if rl.is_key_pressed(KeyboardKey::KEY_C) {
-            self.queue.emit_now(Event::FrustumCullingToggled);
-        }
-        if rl.is_key_pressed(KeyboardKey::KEY_H) {
-            self.queue.emit_now(Event::BiomeLabelToggled);
-        }
-        if rl.is_key_pressed(KeyboardKey::KEY_F3) {
-            self.queue.emit_now(Event::DebugOverlayToggled);
+        // Input handling → emit events. An open modal dialog captures all input.
+        let modal_open = self.overlay_windows.has_modal();
+        if modal_open {
+            self.handle_modal_input(rl);
+        } else {
+            self.handle_world_input(rl, dt);
         }
-        // Hotbar selection: if config present, use it; else fallback to legacy mapping
-        if !self.hotbar.is_empty() {
-            let keys = [
-                KeyboardKey::KEY_ONE,
-                KeyboardKey::KEY_TWO,
-                KeyboardKey::KEY_THREE,
-                KeyboardKey::KEY_FOUR,
-                KeyboardKey::KEY_FIVE,
-                KeyboardKey::KEY_SIX,
-                KeyboardKey::KEY_SEVEN,
-                KeyboardKey::KEY_EIGHT,
-                KeyboardKey::KEY_NINE,
-            ];
-            for (i, key) in keys.iter().enumerate() {
-                if i < self.hotbar.len() && rl.is_key_pressed(*key) {
-                    self.queue.emit_now(Event::PlaceTypeSelected {
-                        block: self.hotbar[i],
-                    });
-                }
-            }
+
+        // Update structure poses: translate non-orbit platforms using manual controls
+        let step_dx = self.gs.structure_speed * dt.max(0.0);
+        let step_dy = self.gs.structure_elev_speed * dt.max(0.0);
+        let sun_id = self.sun.as_ref().map(|s| s.id);
+        let dt_clamped = dt.max(0.0);
+        let inv_dt = if dt_clamped > 0.0001 {
+            1.0 / dt_clamped
         } else {
-            let id_of = |name: &str| self.reg.id_by_name(name).unwrap_or(0);
-            if rl.is_key_pressed(KeyboardKey::KEY_ONE) {
-                self.queue.emit_now(Event::PlaceTypeSelected {
-                    block: Block {
-                        id: id_of("dirt"),
-                        state: 0,
-                    },
-                });
-            }
-            if rl.is_key_pressed(KeyboardKey::KEY_TWO) {
-                self.queue.emit_now(Event::PlaceTypeSelected {
-                    block: Block {
-                        id: id_of("stone"),
-                        state: 0,
-                    },
-                });
-            }
-            if rl.is_key_pressed(KeyboardKey::KEY_THREE) {
-                self.queue.emit_now(Event::PlaceTypeSelected {
-                    block: Block {
-                        id: id_of("sand"),
-                        state: 0,
-                    },
-                });
-            }
-            if rl.is_key_pressed(KeyboardKey::KEY_FOUR) {
-                self.queue.emit_now(Event::PlaceTypeSelected {
-                    block: Block {
-                        id: id_of("grass"),
-                        state: 0,
-                    },
-                });
-            }
-            if rl.is_key_pressed(KeyboardKey::KEY_FIVE) {
-                self.queue.emit_now(Event::PlaceTypeSelected {
-                    block: Block {
-                        id: id_of("snow"),
-                        state: 0,
-                    },
-                });
-            }
-            if rl.is_key_pressed(KeyboardKey::KEY_SIX) {
-                self.queue.emit_now(Event::PlaceTypeSelected {
-                    block: Block {
-                        id: id_of("glowstone"),
-                        state: 0,
-                    },
-                });
-            }
-            if rl.is_key_pressed(KeyboardKey::KEY_SEVEN) {
-                self.queue.emit_now(Event::PlaceTypeSelected {
-                    block: Block {
-                        id: id_of("beacon"),
-                        state: 0,
-                    },
-                });
+            0.0
+        };
+        for (id, st) in self.gs.structures.iter() {
+            if Some(*id) == sun_id || self.schem_orbits.iter().any(|orbit| orbit.id == *id) {
+                continue;
             }
+            let prev = st.pose.pos;
+            let newp = Vec3 {
+                x: prev.x + step_dx,
+                y: prev.y + step_dy,
+                z: prev.z,
+            };
+            let delta = Vector3::new(newp.x - prev.x, newp.y - prev.y, newp.z - prev.z);
+            let velocity = Vec3::new(delta.x, delta.y, delta.z) * inv_dt;
+            // Keep yaw fixed so collisions match visuals
+            let yaw = 0.0_f32;
+            self.queue.emit_now(Event::StructurePoseUpdated {
+                id: *id,
+                pos: vec3_to_rl(newp),
+                yaw_deg: yaw,
+                pitch_deg: st.pose.pitch_deg,
+                roll_deg: st.pose.roll_deg,
+                delta,
+                velocity: vec3_to_rl(velocity),
+            });
         }
 
-        // Minimap interactions (zoom/orbit/pan)
-        let mut minimap_hovered = false;
-        if !self.gs.show_debug_overlay {
-            self.minimap_drag_button = None;
-            self.minimap_last_cursor = None;
-        }
-        if self.gs.show_debug_overlay {
-            if let Some((mx, my, mw, mh)) = self.minimap_ui_rect {
-                let mouse = rl.get_mouse_position();
-                if mouse.x >= mx as f32
-                    && mouse.x <= (mx + mw) as f32
-                    && mouse.y >= my as f32
-                    && mouse.y <= (my + mh) as f32
-                {
-                    minimap_hovered = true;
-                    let wheel = rl.get_mouse_wheel_move();
-                    if wheel.abs() > f32::EPSILON {
-                        let factor = 1.0 + wheel * 0.18;
-                        self.minimap_zoom = (self.minimap_zoom * factor).clamp(0.35, 6.0);
-                    }
-                    if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
-                        self.minimap_drag_button = Some(MouseButton::MOUSE_BUTTON_LEFT);
-                        self.minimap_drag_pan = rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
-                            || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT);
-                        self.minimap_last_cursor = Some(mouse);
-                    }
-                    if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT) {
-                        self.minimap_drag_button = Some(MouseButton::MOUSE_BUTTON_RIGHT);
-                        self.minimap_drag_pan = true;
-                        self.minimap_last_cursor = Some(mouse);
+        // Animate orbital schematics around the tower center
+        if !self.schem_orbits.is_empty() {
+            let (tower_cx, tower_cz) = self.gs.world.center();
+            let (tower_cx, tower_cz) = (tower_cx as f32, tower_cz as f32);
+            for orbit in &mut self.schem_orbits {
+                if let Some(st) = self.gs.structures.get(&orbit.id) {
+                    orbit.angle = (orbit.angle + orbit.angular_speed * dt_clamped)
+                        .rem_euclid(std::f32::consts::TAU);
+                    let target_center_x = tower_cx + orbit.radius * orbit.angle.cos();
+                    let target_center_z = tower_cz + orbit.radius * orbit.angle.sin();
+                    let new_pos = Vec3::new(
+                        target_center_x - st.sx as f32 * 0.5,
+                        orbit.height,
+                        target_center_z - st.sz as f32 * 0.5,
+                    );
+                    let prev = st.pose.pos;
+                    let delta_vec =
+                        Vec3::new(new_pos.x - prev.x, new_pos.y - prev.y, new_pos.z - prev.z);
+                    if delta_vec.length() > 1e-4 {
+                        let velocity = if dt_clamped > 0.0001 {
+                            delta_vec * (1.0 / dt_clamped)
+                        } else {
+                            Vec3::ZERO
+                        };
+                        self.queue.emit_now(Event::StructurePoseUpdated {
+                            id: orbit.id,
+                            pos: vec3_to_rl(new_pos),
+                            yaw_deg: 0.0,
+                            pitch_deg: st.pose.pitch_deg,
+                            roll_deg: st.pose.roll_deg,
+                            delta: Vector3::new(delta_vec.x, delta_vec.y, delta_vec.z),
+                            velocity: vec3_to_rl(velocity),
+                        });
                     }
                 }
             }
         }
 
-        if let Some(button) = self.minimap_drag_button {
-            if !rl.is_mouse_button_down(button) {
-                self.minimap_drag_button = None;
-                self.minimap_last_cursor = None;
-            } else if let Some(prev) = self.minimap_last_cursor {
-                let mouse = rl.get_mouse_position();
-                let dx = mouse.x - prev.x;
-                let dy = mouse.y - prev.y;
-                if dx.abs() > f32::EPSILON || dy.abs() > f32::EPSILON {
-                    if self.minimap_drag_pan {
-                        let pan_scale = 0.01 * self.minimap_zoom.max(0.4);
-                        self.minimap_pan.x -= dx * pan_scale;
-                        self.minimap_pan.z += dy * pan_scale;
-                    } else {
-                        let yaw_speed = 0.010;
-                        let pitch_speed = 0.010;
-                        self.minimap_yaw += dx * yaw_speed;
-                        self.minimap_pitch =
-                            (self.minimap_pitch - dy * pitch_speed).clamp(0.12, 1.45);
-                        let tau = std::f32::consts::TAU;
-                        if self.minimap_yaw > std::f32::consts::PI {
-                            self.minimap_yaw -= tau;
-                        } else if self.minimap_yaw < -std::f32::consts::PI {
-                            self.minimap_yaw += tau;
-                        }
-                    }
-                    self.minimap_last_cursor = Some(mouse);
-                }
-            }
-        } else if !minimap_hovered {
-            self.minimap_last_cursor = None;
+        self.step_falling_blocks(dt_clamped);
+        self.step_actors(dt_clamped);
+        self.step_fluids(dt_clamped);
+        self.step_block_ticks(dt_clamped);
+
+        // Movement intent for this tick (dt→ms); the walker reads keys, so pause it under a modal
+        if !modal_open {
+            let dt_ms = (dt.max(0.0) * 1000.0) as u32;
+            self.queue.emit_now(Event::MovementRequested {
+                dt_ms,
+                yaw: self.cam.yaw,
+                walk_mode: self.gs.walk_mode,
+            });
         }
 
-        let screen_size = (rl.get_screen_width(), rl.get_screen_height());
-        let theme = *self.overlay_windows.theme();
-        let mut overlay_block_input = false;
+        // Background builds for chunks past the evict radius stop early on the workers and
+        // are stripped while draining, so fast travel does not upload meshes it drops.
+        self.runtime
+            .update_stream_gate(self.gs.center_chunk, self.stream_evict_radius());
 
-        if !self.gs.show_debug_overlay {
-            for id in self.overlay_windows.ordered_ids() {
-                if let Some(window) = self.overlay_windows.get_mut(id) {
-                    if window.is_dragging() {
-                        window.end_drag();
-                    }
-                    if window.is_resizing() {
-                        window.end_resize();
-                    }
-                    window.reset_hover();
-                }
+        // Drain worker results, sort deterministically by job_id, and emit completion events for this tick
+        let mut results: Vec<JobOut> = self.runtime.drain_worker_results();
+        results.sort_by_key(|r| r.job_id);
+        for r in results {
+            if r.dropped {
+                self.queue.emit_now(Event::BuildChunkJobDropped {
+                    cx: r.cx,
+                    cy: r.cy,
+                    cz: r.cz,
+                    rev: r.rev,
+                });
+                continue;
             }
-            self.overlay_hover = None;
-            self.overlay_windows.clear_focus();
-        } else {
-            let cursor = rl.get_mouse_position();
-            let hovered_id = self.overlay_windows.handle_hover(cursor);
-            let hovered_region = hovered_id
-                .and_then(|id| self.overlay_windows.get(id).map(|w| (id, w.hover_region())));
-            if let Some((_, region)) = hovered_region {
-                if !matches!(region, HitRegion::None) {
-                    overlay_block_input = true;
+            // Record perf samples into rolling windows
+            match r.kind {
+                geist_runtime::JobKind::Light => {
+                    Self::perf_push(&mut self.perf_light_ms, r.t_light_ms);
+                    Self::perf_push(&mut self.perf_total_ms, r.t_total_ms);
                 }
-            }
-            self.overlay_hover = hovered_region;
-
-            let mouse_press_left = rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT);
-            let mouse_press_right = rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT);
-            let mouse_press_middle = rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_MIDDLE);
-            if mouse_press_left || mouse_press_right || mouse_press_middle {
-                if let Some((id, region)) = hovered_region {
-                    if !matches!(region, HitRegion::None) {
-                        self.overlay_windows.bring_to_front(id);
-                    }
-                }
-            }
-
-            if mouse_press_left {
-                if let Some((id, region)) = hovered_region {
-                    if let Some(window) = self.overlay_windows.get_mut(id) {
-                        match region {
-                            HitRegion::Resize(handle) => {
-                                window.begin_resize(cursor, handle);
-                                overlay_block_input = true;
-                            }
-                            HitRegion::TitleBarButton(button) => {
-                                match button {
-                                    WindowButton::Minimize => {
-                                        window.toggle_minimize();
-                                    }
-                                    WindowButton::Maximize | WindowButton::Restore => {
-                                        window.toggle_maximize(screen_size, &theme);
-                                    }
-                                    WindowButton::Pin => {
-                                        window.toggle_pin();
-                                        self.overlay_windows.update_pin_state(id);
-                                    }
-                                }
-                                overlay_block_input = true;
-                                self.overlay_windows.focus(id);
-                            }
-                            HitRegion::TitleBar => {
-                                window.begin_drag(cursor);
-                                overlay_block_input = true;
-                            }
-                            HitRegion::Content => {
-                                overlay_block_input = true;
-                            }
-                            HitRegion::None => {}
-                        }
-                    }
-                }
-            }
-
-            if rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
-                for id in self.overlay_windows.ordered_ids() {
-                    if let Some(window) = self.overlay_windows.get_mut(id) {
-                        if window.is_dragging() {
-                            overlay_block_input = true;
-                            window.update_drag(cursor, screen_size, &theme);
-                        }
-                        if window.is_resizing() {
-                            overlay_block_input = true;
-                            window.update_resize(cursor, screen_size, &theme);
-                        }
-                    }
-                }
-            } else {
-                for id in self.overlay_windows.ordered_ids() {
-                    if let Some(window) = self.overlay_windows.get_mut(id) {
-                        if window.is_dragging() {
-                            window.end_drag();
-                        }
-                        if window.is_resizing() {
-                            window.end_resize();
-                        }
-                    }
-                }
-            }
-
-            let wheel = rl.get_mouse_wheel_move();
-            if wheel.abs() > f32::EPSILON {
-                if let Some((id, region)) = self.overlay_hover {
-                    if id != WindowId::Minimap && matches!(region, HitRegion::Content) {
-                        if let Some(window) = self.overlay_windows.get_mut(id) {
-                            let delta = Vector2::new(
-                                0.0,
-                                -wheel * (theme.title_font as f32 + theme.padding_y as f32),
-                            );
-                            if window.scroll_by(delta) {
-                                overlay_block_input = true;
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        let block_minimap_input = minimap_hovered || self.minimap_drag_button.is_some();
-        let block_ui_input = block_minimap_input || overlay_block_input;
-
-        // Structure speed controls (horizontal X)
-        if rl.is_key_pressed(KeyboardKey::KEY_MINUS) {
-            self.gs.structure_speed = (self.gs.structure_speed - 1.0).max(0.0);
-        }
-        if rl.is_key_pressed(KeyboardKey::KEY_EQUAL) {
-            self.gs.structure_speed = (self.gs.structure_speed + 1.0).min(64.0);
-        }
-        if rl.is_key_pressed(KeyboardKey::KEY_ZERO) {
-            self.gs.structure_speed = 0.0;
-        }
-
-        // Structure elevation controls (vertical Y)
-        if rl.is_key_pressed(KeyboardKey::KEY_LEFT_BRACKET) {
-            self.gs.structure_elev_speed = (self.gs.structure_elev_speed - 1.0).max(-64.0);
-        }
-        if rl.is_key_pressed(KeyboardKey::KEY_RIGHT_BRACKET) {
-            self.gs.structure_elev_speed = (self.gs.structure_elev_speed + 1.0).min(64.0);
-        }
-        if rl.is_key_pressed(KeyboardKey::KEY_BACKSLASH) {
-            self.gs.structure_elev_speed = 0.0;
-        }
-
-        // Light emitters via hotkeys
-        if rl.is_key_pressed(KeyboardKey::KEY_L) {
-            let fwd = self.cam.forward();
-            let p = self.cam.position + fwd * 4.0;
-            let wx = p.x.floor() as i32;
-            let wy = p.y.floor() as i32;
-            let wz = p.z.floor() as i32;
-            self.queue.emit_now(Event::LightEmitterAdded {
-                wx,
-                wy,
-                wz,
-                level: 255,
-                is_beacon: false,
-            });
-        }
-        if rl.is_key_pressed(KeyboardKey::KEY_K) {
-            let fwd = self.cam.forward();
-            let p = self.cam.position + fwd * 4.0;
-            let wx = p.x.floor() as i32;
-            let wy = p.y.floor() as i32;
-            let wz = p.z.floor() as i32;
-            self.queue
-                .emit_now(Event::LightEmitterRemoved { wx, wy, wz });
-        }
-
-        // Lighting mode cycling removed; FullMicro is the only supported mode.
-
-        // Mouse edit intents
-        let want_edit = !block_ui_input
-            && (rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT)
-                || rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT));
-        if want_edit {
-            let place = rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT);
-            let block = self.gs.place_type;
-            self.queue
-                .emit_now(Event::RaycastEditRequested { place, block });
-        }
-
-        // Update structure poses: translate non-orbit platforms using manual controls
-        let step_dx = self.gs.structure_speed * dt.max(0.0);
-        let step_dy = self.gs.structure_elev_speed * dt.max(0.0);
-        let sun_id = self.sun.as_ref().map(|s| s.id);
-        let dt_clamped = dt.max(0.0);
-        let inv_dt = if dt_clamped > 0.0001 {
-            1.0 / dt_clamped
-        } else {
-            0.0
-        };
-        for (id, st) in self.gs.structures.iter() {
-            if Some(*id) == sun_id || self.schem_orbits.iter().any(|orbit| orbit.id == *id) {
-                continue;
-            }
-            let prev = st.pose.pos;
-            let newp = Vec3 {
-                x: prev.x + step_dx,
-                y: prev.y + step_dy,
-                z: prev.z,
-            };
-            let delta = Vector3::new(newp.x - prev.x, newp.y - prev.y, newp.z - prev.z);
-            let velocity = Vec3::new(delta.x, delta.y, delta.z) * inv_dt;
-            // Keep yaw fixed so collisions match visuals
-            let yaw = 0.0_f32;
-            self.queue.emit_now(Event::StructurePoseUpdated {
-                id: *id,
-                pos: vec3_to_rl(newp),
-                yaw_deg: yaw,
-                delta,
-                velocity: vec3_to_rl(velocity),
-            });
-        }
-
-        // Animate orbital schematics around the tower center
-        if !self.schem_orbits.is_empty() {
-            let tower_cx = (self.gs.world.world_size_x() as f32) * 0.5;
-            let tower_cz = (self.gs.world.world_size_z() as f32) * 0.5;
-            for orbit in &mut self.schem_orbits {
-                if let Some(st) = self.gs.structures.get(&orbit.id) {
-                    orbit.angle = (orbit.angle + orbit.angular_speed * dt_clamped)
-                        .rem_euclid(std::f32::consts::TAU);
-                    let target_center_x = tower_cx + orbit.radius * orbit.angle.cos();
-                    let target_center_z = tower_cz + orbit.radius * orbit.angle.sin();
-                    let new_pos = Vec3::new(
-                        target_center_x - st.sx as f32 * 0.5,
-                        orbit.height,
-                        target_center_z - st.sz as f32 * 0.5,
-                    );
-                    let prev = st.pose.pos;
-                    let delta_vec =
-                        Vec3::new(new_pos.x - prev.x, new_pos.y - prev.y, new_pos.z - prev.z);
-                    if delta_vec.length() > 1e-4 {
-                        let velocity = if dt_clamped > 0.0001 {
-                            delta_vec * (1.0 / dt_clamped)
-                        } else {
-                            Vec3::ZERO
-                        };
-                        self.queue.emit_now(Event::StructurePoseUpdated {
-                            id: orbit.id,
-                            pos: vec3_to_rl(new_pos),
-                            yaw_deg: 0.0,
-                            delta: Vector3::new(delta_vec.x, delta_vec.y, delta_vec.z),
-                            velocity: vec3_to_rl(velocity),
-                        });
-                    }
-                }
-            }
-        }
-
-        // Movement intent for this tick (dt→ms)
-        let dt_ms = (dt.max(0.0) * 1000.0) as u32;
-        self.queue.emit_now(Event::MovementRequested {
-            dt_ms,
-            yaw: self.cam.yaw,
-            walk_mode: self.gs.walk_mode,
-        });
-
-        // Drain worker results, sort deterministically by job_id, and emit completion events for this tick
-        let mut results: Vec<JobOut> = self.runtime.drain_worker_results();
-        results.sort_by_key(|r| r.job_id);
-        for r in results {
-            // Record perf samples into rolling windows
-            match r.kind {
-                geist_runtime::JobKind::Light => {
-                    Self::perf_push(&mut self.perf_light_ms, r.t_light_ms);
-                    Self::perf_push(&mut self.perf_total_ms, r.t_total_ms);
-                }
-                geist_runtime::JobKind::Edit | geist_runtime::JobKind::Bg => {
-                    Self::perf_push(&mut self.perf_mesh_ms, r.t_mesh_ms);
-                    Self::perf_push(&mut self.perf_light_ms, r.t_light_ms);
-                    Self::perf_push(&mut self.perf_total_ms, r.t_total_ms);
+                geist_runtime::JobKind::Edit | geist_runtime::JobKind::Bg => {
+                    Self::perf_push(&mut self.perf_mesh_ms, r.t_mesh_ms);
+                    Self::perf_push(&mut self.perf_light_ms, r.t_light_ms);
+                    Self::perf_push(&mut self.perf_total_ms, r.t_total_ms);
                 }
             }
             if r.t_gen_ms > 0 {
                 Self::perf_push(&mut self.perf_gen_ms, r.t_gen_ms);
             }
             self.record_terrain_metrics(&r.terrain_metrics);
+            if matches!(r.kind, geist_runtime::JobKind::Edit) {
+                self.edit_latency.mark_worker_done(
+                    ChunkCoord::new(r.cx, r.cy, r.cz),
+                    r.rev,
+                    r.t_total_ms,
+                );
+            }
             // Perf logging per job
             match r.kind {
                 geist_runtime::JobKind::Light => {
@@ -670,6 +348,7 @@ impl App {
                     light_grid: None,
                     job_id: r.job_id,
                     column_profile: r.column_profile.clone(),
+                    worldgen_rev: r.worldgen_rev,
                 });
             } else if let Some(cpu) = r.cpu {
                 if let Some(buf) = r.buf {
@@ -686,6 +365,7 @@ impl App {
                         light_grid: r.light_grid,
                         job_id: r.job_id,
                         column_profile: r.column_profile.clone(),
+                        worldgen_rev: r.worldgen_rev,
                     });
                 } else {
                     log::warn!(
@@ -735,17 +415,55 @@ impl App {
             }
         }
 
+        self.edit_latency.expire_stale();
+
         // Drain structure worker results
         for r in self.runtime.drain_structure_results() {
             self.queue.emit_now(Event::StructureBuildCompleted {
                 id: r.id,
                 rev: r.rev,
-                cpu: r.cpu,
+                sections: r.sections,
                 light_grid: r.light_grid,
                 light_borders: r.light_borders,
             });
         }
 
+        for event in self.runtime.drain_batch_events() {
+            self.queue.emit_now(Event::BatchJobsUpdated { event });
+        }
+
+        for d in self.runtime.drain_gen_divergences() {
+            self.gen_divergences += 1;
+            log::warn!(
+                "nondeterministic chunk gen at ({}, {}, {}) via {}: first difference at {:?}, fresh {:?} vs pooled {:?} ({} voxels differ)",
+                d.coord.cx,
+                d.coord.cy,
+                d.coord.cz,
+                if d.from_profile {
+                    "column profile"
+                } else {
+                    "pooled ctx"
+                },
+                d.first,
+                d.fresh,
+                d.pooled,
+                d.differing
+            );
+        }
+
+        // Background builds nearest the camera, and in front of it, go first.
+        {
+            let p = self.cam.position;
+            let f = self.cam.forward();
+            let world = &self.gs.world;
+            let center = ChunkCoord::new(
+                (p.x.floor() as i32).div_euclid(world.chunk_size_x as i32),
+                (p.y.floor() as i32).div_euclid(world.chunk_size_y as i32),
+                (p.z.floor() as i32).div_euclid(world.chunk_size_z as i32),
+            );
+            self.runtime.update_focus(center, [f.x, f.y, f.z]);
+        }
+
         // Snapshot queued events before processing (for debug overlay)
         {
             let (total, by) = self.queue.queued_counts();
@@ -764,29 +482,55 @@ impl App {
             match ev {
                 Event::Tick => "Tick",
                 Event::WalkModeToggled => "WalkModeToggled",
+                Event::SpectatorToggled => "SpectatorToggled",
                 Event::GridToggled => "GridToggled",
                 Event::WireframeToggled => "WireframeToggled",
                 Event::ChunkBoundsToggled => "ChunkBoundsToggled",
                 Event::FrustumCullingToggled => "FrustumCullingToggled",
                 Event::BiomeLabelToggled => "BiomeLabelToggled",
                 Event::DebugOverlayToggled => "DebugOverlayToggled",
+                Event::AmbianceCycled => "AmbianceCycled",
+                Event::WorldMapExportRequested => "WorldMapExportRequested",
+                Event::LightingCompareToggled => "LightingCompareToggled",
+                Event::LightingCompareFlipped => "LightingCompareFlipped",
+                Event::HandTorchToggled => "HandTorchToggled",
+                Event::RenderScaleStepped { .. } => "RenderScaleStepped",
+                Event::UpscaleFilterCycled => "UpscaleFilterCycled",
+                Event::ShadowCascadesCycled => "ShadowCascadesCycled",
+                Event::ShadowResolutionCycled => "ShadowResolutionCycled",
+                Event::UiScaleStepped { .. } => "UiScaleStepped",
+                Event::OverlayPaletteCycled => "OverlayPaletteCycled",
+                Event::ReducedFlashingToggled => "ReducedFlashingToggled",
                 Event::PlaceTypeSelected { .. } => "PlaceTypeSelected",
+                Event::HotbarSlotSelected { .. } => "HotbarSlotSelected",
+                Event::HotbarScrolled { .. } => "HotbarScrolled",
+                Event::BlockPickRequested => "BlockPickRequested",
                 Event::MovementRequested { .. } => "MovementRequested",
                 Event::RaycastEditRequested { .. } => "RaycastEditRequested",
+                Event::EditHistoryStepRequested { .. } => "EditHistoryStepRequested",
                 Event::BlockPlaced { .. } => "BlockPlaced",
                 Event::BlockRemoved { .. } => "BlockRemoved",
+                Event::BlockTicked { .. } => "BlockTicked",
+                Event::DimensionTravelRequested { .. } => "DimensionTravelRequested",
                 Event::ViewCenterChanged { .. } => "ViewCenterChanged",
                 Event::EnsureChunkLoaded { .. } => "EnsureChunkLoaded",
                 Event::EnsureChunkUnloaded { .. } => "EnsureChunkUnloaded",
                 Event::ChunkRebuildRequested { .. } => "ChunkRebuildRequested",
                 Event::BuildChunkJobRequested { .. } => "BuildChunkJobRequested",
                 Event::BuildChunkJobCompleted { .. } => "BuildChunkJobCompleted",
+                Event::BuildChunkJobDropped { .. } => "BuildChunkJobDropped",
                 Event::ChunkLightingRecomputed { .. } => "ChunkLightingRecomputed",
+                Event::ModalResolved { .. } => "ModalResolved",
                 Event::StructureBuildRequested { .. } => "StructureBuildRequested",
                 Event::StructureBuildCompleted { .. } => "StructureBuildCompleted",
                 Event::StructurePoseUpdated { .. } => "StructurePoseUpdated",
                 Event::StructureBlockPlaced { .. } => "StructureBlockPlaced",
                 Event::StructureBlockRemoved { .. } => "StructureBlockRemoved",
+                Event::StructureStampRequested { .. } => "StructureStampRequested",
+                Event::StructureStampCancelRequested => "StructureStampCancelRequested",
+                Event::SchematicSelectionMoved { .. } => "SchematicSelectionMoved",
+                Event::SchematicPasteRequested { .. } => "SchematicPasteRequested",
+                Event::BatchJobsUpdated { .. } => "BatchJobsUpdated",
                 Event::PlayerAttachedToStructure { .. } => "PlayerAttachedToStructure",
                 Event::PlayerDetachedFromStructure { .. } => "PlayerDetachedFromStructure",
                 Event::LightEmitterAdded { .. } => "LightEmitterAdded",
@@ -796,7 +540,9 @@ impl App {
         };
         while let Some(env) = self.queue.pop_ready() {
             // Tally processed stats (session-wide)
-            let label = label_of(&env.kind).to_string();
+            let name = label_of(&env.kind);
+            super::crash::note_event(self.gs.tick, name);
+            let label = name.to_string();
             self.evt_processed_total = self.evt_processed_total.saturating_add(1);
             *self.evt_processed_by.entry(label).or_insert(0) += 1;
             self.handle_event(rl, thread, env);
@@ -807,6 +553,7 @@ impl App {
         }
         // After handling events for this tick, flush prioritized intents.
         self.flush_intents();
+        self.update_dynamic_lights(rl, thread);
         // Snapshot current intents backlog for debug overlay
         self.debug_stats.intents_size = self.intents.len();
         if self.intents.is_empty() {
@@ -851,6 +598,7 @@ impl App {
             }
             self.debug_stats.intents_by_radius = radius_rows;
         }
+        self.update_crash_state();
         self.gs.tick = self.gs.tick.wrapping_add(1);
         self.queue.advance_tick();
         // Sanity check: events left in past ticks will never be processed; warn if detected
@@ -868,6 +616,513 @@ impl App {
                 details
             );
         }
+        // Escape belongs to the dialog while one is open; otherwise it quits as usual.
+        rl.set_exit_key(if self.overlay_windows.has_modal() {
+            None
+        } else {
+            Some(KeyboardKey::KEY_ESCAPE)
+        });
+    }
+
+    fn handle_world_input(&mut self, rl: &mut RaylibHandle, dt: f32) {
+        if rl.is_key_pressed(KeyboardKey::KEY_N) {
+            self.queue.emit_now(Event::SpectatorToggled);
+        }
+        // Walk/fly is the player's mode; leave it alone while spectating
+        if !self.gs.spectator && rl.is_key_pressed(KeyboardKey::KEY_V) {
+            self.queue.emit_now(Event::WalkModeToggled);
+        }
+        if self.gs.spectator {
+            self.cam.update_with_speed(rl, dt, self.gs.spectator_speed);
+        } else if self.gs.walk_mode {
+            self.cam.update_look_only(rl, dt);
+        } else {
+            self.cam.update(rl, dt);
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_G) {
+            self.queue.emit_now(Event::GridToggled);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F) {
+            self.queue.emit_now(Event::WireframeToggled);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_B) {
+            self.queue.emit_now(Event::ChunkBoundsToggled);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_C) {
+            self.queue.emit_now(Event::FrustumCullingToggled);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_H) {
+            self.queue.emit_now(Event::BiomeLabelToggled);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F3) {
+            self.queue.emit_now(Event::DebugOverlayToggled);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F4) {
+            self.queue.emit_now(Event::AmbianceCycled);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F9) {
+            self.queue.emit_now(Event::WorldMapExportRequested);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F6) {
+            self.queue.emit_now(Event::LightingCompareToggled);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F7) {
+            self.queue.emit_now(Event::LightingCompareFlipped);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_T) {
+            self.queue.emit_now(Event::HandTorchToggled);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F10) {
+            let shift = rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+                || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT);
+            self.queue.emit_now(Event::RenderScaleStepped {
+                delta: if shift { -1 } else { 1 },
+            });
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F11) {
+            self.queue.emit_now(Event::UpscaleFilterCycled);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_J) {
+            if rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+                || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT)
+            {
+                self.queue.emit_now(Event::ShadowResolutionCycled);
+            } else {
+                self.queue.emit_now(Event::ShadowCascadesCycled);
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F5) {
+            let shift = rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+                || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT);
+            self.queue.emit_now(Event::UiScaleStepped {
+                delta: if shift { -1 } else { 1 },
+            });
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F2) {
+            self.queue.emit_now(Event::OverlayPaletteCycled);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F1) {
+            self.queue.emit_now(Event::ReducedFlashingToggled);
+        }
+        // Hotbar slots on the number keys
+        let keys = [
+            KeyboardKey::KEY_ONE,
+            KeyboardKey::KEY_TWO,
+            KeyboardKey::KEY_THREE,
+            KeyboardKey::KEY_FOUR,
+            KeyboardKey::KEY_FIVE,
+            KeyboardKey::KEY_SIX,
+            KeyboardKey::KEY_SEVEN,
+            KeyboardKey::KEY_EIGHT,
+            KeyboardKey::KEY_NINE,
+        ];
+        for (slot, key) in keys.iter().enumerate() {
+            if rl.is_key_pressed(*key) {
+                self.queue.emit_now(Event::HotbarSlotSelected { slot });
+            }
+        }
+
+        // Minimap interactions (zoom/orbit/pan)
+        let mut minimap_hovered = false;
+        if !self.gs.show_debug_overlay {
+            self.minimap_drag_button = None;
+            self.minimap_last_cursor = None;
+        }
+        if self.gs.show_debug_overlay {
+            if let Some((mx, my, mw, mh)) = self.minimap_ui_rect {
+                let mouse = rl.get_mouse_position();
+                if mouse.x >= mx as f32
+                    && mouse.x <= (mx + mw) as f32
+                    && mouse.y >= my as f32
+                    && mouse.y <= (my + mh) as f32
+                {
+                    minimap_hovered = true;
+                    let wheel = rl.get_mouse_wheel_move();
+                    if wheel.abs() > f32::EPSILON {
+                        let factor = 1.0 + wheel * 0.18;
+                        self.minimap_zoom = (self.minimap_zoom * factor).clamp(0.35, 6.0);
+                    }
+                    if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+                        self.minimap_drag_button = Some(MouseButton::MOUSE_BUTTON_LEFT);
+                        self.minimap_drag_pan = rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+                            || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT);
+                        self.minimap_last_cursor = Some(mouse);
+                    }
+                    if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT) {
+                        self.minimap_drag_button = Some(MouseButton::MOUSE_BUTTON_RIGHT);
+                        self.minimap_drag_pan = true;
+                        self.minimap_last_cursor = Some(mouse);
+                    }
+                }
+            }
+        }
+
+        if let Some(button) = self.minimap_drag_button {
+            if !rl.is_mouse_button_down(button) {
+                self.minimap_drag_button = None;
+                self.minimap_last_cursor = None;
+            } else if let Some(prev) = self.minimap_last_cursor {
+                let mouse = rl.get_mouse_position();
+                let dx = mouse.x - prev.x;
+                let dy = mouse.y - prev.y;
+                if dx.abs() > f32::EPSILON || dy.abs() > f32::EPSILON {
+                    if self.minimap_drag_pan {
+                        let pan_scale = 0.01 * self.minimap_zoom.max(0.4);
+                        self.minimap_pan.x -= dx * pan_scale;
+                        self.minimap_pan.z += dy * pan_scale;
+                    } else {
+                        let yaw_speed = 0.010;
+                        let pitch_speed = 0.010;
+                        self.minimap_yaw += dx * yaw_speed;
+                        self.minimap_pitch =
+                            (self.minimap_pitch - dy * pitch_speed).clamp(0.12, 1.45);
+                        let tau = std::f32::consts::TAU;
+                        if self.minimap_yaw > std::f32::consts::PI {
+                            self.minimap_yaw -= tau;
+                        } else if self.minimap_yaw < -std::f32::consts::PI {
+                            self.minimap_yaw += tau;
+                        }
+                    }
+                    self.minimap_last_cursor = Some(mouse);
+                }
+            }
+        } else if !minimap_hovered {
+            self.minimap_last_cursor = None;
+        }
+
+        let screen_size = (rl.get_screen_width(), rl.get_screen_height());
+        let theme = *self.overlay_windows.theme();
+        let mut overlay_block_input = false;
+
+        if !self.gs.show_debug_overlay {
+            for id in self.overlay_windows.ordered_ids() {
+                if let Some(window) = self.overlay_windows.get_mut(id) {
+                    if window.is_dragging() {
+                        window.end_drag();
+                    }
+                    if window.is_resizing() {
+                        window.end_resize();
+                    }
+                    window.reset_hover();
+                }
+            }
+            self.overlay_hover = None;
+            self.overlay_windows.clear_focus();
+        } else {
+            let cursor = rl.get_mouse_position();
+            let hovered_id = self.overlay_windows.handle_hover(cursor);
+            let hovered_region = hovered_id
+                .and_then(|id| self.overlay_windows.get(id).map(|w| (id, w.hover_region())));
+            if let Some((_, region)) = hovered_region {
+                if !matches!(region, HitRegion::None) {
+                    overlay_block_input = true;
+                }
+            }
+            self.overlay_hover = hovered_region;
+
+            let mouse_press_left = rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT);
+            let mouse_press_right = rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT);
+            let mouse_press_middle = rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_MIDDLE);
+            if mouse_press_left || mouse_press_right || mouse_press_middle {
+                if let Some((id, region)) = hovered_region {
+                    if !matches!(region, HitRegion::None) {
+                        self.overlay_windows.bring_to_front(id);
+                    }
+                }
+            }
+
+            if mouse_press_left {
+                if let Some((id, region)) = hovered_region {
+                    if let Some(window) = self.overlay_windows.get_mut(id) {
+                        match region {
+                            HitRegion::Resize(handle) => {
+                                window.begin_resize(cursor, handle);
+                                overlay_block_input = true;
+                            }
+                            HitRegion::TitleBarButton(button) => {
+                                match button {
+                                    WindowButton::Minimize => {
+                                        window.toggle_minimize();
+                                    }
+                                    WindowButton::Maximize | WindowButton::Restore => {
+                                        window.toggle_maximize(screen_size, &theme);
+                                    }
+                                    WindowButton::Pin => {
+                                        window.toggle_pin();
+                                        self.overlay_windows.update_pin_state(id);
+                                    }
+                                }
+                                overlay_block_input = true;
+                                self.overlay_windows.focus(id);
+                            }
+                            HitRegion::TitleBar => {
+                                window.begin_drag(cursor);
+                                overlay_block_input = true;
+                            }
+                            HitRegion::Content => {
+                                overlay_block_input = true;
+                            }
+                            HitRegion::None => {}
+                        }
+                    }
+                }
+            }
+
+            if rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+                for id in self.overlay_windows.ordered_ids() {
+                    if let Some(window) = self.overlay_windows.get_mut(id) {
+                        if window.is_dragging() {
+                            overlay_block_input = true;
+                            window.update_drag(cursor, screen_size, &theme);
+                        }
+                        if window.is_resizing() {
+                            overlay_block_input = true;
+                            window.update_resize(cursor, screen_size, &theme);
+                        }
+                    }
+                }
+            } else {
+                for id in self.overlay_windows.ordered_ids() {
+                    if let Some(window) = self.overlay_windows.get_mut(id) {
+                        if window.is_dragging() {
+                            window.end_drag();
+                        }
+                        if window.is_resizing() {
+                            window.end_resize();
+                        }
+                    }
+                }
+            }
+
+            let wheel = rl.get_mouse_wheel_move();
+            if wheel.abs() > f32::EPSILON {
+                if let Some((id, region)) = self.overlay_hover {
+                    if id != WindowId::Minimap && matches!(region, HitRegion::Content) {
+                        if let Some(window) = self.overlay_windows.get_mut(id) {
+                            let delta = Vector2::new(
+                                0.0,
+                                -wheel * (theme.title_font as f32 + theme.padding_y as f32),
+                            );
+                            if window.scroll_by(delta) {
+                                overlay_block_input = true;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let block_minimap_input = minimap_hovered || self.minimap_drag_button.is_some();
+        let block_ui_input = block_minimap_input || overlay_block_input;
+
+        // Mouse wheel: spectator flight speed, otherwise the hotbar (down moves right)
+        if !block_ui_input {
+            let wheel = rl.get_mouse_wheel_move();
+            if wheel.abs() > f32::EPSILON {
+                if self.gs.spectator {
+                    let factor = 1.0 + wheel * 0.15;
+                    self.gs.spectator_speed = (self.gs.spectator_speed * factor).clamp(1.0, 256.0);
+                } else {
+                    self.queue.emit_now(Event::HotbarScrolled {
+                        delta: -wheel.signum() as i32,
+                    });
+                }
+            }
+            if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_MIDDLE) {
+                self.queue.emit_now(Event::BlockPickRequested);
+            }
+        }
+
+        // Structure speed controls (horizontal X)
+        if rl.is_key_pressed(KeyboardKey::KEY_MINUS) {
+            self.gs.structure_speed = (self.gs.structure_speed - 1.0).max(0.0);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_EQUAL) {
+            self.gs.structure_speed = (self.gs.structure_speed + 1.0).min(64.0);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ZERO) {
+            self.gs.structure_speed = 0.0;
+        }
+
+        // Structure elevation controls (vertical Y)
+        if rl.is_key_pressed(KeyboardKey::KEY_LEFT_BRACKET) {
+            self.gs.structure_elev_speed = (self.gs.structure_elev_speed - 1.0).max(-64.0);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_RIGHT_BRACKET) {
+            self.gs.structure_elev_speed = (self.gs.structure_elev_speed + 1.0).min(64.0);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_BACKSLASH) {
+            self.gs.structure_elev_speed = 0.0;
+        }
+
+        // Stamp the ridden structure (or the nearest one) into the world
+        if rl.is_key_pressed(KeyboardKey::KEY_P) {
+            let target = match &self.gs.anchor {
+                WalkerAnchor::Structure(anchor) => Some(anchor.id),
+                WalkerAnchor::World => {
+                    let cam = vec3_from_rl(self.cam.position);
+                    self.gs
+                        .structures
+                        .iter()
+                        .filter(|(id, _)| **id != SUN_STRUCTURE_ID)
+                        .min_by(|(_, a), (_, b)| {
+                            let da = (a.pose.pos - cam).length();
+                            let db = (b.pose.pos - cam).length();
+                            da.total_cmp(&db)
+                        })
+                        .map(|(id, _)| *id)
+                }
+            };
+            if let Some(id) = target {
+                self.queue.emit_now(Event::StructureStampRequested { id });
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_DELETE) && self.stamp_batch.is_some() {
+            self.queue.emit_now(Event::StructureStampCancelRequested);
+        }
+
+        // Travel to the next dimension
+        if rl.is_key_pressed(KeyboardKey::KEY_O) {
+            let target = self.dimensions.set.next(self.dimensions.active);
+            self.queue
+                .emit_now(Event::DimensionTravelRequested { target });
+        }
+
+        // Schematic library browser
+        if rl.is_key_pressed(KeyboardKey::KEY_PAGE_UP) {
+            self.queue
+                .emit_now(Event::SchematicSelectionMoved { delta: -1 });
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_PAGE_DOWN) {
+            self.queue
+                .emit_now(Event::SchematicSelectionMoved { delta: 1 });
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F8)
+            && let Some(meta) = self
+                .schematic_library
+                .as_ref()
+                .and_then(|lib| lib.entries().get(self.schematic_selected))
+        {
+            self.queue.emit_now(Event::SchematicPasteRequested {
+                file: meta.file.clone(),
+            });
+        }
+
+        // Light emitters via hotkeys
+        if rl.is_key_pressed(KeyboardKey::KEY_L) {
+            let fwd = self.cam.forward();
+            let p = self.cam.position + fwd * 4.0;
+            let wx = p.x.floor() as i32;
+            let wy = p.y.floor() as i32;
+            let wz = p.z.floor() as i32;
+            self.queue.emit_now(Event::LightEmitterAdded {
+                wx,
+                wy,
+                wz,
+                level: 255,
+                is_beacon: false,
+            });
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_K) {
+            let fwd = self.cam.forward();
+            let p = self.cam.position + fwd * 4.0;
+            let wx = p.x.floor() as i32;
+            let wy = p.y.floor() as i32;
+            let wz = p.z.floor() as i32;
+            self.queue.emit_now(Event::LightEmitterRemoved {
+                wx,
+                wy,
+                wz,
+                level: 255,
+            });
+        }
+
+        // Lighting mode cycling removed; FullMicro is the only supported mode.
+
+        // Undo: Ctrl+Z; redo: Ctrl+Y or Ctrl+Shift+Z
+        let ctrl = rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+            || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+        if ctrl {
+            let shift = rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+                || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT);
+            if rl.is_key_pressed(KeyboardKey::KEY_Z) {
+                self.queue
+                    .emit_now(Event::EditHistoryStepRequested { redo: shift });
+            } else if rl.is_key_pressed(KeyboardKey::KEY_Y) {
+                self.queue
+                    .emit_now(Event::EditHistoryStepRequested { redo: true });
+            }
+        }
+
+        // Mouse edit intents
+        let want_edit = !block_ui_input
+            && (rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT)
+                || rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT));
+        if want_edit {
+            let place = rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT);
+            let block = self.gs.place_type;
+            self.queue.emit_now(Event::RaycastEditRequested {
+                place,
+                block,
+                issued_at: std::time::Instant::now(),
+            });
+        }
+    }
+
+    fn handle_modal_input(&mut self, rl: &mut RaylibHandle) {
+        let cursor = rl.get_mouse_position();
+        self.overlay_windows.handle_hover(cursor);
+        self.overlay_hover = None;
+        for id in self.overlay_windows.ordered_ids() {
+            if let Some(window) = self.overlay_windows.get_mut(id) {
+                if window.is_dragging() {
+                    window.end_drag();
+                }
+                if window.is_resizing() {
+                    window.end_resize();
+                }
+            }
+        }
+        self.minimap_drag_button = None;
+        self.minimap_last_cursor = None;
+
+        let mut keys = Vec::new();
+        for (key, modal_key) in [
+            (KeyboardKey::KEY_ENTER, ModalKey::Enter),
+            (KeyboardKey::KEY_KP_ENTER, ModalKey::Enter),
+            (KeyboardKey::KEY_ESCAPE, ModalKey::Escape),
+            (KeyboardKey::KEY_TAB, ModalKey::Tab),
+            (KeyboardKey::KEY_LEFT, ModalKey::Left),
+            (KeyboardKey::KEY_RIGHT, ModalKey::Right),
+        ] {
+            if rl.is_key_pressed(key) {
+                keys.push(modal_key);
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE)
+            || rl.is_key_pressed_repeat(KeyboardKey::KEY_BACKSPACE)
+        {
+            keys.push(ModalKey::Backspace);
+        }
+        while let Some(ch) = rl.get_char_pressed() {
+            keys.push(ModalKey::Char(ch));
+        }
+
+        let mut resolved = Vec::new();
+        for key in keys {
+            if let Some(done) = self.overlay_windows.modals_mut().handle_key(key) {
+                resolved.push(done);
+            }
+        }
+        if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT)
+            && let Some(done) = self.overlay_windows.modals_mut().handle_click(cursor)
+        {
+            resolved.push(done);
+        }
+        for (id, result) in resolved {
+            self.queue.emit_now(Event::ModalResolved { id, result });
+        }
     }
 
     fn record_terrain_metrics(&mut self, metrics: &TerrainMetrics) {
@@ -43,10 +43,7 @@ impl SunBody {
             sz: SUN_DIAMETER_BLOCKS,
             blocks: Arc::from(blocks.into_boxed_slice()),
             edits: StructureEditStore::new(),
-            pose: Pose {
-                pos: initial_pos,
-                yaw_deg: 0.0,
-            },
+            pose: Pose::from_yaw(initial_pos, 0.0),
             last_delta: Vec3::ZERO,
             last_velocity: Vec3::ZERO,
             dirty_rev: 1,
@@ -75,6 +72,8 @@ impl SunBody {
             id: self.id,
             pos: vec3_to_rl(target),
             yaw_deg: 0.0,
+            pitch_deg: 0.0,
+            roll_deg: 0.0,
             delta: vec3_to_rl(delta),
             velocity: vec3_to_rl(Vec3::ZERO),
         });
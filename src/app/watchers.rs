@@ -1,6 +1,9 @@
+use geist_blocks::types::Block;
+use geist_world::ChunkCoord;
 use raylib::prelude::*;
 
-use super::App;
+use super::{App, Toast};
+use crate::event::{Event, RebuildCause};
 
 impl App {
     pub fn process_texture_file_events(
@@ -56,10 +59,26 @@ impl App {
                 log::warn!("failed to reload texture {}", path);
             }
         }
+        if let Some(ref mut arr) = self.block_textures {
+            for path in changed.iter() {
+                if let Err(e) = arr.reload_layer(path) {
+                    log::warn!("texture array layer not updated: {}", e);
+                }
+            }
+        }
+        // Layered materials sample the texture array and have no per-material texture.
+        let layered = |mid: geist_blocks::types::MaterialId| {
+            self.block_textures
+                .as_ref()
+                .is_some_and(|a| a.layer(mid).is_some())
+        };
         let mut rebound: std::collections::HashMap<String, usize> = Default::default();
         // Rebind textures on existing chunk renders
         for (_k, cr) in self.renders.iter_mut() {
             for part in cr.parts.iter_mut() {
+                if layered(part.mid) {
+                    continue;
+                }
                 let Some(path) = choose_path(part.mid) else {
                     continue;
                 };
@@ -100,6 +119,9 @@ impl App {
         // Rebind for structure renders as well
         for (_id, cr) in self.structure_renders.iter_mut() {
             for part in cr.parts.iter_mut() {
+                if layered(part.mid) {
+                    continue;
+                }
                 let Some(path) = choose_path(part.mid) else {
                     continue;
                 };
@@ -146,6 +168,71 @@ impl App {
         }
     }
 
+    /// Stack block textures into a GL texture array so chunk parts only switch a layer
+    /// uniform instead of binding a texture per material.
+    pub fn enable_block_texture_array(&mut self) {
+        if self.shader_compat {
+            log::info!("Texture array needs the voxel shaders; keeping per-material textures");
+            return;
+        }
+        // Release the old array before allocating its replacement.
+        self.block_textures = None;
+        match geist_render_raylib::BlockTextureArray::build(&self.reg.materials) {
+            Ok(arr) => {
+                log::info!(
+                    "Block texture array: {} layer(s) at {}x{}",
+                    arr.layers,
+                    arr.width,
+                    arr.height
+                );
+                self.block_textures = Some(arr);
+            }
+            Err(e) => {
+                log::warn!(
+                    "Block texture array unavailable ({}); using per-material textures",
+                    e
+                );
+            }
+        }
+    }
+
+    /// Upload materials past the 16-bit vertex limit as single parts with 32-bit indices
+    /// instead of splitting them. Applies to chunks uploaded afterwards.
+    pub fn enable_wide_indices(&mut self) {
+        match geist_render_raylib::WideIndices::detect() {
+            Ok(wide) => {
+                log::info!("32-bit chunk indices enabled");
+                self.wide_indices = Some(wide);
+            }
+            Err(e) => {
+                log::warn!(
+                    "32-bit chunk indices unavailable ({}); splitting large parts",
+                    e
+                );
+            }
+        }
+    }
+
+    /// Start the scene at `scale` times the window resolution, upscaled with `filter`.
+    /// `msaa` records whether the window was created multisampled.
+    pub fn configure_scene_target(
+        &mut self,
+        scale: f32,
+        filter: geist_render_raylib::UpscaleFilter,
+        msaa: bool,
+    ) {
+        self.scene_target.set_scale(scale);
+        self.scene_target.set_filter(filter);
+        self.msaa = msaa;
+    }
+
+    /// Shadow quality to start with; ignored when the depth pass is unavailable.
+    pub fn configure_shadows(&mut self, settings: geist_render_raylib::ShadowSettings) {
+        if let Some(shadows) = self.shadow_maps.as_mut() {
+            shadows.set_settings(settings);
+        }
+    }
+
     pub fn process_worldgen_file_events(&mut self) {
         let mut changed = false;
         for _ in self.worldgen_event_rx.try_iter() {
@@ -176,6 +263,143 @@ impl App {
         }
     }
 
+    /// Re-parse `materials.toml` and `blocks.toml`. The new registry replaces the live one
+    /// only if every block id keeps its name and state layout, since loaded chunks, edits
+    /// and structures store raw ids; then just the chunks holding changed blocks rebuild,
+    /// or everything when materials changed.
+    pub fn reload_block_registry(&mut self) {
+        let mats = crate::assets::materials_path(&self.assets_root);
+        let blks = crate::assets::blocks_path(&self.assets_root);
+        let mut newreg = match geist_blocks::BlockRegistry::load_from_paths(&mats, &blks) {
+            Ok(reg) => reg,
+            Err(e) => {
+                log::warn!("Registry reload failed: {}", e);
+                return;
+            }
+        };
+        for m in &mut newreg.materials.materials {
+            for p in &mut m.texture_candidates {
+                if p.is_relative() {
+                    *p = self.assets_root.join(&p);
+                }
+            }
+        }
+        let diff = match self.reg.diff_reload(&newreg) {
+            Ok(diff) => diff,
+            Err(problems) => {
+                for p in &problems {
+                    log::warn!("Registry reload refused: {}", p);
+                }
+                self.toast = Some(Toast::new(
+                    format!("Block registry not reloaded: {}", problems[0]),
+                    6.0,
+                ));
+                return;
+            }
+        };
+        if diff.is_empty() {
+            log::info!("Registry files changed but no block or material did");
+            return;
+        }
+        let old = std::mem::replace(&mut self.reg, std::sync::Arc::new(newreg));
+        // Ids are unchanged, so scheduled ticks stay valid; new blocks may need handlers.
+        self.block_tick_handlers =
+            std::sync::Arc::new(geist_runtime::builtin_block_ticks(self.reg.clone()));
+        if diff.materials_changed {
+            self.tex_cache.map.clear();
+            if self.block_textures.is_some() {
+                self.enable_block_texture_array();
+            }
+        }
+
+        let mask = diff.changed_mask();
+        let touched = |b: &Block| mask.get(b.id as usize).copied().unwrap_or(false);
+        let emitter = |reg: &geist_blocks::BlockRegistry, b: Block| {
+            reg.get(b.id)
+                .map(|t| (t.light_emission(b.state), t.light_is_beam()))
+                .filter(|(level, _)| *level > 0)
+        };
+        let coords: Vec<ChunkCoord> = self.renders.keys().copied().collect();
+        let mut rebuilt = 0usize;
+        for coord in coords {
+            let edits = self
+                .gs
+                .edits
+                .snapshot_for_chunk(coord.cx, coord.cy, coord.cz);
+            // Generated blocks are only known while the buffer is kept; rebuild if it is not.
+            let hit = diff.materials_changed
+                || edits.iter().any(|(_, b)| touched(b))
+                || self
+                    .gs
+                    .chunks
+                    .get(&coord)
+                    .and_then(|c| c.buf.as_deref())
+                    .is_none_or(|buf| buf.blocks.iter().any(touched));
+            if !hit {
+                continue;
+            }
+            // Placed emitters are registered with their level; re-register changed ones.
+            for ((wx, wy, wz), b) in edits {
+                if !diff.relit.contains(&b.id) {
+                    continue;
+                }
+                let (before, after) = (emitter(&old, b), emitter(&self.reg, b));
+                if before == after {
+                    continue;
+                }
+                if let Some((level, _)) = before {
+                    self.queue
+                        .emit_now(Event::LightEmitterRemoved { wx, wy, wz, level });
+                }
+                if let Some((level, is_beacon)) = after {
+                    self.queue.emit_now(Event::LightEmitterAdded {
+                        wx,
+                        wy,
+                        wz,
+                        level,
+                        is_beacon,
+                    });
+                }
+            }
+            self.queue.emit_now(Event::ChunkRebuildRequested {
+                cx: coord.cx,
+                cy: coord.cy,
+                cz: coord.cz,
+                cause: RebuildCause::HotReload,
+            });
+            rebuilt += 1;
+        }
+        let mut structures = 0usize;
+        for (id, st) in self.gs.structures.iter_mut() {
+            let hit = diff.materials_changed
+                || st.blocks.iter().any(touched)
+                || st.edits.snapshot_all().iter().any(|(_, b)| touched(b));
+            if !hit {
+                continue;
+            }
+            st.mark_all_sections_dirty();
+            let next_rev = st.built_rev.wrapping_add(1);
+            self.queue.emit_now(Event::StructureBuildRequested {
+                id: *id,
+                rev: next_rev,
+            });
+            structures += 1;
+        }
+        log::info!(
+            "Reloaded voxel registry ({} changed, {} relit, {} added, materials {}); rebuilding {} chunk(s) and {} structure(s)",
+            diff.changed.len(),
+            diff.relit.len(),
+            diff.added.len(),
+            if diff.materials_changed {
+                "changed"
+            } else {
+                "unchanged"
+            },
+            rebuilt,
+            structures
+        );
+    }
+
     pub fn take_worldgen_dirty(&mut self) -> bool {
         if self.worldgen_dirty {
             self.worldgen_dirty = false;
@@ -55,6 +55,10 @@ pub fn hotbar_path(root: &Path) -> PathBuf {
     root.join("assets/voxels/hotbar.toml")
 }
 
+pub fn ambiance_path(root: &Path) -> PathBuf {
+    root.join("assets/voxels/ambiance.toml")
+}
+
 pub fn textures_dir(root: &Path) -> PathBuf {
     root.join("assets/blocks")
 }
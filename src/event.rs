@@ -5,9 +5,13 @@ use geist_blocks::types::Block;
 use geist_chunk::{ChunkBuf, ChunkOccupancy};
 use geist_lighting::{LightBorders, LightGrid};
 use geist_mesh_cpu::{ChunkMeshCPU, NeighborsLoaded};
-use geist_structures::StructureId;
+use geist_runtime::BatchEvent;
+use geist_structures::{SectionCoord, StructureId};
+use geist_ui::{ModalId, ModalResult};
+use geist_world::DimensionId;
 use geist_world::voxel::generation::ChunkColumnProfile;
 use raylib::prelude::Vector3;
+use std::time::Instant;
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum RebuildCause {
@@ -25,15 +29,45 @@ pub enum Event {
 
     // Input-derived intents
     WalkModeToggled,
+    SpectatorToggled,
     GridToggled,
     WireframeToggled,
     ChunkBoundsToggled,
     FrustumCullingToggled,
     BiomeLabelToggled,
     DebugOverlayToggled,
+    AmbianceCycled,
+    WorldMapExportRequested,
+    // Debug A/B of lighting qualities around the camera
+    LightingCompareToggled,
+    LightingCompareFlipped,
+    HandTorchToggled,
+    // Scene resolution scale presets and upscale filter (Settings window)
+    RenderScaleStepped {
+        delta: i32,
+    },
+    UpscaleFilterCycled,
+    // Sun shadow cascade count and map resolution (Settings window)
+    ShadowCascadesCycled,
+    ShadowResolutionCycled,
+    // Accessibility settings (Settings window), persisted on change
+    UiScaleStepped {
+        delta: i32,
+    },
+    OverlayPaletteCycled,
+    ReducedFlashingToggled,
     PlaceTypeSelected {
         block: Block,
     },
+    HotbarSlotSelected {
+        slot: usize,
+    },
+    // Mouse wheel over the world: positive moves right along the hotbar
+    HotbarScrolled {
+        delta: i32,
+    },
+    // Middle click: put the block under the crosshair on the hotbar
+    BlockPickRequested,
     MovementRequested {
         dt_ms: u32,
         yaw: f32,
@@ -42,20 +76,40 @@ pub enum Event {
     RaycastEditRequested {
         place: bool,
         block: Block,
+        // Input timestamp for end-to-end edit latency tracking
+        issued_at: Instant,
+    },
+    // Undo (redo=false) or redo the latest edit step
+    EditHistoryStepRequested {
+        redo: bool,
     },
     BlockPlaced {
         wx: i32,
         wy: i32,
         wz: i32,
         block: Block,
+        issued_at: Option<Instant>,
     },
     BlockRemoved {
         wx: i32,
         wy: i32,
         wz: i32,
+        issued_at: Option<Instant>,
+    },
+    // A block tick handler rewrote a block; skipped if it no longer holds `from`
+    BlockTicked {
+        wx: i32,
+        wy: i32,
+        wz: i32,
+        from: Block,
+        to: Block,
     },
 
     // Player/view
+    // Portal: stream another dimension instead of the current one
+    DimensionTravelRequested {
+        target: DimensionId,
+    },
     ViewCenterChanged {
         ccx: i32,
         ccy: i32,
@@ -101,6 +155,15 @@ pub enum Event {
         light_grid: Option<geist_lighting::LightGrid>,
         job_id: u64,
         column_profile: Option<Arc<ChunkColumnProfile>>,
+        // Worldgen rev of freshly generated blocks; None when an earlier buffer was reused
+        worldgen_rev: Option<u32>,
+    },
+    // The runtime dropped a background build whose chunk left the stream radius
+    BuildChunkJobDropped {
+        cx: i32,
+        cy: i32,
+        cz: i32,
+        rev: u64,
     },
 
     // Lighting-only recompute result (Phase 1 decoupling)
@@ -121,7 +184,7 @@ pub enum Event {
     StructureBuildCompleted {
         id: StructureId,
         rev: u64,
-        cpu: ChunkMeshCPU,
+        sections: Vec<(SectionCoord, ChunkMeshCPU)>,
         light_grid: LightGrid,
         light_borders: LightBorders,
     },
@@ -130,6 +193,8 @@ pub enum Event {
         id: StructureId,
         pos: Vector3,
         yaw_deg: f32,
+        pitch_deg: f32,
+        roll_deg: f32,
         delta: Vector3,
         velocity: Vector3,
     },
@@ -146,6 +211,24 @@ pub enum Event {
         ly: i32,
         lz: i32,
     },
+    // Copy a structure's blocks into the world edits at its current pose
+    StructureStampRequested {
+        id: StructureId,
+    },
+    // Abort the running stamp's chunk rebuilds and revert it
+    StructureStampCancelRequested,
+    // Move the schematic library selection by `delta` entries (wraps)
+    SchematicSelectionMoved {
+        delta: i32,
+    },
+    // Stamp a schematic library entry in front of the camera
+    SchematicPasteRequested {
+        file: String,
+    },
+    // Progress, completion or cancellation reported by a runtime batch
+    BatchJobsUpdated {
+        event: BatchEvent,
+    },
 
     // Player ↔ structure attachment lifecycle
     PlayerAttachedToStructure {
@@ -168,6 +251,9 @@ pub enum Event {
         wx: i32,
         wy: i32,
         wz: i32,
+        /// Emission level of the removed block; emitters that were never registered
+        /// (worldgen, loaded saves) are darkened with it.
+        level: u8,
     },
     LightBordersUpdated {
         cx: i32,
@@ -181,6 +267,12 @@ pub enum Event {
         zn_changed: bool,
         zp_changed: bool,
     },
+
+    // UI
+    ModalResolved {
+        id: ModalId,
+        result: ModalResult,
+    },
 }
 
 pub struct EventEnvelope {
@@ -191,6 +283,35 @@ pub struct EventEnvelope {
     pub kind: Event,
 }
 
+impl Event {
+    /// Events about the current world's voxels, chunks or lights, which mean nothing once
+    /// another dimension is streamed.
+    pub fn is_world_bound(&self) -> bool {
+        matches!(
+            self,
+            Event::RaycastEditRequested { .. }
+                | Event::BlockPlaced { .. }
+                | Event::BlockRemoved { .. }
+                | Event::BlockTicked { .. }
+                | Event::ViewCenterChanged { .. }
+                | Event::EnsureChunkLoaded { .. }
+                | Event::EnsureChunkUnloaded { .. }
+                | Event::ChunkRebuildRequested { .. }
+                | Event::BuildChunkJobRequested { .. }
+                | Event::BuildChunkJobCompleted { .. }
+                | Event::BuildChunkJobDropped { .. }
+                | Event::ChunkLightingRecomputed { .. }
+                | Event::StructureStampRequested { .. }
+                | Event::StructureStampCancelRequested
+                | Event::SchematicPasteRequested { .. }
+                | Event::BatchJobsUpdated { .. }
+                | Event::LightEmitterAdded { .. }
+                | Event::LightEmitterRemoved { .. }
+                | Event::LightBordersUpdated { .. }
+        )
+    }
+}
+
 pub struct EventQueue {
     // map of tick -> FIFO queue of events
     by_tick: BTreeMap<u64, VecDeque<EventEnvelope>>,
@@ -266,6 +387,13 @@ impl EventQueue {
         self.now = self.now.wrapping_add(1);
     }
 
+    /// Drop queued events for which `keep` returns false, in every tick bucket.
+    pub fn retain(&mut self, mut keep: impl FnMut(&Event) -> bool) {
+        for q in self.by_tick.values_mut() {
+            q.retain(|env| keep(&env.kind));
+        }
+    }
+
     // Debug: count events that are in past ticks (< now). These will never be processed.
     pub fn count_stale_events(&self) -> usize {
         let mut total = 0usize;
@@ -298,34 +426,60 @@ impl EventQueue {
                 let label: &'static str = match &env.kind {
                     Event::Tick => "Tick",
                     Event::WalkModeToggled => "WalkModeToggled",
+                    Event::SpectatorToggled => "SpectatorToggled",
                     Event::GridToggled => "GridToggled",
                     Event::WireframeToggled => "WireframeToggled",
                     Event::ChunkBoundsToggled => "ChunkBoundsToggled",
                     Event::FrustumCullingToggled => "FrustumCullingToggled",
                     Event::BiomeLabelToggled => "BiomeLabelToggled",
                     Event::DebugOverlayToggled => "DebugOverlayToggled",
+                    Event::AmbianceCycled => "AmbianceCycled",
+                    Event::WorldMapExportRequested => "WorldMapExportRequested",
+                    Event::LightingCompareToggled => "LightingCompareToggled",
+                    Event::LightingCompareFlipped => "LightingCompareFlipped",
+                    Event::HandTorchToggled => "HandTorchToggled",
+                    Event::RenderScaleStepped { .. } => "RenderScaleStepped",
+                    Event::UpscaleFilterCycled => "UpscaleFilterCycled",
+                    Event::ShadowCascadesCycled => "ShadowCascadesCycled",
+                    Event::ShadowResolutionCycled => "ShadowResolutionCycled",
+                    Event::UiScaleStepped { .. } => "UiScaleStepped",
+                    Event::OverlayPaletteCycled => "OverlayPaletteCycled",
+                    Event::ReducedFlashingToggled => "ReducedFlashingToggled",
                     Event::PlaceTypeSelected { .. } => "PlaceTypeSelected",
+                    Event::HotbarSlotSelected { .. } => "HotbarSlotSelected",
+                    Event::HotbarScrolled { .. } => "HotbarScrolled",
+                    Event::BlockPickRequested => "BlockPickRequested",
                     Event::MovementRequested { .. } => "MovementRequested",
                     Event::RaycastEditRequested { .. } => "RaycastEditRequested",
+                    Event::EditHistoryStepRequested { .. } => "EditHistoryStepRequested",
                     Event::BlockPlaced { .. } => "BlockPlaced",
                     Event::BlockRemoved { .. } => "BlockRemoved",
+                    Event::BlockTicked { .. } => "BlockTicked",
+                    Event::DimensionTravelRequested { .. } => "DimensionTravelRequested",
                     Event::ViewCenterChanged { .. } => "ViewCenterChanged",
                     Event::EnsureChunkLoaded { .. } => "EnsureChunkLoaded",
                     Event::EnsureChunkUnloaded { .. } => "EnsureChunkUnloaded",
                     Event::ChunkRebuildRequested { .. } => "ChunkRebuildRequested",
                     Event::BuildChunkJobRequested { .. } => "BuildChunkJobRequested",
                     Event::BuildChunkJobCompleted { .. } => "BuildChunkJobCompleted",
+                    Event::BuildChunkJobDropped { .. } => "BuildChunkJobDropped",
                     Event::StructureBuildRequested { .. } => "StructureBuildRequested",
                     Event::StructureBuildCompleted { .. } => "StructureBuildCompleted",
                     Event::StructurePoseUpdated { .. } => "StructurePoseUpdated",
                     Event::StructureBlockPlaced { .. } => "StructureBlockPlaced",
                     Event::StructureBlockRemoved { .. } => "StructureBlockRemoved",
+                    Event::StructureStampRequested { .. } => "StructureStampRequested",
+                    Event::StructureStampCancelRequested => "StructureStampCancelRequested",
+                    Event::SchematicSelectionMoved { .. } => "SchematicSelectionMoved",
+                    Event::SchematicPasteRequested { .. } => "SchematicPasteRequested",
+                    Event::BatchJobsUpdated { .. } => "BatchJobsUpdated",
                     Event::PlayerAttachedToStructure { .. } => "PlayerAttachedToStructure",
                     Event::PlayerDetachedFromStructure { .. } => "PlayerDetachedFromStructure",
                     Event::LightEmitterAdded { .. } => "LightEmitterAdded",
                     Event::LightEmitterRemoved { .. } => "LightEmitterRemoved",
                     Event::LightBordersUpdated { .. } => "LightBordersUpdated",
                     Event::ChunkLightingRecomputed { .. } => "ChunkLightingRecomputed",
+                    Event::ModalResolved { .. } => "ModalResolved",
                 };
                 *by.entry(label).or_insert(0) += 1;
             }
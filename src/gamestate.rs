@@ -4,11 +4,12 @@ use std::sync::Arc;
 use crate::player::Walker;
 use geist_blocks::types::Block;
 use geist_chunk::{ChunkBuf, ChunkOccupancy};
-use geist_edit::EditStore;
+use geist_edit::{BlockEntityStore, EditStore};
 use geist_geom::Vec3;
 use geist_lighting::LightingStore;
-use geist_structures::{Structure, StructureId, rotate_yaw, rotate_yaw_inv};
-use geist_world::voxel::{ChunkCoord, World, generation::ChunkColumnProfile};
+use geist_runtime::{ChunkMap, ChunkSnapshot};
+use geist_structures::{Structure, StructureId};
+use geist_world::voxel::{ChunkCoord, World, WorldgenRevTracker, generation::ChunkColumnProfile};
 use log::warn;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -25,7 +26,7 @@ impl ChunkLifecycle {
 }
 
 pub struct ChunkEntry {
-    pub buf: Option<ChunkBuf>,
+    pub buf: Option<Arc<ChunkBuf>>,
     occupancy: Option<ChunkOccupancy>,
     pub built_rev: u64,
     pub lifecycle: ChunkLifecycle,
@@ -71,7 +72,7 @@ impl ChunkEntry {
     pub fn set_ready(
         &mut self,
         occ: ChunkOccupancy,
-        buf: Option<ChunkBuf>,
+        buf: Option<Arc<ChunkBuf>>,
         built_rev: u64,
         column_profile: Option<Arc<ChunkColumnProfile>>,
     ) {
@@ -84,12 +85,29 @@ impl ChunkEntry {
     }
 }
 
-#[derive(Default)]
+/// Chunk lifecycle bookkeeping. Built buffers are mirrored into the runtime's
+/// `ChunkMap`, which block lookups read through `blocks`.
 pub struct ChunkInventory {
     slots: HashMap<ChunkCoord, ChunkEntry>,
+    map: Arc<ChunkMap>,
+    worldgen_revs: WorldgenRevTracker,
 }
 
 impl ChunkInventory {
+    pub fn new(map: Arc<ChunkMap>) -> Self {
+        Self {
+            slots: HashMap::new(),
+            map,
+            worldgen_revs: WorldgenRevTracker::new(),
+        }
+    }
+
+    /// Latest built blocks of every ready chunk.
+    #[inline]
+    pub fn blocks(&self) -> Arc<ChunkSnapshot> {
+        self.map.snapshot()
+    }
+
     #[inline]
     pub fn ready_len(&self) -> usize {
         self.slots.values().filter(|entry| entry.is_ready()).count()
@@ -117,16 +135,13 @@ impl ChunkInventory {
         self.slots.iter().filter(|(_, entry)| entry.is_ready())
     }
 
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&ChunkCoord, &mut ChunkEntry)> {
-        self.slots.iter_mut().filter(|(_, entry)| entry.is_ready())
-    }
-
     pub fn coords_any(&self) -> impl Iterator<Item = ChunkCoord> + '_ {
         self.slots.keys().copied()
     }
 
     #[inline]
     pub fn mark_loading(&mut self, coord: ChunkCoord) -> &mut ChunkEntry {
+        self.map.remove(coord);
         self.slots
             .entry(coord)
             .and_modify(|entry| {
@@ -146,13 +161,25 @@ impl ChunkInventory {
         occupancy: ChunkOccupancy,
         buf: Option<ChunkBuf>,
         built_rev: u64,
+        worldgen_rev: u32,
         column_profile: Option<Arc<ChunkColumnProfile>>,
     ) -> &mut ChunkEntry {
+        self.worldgen_revs.record(coord, worldgen_rev);
+        let buf = buf.map(Arc::new);
+        self.map.insert(coord, occupancy, buf.clone());
         let entry = self.slots.entry(coord).or_insert_with(ChunkEntry::loading);
         entry.set_ready(occupancy, buf, built_rev, column_profile);
         entry
     }
 
+    /// Forget every built buffer (worldgen changed under them); entries stay ready.
+    pub fn drop_bufs(&mut self) {
+        for entry in self.slots.values_mut() {
+            entry.buf = None;
+        }
+        self.map.clear();
+    }
+
     pub fn column_profile(&mut self, coord: &ChunkCoord) -> Option<Arc<ChunkColumnProfile>> {
         let entry = self.slots.get_mut(coord)?;
         if let Some(profile) = entry.column_profile.as_ref() {
@@ -185,13 +212,15 @@ impl ChunkInventory {
 
     #[inline]
     pub fn mark_missing(&mut self, coord: ChunkCoord) {
+        self.map.remove(coord);
         self.slots.remove(&coord);
+        self.worldgen_revs.forget(coord);
     }
 
-    pub fn ready_coords(&self) -> impl Iterator<Item = ChunkCoord> + '_ {
-        self.slots
-            .iter()
-            .filter_map(|(coord, entry)| entry.is_ready().then_some(*coord))
+    /// Worldgen revision every ready chunk's blocks were generated under.
+    #[inline]
+    pub fn worldgen_revs(&self) -> &WorldgenRevTracker {
+        &self.worldgen_revs
     }
 
     #[inline]
@@ -231,11 +260,18 @@ pub struct GameState {
 
     // Edits + lighting (authoritative overlays)
     pub edits: EditStore,
+    // Per-position block data (chest contents, sign text) that follows the edited blocks
+    pub block_entities: BlockEntityStore,
     pub lighting: Arc<LightingStore>,
 
     // Player
     pub walker: Walker,
     pub walk_mode: bool,
+    // Spectator: camera flies free (no-clip) while the walker stays put
+    pub spectator: bool,
+    pub spectator_speed: f32,
+    // Camera position/yaw/pitch when spectating began, restored on exit
+    pub spectator_return: Option<(raylib::prelude::Vector3, f32, f32)>,
 
     // UI/options
     pub place_type: Block,
@@ -243,6 +279,8 @@ pub struct GameState {
     pub wireframe: bool,
     pub show_chunk_bounds: bool,
     pub frustum_culling_enabled: bool,
+    // Skip chunks hidden behind solid chunks (only while frustum culling is on)
+    pub occlusion_culling_enabled: bool,
     pub show_biome_label: bool,
     pub show_debug_overlay: bool,
 
@@ -260,30 +298,41 @@ impl GameState {
         world: Arc<World>,
         edits: EditStore,
         lighting: Arc<LightingStore>,
+        chunk_map: Arc<ChunkMap>,
         spawn_eye: raylib::prelude::Vector3,
     ) -> Self {
         use raylib::prelude::*;
+        let block_entities = BlockEntityStore::new(
+            world.chunk_size_x as i32,
+            world.chunk_size_y as i32,
+            world.chunk_size_z as i32,
+        );
         let mut walker = Walker::new(Vector3::new(spawn_eye.x, spawn_eye.y - 1.60, spawn_eye.z));
         walker.yaw = -45.0;
         Self {
             tick: 0,
             center_chunk: ChunkCoord::new(i32::MIN, i32::MIN, i32::MIN),
             view_radius_chunks: 8,
-            chunks: ChunkInventory::default(),
+            chunks: ChunkInventory::new(chunk_map),
             mesh_counts: HashMap::new(),
             light_counts: HashMap::new(),
             inflight_rev: HashMap::new(),
             finalize: HashMap::new(),
             edits,
+            block_entities,
             lighting,
             walker,
             walk_mode: true,
+            spectator: false,
+            spectator_speed: 16.0,
+            spectator_return: None,
             world,
             place_type: Block { id: 0, state: 0 },
             show_grid: true,
             wireframe: false,
             show_chunk_bounds: false,
             frustum_culling_enabled: true,
+            occlusion_culling_enabled: true,
             show_biome_label: true,
             show_debug_overlay: true,
             structures: HashMap::new(),
@@ -316,12 +365,12 @@ impl StructureAnchor {
 
     #[inline]
     pub fn world_position(&self, structure: &Structure) -> Vec3 {
-        rotate_yaw(self.local_pos, structure.pose.yaw_deg) + structure.pose.pos
+        structure.pose.local_to_world(self.local_pos)
     }
 
     #[inline]
     pub fn world_velocity(&self, structure: &Structure) -> Vec3 {
-        rotate_yaw(self.local_vel, structure.pose.yaw_deg) + structure.last_velocity
+        structure.pose.vector_to_world(self.local_vel) + structure.last_velocity
     }
 
     #[inline]
@@ -336,7 +385,7 @@ impl StructureAnchor {
 
     #[inline]
     pub fn structure_local_from_world(structure: &Structure, world_pos: Vec3) -> Vec3 {
-        rotate_yaw_inv(world_pos - structure.pose.pos, structure.pose.yaw_deg)
+        structure.pose.world_to_local(world_pos)
     }
 
     #[inline]
@@ -4,19 +4,20 @@ mod camera;
 mod event;
 mod gamestate;
 mod player;
-mod raycast;
 #[cfg(test)]
 mod stairs_tests;
 
 use clap::{Args, Parser, Subcommand, ValueEnum};
 use geist_blocks::BlockRegistry;
+use geist_mesh_cpu::{ChunkMeshCPU, ExportFormat, MeshExport};
 use geist_world::{
-    ChunkCoord, OverviewMode, OverviewRegion, TERRAIN_STAGE_COUNT, TERRAIN_STAGE_LABELS,
-    TerrainMetrics, TerrainTileCacheStats, World, WorldGenMode, WorldOverview,
+    CaveSliceRange, ChunkCoord, NavGraph, OverviewMode, OverviewRegion, PngWriter, RowDownsampler,
+    TERRAIN_STAGE_COUNT, TERRAIN_STAGE_LABELS, TerrainMetrics, TerrainTileCacheStats, World,
+    WorldExtent, WorldGenMode, WorldOverview, WorldOverviewImage,
 };
 use std::collections::{BTreeMap, BTreeSet, HashSet};
-use std::fs::{self, OpenOptions};
-use std::io::Write;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -55,6 +56,18 @@ enum Command {
 
     /// Generate offline terrain overview images
     Overview(OverviewArgs),
+
+    /// Export a coarse navigation graph of the generated surface as TOML
+    Nav(NavArgs),
+
+    /// Generate and mesh a region without a window and write it as glTF or OBJ
+    Export(ExportArgs),
+
+    /// Print per-chunk content hashes for a region, or check them against a manifest
+    Hashes(HashesArgs),
+
+    /// Render PNG screenshots of a small world, block or schematic from orbiting angles
+    Snap(SnapArgs),
 }
 
 #[derive(Args, Debug)]
@@ -67,21 +80,28 @@ struct RunArgs {
     #[arg(long)]
     flat_thickness: Option<i32>,
 
+    #[command(flatten)]
+    heightmap: HeightmapArgs,
+
     /// World seed
     #[arg(long, default_value_t = 1337)]
     seed: i32,
 
-    /// Number of chunks along X
+    /// Chunks along X: a sizing hint for caches and layouts, or the world's width with --finite-world
     #[arg(long, default_value_t = 4)]
     chunks_x: usize,
 
     /// Hint for the number of vertical chunks to pre-stream near spawn (world height hint = chunks_y_hint × CHUNK_SIZE)
     #[arg(long = "chunks-y-hint", alias = "chunks-y", default_value_t = 8)]
     chunks_y_hint: usize,
-    /// Number of chunks along Z
+    /// Chunks along Z: a sizing hint for caches and layouts, or the world's depth with --finite-world
     #[arg(long, default_value_t = 4)]
     chunks_z: usize,
 
+    /// Clamp the world to chunks_x × chunks_z chunks instead of streaming without bounds
+    #[arg(long, default_value_t = false)]
+    finite_world: bool,
+
     /// Watch assets/blocks for changes and hot-reload textures
     #[arg(long, default_value_t = true)]
     watch_textures: bool,
@@ -110,6 +130,88 @@ struct RunArgs {
     #[arg(long, default_value_t = false)]
     no_frustum_culling: bool,
 
+    /// Disable chunk occlusion culling (draw chunks hidden behind solid terrain)
+    #[arg(long, default_value_t = false)]
+    no_occlusion_culling: bool,
+
+    /// What lighting assumes below y=0; defaults to void for schem-only worlds, solid otherwise
+    #[arg(long, value_enum)]
+    below_world: Option<BelowWorldCli>,
+
+    /// Starlight: the lowest skylight level (0-255) open sky falls to at night
+    #[arg(long, default_value_t = geist_lighting::DEFAULT_NIGHT_AMBIENT)]
+    night_ambient: u8,
+
+    /// Darkening of block corners enclosed by neighbours, from 0 (off) to 1
+    #[arg(long, default_value_t = geist_mesh_cpu::DEFAULT_AO_STRENGTH)]
+    ao_strength: f32,
+
+    /// Round off normals of terrain materials marked `smooth` for a softer look
+    #[arg(long, default_value_t = false)]
+    smooth_normals: bool,
+
+    /// Skip faces around air pockets sealed inside solid blocks, which are never visible
+    #[arg(long, default_value_t = false)]
+    cull_sealed_pockets: bool,
+
+    /// Stack same-size block textures into a GL texture array instead of binding one per material
+    #[arg(long, default_value_t = false)]
+    texture_array: bool,
+
+    /// Keep materials over 65k vertices in one draw with 32-bit indices instead of splitting them
+    #[arg(long, default_value_t = false)]
+    wide_indices: bool,
+
+    /// Create the window without 4x MSAA (it cannot be changed while running)
+    #[arg(long, default_value_t = false)]
+    no_msaa: bool,
+
+    /// Scene resolution as a multiple of the window size (0.25-2); F10 steps it in-game
+    #[arg(long, default_value_t = 1.0)]
+    render_scale: f32,
+
+    /// How a scaled scene is stretched to the window; F11 cycles it in-game
+    #[arg(long, value_enum, default_value_t = UpscaleCli::Bilinear)]
+    upscale: UpscaleCli,
+
+    /// Sun shadow map size in texels per cascade (512-4096); Shift+J cycles it in-game
+    #[arg(long, default_value_t = 2048)]
+    shadow_resolution: i32,
+
+    /// Sun shadow cascades (0-3, 0 turns shadows off); J cycles them in-game
+    #[arg(long, default_value_t = 2)]
+    shadow_cascades: usize,
+
+    /// World border: 'world' for the chunks_x × chunks_z extent (around the center unless
+    /// --finite-world), a half-size around the world center, or min_x,min_z,max_x,max_z in blocks
+    #[arg(long, value_name = "SPEC", value_parser = app::WorldBorderSpec::parse)]
+    world_border: Option<app::WorldBorderSpec>,
+
+    /// Directory to load world edits from and autosave them to
+    #[arg(long, value_name = "PATH")]
+    save_dir: Option<PathBuf>,
+
+    /// Directory to cache built chunks in, so streamed chunks load instead of regenerating
+    #[arg(long, value_name = "PATH")]
+    chunk_cache: Option<PathBuf>,
+
+    /// Directory crash bundles (panic, state dump, recent events, last frame) are written to
+    #[arg(long, value_name = "PATH", default_value = "crashes")]
+    crash_dir: PathBuf,
+
+    /// Accessibility settings (UI scale, overlay palette, reduced flashing), loaded at startup
+    /// and rewritten when they change in-game
+    #[arg(long, value_name = "PATH", default_value = "settings.toml")]
+    settings: PathBuf,
+
+    /// Initial spectator (N) flight speed in blocks per second; the mouse wheel adjusts it in-game
+    #[arg(long, default_value_t = 16.0)]
+    spectator_speed: f32,
+
+    /// Generate every chunk twice (fresh vs pooled/profile GenCtx) and log any divergence
+    #[arg(long, default_value_t = false)]
+    check_gen_determinism: bool,
+
     /// Generate chunks up to radius 1 and print terrain metrics instead of launching the viewer
     #[arg(long, default_value_t = false)]
     terrain_metrics: bool,
@@ -123,21 +225,56 @@ struct RunArgs {
     terrain_metrics_vertical: Option<i32>,
 }
 
+impl RunArgs {
+    fn extent(&self) -> WorldExtent {
+        if self.finite_world {
+            WorldExtent::Finite {
+                chunks_x: self.chunks_x.max(1),
+                chunks_z: self.chunks_z.max(1),
+            }
+        } else {
+            WorldExtent::Unbounded
+        }
+    }
+}
+
 impl Default for RunArgs {
     fn default() -> Self {
         Self {
             world: WorldKind::Normal,
             flat_thickness: None,
+            heightmap: HeightmapArgs::default(),
             seed: 1337,
             chunks_x: 4,
             chunks_y_hint: 8,
             chunks_z: 4,
+            finite_world: false,
             watch_textures: true,
             world_config: "assets/worldgen/worldgen.toml".to_string(),
             watch_worldgen: true,
             rebuild_on_worldgen_change: true,
             fixed_time: None,
             no_frustum_culling: false,
+            no_occlusion_culling: false,
+            below_world: None,
+            night_ambient: geist_lighting::DEFAULT_NIGHT_AMBIENT,
+            ao_strength: geist_mesh_cpu::DEFAULT_AO_STRENGTH,
+            smooth_normals: false,
+            cull_sealed_pockets: false,
+            texture_array: false,
+            wide_indices: false,
+            no_msaa: false,
+            render_scale: 1.0,
+            upscale: UpscaleCli::Bilinear,
+            shadow_resolution: 2048,
+            shadow_cascades: 2,
+            world_border: None,
+            save_dir: None,
+            chunk_cache: None,
+            crash_dir: PathBuf::from("crashes"),
+            settings: PathBuf::from("settings.toml"),
+            spectator_speed: 16.0,
+            check_gen_determinism: false,
             terrain_metrics: false,
             terrain_metrics_radius: 6,
             terrain_metrics_vertical: None,
@@ -151,6 +288,53 @@ enum WorldKind {
     Normal,
     Flat,
     SchemOnly,
+    /// Terrain from a grayscale PNG or EXR image (see --heightmap)
+    Heightmap,
+}
+
+#[derive(Args, Clone, Debug, Default)]
+struct HeightmapArgs {
+    /// Heightmap image, one pixel per block column (used when --world=heightmap)
+    #[arg(long, value_name = "PATH")]
+    heightmap: Option<PathBuf>,
+
+    /// Ground height of the darkest heightmap value (default 1)
+    #[arg(long)]
+    heightmap_min_y: Option<i32>,
+
+    /// Ground height of the brightest heightmap value (default 3/4 of the world height)
+    #[arg(long)]
+    heightmap_max_y: Option<i32>,
+
+    /// Fill heightmap terrain with water below this height
+    #[arg(long)]
+    heightmap_water: Option<i32>,
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+enum BelowWorldCli {
+    /// Unlit open space; skylight never rises out of it (sky islands)
+    Void,
+    /// Bedrock; nothing below the world seeds the bottom chunks
+    Solid,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum UpscaleCli {
+    Nearest,
+    Bilinear,
+    /// Bilinear plus a contrast-adaptive sharpen
+    Sharpen,
+}
+
+impl From<UpscaleCli> for geist_render_raylib::UpscaleFilter {
+    fn from(v: UpscaleCli) -> Self {
+        match v {
+            UpscaleCli::Nearest => Self::Nearest,
+            UpscaleCli::Bilinear => Self::Bilinear,
+            UpscaleCli::Sharpen => Self::Sharpen,
+        }
+    }
 }
 
 #[derive(Clone, Debug, ValueEnum)]
@@ -217,6 +401,9 @@ struct OverviewArgs {
     #[arg(long)]
     flat_thickness: Option<i32>,
 
+    #[command(flatten)]
+    heightmap: HeightmapArgs,
+
     /// World seed
     #[arg(long, default_value_t = 1337)]
     seed: i32,
@@ -241,16 +428,205 @@ struct OverviewArgs {
     )]
     world_config: String,
 
+    /// Lowest Y level for cave slice modes (defaults to 0)
+    #[arg(long)]
+    slice_y_min: Option<i32>,
+
+    /// Highest Y level for cave slice modes (defaults to the world height hint)
+    #[arg(long)]
+    slice_y_max: Option<i32>,
+
+    /// Spacing in blocks between cave slices
+    #[arg(long, default_value_t = 8)]
+    slice_step: i32,
+
+    /// Image format for written overviews
+    #[arg(long, value_enum, default_value_t = ImageFormatCli::Png)]
+    format: ImageFormatCli,
+
+    /// Render the region as a COLSxROWS grid of tiles, one row of tiles at a time
+    #[arg(long, value_name = "COLSxROWS", value_parser = parse_tile_grid)]
+    tiles: Option<TileGrid>,
+
+    /// With --tiles: also stream the tiles into one stitched PNG map
+    #[arg(long)]
+    stitch: bool,
+
+    /// With --stitch: extra stitched maps, each half the size of the previous one
+    #[arg(long, default_value_t = 0)]
+    zoom_levels: u32,
+
+    /// With --stitch: write only the stitched maps, not the individual tiles
+    #[arg(long)]
+    no_tile_files: bool,
+
     /// Output directory for generated image
     #[arg(long, value_name = "DIR", default_value = "showcase_output")]
     output: String,
 }
 
+#[derive(Args, Debug)]
+struct NavArgs {
+    /// World generation preset
+    #[arg(long, value_enum, default_value_t = WorldKind::Normal)]
+    world: WorldKind,
+
+    /// Flat world thickness (used when --world=flat)
+    #[arg(long)]
+    flat_thickness: Option<i32>,
+
+    #[command(flatten)]
+    heightmap: HeightmapArgs,
+
+    /// World seed
+    #[arg(long, default_value_t = 1337)]
+    seed: i32,
+
+    /// Number of chunk columns along X to summarize, starting at 0
+    #[arg(long, default_value_t = 4)]
+    chunks_x: usize,
+
+    /// Hint for the number of vertical chunks (sets the world height)
+    #[arg(long = "chunks-y-hint", alias = "chunks-y", default_value_t = 8)]
+    chunks_y_hint: usize,
+
+    /// Number of chunk columns along Z to summarize, starting at 0
+    #[arg(long, default_value_t = 4)]
+    chunks_z: usize,
+
+    /// Worldgen config path (TOML)
+    #[arg(
+        long,
+        value_name = "PATH",
+        default_value = "assets/worldgen/worldgen.toml"
+    )]
+    world_config: String,
+
+    /// Output directory for the exported graph
+    #[arg(long, value_name = "DIR", default_value = "showcase_output")]
+    output: String,
+}
+
+#[derive(Args, Debug)]
+struct ExportArgs {
+    /// Chunk columns to export, inclusive (min_cx,min_cz,max_cx,max_cz)
+    #[arg(long, value_parser = parse_chunk_region)]
+    region: ChunkRegion,
+
+    /// Output format; inferred from the --output extension when omitted
+    #[arg(long, value_enum)]
+    format: Option<ExportFormatCli>,
+
+    /// World generation preset
+    #[arg(long, value_enum, default_value_t = WorldKind::Normal)]
+    world: WorldKind,
+
+    /// Flat world thickness (used when --world=flat)
+    #[arg(long)]
+    flat_thickness: Option<i32>,
+
+    #[command(flatten)]
+    heightmap: HeightmapArgs,
+
+    /// World seed
+    #[arg(long, default_value_t = 1337)]
+    seed: i32,
+
+    /// Hint for the number of vertical chunks (sets the world height)
+    #[arg(long = "chunks-y-hint", alias = "chunks-y", default_value_t = 8)]
+    chunks_y_hint: usize,
+
+    /// Worldgen config path (TOML)
+    #[arg(
+        long,
+        value_name = "PATH",
+        default_value = "assets/worldgen/worldgen.toml"
+    )]
+    world_config: String,
+
+    /// Output file (.gltf or .obj); defaults to a timestamped file in showcase_output
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct HashesArgs {
+    /// Chunk columns to hash, inclusive (min_cx,min_cz,max_cx,max_cz)
+    #[arg(long, value_parser = parse_chunk_region)]
+    region: ChunkRegion,
+
+    /// World generation preset
+    #[arg(long, value_enum, default_value_t = WorldKind::Normal)]
+    world: WorldKind,
+
+    /// Flat world thickness (used when --world=flat)
+    #[arg(long)]
+    flat_thickness: Option<i32>,
+
+    #[command(flatten)]
+    heightmap: HeightmapArgs,
+
+    /// World seed
+    #[arg(long, default_value_t = 1337)]
+    seed: i32,
+
+    /// Hint for the number of vertical chunks (sets the world height)
+    #[arg(long = "chunks-y-hint", alias = "chunks-y", default_value_t = 8)]
+    chunks_y_hint: usize,
+
+    /// Worldgen config path (TOML)
+    #[arg(
+        long,
+        value_name = "PATH",
+        default_value = "assets/worldgen/worldgen.toml"
+    )]
+    world_config: String,
+
+    /// Generation threads; hashes must not depend on this
+    #[arg(long, default_value_t = 1)]
+    threads: usize,
+
+    /// Write the manifest here instead of stdout
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Compare against this manifest and exit with status 1 on any difference
+    #[arg(long, value_name = "FILE")]
+    check: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ExportFormatCli {
+    Gltf,
+    Obj,
+}
+
+impl ExportFormatCli {
+    fn format(self) -> ExportFormat {
+        match self {
+            ExportFormatCli::Gltf => ExportFormat::Gltf,
+            ExportFormatCli::Obj => ExportFormat::Obj,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ChunkRegion {
+    min_cx: i32,
+    min_cz: i32,
+    max_cx: i32,
+    max_cz: i32,
+}
+
 #[derive(Clone, Debug, ValueEnum)]
 enum OverviewModeCli {
     Heightmap,
     Biomemap,
     Cavepreview,
+    /// Cave slices at each Y level tiled into a single contact sheet
+    Caveslices,
+    /// Cave slices written as one image per Y level
+    Cavestack,
 }
 
 impl OverviewModeCli {
@@ -259,20 +635,58 @@ impl OverviewModeCli {
             OverviewModeCli::Heightmap => "heightmap",
             OverviewModeCli::Biomemap => "biomemap",
             OverviewModeCli::Cavepreview => "cavepreview",
+            OverviewModeCli::Caveslices => "caveslices",
+            OverviewModeCli::Cavestack => "cavestack",
         }
     }
-}
 
-impl From<OverviewModeCli> for OverviewMode {
-    fn from(value: OverviewModeCli) -> Self {
-        match value {
+    fn to_mode(&self, slices: CaveSliceRange) -> OverviewMode {
+        match self {
             OverviewModeCli::Heightmap => OverviewMode::HeightMap,
             OverviewModeCli::Biomemap => OverviewMode::BiomeMap,
             OverviewModeCli::Cavepreview => OverviewMode::CavePreview,
+            OverviewModeCli::Caveslices | OverviewModeCli::Cavestack => {
+                OverviewMode::CaveSlices(slices)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ImageFormatCli {
+    Png,
+    Ppm,
+}
+
+impl ImageFormatCli {
+    fn extension(&self) -> &'static str {
+        match self {
+            ImageFormatCli::Png => "png",
+            ImageFormatCli::Ppm => "ppm",
         }
     }
 }
 
+#[derive(Clone, Copy, Debug)]
+struct TileGrid {
+    cols: usize,
+    rows: usize,
+}
+
+fn parse_tile_grid(arg: &str) -> Result<TileGrid, String> {
+    let (cols, rows) = arg
+        .split_once(['x', 'X'])
+        .ok_or_else(|| "tiles must be COLSxROWS, e.g. 4x4".to_string())?;
+    let parse = |v: &str| match v.trim().parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("invalid tile count '{}'", v.trim())),
+    };
+    Ok(TileGrid {
+        cols: parse(cols)?,
+        rows: parse(rows)?,
+    })
+}
+
 fn parse_overview_region(arg: &str) -> Result<OverviewRegion, String> {
     let parts: Vec<&str> = arg.split(',').collect();
     if parts.len() != 4 {
@@ -288,6 +702,29 @@ fn parse_overview_region(arg: &str) -> Result<OverviewRegion, String> {
     OverviewRegion::new(values[0], values[1], values[2], values[3]).map_err(|e| e.to_string())
 }
 
+fn parse_chunk_region(arg: &str) -> Result<ChunkRegion, String> {
+    let parts: Vec<&str> = arg.split(',').collect();
+    if parts.len() != 4 {
+        return Err("region must be min_cx,min_cz,max_cx,max_cz".to_string());
+    }
+    let mut values = [0i32; 4];
+    for (idx, part) in parts.iter().enumerate() {
+        values[idx] = part
+            .trim()
+            .parse::<i32>()
+            .map_err(|e| format!("invalid chunk coordinate '{}': {}", part.trim(), e))?;
+    }
+    if values[2] < values[0] || values[3] < values[1] {
+        return Err("region max must not be below its min".to_string());
+    }
+    Ok(ChunkRegion {
+        min_cx: values[0],
+        min_cz: values[1],
+        max_cx: values[2],
+        max_cz: values[3],
+    })
+}
+
 fn resolve_schem_paths(path: Option<PathBuf>) -> Result<Vec<PathBuf>, String> {
     let target = path.unwrap_or_else(|| PathBuf::from("schematics"));
     let metadata =
@@ -619,6 +1056,37 @@ fn load_block_registry(assets_root: &Path) -> Arc<BlockRegistry> {
     Arc::new(reg)
 }
 
+/// Key for cached chunks: they stay valid while the seed, generation mode, worldgen config,
+/// heightmap and block registry are unchanged.
+fn chunk_cache_key(
+    world: &World,
+    reg: &BlockRegistry,
+    assets_root: &Path,
+    config_path: &str,
+    heightmap: &HeightmapArgs,
+) -> u64 {
+    let cfg_path = Path::new(config_path);
+    let cfg_path_abs = if cfg_path.exists() {
+        cfg_path.to_path_buf()
+    } else {
+        assets_root.join(cfg_path)
+    };
+    let config = std::fs::read(&cfg_path_abs).unwrap_or_default();
+    let mut mode = format!("{:?}", world.mode);
+    let mut heightmap_bytes = Vec::new();
+    if let WorldGenMode::Heightmap { path } = &world.mode {
+        mode.push_str(&format!("{:?}", heightmap));
+        heightmap_bytes = std::fs::read(path).unwrap_or_default();
+    }
+    geist_io::chunk_cache_key(&[
+        &world.seed.to_le_bytes(),
+        mode.as_bytes(),
+        &config,
+        &heightmap_bytes,
+        &reg.fingerprint().to_le_bytes(),
+    ])
+}
+
 fn load_worldgen_params(world: &World, assets_root: &Path, config_path: &str) {
     let cfg_path = Path::new(config_path);
     let cfg_path_abs = if cfg_path.exists() {
@@ -649,6 +1117,47 @@ fn load_worldgen_params(world: &World, assets_root: &Path, config_path: &str) {
     }
 }
 
+/// World for a `--world` preset; heightmap worlds load their image here.
+fn create_world(
+    kind: &WorldKind,
+    flat_thickness: Option<i32>,
+    heightmap: &HeightmapArgs,
+    chunks_x: usize,
+    chunks_y_hint: usize,
+    chunks_z: usize,
+    seed: i32,
+) -> Result<World, String> {
+    let mode = match kind {
+        WorldKind::SchemOnly => WorldGenMode::Flat { thickness: 0 },
+        WorldKind::Flat => WorldGenMode::Flat {
+            thickness: flat_thickness.unwrap_or(1),
+        },
+        WorldKind::Normal => WorldGenMode::Normal,
+        WorldKind::Heightmap => {
+            let path = heightmap
+                .heightmap
+                .as_deref()
+                .ok_or("--world heightmap requires --heightmap PATH")?;
+            let world_height = (chunks_y_hint * geist_world::CHUNK_SIZE) as i32;
+            let defaults = geist_io::HeightmapOptions::for_world_height(world_height);
+            let opts = geist_io::HeightmapOptions {
+                min_y: heightmap.heightmap_min_y.unwrap_or(defaults.min_y),
+                max_y: heightmap.heightmap_max_y.unwrap_or(defaults.max_y),
+                water_level: heightmap.heightmap_water,
+            };
+            if opts.max_y < opts.min_y {
+                return Err("--heightmap-max-y must not be below --heightmap-min-y".to_string());
+            }
+            return geist_io::heightmap_world(chunks_x, chunks_y_hint, chunks_z, seed, path, &opts)
+                .map_err(|e| format!("heightmap import failed: {}", e));
+        }
+    };
+    if heightmap.heightmap.is_some() {
+        log::warn!("--heightmap is ignored unless --world heightmap");
+    }
+    Ok(World::new(chunks_x, chunks_y_hint, chunks_z, seed, mode))
+}
+
 #[derive(Clone)]
 struct ChunkReport {
     coord: ChunkCoord,
@@ -702,21 +1211,21 @@ fn run_terrain_metrics(run: &RunArgs, assets_root: &Path) {
         chunks_y_hint = 1;
     }
 
-    let world_mode = match run.world {
-        WorldKind::SchemOnly => WorldGenMode::Flat { thickness: 0 },
-        WorldKind::Flat => WorldGenMode::Flat {
-            thickness: run.flat_thickness.unwrap_or(1),
-        },
-        WorldKind::Normal => WorldGenMode::Normal,
-    };
-
-    let world = World::new(
+    let world = match create_world(
+        &run.world,
+        run.flat_thickness,
+        &run.heightmap,
         run.chunks_x,
         chunks_y_hint,
         run.chunks_z,
         run.seed,
-        world_mode,
-    );
+    ) {
+        Ok(world) => world.with_extent(run.extent()),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+    };
 
     load_worldgen_params(&world, assets_root, &run.world_config);
 
@@ -731,7 +1240,7 @@ fn run_terrain_metrics(run: &RunArgs, assets_root: &Path) {
     let center = ChunkCoord::new(0, 0, 0);
     let coords = chunk_coords_within_radius(center, radius, vertical_limit);
     let mut columns: BTreeMap<(i32, i32), Vec<ChunkCoord>> = BTreeMap::new();
-    for coord in coords {
+    for coord in coords.into_iter().filter(|c| world.contains_chunk(*c)) {
         columns.entry((coord.cx, coord.cz)).or_default().push(coord);
     }
     for column in columns.values_mut() {
@@ -785,6 +1294,7 @@ fn print_terrain_metrics_summary(
         WorldKind::Normal => "Normal",
         WorldKind::Flat => "Flat",
         WorldKind::SchemOnly => "SchemOnly",
+        WorldKind::Heightmap => "Heightmap",
     };
 
     println!(
@@ -1065,6 +1575,32 @@ fn main() {
                 std::process::exit(2);
             }
         }
+        Command::Nav(args) => {
+            if let Err(err) = run_nav(args, assets_root.as_path()) {
+                eprintln!("Nav export failed: {}", err);
+                std::process::exit(2);
+            }
+        }
+        Command::Export(args) => {
+            if let Err(err) = run_export(args, assets_root.as_path()) {
+                eprintln!("Export failed: {}", err);
+                std::process::exit(2);
+            }
+        }
+        Command::Snap(args) => {
+            if let Err(err) = run_snap(args, assets_root.as_path()) {
+                eprintln!("Snapshot failed: {}", err);
+                std::process::exit(2);
+            }
+        }
+        Command::Hashes(args) => match run_hashes(args, assets_root.as_path()) {
+            Ok(true) => {}
+            Ok(false) => std::process::exit(1),
+            Err(err) => {
+                eprintln!("Chunk hashing failed: {}", err);
+                std::process::exit(2);
+            }
+        },
         Command::Run(run) => {
             if run.terrain_metrics {
                 run_terrain_metrics(&run, assets_root.as_path());
@@ -1076,18 +1612,22 @@ fn main() {
 }
 
 fn run_app(run: RunArgs, assets_root: std::path::PathBuf) {
+    crate::app::install_crash_hook(run.crash_dir.clone());
     // Silence raylib's internal logging unless debugging raylib itself
     unsafe {
         // 7 == LOG_NONE in raylib (0 was LOG_NONE; 0 was LOG_ALL and was too chatty)
         raylib::ffi::SetTraceLogLevel(7);
     }
 
-    let (mut rl, thread) = raylib::init()
+    let mut builder = raylib::init();
+    builder
         .size(1280, 720)
         .title("Geist Voxel View (Rust)")
-        .msaa_4x()
-        .resizable()
-        .build();
+        .resizable();
+    if !run.no_msaa {
+        builder.msaa_4x();
+    }
+    let (mut rl, thread) = builder.build();
 
     // Some raylib builds reset the trace level during init; set it again after init
     unsafe {
@@ -1113,20 +1653,21 @@ fn run_app(run: RunArgs, assets_root: std::path::PathBuf) {
     }
     let chunks_z = run.chunks_z;
     let world_seed = run.seed;
-    let world_mode = match run.world {
-        WorldKind::SchemOnly => WorldGenMode::Flat { thickness: 0 },
-        WorldKind::Flat => WorldGenMode::Flat {
-            thickness: run.flat_thickness.unwrap_or(1),
-        },
-        WorldKind::Normal => WorldGenMode::Normal,
-    };
-    let world = Arc::new(World::new(
+    let world = match create_world(
+        &run.world,
+        run.flat_thickness,
+        &run.heightmap,
         chunks_x,
         chunks_y_hint,
         chunks_z,
         world_seed,
-        world_mode,
-    ));
+    ) {
+        Ok(world) => Arc::new(world.with_extent(run.extent())),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+    };
     // Initial worldgen params load (optional)
     load_worldgen_params(world.as_ref(), &assets_root, &run.world_config);
     let lighting_store = Arc::new(geist_lighting::LightingStore::new(
@@ -1134,6 +1675,18 @@ fn run_app(run: RunArgs, assets_root: std::path::PathBuf) {
         world.chunk_size_y,
         world.chunk_size_z,
     ));
+    // Worldgen is air below y=0, so the floor row is cy=0.
+    let below = match (&run.below_world, &run.world) {
+        (Some(BelowWorldCli::Void), _) | (None, WorldKind::SchemOnly) => {
+            geist_lighting::BelowWorld::Void
+        }
+        (Some(BelowWorldCli::Solid), _) | (None, _) => geist_lighting::BelowWorld::Solid,
+    };
+    lighting_store.set_world_floor(Some(geist_lighting::WorldFloor { min_cy: 0, below }));
+    lighting_store.set_night_ambient(run.night_ambient);
+    geist_mesh_cpu::set_ao_strength(run.ao_strength);
+    geist_mesh_cpu::set_normal_smoothing(run.smooth_normals);
+    geist_mesh_cpu::set_sealed_pocket_culling(run.cull_sealed_pockets);
     let edit_store = geist_edit::EditStore::new(
         world.chunk_size_x as i32,
         world.chunk_size_y as i32,
@@ -1170,6 +1723,50 @@ fn run_app(run: RunArgs, assets_root: std::path::PathBuf) {
 
     // Apply initial frustum culling preference from CLI
     app.gs.frustum_culling_enabled = !run.no_frustum_culling;
+    app.gs.occlusion_culling_enabled = !run.no_occlusion_culling;
+    app.gs.spectator_speed = run.spectator_speed.clamp(1.0, 256.0);
+    // A finite world keeps the player inside its extent unless a border is given.
+    let border = run
+        .world_border
+        .or(run.finite_world.then_some(app::WorldBorderSpec::World));
+    app.set_world_border(border.map(|spec| spec.resolve(&app.gs.world)));
+    if run.texture_array {
+        app.enable_block_texture_array();
+    }
+    if run.wide_indices {
+        app.enable_wide_indices();
+    }
+    app.configure_scene_target(run.render_scale, run.upscale.into(), !run.no_msaa);
+    app.configure_shadows(geist_render_raylib::ShadowSettings {
+        resolution: run.shadow_resolution.clamp(512, 4096),
+        cascades: run
+            .shadow_cascades
+            .min(geist_render_raylib::MAX_SHADOW_CASCADES),
+        ..Default::default()
+    });
+    app.set_settings_path(run.settings.clone());
+    app.runtime
+        .set_gen_determinism_check(run.check_gen_determinism);
+    if let Some(dir) = run.save_dir.clone() {
+        app.set_save_dir(dir);
+    }
+    if let Some(dir) = run.chunk_cache.clone() {
+        let key = chunk_cache_key(
+            &world,
+            &reg,
+            &assets_root,
+            &run.world_config,
+            &run.heightmap,
+        );
+        let dims = (world.chunk_size_x, world.chunk_size_y, world.chunk_size_z);
+        match geist_io::ChunkCache::open(&dir, dims, key) {
+            Ok(cache) => {
+                log::info!("caching built chunks in {:?}", dir);
+                app.runtime.set_chunk_cache(Some(Arc::new(cache)));
+            }
+            Err(e) => log::error!("chunk cache at {:?} unavailable: {}", dir, e),
+        }
+    }
 
     while !rl.window_should_close() {
         let dt = rl.get_frame_time();
@@ -1180,6 +1777,7 @@ fn run_app(run: RunArgs, assets_root: std::path::PathBuf) {
         app.step(&mut rl, &thread, dt);
         app.render(&mut rl, &thread);
     }
+    app.shutdown();
 }
 
 fn run_overview(args: OverviewArgs, assets_root: &Path) -> Result<(), String> {
@@ -1188,36 +1786,60 @@ fn run_overview(args: OverviewArgs, assets_root: &Path) -> Result<(), String> {
         mode: mode_cli,
         world,
         flat_thickness,
+        heightmap,
         seed,
         chunks_x,
         chunks_y_hint,
         chunks_z,
         world_config,
+        slice_y_min,
+        slice_y_max,
+        slice_step,
+        format,
+        tiles,
+        stitch,
+        zoom_levels,
+        no_tile_files,
         output,
     } = args;
 
-    let world_mode = match world {
-        WorldKind::SchemOnly => WorldGenMode::Flat { thickness: 0 },
-        WorldKind::Flat => WorldGenMode::Flat {
-            thickness: flat_thickness.unwrap_or(1),
-        },
-        WorldKind::Normal => WorldGenMode::Normal,
-    };
+    if tiles.is_none() && (stitch || zoom_levels > 0 || no_tile_files) {
+        return Err("--stitch, --zoom-levels and --no-tile-files require --tiles".to_string());
+    }
+    if !stitch && (zoom_levels > 0 || no_tile_files) {
+        return Err("--zoom-levels and --no-tile-files require --stitch".to_string());
+    }
+    if zoom_levels > 16 {
+        return Err("--zoom-levels must be at most 16".to_string());
+    }
+    if tiles.is_some()
+        && matches!(
+            mode_cli,
+            OverviewModeCli::Caveslices | OverviewModeCli::Cavestack
+        )
+    {
+        return Err("--tiles supports the heightmap, biomemap and cavepreview modes".to_string());
+    }
 
-    let world = Arc::new(World::new(
+    let world = Arc::new(create_world(
+        &world,
+        flat_thickness,
+        &heightmap,
         chunks_x,
         chunks_y_hint,
         chunks_z,
         seed,
-        world_mode,
-    ));
+    )?);
 
     load_worldgen_params(world.as_ref(), assets_root, &world_config);
 
+    let slices = CaveSliceRange::new(
+        slice_y_min.unwrap_or(0),
+        slice_y_max.unwrap_or(world.world_height_hint() as i32 - 1),
+        slice_step,
+    )
+    .map_err(|e| e.to_string())?;
     let overview = WorldOverview::new(world);
-    let mode: OverviewMode = mode_cli.clone().into();
-    let job = overview.spawn_region(region, mode);
-    let image = job.join().map_err(|e| e.to_string())?;
 
     fs::create_dir_all(&output)
         .map_err(|e| format!("failed to create output directory {}: {}", output, e))?;
@@ -1226,58 +1848,751 @@ fn run_overview(args: OverviewArgs, assets_root: &Path) -> Result<(), String> {
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_secs();
+
+    if matches!(mode_cli, OverviewModeCli::Cavestack) {
+        let layers = overview
+            .generate_cave_slices(region, slices)
+            .map_err(|e| e.to_string())?;
+        let dir = Path::new(&output).join(format!(
+            "overview_{}_{}x{}_{}",
+            mode_cli.as_str(),
+            region.width(),
+            region.height(),
+            timestamp
+        ));
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("failed to create output directory {:?}: {}", dir, e))?;
+        for layer in &layers {
+            write_overview_image(
+                &dir.join(format!("slice_y{:04}.{}", layer.y, format.extension())),
+                &layer.image,
+                format,
+            )?;
+        }
+        println!("Saved {} cave slices to {:?}", layers.len(), dir);
+        return Ok(());
+    }
+
+    if let Some(grid) = tiles {
+        let dir = Path::new(&output).join(format!(
+            "overview_{}_{}x{}_{}",
+            mode_cli.as_str(),
+            region.width(),
+            region.height(),
+            timestamp
+        ));
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("failed to create output directory {:?}: {}", dir, e))?;
+        let tiled = TiledOverview {
+            grid,
+            format,
+            tile_files: !no_tile_files,
+            zoom_levels: stitch.then_some(zoom_levels),
+        };
+        return write_tiled_overview(&overview, region, mode_cli.to_mode(slices), &tiled, &dir);
+    }
+
+    let job = overview.spawn_region(region, mode_cli.to_mode(slices));
+    let image = job.join().map_err(|e| e.to_string())?;
+
     let filename = format!(
-        "overview_{}_{}x{}_{}.ppm",
+        "overview_{}_{}x{}_{}.{}",
         mode_cli.as_str(),
         image.width,
         image.height,
-        timestamp
+        timestamp,
+        format.extension()
     );
     let output_path = Path::new(&output).join(filename);
+    write_overview_image(&output_path, &image, format)?;
+    if matches!(mode_cli, OverviewModeCli::Caveslices) {
+        let levels: Vec<String> = slices.levels().map(|y| y.to_string()).collect();
+        println!("Slice Y levels (row-major): {}", levels.join(", "));
+    }
+    println!("Saved overview to {:?}", output_path);
+    Ok(())
+}
+
+fn run_nav(args: NavArgs, assets_root: &Path) -> Result<(), String> {
+    let NavArgs {
+        world,
+        flat_thickness,
+        heightmap,
+        seed,
+        chunks_x,
+        chunks_y_hint,
+        chunks_z,
+        world_config,
+        output,
+    } = args;
+
+    let world = create_world(
+        &world,
+        flat_thickness,
+        &heightmap,
+        chunks_x,
+        chunks_y_hint,
+        chunks_z,
+        seed,
+    )?;
+    load_worldgen_params(&world, assets_root, &world_config);
+
+    let graph = NavGraph::generate(&world, (0, 0), (chunks_x as i32, chunks_z as i32));
+    let text = graph
+        .to_toml()
+        .map_err(|e| format!("failed to serialize nav graph: {}", e))?;
+
+    fs::create_dir_all(&output)
+        .map_err(|e| format!("failed to create output directory {}: {}", output, e))?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let output_path =
+        Path::new(&output).join(format!("nav_{}x{}_{}.toml", chunks_x, chunks_z, timestamp));
+    fs::write(&output_path, text)
+        .map_err(|e| format!("failed to write {:?}: {}", output_path, e))?;
+    let walkable: u32 = graph.nodes.iter().map(|n| n.walkable).sum();
+    let water: u32 = graph.nodes.iter().map(|n| n.water).sum();
+    println!(
+        "Saved nav graph ({} chunk columns, {} edges, {} walkable / {} water cells) to {:?}",
+        graph.nodes.len(),
+        graph.edges.len(),
+        walkable,
+        water,
+        output_path
+    );
+    Ok(())
+}
+
+/// Returns whether the hashes matched `--check` (always true without it).
+fn run_hashes(args: HashesArgs, assets_root: &Path) -> Result<bool, String> {
+    let HashesArgs {
+        region,
+        world,
+        flat_thickness,
+        heightmap,
+        seed,
+        chunks_y_hint,
+        world_config,
+        threads,
+        output,
+        check,
+    } = args;
+
+    let reg = load_block_registry(assets_root);
+    let chunks_y = chunks_y_hint.max(1);
+    let world = create_world(
+        &world,
+        flat_thickness,
+        &heightmap,
+        (region.max_cx + 1).max(1) as usize,
+        chunks_y,
+        (region.max_cz + 1).max(1) as usize,
+        seed,
+    )?;
+    load_worldgen_params(&world, assets_root, &world_config);
+
+    let mut coords = Vec::new();
+    for cx in region.min_cx..=region.max_cx {
+        for cz in region.min_cz..=region.max_cz {
+            for cy in 0..chunks_y as i32 {
+                coords.push(ChunkCoord::new(cx, cy, cz));
+            }
+        }
+    }
+    let manifest = geist_chunk::ChunkHashManifest::generate(&world, &reg, &coords, threads);
+
+    let text = manifest.to_text();
+    match output {
+        Some(path) => {
+            fs::write(&path, text).map_err(|e| format!("failed to write {:?}: {}", path, e))?;
+            println!("Wrote {} chunk hashes to {:?}", manifest.len(), path);
+        }
+        None => print!("{}", text),
+    }
+
+    let Some(check) = check else {
+        return Ok(true);
+    };
+    let expected_src =
+        fs::read_to_string(&check).map_err(|e| format!("failed to read {:?}: {}", check, e))?;
+    let expected = geist_chunk::ChunkHashManifest::from_text(&expected_src)
+        .map_err(|e| format!("invalid manifest {:?}: {}", check, e))?;
+    let mismatches = manifest.mismatches(&expected);
+    let fmt_hash = |h: Option<u64>| h.map_or("missing".to_string(), |h| format!("{h:016x}"));
+    for m in &mismatches {
+        eprintln!(
+            "chunk ({}, {}, {}): expected {}, got {}",
+            m.coord.cx,
+            m.coord.cy,
+            m.coord.cz,
+            fmt_hash(m.expected),
+            fmt_hash(m.actual)
+        );
+    }
+    if mismatches.is_empty() {
+        eprintln!("All {} chunk hashes match {:?}", manifest.len(), check);
+    } else {
+        eprintln!(
+            "{} of {} chunks differ from {:?}",
+            mismatches.len(),
+            expected.len().max(manifest.len()),
+            check
+        );
+    }
+    Ok(mismatches.is_empty())
+}
+
+fn run_export(args: ExportArgs, assets_root: &Path) -> Result<(), String> {
+    let ExportArgs {
+        region,
+        format,
+        world,
+        flat_thickness,
+        heightmap,
+        seed,
+        chunks_y_hint,
+        world_config,
+        output,
+    } = args;
+
+    let format = match (format, output.as_deref().and_then(ExportFormat::from_path)) {
+        (Some(cli), _) => cli.format(),
+        (None, Some(inferred)) => inferred,
+        (None, None) => ExportFormat::Gltf,
+    };
+    let ext = match format {
+        ExportFormat::Gltf => "gltf",
+        ExportFormat::Obj => "obj",
+    };
+    let output_path = match output {
+        Some(path) => path,
+        None => {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            Path::new("showcase_output").join(format!(
+                "export_{}_{}_{}_{}_{}.{}",
+                region.min_cx, region.min_cz, region.max_cx, region.max_cz, timestamp, ext
+            ))
+        }
+    };
+
+    let reg = load_block_registry(assets_root);
+    let chunks_y = chunks_y_hint.max(1);
+    let world = create_world(
+        &world,
+        flat_thickness,
+        &heightmap,
+        (region.max_cx + 1).max(1) as usize,
+        chunks_y,
+        (region.max_cz + 1).max(1) as usize,
+        seed,
+    )?;
+    load_worldgen_params(&world, assets_root, &world_config);
+
+    let lighting = geist_lighting::LightingStore::new(
+        world.chunk_size_x,
+        world.chunk_size_y,
+        world.chunk_size_z,
+    );
+    let mut export = MeshExport::new();
+    let mut chunks = 0usize;
+    for cx in region.min_cx..=region.max_cx {
+        for cz in region.min_cz..=region.max_cz {
+            let mut ctx = world.make_gen_ctx();
+            // Top down, so skylight borders from above are in the store before each build.
+            for cy in (0..chunks_y as i32).rev() {
+                let coord = ChunkCoord::new(cx, cy, cz);
+                let generated =
+                    geist_chunk::generate_chunk_buffer_with_ctx(&world, coord, &reg, &mut ctx);
+                let Some((mesh, borders)) = geist_mesh_cpu::build_chunk_wcc_cpu_buf(
+                    &generated.buf,
+                    Some(&lighting),
+                    &world,
+                    None,
+                    coord,
+                    &reg,
+                ) else {
+                    continue;
+                };
+                if let Some(borders) = borders {
+                    lighting.update_borders(coord, borders);
+                }
+                export.add_chunk(&mesh);
+                chunks += 1;
+            }
+        }
+    }
+
+    if let Some(dir) = output_path.parent()
+        && !dir.as_os_str().is_empty()
+    {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("failed to create output directory {:?}: {}", dir, e))?;
+    }
+    let written = export
+        .write(&output_path, format, &reg.materials)
+        .map_err(|e| format!("failed to write {:?}: {}", output_path, e))?;
+    println!(
+        "Exported {} chunks ({} materials, {} vertices, {} triangles) to {:?}",
+        chunks,
+        export.parts().count(),
+        export.vertex_count(),
+        export.triangle_count(),
+        written
+    );
+    Ok(())
+}
+
+/// Light floor for baked snapshot meshes, as in the default ambiance's `visual_light_min`.
+const SNAP_VISUAL_LIGHT_MIN: f32 = 18.0 / 255.0;
+const SNAP_FOVY: f32 = 45.0;
+
+/// Meshes for a `chunks_x` by `chunks_z` region of the configured world, light baked in.
+fn snap_region_meshes(
+    args: &SnapArgs,
+    assets_root: &Path,
+    reg: &BlockRegistry,
+) -> Result<Vec<ChunkMeshCPU>, String> {
+    let chunks_y = args.chunks_y_hint.max(1);
+    let world = create_world(
+        &args.world,
+        args.flat_thickness,
+        &args.heightmap,
+        args.chunks_x.max(1),
+        chunks_y,
+        args.chunks_z.max(1),
+        args.seed,
+    )?;
+    load_worldgen_params(&world, assets_root, &args.world_config);
+
+    let lighting = geist_lighting::LightingStore::new(
+        world.chunk_size_x,
+        world.chunk_size_y,
+        world.chunk_size_z,
+    );
+    let mut meshes = Vec::new();
+    for cx in 0..args.chunks_x.max(1) as i32 {
+        for cz in 0..args.chunks_z.max(1) as i32 {
+            let mut ctx = world.make_gen_ctx();
+            // Top down, so skylight borders from above are in the store before each build.
+            for cy in (0..chunks_y as i32).rev() {
+                let coord = ChunkCoord::new(cx, cy, cz);
+                let generated =
+                    geist_chunk::generate_chunk_buffer_with_ctx(&world, coord, reg, &mut ctx);
+                let light = geist_lighting::compute_light_with_borders_buf(
+                    &generated.buf,
+                    &lighting,
+                    reg,
+                    &world,
+                );
+                let Some((mut mesh, borders)) = geist_mesh_cpu::build_chunk_wcc_cpu_buf_with_light(
+                    &generated.buf,
+                    &light,
+                    &world,
+                    None,
+                    coord,
+                    reg,
+                ) else {
+                    continue;
+                };
+                if let Some(borders) = borders {
+                    lighting.update_borders(coord, borders);
+                }
+                let nb = lighting.get_neighbor_borders(coord);
+                let atlas = geist_lighting::pack_light_grid_atlas_with_neighbors(&light, &nb);
+                geist_render_raylib::bake_vertex_light(
+                    &mut mesh,
+                    &atlas,
+                    1.0,
+                    SNAP_VISUAL_LIGHT_MIN,
+                );
+                meshes.push(mesh);
+            }
+        }
+    }
+    Ok(meshes)
+}
+
+/// Mesh for `--block` or `--schem` standing alone under open sky, with its name for
+/// the file names.
+fn snap_item_meshes(
+    args: &SnapArgs,
+    reg: &BlockRegistry,
+) -> Result<(String, Vec<ChunkMeshCPU>), String> {
+    use geist_io::StructureFromSchematic;
+
+    let (label, (sx, sy, sz), blocks) = if let Some(name) = &args.block {
+        let block = reg
+            .make_block_by_name(name, None)
+            .ok_or_else(|| format!("unknown block {:?}", name))?;
+        (name.clone(), (1, 1, 1), vec![block])
+    } else if let Some(path) = &args.schem {
+        let palette = geist_io::PaletteMap::load_default();
+        let st = geist_structures::Structure::from_schematic(path, reg, &palette)?.structure;
+        let label = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "schem".to_string());
+        (label, (st.sx, st.sy, st.sz), st.blocks.to_vec())
+    } else {
+        return Err("nothing to snap: pass --block or --schem".to_string());
+    };
+
+    // Pad with air on the sides and above so every visible face has a lit cell in front.
+    let (px, py, pz) = (sx + 2, sy + 1, sz + 2);
+    let mut padded = vec![geist_blocks::types::Block::AIR; px * py * pz];
+    for y in 0..sy {
+        for z in 0..sz {
+            for x in 0..sx {
+                padded[(y * pz + z + 1) * px + x + 1] = blocks[(y * sz + z) * sx + x];
+            }
+        }
+    }
+    let coord = ChunkCoord::new(0, 0, 0);
+    let buf = geist_chunk::ChunkBuf::from_blocks_local(coord, px, py, pz, padded);
+    let world = World::new(1, 1, 1, 0, WorldGenMode::Flat { thickness: 0 });
+    let store = geist_lighting::LightingStore::new(px, py, pz);
+    let light = geist_lighting::LightGrid::compute_with_borders_buf(&buf, &store, reg);
+    let Some((mut mesh, _)) =
+        geist_mesh_cpu::build_chunk_wcc_cpu_buf_with_light(&buf, &light, &world, None, coord, reg)
+    else {
+        return Ok((label, Vec::new()));
+    };
+    let atlas = geist_lighting::pack_light_grid_atlas_with_neighbors(
+        &light,
+        &store.get_neighbor_borders(coord),
+    );
+    geist_render_raylib::bake_vertex_light(&mut mesh, &atlas, 1.0, SNAP_VISUAL_LIGHT_MIN);
+    Ok((label, vec![mesh]))
+}
+
+/// Render the scene from `angles` evenly spaced yaws around its centre, through a hidden
+/// window, and save each view as `<label>_<nn>.png`. Light is baked into the meshes as in
+/// compatibility rendering, so the images do not depend on shader support or time of day.
+fn run_snap(args: SnapArgs, assets_root: &Path) -> Result<(), String> {
+    use geist_blocks::RenderPass;
+    use raylib::prelude::*;
+
+    if args.width <= 0 || args.height <= 0 {
+        return Err("--width and --height must be positive".to_string());
+    }
+    if args.angles == 0 {
+        return Err("--angles must be at least 1".to_string());
+    }
+
+    let reg = load_block_registry(assets_root);
+    let (label, meshes) = if args.block.is_none() && args.schem.is_none() {
+        let meshes = snap_region_meshes(&args, assets_root, &reg)?;
+        ("world".to_string(), meshes)
+    } else {
+        snap_item_meshes(&args, &reg)?
+    };
+    if meshes.is_empty() {
+        return Err(format!("{} produced no geometry", label));
+    }
+
+    unsafe {
+        raylib::ffi::SetTraceLogLevel(7);
+        // raylib ORs this into the flags the builder sets.
+        raylib::ffi::SetConfigFlags(ConfigFlags::FLAG_WINDOW_HIDDEN as u32);
+    }
+    let (mut rl, thread) = raylib::init()
+        .size(args.width, args.height)
+        .title("Geist Snap")
+        .build();
+    unsafe {
+        raylib::ffi::SetTraceLogLevel(7);
+    }
 
+    let mut tex_cache = geist_render_raylib::TextureCache::new();
+    let mut decorations =
+        geist_render_raylib::DecorationRenderer::load_with_base(&mut rl, &thread, assets_root);
+    let mut renders = Vec::with_capacity(meshes.len());
+    for cpu in meshes {
+        if let Some(dr) = decorations.as_mut() {
+            dr.prepare(&mut rl, &thread, &mut tex_cache, &reg, &cpu.decorations);
+        }
+        if let Some(cr) = geist_render_raylib::upload_chunk_mesh(
+            &mut rl,
+            &thread,
+            cpu,
+            &mut tex_cache,
+            &reg.materials,
+            None,
+            None,
+            None,
+        ) {
+            renders.push(cr);
+        }
+    }
+    let Some(first) = renders.first() else {
+        return Err(format!("failed to upload {}", label));
+    };
+    let (mut min, mut max) = (first.bbox.min, first.bbox.max);
+    for cr in &renders {
+        min = Vector3::new(
+            min.x.min(cr.bbox.min.x),
+            min.y.min(cr.bbox.min.y),
+            min.z.min(cr.bbox.min.z),
+        );
+        max = Vector3::new(
+            max.x.max(cr.bbox.max.x),
+            max.y.max(cr.bbox.max.y),
+            max.z.max(cr.bbox.max.z),
+        );
+    }
+    let centre = (min + max) * 0.5;
+    let radius = ((max - min).length() * 0.5).max(0.5);
+    // Back off until the bounding sphere fits the narrower of the two fields of view.
+    let aspect = args.width as f32 / args.height as f32;
+    let half_v = (SNAP_FOVY * 0.5).to_radians();
+    let half_h = (half_v.tan() * aspect).atan();
+    let dist = radius / half_v.min(half_h).sin();
+    let pitch = args.pitch.clamp(-89.0, 89.0).to_radians();
+
+    let mut target = rl
+        .load_render_texture(&thread, args.width as u32, args.height as u32)
+        .map_err(|e| format!("failed to create render target: {}", e))?;
+    fs::create_dir_all(&args.output)
+        .map_err(|e| format!("failed to create output directory {:?}: {}", args.output, e))?;
+    for i in 0..args.angles {
+        let yaw =
+            std::f32::consts::FRAC_PI_4 + std::f32::consts::TAU * i as f32 / args.angles as f32;
+        let eye = centre
+            + Vector3::new(
+                pitch.cos() * yaw.cos(),
+                pitch.sin(),
+                pitch.cos() * yaw.sin(),
+            ) * dist;
+        let camera = Camera3D::perspective(eye, centre, Vector3::new(0.0, 1.0, 0.0), SNAP_FOVY);
+        {
+            let mut td = rl.begin_texture_mode(&thread, &mut target);
+            td.clear_background(Color::new(150, 190, 235, 255));
+            let mut d3 = td.begin_mode3D(camera);
+            for cr in &renders {
+                for part in cr.parts_in(RenderPass::Opaque) {
+                    part.draw(&mut d3, Vector3::zero(), Color::WHITE);
+                }
+            }
+            if let Some(dr) = decorations.as_mut() {
+                dr.shader.update_frame_uniforms(
+                    eye,
+                    [0.0; 3],
+                    f32::MAX,
+                    f32::MAX,
+                    0.0,
+                    1.0,
+                    SNAP_VISUAL_LIGHT_MIN,
+                );
+                let all = renders.iter().flat_map(|cr| cr.decorations.iter());
+                dr.draw(&mut d3, &reg, all, Vector3::zero());
+            }
+            unsafe {
+                raylib::ffi::rlDrawRenderBatchActive();
+                raylib::ffi::rlDisableDepthMask();
+                raylib::ffi::rlDisableBackfaceCulling();
+            }
+            for cr in &renders {
+                for part in cr.parts_in(RenderPass::Translucent) {
+                    part.draw(&mut d3, Vector3::zero(), Color::WHITE);
+                }
+            }
+            unsafe {
+                raylib::ffi::rlDrawRenderBatchActive();
+                raylib::ffi::rlEnableDepthMask();
+                raylib::ffi::rlEnableBackfaceCulling();
+            }
+        }
+        let mut image = target
+            .load_image()
+            .map_err(|e| format!("failed to read back view {}: {}", i, e))?;
+        // Render targets come back bottom row first.
+        image.flip_vertical();
+        let path = args.output.join(format!("{}_{:02}.png", label, i));
+        let png = image
+            .export_image_to_memory(".png")
+            .map_err(|e| format!("failed to encode {:?}: {}", path, e))?;
+        fs::write(&path, png).map_err(|e| format!("failed to write {:?}: {}", path, e))?;
+    }
+    println!(
+        "Saved {} snapshots of {} to {:?}",
+        args.angles, label, args.output
+    );
+    Ok(())
+}
+
+struct TiledOverview {
+    grid: TileGrid,
+    format: ImageFormatCli,
+    tile_files: bool,
+    /// Extra half-size levels when the tiles are also stitched into one map.
+    zoom_levels: Option<u32>,
+}
+
+/// A stitched map at one zoom level, fed one strip of full-resolution rows at a time.
+struct StitchedLevel {
+    path: PathBuf,
+    sampler: RowDownsampler,
+    png: PngWriter<BufWriter<File>>,
+}
+
+/// Render the region tile row by tile row so only one strip is ever held in memory.
+fn write_tiled_overview(
+    overview: &WorldOverview,
+    region: OverviewRegion,
+    mode: OverviewMode,
+    tiled: &TiledOverview,
+    dir: &Path,
+) -> Result<(), String> {
+    let rows = region.split(tiled.grid.cols, tiled.grid.rows);
+    let (width, height) = (region.width(), region.height());
+    let mut levels = Vec::new();
+    if let Some(zoom_levels) = tiled.zoom_levels {
+        for level in 0..=zoom_levels {
+            let sampler = RowDownsampler::new(width, 1 << level);
+            let (w, h) = (sampler.output_width(), sampler.output_height(height));
+            let path = dir.join(format!("map_z{}_{}x{}.png", level, w, h));
+            let file = File::create(&path)
+                .map_err(|e| format!("failed to open {:?} for writing: {}", path, e))?;
+            let png = PngWriter::new(BufWriter::new(file), w, h)
+                .map_err(|e| format!("failed to write {:?}: {}", path, e))?;
+            levels.push(StitchedLevel { path, sampler, png });
+        }
+    }
+
+    let workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4);
+    let mut tile_count = 0;
+    let mut scaled = Vec::new();
+    for (r, row) in rows.iter().enumerate() {
+        let mut images = Vec::with_capacity(row.len());
+        for batch in row.chunks(workers) {
+            let jobs: Vec<_> = batch
+                .iter()
+                .map(|tile| overview.spawn_region(*tile, mode))
+                .collect();
+            for job in jobs {
+                images.push(job.join().map_err(|e| e.to_string())?);
+            }
+        }
+        if tiled.tile_files {
+            for (c, image) in images.iter().enumerate() {
+                let name = format!("tile_r{:03}_c{:03}.{}", r, c, tiled.format.extension());
+                write_overview_image(&dir.join(name), image, tiled.format)?;
+            }
+        }
+        tile_count += images.len();
+        if levels.is_empty() {
+            continue;
+        }
+        let strip_h = row[0].height();
+        let mut strip = vec![0u8; width * strip_h * 3];
+        let mut x0 = 0;
+        for image in &images {
+            let len = image.width * 3;
+            for y in 0..strip_h {
+                let dst = (y * width + x0) * 3;
+                strip[dst..dst + len].copy_from_slice(&image.data[y * len..(y + 1) * len]);
+            }
+            x0 += image.width;
+        }
+        for level in &mut levels {
+            scaled.clear();
+            level.sampler.push_rows(&strip, &mut scaled);
+            level
+                .png
+                .write_rows(&scaled)
+                .map_err(|e| format!("failed to write {:?}: {}", level.path, e))?;
+        }
+    }
+
+    if tiled.tile_files {
+        println!(
+            "Saved {} tiles ({}x{}) to {:?}",
+            tile_count,
+            tiled.grid.cols.min(width),
+            tiled.grid.rows.min(height),
+            dir
+        );
+    }
+    for mut level in levels {
+        scaled.clear();
+        level.sampler.finish(&mut scaled);
+        level
+            .png
+            .write_rows(&scaled)
+            .map_err(|e| format!("failed to write {:?}: {}", level.path, e))?;
+        level
+            .png
+            .finish()
+            .map_err(|e| format!("failed to write {:?}: {}", level.path, e))?;
+        println!("Saved stitched map to {:?}", level.path);
+    }
+    Ok(())
+}
+
+fn write_overview_image(
+    path: &Path,
+    image: &WorldOverviewImage,
+    format: ImageFormatCli,
+) -> Result<(), String> {
     let mut file = OpenOptions::new()
         .create(true)
         .truncate(true)
         .write(true)
-        .open(&output_path)
-        .map_err(|e| format!("failed to open {:?} for writing: {}", output_path, e))?;
-    write!(file, "P6\n{} {}\n255\n", image.width, image.height)
-        .map_err(|e| format!("failed to write PPM header: {}", e))?;
-    file.write_all(&image.data)
-        .map_err(|e| format!("failed to write PPM pixels: {}", e))?;
-    println!("Saved overview to {:?}", output_path);
-    Ok(())
+        .open(path)
+        .map_err(|e| format!("failed to open {:?} for writing: {}", path, e))?;
+    match format {
+        ImageFormatCli::Png => {
+            let png = image
+                .encode_png()
+                .map_err(|e| format!("failed to encode PNG: {}", e))?;
+            file.write_all(&png)
+                .map_err(|e| format!("failed to write PNG: {}", e))
+        }
+        ImageFormatCli::Ppm => {
+            write!(file, "P6\n{} {}\n255\n", image.width, image.height)
+                .map_err(|e| format!("failed to write PPM header: {}", e))?;
+            file.write_all(&image.data)
+                .map_err(|e| format!("failed to write PPM pixels: {}", e))
+        }
+    }
 }
 
 #[derive(Args, Debug)]
-pub struct SnapArgs {
+struct SnapArgs {
     /// Screenshot width in pixels
     #[arg(long, default_value_t = 512)]
-    pub width: i32,
+    width: i32,
 
     /// Screenshot height in pixels
     #[arg(long, default_value_t = 512)]
-    pub height: i32,
+    height: i32,
 
     /// Number of camera angles around each item (e.g., 4 or 8)
     #[arg(long, default_value_t = 8)]
-    pub angles: usize,
+    angles: usize,
 
     /// World seed
     #[arg(long, default_value_t = 1337)]
-    pub seed: i32,
+    seed: i32,
 
     /// Number of chunks along X
     #[arg(long, default_value_t = 4)]
-    pub chunks_x: usize,
+    chunks_x: usize,
 
     /// Hint for the number of vertical chunks to pre-stream
     #[arg(long = "chunks-y-hint", alias = "chunks-y", default_value_t = 8)]
-    pub chunks_y_hint: usize,
+    chunks_y_hint: usize,
 
     /// Number of chunks along Z
     #[arg(long, default_value_t = 4)]
-    pub chunks_z: usize,
+    chunks_z: usize,
 
     /// Worldgen config path (TOML)
     #[arg(
@@ -1285,5 +2600,32 @@ pub struct SnapArgs {
         value_name = "PATH",
         default_value = "assets/worldgen/worldgen.toml"
     )]
-    pub world_config: String,
+    world_config: String,
+
+    /// World generation preset
+    #[arg(long, value_enum, default_value_t = WorldKind::Normal)]
+    world: WorldKind,
+
+    /// Flat world thickness (used when --world=flat)
+    #[arg(long)]
+    flat_thickness: Option<i32>,
+
+    #[command(flatten)]
+    heightmap: HeightmapArgs,
+
+    /// Snap a single block by registry name instead of a generated world
+    #[arg(long, value_name = "NAME", conflicts_with = "schem")]
+    block: Option<String>,
+
+    /// Snap a schematic file instead of a generated world
+    #[arg(long, value_name = "PATH")]
+    schem: Option<PathBuf>,
+
+    /// Camera elevation above the horizon, in degrees
+    #[arg(long, default_value_t = 30.0)]
+    pitch: f32,
+
+    /// Output directory for the screenshots
+    #[arg(long, value_name = "DIR", default_value = "showcase_output/snap")]
+    output: PathBuf,
 }
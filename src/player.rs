@@ -1,6 +1,52 @@
 use raylib::prelude::*;
 
-use geist_blocks::{Block, BlockRegistry};
+use geist_blocks::{Block, BlockRegistry, FLUID_MAX_LEVEL};
+
+// Swimming: upward acceleration at full submersion, per-second damping of vertical speed in
+// fluid, and the share of walk speed kept while fully under.
+const BUOYANCY: f32 = 34.0;
+const FLUID_DRAG: f32 = 3.0;
+const SWIM_SPEED_MULT: f32 = 0.45;
+// Vertical speed while holding swim up (Space) or down (Q).
+const SWIM_VERTICAL_SPEED: f32 = 3.0;
+// Surface bobbing: buoyancy swings this fraction either way, this many times a second.
+const BOB_AMOUNT: f32 = 0.12;
+const BOB_RATE: f32 = 0.6;
+
+/// Solid things off the voxel grid (posed structures): whether a box, given by its min and
+/// max corners in the walker's space, overlaps any of them.
+pub type Obstacles<'a> = &'a dyn Fn(Vector3, Vector3) -> bool;
+
+/// Movement keys for one physics step.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WalkInput {
+    pub forward: bool,
+    pub back: bool,
+    pub left: bool,
+    pub right: bool,
+    pub run: bool,
+    /// Space went down this frame: jump, or push off the bottom in fluid.
+    pub jump: bool,
+    /// Space held: swim up.
+    pub swim_up: bool,
+    /// Q held: swim down.
+    pub swim_down: bool,
+}
+
+impl WalkInput {
+    pub fn from_keys(rl: &raylib::RaylibHandle) -> Self {
+        Self {
+            forward: rl.is_key_down(KeyboardKey::KEY_W),
+            back: rl.is_key_down(KeyboardKey::KEY_S),
+            left: rl.is_key_down(KeyboardKey::KEY_A),
+            right: rl.is_key_down(KeyboardKey::KEY_D),
+            run: rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT),
+            jump: rl.is_key_pressed(KeyboardKey::KEY_SPACE),
+            swim_up: rl.is_key_down(KeyboardKey::KEY_SPACE),
+            swim_down: rl.is_key_down(KeyboardKey::KEY_Q),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct Walker {
@@ -15,6 +61,8 @@ pub struct Walker {
     pub run_mult: f32,   // when LeftShift held
     pub jump_speed: f32, // initial jump velocity
     pub gravity: f32,    // negative
+    pub submersion: f32, // share of the body's height inside fluid, 0..=1
+    bob_phase: f32,
 }
 
 impl Walker {
@@ -31,9 +79,16 @@ impl Walker {
             run_mult: 1.6,
             jump_speed: 7.5,
             gravity: -25.0,
+            submersion: 0.0,
+            bob_phase: 0.0,
         }
     }
 
+    #[inline]
+    pub fn in_fluid(&self) -> bool {
+        self.submersion > 0.0
+    }
+
     pub fn eye_position(&self) -> Vector3 {
         Vector3::new(self.pos.x, self.pos.y + self.eye_height, self.pos.z)
     }
@@ -41,7 +96,7 @@ impl Walker {
     #[inline]
     fn is_solid_for_collision(reg: &BlockRegistry, b: Block) -> bool {
         if let Some(t) = reg.get(b.id) {
-            if t.name == "water" {
+            if t.fluid_level(b.state).is_some() {
                 return false;
             }
             return t.is_solid(b.state);
@@ -49,7 +104,41 @@ impl Walker {
         false
     }
 
-    fn aabb_collides_with<F>(&self, reg: &BlockRegistry, sample: &F, pos: Vector3) -> bool
+    /// Share of the body's height at `pos` below the surface of any block tagged as fluid,
+    /// sampled along the center column. Flowing fluid is as deep as its level.
+    fn fluid_submersion<F>(&self, reg: &BlockRegistry, sample: &F, pos: Vector3) -> f32
+    where
+        F: Fn(i32, i32, i32) -> Block,
+    {
+        let level_at = |x: i32, y: i32, z: i32| {
+            let b = sample(x, y, z);
+            reg.get(b.id).and_then(|t| t.fluid_level(b.state))
+        };
+        let (x, z) = (pos.x.floor() as i32, pos.z.floor() as i32);
+        let (bottom, top) = (pos.y, pos.y + self.height);
+        let mut wet = 0.0;
+        for y in bottom.floor() as i32..=top.floor() as i32 {
+            let Some(level) = level_at(x, y, z) else {
+                continue;
+            };
+            let fill = if level_at(x, y + 1, z).is_some() {
+                1.0
+            } else {
+                1.0 - f32::from(level) / f32::from(FLUID_MAX_LEVEL + 1)
+            };
+            let (lo, hi) = (y as f32, y as f32 + fill);
+            wet += (hi.min(top) - lo.max(bottom)).max(0.0);
+        }
+        (wet / self.height).clamp(0.0, 1.0)
+    }
+
+    fn aabb_collides_with<F>(
+        &self,
+        reg: &BlockRegistry,
+        sample: &F,
+        obstacles: Obstacles,
+        pos: Vector3,
+    ) -> bool
     where
         F: Fn(i32, i32, i32) -> Block,
     {
@@ -72,10 +161,19 @@ impl Walker {
                 }
             }
         }
-        false
+        let min = Vector3::new(pos.x - rx, pos.y, pos.z - rz);
+        let max = Vector3::new(pos.x + rx, pos.y + h, pos.z + rz);
+        obstacles(min, max)
     }
 
-    fn move_axis<F>(&mut self, reg: &BlockRegistry, sample: &F, axis: usize, amt: f32) -> f32
+    fn move_axis<F>(
+        &mut self,
+        reg: &BlockRegistry,
+        sample: &F,
+        obstacles: Obstacles,
+        axis: usize,
+        amt: f32,
+    ) -> f32
     where
         F: Fn(i32, i32, i32) -> Block,
     {
@@ -98,7 +196,7 @@ impl Walker {
                 1 => p.y += s,
                 _ => p.z += s,
             };
-            if self.aabb_collides_with(reg, sample, p) {
+            if self.aabb_collides_with(reg, sample, obstacles, p) {
                 break; // collision
             } else {
                 self.pos = p;
@@ -112,8 +210,9 @@ impl Walker {
     #[allow(clippy::too_many_arguments)]
     fn update_motion<F>(
         &mut self,
-        rl: &mut raylib::RaylibHandle,
+        input: WalkInput,
         sample: &F,
+        obstacles: Obstacles,
         reg: &BlockRegistry,
         dt: f32,
         yaw: f32,
@@ -138,39 +237,53 @@ impl Walker {
         }
 
         let mut wish = Vector3::zero();
-        if rl.is_key_down(KeyboardKey::KEY_W) {
+        if input.forward {
             wish += fwd;
         }
-        if rl.is_key_down(KeyboardKey::KEY_S) {
+        if input.back {
             wish -= fwd;
         }
-        if rl.is_key_down(KeyboardKey::KEY_A) {
+        if input.left {
             wish -= right;
         }
-        if rl.is_key_down(KeyboardKey::KEY_D) {
+        if input.right {
             wish += right;
         }
         if wish.length() > 0.0 {
             wish = wish.normalized();
         }
 
-        let run = if rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT) {
-            self.run_mult
-        } else {
-            1.0
-        };
+        let run = if input.run { self.run_mult } else { 1.0 };
 
-        let target_v = wish * self.speed * run;
+        self.submersion = self.fluid_submersion(reg, sample, self.pos);
+        let sub = self.submersion;
+        let swim = 1.0 + (SWIM_SPEED_MULT - 1.0) * sub;
+        let target_v = wish * self.speed * run * swim;
         let horiz = Vector3::new(target_v.x, 0.0, target_v.z);
 
         let mut below = self.pos;
         below.y -= 0.10;
-        self.on_ground = self.aabb_collides_with(reg, sample, below);
-        if self.on_ground {
+        self.on_ground = self.aabb_collides_with(reg, sample, obstacles, below);
+        if sub > 0.0 && !(self.on_ground && input.jump) {
+            // Gravity and buoyancy scale with submersion, so the body settles floating with
+            // its head out and bobs there; drag damps the vertical motion.
+            self.bob_phase = (self.bob_phase + BOB_RATE * dt).fract();
+            let bob = 1.0 + BOB_AMOUNT * (self.bob_phase * std::f32::consts::TAU).sin();
+            self.vel.y += (self.gravity + BUOYANCY * sub * bob) * dt;
+            self.vel.y *= (-FLUID_DRAG * sub * dt).exp();
+            if input.swim_up {
+                self.vel.y = self.vel.y.max(SWIM_VERTICAL_SPEED);
+            } else if input.swim_down {
+                self.vel.y = self.vel.y.min(-SWIM_VERTICAL_SPEED);
+            }
+            if self.on_ground && self.vel.y < 0.0 {
+                self.vel.y = 0.0;
+            }
+        } else if self.on_ground {
             if self.vel.y < 0.0 {
                 self.vel.y = 0.0;
             }
-            if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
+            if input.jump {
                 self.vel.y = self.jump_speed;
                 self.on_ground = false;
             }
@@ -182,14 +295,14 @@ impl Walker {
         let dz = horiz.z * dt;
         let dy = self.vel.y * dt;
         let moved_y = if dy > 0.0 {
-            let my = self.move_axis(reg, sample, 1, dy);
-            self.move_axis(reg, sample, 0, dx);
-            self.move_axis(reg, sample, 2, dz);
+            let my = self.move_axis(reg, sample, obstacles, 1, dy);
+            self.move_axis(reg, sample, obstacles, 0, dx);
+            self.move_axis(reg, sample, obstacles, 2, dz);
             my
         } else {
-            self.move_axis(reg, sample, 0, dx);
-            self.move_axis(reg, sample, 2, dz);
-            self.move_axis(reg, sample, 1, dy)
+            self.move_axis(reg, sample, obstacles, 0, dx);
+            self.move_axis(reg, sample, obstacles, 2, dz);
+            self.move_axis(reg, sample, obstacles, 1, dy)
         };
         if dy < 0.0 && moved_y.abs() < dy.abs() * 0.5 {
             self.on_ground = true;
@@ -203,6 +316,7 @@ impl Walker {
         &mut self,
         rl: &mut raylib::RaylibHandle,
         sample: &F,
+        obstacles: Obstacles,
         reg: &BlockRegistry,
         dt: f32,
         yaw: f32,
@@ -212,13 +326,16 @@ impl Walker {
         let yaw_rad = yaw.to_radians();
         let forward = Vector3::new(yaw_rad.cos(), 0.0, yaw_rad.sin());
         let right = forward.cross(Vector3::up());
-        self.update_motion(rl, sample, reg, dt, yaw, forward, right);
+        let input = WalkInput::from_keys(rl);
+        self.update_motion(input, sample, obstacles, reg, dt, yaw, forward, right);
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn update_structure_space<F>(
         &mut self,
         rl: &mut raylib::RaylibHandle,
         sample: &F,
+        obstacles: Obstacles,
         reg: &BlockRegistry,
         dt: f32,
         yaw: f32,
@@ -229,8 +346,92 @@ impl Walker {
         let local_yaw_rad = anchor_yaw_offset.to_radians();
         let local_forward = Vector3::new(local_yaw_rad.cos(), 0.0, local_yaw_rad.sin());
         let local_right = local_forward.cross(Vector3::up());
-        self.update_motion(rl, sample, reg, dt, yaw, local_forward, local_right);
+        self.update_motion(
+            WalkInput::from_keys(rl),
+            sample,
+            obstacles,
+            reg,
+            dt,
+            yaw,
+            local_forward,
+            local_right,
+        );
     }
 
     // No back-compat path: the walker updates only via an explicit sampler tied to loaded chunk buffers.
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1.0 / 60.0;
+    /// Water fills y in 0..SURFACE over a stone floor.
+    const SURFACE: i32 = 40;
+
+    fn registry() -> BlockRegistry {
+        let vox = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/voxels");
+        BlockRegistry::load_from_paths(vox.join("materials.toml"), vox.join("blocks.toml"))
+            .expect("registry")
+    }
+
+    /// Step `walker` for `steps` frames in a world that is all water below `surface`.
+    fn run(walker: &mut Walker, reg: &BlockRegistry, surface: i32, input: WalkInput, steps: usize) {
+        let block = |name: &str| reg.make_block_by_name(name, None).expect(name);
+        let (stone, water, air) = (block("stone"), block("water"), block("air"));
+        let sample = |_x: i32, y: i32, _z: i32| match y {
+            y if y < 0 => stone,
+            y if y < surface => water,
+            _ => air,
+        };
+        let forward = Vector3::new(1.0, 0.0, 0.0);
+        let right = forward.cross(Vector3::up());
+        let none = |_: Vector3, _: Vector3| false;
+        for _ in 0..steps {
+            walker.update_motion(input, &sample, &none, reg, DT, 0.0, forward, right);
+        }
+    }
+
+    #[test]
+    fn water_drag_slows_a_fall_that_air_does_not() {
+        let reg = registry();
+        let mut wet = Walker::new(Vector3::new(0.5, 20.0, 0.5));
+        wet.vel.y = -20.0;
+        run(&mut wet, &reg, SURFACE, WalkInput::default(), 30);
+        assert!(wet.submersion > 0.99);
+        assert!(wet.vel.y > -5.0, "fall not damped: {}", wet.vel.y);
+
+        // The same half second in air keeps accelerating.
+        let mut dry = Walker::new(Vector3::new(0.5, 200.0, 0.5));
+        dry.vel.y = -20.0;
+        run(&mut dry, &reg, 0, WalkInput::default(), 30);
+        assert!(!dry.in_fluid());
+        assert!(dry.vel.y < -30.0, "{}", dry.vel.y);
+    }
+
+    #[test]
+    fn buoyancy_floats_a_submerged_walker_with_its_head_out() {
+        let reg = registry();
+        let mut w = Walker::new(Vector3::new(0.5, 5.0, 0.5));
+        run(&mut w, &reg, SURFACE, WalkInput::default(), 20 * 60);
+        // Bobbing swings the submersion, but the head stays out and the body stays in.
+        for _ in 0..10 * 60 {
+            run(&mut w, &reg, SURFACE, WalkInput::default(), 1);
+            assert!(w.submersion > 0.4 && w.submersion < 1.0, "{}", w.submersion);
+        }
+    }
+
+    #[test]
+    fn swimming_down_sinks_at_swim_speed() {
+        let reg = registry();
+        let mut w = Walker::new(Vector3::new(0.5, 30.0, 0.5));
+        let down = WalkInput {
+            swim_down: true,
+            ..Default::default()
+        };
+        run(&mut w, &reg, SURFACE, down, 60);
+        assert_eq!(w.vel.y, -SWIM_VERTICAL_SPEED);
+        let sunk = 30.0 - w.pos.y;
+        assert!((sunk - SWIM_VERTICAL_SPEED).abs() < 0.1, "sank {}", sunk);
+    }
+}